@@ -0,0 +1,402 @@
+"""
+Audio capture and transcription service.
+
+Records system audio (WASAPI loopback), the default microphone, or both in
+fixed-length chunks, transcribes each chunk locally with faster-whisper and
+saves the transcript through the reverse-IPC storage client. Rust encrypts the
+transcript and indexes it for search (``save_audio_transcript``); raw audio is
+only ever held in memory and is dropped once its chunk has been transcribed.
+
+Two threads keep recording gap-free while Whisper runs:
+    recorder    — reads 1 s blocks from the open recorders and queues each
+                  finished chunk
+    transcriber — loads the Whisper model lazily, skips silent chunks and
+                  saves non-empty transcripts
+
+Recording follows the capture state: while capture is paused or stopped the
+current chunk is cut short and nothing is recorded until it resumes.
+
+``soundcard`` and ``faster_whisper`` are optional dependencies imported on
+first use, so the monitor starts normally without them and ``status()``
+reports the import error instead.
+"""
+
+import logging
+import os
+import queue
+import threading
+import time
+from typing import Any, Dict, Iterable, List, Optional
+
+import numpy as np
+
+from monitor.config import paused_event, stop_event
+
+logger = logging.getLogger(__name__)
+
+
+# ---- Tunable constants ------------------------------------------------------
+
+SAMPLE_RATE = 16000                 # Whisper's native rate; recorders resample
+BLOCK_SECS = 1.0                    # recorder read granularity
+SILENCE_RMS = 0.003                 # chunks quieter than this are not transcribed
+MIN_CHUNK_SECS = 2.0                # shorter partial chunks are dropped
+MAX_QUEUED_CHUNKS = 2               # oldest chunk is dropped when Whisper falls behind
+RETRY_DELAY_SECS = 30.0             # wait after a device or model failure
+AUDIO_SOURCES = ('system', 'microphone', 'both')
+WHISPER_MODELS = ('tiny', 'base', 'small', 'medium')
+
+
+def env_config() -> Dict[str, Any]:
+    """Settings passed by Rust as ``CARBONPAPER_AUDIO_*`` environment variables."""
+    return {
+        'enabled': os.environ.get('CARBONPAPER_AUDIO_ENABLED', 'false').lower() == 'true',
+        'source': os.environ.get('CARBONPAPER_AUDIO_SOURCE', 'system'),
+        'chunk_secs': os.environ.get('CARBONPAPER_AUDIO_CHUNK_SECS', '30'),
+        'model': os.environ.get('CARBONPAPER_WHISPER_MODEL', 'base'),
+        'language': os.environ.get('CARBONPAPER_AUDIO_LANGUAGE', ''),
+    }
+
+
+def mix_to_mono(blocks: List[np.ndarray]) -> np.ndarray:
+    """Average the channels of each block and the blocks of each source together.
+
+    Blocks from different recorders may differ by a few frames; the mix is
+    truncated to the shortest one.
+    """
+    mono = [
+        (b.mean(axis=1) if b.ndim == 2 else b).astype(np.float32, copy=False)
+        for b in blocks
+        if b is not None and len(b)
+    ]
+    if not mono:
+        return np.zeros(0, dtype=np.float32)
+    length = min(len(b) for b in mono)
+    return np.mean([b[:length] for b in mono], axis=0).astype(np.float32)
+
+
+def chunk_is_silent(audio: np.ndarray, threshold: float = SILENCE_RMS) -> bool:
+    if audio.size == 0:
+        return True
+    rms = float(np.sqrt(np.mean(np.square(audio, dtype=np.float64))))
+    return rms < threshold
+
+
+def build_segments(whisper_segments: Iterable[Any]) -> List[Dict[str, Any]]:
+    """Convert faster-whisper segments to ``{start_ms, end_ms, text}`` dicts,
+    dropping blank ones. Offsets are relative to the chunk start."""
+    segments = []
+    for seg in whisper_segments:
+        text = (getattr(seg, 'text', '') or '').strip()
+        if not text:
+            continue
+        segments.append({
+            'start_ms': int(round(float(seg.start) * 1000)),
+            'end_ms': int(round(float(seg.end) * 1000)),
+            'text': text,
+        })
+    return segments
+
+
+class _Chunk:
+    __slots__ = ('started_at', 'ended_at', 'source', 'audio', 'model', 'language')
+
+    def __init__(self, started_at, ended_at, source, audio, model, language):
+        self.started_at = started_at
+        self.ended_at = ended_at
+        self.source = source
+        self.audio = audio
+        self.model = model
+        self.language = language
+
+
+class AudioTranscriptionService:
+    """Singleton recorder + Whisper transcriber."""
+
+    _instance = None
+    _lock = threading.Lock()
+
+    def __new__(cls):
+        if cls._instance is None:
+            cls._instance = super().__new__(cls)
+            cls._instance._init_fields()
+        return cls._instance
+
+    def _init_fields(self):
+        self._storage_client = None
+        self._models_dir: Optional[str] = None
+        self._config: Dict[str, Any] = {
+            'enabled': False,
+            'source': 'system',
+            'chunk_secs': 30,
+            'model': 'base',
+            'language': '',
+        }
+        self._config_version = 0
+        self._config_lock = threading.Lock()
+        self._stop_event = threading.Event()
+        self._wake_event = threading.Event()
+        self._chunks: "queue.Queue[_Chunk]" = queue.Queue(maxsize=MAX_QUEUED_CHUNKS)
+        self._recorder_thread: Optional[threading.Thread] = None
+        self._transcriber_thread: Optional[threading.Thread] = None
+        # Injection points for tests; default to soundcard / faster-whisper.
+        self._open_recorders = _open_soundcard_recorders
+        self._load_model = _load_whisper_model
+        self._model = None
+        self._model_name: Optional[str] = None
+        self._recording = False
+        self._last_error: Optional[str] = None
+        self._chunks_saved = 0
+        self._chunks_silent = 0
+        self._chunks_dropped = 0
+        self._last_saved_at: Optional[int] = None
+
+    # ---- configuration --------------------------------------------------
+
+    def configure(self, enabled=None, source=None, chunk_secs=None, model=None, language=None) -> Dict[str, Any]:
+        """Apply new settings; the recorder picks them up at the next block."""
+        with self._config_lock:
+            cfg = dict(self._config)
+            if enabled is not None:
+                cfg['enabled'] = bool(enabled)
+            if source in AUDIO_SOURCES:
+                cfg['source'] = source
+            if chunk_secs is not None:
+                try:
+                    cfg['chunk_secs'] = max(10, min(300, int(chunk_secs)))
+                except (TypeError, ValueError):
+                    pass
+            if model in WHISPER_MODELS:
+                cfg['model'] = model
+            if language is not None:
+                cfg['language'] = str(language).strip().lower()
+            if cfg != self._config:
+                self._config = cfg
+                self._config_version += 1
+            self._wake_event.set()
+            return dict(cfg)
+
+    def _snapshot(self):
+        with self._config_lock:
+            return dict(self._config), self._config_version
+
+    # ---- lifecycle ------------------------------------------------------
+
+    def start(self, storage_client, models_dir: Optional[str] = None):
+        """Start the recorder and transcriber threads. Idempotent."""
+        with self._lock:
+            if self._recorder_thread is not None and self._recorder_thread.is_alive():
+                return
+            self._storage_client = storage_client
+            self._models_dir = models_dir
+            self._stop_event.clear()
+            self._recorder_thread = threading.Thread(
+                target=self._record_loop,
+                name="audio_recorder",
+                daemon=True,
+            )
+            self._transcriber_thread = threading.Thread(
+                target=self._transcribe_loop,
+                name="audio_transcriber",
+                daemon=True,
+            )
+            self._recorder_thread.start()
+            self._transcriber_thread.start()
+            logger.info("[audio_transcriber] started")
+
+    def stop(self):
+        self._stop_event.set()
+        self._wake_event.set()
+        for thread in (self._recorder_thread, self._transcriber_thread):
+            if thread is not None:
+                thread.join(timeout=5.0)
+        self._recorder_thread = None
+        self._transcriber_thread = None
+        while not self._chunks.empty():
+            self._chunks.get_nowait()
+        self._model = None
+        self._model_name = None
+
+    def status(self) -> Dict[str, Any]:
+        cfg, _ = self._snapshot()
+        return {
+            **cfg,
+            'running': self._recorder_thread is not None and self._recorder_thread.is_alive(),
+            'recording': self._recording,
+            'model_loaded': self._model_name,
+            'queued_chunks': self._chunks.qsize(),
+            'chunks_saved': self._chunks_saved,
+            'chunks_silent': self._chunks_silent,
+            'chunks_dropped': self._chunks_dropped,
+            'last_saved_at': self._last_saved_at,
+            'last_error': self._last_error,
+        }
+
+    # ---- recording ------------------------------------------------------
+
+    def _should_record(self, cfg) -> bool:
+        return (
+            cfg['enabled']
+            and not self._stop_event.is_set()
+            and not paused_event.is_set()
+            and not stop_event.is_set()
+        )
+
+    def _record_loop(self):
+        while not self._stop_event.is_set():
+            cfg, version = self._snapshot()
+            if not self._should_record(cfg):
+                self._recording = False
+                self._wake_event.wait(timeout=1.0)
+                self._wake_event.clear()
+                continue
+            try:
+                with self._open_recorders(cfg['source']) as recorders:
+                    self._recording = True
+                    self._last_error = None
+                    while self._record_chunk(recorders, cfg, version):
+                        pass
+            except Exception as e:
+                self._last_error = f"recording failed: {e}"
+                logger.warning("[audio_transcriber] %s", self._last_error)
+                self._stop_event.wait(RETRY_DELAY_SECS)
+            finally:
+                self._recording = False
+
+    def _record_chunk(self, recorders, cfg, version) -> bool:
+        """Record one chunk and queue it. Returns False when the recorders must be
+        reopened or closed (settings changed, capture paused, service stopping)."""
+        block_frames = int(SAMPLE_RATE * BLOCK_SECS)
+        blocks_per_chunk = max(1, int(cfg['chunk_secs'] / BLOCK_SECS))
+        started_at = int(time.time() * 1000)
+        pieces: List[np.ndarray] = []
+        keep_going = True
+        for _ in range(blocks_per_chunk):
+            if not self._should_record(cfg) or self._snapshot()[1] != version:
+                keep_going = False
+                break
+            pieces.append(mix_to_mono([r.record(numframes=block_frames) for r in recorders]))
+        ended_at = int(time.time() * 1000)
+
+        audio = np.concatenate(pieces) if pieces else np.zeros(0, dtype=np.float32)
+        if audio.size >= MIN_CHUNK_SECS * SAMPLE_RATE:
+            self._enqueue(_Chunk(started_at, ended_at, cfg['source'], audio, cfg['model'], cfg['language']))
+        return keep_going
+
+    def _enqueue(self, chunk: _Chunk):
+        while True:
+            try:
+                self._chunks.put_nowait(chunk)
+                return
+            except queue.Full:
+                try:
+                    self._chunks.get_nowait()
+                    self._chunks_dropped += 1
+                    logger.warning("[audio_transcriber] transcription is behind; dropped oldest chunk")
+                except queue.Empty:
+                    pass
+
+    # ---- transcription --------------------------------------------------
+
+    def _transcribe_loop(self):
+        while True:
+            try:
+                chunk = self._chunks.get(timeout=1.0)
+            except queue.Empty:
+                if self._stop_event.is_set():
+                    return
+                # Free the model while recording is off.
+                if not self._snapshot()[0]['enabled'] and self._model is not None:
+                    self._model = None
+                    self._model_name = None
+                continue
+            try:
+                self._transcribe_chunk(chunk)
+            except Exception as e:
+                self._last_error = f"transcription failed: {e}"
+                logger.warning("[audio_transcriber] %s", self._last_error)
+            finally:
+                chunk.audio = None
+
+    def _transcribe_chunk(self, chunk: _Chunk):
+        if chunk_is_silent(chunk.audio):
+            self._chunks_silent += 1
+            return
+        if self._model is None or self._model_name != chunk.model:
+            self._model = None
+            self._model = self._load_model(chunk.model, self._models_dir)
+            self._model_name = chunk.model
+            logger.info("[audio_transcriber] loaded Whisper model '%s'", chunk.model)
+
+        whisper_segments, info = self._model.transcribe(
+            chunk.audio,
+            language=chunk.language or None,
+            vad_filter=True,
+            beam_size=1,
+        )
+        segments = build_segments(whisper_segments)
+        if not segments:
+            self._chunks_silent += 1
+            return
+        language = chunk.language or getattr(info, 'language', None)
+        if self._storage_client is None:
+            return
+        if self._storage_client.save_audio_transcript(
+            chunk.started_at, chunk.ended_at, chunk.source, language, segments
+        ):
+            self._chunks_saved += 1
+            self._last_saved_at = chunk.ended_at
+        else:
+            self._last_error = "failed to save transcript"
+
+
+class _RecorderGroup:
+    """Opens one soundcard recorder per source and closes them together."""
+
+    def __init__(self, microphones):
+        self._recorders = [
+            mic.recorder(samplerate=SAMPLE_RATE, channels=1, blocksize=1024)
+            for mic in microphones
+        ]
+        self._entered = []
+
+    def __enter__(self):
+        try:
+            for recorder in self._recorders:
+                self._entered.append(recorder.__enter__())
+        except Exception:
+            self.__exit__(None, None, None)
+            raise
+        return self._entered
+
+    def __exit__(self, *exc):
+        for recorder in reversed(self._entered):
+            try:
+                recorder.__exit__(None, None, None)
+            except Exception:
+                pass
+        self._entered = []
+        return False
+
+
+def _open_soundcard_recorders(source: str):
+    import soundcard as sc
+
+    microphones = []
+    if source in ('system', 'both'):
+        speaker = sc.default_speaker()
+        microphones.append(sc.get_microphone(id=str(speaker.name), include_loopback=True))
+    if source in ('microphone', 'both'):
+        microphones.append(sc.default_microphone())
+    return _RecorderGroup(microphones)
+
+
+def _load_whisper_model(model: str, models_dir: Optional[str]):
+    from faster_whisper import WhisperModel
+
+    return WhisperModel(
+        model,
+        device='cpu',
+        compute_type='int8',
+        download_root=models_dir,
+    )
@@ -368,6 +368,11 @@ def _handle_command_impl(req: dict):
         }
         if _ocr_worker:
             status['ocr_stats'] = _ocr_worker.get_stats()
+        try:
+            from audio_transcriber import AudioTranscriptionService
+            status['audio'] = AudioTranscriptionService().status()
+        except Exception:
+            pass
         return status
 
     if cmd == 'index_health':
@@ -405,7 +410,8 @@ def _handle_command_impl(req: dict):
             return {'status': 'error', 'error': 'Vector indexing retry is not available'}
         try:
             limit = int(req.get('limit', 32) or 32)
-            return _ocr_worker.retry_vector_indexing(limit=limit)
+            priority = req.get('priority') or 'backfill'
+            return _ocr_worker.retry_vector_indexing(limit=limit, priority=priority)
         except Exception as e:
             logger.warning('Vector indexing retry failed: %s', e)
             return {'status': 'error', 'error': str(e)}
@@ -418,6 +424,11 @@ def _handle_command_impl(req: dict):
                 processes=filters.get('processes') or req.get('processes'),
                 titles=filters.get('titles') or req.get('titles'),
                 ignore_protected=filters.get('ignore_protected') if 'ignore_protected' in filters else req.get('ignore_protected'),
+                ocr_skip_processes=(
+                    filters['ocr_skip_processes']
+                    if filters.get('ocr_skip_processes') is not None
+                    else req.get('ocr_skip_processes')
+                ),
             )
             return {'status': 'success', 'filters': get_exclusion_settings()}
         except Exception as e:
@@ -447,6 +458,20 @@ def _handle_command_impl(req: dict):
             'classification_enabled': classification_enabled,
         }
 
+    if cmd == 'update_audio_config':
+        try:
+            from audio_transcriber import AudioTranscriptionService
+            audio = AudioTranscriptionService().configure(
+                enabled=req.get('enabled'),
+                source=req.get('source'),
+                chunk_secs=req.get('chunk_secs'),
+                model=req.get('model'),
+                language=req.get('language'),
+            )
+            return {'status': 'success', 'audio': audio}
+        except Exception as e:
+            return {'status': 'error', 'error': str(e)}
+
     # ----- Vector search -----
     if cmd == 'search_nl':
         query = req.get('query', '')
@@ -503,7 +528,7 @@ def _handle_command_impl(req: dict):
             'vector_deleted': vector_info.get('deleted', 0),
         }
 
-    if cmd == 'delete_by_time_range':
+    if cmd in ('delete_by_time_range', 'delete_screenshots'):
         image_hashes = req.get('image_hashes')
         if not isinstance(image_hashes, list):
             image_hashes = []
@@ -858,6 +883,21 @@ def start(_debug, pipe_name: str = None, auth_token: str = None, storage_pipe: s
     except Exception as e:
         logger.warning('Smart Cluster worker failed to start (non-fatal): %s', e)
 
+    # Start audio capture/transcription. Optional like smart clusters; the
+    # service idles until enabled and records nothing while capture is paused.
+    try:
+        if storage_pipe:
+            from audio_transcriber import AudioTranscriptionService, env_config
+            from storage_client import get_storage_client
+            sc = get_storage_client()
+            if sc is not None:
+                audio = AudioTranscriptionService()
+                audio.configure(**env_config())
+                audio.start(sc, models_dir=os.path.join(get_data_dir(), 'models', 'whisper'))
+                logger.info('Audio transcription service started')
+    except Exception as e:
+        logger.warning('Audio transcription service failed to start (non-fatal): %s', e)
+
     # NOTE: Screenshot capture and OCR are handled by Rust. Python provides only
     # classification, vector indexing, clustering, and related post-processing.
 
@@ -882,6 +922,11 @@ def stop():
         SmartClusterWorker().stop()
     except Exception:
         pass
+    try:
+        from audio_transcriber import AudioTranscriptionService
+        AudioTranscriptionService().stop()
+    except Exception:
+        pass
     try:
         from .presidio_worker import get_presidio_worker
         get_presidio_worker().stop()
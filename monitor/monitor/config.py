@@ -81,6 +81,8 @@ EXCLUSION_TITLES = [
 # User-configurable exclusion sets
 USER_EXCLUDED_PROCESSES: set = set()
 USER_EXCLUDED_TITLES: set = set()
+# Processes whose frames are stored but skipped by OCR (applied on the Rust side)
+USER_OCR_SKIP_PROCESSES: set = set()
 IGNORE_PROTECTED_WINDOWS: bool = True
 
 # Advanced capture config (synced from Rust CaptureState)
@@ -115,9 +117,11 @@ def _get_default_filter_settings_path() -> Optional[str]:
 def _apply_exclusion_settings(data: Dict[str, Any]):
     """Apply exclusion settings from a parsed JSON payload."""
     global USER_EXCLUDED_PROCESSES, USER_EXCLUDED_TITLES, IGNORE_PROTECTED_WINDOWS
+    global USER_OCR_SKIP_PROCESSES
     processes = data.get("processes") if isinstance(data, dict) else None
     titles = data.get("titles") if isinstance(data, dict) else None
     ignore_protected = data.get("ignore_protected") if isinstance(data, dict) else None
+    ocr_skip_processes = data.get("ocr_skip_processes") if isinstance(data, dict) else None
     if processes is not None:
         USER_EXCLUDED_PROCESSES = {
             p.strip().lower() for p in processes if isinstance(p, str) and p.strip()
@@ -128,6 +132,10 @@ def _apply_exclusion_settings(data: Dict[str, Any]):
         }
     if ignore_protected is not None:
         IGNORE_PROTECTED_WINDOWS = bool(ignore_protected)
+    if ocr_skip_processes is not None:
+        USER_OCR_SKIP_PROCESSES = {
+            p.strip().lower() for p in ocr_skip_processes if isinstance(p, str) and p.strip()
+        }
 
 
 def _persist_exclusion_settings():
@@ -167,9 +175,11 @@ def _load_exclusion_settings():
         logger.error("Failed to load capture filters: %s", exc)
 
 
-def update_exclusion_settings(processes=None, titles=None, ignore_protected=None):
+def update_exclusion_settings(processes=None, titles=None, ignore_protected=None,
+                              ocr_skip_processes=None):
     """Update user-defined exclusion rules."""
     global USER_EXCLUDED_PROCESSES, USER_EXCLUDED_TITLES, IGNORE_PROTECTED_WINDOWS
+    global USER_OCR_SKIP_PROCESSES
 
     if processes is not None:
         USER_EXCLUDED_PROCESSES = {
@@ -181,6 +191,10 @@ def update_exclusion_settings(processes=None, titles=None, ignore_protected=None
         }
     if ignore_protected is not None:
         IGNORE_PROTECTED_WINDOWS = bool(ignore_protected)
+    if ocr_skip_processes is not None:
+        USER_OCR_SKIP_PROCESSES = {
+            p.strip().lower() for p in ocr_skip_processes if isinstance(p, str) and p.strip()
+        }
 
     _persist_exclusion_settings()
 
@@ -191,6 +205,7 @@ def get_exclusion_settings():
         "processes": sorted(USER_EXCLUDED_PROCESSES),
         "titles": sorted(USER_EXCLUDED_TITLES),
         "ignore_protected": IGNORE_PROTECTED_WINDOWS,
+        "ocr_skip_processes": sorted(USER_OCR_SKIP_PROCESSES),
     }
 
 
@@ -1,6 +1,7 @@
 import threading
 from concurrent.futures import ThreadPoolExecutor
 import json
+import queue
 import datetime
 import logging
 import pywintypes
@@ -22,6 +23,13 @@ from typing import Callable
 MAX_PIPE_MESSAGE_BYTES = 16 * 1024 * 1024
 IPC_PROTOCOL_VERSION = 2
 
+# Multiplexed connections: requests carrying `_ipc_multiplex` run concurrently and
+# each response is wrapped as {"_ipc_request_id": ..., "result": ...} so the client
+# can match it to its request. The connection's I/O thread polls for new requests
+# between writes, backing off while idle.
+MUX_MIN_POLL_SECS = 0.002
+MUX_MAX_POLL_SECS = 0.05
+
 
 def _is_authorized_client_pid(client_pid: int, expected_pid: int | None, curr_ppid: int) -> bool:
     """Return whether a named-pipe client PID is authorized."""
@@ -121,6 +129,7 @@ class _NamedPipeServer(threading.Thread):
             max_workers=self.max_workers,
             thread_name_prefix='NamedPipeHandler',
         )
+        self.mux_workers = int(os.environ.get('CARBONPAPER_IPC_MUX_WORKERS', '8'))
 
     def run(self):
         logger.info(f"NamedPipeServer starting on {self.full_pipe_name}")
@@ -241,6 +250,10 @@ class _NamedPipeServer(threading.Thread):
                     win32file.FlushFileBuffers(handle)
                     return
 
+                if req.get('_ipc_multiplex'):
+                    self._serve_multiplexed(handle, req)
+                    return
+
                 keep_alive = bool(req.get('_ipc_keepalive', False))
                 command = req.get('command')
                 requests_handled += 1
@@ -288,6 +301,103 @@ class _NamedPipeServer(threading.Thread):
             except:
                 pass
 
+    def _run_multiplexed_request(self, req, responses):
+        """Run one multiplexed request and queue its enveloped response."""
+        request_id = req.get('_ipc_request_id')
+        command = req.get('command')
+        exec_started = _time.perf_counter()
+        try:
+            result = self.handler(req)
+        except Exception as e:
+            logger.error(f"IPC handler failed for {command}: {e}", exc_info=True)
+            result = {"error": f"Handler failed: {e}"}
+        handler_elapsed = _time.perf_counter() - exec_started
+        if handler_elapsed >= 3.0:
+            logger.warning(
+                '[DIAG:PIPE] slow handler command=%s exec=%.3fs',
+                command,
+                handler_elapsed,
+            )
+
+        try:
+            payload = json.dumps(
+                {"_ipc_request_id": request_id, "result": result},
+                default=_json_default,
+            ).encode('utf-8')
+        except (TypeError, ValueError) as e:
+            payload = None
+            error = f"Failed to serialize response: {e}"
+        else:
+            error = None
+            if len(payload) > MAX_PIPE_MESSAGE_BYTES:
+                error = f"Response too large (max {MAX_PIPE_MESSAGE_BYTES} bytes)"
+        if error:
+            logger.error(f"[DIAG:PIPE] {error} command={command}")
+            payload = json.dumps(
+                {"_ipc_request_id": request_id, "result": {"error": error}}
+            ).encode('utf-8')
+        responses.put(payload)
+
+    def _serve_multiplexed(self, handle, first_req):
+        """Serve a multiplexed connection until the client disconnects.
+
+        Only this thread touches the pipe handle, so reads and writes never overlap
+        on the synchronous handle; handlers run on a per-connection pool and their
+        responses are written in completion order.
+        """
+        responses = queue.Queue()
+        workers = ThreadPoolExecutor(
+            max_workers=getattr(self, 'mux_workers', 8),
+            thread_name_prefix='NamedPipeMux',
+        )
+        stop_event = getattr(self, 'stop_event', threading.Event())
+        logger.debug("[DIAG:PIPE] multiplexed connection established")
+        try:
+            workers.submit(self._run_multiplexed_request, first_req, responses)
+            idle_wait = MUX_MIN_POLL_SECS
+            while not stop_event.is_set():
+                # 1. Flush finished responses
+                while True:
+                    try:
+                        payload = responses.get_nowait()
+                    except queue.Empty:
+                        break
+                    _write_framed_json(handle, payload)
+
+                # 2. Read the next request if one is waiting
+                _, available, _ = win32pipe.PeekNamedPipe(handle, 0)
+                if available:
+                    try:
+                        payload = _read_framed_json_message(handle)
+                    except ValueError as e:
+                        logger.error(str(e))
+                        return
+                    if not payload:
+                        return
+                    try:
+                        req = json.loads(payload)
+                    except json.JSONDecodeError:
+                        logger.error(f"Invalid JSON: {payload[:100]}")
+                        continue
+                    workers.submit(self._run_multiplexed_request, req, responses)
+                    idle_wait = MUX_MIN_POLL_SECS
+                    continue
+
+                # 3. Wait briefly for a response, backing off while idle
+                try:
+                    payload = responses.get(timeout=idle_wait)
+                except queue.Empty:
+                    idle_wait = min(idle_wait * 2, MUX_MAX_POLL_SECS)
+                    continue
+                _write_framed_json(handle, payload)
+                idle_wait = MUX_MIN_POLL_SECS
+        except pywintypes.error as e:
+            if getattr(e, 'winerror', None) not in (109, 232):
+                raise
+            logger.debug("[DIAG:PIPE] multiplexed client disconnected winerror=%s", e.winerror)
+        finally:
+            workers.shutdown(wait=False, cancel_futures=True)
+
 
 def start_pipe_server(handler, pipe_name):
     server = _NamedPipeServer(handler, pipe_name)
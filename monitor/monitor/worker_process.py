@@ -15,8 +15,8 @@ import queue
 import threading
 import time
 import traceback
-from collections import OrderedDict
-from typing import Any, Dict, Optional
+from collections import OrderedDict, deque
+from typing import Any, Dict, Optional, Tuple
 
 from .worker_supervisor import WorkerSupervisor, attach_response_metadata
 
@@ -24,20 +24,103 @@ from .worker_supervisor import WorkerSupervisor, attach_response_metadata
 logger = logging.getLogger(__name__)
 WORKER_PROTOCOL_VERSION = 2
 
+# Lower rank is served first: user-triggered work, then frames from the live
+# capture loop, then backlog replays.
+OCR_JOB_PRIORITIES = {"interactive": 0, "live": 1, "backfill": 2}
+OCR_QUEUE_DROP_POLICIES = ("reject_new", "drop_oldest")
+
+
+def _priority_rank(priority: Optional[str]) -> int:
+    return OCR_JOB_PRIORITIES.get(priority or "live", OCR_JOB_PRIORITIES["live"])
+
+
+class PriorityJobQueue:
+    """Bounded job queue served by priority, FIFO within a priority.
+
+    When full, ``drop_oldest`` evicts the oldest job of the lowest queued
+    priority unless only higher-priority jobs are queued; ``reject_new`` rejects
+    the new job unless it outranks a queued one, in which case the newest job of
+    the lowest queued priority makes room.
+    """
+
+    def __init__(self, maxsize: int, drop_policy: str = "reject_new"):
+        self.maxsize = max(1, int(maxsize))
+        self.drop_policy = drop_policy if drop_policy in OCR_QUEUE_DROP_POLICIES else "reject_new"
+        self._buckets = {rank: deque() for rank in sorted(set(OCR_JOB_PRIORITIES.values()))}
+        self._size = 0
+        self._cond = threading.Condition()
+
+    def qsize(self) -> int:
+        with self._cond:
+            return self._size
+
+    def sizes_by_priority(self) -> Dict[str, int]:
+        with self._cond:
+            return {name: len(self._buckets[rank]) for name, rank in OCR_JOB_PRIORITIES.items()}
+
+    def _eviction_rank(self, rank: int) -> Optional[int]:
+        lowest = max((r for r, bucket in self._buckets.items() if bucket), default=None)
+        if lowest is None:
+            return None
+        if self.drop_policy == "drop_oldest":
+            return lowest if lowest >= rank else None
+        return lowest if lowest > rank else None
+
+    def put(self, job: Dict[str, Any], priority: Optional[str] = None) -> Tuple[bool, Optional[Dict[str, Any]]]:
+        """Add a job; returns whether it was accepted and the job evicted for it."""
+        rank = _priority_rank(priority)
+        with self._cond:
+            evicted = None
+            if self._size >= self.maxsize:
+                victim_rank = self._eviction_rank(rank)
+                if victim_rank is None:
+                    return False, None
+                bucket = self._buckets[victim_rank]
+                evicted = bucket.popleft() if self.drop_policy == "drop_oldest" else bucket.pop()
+                self._size -= 1
+            self._buckets[rank].append(job)
+            self._size += 1
+            self._cond.notify()
+            return True, evicted
+
+    def get_nowait(self) -> Dict[str, Any]:
+        return self.get(timeout=0)
+
+    def get(self, timeout: float) -> Dict[str, Any]:
+        with self._cond:
+            if not self._size and not self._cond.wait_for(lambda: self._size > 0, timeout=timeout):
+                raise queue.Empty
+            for bucket in self._buckets.values():
+                if bucket:
+                    self._size -= 1
+                    return bucket.popleft()
+        raise queue.Empty
+
 
 class OcrPostprocessQueue:
     """Bounded best-effort OCR post-processing queue.
 
     The capture commit path must not wait for vector indexing or classification.
-    Jobs are dropped when the queue is full; OCR results have already been
-    returned to Rust by then.
+    Jobs carry a priority (see ``OCR_JOB_PRIORITIES``); when the queue is full
+    the configured drop policy decides which job is lost. OCR results have
+    already been returned to Rust by then, and dropped persistent jobs are left
+    for the Rust retry pass.
     """
 
-    def __init__(self, ocr_worker, classifier, maxsize: Optional[int] = None):
+    def __init__(
+        self,
+        ocr_worker,
+        classifier,
+        maxsize: Optional[int] = None,
+        drop_policy: Optional[str] = None,
+    ):
         self.ocr_worker = ocr_worker
         self.classifier = classifier
         self.maxsize = maxsize or int(os.environ.get("CARBONPAPER_OCR_POSTPROCESS_QUEUE_MAX", "64"))
-        self._queue = queue.Queue(maxsize=max(1, self.maxsize))
+        self._queue = PriorityJobQueue(
+            self.maxsize,
+            drop_policy or os.environ.get("CARBONPAPER_OCR_QUEUE_DROP_POLICY", "reject_new"),
+        )
         self._stop = threading.Event()
         self._thread: Optional[threading.Thread] = None
         self.dropped = 0
@@ -69,25 +152,49 @@ class OcrPostprocessQueue:
         if self._thread:
             self._thread.join(timeout=timeout)
 
-    def enqueue(self, job: Dict[str, Any]) -> bool:
-        try:
-            self._queue.put_nowait(job)
-            logger.info(
-                "[DIAG:ocr_postprocess] enqueued screenshot_id=%s text_len=%s queue_size=%s",
-                job.get("screenshot_id"),
-                len(job.get("ocr_text", "") or ""),
-                self._queue.qsize(),
-            )
-            return True
-        except queue.Full:
+    def enqueue(self, job: Dict[str, Any], priority: Optional[str] = None) -> bool:
+        accepted, evicted = self._queue.put(job, priority)
+        if evicted is not None:
+            self._record_drop(evicted)
+        if not accepted:
             self.dropped += 1
             logger.warning(
-                "[DIAG:ocr_postprocess] queue full; dropped screenshot_id=%s dropped=%s maxsize=%s",
+                "[DIAG:ocr_postprocess] queue full; rejected screenshot_id=%s priority=%s dropped=%s maxsize=%s",
                 job.get("screenshot_id"),
+                priority,
                 self.dropped,
                 self.maxsize,
             )
             return False
+        logger.info(
+            "[DIAG:ocr_postprocess] enqueued screenshot_id=%s priority=%s text_len=%s queue_size=%s",
+            job.get("screenshot_id"),
+            priority,
+            len(job.get("ocr_text", "") or ""),
+            self._queue.qsize(),
+        )
+        return True
+
+    def _record_drop(self, job: Dict[str, Any]):
+        """Account for a job evicted to make room and hand it back to the Rust retry pass."""
+        self.dropped += 1
+        logger.warning(
+            "[DIAG:ocr_postprocess] queue full; evicted screenshot_id=%s policy=%s dropped=%s",
+            job.get("screenshot_id"),
+            self._queue.drop_policy,
+            self.dropped,
+        )
+        if not job.get("_persistent_postprocess"):
+            return
+        try:
+            from storage_client import get_storage_client
+            sc = get_storage_client()
+            if sc:
+                sc.record_ocr_postprocess_retry(
+                    int(job.get("screenshot_id")), "Evicted from full postprocess queue"
+                )
+        except Exception:
+            pass
 
     def _run(self):
         while not self._stop.is_set():
@@ -129,8 +236,6 @@ class OcrPostprocessQueue:
                     exc,
                     exc_info=True,
                 )
-            finally:
-                self._queue.task_done()
 
     def _record_vector_failure(self, job: Dict[str, Any], reason: str):
         screenshot_id = job.get("screenshot_id")
@@ -277,17 +382,18 @@ class OcrPostprocessQueue:
                         int(screenshot_id), "Vector indexing failed"
                     )
 
-    def retry_failed_vector_indexing(self, limit: int = 32) -> Dict[str, Any]:
+    def retry_failed_vector_indexing(self, limit: int = 32, priority: str = "backfill") -> Dict[str, Any]:
         limit = max(1, min(int(limit or 32), self.max_vector_retry_backlog))
         enqueued = 0
         with self._stats_lock:
             retry_items = list(self._vector_retry_backlog.items())[:limit]
         for _key, job in retry_items:
-            try:
-                self._queue.put_nowait(dict(job))
-                enqueued += 1
-            except queue.Full:
+            accepted, evicted = self._queue.put(dict(job), priority)
+            if evicted is not None:
+                self._record_drop(evicted)
+            if not accepted:
                 break
+            enqueued += 1
         with self._stats_lock:
             self.vector_retry_enqueued += enqueued
         return {
@@ -310,6 +416,8 @@ class OcrPostprocessQueue:
         return {
             "queue_size": self._queue.qsize(),
             "queue_max_size": self._queue.maxsize,
+            "queue_by_priority": self._queue.sizes_by_priority(),
+            "drop_policy": self._queue.drop_policy,
             "dropped": self.dropped,
             "processed": self.processed,
             "failed": self.failed,
@@ -352,6 +460,9 @@ def _enqueue_ocr_postprocess(req: Dict[str, Any], postprocess_queue: Optional[Oc
     image_bytes = response.get("data", {}).get("image_bytes")
     if not image_bytes:
         return {"error": "No image data returned from storage"}
+    priority = req.get("priority") or "live"
+    if priority not in OCR_JOB_PRIORITIES:
+        return {"error": f"Unknown OCR job priority: {priority}"}
     enqueued = postprocess_queue.enqueue({
         "screenshot_id": int(screenshot_id),
         "image_hash": req.get("image_hash", ""),
@@ -361,7 +472,7 @@ def _enqueue_ocr_postprocess(req: Dict[str, Any], postprocess_queue: Optional[Oc
         "ocr_text": req.get("ocr_text", ""),
         "image_bytes": image_bytes,
         "_persistent_postprocess": True,
-    })
+    }, priority)
     return {
         "status": "success",
         "postprocess_enqueued": bool(enqueued),
@@ -392,7 +503,10 @@ def _worker_main(conn, storage_pipe: Optional[str], data_dir: str, env: Dict[str
         )
 
         if storage_pipe:
-            init_storage_client(storage_pipe)
+            init_storage_client(
+                storage_pipe,
+                spill_path=os.path.join(data_dir, "reverse_ipc_spill.jsonl") if data_dir else None,
+            )
 
         try:
             import chromadb
@@ -462,7 +576,8 @@ def _worker_main(conn, storage_pipe: Optional[str], data_dir: str, env: Dict[str
                 })
             elif command == "retry_vector_indexing":
                 limit = int(msg.get("limit", 32) or 32)
-                send_response(postprocess_queue.retry_failed_vector_indexing(limit=limit))
+                priority = msg.get("priority") or "backfill"
+                send_response(postprocess_queue.retry_failed_vector_indexing(limit=limit, priority=priority))
             elif command == "search_by_natural_language":
                 args = msg.get("args", {})
                 send_response({
@@ -585,8 +700,12 @@ class RestartableModelWorker(WorkerSupervisor):
             return result
         raise RuntimeError(result.get("error", "Model worker index health failed"))
 
-    def retry_vector_indexing(self, limit: int = 32):
-        result = self.request("retry_vector_indexing", {"limit": int(limit or 32)}, timeout=30)
+    def retry_vector_indexing(self, limit: int = 32, priority: str = "backfill"):
+        result = self.request(
+            "retry_vector_indexing",
+            {"limit": int(limit or 32), "priority": priority},
+            timeout=30,
+        )
         if result.get("status") == "success":
             return result
         raise RuntimeError(result.get("error", "Model worker vector retry failed"))
@@ -6,6 +6,7 @@ Fix notes:
 """
 import os
 import gc
+import json
 import logging
 import numpy as np
 from typing import Optional, List, Tuple, Dict, Any
@@ -14,6 +15,9 @@ import threading
 
 logger = logging.getLogger(__name__)
 
+# Stdout prefix parsed by the host (see `dml_fallback.rs`).
+DML_FALLBACK_MARKER = "[DML_FALLBACK]"
+
 
 
 
@@ -34,6 +38,27 @@ def _get_ppocr_base_dir() -> str:
     return os.path.join(base_dir, "ppOCRmodel")
 
 
+def _report_dml_fallback(device_id: Optional[int], error: Exception) -> None:
+    """Print the marker line the host watches for to persist a DirectML fallback."""
+    payload = {'device_id': device_id, 'error': str(error)[:500]}
+    print(f"{DML_FALLBACK_MARKER} {json.dumps(payload)}", flush=True)
+
+
+def _create_rapid_ocr(init_params: Dict[str, Any]):
+    """Create the RapidOCR wrapper, retrying once without ``ocr_version``."""
+    from rapidocr_capability import PaddleOCR as RapidPaddleOCR
+    try:
+        return RapidPaddleOCR(**init_params)
+    except Exception as e:
+        logger.error("Using %s to initialize RapidOCR FAILED: %s", init_params.get('ocr_version'), e)
+        init_params.pop('ocr_version', None)
+        try:
+            return RapidPaddleOCR(**init_params)
+        except Exception as e2:
+            logger.error("Retry to initialize RapidOCR FAILED: %s", e2)
+            raise e2
+
+
 class OCREngine:
     """Thread-safe singleton OCR engine."""
 
@@ -55,6 +80,7 @@ class OCREngine:
         use_gpu: bool = False,
         use_dml: bool = False,
         dml_device_id: Optional[int] = None,
+        npu_provider: Optional[str] = None,
         ocr_version: str = 'PP-OCRv5',
         model_size: str = "mobile",
         det_model_dir: Optional[str] = None,
@@ -70,6 +96,7 @@ class OCREngine:
             use_gpu: Whether to use GPU (compatibility parameter).
             use_dml: Whether to use DirectML acceleration.
             dml_device_id: DirectML device ID (None = default GPU).
+            npu_provider: ONNX Runtime NPU execution provider (None = no NPU).
             ocr_version: OCR model version.
             model_size: Model size, "mobile" or "server" (default: mobile).
             det_model_dir: Detection model directory (optional).
@@ -82,12 +109,15 @@ class OCREngine:
 
         from logging_config import log_model_loading
         log_model_loading("PaddleOCR (RapidOCR)")
-        logger.info("Initialising OCR (using %s, DirectML=%s)...", ocr_version, use_dml)
+        logger.info(
+            "Initialising OCR (using %s, DirectML=%s, NPU=%s)...", ocr_version, use_dml, npu_provider
+        )
 
         # Record GPU usage flags
         self._use_gpu = bool(use_gpu)
         self._use_dml = bool(use_dml)
         self._dml_device_id = dml_device_id
+        self._npu_provider = npu_provider or None
 
         init_params = {
             'use_angle_cls': use_angle_cls,
@@ -161,27 +191,56 @@ class OCREngine:
         with self._init_lock:
             if getattr(self, '_initialized', False):
                 return
-            from rapidocr_capability import PaddleOCR as RapidPaddleOCR
-            try:
-                init_params['ocr_version'] = ocr_version
-                init_params['cpu_threads'] = 1
-                init_params['use_dml'] = self._use_dml
-                if self._dml_device_id is not None:
-                    init_params['dml_device_id'] = self._dml_device_id
-                self.ocr = RapidPaddleOCR(**init_params)
-            except Exception as e:
-                logger.error("Using %s to initialize RapidOCR FAILED: %s", ocr_version, e)
-                if 'ocr_version' in init_params:
-                     del init_params['ocr_version']
+            init_params['ocr_version'] = ocr_version
+            init_params['cpu_threads'] = 1
+            init_params = self._accelerator_params(init_params)
+            while True:
                 try:
-                    init_params['cpu_threads'] = 1
-                    self.ocr = RapidPaddleOCR(**init_params)
-                except Exception as e2:
-                    logger.error("Retry to initialize RapidOCR FAILED: %s", e2)
-                    raise e2
+                    self.ocr = _create_rapid_ocr(init_params)
+                    break
+                except Exception as e:
+                    # The selected NPU or GPU failed to initialise (driver reset, eGPU
+                    # undocked): keep OCR running without it instead of failing every capture.
+                    if not self._leave_accelerator(e):
+                        raise
+                    init_params = self._accelerator_params(init_params)
+                    init_params['ocr_version'] = ocr_version
+            self._init_params = init_params
 
             self._initialized = True
-            logger.info("OCR initialized successfully (DirectML=%s)", self._use_dml)
+            logger.info(
+                "OCR initialized successfully (DirectML=%s, NPU=%s)",
+                self._use_dml,
+                self._npu_provider,
+            )
+
+    def _accelerator_params(self, init_params: Dict[str, Any]) -> Dict[str, Any]:
+        """Copy of ``init_params`` selecting the accelerators still in use."""
+        params = dict(init_params, use_dml=self._use_dml, npu_provider=self._npu_provider)
+        params.pop('dml_device_id', None)
+        if self._use_dml and self._dml_device_id is not None:
+            params['dml_device_id'] = self._dml_device_id
+        return params
+
+    def _leave_accelerator(self, error: Exception) -> bool:
+        """Drop the NPU, or else DirectML, after ``error``. Returns False on CPU."""
+        if self._npu_provider:
+            logger.warning("NPU provider %s failed, continuing without it: %s", self._npu_provider, error)
+            self._npu_provider = None
+            return True
+        if self._use_dml:
+            self._leave_dml(error)
+            return True
+        return False
+
+    def _leave_dml(self, error: Exception) -> None:
+        """Switch this engine to CPU and tell the host to persist the fallback."""
+        logger.warning(
+            "DirectML device %s failed, falling back to CPU: %s", self._dml_device_id, error
+        )
+        _report_dml_fallback(self._dml_device_id, error)
+        self._use_dml = False
+        self._dml_device_id = None
 
     def close(self) -> None:
         """Explicitly release the OCR instance and free memory.
@@ -197,6 +256,19 @@ class OCREngine:
         gc.collect()
         self._initialized = False
     
+    def _predict(self, image_np):
+        """Run inference; an NPU or DirectML failure (e.g. device removed) is retried
+        without that accelerator, down to CPU."""
+        try:
+            return self.ocr.predict(image_np)
+        except Exception as e:
+            if not self._leave_accelerator(e):
+                raise
+            init_params = self._accelerator_params(self._init_params)
+            self.ocr = _create_rapid_ocr(init_params)
+            self._init_params = init_params
+            return self._predict(image_np)
+
     def recognize(
         self,
         image_input: Any,
@@ -225,7 +297,7 @@ class OCREngine:
                 logger.info("[OCR Engine] Using PaddleOCR.predict(), Image Size: %s", image_np.shape if hasattr(image_np, 'shape') else 'unknown')
                 # PaddleOCR 3.x no longer supports passing cls at call time
                 # The orientation classifier is controlled at init via use_angle_cls
-                result = self.ocr.predict(image_np)
+                result = self._predict(image_np)
                 logger.info("[OCR Engine] PaddleOCR.predict() return: %s, length: %s", type(result), len(result) if result else 'None')
             except Exception as ocr_err:
                 logger.exception("[OCR Engine] PaddleOCR.predict() ERROR: %s", ocr_err)
@@ -436,6 +508,8 @@ def get_ocr_engine(**kwargs) -> OCREngine:
     """Get the OCR engine instance (singleton)."""
     if 'use_dml' not in kwargs:
         kwargs['use_dml'] = os.environ.get('CARBONPAPER_USE_DML', '').strip() == '1'
+    if 'npu_provider' not in kwargs:
+        kwargs['npu_provider'] = os.environ.get('CARBONPAPER_NPU_PROVIDER', '').strip() or None
     if 'dml_device_id' not in kwargs:
         device_id_str = os.environ.get('CARBONPAPER_DML_DEVICE_ID', '').strip()
         if device_id_str:
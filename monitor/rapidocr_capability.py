@@ -65,6 +65,69 @@ def _patch_ort_dml_device_id(device_id: int):
     )
 
 
+# Provider options that point each vendor EP at the NPU rather than its default device.
+NPU_PROVIDER_OPTIONS = {
+    "OpenVINOExecutionProvider": {"device_type": "NPU"},
+    "VitisAIExecutionProvider": {},
+    "QNNExecutionProvider": {"backend_path": "QnnHtp.dll"},
+}
+
+# NPU execution provider prepended to every new session; None disables it.
+_active_npu_provider: Optional[str] = None
+
+
+def _with_npu_provider(providers: list, npu_provider: str) -> list:
+    """Return ``providers`` with ``npu_provider`` first; ONNX Runtime assigns the
+    nodes the NPU cannot run to the remaining providers (DirectML or CPU)."""
+    rest = [
+        p for p in providers
+        if (p[0] if isinstance(p, tuple) else p) != npu_provider
+    ]
+    return [(npu_provider, dict(NPU_PROVIDER_OPTIONS.get(npu_provider, {})))] + rest
+
+
+def _set_ort_npu_provider(npu_provider: Optional[str]) -> Optional[str]:
+    """
+    Make new onnxruntime sessions try ``npu_provider`` first.
+
+    Like the DML device patch, this intercepts InferenceSession because RapidOCR
+    only knows about CPU, CUDA and DirectML. Returns the provider that is now
+    active, or None when it is disabled or this onnxruntime build lacks it.
+    """
+    global _active_npu_provider
+    _active_npu_provider = None
+    if not npu_provider:
+        return None
+    try:
+        import onnxruntime as ort
+    except ImportError:
+        logger.warning("onnxruntime not available, cannot use NPU provider %s", npu_provider)
+        return None
+
+    if npu_provider not in ort.get_available_providers():
+        logger.warning(
+            "%s is not available in this onnxruntime build, OCR will not use the NPU",
+            npu_provider,
+        )
+        return None
+
+    if not getattr(ort.InferenceSession, "_npu_provider_patched", False):
+        _original_init = ort.InferenceSession.__init__
+
+        def _patched_init(self, *args, **kwargs):
+            providers = kwargs.get("providers", None)
+            if _active_npu_provider and providers:
+                kwargs["providers"] = _with_npu_provider(providers, _active_npu_provider)
+            _original_init(self, *args, **kwargs)
+
+        ort.InferenceSession.__init__ = _patched_init
+        ort.InferenceSession._npu_provider_patched = True
+
+    _active_npu_provider = npu_provider
+    logger.info("Patched onnxruntime.InferenceSession to prefer %s", npu_provider)
+    return npu_provider
+
+
 class PaddleOCR:
     """
     RapidOCR-to-PaddleOCR 3.3 compatibility layer.
@@ -75,6 +138,7 @@ class PaddleOCR:
     - show_log: Whether to show logs.
     - use_gpu: GPU acceleration (compatibility parameter).
     - use_dml: Whether to use DirectML acceleration.
+    - npu_provider: ONNX Runtime NPU execution provider to try first.
     - cpu_threads: CPU thread count (unclear whether effective; may be removed).
     - use_doc_orientation_classify: Document orientation classification (compat).
     - use_doc_unwarping: Document unwarping (compat).
@@ -90,6 +154,7 @@ class PaddleOCR:
         use_gpu: bool = False,
         use_dml: bool = False,
         dml_device_id: Optional[int] = None,
+        npu_provider: Optional[str] = None,
         show_log: bool = False,
         cpu_threads: int = 2,
         use_doc_orientation_classify: bool = False,
@@ -110,6 +175,8 @@ class PaddleOCR:
             use_gpu: Whether to use GPU (compatibility parameter).
             use_dml: Whether to use DirectML acceleration (requires onnxruntime-directml).
             dml_device_id: DirectML device ID (None = default GPU).
+            npu_provider: NPU execution provider, e.g. "OpenVINOExecutionProvider"
+                (None = no NPU).
             show_log: Whether to show logs.
             cpu_threads: CPU thread count (unclear if effective; may be removed later).
             TODO: Remove cpu_threads parameter.
@@ -123,6 +190,8 @@ class PaddleOCR:
         if use_dml and dml_device_id is not None:
             _patch_ort_dml_device_id(dml_device_id)
 
+        self.npu_provider = _set_ort_npu_provider(npu_provider)
+
         if use_dml:
             params["EngineConfig.onnxruntime.use_dml"] = True
         if det_model_path:
@@ -16,12 +16,30 @@ import win32pipe
 import pywintypes
 from typing import Optional, Dict, Any, List
 
+try:
+    import zstandard as _zstd
+except ImportError:  # optional: compression falls back to lz4 or plain frames
+    _zstd = None
+try:
+    import lz4.block as _lz4_block
+except ImportError:
+    _lz4_block = None
+
 
 IPC_PROTOCOL_VERSION = 2
 MAX_PIPE_MESSAGE_BYTES = 16 * 1024 * 1024
 MAX_PIPE_BINARY_BYTES = 64 * 1024 * 1024
 PIPE_CLOSED_WINERRORS = (109, 232)
 
+# Frame compression (negotiated per connection via `_ipc_codecs` / `_ipc_codec`).
+# A compressed frame sets the high bit of the length prefix; its body is
+# <codec id: u8><uncompressed length: u32 LE><compressed bytes>.
+COMPRESSED_FRAME_FLAG = 0x80000000
+COMPRESSED_HEADER = struct.Struct('<BI')
+IPC_CODEC_IDS = {'zstd': 1, 'lz4': 2}
+IPC_CODEC_NAMES = {v: k for k, v in IPC_CODEC_IDS.items()}
+IPC_COMPRESSION_MIN_BYTES = 64 * 1024
+
 
 def _default_reverse_ipc_timeout_secs() -> float:
     raw = os.environ.get("CARBONPAPER_REVERSE_IPC_TIMEOUT_SECS", "15") or "15"
@@ -51,6 +69,9 @@ def _env_int(name: str, default: int, minimum: int) -> int:
 
 
 DEFAULT_REVERSE_IPC_TIMEOUT_SECS = _default_reverse_ipc_timeout_secs()
+REVERSE_IPC_COMPRESSION_ENABLED = os.environ.get(
+    "CARBONPAPER_REVERSE_IPC_COMPRESSION", "1"
+).strip().lower() not in ("0", "false", "off")
 DEFAULT_REVERSE_IPC_CIRCUIT_FAILURE_THRESHOLD = _env_int(
     "CARBONPAPER_REVERSE_IPC_CIRCUIT_FAILURE_THRESHOLD",
     3,
@@ -66,6 +87,17 @@ RETRY_BACKOFF_BASE_SECS = _env_float(
     0.05,
     0.01,
 )
+BACKEND_HEARTBEAT_INTERVAL_SECS = _env_float(
+    "CARBONPAPER_BACKEND_HEARTBEAT_INTERVAL_SECS",
+    10.0,
+    1.0,
+)
+BACKEND_HEARTBEAT_TIMEOUT_SECS = _env_float(
+    "CARBONPAPER_BACKEND_HEARTBEAT_TIMEOUT_SECS",
+    5.0,
+    0.5,
+)
+MAX_SPILL_ENTRIES = _env_int("CARBONPAPER_REVERSE_IPC_MAX_SPILL_ENTRIES", 5000, 1)
 
 READ_RETRY_COMMANDS = {
     'get_public_key',
@@ -81,6 +113,7 @@ READ_RETRY_COMMANDS = {
     'get_auth_status',
     'get_temp_image',
     'screenshot_exists',
+    'heartbeat',
 }
 
 IDEMPOTENT_RETRY_COMMANDS = {
@@ -91,6 +124,8 @@ IDEMPOTENT_RETRY_COMMANDS = {
     'smart_cluster_delete_pending',
     'smart_cluster_record_assignment',
     'abort_screenshot',
+    # Rust keys transcripts on (started_at, source) and ignores duplicates.
+    'save_audio_transcript',
 }
 
 UNSAFE_AFTER_SEND_COMMANDS = {
@@ -101,6 +136,22 @@ UNSAFE_AFTER_SEND_COMMANDS = {
 
 SAFE_RETRY_AFTER_SEND_COMMANDS = READ_RETRY_COMMANDS | IDEMPOTENT_RETRY_COMMANDS
 
+# Writes that may be parked in the spill file while the backend is unresponsive
+# and replayed later; replaying them out of band must be harmless.
+SPILLABLE_COMMANDS = IDEMPOTENT_RETRY_COMMANDS
+
+TRANSPORT_FAILURE_CODES = {'ipc_timeout', 'ipc_circuit_open'}
+
+
+def _is_transport_failure(response: Dict[str, Any]) -> bool:
+    """True when the backend never produced an answer (as opposed to rejecting the request)."""
+    if response.get('status') == 'success':
+        return False
+    if response.get('code') in TRANSPORT_FAILURE_CODES:
+        return True
+    error = str(response.get('error') or '')
+    return error.startswith('IPC error') or error == 'Empty response'
+
 
 class ReverseIpcTimeoutError(TimeoutError):
     """Raised when a reverse IPC storage request exceeds its deadline."""
@@ -115,10 +166,68 @@ class ReverseIpcTimeoutError(TimeoutError):
         )
 
 
-def _write_framed_json(handle, payload: bytes) -> None:
+def _available_codecs() -> List[str]:
+    """Codecs this process can use, in preference order."""
+    if not REVERSE_IPC_COMPRESSION_ENABLED:
+        return []
+    codecs = []
+    if _zstd is not None:
+        codecs.append('zstd')
+    if _lz4_block is not None:
+        codecs.append('lz4')
+    return codecs
+
+
+def _compress(codec: str, payload: bytes) -> bytes:
+    if codec == 'zstd':
+        return _zstd.ZstdCompressor(level=1).compress(payload)
+    if codec == 'lz4':
+        return _lz4_block.compress(payload, store_size=False)
+    raise ValueError(f"Unsupported IPC codec: {codec}")
+
+
+def _decompress(codec: str, payload: bytes, raw_len: int) -> bytes:
+    if codec == 'zstd' and _zstd is not None:
+        return _zstd.ZstdDecompressor().decompress(payload, max_output_size=raw_len)
+    if codec == 'lz4' and _lz4_block is not None:
+        return _lz4_block.decompress(payload, uncompressed_size=raw_len)
+    raise RuntimeError(f"Unsupported IPC codec: {codec}")
+
+
+def _encode_frame(payload: bytes, codec: Optional[str]) -> tuple:
+    """Return (length prefix, body), compressing large payloads when negotiated."""
+    if codec and len(payload) >= IPC_COMPRESSION_MIN_BYTES:
+        try:
+            compressed = _compress(codec, payload)
+        except Exception as exc:
+            logger.warning("[storage_client] %s compression failed: %s", codec, exc)
+            compressed = None
+        if compressed is not None and len(compressed) + COMPRESSED_HEADER.size < len(payload):
+            body = COMPRESSED_HEADER.pack(IPC_CODEC_IDS[codec], len(payload)) + compressed
+            return struct.pack('<I', len(body) | COMPRESSED_FRAME_FLAG), body
+    return struct.pack('<I', len(payload)), payload
+
+
+def _decode_compressed_body(body: bytes) -> bytes:
+    if len(body) < COMPRESSED_HEADER.size:
+        raise RuntimeError(f"Compressed frame too short: {len(body)} bytes")
+    codec_id, raw_len = COMPRESSED_HEADER.unpack_from(body)
+    codec = IPC_CODEC_NAMES.get(codec_id)
+    if codec is None:
+        raise RuntimeError(f"Unknown IPC compression codec: {codec_id}")
+    if not 0 < raw_len <= MAX_PIPE_MESSAGE_BYTES:
+        raise RuntimeError(f"Invalid decompressed frame length: {raw_len}")
+    decoded = _decompress(codec, body[COMPRESSED_HEADER.size:], raw_len)
+    if len(decoded) != raw_len:
+        raise RuntimeError(f"Decompressed frame length mismatch: expected {raw_len}, got {len(decoded)}")
+    return decoded
+
+
+def _write_framed_json(handle, payload: bytes, codec: Optional[str] = None) -> None:
     if len(payload) > MAX_PIPE_MESSAGE_BYTES:
         raise ValueError(f"Request too large (max {MAX_PIPE_MESSAGE_BYTES} bytes)")
-    win32file.WriteFile(handle, struct.pack('<I', len(payload)))
+    prefix, payload = _encode_frame(payload, codec)
+    win32file.WriteFile(handle, prefix)
     offset = 0
     chunk_size = 64 * 1024
     while offset < len(payload):
@@ -161,7 +270,9 @@ def _read_framed_json(handle) -> Dict[str, Any]:
         return {'status': 'error', 'error': 'Empty response'}
 
     if len(first) == 4:
-        frame_len = struct.unpack('<I', first)[0]
+        prefix = struct.unpack('<I', first)[0]
+        compressed = bool(prefix & COMPRESSED_FRAME_FLAG)
+        frame_len = prefix & ~COMPRESSED_FRAME_FLAG
         if 0 < frame_len <= MAX_PIPE_MESSAGE_BYTES:
             chunks = []
             remaining = frame_len
@@ -174,6 +285,11 @@ def _read_framed_json(handle) -> Dict[str, Any]:
             response_bytes = b''.join(chunks)
             if len(response_bytes) != frame_len:
                 return {'status': 'error', 'error': f'Incomplete response frame: expected {frame_len}, got {len(response_bytes)}'}
+            if compressed:
+                try:
+                    response_bytes = _decode_compressed_body(response_bytes)
+                except Exception as e:
+                    return {'status': 'error', 'error': f'Compressed response decode failed: {e}'}
             response = json.loads(response_bytes.decode('utf-8'))
             if response.get('status') == 'success' and response.get('data', {}).get('binary_frame'):
                 try:
@@ -191,12 +307,14 @@ def _read_framed_json(handle) -> Dict[str, Any]:
 class StorageClient:
     """Client for communicating with the Rust storage service."""
     
-    def __init__(self, pipe_name: str):
+    def __init__(self, pipe_name: str, spill_path: Optional[str] = None):
         r"""
         Initialise the storage client.
 
         Args:
             pipe_name: Pipe name of the Rust storage service (without the \\.\pipe\ prefix).
+            spill_path: JSON-lines file for writes parked while the backend is hung.
+                Spilling is disabled when omitted.
         """
         self.pipe_name = pipe_name
         self.full_pipe_name = rf"\\.\pipe\{pipe_name}"
@@ -221,6 +339,16 @@ class StorageClient:
         self._circuit_last_failure_at: Optional[float] = None
         self._circuit_last_error: Optional[str] = None
         self._circuit_last_command: Optional[str] = None
+        self._spill_path = spill_path
+        self._spill_lock = threading.Lock()
+        self._spill_count = self._count_spilled_entries()
+        self._backend_healthy = True
+        self._last_heartbeat_ok_at: Optional[float] = None
+        self._heartbeat_thread: Optional[threading.Thread] = None
+        self._heartbeat_stop = threading.Event()
+        # Codec negotiated on the current persistent handle; reset on reconnect.
+        self._ipc_codec: Optional[str] = None
+        self._ipc_codec_handle = None
 
     def _timeout_response(self, exc: ReverseIpcTimeoutError) -> Dict[str, Any]:
         self._last_timeout_at = time.time()
@@ -234,9 +362,13 @@ class StorageClient:
             'error': str(exc),
         }
 
-    def _build_request_bytes(self, request: Dict[str, Any]) -> bytes:
+    def _build_request_bytes(self, request: Dict[str, Any], offer_codecs: bool = False) -> bytes:
         framed_request = dict(request)
         framed_request['_ipc_keepalive'] = True
+        if offer_codecs:
+            codecs = _available_codecs()
+            if codecs:
+                framed_request['_ipc_codecs'] = codecs
         framed_request['_auth_token'] = self._auth_token
         self._seq_no += 1
         framed_request['_seq_no'] = self._seq_no
@@ -339,8 +471,173 @@ class StorageClient:
                 'last_command': self._circuit_last_command,
                 'last_timeout_at': self._last_timeout_at,
                 'last_timeout_command': self._last_timeout_command,
+                'backend_healthy': self._backend_healthy,
+                'last_heartbeat_ok_at': self._last_heartbeat_ok_at,
+                'spilled_pending': self._spill_count,
+                'compression_codec': self._ipc_codec,
             }
 
+    # ---- Backend heartbeat and spill/replay -----------------------------
+
+    def _count_spilled_entries(self) -> int:
+        if not self._spill_path:
+            return 0
+        try:
+            with open(self._spill_path, 'r', encoding='utf-8') as spill_file:
+                return sum(1 for line in spill_file if line.strip())
+        except FileNotFoundError:
+            return 0
+        except Exception as exc:
+            logger.warning("[storage_client] failed to read spill file: %s", exc)
+            return 0
+
+    def _spill_request(self, request: Dict[str, Any]) -> bool:
+        """Append a write to the spill file. Returns False if it could not be parked."""
+        if not self._spill_path:
+            return False
+        with self._spill_lock:
+            if self._spill_count >= MAX_SPILL_ENTRIES:
+                logger.error(
+                    "[storage_client] spill file full (%s entries); dropping command=%s",
+                    self._spill_count,
+                    request.get('command'),
+                )
+                return False
+            try:
+                os.makedirs(os.path.dirname(self._spill_path) or '.', exist_ok=True)
+                with open(self._spill_path, 'a', encoding='utf-8') as spill_file:
+                    spill_file.write(json.dumps(request) + '\n')
+            except Exception as exc:
+                logger.error("[storage_client] failed to spill command=%s: %s", request.get('command'), exc)
+                return False
+            self._spill_count += 1
+        return True
+
+    def _send_request_spillable(self, request: Dict[str, Any]) -> Dict[str, Any]:
+        """Send an idempotent write, parking it in the spill file if the backend is hung.
+
+        A spilled write reports success so callers keep going; it is replayed in
+        order once the heartbeat sees the backend respond again.
+        """
+        command = request.get('command')
+        if command in SPILLABLE_COMMANDS and (not self._backend_healthy or self._spill_count > 0):
+            # Keep ordering: while anything is parked, new writes queue behind it.
+            if self._spill_request(request):
+                return {'status': 'success', 'spilled': True, 'data': {}}
+
+        response = self._send_request(request)
+        if command in SPILLABLE_COMMANDS and _is_transport_failure(response):
+            self._backend_healthy = False
+            if self._spill_request(request):
+                logger.warning(
+                    "[storage_client] backend unresponsive; spilled command=%s (%s pending)",
+                    command,
+                    self._spill_count,
+                )
+                return {'status': 'success', 'spilled': True, 'data': {}}
+        return response
+
+    def replay_spilled_requests(self) -> int:
+        """Replay parked writes in order. Stops at the first transport failure.
+
+        Returns the number of entries removed from the spill file.
+        """
+        if not self._spill_path:
+            return 0
+        with self._spill_lock:
+            try:
+                with open(self._spill_path, 'r', encoding='utf-8') as spill_file:
+                    lines = [line for line in spill_file if line.strip()]
+            except FileNotFoundError:
+                self._spill_count = 0
+                return 0
+
+            replayed = 0
+            for line in lines:
+                try:
+                    request = json.loads(line)
+                except json.JSONDecodeError:
+                    logger.warning("[storage_client] dropping corrupt spill entry")
+                    replayed += 1
+                    continue
+                response = self._send_request(request)
+                if _is_transport_failure(response):
+                    self._backend_healthy = False
+                    break
+                if response.get('status') != 'success':
+                    logger.warning(
+                        "[storage_client] spilled command=%s rejected on replay: %s",
+                        request.get('command'),
+                        response.get('error'),
+                    )
+                replayed += 1
+
+            remaining = lines[replayed:]
+            try:
+                if remaining:
+                    tmp_path = self._spill_path + '.tmp'
+                    with open(tmp_path, 'w', encoding='utf-8') as tmp_file:
+                        tmp_file.writelines(remaining)
+                    os.replace(tmp_path, self._spill_path)
+                else:
+                    os.remove(self._spill_path)
+            except Exception as exc:
+                logger.error("[storage_client] failed to rewrite spill file: %s", exc)
+            self._spill_count = len(remaining)
+
+        if replayed:
+            logger.info(
+                "[storage_client] replayed %s spilled writes (%s remaining)",
+                replayed,
+                self._spill_count,
+            )
+        return replayed
+
+    def heartbeat_once(self) -> bool:
+        """Probe the backend; on recovery, replay anything spilled while it was hung."""
+        response = self._send_request({'command': 'heartbeat'}, timeout=BACKEND_HEARTBEAT_TIMEOUT_SECS)
+        data = response.get('data') if isinstance(response.get('data'), dict) else {}
+        healthy = response.get('status') == 'success' and bool(data.get('db_responsive', True))
+
+        if healthy:
+            if not self._backend_healthy:
+                logger.info("[storage_client] backend heartbeat recovered")
+            self._backend_healthy = True
+            self._last_heartbeat_ok_at = time.time()
+            if self._spill_count > 0:
+                self.replay_spilled_requests()
+        else:
+            if self._backend_healthy:
+                logger.error(
+                    "[storage_client] backend heartbeat failed: %s",
+                    response.get('error') or 'database unresponsive',
+                )
+            self._backend_healthy = False
+        return healthy
+
+    def start_heartbeat(self) -> None:
+        """Start the background heartbeat thread (idempotent)."""
+        if self._heartbeat_thread is not None and self._heartbeat_thread.is_alive():
+            return
+        self._heartbeat_stop.clear()
+
+        def _loop():
+            while not self._heartbeat_stop.wait(BACKEND_HEARTBEAT_INTERVAL_SECS):
+                try:
+                    self.heartbeat_once()
+                except Exception as exc:
+                    logger.debug("[storage_client] heartbeat error: %s", exc)
+
+        self._heartbeat_thread = threading.Thread(
+            target=_loop,
+            name="storage-backend-heartbeat",
+            daemon=True,
+        )
+        self._heartbeat_thread.start()
+
+    def stop_heartbeat(self) -> None:
+        self._heartbeat_stop.set()
+
     def _remaining_deadline(self, deadline: float, command: str, timeout_secs: float, phase: str) -> float:
         remaining = deadline - time.monotonic()
         if remaining <= 0:
@@ -364,6 +661,8 @@ class StorageClient:
     def _close_persistent_handle(self) -> None:
         handle = self._persistent_handle
         self._persistent_handle = None
+        self._ipc_codec = None
+        self._ipc_codec_handle = None
         if handle is not None:
             try:
                 win32file.CloseHandle(handle)
@@ -471,10 +770,11 @@ class StorageClient:
             for attempt in range(2):
                 self._remaining_deadline(deadline, command, timeout_secs, "connect")
                 handle = self._connect_persistent_handle()
-                request_bytes = self._build_request_bytes(request)
+                negotiated = self._ipc_codec_handle is handle
+                request_bytes = self._build_request_bytes(request, offer_codecs=not negotiated)
                 try:
                     self._remaining_deadline(deadline, command, timeout_secs, "write")
-                    _write_framed_json(handle, request_bytes)
+                    _write_framed_json(handle, request_bytes, self._ipc_codec if negotiated else None)
 
                     # Flush pipe to ensure all data has been sent.
                     try:
@@ -522,6 +822,11 @@ class StorageClient:
                         continue
                     self._record_ipc_failure(command, "Empty response")
                     return response
+                if not negotiated and '_ipc_codec' in response:
+                    codec = response.pop('_ipc_codec')
+                    self._ipc_codec = codec if codec in IPC_CODEC_IDS else None
+                    self._ipc_codec_handle = handle
+                    logger.debug("[storage_client] reverse IPC compression: %s", self._ipc_codec or 'none')
                 self._record_ipc_success()
                 return response
 
@@ -740,7 +1045,7 @@ class StorageClient:
         }
         if category_confidence is not None:
             request['category_confidence'] = float(category_confidence)
-        response = self._send_request(request)
+        response = self._send_request_spillable(request)
         return response.get('status') == 'success'
 
     # ---- Smart Cluster reverse IPC --------------------------------------
@@ -766,7 +1071,7 @@ class StorageClient:
         return []
 
     def smart_cluster_enqueue_pending(self, screenshot_id: int) -> bool:
-        response = self._send_request({
+        response = self._send_request_spillable({
             'command': 'smart_cluster_enqueue_pending',
             'screenshot_id': int(screenshot_id),
         })
@@ -793,7 +1098,7 @@ class StorageClient:
         """Remove pending ids after they have been scored and assignments persisted."""
         if not ids:
             return True
-        response = self._send_request({
+        response = self._send_request_spillable({
             'command': 'smart_cluster_delete_pending',
             'ids': [int(i) for i in ids],
         })
@@ -811,7 +1116,7 @@ class StorageClient:
         screenshot_id: int,
         rerank_score: float,
     ) -> bool:
-        response = self._send_request({
+        response = self._send_request_spillable({
             'command': 'smart_cluster_record_assignment',
             'smart_cluster_id': int(smart_cluster_id),
             'screenshot_id': int(screenshot_id),
@@ -819,6 +1124,25 @@ class StorageClient:
         })
         return response.get('status') == 'success'
 
+    def save_audio_transcript(
+        self,
+        started_at: int,
+        ended_at: int,
+        source: str,
+        language: Optional[str],
+        segments: List[Dict[str, Any]],
+    ) -> bool:
+        """Store the transcript of one audio chunk (times in Unix milliseconds)."""
+        response = self._send_request_spillable({
+            'command': 'save_audio_transcript',
+            'started_at': int(started_at),
+            'ended_at': int(ended_at),
+            'source': source,
+            'language': language,
+            'segments': segments,
+        })
+        return response.get('status') == 'success'
+
     def is_session_valid(self) -> bool:
         """Check whether the Rust credential session is currently unlocked."""
         response = self._send_request({'command': 'get_auth_status'})
@@ -984,7 +1308,7 @@ class StorageClient:
         status: str,
         error: Optional[str] = None,
     ) -> bool:
-        response = self._send_request({
+        response = self._send_request_spillable({
             'command': 'set_ocr_postprocess_status',
             'screenshot_id': int(screenshot_id),
             'status': status,
@@ -993,7 +1317,7 @@ class StorageClient:
         return response.get('status') == 'success'
 
     def record_ocr_postprocess_retry(self, screenshot_id: int, error: str) -> bool:
-        response = self._send_request({
+        response = self._send_request_spillable({
             'command': 'record_ocr_postprocess_retry',
             'screenshot_id': int(screenshot_id),
             'error': str(error or 'OCR postprocess failed'),
@@ -1010,7 +1334,7 @@ class StorageClient:
             'reason': reason
         }
 
-        response = self._send_request(request)
+        response = self._send_request_spillable(request)
 
         if response.get('status') == 'success':
             return response.get('data', {})
@@ -1030,16 +1354,26 @@ def get_storage_client() -> Optional[StorageClient]:
     return _storage_client
 
 
-def init_storage_client(pipe_name: str) -> StorageClient:
+def init_storage_client(pipe_name: str, spill_path: Optional[str] = None) -> StorageClient:
     """
     Initialise the global storage client.
 
     Args:
         pipe_name: Pipe name.
+        spill_path: Optional spill file; when set, the backend heartbeat is started
+            and writes are parked there while the backend is unresponsive.
 
     Returns:
         Storage client instance.
     """
     global _storage_client
-    _storage_client = StorageClient(pipe_name)
+    if _storage_client is not None:
+        # Re-initialising for the same pipe without a spill file (e.g. OCRService)
+        # keeps the existing client so its heartbeat and spill state survive.
+        if spill_path is None and _storage_client.pipe_name == pipe_name:
+            return _storage_client
+        _storage_client.stop_heartbeat()
+    _storage_client = StorageClient(pipe_name, spill_path=spill_path)
+    if spill_path:
+        _storage_client.start_heartbeat()
     return _storage_client
@@ -0,0 +1,105 @@
+import types
+
+import numpy as np
+
+import audio_transcriber as at
+
+
+def _segment(start, end, text):
+    return types.SimpleNamespace(start=start, end=end, text=text)
+
+
+def _speech(secs=3.0):
+    t = np.arange(int(at.SAMPLE_RATE * secs), dtype=np.float32) / at.SAMPLE_RATE
+    return (0.2 * np.sin(2 * np.pi * 440 * t)).astype(np.float32)
+
+
+class FakeModel:
+    def __init__(self, segments, language="en"):
+        self.segments = segments
+        self.language = language
+        self.calls = []
+
+    def transcribe(self, audio, **kwargs):
+        self.calls.append(kwargs)
+        return iter(self.segments), types.SimpleNamespace(language=self.language)
+
+
+class FakeStorageClient:
+    def __init__(self):
+        self.saved = []
+
+    def save_audio_transcript(self, started_at, ended_at, source, language, segments):
+        self.saved.append((started_at, ended_at, source, language, segments))
+        return True
+
+
+def _service(model):
+    service = at.AudioTranscriptionService()
+    service._init_fields()
+    service._storage_client = FakeStorageClient()
+    service._load_model = lambda name, models_dir: model
+    return service
+
+
+def test_build_segments_converts_offsets_and_drops_blank_text():
+    segments = at.build_segments([
+        _segment(0.0, 1.25, " Ship it on Friday "),
+        _segment(1.25, 2.0, "   "),
+        _segment(2.0, 3.5004, "发布"),
+    ])
+    assert segments == [
+        {"start_ms": 0, "end_ms": 1250, "text": "Ship it on Friday"},
+        {"start_ms": 2000, "end_ms": 3500, "text": "发布"},
+    ]
+
+
+def test_mix_to_mono_averages_channels_and_sources():
+    stereo = np.array([[0.2, 0.4], [0.0, 0.2], [1.0, 1.0]], dtype=np.float32)
+    mono = np.array([[0.1], [0.1]], dtype=np.float32)
+
+    mixed = at.mix_to_mono([stereo, mono])
+
+    assert mixed.dtype == np.float32
+    np.testing.assert_allclose(mixed, [0.2, 0.1], rtol=1e-6)
+    assert at.chunk_is_silent(at.mix_to_mono([])) is True
+    assert at.chunk_is_silent(np.zeros(16000, dtype=np.float32)) is True
+    assert at.chunk_is_silent(_speech()) is False
+
+
+def test_silent_chunk_is_skipped_without_loading_the_model():
+    service = _service(model=None)
+    service._load_model = lambda name, models_dir: (_ for _ in ()).throw(AssertionError("loaded"))
+
+    service._transcribe_chunk(at._Chunk(0, 3000, "system", np.zeros(48000, dtype=np.float32), "base", ""))
+
+    assert service._storage_client.saved == []
+    assert service.status()["chunks_silent"] == 1
+
+
+def test_transcript_is_saved_with_detected_language():
+    model = FakeModel([_segment(0.5, 2.0, "standup notes")], language="en")
+    service = _service(model)
+
+    service._transcribe_chunk(at._Chunk(1_000, 4_000, "both", _speech(), "tiny", ""))
+
+    assert model.calls == [{"language": None, "vad_filter": True, "beam_size": 1}]
+    assert service._storage_client.saved == [
+        (1_000, 4_000, "both", "en", [{"start_ms": 500, "end_ms": 2000, "text": "standup notes"}]),
+    ]
+    status = service.status()
+    assert status["chunks_saved"] == 1
+    assert status["model_loaded"] == "tiny"
+    assert status["last_saved_at"] == 4_000
+
+
+def test_configure_ignores_unsupported_values_and_bumps_version():
+    service = _service(model=None)
+    _, version = service._snapshot()
+
+    cfg = service.configure(enabled=True, source="speakers", chunk_secs="5", model="large-v3", language=" DE ")
+
+    assert cfg == {"enabled": True, "source": "system", "chunk_secs": 10, "model": "base", "language": "de"}
+    assert service._snapshot()[1] == version + 1
+    service.configure(enabled=True)
+    assert service._snapshot()[1] == version + 1
@@ -0,0 +1,78 @@
+import json
+import struct
+import threading
+
+import monitor.ipc_pipe as ipc_pipe
+
+
+class FakePyWinError(Exception):
+    def __init__(self, winerror, message=""):
+        super().__init__(message or f"winerror={winerror}")
+        self.winerror = winerror
+
+
+def _decode_frames(wire):
+    frames = []
+    offset = 0
+    while offset + 4 <= len(wire):
+        frame_len = struct.unpack("<I", wire[offset:offset + 4])[0]
+        frames.append(json.loads(wire[offset + 4:offset + 4 + frame_len]))
+        offset += 4 + frame_len
+    return frames
+
+
+def test_multiplexed_connection_runs_requests_concurrently(monkeypatch):
+    server = object.__new__(ipc_pipe._NamedPipeServer)
+    server.mux_workers = 4
+    server.stop_event = threading.Event()
+    state = {"writes": [], "closed": False}
+    second_started = threading.Event()
+
+    def fake_handler(req):
+        if req["command"] == "search":
+            # Finishes only once the later request is running, so a serial server
+            # would deadlock here.
+            assert second_started.wait(timeout=5)
+        else:
+            second_started.set()
+        return {"ok": req["command"]}
+
+    server.handler = fake_handler
+    pending_reads = [
+        '{"command":"search","_auth_token":"t","_seq_no":1,"_ipc_multiplex":true,"_ipc_request_id":1}',
+        '{"command":"status","_auth_token":"t","_seq_no":2,"_ipc_multiplex":true,"_ipc_request_id":2}',
+    ]
+
+    def fake_peek(_h, _size):
+        if pending_reads:
+            return b"", 1, 1
+        if len(_decode_frames(b"".join(state["writes"]))) < 2:
+            return b"", 0, 0
+        raise FakePyWinError(109)
+
+    def fake_write(_h, data):
+        state["writes"].append(data)
+        return 0, len(data)
+
+    monkeypatch.setattr(ipc_pipe.pywintypes, "error", FakePyWinError)
+    monkeypatch.setenv("CARBON_PARENT_PID", "4242")
+    monkeypatch.setattr(ipc_pipe.win32pipe, "GetNamedPipeClientProcessId", lambda _h: 4242)
+    monkeypatch.setattr(ipc_pipe.win32pipe, "PeekNamedPipe", fake_peek, raising=False)
+    monkeypatch.setattr(ipc_pipe.os, "getppid", lambda: 1111)
+    monkeypatch.setattr(ipc_pipe, "_read_framed_json_message", lambda _h: pending_reads.pop(0))
+    monkeypatch.setattr(ipc_pipe.win32file, "WriteFile", fake_write)
+    monkeypatch.setattr(ipc_pipe.win32file, "FlushFileBuffers", lambda _h: None)
+    monkeypatch.setattr(
+        ipc_pipe.win32file,
+        "CloseHandle",
+        lambda _h: state.__setitem__("closed", True),
+    )
+
+    server._client_handler(object())
+
+    assert state["closed"] is True
+    frames = _decode_frames(b"".join(state["writes"]))
+    assert frames == [
+        {"_ipc_request_id": 2, "result": {"ok": "status"}},
+        {"_ipc_request_id": 1, "result": {"ok": "search"}},
+    ]
@@ -37,7 +37,7 @@ class DummyOcrWorker:
             "postprocess": {"vector_retry_backlog_count": 2},
         }
 
-    def retry_vector_indexing(self, limit=32):
+    def retry_vector_indexing(self, limit=32, priority="backfill"):
         self.retry_calls.append(limit)
         return {"status": "success", "enqueued": min(limit, 2)}
 
@@ -311,3 +311,25 @@ def test_retry_vector_indexing_dispatches_to_worker():
         assert worker.retry_calls == [5]
     finally:
         _restore_globals(snapshot)
+
+
+def test_update_filters_passes_an_empty_ocr_skip_list(monkeypatch):
+    snapshot = _snapshot_globals()
+    calls = []
+    monkeypatch.setattr(mm, "update_exclusion_settings", lambda **kwargs: calls.append(kwargs))
+    monkeypatch.setattr(mm, "get_exclusion_settings", lambda: {})
+
+    try:
+        mm._auth_token = None
+        mm._last_seq_no = -1
+
+        result = mm._handle_command_impl({
+            "command": "update_filters",
+            "filters": {"ocr_skip_processes": []},
+            "ocr_skip_processes": ["mpv.exe"],
+        })
+
+        assert result["status"] == "success"
+        assert calls[0]["ocr_skip_processes"] == []
+    finally:
+        _restore_globals(snapshot)
@@ -0,0 +1,86 @@
+import json
+
+import numpy as np
+import pytest
+
+import ocr_engine
+import rapidocr_capability
+
+
+class _FakePaddleOCR:
+    fail_dml_init = False
+    fail_dml_predict = False
+    fail_npu_init = False
+
+    def __init__(self, **params):
+        if params.get("npu_provider") and self.fail_npu_init:
+            raise RuntimeError("NPU driver not found")
+        if params.get("use_dml") and self.fail_dml_init:
+            raise RuntimeError("DXGI_ERROR_DEVICE_REMOVED")
+        self.params = params
+
+    def predict(self, _image):
+        if self.params.get("use_dml") and self.fail_dml_predict:
+            raise RuntimeError("DXGI_ERROR_DEVICE_REMOVED")
+        return [{"rec_texts": ["hello"], "rec_scores": [0.9], "dt_polys": [[[0, 0], [1, 0], [1, 1], [0, 1]]]}]
+
+
+@pytest.fixture
+def fake_rapidocr(monkeypatch, tmp_path):
+    monkeypatch.setattr(rapidocr_capability, "PaddleOCR", _FakePaddleOCR)
+    monkeypatch.setattr(_FakePaddleOCR, "fail_dml_init", False)
+    monkeypatch.setattr(_FakePaddleOCR, "fail_dml_predict", False)
+    monkeypatch.setattr(_FakePaddleOCR, "fail_npu_init", False)
+    monkeypatch.setenv("CARBONPAPER_OCR_MODEL_DIR", str(tmp_path))
+    for name in ("ch_PP-OCRv5_det_mobile.onnx", "ch_PP-OCRv5_rec_mobile.onnx", "ppocrv5_dict.txt"):
+        (tmp_path / name).write_text("")
+    monkeypatch.setattr(ocr_engine.OCREngine, "_instance", None)
+    yield _FakePaddleOCR
+    ocr_engine.OCREngine._instance = None
+
+
+def _fallback_lines(output):
+    return [
+        json.loads(line[len(ocr_engine.DML_FALLBACK_MARKER):])
+        for line in output.splitlines()
+        if line.startswith(ocr_engine.DML_FALLBACK_MARKER)
+    ]
+
+
+def test_dml_init_failure_falls_back_to_cpu_and_reports_device(fake_rapidocr, capsys):
+    fake_rapidocr.fail_dml_init = True
+
+    engine = ocr_engine.OCREngine(use_dml=True, dml_device_id=1)
+
+    assert engine.ocr.params["use_dml"] is False
+    assert "dml_device_id" not in engine.ocr.params
+    reports = _fallback_lines(capsys.readouterr().out)
+    assert [r["device_id"] for r in reports] == [1]
+    assert "DEVICE_REMOVED" in reports[0]["error"]
+
+
+def test_dml_inference_failure_is_retried_on_cpu(fake_rapidocr, capsys):
+    fake_rapidocr.fail_dml_predict = True
+    engine = ocr_engine.OCREngine(use_dml=True, dml_device_id=0)
+
+    results = engine.recognize(np.zeros((4, 4, 3), dtype=np.uint8))
+
+    assert [r["text"] for r in results] == ["hello"]
+    assert engine.ocr.params["use_dml"] is False
+    assert [r["device_id"] for r in _fallback_lines(capsys.readouterr().out)] == [0]
+    # Already on CPU: a later failure is not reported again.
+    assert engine.recognize(np.zeros((4, 4, 3), dtype=np.uint8))[0]["text"] == "hello"
+    assert _fallback_lines(capsys.readouterr().out) == []
+
+
+def test_npu_init_failure_keeps_directml_without_reporting_a_dml_fallback(fake_rapidocr, capsys):
+    fake_rapidocr.fail_npu_init = True
+
+    engine = ocr_engine.OCREngine(
+        use_dml=True, dml_device_id=0, npu_provider="OpenVINOExecutionProvider"
+    )
+
+    assert engine.ocr.params["npu_provider"] is None
+    assert engine.ocr.params["use_dml"] is True
+    assert engine.ocr.params["dml_device_id"] == 0
+    assert _fallback_lines(capsys.readouterr().out) == []
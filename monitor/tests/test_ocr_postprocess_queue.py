@@ -82,6 +82,46 @@ def test_ocr_postprocess_queue_drops_when_full():
     assert queue.dropped == 1
 
 
+def test_ocr_postprocess_queue_serves_interactive_jobs_first():
+    queue = OcrPostprocessQueue(DummyOcrWorker(), None, maxsize=4)
+
+    assert queue.enqueue({"screenshot_id": 1}, "backfill")
+    assert queue.enqueue({"screenshot_id": 2}, "live")
+    assert queue.enqueue({"screenshot_id": 3}, "interactive")
+    assert queue.enqueue({"screenshot_id": 4}, "live")
+
+    order = [queue._queue.get_nowait()["screenshot_id"] for _ in range(4)]
+    assert order == [3, 2, 4, 1]
+    assert queue.status_snapshot()["queue_by_priority"] == {"interactive": 0, "live": 0, "backfill": 0}
+
+
+def test_ocr_postprocess_queue_reject_new_lets_higher_priority_displace_newest():
+    queue = OcrPostprocessQueue(DummyOcrWorker(), None, maxsize=2, drop_policy="reject_new")
+
+    assert queue.enqueue({"screenshot_id": 1}, "live")
+    assert queue.enqueue({"screenshot_id": 2}, "live")
+    assert not queue.enqueue({"screenshot_id": 3}, "live")
+    assert queue.enqueue({"screenshot_id": 4}, "interactive")
+
+    order = [queue._queue.get_nowait()["screenshot_id"] for _ in range(2)]
+    assert order == [4, 1]
+    assert queue.dropped == 2
+
+
+def test_ocr_postprocess_queue_drop_oldest_keeps_newest_and_higher_priority():
+    queue = OcrPostprocessQueue(DummyOcrWorker(), None, maxsize=2, drop_policy="drop_oldest")
+
+    assert queue.enqueue({"screenshot_id": 1}, "interactive")
+    assert queue.enqueue({"screenshot_id": 2}, "live")
+    assert queue.enqueue({"screenshot_id": 3}, "live")
+    assert not queue.enqueue({"screenshot_id": 4}, "backfill")
+
+    order = [queue._queue.get_nowait()["screenshot_id"] for _ in range(2)]
+    assert order == [1, 3]
+    assert queue.dropped == 2
+    assert queue.status_snapshot()["drop_policy"] == "drop_oldest"
+
+
 def test_ocr_postprocess_updates_category_async_path(monkeypatch):
     classifier = DummyClassifier()
     storage = DummyStorageClient()
@@ -176,7 +216,6 @@ def test_vector_retry_enqueues_retry_only_job_without_classification(monkeypatch
 
     result = queue.retry_failed_vector_indexing(limit=1)
     queued = queue._queue.get_nowait()
-    queue._queue.task_done()
 
     monkeypatch.setattr(config, "CLASSIFICATION_ENABLED", True)
     monkeypatch.setattr("storage_client.get_storage_client", lambda: storage)
@@ -41,3 +41,34 @@ def test_directml_options_remain_compatible_with_bundled_models(monkeypatch):
     )
 
     assert wrapper.engine.params["EngineConfig.onnxruntime.use_dml"] is True
+
+
+class _FakeInferenceSession:
+    def __init__(self, path, sess_options=None, providers=None):
+        self.providers = providers
+
+
+def test_npu_provider_is_tried_first_when_available(monkeypatch):
+    import sys
+    import types
+
+    fake_ort = types.SimpleNamespace(
+        InferenceSession=type("InferenceSession", (_FakeInferenceSession,), {}),
+        get_available_providers=lambda: ["OpenVINOExecutionProvider", "CPUExecutionProvider"],
+    )
+    monkeypatch.setitem(sys.modules, "onnxruntime", fake_ort)
+    monkeypatch.setattr(rapidocr_capability, "_active_npu_provider", None)
+
+    assert rapidocr_capability._set_ort_npu_provider("QNNExecutionProvider") is None
+    assert rapidocr_capability._set_ort_npu_provider("OpenVINOExecutionProvider") == (
+        "OpenVINOExecutionProvider"
+    )
+    session = fake_ort.InferenceSession("det.onnx", providers=["CPUExecutionProvider"])
+    assert session.providers == [
+        ("OpenVINOExecutionProvider", {"device_type": "NPU"}),
+        "CPUExecutionProvider",
+    ]
+
+    rapidocr_capability._set_ort_npu_provider(None)
+    session = fake_ort.InferenceSession("det.onnx", providers=["CPUExecutionProvider"])
+    assert session.providers == ["CPUExecutionProvider"]
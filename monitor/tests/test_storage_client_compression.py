@@ -0,0 +1,139 @@
+import json
+import struct
+import types
+import zlib
+
+import storage_client as sc
+
+
+class FakePyWinError(Exception):
+    def __init__(self, winerror, message=""):
+        super().__init__(message or f"winerror={winerror}")
+        self.winerror = winerror
+
+
+def _fake_zstd_module():
+    """zlib stand-in exposing the small zstandard surface the client uses."""
+
+    class Compressor:
+        def __init__(self, level=3):
+            self.level = level
+
+        def compress(self, data):
+            return zlib.compress(data, self.level)
+
+    class Decompressor:
+        def decompress(self, data, max_output_size=0):
+            return zlib.decompress(data)
+
+    return types.SimpleNamespace(ZstdCompressor=Compressor, ZstdDecompressor=Decompressor)
+
+
+def _use_fake_zstd(monkeypatch):
+    monkeypatch.setattr(sc, "_zstd", _fake_zstd_module())
+    monkeypatch.setattr(sc, "_lz4_block", None)
+    monkeypatch.setattr(sc, "REVERSE_IPC_COMPRESSION_ENABLED", True)
+
+
+def _plain_frame(obj) -> bytes:
+    payload = json.dumps(obj).encode("utf-8")
+    return struct.pack("<I", len(payload)) + payload
+
+
+def _install_pipe(monkeypatch, responses):
+    stream = bytearray(b"".join(responses))
+    written = bytearray()
+
+    def fake_write_file(_handle, payload):
+        written.extend(payload)
+        return 0, len(payload)
+
+    def fake_read_file(_handle, size):
+        n = min(size, len(stream))
+        chunk = bytes(stream[:n])
+        del stream[:n]
+        return 0, chunk
+
+    monkeypatch.setattr(sc.pywintypes, "error", FakePyWinError)
+    monkeypatch.setattr(sc.win32file, "CreateFile", lambda *_a, **_k: object())
+    monkeypatch.setattr(sc.win32pipe, "SetNamedPipeHandleState", lambda *_a, **_k: None)
+    monkeypatch.setattr(sc.win32file, "WriteFile", fake_write_file)
+    monkeypatch.setattr(sc.win32file, "FlushFileBuffers", lambda _h: None)
+    monkeypatch.setattr(sc.win32file, "ReadFile", fake_read_file)
+    monkeypatch.setattr(sc.win32file, "CloseHandle", lambda _h: None)
+    return written
+
+
+def _split_frames(data: bytes):
+    frames = []
+    offset = 0
+    while offset < len(data):
+        prefix = struct.unpack_from("<I", data, offset)[0]
+        length = prefix & ~sc.COMPRESSED_FRAME_FLAG
+        body = bytes(data[offset + 4:offset + 4 + length])
+        frames.append((bool(prefix & sc.COMPRESSED_FRAME_FLAG), body))
+        offset += 4 + length
+    return frames
+
+
+def test_encode_frame_round_trips_large_payloads_and_keeps_small_ones_plain(monkeypatch):
+    _use_fake_zstd(monkeypatch)
+    payload = json.dumps({"ocr": ["repeated text"] * 20000}).encode("utf-8")
+
+    prefix, body = sc._encode_frame(payload, "zstd")
+    assert struct.unpack("<I", prefix)[0] & sc.COMPRESSED_FRAME_FLAG
+    assert len(body) < len(payload)
+    assert sc._decode_compressed_body(body) == payload
+
+    prefix, body = sc._encode_frame(b'{"command":"ping"}', "zstd")
+    assert struct.unpack("<I", prefix)[0] == len(body)
+    prefix, body = sc._encode_frame(payload, None)
+    assert body == payload
+
+
+def test_handshake_enables_compression_for_later_requests(monkeypatch):
+    _use_fake_zstd(monkeypatch)
+    big_response = {"status": "success", "data": {"rows": ["row text"] * 20000}}
+    prefix, body = sc._encode_frame(json.dumps(big_response).encode("utf-8"), "zstd")
+    written = _install_pipe(
+        monkeypatch,
+        [
+            _plain_frame({"status": "success", "data": {}, "_ipc_codec": "zstd"}),
+            prefix + body,
+        ],
+    )
+    client = sc.StorageClient("test-pipe")
+
+    first = client._send_request({"command": "heartbeat"})
+    assert first == {"status": "success", "data": {}}
+    assert client.ipc_health_snapshot()["compression_codec"] == "zstd"
+
+    second = client._send_request({"command": "save_batch", "payload": "x" * (128 * 1024)})
+    assert second == big_response
+
+    (first_compressed, first_body), (second_compressed, second_body) = _split_frames(written)
+    assert not first_compressed
+    assert json.loads(first_body)["_ipc_codecs"] == ["zstd"]
+    assert second_compressed
+    sent = json.loads(sc._decode_compressed_body(second_body))
+    assert "_ipc_codecs" not in sent
+    assert len(sent["payload"]) == 128 * 1024
+
+
+def test_old_backend_without_codec_reply_keeps_plain_frames(monkeypatch):
+    _use_fake_zstd(monkeypatch)
+    written = _install_pipe(
+        monkeypatch,
+        [
+            _plain_frame({"status": "success", "data": {}}),
+            _plain_frame({"status": "success", "data": {}}),
+        ],
+    )
+    client = sc.StorageClient("test-pipe")
+
+    client._send_request({"command": "heartbeat"})
+    client._send_request({"command": "save_batch", "payload": "x" * (128 * 1024)})
+
+    frames = _split_frames(written)
+    assert [compressed for compressed, _ in frames] == [False, False]
+    assert client.ipc_health_snapshot()["compression_codec"] is None
@@ -119,3 +119,23 @@ def test_storage_client_smart_cluster_reverse_ipc_payload_contract():
             "rerank_score": 0.88,
         },
     ]
+
+
+def test_storage_client_audio_transcript_payload_contract():
+    client = sc.StorageClient("test-pipe")
+    requests = _capture_requests(client, [{"status": "success", "data": {"id": 5}}])
+    segments = [{"start_ms": 0, "end_ms": 1800, "text": "Ship it on Friday"}]
+
+    assert client.save_audio_transcript(1_000.0, 31_000, "system", "en", segments) is True
+    assert "save_audio_transcript" in sc.SPILLABLE_COMMANDS
+
+    assert requests == [
+        {
+            "command": "save_audio_transcript",
+            "started_at": 1_000,
+            "ended_at": 31_000,
+            "source": "system",
+            "language": "en",
+            "segments": segments,
+        },
+    ]
@@ -181,7 +181,7 @@ def test_send_request_watchdog_closes_handle_during_blocking_read(monkeypatch):
     fake_handle = object()
 
     monkeypatch.setattr(client, "_connect_persistent_handle", lambda: fake_handle)
-    monkeypatch.setattr(sc, "_write_framed_json", lambda _handle, _payload: None)
+    monkeypatch.setattr(sc, "_write_framed_json", lambda _handle, _payload, _codec=None: None)
     monkeypatch.setattr(sc.win32file, "FlushFileBuffers", lambda _handle: None)
 
     def slow_read(_handle):
@@ -52,6 +52,8 @@ def install_pipe_mocks(monkeypatch, read_chunks, flush_error=None):
     def fake_close_handle(_handle):
         state["close_calls"] += 1
 
+    # Exact wire-size assertions assume plain frames without a codec offer.
+    monkeypatch.setattr(sc, "_available_codecs", lambda: [])
     monkeypatch.setattr(sc.pywintypes, "error", FakePyWinError)
     monkeypatch.setattr(sc.win32file, "CreateFile", fake_create_file)
     monkeypatch.setattr(sc.win32pipe, "SetNamedPipeHandleState", fake_set_mode)
@@ -0,0 +1,123 @@
+import json
+
+import storage_client as sc
+
+
+TIMEOUT = {"status": "error", "code": "ipc_timeout", "error": "IPC timeout after 5s"}
+OK = {"status": "success", "data": {}}
+
+
+def _client_with_responses(tmp_path, responses):
+    client = sc.StorageClient("test-pipe", spill_path=str(tmp_path / "spill.jsonl"))
+    sent = []
+
+    def fake_send(request, timeout=None):
+        sent.append(request)
+        response = responses.pop(0)
+        return response(request) if callable(response) else response
+
+    client._send_request = fake_send
+    return client, sent
+
+
+def _spilled(tmp_path):
+    path = tmp_path / "spill.jsonl"
+    if not path.exists():
+        return []
+    return [json.loads(line) for line in path.read_text(encoding="utf-8").splitlines() if line.strip()]
+
+
+def test_idempotent_write_spills_on_transport_failure_and_queues_followups(tmp_path):
+    client, sent = _client_with_responses(tmp_path, [TIMEOUT])
+
+    assert client.update_screenshot_category(7, "Coding") is True
+    assert client.set_ocr_postprocess_status(7, "done") is True
+
+    # Only the first write reached the pipe; the second queued behind it.
+    assert [r["command"] for r in sent] == ["update_screenshot_category"]
+    assert [r["command"] for r in _spilled(tmp_path)] == [
+        "update_screenshot_category",
+        "set_ocr_postprocess_status",
+    ]
+    snapshot = client.ipc_health_snapshot()
+    assert snapshot["backend_healthy"] is False
+    assert snapshot["spilled_pending"] == 2
+
+
+def test_backend_rejection_is_not_spilled(tmp_path):
+    client, _ = _client_with_responses(
+        tmp_path,
+        [{"status": "error", "error": "Screenshot not found"}],
+    )
+
+    assert client.update_screenshot_category(7, "Coding") is False
+    assert _spilled(tmp_path) == []
+    assert client.ipc_health_snapshot()["backend_healthy"] is True
+
+
+def test_heartbeat_recovery_replays_in_order_and_clears_file(tmp_path):
+    client, sent = _client_with_responses(
+        tmp_path,
+        [
+            TIMEOUT,
+            {"status": "success", "data": {"db_responsive": True}},
+            OK,
+            OK,
+        ],
+    )
+    client.update_screenshot_category(7, "Coding")
+    client.set_ocr_postprocess_status(7, "done")
+
+    assert client.heartbeat_once() is True
+
+    assert [r["command"] for r in sent] == [
+        "update_screenshot_category",
+        "heartbeat",
+        "update_screenshot_category",
+        "set_ocr_postprocess_status",
+    ]
+    assert not (tmp_path / "spill.jsonl").exists()
+    snapshot = client.ipc_health_snapshot()
+    assert snapshot["backend_healthy"] is True
+    assert snapshot["spilled_pending"] == 0
+    assert snapshot["last_heartbeat_ok_at"] is not None
+
+
+def test_heartbeat_with_stuck_database_keeps_spilling(tmp_path):
+    client, sent = _client_with_responses(
+        tmp_path,
+        [TIMEOUT, {"status": "success", "data": {"db_responsive": False}}],
+    )
+    client.update_screenshot_category(7, "Coding")
+
+    assert client.heartbeat_once() is False
+    assert [r["command"] for r in sent] == ["update_screenshot_category", "heartbeat"]
+    assert len(_spilled(tmp_path)) == 1
+
+
+def test_replay_stops_at_transport_failure_and_keeps_remaining(tmp_path):
+    client, _ = _client_with_responses(tmp_path, [TIMEOUT, OK, TIMEOUT])
+    client.update_screenshot_category(7, "Coding")
+    client.set_ocr_postprocess_status(7, "done")
+
+    assert client.replay_spilled_requests() == 1
+    assert [r["command"] for r in _spilled(tmp_path)] == ["set_ocr_postprocess_status"]
+    assert client.ipc_health_snapshot()["spilled_pending"] == 1
+
+
+def test_pending_spill_is_counted_on_startup(tmp_path):
+    (tmp_path / "spill.jsonl").write_text(
+        json.dumps({"command": "abort_screenshot", "screenshot_id": "1", "reason": "x"}) + "\n",
+        encoding="utf-8",
+    )
+
+    client = sc.StorageClient("test-pipe", spill_path=str(tmp_path / "spill.jsonl"))
+
+    assert client.ipc_health_snapshot()["spilled_pending"] == 1
+
+
+def test_without_spill_path_failures_surface_unchanged(tmp_path):
+    client = sc.StorageClient("test-pipe")
+    client._send_request = lambda request, timeout=None: TIMEOUT
+
+    assert client.update_screenshot_category(7, "Coding") is False
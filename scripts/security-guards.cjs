@@ -14,6 +14,12 @@ const COMMAND_TIERS = {
   'monitor::get_monitor_status': 'public',
   'monitor::monitor_search_nl': 'session_required',
   'monitor::monitor_update_filters': 'session_required',
+  'monitor::monitor_get_filters': 'session_required',
+  'monitor::monitor_set_exclusion_rules': 'session_required',
+  'monitor::monitor_get_exclusion_rules': 'session_required',
+  'monitor::get_capture_schedule': 'public',
+  'monitor::monitor_get_metrics': 'public',
+  'monitor::set_capture_schedule': 'session_required',
   'monitor::monitor_update_advanced_config': 'session_required',
   'monitor::monitor_update_feature_config': 'session_required',
   'monitor::monitor_run_clustering': 'session_required',
@@ -33,6 +39,14 @@ const COMMAND_TIERS = {
   'commands::storage::storage_get_timeline': 'session_required',
   'commands::storage::storage_get_timeline_density': 'session_required',
   'commands::storage::storage_search': 'session_required',
+  'commands::storage::storage_search_count': 'session_required',
+  'commands::storage::storage_search_titles': 'session_required',
+  'commands::storage::storage_get_search_history': 'session_required',
+  'commands::storage::storage_suggest_search_queries': 'session_required',
+  'commands::storage::storage_clear_search_history': 'session_required',
+  'commands::storage::storage_create_saved_search': 'session_required',
+  'commands::storage::storage_list_saved_searches': 'session_required',
+  'commands::storage::storage_delete_saved_search': 'session_required',
   'commands::storage::storage_get_image': 'session_required',
   'commands::storage::storage_get_thumbnail': 'session_required',
   'commands::storage::storage_batch_get_thumbnails': 'session_required',
@@ -41,7 +55,13 @@ const COMMAND_TIERS = {
   'commands::storage::storage_cancel_thumbnail_warmup': 'session_required',
   'commands::storage::storage_get_screenshot_details': 'session_required',
   'commands::storage::storage_delete_screenshot': 'session_required',
+  'commands::storage::storage_restore_screenshot': 'session_required',
+  'commands::storage::storage_list_trash': 'session_required',
+  'commands::storage::storage_empty_trash': 'session_required',
   'commands::storage::storage_delete_by_time_range': 'session_required',
+  'commands::storage::storage_protect_range': 'session_required',
+  'commands::storage::storage_list_protected_ranges': 'session_required',
+  'commands::storage::storage_unprotect_range': 'session_required',
   'commands::storage::storage_list_processes': 'session_required',
   'commands::storage::storage_get_process_stats': 'session_required',
   'commands::storage::storage_get_process_monthly_thumbnails': 'session_required',
@@ -77,6 +97,61 @@ const COMMAND_TIERS = {
   'commands::storage::storage_remove_task_screenshot': 'session_required',
   'commands::storage::storage_merge_tasks': 'session_required',
   'commands::storage::storage_save_clustering_results': 'session_required',
+  'commands::storage::storage_verify_images': 'session_required',
+  'commands::storage::storage_check_integrity': 'session_required',
+  'commands::storage::storage_export_search': 'session_required',
+  'commands::storage::storage_export_pdf': 'session_required',
+  'commands::storage::storage_export_daily_note': 'session_required',
+  'commands::storage::storage_get_daily_note_vault': 'session_required',
+  'commands::storage::storage_set_daily_note_vault': 'session_required',
+  'commands::storage::storage_export_weekly_digest': 'session_required',
+  'commands::storage::storage_get_ocr_text_rules': 'session_required',
+  'commands::storage::storage_set_ocr_text_rules': 'session_required',
+  'commands::storage::storage_get_retention_policy': 'session_required',
+  'commands::storage::storage_set_retention_policy': 'session_required',
+  'commands::storage::storage_get_compliance_status': 'session_required',
+  'commands::storage::storage_set_compliance_mode': 'session_required',
+  'commands::storage::storage_get_paranoid_mode': 'session_required',
+  'commands::storage::storage_set_paranoid_mode': 'session_required',
+  'commands::storage::storage_get_pending_recovery': 'session_required',
+  'commands::storage::storage_list_month_shards': 'session_required',
+  'commands::storage::storage_detach_month': 'session_required',
+  'commands::storage::storage_attach_month': 'session_required',
+  'commands::storage::storage_gc': 'session_required',
+  'commands::storage::storage_get_quota': 'session_required',
+  'commands::storage::storage_set_quota': 'session_required',
+  'commands::storage::storage_export_range': 'session_required',
+  'commands::storage::storage_get_context_at': 'session_required',
+  'commands::storage::storage_import_archive': 'session_required',
+  'commands::storage::storage_get_usage_by_day': 'session_required',
+  'commands::storage::storage_batch_apply': 'session_required',
+  'commands::storage::storage_get_capture_days': 'session_required',
+  'commands::storage::storage_get_screenshots_by_capture_day': 'session_required',
+  'commands::storage::storage_get_image_preview': 'public',
+  'commands::storage::storage_get_ocr_texts': 'session_required',
+  'commands::storage::storage_get_stats': 'session_required',
+  'commands::storage::storage_add_tag': 'session_required',
+  'commands::storage::storage_remove_tag': 'session_required',
+  'commands::storage::storage_list_tags': 'session_required',
+  'commands::storage::storage_search_by_tag': 'session_required',
+  'commands::storage::storage_pin_screenshot': 'session_required',
+  'commands::storage::storage_unpin_screenshot': 'session_required',
+  'commands::storage::storage_list_pinned': 'session_required',
+  'commands::storage::storage_update_ocr_text': 'session_required',
+  'commands::storage::storage_add_annotation': 'session_required',
+  'commands::storage::storage_update_annotation': 'session_required',
+  'commands::storage::storage_delete_annotation': 'session_required',
+  'commands::storage::storage_list_annotations': 'session_required',
+  'commands::storage::storage_search_annotations': 'session_required',
+  'commands::storage::storage_list_clipboard_entries': 'session_required',
+  'commands::storage::storage_search_clipboard_entries': 'session_required',
+  'commands::storage::storage_delete_clipboard_entry': 'session_required',
+  'commands::storage::storage_clear_clipboard_history': 'session_required',
+  'commands::storage::storage_list_audio_transcripts': 'session_required',
+  'commands::storage::storage_search_audio_transcripts': 'session_required',
+  'commands::storage::storage_delete_audio_transcript': 'session_required',
+  'commands::storage::storage_get_sessions': 'session_required',
+  'commands::storage::storage_get_audit_log': 'session_required',
   'analysis::get_analysis_overview': 'session_required',
   'commands::mcp::mcp_set_enabled': 'session_required',
   'commands::mcp::mcp_get_status': 'public',
@@ -87,16 +162,41 @@ const COMMAND_TIERS = {
   'commands::mcp::mcp_set_port': 'session_required',
   'commands::mcp::mcp_get_sensitive_filter_config': 'session_required',
   'commands::mcp::mcp_set_sensitive_filter_config': 'session_required',
+  'commands::mcp::mcp_list_api_tokens': 'session_required',
+  'commands::mcp::mcp_create_api_token': 'session_required',
+  'commands::mcp::mcp_copy_api_token_to_clipboard': 'session_required',
+  'commands::mcp::mcp_revoke_api_token': 'session_required',
   'commands::utility::get_advanced_config': 'public',
   'commands::utility::set_advanced_config': 'session_required',
   'monitor::enumerate_gpus': 'public',
+  'monitor::enumerate_npus': 'public',
+  'monitor::get_capture_exclude_own_windows': 'public',
+  'monitor::set_capture_exclude_own_windows': 'session_required',
   'commands::utility::toggle_game_mode': 'session_required',
   'commands::utility::get_game_mode_status': 'public',
+  'commands::utility::get_game_mode_config': 'public',
+  'commands::utility::set_game_mode_config': 'session_required',
+  'commands::utility::get_screen_share_status': 'public',
+  'commands::utility::set_screen_share_pause_enabled': 'session_required',
+  'commands::utility::get_clipboard_history_settings': 'public',
+  'commands::utility::set_clipboard_history_settings': 'session_required',
+  'commands::utility::get_audio_capture_settings': 'public',
+  'commands::utility::set_audio_capture_settings': 'session_required',
   'commands::migration::storage_list_plaintext_files': 'session_required',
   'commands::migration::storage_migrate_plaintext': 'session_required',
+  'commands::migration::storage_normalize_paths': 'session_required',
   'commands::migration::storage_migrate_data_dir': 'session_required',
   'commands::migration::storage_migration_cancel': 'session_required',
   'commands::migration::storage_delete_plaintext': 'session_required',
+  'commands::migration::storage_maintenance': 'session_required',
+  'commands::migration::storage_backup_now': 'session_required',
+  'commands::migration::storage_get_backup_dir': 'session_required',
+  'commands::migration::storage_set_backup_dir': 'session_required',
+  'commands::migration::storage_restore_from_backup': 'session_required',
+  'commands::migration::storage_sync_now': 'session_required',
+  'commands::migration::storage_get_sync_config': 'session_required',
+  'commands::migration::storage_set_sync_config': 'session_required',
+  'commands::migration::storage_rebuild_index': 'session_required',
   'commands::credential::credential_initialize': 'public',
   'commands::credential::credential_verify_user': 'public',
   'commands::credential::credential_check_session': 'public',
@@ -104,6 +204,7 @@ const COMMAND_TIERS = {
   'commands::credential::credential_set_foreground': 'public',
   'commands::credential::credential_set_session_timeout': 'session_required',
   'commands::credential::credential_get_session_timeout': 'public',
+  'commands::credential::credential_rotate_keys': 'session_required',
   'get_autostart_status': 'public',
   'set_autostart': 'session_required',
   'python::check_python_status': 'public',
@@ -133,6 +234,13 @@ const COMMAND_TIERS = {
   'commands::utility::mark_smart_cluster_setup_done': 'public',
   'commands::utility::get_extension_enhancement_config': 'public',
   'commands::utility::set_extension_enhancement': 'session_required',
+  'commands::utility::get_browser_url_capture_config': 'public',
+  'commands::utility::set_browser_url_capture': 'session_required',
+  'commands::utility::get_hotkeys': 'public',
+  'commands::utility::set_capture_hotkey': 'session_required',
+  'commands::utility::set_search_hotkey': 'session_required',
+  'search_overlay::search_overlay_hide': 'public',
+  'search_overlay::search_overlay_open_main': 'public',
   'commands::utility::get_nmh_sessions': 'public',
   'commands::smart_cluster::smart_cluster_list': 'session_required',
   'commands::smart_cluster::smart_cluster_get': 'session_required',
@@ -164,6 +272,7 @@ const COMMAND_TIERS = {
   'commands::utility::get_lightweight_config': 'public',
   'commands::utility::set_lightweight_config': 'session_required',
   'commands::utility::open_path': 'session_required',
+  'commands::utility::get_read_only_mode': 'public',
   'power::get_power_saving_status': 'public',
   'power::set_power_saving_enabled': 'session_required',
   'ml_runtime::get_ml_ocr_status': 'public',
@@ -171,9 +280,22 @@ const COMMAND_TIERS = {
   'ml_runtime::get_rust_ocr_model_status': 'public',
   'ml_runtime::download_rust_ocr_model': 'bootstrap_policy',
   'ml_runtime::take_ocr_model_repair_request': 'public',
+  'ocr_reprocess::reprocess_ocr': 'session_required',
   'ml_runtime::debug_trigger_ocr_model_repair_notification': 'session_required',
   'semantic_runtime::get_ml_semantic_status': 'public',
   'semantic_runtime::restart_ml_semantic_worker': 'runtime_public',
+  'commands::scheduler::scheduler_list_jobs': 'session_required',
+  'commands::scheduler::scheduler_set_job_enabled': 'session_required',
+  'commands::scheduler::scheduler_set_job_interval': 'session_required',
+  'commands::scheduler::scheduler_run_job': 'session_required',
+  'commands::setup::get_setup_state': 'public',
+  'commands::setup::advance_setup_step': 'bootstrap_policy',
+  'commands::setup::setup_list_data_drives': 'public',
+  'commands::setup::setup_estimate_storage': 'public',
+  'commands::setup::setup_validate_data_dir': 'public',
+  'commands::setup::setup_set_data_dir': 'bootstrap_policy',
+  'hooks::get_monitor_hooks': 'session_required',
+  'hooks::set_monitor_hooks': 'session_required',
 };
 
 function read(file) {
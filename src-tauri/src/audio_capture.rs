@@ -0,0 +1,151 @@
+//! Audio capture and transcription settings.
+//!
+//! Recording and transcription run in the Python monitor (`monitor/audio_transcriber.py`):
+//! system audio and/or the microphone are recorded in fixed-length chunks, each
+//! chunk is transcribed locally with Whisper and the transcript is saved over
+//! reverse IPC (`save_audio_transcript`), where it is encrypted and indexed in
+//! `storage/audio_transcripts.rs`. Raw audio never leaves the monitor process.
+//!
+//! The options live in the registry. They are passed to the monitor as environment
+//! variables when it starts and pushed with the `update_audio_config` command when
+//! they change. Transcripts older than the retention period are removed by the
+//! `audio_retention` scheduled job.
+
+use serde::{Deserialize, Serialize};
+
+pub const AUDIO_CAPTURE_ENABLED_KEY: &str = "audio_capture_enabled";
+pub const AUDIO_CAPTURE_SOURCE_KEY: &str = "audio_capture_source";
+pub const AUDIO_CHUNK_SECS_KEY: &str = "audio_chunk_secs";
+pub const AUDIO_WHISPER_MODEL_KEY: &str = "audio_whisper_model";
+/// Whisper language code; empty lets Whisper detect the language of each chunk.
+pub const AUDIO_LANGUAGE_KEY: &str = "audio_language";
+pub const AUDIO_RETENTION_DAYS_KEY: &str = "audio_retention_days";
+
+pub const AUDIO_SOURCES: &[&str] = &["system", "microphone", "both"];
+pub const WHISPER_MODELS: &[&str] = &["tiny", "base", "small", "medium"];
+pub const DEFAULT_AUDIO_CHUNK_SECS: u32 = 30;
+pub const MIN_AUDIO_CHUNK_SECS: u32 = 10;
+pub const MAX_AUDIO_CHUNK_SECS: u32 = 300;
+pub const DEFAULT_AUDIO_RETENTION_DAYS: u32 = 30;
+const MAX_AUDIO_RETENTION_DAYS: u32 = 3650;
+
+/// Audio capture options. `retention_days == 0` keeps transcripts until deleted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioCaptureSettings {
+    pub enabled: bool,
+    /// One of `AUDIO_SOURCES`.
+    pub source: String,
+    pub chunk_secs: u32,
+    /// One of `WHISPER_MODELS`.
+    pub model: String,
+    pub language: String,
+    pub retention_days: u32,
+}
+
+impl AudioCaptureSettings {
+    /// Clamps numbers into range and replaces unknown choices with the defaults.
+    fn normalized(self) -> Self {
+        let source = if AUDIO_SOURCES.contains(&self.source.as_str()) {
+            self.source
+        } else {
+            AUDIO_SOURCES[0].to_string()
+        };
+        let model = if WHISPER_MODELS.contains(&self.model.as_str()) {
+            self.model
+        } else {
+            "base".to_string()
+        };
+        let language: String = self
+            .language
+            .trim()
+            .to_lowercase()
+            .chars()
+            .filter(|c| c.is_ascii_alphabetic())
+            .take(8)
+            .collect();
+        Self {
+            enabled: self.enabled,
+            source,
+            chunk_secs: self
+                .chunk_secs
+                .clamp(MIN_AUDIO_CHUNK_SECS, MAX_AUDIO_CHUNK_SECS),
+            model,
+            language,
+            retention_days: self.retention_days.min(MAX_AUDIO_RETENTION_DAYS),
+        }
+    }
+
+    /// Payload of the monitor's `update_audio_config` command.
+    pub fn to_monitor_command(&self) -> serde_json::Value {
+        serde_json::json!({
+            "command": "update_audio_config",
+            "enabled": self.enabled,
+            "source": self.source,
+            "chunk_secs": self.chunk_secs,
+            "model": self.model,
+            "language": self.language,
+        })
+    }
+
+    /// Environment variables the monitor reads at startup.
+    pub fn monitor_env(&self) -> [(&'static str, String); 5] {
+        [
+            ("CARBONPAPER_AUDIO_ENABLED", self.enabled.to_string()),
+            ("CARBONPAPER_AUDIO_SOURCE", self.source.clone()),
+            ("CARBONPAPER_AUDIO_CHUNK_SECS", self.chunk_secs.to_string()),
+            ("CARBONPAPER_WHISPER_MODEL", self.model.clone()),
+            ("CARBONPAPER_AUDIO_LANGUAGE", self.language.clone()),
+        ]
+    }
+}
+
+pub fn load_settings() -> AudioCaptureSettings {
+    AudioCaptureSettings {
+        enabled: crate::registry_config::get_bool(AUDIO_CAPTURE_ENABLED_KEY).unwrap_or(false),
+        source: crate::registry_config::get_string(AUDIO_CAPTURE_SOURCE_KEY)
+            .unwrap_or_else(|| AUDIO_SOURCES[0].to_string()),
+        chunk_secs: crate::registry_config::get_u32(AUDIO_CHUNK_SECS_KEY)
+            .unwrap_or(DEFAULT_AUDIO_CHUNK_SECS),
+        model: crate::registry_config::get_string(AUDIO_WHISPER_MODEL_KEY)
+            .unwrap_or_else(|| "base".to_string()),
+        language: crate::registry_config::get_string(AUDIO_LANGUAGE_KEY).unwrap_or_default(),
+        retention_days: crate::registry_config::get_u32(AUDIO_RETENTION_DAYS_KEY)
+            .unwrap_or(DEFAULT_AUDIO_RETENTION_DAYS),
+    }
+    .normalized()
+}
+
+/// Persists `settings` and returns them as stored.
+pub fn save_settings(settings: AudioCaptureSettings) -> Result<AudioCaptureSettings, String> {
+    let settings = settings.normalized();
+    crate::registry_config::set_bool(AUDIO_CAPTURE_ENABLED_KEY, settings.enabled)?;
+    crate::registry_config::set_string(AUDIO_CAPTURE_SOURCE_KEY, &settings.source)?;
+    crate::registry_config::set_u32(AUDIO_CHUNK_SECS_KEY, settings.chunk_secs)?;
+    crate::registry_config::set_string(AUDIO_WHISPER_MODEL_KEY, &settings.model)?;
+    crate::registry_config::set_string(AUDIO_LANGUAGE_KEY, &settings.language)?;
+    crate::registry_config::set_u32(AUDIO_RETENTION_DAYS_KEY, settings.retention_days)?;
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_are_normalized_to_supported_values() {
+        let settings = AudioCaptureSettings {
+            enabled: true,
+            source: "speakers".to_string(),
+            chunk_secs: 2,
+            model: "large-v3".to_string(),
+            language: " EN; ".to_string(),
+            retention_days: 100_000,
+        }
+        .normalized();
+        assert_eq!(settings.source, "system");
+        assert_eq!(settings.chunk_secs, MIN_AUDIO_CHUNK_SECS);
+        assert_eq!(settings.model, "base");
+        assert_eq!(settings.language, "en");
+        assert_eq!(settings.retention_days, MAX_AUDIO_RETENTION_DAYS);
+    }
+}
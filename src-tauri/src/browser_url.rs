@@ -0,0 +1,163 @@
+//! Browser address-bar URL via UI Automation.
+//!
+//! For users without the browser extension, the capture loop can read the URL shown
+//! in the foreground browser's address bar and store it as the screenshot's
+//! `page_url`. The address bar is found as the first Edit control of the browser
+//! window (the toolbar precedes page content in Chromium and Gecko), or by its
+//! automation ID in Gecko-based browsers, and read through its Value pattern.
+//!
+//! Off by default. Private/incognito windows never get here because the capture loop
+//! skips excluded windows first, and the value is ignored while the address bar has
+//! keyboard focus, since it then holds what the user is typing rather than the page
+//! URL. Reads go through `uia::read_with_timeout`, so a hung browser delays a capture
+//! by at most `READ_TIMEOUT`.
+
+use std::time::Duration;
+
+use windows::core::VARIANT;
+use windows::Win32::Foundation::HWND;
+use windows::Win32::UI::Accessibility::{
+    IUIAutomation, IUIAutomationValuePattern, TreeScope_Descendants, UIA_AutomationIdPropertyId,
+    UIA_ControlTypePropertyId, UIA_EditControlTypeId, UIA_ValuePatternId,
+};
+
+/// Registry flag for reading the address bar. Defaults to off.
+const BROWSER_URL_CAPTURE_KEY: &str = "browser_url_capture_enabled";
+
+/// Longest time a capture waits for the browser to answer.
+const READ_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Gecko-based browsers, whose URL field has a stable automation ID.
+const GECKO_BROWSERS: &[&str] = &["firefox", "waterfox", "floorp", "librewolf", "seamonkey"];
+const GECKO_URLBAR_ID: &str = "urlbar-input";
+
+pub fn browser_url_capture_enabled() -> bool {
+    crate::registry_config::get_bool(BROWSER_URL_CAPTURE_KEY).unwrap_or(false)
+}
+
+pub fn set_browser_url_capture_enabled(enabled: bool) -> Result<(), String> {
+    crate::registry_config::set_bool(BROWSER_URL_CAPTURE_KEY, enabled)
+}
+
+/// Address-bar text of the browser window `hwnd_raw`, or `None` when it has no
+/// address bar, it is being edited, or UI Automation fails.
+fn read_address_bar(
+    automation: &IUIAutomation,
+    hwnd_raw: isize,
+    process_name: &str,
+) -> Result<Option<String>, String> {
+    // SAFETY: every interface is reference counted by its wrapper, and `hwnd_raw` is
+    // only passed to UI Automation, which validates it.
+    unsafe {
+        let root = automation
+            .ElementFromHandle(HWND(hwnd_raw as *mut _))
+            .map_err(|e| format!("Failed to get browser window element: {}", e))?;
+
+        let condition = if GECKO_BROWSERS.contains(&process_name.trim_end_matches(".exe")) {
+            automation.CreatePropertyCondition(
+                UIA_AutomationIdPropertyId,
+                &VARIANT::from(GECKO_URLBAR_ID),
+            )
+        } else {
+            automation.CreatePropertyCondition(
+                UIA_ControlTypePropertyId,
+                &VARIANT::from(UIA_EditControlTypeId.0),
+            )
+        }
+        .map_err(|e| format!("Failed to create address bar condition: {}", e))?;
+
+        let Ok(address_bar) = root.FindFirst(TreeScope_Descendants, &condition) else {
+            return Ok(None);
+        };
+        if address_bar
+            .CurrentHasKeyboardFocus()
+            .map(|focused| focused.as_bool())
+            .unwrap_or(true)
+        {
+            return Ok(None);
+        }
+        let value = address_bar
+            .GetCurrentPatternAs::<IUIAutomationValuePattern>(UIA_ValuePatternId)
+            .and_then(|pattern| pattern.CurrentValue())
+            .map_err(|e| format!("Failed to read address bar: {}", e))?;
+        Ok(Some(value.to_string()))
+    }
+}
+
+/// Turns address-bar text into a page URL. Chromium hides the `https://` scheme, so
+/// bare hosts are assumed to be HTTPS; search text and browser-internal pages give
+/// `None`.
+fn normalize_address_bar_value(raw: &str) -> Option<String> {
+    let value = raw.trim();
+    if value.is_empty() || value.chars().any(char::is_whitespace) {
+        return None;
+    }
+    if let Some((scheme, rest)) = value.split_once("://") {
+        let scheme = scheme.to_ascii_lowercase();
+        return (matches!(scheme.as_str(), "http" | "https" | "file") && !rest.is_empty())
+            .then(|| value.to_string());
+    }
+    let host = value
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default()
+        .rsplit('@')
+        .next()
+        .unwrap_or_default();
+    let host_name = host.split(':').next().unwrap_or_default();
+    let looks_like_host = host_name.eq_ignore_ascii_case("localhost")
+        || (host_name.contains('.')
+            && !host_name.starts_with('.')
+            && !host_name.ends_with('.')
+            && host_name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '.' || c == '-'));
+    looks_like_host.then(|| format!("https://{}", value))
+}
+
+/// URL shown by the foreground browser window, when address-bar capture is enabled.
+pub async fn foreground_browser_url(hwnd_raw: isize, process_name: &str) -> Option<String> {
+    if !browser_url_capture_enabled() || !crate::capture::is_browser_process(process_name) {
+        return None;
+    }
+    let process_name = process_name.to_lowercase();
+    let read = crate::uia::read_with_timeout(READ_TIMEOUT, move |automation| {
+        read_address_bar(automation, hwnd_raw, &process_name)
+    });
+    match read.await {
+        Ok(value) => value.as_deref().and_then(normalize_address_bar_value),
+        Err(e) => {
+            tracing::debug!("Browser URL capture: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn address_bar_values_are_normalized_to_urls() {
+        assert_eq!(
+            normalize_address_bar_value(" github.com/White-NX/carbonPaper "),
+            Some("https://github.com/White-NX/carbonPaper".to_string())
+        );
+        assert_eq!(
+            normalize_address_bar_value("http://intranet.local:8080/wiki"),
+            Some("http://intranet.local:8080/wiki".to_string())
+        );
+        assert_eq!(
+            normalize_address_bar_value("localhost:5173/?q=1"),
+            Some("https://localhost:5173/?q=1".to_string())
+        );
+        assert_eq!(
+            normalize_address_bar_value("例子.测试/页面"),
+            Some("https://例子.测试/页面".to_string())
+        );
+        assert_eq!(normalize_address_bar_value("rust borrow checker"), None);
+        assert_eq!(normalize_address_bar_value("carbonpaper"), None);
+        assert_eq!(normalize_address_bar_value("chrome://settings"), None);
+        assert_eq!(normalize_address_bar_value(""), None);
+    }
+}
@@ -2,15 +2,21 @@
 //!
 //! The module prefers Windows Graphics Capture, falls back to GDI where necessary,
 //! applies exclusion and activity policy, and commits encoded frames to storage.
+//!
+//! Frames always contain the foreground window alone. The capture item is created
+//! for that window's HWND, which renders only its own content, so background and
+//! overlapping windows (mail previews, chats) never reach storage or OCR. There is
+//! no full-desktop mode.
 
 use crate::monitor::MonitorState;
 use crate::storage::{OcrResultInput, SaveScreenshotRequest, StorageState};
 use base64::Engine;
 use image::codecs::jpeg::JpegEncoder;
 use image::{ImageEncoder, RgbImage};
+use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use tauri::Manager;
 
@@ -23,7 +29,7 @@ use windows::Win32::System::Threading::{
 };
 use windows::Win32::UI::WindowsAndMessaging::{
     GetClassNameW, GetForegroundWindow, GetWindowDisplayAffinity, GetWindowRect, GetWindowTextW,
-    GetWindowThreadProcessId,
+    GetWindowThreadProcessId, SetWindowDisplayAffinity, WDA_EXCLUDEFROMCAPTURE, WDA_NONE,
 };
 
 use std::sync::mpsc::{sync_channel, Receiver};
@@ -59,6 +65,17 @@ pub struct CaptureConfig {
     pub dhash_history_size: usize,
 }
 
+/// Registry keys for the user-tunable parts of `CaptureConfig`.
+pub const CAPTURE_INTERVAL_REGISTRY_KEY: &str = "capture_interval_secs";
+pub const CAPTURE_DHASH_THRESHOLD_REGISTRY_KEY: &str = "capture_dhash_threshold";
+
+/// Bounds for the interval trigger, in seconds.
+pub const MIN_CAPTURE_INTERVAL_SECS: u64 = 2;
+pub const MAX_CAPTURE_INTERVAL_SECS: u64 = 600;
+
+/// Upper bound for the change-detection threshold, in differing bits of the 256-bit dHash.
+pub const MAX_CAPTURE_DHASH_THRESHOLD: u32 = 64;
+
 impl Default for CaptureConfig {
     fn default() -> Self {
         Self {
@@ -72,14 +89,63 @@ impl Default for CaptureConfig {
     }
 }
 
+/// How a redaction region is masked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RedactionMode {
+    #[default]
+    Blackout,
+    Blur,
+}
+
+/// Coordinate space of a redaction region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RegionScope {
+    /// Virtual-desktop pixels, so the region stays on one monitor position.
+    Screen,
+    /// Pixels from the captured window's top-left corner.
+    Window,
+}
+
+/// A rectangle masked out of every frame before it is hashed, stored or OCR'd.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedactionRegion {
+    pub scope: RegionScope,
+    /// Lowercase executable name the region is limited to; `None` applies to every window.
+    #[serde(default)]
+    pub process: Option<String>,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub mode: RedactionMode,
+}
+
+/// Redaction regions accepted per filter set.
+const MAX_REDACTION_REGIONS: usize = 32;
+
 /// Settings for excluding specific windows and processes from capture.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExclusionSettings {
     pub exclusion_keywords: Vec<String>,
     pub exclusion_titles: Vec<String>,
+    /// Built-in process blocklist (password managers) checked before any capture.
+    pub exclusion_processes: Vec<String>,
     pub user_excluded_processes: HashSet<String>,
     pub user_excluded_titles: HashSet<String>,
     pub ignore_protected_windows: bool,
+    /// Processes whose frames stay on the timeline but skip OCR and indexing
+    /// (e.g. video players where recognized text is mostly noise).
+    pub ocr_skip_processes: HashSet<String>,
+    /// Allowlist-only mode: when enabled, only `allowed_processes` are captured.
+    pub allowlist_enabled: bool,
+    pub allowed_processes: HashSet<String>,
+    /// Compiled window-title regex rules, persisted in registry_config.
+    #[serde(skip)]
+    pub title_rules: Vec<Regex>,
+    pub redaction_regions: Vec<RedactionRegion>,
 }
 
 impl Default for ExclusionSettings {
@@ -98,9 +164,23 @@ impl Default for ExclusionSettings {
                 "Program Manager".to_string(),
                 "Task Switching".to_string(),
             ],
+            exclusion_processes: vec![
+                "keepass.exe".to_string(),
+                "keepassxc.exe".to_string(),
+                "1password.exe".to_string(),
+                "bitwarden.exe".to_string(),
+                "dashlane.exe".to_string(),
+                "enpass.exe".to_string(),
+                "roboform.exe".to_string(),
+            ],
             user_excluded_processes: HashSet::new(),
             user_excluded_titles: HashSet::new(),
             ignore_protected_windows: true,
+            ocr_skip_processes: HashSet::new(),
+            allowlist_enabled: false,
+            allowed_processes: HashSet::new(),
+            title_rules: Vec::new(),
+            redaction_regions: Vec::new(),
         }
     }
 }
@@ -161,6 +241,60 @@ impl Drop for WgcCaptureSession {
 /// which could otherwise trigger Windows Hello authentication.
 pub type OcrImageCache = Arc<Mutex<HashMap<i64, Arc<[u8]>>>>;
 
+/// Running capture and OCR counters reported by `monitor_get_metrics`.
+#[derive(Default)]
+pub struct CaptureMetrics {
+    /// Frames that passed deduplication and were handed to storage/OCR
+    pub frames_captured: AtomicU64,
+    /// Frames dropped as visually unchanged by the dHash check
+    pub frames_skipped_duplicate: AtomicU64,
+    /// Capture ticks skipped because the previous frame was still in OCR
+    pub frames_skipped_ocr_busy: AtomicU64,
+    /// Capture ticks skipped by exclusion filters
+    pub frames_skipped_excluded: AtomicU64,
+    pub ocr_completed: AtomicU64,
+    pub ocr_failed: AtomicU64,
+    ocr_total_ms: AtomicU64,
+    ocr_last_ms: AtomicU64,
+}
+
+impl CaptureMetrics {
+    pub fn record_ocr(&self, elapsed_ms: u64, succeeded: bool) {
+        if succeeded {
+            self.ocr_completed.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.ocr_failed.fetch_add(1, Ordering::Relaxed);
+        }
+        self.ocr_total_ms.fetch_add(elapsed_ms, Ordering::Relaxed);
+        self.ocr_last_ms.store(elapsed_ms, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> serde_json::Value {
+        let completed = self.ocr_completed.load(Ordering::Relaxed);
+        let failed = self.ocr_failed.load(Ordering::Relaxed);
+        let runs = completed + failed;
+        let average_ms = if runs == 0 {
+            None
+        } else {
+            Some(self.ocr_total_ms.load(Ordering::Relaxed) as f64 / runs as f64)
+        };
+        serde_json::json!({
+            "frames": {
+                "captured": self.frames_captured.load(Ordering::Relaxed),
+                "skipped_duplicate": self.frames_skipped_duplicate.load(Ordering::Relaxed),
+                "skipped_ocr_busy": self.frames_skipped_ocr_busy.load(Ordering::Relaxed),
+                "skipped_excluded": self.frames_skipped_excluded.load(Ordering::Relaxed),
+            },
+            "ocr": {
+                "completed": completed,
+                "failed": failed,
+                "average_ms": average_ms,
+                "last_ms": (runs > 0).then(|| self.ocr_last_ms.load(Ordering::Relaxed)),
+            },
+        })
+    }
+}
+
 /// Shared state for the capture subsystem, including pause/stop flags and OCR backpressure.
 pub struct CaptureState {
     pub paused: AtomicBool,
@@ -176,6 +310,13 @@ pub struct CaptureState {
     pub wgc_state: Mutex<Option<WgcCaptureSession>>,
     /// Game mode: capture paused because a non-browser fullscreen app is in the foreground
     pub game_mode_capture_paused: AtomicBool,
+    /// Capture paused because a screen share or presentation indicator is visible
+    pub screen_share_paused: AtomicBool,
+    /// Capture paused because the weekly capture schedule is outside its hours
+    pub schedule_paused: AtomicBool,
+    /// Wakes the capture loop for a manual capture (see `request_manual_capture`)
+    pub manual_capture: tokio::sync::Notify,
+    pub metrics: CaptureMetrics,
 }
 
 pub(crate) struct OcrSlotReservation {
@@ -257,9 +398,21 @@ impl CaptureState {
             ocr_image_cache: Arc::new(Mutex::new(HashMap::new())),
             wgc_state: Mutex::new(None),
             game_mode_capture_paused: AtomicBool::new(false),
+            screen_share_paused: AtomicBool::new(false),
+            schedule_paused: AtomicBool::new(false),
+            manual_capture: tokio::sync::Notify::new(),
+            metrics: CaptureMetrics::default(),
         }
     }
 
+    /// Asks the capture loop to capture the foreground window now, regardless of the
+    /// interval and change threshold. The frame is stored with `source: "manual"`.
+    /// Pauses and exclusions still apply; a request made while capture is paused is
+    /// dropped rather than held until resume.
+    pub fn request_manual_capture(&self) {
+        self.manual_capture.notify_one();
+    }
+
     pub(crate) fn try_reserve_ocr_slot(self: &Arc<Self>) -> Option<OcrSlotReservation> {
         self.in_flight_ocr_count
             .compare_exchange(0, 1, Ordering::SeqCst, Ordering::SeqCst)
@@ -309,15 +462,75 @@ impl CaptureState {
                 {
                     settings.ignore_protected_windows = ignore_protected;
                 }
+                if let Some(processes) = data.get("ocr_skip_processes").and_then(|v| v.as_array()) {
+                    settings.ocr_skip_processes = processes
+                        .iter()
+                        .filter_map(|v| v.as_str())
+                        .filter(|s| !s.trim().is_empty())
+                        .map(|s| s.trim().to_lowercase())
+                        .collect();
+                }
+                if let Some(regions) = data.get("redaction_regions") {
+                    match serde_json::from_value::<Vec<RedactionRegion>>(regions.clone()) {
+                        Ok(regions) => {
+                            settings.redaction_regions = normalize_redaction_regions(regions)
+                        }
+                        Err(e) => tracing::warn!("Ignoring invalid redaction regions: {}", e),
+                    }
+                }
+                if let Some(enabled) = data.get("allowlist_enabled").and_then(|v| v.as_bool()) {
+                    settings.allowlist_enabled = enabled;
+                }
+                if let Some(processes) = data.get("allowed_processes").and_then(|v| v.as_array()) {
+                    settings.allowed_processes = processes
+                        .iter()
+                        .filter_map(|v| v.as_str())
+                        .filter(|s| !s.trim().is_empty())
+                        .map(|s| s.trim().to_lowercase())
+                        .collect();
+                }
                 tracing::info!(
-                    "Loaded exclusion settings: {} processes, {} titles",
+                    "Loaded exclusion settings: {} processes, {} titles, {} OCR-skipped processes, allowlist {} ({} processes)",
                     settings.user_excluded_processes.len(),
-                    settings.user_excluded_titles.len()
+                    settings.user_excluded_titles.len(),
+                    settings.ocr_skip_processes.len(),
+                    if settings.allowlist_enabled { "on" } else { "off" },
+                    settings.allowed_processes.len()
                 );
             }
         }
     }
 
+    /// Current exclusion settings in the `monitor_filters.json` shape, plus the
+    /// built-in lists that are always enforced.
+    pub fn exclusion_settings_snapshot(&self) -> serde_json::Value {
+        let settings = self
+            .exclusion_settings
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let mut processes: Vec<String> = settings.user_excluded_processes.iter().cloned().collect();
+        let mut titles: Vec<String> = settings.user_excluded_titles.iter().cloned().collect();
+        let mut ocr_skip_processes: Vec<String> =
+            settings.ocr_skip_processes.iter().cloned().collect();
+        let mut allowed_processes: Vec<String> =
+            settings.allowed_processes.iter().cloned().collect();
+        processes.sort();
+        titles.sort();
+        ocr_skip_processes.sort();
+        allowed_processes.sort();
+        serde_json::json!({
+            "processes": processes,
+            "titles": titles,
+            "ignore_protected": settings.ignore_protected_windows,
+            "ocr_skip_processes": ocr_skip_processes,
+            "allowlist_enabled": settings.allowlist_enabled,
+            "allowed_processes": allowed_processes,
+            "redaction_regions": settings.redaction_regions,
+            "builtin_processes": settings.exclusion_processes,
+            "builtin_title_keywords": settings.exclusion_keywords,
+        })
+    }
+
     /// Saves the current exclusion settings to the `monitor_filters.json` file, using a safe temporary file renaming approach.
     pub fn save_exclusion_settings(&self, data_dir: &std::path::Path) {
         let settings = self
@@ -328,6 +541,10 @@ impl CaptureState {
             "processes": settings.user_excluded_processes.iter().cloned().collect::<Vec<_>>(),
             "titles": settings.user_excluded_titles.iter().cloned().collect::<Vec<_>>(),
             "ignore_protected": settings.ignore_protected_windows,
+            "ocr_skip_processes": settings.ocr_skip_processes.iter().cloned().collect::<Vec<_>>(),
+            "allowlist_enabled": settings.allowlist_enabled,
+            "allowed_processes": settings.allowed_processes.iter().cloned().collect::<Vec<_>>(),
+            "redaction_regions": settings.redaction_regions,
         });
         let path = data_dir.join("monitor_filters.json");
         if let Ok(content) = serde_json::to_string_pretty(&payload) {
@@ -338,12 +555,14 @@ impl CaptureState {
         }
     }
 
-    /// Updates the exclusion filters in memory with new process names, window titles, or the protected window ignore flag.
+    /// Updates the exclusion filters in memory with new process names, window titles,
+    /// OCR-skipped processes, or the protected window ignore flag.
     pub fn update_exclusion_settings(
         &self,
         processes: Option<Vec<String>>,
         titles: Option<Vec<String>>,
         ignore_protected: Option<bool>,
+        ocr_skip_processes: Option<Vec<String>>,
     ) {
         let mut settings = self
             .exclusion_settings
@@ -366,6 +585,100 @@ impl CaptureState {
         if let Some(ip) = ignore_protected {
             settings.ignore_protected_windows = ip;
         }
+        if let Some(procs) = ocr_skip_processes {
+            settings.ocr_skip_processes = procs
+                .into_iter()
+                .filter(|s| !s.trim().is_empty())
+                .map(|s| s.trim().to_lowercase())
+                .collect();
+        }
+    }
+
+    /// Updates allowlist-only mode and the set of processes it lets through.
+    pub fn update_allowlist_settings(&self, enabled: Option<bool>, processes: Option<Vec<String>>) {
+        let mut settings = self
+            .exclusion_settings
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if let Some(enabled) = enabled {
+            settings.allowlist_enabled = enabled;
+        }
+        if let Some(procs) = processes {
+            settings.allowed_processes = procs
+                .into_iter()
+                .filter(|s| !s.trim().is_empty())
+                .map(|s| s.trim().to_lowercase())
+                .collect();
+        }
+    }
+
+    /// Updates the capture cadence and change-detection threshold, clamped to their
+    /// supported ranges. The running capture loop picks them up on its next tick.
+    pub fn set_capture_tuning(&self, interval_secs: Option<u64>, dhash_threshold: Option<u32>) {
+        let mut config = self.config.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(secs) = interval_secs {
+            config.interval_secs = secs.clamp(MIN_CAPTURE_INTERVAL_SECS, MAX_CAPTURE_INTERVAL_SECS);
+        }
+        if let Some(bits) = dhash_threshold {
+            config.dhash_threshold = bits.min(MAX_CAPTURE_DHASH_THRESHOLD);
+        }
+    }
+
+    /// Replaces the redaction regions. Empty rectangles are dropped and the list is
+    /// capped at `MAX_REDACTION_REGIONS`.
+    pub fn update_redaction_regions(&self, regions: Vec<RedactionRegion>) {
+        self.exclusion_settings
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .redaction_regions = normalize_redaction_regions(regions);
+    }
+
+    /// Validates and applies window-title regex rules. Nothing changes if any
+    /// pattern fails to compile.
+    pub fn set_title_rules(&self, patterns: &[String]) -> Result<(), String> {
+        let rules = compile_title_rules(patterns)?;
+        self.exclusion_settings
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .title_rules = rules;
+        Ok(())
+    }
+
+    /// Reloads the window-title regex rules from registry_config. Invalid stored
+    /// patterns are logged and skipped rather than disabling every rule.
+    pub fn load_title_rules(&self) {
+        let rules: Vec<Regex> = title_exclusion_rules()
+            .iter()
+            .filter_map(|pattern| match compile_title_rule(pattern) {
+                Ok(rule) => Some(rule),
+                Err(e) => {
+                    tracing::warn!("Skipping stored title rule: {}", e);
+                    None
+                }
+            })
+            .collect();
+        self.exclusion_settings
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .title_rules = rules;
+    }
+
+    /// Capture filter summary reported by `get_monitor_status`.
+    pub fn capture_filter_status(&self) -> serde_json::Value {
+        let settings = self
+            .exclusion_settings
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let mut allowed_processes: Vec<String> =
+            settings.allowed_processes.iter().cloned().collect();
+        allowed_processes.sort();
+        let blocked_processes =
+            settings.user_excluded_processes.len() + settings.exclusion_processes.len();
+        serde_json::json!({
+            "mode": if settings.allowlist_enabled { "allowlist" } else { "blocklist" },
+            "allowed_processes": allowed_processes,
+            "blocked_processes": blocked_processes,
+        })
     }
 }
 
@@ -379,6 +692,16 @@ pub struct ActiveWindowInfo {
     pid: u32,
 }
 
+impl ActiveWindowInfo {
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+}
+
 /// Retrieves information about the currently focused foreground window,
 /// including its handle, title, screen bounds, and the owning process ID.
 pub fn get_active_window_info() -> Option<ActiveWindowInfo> {
@@ -596,6 +919,100 @@ fn get_process_command_line(pid: u32) -> Option<String> {
 
 // ==================== Window Exclusion ====================
 
+/// Registry flag controlling whether CarbonPaper's own windows are hidden from
+/// screen capture. Defaults to on; turn off to capture the app while debugging.
+const EXCLUDE_OWN_WINDOWS_KEY: &str = "capture_exclude_own_windows";
+
+/// Registry value holding the window-title regex rules as a JSON array of patterns.
+const TITLE_EXCLUSION_RULES_KEY: &str = "capture_title_exclusion_rules";
+
+/// Compiled size cap per title rule, matching the search regex limit.
+const TITLE_RULE_SIZE_LIMIT: usize = 1 << 20;
+
+/// Title patterns accepted per rule set.
+const MAX_TITLE_RULES: usize = 64;
+
+fn compile_title_rule(pattern: &str) -> Result<Regex, String> {
+    RegexBuilder::new(pattern)
+        .size_limit(TITLE_RULE_SIZE_LIMIT)
+        .build()
+        .map_err(|e| format!("Invalid title rule '{}': {}", pattern, e))
+}
+
+fn compile_title_rules(patterns: &[String]) -> Result<Vec<Regex>, String> {
+    if patterns.len() > MAX_TITLE_RULES {
+        return Err(format!(
+            "At most {} title rules are supported",
+            MAX_TITLE_RULES
+        ));
+    }
+    patterns.iter().map(|p| compile_title_rule(p)).collect()
+}
+
+/// Window-title regex rules stored in registry_config, in the order they were saved.
+pub fn title_exclusion_rules() -> Vec<String> {
+    crate::registry_config::get_string(TITLE_EXCLUSION_RULES_KEY)
+        .and_then(|raw| serde_json::from_str::<Vec<String>>(&raw).ok())
+        .unwrap_or_default()
+}
+
+/// Persists window-title regex rules after checking that every pattern compiles.
+/// Blank patterns are dropped.
+pub fn set_title_exclusion_rules(patterns: &[String]) -> Result<Vec<String>, String> {
+    let patterns: Vec<String> = patterns
+        .iter()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect();
+    compile_title_rules(&patterns)?;
+    if patterns.is_empty() {
+        crate::registry_config::delete_value(TITLE_EXCLUSION_RULES_KEY)?;
+    } else {
+        let raw = serde_json::to_string(&patterns)
+            .map_err(|e| format!("Failed to serialize title rules: {}", e))?;
+        crate::registry_config::set_string(TITLE_EXCLUSION_RULES_KEY, &raw)?;
+    }
+    Ok(patterns)
+}
+
+pub fn exclude_own_windows_enabled() -> bool {
+    crate::registry_config::get_bool(EXCLUDE_OWN_WINDOWS_KEY).unwrap_or(true)
+}
+
+pub fn set_exclude_own_windows_enabled(enabled: bool) -> Result<(), String> {
+    crate::registry_config::set_bool(EXCLUDE_OWN_WINDOWS_KEY, enabled)
+}
+
+/// Sets or clears `WDA_EXCLUDEFROMCAPTURE` on one of the app's windows so the
+/// timeline is not polluted with recursive screenshots of CarbonPaper itself.
+pub fn apply_capture_exclusion<R: tauri::Runtime>(
+    window: &tauri::WebviewWindow<R>,
+    excluded: bool,
+) -> Result<(), String> {
+    let hwnd = window
+        .hwnd()
+        .map_err(|e| format!("Failed to get window handle: {}", e))?;
+    let affinity = if excluded {
+        WDA_EXCLUDEFROMCAPTURE
+    } else {
+        WDA_NONE
+    };
+    // SAFETY: the HWND belongs to a live Tauri window owned by this process.
+    unsafe { SetWindowDisplayAffinity(HWND(hwnd.0 as _), affinity) }
+        .map_err(|e| format!("Failed to set window display affinity: {:?}", e))
+}
+
+/// Applies the current own-window exclusion setting to every open webview window.
+/// Call after creating a window and whenever the setting changes.
+pub fn apply_capture_exclusion_to_all<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    let excluded = exclude_own_windows_enabled();
+    for (label, window) in app.webview_windows() {
+        if let Err(e) = apply_capture_exclusion(&window, excluded) {
+            tracing::warn!("[CAPTURE] capture exclusion for window {}: {}", label, e);
+        }
+    }
+}
+
 fn is_window_protected(hwnd_raw: isize) -> bool {
     // SAFETY: `hwnd_raw` was obtained from Windows foreground-window enumeration; the
     // affinity output points to valid stack storage and is not retained.
@@ -606,12 +1023,21 @@ fn is_window_protected(hwnd_raw: isize) -> bool {
     }
 }
 
-fn is_excluded(info: &ActiveWindowInfo, settings: &ExclusionSettings) -> bool {
+pub(crate) fn is_excluded(info: &ActiveWindowInfo, settings: &ExclusionSettings) -> bool {
     // Empty title
     if info.title.is_empty() {
         return true;
     }
 
+    let process_name = get_process_path_from_pid(info.pid)
+        .map(|path| get_process_name_from_path(&path))
+        .unwrap_or_default();
+
+    // Allowlist-only mode
+    if !is_process_allowed(&process_name, settings) {
+        return true;
+    }
+
     let title = &info.title;
     let title_lower = title.to_lowercase();
 
@@ -636,19 +1062,19 @@ fn is_excluded(info: &ActiveWindowInfo, settings: &ExclusionSettings) -> bool {
         }
     }
 
+    // User-defined title regex rules
+    if matches_title_rule(title, settings) {
+        return true;
+    }
+
     // Protected window check
     if settings.ignore_protected_windows && is_window_protected(info.hwnd_raw) {
         return true;
     }
 
-    // User-defined process name exclusion
-    if !settings.user_excluded_processes.is_empty() {
-        if let Some(path) = get_process_path_from_pid(info.pid) {
-            let pname = get_process_name_from_path(&path);
-            if !pname.is_empty() && settings.user_excluded_processes.contains(&pname) {
-                return true;
-            }
-        }
+    // Built-in and user-defined process name exclusion
+    if is_process_excluded(&process_name, settings) {
+        return true;
     }
 
     // Browser incognito command line check
@@ -665,6 +1091,162 @@ fn is_excluded(info: &ActiveWindowInfo, settings: &ExclusionSettings) -> bool {
     false
 }
 
+/// Whether `process_name` is on the built-in or the user process blocklist.
+fn is_process_excluded(process_name: &str, settings: &ExclusionSettings) -> bool {
+    if process_name.is_empty() {
+        return false;
+    }
+    let process_name = process_name.to_lowercase();
+    settings
+        .exclusion_processes
+        .iter()
+        .any(|p| *p == process_name)
+        || settings.user_excluded_processes.contains(&process_name)
+}
+
+fn normalize_redaction_regions(regions: Vec<RedactionRegion>) -> Vec<RedactionRegion> {
+    regions
+        .into_iter()
+        .filter(|r| r.width > 0 && r.height > 0)
+        .map(|mut r| {
+            r.process = r
+                .process
+                .map(|p| p.trim().to_lowercase())
+                .filter(|p| !p.is_empty());
+            r
+        })
+        .take(MAX_REDACTION_REGIONS)
+        .collect()
+}
+
+/// Masks the regions that apply to `process_name` in place. `window` is the captured
+/// window's screen rectangle (left, top, width, height); the frame may have been
+/// downscaled from it. Returns whether any pixel was masked.
+fn apply_redaction_regions(
+    image: &mut RgbImage,
+    regions: &[RedactionRegion],
+    process_name: &str,
+    window: (i32, i32, i32, i32),
+) -> bool {
+    let (left, top, win_w, win_h) = window;
+    let (img_w, img_h) = image.dimensions();
+    let scale_x = if win_w > 0 {
+        img_w as f64 / win_w as f64
+    } else {
+        1.0
+    };
+    let scale_y = if win_h > 0 {
+        img_h as f64 / win_h as f64
+    } else {
+        1.0
+    };
+
+    let mut masked = false;
+    for region in regions {
+        if let Some(process) = &region.process {
+            if !process.eq_ignore_ascii_case(process_name) {
+                continue;
+            }
+        }
+        let (rx, ry) = match region.scope {
+            RegionScope::Screen => (region.x - left, region.y - top),
+            RegionScope::Window => (region.x, region.y),
+        };
+        let x0 = ((rx as f64 * scale_x).floor().max(0.0) as u32).min(img_w);
+        let y0 = ((ry as f64 * scale_y).floor().max(0.0) as u32).min(img_h);
+        let x1 = (((rx as f64 + region.width as f64) * scale_x)
+            .ceil()
+            .max(0.0) as u32)
+            .min(img_w);
+        let y1 = (((ry as f64 + region.height as f64) * scale_y)
+            .ceil()
+            .max(0.0) as u32)
+            .min(img_h);
+        if x1 <= x0 || y1 <= y0 {
+            continue;
+        }
+
+        match region.mode {
+            RedactionMode::Blackout => {
+                for y in y0..y1 {
+                    for x in x0..x1 {
+                        image.put_pixel(x, y, image::Rgb([0, 0, 0]));
+                    }
+                }
+            }
+            RedactionMode::Blur => {
+                let (w, h) = (x1 - x0, y1 - y0);
+                let patch = image::imageops::crop_imm(image, x0, y0, w, h).to_image();
+                let sigma = (w.min(h) as f32 / 4.0).clamp(4.0, 32.0);
+                let blurred = image::imageops::fast_blur(&patch, sigma);
+                image::imageops::replace(image, &blurred, x0 as i64, y0 as i64);
+            }
+        }
+        masked = true;
+    }
+    masked
+}
+
+/// Returns `captured` with redaction regions applied and the JPEG re-encoded, or the
+/// original frame when no region covers it. `None` means the masked frame could not
+/// be encoded and must be dropped rather than stored unmasked.
+fn redact_captured(
+    captured: CapturedImage,
+    regions: &[RedactionRegion],
+    process_name: &str,
+    rect: &RECT,
+    jpeg_quality: u8,
+) -> Option<CapturedImage> {
+    if regions.is_empty() {
+        return Some(captured);
+    }
+    let mut image = (*captured.rgb_image).clone();
+    let window = (
+        rect.left,
+        rect.top,
+        rect.right - rect.left,
+        rect.bottom - rect.top,
+    );
+    if !apply_redaction_regions(&mut image, regions, process_name, window) {
+        return Some(captured);
+    }
+    match encode_rgb_jpeg(&image, jpeg_quality) {
+        Ok(jpeg) => Some(CapturedImage {
+            jpeg_bytes: Arc::from(jpeg),
+            width: image.width(),
+            height: image.height(),
+            rgb_image: Arc::new(image),
+        }),
+        Err(e) => {
+            tracing::warn!("Dropping frame after redaction: {}", e);
+            None
+        }
+    }
+}
+
+/// Whether `title` matches any window-title regex rule.
+fn matches_title_rule(title: &str, settings: &ExclusionSettings) -> bool {
+    settings.title_rules.iter().any(|rule| rule.is_match(title))
+}
+
+/// Whether allowlist-only mode lets `process_name` through. An enabled allowlist
+/// with no entries captures nothing.
+fn is_process_allowed(process_name: &str, settings: &ExclusionSettings) -> bool {
+    !settings.allowlist_enabled
+        || (!process_name.is_empty()
+            && settings
+                .allowed_processes
+                .contains(&process_name.to_lowercase()))
+}
+
+/// Whether frames from `process_name` should be stored without running OCR.
+fn should_skip_ocr(process_name: &str, settings: &ExclusionSettings) -> bool {
+    !process_name.is_empty()
+        && settings
+            .ocr_skip_processes
+            .contains(&process_name.to_lowercase())
+}
+
 // ==================== dHash ====================
 
 type DHash = [u64; 4];
@@ -1318,33 +1900,37 @@ pub async fn run_capture_loop(
     let mut history_hashes: Vec<DHash> = Vec::new();
     let mut icon_cache: HashMap<String, Option<String>> = HashMap::new();
 
-    // Load config
-    let (
-        interval_secs,
-        polling_rate_ms,
-        max_side,
-        jpeg_quality,
-        dhash_threshold,
-        dhash_history_size,
-    ) = {
+    // Load config. The interval and change threshold are re-read every tick so
+    // `set_advanced_config` applies without restarting capture.
+    let (polling_rate_ms, max_side, jpeg_quality, dhash_history_size) = {
         let config = capture_state
             .config
             .lock()
             .unwrap_or_else(|e| e.into_inner());
         (
-            config.interval_secs,
             config.polling_rate_ms,
             config.max_side,
             config.jpeg_quality,
-            config.dhash_threshold,
             config.dhash_history_size,
         )
     };
 
     let polling_duration = tokio::time::Duration::from_millis(polling_rate_ms);
+    let mut manual_pending = false;
 
     loop {
-        tokio::time::sleep(polling_duration).await;
+        tokio::select! {
+            _ = tokio::time::sleep(polling_duration) => {}
+            _ = capture_state.manual_capture.notified() => manual_pending = true,
+        }
+
+        let (interval_secs, dhash_threshold) = {
+            let config = capture_state
+                .config
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            (config.interval_secs, config.dhash_threshold)
+        };
 
         // Check stop
         if capture_state.stopped.load(Ordering::SeqCst) {
@@ -1352,16 +1938,17 @@ pub async fn run_capture_loop(
             break;
         }
 
-        // Check pause
-        if capture_state.paused.load(Ordering::SeqCst) {
-            continue;
-        }
-
-        // Check game mode fullscreen pause
-        if capture_state
-            .game_mode_capture_paused
-            .load(Ordering::SeqCst)
+        // Check user, game mode, screen share and schedule pauses
+        if capture_state.paused.load(Ordering::SeqCst)
+            || capture_state
+                .game_mode_capture_paused
+                .load(Ordering::SeqCst)
+            || capture_state.screen_share_paused.load(Ordering::SeqCst)
+            || capture_state.schedule_paused.load(Ordering::SeqCst)
         {
+            if std::mem::take(&mut manual_pending) {
+                tracing::info!("Manual capture ignored: capture is paused");
+            }
             continue;
         }
 
@@ -1380,6 +1967,13 @@ pub async fn run_capture_loop(
                 .lock()
                 .unwrap_or_else(|e| e.into_inner());
             if is_excluded(&window_info, &settings) {
+                capture_state
+                    .metrics
+                    .frames_skipped_excluded
+                    .fetch_add(1, Ordering::Relaxed);
+                if std::mem::take(&mut manual_pending) {
+                    tracing::info!("Manual capture ignored: foreground window is excluded");
+                }
                 last_hwnd_raw = current_hwnd_raw;
                 continue;
             }
@@ -1388,14 +1982,23 @@ pub async fn run_capture_loop(
         // Raw RGB frames are never queued. Keep capture and OCR strictly single-flight.
         let in_flight = capture_state.in_flight_ocr_count.load(Ordering::SeqCst);
         if in_flight > 0 {
+            capture_state
+                .metrics
+                .frames_skipped_ocr_busy
+                .fetch_add(1, Ordering::Relaxed);
             continue;
         }
 
         let mut should_capture = false;
         let mut scan_reason = "";
 
+        // Manual trigger (global hotkey); waits here while OCR is busy
+        if manual_pending {
+            should_capture = true;
+            scan_reason = "manual";
+        }
         // Focus change detection
-        if current_hwnd_raw != last_hwnd_raw {
+        else if current_hwnd_raw != last_hwnd_raw {
             should_capture = true;
             scan_reason = "focus_change";
         }
@@ -1411,6 +2014,8 @@ pub async fn run_capture_loop(
         }
 
         force_first_capture = false;
+        manual_pending = false;
+        let manual = scan_reason == "manual";
 
         // Focus change: wait for window to stabilize
         if scan_reason == "focus_change" {
@@ -1429,6 +2034,11 @@ pub async fn run_capture_loop(
             {
                 continue;
             }
+            if capture_state.screen_share_paused.load(Ordering::SeqCst)
+                || capture_state.schedule_paused.load(Ordering::SeqCst)
+            {
+                continue;
+            }
         }
 
         // Capture screenshot
@@ -1446,35 +2056,67 @@ pub async fn run_capture_loop(
             }
         };
 
-        // dHash dedup
+        // Get process metadata
+        let process_path = get_process_path_from_pid(window_info.pid).unwrap_or_default();
+        let process_name = if !process_path.is_empty() {
+            get_process_name_from_path(&process_path)
+        } else {
+            String::new()
+        };
+
+        // Mask redaction regions before the frame is hashed, stored or OCR'd
+        let redaction_regions = capture_state
+            .exclusion_settings
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .redaction_regions
+            .clone();
+        let captured = match redact_captured(
+            captured,
+            &redaction_regions,
+            &process_name,
+            &window_info.rect,
+            jpeg_quality,
+        ) {
+            Some(c) => c,
+            None => {
+                last_hwnd_raw = current_hwnd_raw;
+                continue;
+            }
+        };
+
+        // dHash dedup. A manual capture is kept even when nothing changed.
         let current_hash = compute_dhash(&captured.rgb_image, 16);
-        if is_redundant(&current_hash, &history_hashes, dhash_threshold) {
+        if !manual && is_redundant(&current_hash, &history_hashes, dhash_threshold) {
+            capture_state
+                .metrics
+                .frames_skipped_duplicate
+                .fetch_add(1, Ordering::Relaxed);
             last_capture_time = std::time::Instant::now();
             last_hwnd_raw = current_hwnd_raw;
             continue;
         }
 
+        capture_state
+            .metrics
+            .frames_captured
+            .fetch_add(1, Ordering::Relaxed);
+
         // Update history
         history_hashes.push(current_hash);
         if history_hashes.len() > dhash_history_size {
             history_hashes.remove(0);
         }
 
-        // Get process metadata
-        let process_path = get_process_path_from_pid(window_info.pid).unwrap_or_default();
-        let process_name = if !process_path.is_empty() {
-            get_process_name_from_path(&process_path)
-        } else {
-            String::new()
-        };
-
         // Route to a registered browser-extension session (matched by the
         // foreground window's PID) when extension enhancement is enabled.
         // The extension captures with richer metadata (URL, title, favicon,
         // links). If no session matches or the request fails, fall through
-        // to the normal capture path — never skip a frame on a guess.
-        if let Some(session) =
-            crate::reverse_ipc::find_nmh_session_for_pid(window_info.pid, &process_path)
+        // to the normal capture path — never skip a frame on a guess. Manual
+        // captures stay on the normal path so they keep their `manual` source.
+        if let Some(session) = (!manual)
+            .then(|| crate::reverse_ipc::find_nmh_session_for_pid(window_info.pid, &process_path))
+            .flatten()
         {
             tracing::debug!(
                 "Requesting extension capture from {} (pid {})",
@@ -1552,14 +2194,9 @@ pub async fn run_capture_loop(
             "timestamp": ts_str,
         });
 
-        let Some(ocr_slot) = capture_state.try_reserve_ocr_slot() else {
-            tracing::debug!(
-                "OCR slot was claimed while capture was being prepared; dropping frame"
-            );
-            last_capture_time = std::time::Instant::now();
-            last_hwnd_raw = current_hwnd_raw;
-            continue;
-        };
+        // Without the extension, the address bar is the only source of the URL.
+        let page_url =
+            crate::browser_url::foreground_browser_url(current_hwnd_raw, &process_name).await;
 
         // Save screenshot temp (directly, no IPC needed)
         let save_request = SaveScreenshotRequest {
@@ -1571,12 +2208,46 @@ pub async fn run_capture_loop(
             process_name: Some(process_name.clone()),
             metadata: Some(metadata.clone()),
             ocr_results: None,
-            source: Some("capture".to_string()),
-            page_url: None,
+            source: Some(if manual { "manual" } else { "capture" }.to_string()),
+            page_url,
             page_icon: None,
             visible_links: None,
         };
 
+        let skip_ocr = {
+            let settings = capture_state
+                .exclusion_settings
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            should_skip_ocr(&process_name, &settings)
+        };
+        // A manual capture is an explicit request for OCR, so the skip list does not apply.
+        if skip_ocr && !manual {
+            store_frame_without_ocr(&storage, &save_request, &captured.jpeg_bytes);
+            last_capture_time = std::time::Instant::now();
+            last_hwnd_raw = current_hwnd_raw;
+            continue;
+        }
+
+        // Native apps that expose their text through UI Automation skip the OCR model.
+        let uia_text = crate::uia_text::extract_window_text(
+            current_hwnd_raw,
+            &window_info.rect,
+            captured.width,
+            captured.height,
+            &process_name,
+        )
+        .await;
+
+        let Some(ocr_slot) = capture_state.try_reserve_ocr_slot() else {
+            tracing::debug!(
+                "OCR slot was claimed while capture was being prepared; dropping frame"
+            );
+            last_capture_time = std::time::Instant::now();
+            last_hwnd_raw = current_hwnd_raw;
+            continue;
+        };
+
         let screenshot_id =
             match storage.save_screenshot_temp_bytes(&save_request, &captured.jpeg_bytes) {
                 Ok(resp) => {
@@ -1632,6 +2303,7 @@ pub async fn run_capture_loop(
                 window_title_clone,
                 process_name_clone,
                 timestamp_ms,
+                uia_text,
             )
             .await;
         });
@@ -1644,6 +2316,44 @@ pub async fn run_capture_loop(
     tracing::info!("Rust capture loop ended");
 }
 
+/// Persist a frame from an OCR-skipped process: commit it without OCR rows and mark
+/// its OCR lifecycle `skipped`, so it is neither retried nor sent for indexing.
+fn store_frame_without_ocr(
+    storage: &StorageState,
+    request: &SaveScreenshotRequest,
+    jpeg_bytes: &[u8],
+) {
+    let screenshot_id = match storage.save_screenshot_temp_bytes(request, jpeg_bytes) {
+        Ok(resp) if resp.status == "duplicate" => {
+            tracing::debug!("Duplicate screenshot, skipping");
+            return;
+        }
+        Ok(resp) => match resp.screenshot_id {
+            Some(id) => id,
+            None => {
+                tracing::error!("save_screenshot_temp returned no ID");
+                return;
+            }
+        },
+        Err(e) => {
+            tracing::error!("save_screenshot_temp failed: {}", e);
+            return;
+        }
+    };
+
+    if let Err(e) = storage.commit_screenshot(screenshot_id, None, None, None) {
+        tracing::error!(
+            "Failed to commit OCR-skipped screenshot {}: {}",
+            screenshot_id,
+            e
+        );
+        return;
+    }
+    if let Err(e) = storage.set_ocr_status(screenshot_id, "skipped", None, None, None, None, None) {
+        tracing::warn!("Failed to mark OCR skipped for {}: {}", screenshot_id, e);
+    }
+}
+
 async fn process_ocr_async(
     app: &tauri::AppHandle,
     storage: Arc<StorageState>,
@@ -1655,23 +2365,25 @@ async fn process_ocr_async(
     window_title: String,
     process_name: String,
     timestamp_ms: i64,
+    uia_text: Option<Vec<OcrResultInput>>,
 ) {
     const OCR_ASYNC_WARN_MS: u128 = 60_000;
     let in_flight_after_inc = capture_state.in_flight_ocr_count.load(Ordering::SeqCst);
 
     let task_started = std::time::Instant::now();
     let route = OcrRouteConfig::from_registry();
-    let initial_engine = "rust";
-    let initial_provider = if route.use_directml_beta {
-        "directml_beta"
+    let (initial_engine, initial_model, initial_provider) = if uia_text.is_some() {
+        (UIA_OCR_ENGINE, UIA_OCR_MODEL, UIA_OCR_PROVIDER)
+    } else if route.use_directml_beta {
+        ("rust", "ppocrv5-ch-mobile", "directml_beta")
     } else {
-        "cpu"
+        ("rust", "ppocrv5-ch-mobile", "cpu")
     };
     if let Err(error) = storage.set_ocr_status(
         screenshot_id,
         "running",
         Some(initial_engine),
-        Some("ppocrv5-ch-mobile"),
+        Some(initial_model),
         Some(initial_provider),
         None,
         None,
@@ -1708,19 +2420,41 @@ async fn process_ocr_async(
         capture_state.ocr_timeout_secs.load(Ordering::SeqCst).max(1)
     };
 
-    let result = process_ocr_inner(
-        app,
-        &storage,
-        screenshot_id,
-        rgb_image,
-        &image_hash,
-        &window_title,
-        &process_name,
-        timestamp_ms,
-        timeout_secs,
-        route,
-    )
-    .await;
+    let result = match uia_text {
+        Some(ocr_results) => {
+            commit_ocr_results(
+                app,
+                &storage,
+                screenshot_id,
+                ocr_results,
+                (UIA_OCR_ENGINE, UIA_OCR_MODEL, UIA_OCR_PROVIDER),
+                task_started.elapsed().as_secs_f64() * 1000.0,
+                &image_hash,
+                &window_title,
+                &process_name,
+                timestamp_ms,
+            )
+            .await
+        }
+        None => {
+            process_ocr_inner(
+                app,
+                &storage,
+                screenshot_id,
+                rgb_image,
+                &image_hash,
+                &window_title,
+                &process_name,
+                timestamp_ms,
+                timeout_secs,
+                route,
+            )
+            .await
+        }
+    };
+    capture_state
+        .metrics
+        .record_ocr(task_started.elapsed().as_millis() as u64, result.is_ok());
 
     if let Err(e) = result {
         crate::ml_runtime::schedule_ocr_model_health_notification(app.clone());
@@ -1742,7 +2476,7 @@ async fn process_ocr_async(
             screenshot_id,
             "failed",
             Some(initial_engine),
-            Some("ppocrv5-ch-mobile"),
+            Some(initial_model),
             Some(initial_provider),
             Some(&e),
             Some(task_started.elapsed().as_secs_f64() * 1000.0),
@@ -1794,7 +2528,7 @@ pub(crate) async fn process_ocr_inner(
     timeout_secs: u32,
     route: OcrRouteConfig,
 ) -> Result<(), String> {
-    let use_directml_beta = route.use_directml_beta;
+    let mut use_directml_beta = route.use_directml_beta;
     tracing::info!(
         "[ML:ROUTER] Raw RGB Rust OCR selected screenshot_id={} dml_beta={} dimensions={}x{} bytes={} timeout_secs={}",
         screenshot_id,
@@ -1808,14 +2542,35 @@ pub(crate) async fn process_ocr_inner(
         .state::<Arc<crate::ml_runtime::MlRuntimeState>>()
         .inner()
         .clone();
-    let output = ml_state
-        .run_ocr(
-            app.clone(),
-            rgb_image,
-            std::time::Duration::from_secs(timeout_secs as u64),
-            use_directml_beta,
-        )
-        .await?;
+    if use_directml_beta {
+        let deferred = crate::gpu_priority::wait_for_gpu_idle().await;
+        if deferred >= std::time::Duration::from_secs(1) {
+            tracing::info!(
+                "[ML:ROUTER] DirectML OCR deferred {:?} for a busy foreground app screenshot_id={}",
+                deferred,
+                screenshot_id
+            );
+        }
+    }
+    let timeout = std::time::Duration::from_secs(timeout_secs as u64);
+    let output = match ml_state
+        .run_ocr(app.clone(), rgb_image.clone(), timeout, use_directml_beta)
+        .await
+    {
+        Ok(output) => output,
+        Err(error) if use_directml_beta => {
+            // run_ocr already moved the worker to CPU for this session; keep this frame
+            // instead of dropping its OCR and persist the switch.
+            if let Err(e) = crate::dml_fallback::fall_back_rust_dml(app, &error) {
+                tracing::warn!("Failed to persist DirectML fallback: {}", e);
+            }
+            use_directml_beta = false;
+            ml_state
+                .run_ocr(app.clone(), rgb_image, timeout, false)
+                .await?
+        }
+        Err(error) => return Err(error),
+    };
     let ocr_results = convert_ml_ocr_blocks(output.blocks)?;
     tracing::info!(
         "[ML:ROUTER] Rust OCR commit screenshot_id={} blocks={} prepare_ms={:.1} model_ms={:.1} worker_total_ms={:.1}",
@@ -1825,35 +2580,81 @@ pub(crate) async fn process_ocr_inner(
         output.timings.model_total_ms,
         output.timings.request_total_ms
     );
+    commit_ocr_results(
+        app,
+        storage,
+        screenshot_id,
+        ocr_results,
+        (
+            "rust",
+            "ppocrv5-ch-mobile",
+            if use_directml_beta {
+                "directml_beta"
+            } else {
+                "cpu"
+            },
+        ),
+        output.timings.request_total_ms,
+        image_hash,
+        window_title,
+        process_name,
+        timestamp_ms,
+    )
+    .await
+}
+
+/// OCR status labels for text read through UI Automation instead of the OCR model.
+const UIA_OCR_ENGINE: &str = "uia";
+const UIA_OCR_MODEL: &str = "ui-automation";
+const UIA_OCR_PROVIDER: &str = "accessibility";
+
+/// Commits recognized text for a temporary screenshot, marks OCR completed with the
+/// `(engine, model, provider)` that produced it and queues the Python postprocess.
+#[allow(clippy::too_many_arguments)]
+async fn commit_ocr_results(
+    app: &tauri::AppHandle,
+    storage: &StorageState,
+    screenshot_id: i64,
+    ocr_results: Vec<OcrResultInput>,
+    (engine, model_id, provider): (&str, &str, &str),
+    elapsed_ms: f64,
+    image_hash: &str,
+    window_title: &str,
+    process_name: &str,
+    timestamp_ms: i64,
+) -> Result<(), String> {
     storage.commit_screenshot(screenshot_id, Some(&ocr_results), None, None)?;
+    crate::saved_search_alerts::notify_saved_search_matches(
+        app,
+        storage,
+        screenshot_id,
+        &ocr_results,
+    );
     if let Err(error) = storage.set_ocr_status(
         screenshot_id,
         "completed",
-        Some("rust"),
-        Some("ppocrv5-ch-mobile"),
-        Some(if use_directml_beta {
-            "directml_beta"
-        } else {
-            "cpu"
-        }),
+        Some(engine),
+        Some(model_id),
+        Some(provider),
         None,
-        Some(output.timings.request_total_ms),
+        Some(elapsed_ms),
     ) {
         tracing::warn!(
-            "Rust OCR data was committed but completion status update failed screenshot_id={}: {}",
+            "OCR data was committed but completion status update failed screenshot_id={}: {}",
             screenshot_id,
             error
         );
     }
     if let Err(error) = storage.set_ocr_postprocess_status(screenshot_id, "pending", None) {
         tracing::warn!(
-            "Rust OCR data was committed but postprocess status initialization failed screenshot_id={}: {}",
+            "OCR data was committed but postprocess status initialization failed screenshot_id={}: {}",
             screenshot_id,
             error
         );
     }
     match enqueue_python_ocr_postprocess(
         app,
+        OCR_PRIORITY_LIVE,
         screenshot_id,
         image_hash,
         window_title,
@@ -1880,7 +2681,7 @@ pub(crate) async fn process_ocr_inner(
         Err(error) => {
             let _ = storage.record_ocr_postprocess_retry(screenshot_id, &error);
             tracing::warn!(
-                "[ML:POSTPROCESS] Rust OCR saved but postprocess enqueue failed screenshot_id={}: {}",
+                "[ML:POSTPROCESS] OCR saved but postprocess enqueue failed screenshot_id={}: {}",
                 screenshot_id,
                 error
             );
@@ -1890,7 +2691,7 @@ pub(crate) async fn process_ocr_inner(
     Ok(())
 }
 
-fn convert_ml_ocr_blocks(
+pub(crate) fn convert_ml_ocr_blocks(
     blocks: Vec<crate::ml_protocol::MlOcrBlock>,
 ) -> Result<Vec<OcrResultInput>, String> {
     const MAX_BLOCKS: usize = 10_000;
@@ -1930,8 +2731,35 @@ fn convert_ml_ocr_blocks(
     Ok(results)
 }
 
+/// Registry keys for the Python OCR postprocess queue, passed to the monitor at start.
+pub const OCR_QUEUE_MAX_SIZE_REGISTRY_KEY: &str = "ocr_queue_max_size";
+pub const OCR_QUEUE_DROP_POLICY_REGISTRY_KEY: &str = "ocr_queue_drop_policy";
+pub const DEFAULT_OCR_QUEUE_MAX_SIZE: u32 = 64;
+pub const MAX_OCR_QUEUE_MAX_SIZE: u32 = 1024;
+/// What a full queue does with new work: `reject_new` keeps queued jobs,
+/// `drop_oldest` evicts the oldest job of the lowest queued priority.
+pub const OCR_QUEUE_DROP_POLICIES: &[&str] = &["reject_new", "drop_oldest"];
+
+/// OCR postprocess job priorities, served in this order by the Python queue.
+pub(crate) const OCR_PRIORITY_INTERACTIVE: &str = "interactive";
+pub(crate) const OCR_PRIORITY_LIVE: &str = "live";
+pub(crate) const OCR_PRIORITY_BACKFILL: &str = "backfill";
+
+/// Persisted OCR queue limit and drop policy, with defaults for missing or invalid values.
+pub fn ocr_queue_settings() -> (u32, String) {
+    let max_size = crate::registry_config::get_u32(OCR_QUEUE_MAX_SIZE_REGISTRY_KEY)
+        .unwrap_or(DEFAULT_OCR_QUEUE_MAX_SIZE)
+        .clamp(1, MAX_OCR_QUEUE_MAX_SIZE);
+    let drop_policy = crate::registry_config::get_string(OCR_QUEUE_DROP_POLICY_REGISTRY_KEY)
+        .filter(|policy| OCR_QUEUE_DROP_POLICIES.contains(&policy.as_str()))
+        .unwrap_or_else(|| OCR_QUEUE_DROP_POLICIES[0].to_string());
+    (max_size, drop_policy)
+}
+
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn enqueue_python_ocr_postprocess(
     app: &tauri::AppHandle,
+    priority: &str,
     screenshot_id: i64,
     image_hash: &str,
     window_title: &str,
@@ -1953,6 +2781,7 @@ pub(crate) async fn enqueue_python_ocr_postprocess(
         "process_name": process_name,
         "timestamp": timestamp_ms,
         "ocr_text": ocr_text,
+        "priority": priority,
         "rust_provider_active": true,
     });
     let response = crate::monitor::forward_command_to_python(&monitor_state, payload).await?;
@@ -2103,6 +2932,205 @@ mod tests {
         assert!(converted.is_empty());
     }
 
+    #[test]
+    fn ocr_skip_matches_configured_processes_case_insensitively() {
+        let state = CaptureState::new();
+        state.update_exclusion_settings(
+            None,
+            None,
+            None,
+            Some(vec![" VLC.exe ".to_string(), "".to_string()]),
+        );
+        let settings = state.exclusion_settings.lock().unwrap();
+
+        assert_eq!(settings.ocr_skip_processes.len(), 1);
+        assert!(should_skip_ocr("vlc.exe", &settings));
+        assert!(should_skip_ocr("VLC.EXE", &settings));
+        assert!(!should_skip_ocr("code.exe", &settings));
+        assert!(!should_skip_ocr("", &settings));
+    }
+
+    #[test]
+    fn ocr_skip_processes_round_trip_through_filter_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let state = CaptureState::new();
+        state.update_exclusion_settings(None, None, None, Some(vec!["mpv.exe".to_string()]));
+        state.save_exclusion_settings(temp.path());
+
+        let reloaded = CaptureState::new();
+        reloaded.load_exclusion_settings(temp.path());
+        let settings = reloaded.exclusion_settings.lock().unwrap();
+        assert!(settings.ocr_skip_processes.contains("mpv.exe"));
+    }
+
+    #[test]
+    fn process_blocklist_covers_builtin_and_user_entries() {
+        let state = CaptureState::new();
+        state.update_exclusion_settings(Some(vec![" Banking.EXE ".to_string()]), None, None, None);
+        let settings = state.exclusion_settings.lock().unwrap();
+
+        assert!(is_process_excluded("KeePassXC.exe", &settings));
+        assert!(is_process_excluded("bitwarden.exe", &settings));
+        assert!(is_process_excluded("banking.exe", &settings));
+        assert!(!is_process_excluded("code.exe", &settings));
+        assert!(!is_process_excluded("", &settings));
+    }
+
+    #[test]
+    fn allowlist_mode_only_lets_listed_processes_through() {
+        let state = CaptureState::new();
+        assert!(is_process_allowed(
+            "chrome.exe",
+            &state.exclusion_settings.lock().unwrap()
+        ));
+
+        state.update_allowlist_settings(Some(true), None);
+        assert!(!is_process_allowed(
+            "chrome.exe",
+            &state.exclusion_settings.lock().unwrap()
+        ));
+
+        state.update_allowlist_settings(None, Some(vec![" Code.exe ".to_string()]));
+        let temp = tempfile::tempdir().unwrap();
+        state.save_exclusion_settings(temp.path());
+        let reloaded = CaptureState::new();
+        reloaded.load_exclusion_settings(temp.path());
+        let settings = reloaded.exclusion_settings.lock().unwrap();
+        assert!(is_process_allowed("CODE.EXE", &settings));
+        assert!(!is_process_allowed("chrome.exe", &settings));
+        assert!(!is_process_allowed("", &settings));
+    }
+
+    #[test]
+    fn title_rules_match_regex_and_reject_invalid_patterns() {
+        let state = CaptureState::new();
+        state
+            .set_title_rules(&[
+                ".*Incognito.*".to_string(),
+                "(?i)private browsing".to_string(),
+            ])
+            .unwrap();
+        {
+            let settings = state.exclusion_settings.lock().unwrap();
+            assert!(matches_title_rule("New Incognito Tab - Chrome", &settings));
+            assert!(matches_title_rule(
+                "Mozilla Firefox Private Browsing",
+                &settings
+            ));
+            assert!(!matches_title_rule("incognito notes.txt", &settings));
+        }
+
+        let err = state
+            .set_title_rules(&["(unclosed".to_string()])
+            .unwrap_err();
+        assert!(err.contains("(unclosed"));
+        // A rejected set leaves the previous rules in place.
+        assert_eq!(
+            state.exclusion_settings.lock().unwrap().title_rules.len(),
+            2
+        );
+    }
+
+    #[test]
+    fn redaction_regions_mask_scaled_screen_and_window_rectangles() {
+        let regions = normalize_redaction_regions(vec![
+            RedactionRegion {
+                scope: RegionScope::Screen,
+                process: None,
+                x: 120,
+                y: 20,
+                width: 20,
+                height: 20,
+                mode: RedactionMode::Blackout,
+            },
+            RedactionRegion {
+                scope: RegionScope::Window,
+                process: Some(" Slack.exe ".to_string()),
+                x: 0,
+                y: 0,
+                width: 10,
+                height: 10,
+                mode: RedactionMode::Blackout,
+            },
+            RedactionRegion {
+                scope: RegionScope::Window,
+                process: None,
+                x: 0,
+                y: 0,
+                width: 0,
+                height: 10,
+                mode: RedactionMode::Blackout,
+            },
+        ]);
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[1].process.as_deref(), Some("slack.exe"));
+
+        // A 200x100 window at (100, 0) captured at half size.
+        let mut image = RgbImage::from_pixel(100, 50, image::Rgb([255, 255, 255]));
+        assert!(apply_redaction_regions(
+            &mut image,
+            &regions,
+            "code.exe",
+            (100, 0, 200, 100)
+        ));
+        assert_eq!(image.get_pixel(10, 10), &image::Rgb([0, 0, 0]));
+        assert_eq!(image.get_pixel(19, 19), &image::Rgb([0, 0, 0]));
+        assert_eq!(image.get_pixel(20, 20), &image::Rgb([255, 255, 255]));
+        assert_eq!(image.get_pixel(0, 0), &image::Rgb([255, 255, 255]));
+
+        assert!(apply_redaction_regions(
+            &mut image,
+            &regions,
+            "SLACK.EXE",
+            (100, 0, 200, 100)
+        ));
+        assert_eq!(image.get_pixel(0, 0), &image::Rgb([0, 0, 0]));
+
+        let mut offscreen = RgbImage::from_pixel(100, 50, image::Rgb([255, 255, 255]));
+        assert!(!apply_redaction_regions(
+            &mut offscreen,
+            &regions[..1],
+            "code.exe",
+            (500, 0, 200, 100)
+        ));
+    }
+
+    #[test]
+    fn capture_metrics_average_ocr_time_over_all_runs() {
+        let metrics = CaptureMetrics::default();
+        assert!(metrics.snapshot()["ocr"]["average_ms"].is_null());
+
+        metrics.record_ocr(100, true);
+        metrics.record_ocr(300, false);
+        metrics.frames_captured.fetch_add(2, Ordering::Relaxed);
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot["ocr"]["completed"], 1);
+        assert_eq!(snapshot["ocr"]["failed"], 1);
+        assert_eq!(snapshot["ocr"]["average_ms"], 200.0);
+        assert_eq!(snapshot["ocr"]["last_ms"], 300);
+        assert_eq!(snapshot["frames"]["captured"], 2);
+    }
+
+    #[test]
+    fn capture_tuning_is_clamped_and_partial() {
+        let state = CaptureState::new();
+        state.set_capture_tuning(Some(1), None);
+        {
+            let config = state.config.lock().unwrap();
+            assert_eq!(config.interval_secs, MIN_CAPTURE_INTERVAL_SECS);
+            assert_eq!(
+                config.dhash_threshold,
+                CaptureConfig::default().dhash_threshold
+            );
+        }
+
+        state.set_capture_tuning(None, Some(1000));
+        state.set_capture_tuning(Some(30), None);
+        let config = state.config.lock().unwrap();
+        assert_eq!(config.interval_secs, 30);
+        assert_eq!(config.dhash_threshold, MAX_CAPTURE_DHASH_THRESHOLD);
+    }
+
     #[test]
     fn ocr_slot_reservation_is_strictly_single_flight() {
         let state = Arc::new(CaptureState::new());
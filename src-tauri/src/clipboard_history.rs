@@ -0,0 +1,296 @@
+//! Opt-in clipboard history.
+//!
+//! While enabled, text copied in another app is stored as an encrypted timeline entry
+//! together with the process and window title it was copied in (see
+//! `storage/clipboard.rs`). The clipboard sequence number is polled, so no hidden
+//! listener window is needed.
+//!
+//! A copy is not recorded while capture is paused, stopped or paused for a screen
+//! share, when the foreground window is excluded from capture (password managers,
+//! private browsing, user rules), when its process is on the clipboard exclusion list,
+//! or when the source app marks the data as private with the
+//! `ExcludeClipboardContentFromMonitorProcessing` / `CanIncludeInClipboardHistory`
+//! formats, as password managers do. Entries older than the retention period are
+//! removed by the `clipboard_retention` scheduled job.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use windows::core::w;
+use windows::Win32::Foundation::{HANDLE, HGLOBAL, HWND};
+use windows::Win32::System::DataExchange::{
+    CloseClipboard, GetClipboardData, GetClipboardSequenceNumber, IsClipboardFormatAvailable,
+    OpenClipboard, RegisterClipboardFormatW,
+};
+use windows::Win32::System::Memory::{GlobalLock, GlobalSize, GlobalUnlock};
+
+use crate::capture::CaptureState;
+use crate::storage::StorageState;
+
+pub const CLIPBOARD_HISTORY_ENABLED_KEY: &str = "clipboard_history_enabled";
+pub const CLIPBOARD_RETENTION_DAYS_KEY: &str = "clipboard_retention_days";
+/// JSON array of lower-case process names whose copies are never recorded.
+pub const CLIPBOARD_EXCLUDED_PROCESSES_KEY: &str = "clipboard_excluded_processes";
+pub const DEFAULT_CLIPBOARD_RETENTION_DAYS: u32 = 30;
+const MAX_CLIPBOARD_RETENTION_DAYS: u32 = 3650;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const CF_UNICODETEXT: u32 = 13;
+
+/// Ensures only one polling task is ever spawned.
+static MONITOR_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Clipboard history options. `retention_days == 0` keeps entries until deleted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClipboardHistorySettings {
+    pub enabled: bool,
+    pub retention_days: u32,
+    pub excluded_processes: Vec<String>,
+}
+
+pub fn clipboard_history_enabled() -> bool {
+    crate::registry_config::get_bool(CLIPBOARD_HISTORY_ENABLED_KEY).unwrap_or(false)
+}
+
+pub fn load_settings() -> ClipboardHistorySettings {
+    let excluded_processes = crate::registry_config::get_string(CLIPBOARD_EXCLUDED_PROCESSES_KEY)
+        .and_then(|raw| serde_json::from_str::<Vec<String>>(&raw).ok())
+        .unwrap_or_default();
+    ClipboardHistorySettings {
+        enabled: clipboard_history_enabled(),
+        retention_days: crate::registry_config::get_u32(CLIPBOARD_RETENTION_DAYS_KEY)
+            .unwrap_or(DEFAULT_CLIPBOARD_RETENTION_DAYS)
+            .min(MAX_CLIPBOARD_RETENTION_DAYS),
+        excluded_processes: normalize_process_names(&excluded_processes),
+    }
+}
+
+/// Persists `settings` and returns them as stored.
+pub fn save_settings(
+    settings: &ClipboardHistorySettings,
+) -> Result<ClipboardHistorySettings, String> {
+    let normalized = ClipboardHistorySettings {
+        enabled: settings.enabled,
+        retention_days: settings.retention_days.min(MAX_CLIPBOARD_RETENTION_DAYS),
+        excluded_processes: normalize_process_names(&settings.excluded_processes),
+    };
+    let excluded = serde_json::to_string(&normalized.excluded_processes)
+        .map_err(|e| format!("Failed to serialize clipboard exclusions: {}", e))?;
+    crate::registry_config::set_bool(CLIPBOARD_HISTORY_ENABLED_KEY, normalized.enabled)?;
+    crate::registry_config::set_u32(CLIPBOARD_RETENTION_DAYS_KEY, normalized.retention_days)?;
+    crate::registry_config::set_string(CLIPBOARD_EXCLUDED_PROCESSES_KEY, &excluded)?;
+    Ok(normalized)
+}
+
+/// Lower-cased, trimmed, de-duplicated process names in their original order.
+fn normalize_process_names(names: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for name in names {
+        let name = name.trim().to_lowercase();
+        if !name.is_empty() && !normalized.contains(&name) {
+            normalized.push(name);
+        }
+    }
+    normalized
+}
+
+/// Entries captured before this Unix time (ms) have expired; `None` keeps everything.
+pub fn retention_cutoff_ms(retention_days: u32, now_ms: i64) -> Option<i64> {
+    (retention_days > 0).then(|| now_ms - i64::from(retention_days) * 24 * 60 * 60 * 1000)
+}
+
+/// Closes the clipboard when a read returns early.
+struct ClipboardGuard;
+
+impl Drop for ClipboardGuard {
+    fn drop(&mut self) {
+        // SAFETY: the guard is created only after `OpenClipboard` succeeds and closes
+        // that thread-owned clipboard exactly once.
+        unsafe {
+            let _ = CloseClipboard();
+        }
+    }
+}
+
+/// Whether the open clipboard holds a format by which the source asks clipboard
+/// monitors and history to ignore the data.
+///
+/// # Safety
+/// The clipboard must be open on the calling thread.
+unsafe fn clipboard_marked_private() -> bool {
+    let exclude = RegisterClipboardFormatW(w!("ExcludeClipboardContentFromMonitorProcessing"));
+    if exclude != 0 && IsClipboardFormatAvailable(exclude).is_ok() {
+        return true;
+    }
+    // A DWORD of 0 opts the data out of clipboard history.
+    let can_include = RegisterClipboardFormatW(w!("CanIncludeInClipboardHistory"));
+    if can_include == 0 || IsClipboardFormatAvailable(can_include).is_err() {
+        return false;
+    }
+    let Ok(handle) = GetClipboardData(can_include) else {
+        return true;
+    };
+    let global = HGLOBAL(handle.0);
+    let locked = GlobalLock(global) as *const u32;
+    if locked.is_null() {
+        return true;
+    }
+    let allowed = GlobalSize(global) >= std::mem::size_of::<u32>() && locked.read_unaligned() != 0;
+    let _ = GlobalUnlock(global);
+    !allowed
+}
+
+/// Text on the clipboard, or `None` when it holds no text or the source marked it
+/// private.
+fn read_clipboard_text() -> Result<Option<String>, String> {
+    // SAFETY: the clipboard is opened on this thread and closed by `ClipboardGuard`; the
+    // data handle is owned by the clipboard, locked only while it is copied, and read no
+    // further than its reported size.
+    unsafe {
+        if IsClipboardFormatAvailable(CF_UNICODETEXT).is_err() {
+            return Ok(None);
+        }
+        OpenClipboard(HWND::default()).map_err(|e| format!("Failed to open clipboard: {:?}", e))?;
+        let _clipboard_open = ClipboardGuard;
+        if clipboard_marked_private() {
+            return Ok(None);
+        }
+
+        let handle: HANDLE = GetClipboardData(CF_UNICODETEXT)
+            .map_err(|e| format!("Failed to read clipboard data: {:?}", e))?;
+        let global = HGLOBAL(handle.0);
+        let locked = GlobalLock(global) as *const u16;
+        if locked.is_null() {
+            return Err("GlobalLock failed".to_string());
+        }
+        let units = std::slice::from_raw_parts(locked, GlobalSize(global) / 2);
+        let len = units.iter().position(|&c| c == 0).unwrap_or(units.len());
+        let text = String::from_utf16_lossy(&units[..len]);
+        let _ = GlobalUnlock(global);
+        Ok(Some(text))
+    }
+}
+
+/// Process name and window title of the foreground window when a copy made there may
+/// be recorded. `None` skips the copy.
+fn recordable_source(capture_state: &CaptureState) -> Option<(String, String)> {
+    if capture_state.paused.load(Ordering::SeqCst)
+        || capture_state.stopped.load(Ordering::SeqCst)
+        || capture_state.screen_share_paused.load(Ordering::SeqCst)
+    {
+        return None;
+    }
+    let info = crate::capture::get_active_window_info()?;
+    // CarbonPaper itself copies the MCP token.
+    if info.pid() == std::process::id() {
+        return None;
+    }
+    {
+        let settings = capture_state
+            .exclusion_settings
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if crate::capture::is_excluded(&info, &settings) {
+            return None;
+        }
+    }
+    let process_name = crate::capture::get_process_path_from_pid(info.pid())
+        .map(|path| crate::capture::get_process_name_from_path(&path))
+        .unwrap_or_default();
+    if load_settings().excluded_processes.contains(&process_name) {
+        return None;
+    }
+    Some((process_name, info.title().to_string()))
+}
+
+/// Records the current clipboard text if its source may be recorded.
+fn record_clipboard_change(app: &AppHandle) -> Result<(), String> {
+    let storage = app.state::<Arc<StorageState>>();
+    if storage.is_read_only() {
+        return Ok(());
+    }
+    let capture_state = app.state::<Arc<CaptureState>>();
+    let Some((process_name, window_title)) = recordable_source(&capture_state) else {
+        return Ok(());
+    };
+    let Some(text) = read_clipboard_text()? else {
+        return Ok(());
+    };
+    let captured_at = chrono::Utc::now().timestamp_millis();
+    let process_name = (!process_name.is_empty()).then_some(process_name.as_str());
+    if let Some(id) =
+        storage.add_clipboard_entry(&text, process_name, Some(&window_title), captured_at)?
+    {
+        let _ = app.emit(
+            "clipboard-entry-added",
+            serde_json::json!({ "id": id, "captured_at": captured_at }),
+        );
+    }
+    Ok(())
+}
+
+/// Starts the clipboard polling loop. Safe to call more than once.
+pub fn start_clipboard_monitor(app: AppHandle) {
+    if MONITOR_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        // SAFETY: reads a global counter and has no preconditions.
+        let mut last_sequence = unsafe { GetClipboardSequenceNumber() };
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            // SAFETY: as above.
+            let sequence = unsafe { GetClipboardSequenceNumber() };
+            if sequence == last_sequence {
+                continue;
+            }
+            if !clipboard_history_enabled() {
+                last_sequence = sequence;
+                continue;
+            }
+            let handle = app.clone();
+            let result = tokio::task::spawn_blocking(move || record_clipboard_change(&handle))
+                .await
+                .map_err(|e| format!("Task join error: {:?}", e))
+                .and_then(|r| r);
+            match result {
+                Ok(()) => last_sequence = sequence,
+                // Another app may hold the clipboard open; retry on the next poll.
+                Err(e) if e.starts_with("Failed to open clipboard") => {}
+                Err(e) => {
+                    tracing::warn!("Clipboard history: {}", e);
+                    last_sequence = sequence;
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn process_names_are_normalized_and_deduplicated() {
+        let names = vec![
+            " Code.exe ".to_string(),
+            "code.exe".to_string(),
+            String::new(),
+            "WindowsTerminal.exe".to_string(),
+        ];
+        assert_eq!(
+            normalize_process_names(&names),
+            vec!["code.exe".to_string(), "windowsterminal.exe".to_string()]
+        );
+    }
+
+    #[test]
+    fn retention_zero_keeps_everything() {
+        let now_ms = 1_700_000_000_000;
+        assert_eq!(retention_cutoff_ms(0, now_ms), None);
+        assert_eq!(retention_cutoff_ms(1, now_ms), Some(now_ms - 86_400_000));
+    }
+}
@@ -8,6 +8,7 @@ use crate::credential_manager::{self, CredentialManagerState};
 use crate::mcp_server;
 use crate::storage::StorageState;
 use std::sync::Arc;
+use tauri::Emitter;
 
 /// Initializes the CNG key pair, cached public key, master key, and encrypted storage.
 ///
@@ -105,12 +106,15 @@ pub async fn credential_check_session(
 
 /// Invalidates the current authenticated session immediately.
 ///
-/// Authentication: not required. Returns JSON `null` on success.
+/// Authentication: not required. Also drops the decrypted images and row keys cached by
+/// storage. Returns JSON `null` on success.
 #[tauri::command]
 pub async fn credential_lock_session(
     state: tauri::State<'_, Arc<CredentialManagerState>>,
+    storage: tauri::State<'_, Arc<StorageState>>,
 ) -> Result<(), String> {
     state.invalidate_session();
+    storage.flush_decrypt_cache();
     Ok(())
 }
 
@@ -121,23 +125,34 @@ pub async fn credential_lock_session(
 #[tauri::command]
 pub async fn credential_set_foreground(
     state: tauri::State<'_, Arc<CredentialManagerState>>,
+    storage: tauri::State<'_, Arc<StorageState>>,
     in_foreground: bool,
 ) -> Result<(), String> {
     state.set_foreground_state(in_foreground);
+    if !state.is_session_valid() {
+        storage.flush_decrypt_cache();
+    }
     Ok(())
 }
 
 /// Changes and persists the authenticated-session timeout in seconds.
 ///
 /// Authentication: required. `timeout` is an integer number of seconds; returns JSON
-/// `null`. Frontend: `hooks/useAuthSession.js`.
+/// `null`. While compliance mode is on the value is capped and `-1` is rejected.
+/// Frontend: `hooks/useAuthSession.js`.
 #[tauri::command]
 pub async fn credential_set_session_timeout(
     state: tauri::State<'_, Arc<CredentialManagerState>>,
+    storage: tauri::State<'_, Arc<StorageState>>,
     timeout: i64,
 ) -> Result<(), String> {
     crate::commands::check_auth_required(&state)?;
 
+    let timeout = if storage.compliance_mode_enabled() {
+        crate::storage::compliance_session_timeout(timeout)
+    } else {
+        timeout
+    };
     state.set_session_timeout(timeout);
     if let Err(e) = crate::registry_config::set_string("session_timeout_secs", &timeout.to_string())
     {
@@ -156,3 +171,48 @@ pub async fn credential_get_session_timeout(
 ) -> Result<i64, String> {
     Ok(state.get_session_timeout())
 }
+
+/// Rotates the CNG key pair and master key and re-wraps every stored row key.
+///
+/// Authentication: required; rejected in guest mode. Emits `key-rotation-progress`
+/// events (`{ phase, processed, total }`, where `phase` is the table being re-wrapped
+/// or `switching`) and `key-rotation-complete` once the new keys are installed.
+/// Returns JSON `null`, or `ALREADY_RUNNING` while a rotation is active. An interrupted
+/// rotation resumes on the next call. Search results fill in again as the background
+/// indexer rebuilds the blind index under the new key.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn credential_rotate_keys(
+    app_handle: tauri::AppHandle,
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    storage_state: tauri::State<'_, Arc<StorageState>>,
+) -> Result<(), String> {
+    crate::commands::check_auth_required(&credential_state)?;
+    crate::commands::check_writable(&storage_state)?;
+
+    let storage_state = storage_state.inner().clone();
+    if storage_state.is_key_rotation_in_progress() {
+        return Err("ALREADY_RUNNING".to_string());
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let progress_handle = app_handle.clone();
+        let result = storage_state.run_key_rotation(move |phase, processed, total| {
+            let _ = progress_handle.emit(
+                "key-rotation-progress",
+                serde_json::json!({
+                    "phase": phase,
+                    "processed": processed,
+                    "total": total
+                }),
+            );
+        });
+
+        if result.is_ok() {
+            let _ = app_handle.emit("key-rotation-complete", ());
+        }
+        result
+    })
+    .await
+    .map_err(|e| format!("Key rotation task panicked: {}", e))?
+}
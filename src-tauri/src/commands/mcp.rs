@@ -391,6 +391,123 @@ pub async fn mcp_copy_token_to_clipboard(
     }))
 }
 
+/// Lists scoped API tokens without their encrypted secrets.
+///
+/// Authentication: required. Returns an array of `{ "id", "name", "scopes",
+/// "created_at" }` objects. Frontend:
+/// `components/settings/agent-access/useApiTokenSettings.js`.
+#[tauri::command]
+pub async fn mcp_list_api_tokens(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    storage_state: tauri::State<'_, Arc<StorageState>>,
+) -> Result<Vec<serde_json::Value>, String> {
+    super::check_auth_required(&credential_state)?;
+
+    let policy = storage_state.load_policy()?;
+    Ok(mcp_token::load_api_tokens(&policy)
+        .into_iter()
+        .map(|record| {
+            serde_json::json!({
+                "id": record.id,
+                "name": record.name,
+                "scopes": record.scopes,
+                "created_at": record.created_at,
+            })
+        })
+        .collect())
+}
+
+/// Creates a scoped API token and copies its plaintext to the clipboard.
+///
+/// Authentication: required. `scopes` is a non-empty list of `read-search`,
+/// `read-images`, or `admin`. Returns `{ "status": "ok", "id", "token_delivery":
+/// "clipboard", "copied_to_clipboard": boolean }`; the token is never serialized to
+/// JavaScript and is accepted by a running server immediately. Frontend:
+/// `components/settings/agent-access/useApiTokenSettings.js`.
+#[tauri::command]
+pub async fn mcp_create_api_token(
+    window: tauri::Window,
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    storage_state: tauri::State<'_, Arc<StorageState>>,
+    mcp_state: tauri::State<'_, mcp_server::McpRuntimeState>,
+    name: String,
+    scopes: Vec<mcp_token::TokenScope>,
+) -> Result<serde_json::Value, String> {
+    super::check_auth_required(&credential_state)?;
+
+    let (record, token) = mcp_token::create_api_token(&credential_state, &name, scopes)?;
+    let id = record.id.clone();
+
+    let mut policy = storage_state.load_policy()?;
+    let mut tokens = mcp_token::load_api_tokens(&policy);
+    tokens.push(record);
+    mcp_token::store_api_tokens(&mut policy, &tokens)?;
+    storage_state.save_policy(&policy)?;
+    mcp_state.set_api_tokens(&tokens);
+
+    let copied_to_clipboard = copy_mcp_token_to_clipboard(&window, &token).is_ok();
+    Ok(serde_json::json!({
+        "status": "ok",
+        "id": id,
+        "token_delivery": "clipboard",
+        "copied_to_clipboard": copied_to_clipboard
+    }))
+}
+
+/// Copies an existing scoped API token to the clipboard.
+///
+/// Authentication: required. Returns `{ "status": "ok", "token_delivery":
+/// "clipboard", "copied_to_clipboard": true }`; plaintext never crosses IPC. Frontend:
+/// `components/settings/agent-access/useApiTokenSettings.js`.
+#[tauri::command]
+pub async fn mcp_copy_api_token_to_clipboard(
+    window: tauri::Window,
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    storage_state: tauri::State<'_, Arc<StorageState>>,
+    id: String,
+) -> Result<serde_json::Value, String> {
+    super::check_auth_required(&credential_state)?;
+
+    let policy = storage_state.load_policy()?;
+    let record = mcp_token::load_api_tokens(&policy)
+        .into_iter()
+        .find(|record| record.id == id)
+        .ok_or_else(|| format!("API token not found: {}", id))?;
+    let token = mcp_token::decrypt_token(&credential_state, &record.token_encrypted)?;
+    copy_mcp_token_to_clipboard(&window, &token)?;
+    Ok(serde_json::json!({
+        "status": "ok",
+        "token_delivery": "clipboard",
+        "copied_to_clipboard": true
+    }))
+}
+
+/// Revokes a scoped API token; a running server rejects it immediately.
+///
+/// Authentication: required. Returns `true` when a token with `id` was removed.
+/// Frontend: `components/settings/agent-access/useApiTokenSettings.js`.
+#[tauri::command]
+pub async fn mcp_revoke_api_token(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    storage_state: tauri::State<'_, Arc<StorageState>>,
+    mcp_state: tauri::State<'_, mcp_server::McpRuntimeState>,
+    id: String,
+) -> Result<bool, String> {
+    super::check_auth_required(&credential_state)?;
+
+    let mut policy = storage_state.load_policy()?;
+    let mut tokens = mcp_token::load_api_tokens(&policy);
+    let before = tokens.len();
+    tokens.retain(|record| record.id != id);
+    if tokens.len() == before {
+        return Ok(false);
+    }
+    mcp_token::store_api_tokens(&mut policy, &tokens)?;
+    storage_state.save_policy(&policy)?;
+    mcp_state.set_api_tokens(&tokens);
+    Ok(true)
+}
+
 /// Returns the configured loopback MCP port as a JSON integer.
 ///
 /// Authentication: not required. The settings UI currently obtains this through
@@ -7,7 +7,7 @@
 use crate::capture::CaptureState;
 use crate::credential_manager::{get_cached_master_key, CredentialManagerState};
 use crate::monitor::{start_monitor_impl, stop_monitor_impl, MonitorState};
-use crate::storage::StorageState;
+use crate::storage::{PathNormalizationReport, RestoreReport, StorageState, SyncConfig};
 use aes_gcm::{
     aead::{Aead, KeyInit},
     Aes256Gcm, Nonce,
@@ -131,6 +131,269 @@ pub async fn storage_run_manual_vacuum(
     .map_err(|e| format!("Manual VACUUM task panicked: {}", e))?
 }
 
+/// Runs full database maintenance: blind index compaction, REINDEX, ANALYZE, and
+/// VACUUM, emitting `storage-maintenance-progress` events (`{ step, index, total }`).
+///
+/// Authentication: required. Returns `{ "ok": boolean, "already_running": boolean,
+/// "report": DatabaseMaintenanceReport | null }`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_maintenance(
+    app_handle: tauri::AppHandle,
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+) -> Result<serde_json::Value, String> {
+    super::check_auth_required(&credential_state)?;
+    super::check_writable(&state)?;
+
+    let state = state.inner().clone();
+
+    tokio::task::spawn_blocking(move || {
+        let on_progress = |step: &str, index: usize, total: usize| {
+            let _ = app_handle.emit(
+                "storage-maintenance-progress",
+                serde_json::json!({ "step": step, "index": index, "total": total }),
+            );
+        };
+        match state.run_database_maintenance(&on_progress) {
+            Ok(report) => Ok(serde_json::json!({
+                "ok": true,
+                "already_running": false,
+                "report": report
+            })),
+            Err(e) if e == "ALREADY_RUNNING" => Ok(serde_json::json!({
+                "ok": false,
+                "already_running": true,
+                "report": null
+            })),
+            Err(e) => Err(e),
+        }
+    })
+    .await
+    .map_err(|e| format!("Maintenance task panicked: {}", e))?
+}
+
+/// Backs up the database and encrypted files to `destination` (default: the saved
+/// backup folder), emitting `storage-backup-progress` events (`{ phase, done, total }`).
+///
+/// Authentication: required. Only new and changed files are copied; see
+/// `storage/backup.rs`. Returns `{ "ok": boolean, "already_running": boolean,
+/// "report": BackupReport | null }`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_backup_now(
+    app_handle: tauri::AppHandle,
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    destination: Option<String>,
+) -> Result<serde_json::Value, String> {
+    super::check_auth_required(&credential_state)?;
+
+    let destination = destination
+        .map(std::path::PathBuf::from)
+        .or_else(crate::storage::backup_dir)
+        .ok_or_else(|| "No backup folder configured".to_string())?;
+    let state = state.inner().clone();
+
+    tokio::task::spawn_blocking(move || {
+        let on_progress = |phase: &str, done: usize, total: usize| {
+            let _ = app_handle.emit(
+                "storage-backup-progress",
+                serde_json::json!({ "phase": phase, "done": done, "total": total }),
+            );
+        };
+        match state.backup_now(&destination, &on_progress) {
+            Ok(report) => Ok(serde_json::json!({
+                "ok": true,
+                "already_running": false,
+                "report": report
+            })),
+            Err(e) if e == "ALREADY_RUNNING" => Ok(serde_json::json!({
+                "ok": false,
+                "already_running": true,
+                "report": null
+            })),
+            Err(e) => Err(e),
+        }
+    })
+    .await
+    .map_err(|e| format!("Backup task panicked: {}", e))?
+}
+
+/// Returns the folder the `backup` scheduled job writes to, or `null`.
+///
+/// Authentication: required. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_get_backup_dir(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+) -> Result<Option<String>, String> {
+    super::check_auth_required(&credential_state)?;
+    Ok(crate::storage::backup_dir().map(|dir| dir.display().to_string()))
+}
+
+/// Saves (or clears with `null`) the backup folder. Enable the `backup` scheduled job
+/// to back up automatically.
+///
+/// Authentication: required. The folder must exist. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_set_backup_dir(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    backup_dir: Option<String>,
+) -> Result<(), String> {
+    super::check_auth_required(&credential_state)?;
+    crate::storage::set_backup_dir(backup_dir.as_deref())
+}
+
+/// Uploads screenshots not yet on the sync target and records deletions, emitting
+/// `storage-sync-progress` events (`{ phase, done, total }`).
+///
+/// Authentication: required; the master key seals the changelog. See
+/// `storage/remote_sync/mod.rs`. Returns `{ "ok": boolean, "already_running": boolean,
+/// "report": SyncReport | null }`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_sync_now(
+    app_handle: tauri::AppHandle,
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+) -> Result<serde_json::Value, String> {
+    super::check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+
+    tokio::task::spawn_blocking(move || {
+        let on_progress = |phase: &str, done: usize, total: usize| {
+            let _ = app_handle.emit(
+                "storage-sync-progress",
+                serde_json::json!({ "phase": phase, "done": done, "total": total }),
+            );
+        };
+        match state.sync_now(&on_progress) {
+            Ok(report) => Ok(serde_json::json!({
+                "ok": true,
+                "already_running": false,
+                "report": report
+            })),
+            Err(e) if e == "ALREADY_RUNNING" => Ok(serde_json::json!({
+                "ok": false,
+                "already_running": true,
+                "report": null
+            })),
+            Err(e) => Err(e),
+        }
+    })
+    .await
+    .map_err(|e| format!("Sync task panicked: {}", e))?
+}
+
+/// Returns the sync target settings; the password or secret key is never returned,
+/// only `has_secret`.
+///
+/// Authentication: required. Returns `SyncConfig`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_get_sync_config(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+) -> Result<SyncConfig, String> {
+    super::check_auth_required(&credential_state)?;
+    state.sync_config()
+}
+
+/// Saves the sync target. `secret` replaces the saved password or secret key, an
+/// empty string clears it and `null` keeps it. Enable the `sync` scheduled job to
+/// sync automatically.
+///
+/// Authentication: required. Returns the saved `SyncConfig`. Frontend:
+/// `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_set_sync_config(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    config: SyncConfig,
+    secret: Option<String>,
+) -> Result<SyncConfig, String> {
+    super::check_auth_required(&credential_state)?;
+    state.set_sync_config(config, secret.as_deref())
+}
+
+/// Restores a backup folder written by `storage_backup_now`, replacing the database and
+/// encrypted files or, with `merge`, adding the backup's screenshots to the current
+/// database. Emits `storage-restore-progress` (`{ phase, done, total }`), then
+/// `storage-restore-done` or `storage-restore-error`.
+///
+/// Authentication: required; rejected in guest mode. The monitor is stopped while
+/// storage is reopened and `storage_migration_cancel` cancels the restore, putting the
+/// previous files back. Returns `RestoreReport`; when `index_rebuild_required` is set,
+/// run `storage_rebuild_index` so merged text becomes searchable. Frontend:
+/// `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_restore_from_backup(
+    app_handle: tauri::AppHandle,
+    credential_state: State<'_, Arc<CredentialManagerState>>,
+    state: State<'_, Arc<StorageState>>,
+    monitor_state: State<'_, MonitorState>,
+    capture_state: State<'_, Arc<CaptureState>>,
+    path: String,
+    merge: Option<bool>,
+) -> Result<RestoreReport, String> {
+    super::check_auth_required(&credential_state)?;
+    super::check_writable(&state)?;
+
+    let was_running = {
+        let guard = monitor_state
+            .process
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        guard.is_some()
+    };
+    monitor_state
+        .migration_lock
+        .store(true, std::sync::atomic::Ordering::SeqCst);
+    let _ = stop_monitor_impl(
+        monitor_state.clone(),
+        capture_state.clone(),
+        app_handle.clone(),
+    )
+    .await;
+
+    let storage = state.inner().clone();
+    let progress_handle = app_handle.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let on_progress = |phase: &str, done: usize, total: usize| {
+            let _ = progress_handle.emit(
+                "storage-restore-progress",
+                serde_json::json!({ "phase": phase, "done": done, "total": total }),
+            );
+        };
+        storage.restore_from_backup(
+            std::path::Path::new(&path),
+            merge.unwrap_or(false),
+            &on_progress,
+        )
+    })
+    .await
+    .map_err(|e| format!("Restore task panicked: {}", e))?;
+
+    monitor_state
+        .migration_lock
+        .store(false, std::sync::atomic::Ordering::SeqCst);
+    match &result {
+        Ok(report) => {
+            let _ = app_handle.emit("storage-restore-done", report);
+        }
+        Err(e) => {
+            let _ = app_handle.emit("storage-restore-error", serde_json::json!({ "message": e }));
+        }
+    }
+
+    // Storage is reopened whether the restore succeeded or was rolled back.
+    if was_running {
+        let monitor_state_for_start = app_handle.state::<MonitorState>();
+        if let Err(e) = start_monitor_impl(monitor_state_for_start, app_handle.clone()).await {
+            tracing::error!("Restore: Failed to restart monitor: {}", e);
+        }
+    }
+
+    result
+}
+
 /// Starts HMAC migration and emits `hmac-migration-progress` events.
 ///
 /// Authentication: required. Returns JSON `null` when complete and emits
@@ -142,6 +405,7 @@ pub async fn storage_run_hmac_migration(
     state: tauri::State<'_, Arc<StorageState>>,
 ) -> Result<(), String> {
     super::check_auth_required(&credential_state)?;
+    super::check_writable(&state)?;
 
     let state = state.inner().clone();
 
@@ -189,6 +453,50 @@ pub async fn storage_hmac_migration_cancel(
     }))
 }
 
+/// Rebuilds the blind search index from the decrypted text of every OCR row.
+///
+/// Authentication: required; rejected in guest mode. Emits `index-rebuild-progress`
+/// events (`{ phase, processed, total }`, `phase` is `indexing` or `swapping`) and
+/// `index-rebuild-complete`. The current index keeps serving searches until the rebuilt
+/// one is swapped in. Returns JSON `null`, or `ALREADY_RUNNING` while a rebuild is
+/// active. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_rebuild_index(
+    app_handle: tauri::AppHandle,
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+) -> Result<(), String> {
+    super::check_auth_required(&credential_state)?;
+    super::check_writable(&state)?;
+
+    let state = state.inner().clone();
+
+    if state.is_index_rebuild_in_progress() {
+        return Err("ALREADY_RUNNING".to_string());
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let app_handle_clone = app_handle.clone();
+        let result = state.run_index_rebuild(move |phase, processed, total| {
+            let _ = app_handle_clone.emit(
+                "index-rebuild-progress",
+                serde_json::json!({
+                    "phase": phase,
+                    "processed": processed,
+                    "total": total
+                }),
+            );
+        });
+
+        if result.is_ok() {
+            let _ = app_handle.emit("index-rebuild-complete", ());
+        }
+        result
+    })
+    .await
+    .map_err(|e| format!("Index rebuild task panicked: {}", e))?
+}
+
 /// Lists screenshot files that still exist as plaintext paths.
 ///
 /// Authentication: required. Returns a JSON array of path strings.
@@ -213,6 +521,7 @@ pub async fn storage_migrate_plaintext(
     state: tauri::State<'_, Arc<StorageState>>,
 ) -> Result<serde_json::Value, String> {
     super::check_auth_required(&credential_state)?;
+    super::check_writable(&state)?;
 
     let state = state.inner().clone();
     let res = tokio::task::spawn_blocking(move || state.migrate_plaintext_screenshots())
@@ -227,6 +536,25 @@ pub async fn storage_migrate_plaintext(
     }))
 }
 
+/// Rewrites legacy absolute image paths to data-dir-relative form.
+///
+/// Authentication: required. A row is only rewritten when its file is found under the
+/// current data directory; the others are reported and left unchanged. Returns
+/// `PathNormalizationReport`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_normalize_paths(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+) -> Result<PathNormalizationReport, String> {
+    super::check_auth_required(&credential_state)?;
+    super::check_writable(&state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.normalize_image_paths())
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
 /// Moves storage to `target`, optionally including screenshot data files.
 ///
 /// Authentication: required. `migrate_data_files` controls whether only configuration
@@ -241,6 +569,7 @@ pub async fn storage_migrate_data_dir(
     migrate_data_files: bool,
 ) -> Result<serde_json::Value, String> {
     super::check_auth_required(&credential_state)?;
+    super::check_writable(&state)?;
 
     let state = state.inner().clone();
     tokio::task::spawn_blocking(move || {
@@ -278,6 +607,7 @@ pub async fn storage_delete_plaintext(
     state: tauri::State<'_, Arc<StorageState>>,
 ) -> Result<serde_json::Value, String> {
     super::check_auth_required(&credential_state)?;
+    super::check_writable(&state)?;
 
     let state = state.inner().clone();
     let count = tokio::task::spawn_blocking(move || state.delete_plaintext_screenshots())
@@ -504,6 +834,7 @@ pub async fn storage_import_backup(
     backup_zip_path: String,
 ) -> Result<(), String> {
     super::check_auth_required(&credential_state)?;
+    super::check_writable(&state)?;
 
     tracing::info!("Migration: Starting data import from {}", backup_zip_path);
 
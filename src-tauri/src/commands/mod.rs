@@ -2,10 +2,12 @@
 //!
 //! Command functions document their authentication requirements, serialized return
 //! contract, and the frontend module that invokes them. Keep privileged operations
-//! behind [`check_auth_required`] and restrict window-management commands with
-//! [`check_main_window`].
+//! behind [`check_auth_required`], restrict window-management commands with
+//! [`check_main_window`], and reject capture/deletion in guest mode with
+//! [`check_writable`].
 
 use crate::credential_manager::CredentialManagerState;
+use crate::storage::StorageState;
 
 /// Checks whether the current session requires re-authentication.
 pub fn check_auth_required(credential_state: &CredentialManagerState) -> Result<(), String> {
@@ -15,6 +17,14 @@ pub fn check_auth_required(credential_state: &CredentialManagerState) -> Result<
     Ok(())
 }
 
+/// Rejects commands that would modify history while running in read-only guest mode.
+pub fn check_writable(storage: &StorageState) -> Result<(), String> {
+    if storage.is_read_only() {
+        return Err("READ_ONLY_MODE".to_string());
+    }
+    Ok(())
+}
+
 /// Rejects calls that did not originate from CarbonPaper's main Tauri window.
 pub fn check_main_window(window: &tauri::Window) -> Result<(), String> {
     if window.label() != "main" {
@@ -26,6 +36,8 @@ pub fn check_main_window(window: &tauri::Window) -> Result<(), String> {
 pub mod credential;
 pub mod mcp;
 pub mod migration;
+pub mod scheduler;
+pub mod setup;
 pub mod smart_cluster;
 pub mod storage;
 pub mod utility;
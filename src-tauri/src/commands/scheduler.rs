@@ -0,0 +1,84 @@
+//! Tauri commands for inspecting and controlling background scheduled jobs.
+
+use super::check_auth_required;
+use crate::credential_manager::CredentialManagerState;
+use crate::scheduler::{self, JobStatus, SchedulerState};
+use crate::storage::StorageState;
+use std::sync::Arc;
+
+fn require_known_job(name: &str) -> Result<&'static scheduler::JobSpec, String> {
+    scheduler::find_job(name).ok_or_else(|| format!("Unknown scheduled job: {}", name))
+}
+
+/// Lists scheduled jobs with their interval, enabled flag, and last-run result.
+///
+/// Authentication: required. Returns an array of `JobStatus` objects.
+#[tauri::command]
+pub async fn scheduler_list_jobs(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    storage_state: tauri::State<'_, Arc<StorageState>>,
+    scheduler_state: tauri::State<'_, Arc<SchedulerState>>,
+) -> Result<Vec<JobStatus>, String> {
+    check_auth_required(&credential_state)?;
+
+    let storage = storage_state.inner().clone();
+    let scheduler = scheduler_state.inner().clone();
+    tokio::task::spawn_blocking(move || scheduler.job_statuses(&storage))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Enables or disables a scheduled job.
+///
+/// Authentication: required. Returns JSON `null`; fails for unknown job names.
+#[tauri::command]
+pub async fn scheduler_set_job_enabled(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    storage_state: tauri::State<'_, Arc<StorageState>>,
+    name: String,
+    enabled: bool,
+) -> Result<(), String> {
+    check_auth_required(&credential_state)?;
+    require_known_job(&name)?;
+
+    if !storage_state.set_scheduled_job_enabled(&name, enabled)? {
+        return Err(format!("Scheduled job not registered: {}", name));
+    }
+    tracing::info!("[SCHEDULER] {} enabled={}", name, enabled);
+    Ok(())
+}
+
+/// Changes how often a scheduled job runs.
+///
+/// Authentication: required. `interval_secs` must be at least 30. Returns JSON `null`.
+#[tauri::command]
+pub async fn scheduler_set_job_interval(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    storage_state: tauri::State<'_, Arc<StorageState>>,
+    name: String,
+    interval_secs: i64,
+) -> Result<(), String> {
+    check_auth_required(&credential_state)?;
+    require_known_job(&name)?;
+
+    if !storage_state.set_scheduled_job_interval(&name, interval_secs)? {
+        return Err(format!("Scheduled job not registered: {}", name));
+    }
+    Ok(())
+}
+
+/// Runs a scheduled job immediately, regardless of its schedule or enabled flag.
+///
+/// Authentication: required. Returns the run summary string, or `null` when the job
+/// had nothing to do. Fails if the job is already running.
+#[tauri::command]
+pub async fn scheduler_run_job(
+    app: tauri::AppHandle,
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    name: String,
+) -> Result<Option<String>, String> {
+    check_auth_required(&credential_state)?;
+    let spec = require_known_job(&name)?;
+
+    scheduler::run_job(app, spec).await
+}
@@ -0,0 +1,109 @@
+//! Tauri commands for the first-run setup state machine.
+
+use std::sync::Arc;
+
+use crate::capture::CaptureConfig;
+use crate::setup::{self, SetupState, SetupStep};
+use crate::storage::{
+    self, DataDirValidation, DataDrive, StorageState, StorageUsageEstimate, StoredImageCodec,
+};
+
+/// Active capture hours per day assumed by the usage estimate when none are given.
+const DEFAULT_ESTIMATE_ACTIVE_HOURS: f64 = 8.0;
+
+/// Returns the persisted first-run setup state, reconciled with what is on disk.
+///
+/// Authentication: not required because setup runs before credentials exist.
+/// Returns `SetupState`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn get_setup_state(app: tauri::AppHandle) -> Result<SetupState, String> {
+    setup::load_setup_state(&app).await
+}
+
+/// Runs one setup step and returns the updated state.
+///
+/// Authentication: not required; steps that install software apply their own
+/// bootstrap checks. `step` defaults to the current step and may name an earlier
+/// step to re-run it. A failing step is recorded in the returned state rather than
+/// returned as an error. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn advance_setup_step(
+    app: tauri::AppHandle,
+    window: tauri::Window,
+    step: Option<SetupStep>,
+    python_path: Option<String>,
+) -> Result<SetupState, String> {
+    super::check_main_window(&window)?;
+
+    setup::advance_setup(&app, &window, step, python_path).await
+}
+
+/// Lists mounted drives with their free space for the first-run data directory chooser.
+///
+/// Authentication: not required because setup runs before credentials exist.
+/// Returns `DataDrive[]`, largest free space first. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn setup_list_data_drives() -> Result<Vec<DataDrive>, String> {
+    tokio::task::spawn_blocking(storage::list_data_drives)
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))
+}
+
+/// Estimates monthly disk growth for the capture settings chosen during setup.
+///
+/// Authentication: not required. `codec` is `png` or `webp` (default `png`),
+/// `quality` defaults to 80, `interval_secs` to the capture default and
+/// `active_hours_per_day` to 8. Returns `StorageUsageEstimate`.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn setup_estimate_storage(
+    codec: Option<String>,
+    quality: Option<u32>,
+    interval_secs: Option<u64>,
+    active_hours_per_day: Option<f64>,
+) -> Result<StorageUsageEstimate, String> {
+    let codec = match codec {
+        Some(value) => StoredImageCodec::parse(&value)
+            .ok_or_else(|| format!("Unknown image codec: {}", value))?,
+        None => StoredImageCodec::Png,
+    };
+    Ok(storage::estimate_monthly_usage(
+        codec,
+        quality.unwrap_or(80).clamp(1, 100) as u8,
+        interval_secs.unwrap_or_else(|| CaptureConfig::default().interval_secs),
+        active_hours_per_day.unwrap_or(DEFAULT_ESTIMATE_ACTIVE_HOURS),
+    ))
+}
+
+/// Checks whether a storage root chosen during setup can hold the data directory.
+///
+/// Authentication: not required. `path` is the storage root; data lives in its
+/// `data` subdirectory. Nothing is created. Returns `DataDirValidation`.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn setup_validate_data_dir(path: String) -> Result<DataDirValidation, String> {
+    tokio::task::spawn_blocking(move || storage::validate_data_root(&path))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))
+}
+
+/// Uses `<path>/data` as the data directory before storage is first initialized.
+///
+/// Authentication: main-window origin required; no session exists yet. Fails with
+/// `DATA_DIR_ALREADY_INITIALIZED` once credentials or the database exist, in which
+/// case `storage_migrate_data_dir` must be used. Returns `DataDirValidation`.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn setup_set_data_dir(
+    window: tauri::Window,
+    state: tauri::State<'_, Arc<StorageState>>,
+    path: String,
+) -> Result<DataDirValidation, String> {
+    super::check_main_window(&window)?;
+    super::check_writable(&state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.set_initial_data_dir(&path))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
@@ -16,7 +16,7 @@ use crate::storage::smart_cluster::{
 use crate::storage::StorageState;
 use serde::{Deserialize, Serialize};
 
-use super::check_auth_required;
+use super::{check_auth_required, check_writable};
 
 /// Days of hot-layer screenshots to consider when backfilling on cluster
 /// creation. Matches `monitor/task_clustering.py::HOT_LAYER_DAYS` and the
@@ -96,6 +96,7 @@ pub async fn smart_cluster_create(
     req: CreateSmartClusterRequest,
 ) -> Result<CreateSmartClusterResponse, String> {
     check_auth_required(&credential_state)?;
+    check_writable(&state)?;
     let state = state.inner().clone();
     tokio::task::spawn_blocking(move || {
         let anchor = normalize_anchor_text(&req.anchor_text)?;
@@ -123,6 +124,7 @@ pub fn smart_cluster_delete(
     id: i64,
 ) -> Result<(), String> {
     check_auth_required(&credential_state)?;
+    check_writable(&state)?;
     state.delete_smart_cluster(id)
 }
 
@@ -137,6 +139,7 @@ pub fn smart_cluster_update_anchor(
     anchor: String,
 ) -> Result<(), String> {
     check_auth_required(&credential_state)?;
+    check_writable(&state)?;
     let anchor = normalize_anchor_text(&anchor)?;
     state.update_smart_cluster_anchor(id, &anchor)
 }
@@ -152,6 +155,7 @@ pub fn smart_cluster_update_threshold(
     threshold: f64,
 ) -> Result<(), String> {
     check_auth_required(&credential_state)?;
+    check_writable(&state)?;
     state.update_smart_cluster_threshold(id, threshold)
 }
 
@@ -166,6 +170,7 @@ pub fn smart_cluster_toggle_enabled(
     enabled: bool,
 ) -> Result<(), String> {
     check_auth_required(&credential_state)?;
+    check_writable(&state)?;
     state.update_smart_cluster_enabled(id, enabled)
 }
 
@@ -225,6 +230,7 @@ pub fn smart_cluster_upsert_summary(
     summary: SmartClusterSummaryUpsert,
 ) -> Result<SmartClusterSummaryRecord, String> {
     check_auth_required(&credential_state)?;
+    check_writable(&state)?;
     state.upsert_smart_cluster_summary(&summary)
 }
 
@@ -239,6 +245,7 @@ pub fn smart_cluster_delete_summary(
     cluster_id: i64,
 ) -> Result<bool, String> {
     check_auth_required(&credential_state)?;
+    check_writable(&state)?;
     state.delete_smart_cluster_summary(cluster_id)
 }
 
@@ -257,6 +264,7 @@ pub async fn smart_cluster_rescan(
     _cluster_id: i64,
 ) -> Result<i64, String> {
     check_auth_required(&credential_state)?;
+    check_writable(&state)?;
     let state = state.inner().clone();
     tokio::task::spawn_blocking(move || state.enqueue_pending_from_recent(HOT_LAYER_DAYS))
         .await
@@ -274,6 +282,7 @@ pub async fn smart_cluster_rescan_all(
     state: tauri::State<'_, Arc<StorageState>>,
 ) -> Result<i64, String> {
     check_auth_required(&credential_state)?;
+    check_writable(&state)?;
     let state = state.inner().clone();
     tokio::task::spawn_blocking(move || state.enqueue_pending_from_recent(HOT_LAYER_DAYS))
         .await
@@ -290,6 +299,7 @@ pub fn smart_cluster_clear_assignments(
     cluster_id: i64,
 ) -> Result<(), String> {
     check_auth_required(&credential_state)?;
+    check_writable(&state)?;
     state.clear_smart_cluster_assignments(cluster_id)
 }
 
@@ -5,14 +5,19 @@
 //! Screenshot and search wrappers live in `src/lib/monitor_api.js`; task and cluster
 //! wrappers live in `src/lib/task_api.js`.
 
-use super::check_auth_required;
+use super::{check_auth_required, check_writable};
 use crate::credential_manager::CredentialManagerState;
 use crate::monitor::{self, MonitorState};
-use crate::storage::{self, StorageState};
+use crate::storage::{
+    self, ComplianceStatus, ContextAt, GcReport, MonthShard, OcrTextEntry, OcrTextRules,
+    ParanoidModeStatus, PendingRecoverySummary, ProtectedRange, RangeArchiveImportSummary,
+    RangeArchiveSummary, RetentionPolicy, StorageQuota, StorageState, TrashEntry,
+};
 use once_cell::sync::Lazy;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tauri::Emitter;
 
 #[derive(Default, Clone)]
 struct ThumbnailWarmupProgress {
@@ -46,6 +51,14 @@ fn thumbnail_warmup_progress_json() -> serde_json::Value {
     })
 }
 
+/// Policy keys written only by their own commands; a policy update cannot set them.
+const RESERVED_POLICY_KEYS: [&str; 4] = [
+    crate::mcp_token::API_TOKENS_POLICY_KEY,
+    "mcp_token_encrypted",
+    crate::storage::SYNC_SECRET_POLICY_KEY,
+    crate::storage::SYNC_KEY_POLICY_KEY,
+];
+
 fn merge_policy_update(
     mut existing: serde_json::Value,
     update: serde_json::Value,
@@ -58,6 +71,9 @@ fn merge_policy_update(
         .ok_or_else(|| "Policy update is not a valid JSON object".to_string())?;
 
     for (key, value) in update_obj {
+        if RESERVED_POLICY_KEYS.contains(&key.as_str()) {
+            continue;
+        }
         existing_obj.insert(key.clone(), value.clone());
     }
 
@@ -67,7 +83,10 @@ fn merge_policy_update(
 fn redact_policy_for_frontend(policy: &mut serde_json::Value) {
     if let Some(obj) = policy.as_object_mut() {
         obj.remove("mcp_token_encrypted");
+        obj.remove(crate::storage::SYNC_SECRET_POLICY_KEY);
+        obj.remove(crate::storage::SYNC_KEY_POLICY_KEY);
     }
+    crate::mcp_token::redact_api_tokens(policy);
 }
 
 fn value_as_i64(value: Option<&serde_json::Value>) -> Option<i64> {
@@ -214,6 +233,31 @@ mod tests {
         assert_eq!(merged["sensitive_filter"]["enabled"], false);
     }
 
+    #[test]
+    fn merge_policy_update_cannot_overwrite_reserved_secrets() {
+        let existing = json!({
+            "api_tokens": [{ "id": "a" }],
+            "mcp_token_encrypted": "secret",
+            "sync_secret_encrypted": "sync-secret",
+            "storage_limit": "20"
+        });
+        let update = json!({
+            "api_tokens": [],
+            "mcp_token_encrypted": "forged",
+            "sync_secret_encrypted": null,
+            "sync_key_encrypted": "forged-key",
+            "storage_limit": "10"
+        });
+
+        let merged = merge_policy_update(existing, update).unwrap();
+
+        assert_eq!(merged["storage_limit"], "10");
+        assert_eq!(merged["api_tokens"][0]["id"], "a");
+        assert_eq!(merged["mcp_token_encrypted"], "secret");
+        assert_eq!(merged["sync_secret_encrypted"], "sync-secret");
+        assert!(merged.get("sync_key_encrypted").is_none());
+    }
+
     #[test]
     fn merge_policy_update_rejects_non_object_update() {
         let err = merge_policy_update(json!({}), json!(null)).unwrap_err();
@@ -330,18 +374,23 @@ mod tests {
     }
 }
 
-/// Returns timeline records between millisecond timestamps `start_time` and `end_time`.
+/// Returns one page of timeline records between millisecond timestamps `start_time`
+/// and `end_time`, oldest first.
 ///
-/// Authentication: required. `max_records` caps the result; returns an array of
-/// `ScreenshotRecord` objects. Frontend: `lib/monitor_api.js`.
+/// Authentication: required. `limit` defaults to 500 (max 2000). To load the next page
+/// pass `after_id` and `after_timestamp` from the previous page's `next_cursor`.
+/// Returns a `TimelinePage`; its `offline_months` names detached months in the range,
+/// whose records are not included. Frontend: `lib/monitor_api.js`.
 #[tauri::command]
 pub async fn storage_get_timeline(
     credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
     state: tauri::State<'_, Arc<StorageState>>,
     start_time: f64,
     end_time: f64,
-    max_records: Option<i64>,
-) -> Result<Vec<storage::ScreenshotRecord>, String> {
+    limit: Option<i64>,
+    after_id: Option<i64>,
+    after_timestamp: Option<i64>,
+) -> Result<storage::TimelinePage, String> {
     check_auth_required(&credential_state)?;
 
     let start_ts = if start_time > 10_000_000_000.0 {
@@ -354,19 +403,28 @@ pub async fn storage_get_timeline(
     } else {
         end_time
     };
+    let after = match (after_id, after_timestamp) {
+        (Some(after_id), Some(after_timestamp)) => Some(storage::TimelineCursor {
+            after_id,
+            after_timestamp,
+        }),
+        (None, None) => None,
+        _ => return Err("after_id and after_timestamp must be given together".to_string()),
+    };
+    let limit = limit.unwrap_or(500).clamp(1, 2000);
 
     let state = state.inner().clone();
-    tokio::task::spawn_blocking(move || {
-        state.get_screenshots_by_time_range_limited(start_ts, end_ts, max_records.or(Some(500)))
-    })
-    .await
-    .map_err(|e| format!("Task join error: {:?}", e))?
+    tokio::task::spawn_blocking(move || state.get_timeline_page(start_ts, end_ts, after, limit))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
 }
 
 /// Aggregates screenshot counts into `bucket_ms` timeline buckets.
 ///
 /// Authentication: required. Returns an array of `DensityBucket` objects for the
-/// requested millisecond range. Frontend: `lib/monitor_api.js`.
+/// requested millisecond range, each with its screenshot count and dominant process.
+/// Nothing is decrypted, so zoomed-out timeline views stay cheap. Frontend:
+/// `lib/monitor_api.js`.
 #[tauri::command]
 pub async fn storage_get_timeline_density(
     credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
@@ -398,10 +456,53 @@ pub async fn storage_get_timeline_density(
     .map_err(|e| format!("Task join error: {:?}", e))?
 }
 
-/// Searches OCR records with pagination, fuzzy matching, process, time, and category filters.
+/// Returns activity sessions (consecutive screenshots of one process) overlapping a range.
+///
+/// Authentication: required. `start` and `end` accept seconds or milliseconds.
+/// Screenshots captured since the last call are segmented first. Returns an array of
+/// `ActivitySession` objects, oldest first. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_get_sessions(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    start: f64,
+    end: f64,
+) -> Result<Vec<storage::ActivitySession>, String> {
+    check_auth_required(&credential_state)?;
+
+    let start_ts = if start > 10_000_000_000.0 {
+        start / 1000.0
+    } else {
+        start
+    };
+    let end_ts = if end > 10_000_000_000.0 {
+        end / 1000.0
+    } else {
+        end
+    };
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.get_activity_sessions(start_ts, end_ts))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Searches OCR records with pagination, fuzzy matching, process, time, category, and tag
+/// filters. `query` accepts `a OR b`, `-term` / `NOT term` exclusions and `"quoted
+/// phrases"`; plain whitespace-separated terms must all match. Terms also match the
+/// page URL and link text of browser captures. `domain:example.com` and
+/// `title:report` (or `-domain:` / `-title:`) keep (or drop) captures of that site or
+/// with that text in the window title.
 ///
 /// Authentication: required. Returns an array of `SearchResult` objects; optional
-/// filters are omitted as JSON `null`. Frontend: `lib/monitor_api.js`.
+/// filters are omitted as JSON `null`. `sort_by` is `recent` (default) or
+/// `relevance`; `match_mode: "phrase"` matches the query literally instead, and
+/// `"regex"` as a regular expression (an invalid pattern is an error). With
+/// `include_thumbnails`, each result carries its cached thumbnail inline so the grid
+/// needs no follow-up image calls. `min_confidence` (0-1) ignores text boxes the OCR
+/// engine scored below it. `record_history` adds the query to the search
+/// history when fetching the first page; callers set it only for submitted queries,
+/// not for search-as-you-type. Frontend: `lib/monitor_api.js`.
 #[tauri::command]
 pub async fn storage_search(
     credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
@@ -414,6 +515,12 @@ pub async fn storage_search(
     start_time: Option<f64>,
     end_time: Option<f64>,
     categories: Option<Vec<String>>,
+    tags: Option<Vec<String>>,
+    include_thumbnails: Option<bool>,
+    sort_by: Option<storage::SearchSortBy>,
+    match_mode: Option<storage::SearchMatchMode>,
+    min_confidence: Option<f64>,
+    record_history: Option<bool>,
 ) -> Result<Vec<storage::SearchResult>, String> {
     check_auth_required(&credential_state)?;
 
@@ -422,7 +529,7 @@ pub async fn storage_search(
     let offset = offset.unwrap_or(0);
     let fuzzy = fuzzy.unwrap_or(true);
     tokio::task::spawn_blocking(move || {
-        state.search_text(
+        let mut results = state.search_text(
             &query,
             limit,
             offset,
@@ -431,16 +538,240 @@ pub async fn storage_search(
             start_time,
             end_time,
             categories,
+            tags,
+            sort_by.unwrap_or_default(),
+            match_mode.unwrap_or_default(),
+            min_confidence,
+        )?;
+        if !results.is_empty() {
+            let mut screenshot_ids: Vec<i64> = results.iter().map(|r| r.screenshot_id).collect();
+            screenshot_ids.dedup();
+            state.record_access_audit(
+                "search",
+                &serde_json::json!({ "screenshot_ids": screenshot_ids }),
+            )?;
+        }
+        if include_thumbnails.unwrap_or(false) {
+            state.attach_search_thumbnails(&mut results);
+        }
+        if record_history.unwrap_or(false) && offset == 0 && !state.is_read_only() {
+            if let Err(e) = state.record_search_query(&query) {
+                tracing::warn!("Failed to record search history: {}", e);
+            }
+        }
+        Ok(results)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Counts the matches of a search query with per-process and per-day facets.
+///
+/// Authentication: required. Takes the same query and filters as `storage_search`
+/// and returns a `SearchCount`; the process facet ignores `process_names`.
+/// `offline_months` lists detached months in the range, which are not searched.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_search_count(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    query: String,
+    fuzzy: Option<bool>,
+    process_names: Option<Vec<String>>,
+    start_time: Option<f64>,
+    end_time: Option<f64>,
+    categories: Option<Vec<String>>,
+    tags: Option<Vec<String>>,
+    match_mode: Option<storage::SearchMatchMode>,
+    min_confidence: Option<f64>,
+) -> Result<storage::SearchCount, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    let fuzzy = fuzzy.unwrap_or(true);
+    tokio::task::spawn_blocking(move || {
+        let mut count = state.count_search_matches(
+            &query,
+            fuzzy,
+            process_names,
+            start_time,
+            end_time,
+            categories,
+            tags,
+            match_mode.unwrap_or_default(),
+            min_confidence,
+        )?;
+        count.offline_months = state.offline_months_between(start_time, end_time)?;
+        Ok(count)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Finds distinct window titles containing `query`, ignoring case and spacing.
+///
+/// Authentication: required. `limit` defaults to 50 (max 200). Returns
+/// `TitleSearchResult[]`, most recently seen first, each with its screenshot count
+/// and newest screenshot. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_search_titles(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    query: String,
+    limit: Option<u32>,
+    offset: Option<u32>,
+) -> Result<Vec<storage::TitleSearchResult>, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    let limit = limit.unwrap_or(50).min(200) as usize;
+    let offset = offset.unwrap_or(0) as usize;
+    tokio::task::spawn_blocking(move || {
+        let results = state.search_window_titles(&query, limit, offset)?;
+        if !results.is_empty() {
+            let screenshot_ids: Vec<i64> = results.iter().map(|r| r.latest_screenshot_id).collect();
+            state.record_access_audit(
+                "search_titles",
+                &serde_json::json!({ "screenshot_ids": screenshot_ids }),
+            )?;
+        }
+        Ok(results)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Lists recently submitted search queries, newest first.
+///
+/// Authentication: required. `limit` defaults to 50 (max 200). Returns
+/// `SearchHistoryEntry[]`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_get_search_history(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    limit: Option<u32>,
+) -> Result<Vec<storage::SearchHistoryEntry>, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    let limit = limit.unwrap_or(50).min(200) as usize;
+    tokio::task::spawn_blocking(move || state.get_search_history(limit))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Completes a partial query from the search history.
+///
+/// Authentication: required. Matches past queries starting with `prefix`, ignoring
+/// case and spacing; most used first. `limit` defaults to 8 (max 50). Returns the
+/// suggested queries. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_suggest_search_queries(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    prefix: String,
+    limit: Option<u32>,
+) -> Result<Vec<String>, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    let limit = limit.unwrap_or(8).min(50) as usize;
+    tokio::task::spawn_blocking(move || state.suggest_search_queries(&prefix, limit))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Deletes the whole search history.
+///
+/// Authentication: required. Returns the number of queries removed.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_clear_search_history(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+) -> Result<usize, String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.clear_search_history())
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Saves a search query, optionally alerting when new screenshots match it.
+///
+/// Authentication: required. `match_mode` defaults to `terms` and `notify` to
+/// `true`; an empty `name` defaults to the query. While `notify` is set, each newly
+/// committed screenshot is checked against the query, and a match emits
+/// `saved-search-matched` with a `SavedSearchMatch` plus a native notification.
+/// Returns the new `SavedSearch`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_create_saved_search(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    name: Option<String>,
+    query: String,
+    match_mode: Option<storage::SearchMatchMode>,
+    notify: Option<bool>,
+) -> Result<storage::SavedSearch, String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        state.create_saved_search(
+            name.as_deref().unwrap_or(""),
+            &query,
+            match_mode.unwrap_or_default(),
+            notify.unwrap_or(true),
         )
     })
     .await
     .map_err(|e| format!("Task join error: {:?}", e))?
 }
 
+/// Lists saved searches, oldest first.
+///
+/// Authentication: required. Returns `SavedSearch[]`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_list_saved_searches(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+) -> Result<Vec<storage::SavedSearch>, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.list_saved_searches())
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Deletes a saved search.
+///
+/// Authentication: required. Returns `true` when it existed.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_delete_saved_search(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    saved_search_id: i64,
+) -> Result<bool, String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.delete_saved_search(saved_search_id))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
 /// Loads and decrypts a full screenshot selected by `id` or legacy `path`.
 ///
 /// Authentication: required. Exactly one selector should be supplied. Returns a status
-/// object containing image data and metadata. Frontend: `lib/monitor_api.js`.
+/// object containing base64 image data and metadata. The frontend loads images by id
+/// through the `carbonpaper-img://` protocol (`image_protocol.rs`) and only uses this
+/// command for legacy paths. Frontend: `lib/monitor_api.js`.
 #[tauri::command]
 pub async fn storage_get_image(
     credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
@@ -452,14 +783,23 @@ pub async fn storage_get_image(
 
     let state = state.inner().clone();
     tokio::task::spawn_blocking(move || {
-        let image_path = if let Some(id) = id {
-            let record = state.get_screenshot_by_id(id)?;
-            record.map(|r| r.image_path)
-        } else {
-            path
-        };
+        if let Some(id) = id {
+            state.record_access_audit("view_image", &serde_json::json!({ "screenshot_id": id }))?;
+            let (image_data, mime_type) = state.read_screenshot_image(id)?;
+            return Ok(serde_json::json!({
+                "status": "success",
+                "data": base64::Engine::encode(
+                    &base64::engine::general_purpose::STANDARD,
+                    &image_data
+                ),
+                "mime_type": mime_type
+            }));
+        }
+        if let Some(ref p) = path {
+            state.record_access_audit("view_image", &serde_json::json!({ "image_path": p }))?;
+        }
 
-        match image_path {
+        match path {
             Some(path) => match state.read_image(&path) {
                 Ok((data, mime_type)) => Ok(serde_json::json!({
                     "status": "success",
@@ -491,9 +831,19 @@ pub async fn storage_get_thumbnail(
     let state = state.inner().clone();
     tokio::task::spawn_blocking(move || {
         let image_path = if let Some(id) = id {
+            state.record_access_audit(
+                "view_thumbnail",
+                &serde_json::json!({ "screenshot_id": id }),
+            )?;
             let record = state.get_screenshot_by_id(id)?;
             record.map(|r| r.image_path)
         } else {
+            if let Some(ref p) = path {
+                state.record_access_audit(
+                    "view_thumbnail",
+                    &serde_json::json!({ "image_path": p }),
+                )?;
+            }
             path
         };
 
@@ -527,6 +877,10 @@ pub async fn storage_batch_get_thumbnails(
 
     let state = state.inner().clone();
     tokio::task::spawn_blocking(move || {
+        state.record_access_audit(
+            "view_thumbnails",
+            &serde_json::json!({ "screenshot_ids": ids }),
+        )?;
         let results_map = state.batch_read_thumbnails_by_ids(&ids);
 
         let mut results = serde_json::Map::new();
@@ -623,6 +977,20 @@ pub async fn storage_warmup_thumbnails(
             }
         };
 
+        if let Err(e) = state.record_access_audit(
+            "warmup_thumbnails",
+            &serde_json::json!({ "image_count": paths.len() }),
+        ) {
+            tracing::warn!("[Warmup] Not starting without an audit entry: {}", e);
+            let mut progress = THUMBNAIL_WARMUP_PROGRESS
+                .lock()
+                .unwrap_or_else(|poison| poison.into_inner());
+            progress.running = false;
+            progress.errors += 1;
+            THUMBNAIL_WARMUP_RUNNING.store(false, Ordering::SeqCst);
+            return;
+        }
+
         {
             let mut progress = THUMBNAIL_WARMUP_PROGRESS
                 .lock()
@@ -704,6 +1072,26 @@ pub async fn storage_warmup_thumbnails(
     }))
 }
 
+/// Returns a pixelated, unreadable preview of screenshot `id` for locked views.
+///
+/// Authentication: not required, except in compliance mode; the preview is a tiny
+/// downscaled copy kept under the database key, so text cannot be recovered from it. `blur_level` (0-3, default 1)
+/// halves the block grid per level. Returns `InlineThumbnail`, or `null` when the
+/// screenshot has no preview yet or paranoid/compliance mode is on. Frontend:
+/// `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_get_image_preview(
+    state: tauri::State<'_, Arc<StorageState>>,
+    id: i64,
+    blur_level: Option<u8>,
+) -> Result<Option<storage::InlineThumbnail>, String> {
+    state.check_preview_access()?;
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.get_image_preview(id, blur_level.unwrap_or(1)))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
 /// Returns the current thumbnail warmup progress object.
 ///
 /// Authentication: required. The object includes running, totals, processed counts,
@@ -737,14 +1125,16 @@ pub fn storage_cancel_thumbnail_warmup(
 
 /// Returns a screenshot record and all associated OCR rows selected by `id` or `path`.
 ///
-/// Authentication: required. Returns `{ "status": "success" | "not_found",
-/// "record", "ocr_results" }`. Frontend: `lib/monitor_api.js`.
+/// Authentication: required. `min_confidence` (0-1) leaves out OCR rows scored below
+/// it, for hiding noise in the detail overlay. Returns `{ "status": "success" | "not_found",
+/// "record", "ocr_results", "pinned" }`. Frontend: `lib/monitor_api.js`.
 #[tauri::command]
 pub async fn storage_get_screenshot_details(
     credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
     state: tauri::State<'_, Arc<StorageState>>,
     id: Option<i64>,
     path: Option<String>,
+    min_confidence: Option<f64>,
 ) -> Result<serde_json::Value, String> {
     check_auth_required(&credential_state)?;
 
@@ -760,11 +1150,17 @@ pub async fn storage_get_screenshot_details(
 
         match &record {
             Some(r) => {
-                let ocr_results = state.get_screenshot_ocr_results(r.id)?;
+                state.record_access_audit(
+                    "view_details",
+                    &serde_json::json!({ "screenshot_id": r.id }),
+                )?;
+                let ocr_results = state.get_screenshot_ocr_results(r.id, min_confidence)?;
+                let pinned = state.is_screenshot_pinned(r.id)?;
                 Ok(serde_json::json!({
                     "status": "success",
                     "record": record,
-                    "ocr_results": ocr_results
+                    "ocr_results": ocr_results,
+                    "pinned": pinned
                 }))
             }
             None => Ok(serde_json::json!({
@@ -778,11 +1174,39 @@ pub async fn storage_get_screenshot_details(
     .map_err(|e| format!("Task join error: {:?}", e))?
 }
 
-/// Permanently deletes one screenshot and asks the vector index to remove its embedding.
+/// Returns the decrypted OCR text of up to 500 screenshots in one call.
 ///
-/// Authentication: required. `screenshot_id` identifies the record. Returns
-/// `{ "status": "success", "deleted": boolean, "vector_deleted": number | null }`.
-/// Frontend: `lib/monitor_api.js`.
+/// Authentication: required. `max_chars` caps each text (default 8000 characters).
+/// Returns `[{ "screenshot_id", "text", "truncated" }]` in the order of
+/// `screenshot_ids`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_get_ocr_texts(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    screenshot_ids: Vec<i64>,
+    max_chars: Option<usize>,
+) -> Result<Vec<OcrTextEntry>, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let entries = state.get_ocr_texts(&screenshot_ids, max_chars)?;
+        state.record_access_audit(
+            "view_ocr_texts",
+            &serde_json::json!({ "screenshot_ids": screenshot_ids }),
+        )?;
+        Ok(entries)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Deletes one screenshot and asks the vector index to remove its embedding.
+///
+/// Authentication: required. `screenshot_id` identifies the record. The screenshot moves
+/// to the trash while `trash_retention_days` is above zero and is deleted permanently
+/// otherwise. Returns `{ "status": "success", "deleted": boolean, "trashed": boolean,
+/// "vector_deleted": number | null }`. Frontend: `lib/monitor_api.js`.
 #[tauri::command]
 pub async fn storage_delete_screenshot(
     credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
@@ -791,13 +1215,19 @@ pub async fn storage_delete_screenshot(
     screenshot_id: i64,
 ) -> Result<serde_json::Value, String> {
     check_auth_required(&credential_state)?;
+    check_writable(&state)?;
 
     let image_hash = match state.get_screenshot_by_id(screenshot_id)? {
         Some(record) => Some(record.image_hash),
         None => None,
     };
 
-    let deleted = state.delete_screenshot(screenshot_id)?;
+    let trashed = state.trash_retention_days() > 0;
+    let deleted = if trashed {
+        state.move_screenshot_to_trash(screenshot_id)?
+    } else {
+        state.delete_screenshot(screenshot_id)?
+    };
     let mut vector_deleted: Option<i64> = None;
 
     if deleted {
@@ -820,14 +1250,85 @@ pub async fn storage_delete_screenshot(
     Ok(serde_json::json!({
         "status": "success",
         "deleted": deleted,
+        "trashed": deleted && trashed,
         "vector_deleted": vector_deleted
     }))
 }
 
+/// Restores a screenshot from the trash.
+///
+/// Authentication: required. Rejected in read-only mode. Its OCR rows come back and its
+/// vector embedding is rebuilt by OCR postprocessing. Returns
+/// `{ "status": "success", "restored": boolean }`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_restore_screenshot(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    screenshot_id: i64,
+) -> Result<serde_json::Value, String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    let state = state.inner().clone();
+    let restored = tokio::task::spawn_blocking(move || state.restore_screenshot(screenshot_id))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))??;
+    Ok(serde_json::json!({
+        "status": "success",
+        "restored": restored
+    }))
+}
+
+/// Lists the screenshots in the trash, most recently deleted first.
+///
+/// Authentication: required. `limit` defaults to 100 (max 1000). Returns
+/// `TrashEntry[]`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_list_trash(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Vec<TrashEntry>, String> {
+    check_auth_required(&credential_state)?;
+
+    let limit = limit.unwrap_or(100).clamp(1, 1000);
+    let offset = offset.unwrap_or(0).max(0);
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.list_trash(limit, offset))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Permanently deletes every screenshot in the trash.
+///
+/// Authentication: required. Rejected in read-only mode. Files and rows are removed by
+/// the delete queue in the background. Returns `{ "status": "success", "purged": number }`.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_empty_trash(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+) -> Result<serde_json::Value, String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    let state = state.inner().clone();
+    let purged = tokio::task::spawn_blocking(move || state.empty_trash())
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))??;
+    Ok(serde_json::json!({
+        "status": "success",
+        "purged": purged
+    }))
+}
+
 /// Permanently deletes screenshots in the requested millisecond time range.
 ///
-/// Authentication: required. Returns `{ "status": "success", "deleted_count": number,
-/// "vector_deleted": number | null }`. Frontend: `lib/monitor_api.js`.
+/// Authentication: required. Pinned screenshots and screenshots in a protected range
+/// are kept unless `force` is `true`. Returns `{ "status": "success", "deleted_count":
+/// number, "pinned_kept": number, "protected_kept": number, "vector_deleted": number |
+/// null }`. Frontend: `lib/monitor_api.js`.
 #[tauri::command]
 pub async fn storage_delete_by_time_range(
     credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
@@ -835,31 +1336,21 @@ pub async fn storage_delete_by_time_range(
     monitor_state: tauri::State<'_, MonitorState>,
     start_time: f64,
     end_time: f64,
+    force: Option<bool>,
 ) -> Result<serde_json::Value, String> {
     check_auth_required(&credential_state)?;
+    check_writable(&state)?;
 
-    let start_ts = start_time / 1000.0;
-    let end_ts = end_time / 1000.0;
-    let image_hashes = match state.get_screenshots_by_time_range(start_ts, end_ts) {
-        Ok(records) => records
-            .into_iter()
-            .map(|r| r.image_hash)
-            .collect::<Vec<_>>(),
-        Err(e) => {
-            tracing::error!("Failed to load hashes: {}", e);
-            Vec::new()
-        }
-    };
-
-    let deleted_count = state.delete_screenshots_by_time_range(start_time, end_time)?;
+    let force = force.unwrap_or(false);
+    let result = state.delete_screenshots_by_time_range(start_time, end_time, force, force)?;
     let mut vector_deleted: Option<i64> = None;
 
-    if !image_hashes.is_empty() {
+    if !result.image_hashes.is_empty() {
         let payload = serde_json::json!({
             "command": "delete_by_time_range",
             "start_time": start_time,
             "end_time": end_time,
-            "image_hashes": image_hashes
+            "image_hashes": result.image_hashes
         });
 
         match monitor::forward_command_to_python(&monitor_state, payload).await {
@@ -873,14 +1364,77 @@ pub async fn storage_delete_by_time_range(
     }
     Ok(serde_json::json!({
         "status": "success",
-        "deleted_count": deleted_count,
+        "deleted_count": result.deleted,
+        "pinned_kept": result.pinned_kept,
+        "protected_kept": result.protected_kept,
         "vector_deleted": vector_deleted
     }))
 }
 
-/// Lists distinct process names and their screenshot counts.
+/// Protects the screenshots captured within the millisecond range `[start_time,
+/// end_time]` from retention cleanup and non-forced time-range deletion.
 ///
-/// Authentication: required. Returns `[{ "process_name": string, "count": number }]`.
+/// Authentication: required. Rejected in read-only mode. `label` is an optional note.
+/// Returns the new `ProtectedRange`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_protect_range(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    start_time: f64,
+    end_time: f64,
+    label: Option<String>,
+) -> Result<ProtectedRange, String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.protect_range(start_time, end_time, label.as_deref()))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Lists the protected ranges, earliest first.
+///
+/// Authentication: required. Returns `ProtectedRange[]`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_list_protected_ranges(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+) -> Result<Vec<ProtectedRange>, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.list_protected_ranges())
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Lifts a protected range; its screenshots fall back under the normal retention rules.
+///
+/// Authentication: required. Rejected in read-only mode. Returns
+/// `{ "status": "success", "removed": boolean }`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_unprotect_range(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    range_id: i64,
+) -> Result<serde_json::Value, String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    let state = state.inner().clone();
+    let removed = tokio::task::spawn_blocking(move || state.remove_protected_range(range_id))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))??;
+    Ok(serde_json::json!({
+        "status": "success",
+        "removed": removed
+    }))
+}
+
+/// Lists distinct process names and their screenshot counts.
+///
+/// Authentication: required. Returns `[{ "process_name": string, "count": number }]`.
 /// Frontend: `lib/monitor_api.js`.
 #[tauri::command]
 pub async fn storage_list_processes(
@@ -918,6 +1472,120 @@ pub async fn storage_get_process_stats(
         .map_err(|e| format!("Task join error: {:?}", e))?
 }
 
+/// Returns per-day screenshot counts, grouping each screenshot by the local day in
+/// the time zone that was active when it was captured.
+///
+/// Authentication: required. `start` and `end` accept seconds or milliseconds.
+/// Returns an array of `CaptureLocalDay` objects in ascending day order.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_get_capture_days(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    start: f64,
+    end: f64,
+) -> Result<Vec<storage::CaptureLocalDay>, String> {
+    check_auth_required(&credential_state)?;
+
+    let start_ts = if start > 10_000_000_000.0 {
+        start / 1000.0
+    } else {
+        start
+    };
+    let end_ts = if end > 10_000_000_000.0 {
+        end / 1000.0
+    } else {
+        end
+    };
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.get_capture_days(start_ts, end_ts))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Returns the screenshots captured on a local day (`YYYY-MM-DD`) in the time zone
+/// active at capture time, so trips abroad stay on the day they happened.
+///
+/// Authentication: required. Returns `ScreenshotRecord[]` in capture order.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_get_screenshots_by_capture_day(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    day: String,
+) -> Result<Vec<storage::ScreenshotRecord>, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.get_screenshots_by_capture_day(&day))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Returns encrypted-image bytes and OCR row counts per local day and per app.
+///
+/// Authentication: required. `start` and `end` accept seconds or milliseconds.
+/// Returns an array of `DailyStorageUsage` objects in ascending day order.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_get_usage_by_day(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    start: f64,
+    end: f64,
+) -> Result<Vec<storage::DailyStorageUsage>, String> {
+    check_auth_required(&credential_state)?;
+
+    let start_ts = if start > 10_000_000_000.0 {
+        start / 1000.0
+    } else {
+        start
+    };
+    let end_ts = if end > 10_000_000_000.0 {
+        end / 1000.0
+    } else {
+        end
+    };
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.get_storage_usage_by_day(start_ts, end_ts))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Returns the storage dashboard summary: counts, database and index sizes, and usage
+/// broken down by day and by process.
+///
+/// Authentication: required. `start` and `end` accept seconds or milliseconds and
+/// default to all time. Returns a `StorageStats` object. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_get_stats(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    start: Option<f64>,
+    end: Option<f64>,
+) -> Result<storage::StorageStats, String> {
+    check_auth_required(&credential_state)?;
+
+    let to_secs = |ts: f64| {
+        if ts > 10_000_000_000.0 {
+            ts / 1000.0
+        } else {
+            ts
+        }
+    };
+    let start_ts = start.map(to_secs).unwrap_or(0.0);
+    let end_ts = end
+        .map(to_secs)
+        .unwrap_or_else(|| chrono::Utc::now().timestamp() as f64);
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.get_storage_stats(start_ts, end_ts))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
 /// Returns a paginated month/thumbnail summary for `process_name`.
 ///
 /// Authentication: required. `page` defaults to 0 and `page_size` to 60; returns a
@@ -934,6 +1602,10 @@ pub async fn storage_get_process_monthly_thumbnails(
 
     let state = state.inner().clone();
     tokio::task::spawn_blocking(move || {
+        state.record_access_audit(
+            "view_thumbnails",
+            &serde_json::json!({ "process_name": process_name, "page": page.unwrap_or(0) }),
+        )?;
         state.get_process_monthly_thumbnails(
             &process_name,
             page.unwrap_or(0),
@@ -955,6 +1627,7 @@ pub async fn storage_soft_delete(
     month: Option<String>,
 ) -> Result<storage::SoftDeleteResult, String> {
     check_auth_required(&credential_state)?;
+    check_writable(&state)?;
 
     let state = state.inner().clone();
     tokio::task::spawn_blocking(move || {
@@ -975,6 +1648,7 @@ pub async fn storage_soft_delete_screenshots(
     screenshot_ids: Vec<i64>,
 ) -> Result<storage::SoftDeleteScreenshotsResult, String> {
     check_auth_required(&credential_state)?;
+    check_writable(&state)?;
 
     let state = state.inner().clone();
     tokio::task::spawn_blocking(move || state.soft_delete_screenshots(&screenshot_ids))
@@ -982,151 +1656,888 @@ pub async fn storage_soft_delete_screenshots(
         .map_err(|e| format!("Task join error: {:?}", e))?
 }
 
-/// Returns pending and completed soft-delete queue counts.
+/// Applies one operation to a selection of screenshots (e.g. all search results).
 ///
-/// Authentication: required. Returns `DeleteQueueStatus`.
+/// Authentication: required. `operation` is `{ "op": "delete" }`; the database changes
+/// run in a single transaction and removed screenshots are dropped from the vector
+/// index with one `delete_screenshots` IPC call. Returns `BatchOperationResult`.
 /// Frontend: `lib/monitor_api.js`.
 #[tauri::command]
-pub async fn storage_get_delete_queue_status(
+pub async fn storage_batch_apply(
     credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
     state: tauri::State<'_, Arc<StorageState>>,
-) -> Result<storage::DeleteQueueStatus, String> {
+    monitor_state: tauri::State<'_, MonitorState>,
+    screenshot_ids: Vec<i64>,
+    operation: storage::BatchScreenshotOperation,
+) -> Result<storage::BatchOperationResult, String> {
     check_auth_required(&credential_state)?;
+    check_writable(&state)?;
 
     let state = state.inner().clone();
-    tokio::task::spawn_blocking(move || state.get_delete_queue_status())
+    let (mut result, removed_hashes) = tokio::task::spawn_blocking(move || {
+        state.apply_batch_operation(&screenshot_ids, &operation)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))??;
+
+    if !removed_hashes.is_empty() {
+        let payload = serde_json::json!({
+            "command": "delete_screenshots",
+            "image_hashes": removed_hashes
+        });
+        match monitor::forward_command_to_python(&monitor_state, payload).await {
+            Ok(resp) => {
+                result.vector_deleted = resp.get("vector_deleted").and_then(|v| v.as_i64());
+            }
+            Err(e) => {
+                tracing::error!("Vector delete failed: {}", e);
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Attaches a user tag to one screenshot.
+///
+/// Authentication: required. Tags are trimmed, at most 64 characters and matched
+/// case-insensitively. Returns `true` when added, `false` when already present.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_add_tag(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    screenshot_id: i64,
+    tag: String,
+) -> Result<bool, String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.add_tag(screenshot_id, &tag))
         .await
         .map_err(|e| format!("Task join error: {:?}", e))?
 }
 
-/// Combines encrypted-storage index statistics with Python vector-index health.
+/// Removes a user tag from one screenshot.
 ///
-/// Authentication: required. `refresh_vector` requests a live vector recount. Returns a
-/// JSON health object for both stores. Frontend: `lib/monitor_api.js`.
+/// Authentication: required. Returns `true` when the tag was removed.
+/// Frontend: `lib/monitor_api.js`.
 #[tauri::command]
-pub async fn storage_get_index_health(
+pub async fn storage_remove_tag(
     credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
     state: tauri::State<'_, Arc<StorageState>>,
-    monitor_state: tauri::State<'_, MonitorState>,
-    refresh_vector: Option<bool>,
-) -> Result<serde_json::Value, String> {
+    screenshot_id: i64,
+    tag: String,
+) -> Result<bool, String> {
     check_auth_required(&credential_state)?;
+    check_writable(&state)?;
 
-    let storage_state = state.inner().clone();
-    let storage_stats =
-        tokio::task::spawn_blocking(move || storage_state.get_index_storage_stats())
-            .await
-            .map_err(|e| format!("Task join error: {:?}", e))??;
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.remove_tag(screenshot_id, &tag))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
 
-    let monitor_health = monitor::forward_command_to_python(
-        &monitor_state,
-        serde_json::json!({
-            "command": "index_health",
-            "refresh": refresh_vector.unwrap_or(false),
-        }),
-    )
-    .await;
+/// Lists user tags with their screenshot counts, or the tags of one screenshot.
+///
+/// Authentication: required. Returns `TagCount[]`, most used first.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_list_tags(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    screenshot_id: Option<i64>,
+) -> Result<Vec<storage::TagCount>, String> {
+    check_auth_required(&credential_state)?;
 
-    Ok(compose_index_health_response(storage_stats, monitor_health))
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.list_tags(screenshot_id))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
 }
 
-/// Retries failed vector indexing through the monitor service.
+/// Returns screenshots carrying `tag`, newest first.
 ///
-/// Authentication: required. `limit` defaults to 32 and is clamped to 1..=256; returns
-/// the monitor's retry result object. Frontend: `lib/monitor_api.js`.
+/// Authentication: required. `limit` defaults to 100 (max 500) and `offset` to 0.
+/// Returns an array of `ScreenshotRecord` objects. Frontend: `lib/monitor_api.js`.
 #[tauri::command]
-pub async fn storage_retry_vector_indexing(
+pub async fn storage_search_by_tag(
     credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
-    monitor_state: tauri::State<'_, MonitorState>,
-    limit: Option<u32>,
-) -> Result<serde_json::Value, String> {
+    state: tauri::State<'_, Arc<StorageState>>,
+    tag: String,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Vec<storage::ScreenshotRecord>, String> {
     check_auth_required(&credential_state)?;
 
-    monitor::forward_command_to_python(
-        &monitor_state,
-        serde_json::json!({
-            "command": "retry_vector_indexing",
-            "limit": limit.unwrap_or(32).clamp(1, 256),
-        }),
-    )
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        state.search_by_tag(&tag, limit.unwrap_or(100), offset.unwrap_or(0))
+    })
     .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
 }
 
-/// Persists a screenshot and its metadata from a trusted native producer.
+/// Attaches an encrypted note to one screenshot.
 ///
-/// Authentication: required. `request` is `SaveScreenshotRequest`; returns
-/// `SaveScreenshotResponse`. This command is registered for internal/native callers.
+/// Authentication: required. Text is trimmed and at most 10,000 characters; it is
+/// added to the annotation search index. Returns the new `Annotation`.
+/// Frontend: `lib/monitor_api.js`.
 #[tauri::command]
-pub async fn storage_save_screenshot(
+pub async fn storage_add_annotation(
     credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
     state: tauri::State<'_, Arc<StorageState>>,
-    request: storage::SaveScreenshotRequest,
-) -> Result<storage::SaveScreenshotResponse, String> {
+    screenshot_id: i64,
+    text: String,
+) -> Result<storage::Annotation, String> {
     check_auth_required(&credential_state)?;
+    check_writable(&state)?;
 
-    state.save_screenshot(&request)
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.add_annotation(screenshot_id, &text))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
 }
 
-/// Scores visible links using aggregate storage statistics.
+/// Replaces the recognized text of one OCR box with a user correction.
 ///
-/// Authentication: not required; input contains link features rather than stored user
-/// records. Returns an array of `ScoredLink` objects for the browser integration.
+/// Authentication: required. The old text's search postings are replaced by the new
+/// one's. Returns the updated `OcrResult`. Frontend: `lib/monitor_api.js`.
 #[tauri::command]
-pub async fn storage_compute_link_scores(
+pub async fn storage_update_ocr_text(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
     state: tauri::State<'_, Arc<StorageState>>,
-    links: Vec<storage::VisibleLink>,
-) -> Result<Vec<storage::ScoredLink>, String> {
-    state.compute_link_scores(&links)
+    ocr_id: i64,
+    new_text: String,
+) -> Result<storage::OcrResult, String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.update_ocr_text(ocr_id, &new_text))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
 }
 
-/// Returns the storage encryption public key as standard Base64.
+/// Replaces the text of a note.
 ///
-/// Authentication: not required because the public key cannot decrypt data. Returns a
-/// JSON string for trusted native/browser producers.
+/// Authentication: required. Returns the updated `Annotation`.
+/// Frontend: `lib/monitor_api.js`.
 #[tauri::command]
-pub async fn storage_get_public_key(
+pub async fn storage_update_annotation(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
     state: tauri::State<'_, Arc<StorageState>>,
-) -> Result<String, String> {
-    let key = state.get_public_key()?;
-    Ok(base64::Engine::encode(
-        &base64::engine::general_purpose::STANDARD,
-        &key,
-    ))
+    annotation_id: i64,
+    text: String,
+) -> Result<storage::Annotation, String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.update_annotation(annotation_id, &text))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
 }
 
-/// Merges and persists a partial storage policy update.
+/// Deletes a note.
 ///
-/// Authentication: required. `policy` must be a JSON object. Returns the merged policy
-/// with encrypted secrets redacted. Frontend: settings controllers using `invoke`.
+/// Authentication: required. Returns `true` when it existed.
+/// Frontend: `lib/monitor_api.js`.
 #[tauri::command]
-pub async fn storage_set_policy(
+pub async fn storage_delete_annotation(
     credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
     state: tauri::State<'_, Arc<StorageState>>,
-    policy: serde_json::Value,
-) -> Result<serde_json::Value, String> {
+    annotation_id: i64,
+) -> Result<bool, String> {
     check_auth_required(&credential_state)?;
+    check_writable(&state)?;
 
-    let existing = state
-        .load_policy()
-        .map_err(|e| format!("Failed to load policy: {}", e))?;
-    let merged = merge_policy_update(existing, policy)?;
-
-    state
-        .save_policy(&merged)
-        .map_err(|e| format!("Failed to save policy: {}", e))?;
-    let mut response = merged;
-    redact_policy_for_frontend(&mut response);
-    Ok(response)
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.delete_annotation(annotation_id))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
 }
 
-/// Returns the current storage policy with encrypted secrets redacted.
+/// Lists the decrypted notes of one screenshot, oldest first.
 ///
-/// Authentication: required. Returns a JSON object consumed by frontend settings.
+/// Authentication: required. Returns `Annotation[]`. Frontend: `lib/monitor_api.js`.
 #[tauri::command]
-pub async fn storage_get_policy(
+pub async fn storage_list_annotations(
     credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
     state: tauri::State<'_, Arc<StorageState>>,
-) -> Result<serde_json::Value, String> {
+    screenshot_id: i64,
+) -> Result<Vec<storage::Annotation>, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let annotations = state.list_annotations(screenshot_id)?;
+        if !annotations.is_empty() {
+            state.record_access_audit(
+                "view_annotations",
+                &serde_json::json!({ "screenshot_id": screenshot_id }),
+            )?;
+        }
+        Ok(annotations)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Searches note text through the blind bigram index.
+///
+/// Authentication: required. Every keyword must match. `limit` defaults to 100
+/// (max 500) and `offset` to 0. Returns `Annotation[]`, most recently edited first.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_search_annotations(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    query: String,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Vec<storage::Annotation>, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let annotations =
+            state.search_annotations(&query, limit.unwrap_or(100), offset.unwrap_or(0))?;
+        if !annotations.is_empty() {
+            let annotation_ids: Vec<i64> = annotations.iter().map(|a| a.id).collect();
+            state.record_access_audit(
+                "search_annotations",
+                &serde_json::json!({ "annotation_ids": annotation_ids }),
+            )?;
+        }
+        Ok(annotations)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Lists clipboard history entries captured in `[start_ms, end_ms)`, newest first.
+///
+/// Authentication: required. Bounds are Unix milliseconds and default to the whole
+/// history; `limit` defaults to 200 (max 1000). Returns `ClipboardEntry[]`.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_list_clipboard_entries(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    start_ms: Option<i64>,
+    end_ms: Option<i64>,
+    limit: Option<i64>,
+) -> Result<Vec<storage::ClipboardEntry>, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let entries = state.list_clipboard_entries(start_ms, end_ms, limit.unwrap_or(200))?;
+        if !entries.is_empty() {
+            let entry_ids: Vec<i64> = entries.iter().map(|e| e.id).collect();
+            state.record_access_audit(
+                "view_clipboard",
+                &serde_json::json!({ "clipboard_entry_ids": entry_ids }),
+            )?;
+        }
+        Ok(entries)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Searches clipboard history text through its blind bigram index.
+///
+/// Authentication: required. Every keyword must match. `limit` defaults to 100
+/// (max 500) and `offset` to 0. Returns `ClipboardEntry[]`, newest first.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_search_clipboard_entries(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    query: String,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Vec<storage::ClipboardEntry>, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let entries =
+            state.search_clipboard_entries(&query, limit.unwrap_or(100), offset.unwrap_or(0))?;
+        if !entries.is_empty() {
+            let entry_ids: Vec<i64> = entries.iter().map(|e| e.id).collect();
+            state.record_access_audit(
+                "search_clipboard",
+                &serde_json::json!({ "clipboard_entry_ids": entry_ids }),
+            )?;
+        }
+        Ok(entries)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Deletes one clipboard history entry.
+///
+/// Authentication: required. Returns `true` when it existed.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_delete_clipboard_entry(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    entry_id: i64,
+) -> Result<bool, String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.delete_clipboard_entries(&[entry_id]))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+        .map(|deleted| deleted > 0)
+}
+
+/// Deletes the whole clipboard history.
+///
+/// Authentication: required. Returns the number of entries deleted.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_clear_clipboard_history(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+) -> Result<usize, String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.clear_clipboard_history())
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Lists audio transcripts of chunks that started in `[start_ms, end_ms)`, newest
+/// first.
+///
+/// Authentication: required. Bounds are Unix milliseconds and default to the whole
+/// history; `limit` defaults to 200 (max 1000). Returns `AudioTranscript[]`.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_list_audio_transcripts(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    start_ms: Option<i64>,
+    end_ms: Option<i64>,
+    limit: Option<i64>,
+) -> Result<Vec<storage::AudioTranscript>, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let transcripts = state.list_audio_transcripts(start_ms, end_ms, limit.unwrap_or(200))?;
+        if !transcripts.is_empty() {
+            let transcript_ids: Vec<i64> = transcripts.iter().map(|t| t.id).collect();
+            state.record_access_audit(
+                "view_transcripts",
+                &serde_json::json!({ "transcript_ids": transcript_ids }),
+            )?;
+        }
+        Ok(transcripts)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Searches audio transcript text through its blind bigram index.
+///
+/// Authentication: required. Every keyword must match. `limit` defaults to 100
+/// (max 500) and `offset` to 0. Returns `AudioTranscript[]`, newest first.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_search_audio_transcripts(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    query: String,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Vec<storage::AudioTranscript>, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let transcripts =
+            state.search_audio_transcripts(&query, limit.unwrap_or(100), offset.unwrap_or(0))?;
+        if !transcripts.is_empty() {
+            let transcript_ids: Vec<i64> = transcripts.iter().map(|t| t.id).collect();
+            state.record_access_audit(
+                "search_transcripts",
+                &serde_json::json!({ "transcript_ids": transcript_ids }),
+            )?;
+        }
+        Ok(transcripts)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Deletes one audio transcript.
+///
+/// Authentication: required. Returns `true` when it existed.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_delete_audio_transcript(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    transcript_id: i64,
+) -> Result<bool, String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.delete_audio_transcripts(&[transcript_id]))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+        .map(|deleted| deleted > 0)
+}
+
+/// Pins one screenshot so retention, quota reclaim and range deletion keep it.
+///
+/// Authentication: required. Returns `true` when the flag changed.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_pin_screenshot(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    screenshot_id: i64,
+) -> Result<bool, String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.set_screenshot_pinned(screenshot_id, true))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Unpins one screenshot.
+///
+/// Authentication: required. Returns `true` when the flag changed.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_unpin_screenshot(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    screenshot_id: i64,
+) -> Result<bool, String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.set_screenshot_pinned(screenshot_id, false))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Returns pinned screenshots, newest first.
+///
+/// Authentication: required. `limit` defaults to 100 (max 500) and `offset` to 0.
+/// Returns an array of `ScreenshotRecord` objects. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_list_pinned(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Vec<storage::ScreenshotRecord>, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        state.list_pinned(limit.unwrap_or(100), offset.unwrap_or(0))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Returns the append-only log of decrypted-content reads.
+///
+/// Authentication: required. `limit` defaults to 100 (max 1000), `offset` to 0, and
+/// `action` optionally filters one action such as `view_image`. Reading the log is not
+/// itself audited. Returns `AccessAuditEntry[]`, newest first. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_get_audit_log(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+    action: Option<String>,
+) -> Result<Vec<storage::AccessAuditEntry>, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        state.get_access_audit_log(limit.unwrap_or(100), offset.unwrap_or(0), action.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Returns pending and completed soft-delete queue counts.
+///
+/// Authentication: required. Returns `DeleteQueueStatus`.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_get_delete_queue_status(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+) -> Result<storage::DeleteQueueStatus, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.get_delete_queue_status())
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Combines encrypted-storage index statistics with Python vector-index health.
+///
+/// Authentication: required. `refresh_vector` requests a live vector recount. Returns a
+/// JSON health object for both stores. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_get_index_health(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    monitor_state: tauri::State<'_, MonitorState>,
+    refresh_vector: Option<bool>,
+) -> Result<serde_json::Value, String> {
+    check_auth_required(&credential_state)?;
+
+    let storage_state = state.inner().clone();
+    let storage_stats =
+        tokio::task::spawn_blocking(move || storage_state.get_index_storage_stats())
+            .await
+            .map_err(|e| format!("Task join error: {:?}", e))??;
+
+    let monitor_health = monitor::forward_command_to_python(
+        &monitor_state,
+        serde_json::json!({
+            "command": "index_health",
+            "refresh": refresh_vector.unwrap_or(false),
+        }),
+    )
+    .await;
+
+    Ok(compose_index_health_response(storage_stats, monitor_health))
+}
+
+/// Retries failed vector indexing through the monitor service.
+///
+/// Authentication: required. `limit` defaults to 32 and is clamped to 1..=256; retries
+/// are queued ahead of live capture work. Returns the monitor's retry result object.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_retry_vector_indexing(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    monitor_state: tauri::State<'_, MonitorState>,
+    limit: Option<u32>,
+) -> Result<serde_json::Value, String> {
+    check_auth_required(&credential_state)?;
+
+    monitor::forward_command_to_python(
+        &monitor_state,
+        serde_json::json!({
+            "command": "retry_vector_indexing",
+            "limit": limit.unwrap_or(32).clamp(1, 256),
+            "priority": crate::capture::OCR_PRIORITY_INTERACTIVE,
+        }),
+    )
+    .await
+}
+
+/// Spot-checks that a `sample_rate` fraction of stored `.enc` images decrypt and decode.
+///
+/// Authentication: required because row keys are unwrapped. Returns
+/// `ImageVerificationReport`; corrupt entries carry an `IMAGE_*` code distinguishing a
+/// wrong key from a damaged file. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_verify_images(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    sample_rate: f64,
+) -> Result<storage::ImageVerificationReport, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.verify_images(sample_rate))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Checks the database pages, a sample of row keys and the blind bitmap index.
+///
+/// Authentication: required because row keys are unwrapped. With `repair` (default
+/// `false`) index damage is rebuilt and dangling postings are compacted; repairing
+/// also requires writable storage. Returns `DatabaseHealthReport`. Frontend:
+/// `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_check_integrity(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    repair: Option<bool>,
+) -> Result<storage::DatabaseHealthReport, String> {
+    check_auth_required(&credential_state)?;
+    let repair = repair.unwrap_or(false);
+    if repair {
+        check_writable(&state)?;
+    }
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.check_database_integrity(repair))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Writes the results of a text search to a CSV or JSON file at `output_path`.
+///
+/// Authentication: required. `query` and the optional filters work as in
+/// `storage_search`, without fuzzy matching; an empty query exports every text box in
+/// the time range. `format` is `csv` (default) or `json`; `max_rows` defaults to
+/// 100000. Returns `SearchExportSummary`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_export_search(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    query: String,
+    format: Option<storage::SearchExportFormat>,
+    output_path: String,
+    process_names: Option<Vec<String>>,
+    start_time: Option<f64>,
+    end_time: Option<f64>,
+    match_mode: Option<storage::SearchMatchMode>,
+    max_rows: Option<usize>,
+) -> Result<storage::SearchExportSummary, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    let max_rows = max_rows.unwrap_or(100_000).clamp(1, 1_000_000);
+    tokio::task::spawn_blocking(move || {
+        state.export_search_results(
+            &query,
+            format.unwrap_or_default(),
+            std::path::Path::new(&output_path),
+            process_names,
+            start_time,
+            end_time,
+            match_mode.unwrap_or_default(),
+            max_rows,
+        )
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Writes one screenshot, or the screenshots captured in `[start_ts, end_ts]` (Unix
+/// seconds), to a searchable PDF at `output_path`, one page per screenshot with the OCR
+/// text as a selectable layer.
+///
+/// Authentication: required. `limit` caps a range export (default 200, max 2000),
+/// oldest first. Returns `PdfExportSummary`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_export_pdf(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    output_path: String,
+    screenshot_id: Option<i64>,
+    start_ts: Option<f64>,
+    end_ts: Option<f64>,
+    limit: Option<i64>,
+) -> Result<storage::PdfExportSummary, String> {
+    check_auth_required(&credential_state)?;
+
+    let (start_ts, end_ts) = match (screenshot_id, start_ts, end_ts) {
+        (Some(_), _, _) => (0.0, 0.0),
+        (None, Some(start), Some(end)) => (start, end),
+        _ => return Err("Either screenshot_id or start_ts and end_ts is required".to_string()),
+    };
+    let limit = limit.unwrap_or(200).clamp(1, 2000);
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        state.export_screenshots_pdf(
+            screenshot_id,
+            start_ts,
+            end_ts,
+            limit,
+            std::path::Path::new(&output_path),
+        )
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Writes the Markdown daily note of local day `date` (`YYYY-MM-DD`, default today) with
+/// session summaries, OCR snippets and exported images.
+///
+/// Authentication: required. `vault_dir` defaults to the folder saved with
+/// `storage_set_daily_note_vault`. A note of the same name not written by CarbonPaper
+/// is never overwritten. Returns `DailyNoteSummary`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_export_daily_note(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    date: Option<String>,
+    vault_dir: Option<String>,
+) -> Result<storage::DailyNoteSummary, String> {
+    check_auth_required(&credential_state)?;
+
+    let date = match date {
+        Some(date) => chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|e| format!("Invalid date '{}': {}", date, e))?,
+        None => chrono::Local::now().date_naive(),
+    };
+    let vault_dir = vault_dir
+        .map(std::path::PathBuf::from)
+        .or_else(storage::daily_note_vault_dir)
+        .ok_or_else(|| "No vault folder configured".to_string())?;
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.export_daily_note(date, &vault_dir, false))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Returns the vault folder the `daily_note` scheduled job writes to, or `null`.
+///
+/// Authentication: required. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_get_daily_note_vault(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+) -> Result<Option<String>, String> {
+    check_auth_required(&credential_state)?;
+    Ok(storage::daily_note_vault_dir().map(|dir| dir.display().to_string()))
+}
+
+/// Saves (or clears with `null`) the vault folder for daily notes. Enable the
+/// `daily_note` scheduled job to keep notes written automatically.
+///
+/// Authentication: required. The folder must exist. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_set_daily_note_vault(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    vault_dir: Option<String>,
+) -> Result<(), String> {
+    check_auth_required(&credential_state)?;
+    storage::set_daily_note_vault_dir(vault_dir.as_deref())
+}
+
+/// Writes a self-contained HTML digest of the week starting at `week_start`.
+///
+/// Authentication: required. `week_start` is Unix seconds and defaults to seven days
+/// ago; `privacy_level` (`summary`, `apps`, `detailed`) controls which content is
+/// included and defaults to `apps`. Returns `WeeklyDigest`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_export_weekly_digest(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    output_path: String,
+    week_start: Option<i64>,
+    privacy_level: Option<storage::DigestPrivacyLevel>,
+) -> Result<storage::WeeklyDigest, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    let week_start =
+        week_start.unwrap_or_else(|| chrono::Utc::now().timestamp() - 7 * 24 * 60 * 60);
+    let privacy_level = privacy_level.unwrap_or_default();
+    tokio::task::spawn_blocking(move || {
+        state.export_weekly_digest_html(
+            week_start,
+            privacy_level,
+            std::path::Path::new(&output_path),
+        )
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Persists a screenshot and its metadata from a trusted native producer.
+///
+/// Authentication: required. `request` is `SaveScreenshotRequest`; returns
+/// `SaveScreenshotResponse`. This command is registered for internal/native callers.
+#[tauri::command]
+pub async fn storage_save_screenshot(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    request: storage::SaveScreenshotRequest,
+) -> Result<storage::SaveScreenshotResponse, String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    state.save_screenshot(&request)
+}
+
+/// Scores visible links using aggregate storage statistics.
+///
+/// Authentication: not required; input contains link features rather than stored user
+/// records. Returns an array of `ScoredLink` objects for the browser integration.
+#[tauri::command]
+pub async fn storage_compute_link_scores(
+    state: tauri::State<'_, Arc<StorageState>>,
+    links: Vec<storage::VisibleLink>,
+) -> Result<Vec<storage::ScoredLink>, String> {
+    state.compute_link_scores(&links)
+}
+
+/// Returns the storage encryption public key as standard Base64.
+///
+/// Authentication: not required because the public key cannot decrypt data. Returns a
+/// JSON string for trusted native/browser producers.
+#[tauri::command]
+pub async fn storage_get_public_key(
+    state: tauri::State<'_, Arc<StorageState>>,
+) -> Result<String, String> {
+    let key = state.get_public_key()?;
+    Ok(base64::Engine::encode(
+        &base64::engine::general_purpose::STANDARD,
+        &key,
+    ))
+}
+
+/// Merges and persists a partial storage policy update.
+///
+/// Authentication: required. `policy` must be a JSON object. Returns the merged policy
+/// with encrypted secrets redacted. Frontend: settings controllers using `invoke`.
+#[tauri::command]
+pub async fn storage_set_policy(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    policy: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    let existing = state
+        .load_policy()
+        .map_err(|e| format!("Failed to load policy: {}", e))?;
+    let merged = merge_policy_update(existing, policy)?;
+    state.check_policy_compliant(&merged)?;
+
+    state
+        .save_policy(&merged)
+        .map_err(|e| format!("Failed to save policy: {}", e))?;
+    state.apply_ocr_text_rules_from_policy(&merged);
+    let mut response = merged;
+    redact_policy_for_frontend(&mut response);
+    Ok(response)
+}
+
+/// Returns the current storage policy with encrypted secrets redacted.
+///
+/// Authentication: required. Returns a JSON object consumed by frontend settings.
+#[tauri::command]
+pub async fn storage_get_policy(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+) -> Result<serde_json::Value, String> {
     check_auth_required(&credential_state)?;
 
     let mut policy = state
@@ -1136,6 +2547,397 @@ pub async fn storage_get_policy(
     Ok(policy)
 }
 
+/// Returns the automatic retention limits (maximum age and total screenshot size).
+///
+/// Authentication: required. Returns `RetentionPolicy`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_get_retention_policy(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+) -> Result<RetentionPolicy, String> {
+    check_auth_required(&credential_state)?;
+
+    state.retention_policy()
+}
+
+/// Persists the automatic retention limits.
+///
+/// Authentication: required. Cleanup runs on the next `storage_policy` scheduled job;
+/// deleted screenshots go through the delete queue, which also removes their OCR rows
+/// and blind index postings. Returns JSON `null`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_set_retention_policy(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    retention: RetentionPolicy,
+) -> Result<(), String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    state.set_retention_policy(retention)
+}
+
+/// Returns the screenshot storage quota and current screenshots-directory usage.
+///
+/// Authentication: required. Returns `StorageQuota`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_get_quota(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+) -> Result<StorageQuota, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.storage_quota())
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Sets the screenshot storage quota in GB; `null` or `0` removes it.
+///
+/// Authentication: required. When the screenshots directory exceeds the quota, the
+/// `storage_policy` scheduled job queues the oldest committed screenshots for deletion
+/// (rows, encrypted files, and bitmap postings) and emits `storage-quota-evicted`
+/// with a `QuotaEviction` payload. Returns `StorageQuota`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_set_quota(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    quota_gb: Option<u64>,
+) -> Result<StorageQuota, String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.set_storage_quota(quota_gb))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Returns whether data-at-rest compliance mode is on and what plaintext remains.
+///
+/// Authentication: required. Returns `ComplianceStatus`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_get_compliance_status(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+) -> Result<ComplianceStatus, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.compliance_status())
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Switches data-at-rest compliance mode on or off.
+///
+/// Authentication: required. Enabling runs the migrations in one step: legacy plaintext
+/// process names are encrypted, the plaintext `process_name` values are cleared,
+/// retention is capped at 90 days, the session timeout is capped, and reads of full
+/// images and screenshot details are written to the encrypted access audit log.
+/// Returns `ComplianceStatus`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_set_compliance_mode(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    enabled: bool,
+) -> Result<ComplianceStatus, String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        if enabled {
+            state.enable_compliance_mode()
+        } else {
+            state.disable_compliance_mode()
+        }
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Returns whether paranoid mode is on and what plaintext remains.
+///
+/// Authentication: required. Returns `ParanoidModeStatus`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_get_paranoid_mode(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+) -> Result<ParanoidModeStatus, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.paranoid_mode_status())
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Switches paranoid mode on or off.
+///
+/// Authentication: required. Enabling hashes every process name into the blind index,
+/// then encrypts and clears the plaintext `process_name` values; new captures store no
+/// plaintext metadata or privacy preview. Retention and the session timeout are left
+/// alone. Returns `ParanoidModeStatus`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_set_paranoid_mode(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    enabled: bool,
+) -> Result<ParanoidModeStatus, String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.set_paranoid_mode(enabled))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Returns what the startup recovery did with captures interrupted by a crash.
+///
+/// Authentication: required. Returns `PendingRecoverySummary`, or `null` when storage
+/// was initialized read-only or the pass failed. The same summary is emitted as
+/// `storage-pending-recovery` at startup when anything was recovered. Frontend:
+/// `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_get_pending_recovery(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+) -> Result<Option<PendingRecoverySummary>, String> {
+    check_auth_required(&credential_state)?;
+
+    Ok(state.pending_recovery_summary())
+}
+
+/// Lists the months that were detached into their own database shard.
+///
+/// Authentication: required. Returns `Vec<MonthShard>`, oldest month first. Frontend:
+/// `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_list_month_shards(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+) -> Result<Vec<MonthShard>, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.list_month_shards())
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Moves a closed month (`YYYY-MM`, local time) out of the live database into a shard.
+///
+/// Authentication: required. The current month cannot be detached; pinned and
+/// protected screenshots stay in the live database. Detached screenshots no longer
+/// show up in the timeline or search until the month is attached again. Returns
+/// `MonthShard`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_detach_month(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    month: String,
+) -> Result<MonthShard, String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.detach_month(&month))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Moves a detached month back into the live database and deletes its shard.
+///
+/// Authentication: required. Search postings of the restored rows are rebuilt in the
+/// background. Returns the number of screenshots restored. Frontend:
+/// `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_attach_month(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    month: String,
+) -> Result<usize, String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.attach_month(&month))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Finds image files no row refers to and screenshots whose file is missing.
+///
+/// Authentication: required. With `clean` (default `false`) orphaned files older than
+/// ten minutes are deleted and missing-file screenshots moved to the trash, except
+/// pinned and protected ones; cleaning also requires writable storage. Returns
+/// `GcReport`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_gc(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    clean: Option<bool>,
+) -> Result<GcReport, String> {
+    check_auth_required(&credential_state)?;
+    let clean = clean.unwrap_or(false);
+    if clean {
+        check_writable(&state)?;
+    }
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.collect_garbage(clean))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Returns the screenshot nearest to `timestamp` plus a summary of the session around it.
+///
+/// Authentication: required. `timestamp` accepts seconds or milliseconds; nothing is
+/// returned if the nearest capture is more than `max_distance_secs` away (default 30
+/// minutes). Returns `ContextAt`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_get_context_at(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    timestamp: f64,
+    max_distance_secs: Option<i64>,
+) -> Result<ContextAt, String> {
+    check_auth_required(&credential_state)?;
+
+    let timestamp = if timestamp > 10_000_000_000.0 {
+        timestamp / 1000.0
+    } else {
+        timestamp
+    };
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || state.get_context_at(timestamp, max_distance_secs))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Exports screenshots, OCR results, and metadata in `[start_ts, end_ts]` (Unix seconds)
+/// to a password-protected portable archive at `export_path`.
+///
+/// Authentication: required. Images are decrypted with the local CNG-bound row keys and
+/// re-encrypted with an Argon2-derived key, so the archive can be opened on another
+/// machine. Emits `range-archive-export-progress` with `{ done, total }`.
+/// Returns `RangeArchiveSummary`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_export_range(
+    app_handle: tauri::AppHandle,
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    start_ts: f64,
+    end_ts: f64,
+    password: String,
+    export_path: String,
+) -> Result<RangeArchiveSummary, String> {
+    check_auth_required(&credential_state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        state.export_range_archive(
+            start_ts,
+            end_ts,
+            &password,
+            std::path::Path::new(&export_path),
+            |done, total| {
+                if done % 20 == 0 || done == total {
+                    let _ = app_handle.emit(
+                        "range-archive-export-progress",
+                        serde_json::json!({ "done": done, "total": total }),
+                    );
+                }
+            },
+        )
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Imports a portable archive produced by `storage_export_range` into the timeline.
+///
+/// Authentication: required. Entries are decrypted with `password`, re-encrypted with
+/// new local row keys, inserted with their original capture times, and their OCR text
+/// is indexed for search. Screenshots already present (same image hash) are skipped.
+/// Emits `range-archive-import-progress` with `{ done, total }`.
+/// Returns `RangeArchiveImportSummary`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_import_archive(
+    app_handle: tauri::AppHandle,
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    archive_path: String,
+    password: String,
+) -> Result<RangeArchiveImportSummary, String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        state.import_range_archive(
+            std::path::Path::new(&archive_path),
+            &password,
+            |done, total| {
+                if done % 20 == 0 || done == total {
+                    let _ = app_handle.emit(
+                        "range-archive-import-progress",
+                        serde_json::json!({ "done": done, "total": total }),
+                    );
+                }
+            },
+        )
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+/// Returns the OCR text post-processing rules applied before storage and indexing.
+///
+/// Authentication: required. Returns `OcrTextRules`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_get_ocr_text_rules(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+) -> Result<OcrTextRules, String> {
+    check_auth_required(&credential_state)?;
+
+    Ok(state.ocr_text_rules())
+}
+
+/// Replaces and persists the OCR text post-processing rules.
+///
+/// Authentication: required. Only affects OCR results stored afterwards; returns JSON
+/// `null`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn storage_set_ocr_text_rules(
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    rules: OcrTextRules,
+) -> Result<(), String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    let existing = state
+        .load_policy()
+        .map_err(|e| format!("Failed to load policy: {}", e))?;
+    let rules_value =
+        serde_json::to_value(rules).map_err(|e| format!("Failed to serialize rules: {}", e))?;
+    let merged = merge_policy_update(
+        existing,
+        serde_json::json!({ "ocr_text_rules": rules_value }),
+    )?;
+    state
+        .save_policy(&merged)
+        .map_err(|e| format!("Failed to save policy: {}", e))?;
+    state.set_ocr_text_rules(rules);
+    Ok(())
+}
+
 /// Encrypts plaintext for the legacy ChromaDB bridge.
 ///
 /// Authentication: not required because only the public-key encryption path is used.
@@ -1160,6 +2962,7 @@ pub async fn storage_decrypt_from_chromadb(
 ) -> Result<String, String> {
     check_auth_required(&credential_state)?;
 
+    state.record_access_audit("decrypt_vector_text", &serde_json::json!({}))?;
     state.decrypt_from_chromadb(&encrypted)
 }
 
@@ -1176,6 +2979,7 @@ pub async fn storage_update_category(
     category: String,
 ) -> Result<serde_json::Value, String> {
     check_auth_required(&credential_state)?;
+    check_writable(&state)?;
 
     let old_category = state
         .get_screenshot_by_id(screenshot_id)
@@ -1190,7 +2994,7 @@ pub async fn storage_update_category(
             let title = record.window_title.clone().unwrap_or_default();
             let process_name = record.process_name.clone().unwrap_or_default();
 
-            let ocr_text = match state.get_screenshot_ocr_results(screenshot_id) {
+            let ocr_text = match state.get_screenshot_ocr_results(screenshot_id, None) {
                 Ok(results) => {
                     let texts: Vec<String> = results.iter().map(|r| r.text.clone()).collect();
                     texts.join(" ")
@@ -1338,6 +3142,7 @@ pub async fn storage_update_task_label(
     label: String,
 ) -> Result<(), String> {
     check_auth_required(&credential_state)?;
+    check_writable(&state)?;
 
     state.update_task_label(task_id, &label)
 }
@@ -1352,6 +3157,7 @@ pub async fn storage_delete_task(
     task_id: i64,
 ) -> Result<(), String> {
     check_auth_required(&credential_state)?;
+    check_writable(&state)?;
 
     state.delete_task(task_id)
 }
@@ -1368,6 +3174,7 @@ pub async fn storage_remove_task_screenshot(
     screenshot_id: i64,
 ) -> Result<i64, String> {
     check_auth_required(&credential_state)?;
+    check_writable(&state)?;
 
     state.remove_task_screenshot(task_id, screenshot_id)
 }
@@ -1383,6 +3190,7 @@ pub async fn storage_merge_tasks(
     task_ids: Vec<i64>,
 ) -> Result<i64, String> {
     check_auth_required(&credential_state)?;
+    check_writable(&state)?;
 
     state.merge_tasks(&task_ids)
 }
@@ -1398,6 +3206,7 @@ pub async fn storage_save_clustering_results(
     tasks: Vec<storage::task::SaveTaskRequest>,
 ) -> Result<Vec<i64>, String> {
     check_auth_required(&credential_state)?;
+    check_writable(&state)?;
 
     state.save_clustering_results(&tasks)
 }
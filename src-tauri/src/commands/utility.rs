@@ -3,6 +3,15 @@
 //! Commands that can terminate the process, open local paths, or modify sensitive
 //! runtime configuration validate the calling window and/or authenticated session.
 
+use crate::capture::{
+    ocr_queue_settings, CaptureConfig, CAPTURE_DHASH_THRESHOLD_REGISTRY_KEY,
+    CAPTURE_INTERVAL_REGISTRY_KEY, MAX_OCR_QUEUE_MAX_SIZE, OCR_QUEUE_DROP_POLICIES,
+    OCR_QUEUE_DROP_POLICY_REGISTRY_KEY, OCR_QUEUE_MAX_SIZE_REGISTRY_KEY,
+};
+use crate::storage::{
+    StoredImageCodec, DELTA_FRAMES_REGISTRY_KEY, IMAGE_CODEC_REGISTRY_KEY,
+    IMAGE_QUALITY_REGISTRY_KEY, NEAR_DUPLICATE_THRESHOLD_REGISTRY_KEY,
+};
 use crate::{
     capture::CaptureState, monitor, monitor::MonitorState, registry_config, storage::StorageState,
     LightweightModeState, IS_QUITTING,
@@ -121,19 +130,39 @@ pub fn close_process(window: tauri::Window) -> Result<(), String> {
     std::process::exit(0);
 }
 
+/// Reports whether the app runs in read-only guest mode (started with `--guest`).
+///
+/// Authentication: not required. In this mode the database is opened read-only and
+/// capture, deletion, and import commands fail with `READ_ONLY_MODE`. Returns a
+/// boolean. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub fn get_read_only_mode(storage_state: tauri::State<'_, Arc<StorageState>>) -> bool {
+    storage_state.is_read_only()
+}
+
 /// Returns advanced runtime configuration as a JSON object.
 ///
 /// Authentication: not required; the object contains preferences but no secrets.
 /// Frontend: settings controllers.
 #[tauri::command]
-pub fn get_advanced_config() -> Result<serde_json::Value, String> {
+pub fn get_advanced_config(
+    storage_state: tauri::State<'_, Arc<StorageState>>,
+) -> Result<serde_json::Value, String> {
     let cpu_limit_enabled = registry_config::get_bool("cpu_limit_enabled").unwrap_or(true);
     let cpu_limit_percent = registry_config::get_u32("cpu_limit_percent").unwrap_or(10);
+    let job_memory_limit_mb =
+        registry_config::get_u32(monitor::JOB_MEMORY_LIMIT_REGISTRY_KEY).unwrap_or(0);
+    let sidecar_memory_limit_mb = crate::sidecar_memory::limit_mb();
     let ocr_timeout_secs = registry_config::get_u32("ocr_timeout_secs").unwrap_or(120);
     let rust_ocr_dml_beta = registry_config::get_bool("rust_ocr_dml_beta").unwrap_or(false);
     let use_dml = registry_config::get_bool("use_dml").unwrap_or(false);
     let dml_device_id = registry_config::get_u32("dml_device_id").unwrap_or(0);
     let game_mode_enabled = registry_config::get_bool("game_mode_enabled").unwrap_or(true);
+    let use_npu = registry_config::get_bool("use_npu").unwrap_or(false);
+    let npu_provider =
+        registry_config::get_string("npu_provider").unwrap_or_else(|| "auto".to_string());
+    let dml_idle_priority = crate::gpu_priority::idle_priority_enabled();
+    let dml_idle_gpu_threshold = crate::gpu_priority::gpu_threshold_percent();
     let clustering_interval =
         registry_config::get_string("clustering_interval").unwrap_or_else(|| "1w".to_string());
     let clustering_enabled = registry_config::get_bool("clustering_enabled").unwrap_or(true);
@@ -144,15 +173,30 @@ pub fn get_advanced_config() -> Result<serde_json::Value, String> {
         registry_config::get_bool("clustering_allow_full_low_memory").unwrap_or(false);
     let network_enabled = registry_config::get_bool("network_enabled").unwrap_or(true);
     let use_onnx = registry_config::get_bool("use_onnx").unwrap_or(true);
+    let (image_codec, image_codec_quality) = storage_state.image_codec();
+    let capture_defaults = CaptureConfig::default();
+    let capture_interval_secs = registry_config::get_u32(CAPTURE_INTERVAL_REGISTRY_KEY)
+        .map(u64::from)
+        .unwrap_or(capture_defaults.interval_secs);
+    let capture_dhash_threshold = registry_config::get_u32(CAPTURE_DHASH_THRESHOLD_REGISTRY_KEY)
+        .unwrap_or(capture_defaults.dhash_threshold);
+    let (ocr_queue_max_size, ocr_queue_drop_policy) = ocr_queue_settings();
+    let uia_text = crate::uia_text::load_settings();
 
     Ok(serde_json::json!({
         "cpu_limit_enabled": cpu_limit_enabled,
         "cpu_limit_percent": cpu_limit_percent,
+        "job_memory_limit_mb": job_memory_limit_mb,
+        "sidecar_memory_limit_mb": sidecar_memory_limit_mb,
         "ocr_timeout_secs": ocr_timeout_secs,
         "rust_ocr_dml_beta": rust_ocr_dml_beta,
         "use_dml": use_dml,
         "dml_device_id": dml_device_id,
         "game_mode_enabled": game_mode_enabled,
+        "use_npu": use_npu,
+        "npu_provider": npu_provider,
+        "dml_idle_priority": dml_idle_priority,
+        "dml_idle_gpu_threshold": dml_idle_gpu_threshold,
         "clustering_interval": clustering_interval,
         "clustering_enabled": clustering_enabled,
         "classification_enabled": classification_enabled,
@@ -160,16 +204,30 @@ pub fn get_advanced_config() -> Result<serde_json::Value, String> {
         "clustering_allow_full_low_memory": clustering_allow_full_low_memory,
         "network_enabled": network_enabled,
         "use_onnx": use_onnx,
+        "image_codec": image_codec.as_str(),
+        "image_codec_quality": image_codec_quality,
+        "near_duplicate_threshold": storage_state.near_duplicate_threshold(),
+        "delta_frames_enabled": storage_state.delta_frames_enabled(),
+        "capture_interval_secs": capture_interval_secs,
+        "capture_dhash_threshold": capture_dhash_threshold,
+        "ocr_queue_max_size": ocr_queue_max_size,
+        "ocr_queue_drop_policy": ocr_queue_drop_policy,
+        "uia_text_enabled": uia_text.enabled,
+        "uia_text_processes": uia_text.processes,
     }))
 }
 
-/// Applies a partial advanced-configuration JSON object.
+/// Applies a partial advanced-configuration JSON object. CPU and memory limits are
+/// applied to the running sidecar's Job Object immediately.
 ///
 /// Authentication: required. Unknown keys are ignored; returns JSON `null`.
 /// Frontend: settings controllers.
 #[tauri::command]
 pub fn set_advanced_config(
     credential_state: tauri::State<'_, Arc<crate::credential_manager::CredentialManagerState>>,
+    monitor_state: tauri::State<'_, MonitorState>,
+    storage_state: tauri::State<'_, Arc<StorageState>>,
+    capture_state: tauri::State<'_, Arc<CaptureState>>,
     config: serde_json::Value,
 ) -> Result<(), String> {
     crate::commands::check_auth_required(&credential_state)?;
@@ -177,12 +235,63 @@ pub fn set_advanced_config(
         registry_config::set_bool("cpu_limit_enabled", v)?;
     }
     if let Some(v) = config.get("cpu_limit_percent").and_then(|v| v.as_u64()) {
-        registry_config::set_u32("cpu_limit_percent", v as u32)?;
+        registry_config::set_u32("cpu_limit_percent", v.clamp(1, 100) as u32)?;
+    }
+    if let Some(v) = config.get("job_memory_limit_mb").and_then(|v| v.as_u64()) {
+        let mb = match v {
+            0 => 0,
+            v => v.clamp(monitor::MIN_JOB_MEMORY_LIMIT_MB as u64, u32::MAX as u64) as u32,
+        };
+        registry_config::set_u32(monitor::JOB_MEMORY_LIMIT_REGISTRY_KEY, mb)?;
+    }
+    if let Some(v) = config
+        .get("sidecar_memory_limit_mb")
+        .and_then(|v| v.as_u64())
+    {
+        // Read by the memory watchdog on every sample; no restart needed.
+        registry_config::set_u32(
+            crate::sidecar_memory::LIMIT_REGISTRY_KEY,
+            crate::sidecar_memory::clamp_limit_mb(v),
+        )?;
+    }
+    if [
+        "cpu_limit_enabled",
+        "cpu_limit_percent",
+        "job_memory_limit_mb",
+    ]
+    .iter()
+    .any(|key| config.get(*key).is_some())
+    {
+        // The settings are saved either way; the next monitor start picks them up.
+        if let Err(e) = monitor::apply_job_limits(&monitor_state) {
+            tracing::warn!("Failed to apply Job Object limits: {}", e);
+        }
     }
     if let Some(v) = config.get("ocr_timeout_secs").and_then(|v| v.as_u64()) {
         let clamped = (v as u32).clamp(30, 600);
         registry_config::set_u32("ocr_timeout_secs", clamped)?;
     }
+    if let Some(v) = config.get("ocr_queue_max_size").and_then(|v| v.as_u64()) {
+        // Read by the monitor at start; takes effect after a restart.
+        let clamped = v.clamp(1, MAX_OCR_QUEUE_MAX_SIZE as u64) as u32;
+        registry_config::set_u32(OCR_QUEUE_MAX_SIZE_REGISTRY_KEY, clamped)?;
+    }
+    if let Some(v) = config.get("ocr_queue_drop_policy").and_then(|v| v.as_str()) {
+        if !OCR_QUEUE_DROP_POLICIES.contains(&v) {
+            return Err(format!("Unsupported OCR queue drop policy: {}", v));
+        }
+        registry_config::set_string(OCR_QUEUE_DROP_POLICY_REGISTRY_KEY, v)?;
+    }
+    if let Some(v) = config.get("uia_text_enabled").and_then(|v| v.as_bool()) {
+        crate::uia_text::save_enabled(v)?;
+    }
+    if let Some(v) = config.get("uia_text_processes").and_then(|v| v.as_array()) {
+        let processes: Vec<String> = v
+            .iter()
+            .filter_map(|name| name.as_str().map(str::to_string))
+            .collect();
+        crate::uia_text::save_processes(&processes)?;
+    }
     if let Some(v) = config.get("rust_ocr_dml_beta").and_then(|v| v.as_bool()) {
         // Temporary migration setting. It intentionally does not mirror the
         // existing Python DML preference and will be removed when the Rust
@@ -198,6 +307,26 @@ pub fn set_advanced_config(
     if let Some(v) = config.get("game_mode_enabled").and_then(|v| v.as_bool()) {
         registry_config::set_bool("game_mode_enabled", v)?;
     }
+    if let Some(v) = config.get("use_npu").and_then(|v| v.as_bool()) {
+        registry_config::set_bool("use_npu", v)?;
+    }
+    if let Some(v) = config.get("npu_provider").and_then(|v| v.as_str()) {
+        let provider = crate::monitor::NpuProvider::parse(v)
+            .map(crate::monitor::NpuProvider::as_str)
+            .unwrap_or("auto");
+        registry_config::set_string("npu_provider", provider)?;
+    }
+    if let Some(v) = config.get("dml_idle_priority").and_then(|v| v.as_bool()) {
+        // The GPU priority class is applied to OCR workers when they start.
+        registry_config::set_bool(crate::gpu_priority::DML_IDLE_PRIORITY_KEY, v)?;
+    }
+    if let Some(v) = config
+        .get("dml_idle_gpu_threshold")
+        .and_then(|v| v.as_u64())
+    {
+        let clamped = v.clamp(crate::gpu_priority::MIN_DML_IDLE_GPU_THRESHOLD as u64, 100) as u32;
+        registry_config::set_u32(crate::gpu_priority::DML_IDLE_GPU_THRESHOLD_KEY, clamped)?;
+    }
     if let Some(v) = config.get("clustering_interval").and_then(|v| v.as_str()) {
         registry_config::set_string("clustering_interval", v)?;
     }
@@ -228,6 +357,57 @@ pub fn set_advanced_config(
     if let Some(v) = config.get("use_onnx").and_then(|v| v.as_bool()) {
         registry_config::set_bool("use_onnx", v)?;
     }
+    let codec_update = config.get("image_codec").and_then(|v| v.as_str());
+    let quality_update = config.get("image_codec_quality").and_then(|v| v.as_u64());
+    if codec_update.is_some() || quality_update.is_some() {
+        let (mut codec, mut quality) = storage_state.image_codec();
+        if let Some(v) = codec_update {
+            codec = StoredImageCodec::parse(v)
+                .ok_or_else(|| format!("Unsupported image codec: {}", v))?;
+            registry_config::set_string(IMAGE_CODEC_REGISTRY_KEY, codec.as_str())?;
+        }
+        if let Some(v) = quality_update {
+            quality = v.clamp(1, 100) as u8;
+            registry_config::set_u32(IMAGE_QUALITY_REGISTRY_KEY, quality as u32)?;
+        }
+        // Applies to screenshots saved from now on; existing files keep their format.
+        storage_state.set_image_codec(codec, quality);
+    }
+    if let Some(v) = config
+        .get("near_duplicate_threshold")
+        .and_then(|v| v.as_u64())
+    {
+        // Bits out of the 256-bit dHash; 0 turns near-duplicate suppression off.
+        storage_state.set_near_duplicate_threshold(v.min(u32::MAX as u64) as u32);
+        registry_config::set_u32(
+            NEAR_DUPLICATE_THRESHOLD_REGISTRY_KEY,
+            storage_state.near_duplicate_threshold(),
+        )?;
+    }
+    if let Some(v) = config.get("delta_frames_enabled").and_then(|v| v.as_bool()) {
+        storage_state.set_delta_frames_enabled(v);
+        registry_config::set_bool(DELTA_FRAMES_REGISTRY_KEY, v)?;
+    }
+    let interval_update = config.get("capture_interval_secs").and_then(|v| v.as_u64());
+    let threshold_update = config
+        .get("capture_dhash_threshold")
+        .and_then(|v| v.as_u64())
+        .map(|v| v.min(u32::MAX as u64) as u32);
+    if interval_update.is_some() || threshold_update.is_some() {
+        // Applied to the live capture loop; no monitor restart needed.
+        capture_state.set_capture_tuning(interval_update, threshold_update);
+        let tuned = capture_state
+            .config
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        if interval_update.is_some() {
+            registry_config::set_u32(CAPTURE_INTERVAL_REGISTRY_KEY, tuned.interval_secs as u32)?;
+        }
+        if threshold_update.is_some() {
+            registry_config::set_u32(CAPTURE_DHASH_THRESHOLD_REGISTRY_KEY, tuned.dhash_threshold)?;
+        }
+    }
     Ok(())
 }
 
@@ -363,6 +543,68 @@ pub fn set_extension_enhancement(
     registry_config::set_bool("extension_enhanced_global", enabled)
 }
 
+/// Returns `{ "enabled": boolean }` for reading the page URL from the browser's
+/// address bar when the extension does not handle the capture.
+///
+/// Authentication: not required. Frontend: extension settings.
+#[tauri::command]
+pub fn get_browser_url_capture_config() -> serde_json::Value {
+    serde_json::json!({
+        "enabled": crate::browser_url::browser_url_capture_enabled(),
+    })
+}
+
+/// Enables or disables address-bar URL capture. Takes effect on the next capture.
+///
+/// Authentication: required. Returns JSON `null`.
+#[tauri::command]
+pub fn set_browser_url_capture(
+    credential_state: tauri::State<'_, Arc<crate::credential_manager::CredentialManagerState>>,
+    enabled: bool,
+) -> Result<(), String> {
+    crate::commands::check_auth_required(&credential_state)?;
+    crate::browser_url::set_browser_url_capture_enabled(enabled)
+}
+
+/// Returns the global hotkey bindings (empty strings when unbound).
+///
+/// Authentication: not required. Frontend: `lib/hotkey_api.js`.
+#[tauri::command]
+pub fn get_hotkeys() -> serde_json::Value {
+    serde_json::json!({
+        "capture": crate::hotkeys::HotkeyAction::Capture.saved(),
+        "search": crate::hotkeys::HotkeyAction::Search.saved(),
+    })
+}
+
+/// Binds the manual-capture hotkey; an empty string removes the binding. Fails when
+/// the accelerator is invalid or already taken.
+///
+/// Authentication: required. Returns the saved accelerator string.
+#[tauri::command]
+pub fn set_capture_hotkey(
+    app: tauri::AppHandle,
+    credential_state: tauri::State<'_, Arc<crate::credential_manager::CredentialManagerState>>,
+    hotkey: String,
+) -> Result<String, String> {
+    crate::commands::check_auth_required(&credential_state)?;
+    crate::hotkeys::set_hotkey(&app, crate::hotkeys::HotkeyAction::Capture, &hotkey)
+}
+
+/// Binds the hotkey that shows the search overlay; an empty string removes the
+/// binding. Fails when the accelerator is invalid or already taken.
+///
+/// Authentication: required. Returns the saved accelerator string.
+#[tauri::command]
+pub fn set_search_hotkey(
+    app: tauri::AppHandle,
+    credential_state: tauri::State<'_, Arc<crate::credential_manager::CredentialManagerState>>,
+    hotkey: String,
+) -> Result<String, String> {
+    crate::commands::check_auth_required(&credential_state)?;
+    crate::hotkeys::set_hotkey(&app, crate::hotkeys::HotkeyAction::Search, &hotkey)
+}
+
 /// One-time migration: the per-browser enhancement toggles
 /// (extension_enhanced_chrome/edge) were replaced by a single global toggle.
 /// If either old toggle was on, the user wanted enhancement — carry it over.
@@ -427,6 +669,107 @@ pub fn get_game_mode_status(app: tauri::AppHandle) -> serde_json::Value {
     })
 }
 
+/// Returns the game-mode triggers: listed processes, GPU memory thresholds and the
+/// delay before DirectML is restored.
+///
+/// Authentication: not required. Frontend: `ResourcePolicyCard`.
+#[tauri::command]
+pub fn get_game_mode_config() -> crate::game_mode::GameModeSettings {
+    crate::game_mode::load_settings()
+}
+
+/// Saves the game-mode triggers. Values are clamped and the process names
+/// lower-cased; the running monitor picks them up on its next GPU poll.
+///
+/// Authentication: required. Returns the settings as saved.
+#[tauri::command]
+pub fn set_game_mode_config(
+    credential_state: tauri::State<'_, Arc<crate::credential_manager::CredentialManagerState>>,
+    config: crate::game_mode::GameModeSettings,
+) -> Result<crate::game_mode::GameModeSettings, String> {
+    crate::commands::check_auth_required(&credential_state)?;
+    crate::game_mode::save_settings(config)
+}
+
+/// Returns whether capture auto-pauses during screen shares and whether it is paused now.
+///
+/// Authentication: not required. Returns `{ "enabled", "paused" }` booleans.
+#[tauri::command]
+pub fn get_screen_share_status(app: tauri::AppHandle) -> serde_json::Value {
+    let capture_state = app.state::<Arc<CaptureState>>();
+    serde_json::json!({
+        "enabled": crate::screen_share::screen_share_pause_enabled(),
+        "paused": capture_state.screen_share_paused.load(Ordering::SeqCst),
+    })
+}
+
+/// Enables or disables the automatic pause while a screen share or presentation is
+/// active. Takes effect on the next detection poll.
+///
+/// Authentication: required. Returns JSON `null`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub fn set_screen_share_pause_enabled(
+    credential_state: tauri::State<'_, Arc<crate::credential_manager::CredentialManagerState>>,
+    enabled: bool,
+) -> Result<(), String> {
+    crate::commands::check_auth_required(&credential_state)?;
+    crate::screen_share::set_screen_share_pause_enabled(enabled)
+}
+
+/// Returns the clipboard history options.
+///
+/// Authentication: not required. Returns `{ "enabled", "retention_days",
+/// "excluded_processes" }`; `retention_days` 0 keeps entries until deleted.
+#[tauri::command]
+pub fn get_clipboard_history_settings() -> crate::clipboard_history::ClipboardHistorySettings {
+    crate::clipboard_history::load_settings()
+}
+
+/// Saves the clipboard history options. Enabling takes effect on the next copy;
+/// existing entries are kept when it is turned off.
+///
+/// Authentication: required. Returns the settings as stored, with process names
+/// lower-cased and de-duplicated. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub fn set_clipboard_history_settings(
+    credential_state: tauri::State<'_, Arc<crate::credential_manager::CredentialManagerState>>,
+    settings: crate::clipboard_history::ClipboardHistorySettings,
+) -> Result<crate::clipboard_history::ClipboardHistorySettings, String> {
+    crate::commands::check_auth_required(&credential_state)?;
+    crate::clipboard_history::save_settings(&settings)
+}
+
+/// Returns the audio capture and transcription options.
+///
+/// Authentication: not required. Returns `{ "enabled", "source", "chunk_secs",
+/// "model", "language", "retention_days" }`.
+#[tauri::command]
+pub fn get_audio_capture_settings() -> crate::audio_capture::AudioCaptureSettings {
+    crate::audio_capture::load_settings()
+}
+
+/// Saves the audio capture options and applies them to the running monitor, which
+/// starts or stops recording at its next chunk boundary. When the monitor is not
+/// running they apply from its next start.
+///
+/// Authentication: required. Returns the settings as stored, with unsupported values
+/// replaced by defaults. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn set_audio_capture_settings(
+    credential_state: tauri::State<'_, Arc<crate::credential_manager::CredentialManagerState>>,
+    monitor_state: tauri::State<'_, MonitorState>,
+    settings: crate::audio_capture::AudioCaptureSettings,
+) -> Result<crate::audio_capture::AudioCaptureSettings, String> {
+    crate::commands::check_auth_required(&credential_state)?;
+    let settings = crate::audio_capture::save_settings(settings)?;
+    if let Err(e) =
+        monitor::forward_command_to_python(&monitor_state, settings.to_monitor_command()).await
+    {
+        tracing::debug!("Audio capture settings not pushed to monitor: {}", e);
+    }
+    Ok(settings)
+}
+
 // Lightweight-mode commands.
 
 /// Switches to lightweight mode by destroying the main window.
@@ -52,6 +52,18 @@ const MASTER_KEY_FILE_NAME: &str = "credential_master_key.bin";
 const MASTER_KEY_LEN: usize = 32;
 const MASTER_KEY_FILE_MAGIC: &[u8; 5] = b"CPMK3"; // 版本升级
 const CNG_KEY_NAME: &str = "CarbonPaperMasterKeyV3";
+/// Registry values naming the CNG keys. The active key defaults to `CNG_KEY_NAME`;
+/// a pending key exists only while `credential_rotate_keys` runs, and every key
+/// replaced by a rotation is kept so payloads wrapped before it can still be unwrapped.
+const CNG_KEY_NAME_REGISTRY_KEY: &str = "cng_key_name";
+const CNG_PENDING_KEY_NAME_REGISTRY_KEY: &str = "cng_key_name_pending";
+/// `;`-separated names of replaced keys, oldest first.
+const CNG_RETIRED_KEY_NAMES_REGISTRY_KEY: &str = "cng_key_names_retired";
+/// Single replaced key recorded by earlier versions; folded into the retired list.
+const CNG_PREVIOUS_KEY_NAME_REGISTRY_KEY: &str = "cng_key_name_previous";
+const PUBLIC_KEY_FILE_NAME: &str = "credential_public_key.bin";
+/// Suffix of key files written by a rotation that has not switched over yet.
+const PENDING_KEY_FILE_SUFFIX: &str = ".next";
 // The Software KSP supports RSA encryption and protected UI policy.
 const CNG_PROVIDER_NAME: &str = "Microsoft Software Key Storage Provider";
 
@@ -189,6 +201,16 @@ impl CredentialManagerState {
         *last_auth = Some(std::time::Instant::now());
     }
 
+    /// Caches `master_key` and starts a session without touching key files.
+    #[cfg(test)]
+    pub(crate) fn unlock_for_test(&self, master_key: &[u8]) {
+        *self
+            .cached_master_key
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Some(master_key.to_vec());
+        self.update_auth_time();
+    }
+
     /// Invalidates UI access while retaining the master key for background encryption.
     pub fn invalidate_session(&self) {
         let mut last_auth = self
@@ -270,6 +292,11 @@ impl CredentialManagerState {
         self.file_path(MASTER_KEY_FILE_NAME)
     }
 
+    /// Returns whether a wrapped master key has been written to the data directory.
+    pub fn master_key_file_exists(&self) -> bool {
+        self.master_key_file_path().is_file()
+    }
+
     pub fn import_master_key(&self, master_key: &[u8]) -> Result<(), CredentialError> {
         if master_key.len() != MASTER_KEY_LEN {
             return Err(CredentialError::CryptoError(format!(
@@ -418,12 +445,17 @@ mod windows_impl {
 
     /// Exports the CNG RSA public key without invoking protected private-key UI.
     pub fn export_cng_public_key() -> Result<Vec<u8>, CredentialError> {
+        export_named_cng_public_key(&active_cng_key_name())
+    }
+
+    /// Exports the public blob of CNG key `name`, creating the key pair if needed.
+    pub fn export_named_cng_public_key(name: &str) -> Result<Vec<u8>, CredentialError> {
         use windows::core::HSTRING;
         use windows::Win32::Security::Cryptography::{
             NCryptExportKey, NCryptFreeObject, NCRYPT_FLAGS, NCRYPT_HANDLE, NCRYPT_KEY_HANDLE,
         };
 
-        let key = open_or_create_cng_key()?;
+        let key = open_cng_key(name, true)?;
 
         let blob_type = HSTRING::from("RSAPUBLICBLOB");
         let blob_pcwstr = windows::core::PCWSTR::from_raw(blob_type.as_ptr());
@@ -918,8 +950,68 @@ pub fn get_or_create_master_key_sync(
     Err(CredentialError::AuthRequired)
 }
 
+/// Name of the CNG key that currently protects the master key and new row keys.
+fn active_cng_key_name() -> String {
+    crate::registry_config::get_string(CNG_KEY_NAME_REGISTRY_KEY)
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| CNG_KEY_NAME.to_string())
+}
+
+/// Parses the retired key list and appends a legacy single previous name.
+fn parse_retired_key_names(list: Option<&str>, legacy_previous: Option<&str>) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for name in list
+        .unwrap_or_default()
+        .split(';')
+        .chain(legacy_previous)
+        .map(str::trim)
+    {
+        if !name.is_empty() && !names.iter().any(|known| known == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Adds `replaced` to the retired keys when a rotation switches to `pending`.
+fn retire_cng_key_name(mut retired: Vec<String>, replaced: &str, pending: &str) -> Vec<String> {
+    if !retired.iter().any(|name| name == replaced) {
+        retired.push(replaced.to_string());
+    }
+    retired.retain(|name| name != pending);
+    retired
+}
+
+/// CNG keys replaced by earlier rotations, oldest first.
+fn retired_cng_key_names() -> Vec<String> {
+    parse_retired_key_names(
+        crate::registry_config::get_string(CNG_RETIRED_KEY_NAMES_REGISTRY_KEY).as_deref(),
+        crate::registry_config::get_string(CNG_PREVIOUS_KEY_NAME_REGISTRY_KEY).as_deref(),
+    )
+}
+
+/// Other CNG keys that may have wrapped a stored row key: the key of a rotation in
+/// progress and every key replaced by a rotation, newest first.
+#[cfg(windows)]
+fn fallback_cng_key_names(active: &str) -> Vec<String> {
+    crate::registry_config::get_string(CNG_PENDING_KEY_NAME_REGISTRY_KEY)
+        .into_iter()
+        .chain(retired_cng_key_names().into_iter().rev())
+        .filter(|name| !name.is_empty() && name != active)
+        .collect()
+}
+
 #[cfg(windows)]
 fn open_or_create_cng_key(
+) -> Result<windows::Win32::Security::Cryptography::NCRYPT_KEY_HANDLE, CredentialError> {
+    open_cng_key(&active_cng_key_name(), true)
+}
+
+/// Opens the persisted CNG key `name`, creating it when `create_if_missing` is set.
+#[cfg(windows)]
+fn open_cng_key(
+    name: &str,
+    create_if_missing: bool,
 ) -> Result<windows::Win32::Security::Cryptography::NCRYPT_KEY_HANDLE, CredentialError> {
     use windows::core::{HSTRING, PCWSTR};
     use windows::Win32::Security::Cryptography::{
@@ -944,7 +1036,7 @@ fn open_or_create_cng_key(
 
     // Reuse the persisted key when it already exists.
     let mut key = NCRYPT_KEY_HANDLE::default();
-    let key_name = HSTRING::from(CNG_KEY_NAME);
+    let key_name = HSTRING::from(name);
     let key_pcwstr = PCWSTR::from_raw(key_name.as_ptr());
     // SAFETY: provider and key-name storage remain live; `key` is valid writable output
     // storage and CNG does not retain any Rust pointer.
@@ -965,6 +1057,12 @@ fn open_or_create_cng_key(
         return Ok(key);
     }
 
+    if !create_if_missing {
+        // SAFETY: the provider handle is owned here and no key was opened.
+        let _ = unsafe { NCryptFreeObject(NCRYPT_HANDLE(provider.0)) };
+        return Err(CredentialError::KeyNotFound);
+    }
+
     // Create a new persisted key when the lookup failed.
     let mut new_key = NCRYPT_KEY_HANDLE::default();
     // SAFETY: provider and algorithm/key-name strings are live, and `new_key` is writable
@@ -1122,16 +1220,43 @@ pub fn decrypt_master_key_with_cng_for_window(
     decrypt_master_key_with_cng_flags(ciphertext, NCRYPT_PAD_PKCS1_FLAG, owner_hwnd)
 }
 
+/// Decrypts with the active CNG key. Ciphertext that the active key rejects is retried
+/// with the pending and retired rotation keys, so row keys wrapped before any key
+/// rotation stay readable.
 #[cfg(windows)]
 fn decrypt_master_key_with_cng_flags(
     ciphertext: &[u8],
     flags: windows::Win32::Security::Cryptography::NCRYPT_FLAGS,
     owner_hwnd: Option<isize>,
+) -> Result<Vec<u8>, CredentialError> {
+    let active = active_cng_key_name();
+    match decrypt_with_named_cng_key(&active, true, ciphertext, flags, owner_hwnd) {
+        Err(CredentialError::SystemError(message)) => {
+            for name in fallback_cng_key_names(&active) {
+                if let Ok(plaintext) =
+                    decrypt_with_named_cng_key(&name, false, ciphertext, flags, owner_hwnd)
+                {
+                    return Ok(plaintext);
+                }
+            }
+            Err(CredentialError::SystemError(message))
+        }
+        result => result,
+    }
+}
+
+#[cfg(windows)]
+fn decrypt_with_named_cng_key(
+    name: &str,
+    create_if_missing: bool,
+    ciphertext: &[u8],
+    flags: windows::Win32::Security::Cryptography::NCRYPT_FLAGS,
+    owner_hwnd: Option<isize>,
 ) -> Result<Vec<u8>, CredentialError> {
     use windows::Win32::Foundation::NTE_SILENT_CONTEXT;
     use windows::Win32::Security::Cryptography::{NCryptDecrypt, NCryptFreeObject, NCRYPT_HANDLE};
 
-    let key = open_or_create_cng_key()?;
+    let key = open_cng_key(name, create_if_missing)?;
     if let Some(hwnd) = owner_hwnd {
         use windows::Win32::Security::Cryptography::{
             NCryptSetProperty, NCRYPT_WINDOW_HANDLE_PROPERTY,
@@ -1246,7 +1371,7 @@ pub fn load_public_key_from_file(
         return Ok(key);
     }
 
-    let key_file = state.file_path("credential_public_key.bin");
+    let key_file = state.file_path(PUBLIC_KEY_FILE_NAME);
     if !key_file.exists() {
         return Err(CredentialError::KeyNotFound);
     }
@@ -1270,7 +1395,7 @@ pub fn save_public_key_to_file(
     state: &CredentialManagerState,
     public_key: &[u8],
 ) -> Result<(), CredentialError> {
-    let key_file = state.file_path("credential_public_key.bin");
+    let key_file = state.file_path(PUBLIC_KEY_FILE_NAME);
 
     // Create the parent directory before atomically writing the public key.
     if let Some(parent) = key_file.parent() {
@@ -1285,6 +1410,154 @@ pub fn save_public_key_to_file(
     Ok(())
 }
 
+/// Keys a key rotation switches to: a fresh master key and the public half of a
+/// new CNG key pair.
+pub struct RotatedKeys {
+    pub master_key: Vec<u8>,
+    pub public_key: Vec<u8>,
+}
+
+impl Drop for RotatedKeys {
+    fn drop(&mut self) {
+        self.master_key.iter_mut().for_each(|b| *b = 0);
+    }
+}
+
+impl CredentialManagerState {
+    fn pending_key_file_path(&self, file_name: &str) -> PathBuf {
+        self.file_path(&format!("{}{}", file_name, PENDING_KEY_FILE_SUFFIX))
+    }
+}
+
+/// Creates the CNG key pair and master key a rotation switches to, and writes them
+/// next to the current key files with a `.next` suffix.
+///
+/// An interrupted rotation is resumed with its existing pending keys so row keys
+/// already re-wrapped for them stay valid. Requires an unlocked master key.
+#[cfg(windows)]
+pub fn begin_key_rotation(state: &CredentialManagerState) -> Result<RotatedKeys, CredentialError> {
+    use windows::Win32::Security::Cryptography::NCRYPT_PAD_PKCS1_FLAG;
+
+    if get_cached_master_key(state).is_none() {
+        return Err(CredentialError::AuthRequired);
+    }
+
+    let master_key_file = state.pending_key_file_path(MASTER_KEY_FILE_NAME);
+    let public_key_file = state.pending_key_file_path(PUBLIC_KEY_FILE_NAME);
+
+    if let Some(name) = crate::registry_config::get_string(CNG_PENDING_KEY_NAME_REGISTRY_KEY) {
+        if master_key_file.is_file() && public_key_file.is_file() {
+            let public_key = std::fs::read(&public_key_file).map_err(|e| {
+                CredentialError::SystemError(format!("Failed to read pending public key: {}", e))
+            })?;
+            let file_data = std::fs::read(&master_key_file).map_err(|e| {
+                CredentialError::SystemError(format!("Failed to read pending master key: {}", e))
+            })?;
+            let ciphertext = decode_master_key_file(&file_data)?;
+            let master_key =
+                decrypt_with_named_cng_key(&name, false, &ciphertext, NCRYPT_PAD_PKCS1_FLAG, None)?;
+            tracing::info!("[KEY_ROTATION] Resuming rotation to CNG key {}", name);
+            return Ok(RotatedKeys {
+                master_key,
+                public_key,
+            });
+        }
+    }
+
+    let created = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let name = format!("{}-{}", CNG_KEY_NAME, created);
+    let public_key = export_named_cng_public_key(&name)?;
+
+    let mut master_key = vec![0u8; MASTER_KEY_LEN];
+    rand::thread_rng().fill_bytes(&mut master_key);
+    let ciphertext = encrypt_with_exported_public_key(&public_key, &master_key)?;
+
+    std::fs::write(&master_key_file, encode_master_key_file(&ciphertext)).map_err(|e| {
+        CredentialError::SystemError(format!("Failed to save pending master key: {}", e))
+    })?;
+    std::fs::write(&public_key_file, &public_key).map_err(|e| {
+        CredentialError::SystemError(format!("Failed to save pending public key: {}", e))
+    })?;
+    crate::registry_config::set_string(CNG_PENDING_KEY_NAME_REGISTRY_KEY, &name)
+        .map_err(CredentialError::SystemError)?;
+
+    tracing::info!("[KEY_ROTATION] Created CNG key {}", name);
+    Ok(RotatedKeys {
+        master_key,
+        public_key,
+    })
+}
+
+#[cfg(not(windows))]
+pub fn begin_key_rotation(_state: &CredentialManagerState) -> Result<RotatedKeys, CredentialError> {
+    Err(CredentialError::SystemError(
+        "CNG is only available on Windows".to_string(),
+    ))
+}
+
+/// Public key of a rotation that has not switched over yet, if any.
+pub fn pending_rotation_public_key(state: &CredentialManagerState) -> Option<Vec<u8>> {
+    crate::registry_config::get_string(CNG_PENDING_KEY_NAME_REGISTRY_KEY)?;
+    std::fs::read(state.pending_key_file_path(PUBLIC_KEY_FILE_NAME)).ok()
+}
+
+/// Makes the pending rotation keys current: the `.next` files replace the key files
+/// and the pending CNG key becomes active. The replaced key is appended to the retired
+/// keys rather than deleted; vector-store payloads written by the Python service and
+/// audit-log targets are still wrapped with it, across any number of rotations.
+///
+/// `master_key` refreshes the session cache; pass `None` when recovering at startup,
+/// before the user has unlocked.
+pub fn commit_key_rotation(
+    state: &CredentialManagerState,
+    master_key: Option<&[u8]>,
+) -> Result<(), CredentialError> {
+    let pending = crate::registry_config::get_string(CNG_PENDING_KEY_NAME_REGISTRY_KEY)
+        .ok_or(CredentialError::KeyNotFound)?;
+
+    for file_name in [MASTER_KEY_FILE_NAME, PUBLIC_KEY_FILE_NAME] {
+        let pending_file = state.pending_key_file_path(file_name);
+        if pending_file.is_file() {
+            std::fs::rename(&pending_file, state.file_path(file_name)).map_err(|e| {
+                CredentialError::SystemError(format!("Failed to install {}: {}", file_name, e))
+            })?;
+        }
+    }
+
+    let previous = active_cng_key_name();
+    if previous != pending {
+        let retired = retire_cng_key_name(retired_cng_key_names(), &previous, &pending);
+        crate::registry_config::set_string(CNG_RETIRED_KEY_NAMES_REGISTRY_KEY, &retired.join(";"))
+            .map_err(CredentialError::SystemError)?;
+        crate::registry_config::delete_value(CNG_PREVIOUS_KEY_NAME_REGISTRY_KEY)
+            .map_err(CredentialError::SystemError)?;
+    }
+    crate::registry_config::set_string(CNG_KEY_NAME_REGISTRY_KEY, &pending)
+        .map_err(CredentialError::SystemError)?;
+    crate::registry_config::delete_value(CNG_PENDING_KEY_NAME_REGISTRY_KEY)
+        .map_err(CredentialError::SystemError)?;
+
+    *state
+        .cached_public_key
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) = None;
+    *state
+        .cached_db_key
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) = None;
+    *state
+        .cached_master_key
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) = master_key.map(|key| key.to_vec());
+    load_public_key_from_file(state)?;
+
+    tracing::info!("[KEY_ROTATION] Switched to CNG key {}", pending);
+    Ok(())
+}
+
 #[cfg(not(windows))]
 pub fn decrypt_row_key_with_cng(_ciphertext: &[u8]) -> Result<Vec<u8>, CredentialError> {
     Err(CredentialError::SystemError(
@@ -1322,6 +1595,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn retired_key_names_fold_in_the_legacy_previous_key() {
+        assert!(parse_retired_key_names(None, None).is_empty());
+        assert_eq!(
+            parse_retired_key_names(None, Some("CarbonPaperMasterKeyV3")),
+            vec!["CarbonPaperMasterKeyV3"]
+        );
+        assert_eq!(
+            parse_retired_key_names(
+                Some("CarbonPaperMasterKeyV3;;CarbonPaperMasterKeyV3-1"),
+                Some("CarbonPaperMasterKeyV3"),
+            ),
+            vec!["CarbonPaperMasterKeyV3", "CarbonPaperMasterKeyV3-1"]
+        );
+    }
+
+    #[test]
+    fn every_rotation_keeps_the_keys_it_replaced() {
+        let first = retire_cng_key_name(Vec::new(), CNG_KEY_NAME, "CarbonPaperMasterKeyV3-1");
+        assert_eq!(first, vec![CNG_KEY_NAME]);
+        let second = retire_cng_key_name(
+            first,
+            "CarbonPaperMasterKeyV3-1",
+            "CarbonPaperMasterKeyV3-2",
+        );
+        assert_eq!(second, vec![CNG_KEY_NAME, "CarbonPaperMasterKeyV3-1"]);
+        // Re-running an interrupted commit does not duplicate the entry.
+        assert_eq!(
+            retire_cng_key_name(
+                second.clone(),
+                "CarbonPaperMasterKeyV3-1",
+                "CarbonPaperMasterKeyV3-2"
+            ),
+            second
+        );
+    }
+
     #[test]
     fn test_encrypt_decrypt() {
         let public_key = b"12345678901234567890123456789012";
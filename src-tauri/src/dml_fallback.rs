@@ -0,0 +1,187 @@
+//! Automatic fallback when the selected DirectML device fails.
+//!
+//! A DirectML device can disappear under a running monitor (eGPU undocked, driver
+//! reset) or fail to initialize after a driver change. Instead of dropping OCR, the
+//! engine that hit the failure keeps going on CPU and the decision is persisted here:
+//! the Python monitor's `dml_device_id` moves to the next DirectML-capable adapter (or
+//! `use_dml` is turned off when none is left), and the Rust OCR DirectML Beta is turned
+//! off. Each fallback is emitted as `dml-fallback` and shown as a notification.
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter};
+
+/// Prefix of the stdout line the Python OCR engine prints after leaving DirectML.
+const PYTHON_FALLBACK_MARKER: &str = "[DML_FALLBACK]";
+
+/// Payload of the `dml-fallback` event.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DmlFallback {
+    /// `"python"` for the monitor's OCR engine, `"rust"` for the Rust OCR worker.
+    pub engine: &'static str,
+    pub failed_device_id: Option<u32>,
+    /// Adapter used from now on; `None` means CPU.
+    pub fallback_device_id: Option<u32>,
+    pub fallback_device_name: Option<String>,
+    pub error: String,
+}
+
+/// Device DirectML should run on: `configured` while it is still listed and
+/// DirectML-capable, otherwise the recommended adapter other than `exclude`.
+pub fn select_dml_device(gpus: &[Value], configured: u32, exclude: Option<u32>) -> Option<u32> {
+    let usable: Vec<Value> = gpus
+        .iter()
+        .filter(|gpu| gpu["id"].as_u64().map(|id| id as u32) != exclude)
+        .cloned()
+        .collect();
+    if usable.iter().any(|gpu| {
+        gpu["id"].as_u64() == Some(configured as u64)
+            && gpu["directml_supported"].as_bool() == Some(true)
+    }) {
+        return Some(configured);
+    }
+    crate::monitor::recommend_gpu(&usable)
+}
+
+/// `(failed_device_id, error)` reported by a Python `[DML_FALLBACK] {...}` line.
+pub fn parse_python_fallback_line(line: &str) -> Option<(Option<u32>, String)> {
+    let payload = line.trim().strip_prefix(PYTHON_FALLBACK_MARKER)?;
+    let value: Value = serde_json::from_str(payload.trim()).ok()?;
+    let device_id = value
+        .get("device_id")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32);
+    let error = value
+        .get("error")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+    Some((device_id, error))
+}
+
+/// Persists a new device for the Python monitor after `failed_device_id` failed, or
+/// turns `use_dml` off when no other DirectML-capable adapter is present. The running
+/// monitor already continues on CPU; the choice applies from its next start.
+pub fn fall_back_python_dml(
+    app: &AppHandle,
+    failed_device_id: Option<u32>,
+    error: &str,
+) -> Result<DmlFallback, String> {
+    let gpus = crate::monitor::enumerate_gpus_internal().unwrap_or_default();
+    let configured = crate::registry_config::get_u32("dml_device_id").unwrap_or(0);
+    let failed = failed_device_id.unwrap_or(configured);
+    let fallback_device_id = select_dml_device(&gpus, configured, Some(failed));
+    match fallback_device_id {
+        Some(device_id) => crate::registry_config::set_u32("dml_device_id", device_id)?,
+        None => crate::registry_config::set_bool("use_dml", false)?,
+    }
+    let fallback_device_name = fallback_device_id.and_then(|device_id| {
+        gpus.iter()
+            .find(|gpu| gpu["id"].as_u64() == Some(device_id as u64))
+            .and_then(|gpu| gpu["name"].as_str())
+            .map(str::to_string)
+    });
+    let fallback = DmlFallback {
+        engine: "python",
+        failed_device_id: Some(failed),
+        fallback_device_id,
+        fallback_device_name,
+        error: error.to_string(),
+    };
+    report_fallback(app, &fallback);
+    Ok(fallback)
+}
+
+/// Turns the Rust OCR DirectML Beta off after it failed; the worker has already
+/// switched to CPU for this session.
+pub fn fall_back_rust_dml(app: &AppHandle, error: &str) -> Result<DmlFallback, String> {
+    crate::registry_config::set_bool("rust_ocr_dml_beta", false)?;
+    let fallback = DmlFallback {
+        engine: "rust",
+        failed_device_id: None,
+        fallback_device_id: None,
+        fallback_device_name: None,
+        error: error.to_string(),
+    };
+    report_fallback(app, &fallback);
+    Ok(fallback)
+}
+
+/// Logs, emits `dml-fallback` and shows a native notification for `fallback`.
+pub fn report_fallback(app: &AppHandle, fallback: &DmlFallback) {
+    use tauri_plugin_notification::NotificationExt;
+
+    tracing::warn!(
+        "[ML:DML] {} OCR left DirectML device {:?} for {:?}: {}",
+        fallback.engine,
+        fallback.failed_device_id,
+        fallback.fallback_device_id,
+        fallback.error
+    );
+    let _ = app.emit("dml-fallback", fallback);
+
+    let language =
+        crate::registry_config::get_string("language").unwrap_or_else(|| "zh-CN".to_string());
+    let title = crate::i18n::t(&language, "notifications.dml_fallback.title");
+    let body = match fallback.fallback_device_id {
+        _ if fallback.engine == "rust" => {
+            crate::i18n::t(&language, "notifications.dml_fallback.body_rust")
+        }
+        Some(device_id) => crate::i18n::t(&language, "notifications.dml_fallback.body_gpu")
+            .replace(
+                "{{device}}",
+                &fallback
+                    .fallback_device_name
+                    .clone()
+                    .unwrap_or_else(|| format!("GPU {}", device_id)),
+            ),
+        None => crate::i18n::t(&language, "notifications.dml_fallback.body_cpu"),
+    };
+    if let Err(error) = app.notification().builder().title(&title).body(body).show() {
+        tracing::warn!("Failed to show DirectML fallback notification: {}", error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gpu(id: u32, vram_mb: u64, directml: bool) -> Value {
+        serde_json::json!({
+            "id": id,
+            "dedicated_vram_mb": vram_mb,
+            "directml_supported": directml,
+        })
+    }
+
+    #[test]
+    fn keeps_configured_device_while_present_and_capable() {
+        let gpus = vec![gpu(0, 128, true), gpu(1, 8192, true)];
+        assert_eq!(select_dml_device(&gpus, 0, None), Some(0));
+    }
+
+    #[test]
+    fn moves_off_failed_or_missing_device_and_returns_none_for_cpu() {
+        let gpus = vec![gpu(0, 128, true), gpu(1, 8192, true), gpu(2, 16384, false)];
+        assert_eq!(select_dml_device(&gpus, 1, Some(1)), Some(0));
+        assert_eq!(select_dml_device(&gpus, 5, None), Some(1));
+        assert_eq!(select_dml_device(&gpus[..1], 0, Some(0)), None);
+        assert_eq!(select_dml_device(&[], 0, None), None);
+    }
+
+    #[test]
+    fn parses_python_fallback_marker_only() {
+        assert_eq!(
+            parse_python_fallback_line(
+                "[DML_FALLBACK] {\"device_id\": 1, \"error\": \"device removed\"}"
+            ),
+            Some((Some(1), "device removed".to_string()))
+        );
+        assert_eq!(
+            parse_python_fallback_line("[DML_FALLBACK] {\"error\": \"init failed\"}"),
+            Some((None, "init failed".to_string()))
+        );
+        assert_eq!(parse_python_fallback_line("[INFO] OCR ready"), None);
+        assert_eq!(parse_python_fallback_line("[DML_FALLBACK] not json"), None);
+    }
+}
@@ -0,0 +1,339 @@
+//! Startup preflight for DirectML after GPU driver or adapter changes.
+//!
+//! A driver update or a changed adapter list (eGPU, new card, hybrid graphics switch)
+//! can make the first DirectML session after launch stutter or crash the OCR worker
+//! mid-capture. At startup we fingerprint every hardware adapter — identity plus user
+//! mode driver version — and compare it with the previous run. When it changed, the
+//! selected `dml_device_id` is remapped to the same physical adapter and the Rust OCR
+//! DirectML path is benchmarked once before capture relies on it; a failing or very
+//! slow run keeps OCR on CPU for the session.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use windows::core::Interface;
+use windows::Win32::Graphics::Dxgi::{
+    CreateDXGIFactory1, IDXGIDevice, IDXGIFactory1, DXGI_ADAPTER_FLAG_SOFTWARE,
+};
+
+use crate::ml_runtime::MlRuntimeState;
+
+const FINGERPRINT_REGISTRY_KEY: &str = "dml_adapter_fingerprint";
+/// Let the model health check and tray setup finish before loading the GPU.
+const PREFLIGHT_DELAY: Duration = Duration::from_secs(3);
+/// The first DirectML run compiles shaders for the new driver, which can take a while.
+const BENCHMARK_TIMEOUT: Duration = Duration::from_secs(90);
+/// A warm DirectML pass on the benchmark frame slower than this is treated as broken.
+const BENCHMARK_MAX_WARM_MS: f64 = 5_000.0;
+const BENCHMARK_WIDTH: u32 = 1280;
+const BENCHMARK_HEIGHT: u32 = 720;
+
+/// Identity and driver version of one hardware adapter, as seen by DXGI.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GpuAdapterFingerprint {
+    /// DXGI enumeration index, which is what `dml_device_id` refers to.
+    pub index: u32,
+    pub name: String,
+    pub vendor_id: u32,
+    pub device_id: u32,
+    pub subsys_id: u32,
+    pub revision: u32,
+    /// User mode driver version (`a.b.c.d`), if DXGI reports it.
+    pub driver_version: Option<String>,
+}
+
+impl GpuAdapterFingerprint {
+    fn same_hardware(&self, other: &Self) -> bool {
+        self.vendor_id == other.vendor_id
+            && self.device_id == other.device_id
+            && self.subsys_id == other.subsys_id
+            && self.revision == other.revision
+    }
+}
+
+/// Result of a startup preflight; emitted to the frontend as `dml-preflight`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DmlPreflightReport {
+    /// Human-readable adapter/driver differences since the last run.
+    pub changes: Vec<String>,
+    /// `(old, new)` when `dml_device_id` was moved to follow its adapter.
+    pub remapped_device_id: Option<(u32, u32)>,
+    /// Cold and warm DirectML OCR timings, in milliseconds.
+    pub benchmark_ms: Vec<f64>,
+    /// `Some(false)` when DirectML was disabled for this session.
+    pub directml_ok: Option<bool>,
+    pub error: Option<String>,
+}
+
+/// Formats a packed user mode driver version as `a.b.c.d`.
+pub(crate) fn format_driver_version(umd_version: i64) -> String {
+    let v = umd_version as u64;
+    format!(
+        "{}.{}.{}.{}",
+        (v >> 48) & 0xFFFF,
+        (v >> 32) & 0xFFFF,
+        (v >> 16) & 0xFFFF,
+        v & 0xFFFF
+    )
+}
+
+/// Fingerprints every hardware adapter, excluding software renderers.
+pub fn collect_adapter_fingerprints() -> Result<Vec<GpuAdapterFingerprint>, String> {
+    // SAFETY: DXGI returns reference-counted COM interfaces whose lifetimes are managed
+    // by windows-rs; adapter indices are enumerated until Windows reports exhaustion.
+    unsafe {
+        let factory: IDXGIFactory1 =
+            CreateDXGIFactory1().map_err(|e| format!("Failed to create DXGI factory: {:?}", e))?;
+
+        let mut adapters = Vec::new();
+        let mut i: u32 = 0;
+        while let Ok(adapter) = factory.EnumAdapters1(i) {
+            let desc = adapter
+                .GetDesc1()
+                .map_err(|e| format!("Failed to get adapter desc: {:?}", e))?;
+            if (desc.Flags & DXGI_ADAPTER_FLAG_SOFTWARE.0 as u32) == 0 {
+                let name = String::from_utf16_lossy(
+                    &desc.Description[..desc
+                        .Description
+                        .iter()
+                        .position(|&c| c == 0)
+                        .unwrap_or(desc.Description.len())],
+                );
+                let driver_version = adapter
+                    .CheckInterfaceSupport(&IDXGIDevice::IID)
+                    .ok()
+                    .map(format_driver_version);
+                adapters.push(GpuAdapterFingerprint {
+                    index: i,
+                    name: name.trim().to_string(),
+                    vendor_id: desc.VendorId,
+                    device_id: desc.DeviceId,
+                    subsys_id: desc.SubSysId,
+                    revision: desc.Revision,
+                    driver_version,
+                });
+            }
+            i += 1;
+        }
+        Ok(adapters)
+    }
+}
+
+/// Describe what changed between two adapter snapshots; empty when the DML setup
+/// from the previous run can be trusted as-is.
+pub(crate) fn check_dml_setup_needed(
+    previous: &[GpuAdapterFingerprint],
+    current: &[GpuAdapterFingerprint],
+) -> Vec<String> {
+    let mut changes = Vec::new();
+    for adapter in current {
+        match previous.iter().find(|p| p.same_hardware(adapter)) {
+            None => changes.push(format!("adapter added: {}", adapter.name)),
+            Some(p) if p.driver_version != adapter.driver_version => changes.push(format!(
+                "driver changed for {}: {} -> {}",
+                adapter.name,
+                p.driver_version.as_deref().unwrap_or("unknown"),
+                adapter.driver_version.as_deref().unwrap_or("unknown")
+            )),
+            Some(p) if p.index != adapter.index => changes.push(format!(
+                "adapter {} moved from index {} to {}",
+                adapter.name, p.index, adapter.index
+            )),
+            Some(_) => {}
+        }
+    }
+    for adapter in previous {
+        if !current.iter().any(|c| c.same_hardware(adapter)) {
+            changes.push(format!("adapter removed: {}", adapter.name));
+        }
+    }
+    changes
+}
+
+/// New index of the adapter `device_id` pointed to last run, if it moved.
+fn remap_device_id(
+    previous: &[GpuAdapterFingerprint],
+    current: &[GpuAdapterFingerprint],
+    device_id: u32,
+) -> Option<u32> {
+    let selected = previous.iter().find(|p| p.index == device_id)?;
+    current
+        .iter()
+        .find(|c| c.same_hardware(selected))
+        .map(|c| c.index)
+        .filter(|&index| index != device_id)
+}
+
+/// White frame with dark text-like bars, so detection and recognition both run.
+fn benchmark_frame() -> image::RgbImage {
+    image::RgbImage::from_fn(BENCHMARK_WIDTH, BENCHMARK_HEIGHT, |x, y| {
+        let line = y / 36;
+        let in_text_row = (y % 36) < 18 && line % 2 == 0;
+        let in_glyph = (x / 9) % 3 != 0 && x < BENCHMARK_WIDTH - (line * 37) % 400;
+        if in_text_row && in_glyph {
+            image::Rgb([20, 20, 20])
+        } else {
+            image::Rgb([250, 250, 250])
+        }
+    })
+}
+
+async fn benchmark_rust_ocr_directml(app: &AppHandle, report: &mut DmlPreflightReport) {
+    let ml = app.state::<Arc<MlRuntimeState>>().inner().clone();
+    let frame = Arc::new(benchmark_frame());
+    for _ in 0..2 {
+        let started = Instant::now();
+        // run_ocr disables DirectML for the session itself when the provider fails.
+        let result = ml
+            .run_ocr(app.clone(), frame.clone(), BENCHMARK_TIMEOUT, true)
+            .await;
+        report
+            .benchmark_ms
+            .push(started.elapsed().as_secs_f64() * 1000.0);
+        if let Err(e) = result {
+            report.directml_ok = Some(false);
+            report.error = Some(e);
+            return;
+        }
+    }
+    let warm_ms = report.benchmark_ms.last().copied().unwrap_or_default();
+    if warm_ms > BENCHMARK_MAX_WARM_MS {
+        let error = format!(
+            "DirectML OCR benchmark took {:.0} ms after warm-up (limit {:.0} ms)",
+            warm_ms, BENCHMARK_MAX_WARM_MS
+        );
+        ml.disable_directml_for_session(&error);
+        report.directml_ok = Some(false);
+        report.error = Some(error);
+    } else {
+        report.directml_ok = Some(true);
+    }
+}
+
+async fn run_preflight(app: &AppHandle) -> Result<Option<DmlPreflightReport>, String> {
+    let current = tokio::task::spawn_blocking(collect_adapter_fingerprints)
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))??;
+    let previous: Option<Vec<GpuAdapterFingerprint>> =
+        crate::registry_config::get_string(FINGERPRINT_REGISTRY_KEY)
+            .and_then(|v| serde_json::from_str(&v).ok());
+    let save_current = || {
+        let value = serde_json::to_string(&current)
+            .map_err(|e| format!("Failed to serialize adapter fingerprint: {}", e))?;
+        crate::registry_config::set_string(FINGERPRINT_REGISTRY_KEY, &value)
+    };
+
+    // First run after install/upgrade: nothing to compare against yet.
+    let Some(previous) = previous else {
+        save_current()?;
+        return Ok(None);
+    };
+    let changes = check_dml_setup_needed(&previous, &current);
+    if changes.is_empty() {
+        return Ok(None);
+    }
+    for change in &changes {
+        tracing::info!("[ML:DML] GPU preflight: {}", change);
+    }
+    let mut report = DmlPreflightReport {
+        changes,
+        ..Default::default()
+    };
+
+    if crate::registry_config::get_bool("use_dml").unwrap_or(false) {
+        let device_id = crate::registry_config::get_u32("dml_device_id").unwrap_or(0);
+        if let Some(new_id) = remap_device_id(&previous, &current, device_id) {
+            tracing::info!(
+                "[ML:DML] GPU preflight: dml_device_id {} follows its adapter to {}",
+                device_id,
+                new_id
+            );
+            crate::registry_config::set_u32("dml_device_id", new_id)?;
+            report.remapped_device_id = Some((device_id, new_id));
+        }
+    }
+
+    let dml_beta = crate::registry_config::get_bool("rust_ocr_dml_beta").unwrap_or(false);
+    let app_for_status = app.clone();
+    let model_installed =
+        tokio::task::spawn_blocking(move || crate::ml_runtime::ocr_model_status(&app_for_status))
+            .await
+            .map_err(|e| format!("Task join error: {:?}", e))?
+            .map(|status| status.installed)
+            .unwrap_or(false);
+    if dml_beta && model_installed && !current.is_empty() {
+        benchmark_rust_ocr_directml(app, &mut report).await;
+        tracing::info!(
+            "[ML:DML] GPU preflight benchmark ok={:?} timings_ms={:?}",
+            report.directml_ok,
+            report.benchmark_ms
+        );
+    }
+
+    // Record the new snapshot even when DirectML failed: it stays disabled for this
+    // session, and re-benchmarking on every launch would only repeat the stall.
+    save_current()?;
+    Ok(Some(report))
+}
+
+/// Run the DirectML preflight shortly after startup, before capture loads the GPU.
+pub fn schedule_startup_preflight(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(PREFLIGHT_DELAY).await;
+        match run_preflight(&app).await {
+            Ok(Some(report)) => {
+                let _ = app.emit("dml-preflight", &report);
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("[ML:DML] GPU preflight failed: {}", e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adapter(index: u32, device_id: u32, driver: &str) -> GpuAdapterFingerprint {
+        GpuAdapterFingerprint {
+            index,
+            name: format!("GPU {:04x}", device_id),
+            vendor_id: 0x10de,
+            device_id,
+            subsys_id: 0,
+            revision: 1,
+            driver_version: Some(driver.to_string()),
+        }
+    }
+
+    #[test]
+    fn driver_updates_and_topology_changes_are_detected() {
+        let previous = vec![
+            adapter(0, 0x2684, "32.0.15.6094"),
+            adapter(1, 0x1234, "1.0.0.0"),
+        ];
+        assert!(check_dml_setup_needed(&previous, &previous).is_empty());
+
+        let updated = vec![
+            adapter(0, 0x2684, "32.0.15.6590"),
+            adapter(1, 0x1234, "1.0.0.0"),
+        ];
+        let changes = check_dml_setup_needed(&previous, &updated);
+        assert_eq!(changes.len(), 1);
+        assert!(changes[0].contains("32.0.15.6590"));
+
+        // The integrated GPU disappeared and the discrete card moved to index 0.
+        let moved = vec![adapter(0, 0x1234, "1.0.0.0")];
+        assert_eq!(check_dml_setup_needed(&previous, &moved).len(), 2);
+        assert_eq!(remap_device_id(&previous, &moved, 1), Some(0));
+        assert_eq!(remap_device_id(&previous, &moved, 0), None);
+        assert_eq!(remap_device_id(&previous, &previous, 1), None);
+    }
+
+    #[test]
+    fn driver_version_is_formatted_from_umd_words() {
+        let umd = (32i64 << 48) | (15 << 16) | 6590;
+        assert_eq!(format_driver_version(umd), "32.0.15.6590");
+    }
+}
@@ -0,0 +1,262 @@
+//! User-tunable triggers for game mode's DirectML suppression.
+//!
+//! The game-mode monitor (`monitor::start_game_mode_monitor`) restarts the OCR
+//! runtime without DirectML while the GPU is busy. By default "busy" means GPU 0
+//! dedicated memory at or above 50%, and DirectML returns once it drops to 40%. Users
+//! can change both thresholds, name processes (games, renderers) whose presence
+//! suppresses DirectML regardless of memory use, and require the GPU to stay quiet for
+//! a while before DirectML is restored. Settings are re-read on every GPU poll.
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+const PROCESSES_KEY: &str = "game_mode_processes";
+const SUPPRESS_THRESHOLD_KEY: &str = "game_mode_suppress_threshold_pct";
+const RESUME_THRESHOLD_KEY: &str = "game_mode_resume_threshold_pct";
+const RESUME_DELAY_KEY: &str = "game_mode_resume_delay_secs";
+
+const DEFAULT_SUPPRESS_THRESHOLD_PCT: u32 = 50;
+const DEFAULT_RESUME_THRESHOLD_PCT: u32 = 40;
+const MIN_SUPPRESS_THRESHOLD_PCT: u32 = 10;
+const MAX_SUPPRESS_THRESHOLD_PCT: u32 = 100;
+/// Smallest gap between the two thresholds, so DirectML does not flap at the boundary.
+const MIN_THRESHOLD_GAP_PCT: u32 = 5;
+const MAX_RESUME_DELAY_SECS: u32 = 3600;
+
+/// Game-mode trigger settings, as exposed to the settings UI.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameModeSettings {
+    /// Lower-case process names that suppress DirectML while running.
+    pub processes: Vec<String>,
+    /// GPU 0 dedicated-memory use, in percent, at which DirectML is suppressed.
+    pub suppress_threshold_pct: u32,
+    /// Use, in percent, at or below which DirectML may be restored.
+    pub resume_threshold_pct: u32,
+    /// How long the GPU must stay quiet before DirectML is restored. The GPU is polled
+    /// about every 9 seconds, so shorter delays round up to the next poll.
+    pub resume_delay_secs: u32,
+}
+
+impl Default for GameModeSettings {
+    fn default() -> Self {
+        Self {
+            processes: Vec::new(),
+            suppress_threshold_pct: DEFAULT_SUPPRESS_THRESHOLD_PCT,
+            resume_threshold_pct: DEFAULT_RESUME_THRESHOLD_PCT,
+            resume_delay_secs: 0,
+        }
+    }
+}
+
+/// What the monitor should do with DirectML after a poll.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DmlAction {
+    Suppress,
+    Restore,
+    Keep,
+}
+
+impl GameModeSettings {
+    fn normalized(mut self) -> Self {
+        let mut processes: Vec<String> = Vec::new();
+        for name in &self.processes {
+            let name = name.trim().to_lowercase();
+            if !name.is_empty() && !processes.contains(&name) {
+                processes.push(name);
+            }
+        }
+        self.processes = processes;
+        self.suppress_threshold_pct = self
+            .suppress_threshold_pct
+            .clamp(MIN_SUPPRESS_THRESHOLD_PCT, MAX_SUPPRESS_THRESHOLD_PCT);
+        self.resume_threshold_pct = self
+            .resume_threshold_pct
+            .min(self.suppress_threshold_pct - MIN_THRESHOLD_GAP_PCT);
+        self.resume_delay_secs = self.resume_delay_secs.min(MAX_RESUME_DELAY_SECS);
+        self
+    }
+
+    /// Decides the next step from the latest GPU memory use (`None` when the query
+    /// failed) and whether a listed process is running. `quiet_since` is the monitor's
+    /// record of when the GPU last became quiet and is updated here.
+    pub fn decide(
+        &self,
+        suppressed: bool,
+        usage: Option<f64>,
+        process_running: bool,
+        quiet_since: &mut Option<Instant>,
+        now: Instant,
+    ) -> DmlAction {
+        if !suppressed {
+            *quiet_since = None;
+            let busy = process_running
+                || usage.is_some_and(|u| u * 100.0 >= self.suppress_threshold_pct as f64);
+            return if busy {
+                DmlAction::Suppress
+            } else {
+                DmlAction::Keep
+            };
+        }
+
+        let quiet = !process_running
+            && usage.is_some_and(|u| u * 100.0 <= self.resume_threshold_pct as f64);
+        if !quiet {
+            *quiet_since = None;
+            return DmlAction::Keep;
+        }
+        let since = *quiet_since.get_or_insert(now);
+        if now.duration_since(since) >= Duration::from_secs(self.resume_delay_secs as u64) {
+            *quiet_since = None;
+            DmlAction::Restore
+        } else {
+            DmlAction::Keep
+        }
+    }
+}
+
+pub fn load_settings() -> GameModeSettings {
+    let defaults = GameModeSettings::default();
+    GameModeSettings {
+        processes: crate::registry_config::get_string(PROCESSES_KEY)
+            .and_then(|raw| serde_json::from_str::<Vec<String>>(&raw).ok())
+            .unwrap_or_default(),
+        suppress_threshold_pct: crate::registry_config::get_u32(SUPPRESS_THRESHOLD_KEY)
+            .unwrap_or(defaults.suppress_threshold_pct),
+        resume_threshold_pct: crate::registry_config::get_u32(RESUME_THRESHOLD_KEY)
+            .unwrap_or(defaults.resume_threshold_pct),
+        resume_delay_secs: crate::registry_config::get_u32(RESUME_DELAY_KEY)
+            .unwrap_or(defaults.resume_delay_secs),
+    }
+    .normalized()
+}
+
+/// Normalizes and persists `settings`, returning what was saved.
+pub fn save_settings(settings: GameModeSettings) -> Result<GameModeSettings, String> {
+    let settings = settings.normalized();
+    let processes = serde_json::to_string(&settings.processes)
+        .map_err(|e| format!("Failed to serialize game mode processes: {}", e))?;
+    crate::registry_config::set_string(PROCESSES_KEY, &processes)?;
+    crate::registry_config::set_u32(SUPPRESS_THRESHOLD_KEY, settings.suppress_threshold_pct)?;
+    crate::registry_config::set_u32(RESUME_THRESHOLD_KEY, settings.resume_threshold_pct)?;
+    crate::registry_config::set_u32(RESUME_DELAY_KEY, settings.resume_delay_secs)?;
+    Ok(settings)
+}
+
+/// First listed process that is currently running, refreshing `sys` to find out.
+pub fn find_running_process(sys: &mut sysinfo::System, names: &[String]) -> Option<String> {
+    if names.is_empty() {
+        return None;
+    }
+    sys.refresh_processes();
+    sys.processes()
+        .values()
+        .map(|process| process.name().to_lowercase())
+        .find(|name| names.contains(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_are_normalized_with_a_threshold_gap() {
+        let settings = GameModeSettings {
+            processes: vec![" Game.EXE ".into(), "game.exe".into(), "".into()],
+            suppress_threshold_pct: 150,
+            resume_threshold_pct: 99,
+            resume_delay_secs: 90_000,
+        }
+        .normalized();
+        assert_eq!(settings.processes, vec!["game.exe".to_string()]);
+        assert_eq!(settings.suppress_threshold_pct, 100);
+        assert_eq!(settings.resume_threshold_pct, 95);
+        assert_eq!(settings.resume_delay_secs, MAX_RESUME_DELAY_SECS);
+
+        let low = GameModeSettings {
+            suppress_threshold_pct: 0,
+            resume_threshold_pct: 40,
+            ..GameModeSettings::default()
+        }
+        .normalized();
+        assert_eq!(low.suppress_threshold_pct, MIN_SUPPRESS_THRESHOLD_PCT);
+        assert_eq!(low.resume_threshold_pct, 5);
+    }
+
+    #[test]
+    fn listed_process_or_threshold_suppresses_and_delay_gates_restore() {
+        let settings = GameModeSettings {
+            suppress_threshold_pct: 70,
+            resume_threshold_pct: 30,
+            resume_delay_secs: 20,
+            ..GameModeSettings::default()
+        };
+        let start = Instant::now();
+        let mut quiet_since = None;
+
+        assert_eq!(
+            settings.decide(false, Some(0.6), false, &mut quiet_since, start),
+            DmlAction::Keep
+        );
+        assert_eq!(
+            settings.decide(false, Some(0.75), false, &mut quiet_since, start),
+            DmlAction::Suppress
+        );
+        assert_eq!(
+            settings.decide(false, None, true, &mut quiet_since, start),
+            DmlAction::Suppress
+        );
+
+        // Quiet GPU but the game is still running: stay suppressed.
+        assert_eq!(
+            settings.decide(true, Some(0.1), true, &mut quiet_since, start),
+            DmlAction::Keep
+        );
+        assert_eq!(quiet_since, None);
+
+        assert_eq!(
+            settings.decide(true, Some(0.2), false, &mut quiet_since, start),
+            DmlAction::Keep
+        );
+        assert_eq!(
+            settings.decide(
+                true,
+                Some(0.5),
+                false,
+                &mut quiet_since,
+                start + Duration::from_secs(9)
+            ),
+            DmlAction::Keep
+        );
+        assert_eq!(quiet_since, None, "a busy poll restarts the delay");
+
+        let quiet_start = start + Duration::from_secs(18);
+        assert_eq!(
+            settings.decide(true, Some(0.2), false, &mut quiet_since, quiet_start),
+            DmlAction::Keep
+        );
+        assert_eq!(
+            settings.decide(
+                true,
+                Some(0.2),
+                false,
+                &mut quiet_since,
+                quiet_start + Duration::from_secs(20)
+            ),
+            DmlAction::Restore
+        );
+    }
+
+    #[test]
+    fn default_settings_restore_on_the_first_quiet_poll() {
+        let settings = GameModeSettings::default();
+        let mut quiet_since = None;
+        assert_eq!(
+            settings.decide(true, Some(0.3), false, &mut quiet_since, Instant::now()),
+            DmlAction::Restore
+        );
+        assert_eq!(
+            settings.decide(true, None, false, &mut quiet_since, Instant::now()),
+            DmlAction::Keep
+        );
+    }
+}
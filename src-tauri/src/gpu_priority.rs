@@ -0,0 +1,266 @@
+//! Idle-priority GPU inference for DirectML OCR.
+//!
+//! When enabled, the OCR worker processes are moved to the IDLE GPU scheduling priority
+//! class, so the Windows GPU scheduler serves games and video editors first, and
+//! DirectML OCR of a frame is deferred while the foreground app's own GPU utilization
+//! is above a threshold. Unlike game mode, which restarts the monitor without DirectML
+//! once VRAM fills up, this keeps DirectML loaded and only delays individual frames.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+use windows::core::{s, w};
+use windows::Win32::Foundation::HANDLE;
+use windows::Win32::System::LibraryLoader::{GetProcAddress, LoadLibraryW};
+use windows::Win32::System::Performance::{
+    PdhAddEnglishCounterW, PdhCloseQuery, PdhCollectQueryData, PdhGetFormattedCounterArrayW,
+    PdhOpenQueryW, PDH_FMT_COUNTERVALUE_ITEM_W, PDH_FMT_DOUBLE,
+};
+use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+pub const DML_IDLE_PRIORITY_KEY: &str = "dml_idle_priority";
+pub const DML_IDLE_GPU_THRESHOLD_KEY: &str = "dml_idle_gpu_threshold";
+pub const DEFAULT_DML_IDLE_GPU_THRESHOLD: u32 = 60;
+pub const MIN_DML_IDLE_GPU_THRESHOLD: u32 = 10;
+/// The foreground app counts as idle again this many points below the threshold.
+const THRESHOLD_HYSTERESIS: f64 = 10.0;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Gap between the two PDH samples a utilization reading is computed from.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+/// Longest a frame waits for the foreground app before OCR runs anyway.
+const MAX_DEFER: Duration = Duration::from_secs(60);
+const DEFER_POLL: Duration = Duration::from_millis(500);
+
+/// `D3DKMT_SCHEDULINGPRIORITYCLASS_IDLE`.
+const D3DKMT_SCHEDULINGPRIORITYCLASS_IDLE: i32 = 0;
+const PDH_MORE_DATA: u32 = 0x800007D2;
+
+static FOREGROUND_GPU_BUSY: AtomicBool = AtomicBool::new(false);
+static MONITOR_STARTED: AtomicBool = AtomicBool::new(false);
+
+pub fn idle_priority_enabled() -> bool {
+    crate::registry_config::get_bool(DML_IDLE_PRIORITY_KEY).unwrap_or(false)
+}
+
+/// Foreground GPU utilization (percent) above which DirectML OCR is deferred.
+pub fn gpu_threshold_percent() -> u32 {
+    crate::registry_config::get_u32(DML_IDLE_GPU_THRESHOLD_KEY)
+        .unwrap_or(DEFAULT_DML_IDLE_GPU_THRESHOLD)
+        .clamp(MIN_DML_IDLE_GPU_THRESHOLD, 100)
+}
+
+fn directml_in_use() -> bool {
+    crate::registry_config::get_bool("use_dml").unwrap_or(false)
+        || crate::registry_config::get_bool("rust_ocr_dml_beta").unwrap_or(false)
+}
+
+/// Whether the foreground app is busy given its utilization, with hysteresis so a
+/// reading hovering around the threshold does not flap.
+fn foreground_gpu_busy(utilization: f64, threshold: u32, was_busy: bool) -> bool {
+    let threshold = threshold as f64;
+    if was_busy {
+        utilization > threshold - THRESHOLD_HYSTERESIS
+    } else {
+        utilization >= threshold
+    }
+}
+
+/// Moves `process` to the IDLE GPU scheduling priority class.
+pub fn apply_idle_gpu_priority(process: HANDLE) -> Result<(), String> {
+    type SetPriorityClassFn = unsafe extern "system" fn(HANDLE, i32) -> i32;
+    // SAFETY: gdi32 stays loaded for the life of the process; the export has the
+    // documented `NTSTATUS (HANDLE, D3DKMT_SCHEDULINGPRIORITYCLASS)` signature and the
+    // caller passes a live process handle.
+    unsafe {
+        let gdi32 =
+            LoadLibraryW(w!("gdi32.dll")).map_err(|e| format!("Failed to load gdi32: {}", e))?;
+        let proc = GetProcAddress(gdi32, s!("D3DKMTSetProcessSchedulingPriorityClass"))
+            .ok_or_else(|| "D3DKMTSetProcessSchedulingPriorityClass is unavailable".to_string())?;
+        let set_priority_class: SetPriorityClassFn = std::mem::transmute(proc);
+        let status = set_priority_class(process, D3DKMT_SCHEDULINGPRIORITYCLASS_IDLE);
+        if status != 0 {
+            return Err(format!(
+                "Failed to set GPU scheduling priority: 0x{:08X}",
+                status
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Applies the idle GPU priority to a freshly spawned OCR worker when the option is
+/// on. Failures are logged; the worker keeps the normal priority.
+pub fn apply_to_child_if_enabled(child: &std::process::Child, label: &str) {
+    use std::os::windows::io::AsRawHandle;
+
+    if !idle_priority_enabled() {
+        return;
+    }
+    match apply_idle_gpu_priority(HANDLE(child.as_raw_handle() as _)) {
+        Ok(()) => tracing::info!("[ML:DML] {} runs at idle GPU priority", label),
+        Err(e) => tracing::warn!("[ML:DML] {}: {}", label, e),
+    }
+}
+
+fn foreground_process_id() -> Option<u32> {
+    // SAFETY: both calls only read window manager state; `pid` outlives the call.
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.0.is_null() {
+            return None;
+        }
+        let mut pid: u32 = 0;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+        (pid != 0).then_some(pid)
+    }
+}
+
+/// Highest utilization (percent) of any GPU engine used by process `pid`, sampled
+/// over `SAMPLE_INTERVAL`.
+fn query_process_gpu_utilization(pid: u32) -> Result<f64, String> {
+    let prefix = format!("pid_{}_", pid);
+    let counter_path: Vec<u16> = "\\GPU Engine(*)\\Utilization Percentage"
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+    // SAFETY: the PDH path is NUL-terminated, the item buffer is sized by the first
+    // `PdhGetFormattedCounterArrayW` call and outlives the second, and the query handle
+    // is closed on every path after it is opened.
+    unsafe {
+        let mut query = 0isize;
+        let status = PdhOpenQueryW(None, 0, &mut query);
+        if status != 0 {
+            return Err(format!("PdhOpenQuery failed: 0x{:08X}", status));
+        }
+        let result = (|| {
+            let mut counter = 0isize;
+            let status = PdhAddEnglishCounterW(
+                query,
+                windows::core::PCWSTR(counter_path.as_ptr()),
+                0,
+                &mut counter,
+            );
+            if status != 0 {
+                return Err(format!("PdhAddEnglishCounter failed: 0x{:08X}", status));
+            }
+            // Utilization is a rate counter and needs two samples.
+            PdhCollectQueryData(query);
+            std::thread::sleep(SAMPLE_INTERVAL);
+            let status = PdhCollectQueryData(query);
+            if status != 0 {
+                return Err(format!("PdhCollectQueryData failed: 0x{:08X}", status));
+            }
+
+            let mut buffer_size: u32 = 0;
+            let mut item_count: u32 = 0;
+            let status = PdhGetFormattedCounterArrayW(
+                counter,
+                PDH_FMT_DOUBLE,
+                &mut buffer_size,
+                &mut item_count,
+                None,
+            );
+            if status != PDH_MORE_DATA {
+                return Err(format!(
+                    "PdhGetFormattedCounterArray failed: 0x{:08X}",
+                    status
+                ));
+            }
+            let item_size = std::mem::size_of::<PDH_FMT_COUNTERVALUE_ITEM_W>();
+            let mut buffer: Vec<PDH_FMT_COUNTERVALUE_ITEM_W> =
+                Vec::with_capacity((buffer_size as usize).div_ceil(item_size));
+            let status = PdhGetFormattedCounterArrayW(
+                counter,
+                PDH_FMT_DOUBLE,
+                &mut buffer_size,
+                &mut item_count,
+                Some(buffer.as_mut_ptr()),
+            );
+            if status != 0 {
+                return Err(format!(
+                    "PdhGetFormattedCounterArray failed: 0x{:08X}",
+                    status
+                ));
+            }
+            buffer.set_len(item_count as usize);
+
+            Ok(buffer
+                .iter()
+                .filter(|item| {
+                    item.szName
+                        .to_string()
+                        .is_ok_and(|name| name.starts_with(&prefix))
+                })
+                .map(|item| item.FmtValue.Anonymous.doubleValue)
+                .fold(0.0_f64, f64::max))
+        })();
+        PdhCloseQuery(query);
+        result
+    }
+}
+
+/// Starts polling the foreground app's GPU utilization. Safe to call more than once.
+pub fn start_gpu_pressure_monitor(app: AppHandle) {
+    if MONITOR_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        let own_pid = std::process::id();
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let busy = if idle_priority_enabled() && directml_in_use() {
+                let was_busy = FOREGROUND_GPU_BUSY.load(Ordering::SeqCst);
+                let reading = tokio::task::spawn_blocking(move || {
+                    let pid = foreground_process_id().filter(|&pid| pid != own_pid)?;
+                    query_process_gpu_utilization(pid).ok()
+                })
+                .await
+                .unwrap_or(None);
+                match reading {
+                    Some(utilization) => {
+                        foreground_gpu_busy(utilization, gpu_threshold_percent(), was_busy)
+                    }
+                    None => false,
+                }
+            } else {
+                false
+            };
+            if FOREGROUND_GPU_BUSY.swap(busy, Ordering::SeqCst) != busy {
+                tracing::info!(
+                    "[ML:DML] foreground app GPU use {} the idle threshold, {} DirectML OCR",
+                    if busy { "above" } else { "back below" },
+                    if busy { "deferring" } else { "resuming" }
+                );
+                let _ = app.emit("gpu-pressure-status", serde_json::json!({ "busy": busy }));
+            }
+        }
+    });
+}
+
+/// Waits while the foreground app keeps the GPU busy, up to `MAX_DEFER`. Returns how
+/// long the caller was held back.
+pub async fn wait_for_gpu_idle() -> Duration {
+    let started = Instant::now();
+    while FOREGROUND_GPU_BUSY.load(Ordering::SeqCst)
+        && idle_priority_enabled()
+        && started.elapsed() < MAX_DEFER
+    {
+        tokio::time::sleep(DEFER_POLL).await;
+    }
+    started.elapsed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn busy_state_uses_hysteresis_below_the_threshold() {
+        assert!(!foreground_gpu_busy(59.0, 60, false));
+        assert!(foreground_gpu_busy(60.0, 60, false));
+        assert!(foreground_gpu_busy(55.0, 60, true));
+        assert!(!foreground_gpu_busy(50.0, 60, true));
+    }
+}
@@ -0,0 +1,336 @@
+//! User-configured hooks fired on monitor state changes.
+//!
+//! Each hook runs an executable or POSTs to a webhook when capture starts, stops,
+//! pauses, resumes, or when game mode or a screen share pauses/resumes capture — e.g. to drive a status
+//! light or switch OBS scenes. Hooks are stored in `monitor_hooks.json` in the data
+//! directory and fire-and-forget: failures are logged and never block the monitor.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+
+use crate::credential_manager::CredentialManagerState;
+use crate::storage::StorageState;
+
+const MONITOR_HOOKS_FILE: &str = "monitor_hooks.json";
+const MAX_HOOKS: usize = 32;
+const WEBHOOK_TIMEOUT_SECS: u64 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MonitorHookEvent {
+    Start,
+    Stop,
+    Pause,
+    Resume,
+    /// A fullscreen game paused capture.
+    GameModeOn,
+    /// The fullscreen game exited and capture resumed.
+    GameModeOff,
+    /// A screen share or presentation paused capture.
+    ScreenShareOn,
+    /// The screen share ended and capture resumed.
+    ScreenShareOff,
+}
+
+impl MonitorHookEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Start => "start",
+            Self::Stop => "stop",
+            Self::Pause => "pause",
+            Self::Resume => "resume",
+            Self::GameModeOn => "game_mode_on",
+            Self::GameModeOff => "game_mode_off",
+            Self::ScreenShareOn => "screen_share_on",
+            Self::ScreenShareOff => "screen_share_off",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MonitorHookAction {
+    /// Run `path` with `args`; the event name is passed in `CARBONPAPER_EVENT`.
+    Exec {
+        path: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    /// POST `{ "event": ..., "timestamp": ... }` to `url`.
+    Webhook { url: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MonitorHook {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub events: Vec<MonitorHookEvent>,
+    #[serde(flatten)]
+    pub action: MonitorHookAction,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MonitorHooksConfig {
+    #[serde(default)]
+    pub hooks: Vec<MonitorHook>,
+}
+
+impl MonitorHooksConfig {
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(MONITOR_HOOKS_FILE);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring invalid {}: {}", MONITOR_HOOKS_FILE, e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize monitor hooks: {}", e))?;
+        std::fs::write(data_dir.join(MONITOR_HOOKS_FILE), content)
+            .map_err(|e| format!("Failed to save monitor hooks: {}", e))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.hooks.len() > MAX_HOOKS {
+            return Err(format!("At most {} hooks can be configured", MAX_HOOKS));
+        }
+        self.hooks.iter().try_for_each(MonitorHook::validate)
+    }
+
+    /// Enabled hooks registered for `event`. The file can be edited by hand, so hooks
+    /// that `set_monitor_hooks` would reject (and any past `MAX_HOOKS`) are skipped.
+    fn hooks_for(&self, event: MonitorHookEvent) -> impl Iterator<Item = &MonitorHook> {
+        self.hooks
+            .iter()
+            .take(MAX_HOOKS)
+            .filter(move |hook| hook.enabled && hook.events.contains(&event))
+            .filter(|hook| match hook.validate() {
+                Ok(()) => true,
+                Err(e) => {
+                    tracing::warn!("Skipping invalid monitor hook: {}", e);
+                    false
+                }
+            })
+    }
+}
+
+impl MonitorHook {
+    fn validate(&self) -> Result<(), String> {
+        if self.events.is_empty() {
+            return Err("Each hook needs at least one event".to_string());
+        }
+        match &self.action {
+            MonitorHookAction::Exec { path, .. } => {
+                if !Path::new(path).is_absolute() {
+                    return Err(format!(
+                        "Hook executable must be an absolute path: {}",
+                        path
+                    ));
+                }
+            }
+            MonitorHookAction::Webhook { url } => {
+                let parsed = reqwest::Url::parse(url)
+                    .map_err(|e| format!("Invalid webhook URL {}: {}", url, e))?;
+                if !matches!(parsed.scheme(), "http" | "https") {
+                    return Err(format!("Webhook URL must use http or https: {}", url));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn data_dir(app: &AppHandle) -> std::path::PathBuf {
+    app.state::<Arc<StorageState>>()
+        .data_dir
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+fn run_exec_hook(path: &str, args: &[String], event: MonitorHookEvent) -> Result<(), String> {
+    let mut command = std::process::Command::new(path);
+    command
+        .args(args)
+        .env("CARBONPAPER_EVENT", event.as_str())
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    command
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("Failed to run hook {}: {}", path, e))
+}
+
+async fn post_webhook(url: String, event: MonitorHookEvent) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("Failed to build webhook client: {}", e))?;
+    let response = client
+        .post(&url)
+        .json(&serde_json::json!({
+            "event": event.as_str(),
+            "timestamp": chrono::Utc::now().timestamp(),
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("Webhook {} failed: {}", url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("Webhook {} returned {}", url, response.status()));
+    }
+    Ok(())
+}
+
+/// Fire every enabled, valid hook registered for `event`. Never blocks on the hooks.
+pub fn fire(app: &AppHandle, event: MonitorHookEvent) {
+    let config = MonitorHooksConfig::load(&data_dir(app));
+    for hook in config.hooks_for(event) {
+        match hook.action.clone() {
+            MonitorHookAction::Exec { path, args } => {
+                if let Err(e) = run_exec_hook(&path, &args, event) {
+                    tracing::warn!("Monitor hook ({}): {}", event.as_str(), e);
+                }
+            }
+            MonitorHookAction::Webhook { url } => {
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = post_webhook(url, event).await {
+                        tracing::warn!("Monitor hook ({}): {}", event.as_str(), e);
+                    }
+                });
+            }
+        }
+    }
+}
+
+/// Returns the configured monitor hooks.
+///
+/// Authentication: required. Returns `MonitorHooksConfig`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub fn get_monitor_hooks(
+    window: tauri::Window,
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    app: AppHandle,
+) -> Result<MonitorHooksConfig, String> {
+    crate::commands::check_main_window(&window)?;
+    crate::commands::check_auth_required(&credential_state)?;
+    Ok(MonitorHooksConfig::load(&data_dir(&app)))
+}
+
+/// Replaces the monitor hooks (executables or webhooks fired on capture start, stop,
+/// pause, resume, and game-mode changes).
+///
+/// Authentication: required. Executables must be absolute paths and webhooks http(s)
+/// URLs. Returns JSON `null`. Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub fn set_monitor_hooks(
+    window: tauri::Window,
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    app: AppHandle,
+    config: MonitorHooksConfig,
+) -> Result<(), String> {
+    crate::commands::check_main_window(&window)?;
+    crate::commands::check_auth_required(&credential_state)?;
+    config.validate()?;
+    config.save(&data_dir(&app))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_round_trips_and_selects_hooks_by_event() {
+        let json = r#"{
+            "hooks": [
+                { "events": ["pause", "game_mode_on"], "kind": "webhook", "url": "http://127.0.0.1:8080/light" },
+                { "enabled": false, "events": ["pause"], "kind": "exec", "path": "C:\\obs.exe", "args": ["--scene", "Away"] }
+            ]
+        }"#;
+        let config: MonitorHooksConfig = serde_json::from_str(json).unwrap();
+        assert!(config.hooks[0].enabled);
+        assert_eq!(config.hooks_for(MonitorHookEvent::Pause).count(), 1);
+        assert_eq!(config.hooks_for(MonitorHookEvent::Resume).count(), 0);
+
+        let temp = tempfile::tempdir().unwrap();
+        config.save(temp.path()).unwrap();
+        assert_eq!(MonitorHooksConfig::load(temp.path()), config);
+    }
+
+    #[test]
+    fn hand_edited_invalid_hooks_are_not_fired() {
+        let json = r#"{
+            "hooks": [
+                { "events": ["pause"], "kind": "exec", "path": "light.exe" },
+                { "events": ["pause"], "kind": "webhook", "url": "file:///C:/x" },
+                { "events": ["pause"], "kind": "webhook", "url": "https://example.com/hook" }
+            ]
+        }"#;
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join(MONITOR_HOOKS_FILE), json).unwrap();
+        let config = MonitorHooksConfig::load(temp.path());
+        assert_eq!(config.hooks.len(), 3);
+
+        let fired: Vec<_> = config.hooks_for(MonitorHookEvent::Pause).collect();
+        assert_eq!(fired.len(), 1);
+        assert_eq!(
+            fired[0].action,
+            MonitorHookAction::Webhook {
+                url: "https://example.com/hook".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn validation_rejects_relative_paths_and_non_http_urls() {
+        let hook = |action| MonitorHooksConfig {
+            hooks: vec![MonitorHook {
+                enabled: true,
+                events: vec![MonitorHookEvent::Start],
+                action,
+            }],
+        };
+        assert!(hook(MonitorHookAction::Exec {
+            path: "light.exe".to_string(),
+            args: vec![],
+        })
+        .validate()
+        .is_err());
+        assert!(hook(MonitorHookAction::Webhook {
+            url: "file:///C:/x".to_string(),
+        })
+        .validate()
+        .is_err());
+        assert!(hook(MonitorHookAction::Webhook {
+            url: "https://example.com/hook".to_string(),
+        })
+        .validate()
+        .is_ok());
+        assert!(MonitorHooksConfig {
+            hooks: vec![MonitorHook {
+                enabled: true,
+                events: vec![],
+                action: MonitorHookAction::Webhook {
+                    url: "https://example.com/hook".to_string(),
+                },
+            }],
+        }
+        .validate()
+        .is_err());
+    }
+}
@@ -0,0 +1,158 @@
+//! System-wide hotkeys.
+//!
+//! Bindings are accelerator strings such as `Ctrl+Shift+S`, stored in registry config
+//! and registered through the global-shortcut plugin. Nothing is bound by default:
+//! the obvious combinations already mean something in other apps (Ctrl+Shift+S is
+//! "Save As" in most editors), and a global registration would take them away.
+
+use std::sync::Arc;
+
+use tauri::plugin::TauriPlugin;
+use tauri::{AppHandle, Manager, Wry};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::capture::CaptureState;
+
+/// What a global hotkey does when pressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyAction {
+    /// Capture the foreground window now (`CaptureState::request_manual_capture`).
+    Capture,
+    /// Show or hide the search overlay.
+    Search,
+}
+
+impl HotkeyAction {
+    const ALL: [HotkeyAction; 2] = [HotkeyAction::Capture, HotkeyAction::Search];
+
+    /// Registry key holding the binding; empty or missing means unbound.
+    fn registry_key(self) -> &'static str {
+        match self {
+            HotkeyAction::Capture => "capture_hotkey",
+            HotkeyAction::Search => "search_hotkey",
+        }
+    }
+
+    /// The saved binding, or an empty string when none is set.
+    pub fn saved(self) -> String {
+        crate::registry_config::get_string(self.registry_key()).unwrap_or_default()
+    }
+
+    fn run(self, app: &AppHandle) {
+        tracing::info!("{:?} hotkey pressed", self);
+        match self {
+            HotkeyAction::Capture => {
+                if let Some(capture_state) = app.try_state::<Arc<CaptureState>>() {
+                    capture_state.request_manual_capture();
+                }
+            }
+            HotkeyAction::Search => crate::search_overlay::toggle(app),
+        }
+    }
+}
+
+/// Parses an accelerator string. Blank input means "no binding".
+fn parse_hotkey(value: &str) -> Result<Option<Shortcut>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    value
+        .parse::<Shortcut>()
+        .map(Some)
+        .map_err(|e| format!("Invalid hotkey '{}': {}", value, e))
+}
+
+fn is_binding(shortcut: &Shortcut, saved: &str) -> bool {
+    matches!(parse_hotkey(saved), Ok(Some(bound)) if bound == *shortcut)
+}
+
+/// Global-shortcut plugin with the app's press handler installed.
+pub fn plugin() -> TauriPlugin<Wry> {
+    tauri_plugin_global_shortcut::Builder::new()
+        .with_handler(|app, shortcut, event| {
+            if event.state() != ShortcutState::Pressed {
+                return;
+            }
+            if let Some(action) = HotkeyAction::ALL
+                .into_iter()
+                .find(|action| is_binding(shortcut, &action.saved()))
+            {
+                action.run(app);
+            }
+        })
+        .build()
+}
+
+/// Registers the saved bindings. Called once from app setup; a binding another app
+/// already owns is logged and left unregistered.
+pub fn register_saved(app: &AppHandle) {
+    for action in HotkeyAction::ALL {
+        match parse_hotkey(&action.saved()) {
+            Ok(Some(shortcut)) => {
+                if let Err(e) = app.global_shortcut().register(shortcut) {
+                    tracing::warn!("Failed to register {:?} hotkey: {}", action, e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Ignoring saved {:?} hotkey: {}", action, e),
+        }
+    }
+}
+
+/// Replaces the binding for `action` and saves it. The previous binding stays
+/// active when the new one cannot be registered (e.g. another app owns it).
+pub fn set_hotkey(app: &AppHandle, action: HotkeyAction, value: &str) -> Result<String, String> {
+    let next = parse_hotkey(value)?;
+    if let Some(next) = next {
+        let taken_by = HotkeyAction::ALL
+            .into_iter()
+            .filter(|other| *other != action)
+            .find(|other| is_binding(&next, &other.saved()));
+        if let Some(other) = taken_by {
+            return Err(format!(
+                "Hotkey '{}' is already used for {:?}",
+                value.trim(),
+                other
+            ));
+        }
+    }
+
+    let previous = parse_hotkey(&action.saved()).ok().flatten();
+    let shortcuts = app.global_shortcut();
+
+    if let Some(previous) = previous {
+        let _ = shortcuts.unregister(previous);
+    }
+    if let Some(next) = next {
+        if let Err(e) = shortcuts.register(next) {
+            if let Some(previous) = previous {
+                let _ = shortcuts.register(previous);
+            }
+            return Err(format!(
+                "Failed to register hotkey '{}': {}",
+                value.trim(),
+                e
+            ));
+        }
+    }
+
+    let saved = value.trim().to_string();
+    crate::registry_config::set_string(action.registry_key(), &saved)?;
+    Ok(saved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hotkeys_parse_accelerators_and_treat_blank_as_unbound() {
+        assert_eq!(parse_hotkey("  "), Ok(None));
+        let shortcut = parse_hotkey("Ctrl+Shift+S").unwrap().unwrap();
+        assert!(is_binding(&shortcut, " ctrl+shift+s "));
+        assert!(!is_binding(&shortcut, "Ctrl+Alt+S"));
+        assert!(!is_binding(&shortcut, ""));
+        assert!(parse_hotkey("Ctrl+Nope").is_err());
+    }
+}
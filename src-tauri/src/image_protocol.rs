@@ -0,0 +1,88 @@
+//! `carbonpaper-img://` protocol serving decrypted screenshots to the webview.
+//!
+//! `storage_get_image` returns the image as base64 inside a JSON response, which
+//! copies a multi-megabyte frame several times on its way through IPC. The webview
+//! instead loads `carbonpaper-img://localhost/<screenshot id>` (on Windows
+//! `http://carbonpaper-img.localhost/<id>`) and receives the decrypted bytes as the
+//! response body. Requests need the same unlocked session as the storage commands,
+//! are only answered for CarbonPaper's own webviews, are recorded in the access audit
+//! log, and are marked `no-store` so the webview never writes a decrypted frame to
+//! its HTTP cache.
+
+use std::sync::Arc;
+
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::{AppHandle, Manager};
+
+use crate::credential_manager::CredentialManagerState;
+use crate::storage::StorageState;
+
+/// URI scheme registered with the webview.
+pub const IMAGE_PROTOCOL: &str = "carbonpaper-img";
+
+/// Webviews allowed to load images; matches `capabilities/default.json`.
+const ALLOWED_WEBVIEWS: &[&str] = &["main", "snapshot-preview"];
+
+fn plain_response(status: StatusCode, body: &str) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain")
+        .header(header::CACHE_CONTROL, "no-store")
+        .body(body.as_bytes().to_vec())
+        .unwrap_or_default()
+}
+
+/// Screenshot id from a request path such as `/42`.
+fn parse_screenshot_id(path: &str) -> Option<i64> {
+    path.trim_matches('/').parse().ok().filter(|id| *id > 0)
+}
+
+/// Answer one protocol request. Runs on a blocking thread.
+pub fn handle_image_request(
+    app: &AppHandle,
+    webview_label: &str,
+    request: &Request<Vec<u8>>,
+) -> Response<Vec<u8>> {
+    if !ALLOWED_WEBVIEWS.contains(&webview_label) {
+        return plain_response(StatusCode::FORBIDDEN, "WINDOW_NOT_AUTHORIZED");
+    }
+    let credential_state = app.state::<Arc<CredentialManagerState>>();
+    if crate::commands::check_auth_required(&credential_state).is_err() {
+        return plain_response(StatusCode::UNAUTHORIZED, "AUTH_REQUIRED");
+    }
+    let Some(id) = parse_screenshot_id(request.uri().path()) else {
+        return plain_response(StatusCode::BAD_REQUEST, "Invalid screenshot id");
+    };
+
+    let storage = app.state::<Arc<StorageState>>();
+    let result = storage
+        .record_access_audit("view_image", &serde_json::json!({ "screenshot_id": id }))
+        .and_then(|_| storage.read_screenshot_image(id));
+    match result {
+        Ok((bytes, mime_type)) => Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, mime_type)
+            .header(header::CACHE_CONTROL, "no-store")
+            .body(bytes)
+            .unwrap_or_default(),
+        Err(e) if e == "Image not found" => plain_response(StatusCode::NOT_FOUND, &e),
+        Err(e) => {
+            tracing::warn!("[IMAGE_PROTOCOL] failed to serve screenshot {}: {}", id, e);
+            plain_response(StatusCode::INTERNAL_SERVER_ERROR, &e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn screenshot_ids_are_parsed_from_the_path() {
+        assert_eq!(parse_screenshot_id("/42"), Some(42));
+        assert_eq!(parse_screenshot_id("/42/"), Some(42));
+        assert_eq!(parse_screenshot_id("/0"), None);
+        assert_eq!(parse_screenshot_id("/../screenshots.db"), None);
+        assert_eq!(parse_screenshot_id("/"), None);
+    }
+}
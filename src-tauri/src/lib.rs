@@ -4,14 +4,24 @@
 //! commands, tray behavior, and application lifecycle into the desktop runtime.
 
 mod analysis;
+mod audio_capture;
 mod autostart;
+mod browser_url;
 mod capture;
+mod clipboard_history;
 pub mod commands;
 mod credential_manager;
+mod dml_fallback;
+mod dml_preflight;
 pub mod error;
 mod error_window;
+mod game_mode;
+mod gpu_priority;
+mod hooks;
+mod hotkeys;
 mod i18n;
 mod idle;
+mod image_protocol;
 mod logging;
 mod mcp_server;
 mod mcp_token;
@@ -24,6 +34,7 @@ mod model_management;
 mod monitor;
 mod monitor_ipc;
 mod native_messaging;
+mod ocr_reprocess;
 mod power;
 mod python;
 mod python_launcher;
@@ -31,13 +42,21 @@ mod registry_config;
 mod resource_utils;
 mod reverse_ipc;
 mod reverse_ipc_protocol;
+mod saved_search_alerts;
+mod scheduler;
+mod screen_share;
 mod script_integrity;
+mod search_overlay;
 #[allow(dead_code)]
 mod semantic_models;
 #[allow(dead_code)]
 mod semantic_runtime;
 mod sensitive_filter;
+mod setup;
+mod sidecar_memory;
 mod storage;
+mod uia;
+mod uia_text;
 mod updater;
 
 use analysis::AnalysisState;
@@ -51,7 +70,7 @@ use sensitive_filter::SensitiveFilterState;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use storage::StorageState;
-use tauri::menu::{MenuBuilder, MenuItem, MenuItemBuilder};
+use tauri::menu::{MenuBuilder, MenuItem, MenuItemBuilder, Submenu, SubmenuBuilder};
 use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
 use tauri::Emitter;
 use tauri::Manager;
@@ -60,6 +79,10 @@ use window_vibrancy::apply_acrylic;
 
 const MENU_ID_OPEN: &str = "open";
 const MENU_ID_TOGGLE_CAPTURE: &str = "toggle_capture";
+const MENU_ID_PAUSE_FOR: &str = "pause_for";
+const MENU_ID_PAUSE_FOR_PREFIX: &str = "pause_for_";
+/// Durations offered by the tray "Pause for" submenu, in minutes.
+const TRAY_PAUSE_MINUTES: [u64; 3] = [15, 30, 60];
 const MENU_ID_RESTART: &str = "restart";
 const MENU_ID_LIGHTWEIGHT: &str = "lightweight";
 const MENU_ID_QUIT: &str = "quit";
@@ -72,6 +95,8 @@ type TrayMenuItem = MenuItem<tauri::Wry>;
 pub struct TrayMenuState {
     pub open: TrayMenuItem,
     pub toggle_capture: TrayMenuItem,
+    pub pause_for: Submenu<tauri::Wry>,
+    pub pause_for_items: Vec<TrayMenuItem>,
     pub restart: TrayMenuItem,
     pub lightweight: TrayMenuItem,
     pub quit: TrayMenuItem,
@@ -82,7 +107,11 @@ struct TrayTexts {
     open: &'static str,
     screenshot_running: &'static str,
     screenshot_paused: &'static str,
+    screenshot_share_paused: &'static str,
+    screenshot_schedule_paused: &'static str,
     screenshot_stopped: &'static str,
+    pause_for: &'static str,
+    pause_for_options: [&'static str; 3],
     restart: &'static str,
     lightweight: &'static str,
     lightweight_active: &'static str,
@@ -96,7 +125,11 @@ const TRAY_TEXTS_ZH: TrayTexts = TrayTexts {
     open: "打开界面",
     screenshot_running: "截图：运行中（点击暂停）",
     screenshot_paused: "截图：已暂停（点击恢复）",
+    screenshot_share_paused: "截图：屏幕共享中，已自动暂停",
+    screenshot_schedule_paused: "截图：不在计划时段，已自动暂停",
     screenshot_stopped: "截图：未运行",
+    pause_for: "暂停一段时间",
+    pause_for_options: ["15 分钟", "30 分钟", "1 小时"],
     restart: "重启截图",
     lightweight: "切换到轻量模式",
     lightweight_active: "轻量模式已开启",
@@ -110,7 +143,11 @@ const TRAY_TEXTS_EN: TrayTexts = TrayTexts {
     open: "Open Window",
     screenshot_running: "Screenshots: On (click to pause)",
     screenshot_paused: "Screenshots: Paused (click to resume)",
+    screenshot_share_paused: "Screenshots: Paused while screen sharing",
+    screenshot_schedule_paused: "Screenshots: Paused outside scheduled hours",
     screenshot_stopped: "Screenshots: Not Running",
+    pause_for: "Pause for",
+    pause_for_options: ["15 minutes", "30 minutes", "1 hour"],
     restart: "Restart Screenshots",
     lightweight: "Switch to Lightweight Mode",
     lightweight_active: "Lightweight Mode On",
@@ -155,6 +192,14 @@ pub(crate) fn refresh_tray_menu(app: &tauri::AppHandle) {
     let _ = menu_state.open.set_text(texts.open);
     let _ = menu_state.restart.set_text(texts.restart);
     let _ = menu_state.quit.set_text(texts.quit);
+    let _ = menu_state.pause_for.set_text(texts.pause_for);
+    for (item, label) in menu_state
+        .pause_for_items
+        .iter()
+        .zip(texts.pause_for_options)
+    {
+        let _ = item.set_text(label);
+    }
 
     let monitor_running = app
         .try_state::<MonitorState>()
@@ -168,11 +213,24 @@ pub(crate) fn refresh_tray_menu(app: &tauri::AppHandle) {
         .try_state::<Arc<CaptureState>>()
         .map(|state| state.paused.load(Ordering::SeqCst))
         .unwrap_or(false);
+    let share_paused = app
+        .try_state::<Arc<CaptureState>>()
+        .map(|state| state.screen_share_paused.load(Ordering::SeqCst))
+        .unwrap_or(false);
+    let schedule_paused = app
+        .try_state::<Arc<CaptureState>>()
+        .map(|state| state.schedule_paused.load(Ordering::SeqCst))
+        .unwrap_or(false);
 
+    let _ = menu_state.pause_for.set_enabled(monitor_running);
     if monitor_running {
         let _ = menu_state.toggle_capture.set_enabled(true);
         let _ = menu_state.toggle_capture.set_text(if capture_paused {
             texts.screenshot_paused
+        } else if share_paused {
+            texts.screenshot_share_paused
+        } else if schedule_paused {
+            texts.screenshot_schedule_paused
         } else {
             texts.screenshot_running
         });
@@ -217,44 +275,12 @@ impl LightweightModeState {
 async fn run_delete_queue_maintenance_loop(app_handle: tauri::AppHandle) {
     const OCR_BATCH_SIZE: i64 = 500;
     const SCREENSHOT_BATCH_SIZE: i64 = 100;
-    const POLICY_CHECK_INTERVAL_SECS: u64 = 60;
-
-    let mut last_policy_check =
-        std::time::Instant::now() - std::time::Duration::from_secs(POLICY_CHECK_INTERVAL_SECS);
 
     loop {
         tokio::time::sleep(std::time::Duration::from_secs(5)).await;
 
         let storage = app_handle.state::<Arc<StorageState>>().inner().clone();
 
-        let policy_pruned = if last_policy_check.elapsed()
-            >= std::time::Duration::from_secs(POLICY_CHECK_INTERVAL_SECS)
-        {
-            last_policy_check = std::time::Instant::now();
-            match tokio::task::spawn_blocking({
-                let storage = storage.clone();
-                move || storage.enforce_snapshot_storage_policy_once()
-            })
-            .await
-            {
-                Ok(Ok(Some(summary))) => {
-                    tracing::info!("[POLICY] {}", summary);
-                    true
-                }
-                Ok(Ok(None)) => false,
-                Ok(Err(e)) => {
-                    tracing::warn!("[POLICY] enforce failed: {}", e);
-                    false
-                }
-                Err(e) => {
-                    tracing::warn!("[POLICY] enforce join error: {:?}", e);
-                    false
-                }
-            }
-        } else {
-            false
-        };
-
         let ocr_processed = match tokio::task::spawn_blocking({
             let storage = storage.clone();
             move || storage.process_ocr_delete_queue_batch(OCR_BATCH_SIZE)
@@ -411,10 +437,9 @@ async fn run_delete_queue_maintenance_loop(app_handle: tauri::AppHandle) {
             }
         };
 
-        if ocr_processed > 0 || finalized_screenshots > 0 || vacuum_ran || policy_pruned {
+        if ocr_processed > 0 || finalized_screenshots > 0 || vacuum_ran {
             tracing::info!(
-                "[DELETE_QUEUE] cycle complete: policy_pruned={}, ocr_processed={}, screenshots_finalized={}, vacuum_ran={}",
-                policy_pruned,
+                "[DELETE_QUEUE] cycle complete: ocr_processed={}, screenshots_finalized={}, vacuum_ran={}",
                 ocr_processed,
                 finalized_screenshots,
                 vacuum_ran
@@ -441,7 +466,7 @@ fn is_open_tray_click(button: MouseButton, button_state: MouseButtonState) -> bo
     )
 }
 
-fn open_main_window(app: &tauri::AppHandle, show_ocr_model_repair: bool) {
+pub(crate) fn open_main_window(app: &tauri::AppHandle, show_ocr_model_repair: bool) {
     cancel_auto_lightweight_timer(app);
 
     if let Some(window) = app.get_webview_window("main") {
@@ -507,6 +532,20 @@ fn build_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
         MenuItemBuilder::with_id(MENU_ID_TOGGLE_CAPTURE, texts.screenshot_stopped)
             .enabled(false)
             .build(&app_handle)?;
+    let pause_for_items = TRAY_PAUSE_MINUTES
+        .iter()
+        .zip(texts.pause_for_options)
+        .map(|(minutes, label)| {
+            MenuItemBuilder::with_id(format!("{}{}", MENU_ID_PAUSE_FOR_PREFIX, minutes), label)
+                .build(&app_handle)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut pause_for_builder =
+        SubmenuBuilder::with_id(&app_handle, MENU_ID_PAUSE_FOR, texts.pause_for);
+    for item in &pause_for_items {
+        pause_for_builder = pause_for_builder.item(item);
+    }
+    let pause_for_menu = pause_for_builder.enabled(false).build()?;
     let restart_item =
         MenuItemBuilder::with_id(MENU_ID_RESTART, texts.restart).build(&app_handle)?;
     let lightweight_item =
@@ -516,6 +555,7 @@ fn build_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
     let menu = MenuBuilder::new(&app_handle)
         .item(&open_item)
         .item(&toggle_capture_item)
+        .item(&pause_for_menu)
         .item(&restart_item)
         .item(&lightweight_item)
         .separator()
@@ -560,13 +600,30 @@ fn build_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
                                 monitor::resume_monitor_impl(state, cs, app_handle.clone()).await;
                         } else {
                             let _ =
-                                monitor::pause_monitor_impl(state, cs, app_handle.clone()).await;
+                                monitor::pause_monitor_impl(state, cs, app_handle.clone(), None)
+                                    .await;
                         }
                     } else {
                         refresh_tray_menu(&app_handle);
                     }
                 });
             }
+            id if id.starts_with(MENU_ID_PAUSE_FOR_PREFIX) => {
+                let Ok(minutes) = id[MENU_ID_PAUSE_FOR_PREFIX.len()..].parse::<u64>() else {
+                    return;
+                };
+                let app_handle = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    let state = app_handle.state::<MonitorState>();
+                    let cs = app_handle.state::<Arc<CaptureState>>();
+                    if let Err(e) =
+                        monitor::pause_monitor_impl(state, cs, app_handle.clone(), Some(minutes))
+                            .await
+                    {
+                        tracing::warn!("Failed to pause for {} minutes from tray: {}", minutes, e);
+                    }
+                });
+            }
             MENU_ID_RESTART => {
                 let app_handle = app.clone();
                 tauri::async_runtime::spawn(async move {
@@ -622,6 +679,8 @@ fn build_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
     app.manage(TrayMenuState {
         open: open_item,
         toggle_capture: toggle_capture_item,
+        pause_for: pause_for_menu,
+        pause_for_items,
         restart: restart_item,
         lightweight: lightweight_item,
         quit: quit_item,
@@ -682,6 +741,7 @@ pub fn create_main_window(app: &tauri::AppHandle) -> Result<(), Box<dyn std::err
 
     // 应用 Acrylic 效果
     let _ = apply_acrylic(&window, Some((0, 0, 0, 0)));
+    capture::apply_capture_exclusion_to_all(app);
 
     tracing::info!("Main window created successfully");
     Ok(())
@@ -784,6 +844,10 @@ pub fn run() {
         data_dir.clone(),
         credential_state.clone(),
     ));
+    if std::env::var("CARBONPAPER_GUEST_MODE").is_ok() {
+        tracing::info!("Starting in read-only guest mode");
+        storage_state.set_read_only(true);
+    }
     let lightweight_state = Arc::new(LightweightModeState::new());
 
     // 如果隐藏启动，标记为轻量模式
@@ -795,6 +859,7 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_notification::init())
+        .plugin(hotkeys::plugin())
         .manage(MonitorState::new())
         .manage(Arc::new(ml_runtime::MlRuntimeState::new()))
         .manage(Arc::new(semantic_runtime::SemanticRuntimeState::new()))
@@ -808,6 +873,22 @@ pub fn run() {
         .manage(lightweight_state.clone())
         .manage(Arc::new(PowerState::new()))
         .manage(Arc::new(IdleState::new()))
+        .manage(Arc::new(scheduler::SchedulerState::default()))
+        .manage(Arc::new(setup::SetupManager::default()))
+        .register_asynchronous_uri_scheme_protocol(
+            image_protocol::IMAGE_PROTOCOL,
+            |ctx, request, responder| {
+                let app = ctx.app_handle().clone();
+                let webview_label = ctx.webview_label().to_string();
+                tauri::async_runtime::spawn_blocking(move || {
+                    responder.respond(image_protocol::handle_image_request(
+                        &app,
+                        &webview_label,
+                        &request,
+                    ));
+                });
+            },
+        )
         .on_window_event(|window, event| {
             if let tauri::WindowEvent::CloseRequested { api, .. } = event {
                 if window.label() == "main" {
@@ -847,6 +928,7 @@ pub fn run() {
                     if let Some(window) = app.get_webview_window("main") {
                         let _ = apply_acrylic(&window, Some((0, 0, 0, 0)));
                     }
+                    capture::apply_capture_exclusion_to_all(app.handle());
                 } else {
                     // 隐藏启动：销毁窗口以实现真正的轻量模式，释放 WebView 内存
                     if let Some(window) = app.get_webview_window("main") {
@@ -858,7 +940,9 @@ pub fn run() {
                     }
                 }
 
+                hotkeys::register_saved(app.handle());
                 analysis::start_memory_sampler(app.handle().clone());
+                sidecar_memory::start_watchdog(app.handle().clone());
                 logging::spawn_maintenance_task(data_dir.clone());
 
                 tracing::info!(
@@ -918,7 +1002,16 @@ pub fn run() {
                 if public_key_ready {
                     if let Err(e) = storage.initialize() {
                         tracing::error!("Failed to initialize storage: {}", e);
+                    } else if storage.is_read_only() {
+                        tracing::info!("Guest mode: background maintenance and scheduler disabled");
                     } else {
+                        if let Some(summary) = storage.pending_recovery_summary() {
+                            if summary.committed + summary.aborted + summary.orphan_files_removed
+                                > 0
+                            {
+                                let _ = app.emit("storage-pending-recovery", summary);
+                            }
+                        }
                         match storage.discard_incomplete_ocr_postprocess() {
                             Ok(discarded) if discarded > 0 => tracing::info!(
                                 "[ML:POSTPROCESS] discarded {} incomplete rows from the previous application process",
@@ -940,6 +1033,7 @@ pub fn run() {
                         tauri::async_runtime::spawn(async move {
                             run_delete_queue_maintenance_loop(app_handle_cleanup).await;
                         });
+                        scheduler::start_scheduler(app.handle().clone());
                         let app_handle_postprocess = app.handle().clone();
                         tauri::async_runtime::spawn(async move {
                             ml_runtime::run_postprocess_retry_loop(app_handle_postprocess).await;
@@ -957,6 +1051,10 @@ pub fn run() {
                 // Start power monitor (power saving mode)
                 power::start_power_monitor(app.handle().clone());
                 idle::start_idle_monitor(app.handle().clone());
+                screen_share::start_screen_share_monitor(app.handle().clone());
+                gpu_priority::start_gpu_pressure_monitor(app.handle().clone());
+                clipboard_history::start_clipboard_monitor(app.handle().clone());
+                monitor::start_capture_schedule_task(app.handle().clone());
 
                 match native_messaging::sync_installed_extension() {
                     Ok(true) => tracing::info!("Browser extension synced to latest version"),
@@ -1004,7 +1102,13 @@ pub fn run() {
                                 filter_state.update_config(config);
                             }
                         }
+                        storage.apply_ocr_text_rules_from_policy(&policy);
+                        storage.apply_compliance_mode_from_policy(&policy);
+                        storage.apply_paranoid_mode_from_policy(&policy);
                     }
+                    storage.load_image_codec_from_registry();
+                    storage.load_near_duplicate_threshold_from_registry();
+                    storage.load_delta_frames_from_registry();
                 }
 
                 {
@@ -1040,6 +1144,7 @@ pub fn run() {
                 python::auto_install_spacy_models(app.handle().clone());
 
                 ml_runtime::schedule_ocr_model_health_notification(app.handle().clone());
+                dml_preflight::schedule_startup_preflight(app.handle().clone());
 
                 // 轻量模式下自动启动监控
                 if start_hidden
@@ -1061,15 +1166,26 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             commands::utility::close_process,
             commands::utility::set_app_language,
+            commands::utility::get_read_only_mode,
             monitor::start_monitor,
             monitor::get_monitor_autostart,
             monitor::set_monitor_autostart,
+            monitor::get_capture_exclude_own_windows,
+            monitor::set_capture_exclude_own_windows,
             monitor::stop_monitor,
             monitor::pause_monitor,
             monitor::resume_monitor,
+            hooks::get_monitor_hooks,
+            hooks::set_monitor_hooks,
             monitor::get_monitor_status,
             monitor::monitor_search_nl,
             monitor::monitor_update_filters,
+            monitor::monitor_get_filters,
+            monitor::monitor_set_exclusion_rules,
+            monitor::monitor_get_exclusion_rules,
+            monitor::get_capture_schedule,
+            monitor::monitor_get_metrics,
+            monitor::set_capture_schedule,
             monitor::monitor_update_advanced_config,
             monitor::monitor_update_feature_config,
             monitor::monitor_run_clustering,
@@ -1089,6 +1205,7 @@ pub fn run() {
             ml_runtime::get_rust_ocr_model_status,
             ml_runtime::download_rust_ocr_model,
             ml_runtime::take_ocr_model_repair_request,
+            ocr_reprocess::reprocess_ocr,
             ml_runtime::debug_trigger_ocr_model_repair_notification,
             semantic_runtime::get_ml_semantic_status,
             semantic_runtime::restart_ml_semantic_worker,
@@ -1098,27 +1215,96 @@ pub fn run() {
             // 存储相关命令
             commands::storage::storage_get_timeline,
             commands::storage::storage_get_timeline_density,
+            commands::storage::storage_get_sessions,
             commands::storage::storage_search,
+            commands::storage::storage_search_count,
+            commands::storage::storage_search_titles,
+            commands::storage::storage_get_search_history,
+            commands::storage::storage_suggest_search_queries,
+            commands::storage::storage_clear_search_history,
+            commands::storage::storage_create_saved_search,
+            commands::storage::storage_list_saved_searches,
+            commands::storage::storage_delete_saved_search,
             commands::storage::storage_get_image,
             commands::storage::storage_get_thumbnail,
+            commands::storage::storage_get_image_preview,
             commands::storage::storage_batch_get_thumbnails,
             commands::storage::storage_warmup_thumbnails,
             commands::storage::storage_get_thumbnail_warmup_status,
             commands::storage::storage_cancel_thumbnail_warmup,
             commands::storage::storage_get_screenshot_details,
+            commands::storage::storage_get_ocr_texts,
             commands::storage::storage_delete_screenshot,
+            commands::storage::storage_restore_screenshot,
+            commands::storage::storage_list_trash,
+            commands::storage::storage_empty_trash,
             commands::storage::storage_delete_by_time_range,
+            commands::storage::storage_protect_range,
+            commands::storage::storage_list_protected_ranges,
+            commands::storage::storage_unprotect_range,
             commands::storage::storage_list_processes,
             commands::storage::storage_get_process_stats,
+            commands::storage::storage_get_usage_by_day,
+            commands::storage::storage_get_stats,
+            commands::storage::storage_get_capture_days,
+            commands::storage::storage_get_screenshots_by_capture_day,
             commands::storage::storage_get_process_monthly_thumbnails,
             commands::storage::storage_soft_delete,
             commands::storage::storage_soft_delete_screenshots,
+            commands::storage::storage_batch_apply,
+            commands::storage::storage_add_tag,
+            commands::storage::storage_remove_tag,
+            commands::storage::storage_list_tags,
+            commands::storage::storage_search_by_tag,
+            commands::storage::storage_update_ocr_text,
+            commands::storage::storage_add_annotation,
+            commands::storage::storage_update_annotation,
+            commands::storage::storage_delete_annotation,
+            commands::storage::storage_list_annotations,
+            commands::storage::storage_search_annotations,
+            commands::storage::storage_list_clipboard_entries,
+            commands::storage::storage_search_clipboard_entries,
+            commands::storage::storage_delete_clipboard_entry,
+            commands::storage::storage_clear_clipboard_history,
+            commands::storage::storage_list_audio_transcripts,
+            commands::storage::storage_search_audio_transcripts,
+            commands::storage::storage_delete_audio_transcript,
+            commands::storage::storage_pin_screenshot,
+            commands::storage::storage_unpin_screenshot,
+            commands::storage::storage_list_pinned,
+            commands::storage::storage_get_audit_log,
             commands::storage::storage_get_delete_queue_status,
             commands::storage::storage_get_index_health,
             commands::storage::storage_retry_vector_indexing,
+            commands::storage::storage_verify_images,
+            commands::storage::storage_check_integrity,
+            commands::storage::storage_export_search,
+            commands::storage::storage_export_pdf,
+            commands::storage::storage_export_daily_note,
+            commands::storage::storage_get_daily_note_vault,
+            commands::storage::storage_set_daily_note_vault,
+            commands::storage::storage_export_weekly_digest,
             commands::storage::storage_save_screenshot,
             commands::storage::storage_set_policy,
             commands::storage::storage_get_policy,
+            commands::storage::storage_get_retention_policy,
+            commands::storage::storage_set_retention_policy,
+            commands::storage::storage_get_quota,
+            commands::storage::storage_set_quota,
+            commands::storage::storage_get_ocr_text_rules,
+            commands::storage::storage_set_ocr_text_rules,
+            commands::storage::storage_get_compliance_status,
+            commands::storage::storage_set_compliance_mode,
+            commands::storage::storage_get_paranoid_mode,
+            commands::storage::storage_set_paranoid_mode,
+            commands::storage::storage_get_pending_recovery,
+            commands::storage::storage_list_month_shards,
+            commands::storage::storage_detach_month,
+            commands::storage::storage_attach_month,
+            commands::storage::storage_gc,
+            commands::storage::storage_get_context_at,
+            commands::storage::storage_export_range,
+            commands::storage::storage_import_archive,
             commands::storage::storage_get_public_key,
             commands::storage::storage_compute_link_scores,
             commands::storage::storage_encrypt_for_chromadb,
@@ -1130,9 +1316,18 @@ pub fn run() {
             commands::migration::storage_get_startup_vacuum_status,
             commands::migration::storage_run_startup_vacuum_if_needed,
             commands::migration::storage_run_manual_vacuum,
+            commands::migration::storage_maintenance,
+            commands::migration::storage_backup_now,
+            commands::migration::storage_get_backup_dir,
+            commands::migration::storage_set_backup_dir,
+            commands::migration::storage_restore_from_backup,
+            commands::migration::storage_sync_now,
+            commands::migration::storage_get_sync_config,
+            commands::migration::storage_set_sync_config,
             commands::migration::storage_check_hmac_migration_status,
             commands::migration::storage_run_hmac_migration,
             commands::migration::storage_hmac_migration_cancel,
+            commands::migration::storage_rebuild_index,
             commands::migration::storage_export_backup,
             commands::migration::storage_import_backup,
             // 任务聚类命令
@@ -1151,30 +1346,55 @@ pub fn run() {
             commands::mcp::mcp_ack_privacy_warning,
             commands::mcp::mcp_reset_token,
             commands::mcp::mcp_copy_token_to_clipboard,
+            commands::mcp::mcp_list_api_tokens,
+            commands::mcp::mcp_create_api_token,
+            commands::mcp::mcp_copy_api_token_to_clipboard,
+            commands::mcp::mcp_revoke_api_token,
             commands::mcp::mcp_get_port,
             commands::mcp::mcp_set_port,
             commands::mcp::mcp_get_sensitive_filter_config,
             commands::mcp::mcp_set_sensitive_filter_config,
+            commands::scheduler::scheduler_list_jobs,
+            commands::scheduler::scheduler_set_job_enabled,
+            commands::scheduler::scheduler_set_job_interval,
+            commands::scheduler::scheduler_run_job,
             // 高级配置命令
             commands::utility::get_advanced_config,
             commands::utility::set_advanced_config,
             monitor::enumerate_gpus,
+            monitor::enumerate_npus,
             commands::utility::toggle_game_mode,
             commands::utility::get_game_mode_status,
+            commands::utility::get_game_mode_config,
+            commands::utility::set_game_mode_config,
+            commands::utility::get_screen_share_status,
+            commands::utility::set_screen_share_pause_enabled,
+            commands::utility::get_clipboard_history_settings,
+            commands::utility::set_clipboard_history_settings,
+            commands::utility::get_audio_capture_settings,
+            commands::utility::set_audio_capture_settings,
             // 数据迁移命令
             commands::migration::storage_list_plaintext_files,
             commands::migration::storage_migrate_plaintext,
+            commands::migration::storage_normalize_paths,
             commands::migration::storage_migrate_data_dir,
             commands::migration::storage_migration_cancel,
             commands::migration::storage_delete_plaintext,
             // 凭证管理相关命令
             commands::credential::credential_initialize,
+            commands::setup::get_setup_state,
+            commands::setup::advance_setup_step,
+            commands::setup::setup_list_data_drives,
+            commands::setup::setup_estimate_storage,
+            commands::setup::setup_validate_data_dir,
+            commands::setup::setup_set_data_dir,
             commands::credential::credential_verify_user,
             commands::credential::credential_check_session,
             commands::credential::credential_lock_session,
             commands::credential::credential_set_foreground,
             commands::credential::credential_set_session_timeout,
             commands::credential::credential_get_session_timeout,
+            commands::credential::credential_rotate_keys,
             get_autostart_status,
             set_autostart,
             python::check_python_status,
@@ -1206,6 +1426,13 @@ pub fn run() {
             commands::utility::mark_smart_cluster_setup_done,
             commands::utility::get_extension_enhancement_config,
             commands::utility::set_extension_enhancement,
+            commands::utility::get_browser_url_capture_config,
+            commands::utility::set_browser_url_capture,
+            commands::utility::get_hotkeys,
+            commands::utility::set_capture_hotkey,
+            commands::utility::set_search_hotkey,
+            search_overlay::search_overlay_hide,
+            search_overlay::search_overlay_open_main,
             commands::utility::get_nmh_sessions,
             // Smart Cluster commands
             commands::smart_cluster::smart_cluster_list,
@@ -22,6 +22,9 @@ fn main() {
     if args.contains(&"--hidden".to_string()) {
         std::env::set_var("CARBONPAPER_START_HIDDEN", "1");
     }
+    if args.contains(&"--guest".to_string()) {
+        std::env::set_var("CARBONPAPER_GUEST_MODE", "1");
+    }
 
     carbonpaper_lib::run();
 }
@@ -4,7 +4,7 @@
 //! over Streamable HTTP. Binds to 127.0.0.1 only. Requires Bearer token auth.
 
 use axum::{
-    extract::{Request, State},
+    extract::{Extension, Request, State},
     http::{HeaderMap, StatusCode},
     middleware::{self, Next},
     response::{IntoResponse, Json, Response},
@@ -18,11 +18,11 @@ use tokio::sync::oneshot;
 use tower_http::cors::CorsLayer;
 
 use crate::credential_manager::CredentialManagerState;
-use crate::mcp_token;
+use crate::mcp_token::{self, ApiTokenRecord, TokenScope};
 use crate::monitor::{self, MonitorState};
 use crate::sensitive_filter::SensitiveFilterState;
 use crate::storage::smart_cluster::{SmartClusterSummaryRecord, SmartClusterSummaryUpsert};
-use crate::storage::StorageState;
+use crate::storage::{SearchMatchMode, SearchSortBy, StorageState, AUDIT_ACTOR_MCP};
 use percent_encoding::percent_decode_str;
 use tauri::{Emitter, Manager};
 
@@ -38,6 +38,7 @@ pub struct McpRuntimeState {
     server_handle: Mutex<Option<tokio::task::JoinHandle<()>>>,
     shutdown_tx: Mutex<Option<oneshot::Sender<()>>>,
     token_hash: Mutex<Option<[u8; 32]>>,
+    api_tokens: Mutex<Vec<ApiTokenGrant>>,
     idle_check_handle: Mutex<Option<tokio::task::JoinHandle<()>>>,
     last_error: Mutex<Option<String>>,
 }
@@ -54,6 +55,7 @@ impl McpRuntimeState {
             server_handle: Mutex::new(None),
             shutdown_tx: Mutex::new(None),
             token_hash: Mutex::new(None),
+            api_tokens: Mutex::new(Vec::new()),
             idle_check_handle: Mutex::new(None),
             last_error: Mutex::new(None),
         }
@@ -76,6 +78,32 @@ impl McpRuntimeState {
         *self.token_hash.lock().unwrap_or_else(|e| e.into_inner())
     }
 
+    /// Replace the scoped tokens accepted by the running server.
+    pub fn set_api_tokens(&self, records: &[ApiTokenRecord]) {
+        let grants = records
+            .iter()
+            .filter_map(|record| {
+                Some(ApiTokenGrant {
+                    hash: record.hash_bytes()?,
+                    scopes: record.scopes.clone(),
+                })
+            })
+            .collect();
+        *self.api_tokens.lock().unwrap_or_else(|e| e.into_inner()) = grants;
+    }
+
+    fn find_api_token_scopes(&self, hash: &[u8; 32]) -> Option<Vec<TokenScope>> {
+        let grants = self.api_tokens.lock().unwrap_or_else(|e| e.into_inner());
+        let mut found = None;
+        // Compare against every grant so timing does not reveal the match position.
+        for grant in grants.iter() {
+            if constant_time_eq(hash, &grant.hash) && found.is_none() {
+                found = Some(grant.scopes.clone());
+            }
+        }
+        found
+    }
+
     pub fn clear_last_error(&self) {
         let mut guard = self.last_error.lock().unwrap_or_else(|e| e.into_inner());
         *guard = None;
@@ -94,6 +122,35 @@ impl McpRuntimeState {
     }
 }
 
+/// Hash and scopes of a scoped API token, as checked by the auth middleware.
+struct ApiTokenGrant {
+    hash: [u8; 32],
+    scopes: Vec<TokenScope>,
+}
+
+/// Scopes of the token that authenticated the current request.
+#[derive(Clone)]
+struct GrantedScopes(Vec<TokenScope>);
+
+/// Scope a tool requires. Tools not listed here, including unknown names and any
+/// tool added later without a scope, require `Admin`.
+fn tool_required_scope(tool_name: &str) -> TokenScope {
+    match tool_name {
+        "get_snapshots_by_time_range"
+        | "get_context_at"
+        | "get_snapshot_details"
+        | "search_ocr_text"
+        | "search_nl"
+        | "get_task_clusters"
+        | "get_task_screenshots"
+        | "get_smart_clusters"
+        | "get_smart_cluster_ocr_corpus"
+        | "get_smart_cluster_summary" => TokenScope::ReadSearch,
+        "get_snapshot_image" => TokenScope::ReadImages,
+        _ => TokenScope::Admin,
+    }
+}
+
 /// Internal shared state passed to axum handlers.
 struct McpServerInner {
     app_handle: tauri::AppHandle,
@@ -167,7 +224,7 @@ impl JsonRpcResponse {
 
 async fn auth_middleware(
     State(state): State<Arc<McpServerInner>>,
-    req: Request,
+    mut req: Request,
     next: Next,
 ) -> Response {
     let method = req.method().clone();
@@ -190,12 +247,21 @@ async fn auth_middleware(
     };
 
     let provided_hash = mcp_token::hash_token(token);
-    if !constant_time_eq(&provided_hash, &state.token_hash) {
-        tracing::warn!("MCP {} {} — 401 invalid token", method, uri);
-        return (StatusCode::UNAUTHORIZED, "Invalid token").into_response();
-    }
+    let scopes = if constant_time_eq(&provided_hash, &state.token_hash) {
+        vec![TokenScope::Admin]
+    } else {
+        let mcp_runtime = state.app_handle.state::<McpRuntimeState>();
+        match mcp_runtime.find_api_token_scopes(&provided_hash) {
+            Some(scopes) => scopes,
+            None => {
+                tracing::warn!("MCP {} {} — 401 invalid token", method, uri);
+                return (StatusCode::UNAUTHORIZED, "Invalid token").into_response();
+            }
+        }
+    };
 
-    tracing::info!("MCP {} {} — auth ok", method, uri);
+    tracing::info!("MCP {} {} — auth ok (scopes={:?})", method, uri, scopes);
+    req.extensions_mut().insert(GrantedScopes(scopes));
     next.run(req).await
 }
 
@@ -212,6 +278,7 @@ fn constant_time_eq(a: &[u8; 32], b: &[u8; 32]) -> bool {
 
 async fn handle_mcp(
     State(state): State<Arc<McpServerInner>>,
+    Extension(granted): Extension<GrantedScopes>,
     Json(req): Json<JsonRpcRequest>,
 ) -> (StatusCode, HeaderMap, Json<JsonRpcResponse>) {
     let mut headers = HeaderMap::new();
@@ -223,8 +290,8 @@ async fn handle_mcp(
         "initialize" => handle_initialize(req.id),
         "notifications/initialized" => JsonRpcResponse::success(req.id, serde_json::json!({})),
         "ping" => JsonRpcResponse::success(req.id, serde_json::json!({})),
-        "tools/list" => handle_tools_list(&state, req.id),
-        "tools/call" => handle_tools_call(&state, req.id, req.params).await,
+        "tools/list" => handle_tools_list(&state, &granted, req.id),
+        "tools/call" => handle_tools_call(&state, &granted, req.id, req.params).await,
         other => {
             tracing::warn!("MCP unknown method: {}", other);
             JsonRpcResponse::error(req.id, -32601, "Method not found".to_string())
@@ -252,7 +319,11 @@ fn handle_initialize(id: Option<Value>) -> JsonRpcResponse {
 
 // ==================== Tool definitions ====================
 
-fn handle_tools_list(state: &McpServerInner, id: Option<Value>) -> JsonRpcResponse {
+fn handle_tools_list(
+    state: &McpServerInner,
+    granted: &GrantedScopes,
+    id: Option<Value>,
+) -> JsonRpcResponse {
     let mut tools = serde_json::json!({
         "tools": [
             {
@@ -268,6 +339,18 @@ fn handle_tools_list(state: &McpServerInner, id: Option<Value>) -> JsonRpcRespon
                     "required": ["start_time", "end_time"]
                 }
             },
+            {
+                "name": "get_context_at",
+                "description": "Answer \"what was I doing at this moment\": returns the nearest snapshot (active app, window title, page URL) and a summary of the surrounding capture session (time bounds, count, top apps and categories). Timestamp is in milliseconds since Unix epoch.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "timestamp": { "type": "number", "description": "Moment of interest in milliseconds" },
+                        "max_distance_secs": { "type": "integer", "description": "Ignore snapshots further away than this many seconds (default 1800)" }
+                    },
+                    "required": ["timestamp"]
+                }
+            },
             {
                 "name": "get_snapshot_details",
                 "description": "Get full details of a specific snapshot including metadata, OCR text, and the task cluster it belongs to (if any). By default OCR bounding box coordinates are omitted to save tokens; set include_coords=true to include them.",
@@ -280,20 +363,33 @@ fn handle_tools_list(state: &McpServerInner, id: Option<Value>) -> JsonRpcRespon
                     "required": ["id"]
                 }
             },
+            {
+                "name": "get_snapshot_image",
+                "description": "Get the decrypted screenshot image for a snapshot as base64. Requires a token with the read-images scope.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "integer", "description": "Screenshot ID" }
+                    },
+                    "required": ["id"]
+                }
+            },
             {
                 "name": "search_ocr_text",
                 "description": "Search screenshot OCR text using full-text search. Supports CJK and English text.",
                 "inputSchema": {
                     "type": "object",
                     "properties": {
-                        "query": { "type": "string", "description": "Search query text" },
+                        "query": { "type": "string", "description": "Search query text. Terms must all match; supports `a OR b`, `-term` to exclude, and \"quoted phrases\"" },
                         "limit": { "type": "integer", "description": "Max results (default 20)" },
                         "offset": { "type": "integer", "description": "Pagination offset (default 0)" },
                         "fuzzy": { "type": "boolean", "description": "Enable fuzzy matching (default true)" },
                         "process_names": { "type": "array", "items": { "type": "string" }, "description": "Filter by process names" },
                         "start_time": { "type": "number", "description": "Filter start time (ms)" },
                         "end_time": { "type": "number", "description": "Filter end time (ms)" },
-                        "categories": { "type": "array", "items": { "type": "string" }, "description": "Filter by categories" }
+                        "categories": { "type": "array", "items": { "type": "string" }, "description": "Filter by categories" },
+                        "sort_by": { "type": "string", "enum": ["recent", "relevance"], "description": "Result order: newest first or BM25 relevance (default recent)" },
+                        "match_mode": { "type": "string", "enum": ["terms", "phrase", "regex"], "description": "'phrase' matches the whole query literally within one text box, 'regex' as a regular expression, case-sensitive unless prefixed with (?i) (default terms)" }
                     },
                     "required": ["query"]
                 }
@@ -430,10 +526,12 @@ fn handle_tools_list(state: &McpServerInner, id: Option<Value>) -> JsonRpcRespon
                 .is_some()
         })
         .unwrap_or(false);
-    if !python_running {
-        if let Some(items) = tools.get_mut("tools").and_then(Value::as_array_mut) {
-            items.retain(|tool| tool.get("name").and_then(Value::as_str) != Some("search_nl"));
-        }
+    if let Some(items) = tools.get_mut("tools").and_then(Value::as_array_mut) {
+        items.retain(|tool| {
+            let name = tool.get("name").and_then(Value::as_str).unwrap_or("");
+            (python_running || name != "search_nl")
+                && mcp_token::scopes_allow(&granted.0, tool_required_scope(name))
+        });
     }
     JsonRpcResponse::success(id, tools)
 }
@@ -442,6 +540,7 @@ fn handle_tools_list(state: &McpServerInner, id: Option<Value>) -> JsonRpcRespon
 
 async fn handle_tools_call(
     state: &McpServerInner,
+    granted: &GrantedScopes,
     id: Option<Value>,
     params: Option<Value>,
 ) -> JsonRpcResponse {
@@ -458,9 +557,24 @@ async fn handle_tools_call(
 
     tracing::info!("MCP tools/call: tool={}", tool_name);
 
+    let required = tool_required_scope(tool_name);
+    if !mcp_token::scopes_allow(&granted.0, required) {
+        tracing::warn!(
+            "MCP tools/call: tool={} — forbidden for token scopes",
+            tool_name
+        );
+        return JsonRpcResponse::error(
+            id,
+            -32003,
+            format!("FORBIDDEN: token lacks the {:?} scope", required),
+        );
+    }
+
     let result = match tool_name {
         "get_snapshots_by_time_range" => tool_get_snapshots(state, args).await,
+        "get_context_at" => tool_get_context_at(state, args).await,
         "get_snapshot_details" => tool_get_snapshot_details(state, args).await,
+        "get_snapshot_image" => tool_get_snapshot_image(state, args).await,
         "search_ocr_text" => tool_search_ocr(state, args).await,
         "search_nl" => tool_search_nl(state, args).await,
         "get_task_clusters" => tool_get_task_clusters(state, args).await,
@@ -922,6 +1036,69 @@ async fn tool_get_snapshots(state: &McpServerInner, args: Value) -> Result<Value
     Ok(Value::Array(output))
 }
 
+async fn tool_get_context_at(state: &McpServerInner, args: Value) -> Result<Value, String> {
+    require_authenticated_session(&state.app_handle)?;
+
+    let timestamp = args
+        .get("timestamp")
+        .and_then(|v| v.as_f64())
+        .ok_or("Missing required parameter: timestamp")?;
+    let max_distance_secs = args.get("max_distance_secs").and_then(|v| v.as_i64());
+    let timestamp = if timestamp > 10_000_000_000.0 {
+        timestamp / 1000.0
+    } else {
+        timestamp
+    };
+
+    let storage = state.app_handle.state::<Arc<StorageState>>();
+    let storage = storage.inner().clone();
+    let filter = state.app_handle.state::<Arc<SensitiveFilterState>>();
+    let filter = filter.inner().clone();
+
+    let context =
+        tokio::task::spawn_blocking(move || storage.get_context_at(timestamp, max_distance_secs))
+            .await
+            .map_err(|e| format!("Task join error: {:?}", e))??;
+
+    let snapshot = context.screenshot.as_ref().map(|r| {
+        let mut window_title = r.window_title.clone();
+        let mut page_url = r.page_url.clone();
+        if filter.is_enabled() {
+            let mask = filter.get_mode() == "mask";
+            if let Some(title) = window_title.as_mut() {
+                if filter.contains_sensitive(title) {
+                    *title = if mask {
+                        filter.mask_sensitive(title)
+                    } else {
+                        CENSORED_LABEL.to_string()
+                    };
+                }
+            }
+            if let Some(url) = page_url.as_mut() {
+                if filter.contains_sensitive(&decode_url_for_filter(url)) {
+                    *url = CENSORED_LABEL.to_string();
+                }
+            }
+        }
+        serde_json::json!({
+            "id": r.id,
+            "process_name": r.process_name,
+            "window_title": window_title,
+            "page_url": page_url,
+            "category": r.category,
+            "created_at": r.created_at,
+            "timestamp": r.timestamp,
+        })
+    });
+
+    Ok(serde_json::json!({
+        "requested_ts": context.requested_ts,
+        "distance_secs": context.distance_secs,
+        "snapshot": snapshot,
+        "session": context.session,
+    }))
+}
+
 async fn tool_get_snapshot_details(state: &McpServerInner, args: Value) -> Result<Value, String> {
     require_authenticated_session(&state.app_handle)?;
 
@@ -947,7 +1124,12 @@ async fn tool_get_snapshot_details(state: &McpServerInner, args: Value) -> Resul
             Some(mut r) => {
                 r.metadata = None;
                 r.page_icon = None;
-                let ocr_results = storage.get_screenshot_ocr_results(r.id)?;
+                storage.record_access_audit_by(
+                    AUDIT_ACTOR_MCP,
+                    "view_details",
+                    &serde_json::json!({ "screenshot_id": r.id }),
+                )?;
+                let ocr_results = storage.get_screenshot_ocr_results(r.id, None)?;
                 let filter_mode = filter.get_mode();
 
                 // Dictionary-based filtering (tier 1)
@@ -1255,6 +1437,37 @@ async fn tool_get_snapshot_details(state: &McpServerInner, args: Value) -> Resul
     }))
 }
 
+async fn tool_get_snapshot_image(state: &McpServerInner, args: Value) -> Result<Value, String> {
+    require_authenticated_session(&state.app_handle)?;
+
+    let id = args
+        .get("id")
+        .and_then(|v| v.as_i64())
+        .ok_or("Missing required parameter: id")?;
+
+    let storage = state.app_handle.state::<Arc<StorageState>>();
+    let storage = storage.inner().clone();
+    let (data, mime_type) = tokio::task::spawn_blocking(move || {
+        let record = storage
+            .get_screenshot_by_id(id)?
+            .ok_or_else(|| format!("Screenshot {} not found", id))?;
+        storage.record_access_audit_by(
+            AUDIT_ACTOR_MCP,
+            "view_image",
+            &serde_json::json!({ "screenshot_id": id }),
+        )?;
+        storage.read_image(&record.image_path)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))??;
+
+    Ok(serde_json::json!({
+        "id": id,
+        "mime_type": mime_type,
+        "data": data,
+    }))
+}
+
 async fn tool_search_ocr(state: &McpServerInner, args: Value) -> Result<Value, String> {
     require_authenticated_session(&state.app_handle)?;
 
@@ -1274,6 +1487,14 @@ async fn tool_search_ocr(state: &McpServerInner, args: Value) -> Result<Value, S
     let categories: Option<Vec<String>> = args
         .get("categories")
         .and_then(|v| serde_json::from_value(v.clone()).ok());
+    let sort_by: SearchSortBy = args
+        .get("sort_by")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+    let match_mode: SearchMatchMode = args
+        .get("match_mode")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
 
     let storage = state.app_handle.state::<Arc<StorageState>>();
     let storage = storage.inner().clone();
@@ -1292,7 +1513,20 @@ async fn tool_search_ocr(state: &McpServerInner, args: Value) -> Result<Value, S
             start_time,
             end_time,
             categories,
+            None,
+            sort_by,
+            match_mode,
+            None,
         )?;
+        if !results.is_empty() {
+            let mut screenshot_ids: Vec<i64> = results.iter().map(|r| r.screenshot_id).collect();
+            screenshot_ids.dedup();
+            storage.record_access_audit_by(
+                AUDIT_ACTOR_MCP,
+                "search",
+                &serde_json::json!({ "screenshot_ids": screenshot_ids }),
+            )?;
+        }
         let results: Vec<_> = results
             .into_iter()
             .filter(|r| !filter.is_record_sensitive(r.window_title.as_deref(), &[r.text.as_str()]))
@@ -1839,6 +2073,11 @@ async fn tool_get_smart_cluster_ocr_corpus(
     let mut items = tokio::task::spawn_blocking(move || {
         let filter_mode = filter.get_mode();
         let items = storage.list_smart_cluster_ocr_corpus(cluster_id, page, page_size)?;
+        storage.record_access_audit_by(
+            AUDIT_ACTOR_MCP,
+            "view_ocr_texts",
+            &serde_json::json!({ "smart_cluster_id": cluster_id, "page": page }),
+        )?;
         let items: Vec<_> = items
             .into_iter()
             .filter_map(|mut item| {
@@ -2125,6 +2364,9 @@ pub async fn start_server(
 
     let mcp_runtime = app_handle.state::<McpRuntimeState>();
     mcp_runtime.clear_last_error();
+    if let Ok(policy) = app_handle.state::<Arc<StorageState>>().load_policy() {
+        mcp_runtime.set_api_tokens(&mcp_token::load_api_tokens(&policy));
+    }
     {
         let mut guard = mcp_runtime
             .server_handle
@@ -2306,3 +2548,24 @@ pub fn get_port(storage_state: &StorageState) -> u16 {
         .map(|v| v as u16)
         .unwrap_or(DEFAULT_MCP_PORT)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::tool_required_scope;
+    use crate::mcp_token::TokenScope;
+
+    #[test]
+    fn unknown_and_write_tools_require_admin() {
+        assert_eq!(
+            tool_required_scope("search_ocr_text"),
+            TokenScope::ReadSearch
+        );
+        assert_eq!(
+            tool_required_scope("get_snapshot_image"),
+            TokenScope::ReadImages
+        );
+        assert_eq!(tool_required_scope("rename_task"), TokenScope::Admin);
+        assert_eq!(tool_required_scope("delete_everything"), TokenScope::Admin);
+        assert_eq!(tool_required_scope(""), TokenScope::Admin);
+    }
+}
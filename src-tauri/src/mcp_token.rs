@@ -3,9 +3,11 @@
 use crate::credential_manager::{
     self, decrypt_with_master_key, encrypt_with_master_key, CredentialManagerState,
 };
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
 const TOKEN_V2_PREFIX: &str = "v2:";
+pub(crate) const API_TOKENS_POLICY_KEY: &str = "api_tokens";
 
 fn derive_legacy_mcp_key(credential_state: &CredentialManagerState) -> Result<[u8; 32], String> {
     let public_key = credential_manager::get_cached_public_key(credential_state)
@@ -17,13 +19,17 @@ fn derive_legacy_mcp_key(credential_state: &CredentialManagerState) -> Result<[u
     Ok(hasher.finalize().into())
 }
 
+fn mcp_key_from_master(master_key: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(master_key);
+    hasher.update(b"CarbonPaper-MCP-Token-Key-v2");
+    hasher.finalize().into()
+}
+
 fn derive_mcp_key(credential_state: &CredentialManagerState) -> Result<[u8; 32], String> {
     let master_key = credential_manager::get_cached_master_key(credential_state)
         .ok_or_else(|| "AUTH_REQUIRED".to_string())?;
-    let mut hasher = Sha256::new();
-    hasher.update(&master_key);
-    hasher.update(b"CarbonPaper-MCP-Token-Key-v2");
-    Ok(hasher.finalize().into())
+    Ok(mcp_key_from_master(&master_key))
 }
 
 /// Generate a random 64-character hex token.
@@ -38,8 +44,11 @@ pub fn encrypt_token(
     credential_state: &CredentialManagerState,
     token: &str,
 ) -> Result<String, String> {
-    let key = derive_mcp_key(credential_state)?;
-    let encrypted = encrypt_with_master_key(&key, token.as_bytes())
+    encrypt_token_with_key(&derive_mcp_key(credential_state)?, token)
+}
+
+fn encrypt_token_with_key(key: &[u8; 32], token: &str) -> Result<String, String> {
+    let encrypted = encrypt_with_master_key(key, token.as_bytes())
         .map_err(|e| format!("Token encryption failed: {}", e))?;
     let encoded = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &encrypted);
     Ok(format!("{}{}", TOKEN_V2_PREFIX, encoded))
@@ -77,3 +86,179 @@ pub fn decrypt_token(
 pub fn hash_token(token: &str) -> [u8; 32] {
     Sha256::digest(token.as_bytes()).into()
 }
+
+/// Capability granted to a scoped API token. `Admin` implies every other scope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TokenScope {
+    /// Search, timeline, and OCR text access.
+    ReadSearch,
+    /// Decrypted screenshot image access.
+    ReadImages,
+    /// Everything, including tools that modify stored data.
+    Admin,
+}
+
+/// Whether `granted` satisfies `required`.
+pub fn scopes_allow(granted: &[TokenScope], required: TokenScope) -> bool {
+    granted.contains(&TokenScope::Admin) || granted.contains(&required)
+}
+
+/// A scoped API token as persisted in the storage policy.
+///
+/// The plaintext token is kept only in `token_encrypted` (v2 format); `token_hash`
+/// lets the server authenticate requests without unlocking the master key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiTokenRecord {
+    pub id: String,
+    pub name: String,
+    pub scopes: Vec<TokenScope>,
+    pub token_encrypted: String,
+    pub token_hash: String,
+    pub created_at: i64,
+}
+
+impl ApiTokenRecord {
+    pub fn hash_bytes(&self) -> Option<[u8; 32]> {
+        hex::decode(&self.token_hash).ok()?.try_into().ok()
+    }
+}
+
+/// Read scoped API tokens from the policy; malformed entries are skipped.
+pub fn load_api_tokens(policy: &serde_json::Value) -> Vec<ApiTokenRecord> {
+    policy
+        .get(API_TOKENS_POLICY_KEY)
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| serde_json::from_value(item.clone()).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Replace the scoped API token list in the policy.
+pub fn store_api_tokens(
+    policy: &mut serde_json::Value,
+    tokens: &[ApiTokenRecord],
+) -> Result<(), String> {
+    let value = serde_json::to_value(tokens)
+        .map_err(|e| format!("Failed to serialize API tokens: {}", e))?;
+    policy
+        .as_object_mut()
+        .ok_or_else(|| "Policy is not a valid JSON object".to_string())?
+        .insert(API_TOKENS_POLICY_KEY.into(), value);
+    Ok(())
+}
+
+/// Remove scoped API tokens from a policy copy before it leaves the backend.
+pub fn redact_api_tokens(policy: &mut serde_json::Value) {
+    if let Some(obj) = policy.as_object_mut() {
+        obj.remove(API_TOKENS_POLICY_KEY);
+    }
+}
+
+/// Re-encrypt the MCP token and every scoped API token in `policy` for `new_master_key`
+/// during a key rotation. The tokens are decrypted with the current keys, so this must
+/// run before the rotated keys are installed.
+pub fn reseal_policy_tokens(
+    credential_state: &CredentialManagerState,
+    policy: &mut serde_json::Value,
+    new_master_key: &[u8],
+) -> Result<(), String> {
+    let new_key = mcp_key_from_master(new_master_key);
+    let reseal = |encrypted: &str| -> Result<String, String> {
+        encrypt_token_with_key(&new_key, &decrypt_token(credential_state, encrypted)?)
+    };
+
+    if let Some(encrypted) = policy.get("mcp_token_encrypted").and_then(|v| v.as_str()) {
+        let resealed = reseal(encrypted)?;
+        policy["mcp_token_encrypted"] = resealed.into();
+    }
+    let mut tokens = load_api_tokens(policy);
+    if !tokens.is_empty() {
+        for token in &mut tokens {
+            token.token_encrypted = reseal(&token.token_encrypted)?;
+        }
+        store_api_tokens(policy, &tokens)?;
+    }
+    Ok(())
+}
+
+/// Create a scoped token, returning its record and the plaintext for one-time delivery.
+pub fn create_api_token(
+    credential_state: &CredentialManagerState,
+    name: &str,
+    mut scopes: Vec<TokenScope>,
+) -> Result<(ApiTokenRecord, String), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Token name must not be empty".to_string());
+    }
+    if scopes.is_empty() {
+        return Err("At least one scope is required".to_string());
+    }
+
+    let token = generate_token();
+    let mut id_bytes = [0u8; 8];
+    rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut id_bytes);
+
+    scopes.sort_unstable();
+    scopes.dedup();
+    let record = ApiTokenRecord {
+        id: hex::encode(id_bytes),
+        name: name.to_string(),
+        scopes,
+        token_encrypted: encrypt_token(credential_state, &token)?,
+        token_hash: hex::encode(hash_token(&token)),
+        created_at: chrono::Utc::now().timestamp(),
+    };
+    Ok((record, token))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: &str, scopes: Vec<TokenScope>) -> ApiTokenRecord {
+        ApiTokenRecord {
+            id: id.to_string(),
+            name: format!("token {}", id),
+            scopes,
+            token_encrypted: "v2:AAAA".to_string(),
+            token_hash: hex::encode(hash_token(id)),
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn admin_scope_implies_all_others() {
+        assert!(scopes_allow(&[TokenScope::Admin], TokenScope::ReadImages));
+        assert!(scopes_allow(
+            &[TokenScope::ReadSearch],
+            TokenScope::ReadSearch
+        ));
+        assert!(!scopes_allow(
+            &[TokenScope::ReadSearch],
+            TokenScope::ReadImages
+        ));
+        assert!(!scopes_allow(&[], TokenScope::ReadSearch));
+    }
+
+    #[test]
+    fn api_tokens_round_trip_through_policy_and_redaction() {
+        let mut policy = serde_json::json!({ "mcp_enabled": true });
+        let tokens = vec![record("a", vec![TokenScope::ReadSearch])];
+        store_api_tokens(&mut policy, &tokens).unwrap();
+        assert_eq!(policy["api_tokens"][0]["scopes"][0], "read-search");
+
+        let loaded = load_api_tokens(&policy);
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].hash_bytes(), Some(hash_token("a")));
+
+        redact_api_tokens(&mut policy);
+        assert!(policy.get("api_tokens").is_none());
+        assert_eq!(policy["mcp_enabled"], true);
+    }
+}
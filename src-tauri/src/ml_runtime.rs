@@ -468,6 +468,7 @@ impl MlRuntimeState {
             .map_err(|error| format!("failed to start Rust ML worker: {error}"))?;
         let mut pending_child = PendingMlChild::new(child);
         let job = assign_kill_on_close_job(pending_child.child())?;
+        crate::gpu_priority::apply_to_child_if_enabled(pending_child.child(), "Rust ML worker");
         let stdin = pending_child
             .child_mut()
             .stdin
@@ -558,7 +559,7 @@ impl MlRuntimeState {
         inner.last_elapsed_ms = Some(elapsed_ms);
     }
 
-    fn disable_directml_for_session(&self, error: &str) {
+    pub(crate) fn disable_directml_for_session(&self, error: &str) {
         tracing::warn!(
             "[ML:DML] disabling temporary DirectML Beta provider for this session: {}",
             error
@@ -1166,6 +1167,8 @@ fn is_monitor_unavailable_error(error: &str) -> bool {
         "Write frame body error:",
         "Read frame length error:",
         "Read frame body error:",
+        "IPC connection closed",
+        "IPC request queue is full",
         "IPC response timed out after",
     ];
     TRANSPORT_ERROR_PREFIXES
@@ -1236,6 +1239,7 @@ async fn drain_pending_postprocess(app: &AppHandle) -> Result<(), String> {
         }
         let enqueue_result = crate::capture::enqueue_python_ocr_postprocess(
             app,
+            crate::capture::OCR_PRIORITY_BACKFILL,
             screenshot_id,
             &record.image_hash,
             record.window_title.as_deref().unwrap_or(""),
@@ -1336,6 +1340,12 @@ mod tests {
             "Read frame length error: connection reset"
         ));
         assert!(is_monitor_unavailable_error("IPC response timed out after 30s"));
+        assert!(is_monitor_unavailable_error(
+            "IPC connection closed: Read frame length error: broken pipe"
+        ));
+        assert!(is_monitor_unavailable_error(
+            "IPC request queue is full (64 requests waiting)"
+        ));
 
         // Failures reported by Python (or unknown errors) consume the budget.
         assert!(!is_monitor_unavailable_error("postprocess worker crashed"));
@@ -4,10 +4,11 @@
 //! limits, game-mode suppression, restart behavior, and frontend lifecycle events.
 
 use crate::capture::CaptureState;
+use crate::hooks::MonitorHookEvent;
 #[cfg(test)]
 use crate::monitor_ipc::parse_ipc_response;
 use crate::monitor_ipc::{
-    generate_auth_token, generate_random_pipe_name, inject_ipc_auth, send_ipc_request_on_client,
+    generate_auth_token, generate_random_pipe_name, inject_ipc_auth, MultiplexedIpcClient,
 };
 use crate::resource_utils::{find_existing_file_in_resources, normalize_path_for_command};
 use crate::reverse_ipc::{
@@ -22,18 +23,26 @@ use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use tauri::Emitter;
 use tauri::{AppHandle, Manager, State};
-use tokio::net::windows::named_pipe::{ClientOptions, NamedPipeClient};
+use tokio::net::windows::named_pipe::ClientOptions;
 use tokio::sync::Mutex as AsyncMutex;
 
 use std::os::windows::io::AsRawHandle;
+use windows::core::Interface;
 use windows::Win32::Foundation::{CloseHandle, HANDLE};
+use windows::Win32::Graphics::DXCore::{
+    self as dxcore, DXCoreCreateAdapterFactory, IDXCoreAdapter, IDXCoreAdapterFactory,
+    IDXCoreAdapterList, DXCORE_ADAPTER_ATTRIBUTE_D3D12_GENERIC_ML,
+    DXCORE_ADAPTER_ATTRIBUTE_D3D12_GRAPHICS,
+};
+use windows::Win32::Graphics::Direct3D::D3D_FEATURE_LEVEL_11_0;
+use windows::Win32::Graphics::Direct3D12::{D3D12CreateDevice, ID3D12Device};
 use windows::Win32::Graphics::Dxgi::*;
 use windows::Win32::System::JobObjects::{
     AssignProcessToJobObject, CreateJobObjectW, JobObjectCpuRateControlInformation,
-    JobObjectExtendedLimitInformation, SetInformationJobObject,
+    JobObjectExtendedLimitInformation, QueryInformationJobObject, SetInformationJobObject,
     JOBOBJECT_CPU_RATE_CONTROL_INFORMATION, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
     JOB_OBJECT_CPU_RATE_CONTROL_ENABLE, JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP,
-    JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+    JOB_OBJECT_LIMIT_JOB_MEMORY, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
 };
 use windows::Win32::System::Performance::*;
 
@@ -46,18 +55,25 @@ struct MonitorRecoveryState {
     last_error: Option<String>,
     last_crashed_at_ms: Option<u64>,
     crash_count: u64,
+    /// Watchdog restarts made since the monitor last ran stably
+    restart_attempts: u32,
+    next_restart_at_ms: Option<u64>,
+    running_since_ms: Option<u64>,
 }
 
 impl Default for MonitorRecoveryState {
     fn default() -> Self {
         Self {
             state: "stopped".to_string(),
-            policy: "manual_restart".to_string(),
+            policy: "auto_restart".to_string(),
             restart_available: true,
             last_exit_code: None,
             last_error: None,
             last_crashed_at_ms: None,
             crash_count: 0,
+            restart_attempts: 0,
+            next_restart_at_ms: None,
+            running_since_ms: None,
         }
     }
 }
@@ -72,8 +88,47 @@ impl MonitorRecoveryState {
             "last_error": self.last_error,
             "last_crashed_at_ms": self.last_crashed_at_ms,
             "crash_count": self.crash_count,
+            "restart_attempts": self.restart_attempts,
+            "max_restart_attempts": WATCHDOG_MAX_RESTARTS,
+            "next_restart_at_ms": self.next_restart_at_ms,
         })
     }
+
+    /// Books the next watchdog restart with exponential backoff. Returns `false` and
+    /// falls back to the manual restart policy once the restart budget is spent.
+    fn schedule_restart(&mut self, now_ms: u64) -> bool {
+        if self.restart_attempts >= WATCHDOG_MAX_RESTARTS {
+            self.policy = "manual_restart".to_string();
+            self.next_restart_at_ms = None;
+            return false;
+        }
+        self.restart_attempts += 1;
+        self.policy = "auto_restart".to_string();
+        self.next_restart_at_ms =
+            Some(now_ms + watchdog_backoff(self.restart_attempts).as_millis() as u64);
+        true
+    }
+}
+
+// Watchdog: automatic restart after the monitor dies or its pipe stops answering.
+const WATCHDOG_MAX_RESTARTS: u32 = 5;
+const WATCHDOG_BASE_DELAY_SECS: u64 = 2;
+const WATCHDOG_MAX_DELAY_SECS: u64 = 120;
+/// A monitor that ran this long before crashing gets a fresh restart budget.
+const WATCHDOG_STABLE_MS: u64 = 5 * 60 * 1000;
+const WATCHDOG_PROBE_INTERVAL_SECS: u64 = 15;
+const WATCHDOG_PROBE_TIMEOUT_SECS: u64 = 10;
+/// Consecutive failed pipe probes before the monitor is treated as hung and killed.
+const WATCHDOG_MAX_PROBE_FAILURES: u32 = 3;
+
+static WATCHDOG_PROBE_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Delay before restart `attempt` (1-based): 2s, 4s, 8s, … capped at two minutes.
+fn watchdog_backoff(attempt: u32) -> std::time::Duration {
+    let exponent = attempt.saturating_sub(1).min(16);
+    std::time::Duration::from_secs(
+        (WATCHDOG_BASE_DELAY_SECS << exponent).min(WATCHDOG_MAX_DELAY_SECS),
+    )
 }
 
 pub struct MonitorState {
@@ -97,14 +152,12 @@ pub struct MonitorState {
     pub stopping: AtomicBool,
     /// Prevents the monitor from restarting during migration tasks
     pub migration_lock: AtomicBool,
+    /// Timed pause: task that resumes capture when the pause elapses
+    pub pause_timer: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+    /// Timed pause: resume time in Unix milliseconds, 0 when no timed pause is active
+    pub pause_resume_at_ms: AtomicU64,
     recovery: Mutex<MonitorRecoveryState>,
-    python_ipc_client: AsyncMutex<Option<PersistentIpcClient>>,
-}
-
-struct PersistentIpcClient {
-    pipe_name: String,
-    client: NamedPipeClient,
-    requests: u64,
+    python_ipc_client: AsyncMutex<Option<Arc<MultiplexedIpcClient>>>,
 }
 
 impl MonitorState {
@@ -122,6 +175,8 @@ impl MonitorState {
             game_mode_task: Mutex::new(None),
             stopping: AtomicBool::new(false),
             migration_lock: AtomicBool::new(false),
+            pause_timer: Mutex::new(None),
+            pause_resume_at_ms: AtomicU64::new(0),
             recovery: Mutex::new(MonitorRecoveryState::default()),
             python_ipc_client: AsyncMutex::new(None),
         }
@@ -186,12 +241,13 @@ fn monitor_recovery_snapshot(state: &MonitorState) -> Value {
         .to_json()
 }
 
-fn stopped_monitor_status(state: &MonitorState) -> Value {
+fn stopped_monitor_status(state: &MonitorState, capture_state: &CaptureState) -> Value {
     serde_json::json!({
         "paused": false,
         "stopped": true,
         "interval": 0,
         "recovery": monitor_recovery_snapshot(state),
+        "capture_filter": capture_state.capture_filter_status(),
     })
 }
 
@@ -209,6 +265,8 @@ fn set_monitor_recovery_running(state: &MonitorState) {
     recovery.last_error = None;
     recovery.last_exit_code = None;
     recovery.last_crashed_at_ms = None;
+    recovery.next_restart_at_ms = None;
+    recovery.running_since_ms = Some(current_epoch_ms());
 }
 
 fn set_monitor_recovery_stopped(state: &MonitorState) {
@@ -218,6 +276,18 @@ fn set_monitor_recovery_stopped(state: &MonitorState) {
     recovery.last_error = None;
     recovery.last_exit_code = None;
     recovery.last_crashed_at_ms = None;
+    recovery.policy = "auto_restart".to_string();
+    recovery.restart_attempts = 0;
+    recovery.next_restart_at_ms = None;
+    recovery.running_since_ms = None;
+}
+
+/// A manual start gets a fresh watchdog restart budget.
+fn reset_monitor_restart_budget(state: &MonitorState) {
+    let mut recovery = state.recovery.lock().unwrap_or_else(|e| e.into_inner());
+    recovery.policy = "auto_restart".to_string();
+    recovery.restart_attempts = 0;
+    recovery.next_restart_at_ms = None;
 }
 
 fn set_monitor_recovery_failed(state: &MonitorState, error: String) -> Value {
@@ -234,15 +304,186 @@ fn set_monitor_recovery_crashed(
     error: Option<String>,
 ) -> Value {
     let mut recovery = state.recovery.lock().unwrap_or_else(|e| e.into_inner());
+    let now = current_epoch_ms();
+    if recovery
+        .running_since_ms
+        .is_some_and(|since| now.saturating_sub(since) >= WATCHDOG_STABLE_MS)
+    {
+        recovery.restart_attempts = 0;
+    }
+    recovery.running_since_ms = None;
     recovery.state = "crashed".to_string();
     recovery.restart_available = true;
     recovery.last_exit_code = Some(exit_code);
     recovery.last_error = error;
-    recovery.last_crashed_at_ms = Some(current_epoch_ms());
+    recovery.last_crashed_at_ms = Some(now);
     recovery.crash_count = recovery.crash_count.saturating_add(1);
+    recovery.schedule_restart(now);
     recovery.to_json()
 }
 
+/// Hands a crash over to the watchdog: schedules the backoff restart, or tells the
+/// user once the restart budget is spent.
+fn handle_monitor_crash(app: &AppHandle, recovery: &Value) {
+    if recovery["policy"] == "auto_restart" {
+        spawn_watchdog_restart(app.clone());
+    } else {
+        notify_watchdog_gave_up(app, recovery["restart_attempts"].as_u64().unwrap_or(0));
+    }
+}
+
+fn notify_watchdog_gave_up(app: &AppHandle, attempts: u64) {
+    use tauri_plugin_notification::NotificationExt;
+
+    tracing::error!(
+        "Watchdog: monitor keeps failing after {} restarts, giving up",
+        attempts
+    );
+    let language =
+        crate::registry_config::get_string("language").unwrap_or_else(|| "zh-CN".to_string());
+    let title = crate::i18n::t(&language, "notifications.monitor_watchdog.title");
+    let body = crate::i18n::t(&language, "notifications.monitor_watchdog.body");
+    if let Err(error) = app
+        .notification()
+        .builder()
+        .title(&title)
+        .body(body.replace("{{attempts}}", &attempts.to_string()))
+        .show()
+    {
+        tracing::warn!("Failed to show watchdog notification: {}", error);
+    }
+}
+
+/// Restarts the monitor after the booked backoff delay, retrying failed starts until
+/// the restart budget is spent. Stops quietly when the user starts or stops the monitor
+/// in the meantime.
+fn spawn_watchdog_restart(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let (delay_ms, attempt) = {
+                let state = app.state::<MonitorState>();
+                let recovery = state.recovery.lock().unwrap_or_else(|e| e.into_inner());
+                match recovery.next_restart_at_ms {
+                    Some(at) => (
+                        at.saturating_sub(current_epoch_ms()),
+                        recovery.restart_attempts,
+                    ),
+                    None => return,
+                }
+            };
+            tracing::info!(
+                "Watchdog: restarting monitor in {}ms (attempt {}/{})",
+                delay_ms,
+                attempt,
+                WATCHDOG_MAX_RESTARTS
+            );
+            tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
+
+            let state = app.state::<MonitorState>();
+            let still_down = {
+                let recovery = state.recovery.lock().unwrap_or_else(|e| e.into_inner());
+                matches!(recovery.state.as_str(), "crashed" | "failed")
+                    && recovery.restart_attempts == attempt
+            };
+            let process_running = state
+                .process
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .is_some();
+            if !still_down
+                || process_running
+                || state.stopping.load(Ordering::SeqCst)
+                || state.migration_lock.load(Ordering::SeqCst)
+            {
+                return;
+            }
+
+            match start_monitor_impl(app.state::<MonitorState>(), app.clone()).await {
+                Ok(_) => {
+                    tracing::info!("Watchdog: monitor restarted (attempt {})", attempt);
+                    let _ = app.emit("monitor-recovery", monitor_recovery_snapshot(&state));
+                    return;
+                }
+                Err(e) => {
+                    tracing::warn!("Watchdog: restart attempt {} failed: {}", attempt, e);
+                    let (scheduled, recovery) = {
+                        let mut recovery = state.recovery.lock().unwrap_or_else(|e| e.into_inner());
+                        let scheduled = recovery.schedule_restart(current_epoch_ms());
+                        (scheduled, recovery.to_json())
+                    };
+                    let _ = app.emit("monitor-recovery", recovery);
+                    if !scheduled {
+                        notify_watchdog_gave_up(&app, WATCHDOG_MAX_RESTARTS as u64);
+                        return;
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Periodically pings the monitor over its pipe. A process that stays alive but stops
+/// answering is killed, which hands it to the exit watcher and from there to the
+/// watchdog restart. Safe to call more than once.
+fn start_watchdog_probe(app: AppHandle) {
+    if WATCHDOG_PROBE_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        let mut failures = 0u32;
+        loop {
+            tokio::time::sleep(tokio::time::Duration::from_secs(
+                WATCHDOG_PROBE_INTERVAL_SECS,
+            ))
+            .await;
+            let state = app.state::<MonitorState>();
+            let running = state
+                .recovery
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .state
+                == "running";
+            if !running || state.stopping.load(Ordering::SeqCst) {
+                failures = 0;
+                continue;
+            }
+
+            let probe = tokio::time::timeout(
+                tokio::time::Duration::from_secs(WATCHDOG_PROBE_TIMEOUT_SECS),
+                forward_command_to_python(&state, serde_json::json!({ "command": "status" })),
+            )
+            .await;
+            let error = match probe {
+                Ok(Ok(_)) => {
+                    failures = 0;
+                    continue;
+                }
+                Ok(Err(e)) => e,
+                Err(_) => "status probe timed out".to_string(),
+            };
+            failures += 1;
+            tracing::warn!(
+                "Watchdog: monitor pipe probe failed ({}/{}): {}",
+                failures,
+                WATCHDOG_MAX_PROBE_FAILURES,
+                error
+            );
+            if failures >= WATCHDOG_MAX_PROBE_FAILURES {
+                failures = 0;
+                tracing::error!("Watchdog: monitor pipe unresponsive, killing monitor process");
+                if let Some(child) = state
+                    .process
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .as_mut()
+                {
+                    let _ = child.kill();
+                }
+            }
+        }
+    });
+}
+
 fn cleanup_monitor_runtime_after_unexpected_exit(state: &MonitorState) {
     {
         let mut guard = state.reverse_ipc.lock().unwrap_or_else(|e| e.into_inner());
@@ -339,7 +580,7 @@ pub async fn send_ipc_request_reused(
     seq_no: u64,
     req: Value,
 ) -> Result<Value, String> {
-    let mut req = inject_ipc_auth(req, auth_token, seq_no);
+    let req = inject_ipc_auth(req, auth_token, seq_no);
 
     let command_name = req
         .get("command")
@@ -359,72 +600,48 @@ pub async fn send_ipc_request_reused(
         .unwrap_or(default_timeout_secs)
         .clamp(1, 605);
     let ipc_started = std::time::Instant::now();
-    let keepalive = command_name != "stop";
-    if let Some(obj) = req.as_object_mut() {
-        obj.insert("_ipc_keepalive".to_string(), Value::Bool(keepalive));
-    }
 
-    let mut persistent = {
+    // Every caller shares one connection; only the first one after a restart or a
+    // broken pipe connects, so concurrent requests never exhaust pipe instances.
+    let client = {
         let mut guard = state.python_ipc_client.lock().await;
-        match guard.take() {
-            Some(existing) if existing.pipe_name == pipe_name => existing,
+        match &*guard {
+            Some(existing) if existing.pipe_name() == pipe_name && !existing.is_closed() => {
+                existing.clone()
+            }
             _ => {
-                drop(guard);
-                let client = connect_to_pipe(pipe_name).await?;
+                let pipe = connect_to_pipe(pipe_name).await?;
                 tracing::debug!(
-                    "[DIAG:IPC] persistent connection established pipe={}",
+                    "[DIAG:IPC] multiplexed connection established pipe={}",
                     pipe_name
                 );
-                PersistentIpcClient {
-                    pipe_name: pipe_name.to_string(),
-                    client,
-                    requests: 0,
-                }
+                let client = Arc::new(MultiplexedIpcClient::new(pipe_name, pipe));
+                *guard = Some(client.clone());
+                client
             }
         }
     };
 
-    let result = send_ipc_request_on_client(&mut persistent.client, &req, ipc_timeout_secs).await;
+    let result = client.request(req, ipc_timeout_secs).await;
 
     match &result {
-        Ok(_) if keepalive => {
-            persistent.requests = persistent.requests.saturating_add(1);
-            if persistent.requests % 100 == 0 {
-                tracing::debug!(
-                    "[DIAG:IPC] persistent request done command={} seq_no={} reused_count={} elapsed={}ms",
-                    command_name,
-                    seq_no,
-                    persistent.requests,
-                    ipc_started.elapsed().as_millis()
-                );
-            }
-            let pipe_still_current = {
-                let guard = state.pipe_name.lock().unwrap_or_else(|e| e.into_inner());
-                guard.as_deref() == Some(pipe_name)
-            };
+        Ok(_) if command_name == "stop" => {
+            tracing::debug!("[DIAG:IPC] closing connection after command=stop");
             let mut guard = state.python_ipc_client.lock().await;
-            if pipe_still_current && guard.is_none() {
-                *guard = Some(persistent);
-            } else {
-                tracing::debug!(
-                    "[DIAG:IPC] dropping reusable connection command={} pipe_current={} newer_client={}",
-                    command_name,
-                    pipe_still_current,
-                    guard.is_some()
-                );
+            if guard
+                .as_ref()
+                .is_some_and(|current| Arc::ptr_eq(current, &client))
+            {
+                *guard = None;
             }
         }
-        Ok(_) => {
-            tracing::debug!(
-                "[DIAG:IPC] closing persistent connection after command={}",
-                command_name
-            );
-        }
+        Ok(_) => {}
         Err(e) => {
             tracing::warn!(
-                "[DIAG:IPC] persistent connection discarded command={} seq_no={} error={}",
+                "[DIAG:IPC] request failed command={} seq_no={} elapsed={}ms error={}",
                 command_name,
                 seq_no,
+                ipc_started.elapsed().as_millis(),
                 e
             );
         }
@@ -433,6 +650,65 @@ pub async fn send_ipc_request_reused(
     result
 }
 
+/// Registry key for the sidecar Job Object's committed-memory ceiling in MB; 0 is off.
+pub const JOB_MEMORY_LIMIT_REGISTRY_KEY: &str = "job_memory_limit_mb";
+/// Smallest non-zero memory ceiling. The sidecar commits several hundred MB just to
+/// load its models, so lower values would fail it at startup.
+pub const MIN_JOB_MEMORY_LIMIT_MB: u32 = 1024;
+
+/// CPU cap from registry config as `(enabled, percent)`, with the percent in the
+/// 1..=100 range `JOBOBJECT_CPU_RATE_CONTROL_INFORMATION` accepts.
+fn configured_cpu_limit() -> (bool, u32) {
+    let enabled = crate::registry_config::get_bool("cpu_limit_enabled").unwrap_or(true);
+    let percent = crate::registry_config::get_u32("cpu_limit_percent")
+        .unwrap_or(10)
+        .clamp(1, 100);
+    (enabled, percent)
+}
+
+fn configured_job_memory_limit_mb() -> u32 {
+    match crate::registry_config::get_u32(JOB_MEMORY_LIMIT_REGISTRY_KEY).unwrap_or(0) {
+        0 => 0,
+        mb => mb.max(MIN_JOB_MEMORY_LIMIT_MB),
+    }
+}
+
+/// Applies the configured CPU cap and memory ceiling to the running sidecar's Job
+/// Object, so settings changes take effect without restarting the monitor. Does
+/// nothing when the monitor is not running.
+pub fn apply_job_limits(state: &MonitorState) -> Result<(), String> {
+    let guard = state.job_handle.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(job) = guard.as_ref() else {
+        return Ok(());
+    };
+    let (cpu_limit_enabled, cpu_limit_percent) = configured_cpu_limit();
+    set_job_cpu_limit(**job, cpu_limit_enabled, cpu_limit_percent)?;
+    set_job_extended_limits(**job, configured_job_memory_limit_mb())
+}
+
+/// Sets the Job Object's extended limits: kill-on-close always, plus a ceiling on the
+/// memory committed by the whole job when `memory_limit_mb` is non-zero. Allocations
+/// past the ceiling fail inside the sidecar, so it is a backstop, not a soft target.
+fn set_job_extended_limits(handle: HANDLE, memory_limit_mb: u32) -> Result<(), String> {
+    let mut limit_info = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+    limit_info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+    if memory_limit_mb > 0 {
+        limit_info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_JOB_MEMORY;
+        limit_info.JobMemoryLimit = memory_limit_mb as usize * 1024 * 1024;
+    }
+    // SAFETY: `handle` is a live Job Object owned by the caller; `limit_info` has the
+    // exact layout and byte size required by `JobObjectExtendedLimitInformation`.
+    unsafe {
+        SetInformationJobObject(
+            handle,
+            JobObjectExtendedLimitInformation,
+            &limit_info as *const _ as *const _,
+            std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+        )
+    }
+    .map_err(|e| format!("Failed to set Job Object limits: {:?}", e))
+}
+
 /// Updates the Job Object CPU cap at runtime.
 fn set_job_cpu_limit(handle: HANDLE, enabled: bool, percent: u32) -> Result<(), String> {
     // SAFETY: `handle` is a live Job Object owned by monitor state; `cpu_info` has the
@@ -469,8 +745,7 @@ unsafe impl Send for CpuLimitGuard {}
 impl Drop for CpuLimitGuard {
     fn drop(&mut self) {
         let handle = HANDLE(self.job_handle_raw as *mut std::ffi::c_void);
-        let enabled = crate::registry_config::get_bool("cpu_limit_enabled").unwrap_or(true);
-        let percent = crate::registry_config::get_u32("cpu_limit_percent").unwrap_or(10);
+        let (enabled, percent) = configured_cpu_limit();
         let _ = set_job_cpu_limit(handle, enabled, percent);
     }
 }
@@ -526,6 +801,36 @@ fn apply_monitor_side_effects(
                 .get("ignore_protected")
                 .or_else(|| payload.get("ignore_protected"))
                 .and_then(|v| v.as_bool());
+            let ocr_skip_processes = filters
+                .get("ocr_skip_processes")
+                .or_else(|| payload.get("ocr_skip_processes"))
+                .and_then(|v| v.as_array())
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| v.as_str().map(String::from))
+                        .collect::<Vec<_>>()
+                });
+            let redaction_regions = filters
+                .get("redaction_regions")
+                .or_else(|| payload.get("redaction_regions"))
+                .and_then(|v| {
+                    serde_json::from_value::<Vec<crate::capture::RedactionRegion>>(v.clone())
+                        .map_err(|e| tracing::warn!("Ignoring invalid redaction regions: {}", e))
+                        .ok()
+                });
+            let allowlist_enabled = filters
+                .get("allowlist_enabled")
+                .or_else(|| payload.get("allowlist_enabled"))
+                .and_then(|v| v.as_bool());
+            let allowed_processes = filters
+                .get("allowed_processes")
+                .or_else(|| payload.get("allowed_processes"))
+                .and_then(|v| v.as_array())
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| v.as_str().map(String::from))
+                        .collect::<Vec<_>>()
+                });
 
             {
                 let data_dir = storage
@@ -533,7 +838,16 @@ fn apply_monitor_side_effects(
                     .lock()
                     .unwrap_or_else(|e| e.into_inner())
                     .clone();
-                capture_state.update_exclusion_settings(processes, titles, ignore_protected);
+                capture_state.update_exclusion_settings(
+                    processes,
+                    titles,
+                    ignore_protected,
+                    ocr_skip_processes,
+                );
+                capture_state.update_allowlist_settings(allowlist_enabled, allowed_processes);
+                if let Some(regions) = redaction_regions {
+                    capture_state.update_redaction_regions(regions);
+                }
                 capture_state.save_exclusion_settings(&data_dir);
             }
         }
@@ -623,7 +937,66 @@ pub async fn monitor_update_filters(
         "command": "update_filters",
         "filters": filters,
     });
-    dispatch_typed_monitor_command(&state, Some(&capture_state), Some(&storage), payload).await
+    match dispatch_typed_monitor_command(&state, Some(&capture_state), Some(&storage), payload)
+        .await
+    {
+        // The blocklist is already persisted to monitor_filters.json, so the next
+        // capture loop starts with it even though there is no worker to notify.
+        Err(e) if e == "Monitor not started" => Ok(serde_json::json!({
+            "status": "saved",
+            "running": false,
+        })),
+        result => result,
+    }
+}
+
+/// Replaces the window-title regex rules (e.g. `.*Incognito.*`). Capture skips any
+/// foreground window whose title matches. Rules are validated, then persisted in
+/// registry_config and applied to the running capture loop.
+#[tauri::command]
+pub async fn monitor_set_exclusion_rules(
+    credential_state: State<'_, Arc<crate::credential_manager::CredentialManagerState>>,
+    capture_state: State<'_, Arc<CaptureState>>,
+    rules: Vec<String>,
+) -> Result<Value, String> {
+    crate::commands::check_auth_required(&credential_state)?;
+    let rules = crate::capture::set_title_exclusion_rules(&rules)?;
+    capture_state.set_title_rules(&rules)?;
+    Ok(serde_json::json!({ "rules": rules }))
+}
+
+/// Returns the window-title regex rules stored in registry_config.
+#[tauri::command]
+pub async fn monitor_get_exclusion_rules(
+    credential_state: State<'_, Arc<crate::credential_manager::CredentialManagerState>>,
+) -> Result<Value, String> {
+    crate::commands::check_auth_required(&credential_state)?;
+    Ok(serde_json::json!({ "rules": crate::capture::title_exclusion_rules() }))
+}
+
+/// Returns the persisted capture blocklist (`monitor_filters.json`) together with the
+/// built-in process and title lists that are always enforced.
+#[tauri::command]
+pub async fn monitor_get_filters(
+    credential_state: State<'_, Arc<crate::credential_manager::CredentialManagerState>>,
+    capture_state: State<'_, Arc<CaptureState>>,
+    storage: State<'_, Arc<StorageState>>,
+) -> Result<Value, String> {
+    crate::commands::check_auth_required(&credential_state)?;
+    let data_dir = storage
+        .data_dir
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    let persisted = data_dir.join("monitor_filters.json").exists();
+    if persisted {
+        capture_state.load_exclusion_settings(&data_dir);
+    }
+    let mut snapshot = capture_state.exclusion_settings_snapshot();
+    if let Some(obj) = snapshot.as_object_mut() {
+        obj.insert("persisted".to_string(), Value::Bool(persisted));
+    }
+    Ok(snapshot)
 }
 
 #[tauri::command]
@@ -925,7 +1298,11 @@ pub async fn forward_command_to_python(
     send_ipc_request_reused(state, &pipe_name, &auth_token, seq_no, payload).await
 }
 
-fn create_job_object(cpu_limit_enabled: bool, cpu_limit_percent: u32) -> Result<JobHandle, String> {
+fn create_job_object(
+    cpu_limit_enabled: bool,
+    cpu_limit_percent: u32,
+    memory_limit_mb: u32,
+) -> Result<JobHandle, String> {
     // SAFETY: information structures match their Job Object information classes and
     // remain alive for each call. The new handle is closed on all error paths or moved
     // into `JobHandle`, which enforces single-close ownership.
@@ -952,18 +1329,11 @@ fn create_job_object(cpu_limit_enabled: bool, cpu_limit_percent: u32) -> Result<
             }
         }
 
-        // Ensure children terminate when CarbonPaper closes the last Job handle.
-        let mut limit_info = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
-        limit_info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
-
-        if let Err(e) = SetInformationJobObject(
-            handle,
-            JobObjectExtendedLimitInformation,
-            &limit_info as *const _ as *const _,
-            std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
-        ) {
+        // Ensure children terminate when CarbonPaper closes the last Job handle, and
+        // apply the memory ceiling if one is configured.
+        if let Err(e) = set_job_extended_limits(handle, memory_limit_mb) {
             let _ = CloseHandle(handle); // 确保清理资源
-            return Err(format!("Failed to set Kill-on-Close limit: {:?}", e));
+            return Err(e);
         }
 
         Ok(JobHandle::new(handle))
@@ -1067,6 +1437,9 @@ pub async fn start_monitor_impl(
     if state.migration_lock.load(Ordering::SeqCst) {
         return Err("Cannot start monitor: Migration is currently in progress".to_string());
     }
+    if app.state::<Arc<StorageState>>().is_read_only() {
+        return Err("Cannot start monitor: storage is in read-only guest mode".to_string());
+    }
 
     // Check if required model files are complete
     if let Ok(model_status) = crate::model_management::check_model_files().await {
@@ -1329,12 +1702,13 @@ pub async fn start_monitor_impl(
         use std::process::Stdio;
 
         let job = {
-            let cpu_limit_enabled =
-                crate::registry_config::get_bool("cpu_limit_enabled").unwrap_or(true);
-            let cpu_limit_percent =
-                crate::registry_config::get_u32("cpu_limit_percent").unwrap_or(10);
-            create_job_object(cpu_limit_enabled, cpu_limit_percent)
-                .map_err(|e| format!("Failed to create Job Object: {}", e))?
+            let (cpu_limit_enabled, cpu_limit_percent) = configured_cpu_limit();
+            create_job_object(
+                cpu_limit_enabled,
+                cpu_limit_percent,
+                configured_job_memory_limit_mb(),
+            )
+            .map_err(|e| format!("Failed to create Job Object: {}", e))?
         };
 
         let mut cmd_proc = Command::new(&launcher_executable);
@@ -1436,6 +1810,14 @@ pub async fn start_monitor_impl(
                     .clamp(30, 600)
                     .to_string(),
             );
+        let (ocr_queue_max_size, ocr_queue_drop_policy) = crate::capture::ocr_queue_settings();
+        cmd_proc
+            .env(
+                "CARBONPAPER_OCR_POSTPROCESS_QUEUE_MAX",
+                ocr_queue_max_size.to_string(),
+            )
+            .env("CARBONPAPER_OCR_QUEUE_DROP_POLICY", ocr_queue_drop_policy);
+        cmd_proc.envs(crate::audio_capture::load_settings().monitor_env());
 
         if let Some(resolved) = &resolved_model_runtime {
             let paths = &resolved.paths;
@@ -1459,38 +1841,50 @@ pub async fn start_monitor_impl(
                     .game_mode_permanently_suppressed
                     .load(Ordering::SeqCst);
             if !suppressed {
-                // 先枚举可用 GPU，如果完全没有可用显卡则跳过 DML
+                // 校验所选 GPU 是否仍然存在且支持 DirectML，否则换卡或回退到 CPU 并持久化
+                let configured = crate::registry_config::get_u32("dml_device_id").unwrap_or(0);
                 let gpus = enumerate_gpus_internal().unwrap_or_default();
-                if gpus.is_empty() {
-                    tracing::warn!(
-                        "No compatible GPU detected, skipping DirectML (falling back to CPU)"
-                    );
-                } else {
-                    cmd_proc.env("CARBONPAPER_USE_DML", "1");
-                    let mut device_id =
-                        crate::registry_config::get_u32("dml_device_id").unwrap_or(0);
-                    // 校验 device_id 是否仍然有效，无效则回退到第一张可用卡
-                    if !gpus
-                        .iter()
-                        .any(|g| g.get("id").and_then(|v| v.as_u64()) == Some(device_id as u64))
-                    {
-                        let fallback_id =
-                            gpus[0].get("id").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
-                        tracing::warn!(
-                            "DML device_id {} no longer exists, falling back to {}",
-                            device_id,
-                            fallback_id
-                        );
-                        device_id = fallback_id;
-                        let _ = crate::registry_config::set_u32("dml_device_id", device_id);
+                let mut device_id = crate::dml_fallback::select_dml_device(&gpus, configured, None);
+                if device_id != Some(configured) {
+                    match crate::dml_fallback::fall_back_python_dml(
+                        &app,
+                        Some(configured),
+                        "Selected DirectML device is no longer available",
+                    ) {
+                        Ok(fallback) => device_id = fallback.fallback_device_id,
+                        Err(e) => tracing::warn!("Failed to persist DirectML fallback: {}", e),
+                    }
+                }
+                match device_id {
+                    Some(device_id) => {
+                        cmd_proc.env("CARBONPAPER_USE_DML", "1");
+                        cmd_proc.env("CARBONPAPER_DML_DEVICE_ID", device_id.to_string());
                     }
-                    cmd_proc.env("CARBONPAPER_DML_DEVICE_ID", device_id.to_string());
+                    None => tracing::warn!(
+                        "No DirectML-capable GPU detected, skipping DirectML (falling back to CPU)"
+                    ),
                 }
             } else {
                 tracing::info!("Game mode: DML suppressed, starting Python without DML");
             }
         }
 
+        // NPUs are not shared with games, so game mode does not suppress them.
+        if crate::registry_config::get_bool("use_npu").unwrap_or(false) {
+            let configured = crate::registry_config::get_string("npu_provider")
+                .unwrap_or_else(|| "auto".to_string());
+            let npus = enumerate_npus_internal().unwrap_or_default();
+            match resolve_npu_provider(&configured, &npus) {
+                Some(provider) => {
+                    cmd_proc.env("CARBONPAPER_NPU_PROVIDER", provider.ep_name());
+                }
+                None => tracing::warn!(
+                    "No supported NPU detected, skipping NPU execution provider (npu_provider={})",
+                    configured
+                ),
+            }
+        }
+
         cmd_proc
             .env(
                 "TRACEMALLOC_SNAPSHOT_DIR",
@@ -1517,6 +1911,7 @@ pub async fn start_monitor_impl(
             AssignProcessToJobObject(*job, process_handle)
                 .expect("Failed to assign process to job");
         }
+        crate::gpu_priority::apply_to_child_if_enabled(&child, "Python monitor");
 
         // 存储管道名和认证 token
         {
@@ -1552,6 +1947,15 @@ pub async fn start_monitor_impl(
                             cache.drain(0..overflow);
                         }
                     }
+                    if let Some((device_id, error)) =
+                        crate::dml_fallback::parse_python_fallback_line(&l)
+                    {
+                        if let Err(e) =
+                            crate::dml_fallback::fall_back_python_dml(&app_clone, device_id, &error)
+                        {
+                            tracing::warn!("Failed to persist DirectML fallback: {}", e);
+                        }
+                    }
                     // Print to console as well as emit to frontend, so logs are visible in terminal
                     tracing::info!(target: "monitor.stdout", "{}", l);
                     let _ = app_clone.emit(
@@ -1629,6 +2033,7 @@ pub async fn start_monitor_impl(
                                 "monitor-exited",
                                 serde_json::json!({"code": code, "recovery": recovery}),
                             );
+                            handle_monitor_crash(&app_clone, &recovery);
                         }
                         break;
                     }
@@ -1654,6 +2059,7 @@ pub async fn start_monitor_impl(
                                     "recovery": recovery,
                                 }),
                             );
+                            handle_monitor_crash(&app_clone, &recovery);
                         }
                         break;
                     }
@@ -1672,8 +2078,10 @@ pub async fn start_monitor_impl(
             Ok(_) => {
                 // 管道可连接，说明服务已就绪 — 启动 Rust 截图循环
                 set_monitor_recovery_running(&state);
+                start_watchdog_probe(app.clone());
                 spawn_capture_loop(&app);
                 crate::refresh_tray_menu(&app);
+                crate::hooks::fire(&app, MonitorHookEvent::Start);
                 return Ok("Monitor started".into());
             }
             Err(e) => {
@@ -1753,6 +2161,7 @@ pub async fn start_monitor(
     app: AppHandle,
 ) -> Result<String, String> {
     crate::commands::check_main_window(&window)?;
+    reset_monitor_restart_budget(&state);
     start_monitor_impl(state, app).await
 }
 
@@ -1772,6 +2181,26 @@ pub fn set_monitor_autostart(
     crate::registry_config::set_bool("autoStartMonitor", enabled)
 }
 
+#[tauri::command]
+pub fn get_capture_exclude_own_windows() -> bool {
+    crate::capture::exclude_own_windows_enabled()
+}
+
+/// Toggles hiding CarbonPaper's own windows from screen capture (debugging aid).
+#[tauri::command]
+pub fn set_capture_exclude_own_windows(
+    window: tauri::Window,
+    credential_state: State<'_, Arc<crate::credential_manager::CredentialManagerState>>,
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), String> {
+    crate::commands::check_main_window(&window)?;
+    crate::commands::check_auth_required(&credential_state)?;
+    crate::capture::set_exclude_own_windows_enabled(enabled)?;
+    crate::capture::apply_capture_exclusion_to_all(&app);
+    Ok(())
+}
+
 /// Spawn the Rust-side capture loop using CaptureState
 fn spawn_capture_loop(app: &AppHandle) {
     let capture_state = app.state::<Arc<CaptureState>>();
@@ -1795,10 +2224,16 @@ fn spawn_capture_loop(app: &AppHandle) {
             .unwrap_or_else(|e| e.into_inner())
             .clone();
         capture_state.load_exclusion_settings(&data_dir);
+        capture_state.load_title_rules();
     }
 
     // Load advanced config from registry
     {
+        capture_state.set_capture_tuning(
+            crate::registry_config::get_u32(crate::capture::CAPTURE_INTERVAL_REGISTRY_KEY)
+                .map(u64::from),
+            crate::registry_config::get_u32(crate::capture::CAPTURE_DHASH_THRESHOLD_REGISTRY_KEY),
+        );
         let ocr_timeout_secs = crate::registry_config::get_u32("ocr_timeout_secs")
             .unwrap_or(120)
             .clamp(30, 600);
@@ -1879,6 +2314,7 @@ pub async fn stop_monitor_impl(
     // 1. Stop the Rust capture loop
     capture_state.stopped.store(true, Ordering::SeqCst);
     capture_state.paused.store(false, Ordering::SeqCst);
+    cancel_pause_timer(&state, &app);
 
     // Signal the watcher thread to suppress monitor-exited event
     state.stopping.store(true, Ordering::SeqCst);
@@ -1993,6 +2429,7 @@ pub async fn stop_monitor_impl(
     set_monitor_recovery_stopped(&state);
     crate::refresh_tray_menu(&app);
     let _ = app.emit("monitor-stopped", serde_json::json!({"intentional": true}));
+    crate::hooks::fire(&app, MonitorHookEvent::Stop);
 
     Ok("Monitor stopped".into())
 }
@@ -2009,29 +2446,183 @@ pub async fn stop_monitor(
 }
 
 /// Pauses screenshot capture without stopping the Python process.
+/// Longest timed pause accepted by `pause_monitor`.
+const MAX_TIMED_PAUSE_MINUTES: u64 = 24 * 60;
+
+/// Cancels a pending timed pause and tells the UI the countdown is gone.
+fn cancel_pause_timer(state: &MonitorState, app: &AppHandle) {
+    if let Some(handle) = state
+        .pause_timer
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take()
+    {
+        handle.abort();
+    }
+    if state.pause_resume_at_ms.swap(0, Ordering::SeqCst) != 0 {
+        let _ = app.emit(
+            "monitor-pause-timer",
+            serde_json::json!({ "resume_at": null, "remaining_secs": 0 }),
+        );
+    }
+}
+
+/// Schedules an automatic resume after `duration`, emitting `monitor-pause-timer` every
+/// second so the UI can show the countdown.
+fn start_pause_timer(state: &MonitorState, app: &AppHandle, duration: std::time::Duration) {
+    cancel_pause_timer(state, app);
+    let resume_at = chrono::Utc::now().timestamp_millis() as u64 + duration.as_millis() as u64;
+    state.pause_resume_at_ms.store(resume_at, Ordering::SeqCst);
+
+    let app_handle = app.clone();
+    let handle = tauri::async_runtime::spawn(async move {
+        let deadline = tokio::time::Instant::now() + duration;
+        loop {
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            let _ = app_handle.emit(
+                "monitor-pause-timer",
+                serde_json::json!({
+                    "resume_at": resume_at,
+                    "remaining_secs": remaining.as_secs(),
+                }),
+            );
+            if remaining.is_zero() {
+                break;
+            }
+            tokio::time::sleep(remaining.min(std::time::Duration::from_secs(1))).await;
+        }
+
+        let state = app_handle.state::<MonitorState>();
+        // Detach this task first so resuming does not abort it.
+        state
+            .pause_timer
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        tracing::info!("Timed pause elapsed, resuming capture");
+        if let Err(e) = resume_monitor_impl(
+            state,
+            app_handle.state::<Arc<CaptureState>>(),
+            app_handle.clone(),
+        )
+        .await
+        {
+            tracing::warn!("Failed to resume after timed pause: {}", e);
+        }
+    });
+    *state.pause_timer.lock().unwrap_or_else(|e| e.into_inner()) = Some(handle);
+}
+
+/// User pause state, saved before a monitor restart so it can be re-applied afterwards
+/// (`stop_monitor_impl` and the new capture loop both clear it).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PauseSnapshot {
+    pub paused: bool,
+    /// End of a timed pause in Unix milliseconds, 0 for an indefinite pause.
+    pub resume_at_ms: u64,
+}
+
+/// How to re-apply a `PauseSnapshot` after the restart.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum PauseRestore {
+    /// Capture was running, or the timed pause ran out during the restart.
+    Running,
+    Indefinite,
+    /// Timed pause with this much time left.
+    Timed(std::time::Duration),
+}
+
+impl PauseSnapshot {
+    pub(crate) fn capture(state: &MonitorState, capture_state: &CaptureState) -> Self {
+        let paused = capture_state.paused.load(Ordering::SeqCst);
+        Self {
+            paused,
+            resume_at_ms: if paused {
+                state.pause_resume_at_ms.load(Ordering::SeqCst)
+            } else {
+                0
+            },
+        }
+    }
+
+    pub(crate) fn restore_at(&self, now_ms: u64) -> PauseRestore {
+        if !self.paused {
+            return PauseRestore::Running;
+        }
+        if self.resume_at_ms == 0 {
+            return PauseRestore::Indefinite;
+        }
+        match self.resume_at_ms.saturating_sub(now_ms) {
+            0 => PauseRestore::Running,
+            left => PauseRestore::Timed(std::time::Duration::from_millis(left)),
+        }
+    }
+}
+
+/// Re-applies a pause saved with `PauseSnapshot::capture` after the monitor restarted,
+/// re-arming a timed pause for the time it had left. Does not fire pause hooks: the
+/// user-visible state is unchanged.
+pub(crate) async fn restore_pause(
+    state: State<'_, MonitorState>,
+    capture_state: State<'_, Arc<CaptureState>>,
+    app: &AppHandle,
+    snapshot: PauseSnapshot,
+) -> Result<(), String> {
+    let now_ms = chrono::Utc::now().timestamp_millis() as u64;
+    match snapshot.restore_at(now_ms) {
+        PauseRestore::Running => return Ok(()),
+        PauseRestore::Indefinite => capture_state.paused.store(true, Ordering::SeqCst),
+        PauseRestore::Timed(left) => {
+            capture_state.paused.store(true, Ordering::SeqCst);
+            start_pause_timer(&state, app, left);
+        }
+    }
+    let result = send_ipc_command_internal(&state, "pause").await;
+    crate::refresh_tray_menu(app);
+    result.map(|_| ())
+}
+
+/// Pauses capture, indefinitely or for `duration_minutes` when given.
 pub async fn pause_monitor_impl(
     state: State<'_, MonitorState>,
     capture_state: State<'_, Arc<CaptureState>>,
     app: AppHandle,
+    duration_minutes: Option<u64>,
 ) -> Result<String, String> {
+    if let Some(minutes) = duration_minutes {
+        if minutes == 0 || minutes > MAX_TIMED_PAUSE_MINUTES {
+            return Err(format!(
+                "Pause duration must be between 1 and {} minutes",
+                MAX_TIMED_PAUSE_MINUTES
+            ));
+        }
+    }
     // Pause Rust capture loop
     capture_state.paused.store(true, Ordering::SeqCst);
+    match duration_minutes {
+        Some(minutes) => {
+            start_pause_timer(&state, &app, std::time::Duration::from_secs(minutes * 60))
+        }
+        None => cancel_pause_timer(&state, &app),
+    }
     // Also forward to Python so OCR worker pauses
     let result = send_ipc_command_internal(&state, "pause").await;
     crate::refresh_tray_menu(&app);
+    crate::hooks::fire(&app, MonitorHookEvent::Pause);
     result
 }
 
-/// Resumes screenshot capture after a pause.
+/// Pauses screenshot capture; with `duration_minutes` capture resumes automatically.
 #[tauri::command]
 pub async fn pause_monitor(
     window: tauri::Window,
     state: State<'_, MonitorState>,
     capture_state: State<'_, Arc<CaptureState>>,
     app: AppHandle,
+    duration_minutes: Option<u64>,
 ) -> Result<String, String> {
     crate::commands::check_main_window(&window)?;
-    pause_monitor_impl(state, capture_state, app).await
+    pause_monitor_impl(state, capture_state, app, duration_minutes).await
 }
 
 pub async fn resume_monitor_impl(
@@ -2041,9 +2632,11 @@ pub async fn resume_monitor_impl(
 ) -> Result<String, String> {
     // Resume Rust capture loop
     capture_state.paused.store(false, Ordering::SeqCst);
+    cancel_pause_timer(&state, &app);
     // Also forward to Python so OCR worker resumes
     let result = send_ipc_command_internal(&state, "resume").await;
     crate::refresh_tray_menu(&app);
+    crate::hooks::fire(&app, MonitorHookEvent::Resume);
     result
 }
 
@@ -2059,15 +2652,26 @@ pub async fn resume_monitor(
 }
 
 #[tauri::command]
-pub async fn get_monitor_status(state: State<'_, MonitorState>) -> Result<String, String> {
+pub async fn get_monitor_status(
+    state: State<'_, MonitorState>,
+    capture_state: State<'_, Arc<CaptureState>>,
+) -> Result<String, String> {
     if state.stopping.load(Ordering::SeqCst) {
-        return Ok(stopped_monitor_status(&state).to_string());
+        return Ok(stopped_monitor_status(&state, &capture_state).to_string());
     }
 
     match forward_command_to_python(&state, serde_json::json!({ "command": "status" })).await {
         Ok(mut status) => {
             if let Some(obj) = status.as_object_mut() {
                 obj.insert("recovery".to_string(), monitor_recovery_snapshot(&state));
+                obj.insert(
+                    "capture_filter".to_string(),
+                    capture_state.capture_filter_status(),
+                );
+                let resume_at = state.pause_resume_at_ms.load(Ordering::SeqCst);
+                if resume_at != 0 {
+                    obj.insert("pause_resume_at".to_string(), serde_json::json!(resume_at));
+                }
             }
             Ok(status.to_string())
         }
@@ -2092,7 +2696,7 @@ pub async fn get_monitor_status(state: State<'_, MonitorState>) -> Result<String
             }
 
             if !running {
-                return Ok(stopped_monitor_status(&state).to_string());
+                return Ok(stopped_monitor_status(&state, &capture_state).to_string());
             }
 
             Err(e)
@@ -2100,9 +2704,257 @@ pub async fn get_monitor_status(state: State<'_, MonitorState>) -> Result<String
     }
 }
 
+/// Memory use of the Python sidecar: working set and private bytes of the monitor
+/// process, plus the peak committed memory of its Job Object (children included).
+fn monitor_memory_snapshot(state: &MonitorState) -> Value {
+    use windows::Win32::System::ProcessStatus::{
+        GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS, PROCESS_MEMORY_COUNTERS_EX,
+    };
+
+    let mut snapshot = serde_json::json!({
+        "working_set_bytes": null,
+        "private_bytes": null,
+        "job_peak_bytes": null,
+    });
+    {
+        let guard = state.process.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(child) = guard.as_ref() {
+            let mut counters = PROCESS_MEMORY_COUNTERS_EX::default();
+            // SAFETY: the process handle is owned by `child`, which stays alive while the
+            // lock is held, and `counters` is sized by the `cb` argument.
+            let queried = unsafe {
+                GetProcessMemoryInfo(
+                    HANDLE(child.as_raw_handle() as _),
+                    &mut counters as *mut _ as *mut PROCESS_MEMORY_COUNTERS,
+                    std::mem::size_of::<PROCESS_MEMORY_COUNTERS_EX>() as u32,
+                )
+            };
+            if queried.is_ok() {
+                snapshot["working_set_bytes"] = serde_json::json!(counters.WorkingSetSize);
+                snapshot["private_bytes"] = serde_json::json!(counters.PrivateUsage);
+            }
+        }
+    }
+    {
+        let guard = state.job_handle.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(job) = guard.as_ref() {
+            let mut info = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+            // SAFETY: `job` owns a live Job Object handle for the duration of the lock and
+            // `info` is sized by the length argument.
+            let queried = unsafe {
+                QueryInformationJobObject(
+                    **job,
+                    JobObjectExtendedLimitInformation,
+                    &mut info as *mut _ as *mut _,
+                    std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+                    None,
+                )
+            };
+            if queried.is_ok() {
+                snapshot["job_peak_bytes"] = serde_json::json!(info.PeakJobMemoryUsed);
+            }
+        }
+    }
+    snapshot
+}
+
+/// Diagnostics for stutter reports: capture counters, OCR timing and queue depth,
+/// a live pipe round trip to the Python monitor and the sidecar's memory use.
+#[tauri::command]
+pub async fn monitor_get_metrics(
+    state: State<'_, MonitorState>,
+    capture_state: State<'_, Arc<CaptureState>>,
+) -> Result<Value, String> {
+    let running = state
+        .process
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .is_some();
+    let pipe_rtt_ms = if running && !state.stopping.load(Ordering::SeqCst) {
+        let started = std::time::Instant::now();
+        match forward_command_to_python(&state, serde_json::json!({ "command": "status" })).await {
+            Ok(_) => Some(started.elapsed().as_secs_f64() * 1000.0),
+            Err(e) => {
+                tracing::debug!("Metrics: pipe round trip failed: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let mut metrics = capture_state.metrics.snapshot();
+    metrics["ocr"]["queue_depth"] =
+        serde_json::json!(capture_state.in_flight_ocr_count.load(Ordering::SeqCst));
+    metrics["running"] = serde_json::json!(running);
+    metrics["pipe_rtt_ms"] = serde_json::json!(pipe_rtt_ms);
+    metrics["memory"] = monitor_memory_snapshot(&state);
+    metrics["memory"]["recycles"] = serde_json::json!(crate::sidecar_memory::recycle_count());
+    Ok(metrics)
+}
+
+// Scheduled capture hours.
+
+/// Registry value holding the weekly capture schedule as JSON.
+const CAPTURE_SCHEDULE_KEY: &str = "capture_schedule";
+
+/// Polling cadence of the schedule task.
+const CAPTURE_SCHEDULE_POLL_SECS: u64 = 30;
+
+/// Ensures only one schedule task is ever spawned.
+static CAPTURE_SCHEDULE_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// One recurring capture window. `days` are 0 = Monday … 6 = Sunday; times are
+/// local `HH:MM`. An `end` earlier than `start` runs past midnight into the next day,
+/// and equal times cover the whole day.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CaptureScheduleWindow {
+    pub days: Vec<u8>,
+    pub start: String,
+    pub end: String,
+}
+
+/// Weekly capture schedule. When enabled, capture is paused outside every window.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CaptureSchedule {
+    pub enabled: bool,
+    #[serde(default)]
+    pub windows: Vec<CaptureScheduleWindow>,
+}
+
+fn parse_schedule_time(value: &str) -> Option<u32> {
+    let (hours, minutes) = value.trim().split_once(':')?;
+    let hours: u32 = hours.parse().ok()?;
+    let minutes: u32 = minutes.parse().ok()?;
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+impl CaptureSchedule {
+    fn validate(&self) -> Result<(), String> {
+        for window in &self.windows {
+            if window.days.is_empty() || window.days.iter().any(|d| *d > 6) {
+                return Err("Schedule days must be between 0 (Monday) and 6 (Sunday)".to_string());
+            }
+            for time in [&window.start, &window.end] {
+                if parse_schedule_time(time).is_none() {
+                    return Err(format!("Invalid schedule time '{}', expected HH:MM", time));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether capture may run at `minute` of `weekday` (0 = Monday). A disabled or
+    /// empty schedule always allows capture.
+    fn allows(&self, weekday: u8, minute: u32) -> bool {
+        if !self.enabled || self.windows.is_empty() {
+            return true;
+        }
+        let previous_day = (weekday + 6) % 7;
+        self.windows.iter().any(|window| {
+            let (Some(start), Some(end)) = (
+                parse_schedule_time(&window.start),
+                parse_schedule_time(&window.end),
+            ) else {
+                return false;
+            };
+            let today = window.days.contains(&weekday);
+            match start.cmp(&end) {
+                std::cmp::Ordering::Less => today && minute >= start && minute < end,
+                std::cmp::Ordering::Equal => today,
+                std::cmp::Ordering::Greater => {
+                    (today && minute >= start)
+                        || (window.days.contains(&previous_day) && minute < end)
+                }
+            }
+        })
+    }
+
+    fn allows_now(&self) -> bool {
+        use chrono::{Datelike, Timelike};
+        let now = chrono::Local::now();
+        self.allows(
+            now.weekday().num_days_from_monday() as u8,
+            now.hour() * 60 + now.minute(),
+        )
+    }
+}
+
+pub fn load_capture_schedule() -> CaptureSchedule {
+    crate::registry_config::get_string(CAPTURE_SCHEDULE_KEY)
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+/// Re-evaluates the schedule and updates `CaptureState::schedule_paused`.
+fn apply_capture_schedule(app: &AppHandle, schedule: &CaptureSchedule) {
+    let capture_state = app.state::<Arc<CaptureState>>();
+    let should_pause = !schedule.allows_now();
+    let was_paused = capture_state
+        .schedule_paused
+        .swap(should_pause, Ordering::SeqCst);
+    if was_paused == should_pause {
+        return;
+    }
+    if should_pause {
+        tracing::info!("Capture schedule: outside capture hours, pausing capture");
+    } else {
+        tracing::info!("Capture schedule: capture hours started, resuming capture");
+    }
+    let _ = app.emit(
+        "capture-schedule-status",
+        serde_json::json!({ "paused": should_pause }),
+    );
+    crate::refresh_tray_menu(app);
+}
+
+/// Starts the task that pauses and resumes capture according to the weekly schedule.
+/// Safe to call more than once.
+pub fn start_capture_schedule_task(app: AppHandle) {
+    if CAPTURE_SCHEDULE_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        loop {
+            apply_capture_schedule(&app, &load_capture_schedule());
+            tokio::time::sleep(tokio::time::Duration::from_secs(CAPTURE_SCHEDULE_POLL_SECS)).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_capture_schedule(app: AppHandle) -> Result<Value, String> {
+    let schedule = load_capture_schedule();
+    let paused = app
+        .state::<Arc<CaptureState>>()
+        .schedule_paused
+        .load(Ordering::SeqCst);
+    Ok(serde_json::json!({ "schedule": schedule, "paused": paused }))
+}
+
+#[tauri::command]
+pub async fn set_capture_schedule(
+    credential_state: State<'_, Arc<crate::credential_manager::CredentialManagerState>>,
+    app: AppHandle,
+    schedule: CaptureSchedule,
+) -> Result<Value, String> {
+    crate::commands::check_auth_required(&credential_state)?;
+    schedule.validate()?;
+    let raw = serde_json::to_string(&schedule)
+        .map_err(|e| format!("Failed to serialize capture schedule: {}", e))?;
+    crate::registry_config::set_string(CAPTURE_SCHEDULE_KEY, &raw)?;
+    apply_capture_schedule(&app, &schedule);
+    let paused = app
+        .state::<Arc<CaptureState>>()
+        .schedule_paused
+        .load(Ordering::SeqCst);
+    Ok(serde_json::json!({ "schedule": schedule, "paused": paused }))
+}
+
 // GPU enumeration and game-mode resource suppression.
 
-/// Enumerates hardware GPU adapters, excluding software renderers.
+/// Enumerates hardware GPU adapters, excluding software renderers, with their memory
+/// sizes, driver version and whether DirectML can run on them.
 pub fn enumerate_gpus_internal() -> Result<Vec<serde_json::Value>, String> {
     // SAFETY: DXGI returns reference-counted COM interfaces whose lifetimes are managed
     // by windows-rs; adapter indices are enumerated until Windows reports exhaustion.
@@ -2125,9 +2977,26 @@ pub fn enumerate_gpus_internal() -> Result<Vec<serde_json::Value>, String> {
                         .position(|&c| c == 0)
                         .unwrap_or(desc.Description.len())],
                 );
+                let driver_version = adapter
+                    .CheckInterfaceSupport(&IDXGIDevice::IID)
+                    .ok()
+                    .map(crate::dml_preflight::format_driver_version);
+                // DirectML needs a D3D12 device at feature level 11_0; a null output
+                // pointer only tests whether one could be created.
+                let directml_supported = D3D12CreateDevice::<_, ID3D12Device>(
+                    &adapter,
+                    D3D_FEATURE_LEVEL_11_0,
+                    std::ptr::null_mut(),
+                )
+                .is_ok();
                 gpus.push(serde_json::json!({
                     "id": i,
                     "name": name.trim().to_string(),
+                    "vendor_id": desc.VendorId,
+                    "dedicated_vram_mb": desc.DedicatedVideoMemory / (1024 * 1024),
+                    "shared_memory_mb": desc.SharedSystemMemory / (1024 * 1024),
+                    "driver_version": driver_version,
+                    "directml_supported": directml_supported,
                 }));
             }
             i += 1;
@@ -2136,11 +3005,182 @@ pub fn enumerate_gpus_internal() -> Result<Vec<serde_json::Value>, String> {
     }
 }
 
+/// Enumerates NPUs: hardware adapters DirectML can run ML workloads on that have no
+/// graphics engine. DXGI does not list these, so they come from DXCore.
+pub fn enumerate_npus_internal() -> Result<Vec<serde_json::Value>, String> {
+    // SAFETY: DXCore returns reference-counted COM interfaces managed by windows-rs;
+    // every property buffer is sized by `GetPropertySize` (or is the fixed-size type the
+    // property is documented to hold) and outlives the `GetProperty` call.
+    unsafe {
+        let factory: IDXCoreAdapterFactory = DXCoreCreateAdapterFactory()
+            .map_err(|e| format!("Failed to create DXCore factory: {:?}", e))?;
+        let list: IDXCoreAdapterList = factory
+            .CreateAdapterList(&[DXCORE_ADAPTER_ATTRIBUTE_D3D12_GENERIC_ML])
+            .map_err(|e| format!("Failed to list ML adapters: {:?}", e))?;
+
+        let mut npus = Vec::new();
+        for i in 0..list.GetAdapterCount() {
+            let Ok(adapter) = list.GetAdapter::<IDXCoreAdapter>(i) else {
+                continue;
+            };
+            if adapter.IsAttributeSupported(&DXCORE_ADAPTER_ATTRIBUTE_D3D12_GRAPHICS) {
+                continue;
+            }
+            let mut is_hardware = false;
+            let _ = adapter.GetProperty(
+                dxcore::IsHardware,
+                std::mem::size_of::<bool>(),
+                &mut is_hardware as *mut bool as *mut std::ffi::c_void,
+            );
+            if !is_hardware {
+                continue;
+            }
+
+            let name = adapter
+                .GetPropertySize(dxcore::DriverDescription)
+                .ok()
+                .and_then(|size| {
+                    let mut buf = vec![0u8; size];
+                    adapter
+                        .GetProperty(
+                            dxcore::DriverDescription,
+                            size,
+                            buf.as_mut_ptr() as *mut std::ffi::c_void,
+                        )
+                        .ok()?;
+                    let end = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+                    Some(String::from_utf8_lossy(&buf[..end]).trim().to_string())
+                })
+                .unwrap_or_default();
+            let mut driver_version: u64 = 0;
+            let driver_version = adapter
+                .GetProperty(
+                    dxcore::DriverVersion,
+                    std::mem::size_of::<u64>(),
+                    &mut driver_version as *mut u64 as *mut std::ffi::c_void,
+                )
+                .ok()
+                .map(|_| crate::dml_preflight::format_driver_version(driver_version as i64));
+            let mut hardware_id = dxcore::DXCoreHardwareID::default();
+            let _ = adapter.GetProperty(
+                dxcore::HardwareID,
+                std::mem::size_of::<dxcore::DXCoreHardwareID>(),
+                &mut hardware_id as *mut dxcore::DXCoreHardwareID as *mut std::ffi::c_void,
+            );
+            let mut dedicated_memory: u64 = 0;
+            let _ = adapter.GetProperty(
+                dxcore::DedicatedAdapterMemory,
+                std::mem::size_of::<u64>(),
+                &mut dedicated_memory as *mut u64 as *mut std::ffi::c_void,
+            );
+
+            npus.push(serde_json::json!({
+                "name": name,
+                "vendor_id": hardware_id.vendorID,
+                "execution_provider": npu_execution_provider(hardware_id.vendorID)
+                    .map(|provider| provider.ep_name()),
+                "dedicated_memory_mb": dedicated_memory / (1024 * 1024),
+                "driver_version": driver_version,
+                "directml_supported": true,
+            }));
+        }
+        Ok(npus)
+    }
+}
+
+/// Vendor ONNX Runtime execution provider used to run OCR on an NPU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NpuProvider {
+    /// Intel NPUs through OpenVINO.
+    OpenVino,
+    /// AMD Ryzen AI NPUs through Vitis AI.
+    VitisAi,
+    /// Qualcomm Hexagon NPUs through QNN.
+    Qnn,
+}
+
+impl NpuProvider {
+    /// Value stored in the `npu_provider` registry key.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NpuProvider::OpenVino => "openvino",
+            NpuProvider::VitisAi => "vitisai",
+            NpuProvider::Qnn => "qnn",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "openvino" => Some(NpuProvider::OpenVino),
+            "vitisai" => Some(NpuProvider::VitisAi),
+            "qnn" => Some(NpuProvider::Qnn),
+            _ => None,
+        }
+    }
+
+    /// Execution provider name as registered by ONNX Runtime.
+    pub fn ep_name(self) -> &'static str {
+        match self {
+            NpuProvider::OpenVino => "OpenVINOExecutionProvider",
+            NpuProvider::VitisAi => "VitisAIExecutionProvider",
+            NpuProvider::Qnn => "QNNExecutionProvider",
+        }
+    }
+}
+
+/// Execution provider for an NPU from its PCI (or ACPI, for Qualcomm) vendor id.
+pub fn npu_execution_provider(vendor_id: u32) -> Option<NpuProvider> {
+    match vendor_id {
+        0x8086 => Some(NpuProvider::OpenVino),
+        0x1022 | 0x1002 => Some(NpuProvider::VitisAi),
+        // "QCOM" as the ACPI vendor id, or Qualcomm's PCI id.
+        0x4D4F_4351 | 0x5143 => Some(NpuProvider::Qnn),
+        _ => None,
+    }
+}
+
+/// Provider the OCR engine should use for the `npu_provider` setting: an explicit
+/// provider as is, `"auto"` (or anything unknown) the provider of the first detected
+/// NPU with a known vendor.
+pub fn resolve_npu_provider(configured: &str, npus: &[serde_json::Value]) -> Option<NpuProvider> {
+    NpuProvider::parse(configured).or_else(|| {
+        npus.iter()
+            .filter_map(|npu| npu.get("vendor_id").and_then(|v| v.as_u64()))
+            .find_map(|vendor_id| npu_execution_provider(vendor_id as u32))
+    })
+}
+
+/// GPU the setup wizard should preselect for DirectML: the DirectML-capable adapter
+/// with the most dedicated VRAM, lowest index first on ties.
+pub fn recommend_gpu(gpus: &[serde_json::Value]) -> Option<u32> {
+    gpus.iter()
+        .filter(|gpu| {
+            gpu.get("directml_supported")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false)
+        })
+        .filter_map(|gpu| {
+            let id = gpu.get("id").and_then(|v| v.as_u64())? as u32;
+            let vram = gpu
+                .get("dedicated_vram_mb")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0);
+            Some((id, vram))
+        })
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+        .map(|(id, _)| id)
+}
+
 #[tauri::command]
 pub fn enumerate_gpus() -> Result<Vec<serde_json::Value>, String> {
     enumerate_gpus_internal()
 }
 
+#[tauri::command]
+pub fn enumerate_npus() -> Result<Vec<serde_json::Value>, String> {
+    enumerate_npus_internal()
+}
+
 /// Queries system-wide dedicated-memory use for one GPU via Windows PDH.
 fn query_gpu_memory_usage(device_id: u32) -> Result<f64, String> {
     // SAFETY: DXGI COM interfaces are managed by windows-rs; the PDH path is
@@ -2249,6 +3289,10 @@ pub fn start_game_mode_monitor(app: AppHandle) {
         let mut gpu_tick_counter: u32 = 0;
         const GPU_CHECK_INTERVAL_TICKS: u32 = 3; // 3 * 3s ≈ 9s (close to original 10s)
 
+        // User-listed processes and the quiet period before DML is restored
+        let mut process_scan = sysinfo::System::new();
+        let mut quiet_since: Option<std::time::Instant> = None;
+
         loop {
             tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
 
@@ -2299,6 +3343,14 @@ pub fn start_game_mode_monitor(app: AppHandle) {
                         "permanent": app_clone.state::<MonitorState>().game_mode_permanently_suppressed.load(Ordering::SeqCst),
                         "fullscreen_paused": should_pause,
                     }));
+                    crate::hooks::fire(
+                        &app_clone,
+                        if should_pause {
+                            MonitorHookEvent::GameModeOn
+                        } else {
+                            MonitorHookEvent::GameModeOff
+                        },
+                    );
                 }
             }
 
@@ -2324,22 +3376,34 @@ pub fn start_game_mode_monitor(app: AppHandle) {
                 continue;
             }
 
+            let settings = crate::game_mode::load_settings();
+            let running_process =
+                crate::game_mode::find_running_process(&mut process_scan, &settings.processes);
             let usage = match query_gpu_memory_usage(MONITOR_DEVICE_ID) {
-                Ok(u) => u,
+                Ok(u) => Some(u),
                 Err(e) => {
                     tracing::warn!("Game mode: failed to query GPU 0 memory: {}", e);
-                    continue;
+                    None
                 }
             };
+            let usage_pct = usage.unwrap_or(0.0) * 100.0;
 
             let currently_suppressed = state.game_mode_dml_suppressed.load(Ordering::SeqCst);
             tracing::debug!(
-                "Game mode: GPU 0 memory usage {:.1}%, DML suppressed: {}",
-                usage * 100.0,
+                "Game mode: GPU 0 memory usage {:?}, listed process {:?}, DML suppressed: {}",
+                usage,
+                running_process,
                 currently_suppressed
             );
 
-            if !currently_suppressed && usage >= 0.50 {
+            let action = settings.decide(
+                currently_suppressed,
+                usage,
+                running_process.is_some(),
+                &mut quiet_since,
+                std::time::Instant::now(),
+            );
+            if action == crate::game_mode::DmlAction::Suppress {
                 // 记录触发时间，检查频率限制
                 let now = std::time::Instant::now();
                 trigger_timestamps.retain(|t| now.duration_since(*t).as_secs() < 60);
@@ -2377,14 +3441,25 @@ pub fn start_game_mode_monitor(app: AppHandle) {
                     continue;
                 }
 
-                tracing::info!(
-                    "Game mode: GPU 0 memory usage {:.1}% >= 50%, suppressing DML",
-                    usage * 100.0
-                );
+                match &running_process {
+                    Some(process) => tracing::info!(
+                        "Game mode: listed process {} is running, suppressing DML",
+                        process
+                    ),
+                    None => tracing::info!(
+                        "Game mode: GPU 0 memory usage {:.1}% >= {}%, suppressing DML",
+                        usage_pct,
+                        settings.suppress_threshold_pct
+                    ),
+                }
                 state.game_mode_dml_suppressed.store(true, Ordering::SeqCst);
                 let _ = app_clone.emit(
                     "game-mode-status",
-                    serde_json::json!({"active": true, "usage": usage}),
+                    serde_json::json!({
+                        "active": true,
+                        "usage": usage,
+                        "process": running_process,
+                    }),
                 );
 
                 // 重启 Python（不带 DML）
@@ -2396,7 +3471,7 @@ pub fn start_game_mode_monitor(app: AppHandle) {
                 .await;
                 let _ =
                     start_monitor_impl(app_clone.state::<MonitorState>(), app_clone.clone()).await;
-            } else if currently_suppressed && usage <= 0.40 {
+            } else if action == crate::game_mode::DmlAction::Restore {
                 // 记录触发时间（恢复也计入）
                 let now = std::time::Instant::now();
                 trigger_timestamps.retain(|t| now.duration_since(*t).as_secs() < 60);
@@ -2423,8 +3498,10 @@ pub fn start_game_mode_monitor(app: AppHandle) {
                 }
 
                 tracing::info!(
-                    "Game mode: GPU 0 memory usage {:.1}% <= 40%, restoring DML",
-                    usage * 100.0
+                    "Game mode: GPU 0 memory usage {:.1}% <= {}% for {}s, restoring DML",
+                    usage_pct,
+                    settings.resume_threshold_pct,
+                    settings.resume_delay_secs
                 );
                 state
                     .game_mode_dml_suppressed
@@ -2559,7 +3636,54 @@ mod tests {
     }
 
     #[test]
-    fn test_monitor_recovery_crash_snapshot_uses_manual_restart_policy() {
+    fn capture_schedule_covers_daytime_and_overnight_windows() {
+        let schedule = CaptureSchedule {
+            enabled: true,
+            windows: vec![
+                CaptureScheduleWindow {
+                    days: vec![0, 1, 2, 3, 4],
+                    start: "09:00".to_string(),
+                    end: "18:00".to_string(),
+                },
+                CaptureScheduleWindow {
+                    days: vec![5],
+                    start: "22:00".to_string(),
+                    end: "02:00".to_string(),
+                },
+            ],
+        };
+        assert!(schedule.validate().is_ok());
+
+        // Monday 09:00 and 17:59 are in, 18:00 and 08:59 are out.
+        assert!(schedule.allows(0, 9 * 60));
+        assert!(schedule.allows(0, 17 * 60 + 59));
+        assert!(!schedule.allows(0, 18 * 60));
+        assert!(!schedule.allows(0, 8 * 60 + 59));
+        // Saturday night runs into Sunday morning only.
+        assert!(schedule.allows(5, 23 * 60));
+        assert!(schedule.allows(6, 60));
+        assert!(!schedule.allows(6, 3 * 60));
+        assert!(!schedule.allows(5, 60));
+
+        let disabled = CaptureSchedule {
+            enabled: false,
+            ..schedule.clone()
+        };
+        assert!(disabled.allows(6, 3 * 60));
+
+        let invalid = CaptureSchedule {
+            enabled: true,
+            windows: vec![CaptureScheduleWindow {
+                days: vec![7],
+                start: "24:00".to_string(),
+                end: "18:00".to_string(),
+            }],
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_monitor_recovery_crash_snapshot_schedules_watchdog_restart() {
         let state = MonitorState::new();
         set_monitor_recovery_running(&state);
 
@@ -2567,11 +3691,66 @@ mod tests {
             set_monitor_recovery_crashed(&state, "9".to_string(), Some("pipe failed".to_string()));
 
         assert_eq!(recovery["state"], "crashed");
-        assert_eq!(recovery["policy"], "manual_restart");
+        assert_eq!(recovery["policy"], "auto_restart");
+        assert_eq!(recovery["restart_attempts"], 1);
+        assert!(recovery["next_restart_at_ms"].as_u64().is_some());
         assert_eq!(recovery["restart_available"], true);
         assert_eq!(recovery["last_exit_code"], "9");
         assert_eq!(recovery["last_error"], "pipe failed");
         assert_eq!(recovery["crash_count"], 1);
         assert!(recovery["last_crashed_at_ms"].as_u64().unwrap_or(0) > 0);
     }
+
+    #[test]
+    fn test_watchdog_backs_off_then_falls_back_to_manual_restart() {
+        assert_eq!(watchdog_backoff(1).as_secs(), 2);
+        assert_eq!(watchdog_backoff(3).as_secs(), 8);
+        assert_eq!(watchdog_backoff(30).as_secs(), WATCHDOG_MAX_DELAY_SECS);
+
+        let state = MonitorState::new();
+        for attempt in 1..=WATCHDOG_MAX_RESTARTS {
+            set_monitor_recovery_running(&state);
+            let recovery = set_monitor_recovery_crashed(&state, "1".to_string(), None);
+            assert_eq!(recovery["policy"], "auto_restart");
+            assert_eq!(recovery["restart_attempts"], attempt);
+        }
+        let recovery = set_monitor_recovery_crashed(&state, "1".to_string(), None);
+        assert_eq!(recovery["policy"], "manual_restart");
+        assert!(recovery["next_restart_at_ms"].is_null());
+
+        reset_monitor_restart_budget(&state);
+        assert_eq!(monitor_recovery_snapshot(&state)["restart_attempts"], 0);
+    }
+
+    #[test]
+    fn recommended_gpu_prefers_directml_capable_adapter_with_most_vram() {
+        let gpus = vec![
+            serde_json::json!({ "id": 0, "dedicated_vram_mb": 128, "directml_supported": true }),
+            serde_json::json!({ "id": 1, "dedicated_vram_mb": 16384, "directml_supported": false }),
+            serde_json::json!({ "id": 2, "dedicated_vram_mb": 8192, "directml_supported": true }),
+            serde_json::json!({ "id": 3, "dedicated_vram_mb": 8192, "directml_supported": true }),
+        ];
+        assert_eq!(recommend_gpu(&gpus), Some(2));
+        assert_eq!(recommend_gpu(&gpus[1..2]), None);
+        assert_eq!(recommend_gpu(&[]), None);
+    }
+
+    #[test]
+    fn npu_provider_follows_setting_then_detected_vendor() {
+        let npus = vec![
+            serde_json::json!({ "name": "Unknown NPU", "vendor_id": 0x1234 }),
+            serde_json::json!({ "name": "Intel(R) AI Boost", "vendor_id": 0x8086 }),
+        ];
+        assert_eq!(
+            resolve_npu_provider("auto", &npus),
+            Some(NpuProvider::OpenVino)
+        );
+        assert_eq!(resolve_npu_provider("QNN", &npus), Some(NpuProvider::Qnn));
+        assert_eq!(resolve_npu_provider("auto", &npus[..1]), None);
+        assert_eq!(
+            npu_execution_provider(0x4D4F_4351).map(NpuProvider::ep_name),
+            Some("QNNExecutionProvider")
+        );
+        assert_eq!(npu_execution_provider(0x1022), Some(NpuProvider::VitisAi));
+    }
 }
@@ -1,11 +1,22 @@
 //! Authenticated framed IPC helpers for requests sent to the Python monitor.
+//!
+//! Requests share one multiplexed connection: each frame carries an
+//! `_ipc_request_id`, the monitor answers with `{"_ipc_request_id", "result"}` in
+//! completion order, and a reader task routes every response back to its caller.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 
 use rand::Rng;
 use serde_json::Value;
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::{mpsc, oneshot};
 
 const IPC_PROTOCOL_VERSION: u32 = 2;
 const IPC_MAX_MESSAGE_BYTES: usize = 16 * 1024 * 1024;
+/// Requests allowed to wait for the writer before new ones are rejected.
+const IPC_QUEUE_CAPACITY: usize = 64;
 
 pub(crate) fn generate_random_pipe_name() -> String {
     let mut rng = rand::thread_rng();
@@ -90,30 +101,258 @@ where
     ))
 }
 
-pub(crate) async fn send_ipc_request_on_client<C>(
-    client: &mut C,
-    req: &Value,
-    ipc_timeout_secs: u64,
-) -> Result<Value, String>
-where
-    C: AsyncRead + AsyncWrite + Unpin,
-{
-    let req_bytes = serde_json::to_vec(req).map_err(|e| format!("Serialize error: {}", e))?;
-    if let Err(e) = write_ipc_frame(client, &req_bytes).await {
-        return Err(e);
+type PendingReplies = Arc<Mutex<HashMap<u64, oneshot::Sender<Result<Value, String>>>>>;
+
+struct QueuedRequest {
+    id: u64,
+    body: Vec<u8>,
+}
+
+/// One persistent connection to the monitor carrying concurrent requests.
+pub(crate) struct MultiplexedIpcClient {
+    pipe_name: String,
+    queue: mpsc::Sender<QueuedRequest>,
+    pending: PendingReplies,
+    next_id: AtomicU64,
+    closed: Arc<AtomicBool>,
+    writer_task: tauri::async_runtime::JoinHandle<()>,
+    reader_task: tauri::async_runtime::JoinHandle<()>,
+}
+
+/// Fails every waiting request, e.g. after the connection broke.
+fn fail_pending(pending: &PendingReplies, closed: &AtomicBool, error: &str) {
+    closed.store(true, Ordering::SeqCst);
+    let waiting: Vec<_> = {
+        let mut guard = pending.lock().unwrap_or_else(|e| e.into_inner());
+        guard.drain().collect()
+    };
+    for (_, reply) in waiting {
+        let _ = reply.send(Err(error.to_string()));
     }
+}
 
-    match tokio::time::timeout(
-        std::time::Duration::from_secs(ipc_timeout_secs),
-        read_ipc_frame(client),
-    )
-    .await
+impl MultiplexedIpcClient {
+    pub(crate) fn new<S>(pipe_name: &str, stream: S) -> Self
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
     {
-        Ok(Ok(buf)) => parse_ipc_response(&buf),
-        Ok(Err(e)) => Err(e),
-        Err(_) => {
-            let e = format!("IPC response timed out after {}s", ipc_timeout_secs);
-            Err(e)
+        let (mut reader, mut writer) = tokio::io::split(stream);
+        let (queue, mut requests) = mpsc::channel::<QueuedRequest>(IPC_QUEUE_CAPACITY);
+        let pending: PendingReplies = Arc::new(Mutex::new(HashMap::new()));
+        let closed = Arc::new(AtomicBool::new(false));
+
+        let writer_task = {
+            let pending = pending.clone();
+            let closed = closed.clone();
+            tauri::async_runtime::spawn(async move {
+                while let Some(request) = requests.recv().await {
+                    if let Err(e) = write_ipc_frame(&mut writer, &request.body).await {
+                        tracing::warn!(
+                            "[DIAG:IPC] write failed request_id={} error={}",
+                            request.id,
+                            e
+                        );
+                        fail_pending(&pending, &closed, &format!("IPC connection closed: {}", e));
+                        break;
+                    }
+                }
+            })
+        };
+
+        let reader_task = {
+            let pending = pending.clone();
+            let closed = closed.clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    let frame = match read_ipc_frame(&mut reader).await {
+                        Ok(frame) => frame,
+                        Err(e) => {
+                            fail_pending(
+                                &pending,
+                                &closed,
+                                &format!("IPC connection closed: {}", e),
+                            );
+                            break;
+                        }
+                    };
+                    let (id, result) = match parse_ipc_response(&frame).and_then(unwrap_envelope) {
+                        Ok(parsed) => parsed,
+                        // Rejections sent before the connection is multiplexed (busy
+                        // server, unauthorized client) carry no request id.
+                        Err(e) => {
+                            fail_pending(&pending, &closed, &e);
+                            break;
+                        }
+                    };
+                    let reply = {
+                        let mut guard = pending.lock().unwrap_or_else(|e| e.into_inner());
+                        guard.remove(&id)
+                    };
+                    match reply {
+                        Some(reply) => {
+                            let _ = reply.send(Ok(result));
+                        }
+                        // The caller already timed out.
+                        None => {
+                            tracing::debug!("[DIAG:IPC] late response dropped request_id={}", id)
+                        }
+                    }
+                }
+            })
+        };
+
+        Self {
+            pipe_name: pipe_name.to_string(),
+            queue,
+            pending,
+            next_id: AtomicU64::new(1),
+            closed,
+            writer_task,
+            reader_task,
+        }
+    }
+
+    pub(crate) fn pipe_name(&self) -> &str {
+        &self.pipe_name
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst) || self.queue.is_closed()
+    }
+
+    fn forget(&self, id: u64) {
+        let mut guard = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        guard.remove(&id);
+    }
+
+    /// Queues `req` and waits up to `timeout_secs` for its response. A timed-out
+    /// request is forgotten, so its late response is dropped by the reader.
+    pub(crate) async fn request(&self, mut req: Value, timeout_secs: u64) -> Result<Value, String> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        if let Some(obj) = req.as_object_mut() {
+            obj.insert("_ipc_multiplex".to_string(), Value::Bool(true));
+            obj.insert("_ipc_request_id".to_string(), Value::Number(id.into()));
+        }
+        let body = serde_json::to_vec(&req).map_err(|e| format!("Serialize error: {}", e))?;
+        if body.len() > IPC_MAX_MESSAGE_BYTES {
+            return Err(format!(
+                "IPC request too large: {} bytes (max {})",
+                body.len(),
+                IPC_MAX_MESSAGE_BYTES
+            ));
+        }
+
+        let (reply, response) = oneshot::channel();
+        {
+            let mut guard = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            guard.insert(id, reply);
+        }
+        // The reader marks the client closed before draining, so checking after the
+        // insert never leaves this request waiting on a dead connection.
+        if self.closed.load(Ordering::SeqCst) {
+            self.forget(id);
+            return Err("IPC connection closed".to_string());
+        }
+        if let Err(e) = self.queue.try_send(QueuedRequest { id, body }) {
+            self.forget(id);
+            return Err(match e {
+                mpsc::error::TrySendError::Full(_) => format!(
+                    "IPC request queue is full ({} requests waiting)",
+                    IPC_QUEUE_CAPACITY
+                ),
+                mpsc::error::TrySendError::Closed(_) => "IPC connection closed".to_string(),
+            });
         }
+
+        match tokio::time::timeout(std::time::Duration::from_secs(timeout_secs), response).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err("IPC connection closed before response".to_string()),
+            Err(_) => {
+                self.forget(id);
+                Err(format!("IPC response timed out after {}s", timeout_secs))
+            }
+        }
+    }
+}
+
+impl Drop for MultiplexedIpcClient {
+    fn drop(&mut self) {
+        self.writer_task.abort();
+        self.reader_task.abort();
+    }
+}
+
+/// Splits a multiplexed response into its request id and result.
+fn unwrap_envelope(mut envelope: Value) -> Result<(u64, Value), String> {
+    let id = envelope
+        .get("_ipc_request_id")
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| match envelope.get("error").and_then(|v| v.as_str()) {
+            Some(error) => error.to_string(),
+            None => format!("IPC response without request id: {}", envelope),
+        })?;
+    let result = envelope
+        .get_mut("result")
+        .map(Value::take)
+        .unwrap_or(Value::Null);
+    Ok((id, result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn multiplexed_responses_reach_their_callers_out_of_order() {
+        let (client_end, mut server_end) = tokio::io::duplex(64 * 1024);
+        let client = MultiplexedIpcClient::new("test_pipe", client_end);
+
+        let server = tokio::spawn(async move {
+            let mut ids = Vec::new();
+            for _ in 0..2 {
+                let frame = read_ipc_frame(&mut server_end).await.unwrap();
+                let req: Value = serde_json::from_slice(&frame).unwrap();
+                assert_eq!(req["_ipc_multiplex"], true);
+                ids.push((req["_ipc_request_id"].clone(), req["command"].clone()));
+            }
+            // Answer the later request first.
+            for (id, command) in ids.into_iter().rev() {
+                let resp = serde_json::json!({"_ipc_request_id": id, "result": {"ok": command}});
+                write_ipc_frame(&mut server_end, &serde_json::to_vec(&resp).unwrap())
+                    .await
+                    .unwrap();
+            }
+            server_end
+        });
+
+        let (search, status) = tokio::join!(
+            client.request(serde_json::json!({"command": "search"}), 5),
+            client.request(serde_json::json!({"command": "status"}), 5),
+        );
+        assert_eq!(search.unwrap()["ok"], "search");
+        assert_eq!(status.unwrap()["ok"], "status");
+        let _server_end = server.await.unwrap();
+        assert!(!client.is_closed());
+    }
+
+    #[tokio::test]
+    async fn multiplexed_request_times_out_and_broken_pipe_fails_waiters() {
+        let (client_end, server_end) = tokio::io::duplex(64 * 1024);
+        let client = MultiplexedIpcClient::new("test_pipe", client_end);
+
+        let err = client
+            .request(serde_json::json!({"command": "status"}), 1)
+            .await
+            .unwrap_err();
+        assert!(err.contains("timed out"));
+        assert!(client.pending.lock().unwrap().is_empty());
+
+        drop(server_end);
+        let err = client
+            .request(serde_json::json!({"command": "status"}), 5)
+            .await
+            .unwrap_err();
+        assert!(err.contains("closed"));
+        assert!(client.is_closed());
     }
 }
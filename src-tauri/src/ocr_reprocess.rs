@@ -0,0 +1,176 @@
+//! On-demand OCR re-runs for stored screenshots.
+//!
+//! `reprocess_ocr` decrypts each stored image, runs it through the same ML OCR worker
+//! used during capture and swaps the screenshot's OCR rows and search postings for
+//! the new results. Useful after upgrading the OCR model or enabling a language pack.
+//! Screenshots are processed one at a time; a failure is reported and the rest of the
+//! batch continues.
+
+use std::sync::Arc;
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::capture::{convert_ml_ocr_blocks, OcrRouteConfig};
+use crate::commands::{check_auth_required, check_writable};
+use crate::credential_manager::CredentialManagerState;
+use crate::ml_runtime::MlRuntimeState;
+use crate::storage::StorageState;
+
+/// Screenshots reprocessed per call unless `limit` says otherwise.
+const DEFAULT_REPROCESS_LIMIT: u32 = 200;
+const MAX_REPROCESS_LIMIT: u32 = 2_000;
+const REPROCESS_TIMEOUT_SECS: u64 = 120;
+/// Per-screenshot errors kept in the report.
+const MAX_REPORTED_ERRORS: usize = 20;
+
+/// Outcome of one `reprocess_ocr` call.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OcrReprocessReport {
+    pub screenshots: usize,
+    pub reprocessed: usize,
+    pub failed: usize,
+    pub ocr_rows_removed: usize,
+    pub ocr_rows_added: usize,
+    /// `"<screenshot_id>: <error>"` for the first failures
+    pub errors: Vec<String>,
+}
+
+/// Decrypts a stored screenshot and runs it through the OCR worker.
+async fn reprocess_one(
+    app: &AppHandle,
+    storage: &Arc<StorageState>,
+    ml_state: &Arc<MlRuntimeState>,
+    screenshot_id: i64,
+    image_path: String,
+    route: OcrRouteConfig,
+) -> Result<(usize, usize), String> {
+    let reader = storage.clone();
+    let rgb_image = tokio::task::spawn_blocking(move || {
+        let (bytes, _) = reader.read_image_bytes(&image_path)?;
+        image::load_from_memory(&bytes)
+            .map(|image| image.to_rgb8())
+            .map_err(|e| format!("Failed to decode screenshot image: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))??;
+
+    let started = std::time::Instant::now();
+    let output = ml_state
+        .run_ocr(
+            app.clone(),
+            Arc::new(rgb_image),
+            std::time::Duration::from_secs(REPROCESS_TIMEOUT_SECS),
+            route.use_directml_beta,
+        )
+        .await?;
+    let ocr_results = convert_ml_ocr_blocks(output.blocks)?;
+
+    let writer = storage.clone();
+    let counts = tokio::task::spawn_blocking(move || {
+        writer.replace_ocr_results(screenshot_id, &ocr_results)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))??;
+
+    if let Err(error) = storage.set_ocr_status(
+        screenshot_id,
+        "completed",
+        Some("rust"),
+        Some("ppocrv5-ch-mobile"),
+        Some(if route.use_directml_beta {
+            "directml_beta"
+        } else {
+            "cpu"
+        }),
+        None,
+        Some(started.elapsed().as_secs_f64() * 1000.0),
+    ) {
+        tracing::warn!(
+            "OCR was reprocessed but status update failed screenshot_id={}: {}",
+            screenshot_id,
+            error
+        );
+    }
+    Ok(counts)
+}
+
+/// Re-runs OCR for one screenshot, or for the screenshots captured between
+/// millisecond timestamps `start_time` and `end_time`, replacing their OCR rows.
+///
+/// Authentication: required. Rejected in read-only mode. `limit` caps the number of
+/// screenshots (default 200, max 2000), oldest first. Returns an `OcrReprocessReport`.
+/// Frontend: `lib/monitor_api.js`.
+#[tauri::command]
+pub async fn reprocess_ocr(
+    app: AppHandle,
+    credential_state: tauri::State<'_, Arc<CredentialManagerState>>,
+    state: tauri::State<'_, Arc<StorageState>>,
+    ml_state: tauri::State<'_, Arc<MlRuntimeState>>,
+    screenshot_id: Option<i64>,
+    start_time: Option<f64>,
+    end_time: Option<f64>,
+    limit: Option<u32>,
+) -> Result<OcrReprocessReport, String> {
+    check_auth_required(&credential_state)?;
+    check_writable(&state)?;
+
+    let to_secs = |ts: f64| {
+        if ts > 10_000_000_000.0 {
+            ts / 1000.0
+        } else {
+            ts
+        }
+    };
+    let (start_ts, end_ts) = match (screenshot_id, start_time, end_time) {
+        (Some(_), _, _) => (0.0, 0.0),
+        (None, Some(start), Some(end)) => (to_secs(start), to_secs(end)),
+        _ => return Err("Either screenshot_id or start_time and end_time is required".to_string()),
+    };
+    let limit = limit
+        .unwrap_or(DEFAULT_REPROCESS_LIMIT)
+        .clamp(1, MAX_REPROCESS_LIMIT);
+
+    let storage = state.inner().clone();
+    let targets = {
+        let storage = storage.clone();
+        tokio::task::spawn_blocking(move || {
+            storage.list_ocr_reprocess_targets(screenshot_id, start_ts, end_ts, limit as i64)
+        })
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))??
+    };
+    if let (Some(id), true) = (screenshot_id, targets.is_empty()) {
+        return Err(format!("Screenshot {} not found", id));
+    }
+
+    let ml_state = ml_state.inner().clone();
+    let route = OcrRouteConfig::from_registry();
+    let mut report = OcrReprocessReport {
+        screenshots: targets.len(),
+        ..Default::default()
+    };
+    for (id, image_path) in targets {
+        match reprocess_one(&app, &storage, &ml_state, id, image_path, route).await {
+            Ok((removed, added)) => {
+                report.reprocessed += 1;
+                report.ocr_rows_removed += removed;
+                report.ocr_rows_added += added;
+            }
+            Err(error) => {
+                tracing::warn!("Failed to reprocess OCR for screenshot {}: {}", id, error);
+                report.failed += 1;
+                if report.errors.len() < MAX_REPORTED_ERRORS {
+                    report.errors.push(format!("{}: {}", id, error));
+                }
+            }
+        }
+    }
+    tracing::info!(
+        "[ML:OCR] Reprocessed {} of {} screenshots ({} failed)",
+        report.reprocessed,
+        report.screenshots,
+        report.failed
+    );
+    Ok(report)
+}
@@ -106,7 +106,7 @@ fn probe_python_command(cmd: &str, args: &[&str]) -> Option<(String, String)> {
     Some((version, executable))
 }
 
-fn find_required_python_executable() -> Option<String> {
+pub(crate) fn find_required_python_executable() -> Option<String> {
     for cmd in ["python3", "python", "py"] {
         let args = if cmd == "py" { vec!["-3.12"] } else { vec![] };
         if let Some((version, executable)) = probe_python_command(cmd, &args) {
@@ -1168,7 +1168,7 @@ pub async fn install_spacy_model(
     install_spacy_model_impl(app, model_name).await
 }
 
-fn venv_python_exists(app: &AppHandle) -> bool {
+pub(crate) fn venv_python_exists(app: &AppHandle) -> bool {
     get_venv_dir(app)
         .join("Scripts")
         .join("python.exe")
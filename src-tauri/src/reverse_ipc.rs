@@ -10,13 +10,14 @@ use crate::capture::CaptureState;
 use crate::capture::OcrImageCache;
 use crate::monitor::MonitorState;
 use crate::reverse_ipc_protocol::{
-    read_ipc_frame, write_ipc_binary_frame, write_ipc_frame, StorageResponse,
+    read_ipc_frame, read_ipc_frame_negotiated, write_ipc_binary_frame, write_ipc_frame,
+    write_ipc_frame_negotiated, IpcCodec, StorageResponse,
 };
 #[cfg(test)]
 use crate::storage::ScreenshotRecord;
 use crate::storage::{
-    BackgroundReadError, BackgroundScreenshotSummary, OcrResultInput, SaveScreenshotRequest,
-    StorageState,
+    BackgroundReadError, BackgroundScreenshotSummary, OcrResultInput, SaveAudioTranscriptRequest,
+    SaveScreenshotRequest, StorageState,
 };
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
@@ -88,6 +89,9 @@ pub enum StorageCommand {
     },
     #[serde(rename = "record_ocr_postprocess_retry")]
     RecordOcrPostprocessRetry { screenshot_id: i64, error: String },
+    /// Save the transcript of one recorded audio chunk. Replays are ignored.
+    #[serde(rename = "save_audio_transcript")]
+    SaveAudioTranscript(SaveAudioTranscriptRequest),
 }
 
 // Use OcrResultInput from crate::storage to keep a single canonical type
@@ -495,8 +499,10 @@ async fn handle_client(
     let mut keepalive = true;
     let mut requests_handled: u64 = 0;
     let mut last_seq_no: Option<u64> = None;
+    let mut codec: Option<IpcCodec> = None;
+    let mut codec_negotiated = false;
     while keepalive {
-        let buf = match read_ipc_frame(&mut server).await {
+        let buf = match read_ipc_frame_negotiated(&mut server, codec).await {
             Ok(result) => result,
             Err(e) => {
                 if keepalive && requests_handled > 0 && e == "overall_timeout" {
@@ -546,7 +552,22 @@ async fn handle_client(
             .unwrap_or(false);
         requests_handled = requests_handled.saturating_add(1);
 
-        if req.get("command").and_then(|c| c.as_str()) == Some("get_temp_image") {
+        // Compression handshake: the first JSON-answered request carrying `_ipc_codecs`
+        // fixes the codec for the rest of this connection; its response is sent plain.
+        let is_temp_image = req.get("command").and_then(|c| c.as_str()) == Some("get_temp_image");
+        let mut negotiated_now = None;
+        if !codec_negotiated && !is_temp_image {
+            if let Some(offered) = req.get("_ipc_codecs") {
+                codec_negotiated = true;
+                negotiated_now = Some(IpcCodec::negotiate(offered));
+                tracing::debug!(
+                    "[REVERSE_IPC] negotiated frame compression: {}",
+                    negotiated_now.flatten().map(IpcCodec::name).unwrap_or("none")
+                );
+            }
+        }
+
+        if is_temp_image {
             match get_temp_image_bytes(&req, &storage, &ocr_cache) {
                 Ok((image_bytes, mime_type)) => {
                     let metadata = StorageResponse::success(serde_json::json!({
@@ -577,12 +598,29 @@ async fn handle_client(
             let response = process_request(&req, &storage, &app_handle);
 
             // 发送响应
-            let response_bytes = serde_json::to_vec(&response).unwrap_or_default();
-            if let Err(e) = write_ipc_frame(&mut server, &response_bytes).await {
+            let response_bytes = match negotiated_now {
+                Some(chosen) => {
+                    let mut value = serde_json::to_value(&response).unwrap_or_default();
+                    if let Some(obj) = value.as_object_mut() {
+                        obj.insert(
+                            "_ipc_codec".to_string(),
+                            chosen
+                                .map(|c| serde_json::Value::from(c.name()))
+                                .unwrap_or_default(),
+                        );
+                    }
+                    serde_json::to_vec(&value).unwrap_or_default()
+                }
+                None => serde_json::to_vec(&response).unwrap_or_default(),
+            };
+            if let Err(e) = write_ipc_frame_negotiated(&mut server, &response_bytes, codec).await {
                 tracing::error!("Write error: {}", e);
                 return;
             }
         }
+        if let Some(chosen) = negotiated_now {
+            codec = chosen;
+        }
 
         if keepalive && requests_handled % 100 == 0 {
             tracing::debug!(
@@ -677,6 +715,11 @@ fn process_request(
             "session_valid": storage.is_session_valid()
         })),
 
+        "heartbeat" => StorageResponse::success(serde_json::json!({
+            "ts_ms": chrono::Utc::now().timestamp_millis(),
+            "db_responsive": storage.probe_db_responsive(std::time::Duration::from_secs(2))
+        })),
+
         "screenshot_exists" => {
             let image_hash = req.get("image_hash").and_then(|h| h.as_str()).unwrap_or("");
 
@@ -812,7 +855,17 @@ fn process_request(
                 category.as_deref(),
                 category_confidence,
             ) {
-                Ok(result) => StorageResponse::success(serde_json::to_value(result).unwrap()),
+                Ok(result) => {
+                    if let Some(results) = ocr_results.as_deref() {
+                        crate::saved_search_alerts::notify_saved_search_matches(
+                            app_handle,
+                            storage,
+                            screenshot_id,
+                            results,
+                        );
+                    }
+                    StorageResponse::success(serde_json::to_value(result).unwrap())
+                }
                 Err(e) => StorageResponse::error(&e),
             }
         }
@@ -1067,6 +1120,25 @@ fn process_request(
             }
         }
 
+        // ============ Audio transcription ============
+        "save_audio_transcript" => {
+            let request = match serde_json::from_value::<SaveAudioTranscriptRequest>(req.clone())
+            {
+                Ok(r) => r,
+                Err(e) => return StorageResponse::error(&format!("Invalid request: {}", e)),
+            };
+            match storage.add_audio_transcript(
+                request.started_at,
+                request.ended_at,
+                &request.source,
+                request.language.as_deref(),
+                request.segments,
+            ) {
+                Ok(id) => StorageResponse::success(serde_json::json!({ "id": id })),
+                Err(e) => StorageResponse::error(&e),
+            }
+        }
+
         _ => StorageResponse::error(&format!("Unknown command: {}", command)),
     };
 
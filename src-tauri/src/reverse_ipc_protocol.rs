@@ -8,7 +8,133 @@ pub const IPC_PROTOCOL_VERSION: u32 = 2;
 pub const IPC_MAX_MESSAGE_BYTES: usize = 16 * 1024 * 1024;
 pub const IPC_MAX_BINARY_BYTES: usize = 64 * 1024 * 1024;
 
+/// Set in the length prefix when the frame body is compressed.
+pub const IPC_COMPRESSED_FRAME_FLAG: u32 = 0x8000_0000;
+/// Bodies smaller than this are always sent uncompressed.
+pub const IPC_COMPRESSION_MIN_BYTES: usize = 64 * 1024;
+/// Compressed body header: codec id byte + uncompressed length (u32 LE).
+const COMPRESSED_HEADER_BYTES: usize = 5;
+
+/// Frame compression codec negotiated per connection.
+///
+/// The client lists the codecs it supports in `_ipc_codecs` on its first request;
+/// the server answers with the chosen one in `_ipc_codec`, after which either side
+/// may send frames with [`IPC_COMPRESSED_FRAME_FLAG`] set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpcCodec {
+    Zstd,
+    Lz4,
+}
+
+impl IpcCodec {
+    /// Server preference order.
+    const PREFERENCE: [IpcCodec; 2] = [IpcCodec::Zstd, IpcCodec::Lz4];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            IpcCodec::Zstd => "zstd",
+            IpcCodec::Lz4 => "lz4",
+        }
+    }
+
+    fn id(self) -> u8 {
+        match self {
+            IpcCodec::Zstd => 1,
+            IpcCodec::Lz4 => 2,
+        }
+    }
+
+    fn from_id(id: u8) -> Option<Self> {
+        match id {
+            1 => Some(IpcCodec::Zstd),
+            2 => Some(IpcCodec::Lz4),
+            _ => None,
+        }
+    }
+
+    /// Picks the preferred codec among those offered by the client.
+    pub fn negotiate(offered: &serde_json::Value) -> Option<Self> {
+        let offered: Vec<&str> = offered
+            .as_array()?
+            .iter()
+            .filter_map(|v| v.as_str())
+            .collect();
+        Self::PREFERENCE
+            .into_iter()
+            .find(|codec| offered.contains(&codec.name()))
+    }
+}
+
+/// Compresses `body` when it is large enough and compression actually helps.
+/// Returns the length prefix and the bytes to write after it.
+pub fn encode_frame_body(body: &[u8], codec: Option<IpcCodec>) -> (u32, Vec<u8>) {
+    let plain = || (body.len() as u32, body.to_vec());
+    let Some(codec) = codec else {
+        return plain();
+    };
+    if body.len() < IPC_COMPRESSION_MIN_BYTES {
+        return plain();
+    }
+    let compressed = match codec {
+        IpcCodec::Zstd => match zstd::bulk::compress(body, 1) {
+            Ok(data) => data,
+            Err(e) => {
+                tracing::warn!("[REVERSE_IPC] zstd compression failed: {}", e);
+                return plain();
+            }
+        },
+        IpcCodec::Lz4 => lz4_flex::block::compress(body),
+    };
+    if compressed.len() + COMPRESSED_HEADER_BYTES >= body.len() {
+        return plain();
+    }
+    let mut out = Vec::with_capacity(COMPRESSED_HEADER_BYTES + compressed.len());
+    out.push(codec.id());
+    out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    out.extend_from_slice(&compressed);
+    (out.len() as u32 | IPC_COMPRESSED_FRAME_FLAG, out)
+}
+
+/// Decompresses a frame body written with [`IPC_COMPRESSED_FRAME_FLAG`].
+pub fn decode_compressed_frame_body(body: &[u8]) -> Result<Vec<u8>, String> {
+    if body.len() < COMPRESSED_HEADER_BYTES {
+        return Err(format!("Compressed frame too short: {} bytes", body.len()));
+    }
+    let codec = IpcCodec::from_id(body[0])
+        .ok_or_else(|| format!("Unknown IPC compression codec: {}", body[0]))?;
+    let raw_len = u32::from_le_bytes([body[1], body[2], body[3], body[4]]) as usize;
+    if raw_len == 0 || raw_len > IPC_MAX_MESSAGE_BYTES {
+        return Err(format!(
+            "Invalid decompressed frame length: {} (max {})",
+            raw_len, IPC_MAX_MESSAGE_BYTES
+        ));
+    }
+    let payload = &body[COMPRESSED_HEADER_BYTES..];
+    let decoded = match codec {
+        IpcCodec::Zstd => zstd::bulk::decompress(payload, raw_len)
+            .map_err(|e| format!("zstd decompression failed: {}", e))?,
+        IpcCodec::Lz4 => lz4_flex::block::decompress(payload, raw_len)
+            .map_err(|e| format!("lz4 decompression failed: {}", e))?,
+    };
+    if decoded.len() != raw_len {
+        return Err(format!(
+            "Decompressed frame length mismatch: expected {}, got {}",
+            raw_len,
+            decoded.len()
+        ));
+    }
+    Ok(decoded)
+}
+
 pub async fn read_ipc_frame(server: &mut NamedPipeServer) -> Result<Vec<u8>, String> {
+    read_ipc_frame_negotiated(server, None).await
+}
+
+/// Reads one frame, accepting compressed bodies once `codec` has been negotiated.
+pub async fn read_ipc_frame_negotiated(
+    server: &mut NamedPipeServer,
+    codec: Option<IpcCodec>,
+) -> Result<Vec<u8>, String> {
     let mut first = [0u8; 4];
     match tokio::time::timeout(
         tokio::time::Duration::from_secs(30),
@@ -22,7 +148,12 @@ pub async fn read_ipc_frame(server: &mut NamedPipeServer) -> Result<Vec<u8>, Str
         Err(_) => return Err("overall_timeout".to_string()),
     }
 
-    let len = u32::from_le_bytes(first) as usize;
+    let prefix = u32::from_le_bytes(first);
+    let compressed = prefix & IPC_COMPRESSED_FRAME_FLAG != 0;
+    if compressed && codec.is_none() {
+        return Err("Compressed frame received before compression was negotiated".to_string());
+    }
+    let len = (prefix & !IPC_COMPRESSED_FRAME_FLAG) as usize;
     if len > 0 && len <= IPC_MAX_MESSAGE_BYTES {
         let mut body = vec![0u8; len];
         tokio::time::timeout(
@@ -32,6 +163,9 @@ pub async fn read_ipc_frame(server: &mut NamedPipeServer) -> Result<Vec<u8>, Str
         .await
         .map_err(|_| "overall_timeout".to_string())?
         .map_err(|e| format!("read_body_error:{}", e))?;
+        if compressed {
+            return decode_compressed_frame_body(&body);
+        }
         return Ok(body);
     }
 
@@ -60,6 +194,30 @@ pub async fn write_ipc_frame(server: &mut NamedPipeServer, body: &[u8]) -> Resul
         .map_err(|e| format!("write_body_error:{}", e))
 }
 
+/// Writes one frame, compressing large bodies when a codec has been negotiated.
+pub async fn write_ipc_frame_negotiated(
+    server: &mut NamedPipeServer,
+    body: &[u8],
+    codec: Option<IpcCodec>,
+) -> Result<(), String> {
+    if body.len() > IPC_MAX_MESSAGE_BYTES {
+        return Err(format!(
+            "Response too large: {} bytes (max {})",
+            body.len(),
+            IPC_MAX_MESSAGE_BYTES
+        ));
+    }
+    let (prefix, encoded) = encode_frame_body(body, codec);
+    server
+        .write_all(&prefix.to_le_bytes())
+        .await
+        .map_err(|e| format!("write_prefix_error:{}", e))?;
+    server
+        .write_all(&encoded)
+        .await
+        .map_err(|e| format!("write_body_error:{}", e))
+}
+
 pub async fn write_ipc_binary_frame(
     server: &mut NamedPipeServer,
     body: &[u8],
@@ -109,3 +267,64 @@ impl StorageResponse {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_body() -> Vec<u8> {
+        let row = br#"{"text":"compressible OCR row","confidence":0.98,"box":[[1,2],[3,4]]},"#;
+        row.iter()
+            .copied()
+            .cycle()
+            .take(IPC_COMPRESSION_MIN_BYTES * 2)
+            .collect()
+    }
+
+    #[test]
+    fn compressed_frames_round_trip_for_each_codec() {
+        let body = sample_body();
+        for codec in [IpcCodec::Zstd, IpcCodec::Lz4] {
+            let (prefix, encoded) = encode_frame_body(&body, Some(codec));
+            assert_ne!(prefix & IPC_COMPRESSED_FRAME_FLAG, 0, "{:?}", codec);
+            assert_eq!(
+                (prefix & !IPC_COMPRESSED_FRAME_FLAG) as usize,
+                encoded.len()
+            );
+            assert!(encoded.len() < body.len());
+            assert_eq!(decode_compressed_frame_body(&encoded).unwrap(), body);
+        }
+    }
+
+    #[test]
+    fn small_or_unnegotiated_bodies_stay_plain() {
+        let body = sample_body();
+        let (prefix, encoded) = encode_frame_body(&body, None);
+        assert_eq!(prefix as usize, body.len());
+        assert_eq!(encoded, body);
+
+        let (prefix, _) = encode_frame_body(b"{}", Some(IpcCodec::Zstd));
+        assert_eq!(prefix, 2);
+    }
+
+    #[test]
+    fn negotiation_prefers_zstd_and_ignores_unknown_codecs() {
+        let both = serde_json::json!(["lz4", "zstd"]);
+        assert_eq!(IpcCodec::negotiate(&both), Some(IpcCodec::Zstd));
+        let lz4 = serde_json::json!(["brotli", "lz4"]);
+        assert_eq!(IpcCodec::negotiate(&lz4), Some(IpcCodec::Lz4));
+        assert_eq!(IpcCodec::negotiate(&serde_json::json!(["gzip"])), None);
+        assert_eq!(IpcCodec::negotiate(&serde_json::json!("zstd")), None);
+    }
+
+    #[test]
+    fn rejects_oversized_or_unknown_compressed_headers() {
+        let mut header = vec![9u8];
+        header.extend_from_slice(&16u32.to_le_bytes());
+        assert!(decode_compressed_frame_body(&header).is_err());
+
+        let mut oversized = vec![IpcCodec::Lz4.id()];
+        oversized.extend_from_slice(&((IPC_MAX_MESSAGE_BYTES + 1) as u32).to_le_bytes());
+        assert!(decode_compressed_frame_body(&oversized).is_err());
+    }
+}
@@ -0,0 +1,41 @@
+//! Alerts for saved searches matched by newly committed screenshots.
+//!
+//! Capture and reverse IPC call `notify_saved_search_matches` after committing OCR
+//! text. Matching and cooldowns live in `storage/saved_searches.rs`; this module only
+//! emits the `saved-search-matched` event and shows one native notification per match.
+
+use crate::storage::{OcrResultInput, StorageState};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_notification::NotificationExt;
+
+pub(crate) fn notify_saved_search_matches(
+    app: &AppHandle,
+    storage: &StorageState,
+    screenshot_id: i64,
+    ocr_results: &[OcrResultInput],
+) {
+    let matches = storage.match_saved_searches(screenshot_id, ocr_results);
+    if matches.is_empty() {
+        return;
+    }
+
+    for saved_match in &matches {
+        let _ = app.emit("saved-search-matched", saved_match);
+    }
+
+    let language =
+        crate::registry_config::get_string("language").unwrap_or_else(|| "zh-CN".to_string());
+    let title = crate::i18n::t(&language, "notifications.saved_search_match.title");
+    let body = crate::i18n::t(&language, "notifications.saved_search_match.body");
+    for saved_match in &matches {
+        if let Err(error) = app
+            .notification()
+            .builder()
+            .title(&title)
+            .body(body.replace("{{name}}", &saved_match.name))
+            .show()
+        {
+            tracing::warn!("Failed to show saved search notification: {}", error);
+        }
+    }
+}
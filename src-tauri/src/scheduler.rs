@@ -0,0 +1,492 @@
+//! Cron-like background job scheduler.
+//!
+//! Periodic maintenance (retention, digests, re-indexing, backups, sync and tiering)
+//! registers here instead of spawning its own `tokio` loop. Schedules and
+//! last-run results live in the `scheduled_jobs` table so they survive restarts and
+//! can be inspected or toggled from the settings UI.
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::monitor::{self, MonitorState};
+use crate::storage::{DigestPrivacyLevel, ScheduledJob, StorageState};
+
+/// How often the scheduler checks for due jobs.
+const TICK_INTERVAL: Duration = Duration::from_secs(15);
+/// Minimum time between two idle database maintenance runs. The job itself is checked
+/// hourly so it can wait for an idle window instead of running at a fixed time.
+const DATABASE_MAINTENANCE_MIN_INTERVAL_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// Outcome of a job run: `Ok(Some(summary))` when work was done, `Ok(None)` when
+/// there was nothing to do, or an error message.
+pub type JobResult = Result<Option<String>, String>;
+type JobFuture = Pin<Box<dyn Future<Output = JobResult> + Send>>;
+
+/// A job known to the scheduler, with the defaults used on first registration.
+pub struct JobSpec {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub default_enabled: bool,
+    pub default_interval_secs: i64,
+    run: fn(AppHandle) -> JobFuture,
+}
+
+/// Built-in jobs. New periodic work should be added here.
+pub static JOBS: &[JobSpec] = &[
+    JobSpec {
+        name: "storage_policy",
+        description: "Apply retention period and storage limit policy",
+        default_enabled: true,
+        default_interval_secs: 60,
+        run: |app| Box::pin(run_storage_policy(app)),
+    },
+    JobSpec {
+        name: "weekly_digest",
+        description: "Write the weekly HTML digest to the digests folder",
+        default_enabled: false,
+        default_interval_secs: 7 * 24 * 60 * 60,
+        run: |app| Box::pin(run_weekly_digest(app)),
+    },
+    JobSpec {
+        name: "daily_note",
+        description: "Keep today's Markdown daily note up to date in the configured vault folder",
+        default_enabled: false,
+        default_interval_secs: 60 * 60,
+        run: |app| Box::pin(run_daily_note(app)),
+    },
+    JobSpec {
+        name: "cold_storage",
+        description: "Move screenshots past the cold-storage age into compressed per-day bundles",
+        default_enabled: true,
+        default_interval_secs: 6 * 60 * 60,
+        run: |app| Box::pin(run_cold_storage(app)),
+    },
+    JobSpec {
+        name: "database_maintenance",
+        description: "Compact the search index and VACUUM the database while the user is idle",
+        default_enabled: true,
+        default_interval_secs: 60 * 60,
+        run: |app| Box::pin(run_database_maintenance(app)),
+    },
+    JobSpec {
+        name: "backup",
+        description:
+            "Copy new and changed encrypted files and a database snapshot to the backup folder",
+        default_enabled: false,
+        default_interval_secs: 24 * 60 * 60,
+        run: |app| Box::pin(run_backup(app)),
+    },
+    JobSpec {
+        name: "sync",
+        description: "Upload new encrypted screenshots and changelog batches to the sync target",
+        default_enabled: false,
+        default_interval_secs: 60 * 60,
+        run: |app| Box::pin(run_sync(app)),
+    },
+    JobSpec {
+        name: "vector_index_retry",
+        description: "Retry failed vector indexing in the Python monitor",
+        default_enabled: false,
+        default_interval_secs: 6 * 60 * 60,
+        run: |app| Box::pin(run_vector_index_retry(app)),
+    },
+    JobSpec {
+        name: "clipboard_retention",
+        description: "Delete clipboard history entries older than the clipboard retention period",
+        default_enabled: true,
+        default_interval_secs: 60 * 60,
+        run: |app| Box::pin(run_clipboard_retention(app)),
+    },
+    JobSpec {
+        name: "audio_retention",
+        description: "Delete audio transcripts older than the audio retention period",
+        default_enabled: true,
+        default_interval_secs: 60 * 60,
+        run: |app| Box::pin(run_audio_retention(app)),
+    },
+];
+
+pub fn find_job(name: &str) -> Option<&'static JobSpec> {
+    JOBS.iter().find(|job| job.name == name)
+}
+
+/// A persisted job joined with its built-in description and live running flag.
+#[derive(Debug, Clone, Serialize)]
+pub struct JobStatus {
+    #[serde(flatten)]
+    pub job: ScheduledJob,
+    pub description: &'static str,
+    pub running: bool,
+}
+
+/// Tauri-managed scheduler state: which jobs are currently executing.
+#[derive(Default)]
+pub struct SchedulerState {
+    running: Mutex<HashSet<&'static str>>,
+}
+
+impl SchedulerState {
+    pub fn is_running(&self, name: &str) -> bool {
+        self.running
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains(name)
+    }
+
+    /// Persisted jobs that are still registered, with descriptions and running flags.
+    pub fn job_statuses(&self, storage: &StorageState) -> Result<Vec<JobStatus>, String> {
+        Ok(storage
+            .list_scheduled_jobs()?
+            .into_iter()
+            .filter_map(|job| {
+                let spec = find_job(&job.name)?;
+                Some(JobStatus {
+                    running: self.is_running(spec.name),
+                    description: spec.description,
+                    job,
+                })
+            })
+            .collect())
+    }
+
+    fn try_begin(&self, name: &'static str) -> bool {
+        self.running
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(name)
+    }
+
+    fn finish(&self, name: &str) {
+        self.running
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(name);
+    }
+}
+
+/// Run a job once and persist the outcome. Skips if the job is already running.
+pub async fn run_job(app: AppHandle, spec: &'static JobSpec) -> JobResult {
+    let scheduler = app.state::<Arc<SchedulerState>>().inner().clone();
+    if !scheduler.try_begin(spec.name) {
+        return Err(format!("Job {} is already running", spec.name));
+    }
+
+    let started_at = chrono::Utc::now().timestamp();
+    let started = Instant::now();
+    let result = (spec.run)(app.clone()).await;
+    let duration_ms = started.elapsed().as_millis() as i64;
+    scheduler.finish(spec.name);
+
+    let (status, message) = match &result {
+        Ok(Some(summary)) => ("ok", Some(summary.clone())),
+        Ok(None) => ("skipped", None),
+        Err(e) => ("error", Some(e.clone())),
+    };
+    match status {
+        "error" => tracing::warn!("[SCHEDULER] {} failed: {:?}", spec.name, message),
+        "ok" => tracing::info!("[SCHEDULER] {}: {:?}", spec.name, message),
+        _ => tracing::debug!("[SCHEDULER] {}: nothing to do", spec.name),
+    }
+
+    let storage = app.state::<Arc<StorageState>>().inner().clone();
+    let name = spec.name;
+    match tokio::task::spawn_blocking(move || {
+        storage.record_scheduled_job_run(name, started_at, status, message.as_deref(), duration_ms)
+    })
+    .await
+    {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => tracing::warn!("[SCHEDULER] failed to record {} run: {}", name, e),
+        Err(e) => tracing::warn!("[SCHEDULER] record {} join error: {:?}", name, e),
+    }
+
+    result
+}
+
+/// Register built-in jobs and start the scheduler loop. Call once storage is ready.
+pub fn start_scheduler(app: AppHandle) {
+    let storage = app.state::<Arc<StorageState>>().inner().clone();
+    for spec in JOBS {
+        if let Err(e) = storage.register_scheduled_job(
+            spec.name,
+            spec.default_enabled,
+            spec.default_interval_secs,
+        ) {
+            tracing::warn!("[SCHEDULER] failed to register {}: {}", spec.name, e);
+        }
+    }
+
+    tracing::info!("[SCHEDULER] started with {} jobs", JOBS.len());
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(TICK_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let now = chrono::Utc::now().timestamp();
+            let due = match tokio::task::spawn_blocking({
+                let storage = storage.clone();
+                move || storage.due_scheduled_jobs(now)
+            })
+            .await
+            {
+                Ok(Ok(names)) => names,
+                Ok(Err(e)) => {
+                    tracing::debug!("[SCHEDULER] due-job query failed: {}", e);
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!("[SCHEDULER] due-job query join error: {:?}", e);
+                    continue;
+                }
+            };
+
+            // Jobs run sequentially so maintenance never competes with itself.
+            for name in due {
+                if let Some(spec) = find_job(&name) {
+                    let _ = run_job(app.clone(), spec).await;
+                }
+            }
+        }
+    });
+}
+
+async fn run_storage_policy(app: AppHandle) -> JobResult {
+    let storage = app.state::<Arc<StorageState>>().inner().clone();
+    let run = tokio::task::spawn_blocking(move || storage.enforce_snapshot_storage_policy())
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))??;
+    if let Some(eviction) = run.quota_eviction {
+        let _ = app.emit("storage-quota-evicted", eviction);
+    }
+    Ok(run.summary)
+}
+
+async fn run_clipboard_retention(app: AppHandle) -> JobResult {
+    let settings = crate::clipboard_history::load_settings();
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let Some(cutoff_ms) =
+        crate::clipboard_history::retention_cutoff_ms(settings.retention_days, now_ms)
+    else {
+        return Ok(None);
+    };
+    let storage = app.state::<Arc<StorageState>>().inner().clone();
+    let removed = tokio::task::spawn_blocking(move || storage.prune_clipboard_history(cutoff_ms))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))??;
+    Ok((removed > 0).then(|| format!("{} clipboard entries removed", removed)))
+}
+
+async fn run_audio_retention(app: AppHandle) -> JobResult {
+    let retention_days = crate::audio_capture::load_settings().retention_days;
+    if retention_days == 0 {
+        return Ok(None);
+    }
+    let cutoff_ms =
+        chrono::Utc::now().timestamp_millis() - i64::from(retention_days) * 24 * 60 * 60 * 1000;
+    let storage = app.state::<Arc<StorageState>>().inner().clone();
+    let removed = tokio::task::spawn_blocking(move || storage.prune_audio_transcripts(cutoff_ms))
+        .await
+        .map_err(|e| format!("Task join error: {:?}", e))??;
+    Ok((removed > 0).then(|| format!("{} audio transcripts removed", removed)))
+}
+
+async fn run_weekly_digest(app: AppHandle) -> JobResult {
+    let storage = app.state::<Arc<StorageState>>().inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let data_dir = storage
+            .data_dir
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let digest_dir = data_dir
+            .parent()
+            .map(|p| p.join("digests"))
+            .unwrap_or_else(|| data_dir.join("digests"));
+        let now = chrono::Local::now();
+        let output_path = digest_dir.join(format!("digest-{}.html", now.format("%Y-%m-%d")));
+        let week_start = now.timestamp() - 7 * 24 * 60 * 60;
+
+        // Unattended runs never decrypt titles, so no CNG prompt can appear.
+        let digest = storage.export_weekly_digest_html(
+            week_start,
+            DigestPrivacyLevel::Apps,
+            &output_path,
+        )?;
+        Ok(Some(format!(
+            "{} screenshots written to {}",
+            digest.total_screenshots,
+            output_path.display()
+        )))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+async fn run_daily_note(app: AppHandle) -> JobResult {
+    let Some(vault_dir) = crate::storage::daily_note_vault_dir() else {
+        return Ok(None);
+    };
+    let storage = app.state::<Arc<StorageState>>().inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let today = chrono::Local::now().date_naive();
+        let mut days = vec![today];
+        // Finish yesterday's note once if it was last written before midnight.
+        if let Some(yesterday) = today.pred_opt() {
+            let midnight = today
+                .and_hms_opt(0, 0, 0)
+                .and_then(|t| t.and_local_timezone(chrono::Local).earliest());
+            let written_at = std::fs::metadata(vault_dir.join(format!("{}.md", yesterday)))
+                .and_then(|m| m.modified())
+                .ok()
+                .map(chrono::DateTime::<chrono::Local>::from);
+            if matches!((written_at, midnight), (Some(at), Some(midnight)) if at < midnight) {
+                days.insert(0, yesterday);
+            }
+        }
+        let mut written = Vec::new();
+        for day in days {
+            let note = storage.export_daily_note(day, &vault_dir, true)?;
+            written.push(note.path);
+        }
+        Ok(Some(format!("Wrote {}", written.join(", "))))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+async fn run_cold_storage(app: AppHandle) -> JobResult {
+    let idle = app
+        .state::<Arc<crate::idle::IdleState>>()
+        .is_idle
+        .load(Ordering::SeqCst);
+    if !idle {
+        return Ok(None);
+    }
+
+    let storage = app.state::<Arc<StorageState>>().inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let summary = storage.archive_cold_screenshots()?;
+        if summary.archived == 0 && summary.removed_bundles == 0 {
+            return Ok(None);
+        }
+        Ok(Some(format!(
+            "{} screenshots archived ({} -> {} bytes), {} empty bundles removed{}",
+            summary.archived,
+            summary.bytes_before,
+            summary.bytes_after,
+            summary.removed_bundles,
+            if summary.auth_required {
+                "; stopped waiting for authentication"
+            } else {
+                ""
+            }
+        )))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+async fn run_database_maintenance(app: AppHandle) -> JobResult {
+    let idle = app
+        .state::<Arc<crate::idle::IdleState>>()
+        .is_idle
+        .load(Ordering::SeqCst);
+    if !idle {
+        return Ok(None);
+    }
+
+    let storage = app.state::<Arc<StorageState>>().inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let now = chrono::Utc::now().timestamp();
+        if let Some(last) = storage.last_database_maintenance_at()? {
+            if now - last < DATABASE_MAINTENANCE_MIN_INTERVAL_SECS {
+                return Ok(None);
+            }
+        }
+        match storage.run_database_maintenance(&|_, _, _| {}) {
+            Ok(report) => Ok(Some(format!(
+                "{} -> {} bytes, {} posting lists compacted, {} removed",
+                report.bytes_before,
+                report.bytes_after,
+                report.bitmap_tokens_compacted,
+                report.bitmap_tokens_removed
+            ))),
+            Err(e) if e == "ALREADY_RUNNING" => Ok(None),
+            Err(e) => Err(e),
+        }
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+async fn run_backup(app: AppHandle) -> JobResult {
+    let Some(destination) = crate::storage::backup_dir() else {
+        return Ok(None);
+    };
+    let storage = app.state::<Arc<StorageState>>().inner().clone();
+    tokio::task::spawn_blocking(
+        move || match storage.backup_now(&destination, &|_, _, _| {}) {
+            Ok(report) => Ok(Some(format!(
+                "{} files copied ({} bytes), {} unchanged, {} removed",
+                report.files_copied,
+                report.bytes_copied,
+                report.files_unchanged,
+                report.files_removed
+            ))),
+            Err(e) if e == "ALREADY_RUNNING" => Ok(None),
+            Err(e) => Err(e),
+        },
+    )
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+async fn run_sync(app: AppHandle) -> JobResult {
+    let storage = app.state::<Arc<StorageState>>().inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let config = storage.sync_config()?;
+        if !config.enabled || config.endpoint.is_empty() {
+            return Ok(None);
+        }
+        match storage.sync_now(&|_, _, _| {}) {
+            Ok(report) if report.changelog_batches == 0 => Ok(None),
+            Ok(report) => Ok(Some(format!(
+                "{} screenshots uploaded ({} files, {} bytes), {} deleted",
+                report.screenshots_uploaded,
+                report.files_uploaded,
+                report.bytes_uploaded,
+                report.screenshots_deleted
+            ))),
+            Err(e) if e == "ALREADY_RUNNING" || e == "AUTH_REQUIRED" => Ok(None),
+            Err(e) => Err(e),
+        }
+    })
+    .await
+    .map_err(|e| format!("Task join error: {:?}", e))?
+}
+
+async fn run_vector_index_retry(app: AppHandle) -> JobResult {
+    let monitor_state = app.state::<MonitorState>();
+    let monitor_running = monitor_state
+        .process
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .is_some();
+    if !monitor_running {
+        return Ok(None);
+    }
+
+    let response = monitor::forward_command_to_python(
+        &monitor_state,
+        serde_json::json!({ "command": "retry_vector_indexing", "limit": 64 }),
+    )
+    .await?;
+    Ok(Some(response.to_string()))
+}
@@ -0,0 +1,207 @@
+//! Automatic capture pause while the screen is being shared or presented.
+//!
+//! Windows has no API that reports another process's capture sessions, so detection
+//! looks for the on-screen indicators conferencing apps show while sharing: Teams'
+//! sharing control bar, Zoom's floating meeting toolbar, Chromium's "is sharing your
+//! screen" bar and PowerPoint's slide show window. Matching is best-effort; extend
+//! `SHARE_INDICATORS` when a new client is reported.
+//!
+//! While an indicator is visible `CaptureState::screen_share_paused` is set, the
+//! capture loop skips frames, a `screen-share-status` event is emitted and the tray
+//! label and monitor hooks are updated.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager};
+
+use windows::Win32::Foundation::{BOOL, HWND, LPARAM};
+use windows::Win32::UI::WindowsAndMessaging::{
+    EnumWindows, GetClassNameW, GetWindowTextW, IsWindowVisible,
+};
+
+use crate::capture::CaptureState;
+use crate::hooks::MonitorHookEvent;
+
+/// Registry flag for the automatic pause. Defaults to on.
+const SCREEN_SHARE_PAUSE_KEY: &str = "screen_share_pause_enabled";
+
+/// Polling cadence for indicator windows.
+const POLL_INTERVAL_SECS: u64 = 3;
+
+/// Ensures only one polling task is ever spawned.
+static MONITOR_STARTED: AtomicBool = AtomicBool::new(false);
+
+enum IndicatorMatch {
+    Class(&'static str),
+    TitleContains(&'static str),
+}
+
+/// Known sharing/presenting indicator windows, with the source reported to the UI.
+const SHARE_INDICATORS: &[(&str, IndicatorMatch)] = &[
+    (
+        "teams",
+        IndicatorMatch::TitleContains("Sharing control bar"),
+    ),
+    ("zoom", IndicatorMatch::Class("ZPToolBarParentWndClass")),
+    (
+        "browser",
+        IndicatorMatch::TitleContains("is sharing your screen"),
+    ),
+    (
+        "browser",
+        IndicatorMatch::TitleContains("is sharing a window"),
+    ),
+    (
+        "browser",
+        IndicatorMatch::TitleContains("is sharing this tab"),
+    ),
+    ("powerpoint", IndicatorMatch::Class("screenClass")),
+];
+
+pub fn screen_share_pause_enabled() -> bool {
+    crate::registry_config::get_bool(SCREEN_SHARE_PAUSE_KEY).unwrap_or(true)
+}
+
+pub fn set_screen_share_pause_enabled(enabled: bool) -> Result<(), String> {
+    crate::registry_config::set_bool(SCREEN_SHARE_PAUSE_KEY, enabled)
+}
+
+/// Source name when a window with this class and title is a sharing indicator.
+fn match_share_indicator(class_name: &str, title: &str) -> Option<&'static str> {
+    SHARE_INDICATORS
+        .iter()
+        .find(|(_, rule)| match rule {
+            IndicatorMatch::Class(class) => class_name == *class,
+            IndicatorMatch::TitleContains(text) => title.contains(text),
+        })
+        .map(|(source, _)| *source)
+}
+
+unsafe extern "system" fn collect_indicator(hwnd: HWND, lparam: LPARAM) -> BOOL {
+    // SAFETY: `lparam` is the `Option<&str>` slot owned by `detect_screen_share`, which
+    // outlives the synchronous `EnumWindows` call.
+    let found = &mut *(lparam.0 as *mut Option<&'static str>);
+    if !IsWindowVisible(hwnd).as_bool() {
+        return BOOL(1);
+    }
+    let mut class_buf = [0u16; 256];
+    let class_len = GetClassNameW(hwnd, &mut class_buf);
+    let class_name = String::from_utf16_lossy(&class_buf[..class_len.max(0) as usize]);
+    let mut title_buf = [0u16; 512];
+    let title_len = GetWindowTextW(hwnd, &mut title_buf);
+    let title = String::from_utf16_lossy(&title_buf[..title_len.max(0) as usize]);
+
+    match match_share_indicator(&class_name, &title) {
+        Some(source) => {
+            *found = Some(source);
+            BOOL(0)
+        }
+        None => BOOL(1),
+    }
+}
+
+/// Source of the first visible sharing indicator, if any.
+pub fn detect_screen_share() -> Option<&'static str> {
+    let mut found: Option<&'static str> = None;
+    // SAFETY: the callback only dereferences `found` during this synchronous call.
+    // EnumWindows reports an error when the callback stops early, which is expected.
+    unsafe {
+        let _ = EnumWindows(
+            Some(collect_indicator),
+            LPARAM(&mut found as *mut Option<&'static str> as isize),
+        );
+    }
+    found
+}
+
+/// Applies a detection result. Returns whether the paused state changed.
+fn apply_screen_share_state(app: &AppHandle, source: Option<&'static str>) -> bool {
+    let capture_state = app.state::<Arc<CaptureState>>();
+    let should_pause = source.is_some();
+    let was_paused = capture_state
+        .screen_share_paused
+        .swap(should_pause, Ordering::SeqCst);
+    if was_paused == should_pause {
+        return false;
+    }
+
+    if should_pause {
+        tracing::info!(
+            "Screen share: {} indicator detected, pausing capture",
+            source.unwrap_or_default()
+        );
+    } else {
+        tracing::info!("Screen share: ended, resuming capture");
+    }
+    let _ = app.emit(
+        "screen-share-status",
+        serde_json::json!({
+            "paused": should_pause,
+            "source": source,
+        }),
+    );
+    crate::hooks::fire(
+        app,
+        if should_pause {
+            MonitorHookEvent::ScreenShareOn
+        } else {
+            MonitorHookEvent::ScreenShareOff
+        },
+    );
+    crate::refresh_tray_menu(app);
+    true
+}
+
+/// Starts the indicator polling loop. Safe to call more than once.
+pub fn start_screen_share_monitor(app: AppHandle) {
+    if MONITOR_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tracing::info!(
+        "Screen share: starting monitor (polling {}s)",
+        POLL_INTERVAL_SECS
+    );
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(POLL_INTERVAL_SECS)).await;
+            let source = if screen_share_pause_enabled() {
+                tokio::task::spawn_blocking(detect_screen_share)
+                    .await
+                    .unwrap_or(None)
+            } else {
+                None
+            };
+            apply_screen_share_state(&app, source);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indicators_match_known_clients_only() {
+        assert_eq!(
+            match_share_indicator("TeamsWebView", "Sharing control bar | Microsoft Teams"),
+            Some("teams")
+        );
+        assert_eq!(
+            match_share_indicator("ZPToolBarParentWndClass", ""),
+            Some("zoom")
+        );
+        assert_eq!(
+            match_share_indicator(
+                "Chrome_WidgetWin_1",
+                "meet.google.com is sharing your screen."
+            ),
+            Some("browser")
+        );
+        assert_eq!(match_share_indicator("screenClass", ""), Some("powerpoint"));
+        assert_eq!(
+            match_share_indicator("Chrome_WidgetWin_1", "Screen sharing tips - Google Search"),
+            None
+        );
+        assert_eq!(match_share_indicator("PPTFrameClass", "deck.pptx"), None);
+    }
+}
@@ -0,0 +1,126 @@
+//! Search overlay: a small always-on-top window for querying history without the
+//! main window.
+//!
+//! The overlay is its own webview (`index.html?window=search-overlay`), created on
+//! first use and hidden rather than destroyed afterwards so the hotkey opens it
+//! instantly. It shows text results only and opens a result in the main window.
+//!
+//! Session policy treats the app as foreground only while one of its windows is on
+//! screen (see `CredentialManagerState::set_foreground_state`). Showing the overlay
+//! therefore marks the app foreground, and hiding it hands the state back to the main
+//! window, so an overlay search neither expires nor extends the main window's session.
+
+use std::sync::Arc;
+
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+
+use crate::credential_manager::CredentialManagerState;
+
+pub const SEARCH_OVERLAY_LABEL: &str = "search-overlay";
+
+fn set_foreground(app: &AppHandle, in_foreground: bool) {
+    if let Some(credential_state) = app.try_state::<Arc<CredentialManagerState>>() {
+        credential_state.set_foreground_state(in_foreground);
+    }
+}
+
+fn main_window_on_screen(app: &AppHandle) -> bool {
+    app.get_webview_window("main").is_some_and(|window| {
+        window.is_visible().unwrap_or(false) && !window.is_minimized().unwrap_or(false)
+    })
+}
+
+fn build_window(app: &AppHandle) -> Result<WebviewWindow, String> {
+    WebviewWindowBuilder::new(
+        app,
+        SEARCH_OVERLAY_LABEL,
+        WebviewUrl::App("index.html?window=search-overlay".into()),
+    )
+    .title("carbonpaper search")
+    .inner_size(680.0, 440.0)
+    .resizable(false)
+    .decorations(false)
+    .transparent(true)
+    .shadow(true)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .center()
+    .visible(false)
+    .build()
+    .map_err(|e| format!("Failed to create search overlay: {}", e))
+}
+
+fn show(app: &AppHandle) -> Result<(), String> {
+    let window = match app.get_webview_window(SEARCH_OVERLAY_LABEL) {
+        Some(window) => window,
+        None => {
+            let window = build_window(app)?;
+            crate::capture::apply_capture_exclusion_to_all(app);
+            window
+        }
+    };
+    set_foreground(app, true);
+    window
+        .show()
+        .and_then(|_| window.set_focus())
+        .map_err(|e| format!("Failed to show search overlay: {}", e))?;
+    let _ = app.emit_to(SEARCH_OVERLAY_LABEL, "search-overlay-shown", ());
+    Ok(())
+}
+
+/// Hides the overlay and tells it to drop its query and results.
+pub fn hide(app: &AppHandle) {
+    let Some(window) = app.get_webview_window(SEARCH_OVERLAY_LABEL) else {
+        return;
+    };
+    if !window.is_visible().unwrap_or(false) {
+        return;
+    }
+    let _ = window.hide();
+    let _ = app.emit_to(SEARCH_OVERLAY_LABEL, "search-overlay-hidden", ());
+    set_foreground(app, main_window_on_screen(app));
+}
+
+/// Shows the overlay, or hides it when it is already on screen. Bound to the search
+/// hotkey.
+pub fn toggle(app: &AppHandle) {
+    let visible = app
+        .get_webview_window(SEARCH_OVERLAY_LABEL)
+        .is_some_and(|window| window.is_visible().unwrap_or(false));
+    if visible {
+        hide(app);
+        return;
+    }
+    // The hotkey handler runs on the event-loop thread, where creating a webview
+    // deadlocks on Windows.
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = show(&app) {
+            tracing::error!("{}", e);
+        }
+    });
+}
+
+/// Hides the search overlay (Escape, focus loss, or after opening a result).
+///
+/// Authentication: not required. Returns JSON `null`. Frontend: `SearchOverlay.jsx`.
+#[tauri::command]
+pub fn search_overlay_hide(app: AppHandle) {
+    hide(&app);
+}
+
+/// Hides the overlay and brings up the main window, recreating it in lightweight
+/// mode. The result to open is handed over through the webviews' shared local
+/// storage, since a recreated main window is not listening yet; an existing main
+/// window is told to pick it up with `search-overlay-open-main`.
+///
+/// Authentication: not required. Returns JSON `null`. Frontend: `SearchOverlay.jsx`.
+#[tauri::command]
+pub fn search_overlay_open_main(app: AppHandle) {
+    hide(&app);
+    let main_exists = app.get_webview_window("main").is_some();
+    crate::open_main_window(&app, false);
+    if main_exists {
+        let _ = app.emit_to("main", "search-overlay-open-main", ());
+    }
+}
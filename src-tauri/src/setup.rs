@@ -0,0 +1,639 @@
+//! First-run setup state machine.
+//!
+//! Setup runs as an ordered list of steps (credentials, Python runtime and
+//! environment, models, GPU check, initial policy). Progress is persisted to
+//! `setup_state.json` under Local AppData so an interrupted setup resumes at the
+//! step that was cut off instead of leaving storage half-initialized. Each step is
+//! idempotent and delegates to the same routines the individual commands use.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::credential_manager::{self, CredentialManagerState};
+use crate::ml_runtime::MlRuntimeState;
+use crate::storage::StorageState;
+
+const SETUP_STATE_FILE_NAME: &str = "setup_state.json";
+const SETUP_STATE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SetupStep {
+    Credentials,
+    PythonRuntime,
+    PythonEnvironment,
+    Models,
+    GpuCheck,
+    InitialPolicy,
+    Complete,
+}
+
+/// Steps in execution order; `Complete` is the terminal state and never runs.
+const STEP_ORDER: [SetupStep; 6] = [
+    SetupStep::Credentials,
+    SetupStep::PythonRuntime,
+    SetupStep::PythonEnvironment,
+    SetupStep::Models,
+    SetupStep::GpuCheck,
+    SetupStep::InitialPolicy,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SetupStepStatus {
+    Pending,
+    InProgress,
+    Done,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetupStepRecord {
+    pub step: SetupStep,
+    pub status: SetupStepStatus,
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Step-specific result (e.g. detected GPUs), shown by the setup UI.
+    #[serde(default)]
+    pub detail: Option<serde_json::Value>,
+    #[serde(default)]
+    pub updated_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetupState {
+    pub version: u32,
+    pub current_step: SetupStep,
+    pub steps: Vec<SetupStepRecord>,
+    pub started_at: i64,
+    #[serde(default)]
+    pub completed_at: Option<i64>,
+}
+
+impl SetupState {
+    fn new(now: i64) -> Self {
+        Self {
+            version: SETUP_STATE_VERSION,
+            current_step: STEP_ORDER[0],
+            steps: STEP_ORDER
+                .iter()
+                .map(|&step| SetupStepRecord {
+                    step,
+                    status: SetupStepStatus::Pending,
+                    error: None,
+                    detail: None,
+                    updated_at: None,
+                })
+                .collect(),
+            started_at: now,
+            completed_at: None,
+        }
+    }
+
+    fn record_mut(&mut self, step: SetupStep) -> Option<&mut SetupStepRecord> {
+        self.steps.iter_mut().find(|record| record.step == step)
+    }
+
+    fn status_of(&self, step: SetupStep) -> SetupStepStatus {
+        self.steps
+            .iter()
+            .find(|record| record.step == step)
+            .map(|record| record.status)
+            .unwrap_or(SetupStepStatus::Pending)
+    }
+
+    /// Repairs a state loaded from disk: adds steps introduced by newer versions,
+    /// turns steps interrupted mid-run back into pending, and recomputes the cursor.
+    fn normalize(&mut self) {
+        for &step in &STEP_ORDER {
+            if self.record_mut(step).is_none() {
+                self.steps.push(SetupStepRecord {
+                    step,
+                    status: SetupStepStatus::Pending,
+                    error: None,
+                    detail: None,
+                    updated_at: None,
+                });
+            }
+        }
+        self.steps
+            .retain(|record| STEP_ORDER.contains(&record.step));
+        self.steps.sort_by_key(|record| record.step);
+        for record in &mut self.steps {
+            if record.status == SetupStepStatus::InProgress {
+                record.status = SetupStepStatus::Pending;
+                record.error = Some("Interrupted; will resume".to_string());
+            }
+        }
+        self.version = SETUP_STATE_VERSION;
+        self.recompute_cursor();
+    }
+
+    fn recompute_cursor(&mut self) {
+        self.current_step = STEP_ORDER
+            .iter()
+            .copied()
+            .find(|&step| self.status_of(step) != SetupStepStatus::Done)
+            .unwrap_or(SetupStep::Complete);
+        if self.current_step != SetupStep::Complete {
+            self.completed_at = None;
+        }
+    }
+
+    fn mark(
+        &mut self,
+        step: SetupStep,
+        status: SetupStepStatus,
+        error: Option<String>,
+        detail: Option<serde_json::Value>,
+        now: i64,
+    ) {
+        if let Some(record) = self.record_mut(step) {
+            record.status = status;
+            record.error = error;
+            if detail.is_some() {
+                record.detail = detail;
+            }
+            record.updated_at = Some(now);
+        }
+        self.recompute_cursor();
+        if self.current_step == SetupStep::Complete && self.completed_at.is_none() {
+            self.completed_at = Some(now);
+        }
+    }
+
+    /// Marks a completed step (and every later step) pending again, e.g. when its
+    /// on-disk result has disappeared since it last ran.
+    fn invalidate_from(&mut self, step: SetupStep, reason: &str, now: i64) {
+        for record in &mut self.steps {
+            if record.step >= step && record.status == SetupStepStatus::Done {
+                record.status = SetupStepStatus::Pending;
+                record.error = (record.step == step).then(|| reason.to_string());
+                record.updated_at = Some(now);
+            }
+        }
+        self.recompute_cursor();
+    }
+
+    /// Validates that `step` may run now: it must be the current step or an
+    /// already-reached earlier step being re-run.
+    fn check_runnable(&self, step: SetupStep) -> Result<(), String> {
+        if step == SetupStep::Complete {
+            return Err("Setup is already complete".to_string());
+        }
+        if step > self.current_step {
+            return Err(format!(
+                "Cannot run setup step {:?} before {:?}",
+                step, self.current_step
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Tauri-managed setup state: serializes step execution.
+#[derive(Default)]
+pub struct SetupManager {
+    run_lock: tokio::sync::Mutex<()>,
+}
+
+fn setup_state_path() -> Result<PathBuf, String> {
+    crate::resource_utils::file_in_local_appdata()
+        .map(|dir| dir.join(SETUP_STATE_FILE_NAME))
+        .ok_or_else(|| "Could not determine local appdata directory.".to_string())
+}
+
+fn read_setup_state() -> Result<Option<SetupState>, String> {
+    let path = setup_state_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content =
+        std::fs::read_to_string(&path).map_err(|e| format!("Failed to read setup state: {}", e))?;
+    match serde_json::from_str::<SetupState>(&content) {
+        Ok(mut state) => {
+            state.normalize();
+            Ok(Some(state))
+        }
+        Err(e) => {
+            tracing::warn!("[SETUP] discarding unreadable setup state: {}", e);
+            Ok(None)
+        }
+    }
+}
+
+fn write_setup_state(state: &SetupState) -> Result<(), String> {
+    let path = setup_state_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create setup state directory: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(state)
+        .map_err(|e| format!("Failed to serialize setup state: {}", e))?;
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, content)
+        .map_err(|e| format!("Failed to write setup state: {}", e))?;
+    std::fs::rename(&tmp_path, &path).map_err(|e| format!("Failed to write setup state: {}", e))
+}
+
+fn credentials_present(app: &AppHandle) -> bool {
+    let credential_state = app.state::<Arc<CredentialManagerState>>();
+    credential_state.master_key_file_exists()
+        && credential_manager::load_public_key_from_file(&credential_state).is_ok()
+}
+
+fn required_models_missing(models: &serde_json::Value) -> Vec<String> {
+    models
+        .as_object()
+        .map(|entries| {
+            entries
+                .iter()
+                .filter(|(key, status)| {
+                    !key.starts_with('_')
+                        && status.get("required").and_then(|v| v.as_bool()) == Some(true)
+                        && status.get("complete").and_then(|v| v.as_bool()) != Some(true)
+                })
+                .map(|(key, _)| key.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+async fn models_present(app: &AppHandle) -> bool {
+    let ocr_installed = {
+        let app = app.clone();
+        tokio::task::spawn_blocking(move || crate::ml_runtime::ocr_model_status(&app))
+            .await
+            .ok()
+            .and_then(Result::ok)
+            .map(|status| status.installed)
+            .unwrap_or(false)
+    };
+    ocr_installed
+        && crate::model_management::check_model_files()
+            .await
+            .map(|models| required_models_missing(&models).is_empty())
+            .unwrap_or(false)
+}
+
+fn policy_initialized(app: &AppHandle) -> bool {
+    app.state::<Arc<StorageState>>()
+        .load_policy()
+        .map(|policy| policy.get("retention_period").is_some())
+        .unwrap_or(false)
+}
+
+/// Checks whether the durable result of `step` is still on disk.
+async fn step_satisfied(app: &AppHandle, step: SetupStep) -> bool {
+    match step {
+        SetupStep::Credentials => credentials_present(app),
+        SetupStep::PythonRuntime => {
+            let app = app.clone();
+            tokio::task::spawn_blocking(move || {
+                crate::python::venv_python_exists(&app)
+                    || crate::python::find_required_python_executable().is_some()
+            })
+            .await
+            .unwrap_or(false)
+        }
+        SetupStep::PythonEnvironment => crate::python::venv_python_exists(app),
+        SetupStep::Models => models_present(app).await,
+        SetupStep::InitialPolicy => policy_initialized(app),
+        SetupStep::GpuCheck | SetupStep::Complete => true,
+    }
+}
+
+/// Steps whose result can later disappear from disk and must be re-checked. The
+/// Python runtime is only needed until the venv exists, and the GPU check leaves
+/// nothing behind.
+fn revalidated_on_load(step: SetupStep) -> bool {
+    matches!(
+        step,
+        SetupStep::Credentials
+            | SetupStep::PythonEnvironment
+            | SetupStep::Models
+            | SetupStep::InitialPolicy
+    )
+}
+
+/// Loads the persisted state and reconciles it with what is actually on disk.
+///
+/// Installs that predate the state machine get their already-satisfied steps
+/// marked done, so existing users are not sent through setup again.
+pub async fn load_setup_state(app: &AppHandle) -> Result<SetupState, String> {
+    let now = chrono::Utc::now().timestamp();
+    let mut state = match read_setup_state()? {
+        Some(mut state) => {
+            for &step in &STEP_ORDER {
+                if state.status_of(step) == SetupStepStatus::Done
+                    && revalidated_on_load(step)
+                    && !step_satisfied(app, step).await
+                {
+                    tracing::warn!(
+                        "[SETUP] step {:?} no longer satisfied; resuming there",
+                        step
+                    );
+                    state.invalidate_from(
+                        step,
+                        "Previously completed step is no longer present",
+                        now,
+                    );
+                    break;
+                }
+            }
+            state
+        }
+        None => {
+            let mut state = SetupState::new(now);
+            let mut detected = Vec::new();
+            for &step in &STEP_ORDER {
+                if step == SetupStep::GpuCheck {
+                    continue;
+                }
+                if !step_satisfied(app, step).await {
+                    break;
+                }
+                detected.push(step);
+            }
+            // A fully detected install already chose its accelerator.
+            let existing_install = detected.len() == STEP_ORDER.len() - 1;
+            for &step in &STEP_ORDER {
+                if !(detected.contains(&step) || existing_install) {
+                    break;
+                }
+                state.mark(
+                    step,
+                    SetupStepStatus::Done,
+                    None,
+                    Some(serde_json::json!({ "detected": true })),
+                    now,
+                );
+            }
+            state
+        }
+    };
+    state.recompute_cursor();
+    write_setup_state(&state)?;
+    Ok(state)
+}
+
+async fn run_step(
+    app: &AppHandle,
+    window: &tauri::Window,
+    step: SetupStep,
+    python_path: Option<String>,
+) -> Result<Option<serde_json::Value>, String> {
+    match step {
+        SetupStep::Credentials => {
+            let message = crate::commands::credential::credential_initialize(
+                app.state::<Arc<CredentialManagerState>>(),
+                app.state::<Arc<StorageState>>(),
+            )
+            .await?;
+            Ok(Some(serde_json::json!({ "message": message })))
+        }
+        SetupStep::PythonRuntime => {
+            let existing = {
+                let app = app.clone();
+                tokio::task::spawn_blocking(move || {
+                    crate::python::venv_python_exists(&app)
+                        || crate::python::find_required_python_executable().is_some()
+                })
+                .await
+                .map_err(|e| format!("Task join error: {:?}", e))?
+            };
+            if existing || python_path.is_some() {
+                return Ok(Some(serde_json::json!({ "installed": false })));
+            }
+            let message = crate::python::request_install_python(
+                app.clone(),
+                app.state::<Arc<CredentialManagerState>>(),
+            )
+            .await?;
+            Ok(Some(
+                serde_json::json!({ "installed": true, "message": message }),
+            ))
+        }
+        SetupStep::PythonEnvironment => {
+            if !crate::python::venv_python_exists(app) {
+                let message = crate::python::install_python_venv(
+                    app.clone(),
+                    app.state::<Arc<CredentialManagerState>>(),
+                    python_path,
+                )
+                .await?;
+                return Ok(Some(serde_json::json!({ "message": message })));
+            }
+            let freshness = crate::python::check_deps_freshness(app.clone())?;
+            if freshness
+                .get("needs_update")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false)
+            {
+                let message = crate::python::sync_python_deps(app.clone()).await?;
+                return Ok(Some(serde_json::json!({ "message": message })));
+            }
+            Ok(None)
+        }
+        SetupStep::Models => {
+            let models = crate::model_management::check_model_files().await?;
+            let missing = required_models_missing(&models);
+            for model_id in &missing {
+                crate::model_management::download_model(
+                    app.clone(),
+                    window.clone(),
+                    model_id.clone(),
+                )
+                .await?;
+            }
+            crate::ml_runtime::download_rust_ocr_model(
+                app.clone(),
+                window.clone(),
+                app.state::<Arc<MlRuntimeState>>(),
+            )
+            .await?;
+            Ok(Some(serde_json::json!({ "downloaded": missing })))
+        }
+        SetupStep::GpuCheck => {
+            // Informational: a machine without a usable adapter simply stays on CPU.
+            let mut detail =
+                match tokio::task::spawn_blocking(crate::monitor::enumerate_gpus_internal)
+                    .await
+                    .map_err(|e| format!("Task join error: {:?}", e))?
+                {
+                    Ok(gpus) => serde_json::json!({
+                        "directml_available": gpus.iter().any(|gpu| {
+                            gpu.get("directml_supported").and_then(|v| v.as_bool()) == Some(true)
+                        }),
+                        "recommended_device_id": crate::monitor::recommend_gpu(&gpus),
+                        "gpus": gpus,
+                    }),
+                    Err(e) => serde_json::json!({
+                        "directml_available": false,
+                        "recommended_device_id": null,
+                        "gpus": [],
+                        "error": e,
+                    }),
+                };
+            let npus = tokio::task::spawn_blocking(crate::monitor::enumerate_npus_internal)
+                .await
+                .map_err(|e| format!("Task join error: {:?}", e))?
+                .unwrap_or_else(|e| {
+                    tracing::warn!("NPU enumeration failed: {}", e);
+                    Vec::new()
+                });
+            detail["npu_available"] = serde_json::json!(!npus.is_empty());
+            detail["npus"] = serde_json::json!(npus);
+            Ok(Some(detail))
+        }
+        SetupStep::InitialPolicy => {
+            let storage = app.state::<Arc<StorageState>>();
+            let mut policy = storage.load_policy()?;
+            let obj = policy
+                .as_object_mut()
+                .ok_or_else(|| "Policy is not a valid JSON object".to_string())?;
+            obj.entry("retention_period")
+                .or_insert_with(|| serde_json::json!("permanent"));
+            obj.entry("storage_limit")
+                .or_insert_with(|| serde_json::json!("unlimited"));
+            storage.save_policy(&policy)?;
+            Ok(None)
+        }
+        SetupStep::Complete => Err("Setup is already complete".to_string()),
+    }
+}
+
+/// Runs `step` (default: the current step), persisting progress before and after
+/// so an interruption is resumed on the next launch.
+pub async fn advance_setup(
+    app: &AppHandle,
+    window: &tauri::Window,
+    step: Option<SetupStep>,
+    python_path: Option<String>,
+) -> Result<SetupState, String> {
+    let manager = app.state::<Arc<SetupManager>>().inner().clone();
+    let _guard = manager
+        .run_lock
+        .try_lock()
+        .map_err(|_| "A setup step is already running".to_string())?;
+
+    let mut state = load_setup_state(app).await?;
+    let step = step.unwrap_or(state.current_step);
+    state.check_runnable(step)?;
+
+    let now = chrono::Utc::now().timestamp();
+    state.mark(step, SetupStepStatus::InProgress, None, None, now);
+    write_setup_state(&state)?;
+    tracing::info!("[SETUP] running step {:?}", step);
+
+    let result = run_step(app, window, step, python_path).await;
+    let now = chrono::Utc::now().timestamp();
+    match result {
+        Ok(detail) => {
+            state.mark(step, SetupStepStatus::Done, None, detail, now);
+            tracing::info!("[SETUP] step {:?} done", step);
+        }
+        Err(e) => {
+            tracing::warn!("[SETUP] step {:?} failed: {}", step, e);
+            state.mark(step, SetupStepStatus::Failed, Some(e), None, now);
+        }
+    }
+    write_setup_state(&state)?;
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marking_steps_done_advances_cursor_to_complete() {
+        let mut state = SetupState::new(100);
+        assert_eq!(state.current_step, SetupStep::Credentials);
+
+        for (i, &step) in STEP_ORDER.iter().enumerate() {
+            state.check_runnable(step).expect("current step runnable");
+            state.mark(step, SetupStepStatus::Done, None, None, 200 + i as i64);
+        }
+
+        assert_eq!(state.current_step, SetupStep::Complete);
+        assert_eq!(state.completed_at, Some(205));
+        assert!(state.check_runnable(SetupStep::Credentials).is_err());
+    }
+
+    #[test]
+    fn later_steps_cannot_run_early_but_earlier_steps_can_rerun() {
+        let mut state = SetupState::new(0);
+        state.mark(SetupStep::Credentials, SetupStepStatus::Done, None, None, 1);
+
+        assert!(state.check_runnable(SetupStep::Models).is_err());
+        assert!(state.check_runnable(SetupStep::PythonRuntime).is_ok());
+        assert!(state.check_runnable(SetupStep::Credentials).is_ok());
+    }
+
+    #[test]
+    fn interrupted_step_resumes_as_pending_after_reload() {
+        let mut state = SetupState::new(0);
+        state.mark(SetupStep::Credentials, SetupStepStatus::Done, None, None, 1);
+        state.mark(
+            SetupStep::PythonRuntime,
+            SetupStepStatus::InProgress,
+            None,
+            None,
+            2,
+        );
+        let json = serde_json::to_string(&state).unwrap();
+
+        let mut reloaded: SetupState = serde_json::from_str(&json).unwrap();
+        reloaded.normalize();
+
+        assert_eq!(reloaded.current_step, SetupStep::PythonRuntime);
+        assert_eq!(
+            reloaded.status_of(SetupStep::PythonRuntime),
+            SetupStepStatus::Pending
+        );
+        assert_eq!(
+            reloaded.status_of(SetupStep::Credentials),
+            SetupStepStatus::Done
+        );
+    }
+
+    #[test]
+    fn invalidating_a_step_reopens_it_and_everything_after() {
+        let mut state = SetupState::new(0);
+        for &step in &STEP_ORDER {
+            state.mark(step, SetupStepStatus::Done, None, None, 1);
+        }
+
+        state.invalidate_from(SetupStep::PythonEnvironment, "venv missing", 2);
+
+        assert_eq!(state.current_step, SetupStep::PythonEnvironment);
+        assert_eq!(state.completed_at, None);
+        assert_eq!(
+            state.status_of(SetupStep::PythonRuntime),
+            SetupStepStatus::Done
+        );
+        assert_eq!(
+            state.status_of(SetupStep::InitialPolicy),
+            SetupStepStatus::Pending
+        );
+    }
+
+    #[test]
+    fn required_models_missing_ignores_optional_and_runtime_entries() {
+        let models = serde_json::json!({
+            "_runtime": { "complete": true, "required": false },
+            "chinese-clip": { "complete": false, "required": true },
+            "bge-small-zh": { "complete": true, "required": true },
+            "bge-reranker-v2-m3": { "complete": false, "required": false },
+        });
+
+        assert_eq!(
+            required_models_missing(&models),
+            vec!["chinese-clip".to_string()]
+        );
+    }
+}
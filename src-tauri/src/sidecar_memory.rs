@@ -0,0 +1,340 @@
+//! Sidecar memory watchdog: recycles the Python monitor once its memory grows past a
+//! configured limit.
+//!
+//! Long sessions leak memory on the Python side until the whole machine slows down.
+//! Every 30 seconds the watchdog sums the resident memory of the monitor process and
+//! its descendants (the model worker runs as a child). After two consecutive samples
+//! over `sidecar_memory_limit_mb` it pauses capture, lets in-flight OCR and the
+//! post-processing queue drain, and restarts the monitor, re-applying any pause the
+//! user had set. Each step is reported with a `monitor-memory-recycle` event. Unlike
+//! the Job Object ceiling (`job_memory_limit_mb`), no allocation is ever refused.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use sysinfo::{Pid, System};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::capture::CaptureState;
+use crate::monitor::{MonitorState, PauseSnapshot};
+
+/// Registry key for the recycle threshold in MB; 0 turns the watchdog off.
+pub const LIMIT_REGISTRY_KEY: &str = "sidecar_memory_limit_mb";
+const DEFAULT_LIMIT_MB: u32 = 4096;
+/// Smallest non-zero limit. The monitor holds more than this right after loading its
+/// models, so lower values would recycle it continuously.
+const MIN_LIMIT_MB: u32 = 1024;
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(30);
+/// Consecutive samples over the limit before recycling, so a short spike (a
+/// clustering run, a large batch of vectors) does not trigger a restart.
+const OVER_LIMIT_SAMPLES: u32 = 2;
+/// Minimum time between recycles, in case the monitor comes back over the limit.
+const MIN_RECYCLE_INTERVAL: Duration = Duration::from_secs(15 * 60);
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(60);
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Number of recycles since app start, reported by `monitor_get_metrics`.
+static RECYCLE_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// Normalizes a requested limit: 0 stays off, anything else is at least `MIN_LIMIT_MB`.
+pub fn clamp_limit_mb(value: u64) -> u32 {
+    match value {
+        0 => 0,
+        mb => mb.clamp(MIN_LIMIT_MB as u64, u32::MAX as u64) as u32,
+    }
+}
+
+/// Configured limit in MB, 0 when the watchdog is off.
+pub fn limit_mb() -> u32 {
+    clamp_limit_mb(
+        crate::registry_config::get_u32(LIMIT_REGISTRY_KEY).unwrap_or(DEFAULT_LIMIT_MB) as u64,
+    )
+}
+
+pub fn recycle_count() -> u64 {
+    RECYCLE_COUNT.load(Ordering::Relaxed)
+}
+
+/// Tracks consecutive over-limit samples and the last recycle.
+#[derive(Debug, Default)]
+struct Watchdog {
+    over_limit_samples: u32,
+    last_recycle: Option<Instant>,
+}
+
+impl Watchdog {
+    /// Records a sample and returns true when the monitor should be recycled now.
+    fn observe(&mut self, rss_bytes: u64, limit_bytes: u64, now: Instant) -> bool {
+        if rss_bytes <= limit_bytes {
+            self.over_limit_samples = 0;
+            return false;
+        }
+        self.over_limit_samples += 1;
+        if self.over_limit_samples < OVER_LIMIT_SAMPLES {
+            return false;
+        }
+        if self
+            .last_recycle
+            .is_some_and(|last| now.duration_since(last) < MIN_RECYCLE_INTERVAL)
+        {
+            return false;
+        }
+        self.over_limit_samples = 0;
+        self.last_recycle = Some(now);
+        true
+    }
+}
+
+/// Resident memory of `root` and every process descended from it.
+fn tree_rss_bytes(sys: &System, root: Pid) -> u64 {
+    let mut pids = vec![root];
+    let mut index = 0;
+    while index < pids.len() {
+        let parent = pids[index];
+        for (pid, process) in sys.processes() {
+            if process.parent() == Some(parent) && !pids.contains(pid) {
+                pids.push(*pid);
+            }
+        }
+        index += 1;
+    }
+    pids.iter()
+        .filter_map(|pid| sys.process(*pid))
+        .map(|process| process.memory())
+        .sum()
+}
+
+fn monitor_pid(app: &AppHandle) -> Option<u32> {
+    let monitor_state = app.state::<MonitorState>();
+    if monitor_state.stopping.load(Ordering::SeqCst) {
+        return None;
+    }
+    let guard = monitor_state
+        .process
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    guard.as_ref().map(|child| child.id())
+}
+
+/// Items waiting in the monitor's OCR post-processing queue; 0 when unknown.
+async fn postprocess_queue_size(monitor_state: &MonitorState) -> u64 {
+    let request = serde_json::json!({ "command": "index_health" });
+    match crate::monitor::forward_command_to_python(monitor_state, request).await {
+        Ok(health) => health
+            .get("postprocess")
+            .and_then(|postprocess| postprocess.get("queue_size"))
+            .and_then(|size| size.as_u64())
+            .unwrap_or(0),
+        Err(e) => {
+            tracing::debug!("Memory recycle: failed to read OCR queue size: {}", e);
+            0
+        }
+    }
+}
+
+/// Pauses capture and waits for in-flight OCR and the post-processing queue to
+/// empty. Returns false if they did not within `DRAIN_TIMEOUT`.
+async fn drain(app: &AppHandle) -> bool {
+    let capture_state = app.state::<Arc<CaptureState>>();
+    let monitor_state = app.state::<MonitorState>();
+    capture_state.paused.store(true, Ordering::SeqCst);
+
+    let deadline = Instant::now() + DRAIN_TIMEOUT;
+    loop {
+        let in_flight = capture_state.in_flight_ocr_count.load(Ordering::SeqCst);
+        let queued = if in_flight == 0 {
+            postprocess_queue_size(&monitor_state).await
+        } else {
+            0
+        };
+        if in_flight == 0 && queued == 0 {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            tracing::warn!(
+                "Memory recycle: drain timed out with {} in-flight OCR and {} queued items",
+                in_flight,
+                queued
+            );
+            return false;
+        }
+        tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+    }
+}
+
+async fn recycle(app: &AppHandle, rss_bytes: u64, limit_bytes: u64) {
+    tracing::warn!(
+        "Memory recycle: monitor uses {} MB, over the {} MB limit; restarting",
+        rss_bytes / (1024 * 1024),
+        limit_bytes / (1024 * 1024)
+    );
+    let _ = app.emit(
+        "monitor-memory-recycle",
+        serde_json::json!({
+            "phase": "draining",
+            "rss_bytes": rss_bytes,
+            "limit_bytes": limit_bytes,
+        }),
+    );
+
+    // Draining pauses capture and the restart clears the pause, so remember the
+    // user's own pause (and timed-pause deadline) to put it back afterwards.
+    let pause = PauseSnapshot::capture(
+        &app.state::<MonitorState>(),
+        &app.state::<Arc<CaptureState>>(),
+    );
+    let drained = drain(app).await;
+    if monitor_pid(app).is_none() {
+        // Stopped by the user (or exited) while draining; leave it stopped.
+        tracing::info!("Memory recycle: monitor stopped during drain, not restarting");
+        app.state::<Arc<CaptureState>>()
+            .paused
+            .store(pause.paused, Ordering::SeqCst);
+        return;
+    }
+
+    let _ = crate::monitor::stop_monitor_impl(
+        app.state::<MonitorState>(),
+        app.state::<Arc<CaptureState>>(),
+        app.clone(),
+    )
+    .await;
+    let started =
+        crate::monitor::start_monitor_impl(app.state::<MonitorState>(), app.clone()).await;
+    RECYCLE_COUNT.fetch_add(1, Ordering::Relaxed);
+
+    let error = started.err();
+    if let Some(e) = &error {
+        tracing::error!("Memory recycle: failed to restart monitor: {}", e);
+    } else if let Err(e) = crate::monitor::restore_pause(
+        app.state::<MonitorState>(),
+        app.state::<Arc<CaptureState>>(),
+        app,
+        pause,
+    )
+    .await
+    {
+        tracing::warn!("Memory recycle: failed to restore pause: {}", e);
+    }
+    let _ = app.emit(
+        "monitor-memory-recycle",
+        serde_json::json!({
+            "phase": if error.is_none() { "restarted" } else { "failed" },
+            "rss_bytes": rss_bytes,
+            "limit_bytes": limit_bytes,
+            "drained": drained,
+            "error": error,
+        }),
+    );
+}
+
+/// Starts the watchdog. Called once from app setup.
+pub fn start_watchdog(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+        let mut sys = System::new();
+        let mut watchdog = Watchdog::default();
+
+        loop {
+            interval.tick().await;
+
+            let limit_bytes = limit_mb() as u64 * 1024 * 1024;
+            let Some(pid) = monitor_pid(&app).filter(|_| limit_bytes > 0) else {
+                watchdog.over_limit_samples = 0;
+                continue;
+            };
+            if app
+                .state::<MonitorState>()
+                .migration_lock
+                .load(Ordering::SeqCst)
+            {
+                continue;
+            }
+
+            sys.refresh_processes();
+            let rss_bytes = tree_rss_bytes(&sys, Pid::from_u32(pid));
+            if watchdog.observe(rss_bytes, limit_bytes, Instant::now()) {
+                recycle(&app, rss_bytes, limit_bytes).await;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watchdog_needs_consecutive_samples_and_spaces_recycles() {
+        const LIMIT: u64 = 1000;
+        let start = Instant::now();
+        let mut watchdog = Watchdog::default();
+
+        assert!(!watchdog.observe(1500, LIMIT, start));
+        assert!(
+            !watchdog.observe(900, LIMIT, start),
+            "a quiet sample resets the count"
+        );
+        assert!(!watchdog.observe(1500, LIMIT, start));
+        assert!(watchdog.observe(1500, LIMIT, start));
+
+        let soon = start + Duration::from_secs(60);
+        assert!(!watchdog.observe(1500, LIMIT, soon));
+        assert!(!watchdog.observe(1500, LIMIT, soon));
+
+        let later = start + MIN_RECYCLE_INTERVAL;
+        assert!(watchdog.observe(1500, LIMIT, later));
+    }
+
+    #[test]
+    fn recycling_a_paused_monitor_keeps_the_pause() {
+        use crate::monitor::PauseRestore;
+
+        const LIMIT: u64 = 1000;
+        const MINUTE_MS: u64 = 60_000;
+        let now_ms = 1_800_000_000_000;
+        let monitor_state = MonitorState::new();
+        let capture_state = CaptureState::new();
+        capture_state.paused.store(true, Ordering::SeqCst);
+        monitor_state
+            .pause_resume_at_ms
+            .store(now_ms + 10 * MINUTE_MS, Ordering::SeqCst);
+
+        let mut watchdog = Watchdog::default();
+        let start = Instant::now();
+        assert!(!watchdog.observe(1500, LIMIT, start));
+        assert!(watchdog.observe(1500, LIMIT, start));
+
+        let timed = PauseSnapshot::capture(&monitor_state, &capture_state);
+        // What stop_monitor_impl and the new capture loop do to the live state.
+        capture_state.paused.store(false, Ordering::SeqCst);
+        monitor_state.pause_resume_at_ms.store(0, Ordering::SeqCst);
+        assert_eq!(
+            timed.restore_at(now_ms + MINUTE_MS),
+            PauseRestore::Timed(Duration::from_secs(9 * 60))
+        );
+        assert_eq!(
+            timed.restore_at(now_ms + 11 * MINUTE_MS),
+            PauseRestore::Running,
+            "a timed pause that ran out during the restart is not re-applied"
+        );
+
+        capture_state.paused.store(true, Ordering::SeqCst);
+        let indefinite = PauseSnapshot::capture(&monitor_state, &capture_state);
+        assert_eq!(indefinite.restore_at(now_ms), PauseRestore::Indefinite);
+
+        capture_state.paused.store(false, Ordering::SeqCst);
+        let running = PauseSnapshot::capture(&monitor_state, &capture_state);
+        assert_eq!(running.restore_at(now_ms), PauseRestore::Running);
+    }
+
+    #[test]
+    fn limits_keep_zero_as_off_and_enforce_a_minimum() {
+        assert_eq!(clamp_limit_mb(0), 0);
+        assert_eq!(clamp_limit_mb(10), MIN_LIMIT_MB);
+        assert_eq!(clamp_limit_mb(8192), 8192);
+        assert_eq!(clamp_limit_mb(u64::MAX), u32::MAX);
+    }
+}
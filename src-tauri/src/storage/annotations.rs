@@ -0,0 +1,492 @@
+//! User notes attached to screenshots.
+//!
+//! Note text is sealed with a per-row key exactly like OCR text, so the database
+//! never holds it in plaintext. To keep notes searchable, their bigrams are hashed
+//! with the blind-index HMAC key into `annotation_bitmap_index`, whose postings are
+//! annotation IDs. Postings of notes removed through a screenshot cascade are left
+//! behind and dropped at query time by joining against live rows.
+
+use std::collections::{HashMap, HashSet};
+
+use roaring::RoaringBitmap;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::{Annotation, StorageState};
+
+/// Longest accepted note, in characters.
+const MAX_ANNOTATION_CHARS: usize = 10_000;
+
+type EncryptedAnnotationRow = (i64, i64, Vec<u8>, Vec<u8>, String, String);
+
+/// Trim `text`; rejects empty or overlong notes.
+fn normalize_annotation(text: &str) -> Result<String, String> {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return Err("Annotation must not be empty".to_string());
+    }
+    if trimmed.chars().count() > MAX_ANNOTATION_CHARS {
+        return Err(format!(
+            "Annotation is longer than {} characters",
+            MAX_ANNOTATION_CHARS
+        ));
+    }
+    Ok(trimmed.to_string())
+}
+
+/// Index tokens of a note. Notes are matched case-insensitively.
+fn annotation_tokens(text: &str) -> HashSet<String> {
+    StorageState::bigram_tokenize(&text.to_lowercase())
+}
+
+fn annotation_id_u32(annotation_id: i64) -> Result<u32, String> {
+    u32::try_from(annotation_id)
+        .map_err(|_| format!("Annotation id {} exceeds index range", annotation_id))
+}
+
+impl StorageState {
+    /// Attach a note to a live screenshot.
+    pub fn add_annotation(&self, screenshot_id: i64, text: &str) -> Result<Annotation, String> {
+        let text = normalize_annotation(text)?;
+        let hmac_key = self.credential_state.get_hmac_key()?;
+        let (text_enc, text_key_enc) = self.encrypt_payload_with_row_key(text.as_bytes())?;
+
+        let mut guard = self.get_connection_named("add_annotation")?;
+        let conn = guard.as_mut().unwrap();
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start annotation transaction: {}", e))?;
+        let inserted = tx
+            .execute(
+                "INSERT INTO annotations (screenshot_id, text_enc, text_key_encrypted)
+                 SELECT id, ?2, ?3 FROM screenshots WHERE id = ?1 AND is_deleted = 0",
+                params![screenshot_id, text_enc, text_key_enc],
+            )
+            .map_err(|e| format!("Failed to insert annotation: {}", e))?;
+        if inserted == 0 {
+            return Err(format!("Screenshot {} not found", screenshot_id));
+        }
+        let id = tx.last_insert_rowid();
+        Self::update_annotation_postings(&tx, &hmac_key, id, None, Some(&text))?;
+        let (created_at, updated_at): (String, String) = tx
+            .query_row(
+                "SELECT created_at, updated_at FROM annotations WHERE id = ?1",
+                params![id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|e| format!("Failed to read annotation: {}", e))?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit annotation: {}", e))?;
+
+        Ok(Annotation {
+            id,
+            screenshot_id,
+            text,
+            created_at,
+            updated_at,
+        })
+    }
+
+    /// Replace the text of a note, re-encrypting it under a fresh row key.
+    pub fn update_annotation(&self, annotation_id: i64, text: &str) -> Result<Annotation, String> {
+        let text = normalize_annotation(text)?;
+        let hmac_key = self.credential_state.get_hmac_key()?;
+        let (text_enc, text_key_enc) = self.encrypt_payload_with_row_key(text.as_bytes())?;
+
+        let mut guard = self.get_connection_named("update_annotation")?;
+        let conn = guard.as_mut().unwrap();
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start annotation transaction: {}", e))?;
+        let (screenshot_id, old_enc, old_key_enc, created_at): (i64, Vec<u8>, Vec<u8>, String) = tx
+            .query_row(
+                "SELECT screenshot_id, text_enc, text_key_encrypted, created_at
+                 FROM annotations WHERE id = ?1",
+                params![annotation_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to query annotation: {}", e))?
+            .ok_or_else(|| format!("Annotation {} not found", annotation_id))?;
+        let old_text = self.decrypt_annotation_text(&old_enc, &old_key_enc)?;
+
+        tx.execute(
+            "UPDATE annotations
+             SET text_enc = ?2, text_key_encrypted = ?3, updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?1",
+            params![annotation_id, text_enc, text_key_enc],
+        )
+        .map_err(|e| format!("Failed to update annotation: {}", e))?;
+        Self::update_annotation_postings(
+            &tx,
+            &hmac_key,
+            annotation_id,
+            Some(&old_text),
+            Some(&text),
+        )?;
+        let updated_at: String = tx
+            .query_row(
+                "SELECT updated_at FROM annotations WHERE id = ?1",
+                params![annotation_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to read annotation: {}", e))?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit annotation: {}", e))?;
+
+        Ok(Annotation {
+            id: annotation_id,
+            screenshot_id,
+            text,
+            created_at,
+            updated_at,
+        })
+    }
+
+    /// Delete a note and unlink it from the search index. Returns `false` when it
+    /// did not exist.
+    pub fn delete_annotation(&self, annotation_id: i64) -> Result<bool, String> {
+        let hmac_key = self.credential_state.get_hmac_key()?;
+        let mut guard = self.get_connection_named("delete_annotation")?;
+        let conn = guard.as_mut().unwrap();
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start annotation transaction: {}", e))?;
+        let row: Option<(Vec<u8>, Vec<u8>)> = tx
+            .query_row(
+                "SELECT text_enc, text_key_encrypted FROM annotations WHERE id = ?1",
+                params![annotation_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to query annotation: {}", e))?;
+        let Some((text_enc, text_key_enc)) = row else {
+            return Ok(false);
+        };
+
+        // An undecryptable note cannot be unlinked; its postings are filtered at query time.
+        if let Ok(old_text) = self.decrypt_annotation_text(&text_enc, &text_key_enc) {
+            Self::update_annotation_postings(&tx, &hmac_key, annotation_id, Some(&old_text), None)?;
+        }
+        tx.execute(
+            "DELETE FROM annotations WHERE id = ?1",
+            params![annotation_id],
+        )
+        .map_err(|e| format!("Failed to delete annotation: {}", e))?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit annotation delete: {}", e))?;
+        Ok(true)
+    }
+
+    /// Notes of one screenshot, oldest first.
+    pub fn list_annotations(&self, screenshot_id: i64) -> Result<Vec<Annotation>, String> {
+        let rows: Vec<EncryptedAnnotationRow> = {
+            let guard = self.get_connection_named("list_annotations")?;
+            let conn = guard.as_ref().unwrap();
+            let mut stmt = conn
+                .prepare(
+                    "SELECT id, screenshot_id, text_enc, text_key_encrypted, created_at, updated_at
+                     FROM annotations WHERE screenshot_id = ?1
+                     ORDER BY created_at, id",
+                )
+                .map_err(|e| format!("Failed to prepare annotation query: {}", e))?;
+            let rows = stmt
+                .query_map(params![screenshot_id], |row| {
+                    Ok((
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                        row.get(5)?,
+                    ))
+                })
+                .map_err(|e| format!("Failed to query annotations: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows
+        };
+        self.decrypt_annotation_rows(rows)
+    }
+
+    /// Notes whose text contains every whitespace-separated keyword of `query`,
+    /// most recently edited first.
+    pub fn search_annotations(
+        &self,
+        query: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Annotation>, String> {
+        let hmac_key = self.credential_state.get_hmac_key()?;
+        let rows: Vec<EncryptedAnnotationRow> = {
+            let guard = self.get_connection_named("search_annotations")?;
+            let conn = guard.as_ref().unwrap();
+            let matches = Self::annotation_ids_matching(conn, &hmac_key, query)?;
+            let ids: Vec<i64> = matches.iter().map(i64::from).collect();
+
+            let mut rows: Vec<EncryptedAnnotationRow> = Vec::new();
+            for chunk in ids.chunks(500) {
+                let placeholders = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+                let sql = format!(
+                    "SELECT a.id, a.screenshot_id, a.text_enc, a.text_key_encrypted,
+                            a.created_at, a.updated_at
+                     FROM annotations a
+                     JOIN screenshots s ON s.id = a.screenshot_id
+                     WHERE s.is_deleted = 0 AND a.id IN ({})",
+                    placeholders
+                );
+                let id_params: Vec<&dyn rusqlite::ToSql> =
+                    chunk.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
+                let mut stmt = conn
+                    .prepare(&sql)
+                    .map_err(|e| format!("Failed to prepare annotation search: {}", e))?;
+                let chunk_rows = stmt
+                    .query_map(id_params.as_slice(), |row| {
+                        Ok((
+                            row.get(0)?,
+                            row.get(1)?,
+                            row.get(2)?,
+                            row.get(3)?,
+                            row.get(4)?,
+                            row.get(5)?,
+                        ))
+                    })
+                    .map_err(|e| format!("Failed to search annotations: {}", e))?;
+                rows.extend(chunk_rows.filter_map(|r| r.ok()));
+            }
+            rows
+        };
+
+        let mut rows = rows;
+        rows.sort_by(|a, b| b.5.cmp(&a.5).then(b.0.cmp(&a.0)));
+        let page: Vec<EncryptedAnnotationRow> = rows
+            .into_iter()
+            .skip(offset.max(0) as usize)
+            .take(limit.clamp(1, 500) as usize)
+            .collect();
+        self.decrypt_annotation_rows(page)
+    }
+
+    /// Annotation IDs whose postings contain every bigram of every keyword in `query`.
+    fn annotation_ids_matching(
+        conn: &Connection,
+        hmac_key: &[u8],
+        query: &str,
+    ) -> Result<RoaringBitmap, String> {
+        let tokens: HashSet<String> = query
+            .split_whitespace()
+            .flat_map(annotation_tokens)
+            .collect();
+        Self::blind_postings_matching_all(conn, "annotation_bitmap_index", hmac_key, tokens)
+    }
+
+    /// IDs present in the postings of every token in `tokens`, read from the blind
+    /// bigram table `table` (`token_hash`, `postings_blob`). No tokens match nothing.
+    pub(super) fn blind_postings_matching_all(
+        conn: &Connection,
+        table: &str,
+        hmac_key: &[u8],
+        tokens: HashSet<String>,
+    ) -> Result<RoaringBitmap, String> {
+        if tokens.is_empty() {
+            return Ok(RoaringBitmap::new());
+        }
+
+        let mut stmt = conn
+            .prepare_cached(&format!(
+                "SELECT postings_blob FROM {} WHERE token_hash = ?1",
+                table
+            ))
+            .map_err(|e| format!("Failed to prepare {} read: {}", table, e))?;
+        let mut result: Option<RoaringBitmap> = None;
+        for token in tokens {
+            let token_hash = Self::compute_hmac_hash(&token, hmac_key);
+            let blob: Option<Vec<u8>> = stmt
+                .query_row(params![&token_hash], |row| row.get(0))
+                .optional()
+                .map_err(|e| format!("Failed to query {}: {}", table, e))?;
+            let Some(blob) = blob else {
+                return Ok(RoaringBitmap::new());
+            };
+            let bitmap = RoaringBitmap::deserialize_from(&blob[..])
+                .map_err(|e| format!("Failed to deserialize bitmap: {}", e))?;
+            result = Some(match result {
+                Some(acc) => acc & bitmap,
+                None => bitmap,
+            });
+        }
+        Ok(result.unwrap_or_default())
+    }
+
+    /// Move `annotation_id` from the postings of `old_text` to those of `new_text`
+    /// inside the caller's transaction.
+    pub(super) fn update_annotation_postings(
+        tx: &rusqlite::Transaction<'_>,
+        hmac_key: &[u8],
+        annotation_id: i64,
+        old_text: Option<&str>,
+        new_text: Option<&str>,
+    ) -> Result<(), String> {
+        let posting_id = annotation_id_u32(annotation_id)?;
+        let old_tokens = old_text.map(annotation_tokens).unwrap_or_default();
+        let new_tokens = new_text.map(annotation_tokens).unwrap_or_default();
+
+        let mut changes: HashMap<String, bool> = HashMap::new();
+        for token in old_tokens.difference(&new_tokens) {
+            changes.insert(Self::compute_hmac_hash(token, hmac_key), false);
+        }
+        for token in new_tokens.difference(&old_tokens) {
+            changes.insert(Self::compute_hmac_hash(token, hmac_key), true);
+        }
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let mut get_stmt = tx
+            .prepare_cached(
+                "SELECT postings_blob FROM annotation_bitmap_index WHERE token_hash = ?1",
+            )
+            .map_err(|e| format!("Failed to prepare annotation bitmap read: {}", e))?;
+        let mut put_stmt = tx
+            .prepare_cached(
+                "INSERT OR REPLACE INTO annotation_bitmap_index (token_hash, postings_blob) VALUES (?1, ?2)",
+            )
+            .map_err(|e| format!("Failed to prepare annotation bitmap write: {}", e))?;
+        let mut del_stmt = tx
+            .prepare_cached("DELETE FROM annotation_bitmap_index WHERE token_hash = ?1")
+            .map_err(|e| format!("Failed to prepare annotation bitmap delete: {}", e))?;
+
+        for (token_hash, insert) in changes {
+            let existing_blob: Option<Vec<u8>> = get_stmt
+                .query_row(params![&token_hash], |row| row.get(0))
+                .optional()
+                .map_err(|e| format!("Failed to load annotation bitmap row: {}", e))?;
+            let mut bitmap = match existing_blob {
+                Some(blob) => RoaringBitmap::deserialize_from(&blob[..])
+                    .map_err(|e| format!("Failed to deserialize bitmap: {}", e))?,
+                None if insert => RoaringBitmap::new(),
+                None => continue,
+            };
+            if insert {
+                bitmap.insert(posting_id);
+            } else {
+                bitmap.remove(posting_id);
+            }
+
+            if bitmap.is_empty() {
+                del_stmt
+                    .execute(params![&token_hash])
+                    .map_err(|e| format!("Failed to delete empty bitmap row: {}", e))?;
+            } else {
+                let mut buf = Vec::new();
+                bitmap
+                    .serialize_into(&mut buf)
+                    .map_err(|e| format!("Failed to serialize bitmap: {}", e))?;
+                put_stmt
+                    .execute(params![&token_hash, &buf])
+                    .map_err(|e| format!("Failed to write annotation bitmap row: {}", e))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn decrypt_annotation_text(
+        &self,
+        text_enc: &[u8],
+        text_key_enc: &[u8],
+    ) -> Result<String, String> {
+        let bytes = self.decrypt_payload_with_row_key(text_enc, text_key_enc)?;
+        String::from_utf8(bytes).map_err(|e| format!("Annotation is not valid UTF-8: {}", e))
+    }
+
+    fn decrypt_annotation_rows(
+        &self,
+        rows: Vec<EncryptedAnnotationRow>,
+    ) -> Result<Vec<Annotation>, String> {
+        rows.into_iter()
+            .map(
+                |(id, screenshot_id, text_enc, text_key_enc, created_at, updated_at)| {
+                    Ok(Annotation {
+                        id,
+                        screenshot_id,
+                        text: self.decrypt_annotation_text(&text_enc, &text_key_enc)?,
+                        created_at,
+                        updated_at,
+                    })
+                },
+            )
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_HMAC_KEY: &[u8] = b"annotation-test-hmac-key-32-bytes";
+
+    #[test]
+    fn annotation_postings_follow_edits_and_deletes() {
+        let mut conn = Connection::open_in_memory().expect("in-memory database");
+        conn.execute_batch(
+            "CREATE TABLE annotation_bitmap_index (
+                token_hash TEXT PRIMARY KEY,
+                postings_blob BLOB NOT NULL
+            );",
+        )
+        .expect("index table");
+
+        let tx = conn.transaction().unwrap();
+        StorageState::update_annotation_postings(
+            &tx,
+            TEST_HMAC_KEY,
+            1,
+            None,
+            Some("Quarterly Budget"),
+        )
+        .unwrap();
+        StorageState::update_annotation_postings(&tx, TEST_HMAC_KEY, 2, None, Some("budget 会议"))
+            .unwrap();
+        tx.commit().unwrap();
+
+        let ids = |conn: &Connection, query: &str| -> Vec<u32> {
+            StorageState::annotation_ids_matching(conn, TEST_HMAC_KEY, query)
+                .unwrap()
+                .iter()
+                .collect()
+        };
+        assert_eq!(ids(&conn, "BUDGET"), vec![1, 2]);
+        assert_eq!(ids(&conn, "budget quarterly"), vec![1]);
+        assert_eq!(ids(&conn, "会议"), vec![2]);
+        assert!(ids(&conn, "invoice").is_empty());
+        assert!(ids(&conn, "a").is_empty());
+
+        let tx = conn.transaction().unwrap();
+        StorageState::update_annotation_postings(
+            &tx,
+            TEST_HMAC_KEY,
+            1,
+            Some("Quarterly Budget"),
+            Some("Invoice"),
+        )
+        .unwrap();
+        StorageState::update_annotation_postings(&tx, TEST_HMAC_KEY, 2, Some("budget 会议"), None)
+            .unwrap();
+        tx.commit().unwrap();
+
+        assert!(ids(&conn, "budget").is_empty());
+        assert_eq!(ids(&conn, "invoice"), vec![1]);
+        let rows: i64 = conn
+            .query_row("SELECT COUNT(*) FROM annotation_bitmap_index", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(rows, 6, "only the bigrams of \"invoice\" remain");
+    }
+
+    #[test]
+    fn normalize_annotation_trims_and_rejects_empty() {
+        assert_eq!(normalize_annotation("  call back  ").unwrap(), "call back");
+        assert!(normalize_annotation(" \n ").is_err());
+        assert!(normalize_annotation(&"x".repeat(MAX_ANNOTATION_CHARS + 1)).is_err());
+    }
+}
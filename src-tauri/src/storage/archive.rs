@@ -0,0 +1,790 @@
+//! Password-protected portable archives of a capture time range.
+//!
+//! Row keys are wrapped by the local CNG key, so neither the database nor the
+//! `.enc` image files can be opened on another machine. A range archive instead
+//! decrypts each screenshot in an authenticated session and re-encrypts it with a
+//! key derived from a user password (Argon2id + AES-256-GCM, the same primitives as
+//! the full backup). The ZIP container holds a plaintext `manifest.json` with the
+//! KDF salt and counts, an encrypted `records.json.enc` (metadata + OCR results) and
+//! one encrypted `images/<id>.enc` entry per screenshot. Each image entry is sealed
+//! with its entry name as associated data and the records with the exact manifest
+//! bytes, so entries cannot be swapped or renamed and the manifest cannot be edited
+//! without the password.
+//!
+//! Import reverses this: entries are decrypted with the password and re-encrypted
+//! under fresh local row keys, so imported rows are indistinguishable from captured ones.
+
+use crate::credential_manager::encrypt_with_master_key;
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Nonce,
+};
+use argon2::{password_hash::SaltString, Argon2};
+use rand::RngCore;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use zip::write::FileOptions;
+
+use super::image_codec::image_extension_for_bytes;
+use super::{
+    OcrResult, RangeArchiveImportSummary, RangeArchiveSummary, ScreenshotRecord, StorageState,
+};
+
+/// `format` value written to every range archive manifest.
+pub(crate) const RANGE_ARCHIVE_FORMAT: &str = "carbonpaper-range-archive";
+pub(crate) const RANGE_ARCHIVE_VERSION: u32 = 1;
+pub(crate) const RANGE_ARCHIVE_MANIFEST: &str = "manifest.json";
+pub(crate) const RANGE_ARCHIVE_RECORDS: &str = "records.json.enc";
+const NONCE_LEN: usize = 12;
+
+/// Plaintext header of a range archive. Contains nothing derived from captured content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct RangeArchiveManifest {
+    pub format: String,
+    pub version: u32,
+    pub salt: String,
+    pub created_at: String,
+    pub start_ts: f64,
+    pub end_ts: f64,
+    pub screenshot_count: usize,
+    pub image_count: usize,
+}
+
+/// One screenshot inside `records.json.enc`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct RangeArchiveRecord {
+    pub screenshot: ScreenshotRecord,
+    pub ocr_results: Vec<OcrResult>,
+    /// ZIP entry holding the encrypted image, `None` if the image could not be read.
+    pub image_entry: Option<String>,
+    pub image_mime: Option<String>,
+    /// MD5 of the archived image bytes. Differs from `screenshot.image_hash`, the hash
+    /// of the captured frame, when the stored image was re-encoded or is a delta frame.
+    #[serde(default)]
+    pub image_bytes_hash: Option<String>,
+}
+
+/// Whether decrypted image bytes are the ones the record was exported with. Archives
+/// without `image_bytes_hash` are checked against the capture hash.
+fn archive_image_matches(record: &RangeArchiveRecord, image: &[u8]) -> bool {
+    let expected = record
+        .image_bytes_hash
+        .as_deref()
+        .unwrap_or(&record.screenshot.image_hash);
+    crate::capture::md5_hash(image).eq_ignore_ascii_case(expected)
+}
+
+/// Derive the 256-bit archive key from the password and the manifest salt.
+pub(crate) fn derive_archive_key(password: &str, salt: &str) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt.as_bytes(), &mut key)
+        .map_err(|e| format!("Argon2 error: {}", e))?;
+    Ok(key)
+}
+
+/// Encrypt one archive entry, authenticating `aad` alongside it.
+/// Output layout: `[nonce (12)][ciphertext + tag]`.
+pub(crate) fn seal_archive_entry(
+    key: &[u8; 32],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<Vec<u8>, String> {
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|e| format!("AES error: {}", e))?;
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut nonce_bytes);
+    let payload = Payload {
+        msg: plaintext,
+        aad,
+    };
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce_bytes), payload)
+        .map_err(|e| format!("Encryption error: {}", e))?;
+    let mut sealed = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    sealed.extend_from_slice(&nonce_bytes);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+/// Decrypt an entry produced by [`seal_archive_entry`] with the same `aad`. A wrong
+/// password, a modified entry or different associated data fails here.
+pub(crate) fn open_archive_entry(
+    key: &[u8; 32],
+    aad: &[u8],
+    sealed: &[u8],
+) -> Result<Vec<u8>, String> {
+    if sealed.len() < NONCE_LEN {
+        return Err("Archive entry is truncated".to_string());
+    }
+    let (nonce_bytes, ciphertext) = sealed.split_at(NONCE_LEN);
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|e| format!("AES error: {}", e))?;
+    let payload = Payload {
+        msg: ciphertext,
+        aad,
+    };
+    cipher
+        .decrypt(Nonce::from_slice(nonce_bytes), payload)
+        .map_err(|_| "Failed to decrypt archive: wrong password or corrupted file".to_string())
+}
+
+fn read_zip_entry(archive: &mut zip::ZipArchive<File>, name: &str) -> Result<Vec<u8>, String> {
+    let mut entry = archive
+        .by_name(name)
+        .map_err(|e| format!("Archive entry {} missing: {}", name, e))?;
+    let mut buf = Vec::with_capacity(entry.size() as usize);
+    entry
+        .read_to_end(&mut buf)
+        .map_err(|e| format!("Failed to read archive entry {}: {}", name, e))?;
+    Ok(buf)
+}
+
+/// An opened range archive: validated manifest, decrypted records, and the key
+/// needed to open the image entries.
+pub(crate) struct OpenedRangeArchive {
+    pub manifest: RangeArchiveManifest,
+    pub records: Vec<RangeArchiveRecord>,
+    archive: zip::ZipArchive<File>,
+    key: [u8; 32],
+}
+
+impl OpenedRangeArchive {
+    /// Open and validate an archive. Fails on an unknown format or a wrong password.
+    pub(crate) fn open(path: &Path, password: &str) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("Failed to open archive: {}", e))?;
+        let mut archive =
+            zip::ZipArchive::new(file).map_err(|e| format!("Failed to read archive: {}", e))?;
+
+        let manifest_json = read_zip_entry(&mut archive, RANGE_ARCHIVE_MANIFEST)?;
+        let manifest: RangeArchiveManifest = serde_json::from_slice(&manifest_json)
+            .map_err(|e| format!("Invalid archive manifest: {}", e))?;
+        if manifest.format != RANGE_ARCHIVE_FORMAT {
+            return Err(format!(
+                "Not a CarbonPaper range archive: {}",
+                manifest.format
+            ));
+        }
+        if manifest.version > RANGE_ARCHIVE_VERSION {
+            return Err(format!(
+                "Unsupported archive version {} (this build reads up to {})",
+                manifest.version, RANGE_ARCHIVE_VERSION
+            ));
+        }
+
+        let key = derive_archive_key(password, &manifest.salt)?;
+        let sealed_records = read_zip_entry(&mut archive, RANGE_ARCHIVE_RECORDS)?;
+        // Sealed with the manifest as associated data, so this also authenticates it.
+        let mut records_json = open_archive_entry(&key, &manifest_json, &sealed_records)?;
+        let records = serde_json::from_slice(&records_json)
+            .map_err(|e| format!("Invalid archive records: {}", e));
+        StorageState::zeroize_bytes(&mut records_json);
+
+        Ok(Self {
+            manifest,
+            records: records?,
+            archive,
+            key,
+        })
+    }
+
+    /// Decrypt one image entry referenced by a record.
+    pub(crate) fn read_image(&mut self, entry_name: &str) -> Result<Vec<u8>, String> {
+        let sealed = read_zip_entry(&mut self.archive, entry_name)?;
+        open_archive_entry(&self.key, entry_name.as_bytes(), &sealed)
+    }
+}
+
+impl Drop for OpenedRangeArchive {
+    fn drop(&mut self) {
+        StorageState::zeroize_bytes(&mut self.key);
+    }
+}
+
+impl StorageState {
+    /// Export screenshots, OCR results and metadata in `[start_ts, end_ts]` to a
+    /// password-protected archive at `out_path`.
+    ///
+    /// Images are decrypted with their CNG-wrapped row keys, so this requires an
+    /// authenticated session. `on_progress(done, total)` is called after each
+    /// screenshot. A partially written file is removed on failure.
+    pub fn export_range_archive(
+        &self,
+        start_ts: f64,
+        end_ts: f64,
+        password: &str,
+        out_path: &Path,
+        mut on_progress: impl FnMut(usize, usize),
+    ) -> Result<RangeArchiveSummary, String> {
+        if password.is_empty() {
+            return Err("Archive password must not be empty".to_string());
+        }
+        if end_ts < start_ts {
+            return Err("Invalid time range: end is before start".to_string());
+        }
+
+        let screenshots = self.get_screenshots_by_time_range(start_ts, end_ts)?;
+        self.record_access_audit(
+            "export_range",
+            &serde_json::json!({
+                "start_ts": start_ts,
+                "end_ts": end_ts,
+                "screenshots": screenshots.len(),
+            }),
+        )?;
+
+        let result = self.write_range_archive(
+            start_ts,
+            end_ts,
+            password,
+            out_path,
+            screenshots,
+            &mut on_progress,
+        );
+        if result.is_err() {
+            let _ = std::fs::remove_file(out_path);
+        }
+        result
+    }
+
+    fn write_range_archive(
+        &self,
+        start_ts: f64,
+        end_ts: f64,
+        password: &str,
+        out_path: &Path,
+        screenshots: Vec<ScreenshotRecord>,
+        on_progress: &mut dyn FnMut(usize, usize),
+    ) -> Result<RangeArchiveSummary, String> {
+        let salt = SaltString::generate(&mut rand::thread_rng());
+        let mut key = derive_archive_key(password, salt.as_str())?;
+
+        let file =
+            File::create(out_path).map_err(|e| format!("Failed to create archive file: {}", e))?;
+        let mut zip = zip::ZipWriter::new(file);
+        // Ciphertext does not compress; store entries as-is.
+        let stored: FileOptions<'_, ()> =
+            FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+
+        let total = screenshots.len();
+        let mut records = Vec::with_capacity(total);
+        let mut image_count = 0usize;
+        let mut ocr_count = 0usize;
+        let mut archived_bytes = 0u64;
+
+        for (index, screenshot) in screenshots.into_iter().enumerate() {
+            let ocr_results = self.get_screenshot_ocr_results(screenshot.id, None)?;
+            ocr_count += ocr_results.len();
+
+            let (image_entry, image_mime, image_bytes_hash) =
+                match self.read_image_bytes(&screenshot.image_path) {
+                    Ok((mut bytes, mime)) => {
+                        let entry_name = format!("images/{}.enc", screenshot.id);
+                        let bytes_hash = crate::capture::md5_hash(&bytes);
+                        let sealed = seal_archive_entry(&key, entry_name.as_bytes(), &bytes);
+                        Self::zeroize_bytes(&mut bytes);
+                        let sealed = sealed?;
+                        zip.start_file(entry_name.as_str(), stored)
+                            .map_err(|e| format!("Failed to write archive entry: {}", e))?;
+                        zip.write_all(&sealed)
+                            .map_err(|e| format!("Failed to write archive entry: {}", e))?;
+                        archived_bytes += sealed.len() as u64;
+                        image_count += 1;
+                        (Some(entry_name), Some(mime), Some(bytes_hash))
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            "Range archive: skipping image for screenshot {}: {}",
+                            screenshot.id,
+                            e
+                        );
+                        (None, None, None)
+                    }
+                };
+
+            records.push(RangeArchiveRecord {
+                screenshot,
+                ocr_results,
+                image_entry,
+                image_mime,
+                image_bytes_hash,
+            });
+            on_progress(index + 1, total);
+        }
+
+        // The manifest bytes are the associated data of the sealed records, so build
+        // it first and write exactly those bytes.
+        let manifest = RangeArchiveManifest {
+            format: RANGE_ARCHIVE_FORMAT.to_string(),
+            version: RANGE_ARCHIVE_VERSION,
+            salt: salt.as_str().to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            start_ts,
+            end_ts,
+            screenshot_count: records.len(),
+            image_count,
+        };
+        let manifest_json = serde_json::to_vec_pretty(&manifest)
+            .map_err(|e| format!("Failed to serialize archive manifest: {}", e))?;
+
+        let mut records_json = serde_json::to_vec(&records)
+            .map_err(|e| format!("Failed to serialize archive records: {}", e))?;
+        let sealed_records = seal_archive_entry(&key, &manifest_json, &records_json);
+        Self::zeroize_bytes(&mut records_json);
+        Self::zeroize_bytes(&mut key);
+        let sealed_records = sealed_records?;
+        zip.start_file(RANGE_ARCHIVE_RECORDS, stored)
+            .map_err(|e| format!("Failed to write archive entry: {}", e))?;
+        zip.write_all(&sealed_records)
+            .map_err(|e| format!("Failed to write archive entry: {}", e))?;
+        archived_bytes += sealed_records.len() as u64;
+
+        zip.start_file(RANGE_ARCHIVE_MANIFEST, stored)
+            .map_err(|e| format!("Failed to write archive entry: {}", e))?;
+        zip.write_all(&manifest_json)
+            .map_err(|e| format!("Failed to write archive entry: {}", e))?;
+
+        zip.finish()
+            .map_err(|e| format!("Failed to finalize archive: {}", e))?;
+
+        tracing::info!(
+            "Range archive: exported {} screenshots ({} images, {} OCR results) to {}",
+            records.len(),
+            image_count,
+            ocr_count,
+            out_path.display()
+        );
+
+        Ok(RangeArchiveSummary {
+            path: out_path.to_string_lossy().to_string(),
+            screenshots: records.len(),
+            images: image_count,
+            ocr_results: ocr_count,
+            missing_images: records.len() - image_count,
+            archive_bytes: archived_bytes,
+        })
+    }
+
+    /// Import a range archive produced by [`Self::export_range_archive`].
+    ///
+    /// Each screenshot is re-encrypted under a fresh row key wrapped for this machine,
+    /// inserted with its original capture time, and its OCR text is indexed into the
+    /// blind bitmap postings right away (rows fall back to the lazy indexer if the
+    /// HMAC key is unavailable). Screenshots whose image hash already exists are skipped;
+    /// so are records whose image entry does not decrypt or does not match its hash.
+    pub fn import_range_archive(
+        &self,
+        archive_path: &Path,
+        password: &str,
+        mut on_progress: impl FnMut(usize, usize),
+    ) -> Result<RangeArchiveImportSummary, String> {
+        let mut archive = OpenedRangeArchive::open(archive_path, password)?;
+        let records = std::mem::take(&mut archive.records);
+        let total = records.len();
+        self.record_access_audit(
+            "import_archive",
+            &serde_json::json!({ "screenshots": total }),
+        )?;
+
+        let mut summary = RangeArchiveImportSummary {
+            imported: 0,
+            duplicates: 0,
+            missing_images: 0,
+            rejected_images: 0,
+            ocr_results: 0,
+            indexed_ocr_results: 0,
+        };
+        let hmac_key = self.credential_state.get_hmac_key().ok();
+
+        for (index, record) in records.into_iter().enumerate() {
+            if self.screenshot_exists(&record.screenshot.image_hash)? {
+                summary.duplicates += 1;
+            } else if let Some(entry_name) = record.image_entry.as_deref() {
+                match archive.read_image(entry_name) {
+                    Err(e) => {
+                        tracing::warn!("Range archive: skipping unreadable {}: {}", entry_name, e);
+                        summary.missing_images += 1;
+                    }
+                    Ok(mut image) if !archive_image_matches(&record, &image) => {
+                        tracing::warn!(
+                            "Range archive: skipping {}, image does not match its hash",
+                            entry_name
+                        );
+                        Self::zeroize_bytes(&mut image);
+                        summary.rejected_images += 1;
+                    }
+                    Ok(mut image) => {
+                        let inserted = self.insert_archived_screenshot(&record, &image);
+                        Self::zeroize_bytes(&mut image);
+                        let ocr_rows = inserted?;
+
+                        summary.imported += 1;
+                        summary.ocr_results += ocr_rows.len();
+                        if let Some(hmac_key) = hmac_key.as_deref() {
+                            if !ocr_rows.is_empty() {
+                                summary.indexed_ocr_results +=
+                                    self.index_batch_internal(ocr_rows, hmac_key)?;
+                            }
+                        }
+                    }
+                }
+            } else {
+                summary.missing_images += 1;
+            }
+            on_progress(index + 1, total);
+        }
+
+        tracing::info!(
+            "Range archive: imported {} screenshots ({} duplicates, {} without image, {} rejected) from {} (exported {})",
+            summary.imported,
+            summary.duplicates,
+            summary.missing_images,
+            summary.rejected_images,
+            archive_path.display(),
+            archive.manifest.created_at
+        );
+        Ok(summary)
+    }
+
+    /// Write one archived screenshot as a committed row. Returns the inserted OCR rows
+    /// as `(id, text_enc, text_key_encrypted)` for bitmap indexing.
+    fn insert_archived_screenshot(
+        &self,
+        record: &RangeArchiveRecord,
+        image: &[u8],
+    ) -> Result<Vec<(i64, Vec<u8>, Vec<u8>)>, String> {
+        let screenshot = &record.screenshot;
+        chrono::NaiveDateTime::parse_from_str(&screenshot.created_at, "%Y-%m-%d %H:%M:%S")
+            .map_err(|e| format!("Invalid capture time {}: {}", screenshot.created_at, e))?;
+
+        let mut row_key = vec![0u8; 32];
+        rand::thread_rng().fill_bytes(&mut row_key);
+        let result = self.insert_archived_screenshot_with_key(record, image, &row_key);
+        Self::zeroize_bytes(&mut row_key);
+        result
+    }
+
+    fn insert_archived_screenshot_with_key(
+        &self,
+        record: &RangeArchiveRecord,
+        image: &[u8],
+        row_key: &[u8],
+    ) -> Result<Vec<(i64, Vec<u8>, Vec<u8>)>, String> {
+        let screenshot = &record.screenshot;
+        let encrypted_image = encrypt_with_master_key(row_key, image)
+            .map_err(|e| format!("Failed to encrypt image: {}", e))?;
+        let encrypted_row_key = self
+            .wrap_row_key_for_storage(row_key)
+            .map_err(|e| format!("Failed to wrap image row key: {}", e))?;
+        let encrypt_field = |value: Option<&str>, field: &str| -> Result<Option<Vec<u8>>, String> {
+            match value {
+                Some(v) if !v.is_empty() => encrypt_with_master_key(row_key, v.as_bytes())
+                    .map(Some)
+                    .map_err(|e| format!("Failed to encrypt {}: {}", field, e)),
+                _ => Ok(None),
+            }
+        };
+        let window_title_enc = encrypt_field(screenshot.window_title.as_deref(), "window title")?;
+        let process_name_enc = encrypt_field(screenshot.process_name.as_deref(), "process name")?;
+        let metadata_enc = encrypt_field(screenshot.metadata.as_deref(), "metadata")?;
+        let page_url_enc = encrypt_field(screenshot.page_url.as_deref(), "page_url")?;
+
+        // Name by capture time and hash so a batch import cannot collide on file names.
+        let filename = format!(
+            "screenshot_{}_{}.{}.enc",
+            screenshot
+                .created_at
+                .replace(['-', ':'], "")
+                .replace(' ', "_"),
+            screenshot.image_hash.chars().take(16).collect::<String>(),
+            image_extension_for_bytes(image)
+        );
+        let image_path = self
+            .screenshot_dir
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .join(&filename);
+        std::fs::write(&image_path, &encrypted_image)
+            .map_err(|e| format!("Failed to save encrypted image file: {}", e))?;
+        let image_path_str = self.to_relative_image_path(&image_path);
+
+        if let Err(e) = self.generate_thumbnail_from_data(image, &image_path, row_key) {
+            tracing::warn!("Failed to generate thumbnail during archive import: {}", e);
+        }
+
+        let plaintext_process_name = if self.plaintext_metadata_allowed() {
+            screenshot.process_name.clone()
+        } else {
+            None
+        };
+        let process_name_hash = self.process_name_hash(screenshot.process_name.as_deref());
+
+        let inserted = (|| {
+            let mut guard = self.get_connection_named("import_range_archive")?;
+            let conn = guard.as_mut().unwrap();
+
+            let page_icon_id = match screenshot.page_icon.as_deref() {
+                Some(value) if !value.is_empty() => {
+                    Some(self.get_or_create_page_icon_id(conn, value)?)
+                }
+                _ => None,
+            };
+            let link_set_id = match screenshot.visible_links.as_deref() {
+                Some(links) if !links.is_empty() => {
+                    Some(self.get_or_create_link_set_id(conn, links)?)
+                }
+                _ => None,
+            };
+
+            let tx = conn
+                .transaction()
+                .map_err(|e| format!("Failed to begin import transaction: {}", e))?;
+            tx.execute(
+                "INSERT INTO screenshots (
+                    image_path, image_hash, width, height,
+                    process_name, window_title_enc, process_name_enc, metadata_enc,
+                    content_key_encrypted, source, page_url_enc, page_icon_id, link_set_id,
+                    category, category_confidence, created_at, process_name_hash
+                 ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                params![
+                    &image_path_str,
+                    &screenshot.image_hash,
+                    screenshot.width,
+                    screenshot.height,
+                    plaintext_process_name,
+                    window_title_enc,
+                    process_name_enc,
+                    metadata_enc,
+                    encrypted_row_key,
+                    screenshot.source.as_deref(),
+                    page_url_enc,
+                    page_icon_id,
+                    link_set_id,
+                    screenshot.category.as_deref(),
+                    screenshot.category_confidence,
+                    &screenshot.created_at,
+                    process_name_hash,
+                ],
+            )
+            .map_err(|e| format!("Failed to insert screenshot: {}", e))?;
+            let screenshot_id = tx.last_insert_rowid();
+
+            let mut ocr_rows = Vec::with_capacity(record.ocr_results.len());
+            for ocr in &record.ocr_results {
+                let points = &ocr.box_coords;
+                if points.len() != 4 || points.iter().any(|p| p.len() != 2) {
+                    continue;
+                }
+                let (text_enc, text_key_encrypted) =
+                    self.encrypt_payload_with_row_key(ocr.text.as_bytes())?;
+                tx.execute(
+                    "INSERT INTO ocr_results (
+                        screenshot_id, text, text_hash, text_enc, text_key_encrypted, confidence,
+                        box_x1, box_y1, box_x2, box_y2,
+                        box_x3, box_y3, box_x4, box_y4
+                     ) VALUES (?, NULL, '', ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                    params![
+                        screenshot_id,
+                        text_enc,
+                        text_key_encrypted,
+                        ocr.confidence,
+                        points[0][0],
+                        points[0][1],
+                        points[1][0],
+                        points[1][1],
+                        points[2][0],
+                        points[2][1],
+                        points[3][0],
+                        points[3][1],
+                    ],
+                )
+                .map_err(|e| format!("Failed to insert OCR result: {}", e))?;
+                ocr_rows.push((tx.last_insert_rowid(), text_enc, text_key_encrypted));
+            }
+
+            tx.execute(
+                "INSERT OR REPLACE INTO screenshot_ocr_status (screenshot_id, status, engine, updated_at)
+                 VALUES (?1, 'completed', 'archive_import', CURRENT_TIMESTAMP)",
+                params![screenshot_id],
+            )
+            .map_err(|e| format!("Failed to record OCR status: {}", e))?;
+
+            tx.commit()
+                .map_err(|e| format!("Failed to commit import transaction: {}", e))?;
+            Ok::<_, String>(ocr_rows)
+        })();
+
+        match inserted {
+            Ok(ocr_rows) => {
+                self.ocr_row_count
+                    .fetch_add(ocr_rows.len() as u64, Ordering::Relaxed);
+                Ok(ocr_rows)
+            }
+            Err(e) => {
+                let _ = std::fs::remove_file(Self::thumbnail_path_for(&image_path));
+                let _ = std::fs::remove_file(&image_path);
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_entry_round_trips_and_rejects_wrong_password() {
+        let key = derive_archive_key("correct horse", "c2FsdHNhbHRzYWx0").unwrap();
+        let sealed = seal_archive_entry(&key, b"images/1.enc", b"screenshot bytes").unwrap();
+        assert_ne!(&sealed[NONCE_LEN..], b"screenshot bytes");
+        assert_eq!(
+            open_archive_entry(&key, b"images/1.enc", &sealed).unwrap(),
+            b"screenshot bytes"
+        );
+
+        let wrong = derive_archive_key("battery staple", "c2FsdHNhbHRzYWx0").unwrap();
+        assert!(open_archive_entry(&wrong, b"images/1.enc", &sealed).is_err());
+        assert!(open_archive_entry(&key, b"images/2.enc", &sealed).is_err());
+        assert!(open_archive_entry(&key, b"images/1.enc", &sealed[..4]).is_err());
+    }
+
+    fn write_test_archive(path: &Path, password: &str, format: &str) {
+        write_test_archive_with(path, password, format, |_| {}, "images/7.enc");
+    }
+
+    /// Writes a one-screenshot archive, letting the test edit the manifest after the
+    /// records were sealed and choose the name the image entry is stored under.
+    fn write_test_archive_with(
+        path: &Path,
+        password: &str,
+        format: &str,
+        edit_manifest: impl FnOnce(&mut RangeArchiveManifest),
+        stored_image_entry: &str,
+    ) {
+        let salt = SaltString::generate(&mut rand::thread_rng());
+        let key = derive_archive_key(password, salt.as_str()).unwrap();
+        let records = serde_json::json!([{
+            "screenshot": {
+                "id": 7,
+                "image_path": "screenshots/a.png.enc",
+                "image_hash": "hash-a",
+                "width": 10,
+                "height": 10,
+                "window_title": "Notes",
+                "process_name": "notepad.exe",
+                "created_at": "2026-03-02 10:00:00",
+                "metadata": null,
+                "timestamp": 1772445600
+            },
+            "ocr_results": [],
+            "image_entry": "images/7.enc",
+            "image_mime": "image/png"
+        }]);
+        let mut manifest = RangeArchiveManifest {
+            format: format.to_string(),
+            version: RANGE_ARCHIVE_VERSION,
+            salt: salt.as_str().to_string(),
+            created_at: "2026-03-03T00:00:00Z".to_string(),
+            start_ts: 0.0,
+            end_ts: 1.0,
+            screenshot_count: 1,
+            image_count: 1,
+        };
+
+        let manifest_json = serde_json::to_vec(&manifest).unwrap();
+        let sealed_records =
+            seal_archive_entry(&key, &manifest_json, records.to_string().as_bytes()).unwrap();
+        edit_manifest(&mut manifest);
+
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        let options: FileOptions<'_, ()> = FileOptions::default();
+        zip.start_file(RANGE_ARCHIVE_MANIFEST, options).unwrap();
+        zip.write_all(&serde_json::to_vec(&manifest).unwrap())
+            .unwrap();
+        zip.start_file(RANGE_ARCHIVE_RECORDS, options).unwrap();
+        zip.write_all(&sealed_records).unwrap();
+        zip.start_file(stored_image_entry, options).unwrap();
+        zip.write_all(&seal_archive_entry(&key, b"images/7.enc", b"png bytes").unwrap())
+            .unwrap();
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn opened_archive_decrypts_records_and_images() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("range.zip");
+        write_test_archive(&path, "secret", RANGE_ARCHIVE_FORMAT);
+
+        let mut archive = OpenedRangeArchive::open(&path, "secret").unwrap();
+        assert_eq!(archive.manifest.screenshot_count, 1);
+        assert_eq!(archive.records.len(), 1);
+        assert_eq!(archive.records[0].screenshot.image_hash, "hash-a");
+        assert_eq!(archive.read_image("images/7.enc").unwrap(), b"png bytes");
+
+        assert!(OpenedRangeArchive::open(&path, "wrong").is_err());
+
+        let other = temp.path().join("other.zip");
+        write_test_archive(&other, "secret", "something-else");
+        assert!(OpenedRangeArchive::open(&other, "secret").is_err());
+    }
+
+    #[test]
+    fn archived_images_must_match_their_recorded_hash() {
+        let mut record: RangeArchiveRecord = serde_json::from_value(serde_json::json!({
+            "screenshot": {
+                "id": 1,
+                "image_path": "screenshots/a.webp.enc",
+                "image_hash": crate::capture::md5_hash(b"captured frame"),
+                "width": 10,
+                "height": 10,
+                "window_title": null,
+                "process_name": null,
+                "created_at": "2026-03-02 10:00:00",
+                "metadata": null,
+                "timestamp": 1772445600
+            },
+            "ocr_results": [],
+            "image_entry": "images/1.enc",
+            "image_mime": "image/png"
+        }))
+        .unwrap();
+        assert!(archive_image_matches(&record, b"captured frame"));
+        assert!(!archive_image_matches(&record, b"something else"));
+
+        // Re-encoded on disk: the archived bytes carry their own hash.
+        record.image_bytes_hash = Some(crate::capture::md5_hash(b"webp frame"));
+        assert!(archive_image_matches(&record, b"webp frame"));
+        assert!(!archive_image_matches(&record, b"captured frame"));
+    }
+
+    #[test]
+    fn edited_manifest_and_renamed_entries_are_rejected() {
+        let temp = tempfile::tempdir().unwrap();
+
+        let edited = temp.path().join("edited.zip");
+        write_test_archive_with(
+            &edited,
+            "secret",
+            RANGE_ARCHIVE_FORMAT,
+            |manifest| manifest.end_ts = 99.0,
+            "images/7.enc",
+        );
+        assert!(OpenedRangeArchive::open(&edited, "secret").is_err());
+
+        // The records still point at images/7.enc, but the sealed bytes were moved.
+        let renamed = temp.path().join("renamed.zip");
+        write_test_archive_with(
+            &renamed,
+            "secret",
+            RANGE_ARCHIVE_FORMAT,
+            |_| {},
+            "images/8.enc",
+        );
+        let mut archive = OpenedRangeArchive::open(&renamed, "secret").unwrap();
+        assert!(archive.read_image("images/7.enc").is_err());
+        assert!(archive.read_image("images/8.enc").is_err());
+    }
+}
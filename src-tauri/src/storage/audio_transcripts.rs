@@ -0,0 +1,381 @@
+//! Audio transcripts.
+//!
+//! The Python monitor records audio in fixed-length chunks, transcribes them locally
+//! with Whisper and saves one transcript per chunk over reverse IPC. The text,
+//! language and segment timings are sealed with a per-row key; bigrams of the text
+//! are hashed into `audio_bitmap_index` so transcripts are searched like OCR text.
+//! Transcripts saved while the master key is locked are indexed before the next
+//! search, and deletes subtract IDs from the postings without decrypting.
+
+use std::collections::HashSet;
+
+use roaring::RoaringBitmap;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use super::{AudioTranscript, StorageState, TranscriptSegment};
+
+const AUDIO_INDEX_TABLE: &str = "audio_bitmap_index";
+
+/// Unindexed transcripts decrypted per search.
+const AUDIO_INDEX_BATCH: i64 = 200;
+
+/// Plaintext sealed into `payload_enc`.
+#[derive(Serialize, Deserialize)]
+struct AudioTranscriptPayload {
+    language: Option<String>,
+    segments: Vec<TranscriptSegment>,
+}
+
+/// `(id, started_at, ended_at, source, payload_enc, payload_key_encrypted)`.
+type EncryptedTranscriptRow = (i64, i64, i64, String, Vec<u8>, Vec<u8>);
+
+const TRANSCRIPT_COLUMNS: &str =
+    "id, started_at, ended_at, source, payload_enc, payload_key_encrypted";
+
+fn transcript_text(segments: &[TranscriptSegment]) -> String {
+    segments
+        .iter()
+        .map(|segment| segment.text.trim())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn transcript_token_hashes(segments: &[TranscriptSegment], hmac_key: &[u8]) -> Vec<String> {
+    StorageState::bigram_tokenize(&transcript_text(segments).to_lowercase())
+        .iter()
+        .map(|token| StorageState::compute_hmac_hash(token, hmac_key))
+        .collect()
+}
+
+impl StorageState {
+    /// Store the transcript of the chunk recorded from `source` in
+    /// `[started_at, ended_at)` (Unix ms). Returns `None` when the transcript is empty
+    /// or this chunk was already saved.
+    pub fn add_audio_transcript(
+        &self,
+        started_at: i64,
+        ended_at: i64,
+        source: &str,
+        language: Option<&str>,
+        segments: Vec<TranscriptSegment>,
+    ) -> Result<Option<i64>, String> {
+        if ended_at < started_at {
+            return Err("Audio transcript ends before it starts".to_string());
+        }
+        if transcript_text(&segments).is_empty() {
+            return Ok(None);
+        }
+        let hmac_key = self.ocr_hmac_key_for_save();
+        let token_hashes = hmac_key
+            .as_deref()
+            .map(|key| transcript_token_hashes(&segments, key))
+            .unwrap_or_default();
+        let payload = serde_json::to_vec(&AudioTranscriptPayload {
+            language: language.map(str::to_string),
+            segments,
+        })
+        .map_err(|e| format!("Failed to serialize audio transcript: {}", e))?;
+        let (payload_enc, payload_key_enc) = self.encrypt_payload_with_row_key(&payload)?;
+
+        let mut guard = self.get_connection_named("add_audio_transcript")?;
+        let conn = guard.as_mut().unwrap();
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start audio transcript transaction: {}", e))?;
+        let inserted = tx
+            .execute(
+                "INSERT OR IGNORE INTO audio_transcripts
+                    (started_at, ended_at, source, payload_enc, payload_key_encrypted, indexed)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    started_at,
+                    ended_at,
+                    source,
+                    payload_enc,
+                    payload_key_enc,
+                    hmac_key.is_some()
+                ],
+            )
+            .map_err(|e| format!("Failed to insert audio transcript: {}", e))?;
+        if inserted == 0 {
+            return Ok(None);
+        }
+        let id = tx.last_insert_rowid();
+        if hmac_key.is_some() {
+            Self::add_row_postings(&tx, AUDIO_INDEX_TABLE, id, &token_hashes)?;
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed to commit audio transcript: {}", e))?;
+        Ok(Some(id))
+    }
+
+    /// Transcripts of chunks that started in `[start_ms, end_ms)`, newest first.
+    pub fn list_audio_transcripts(
+        &self,
+        start_ms: Option<i64>,
+        end_ms: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<AudioTranscript>, String> {
+        let rows: Vec<EncryptedTranscriptRow> = {
+            let conn = self.open_read_connection_named("list_audio_transcripts")?;
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT {} FROM audio_transcripts
+                     WHERE started_at >= ?1 AND started_at < ?2
+                     ORDER BY started_at DESC, id DESC
+                     LIMIT ?3",
+                    TRANSCRIPT_COLUMNS
+                ))
+                .map_err(|e| format!("Failed to prepare audio transcript query: {}", e))?;
+            let rows = stmt
+                .query_map(
+                    params![
+                        start_ms.unwrap_or(0),
+                        end_ms.unwrap_or(i64::MAX),
+                        limit.clamp(1, 1_000)
+                    ],
+                    |row| {
+                        Ok((
+                            row.get(0)?,
+                            row.get(1)?,
+                            row.get(2)?,
+                            row.get(3)?,
+                            row.get(4)?,
+                            row.get(5)?,
+                        ))
+                    },
+                )
+                .map_err(|e| format!("Failed to query audio transcripts: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows
+        };
+        self.decrypt_transcript_rows(rows)
+    }
+
+    /// Transcripts containing every whitespace-separated keyword of `query`, newest
+    /// first.
+    pub fn search_audio_transcripts(
+        &self,
+        query: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<AudioTranscript>, String> {
+        if !self.is_read_only() {
+            self.index_pending_audio_transcripts()?;
+        }
+        let hmac_key = self.credential_state.get_hmac_key()?;
+        let tokens: HashSet<String> = query
+            .split_whitespace()
+            .flat_map(|keyword| Self::bigram_tokenize(&keyword.to_lowercase()))
+            .collect();
+
+        let mut rows: Vec<EncryptedTranscriptRow> = {
+            let conn = self.open_read_connection_named("search_audio_transcripts")?;
+            let matches =
+                Self::blind_postings_matching_all(&conn, AUDIO_INDEX_TABLE, &hmac_key, tokens)?;
+            let ids: Vec<i64> = matches.iter().map(i64::from).collect();
+
+            let mut rows: Vec<EncryptedTranscriptRow> = Vec::new();
+            for chunk in ids.chunks(500) {
+                let placeholders = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+                let sql = format!(
+                    "SELECT {} FROM audio_transcripts WHERE id IN ({})",
+                    TRANSCRIPT_COLUMNS, placeholders
+                );
+                let id_params: Vec<&dyn rusqlite::ToSql> =
+                    chunk.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
+                let mut stmt = conn
+                    .prepare(&sql)
+                    .map_err(|e| format!("Failed to prepare audio transcript search: {}", e))?;
+                let chunk_rows = stmt
+                    .query_map(id_params.as_slice(), |row| {
+                        Ok((
+                            row.get(0)?,
+                            row.get(1)?,
+                            row.get(2)?,
+                            row.get(3)?,
+                            row.get(4)?,
+                            row.get(5)?,
+                        ))
+                    })
+                    .map_err(|e| format!("Failed to search audio transcripts: {}", e))?;
+                rows.extend(chunk_rows.filter_map(|r| r.ok()));
+            }
+            rows
+        };
+
+        rows.sort_by(|a, b| b.1.cmp(&a.1).then(b.0.cmp(&a.0)));
+        let page: Vec<EncryptedTranscriptRow> = rows
+            .into_iter()
+            .skip(offset.max(0) as usize)
+            .take(limit.clamp(1, 500) as usize)
+            .collect();
+        self.decrypt_transcript_rows(page)
+    }
+
+    /// Index transcripts saved while the master key was locked or being rotated.
+    /// Returns how many were indexed; the rest wait for the next call.
+    fn index_pending_audio_transcripts(&self) -> Result<usize, String> {
+        let Some(hmac_key) = self.ocr_hmac_key_for_save() else {
+            return Ok(0);
+        };
+        let pending: Vec<(i64, Vec<u8>, Vec<u8>)> = {
+            let conn = self.open_read_connection_named("index_pending_audio_transcripts")?;
+            let mut stmt = conn
+                .prepare(
+                    "SELECT id, payload_enc, payload_key_encrypted
+                     FROM audio_transcripts WHERE indexed = 0 ORDER BY id LIMIT ?1",
+                )
+                .map_err(|e| format!("Failed to prepare audio index query: {}", e))?;
+            let rows = stmt
+                .query_map(params![AUDIO_INDEX_BATCH], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+                })
+                .map_err(|e| format!("Failed to query unindexed audio transcripts: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows
+        };
+        if pending.is_empty() {
+            return Ok(0);
+        }
+
+        let mut hashed: Vec<(i64, Vec<String>)> = Vec::with_capacity(pending.len());
+        for (id, payload_enc, payload_key_enc) in pending {
+            let payload = self.decrypt_transcript_payload(&payload_enc, &payload_key_enc)?;
+            hashed.push((id, transcript_token_hashes(&payload.segments, &hmac_key)));
+        }
+
+        let mut guard = self.get_connection_named("index_pending_audio_transcripts")?;
+        let conn = guard.as_mut().unwrap();
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start audio index transaction: {}", e))?;
+        for (id, token_hashes) in &hashed {
+            let updated = tx
+                .execute(
+                    "UPDATE audio_transcripts SET indexed = 1 WHERE id = ?1 AND indexed = 0",
+                    params![id],
+                )
+                .map_err(|e| format!("Failed to mark audio transcript indexed: {}", e))?;
+            // Deleted or indexed by a concurrent search in the meantime.
+            if updated > 0 {
+                Self::add_row_postings(&tx, AUDIO_INDEX_TABLE, *id, token_hashes)?;
+            }
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed to commit audio index: {}", e))?;
+        Ok(hashed.len())
+    }
+
+    /// Delete transcripts and unlink them from the search index. Returns how many
+    /// existed.
+    pub fn delete_audio_transcripts(&self, transcript_ids: &[i64]) -> Result<usize, String> {
+        if transcript_ids.is_empty() {
+            return Ok(0);
+        }
+        let mut removed = RoaringBitmap::new();
+        for id in transcript_ids {
+            removed.insert(Self::posting_id(*id)?);
+        }
+
+        let mut guard = self.get_connection_named("delete_audio_transcripts")?;
+        let conn = guard.as_mut().unwrap();
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start audio transcript delete: {}", e))?;
+        let mut deleted = 0;
+        {
+            let mut stmt = tx
+                .prepare_cached("DELETE FROM audio_transcripts WHERE id = ?1")
+                .map_err(|e| format!("Failed to prepare audio transcript delete: {}", e))?;
+            for id in transcript_ids {
+                deleted += stmt
+                    .execute(params![id])
+                    .map_err(|e| format!("Failed to delete audio transcript: {}", e))?;
+            }
+        }
+        Self::remove_row_postings(&tx, AUDIO_INDEX_TABLE, &removed)?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit audio transcript delete: {}", e))?;
+        Ok(deleted)
+    }
+
+    /// Delete transcripts of chunks that started before `cutoff_ms`. Runs without
+    /// decrypting. Returns how many were deleted.
+    pub fn prune_audio_transcripts(&self, cutoff_ms: i64) -> Result<usize, String> {
+        let expired: Vec<i64> = {
+            let conn = self.open_read_connection_named("prune_audio_transcripts")?;
+            let mut stmt = conn
+                .prepare("SELECT id FROM audio_transcripts WHERE started_at < ?1")
+                .map_err(|e| format!("Failed to prepare audio retention query: {}", e))?;
+            let ids = stmt
+                .query_map(params![cutoff_ms], |row| row.get(0))
+                .map_err(|e| format!("Failed to query expired audio transcripts: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            ids
+        };
+        self.delete_audio_transcripts(&expired)
+    }
+
+    fn decrypt_transcript_payload(
+        &self,
+        payload_enc: &[u8],
+        payload_key_enc: &[u8],
+    ) -> Result<AudioTranscriptPayload, String> {
+        let bytes = self.decrypt_payload_with_row_key(payload_enc, payload_key_enc)?;
+        serde_json::from_slice(&bytes)
+            .map_err(|e| format!("Failed to parse audio transcript: {}", e))
+    }
+
+    fn decrypt_transcript_rows(
+        &self,
+        rows: Vec<EncryptedTranscriptRow>,
+    ) -> Result<Vec<AudioTranscript>, String> {
+        rows.into_iter()
+            .map(
+                |(id, started_at, ended_at, source, payload_enc, payload_key_enc)| {
+                    let payload =
+                        self.decrypt_transcript_payload(&payload_enc, &payload_key_enc)?;
+                    Ok(AudioTranscript {
+                        id,
+                        started_at,
+                        ended_at,
+                        source,
+                        language: payload.language,
+                        text: transcript_text(&payload.segments),
+                        segments: payload.segments,
+                    })
+                },
+            )
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(text: &str) -> TranscriptSegment {
+        TranscriptSegment {
+            start_ms: 0,
+            end_ms: 1_000,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn transcript_text_joins_non_blank_segments() {
+        let segments = vec![
+            segment(" Let's ship "),
+            segment("  "),
+            segment("on Friday."),
+        ];
+        assert_eq!(transcript_text(&segments), "Let's ship on Friday.");
+        assert!(transcript_text(&[segment(" ")]).is_empty());
+    }
+}
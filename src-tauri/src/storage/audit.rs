@@ -0,0 +1,182 @@
+//! Append-only access audit log of decrypted content.
+//!
+//! Every command that hands decrypted images, OCR text, notes, clipboard history or
+//! audio transcripts to a caller appends an entry naming the caller (`app` for the
+//! UI, `mcp` for MCP clients), the action and the rows involved. The target is sealed with a fresh row key, and triggers
+//! reject `UPDATE`/`DELETE` on the table, so entries can be read back but not
+//! rewritten through SQL.
+//!
+//! Outside compliance mode a failure to record is logged and the read proceeds; with
+//! compliance mode on it fails the read. Guest (read-only) sessions cannot write and
+//! are not audited.
+
+use rusqlite::params;
+use serde_json::Value as JsonValue;
+
+use super::{AccessAuditEntry, StorageState};
+
+/// Actor recorded for reads issued by the desktop UI.
+pub(crate) const AUDIT_ACTOR_APP: &str = "app";
+/// Actor recorded for reads issued by MCP clients.
+pub(crate) const AUDIT_ACTOR_MCP: &str = "mcp";
+
+impl StorageState {
+    /// Appends an audit entry for a UI read. `target` describes what was read
+    /// (e.g. `{ "screenshot_id": 1 }`).
+    pub(crate) fn record_access_audit(
+        &self,
+        action: &str,
+        target: &JsonValue,
+    ) -> Result<(), String> {
+        self.record_access_audit_by(AUDIT_ACTOR_APP, action, target)
+    }
+
+    /// Appends an audit entry for a read issued by `actor`.
+    pub(crate) fn record_access_audit_by(
+        &self,
+        actor: &str,
+        action: &str,
+        target: &JsonValue,
+    ) -> Result<(), String> {
+        if self.is_read_only() {
+            return Ok(());
+        }
+        match self.insert_access_audit(actor, action, target) {
+            Ok(()) => Ok(()),
+            Err(e) if self.compliance_mode_enabled() => Err(e),
+            Err(e) => {
+                tracing::warn!("[AUDIT] failed to record {} by {}: {}", action, actor, e);
+                Ok(())
+            }
+        }
+    }
+
+    fn insert_access_audit(
+        &self,
+        actor: &str,
+        action: &str,
+        target: &JsonValue,
+    ) -> Result<(), String> {
+        let (target_enc, target_key) =
+            self.encrypt_payload_with_row_key(target.to_string().as_bytes())?;
+
+        let guard = self.get_connection_named("record_access_audit")?;
+        let conn = guard.as_ref().unwrap();
+        conn.execute(
+            "INSERT INTO access_audit_log (actor, action, target_enc, target_key_encrypted)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![actor, action, target_enc, target_key],
+        )
+        .map_err(|e| format!("Failed to record access audit: {}", e))?;
+        Ok(())
+    }
+
+    /// Audit entries, newest first, optionally limited to one `action`. Targets that
+    /// cannot be decrypted are returned as `None`.
+    pub fn get_access_audit_log(
+        &self,
+        limit: i64,
+        offset: i64,
+        action: Option<&str>,
+    ) -> Result<Vec<AccessAuditEntry>, String> {
+        type AuditRow = (
+            i64,
+            String,
+            Option<String>,
+            String,
+            Option<Vec<u8>>,
+            Option<Vec<u8>>,
+        );
+        let rows: Vec<AuditRow> = {
+            let guard = self.get_connection_named("get_access_audit_log")?;
+            let conn = guard.as_ref().unwrap();
+            let mut stmt = conn
+                .prepare(
+                    "SELECT id, created_at, actor, action, target_enc, target_key_encrypted
+                     FROM access_audit_log
+                     WHERE ?1 IS NULL OR action = ?1
+                     ORDER BY id DESC
+                     LIMIT ?2 OFFSET ?3",
+                )
+                .map_err(|e| format!("Failed to prepare audit log query: {}", e))?;
+            let rows = stmt
+                .query_map(
+                    params![action, limit.clamp(1, 1000), offset.max(0)],
+                    |row| {
+                        Ok((
+                            row.get(0)?,
+                            row.get(1)?,
+                            row.get(2)?,
+                            row.get(3)?,
+                            row.get(4)?,
+                            row.get(5)?,
+                        ))
+                    },
+                )
+                .map_err(|e| format!("Failed to query audit log: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows
+        };
+
+        Ok(rows
+            .into_iter()
+            .map(|(id, created_at, actor, action, target_enc, target_key)| {
+                let target = match (target_enc, target_key) {
+                    (Some(data), Some(key)) => self
+                        .decrypt_payload_with_row_key(&data, &key)
+                        .ok()
+                        .and_then(|bytes| serde_json::from_slice(&bytes).ok()),
+                    _ => None,
+                };
+                AccessAuditEntry {
+                    id,
+                    created_at,
+                    // Entries written before actors were recorded all came from the UI.
+                    actor: actor.unwrap_or_else(|| AUDIT_ACTOR_APP.to_string()),
+                    action,
+                    target,
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_storage;
+
+    #[test]
+    fn audit_log_rejects_updates_and_deletes() {
+        let (_temp, storage) = test_storage();
+        let guard = storage.db.lock().unwrap_or_else(|e| e.into_inner());
+        let connection = guard.as_ref().expect("test database");
+        connection
+            .execute(
+                "INSERT INTO access_audit_log (actor, action, target_enc, target_key_encrypted)
+                 VALUES ('mcp', 'view_image', NULL, NULL)",
+                [],
+            )
+            .expect("append entry");
+
+        assert!(connection
+            .execute("UPDATE access_audit_log SET action = 'noop'", [])
+            .is_err());
+        assert!(connection
+            .execute("DELETE FROM access_audit_log", [])
+            .is_err());
+        drop(guard);
+
+        let entries = storage
+            .get_access_audit_log(10, 0, Some("view_image"))
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].actor, "mcp");
+        assert!(entries[0].target.is_none());
+        assert!(storage
+            .get_access_audit_log(10, 0, Some("view_details"))
+            .unwrap()
+            .is_empty());
+    }
+}
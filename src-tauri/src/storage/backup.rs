@@ -0,0 +1,482 @@
+//! Incremental backups of the data directory to a second location.
+//!
+//! A backup is a `carbonpaper-backup/` folder inside the destination chosen by the
+//! user, typically on an external drive. It holds a consistent snapshot of
+//! `screenshots.db`, taken with the SQLite backup API from a read connection so
+//! capture keeps writing, and a mirror of the image directories and the credential
+//! files at the data-dir root. Everything stays encrypted exactly as it is on disk,
+//! so a backup is only readable with this machine's key.
+//!
+//! `manifest.json` records the size, modification time and SHA-256 of every file.
+//! The next run copies only files whose size or modification time changed, and drops
+//! files that left the data directory. Copies are written next to their target,
+//! re-read and compared against the source hash before they replace the previous
+//! version, and the manifest is replaced last, so an interrupted run leaves the
+//! previous backup usable. The `backup` scheduled job runs this against the folder
+//! saved under [`BACKUP_DIR_REGISTRY_KEY`].
+
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+
+use rusqlite::backup::{Backup, StepResult};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
+
+use super::cold_storage::COLD_STORAGE_DIR;
+use super::{BackupReport, StorageState};
+use crate::credential_manager::{
+    derive_db_key_from_public_key, get_cached_public_key, load_public_key_from_file,
+};
+
+/// Registry value holding the destination used by the scheduled job.
+pub(crate) const BACKUP_DIR_REGISTRY_KEY: &str = "backup_dir";
+/// Folder created inside the chosen destination.
+pub(super) const BACKUP_FOLDER: &str = "carbonpaper-backup";
+pub(super) const MANIFEST_FILE: &str = "manifest.json";
+pub(super) const MANIFEST_FORMAT: &str = "carbonpaper-backup";
+pub(super) const MANIFEST_VERSION: u32 = 1;
+pub(super) const DATABASE_FILE: &str = "screenshots.db";
+/// Data-dir subdirectories mirrored into the backup.
+const BACKUP_DIRS: &[&str] = &["screenshots", COLD_STORAGE_DIR, "shards"];
+/// Root files never mirrored: the live database, which is snapshotted instead, and
+/// the native messaging token, which belongs to this installation.
+const SKIPPED_ROOT_FILES: &[&str] = &[
+    "screenshots.db",
+    "screenshots.db-wal",
+    "screenshots.db-shm",
+    "screenshots.db-journal",
+    "nmh_auth_token",
+];
+/// Suffix of files still being written, by capture or by the backup itself.
+const IN_FLIGHT_SUFFIXES: &[&str] = &[".pending", ".partial"];
+const PARTIAL_SUFFIX: &str = ".partial";
+
+/// Size, modification time (Unix seconds) and SHA-256 of a backed-up file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(super) struct BackupFileEntry {
+    pub bytes: u64,
+    pub modified: i64,
+    pub sha256: String,
+}
+
+/// `manifest.json` of a backup folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct BackupManifest {
+    pub format: String,
+    pub version: u32,
+    pub created_at: String,
+    pub app_version: String,
+    pub database: BackupFileEntry,
+    /// Data-dir-relative paths with forward slashes.
+    pub files: BTreeMap<String, BackupFileEntry>,
+}
+
+impl BackupManifest {
+    /// Read and validate the manifest of a backup folder.
+    pub(super) fn load(backup_root: &Path) -> Result<Self, String> {
+        let bytes = std::fs::read(backup_root.join(MANIFEST_FILE))
+            .map_err(|e| format!("Failed to read backup manifest: {}", e))?;
+        let manifest: Self = serde_json::from_slice(&bytes)
+            .map_err(|e| format!("Failed to parse backup manifest: {}", e))?;
+        if manifest.format != MANIFEST_FORMAT {
+            return Err("Not a CarbonPaper backup manifest".to_string());
+        }
+        if manifest.version > MANIFEST_VERSION {
+            return Err(format!(
+                "Backup manifest version {} is newer than supported version {}",
+                manifest.version, MANIFEST_VERSION
+            ));
+        }
+        Ok(manifest)
+    }
+}
+
+/// The destination folder configured for the scheduled job.
+pub fn backup_dir() -> Option<PathBuf> {
+    crate::registry_config::get_string(BACKUP_DIR_REGISTRY_KEY)
+        .filter(|dir| !dir.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+/// Save or clear (`None`) the destination folder used by the scheduled job.
+pub fn set_backup_dir(dir: Option<&str>) -> Result<(), String> {
+    match dir.map(str::trim).filter(|dir| !dir.is_empty()) {
+        Some(dir) => {
+            if !Path::new(dir).is_dir() {
+                return Err(format!("Backup folder does not exist: {}", dir));
+            }
+            crate::registry_config::set_string(BACKUP_DIR_REGISTRY_KEY, dir)
+        }
+        None => crate::registry_config::delete_value(BACKUP_DIR_REGISTRY_KEY),
+    }
+}
+
+fn modified_secs(metadata: &std::fs::Metadata) -> i64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn is_in_flight(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|s| s.to_str())
+        .is_some_and(|name| IN_FLIGHT_SUFFIXES.iter().any(|s| name.ends_with(s)))
+}
+
+/// SHA-256 of a file, hex encoded.
+pub(super) fn file_sha256(path: &Path) -> Result<String, String> {
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let read = file
+            .read(&mut buf)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Copy `src` to `dst` through a `.partial` file, verify the written copy against the
+/// source hash, then move it into place. Returns the hash.
+pub(super) fn copy_verified(src: &Path, dst: &Path) -> Result<String, String> {
+    if let Some(parent) = dst.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let partial = PathBuf::from(format!("{}{}", dst.display(), PARTIAL_SUFFIX));
+    let source_hash = {
+        let mut input = std::fs::File::open(src)
+            .map_err(|e| format!("Failed to open {}: {}", src.display(), e))?;
+        let mut output = std::fs::File::create(&partial)
+            .map_err(|e| format!("Failed to create {}: {}", partial.display(), e))?;
+        let mut hasher = Sha256::new();
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            let read = input
+                .read(&mut buf)
+                .map_err(|e| format!("Failed to read {}: {}", src.display(), e))?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buf[..read]);
+            output
+                .write_all(&buf[..read])
+                .map_err(|e| format!("Failed to write {}: {}", partial.display(), e))?;
+        }
+        output
+            .sync_all()
+            .map_err(|e| format!("Failed to flush {}: {}", partial.display(), e))?;
+        hex::encode(hasher.finalize())
+    };
+    let written_hash = file_sha256(&partial)?;
+    if written_hash != source_hash {
+        let _ = std::fs::remove_file(&partial);
+        return Err(format!("Verification failed for {}", dst.display()));
+    }
+    std::fs::rename(&partial, dst)
+        .map_err(|e| format!("Failed to move {} into place: {}", dst.display(), e))?;
+    Ok(source_hash)
+}
+
+/// Files of the data directory that belong in a backup, as `(relative path, path)`.
+fn backup_source_files(data_dir: &Path) -> Vec<(String, PathBuf)> {
+    let mut files = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(data_dir) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if path.is_file()
+                && !SKIPPED_ROOT_FILES.contains(&name.as_str())
+                && !is_in_flight(&path)
+            {
+                files.push((name, path));
+            }
+        }
+    }
+    for dir in BACKUP_DIRS {
+        for entry in WalkDir::new(data_dir.join(dir))
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file() && !is_in_flight(e.path()))
+        {
+            if let Ok(rel) = entry.path().strip_prefix(data_dir) {
+                files.push((rel.to_string_lossy().replace('\\', "/"), entry.into_path()));
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Bring the mirrored files under `backup_root` up to date with `data_dir`, copying
+/// only files that changed since `previous`. Returns the new file entries.
+fn sync_backup_files(
+    data_dir: &Path,
+    backup_root: &Path,
+    previous: &BTreeMap<String, BackupFileEntry>,
+    report: &mut BackupReport,
+    on_progress: &dyn Fn(&str, usize, usize),
+) -> Result<BTreeMap<String, BackupFileEntry>, String> {
+    let sources = backup_source_files(data_dir);
+    let total = sources.len();
+    let mut files = BTreeMap::new();
+    for (index, (rel, path)) in sources.into_iter().enumerate() {
+        on_progress("files", index, total);
+        // A file deleted since the scan is simply not part of this backup.
+        let Ok(metadata) = std::fs::metadata(&path) else {
+            continue;
+        };
+        let modified = modified_secs(&metadata);
+        let target = backup_root.join(&rel);
+        let unchanged = previous.get(&rel).filter(|entry| {
+            entry.bytes == metadata.len()
+                && entry.modified == modified
+                && std::fs::metadata(&target).is_ok_and(|m| m.len() == entry.bytes)
+        });
+        let entry = match unchanged {
+            Some(entry) => {
+                report.files_unchanged += 1;
+                entry.clone()
+            }
+            None => {
+                let sha256 = copy_verified(&path, &target)?;
+                report.files_copied += 1;
+                report.bytes_copied += metadata.len();
+                BackupFileEntry {
+                    bytes: metadata.len(),
+                    modified,
+                    sha256,
+                }
+            }
+        };
+        files.insert(rel, entry);
+    }
+    on_progress("files", total, total);
+
+    for rel in previous.keys().filter(|rel| !files.contains_key(*rel)) {
+        if std::fs::remove_file(backup_root.join(rel)).is_ok() {
+            report.files_removed += 1;
+        }
+    }
+    Ok(files)
+}
+
+impl StorageState {
+    /// Hex SQLCipher key of the main database.
+    pub(super) fn database_key_hex(&self) -> Result<String, String> {
+        let public_key = get_cached_public_key(&self.credential_state)
+            .or_else(|| load_public_key_from_file(&self.credential_state).ok())
+            .ok_or_else(|| "Public key not initialized".to_string())?;
+        Ok(hex::encode(derive_db_key_from_public_key(&public_key)))
+    }
+
+    /// Write a consistent, still encrypted copy of the database to `target` and check it.
+    fn snapshot_database(&self, target: &Path) -> Result<BackupFileEntry, String> {
+        let key_hex = self.database_key_hex()?;
+        let partial = PathBuf::from(format!("{}{}", target.display(), PARTIAL_SUFFIX));
+        let _ = std::fs::remove_file(&partial);
+        {
+            let source = self.open_read_connection_named("snapshot_database")?;
+            let mut copy = Connection::open(&partial)
+                .map_err(|e| format!("Failed to create database snapshot: {}", e))?;
+            copy.execute_batch(&format!("PRAGMA key = \"x'{}'\";", key_hex))
+                .map_err(|e| format!("Failed to set snapshot key: {}", e))?;
+            {
+                let backup = Backup::new(&source, &mut copy)
+                    .map_err(|e| format!("Failed to start database snapshot: {}", e))?;
+                // One step copies every page inside a single read transaction.
+                match backup.step(-1) {
+                    Ok(StepResult::Done) => {}
+                    Ok(other) => {
+                        return Err(format!("Database snapshot did not finish: {:?}", other))
+                    }
+                    Err(e) => return Err(format!("Failed to snapshot database: {}", e)),
+                }
+            }
+            let check: String = copy
+                .query_row("PRAGMA quick_check", [], |row| row.get(0))
+                .map_err(|e| format!("Failed to check database snapshot: {}", e))?;
+            if check != "ok" {
+                let _ = std::fs::remove_file(&partial);
+                return Err(format!("Database snapshot failed its check: {}", check));
+            }
+        }
+        let metadata = std::fs::metadata(&partial)
+            .map_err(|e| format!("Failed to stat database snapshot: {}", e))?;
+        let entry = BackupFileEntry {
+            bytes: metadata.len(),
+            modified: modified_secs(&metadata),
+            sha256: file_sha256(&partial)?,
+        };
+        std::fs::rename(&partial, target)
+            .map_err(|e| format!("Failed to move database snapshot into place: {}", e))?;
+        Ok(entry)
+    }
+
+    /// Back up the database and encrypted files into `destination/carbonpaper-backup`.
+    /// `on_progress(phase, done, total)` is called with phase `database` or `files`.
+    pub fn backup_now(
+        &self,
+        destination: &Path,
+        on_progress: &dyn Fn(&str, usize, usize),
+    ) -> Result<BackupReport, String> {
+        if self.migration_in_progress.load(Ordering::SeqCst) {
+            return Err("A storage migration is in progress".to_string());
+        }
+        if self
+            .backup_in_progress
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Err("ALREADY_RUNNING".to_string());
+        }
+        let result = self.run_backup(destination, on_progress);
+        self.backup_in_progress.store(false, Ordering::SeqCst);
+        result
+    }
+
+    fn run_backup(
+        &self,
+        destination: &Path,
+        on_progress: &dyn Fn(&str, usize, usize),
+    ) -> Result<BackupReport, String> {
+        let started = std::time::Instant::now();
+        if !destination.is_dir() {
+            return Err(format!(
+                "Backup folder does not exist: {}",
+                destination.display()
+            ));
+        }
+        let data_dir = self
+            .data_dir
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let backup_root = destination.join(BACKUP_FOLDER);
+        if backup_root.starts_with(&data_dir) {
+            return Err("The backup folder cannot be inside the data directory".to_string());
+        }
+        std::fs::create_dir_all(&backup_root)
+            .map_err(|e| format!("Failed to create backup folder: {}", e))?;
+        let previous = match BackupManifest::load(&backup_root) {
+            Ok(manifest) => manifest.files,
+            Err(_) => BTreeMap::new(),
+        };
+
+        let mut report = BackupReport {
+            destination: backup_root.display().to_string(),
+            ..Default::default()
+        };
+        // The snapshot comes first: every file it refers to already exists and is
+        // picked up by the file pass below.
+        on_progress("database", 0, 1);
+        let database = self.snapshot_database(&backup_root.join(DATABASE_FILE))?;
+        report.database_bytes = database.bytes;
+        on_progress("database", 1, 1);
+
+        let files =
+            sync_backup_files(&data_dir, &backup_root, &previous, &mut report, on_progress)?;
+        let manifest = BackupManifest {
+            format: MANIFEST_FORMAT.to_string(),
+            version: MANIFEST_VERSION,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            database,
+            files,
+        };
+        let json = serde_json::to_vec_pretty(&manifest)
+            .map_err(|e| format!("Failed to serialize backup manifest: {}", e))?;
+        let partial = backup_root.join(format!("{}{}", MANIFEST_FILE, PARTIAL_SUFFIX));
+        std::fs::write(&partial, json)
+            .map_err(|e| format!("Failed to write backup manifest: {}", e))?;
+        std::fs::rename(&partial, backup_root.join(MANIFEST_FILE))
+            .map_err(|e| format!("Failed to move backup manifest into place: {}", e))?;
+
+        report.duration_ms = started.elapsed().as_millis() as i64;
+        tracing::info!(
+            "[BACKUP] {}: copied={} ({} bytes), unchanged={}, removed={}, database={} bytes",
+            report.destination,
+            report.files_copied,
+            report.bytes_copied,
+            report.files_unchanged,
+            report.files_removed,
+            report.database_bytes
+        );
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_changed_files_are_copied_and_removed_files_dropped() {
+        let data = tempfile::tempdir().expect("data directory");
+        let backup = tempfile::tempdir().expect("backup directory");
+        let data_dir = data.path();
+        std::fs::create_dir_all(data_dir.join("screenshots/thumbs")).unwrap();
+        std::fs::create_dir_all(data_dir.join("logs")).unwrap();
+        std::fs::write(data_dir.join("screenshots/a.png.enc"), [1u8; 8]).unwrap();
+        std::fs::write(
+            data_dir.join("screenshots/thumbs/a.thumb.jpg.enc"),
+            [2u8; 4],
+        )
+        .unwrap();
+        std::fs::write(data_dir.join("screenshots/b.png.enc.pending"), [3u8; 4]).unwrap();
+        std::fs::write(data_dir.join("credential_public_key.bin"), [4u8; 32]).unwrap();
+        std::fs::write(data_dir.join("screenshots.db"), [5u8; 16]).unwrap();
+        std::fs::write(data_dir.join("logs/app.log"), b"log").unwrap();
+
+        let mut report = BackupReport::default();
+        let files = sync_backup_files(
+            data_dir,
+            backup.path(),
+            &BTreeMap::new(),
+            &mut report,
+            &|_, _, _| {},
+        )
+        .expect("first sync");
+        assert_eq!(
+            files.keys().cloned().collect::<Vec<_>>(),
+            vec![
+                "credential_public_key.bin",
+                "screenshots/a.png.enc",
+                "screenshots/thumbs/a.thumb.jpg.enc",
+            ]
+        );
+        assert_eq!(report.files_copied, 3);
+        assert_eq!(
+            files["screenshots/a.png.enc"].sha256,
+            hex::encode(Sha256::digest([1u8; 8]))
+        );
+        assert!(backup.path().join("screenshots/a.png.enc").is_file());
+        assert!(!backup.path().join("screenshots.db").exists());
+
+        std::fs::remove_file(data_dir.join("screenshots/thumbs/a.thumb.jpg.enc")).unwrap();
+        std::fs::write(data_dir.join("screenshots/c.png.enc"), [6u8; 8]).unwrap();
+        let mut report = BackupReport::default();
+        let files = sync_backup_files(data_dir, backup.path(), &files, &mut report, &|_, _, _| {})
+            .expect("second sync");
+        assert_eq!(files.len(), 3);
+        assert_eq!(report.files_copied, 1);
+        assert_eq!(report.files_unchanged, 2);
+        assert_eq!(report.files_removed, 1);
+        assert!(!backup
+            .path()
+            .join("screenshots/thumbs/a.thumb.jpg.enc")
+            .exists());
+    }
+}
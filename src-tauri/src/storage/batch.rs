@@ -0,0 +1,122 @@
+//! Bulk operations on a selection of screenshots (e.g. every hit of a search), applied
+//! in one transaction instead of one command per ID.
+
+use super::{BatchOperationResult, BatchScreenshotOperation, StorageState};
+
+impl StorageState {
+    /// Apply `operation` to `screenshot_ids` in a single transaction.
+    ///
+    /// Returns the result plus the image hashes of screenshots that were removed, so
+    /// the caller can drop their vectors with one IPC call.
+    pub fn apply_batch_operation(
+        &self,
+        screenshot_ids: &[i64],
+        operation: &BatchScreenshotOperation,
+    ) -> Result<(BatchOperationResult, Vec<String>), String> {
+        let normalized_ids = Self::normalize_screenshot_ids(screenshot_ids);
+        let mut result = BatchOperationResult {
+            requested: normalized_ids.len() as i64,
+            affected: 0,
+            vector_deleted: None,
+        };
+        if normalized_ids.is_empty() {
+            return Ok((result, Vec::new()));
+        }
+
+        let mut guard = self.get_connection_named("apply_batch_operation")?;
+        let conn = guard.as_mut().unwrap();
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start batch transaction: {}", e))?;
+
+        let removed_hashes = match operation {
+            BatchScreenshotOperation::Delete => {
+                let mut hashes = Vec::new();
+                for chunk in normalized_ids.chunks(500) {
+                    let placeholders = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+                    let params_ref: Vec<&dyn rusqlite::ToSql> =
+                        chunk.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
+                    let mut stmt = tx
+                        .prepare(&format!(
+                            "SELECT image_hash FROM screenshots WHERE is_deleted = 0 AND id IN ({})",
+                            placeholders
+                        ))
+                        .map_err(|e| format!("Failed to prepare batch hash query: {}", e))?;
+                    let rows = stmt
+                        .query_map(params_ref.as_slice(), |row| row.get::<_, String>(0))
+                        .map_err(|e| format!("Failed to query batch hashes: {}", e))?;
+                    hashes.extend(rows.filter_map(|r| r.ok()));
+                }
+
+                let deleted = Self::soft_delete_screenshots_in_tx(&tx, &normalized_ids)?;
+                result.affected = deleted.screenshots_marked;
+                tx.commit()
+                    .map_err(|e| format!("Failed to commit batch delete: {}", e))?;
+                drop(guard);
+                self.release_ocr_row_count(deleted.ocr_marked);
+                hashes
+            }
+            BatchScreenshotOperation::AddTag { tag }
+            | BatchScreenshotOperation::RemoveTag { tag } => {
+                let tagged = matches!(operation, BatchScreenshotOperation::AddTag { .. });
+                result.affected = Self::set_tag_in_tx(&tx, &normalized_ids, tag, tagged)?;
+                tx.commit()
+                    .map_err(|e| format!("Failed to commit batch tag update: {}", e))?;
+                Vec::new()
+            }
+            BatchScreenshotOperation::Pin | BatchScreenshotOperation::Unpin => {
+                let pinned = matches!(operation, BatchScreenshotOperation::Pin);
+                result.affected = Self::set_pinned_in_tx(&tx, &normalized_ids, pinned)?;
+                tx.commit()
+                    .map_err(|e| format!("Failed to commit batch pin update: {}", e))?;
+                Vec::new()
+            }
+        };
+
+        Ok((result, removed_hashes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_storage;
+
+    #[test]
+    fn batch_delete_marks_rows_and_returns_hashes_once() {
+        let (_temp, storage) = test_storage();
+        let guard = storage.db.lock().unwrap_or_else(|e| e.into_inner());
+        let connection = guard.as_ref().expect("test database");
+        connection
+            .execute_batch(
+                "INSERT INTO screenshots (id, image_path, image_hash, is_deleted) VALUES
+                    (1, 'a', 'h1', 0), (2, 'b', 'h2', 0), (3, 'c', 'h3', 1), (4, 'd', 'h4', 0);
+                 INSERT INTO ocr_results (screenshot_id, text_hash) VALUES (1, 'x'), (2, 'y');",
+            )
+            .expect("batch fixture");
+        drop(guard);
+
+        let (result, mut hashes) = storage
+            .apply_batch_operation(&[2, 1, 1, 3, -5], &BatchScreenshotOperation::Delete)
+            .unwrap();
+        hashes.sort();
+        assert_eq!(result.requested, 3);
+        assert_eq!(result.affected, 2);
+        assert_eq!(hashes, vec!["h1".to_string(), "h2".to_string()]);
+
+        let guard = storage.db.lock().unwrap();
+        let conn = guard.as_ref().unwrap();
+        let live: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM screenshots WHERE is_deleted = 0",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        let queued_ocr: i64 = conn
+            .query_row("SELECT COUNT(*) FROM delete_queue_ocr", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(live, 1);
+        assert_eq!(queued_ocr, 2);
+    }
+}
@@ -0,0 +1,182 @@
+//! Timeline grouping by the local calendar day *at capture time*.
+//!
+//! Each screenshot records the UTC offset that was active when it was captured, so
+//! history recorded abroad stays on the day it happened there instead of shifting
+//! when the machine's time zone changes back. Rows captured before the offset was
+//! recorded fall back to the current local offset.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rusqlite::params;
+
+use super::{CaptureLocalDay, ScreenshotRecord, StorageState};
+
+/// The widest real-world UTC offsets are -12:00 and +14:00.
+const MAX_UTC_OFFSET_SECS: i64 = 14 * 60 * 60;
+
+/// UTC offset of the machine's current time zone, in minutes east of UTC.
+pub(crate) fn current_utc_offset_minutes() -> i32 {
+    chrono::Local::now().offset().local_minus_utc() / 60
+}
+
+/// SQL expression for a row's capture-local `YYYY-MM-DD`; `?1` binds the fallback offset.
+pub(super) const CAPTURE_DAY_SQL: &str =
+    "date(created_at, printf('%+d minutes', COALESCE(utc_offset_minutes, ?1)))";
+
+/// Unix seconds as the UTC `YYYY-MM-DD HH:MM:SS` text stored in `created_at`.
+pub(super) fn format_sql_timestamp(secs: i64) -> Result<String, String> {
+    DateTime::<Utc>::from_timestamp(secs, 0)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+        .ok_or_else(|| format!("Invalid timestamp: {}", secs))
+}
+
+impl StorageState {
+    /// Per-day screenshot counts for `[start_ts, end_ts]` (Unix seconds), where each
+    /// screenshot counts toward the local day it was captured on.
+    ///
+    /// Days are returned in ascending order with the distinct UTC offsets seen that
+    /// day, so the timeline can flag days recorded in another time zone.
+    pub fn get_capture_days(
+        &self,
+        start_ts: f64,
+        end_ts: f64,
+    ) -> Result<Vec<CaptureLocalDay>, String> {
+        let start_dt = format_sql_timestamp(start_ts as i64)?;
+        let end_dt = format_sql_timestamp(end_ts as i64)?;
+        let fallback_offset = current_utc_offset_minutes();
+
+        let guard = self.get_connection_named("get_capture_days")?;
+        let conn = guard.as_ref().unwrap();
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {day} AS day, COUNT(*),
+                        MIN(CAST(strftime('%s', created_at) AS INTEGER)),
+                        MAX(CAST(strftime('%s', created_at) AS INTEGER)),
+                        GROUP_CONCAT(DISTINCT COALESCE(utc_offset_minutes, ?1))
+                 FROM screenshots
+                 WHERE is_deleted = 0 AND created_at BETWEEN ?2 AND ?3
+                 GROUP BY day ORDER BY day",
+                day = CAPTURE_DAY_SQL
+            ))
+            .map_err(|e| format!("Failed to prepare capture day query: {}", e))?;
+        let days = stmt
+            .query_map(params![fallback_offset, start_dt, end_dt], |row| {
+                let offsets: Option<String> = row.get(4)?;
+                let mut utc_offsets: Vec<i32> = offsets
+                    .unwrap_or_default()
+                    .split(',')
+                    .filter_map(|v| v.trim().parse().ok())
+                    .collect();
+                utc_offsets.sort_unstable();
+                Ok(CaptureLocalDay {
+                    day: row.get(0)?,
+                    screenshot_count: row.get(1)?,
+                    first_ts: row.get(2)?,
+                    last_ts: row.get(3)?,
+                    utc_offsets,
+                })
+            })
+            .map_err(|e| format!("Failed to query capture days: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(days)
+    }
+
+    /// All screenshots captured on local day `day` (`YYYY-MM-DD`) in the time zone
+    /// active at capture time, in capture order.
+    pub fn get_screenshots_by_capture_day(
+        &self,
+        day: &str,
+    ) -> Result<Vec<ScreenshotRecord>, String> {
+        let date = NaiveDate::parse_from_str(day, "%Y-%m-%d")
+            .map_err(|e| format!("Invalid day {}: {}", day, e))?;
+        let day_start = date
+            .and_hms_opt(0, 0, 0)
+            .ok_or_else(|| format!("Invalid day {}", day))?
+            .and_utc()
+            .timestamp();
+        // Any offset maps the local day into this UTC window.
+        let window_start = day_start - MAX_UTC_OFFSET_SECS;
+        let window_end = day_start + 86_400 + MAX_UTC_OFFSET_SECS;
+
+        let ids: Vec<i64> = {
+            let guard = self.get_connection_named("get_screenshots_by_capture_day")?;
+            let conn = guard.as_ref().unwrap();
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT id FROM screenshots
+                     WHERE is_deleted = 0 AND created_at BETWEEN ?2 AND ?3 AND {} = ?4",
+                    CAPTURE_DAY_SQL
+                ))
+                .map_err(|e| format!("Failed to prepare capture day lookup: {}", e))?;
+            let ids = stmt
+                .query_map(
+                    params![
+                        current_utc_offset_minutes(),
+                        format_sql_timestamp(window_start)?,
+                        format_sql_timestamp(window_end)?,
+                        day
+                    ],
+                    |row| row.get(0),
+                )
+                .map_err(|e| format!("Failed to query capture day: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            ids
+        };
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut records = self.get_screenshots_by_ids(&ids)?;
+        records.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_storage;
+
+    #[test]
+    fn screenshots_stay_on_their_capture_local_day() {
+        let (_temp, storage) = test_storage();
+        let guard = storage.db.lock().unwrap_or_else(|e| e.into_inner());
+        let connection = guard.as_ref().expect("test database");
+        // 2026-03-02 23:30 in Tokyo (+09:00) is 14:30 UTC; 2026-03-02 20:00 in
+        // New York (-05:00) is 2026-03-03 01:00 UTC.
+        connection
+            .execute_batch(
+                "INSERT INTO screenshots (id, image_path, image_hash, created_at, utc_offset_minutes) VALUES
+                    (1, 'a', 'h1', '2026-03-02 14:30:00', 540),
+                    (2, 'b', 'h2', '2026-03-03 01:00:00', -300),
+                    (3, 'c', 'h3', '2026-03-03 16:00:00', 540);",
+            )
+            .expect("capture day fixture");
+        drop(guard);
+
+        let start = DateTime::parse_from_rfc3339("2026-03-01T00:00:00Z")
+            .unwrap()
+            .timestamp() as f64;
+        let days = storage
+            .get_capture_days(start, start + 5.0 * 86_400.0)
+            .unwrap();
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].day, "2026-03-02");
+        assert_eq!(days[0].screenshot_count, 2);
+        assert_eq!(days[0].utc_offsets, vec![-300, 540]);
+        assert_eq!(days[1].day, "2026-03-04");
+
+        let mut ids: Vec<i64> = storage
+            .get_screenshots_by_capture_day("2026-03-02")
+            .unwrap()
+            .iter()
+            .map(|r| r.id)
+            .collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![1, 2]);
+        assert!(storage
+            .get_screenshots_by_capture_day("03/02/2026")
+            .is_err());
+    }
+}
@@ -0,0 +1,372 @@
+//! Clipboard history entries.
+//!
+//! Copied text (see `clipboard_history.rs`) is stored as a timeline entry: the text
+//! and the process and window title it was copied in are sealed together with a
+//! per-row key like annotation text. Bigrams of the text are hashed with the
+//! blind-index HMAC key into `clipboard_bitmap_index`, whose postings are entry IDs.
+//! Entries saved while the master key is locked are indexed before the next search.
+//!
+//! Entries are never edited. Deleted IDs are subtracted from every posting without
+//! decrypting anything, so retention can prune them unattended.
+
+use std::collections::HashSet;
+
+use roaring::RoaringBitmap;
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use super::{ClipboardEntry, StorageState};
+
+/// Longest stored entry, in characters; longer copies are truncated.
+const MAX_CLIPBOARD_ENTRY_CHARS: usize = 20_000;
+
+const CLIPBOARD_INDEX_TABLE: &str = "clipboard_bitmap_index";
+
+/// Unindexed entries decrypted per search.
+const CLIPBOARD_INDEX_BATCH: i64 = 500;
+
+/// Plaintext sealed into `payload_enc`.
+#[derive(Serialize, Deserialize)]
+struct ClipboardPayload {
+    text: String,
+    process_name: Option<String>,
+    window_title: Option<String>,
+}
+
+/// `(id, captured_at, payload_enc, payload_key_encrypted)`.
+type EncryptedClipboardRow = (i64, i64, Vec<u8>, Vec<u8>);
+
+/// Index tokens of an entry. Entries are matched case-insensitively.
+fn clipboard_tokens(text: &str) -> HashSet<String> {
+    StorageState::bigram_tokenize(&text.to_lowercase())
+}
+
+impl StorageState {
+    /// Keyed hash used to skip an entry that repeats the latest one.
+    fn clipboard_content_hash(text: &str, hmac_key: &[u8]) -> String {
+        Self::compute_hmac_hash(&format!("clipboard:{}", text), hmac_key)
+    }
+
+    /// Store copied text with the window it was copied in. `captured_at` is Unix time
+    /// in milliseconds. Returns `None` for blank text or a repeat of the latest entry.
+    pub fn add_clipboard_entry(
+        &self,
+        text: &str,
+        process_name: Option<&str>,
+        window_title: Option<&str>,
+        captured_at: i64,
+    ) -> Result<Option<i64>, String> {
+        if text.trim().is_empty() {
+            return Ok(None);
+        }
+        let text: String = text.chars().take(MAX_CLIPBOARD_ENTRY_CHARS).collect();
+        let hmac_key = self.ocr_hmac_key_for_save();
+        let content_hash = hmac_key
+            .as_deref()
+            .map(|key| Self::clipboard_content_hash(&text, key));
+        let token_hashes: Vec<String> = hmac_key
+            .as_deref()
+            .map(|key| {
+                clipboard_tokens(&text)
+                    .iter()
+                    .map(|token| Self::compute_hmac_hash(token, key))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let payload = serde_json::to_vec(&ClipboardPayload {
+            text,
+            process_name: process_name.map(str::to_string),
+            window_title: window_title.map(str::to_string),
+        })
+        .map_err(|e| format!("Failed to serialize clipboard entry: {}", e))?;
+        let (payload_enc, payload_key_enc) = self.encrypt_payload_with_row_key(&payload)?;
+
+        let mut guard = self.get_connection_named("add_clipboard_entry")?;
+        let conn = guard.as_mut().unwrap();
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start clipboard transaction: {}", e))?;
+        if let Some(hash) = &content_hash {
+            let latest: Option<Option<String>> = tx
+                .query_row(
+                    "SELECT content_hash FROM clipboard_entries ORDER BY id DESC LIMIT 1",
+                    [],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|e| format!("Failed to query latest clipboard entry: {}", e))?;
+            if latest.flatten().as_deref() == Some(hash.as_str()) {
+                return Ok(None);
+            }
+        }
+        tx.execute(
+            "INSERT INTO clipboard_entries
+                (captured_at, payload_enc, payload_key_encrypted, content_hash, indexed)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                captured_at,
+                payload_enc,
+                payload_key_enc,
+                content_hash,
+                hmac_key.is_some()
+            ],
+        )
+        .map_err(|e| format!("Failed to insert clipboard entry: {}", e))?;
+        let id = tx.last_insert_rowid();
+        if hmac_key.is_some() {
+            Self::add_row_postings(&tx, CLIPBOARD_INDEX_TABLE, id, &token_hashes)?;
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed to commit clipboard entry: {}", e))?;
+        Ok(Some(id))
+    }
+
+    /// Entries captured in `[start_ms, end_ms)`, newest first.
+    pub fn list_clipboard_entries(
+        &self,
+        start_ms: Option<i64>,
+        end_ms: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<ClipboardEntry>, String> {
+        let rows: Vec<EncryptedClipboardRow> = {
+            let conn = self.open_read_connection_named("list_clipboard_entries")?;
+            let mut stmt = conn
+                .prepare(
+                    "SELECT id, captured_at, payload_enc, payload_key_encrypted
+                     FROM clipboard_entries
+                     WHERE captured_at >= ?1 AND captured_at < ?2
+                     ORDER BY captured_at DESC, id DESC
+                     LIMIT ?3",
+                )
+                .map_err(|e| format!("Failed to prepare clipboard query: {}", e))?;
+            let rows = stmt
+                .query_map(
+                    params![
+                        start_ms.unwrap_or(0),
+                        end_ms.unwrap_or(i64::MAX),
+                        limit.clamp(1, 1_000)
+                    ],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+                )
+                .map_err(|e| format!("Failed to query clipboard entries: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows
+        };
+        self.decrypt_clipboard_rows(rows)
+    }
+
+    /// Entries whose text contains every whitespace-separated keyword of `query`,
+    /// newest first.
+    pub fn search_clipboard_entries(
+        &self,
+        query: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<ClipboardEntry>, String> {
+        if !self.is_read_only() {
+            self.index_pending_clipboard_entries()?;
+        }
+        let hmac_key = self.credential_state.get_hmac_key()?;
+        let tokens: HashSet<String> = query
+            .split_whitespace()
+            .flat_map(clipboard_tokens)
+            .collect();
+
+        let mut rows: Vec<EncryptedClipboardRow> = {
+            let conn = self.open_read_connection_named("search_clipboard_entries")?;
+            let matches =
+                Self::blind_postings_matching_all(&conn, CLIPBOARD_INDEX_TABLE, &hmac_key, tokens)?;
+            let ids: Vec<i64> = matches.iter().map(i64::from).collect();
+
+            let mut rows: Vec<EncryptedClipboardRow> = Vec::new();
+            for chunk in ids.chunks(500) {
+                let placeholders = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+                let sql = format!(
+                    "SELECT id, captured_at, payload_enc, payload_key_encrypted
+                     FROM clipboard_entries WHERE id IN ({})",
+                    placeholders
+                );
+                let id_params: Vec<&dyn rusqlite::ToSql> =
+                    chunk.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
+                let mut stmt = conn
+                    .prepare(&sql)
+                    .map_err(|e| format!("Failed to prepare clipboard search: {}", e))?;
+                let chunk_rows = stmt
+                    .query_map(id_params.as_slice(), |row| {
+                        Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+                    })
+                    .map_err(|e| format!("Failed to search clipboard entries: {}", e))?;
+                rows.extend(chunk_rows.filter_map(|r| r.ok()));
+            }
+            rows
+        };
+
+        rows.sort_by(|a, b| b.1.cmp(&a.1).then(b.0.cmp(&a.0)));
+        let page: Vec<EncryptedClipboardRow> = rows
+            .into_iter()
+            .skip(offset.max(0) as usize)
+            .take(limit.clamp(1, 500) as usize)
+            .collect();
+        self.decrypt_clipboard_rows(page)
+    }
+
+    /// Index entries saved while the master key was locked or being rotated. Returns
+    /// how many were indexed; the rest wait for the next call.
+    fn index_pending_clipboard_entries(&self) -> Result<usize, String> {
+        let Some(hmac_key) = self.ocr_hmac_key_for_save() else {
+            return Ok(0);
+        };
+        let pending: Vec<(i64, Vec<u8>, Vec<u8>)> = {
+            let conn = self.open_read_connection_named("index_pending_clipboard_entries")?;
+            let mut stmt = conn
+                .prepare(
+                    "SELECT id, payload_enc, payload_key_encrypted
+                     FROM clipboard_entries WHERE indexed = 0 ORDER BY id LIMIT ?1",
+                )
+                .map_err(|e| format!("Failed to prepare clipboard index query: {}", e))?;
+            let rows = stmt
+                .query_map(params![CLIPBOARD_INDEX_BATCH], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+                })
+                .map_err(|e| format!("Failed to query unindexed clipboard entries: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows
+        };
+        if pending.is_empty() {
+            return Ok(0);
+        }
+
+        let mut hashed: Vec<(i64, String, Vec<String>)> = Vec::with_capacity(pending.len());
+        for (id, payload_enc, payload_key_enc) in pending {
+            let payload = self.decrypt_clipboard_payload(&payload_enc, &payload_key_enc)?;
+            let token_hashes = clipboard_tokens(&payload.text)
+                .iter()
+                .map(|token| Self::compute_hmac_hash(token, &hmac_key))
+                .collect();
+            hashed.push((
+                id,
+                Self::clipboard_content_hash(&payload.text, &hmac_key),
+                token_hashes,
+            ));
+        }
+
+        let mut guard = self.get_connection_named("index_pending_clipboard_entries")?;
+        let conn = guard.as_mut().unwrap();
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start clipboard index transaction: {}", e))?;
+        for (id, content_hash, token_hashes) in &hashed {
+            let updated = tx
+                .execute(
+                    "UPDATE clipboard_entries SET indexed = 1, content_hash = ?2
+                     WHERE id = ?1 AND indexed = 0",
+                    params![id, content_hash],
+                )
+                .map_err(|e| format!("Failed to mark clipboard entry indexed: {}", e))?;
+            // Deleted or indexed by a concurrent search in the meantime.
+            if updated > 0 {
+                Self::add_row_postings(&tx, CLIPBOARD_INDEX_TABLE, *id, token_hashes)?;
+            }
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed to commit clipboard index: {}", e))?;
+        Ok(hashed.len())
+    }
+
+    /// Delete entries and unlink them from the search index. Returns how many existed.
+    pub fn delete_clipboard_entries(&self, entry_ids: &[i64]) -> Result<usize, String> {
+        if entry_ids.is_empty() {
+            return Ok(0);
+        }
+        let mut removed = RoaringBitmap::new();
+        for id in entry_ids {
+            removed.insert(Self::posting_id(*id)?);
+        }
+
+        let mut guard = self.get_connection_named("delete_clipboard_entries")?;
+        let conn = guard.as_mut().unwrap();
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start clipboard delete: {}", e))?;
+        let mut deleted = 0;
+        {
+            let mut stmt = tx
+                .prepare_cached("DELETE FROM clipboard_entries WHERE id = ?1")
+                .map_err(|e| format!("Failed to prepare clipboard delete: {}", e))?;
+            for id in entry_ids {
+                deleted += stmt
+                    .execute(params![id])
+                    .map_err(|e| format!("Failed to delete clipboard entry: {}", e))?;
+            }
+        }
+        Self::remove_row_postings(&tx, CLIPBOARD_INDEX_TABLE, &removed)?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit clipboard delete: {}", e))?;
+        Ok(deleted)
+    }
+
+    /// Delete every entry and the whole clipboard index. Returns how many were deleted.
+    pub fn clear_clipboard_history(&self) -> Result<usize, String> {
+        let mut guard = self.get_connection_named("clear_clipboard_history")?;
+        let conn = guard.as_mut().unwrap();
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start clipboard clear: {}", e))?;
+        let deleted = tx
+            .execute("DELETE FROM clipboard_entries", [])
+            .map_err(|e| format!("Failed to clear clipboard history: {}", e))?;
+        tx.execute("DELETE FROM clipboard_bitmap_index", [])
+            .map_err(|e| format!("Failed to clear clipboard index: {}", e))?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit clipboard clear: {}", e))?;
+        Ok(deleted)
+    }
+
+    /// Delete entries captured before `cutoff_ms`. Runs without decrypting, so it is
+    /// safe for unattended retention. Returns how many were deleted.
+    pub fn prune_clipboard_history(&self, cutoff_ms: i64) -> Result<usize, String> {
+        let expired: Vec<i64> = {
+            let conn = self.open_read_connection_named("prune_clipboard_history")?;
+            let mut stmt = conn
+                .prepare("SELECT id FROM clipboard_entries WHERE captured_at < ?1")
+                .map_err(|e| format!("Failed to prepare clipboard retention query: {}", e))?;
+            let ids = stmt
+                .query_map(params![cutoff_ms], |row| row.get(0))
+                .map_err(|e| format!("Failed to query expired clipboard entries: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            ids
+        };
+        self.delete_clipboard_entries(&expired)
+    }
+
+    fn decrypt_clipboard_payload(
+        &self,
+        payload_enc: &[u8],
+        payload_key_enc: &[u8],
+    ) -> Result<ClipboardPayload, String> {
+        let bytes = self.decrypt_payload_with_row_key(payload_enc, payload_key_enc)?;
+        serde_json::from_slice(&bytes)
+            .map_err(|e| format!("Failed to parse clipboard entry: {}", e))
+    }
+
+    fn decrypt_clipboard_rows(
+        &self,
+        rows: Vec<EncryptedClipboardRow>,
+    ) -> Result<Vec<ClipboardEntry>, String> {
+        rows.into_iter()
+            .map(|(id, captured_at, payload_enc, payload_key_enc)| {
+                let payload = self.decrypt_clipboard_payload(&payload_enc, &payload_key_enc)?;
+                Ok(ClipboardEntry {
+                    id,
+                    captured_at,
+                    text: payload.text,
+                    process_name: payload.process_name,
+                    window_title: payload.window_title,
+                })
+            })
+            .collect()
+    }
+}
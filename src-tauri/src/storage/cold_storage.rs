@@ -0,0 +1,447 @@
+//! Cold-storage tier for old screenshots.
+//!
+//! Screenshots older than `cold_storage_after_days` (storage policy, off by default)
+//! are moved out of `screenshots/` into one bundle per local day under `cold/`.
+//! Each bundle entry is the image zstd-compressed and then encrypted with the row's
+//! own key, so a bundle holds no key material and entries stay independently readable.
+//! Bundles are append-only: a later run for the same day adds entries at the end.
+//!
+//! An archived row's `image_path` names its entry as
+//! `cold/<day>.cpbundle#<offset>-<len>-<original file name>`. The original name keeps
+//! the MIME type and thumbnail name, and [`decrypt_image_file`] reads entries by offset,
+//! so every image read path works unchanged at the cost of decompression on access.
+//! Thumbnails move to `cold/thumbs/` and stay as separate files.
+//!
+//! Space in a bundle is reclaimed once every screenshot in it has been deleted; partly
+//! deleted bundles are not compacted.
+//!
+//! [`decrypt_image_file`]: super::image_io::decrypt_image_file
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use rusqlite::params;
+use serde_json::Value as JsonValue;
+
+use crate::credential_manager::{
+    decrypt_row_key_with_cng_silent, decrypt_with_master_key, encrypt_with_master_key,
+    CredentialError,
+};
+
+use super::image_io::{decrypt_image_file, image_mime_type, ImageIntegrityError};
+use super::{ColdStorageSummary, StorageState};
+
+pub(super) const COLD_STORAGE_DIR: &str = "cold";
+const BUNDLE_EXTENSION: &str = "cpbundle";
+const BUNDLE_MAGIC: &[u8; 8] = b"CPBNDL1\n";
+const BUNDLE_ZSTD_LEVEL: i32 = 19;
+/// Screenshots moved per run so one run never holds the disk for long.
+const COLD_STORAGE_BATCH: i64 = 2000;
+
+/// Days after which screenshots move to cold storage, from the storage policy.
+pub(super) fn parse_cold_storage_after_days(policy: &JsonValue) -> Option<u32> {
+    policy
+        .get("cold_storage_after_days")
+        .and_then(|v| v.as_u64())
+        .filter(|days| *days > 0)
+        .map(|days| days.min(u32::MAX as u64) as u32)
+}
+
+/// Location of one image inside a bundle, parsed from a resolved `image_path`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct BundleEntry {
+    pub bundle: PathBuf,
+    pub offset: u64,
+    pub len: u64,
+    /// File name the image had before it was archived.
+    pub name: String,
+}
+
+impl BundleEntry {
+    pub(super) fn parse(path: &Path) -> Option<Self> {
+        let path = path.to_str()?;
+        let marker = format!(".{}#", BUNDLE_EXTENSION);
+        let split = path.find(&marker)? + marker.len() - 1;
+        let (bundle, entry) = (&path[..split], &path[split + 1..]);
+        let mut parts = entry.splitn(3, '-');
+        let offset = parts.next()?.parse().ok()?;
+        let len = parts.next()?.parse().ok()?;
+        let name = parts.next().filter(|name| !name.is_empty())?;
+        Some(Self {
+            bundle: PathBuf::from(bundle),
+            offset,
+            len,
+            name: name.to_string(),
+        })
+    }
+
+    fn image_path(bundle_rel: &str, offset: u64, len: u64, name: &str) -> String {
+        format!("{}#{}-{}-{}", bundle_rel, offset, len, name)
+    }
+}
+
+/// Read, decrypt and decompress one bundle entry.
+pub(super) fn read_bundle_entry(
+    entry: &BundleEntry,
+    row_key: &[u8],
+) -> Result<(Vec<u8>, String), ImageIntegrityError> {
+    let mut file = File::open(&entry.bundle).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => {
+            ImageIntegrityError::Missing(entry.bundle.display().to_string())
+        }
+        _ => ImageIntegrityError::Io(e.to_string()),
+    })?;
+    let mut sealed = vec![0u8; entry.len as usize];
+    file.seek(SeekFrom::Start(entry.offset))
+        .and_then(|_| file.read_exact(&mut sealed))
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::UnexpectedEof => ImageIntegrityError::Truncated(entry.len as usize),
+            _ => ImageIntegrityError::Io(e.to_string()),
+        })?;
+    let compressed =
+        decrypt_with_master_key(row_key, &sealed).map_err(|_| ImageIntegrityError::Corrupted)?;
+    let image_data = zstd::stream::decode_all(compressed.as_slice())
+        .map_err(|e| ImageIntegrityError::Undecodable(e.to_string()))?;
+    Ok((image_data, image_mime_type(&entry.name).to_string()))
+}
+
+/// Bytes an image takes on disk: the entry length for bundled images.
+pub(super) fn stored_image_size(path: &Path) -> u64 {
+    match BundleEntry::parse(path) {
+        Some(entry) => entry.len,
+        None => std::fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+    }
+}
+
+/// A row waiting to be archived.
+struct ColdCandidate {
+    id: i64,
+    image_path: String,
+    key_enc: Option<Vec<u8>>,
+}
+
+impl StorageState {
+    /// Days after which screenshots move to cold storage; `None` when turned off.
+    pub fn cold_storage_after_days(&self) -> Option<u32> {
+        self.load_policy()
+            .ok()
+            .and_then(|policy| parse_cold_storage_after_days(&policy))
+    }
+
+    /// Move screenshots older than the configured age into per-day bundles.
+    ///
+    /// Row keys are unwrapped without UI; when Windows Hello would have to prompt the
+    /// run stops early and reports `auth_required`.
+    pub fn archive_cold_screenshots(&self) -> Result<ColdStorageSummary, String> {
+        let mut summary = ColdStorageSummary {
+            removed_bundles: self.prune_empty_bundles()?,
+            ..Default::default()
+        };
+        let Some(after_days) = self.cold_storage_after_days() else {
+            return Ok(summary);
+        };
+        if self.is_read_only() {
+            return Ok(summary);
+        }
+
+        let days = self.cold_candidate_days(after_days)?;
+        for (day, rows) in days {
+            if !self.archive_cold_day(&day, rows, &mut summary)? {
+                summary.auth_required = true;
+                break;
+            }
+        }
+        Ok(summary)
+    }
+
+    /// Candidates grouped by local capture day, oldest day first.
+    fn cold_candidate_days(
+        &self,
+        after_days: u32,
+    ) -> Result<Vec<(String, Vec<ColdCandidate>)>, String> {
+        let conn = self.open_read_connection_named("cold_candidate_days")?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, image_path, content_key_encrypted, date(created_at, 'localtime')
+                 FROM screenshots
+                 WHERE is_deleted = 0 AND deleted_at IS NULL
+                   AND created_at < datetime('now', '-' || ?1 || ' days')
+                   AND image_path NOT LIKE ?2
+                   AND image_path NOT LIKE '%.pending'
+                   AND image_path NOT LIKE 'memory://%'
+                 ORDER BY created_at ASC, id ASC
+                 LIMIT ?3",
+            )
+            .map_err(|e| format!("Failed to prepare cold storage query: {}", e))?;
+        let rows = stmt
+            .query_map(
+                params![
+                    after_days,
+                    format!("{}/%", COLD_STORAGE_DIR),
+                    COLD_STORAGE_BATCH
+                ],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(3)?,
+                        ColdCandidate {
+                            id: row.get(0)?,
+                            image_path: row.get(1)?,
+                            key_enc: row.get(2)?,
+                        },
+                    ))
+                },
+            )
+            .map_err(|e| format!("Failed to query cold storage candidates: {}", e))?;
+
+        let mut days: Vec<(String, Vec<ColdCandidate>)> = Vec::new();
+        for (day, candidate) in rows.filter_map(|r| r.ok()) {
+            match days.last_mut() {
+                Some((last_day, group)) if *last_day == day => group.push(candidate),
+                _ => days.push((day, vec![candidate])),
+            }
+        }
+        Ok(days)
+    }
+
+    /// Append one day's screenshots to its bundle and repoint their rows.
+    /// Returns `false` when a row key needs an interactive unlock.
+    fn archive_cold_day(
+        &self,
+        day: &str,
+        rows: Vec<ColdCandidate>,
+        summary: &mut ColdStorageSummary,
+    ) -> Result<bool, String> {
+        let bundle_rel = format!("{}/{}.{}", COLD_STORAGE_DIR, day, BUNDLE_EXTENSION);
+        let bundle_path = self.resolve_image_path(&bundle_rel);
+        if let Some(parent) = bundle_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create cold storage directory: {}", e))?;
+        }
+        let mut bundle = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&bundle_path)
+            .map_err(|e| format!("Failed to open bundle {}: {}", bundle_path.display(), e))?;
+        let mut offset = bundle
+            .metadata()
+            .map_err(|e| format!("Failed to stat bundle: {}", e))?
+            .len();
+        let created = offset == 0;
+        if created {
+            bundle
+                .write_all(BUNDLE_MAGIC)
+                .map_err(|e| format!("Failed to write bundle header: {}", e))?;
+            offset = BUNDLE_MAGIC.len() as u64;
+        }
+
+        let mut moved = Vec::with_capacity(rows.len());
+        let mut auth_ok = true;
+        for row in rows {
+            let Some(key_enc) = row.key_enc.as_deref() else {
+                continue;
+            };
+            let mut row_key = match decrypt_row_key_with_cng_silent(key_enc) {
+                Ok(key) => key,
+                Err(CredentialError::AuthRequired) => {
+                    auth_ok = false;
+                    break;
+                }
+                Err(e) => {
+                    tracing::warn!("[COLD] skipping screenshot {}: {}", row.id, e);
+                    continue;
+                }
+            };
+            let abs_path = self.resolve_image_path(&row.image_path);
+            let sealed = decrypt_image_file(&abs_path, &row_key)
+                .map_err(|e| e.to_string())
+                .and_then(|(image_data, _)| {
+                    let compressed = zstd::bulk::compress(&image_data, BUNDLE_ZSTD_LEVEL)
+                        .map_err(|e| format!("Failed to compress image: {}", e))?;
+                    encrypt_with_master_key(&row_key, &compressed)
+                        .map_err(|e| format!("Failed to encrypt bundle entry: {}", e))
+                });
+            Self::zeroize_bytes(&mut row_key);
+            let sealed = match sealed {
+                Ok(sealed) => sealed,
+                Err(e) => {
+                    tracing::warn!("[COLD] skipping screenshot {}: {}", row.id, e);
+                    continue;
+                }
+            };
+            bundle
+                .write_all(&sealed)
+                .map_err(|e| format!("Failed to write bundle entry: {}", e))?;
+
+            let name = abs_path
+                .file_name()
+                .and_then(|s| s.to_str())
+                .unwrap_or("image.png.enc")
+                .to_string();
+            let new_path = BundleEntry::image_path(&bundle_rel, offset, sealed.len() as u64, &name);
+            summary.bytes_before += std::fs::metadata(&abs_path).map(|m| m.len()).unwrap_or(0);
+            summary.bytes_after += sealed.len() as u64;
+            offset += sealed.len() as u64;
+            moved.push((row.id, row.image_path, new_path, abs_path));
+        }
+        bundle
+            .sync_all()
+            .map_err(|e| format!("Failed to flush bundle: {}", e))?;
+        drop(bundle);
+
+        if moved.is_empty() {
+            if created {
+                let _ = std::fs::remove_file(&bundle_path);
+            }
+            return Ok(auth_ok);
+        }
+
+        // Repoint rows only after the bundle is on disk; a row changed or deleted
+        // meanwhile keeps its original file and leaves an unused entry behind.
+        let mut repointed = Vec::with_capacity(moved.len());
+        {
+            let mut guard = self.get_connection_named("archive_cold_day")?;
+            let conn = guard.as_mut().unwrap();
+            let tx = conn
+                .transaction()
+                .map_err(|e| format!("Failed to start cold storage transaction: {}", e))?;
+            for (id, old_path, new_path, abs_path) in moved {
+                let updated = tx
+                    .execute(
+                        "UPDATE screenshots SET image_path = ?1
+                         WHERE id = ?2 AND image_path = ?3 AND is_deleted = 0",
+                        params![new_path, id, old_path],
+                    )
+                    .map_err(|e| format!("Failed to repoint archived screenshot: {}", e))?;
+                if updated > 0 {
+                    repointed.push((abs_path, new_path));
+                }
+            }
+            tx.commit()
+                .map_err(|e| format!("Failed to commit cold storage: {}", e))?;
+        }
+
+        for (abs_path, new_path) in &repointed {
+            let old_thumb = Self::thumbnail_path_for(abs_path);
+            if old_thumb.exists() {
+                let new_thumb = Self::thumbnail_path_for(&self.resolve_image_path(new_path));
+                if let Some(parent) = new_thumb.parent() {
+                    let _ = std::fs::create_dir_all(parent);
+                }
+                if std::fs::rename(&old_thumb, &new_thumb).is_err() {
+                    let _ = std::fs::remove_file(&old_thumb);
+                }
+            }
+            if let Err(e) = std::fs::remove_file(abs_path) {
+                tracing::warn!("[COLD] failed to remove {}: {}", abs_path.display(), e);
+            }
+        }
+        summary.archived += repointed.len();
+        if created {
+            summary.bundles_written += 1;
+        }
+        tracing::info!(
+            "[COLD] archived {} screenshots from {} into {}",
+            repointed.len(),
+            day,
+            bundle_rel
+        );
+        Ok(auth_ok)
+    }
+
+    /// Delete bundles no screenshot row points into any more; returns how many.
+    fn prune_empty_bundles(&self) -> Result<usize, String> {
+        let cold_dir = self.resolve_image_path(COLD_STORAGE_DIR);
+        let Ok(entries) = std::fs::read_dir(&cold_dir) else {
+            return Ok(0);
+        };
+        let bundles: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| name.ends_with(&format!(".{}", BUNDLE_EXTENSION)))
+            .collect();
+        if bundles.is_empty() {
+            return Ok(0);
+        }
+
+        let conn = self.open_read_connection_named("prune_empty_bundles")?;
+        let mut removed = 0;
+        for name in bundles {
+            let referenced: bool = conn
+                .query_row(
+                    "SELECT EXISTS(SELECT 1 FROM screenshots WHERE image_path LIKE ?1)
+                         OR EXISTS(SELECT 1 FROM month_shards WHERE month = substr(?2, 1, 7))",
+                    params![format!("{}/{}#%", COLD_STORAGE_DIR, name), name],
+                    |row| row.get(0),
+                )
+                .map_err(|e| format!("Failed to check bundle references: {}", e))?;
+            if !referenced && std::fs::remove_file(cold_dir.join(&name)).is_ok() {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundle_entry_paths_round_trip() {
+        let rel = BundleEntry::image_path("cold/2026-01-02.cpbundle", 8, 1234, "a-b.webp.enc");
+        let entry = BundleEntry::parse(Path::new(&format!("/data/{}", rel))).expect("entry");
+        assert_eq!(
+            entry.bundle,
+            PathBuf::from("/data/cold/2026-01-02.cpbundle")
+        );
+        assert_eq!((entry.offset, entry.len), (8, 1234));
+        assert_eq!(entry.name, "a-b.webp.enc");
+
+        assert!(BundleEntry::parse(Path::new("screenshots/a.png.enc")).is_none());
+        assert!(BundleEntry::parse(Path::new("cold/2026-01-02.cpbundle#8-x-a.png")).is_none());
+    }
+
+    #[test]
+    fn bundle_entries_decrypt_from_their_offset() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let bundle = temp.path().join("day.cpbundle");
+        let key = vec![7u8; 32];
+        let image = vec![42u8; 4096];
+        let sealed = encrypt_with_master_key(
+            &key,
+            &zstd::bulk::compress(&image, BUNDLE_ZSTD_LEVEL).unwrap(),
+        )
+        .unwrap();
+        let mut bytes = BUNDLE_MAGIC.to_vec();
+        bytes.extend_from_slice(&sealed);
+        std::fs::write(&bundle, &bytes).unwrap();
+
+        let entry = BundleEntry {
+            bundle,
+            offset: BUNDLE_MAGIC.len() as u64,
+            len: sealed.len() as u64,
+            name: "x.png.enc".to_string(),
+        };
+        let (data, mime) = read_bundle_entry(&entry, &key).expect("entry reads");
+        assert_eq!(data, image);
+        assert_eq!(mime, "image/png");
+        assert!(matches!(
+            read_bundle_entry(&entry, &[9u8; 32]),
+            Err(ImageIntegrityError::Corrupted)
+        ));
+    }
+
+    #[test]
+    fn cold_storage_is_off_unless_configured() {
+        assert_eq!(parse_cold_storage_after_days(&serde_json::json!({})), None);
+        assert_eq!(
+            parse_cold_storage_after_days(&serde_json::json!({ "cold_storage_after_days": 0 })),
+            None
+        );
+        assert_eq!(
+            parse_cold_storage_after_days(&serde_json::json!({ "cold_storage_after_days": 90 })),
+            Some(90)
+        );
+    }
+}
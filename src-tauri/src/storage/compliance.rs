@@ -0,0 +1,361 @@
+//! Data-at-rest compliance mode for users under corporate data policies.
+//!
+//! Enabling the mode strips the plaintext `process_name` column (new rows only store
+//! `process_name_enc`) and caps retention and the authenticated-session timeout. It
+//! also makes the access audit log strict: a read fails when its audit entry cannot be
+//! recorded (see `storage/audit.rs`). Process lists and per-process browsing match
+//! rows through the `process_name_hash` blind index instead (see `storage/paranoid.rs`).
+//! Compliance mode implies paranoid mode's storage rules.
+
+use crate::credential_manager::{
+    decrypt_row_key_with_cng, encrypt_with_master_key, CredentialManagerState,
+};
+use rusqlite::{params, Connection};
+use serde_json::Value as JsonValue;
+use std::sync::atomic::Ordering;
+
+use super::{ComplianceStatus, RetentionPolicy, StorageState};
+
+/// Policy key holding `{ "enabled": bool, "enabled_at": string }`.
+pub(crate) const COMPLIANCE_POLICY_KEY: &str = "compliance_mode";
+/// Longest retention allowed while compliance mode is on.
+pub const COMPLIANCE_MAX_RETENTION_DAYS: u32 = 90;
+/// Longest authenticated-session timeout allowed while compliance mode is on.
+pub const COMPLIANCE_MAX_SESSION_TIMEOUT_SECS: i64 = 300;
+
+/// Clamps a session timeout to the compliance cap; `-1` (never expire) is not allowed.
+pub fn compliance_session_timeout(timeout_secs: i64) -> i64 {
+    if timeout_secs <= 0 {
+        COMPLIANCE_MAX_SESSION_TIMEOUT_SECS
+    } else {
+        timeout_secs.min(COMPLIANCE_MAX_SESSION_TIMEOUT_SECS)
+    }
+}
+
+fn policy_compliance_enabled(policy: &JsonValue) -> bool {
+    policy
+        .get(COMPLIANCE_POLICY_KEY)
+        .and_then(|v| v.get("enabled"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+/// Clears plaintext process names that already have an encrypted copy.
+/// Returns the number of rows cleared.
+fn clear_encrypted_plaintext_process_names(conn: &Connection) -> Result<usize, String> {
+    conn.execute(
+        "UPDATE screenshots SET process_name = NULL
+         WHERE process_name IS NOT NULL AND process_name_enc IS NOT NULL",
+        [],
+    )
+    .map_err(|e| format!("Failed to clear plaintext process names: {}", e))
+}
+
+fn count_plaintext_process_rows(conn: &Connection) -> Result<i64, String> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM screenshots WHERE process_name IS NOT NULL",
+        [],
+        |row| row.get(0),
+    )
+    .map_err(|e| format!("Failed to count plaintext process names: {}", e))
+}
+
+impl StorageState {
+    /// Returns whether data-at-rest compliance mode is active.
+    pub fn compliance_mode_enabled(&self) -> bool {
+        self.compliance_mode.load(Ordering::SeqCst)
+    }
+
+    /// Applies `compliance_mode` from a storage policy object and re-clamps the
+    /// session timeout restored from the registry.
+    pub(crate) fn apply_compliance_mode_from_policy(&self, policy: &JsonValue) {
+        let enabled = policy_compliance_enabled(policy);
+        self.compliance_mode.store(enabled, Ordering::SeqCst);
+        if enabled {
+            clamp_session_timeout(&self.credential_state);
+        }
+    }
+
+    /// Rejects a policy that would violate compliance mode while it is active.
+    pub(crate) fn check_policy_compliant(&self, policy: &JsonValue) -> Result<(), String> {
+        if !self.compliance_mode_enabled() {
+            if policy_compliance_enabled(policy) {
+                return Err("Compliance mode can only be enabled via its own command".to_string());
+            }
+            return Ok(());
+        }
+        if !policy_compliance_enabled(policy) {
+            return Err("Compliance mode can only be disabled via its own command".to_string());
+        }
+        match super::policy::parse_retention_days(policy) {
+            Some(days) if days <= COMPLIANCE_MAX_RETENTION_DAYS => Ok(()),
+            _ => Err(format!(
+                "Compliance mode requires retention of at most {} days",
+                COMPLIANCE_MAX_RETENTION_DAYS
+            )),
+        }
+    }
+
+    /// Reports the current compliance state and any remaining plaintext metadata.
+    pub fn compliance_status(&self) -> Result<ComplianceStatus, String> {
+        let policy = self.load_policy()?;
+        let enabled_at = policy
+            .get(COMPLIANCE_POLICY_KEY)
+            .and_then(|v| v.get("enabled_at"))
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+
+        let guard = self.get_connection_named("compliance_status")?;
+        let conn = guard.as_ref().unwrap();
+        let plaintext_process_rows = count_plaintext_process_rows(conn)?;
+        let audit_log_entries: i64 = conn
+            .query_row("SELECT COUNT(*) FROM access_audit_log", [], |row| {
+                row.get(0)
+            })
+            .map_err(|e| format!("Failed to count audit log entries: {}", e))?;
+
+        Ok(ComplianceStatus {
+            enabled: self.compliance_mode_enabled(),
+            enabled_at,
+            plaintext_process_rows,
+            retention_days: super::policy::parse_retention_days(&policy),
+            session_timeout_secs: self.credential_state.get_session_timeout(),
+            audit_log_entries,
+        })
+    }
+
+    /// Turns compliance mode on and runs the required migrations:
+    /// encrypts legacy plaintext-only process names, drops the plaintext column
+    /// values and stored previews, shortens retention, and caps the session timeout.
+    ///
+    /// Needs an authenticated session to unwrap row keys of legacy rows; rows whose
+    /// key cannot be unwrapped lose their process name rather than keep plaintext.
+    pub fn enable_compliance_mode(&self) -> Result<ComplianceStatus, String> {
+        // Hash names first so process filters keep matching once plaintext is gone.
+        self.backfill_process_name_hashes()?;
+        let (encrypted, dropped, cleared) = {
+            let guard = self.get_connection_named("enable_compliance_mode")?;
+            let conn = guard.as_ref().unwrap();
+            let (encrypted, dropped) = self.encrypt_plaintext_only_process_names(conn)?;
+            let cleared = clear_encrypted_plaintext_process_names(conn)?;
+            super::preview::clear_stored_previews(conn)?;
+            Self::resegment_activity_sessions_from(conn, 0)?;
+            (encrypted, dropped, cleared)
+        };
+
+        let retention = self.retention_policy()?;
+        let capped_days = match retention.max_age_days {
+            Some(days) if days <= COMPLIANCE_MAX_RETENTION_DAYS => days,
+            _ => COMPLIANCE_MAX_RETENTION_DAYS,
+        };
+        self.set_retention_policy(RetentionPolicy {
+            max_age_days: Some(capped_days),
+            ..retention
+        })?;
+
+        let mut policy = self.load_policy()?;
+        policy
+            .as_object_mut()
+            .ok_or_else(|| "Policy is not a valid JSON object".to_string())?
+            .insert(
+                COMPLIANCE_POLICY_KEY.into(),
+                serde_json::json!({
+                    "enabled": true,
+                    "enabled_at": chrono::Utc::now().to_rfc3339(),
+                }),
+            );
+        self.save_policy(&policy)?;
+        self.apply_compliance_mode_from_policy(&policy);
+
+        let timeout = self.credential_state.get_session_timeout();
+        if let Err(e) =
+            crate::registry_config::set_string("session_timeout_secs", &timeout.to_string())
+        {
+            tracing::error!("Failed to persist session_timeout_secs: {}", e);
+        }
+
+        tracing::info!(
+            "[COMPLIANCE] enabled: encrypted {} legacy process names, dropped {}, cleared {} plaintext values, retention {} days",
+            encrypted,
+            dropped,
+            cleared,
+            capped_days
+        );
+        self.compliance_status()
+    }
+
+    /// Turns compliance mode off. Plaintext process names are re-populated by the
+    /// startup backfill on the next launch; retention and timeout are left as-is.
+    pub fn disable_compliance_mode(&self) -> Result<ComplianceStatus, String> {
+        let mut policy = self.load_policy()?;
+        if let Some(obj) = policy.as_object_mut() {
+            obj.remove(COMPLIANCE_POLICY_KEY);
+        }
+        self.save_policy(&policy)?;
+        self.compliance_mode.store(false, Ordering::SeqCst);
+
+        {
+            let guard = self.get_connection_named("disable_compliance_mode")?;
+            let conn = guard.as_ref().unwrap();
+            conn.execute(
+                "DELETE FROM app_metadata WHERE key = ?1",
+                params![Self::BACKFILL_PROCESS_NAMES_DONE_KEY],
+            )
+            .map_err(|e| format!("Failed to reset process name backfill: {}", e))?;
+        }
+
+        tracing::info!("[COMPLIANCE] disabled");
+        self.compliance_status()
+    }
+
+    /// Encrypts rows that only carry a plaintext `process_name`. Returns
+    /// `(encrypted, dropped)`; dropped rows are cleared without an encrypted copy.
+    pub(super) fn encrypt_plaintext_only_process_names(
+        &self,
+        conn: &Connection,
+    ) -> Result<(usize, usize), String> {
+        let rows: Vec<(i64, String, Option<Vec<u8>>)> = {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT id, process_name, content_key_encrypted FROM screenshots
+                     WHERE process_name IS NOT NULL AND process_name_enc IS NULL",
+                )
+                .map_err(|e| format!("Failed to prepare legacy process name query: {}", e))?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+                .map_err(|e| format!("Failed to query legacy process names: {}", e))?;
+            rows.filter_map(|r| r.ok()).collect()
+        };
+
+        let mut encrypted = 0usize;
+        let mut dropped = 0usize;
+        for (id, process_name, wrapped_key) in rows {
+            let result = match wrapped_key {
+                Some(wrapped_key) => decrypt_row_key_with_cng(&wrapped_key)
+                    .map_err(|e| format!("Failed to unwrap row key: {}", e))
+                    .and_then(|mut row_key| {
+                        let enc = encrypt_with_master_key(&row_key, process_name.as_bytes())
+                            .map_err(|e| format!("Failed to encrypt process_name: {}", e));
+                        Self::zeroize_bytes(&mut row_key);
+                        enc
+                    })
+                    .and_then(|enc| {
+                        conn.execute(
+                            "UPDATE screenshots SET process_name_enc = ?1, process_name = NULL
+                             WHERE id = ?2",
+                            params![enc, id],
+                        )
+                        .map_err(|e| format!("Failed to store process_name_enc: {}", e))
+                    }),
+                None => self
+                    .encrypt_payload_with_row_key(process_name.as_bytes())
+                    .and_then(|(enc, wrapped_key)| {
+                        conn.execute(
+                            "UPDATE screenshots
+                             SET process_name_enc = ?1, content_key_encrypted = ?2, process_name = NULL
+                             WHERE id = ?3",
+                            params![enc, wrapped_key, id],
+                        )
+                        .map_err(|e| format!("Failed to store process_name_enc: {}", e))
+                    }),
+            };
+
+            match result {
+                Ok(_) => encrypted += 1,
+                Err(e) => {
+                    tracing::warn!("[COMPLIANCE] dropping process name of row {}: {}", id, e);
+                    conn.execute(
+                        "UPDATE screenshots SET process_name = NULL WHERE id = ?1",
+                        params![id],
+                    )
+                    .map_err(|e| format!("Failed to clear process_name: {}", e))?;
+                    dropped += 1;
+                }
+            }
+        }
+        Ok((encrypted, dropped))
+    }
+}
+
+fn clamp_session_timeout(credential_state: &CredentialManagerState) {
+    let current = credential_state.get_session_timeout();
+    let clamped = compliance_session_timeout(current);
+    if clamped != current {
+        tracing::info!(
+            "[COMPLIANCE] session timeout {}s capped to {}s",
+            current,
+            clamped
+        );
+        credential_state.set_session_timeout(clamped);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_storage;
+
+    #[test]
+    fn session_timeout_is_capped_and_never_expire_rejected() {
+        assert_eq!(
+            compliance_session_timeout(-1),
+            COMPLIANCE_MAX_SESSION_TIMEOUT_SECS
+        );
+        assert_eq!(
+            compliance_session_timeout(0),
+            COMPLIANCE_MAX_SESSION_TIMEOUT_SECS
+        );
+        assert_eq!(
+            compliance_session_timeout(3600),
+            COMPLIANCE_MAX_SESSION_TIMEOUT_SECS
+        );
+        assert_eq!(compliance_session_timeout(60), 60);
+    }
+
+    #[test]
+    fn clearing_keeps_rows_without_encrypted_copy() {
+        let (_temp, storage) = test_storage();
+        let guard = storage.get_connection_named("test").unwrap();
+        let conn = guard.as_ref().unwrap();
+        conn.execute_batch(
+            "INSERT INTO screenshots (id, image_path, image_hash, process_name, process_name_enc)
+             VALUES (1, 'a.enc', 'h1', 'code.exe', X'01'),
+                    (2, 'b.enc', 'h2', 'legacy.exe', NULL),
+                    (3, 'c.enc', 'h3', NULL, X'02');",
+        )
+        .unwrap();
+
+        assert_eq!(clear_encrypted_plaintext_process_names(conn).unwrap(), 1);
+        assert_eq!(count_plaintext_process_rows(conn).unwrap(), 1);
+        let remaining: String = conn
+            .query_row(
+                "SELECT process_name FROM screenshots WHERE id = 2",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(remaining, "legacy.exe");
+    }
+
+    #[test]
+    fn policy_checks_follow_compliance_flag() {
+        let (_temp, storage) = test_storage();
+        let lax = serde_json::json!({ "retention_period": "permanent" });
+        assert!(storage.check_policy_compliant(&lax).is_ok());
+
+        let enabled = serde_json::json!({
+            "compliance_mode": { "enabled": true },
+            "retention_period": "1month",
+        });
+        assert!(storage.check_policy_compliant(&enabled).is_err());
+
+        storage.apply_compliance_mode_from_policy(&enabled);
+        assert!(storage.compliance_mode_enabled());
+        assert!(storage.check_policy_compliant(&enabled).is_ok());
+        assert!(storage.check_policy_compliant(&lax).is_err());
+
+        let mut long = enabled.clone();
+        long["retention_period"] = "1year".into();
+        assert!(storage.check_policy_compliant(&long).is_err());
+    }
+}
@@ -0,0 +1,278 @@
+//! "What was I doing at …" lookups: the screenshot nearest to a moment plus a
+//! summary of the capture session around it, answered in one call for the
+//! quick-search overlay and MCP assistants.
+
+use std::collections::HashMap;
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::credential_manager::{decrypt_row_key_with_cng, decrypt_with_master_key};
+
+use super::capture_day::format_sql_timestamp;
+use super::{ContextAt, ContextSession, DigestBucket, StorageState};
+
+/// Captures further apart than this start a new session.
+const SESSION_GAP_SECS: i64 = 5 * 60;
+/// How far either side of the anchor screenshot a session may extend.
+const SESSION_MAX_SPAN_SECS: i64 = 4 * 60 * 60;
+const SESSION_TOP_N: usize = 5;
+/// Default for how far away the nearest screenshot may be before the answer is "nothing".
+const CONTEXT_DEFAULT_MAX_DISTANCE_SECS: i64 = 30 * 60;
+
+/// Live, committed screenshots; matches `idx_screenshots_deleted_created_at`.
+const CONTEXT_ROW_FILTER: &str = "is_deleted = 0 AND (status IS NULL OR status = 'committed')";
+
+/// One screenshot considered for the session summary.
+struct SessionRow {
+    ts: i64,
+    process_name: Option<String>,
+    process_name_enc: Option<Vec<u8>>,
+    key_enc: Option<Vec<u8>>,
+    category: Option<String>,
+}
+
+/// Expand from `anchor` while neighbouring captures are at most `gap` seconds apart.
+/// `timestamps` must be sorted ascending. Returns inclusive index bounds.
+fn session_bounds(timestamps: &[i64], anchor: usize, gap: i64) -> (usize, usize) {
+    let mut start = anchor;
+    while start > 0 && timestamps[start] - timestamps[start - 1] <= gap {
+        start -= 1;
+    }
+    let mut end = anchor;
+    while end + 1 < timestamps.len() && timestamps[end + 1] - timestamps[end] <= gap {
+        end += 1;
+    }
+    (start, end)
+}
+
+fn top_buckets(counts: HashMap<String, i64>) -> Vec<DigestBucket> {
+    let mut buckets: Vec<DigestBucket> = counts
+        .into_iter()
+        .map(|(label, count)| DigestBucket { label, count })
+        .collect();
+    buckets.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.label.cmp(&b.label)));
+    buckets.truncate(SESSION_TOP_N);
+    buckets
+}
+
+fn nearest_on_side(
+    conn: &Connection,
+    at: &str,
+    before: bool,
+) -> Result<Option<(i64, i64)>, String> {
+    let sql = if before {
+        format!(
+            "SELECT id, CAST(strftime('%s', created_at) AS INTEGER) FROM screenshots
+             WHERE {} AND created_at <= ?1 ORDER BY created_at DESC LIMIT 1",
+            CONTEXT_ROW_FILTER
+        )
+    } else {
+        format!(
+            "SELECT id, CAST(strftime('%s', created_at) AS INTEGER) FROM screenshots
+             WHERE {} AND created_at > ?1 ORDER BY created_at ASC LIMIT 1",
+            CONTEXT_ROW_FILTER
+        )
+    };
+    conn.query_row(&sql, params![at], |row| Ok((row.get(0)?, row.get(1)?)))
+        .optional()
+        .map_err(|e| format!("Failed to query nearest screenshot: {}", e))
+}
+
+impl StorageState {
+    /// Answer "what was I doing at `timestamp`" (Unix seconds) in one call.
+    ///
+    /// Finds the nearest screenshot on either side with two indexed lookups and, if it
+    /// is within `max_distance_secs`, returns it (decrypted) together with the session
+    /// around it: consecutive captures no more than five minutes apart, summarised as
+    /// time bounds, a count, and the top apps and categories.
+    pub fn get_context_at(
+        &self,
+        timestamp: f64,
+        max_distance_secs: Option<i64>,
+    ) -> Result<ContextAt, String> {
+        let at_secs = timestamp as i64;
+        let max_distance = max_distance_secs
+            .unwrap_or(CONTEXT_DEFAULT_MAX_DISTANCE_SECS)
+            .max(0);
+        let at = format_sql_timestamp(at_secs)?;
+
+        let anchor = {
+            let guard = self.get_connection_named("get_context_at")?;
+            let conn = guard.as_ref().unwrap();
+            let before = nearest_on_side(conn, &at, true)?;
+            let after = nearest_on_side(conn, &at, false)?;
+            match (before, after) {
+                (Some(b), Some(a)) => Some(if at_secs - b.1 <= a.1 - at_secs { b } else { a }),
+                (b, a) => b.or(a),
+            }
+        };
+
+        let (anchor_id, anchor_ts) = match anchor {
+            Some((id, ts)) if (ts - at_secs).abs() <= max_distance => (id, ts),
+            _ => {
+                return Ok(ContextAt {
+                    requested_ts: at_secs,
+                    screenshot: None,
+                    distance_secs: None,
+                    session: None,
+                })
+            }
+        };
+
+        let screenshot = self.get_screenshot_by_id(anchor_id)?;
+        let session = self.context_session_around(anchor_ts)?;
+
+        Ok(ContextAt {
+            requested_ts: at_secs,
+            screenshot,
+            distance_secs: Some((anchor_ts - at_secs).abs()),
+            session,
+        })
+    }
+
+    fn context_session_around(&self, anchor_ts: i64) -> Result<Option<ContextSession>, String> {
+        let window_start = format_sql_timestamp(anchor_ts - SESSION_MAX_SPAN_SECS)?;
+        let window_end = format_sql_timestamp(anchor_ts + SESSION_MAX_SPAN_SECS)?;
+
+        let rows: Vec<SessionRow> = {
+            let guard = self.get_connection_named("get_context_at_session")?;
+            let conn = guard.as_ref().unwrap();
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT CAST(strftime('%s', created_at) AS INTEGER), process_name,
+                            process_name_enc, content_key_encrypted, category
+                     FROM screenshots
+                     WHERE {} AND created_at BETWEEN ?1 AND ?2
+                     ORDER BY created_at ASC",
+                    CONTEXT_ROW_FILTER
+                ))
+                .map_err(|e| format!("Failed to prepare session query: {}", e))?;
+            let rows = stmt
+                .query_map(params![window_start, window_end], |row| {
+                    Ok(SessionRow {
+                        ts: row.get(0)?,
+                        process_name: row.get(1)?,
+                        process_name_enc: row.get(2)?,
+                        key_enc: row.get(3)?,
+                        category: row.get(4)?,
+                    })
+                })
+                .map_err(|e| format!("Failed to query session: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows
+            // guard dropped — mutex released before row-key decryption
+        };
+
+        let timestamps: Vec<i64> = rows.iter().map(|r| r.ts).collect();
+        let anchor = match timestamps.iter().position(|ts| *ts == anchor_ts) {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        let (start, end) = session_bounds(&timestamps, anchor, SESSION_GAP_SECS);
+
+        let can_decrypt = self.credential_state.is_session_valid();
+        let mut apps: HashMap<String, i64> = HashMap::new();
+        let mut categories: HashMap<String, i64> = HashMap::new();
+        for row in &rows[start..=end] {
+            let process_name = row
+                .process_name
+                .clone()
+                .filter(|p| !p.is_empty())
+                .or_else(|| {
+                    if !can_decrypt {
+                        return None;
+                    }
+                    let data = row.process_name_enc.as_ref()?;
+                    let mut row_key = decrypt_row_key_with_cng(row.key_enc.as_ref()?).ok()?;
+                    let name = decrypt_with_master_key(&row_key, data)
+                        .ok()
+                        .and_then(|v| String::from_utf8(v).ok());
+                    Self::zeroize_bytes(&mut row_key);
+                    name
+                });
+            if let Some(name) = process_name {
+                *apps.entry(name).or_insert(0) += 1;
+            }
+            let category = row
+                .category
+                .clone()
+                .filter(|c| !c.is_empty())
+                .unwrap_or_else(|| "uncategorized".to_string());
+            *categories.entry(category).or_insert(0) += 1;
+        }
+
+        Ok(Some(ContextSession {
+            start_ts: timestamps[start],
+            end_ts: timestamps[end],
+            screenshot_count: (end - start + 1) as i64,
+            apps: top_buckets(apps),
+            categories: top_buckets(categories),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_storage;
+    use chrono::DateTime;
+
+    fn context_storage() -> (tempfile::TempDir, StorageState) {
+        let (temp, storage) = test_storage();
+        storage
+            .db
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .expect("test database")
+            .execute_batch(
+                "INSERT INTO screenshots (image_path, image_hash, window_title, process_name, category, created_at, is_deleted, status) VALUES
+                    ('a', 'h1', 'main.rs', 'code.exe', 'coding', '2026-03-02 10:00:00', 0, NULL),
+                    ('b', 'h2', 'lib.rs', 'code.exe', 'coding', '2026-03-02 10:03:00', 0, 'committed'),
+                    ('c', 'h3', 'Docs', 'chrome.exe', NULL, '2026-03-02 10:06:00', 0, NULL),
+                    ('d', 'h4', 'Deleted', 'chrome.exe', NULL, '2026-03-02 10:07:00', 1, NULL),
+                    ('e', 'h5', 'Pending', 'chrome.exe', NULL, '2026-03-02 10:08:00', 0, 'pending'),
+                    ('f', 'h6', 'Lunch', 'slack.exe', 'chat', '2026-03-02 12:00:00', 0, NULL);",
+            )
+            .expect("context fixture");
+        (temp, storage)
+    }
+
+    fn ts(rfc3339: &str) -> f64 {
+        DateTime::parse_from_rfc3339(rfc3339).unwrap().timestamp() as f64
+    }
+
+    #[test]
+    fn session_bounds_stop_at_gaps() {
+        let timestamps = [0, 100, 200, 900, 1000];
+        assert_eq!(session_bounds(&timestamps, 1, 300), (0, 2));
+        assert_eq!(session_bounds(&timestamps, 4, 300), (3, 4));
+        assert_eq!(session_bounds(&timestamps, 0, 50), (0, 0));
+    }
+
+    #[test]
+    fn context_returns_nearest_screenshot_and_session_summary() {
+        let (_temp, storage) = context_storage();
+
+        let context = storage
+            .get_context_at(ts("2026-03-02T10:05:00Z"), None)
+            .unwrap();
+        let screenshot = context.screenshot.expect("nearest screenshot");
+        assert_eq!(screenshot.image_hash, "h3");
+        assert_eq!(context.distance_secs, Some(60));
+
+        let session = context.session.expect("session summary");
+        assert_eq!(session.screenshot_count, 3);
+        assert_eq!(session.start_ts, ts("2026-03-02T10:00:00Z") as i64);
+        assert_eq!(session.end_ts, ts("2026-03-02T10:06:00Z") as i64);
+        assert_eq!(session.apps[0].label, "code.exe");
+        assert_eq!(session.apps[0].count, 2);
+
+        let empty = storage
+            .get_context_at(ts("2026-03-02T11:00:00Z"), None)
+            .unwrap();
+        assert!(empty.screenshot.is_none());
+        assert!(empty.session.is_none());
+    }
+}
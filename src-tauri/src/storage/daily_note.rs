@@ -0,0 +1,398 @@
+//! Markdown daily notes for note-taking vaults (Obsidian and similar).
+//!
+//! A note lists the day's activity sessions and highlights the longest ones with an
+//! exported image and the longest OCR lines of their last screenshot. Images are
+//! written as JPEG under `attachments/carbonpaper/<date>/` next to the note and linked
+//! relatively, so the vault stays portable. Notes carry `generator: carbonpaper` in
+//! their front matter and a note without it is never overwritten, so the user's own
+//! daily notes are safe even when the vault folder is shared.
+//!
+//! The `daily_note` scheduled job keeps today's note (and yesterday's, once) up to
+//! date in the folder saved under [`DAILY_NOTE_VAULT_REGISTRY_KEY`].
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use chrono::{Duration, Local, NaiveDate, TimeZone};
+use rusqlite::OptionalExtension;
+
+use super::{ActivitySession, BackgroundReadError, DailyNoteSummary, StorageState};
+
+/// Registry value holding the vault folder used by the scheduled job.
+pub(crate) const DAILY_NOTE_VAULT_REGISTRY_KEY: &str = "daily_note_vault_dir";
+
+const GENERATOR_MARKER: &str = "generator: carbonpaper";
+/// Sessions shorter than this are left out of the list.
+const MIN_SESSION_SECS: i64 = 60;
+const MAX_LISTED_SESSIONS: usize = 60;
+const HIGHLIGHT_SESSIONS: usize = 5;
+const SNIPPETS_PER_HIGHLIGHT: usize = 3;
+const MIN_SNIPPET_CHARS: usize = 8;
+const MAX_SNIPPET_CHARS: usize = 160;
+const IMAGE_MAX_WIDTH: u32 = 1280;
+const IMAGE_JPEG_QUALITY: u8 = 80;
+
+/// A highlighted session with its exported image and OCR snippets.
+struct Highlight {
+    session: ActivitySession,
+    /// Path relative to the note
+    image_link: Option<String>,
+    snippets: Vec<String>,
+}
+
+/// The vault folder configured for the scheduled job.
+pub fn daily_note_vault_dir() -> Option<PathBuf> {
+    crate::registry_config::get_string(DAILY_NOTE_VAULT_REGISTRY_KEY)
+        .filter(|dir| !dir.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+/// Save or clear (`None`) the vault folder used by the scheduled job.
+pub fn set_daily_note_vault_dir(dir: Option<&str>) -> Result<(), String> {
+    match dir.map(str::trim).filter(|dir| !dir.is_empty()) {
+        Some(dir) => {
+            if !Path::new(dir).is_dir() {
+                return Err(format!("Vault folder does not exist: {}", dir));
+            }
+            crate::registry_config::set_string(DAILY_NOTE_VAULT_REGISTRY_KEY, dir)
+        }
+        None => crate::registry_config::delete_value(DAILY_NOTE_VAULT_REGISTRY_KEY),
+    }
+}
+
+/// Longest distinct OCR lines worth quoting, longest first.
+fn pick_snippets(lines: &[String]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut candidates: Vec<String> = lines
+        .iter()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| line.chars().count() >= MIN_SNIPPET_CHARS)
+        .filter(|line| seen.insert(line.clone()))
+        .collect();
+    candidates.sort_by_key(|line| std::cmp::Reverse(line.chars().count()));
+    candidates
+        .into_iter()
+        .take(SNIPPETS_PER_HIGHLIGHT)
+        .map(|line| {
+            if line.chars().count() > MAX_SNIPPET_CHARS {
+                let cut: String = line.chars().take(MAX_SNIPPET_CHARS).collect();
+                format!("{}…", cut)
+            } else {
+                line
+            }
+        })
+        .collect()
+}
+
+fn format_duration(secs: i64) -> String {
+    let minutes = (secs + 30) / 60;
+    if minutes >= 60 {
+        format!("{} h {} min", minutes / 60, minutes % 60)
+    } else {
+        format!("{} min", minutes.max(1))
+    }
+}
+
+fn format_clock(ts: i64) -> String {
+    Local
+        .timestamp_opt(ts, 0)
+        .single()
+        .map(|dt| dt.format("%H:%M").to_string())
+        .unwrap_or_default()
+}
+
+fn session_name(session: &ActivitySession) -> &str {
+    session.process_name.as_deref().unwrap_or("Unknown")
+}
+
+/// Render the note body.
+fn render_daily_note(
+    date: NaiveDate,
+    sessions: &[ActivitySession],
+    highlights: &[Highlight],
+) -> String {
+    let screenshots: i64 = sessions.iter().map(|s| s.screenshot_count).sum();
+    let mut note = format!(
+        "---\ndate: {}\n{}\nscreenshots: {}\ntags: [carbonpaper]\n---\n\n# {}\n\n",
+        date, GENERATOR_MARKER, screenshots, date
+    );
+
+    note.push_str("## Sessions\n\n");
+    let listed: Vec<&ActivitySession> = sessions
+        .iter()
+        .filter(|s| s.duration_secs >= MIN_SESSION_SECS)
+        .take(MAX_LISTED_SESSIONS)
+        .collect();
+    if listed.is_empty() {
+        note.push_str("_No sessions recorded._\n");
+    }
+    for session in listed {
+        note.push_str(&format!(
+            "- {}–{} **{}** ({}, {} screenshots)\n",
+            format_clock(session.start_time),
+            format_clock(session.end_time),
+            session_name(session),
+            format_duration(session.duration_secs),
+            session.screenshot_count
+        ));
+    }
+
+    if !highlights.is_empty() {
+        note.push_str("\n## Highlights\n");
+    }
+    for highlight in highlights {
+        let session = &highlight.session;
+        note.push_str(&format!(
+            "\n### {} {}–{} ({})\n\n",
+            session_name(session),
+            format_clock(session.start_time),
+            format_clock(session.end_time),
+            format_duration(session.duration_secs)
+        ));
+        if let Some(link) = &highlight.image_link {
+            note.push_str(&format!("![{}]({})\n\n", session_name(session), link));
+        }
+        for snippet in &highlight.snippets {
+            note.push_str(&format!("> {}\n>\n", snippet));
+        }
+        if !highlight.snippets.is_empty() {
+            // Drop the trailing empty quote line.
+            note.truncate(note.len() - 2);
+            note.push('\n');
+        }
+    }
+    note
+}
+
+impl StorageState {
+    /// Write the Markdown note of local day `date` to `<vault_dir>/<date>.md`.
+    ///
+    /// `unattended` runs read images and OCR text without any CNG prompt and leave
+    /// out what cannot be decrypted that way.
+    pub fn export_daily_note(
+        &self,
+        date: NaiveDate,
+        vault_dir: &Path,
+        unattended: bool,
+    ) -> Result<DailyNoteSummary, String> {
+        let note_path = vault_dir.join(format!("{}.md", date));
+        if let Ok(existing) = std::fs::read_to_string(&note_path) {
+            if !existing.contains(GENERATOR_MARKER) {
+                return Err(format!(
+                    "{} exists and was not written by CarbonPaper",
+                    note_path.display()
+                ));
+            }
+        }
+
+        let start = Local
+            .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap_or_default())
+            .earliest()
+            .ok_or_else(|| format!("Invalid local date: {}", date))?;
+        let end = start + Duration::days(1);
+        let sessions =
+            self.get_activity_sessions(start.timestamp() as f64, end.timestamp() as f64 - 1.0)?;
+
+        let mut ranked: Vec<&ActivitySession> = sessions.iter().collect();
+        ranked.sort_by_key(|s| std::cmp::Reverse((s.duration_secs, s.screenshot_count)));
+        ranked.truncate(HIGHLIGHT_SESSIONS);
+        ranked.sort_by_key(|s| s.start_time);
+
+        let attachment_rel = format!("attachments/carbonpaper/{}", date);
+        let attachment_dir = vault_dir.join(&attachment_rel);
+        let mut highlights = Vec::with_capacity(ranked.len());
+        let mut images = 0usize;
+        for session in ranked {
+            let screenshot_id = session.last_screenshot_id;
+            let image_link =
+                match self.export_note_image(screenshot_id, &attachment_dir, unattended) {
+                    Ok(Some(file_name)) => {
+                        images += 1;
+                        Some(format!("{}/{}", attachment_rel, file_name))
+                    }
+                    Ok(None) => None,
+                    Err(error) => {
+                        tracing::warn!(
+                            "Daily note image failed for screenshot {}: {}",
+                            screenshot_id,
+                            error
+                        );
+                        None
+                    }
+                };
+            let snippets = match self.note_ocr_lines(screenshot_id, unattended) {
+                Ok(lines) => pick_snippets(&lines),
+                Err(error) => {
+                    tracing::warn!(
+                        "Daily note OCR text failed for screenshot {}: {}",
+                        screenshot_id,
+                        error
+                    );
+                    Vec::new()
+                }
+            };
+            highlights.push(Highlight {
+                session: session.clone(),
+                image_link,
+                snippets,
+            });
+        }
+
+        std::fs::create_dir_all(vault_dir)
+            .map_err(|e| format!("Failed to create vault folder: {}", e))?;
+        std::fs::write(&note_path, render_daily_note(date, &sessions, &highlights))
+            .map_err(|e| format!("Failed to write daily note: {}", e))?;
+
+        if !unattended {
+            let screenshot_ids: Vec<i64> = highlights
+                .iter()
+                .map(|h| h.session.last_screenshot_id)
+                .collect();
+            self.record_access_audit(
+                "export_daily_note",
+                &serde_json::json!({
+                    "path": note_path.display().to_string(),
+                    "screenshot_ids": screenshot_ids,
+                }),
+            )?;
+        }
+        Ok(DailyNoteSummary {
+            path: note_path.display().to_string(),
+            date: date.to_string(),
+            sessions: sessions.len(),
+            highlights: highlights.len(),
+            images,
+        })
+    }
+
+    /// Export one screenshot as a downscaled JPEG; `None` when it cannot be read
+    /// without a prompt in unattended mode.
+    fn export_note_image(
+        &self,
+        screenshot_id: i64,
+        dir: &Path,
+        unattended: bool,
+    ) -> Result<Option<String>, String> {
+        let file_name = format!("carbonpaper-{}.jpg", screenshot_id);
+        if dir.join(&file_name).exists() {
+            return Ok(Some(file_name));
+        }
+        // Only the path is needed; loading the record would decrypt its metadata.
+        let image_path: Option<String> = self
+            .open_read_connection_named("daily_note_image")?
+            .query_row(
+                "SELECT image_path FROM screenshots WHERE id = ?1 AND is_deleted = 0",
+                [screenshot_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to query screenshot: {}", e))?;
+        let Some(image_path) = image_path else {
+            return Ok(None);
+        };
+        let bytes = if unattended {
+            match self.read_image_bytes_silent(&image_path) {
+                Ok((bytes, _)) => bytes,
+                Err(BackgroundReadError::AuthRequired) => return Ok(None),
+                Err(error) => return Err(error.to_string()),
+            }
+        } else {
+            self.read_image_bytes(&image_path)?.0
+        };
+        let mut image = image::load_from_memory(&bytes)
+            .map_err(|e| format!("Failed to decode screenshot image: {}", e))?;
+        if image.width() > IMAGE_MAX_WIDTH {
+            image = image.resize(
+                IMAGE_MAX_WIDTH,
+                u32::MAX,
+                image::imageops::FilterType::Triangle,
+            );
+        }
+        let mut jpeg = Vec::new();
+        image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, IMAGE_JPEG_QUALITY)
+            .encode_image(&image.to_rgb8())
+            .map_err(|e| format!("Failed to encode note image: {}", e))?;
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create attachment folder: {}", e))?;
+        std::fs::write(dir.join(&file_name), jpeg)
+            .map_err(|e| format!("Failed to write note image: {}", e))?;
+        Ok(Some(file_name))
+    }
+
+    /// OCR lines of one screenshot in reading order.
+    fn note_ocr_lines(&self, screenshot_id: i64, unattended: bool) -> Result<Vec<String>, String> {
+        let rows = self.fetch_encrypted_ocr_rows(&[screenshot_id], "daily_note_ocr")?;
+        let mut lines = Vec::with_capacity(rows.len());
+        for (_, text_enc, text_key_enc) in rows {
+            let (Some(data), Some(key)) = (text_enc, text_key_enc) else {
+                continue;
+            };
+            let bytes = if unattended {
+                match self.decrypt_payload_with_row_key_silent(&data, &key) {
+                    Ok(bytes) => bytes,
+                    Err(BackgroundReadError::AuthRequired) => return Ok(Vec::new()),
+                    Err(error) => return Err(error.to_string()),
+                }
+            } else {
+                self.decrypt_payload_with_row_key(&data, &key)?
+            };
+            if let Ok(text) = String::from_utf8(bytes) {
+                lines.push(text);
+            }
+        }
+        Ok(lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snippets_prefer_long_distinct_lines() {
+        let lines: Vec<String> = [
+            "OK",
+            "Quarterly revenue grew 12%",
+            "Quarterly  revenue grew 12%",
+            "Meeting notes for Monday",
+            "short",
+            &"x".repeat(200),
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let snippets = pick_snippets(&lines);
+        assert_eq!(snippets.len(), 3);
+        assert_eq!(snippets[0].chars().count(), MAX_SNIPPET_CHARS + 1);
+        assert_eq!(snippets[1], "Quarterly revenue grew 12%");
+        assert_eq!(snippets[2], "Meeting notes for Monday");
+    }
+
+    #[test]
+    fn note_lists_sessions_and_quotes_highlights() {
+        let date = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let session = |process: &str, duration_secs: i64| ActivitySession {
+            id: 1,
+            process_name: Some(process.to_string()),
+            start_time: 1_772_438_400,
+            end_time: 1_772_438_400 + duration_secs,
+            duration_secs,
+            screenshot_count: 10,
+            first_screenshot_id: 1,
+            last_screenshot_id: 10,
+        };
+        let sessions = vec![session("Code.exe", 2700), session("explorer.exe", 20)];
+        let highlights = vec![Highlight {
+            session: sessions[0].clone(),
+            image_link: Some("attachments/carbonpaper/2026-03-02/carbonpaper-10.jpg".into()),
+            snippets: vec!["fn main() {".into(), "cargo build --release".into()],
+        }];
+        let note = render_daily_note(date, &sessions, &highlights);
+        assert!(note.starts_with("---\ndate: 2026-03-02\ngenerator: carbonpaper\n"));
+        assert!(note.contains("screenshots: 20\n"));
+        assert!(note.contains("**Code.exe** (45 min, 10 screenshots)"));
+        assert!(!note.contains("**explorer.exe**"));
+        assert!(note.contains("![Code.exe](attachments/carbonpaper/2026-03-02/carbonpaper-10.jpg)"));
+        assert!(note.ends_with("> fn main() {\n>\n> cargo build --release\n"));
+    }
+}
@@ -0,0 +1,273 @@
+//! First-run data directory selection.
+//!
+//! Before credentials exist nothing has been written to the default data directory
+//! yet, so setup can point storage at a user-chosen location directly instead of
+//! initializing under `%LOCALAPPDATA%` and migrating afterwards. The helpers here list
+//! candidate drives, estimate monthly growth for the chosen capture settings, and
+//! reject locations that are unwritable or synced by a cloud client.
+
+use std::path::{Component, Path, PathBuf};
+
+use sysinfo::Disks;
+
+use super::image_codec::StoredImageCodec;
+use super::{DataDirValidation, DataDrive, StorageState, StorageUsageEstimate};
+
+/// Folder names created by common sync clients. Syncing a live SQLite database and
+/// thousands of small encrypted files corrupts the former and floods the latter.
+const CLOUD_SYNC_FOLDERS: [(&str, &str); 7] = [
+    ("onedrive", "OneDrive"),
+    ("dropbox", "Dropbox"),
+    ("google drive", "Google Drive"),
+    ("googledrive", "Google Drive"),
+    ("icloud drive", "iCloud Drive"),
+    ("iclouddrive", "iCloud Drive"),
+    ("box", "Box"),
+];
+/// Environment variables the OneDrive client sets to its sync roots.
+const ONEDRIVE_ENV_VARS: [&str; 3] = ["OneDrive", "OneDriveConsumer", "OneDriveCommercial"];
+
+/// Captures are downscaled to this longest side before saving (`CaptureConfig::max_side`).
+const ESTIMATE_FRAME_MEGAPIXELS: f64 = 1600.0 * 900.0 / 1_000_000.0;
+/// Typical stored bytes per megapixel of desktop content, measured on mixed
+/// text/UI captures. Lossy figures are at quality 80.
+const PNG_BYTES_PER_MEGAPIXEL: f64 = 420_000.0;
+const WEBP_BYTES_PER_MEGAPIXEL: f64 = 95_000.0;
+/// OCR rows, search index postings, embeddings and thumbnail per capture.
+const PER_FRAME_OVERHEAD_BYTES: u64 = 24_000;
+/// Share of captures kept after near-duplicate frames are skipped.
+const ESTIMATE_KEPT_FRAME_RATIO: f64 = 0.6;
+const ESTIMATE_DAYS_PER_MONTH: u64 = 30;
+
+const WRITE_PROBE_FILE_NAME: &str = ".carbonpaper_write_probe";
+
+/// Mounted drives that could hold the data directory, largest free space first.
+pub fn list_data_drives() -> Vec<DataDrive> {
+    let disks = Disks::new_with_refreshed_list();
+    let mut drives: Vec<DataDrive> = disks
+        .list()
+        .iter()
+        .filter(|disk| disk.total_space() > 0)
+        .map(|disk| DataDrive {
+            mount_point: disk.mount_point().to_string_lossy().to_string(),
+            name: disk.name().to_string_lossy().to_string(),
+            file_system: disk.file_system().to_string_lossy().to_string(),
+            total_bytes: disk.total_space(),
+            available_bytes: disk.available_space(),
+            is_removable: disk.is_removable(),
+        })
+        .collect();
+    drives.sort_by(|a, b| b.available_bytes.cmp(&a.available_bytes));
+    drives
+}
+
+/// Rough monthly growth for `codec`/`quality` capturing every `interval_secs` for
+/// `active_hours_per_day` hours.
+pub fn estimate_monthly_usage(
+    codec: StoredImageCodec,
+    quality: u8,
+    interval_secs: u64,
+    active_hours_per_day: f64,
+) -> StorageUsageEstimate {
+    let per_megapixel = match codec {
+        StoredImageCodec::Png => PNG_BYTES_PER_MEGAPIXEL,
+        StoredImageCodec::Webp => WEBP_BYTES_PER_MEGAPIXEL * lossy_quality_factor(quality),
+    };
+    let bytes_per_frame =
+        (per_megapixel * ESTIMATE_FRAME_MEGAPIXELS) as u64 + PER_FRAME_OVERHEAD_BYTES;
+    let active_secs = active_hours_per_day.clamp(0.0, 24.0) * 3600.0;
+    let frames_per_day =
+        (active_secs / interval_secs.max(1) as f64 * ESTIMATE_KEPT_FRAME_RATIO).round() as u64;
+    StorageUsageEstimate {
+        bytes_per_frame,
+        frames_per_day,
+        bytes_per_month: bytes_per_frame * frames_per_day * ESTIMATE_DAYS_PER_MONTH,
+    }
+}
+
+/// Lossy encoders grow roughly quadratically with quality; 1.0 at quality 80.
+fn lossy_quality_factor(quality: u8) -> f64 {
+    let curve = |q: f64| 0.3 + 0.7 * (q / 100.0).powi(2);
+    curve(f64::from(quality.clamp(1, 100))) / curve(80.0)
+}
+
+/// Name of the sync client owning `path`, if any.
+fn cloud_sync_provider(path: &Path) -> Option<String> {
+    for var in ONEDRIVE_ENV_VARS {
+        if let Some(root) = std::env::var_os(var).filter(|root| !root.is_empty()) {
+            if path.starts_with(&root) {
+                return Some("OneDrive".to_string());
+            }
+        }
+    }
+    path.components().find_map(|component| {
+        let Component::Normal(name) = component else {
+            return None;
+        };
+        let name = name.to_string_lossy().to_lowercase();
+        CLOUD_SYNC_FOLDERS
+            .iter()
+            .find(|(folder, _)| {
+                // OneDrive for Business folders are named "OneDrive - <Org>".
+                name == *folder || name.starts_with(&format!("{} - ", folder))
+            })
+            .map(|(_, provider)| provider.to_string())
+    })
+}
+
+/// Nearest ancestor of `path` (itself included) that exists on disk.
+fn existing_ancestor(path: &Path) -> Option<&Path> {
+    path.ancestors().find(|candidate| candidate.is_dir())
+}
+
+fn probe_writable(dir: &Path) -> bool {
+    let probe = dir.join(WRITE_PROBE_FILE_NAME);
+    let ok = std::fs::write(&probe, b"ok").is_ok();
+    let _ = std::fs::remove_file(&probe);
+    ok
+}
+
+fn available_bytes_for(path: &Path) -> Option<u64> {
+    let canonical = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let disks = Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|disk| canonical.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+}
+
+/// Check whether `root` can hold the data directory. Like `storage_migrate_data_dir`,
+/// the user picks a storage root and data lives in its `data` subdirectory.
+/// Nothing is created on disk.
+pub fn validate_data_root(root: &str) -> DataDirValidation {
+    let root_path = PathBuf::from(root.trim());
+    let data_dir = root_path.join("data");
+    let mut problems = Vec::new();
+
+    if root.trim().is_empty() || !root_path.is_absolute() {
+        problems.push("NOT_ABSOLUTE".to_string());
+    }
+    let cloud_provider = cloud_sync_provider(&data_dir);
+    if cloud_provider.is_some() {
+        problems.push("CLOUD_SYNCED".to_string());
+    }
+    let ancestor = existing_ancestor(&data_dir);
+    let writable = ancestor.map(probe_writable).unwrap_or(false);
+    if !writable {
+        problems.push("NOT_WRITABLE".to_string());
+    }
+
+    DataDirValidation {
+        ok: problems.is_empty(),
+        data_dir: data_dir.to_string_lossy().to_string(),
+        writable,
+        cloud_provider,
+        available_bytes: ancestor.and_then(available_bytes_for),
+        has_existing_data: data_dir.join("screenshots.db").is_file(),
+        problems,
+    }
+}
+
+impl StorageState {
+    /// Point storage at `<root>/data` during first-run setup.
+    ///
+    /// Only allowed before credentials or the database have been created; afterwards
+    /// the data must be moved with `storage_migrate_data_dir`. Storage is initialized
+    /// at the new location by the credentials setup step.
+    pub fn set_initial_data_dir(&self, root: &str) -> Result<DataDirValidation, String> {
+        if self.is_read_only() {
+            return Err("READ_ONLY_MODE".to_string());
+        }
+        let current = self
+            .data_dir
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let initialized = self.db.lock().unwrap_or_else(|e| e.into_inner()).is_some();
+        if initialized
+            || self.credential_state.master_key_file_exists()
+            || current.join("screenshots.db").is_file()
+        {
+            return Err("DATA_DIR_ALREADY_INITIALIZED".to_string());
+        }
+
+        let validation = validate_data_root(root);
+        if !validation.ok {
+            return Err(format!(
+                "Invalid data directory: {}",
+                validation.problems.join(", ")
+            ));
+        }
+        let data_dir = PathBuf::from(&validation.data_dir);
+        std::fs::create_dir_all(&data_dir)
+            .map_err(|e| format!("Failed to create data directory: {}", e))?;
+        crate::registry_config::set_string("data_dir", &validation.data_dir)
+            .map_err(|e| format!("Failed to persist data_dir to registry: {}", e))?;
+
+        {
+            let mut data_guard = self.data_dir.lock().unwrap_or_else(|e| e.into_inner());
+            *data_guard = data_dir.clone();
+            let mut ss_guard = self
+                .screenshot_dir
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            *ss_guard = data_dir.join("screenshots");
+        }
+        self.credential_state.set_data_dir(data_dir.clone());
+        tracing::info!("First-run data directory set to {}", data_dir.display());
+        Ok(validation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cloud_sync_folders_are_detected_by_component() {
+        assert_eq!(
+            cloud_sync_provider(Path::new("/home/u/OneDrive - Contoso/CarbonPaper/data")),
+            Some("OneDrive".to_string())
+        );
+        assert_eq!(
+            cloud_sync_provider(Path::new("/home/u/Dropbox/data")),
+            Some("Dropbox".to_string())
+        );
+        assert_eq!(cloud_sync_provider(Path::new("/home/u/Boxes/data")), None);
+    }
+
+    #[test]
+    fn estimate_scales_with_codec_quality_and_interval() {
+        let png = estimate_monthly_usage(StoredImageCodec::Png, 80, 10, 8.0);
+        let webp = estimate_monthly_usage(StoredImageCodec::Webp, 80, 10, 8.0);
+        let webp_low = estimate_monthly_usage(StoredImageCodec::Webp, 40, 10, 8.0);
+        let sparse = estimate_monthly_usage(StoredImageCodec::Webp, 80, 20, 8.0);
+
+        assert!(png.bytes_per_frame > webp.bytes_per_frame);
+        assert!(webp_low.bytes_per_frame < webp.bytes_per_frame);
+        assert_eq!(webp.frames_per_day, 1728);
+        assert_eq!(sparse.frames_per_day, 864);
+        assert_eq!(
+            webp.bytes_per_month,
+            webp.bytes_per_frame * webp.frames_per_day * 30
+        );
+    }
+
+    #[test]
+    fn validation_does_not_create_the_data_directory() {
+        let temp = tempfile::tempdir().expect("temp root");
+        let root = temp.path().join("CarbonPaper");
+        let result = validate_data_root(&root.to_string_lossy());
+
+        assert!(result.ok, "{:?}", result.problems);
+        assert!(result.writable);
+        assert!(!result.has_existing_data);
+        assert!(!root.exists());
+        assert!(!temp.path().join(WRITE_PROBE_FILE_NAME).exists());
+
+        let relative = validate_data_root("relative/path");
+        assert!(relative.problems.contains(&"NOT_ABSOLUTE".to_string()));
+    }
+}
@@ -0,0 +1,234 @@
+//! Bounded LRU cache of decrypted images and unwrapped row keys, keyed by screenshot id.
+//!
+//! Scrubbing the timeline back and forth asks for the same frames again and again, and
+//! every read otherwise goes through CNG to unwrap the row key before decrypting the
+//! file. Entries are only stored and served while the UI session is valid; locking the
+//! session (`credential_lock_session`, or backgrounding the app) flushes the cache,
+//! and a session that merely timed out is flushed on the next lookup. Evicted and
+//! flushed bytes are zeroed before they are freed.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::credential_manager::decrypt_row_key_with_cng;
+use rusqlite::{params, OptionalExtension};
+
+use super::image_io::read_encrypted_image_bytes;
+use super::StorageState;
+
+/// Upper bound on the decrypted image bytes kept in memory.
+const MAX_IMAGE_BYTES: usize = 96 * 1024 * 1024;
+/// Upper bound on cached row keys.
+const MAX_ROW_KEYS: usize = 4096;
+
+/// Bytes that are zeroed when dropped.
+struct SecretBytes(Vec<u8>);
+
+impl Drop for SecretBytes {
+    fn drop(&mut self) {
+        StorageState::zeroize_bytes(&mut self.0);
+    }
+}
+
+struct CachedImage {
+    /// `image_path` the bytes were read from; a moved file is a miss.
+    image_path: String,
+    mime_type: String,
+    bytes: SecretBytes,
+}
+
+/// Least-recently-used map bounded by a total weight.
+struct LruMap<V> {
+    entries: HashMap<i64, (u64, usize, V)>,
+    /// Last-use tick to id, oldest first.
+    order: BTreeMap<u64, i64>,
+    tick: u64,
+    weight: usize,
+    max_weight: usize,
+}
+
+impl<V> LruMap<V> {
+    fn new(max_weight: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+            weight: 0,
+            max_weight,
+        }
+    }
+
+    fn get(&mut self, id: i64) -> Option<&V> {
+        let (used, _, _) = self.entries.get_mut(&id)?;
+        self.order.remove(used);
+        self.tick += 1;
+        *used = self.tick;
+        self.order.insert(self.tick, id);
+        self.entries.get(&id).map(|(_, _, value)| value)
+    }
+
+    fn insert(&mut self, id: i64, weight: usize, value: V) {
+        if weight > self.max_weight {
+            return;
+        }
+        self.remove(id);
+        while self.weight + weight > self.max_weight {
+            let Some((_, oldest)) = self.order.pop_first() else {
+                break;
+            };
+            if let Some((_, evicted, _)) = self.entries.remove(&oldest) {
+                self.weight -= evicted;
+            }
+        }
+        self.tick += 1;
+        self.order.insert(self.tick, id);
+        self.entries.insert(id, (self.tick, weight, value));
+        self.weight += weight;
+    }
+
+    fn remove(&mut self, id: i64) {
+        if let Some((used, weight, _)) = self.entries.remove(&id) {
+            self.order.remove(&used);
+            self.weight -= weight;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+        self.weight = 0;
+    }
+}
+
+/// Decrypted images and row keys of recently viewed screenshots.
+pub(super) struct DecryptCache {
+    images: LruMap<CachedImage>,
+    row_keys: LruMap<SecretBytes>,
+}
+
+impl Default for DecryptCache {
+    fn default() -> Self {
+        Self {
+            images: LruMap::new(MAX_IMAGE_BYTES),
+            row_keys: LruMap::new(MAX_ROW_KEYS),
+        }
+    }
+}
+
+impl StorageState {
+    /// Drop every cached image and row key.
+    pub fn flush_decrypt_cache(&self) {
+        let mut cache = self.decrypt_cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache.images.clear();
+        cache.row_keys.clear();
+    }
+
+    /// Whether the cache may be used; flushes it once the session has ended.
+    fn decrypt_cache_usable(&self) -> bool {
+        if self.credential_state.is_session_valid() {
+            return true;
+        }
+        self.flush_decrypt_cache();
+        false
+    }
+
+    /// Row key of a screenshot, unwrapped through CNG unless cached.
+    pub(super) fn cached_row_key(&self, id: i64, key_encrypted: &[u8]) -> Result<Vec<u8>, String> {
+        let usable = self.decrypt_cache_usable();
+        if usable {
+            let mut cache = self.decrypt_cache.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(key) = cache.row_keys.get(id) {
+                return Ok(key.0.clone());
+            }
+        }
+        let key = decrypt_row_key_with_cng(key_encrypted)
+            .map_err(|e| format!("Failed to unwrap image row key: {}", e))?;
+        if usable {
+            let mut cache = self.decrypt_cache.lock().unwrap_or_else(|e| e.into_inner());
+            cache.row_keys.insert(id, 1, SecretBytes(key.clone()));
+        }
+        Ok(key)
+    }
+
+    /// Decrypted image of a live screenshot, served from the cache when possible.
+    /// Returns `(bytes, mime type)`.
+    pub fn read_screenshot_image(&self, id: i64) -> Result<(Vec<u8>, String), String> {
+        let row: Option<(String, Option<Vec<u8>>)> = {
+            let conn = self.open_read_connection_named("read_screenshot_image")?;
+            conn.query_row(
+                "SELECT image_path, content_key_encrypted FROM screenshots
+                 WHERE id = ?1 AND is_deleted = 0",
+                params![id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to query screenshot: {}", e))?
+        };
+        let Some((image_path, key_encrypted)) = row else {
+            return Err("Image not found".to_string());
+        };
+
+        let usable = self.decrypt_cache_usable();
+        if usable {
+            let mut cache = self.decrypt_cache.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(image) = cache.images.get(id) {
+                if image.image_path == image_path {
+                    return Ok((image.bytes.0.clone(), image.mime_type.clone()));
+                }
+            }
+        }
+
+        let key_encrypted =
+            key_encrypted.ok_or_else(|| "Failed to unwrap image row key".to_string())?;
+        let mut row_key = self.cached_row_key(id, &key_encrypted)?;
+        let abs_path = self.resolve_image_path(&image_path);
+        let image = read_encrypted_image_bytes(&abs_path.to_string_lossy(), &row_key);
+        Self::zeroize_bytes(&mut row_key);
+        let (bytes, mime_type) = self
+            .compose_delta_frame(&abs_path, image?, false)
+            .map_err(|e| e.to_string())?;
+
+        if usable {
+            let mut cache = self.decrypt_cache.lock().unwrap_or_else(|e| e.into_inner());
+            cache.images.insert(
+                id,
+                bytes.len(),
+                CachedImage {
+                    image_path,
+                    mime_type: mime_type.clone(),
+                    bytes: SecretBytes(bytes.clone()),
+                },
+            );
+        }
+        Ok((bytes, mime_type))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lru_map_evicts_least_recently_used_by_weight() {
+        let mut map = LruMap::new(10);
+        map.insert(1, 4, "a");
+        map.insert(2, 4, "b");
+        assert_eq!(map.get(1), Some(&"a"));
+        // 1 was used last, so 2 goes first.
+        map.insert(3, 4, "c");
+        assert_eq!(map.get(2), None);
+        assert_eq!(map.get(1), Some(&"a"));
+        assert_eq!(map.get(3), Some(&"c"));
+        assert_eq!(map.weight, 8);
+
+        // Re-inserting replaces the entry instead of counting it twice.
+        map.insert(3, 2, "d");
+        assert_eq!(map.weight, 6);
+        // Oversized values are not cached at all.
+        map.insert(4, 11, "e");
+        assert_eq!(map.get(4), None);
+
+        map.clear();
+        assert_eq!(map.get(1), None);
+        assert_eq!(map.weight, 0);
+    }
+}
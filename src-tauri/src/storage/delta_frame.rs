@@ -0,0 +1,411 @@
+//! Delta-frame storage for mostly static screens.
+//!
+//! When enabled, consecutive captures of the same window (process, title and size)
+//! are compared against the chain's keyframe in 32×32 tiles. If only a small region
+//! changed, the screenshot's file holds just that region as an encrypted PNG patch
+//! (`*.delta.png.enc`) and the row records `delta_keyframe_id`, `delta_x` and
+//! `delta_y`. Reads decrypt the keyframe, paste the patch and return a full PNG.
+//!
+//! Patches always diff against the keyframe rather than the previous frame, so one
+//! reconstruction never needs more than two files. The keyframe is the first frame of
+//! the chain; when its first patch is written its image file is hard-linked (or
+//! copied) into `screenshots/keyframes/` and registered in `delta_keyframes` with a
+//! copy of its wrapped row key, so deleting the keyframe screenshot never breaks the
+//! patches. Keyframes no longer referenced are removed by the storage policy job.
+//!
+//! Chains end when the window changes, the changed area grows past
+//! [`MAX_DELTA_AREA_PERCENT`] or the chain reaches [`MAX_CHAIN_FRAMES`].
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::atomic::Ordering;
+
+use image::RgbImage;
+use rusqlite::{params, OptionalExtension};
+
+use crate::credential_manager::{
+    decrypt_row_key_with_cng, decrypt_row_key_with_cng_silent, CredentialError,
+};
+
+use super::image_io::decrypt_image_file;
+use super::{BackgroundReadError, SaveScreenshotRequest, StorageState};
+
+/// Registry value name used by `get_advanced_config` / `set_advanced_config`.
+pub(crate) const DELTA_FRAMES_REGISTRY_KEY: &str = "delta_frames_enabled";
+
+/// Extension (before `.enc`) of stored patches.
+pub(super) const DELTA_EXTENSION: &str = "delta.png";
+const KEYFRAME_DIR: &str = "keyframes";
+const TILE_SIZE: u32 = 32;
+/// Largest changed area, as a percentage of the frame, still stored as a patch.
+const MAX_DELTA_AREA_PERCENT: u64 = 25;
+/// Patches per keyframe before the next capture starts a new chain.
+const MAX_CHAIN_FRAMES: u32 = 720;
+
+/// The keyframe the next capture of the same window is compared against.
+pub(super) struct DeltaChain {
+    window: u64,
+    keyframe_screenshot_id: i64,
+    /// `delta_keyframes` row, created with the chain's first patch.
+    keyframe_id: Option<i64>,
+    frame: RgbImage,
+    frames: u32,
+}
+
+/// What `plan_delta_frame` decided for a capture.
+#[derive(Default)]
+pub(super) struct DeltaPlan {
+    /// Store this patch instead of the full image.
+    pub patch: Option<DeltaPatch>,
+    /// Start a new chain with this frame once the screenshot row exists.
+    pub next_keyframe: Option<(u64, RgbImage)>,
+}
+
+pub(super) struct DeltaPatch {
+    pub keyframe_id: i64,
+    pub x: u32,
+    pub y: u32,
+    pub png: Vec<u8>,
+}
+
+fn window_key(request: &SaveScreenshotRequest) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    request.process_name.hash(&mut hasher);
+    request.window_title.hash(&mut hasher);
+    request.width.hash(&mut hasher);
+    request.height.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Bounding box `(x, y, width, height)` of the tiles that differ, or `None` when
+/// the frames have different sizes. Identical frames yield the first tile.
+fn changed_rect(base: &RgbImage, frame: &RgbImage) -> Option<(u32, u32, u32, u32)> {
+    if base.dimensions() != frame.dimensions() {
+        return None;
+    }
+    let (width, height) = frame.dimensions();
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (u32::MAX, u32::MAX, 0, 0);
+    for tile_y in (0..height).step_by(TILE_SIZE as usize) {
+        for tile_x in (0..width).step_by(TILE_SIZE as usize) {
+            let tile_w = TILE_SIZE.min(width - tile_x);
+            let tile_h = TILE_SIZE.min(height - tile_y);
+            let differs = (tile_y..tile_y + tile_h).any(|y| {
+                (tile_x..tile_x + tile_w).any(|x| base.get_pixel(x, y) != frame.get_pixel(x, y))
+            });
+            if differs {
+                min_x = min_x.min(tile_x);
+                min_y = min_y.min(tile_y);
+                max_x = max_x.max(tile_x + tile_w);
+                max_y = max_y.max(tile_y + tile_h);
+            }
+        }
+    }
+    if min_x == u32::MAX {
+        return Some((0, 0, TILE_SIZE.min(width), TILE_SIZE.min(height)));
+    }
+    Some((min_x, min_y, max_x - min_x, max_y - min_y))
+}
+
+/// Whether a stored image path names a delta patch.
+pub(super) fn is_delta_path(path: &Path) -> bool {
+    path.to_string_lossy()
+        .contains(&format!(".{}.enc", DELTA_EXTENSION))
+}
+
+impl StorageState {
+    pub fn delta_frames_enabled(&self) -> bool {
+        self.delta_frames_enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_delta_frames_enabled(&self, enabled: bool) {
+        self.delta_frames_enabled.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            *self.delta_chain.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        }
+    }
+
+    /// Restore the setting saved by `set_advanced_config`.
+    pub(crate) fn load_delta_frames_from_registry(&self) {
+        let enabled = crate::registry_config::get_bool(DELTA_FRAMES_REGISTRY_KEY).unwrap_or(false);
+        self.set_delta_frames_enabled(enabled);
+    }
+
+    /// Decide whether a capture is stored as a patch against the current keyframe.
+    pub(super) fn plan_delta_frame(
+        &self,
+        request: &SaveScreenshotRequest,
+        image_data: &[u8],
+    ) -> DeltaPlan {
+        if !self.delta_frames_enabled() {
+            return DeltaPlan::default();
+        }
+        let frame = match image::load_from_memory(image_data) {
+            Ok(img) => img.to_rgb8(),
+            Err(e) => {
+                tracing::warn!("[DELTA] failed to decode capture: {}", e);
+                *self.delta_chain.lock().unwrap_or_else(|e| e.into_inner()) = None;
+                return DeltaPlan::default();
+            }
+        };
+        let window = window_key(request);
+
+        let mut chain_guard = self.delta_chain.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(chain) = chain_guard
+            .as_mut()
+            .filter(|chain| chain.window == window && chain.frames < MAX_CHAIN_FRAMES)
+        else {
+            return DeltaPlan {
+                patch: None,
+                next_keyframe: Some((window, frame)),
+            };
+        };
+        let (frame_w, frame_h) = frame.dimensions();
+        let rect = changed_rect(&chain.frame, &frame).filter(|(_, _, w, h)| {
+            (*w as u64 * *h as u64) * 100
+                <= frame_w as u64 * frame_h as u64 * MAX_DELTA_AREA_PERCENT
+        });
+        let Some((x, y, width, height)) = rect else {
+            return DeltaPlan {
+                patch: None,
+                next_keyframe: Some((window, frame)),
+            };
+        };
+
+        let keyframe_id = match chain.keyframe_id {
+            Some(id) => id,
+            None => match self.register_keyframe(chain.keyframe_screenshot_id) {
+                Ok(id) => {
+                    chain.keyframe_id = Some(id);
+                    id
+                }
+                Err(e) => {
+                    tracing::warn!("[DELTA] failed to register keyframe: {}", e);
+                    return DeltaPlan {
+                        patch: None,
+                        next_keyframe: Some((window, frame)),
+                    };
+                }
+            },
+        };
+
+        let region = image::imageops::crop_imm(&frame, x, y, width, height).to_image();
+        let mut png = std::io::Cursor::new(Vec::new());
+        if let Err(e) = region.write_to(&mut png, image::ImageFormat::Png) {
+            tracing::warn!("[DELTA] failed to encode patch: {}", e);
+            return DeltaPlan::default();
+        }
+        chain.frames += 1;
+        DeltaPlan {
+            patch: Some(DeltaPatch {
+                keyframe_id,
+                x,
+                y,
+                png: png.into_inner(),
+            }),
+            next_keyframe: None,
+        }
+    }
+
+    /// Make a freshly saved full frame the keyframe for following captures.
+    pub(super) fn start_delta_chain(&self, window: u64, screenshot_id: i64, frame: RgbImage) {
+        *self.delta_chain.lock().unwrap_or_else(|e| e.into_inner()) = Some(DeltaChain {
+            window,
+            keyframe_screenshot_id: screenshot_id,
+            keyframe_id: None,
+            frame,
+            frames: 0,
+        });
+    }
+
+    /// Link the keyframe screenshot's file into `keyframes/` and record it.
+    fn register_keyframe(&self, screenshot_id: i64) -> Result<i64, String> {
+        let (image_path, key_enc): (String, Vec<u8>) = {
+            let conn = self.open_read_connection_named("register_keyframe")?;
+            conn.query_row(
+                "SELECT image_path, content_key_encrypted FROM screenshots
+                 WHERE id = ?1 AND is_deleted = 0 AND content_key_encrypted IS NOT NULL",
+                params![screenshot_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to query keyframe screenshot: {}", e))?
+            .ok_or_else(|| "Keyframe screenshot no longer exists".to_string())?
+        };
+
+        // The capture may have been committed (and renamed) since it was saved.
+        let pending = self.resolve_image_path(&image_path);
+        let committed = self.resolve_image_path(image_path.trim_end_matches(".pending"));
+        let source = if committed.exists() {
+            committed
+        } else {
+            pending
+        };
+        let file_name = source
+            .file_name()
+            .and_then(|s| s.to_str())
+            .map(|name| name.trim_end_matches(".pending").to_string())
+            .ok_or_else(|| "Keyframe screenshot has no file name".to_string())?;
+        let keyframe_dir = self
+            .screenshot_dir
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .join(KEYFRAME_DIR);
+        std::fs::create_dir_all(&keyframe_dir)
+            .map_err(|e| format!("Failed to create keyframe directory: {}", e))?;
+        let target = keyframe_dir.join(&file_name);
+        if !target.exists() {
+            std::fs::hard_link(&source, &target)
+                .or_else(|_| std::fs::copy(&source, &target).map(|_| ()))
+                .map_err(|e| format!("Failed to store keyframe: {}", e))?;
+        }
+
+        let guard = self.get_connection_named("register_keyframe")?;
+        let conn = guard.as_ref().unwrap();
+        conn.execute(
+            "INSERT INTO delta_keyframes (image_path, key_encrypted) VALUES (?1, ?2)",
+            params![self.to_relative_image_path(&target), key_enc],
+        )
+        .map_err(|e| format!("Failed to record keyframe: {}", e))?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Rebuild the full frame for a patch read from `abs_path`; other images pass through.
+    pub(super) fn compose_delta_frame(
+        &self,
+        abs_path: &Path,
+        image: (Vec<u8>, String),
+        silent: bool,
+    ) -> Result<(Vec<u8>, String), BackgroundReadError> {
+        if !is_delta_path(abs_path) {
+            return Ok(image);
+        }
+        let rel_path = self.to_relative_image_path(abs_path);
+        let (x, y, keyframe_path, key_enc): (u32, u32, String, Vec<u8>) = {
+            let conn = self.open_read_connection_named("compose_delta_frame")?;
+            conn.query_row(
+                "SELECT s.delta_x, s.delta_y, k.image_path, k.key_encrypted
+                 FROM screenshots s
+                 JOIN delta_keyframes k ON k.id = s.delta_keyframe_id
+                 WHERE s.image_path = ?1",
+                params![rel_path],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to query delta keyframe: {}", e))?
+            .ok_or_else(|| format!("No keyframe recorded for {}", rel_path))?
+        };
+
+        let mut row_key = if silent {
+            decrypt_row_key_with_cng_silent(&key_enc)
+        } else {
+            decrypt_row_key_with_cng(&key_enc)
+        }
+        .map_err(|error| match error {
+            CredentialError::AuthRequired => BackgroundReadError::AuthRequired,
+            other => {
+                BackgroundReadError::Other(format!("Failed to unwrap keyframe row key: {}", other))
+            }
+        })?;
+        let keyframe = decrypt_image_file(&self.resolve_image_path(&keyframe_path), &row_key);
+        Self::zeroize_bytes(&mut row_key);
+        let (keyframe, _) = keyframe.map_err(|e| BackgroundReadError::Other(e.to_string()))?;
+
+        let mut frame = image::load_from_memory(&keyframe)
+            .map_err(|e| format!("Failed to decode keyframe: {}", e))?
+            .to_rgb8();
+        let patch = image::load_from_memory(&image.0)
+            .map_err(|e| format!("Failed to decode delta patch: {}", e))?
+            .to_rgb8();
+        image::imageops::replace(&mut frame, &patch, x as i64, y as i64);
+        let mut png = std::io::Cursor::new(Vec::new());
+        frame
+            .write_to(&mut png, image::ImageFormat::Png)
+            .map_err(|e| format!("Failed to encode delta frame: {}", e))?;
+        Ok((png.into_inner(), "image/png".to_string()))
+    }
+
+    /// Remove keyframes no screenshot refers to any more; returns how many.
+    pub(crate) fn prune_unused_keyframes(&self) -> Result<usize, String> {
+        let active = self
+            .delta_chain
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .and_then(|chain| chain.keyframe_id);
+        let unused: Vec<(i64, String)> = {
+            let guard = self.get_connection_named("prune_unused_keyframes")?;
+            let conn = guard.as_ref().unwrap();
+            let mut stmt = conn
+                .prepare(
+                    "SELECT k.id, k.image_path FROM delta_keyframes k
+                     WHERE k.id IS NOT ?1
+                       AND NOT EXISTS (
+                           SELECT 1 FROM screenshots s WHERE s.delta_keyframe_id = k.id
+                       )
+                       AND NOT EXISTS (
+                           SELECT 1 FROM month_shard_keyframes m WHERE m.keyframe_id = k.id
+                       )",
+                )
+                .map_err(|e| format!("Failed to prepare keyframe query: {}", e))?;
+            let rows: Vec<(i64, String)> = stmt
+                .query_map(params![active], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(|e| format!("Failed to query unused keyframes: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            for (id, _) in &rows {
+                conn.execute("DELETE FROM delta_keyframes WHERE id = ?1", params![id])
+                    .map_err(|e| format!("Failed to delete keyframe record: {}", e))?;
+            }
+            rows
+        };
+        for (_, path) in &unused {
+            let _ = std::fs::remove_file(self.resolve_image_path(path));
+        }
+        Ok(unused.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(cursor_at: Option<(u32, u32)>) -> RgbImage {
+        RgbImage::from_fn(320, 200, |x, y| match cursor_at {
+            Some((cx, cy)) if (cx..cx + 2).contains(&x) && (cy..cy + 12).contains(&y) => {
+                image::Rgb([0, 0, 0])
+            }
+            _ => image::Rgb([(x % 251) as u8, (y % 241) as u8, 200]),
+        })
+    }
+
+    #[test]
+    fn changed_rect_covers_only_touched_tiles() {
+        let base = frame(None);
+        assert_eq!(changed_rect(&base, &base), Some((0, 0, 32, 32)));
+        assert_eq!(
+            changed_rect(&base, &frame(Some((100, 70)))),
+            Some((96, 64, 32, 32))
+        );
+        // Straddling a tile boundary widens the box to both tiles; the last row of
+        // tiles is clipped to the frame.
+        assert_eq!(
+            changed_rect(&base, &frame(Some((127, 190)))),
+            Some((96, 160, 64, 40))
+        );
+        assert_eq!(changed_rect(&base, &RgbImage::new(10, 10)), None);
+    }
+
+    #[test]
+    fn delta_paths_are_recognised() {
+        assert!(is_delta_path(Path::new(
+            "screenshots/screenshot_1.delta.png.enc.pending"
+        )));
+        assert!(is_delta_path(Path::new(
+            "cold/2026-01-02.cpbundle#8-10-screenshot_1.delta.png.enc"
+        )));
+        assert!(!is_delta_path(Path::new(
+            "screenshots/screenshot_1.png.enc"
+        )));
+    }
+}
@@ -1529,19 +1529,7 @@ fn verify_sidecar(path: &Path, expected_checksum: &str) -> Result<(), String> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::credential_manager::CredentialManagerState;
-    use rusqlite::Connection;
-    use std::sync::Arc;
-
-    fn test_storage() -> (tempfile::TempDir, StorageState) {
-        let temp = tempfile::tempdir().expect("temp storage directory");
-        let credential_state = Arc::new(CredentialManagerState::new(temp.path().to_path_buf()));
-        let storage = StorageState::new(temp.path().to_path_buf(), credential_state);
-        let connection = Connection::open_in_memory().expect("in-memory database");
-        storage.init_tables(&connection).expect("initialize schema");
-        *storage.db.lock().unwrap_or_else(|error| error.into_inner()) = Some(connection);
-        (temp, storage)
-    }
+    use crate::storage::test_storage;
 
     fn job(kind: DerivedIndexKind, subject_key: &str) -> DerivedIndexJobSpec {
         DerivedIndexJobSpec {
@@ -0,0 +1,372 @@
+//! Weekly activity digest: aggregates one week of capture metadata and renders it
+//! as a self-contained HTML report.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::{DateTime, Local, Utc};
+use rusqlite::{params, Connection};
+
+use crate::credential_manager::{decrypt_row_key_with_cng, decrypt_with_master_key};
+
+use super::capture_day::format_sql_timestamp;
+use super::{DigestBucket, DigestPrivacyLevel, StorageState, WeeklyDigest};
+
+const DIGEST_WINDOW_SECS: i64 = 7 * 24 * 60 * 60;
+const DIGEST_TOP_N: usize = 10;
+
+/// Rows considered by the digest: live, committed screenshots inside `[?1, ?2)`.
+const DIGEST_ROW_FILTER: &str = "is_deleted = 0
+     AND (status IS NULL OR status = 'committed')
+     AND created_at >= ?1 AND created_at < ?2";
+
+fn query_buckets(
+    conn: &Connection,
+    sql: &str,
+    start: &str,
+    end: &str,
+) -> Result<Vec<DigestBucket>, String> {
+    let mut stmt = conn
+        .prepare(sql)
+        .map_err(|e| format!("Failed to prepare digest query: {}", e))?;
+    let rows = stmt
+        .query_map(params![start, end], |row| {
+            Ok(DigestBucket {
+                label: row.get(0)?,
+                count: row.get(1)?,
+            })
+        })
+        .map_err(|e| format!("Failed to execute digest query: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(rows)
+}
+
+fn top_buckets(counts: HashMap<String, i64>, limit: usize) -> Vec<DigestBucket> {
+    let mut buckets: Vec<DigestBucket> = counts
+        .into_iter()
+        .map(|(label, count)| DigestBucket { label, count })
+        .collect();
+    buckets.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.label.cmp(&b.label)));
+    buckets.truncate(limit);
+    buckets
+}
+
+impl StorageState {
+    /// Aggregate the seven days starting at `week_start` (Unix seconds).
+    ///
+    /// `Summary` keeps only per-day and per-category counts, `Apps` adds per-process
+    /// totals, and `Detailed` also decrypts window titles to list the most frequent.
+    pub fn build_weekly_digest(
+        &self,
+        week_start: i64,
+        privacy_level: DigestPrivacyLevel,
+    ) -> Result<WeeklyDigest, String> {
+        let week_end = week_start + DIGEST_WINDOW_SECS;
+        let start_dt = format_sql_timestamp(week_start)?;
+        let end_dt = format_sql_timestamp(week_end)?;
+
+        let (total_screenshots, daily, categories, processes, encrypted_titles) = {
+            let guard = self.get_connection_named("build_weekly_digest")?;
+            let conn = guard.as_ref().unwrap();
+
+            let total: i64 = conn
+                .query_row(
+                    &format!(
+                        "SELECT COUNT(*) FROM screenshots WHERE {}",
+                        DIGEST_ROW_FILTER
+                    ),
+                    params![start_dt, end_dt],
+                    |row| row.get(0),
+                )
+                .map_err(|e| format!("Failed to count screenshots: {}", e))?;
+
+            let daily = query_buckets(
+                conn,
+                &format!(
+                    "SELECT date(created_at, 'localtime') AS day, COUNT(*) FROM screenshots
+                     WHERE {} GROUP BY day ORDER BY day",
+                    DIGEST_ROW_FILTER
+                ),
+                &start_dt,
+                &end_dt,
+            )?;
+
+            let categories = query_buckets(
+                conn,
+                &format!(
+                    "SELECT COALESCE(NULLIF(category, ''), 'uncategorized') AS cat, COUNT(*)
+                     FROM screenshots WHERE {} GROUP BY cat ORDER BY 2 DESC, cat",
+                    DIGEST_ROW_FILTER
+                ),
+                &start_dt,
+                &end_dt,
+            )?;
+
+            let processes = if privacy_level >= DigestPrivacyLevel::Apps {
+                query_buckets(
+                    conn,
+                    &format!(
+                        "SELECT process_name, COUNT(*) FROM screenshots
+                         WHERE {} AND process_name IS NOT NULL AND process_name != ''
+                         GROUP BY process_name ORDER BY 2 DESC, process_name LIMIT {}",
+                        DIGEST_ROW_FILTER, DIGEST_TOP_N
+                    ),
+                    &start_dt,
+                    &end_dt,
+                )?
+            } else {
+                Vec::new()
+            };
+
+            let encrypted_titles: Vec<(Vec<u8>, Vec<u8>)> =
+                if privacy_level >= DigestPrivacyLevel::Detailed {
+                    let mut stmt = conn
+                        .prepare(&format!(
+                            "SELECT window_title_enc, content_key_encrypted FROM screenshots
+                             WHERE {} AND window_title_enc IS NOT NULL
+                                 AND content_key_encrypted IS NOT NULL",
+                            DIGEST_ROW_FILTER
+                        ))
+                        .map_err(|e| format!("Failed to prepare title query: {}", e))?;
+                    let rows = stmt
+                        .query_map(params![start_dt, end_dt], |row| {
+                            Ok((row.get(0)?, row.get(1)?))
+                        })
+                        .map_err(|e| format!("Failed to query titles: {}", e))?
+                        .filter_map(|r| r.ok())
+                        .collect();
+                    rows
+                } else {
+                    Vec::new()
+                };
+
+            (total, daily, categories, processes, encrypted_titles)
+            // guard dropped — mutex released before row-key decryption
+        };
+
+        let top_titles = Self::count_window_titles(encrypted_titles);
+
+        Ok(WeeklyDigest {
+            week_start,
+            week_end,
+            privacy_level,
+            total_screenshots,
+            daily,
+            categories,
+            processes,
+            top_titles,
+        })
+    }
+
+    fn count_window_titles(rows: Vec<(Vec<u8>, Vec<u8>)>) -> Vec<DigestBucket> {
+        let mut counts: HashMap<String, i64> = HashMap::new();
+        let mut failed = 0usize;
+        for (title_enc, key_enc) in rows {
+            let Ok(mut row_key) = decrypt_row_key_with_cng(&key_enc) else {
+                failed += 1;
+                continue;
+            };
+            let title = decrypt_with_master_key(&row_key, &title_enc)
+                .ok()
+                .and_then(|v| String::from_utf8(v).ok());
+            Self::zeroize_bytes(&mut row_key);
+            match title.map(|t| t.trim().to_string()) {
+                Some(t) if !t.is_empty() => *counts.entry(t).or_insert(0) += 1,
+                Some(_) => {}
+                None => failed += 1,
+            }
+        }
+        if failed > 0 {
+            tracing::warn!("[Digest] {} window titles could not be decrypted", failed);
+        }
+        top_buckets(counts, DIGEST_TOP_N)
+    }
+
+    /// Build the digest and write it as a standalone HTML file at `output_path`.
+    pub fn export_weekly_digest_html(
+        &self,
+        week_start: i64,
+        privacy_level: DigestPrivacyLevel,
+        output_path: &Path,
+    ) -> Result<WeeklyDigest, String> {
+        let digest = self.build_weekly_digest(week_start, privacy_level)?;
+        let html = render_digest_html(&digest);
+
+        if let Some(parent) = output_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create digest directory: {}", e))?;
+        }
+        std::fs::write(output_path, html).map_err(|e| format!("Failed to write digest: {}", e))?;
+        tracing::info!(
+            "[Digest] Wrote weekly digest ({} screenshots, privacy={:?}) to {}",
+            digest.total_screenshots,
+            digest.privacy_level,
+            output_path.display()
+        );
+        Ok(digest)
+    }
+}
+
+fn escape_html(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn format_local_date(secs: i64) -> String {
+    DateTime::<Utc>::from_timestamp(secs, 0)
+        .map(|dt| dt.with_timezone(&Local).format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+fn render_bucket_section(html: &mut String, title: &str, buckets: &[DigestBucket]) {
+    if buckets.is_empty() {
+        return;
+    }
+    let max = buckets.iter().map(|b| b.count).max().unwrap_or(1).max(1);
+    html.push_str(&format!("<section><h2>{}</h2><table>", escape_html(title)));
+    for bucket in buckets {
+        let width = (bucket.count as f64 / max as f64 * 100.0).round() as i64;
+        html.push_str(&format!(
+            "<tr><td class=\"label\">{}</td><td class=\"bar\"><div style=\"width:{}%\"></div></td><td class=\"count\">{}</td></tr>",
+            escape_html(&bucket.label),
+            width,
+            bucket.count
+        ));
+    }
+    html.push_str("</table></section>");
+}
+
+/// Render a digest as a single HTML document with inline styles and no external assets.
+pub fn render_digest_html(digest: &WeeklyDigest) -> String {
+    let range = format!(
+        "{} – {}",
+        format_local_date(digest.week_start),
+        format_local_date(digest.week_end - 1)
+    );
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html><html><head><meta charset=\"utf-8\">");
+    html.push_str(&format!(
+        "<title>CarbonPaper weekly digest {}</title>",
+        escape_html(&range)
+    ));
+    html.push_str(
+        "<style>\
+         body{font-family:system-ui,sans-serif;max-width:760px;margin:2rem auto;color:#222}\
+         h1{font-size:1.4rem}h2{font-size:1.1rem;margin-top:2rem}\
+         table{width:100%;border-collapse:collapse}td{padding:2px 6px}\
+         td.label{width:40%;overflow-wrap:anywhere}td.count{width:10%;text-align:right}\
+         td.bar div{background:#4a7bd0;height:10px;border-radius:2px}\
+         .meta{color:#666}\
+         </style></head><body>",
+    );
+    html.push_str(&format!(
+        "<h1>Weekly digest</h1><p class=\"meta\">{} · {} screenshots</p>",
+        escape_html(&range),
+        digest.total_screenshots
+    ));
+
+    render_bucket_section(&mut html, "Captures per day", &digest.daily);
+    render_bucket_section(&mut html, "Categories", &digest.categories);
+    render_bucket_section(&mut html, "Applications", &digest.processes);
+    render_bucket_section(&mut html, "Top window titles", &digest.top_titles);
+
+    html.push_str("</body></html>");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_storage;
+
+    fn digest_storage() -> (tempfile::TempDir, StorageState) {
+        let (temp, storage) = test_storage();
+        storage
+            .db
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .expect("test database")
+            .execute_batch(
+                "INSERT INTO screenshots (image_path, image_hash, process_name, category, created_at, is_deleted) VALUES
+                    ('a', 'h1', 'code.exe', 'coding', '2026-03-02 10:00:00', 0),
+                    ('b', 'h2', 'code.exe', 'coding', '2026-03-03 10:00:00', 0),
+                    ('c', 'h3', 'chrome.exe', NULL, '2026-03-03 11:00:00', 0),
+                    ('d', 'h4', 'chrome.exe', NULL, '2026-03-04 11:00:00', 1),
+                    ('e', 'h5', 'chrome.exe', NULL, '2026-03-20 11:00:00', 0);",
+            )
+            .expect("digest fixture");
+        (temp, storage)
+    }
+
+    fn week_start() -> i64 {
+        DateTime::parse_from_rfc3339("2026-03-01T00:00:00Z")
+            .unwrap()
+            .timestamp()
+    }
+
+    #[test]
+    fn digest_respects_privacy_level_and_week_window() {
+        let (_temp, storage) = digest_storage();
+
+        let summary = storage
+            .build_weekly_digest(week_start(), DigestPrivacyLevel::Summary)
+            .unwrap();
+        assert_eq!(summary.total_screenshots, 3);
+        assert!(summary.processes.is_empty());
+        assert_eq!(
+            summary.categories,
+            vec![
+                DigestBucket {
+                    label: "coding".to_string(),
+                    count: 2
+                },
+                DigestBucket {
+                    label: "uncategorized".to_string(),
+                    count: 1
+                },
+            ]
+        );
+
+        let apps = storage
+            .build_weekly_digest(week_start(), DigestPrivacyLevel::Apps)
+            .unwrap();
+        assert_eq!(apps.processes[0].label, "code.exe");
+        assert_eq!(apps.processes[0].count, 2);
+        assert!(apps.top_titles.is_empty());
+    }
+
+    #[test]
+    fn rendered_digest_escapes_labels_and_skips_empty_sections() {
+        let digest = WeeklyDigest {
+            week_start: week_start(),
+            week_end: week_start() + DIGEST_WINDOW_SECS,
+            privacy_level: DigestPrivacyLevel::Apps,
+            total_screenshots: 1,
+            daily: Vec::new(),
+            categories: Vec::new(),
+            processes: vec![DigestBucket {
+                label: "<script>.exe".to_string(),
+                count: 1,
+            }],
+            top_titles: Vec::new(),
+        };
+
+        let html = render_digest_html(&digest);
+        assert!(html.contains("&lt;script&gt;.exe"));
+        assert!(!html.contains("<script>"));
+        assert!(!html.contains("Top window titles"));
+    }
+}
@@ -0,0 +1,257 @@
+//! Garbage collection of image files and rows that lost their counterpart.
+//!
+//! A crash between writing a file and inserting its row, or a delete that removed the
+//! row but failed on the file, leaves encrypted data on disk that nothing will ever
+//! read or free. The opposite happens when a file is removed behind the app's back.
+//! `storage_gc` walks `screenshots/` and `cold/` for image, thumbnail and bundle files
+//! that no row refers to, and lists live screenshots whose file is missing. With
+//! `clean`, orphaned files are deleted and missing-file rows are moved to the trash,
+//! except pinned and protected ones, which are only reported.
+//!
+//! Rows in the trash and in detached month shards still count as references, so
+//! neither restoring from the trash nor re-attaching a month finds its files gone.
+//! `.pending` files belong to the startup recovery and recently written files to a
+//! capture that may still be in flight; both are skipped.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use walkdir::WalkDir;
+
+use super::cold_storage::{BundleEntry, COLD_STORAGE_DIR};
+use super::protected_ranges::PROTECTED_RANGE_MATCH;
+use super::{GcReport, OrphanFile, StorageState};
+
+/// Files younger than this may belong to a capture that has no row yet.
+const MIN_ORPHAN_AGE: Duration = Duration::from_secs(10 * 60);
+/// Orphaned files and missing ids listed in the report; the rest are only counted.
+const MAX_LISTED: usize = 200;
+/// Extensions of files the collector may delete.
+const IMAGE_FILE_SUFFIXES: &[&str] = &[".enc", ".cpbundle"];
+
+impl StorageState {
+    /// Report orphaned image files and rows whose file is missing; remove both when
+    /// `clean` is set.
+    pub fn collect_garbage(&self, clean: bool) -> Result<GcReport, String> {
+        self.collect_garbage_older_than(clean, MIN_ORPHAN_AGE)
+    }
+
+    fn collect_garbage_older_than(
+        &self,
+        clean: bool,
+        min_age: Duration,
+    ) -> Result<GcReport, String> {
+        let mut report = GcReport::default();
+        let referenced = self.referenced_image_files()?;
+        let detached_months: HashSet<String> = self.detached_months()?.into_iter().collect();
+
+        let data_dir = self
+            .data_dir
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let now = SystemTime::now();
+        for dir in ["screenshots", COLD_STORAGE_DIR] {
+            for entry in WalkDir::new(data_dir.join(dir))
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file())
+            {
+                let path = entry.path();
+                let name = entry.file_name().to_string_lossy();
+                if !IMAGE_FILE_SUFFIXES
+                    .iter()
+                    .any(|suffix| name.ends_with(suffix))
+                {
+                    continue;
+                }
+                report.scanned_files += 1;
+                let rel = self.to_relative_image_path(path);
+                if referenced.contains(&rel) || bundle_month_detached(&name, &detached_months) {
+                    continue;
+                }
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                let recent = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|modified| now.duration_since(modified).ok())
+                    .is_none_or(|age| age < min_age);
+                if recent {
+                    continue;
+                }
+
+                report.orphan_files += 1;
+                report.orphan_bytes += metadata.len();
+                if report.orphan_samples.len() < MAX_LISTED {
+                    report.orphan_samples.push(OrphanFile {
+                        path: rel,
+                        bytes: metadata.len(),
+                    });
+                }
+                if clean && std::fs::remove_file(path).is_ok() {
+                    report.files_removed += 1;
+                    report.bytes_removed += metadata.len();
+                }
+            }
+        }
+
+        let missing = self.screenshots_missing_files()?;
+        report.missing_rows = missing.len();
+        report.missing_ids = missing.iter().take(MAX_LISTED).map(|(id, _)| *id).collect();
+        let removable: Vec<i64> = missing
+            .iter()
+            .filter(|(_, keep)| !keep)
+            .map(|(id, _)| *id)
+            .collect();
+        report.missing_kept = missing.len() - removable.len();
+        if clean && !removable.is_empty() {
+            report.rows_removed = self.soft_delete_screenshots(&removable)?.screenshots_marked;
+        }
+
+        tracing::info!(
+            "[GC] scanned={} orphan_files={} orphan_bytes={} missing_rows={} files_removed={} rows_removed={}",
+            report.scanned_files,
+            report.orphan_files,
+            report.orphan_bytes,
+            report.missing_rows,
+            report.files_removed,
+            report.rows_removed
+        );
+        Ok(report)
+    }
+
+    /// Data-dir-relative paths of every file a row refers to: images (bundles for
+    /// archived ones), their thumbnails and delta keyframes, including trashed rows
+    /// and rows in detached months.
+    fn referenced_image_files(&self) -> Result<HashSet<String>, String> {
+        let mut image_paths: Vec<String> = {
+            let guard = self.get_connection_named("referenced_image_files")?;
+            let conn = guard.as_ref().unwrap();
+            let mut stmt = conn
+                .prepare(
+                    "SELECT image_path FROM screenshots
+                     UNION ALL SELECT image_path FROM delta_keyframes",
+                )
+                .map_err(|e| format!("Failed to prepare image path query: {}", e))?;
+            let rows = stmt
+                .query_map([], |row| row.get(0))
+                .map_err(|e| format!("Failed to query image paths: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows
+        };
+        image_paths.extend(self.month_shard_image_paths()?);
+
+        let mut referenced = HashSet::with_capacity(image_paths.len() * 2);
+        for image_path in image_paths {
+            let abs = self.resolve_image_path(&image_path);
+            let file = match BundleEntry::parse(&abs) {
+                Some(entry) => entry.bundle,
+                None => abs.clone(),
+            };
+            referenced.insert(self.to_relative_image_path(&file));
+            referenced.insert(self.to_relative_image_path(&Self::thumbnail_path_for(&abs)));
+        }
+        Ok(referenced)
+    }
+
+    /// Live, committed screenshots whose image file is missing, with whether each
+    /// must be kept because it is pinned or protected.
+    fn screenshots_missing_files(&self) -> Result<Vec<(i64, bool)>, String> {
+        let rows: Vec<(i64, String, bool)> = {
+            let guard = self.get_connection_named("screenshots_missing_files")?;
+            let conn = guard.as_ref().unwrap();
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT id, image_path, pinned != 0 OR {}
+                     FROM screenshots
+                     WHERE is_deleted = 0 AND (status IS NULL OR status != 'pending')
+                     ORDER BY id",
+                    PROTECTED_RANGE_MATCH
+                ))
+                .map_err(|e| format!("Failed to prepare missing file query: {}", e))?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+                .map_err(|e| format!("Failed to query screenshots: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows
+        };
+        Ok(rows
+            .into_iter()
+            .filter(|(_, image_path, _)| !self.stored_image_exists(image_path))
+            .map(|(id, _, keep)| (id, keep))
+            .collect())
+    }
+}
+
+/// Whether a file is the bundle of a month detached into a shard, which is kept even
+/// when the shard file itself is missing.
+fn bundle_month_detached(file_name: &str, detached_months: &HashSet<String>) -> bool {
+    Path::new(file_name)
+        .extension()
+        .is_some_and(|ext| ext == "cpbundle")
+        && file_name
+            .get(..7)
+            .is_some_and(|month| detached_months.contains(month))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_storage;
+
+    #[test]
+    fn orphaned_files_and_missing_rows_are_found_and_cleaned() {
+        let (temp, storage) = test_storage();
+        let screenshots = temp.path().join("screenshots");
+        std::fs::create_dir_all(screenshots.join("thumbs")).unwrap();
+        std::fs::write(screenshots.join("kept.png.enc"), [1u8; 8]).unwrap();
+        std::fs::write(screenshots.join("thumbs/kept.thumb.jpg.enc"), [1u8; 4]).unwrap();
+        std::fs::write(screenshots.join("trashed.png.enc"), [1u8; 8]).unwrap();
+        std::fs::write(screenshots.join("stray.png.enc"), [1u8; 16]).unwrap();
+        std::fs::write(screenshots.join("thumbs/stray.thumb.jpg.enc"), [1u8; 4]).unwrap();
+        std::fs::write(screenshots.join("capture.png.enc.pending"), [1u8; 8]).unwrap();
+
+        let guard = storage.db.lock().unwrap_or_else(|e| e.into_inner());
+        let connection = guard.as_ref().expect("test database");
+        connection
+            .execute_batch(
+                "INSERT INTO screenshots (id, image_path, image_hash, is_deleted, pinned) VALUES
+                    (1, 'screenshots/kept.png.enc', 'h1', 0, 0),
+                    (2, 'screenshots/trashed.png.enc', 'h2', 1, 0),
+                    (3, 'screenshots/gone.png.enc', 'h3', 0, 0),
+                    (4, 'screenshots/gone-pinned.png.enc', 'h4', 0, 1);",
+            )
+            .expect("gc fixture");
+        drop(guard);
+
+        let report = storage
+            .collect_garbage_older_than(false, Duration::ZERO)
+            .expect("gc dry run");
+        assert_eq!(report.scanned_files, 5);
+        assert_eq!(report.orphan_files, 2);
+        assert_eq!(report.orphan_bytes, 20);
+        assert_eq!(report.missing_ids, vec![3, 4]);
+        assert_eq!(report.missing_kept, 1);
+        assert_eq!(report.files_removed, 0);
+        assert!(screenshots.join("stray.png.enc").exists());
+
+        // Nothing is old enough with the default grace period.
+        let report = storage.collect_garbage(false).expect("gc");
+        assert_eq!(report.orphan_files, 0);
+
+        let report = storage
+            .collect_garbage_older_than(true, Duration::ZERO)
+            .expect("gc clean");
+        assert_eq!(report.files_removed, 2);
+        assert_eq!(report.rows_removed, 1);
+        assert!(!screenshots.join("stray.png.enc").exists());
+        assert!(screenshots.join("kept.png.enc").exists());
+        assert!(screenshots.join("trashed.png.enc").exists());
+        assert!(screenshots.join("capture.png.enc.pending").exists());
+    }
+}
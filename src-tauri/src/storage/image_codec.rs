@@ -0,0 +1,225 @@
+//! Optional re-encoding of captured screenshots before encryption.
+//!
+//! Captures arrive as lossless PNG, which dominates disk usage. When a codec is
+//! configured in advanced settings the image is re-encoded to lossy WebP before it is
+//! encrypted; the file name carries the codec (`.webp.enc`) so reads report the right
+//! MIME type and old PNG files keep working.
+//!
+//! Only codecs the build can decode are offered: every read path (thumbnails, PDF and
+//! daily-note export, OCR reprocessing, near-duplicate hashing) decodes stored frames
+//! with `image::load_from_memory`. AVIF is left out because `image` only decodes it
+//! through `dav1d`, which the Windows build does not link.
+
+use std::sync::atomic::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use super::StorageState;
+
+/// Registry value names used by `get_advanced_config` / `set_advanced_config`.
+pub(crate) const IMAGE_CODEC_REGISTRY_KEY: &str = "image_codec";
+pub(crate) const IMAGE_QUALITY_REGISTRY_KEY: &str = "image_codec_quality";
+
+pub(super) const DEFAULT_IMAGE_QUALITY: u8 = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StoredImageCodec {
+    /// Keep the captured PNG bytes unchanged.
+    #[default]
+    Png,
+    Webp,
+}
+
+impl StoredImageCodec {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "png" | "none" | "" => Some(Self::Png),
+            "webp" => Some(Self::Webp),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Webp => "webp",
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Webp,
+            _ => Self::Png,
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            Self::Png => 0,
+            Self::Webp => 1,
+        }
+    }
+}
+
+/// Clamp a user-supplied quality to the 1–100 range the WebP encoder accepts.
+pub(crate) fn clamp_image_quality(quality: u32) -> u8 {
+    quality.clamp(1, 100) as u8
+}
+
+/// File extension (before `.enc`) for stored image bytes, detected from their header.
+pub(crate) fn image_extension_for_bytes(bytes: &[u8]) -> &'static str {
+    match image::guess_format(bytes) {
+        Ok(image::ImageFormat::WebP) => "webp",
+        Ok(image::ImageFormat::Jpeg) => "jpg",
+        _ => "png",
+    }
+}
+
+/// Re-encode captured bytes with `codec`. Returns the bytes to store and their extension.
+///
+/// Keeps the original bytes when the codec is PNG, re-encoding fails, or the result
+/// would not be smaller.
+pub(crate) fn encode_for_storage(
+    captured: &[u8],
+    codec: StoredImageCodec,
+    quality: u8,
+) -> (Vec<u8>, &'static str) {
+    if codec != StoredImageCodec::Png {
+        match try_encode(captured, codec, quality) {
+            Ok(encoded) if encoded.len() < captured.len() => return (encoded, codec.as_str()),
+            Ok(_) => {}
+            Err(e) => tracing::warn!(
+                "Failed to encode screenshot as {}, storing original: {}",
+                codec.as_str(),
+                e
+            ),
+        }
+    }
+    (captured.to_vec(), image_extension_for_bytes(captured))
+}
+
+fn try_encode(captured: &[u8], codec: StoredImageCodec, quality: u8) -> Result<Vec<u8>, String> {
+    let rgb = image::load_from_memory(captured)
+        .map_err(|e| format!("Failed to decode image: {}", e))?
+        .to_rgb8();
+    let (width, height) = rgb.dimensions();
+    match codec {
+        StoredImageCodec::Png => Ok(captured.to_vec()),
+        StoredImageCodec::Webp => Ok(webp::Encoder::from_rgb(rgb.as_raw(), width, height)
+            .encode(quality as f32)
+            .to_vec()),
+    }
+}
+
+impl StorageState {
+    /// Codec and quality applied to newly saved screenshots.
+    pub fn image_codec(&self) -> (StoredImageCodec, u8) {
+        (
+            StoredImageCodec::from_u8(self.image_codec.load(Ordering::Relaxed)),
+            self.image_quality.load(Ordering::Relaxed),
+        )
+    }
+
+    pub fn set_image_codec(&self, codec: StoredImageCodec, quality: u8) {
+        self.image_codec.store(codec.to_u8(), Ordering::Relaxed);
+        self.image_quality
+            .store(clamp_image_quality(quality as u32), Ordering::Relaxed);
+    }
+
+    /// Restore the codec settings saved by `set_advanced_config`.
+    pub(crate) fn load_image_codec_from_registry(&self) {
+        let codec = crate::registry_config::get_string(IMAGE_CODEC_REGISTRY_KEY)
+            .and_then(|v| StoredImageCodec::parse(&v))
+            .unwrap_or_default();
+        let quality = crate::registry_config::get_u32(IMAGE_QUALITY_REGISTRY_KEY)
+            .unwrap_or(DEFAULT_IMAGE_QUALITY as u32);
+        self.set_image_codec(codec, clamp_image_quality(quality));
+    }
+
+    /// Re-encode captured bytes with the configured codec before encryption.
+    pub(super) fn encode_image_for_storage(&self, captured: &[u8]) -> (Vec<u8>, &'static str) {
+        let (codec, quality) = self.image_codec();
+        encode_for_storage(captured, codec, quality)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::image_io::decrypt_image_file;
+    use super::*;
+    use crate::credential_manager::encrypt_with_master_key;
+    use crate::storage::test_storage;
+
+    fn png_bytes() -> Vec<u8> {
+        let img = image::RgbImage::from_fn(64, 64, |x, y| {
+            image::Rgb([(x * 4) as u8, (y * 4) as u8, ((x + y) * 2) as u8])
+        });
+        let mut buf = std::io::Cursor::new(Vec::new());
+        img.write_to(&mut buf, image::ImageFormat::Png)
+            .expect("encode fixture png");
+        buf.into_inner()
+    }
+
+    #[test]
+    fn png_codec_keeps_original_bytes() {
+        let png = png_bytes();
+        let (stored, ext) = encode_for_storage(&png, StoredImageCodec::Png, 80);
+        assert_eq!(stored, png);
+        assert_eq!(ext, "png");
+    }
+
+    #[test]
+    fn webp_output_is_detected_and_decodable() {
+        let png = png_bytes();
+        let (stored, ext) = encode_for_storage(&png, StoredImageCodec::Webp, 60);
+        assert_eq!(ext, image_extension_for_bytes(&stored));
+        let decoded = image::load_from_memory(&stored).expect("stored image decodes");
+        assert_eq!((decoded.width(), decoded.height()), (64, 64));
+    }
+
+    /// Detailed enough that lossy WebP always beats PNG, so the codec is really used.
+    fn noisy_png_bytes() -> Vec<u8> {
+        let img = image::RgbImage::from_fn(256, 256, |x, y| {
+            let v = x.wrapping_mul(2_654_435_761) ^ y.wrapping_mul(40_503) ^ (x * y);
+            image::Rgb([v as u8, (v >> 8) as u8, (v >> 16) as u8])
+        });
+        let mut buf = std::io::Cursor::new(Vec::new());
+        img.write_to(&mut buf, image::ImageFormat::Png)
+            .expect("encode fixture png");
+        buf.into_inner()
+    }
+
+    #[test]
+    fn every_accepted_codec_round_trips_through_encrypted_storage() {
+        let (temp, storage) = test_storage();
+        let captured = noisy_png_bytes();
+        let row_key = [9u8; 32];
+
+        for name in ["png", "webp"] {
+            let codec = StoredImageCodec::parse(name).expect("accepted codec");
+            storage.set_image_codec(codec, DEFAULT_IMAGE_QUALITY);
+            let (stored, ext) = storage.encode_image_for_storage(&captured);
+            assert_eq!(ext, codec.as_str());
+            let path = temp.path().join(format!("shot.{}.enc", ext));
+            std::fs::write(&path, encrypt_with_master_key(&row_key, &stored).unwrap()).unwrap();
+
+            let (read_back, mime) = decrypt_image_file(&path, &row_key).expect("image reads");
+            assert_eq!(mime, format!("image/{}", name));
+            let decoded = image::load_from_memory(&read_back).expect("stored image decodes");
+            assert_eq!((decoded.width(), decoded.height()), (256, 256));
+        }
+    }
+
+    #[test]
+    fn codec_names_parse_and_quality_clamps() {
+        assert_eq!(
+            StoredImageCodec::parse("WebP"),
+            Some(StoredImageCodec::Webp)
+        );
+        assert_eq!(StoredImageCodec::parse("none"), Some(StoredImageCodec::Png));
+        assert_eq!(StoredImageCodec::parse("jxl"), None);
+        assert_eq!(clamp_image_quality(0), 1);
+        assert_eq!(clamp_image_quality(250), 100);
+    }
+}
@@ -6,7 +6,8 @@ use crate::credential_manager::{
 };
 use std::path::{Path, PathBuf};
 
-use super::{BackgroundReadError, StorageState};
+use super::cold_storage::{read_bundle_entry, BundleEntry};
+use super::{BackgroundReadError, InlineThumbnail, SearchResult, StorageState};
 
 impl StorageState {
     /// Read an encrypted image file and return Base64-encoded data.
@@ -50,14 +51,23 @@ impl StorageState {
         let query_elapsed = diag_start.elapsed();
 
         // Phase 2: CNG decrypt + file read + AES decrypt + base64 — all outside mutex
-        let mut row_key = key_enc
-            .as_ref()
-            .and_then(|enc| decrypt_row_key_with_cng(enc).ok())
-            .ok_or_else(|| "Failed to unwrap image row key".to_string())?;
+        let mut row_key =
+            Self::unwrap_image_row_key(key_enc.as_deref()).map_err(|e| e.to_string())?;
 
         let abs_path_str = abs_path.to_string_lossy().to_string();
-        let result = read_encrypted_image_as_base64(&abs_path_str, &row_key);
+        let image = read_encrypted_image_bytes(&abs_path_str, &row_key);
         Self::zeroize_bytes(&mut row_key);
+        let result = image
+            .and_then(|image| {
+                self.compose_delta_frame(&abs_path, image, false)
+                    .map_err(|e| e.to_string())
+            })
+            .map(|(image_data, mime_type)| {
+                (
+                    base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &image_data),
+                    mime_type,
+                )
+            });
         if diag_start.elapsed().as_secs() >= 5 {
             tracing::warn!(
                 "[DIAG:DB] read_image({}) query {:?}, total {:?}",
@@ -69,6 +79,16 @@ impl StorageState {
         result
     }
 
+    /// Unwrap a screenshot row key, reporting failures as `ImageIntegrityError::WrongKey`.
+    pub(super) fn unwrap_image_row_key(
+        key_enc: Option<&[u8]>,
+    ) -> Result<Vec<u8>, ImageIntegrityError> {
+        let encrypted_key = key_enc
+            .ok_or_else(|| ImageIntegrityError::WrongKey("no row key stored".to_string()))?;
+        decrypt_row_key_with_cng(encrypted_key)
+            .map_err(|e| ImageIntegrityError::WrongKey(e.to_string()))
+    }
+
     /// Read an encrypted image file and return raw image bytes.
     pub fn read_image_bytes(&self, path: &str) -> Result<(Vec<u8>, String), String> {
         self.read_image_bytes_with_mode(path, false)
@@ -148,9 +168,10 @@ impl StorageState {
         })?;
 
         let abs_path_str = abs_path.to_string_lossy().to_string();
-        let result =
+        let image =
             read_encrypted_image_bytes(&abs_path_str, &row_key).map_err(BackgroundReadError::Other);
         Self::zeroize_bytes(&mut row_key);
+        let result = image.and_then(|image| self.compose_delta_frame(&abs_path, image, silent));
         if diag_start.elapsed().as_secs() >= 5 {
             tracing::warn!(
                 "[DIAG:DB] read_image_bytes({}) query {:?}, total {:?}",
@@ -164,7 +185,13 @@ impl StorageState {
 
     /// Derive the cached thumbnail path from an original image path.
     /// E.g. `screenshots/foo.png.enc` → `screenshots/thumbs/foo.thumb.jpg.enc`
+    /// Bundled images use `cold/thumbs/` and the name the image had before archiving.
     pub fn thumbnail_path_for(original: &Path) -> PathBuf {
+        if let Some(entry) = BundleEntry::parse(original) {
+            let file_name = Path::new(&entry.name);
+            let parent = entry.bundle.parent().unwrap_or_else(|| Path::new("."));
+            return Self::thumbnail_path_for(&parent.join(file_name));
+        }
         let parent = original.parent().unwrap_or_else(|| Path::new("."));
         let thumbs_dir = parent.join("thumbs");
         let stem = original
@@ -184,38 +211,40 @@ impl StorageState {
     /// Returns base64-encoded JPEG data with MIME type.
     pub fn read_thumbnail(&self, path: &str) -> Result<(String, String), String> {
         // Phase 1: DB query for the encrypted row key
-        let (key_enc, abs_path) = {
+        let (row, abs_path) = {
             let conn = self.open_read_connection_named("read_thumbnail")?;
 
             if let Some(hash) = path.strip_prefix("memory://") {
-                let result: Option<(Option<Vec<u8>>, String)> = conn
+                let result: Option<(i64, Option<Vec<u8>>, String)> = conn
                     .query_row(
-                        "SELECT content_key_encrypted, image_path FROM screenshots WHERE image_hash = ? AND is_deleted = 0",
+                        "SELECT id, content_key_encrypted, image_path FROM screenshots WHERE image_hash = ? AND is_deleted = 0",
                         [hash],
-                        |row| Ok((row.get(0)?, row.get(1)?)),
+                        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
                     )
                     .ok();
                 match result {
-                    Some((key, real_path)) => (key, self.resolve_image_path(&real_path)),
+                    Some((id, key, real_path)) => (
+                        key.map(|key| (id, key)),
+                        self.resolve_image_path(&real_path),
+                    ),
                     None => return Err(format!("No screenshot found for hash: {}", hash)),
                 }
             } else {
-                let key: Option<Vec<u8>> = conn
+                let row: Option<(i64, Option<Vec<u8>>)> = conn
                     .query_row(
-                        "SELECT content_key_encrypted FROM screenshots WHERE image_path = ? AND is_deleted = 0",
+                        "SELECT id, content_key_encrypted FROM screenshots WHERE image_path = ? AND is_deleted = 0",
                         [path],
-                        |row| row.get(0),
+                        |row| Ok((row.get(0)?, row.get(1)?)),
                     )
                     .ok();
                 let resolved = self.resolve_image_path(path);
-                (key, resolved)
+                (row.and_then(|(id, key)| key.map(|key| (id, key))), resolved)
             }
         };
 
-        // Phase 2: Decrypt row key
-        let mut row_key = key_enc
-            .as_ref()
-            .and_then(|enc| decrypt_row_key_with_cng(enc).ok())
+        // Phase 2: Decrypt row key (cached while the session is unlocked)
+        let mut row_key = row
+            .and_then(|(id, key_enc)| self.cached_row_key(id, &key_enc).ok())
             .ok_or_else(|| "Failed to unwrap image row key".to_string())?;
 
         let thumb_path = Self::thumbnail_path_for(&abs_path);
@@ -380,6 +409,25 @@ impl StorageState {
         results
     }
 
+    /// Attach thumbnails to search results with one batched lookup.
+    /// Results whose thumbnail cannot be read keep `thumbnail: None`, so callers can
+    /// fall back to per-item loading.
+    pub fn attach_search_thumbnails(&self, results: &mut [SearchResult]) {
+        let mut ids: Vec<i64> = results.iter().map(|r| r.screenshot_id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+
+        let thumbnails = self.batch_read_thumbnails_by_ids(&ids);
+        for result in results.iter_mut() {
+            if let Some(Ok((data, mime_type))) = thumbnails.get(&result.screenshot_id.to_string()) {
+                result.thumbnail = Some(InlineThumbnail {
+                    data: data.clone(),
+                    mime_type: mime_type.clone(),
+                });
+            }
+        }
+    }
+
     /// Batch read thumbnails for multiple image paths in a single operation.
     /// Does one DB query to fetch all encrypted keys, then processes each thumbnail outside the lock.
     /// Returns a Vec of (path, Result<(base64, mime_type), error_string>).
@@ -392,7 +440,7 @@ impl StorageState {
         }
 
         // Single DB query to get all content_key_encrypted values
-        let key_map: std::collections::HashMap<String, Option<Vec<u8>>> = {
+        let key_map: std::collections::HashMap<String, (i64, Option<Vec<u8>>)> = {
             let conn = match self.open_read_connection_named("batch_read_thumbnails") {
                 Ok(conn) => conn,
                 Err(e) => {
@@ -407,7 +455,7 @@ impl StorageState {
             for chunk in paths.chunks(500) {
                 let placeholders: Vec<&str> = chunk.iter().map(|_| "?").collect();
                 let sql = format!(
-                    "SELECT image_path, content_key_encrypted FROM screenshots WHERE is_deleted = 0 AND image_path IN ({})",
+                    "SELECT image_path, id, content_key_encrypted FROM screenshots WHERE is_deleted = 0 AND image_path IN ({})",
                     placeholders.join(",")
                 );
                 let params: Vec<&dyn rusqlite::ToSql> =
@@ -415,7 +463,10 @@ impl StorageState {
 
                 if let Ok(mut stmt) = conn.prepare(&sql) {
                     if let Ok(rows) = stmt.query_map(params.as_slice(), |row| {
-                        Ok((row.get::<_, String>(0)?, row.get::<_, Option<Vec<u8>>>(1)?))
+                        Ok((
+                            row.get::<_, String>(0)?,
+                            (row.get::<_, i64>(1)?, row.get::<_, Option<Vec<u8>>>(2)?),
+                        ))
                     }) {
                         for row in rows.filter_map(|r| r.ok()) {
                             map.insert(row.0, row.1);
@@ -431,13 +482,13 @@ impl StorageState {
             .iter()
             .map(|path| {
                 let result = (|| -> Result<(String, String), String> {
-                    let key_enc = key_map
+                    let (id, key_enc) = key_map
                         .get(path)
                         .ok_or_else(|| format!("No screenshot found for path: {}", path))?;
 
                     let mut row_key = key_enc
                         .as_ref()
-                        .and_then(|enc| decrypt_row_key_with_cng(enc).ok())
+                        .and_then(|enc| self.cached_row_key(*id, enc).ok())
                         .ok_or_else(|| "Failed to unwrap image row key".to_string())?;
 
                     let abs_path = self.resolve_image_path(path);
@@ -551,22 +602,12 @@ impl StorageState {
         thumb_path: &Path,
         row_key: &[u8],
     ) -> Result<(String, String), String> {
+        // Read and decrypt original image (loose file or cold-storage bundle entry)
         let original = Path::new(original_path);
-        if !original.exists() {
-            return Err(format!("Image file not found: {}", original_path));
-        }
-
-        // Read and decrypt original image
-        let raw_data =
-            std::fs::read(original).map_err(|e| format!("Failed to read image: {}", e))?;
-        let fname = original.file_name().and_then(|s| s.to_str()).unwrap_or("");
-        let is_encrypted = fname.contains(".enc");
-        let image_data = if is_encrypted {
-            decrypt_with_master_key(row_key, &raw_data)
-                .map_err(|e| format!("Failed to decrypt image: {}", e))?
-        } else {
-            raw_data
-        };
+        let image = decrypt_image_file(original, row_key).map_err(|e| e.to_string())?;
+        let (image_data, _) = self
+            .compose_delta_frame(original, image, false)
+            .map_err(|e| e.to_string())?;
 
         // Decode and resize
         let img = image::load_from_memory(&image_data)
@@ -580,13 +621,15 @@ impl StorageState {
             .map_err(|e| format!("Failed to encode thumbnail: {}", e))?;
         let jpeg_bytes = jpeg_buf.into_inner();
 
-        // Encrypt and write to cache
-        let encrypted = encrypt_with_master_key(row_key, &jpeg_bytes)
-            .map_err(|e| format!("Failed to encrypt thumbnail: {}", e))?;
-        if let Some(parent) = thumb_path.parent() {
-            let _ = std::fs::create_dir_all(parent);
+        // Encrypt and write to cache (guest mode leaves the data directory untouched)
+        if !self.is_read_only() {
+            let encrypted = encrypt_with_master_key(row_key, &jpeg_bytes)
+                .map_err(|e| format!("Failed to encrypt thumbnail: {}", e))?;
+            if let Some(parent) = thumb_path.parent() {
+                let _ = std::fs::create_dir_all(parent);
+            }
+            let _ = std::fs::write(thumb_path, &encrypted);
         }
-        let _ = std::fs::write(thumb_path, &encrypted);
 
         // Return base64
         let b64 = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &jpeg_bytes);
@@ -646,6 +689,7 @@ pub fn read_image_as_base64(path: &str) -> Result<(String, String), String> {
 }
 
 /// Read an encrypted image file and return Base64-encoded data (with decryption).
+#[allow(dead_code)]
 pub fn read_encrypted_image_as_base64(
     path: &str,
     row_key: &[u8],
@@ -657,31 +701,48 @@ pub fn read_encrypted_image_as_base64(
     Ok((base64_data, mime_type))
 }
 
-/// Read an encrypted image file and return raw image bytes (with decryption).
-pub fn read_encrypted_image_bytes(path: &str, row_key: &[u8]) -> Result<(Vec<u8>, String), String> {
-    let path = Path::new(path);
+/// Why a stored image failed to load or verify.
+///
+/// AES-GCM alone cannot tell a wrong key from a damaged ciphertext, but row keys
+/// are unwrapped with RSA-OAEP, which rejects the wrong private key. A row key that
+/// unwraps cleanly followed by a tag mismatch therefore means the file itself is bad.
+#[derive(Debug, thiserror::Error)]
+pub enum ImageIntegrityError {
+    #[error("Image file not found: {0}")]
+    Missing(String),
+    #[error("Failed to read image file: {0}")]
+    Io(String),
+    #[error("Failed to unwrap image row key: {0}")]
+    WrongKey(String),
+    #[error("Failed to decrypt image: ciphertext truncated ({0} bytes)")]
+    Truncated(usize),
+    #[error("Failed to decrypt image: authentication tag mismatch")]
+    Corrupted,
+    #[error("Failed to decode image: {0}")]
+    Undecodable(String),
+}
 
-    if !path.exists() {
-        return Err(format!("Image file not found: {}", path.display()));
+impl ImageIntegrityError {
+    /// Stable machine-readable code for frontend handling.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Missing(_) => "IMAGE_MISSING",
+            Self::Io(_) => "IMAGE_IO",
+            Self::WrongKey(_) => "IMAGE_WRONG_KEY",
+            Self::Truncated(_) => "IMAGE_TRUNCATED",
+            Self::Corrupted => "IMAGE_CORRUPTED",
+            Self::Undecodable(_) => "IMAGE_UNDECODABLE",
+        }
     }
+}
 
-    let data = std::fs::read(path).map_err(|e| format!("Failed to read image file: {}", e))?;
-    // Detect encrypted file: filename contains ".enc" (compatible with .enc.pending)
-    let fname = path.file_name().and_then(|s| s.to_str()).unwrap_or("");
-    let is_encrypted = fname.contains(".enc");
-
-    // Determine MIME type from base filename (strip .enc/.pending suffixes)
-    let base_name = if is_encrypted {
-        if let Some(pos) = fname.find(".enc") {
-            &fname[..pos]
-        } else {
-            fname
-        }
-    } else {
-        fname
+/// MIME type of a stored image from its file name, ignoring `.enc`/`.pending` suffixes.
+pub(super) fn image_mime_type(file_name: &str) -> &'static str {
+    let base_name = match file_name.find(".enc") {
+        Some(pos) => &file_name[..pos],
+        None => file_name,
     };
-
-    let mime_type = if base_name.ends_with(".png") {
+    if base_name.ends_with(".png") {
         "image/png"
     } else if base_name.ends_with(".jpg") || base_name.ends_with(".jpeg") {
         "image/jpeg"
@@ -691,15 +752,116 @@ pub fn read_encrypted_image_bytes(path: &str, row_key: &[u8]) -> Result<(Vec<u8>
         "image/webp"
     } else {
         "image/png"
-    };
+    }
+}
+
+/// AES-GCM nonce (12 bytes) plus authentication tag (16 bytes).
+const MIN_ENCRYPTED_LEN: usize = 12 + 16;
+
+/// Read an image file and decrypt it when its name carries `.enc`, returning typed failures.
+/// Images archived to cold storage are read from their bundle entry.
+pub fn decrypt_image_file(
+    path: &Path,
+    row_key: &[u8],
+) -> Result<(Vec<u8>, String), ImageIntegrityError> {
+    if let Some(entry) = BundleEntry::parse(path) {
+        return read_bundle_entry(&entry, row_key);
+    }
+    if !path.exists() {
+        return Err(ImageIntegrityError::Missing(path.display().to_string()));
+    }
+
+    let data = std::fs::read(path).map_err(|e| ImageIntegrityError::Io(e.to_string()))?;
+    // Detect encrypted file: filename contains ".enc" (compatible with .enc.pending)
+    let fname = path.file_name().and_then(|s| s.to_str()).unwrap_or("");
+    let is_encrypted = fname.contains(".enc");
+
+    let mime_type = image_mime_type(fname);
 
     let image_data = if is_encrypted {
-        // Decrypt file contents
-        decrypt_with_master_key(row_key, &data)
-            .map_err(|e| format!("Failed to decrypt image: {}", e))?
+        if data.len() < MIN_ENCRYPTED_LEN {
+            return Err(ImageIntegrityError::Truncated(data.len()));
+        }
+        decrypt_with_master_key(row_key, &data).map_err(|_| ImageIntegrityError::Corrupted)?
     } else {
         data
     };
 
     Ok((image_data, mime_type.to_string()))
 }
+
+/// Decrypt an image file and confirm the plaintext decodes as an image.
+pub fn verify_image_file(path: &Path, row_key: &[u8]) -> Result<(), ImageIntegrityError> {
+    let (image_data, _) = decrypt_image_file(path, row_key)?;
+    image::load_from_memory(&image_data)
+        .map(|_| ())
+        .map_err(|e| ImageIntegrityError::Undecodable(e.to_string()))
+}
+
+/// Read an encrypted image file and return raw image bytes (with decryption).
+pub fn read_encrypted_image_bytes(path: &str, row_key: &[u8]) -> Result<(Vec<u8>, String), String> {
+    decrypt_image_file(Path::new(path), row_key).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decrypt_image_file, verify_image_file, ImageIntegrityError};
+    use crate::credential_manager::encrypt_with_master_key;
+
+    fn png_bytes() -> Vec<u8> {
+        let img = image::RgbImage::from_pixel(4, 4, image::Rgb([10, 20, 30]));
+        let mut buf = std::io::Cursor::new(Vec::new());
+        img.write_to(&mut buf, image::ImageFormat::Png)
+            .expect("encode fixture png");
+        buf.into_inner()
+    }
+
+    #[test]
+    fn verify_image_file_accepts_intact_encrypted_png() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let key = [7u8; 32];
+        let path = temp.path().join("shot.png.enc");
+        std::fs::write(&path, encrypt_with_master_key(&key, &png_bytes()).unwrap()).unwrap();
+
+        verify_image_file(&path, &key).expect("intact image verifies");
+    }
+
+    #[test]
+    fn verify_image_file_classifies_failures() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let key = [7u8; 32];
+
+        let missing = temp.path().join("missing.png.enc");
+        assert!(matches!(
+            verify_image_file(&missing, &key),
+            Err(ImageIntegrityError::Missing(_))
+        ));
+
+        let truncated = temp.path().join("truncated.png.enc");
+        std::fs::write(&truncated, [0u8; 10]).unwrap();
+        assert!(matches!(
+            verify_image_file(&truncated, &key),
+            Err(ImageIntegrityError::Truncated(10))
+        ));
+
+        let flipped = temp.path().join("flipped.png.enc");
+        let mut encrypted = encrypt_with_master_key(&key, &png_bytes()).unwrap();
+        let last = encrypted.len() - 1;
+        encrypted[last] ^= 0xFF;
+        std::fs::write(&flipped, encrypted).unwrap();
+        let err = verify_image_file(&flipped, &key).unwrap_err();
+        assert_eq!(err.code(), "IMAGE_CORRUPTED");
+
+        let garbage = temp.path().join("garbage.png.enc");
+        std::fs::write(
+            &garbage,
+            encrypt_with_master_key(&key, b"not an image").unwrap(),
+        )
+        .unwrap();
+        assert!(decrypt_image_file(&garbage, &key).is_ok());
+        assert!(matches!(
+            verify_image_file(&garbage, &key),
+            Err(ImageIntegrityError::Undecodable(_))
+        ));
+    }
+}
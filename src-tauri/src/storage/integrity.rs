@@ -0,0 +1,340 @@
+//! Sampled verification of encrypted screenshot files, and the database health check.
+//!
+//! `check_database_integrity` runs SQLite's and SQLCipher's page checks, looks for
+//! foreign key violations, unwraps a random sample of row keys through CNG, and
+//! cross-checks the blind bitmap index against `ocr_results`. With `repair` it fixes
+//! what can be fixed in place: indexes are rebuilt when the page check reports index
+//! damage, and dangling postings are compacted away. Damage to table pages, keys that
+//! no longer unwrap and unreadable posting lists are only reported.
+
+use rand::Rng;
+use roaring::RoaringBitmap;
+use rusqlite::{params, Connection};
+
+use super::image_io::verify_image_file;
+use super::{
+    CorruptImageEntry, DatabaseHealthReport, DatabaseMaintenanceReport, ImageVerificationReport,
+    StorageState,
+};
+
+/// Row keys unwrapped per health check.
+const ROW_KEY_SAMPLE: i64 = 32;
+/// Messages kept from each pragma; a badly damaged file can produce thousands.
+const MAX_PRAGMA_MESSAGES: usize = 100;
+/// Posting lists read per query while cross-checking the bitmap index.
+const BITMAP_SCAN_BATCH: i64 = 500;
+
+/// Messages returned by a check pragma, with the single `ok` row meaning none.
+fn pragma_messages(conn: &Connection, pragma: &str) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA {}", pragma))?;
+    let messages = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .filter_map(|r| r.ok())
+        .filter(|message| message != "ok")
+        .take(MAX_PRAGMA_MESSAGES)
+        .collect();
+    Ok(messages)
+}
+
+/// Whether `integrity_check` output only complains about indexes, which `REINDEX`
+/// rebuilds from the table data.
+fn only_index_damage(messages: &[String]) -> bool {
+    !messages.is_empty()
+        && messages.iter().all(|message| {
+            message.contains("missing from index")
+                || message.contains("wrong # of entries in index")
+                || message.starts_with("non-unique entry in index")
+        })
+}
+
+impl StorageState {
+    /// Spot-check that stored `.enc` images still decrypt and decode.
+    ///
+    /// Each active screenshot is checked with probability `sample_rate` (clamped to
+    /// `0.0..=1.0`). Failures are returned with a stable code so the UI can offer
+    /// re-capture or removal; nothing is deleted here.
+    pub fn verify_images(&self, sample_rate: f64) -> Result<ImageVerificationReport, String> {
+        if !sample_rate.is_finite() || sample_rate <= 0.0 {
+            return Err(format!("Invalid sample_rate: {}", sample_rate));
+        }
+        let sample_rate = sample_rate.min(1.0);
+
+        let rows: Vec<(i64, String, Option<Vec<u8>>)> = {
+            let conn = self.open_read_connection_named("verify_images")?;
+            let mut stmt = conn
+                .prepare(
+                    "SELECT id, image_path, content_key_encrypted FROM screenshots
+                     WHERE is_deleted = 0 AND (status IS NULL OR status = 'committed')
+                     ORDER BY id",
+                )
+                .map_err(|e| format!("Failed to prepare query: {}", e))?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+                .map_err(|e| format!("Failed to query screenshots: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows
+        };
+
+        let total = rows.len() as i64;
+        let mut rng = rand::thread_rng();
+        let mut checked = 0i64;
+        let mut corrupt = Vec::new();
+
+        for (screenshot_id, image_path, key_enc) in rows {
+            if sample_rate < 1.0 && !rng.gen_bool(sample_rate) {
+                continue;
+            }
+            checked += 1;
+
+            let abs_path = self.resolve_image_path(&image_path);
+            let result = Self::unwrap_image_row_key(key_enc.as_deref()).and_then(|mut row_key| {
+                let verified = verify_image_file(&abs_path, &row_key);
+                Self::zeroize_bytes(&mut row_key);
+                verified
+            });
+
+            if let Err(error) = result {
+                tracing::warn!(
+                    "[Integrity] screenshot {} failed verification: {} ({})",
+                    screenshot_id,
+                    error.code(),
+                    error
+                );
+                corrupt.push(CorruptImageEntry {
+                    screenshot_id,
+                    image_path,
+                    code: error.code().to_string(),
+                    error: error.to_string(),
+                });
+            }
+        }
+
+        Ok(ImageVerificationReport {
+            sample_rate,
+            total,
+            checked,
+            ok: checked - corrupt.len() as i64,
+            corrupt,
+        })
+    }
+
+    /// Check the database for damage and, with `repair`, fix what can be fixed.
+    pub fn check_database_integrity(&self, repair: bool) -> Result<DatabaseHealthReport, String> {
+        let mut report = DatabaseHealthReport::default();
+        {
+            let guard = self.get_connection_named("check_database_integrity")?;
+            let conn = guard.as_ref().unwrap();
+            report.integrity_errors = pragma_messages(conn, "integrity_check")
+                .map_err(|e| format!("Failed to run integrity check: {}", e))?;
+            // Plain SQLite builds reject the pragma; SQLCipher returns nothing when
+            // every page authenticates.
+            report.cipher_errors = pragma_messages(conn, "cipher_integrity_check").ok();
+            report.foreign_key_violations = conn
+                .query_row("SELECT COUNT(*) FROM pragma_foreign_key_check", [], |row| {
+                    row.get(0)
+                })
+                .map_err(|e| format!("Failed to run foreign key check: {}", e))?;
+        }
+
+        self.check_row_key_sample(&mut report)?;
+        self.check_bitmap_postings(&mut report)?;
+
+        if repair {
+            if only_index_damage(&report.integrity_errors) {
+                let guard = self.get_connection_named("check_database_integrity.reindex")?;
+                let conn = guard.as_ref().unwrap();
+                conn.execute_batch("REINDEX;")
+                    .map_err(|e| format!("Failed to run reindex: {}", e))?;
+                report.integrity_errors = pragma_messages(conn, "integrity_check")
+                    .map_err(|e| format!("Failed to run integrity check: {}", e))?;
+                report.repairs.push("reindex".to_string());
+            }
+            if report.bitmap_tokens_dangling > 0 {
+                self.compact_blind_bitmap_index(&mut DatabaseMaintenanceReport::default())?;
+                report.bitmap_tokens_dangling = 0;
+                report.bitmap_dangling_postings = 0;
+                report.repairs.push("compact_bitmap_index".to_string());
+            }
+        }
+
+        report.healthy = report.integrity_errors.is_empty()
+            && report.cipher_errors.as_ref().is_none_or(|e| e.is_empty())
+            && report.foreign_key_violations == 0
+            && report.row_key_failures.is_empty()
+            && report.bitmap_tokens_dangling == 0
+            && report.bitmap_tokens_unreadable == 0;
+        tracing::info!(
+            "[Integrity] database check: healthy={}, integrity_errors={}, row_key_failures={}, dangling_tokens={}, repairs={:?}",
+            report.healthy,
+            report.integrity_errors.len(),
+            report.row_key_failures.len(),
+            report.bitmap_tokens_dangling,
+            report.repairs
+        );
+        Ok(report)
+    }
+
+    /// Unwrap the row keys of a random sample of committed screenshots.
+    fn check_row_key_sample(&self, report: &mut DatabaseHealthReport) -> Result<(), String> {
+        let rows: Vec<(i64, Option<Vec<u8>>)> = {
+            let guard = self.get_connection_named("check_row_key_sample")?;
+            let conn = guard.as_ref().unwrap();
+            let mut stmt = conn
+                .prepare(
+                    "SELECT id, content_key_encrypted FROM screenshots
+                     WHERE is_deleted = 0 AND (status IS NULL OR status = 'committed')
+                     ORDER BY RANDOM() LIMIT ?1",
+                )
+                .map_err(|e| format!("Failed to prepare row key sample: {}", e))?;
+            let rows = stmt
+                .query_map(params![ROW_KEY_SAMPLE], |row| {
+                    Ok((row.get(0)?, row.get(1)?))
+                })
+                .map_err(|e| format!("Failed to sample row keys: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows
+        };
+
+        for (id, key_enc) in rows {
+            report.row_keys_checked += 1;
+            match Self::unwrap_image_row_key(key_enc.as_deref()) {
+                Ok(mut row_key) => Self::zeroize_bytes(&mut row_key),
+                Err(e) => {
+                    tracing::warn!("[Integrity] row key of screenshot {} failed: {}", id, e);
+                    report.row_key_failures.push(id);
+                }
+            }
+        }
+        report.row_key_failures.sort_unstable();
+        Ok(())
+    }
+
+    /// Count postings in `blind_bitmap_index` that point at missing OCR rows. IDs above
+    /// the newest OCR row belong to a capture in flight and are not counted.
+    fn check_bitmap_postings(&self, report: &mut DatabaseHealthReport) -> Result<(), String> {
+        let live: RoaringBitmap = {
+            let guard = self.get_connection_named("check_bitmap_postings.live")?;
+            let conn = guard.as_ref().unwrap();
+            let mut stmt = conn
+                .prepare("SELECT id FROM ocr_results")
+                .map_err(|e| format!("Failed to prepare live OCR id query: {}", e))?;
+            let live = stmt
+                .query_map([], |row| row.get::<_, i64>(0))
+                .map_err(|e| format!("Failed to query live OCR ids: {}", e))?
+                .filter_map(|r| r.ok())
+                .filter_map(|id| u32::try_from(id).ok())
+                .collect();
+            live
+        };
+        let max_live_id = live.max().unwrap_or(0);
+
+        let mut last_token = String::new();
+        loop {
+            let rows: Vec<(String, Vec<u8>)> = {
+                let guard = self.get_connection_named("check_bitmap_postings")?;
+                let conn = guard.as_ref().unwrap();
+                let mut stmt = conn
+                    .prepare_cached(
+                        "SELECT token_hash, postings_blob FROM blind_bitmap_index
+                         WHERE token_hash > ?1 ORDER BY token_hash LIMIT ?2",
+                    )
+                    .map_err(|e| format!("Failed to prepare bitmap scan: {}", e))?;
+                let rows = stmt
+                    .query_map(params![&last_token, BITMAP_SCAN_BATCH], |row| {
+                        Ok((row.get(0)?, row.get(1)?))
+                    })
+                    .map_err(|e| format!("Failed to scan bitmap index: {}", e))?
+                    .filter_map(|r| r.ok())
+                    .collect();
+                rows
+            };
+            let Some((token, _)) = rows.last() else {
+                break;
+            };
+            last_token = token.clone();
+
+            for (_, blob) in &rows {
+                report.bitmap_tokens_scanned += 1;
+                let Ok(mut bitmap) = RoaringBitmap::deserialize_from(&blob[..]) else {
+                    report.bitmap_tokens_unreadable += 1;
+                    continue;
+                };
+                bitmap.remove_range(max_live_id.saturating_add(1)..);
+                let dangling = bitmap.len() - bitmap.intersection_len(&live);
+                if dangling > 0 {
+                    report.bitmap_tokens_dangling += 1;
+                    report.bitmap_dangling_postings += dangling as i64;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_storage;
+
+    fn postings(ids: &[u32]) -> Vec<u8> {
+        let bitmap: RoaringBitmap = ids.iter().copied().collect();
+        let mut buf = Vec::new();
+        bitmap.serialize_into(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn health_check_finds_and_compacts_dangling_postings() {
+        let (_temp, storage) = test_storage();
+        let guard = storage.db.lock().unwrap_or_else(|e| e.into_inner());
+        let connection = guard.as_ref().expect("test database");
+        connection
+            .execute_batch(
+                "INSERT INTO screenshots (id, image_path, image_hash) VALUES
+                    (1, 'screenshots/a.png.enc', 'h1');
+                 INSERT INTO ocr_results (id, screenshot_id, text_hash) VALUES
+                    (1, 1, 't1'), (3, 1, 't3');",
+            )
+            .expect("health fixture");
+        connection
+            .execute(
+                "INSERT INTO blind_bitmap_index (token_hash, postings_blob) VALUES
+                    ('a', ?1), ('b', ?2), ('c', x'00')",
+                params![postings(&[1, 2, 3]), postings(&[1, 9])],
+            )
+            .expect("bitmap fixture");
+        drop(guard);
+
+        let report = storage.check_database_integrity(false).expect("check");
+        assert!(!report.healthy);
+        assert!(report.integrity_errors.is_empty());
+        assert_eq!(report.foreign_key_violations, 0);
+        // The row has no wrapped key, so unwrapping it fails.
+        assert_eq!(report.row_key_failures, vec![1]);
+        assert_eq!(report.bitmap_tokens_scanned, 3);
+        // 2 is dangling; 9 is newer than every OCR row and may still be written.
+        assert_eq!(report.bitmap_tokens_dangling, 1);
+        assert_eq!(report.bitmap_dangling_postings, 1);
+        assert_eq!(report.bitmap_tokens_unreadable, 1);
+
+        let report = storage.check_database_integrity(true).expect("repair");
+        assert_eq!(report.repairs, vec!["compact_bitmap_index".to_string()]);
+        let report = storage.check_database_integrity(false).expect("recheck");
+        assert_eq!(report.bitmap_tokens_dangling, 0);
+    }
+
+    #[test]
+    fn reindex_is_only_offered_for_index_damage() {
+        assert!(only_index_damage(&[
+            "row 12 missing from index idx_screenshots_created_at".to_string(),
+            "wrong # of entries in index idx_screenshots_created_at".to_string(),
+        ]));
+        assert!(!only_index_damage(&[
+            "row 12 missing from index idx_a".to_string(),
+            "Page 40: btreeInitPage() returns error code 11".to_string(),
+        ]));
+        assert!(!only_index_damage(&[]));
+    }
+}
@@ -0,0 +1,540 @@
+//! Re-wrapping of stored row keys for a master/CNG key rotation.
+//!
+//! Rotation runs in two phases. The re-wrap phase unwraps every row key with the
+//! current CNG key and stages it wrapped for the new public key in
+//! `key_rotation_staging`, in batches that hold the writer lock only briefly; an
+//! interrupted run resumes from what is already staged. The switch phase holds the
+//! writer lock throughout: it re-wraps rows written or re-keyed since they were
+//! staged, swaps every staged key in with one transaction, resets the blind indexes
+//! (their tokens are HMACs under the master key), rekeys the database for the new
+//! public key and only then installs the new key files.
+//!
+//! Secrets in the storage policy that are sealed under master-key-derived keys (the
+//! MCP token, scoped API tokens, the sync secret and the sync changelog key) are
+//! re-sealed into a pending policy file before the switch, which is installed with
+//! the key files, also when an interrupted switch is finished at startup.
+//!
+//! `access_audit_log` is append-only, so its targets stay wrapped with the previous
+//! key. That key is kept rather than deleted, and row-key unwrapping falls back to it.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+
+use rusqlite::{params, Connection};
+use serde_json::Value as JsonValue;
+
+use crate::credential_manager::{
+    begin_key_rotation, commit_key_rotation, decrypt_row_key_with_cng,
+    derive_db_key_from_public_key, derive_hmac_key_from_master, encrypt_with_exported_public_key,
+    get_cached_master_key, pending_rotation_public_key, CredentialError, RotatedKeys,
+};
+
+use super::StorageState;
+
+/// Rows re-wrapped per writer-lock acquisition during the re-wrap phase.
+const KEY_ROTATION_BATCH: i64 = 200;
+/// Suffix of the re-sealed policy written before the switch.
+const PENDING_POLICY_SUFFIX: &str = ".next";
+
+/// Tables and columns holding row keys wrapped with the CNG public key.
+const WRAPPED_KEY_COLUMNS: &[(&str, &str)] = &[
+    ("screenshots", "content_key_encrypted"),
+    ("ocr_results", "text_key_encrypted"),
+    ("annotations", "text_key_encrypted"),
+    ("page_icons", "icon_key_encrypted"),
+    ("link_sets", "links_key_encrypted"),
+    ("saved_searches", "payload_key_encrypted"),
+    ("search_history", "query_key_encrypted"),
+    ("delta_keyframes", "key_encrypted"),
+    ("clipboard_entries", "payload_key_encrypted"),
+    ("audio_transcripts", "payload_key_encrypted"),
+];
+
+/// A row key staged for the switch: `(row id, current wrapped key, re-wrapped key)`.
+type StagedKey = (i64, Vec<u8>, Vec<u8>);
+
+impl StorageState {
+    pub fn is_key_rotation_in_progress(&self) -> bool {
+        self.key_rotation_in_progress.load(Ordering::SeqCst)
+    }
+
+    /// Rotate to a new CNG key pair and master key. `progress_callback` receives
+    /// `(phase, processed, total)` where `phase` is the table being re-wrapped or
+    /// `"switching"`.
+    pub fn run_key_rotation<F>(&self, mut progress_callback: F) -> Result<(), String>
+    where
+        F: FnMut(&str, usize, usize),
+    {
+        if self
+            .key_rotation_in_progress
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Err("ALREADY_RUNNING".to_string());
+        }
+
+        let result = self.run_key_rotation_internal(&mut progress_callback);
+
+        self.key_rotation_in_progress.store(false, Ordering::SeqCst);
+        result
+    }
+
+    fn run_key_rotation_internal<F>(&self, progress_callback: &mut F) -> Result<(), String>
+    where
+        F: FnMut(&str, usize, usize),
+    {
+        if self.is_index_rebuild_in_progress() {
+            return Err("INDEX_REBUILD_IN_PROGRESS".to_string());
+        }
+        // Shards keep row keys wrapped with the current key; see `storage/month_shards.rs`.
+        if self.has_month_shards()? {
+            return Err("Attach detached months before rotating keys".to_string());
+        }
+        // Policy secrets are re-sealed with the current master key at the switch.
+        if get_cached_master_key(&self.credential_state).is_none() {
+            return Err("AUTH_REQUIRED".to_string());
+        }
+        let keys = begin_key_rotation(&self.credential_state)
+            .map_err(|e| format!("Failed to prepare key rotation: {}", e))?;
+
+        for (table, column) in WRAPPED_KEY_COLUMNS {
+            self.stage_rewrapped_keys(table, column, &keys.public_key, progress_callback)?;
+        }
+
+        progress_callback("switching", 0, 1);
+        self.switch_to_rotated_keys(&keys)?;
+        progress_callback("switching", 1, 1);
+
+        tracing::info!("[KEY_ROTATION] Key rotation completed");
+        Ok(())
+    }
+
+    fn stage_rewrapped_keys<F>(
+        &self,
+        table: &str,
+        column: &str,
+        public_key: &[u8],
+        progress_callback: &mut F,
+    ) -> Result<(), String>
+    where
+        F: FnMut(&str, usize, usize),
+    {
+        let (mut cursor, mut processed, total) = {
+            let guard = self.get_connection_named("key_rotation_progress")?;
+            let conn = guard.as_ref().unwrap();
+            let (cursor, staged): (i64, i64) = conn
+                .query_row(
+                    "SELECT COALESCE(MAX(row_id), 0), COUNT(*) FROM key_rotation_staging
+                     WHERE table_name = ?1",
+                    params![table],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .map_err(|e| format!("Failed to read key rotation progress: {}", e))?;
+            let total: i64 = conn
+                .query_row(
+                    &format!(
+                        "SELECT COUNT(*) FROM {} WHERE {} IS NOT NULL",
+                        table, column
+                    ),
+                    [],
+                    |row| row.get(0),
+                )
+                .map_err(|e| format!("Failed to count {} rows: {}", table, e))?;
+            (cursor, staged as usize, total as usize)
+        };
+        progress_callback(table, processed.min(total), total);
+
+        loop {
+            let rows: Vec<(i64, Vec<u8>)> = {
+                let guard = self.get_connection_named("key_rotation_read")?;
+                let conn = guard.as_ref().unwrap();
+                let mut stmt = conn
+                    .prepare(&format!(
+                        "SELECT id, {column} FROM {table}
+                         WHERE id > ?1 AND {column} IS NOT NULL
+                         ORDER BY id ASC LIMIT ?2"
+                    ))
+                    .map_err(|e| format!("Failed to prepare key rotation read: {}", e))?;
+                let rows = stmt
+                    .query_map(params![cursor, KEY_ROTATION_BATCH], |row| {
+                        Ok((row.get(0)?, row.get(1)?))
+                    })
+                    .map_err(|e| format!("Failed to read {} row keys: {}", table, e))?
+                    .filter_map(|r| r.ok())
+                    .collect();
+                rows
+            };
+            let Some((last_id, _)) = rows.last() else {
+                break;
+            };
+            cursor = *last_id;
+            processed += rows.len();
+
+            // Unwrapping goes through CNG, so it runs without holding the writer lock.
+            let staged = Self::rewrap_row_keys(table, rows, public_key)?;
+            {
+                let mut guard = self.get_connection_named("key_rotation_stage")?;
+                let conn = guard.as_mut().unwrap();
+                let tx = conn
+                    .transaction()
+                    .map_err(|e| format!("Failed to begin key rotation batch: {}", e))?;
+                Self::insert_staged_keys(&tx, table, &staged)?;
+                tx.commit()
+                    .map_err(|e| format!("Failed to commit key rotation batch: {}", e))?;
+            }
+
+            progress_callback(table, processed.min(total), total);
+        }
+        Ok(())
+    }
+
+    /// Unwrap each row key with the current CNG key and wrap it for `public_key`.
+    /// Keys that cannot be unwrapped are left as they are; locked or cancelled
+    /// credentials abort the rotation instead.
+    fn rewrap_row_keys(
+        table: &str,
+        rows: Vec<(i64, Vec<u8>)>,
+        public_key: &[u8],
+    ) -> Result<Vec<StagedKey>, String> {
+        let mut staged = Vec::with_capacity(rows.len());
+        for (id, wrapped) in rows {
+            let mut row_key = match decrypt_row_key_with_cng(&wrapped) {
+                Ok(key) => key,
+                Err(e @ (CredentialError::AuthRequired | CredentialError::UserCancelled)) => {
+                    return Err(format!("Failed to unwrap row key: {}", e));
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "[KEY_ROTATION] {} row {} keeps its current key: {}",
+                        table,
+                        id,
+                        e
+                    );
+                    continue;
+                }
+            };
+            let rewrapped = encrypt_with_exported_public_key(public_key, &row_key);
+            Self::zeroize_bytes(&mut row_key);
+            let rewrapped =
+                rewrapped.map_err(|e| format!("Failed to wrap row key for new key: {}", e))?;
+            staged.push((id, wrapped, rewrapped));
+        }
+        Ok(staged)
+    }
+
+    fn insert_staged_keys(
+        tx: &rusqlite::Transaction<'_>,
+        table: &str,
+        staged: &[StagedKey],
+    ) -> Result<(), String> {
+        let mut stmt = tx
+            .prepare_cached(
+                "INSERT OR REPLACE INTO key_rotation_staging
+                 (table_name, row_id, old_key_encrypted, key_encrypted)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )
+            .map_err(|e| format!("Failed to prepare key rotation staging: {}", e))?;
+        for (id, old_key, new_key) in staged {
+            stmt.execute(params![table, id, old_key, new_key])
+                .map_err(|e| format!("Failed to stage row key: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Replace each wrapped key that still matches its staged original. Rows re-keyed
+    /// after staging keep their current key.
+    fn apply_staged_keys(tx: &rusqlite::Transaction<'_>) -> Result<(), String> {
+        for (table, column) in WRAPPED_KEY_COLUMNS {
+            tx.execute(
+                &format!(
+                    "UPDATE {table} SET {column} = s.key_encrypted
+                     FROM key_rotation_staging s
+                     WHERE s.table_name = ?1 AND s.row_id = {table}.id
+                       AND s.old_key_encrypted = {table}.{column}"
+                ),
+                params![table],
+            )
+            .map_err(|e| format!("Failed to switch {} row keys: {}", table, e))?;
+        }
+        Ok(())
+    }
+
+    /// The storage policy with every secret sealed under a master-key-derived key
+    /// re-sealed for `new_master_key`.
+    fn resealed_policy(&self, new_master_key: &[u8]) -> Result<JsonValue, String> {
+        let mut old_master_key = get_cached_master_key(&self.credential_state)
+            .ok_or_else(|| "AUTH_REQUIRED".to_string())?;
+        let mut policy = self.load_policy()?;
+        let resealed = crate::mcp_token::reseal_policy_tokens(
+            &self.credential_state,
+            &mut policy,
+            new_master_key,
+        )
+        .and_then(|()| {
+            super::remote_sync::reseal_sync_secrets(&mut policy, &old_master_key, new_master_key)
+        });
+        Self::zeroize_bytes(&mut old_master_key);
+        resealed?;
+        Ok(policy)
+    }
+
+    fn pending_policy_path(&self) -> PathBuf {
+        let mut path = self.policy_path().into_os_string();
+        path.push(PENDING_POLICY_SUFFIX);
+        PathBuf::from(path)
+    }
+
+    /// Replace the policy with the one re-sealed for the rotation, if there is one.
+    fn install_pending_policy(&self) -> Result<(), String> {
+        let pending = self.pending_policy_path();
+        if pending.is_file() {
+            std::fs::rename(&pending, self.policy_path())
+                .map_err(|e| format!("Failed to install re-sealed policy: {}", e))?;
+        }
+        Ok(())
+    }
+
+    fn switch_to_rotated_keys(&self, keys: &RotatedKeys) -> Result<(), String> {
+        let policy = serde_json::to_string_pretty(&self.resealed_policy(&keys.master_key)?)
+            .map_err(|e| format!("serde json error: {}", e))?;
+        std::fs::write(self.pending_policy_path(), policy)
+            .map_err(|e| format!("Failed to write re-sealed policy: {}", e))?;
+
+        let mut guard = self.get_connection_named("key_rotation_switch")?;
+        let conn = guard.as_mut().unwrap();
+
+        // Rows inserted or re-keyed since they were staged.
+        let mut late_rows: Vec<(&str, Vec<StagedKey>)> = Vec::new();
+        for (table, column) in WRAPPED_KEY_COLUMNS {
+            let rows: Vec<(i64, Vec<u8>)> = {
+                let mut stmt = conn
+                    .prepare(&format!(
+                        "SELECT t.id, t.{column} FROM {table} t
+                         LEFT JOIN key_rotation_staging s
+                           ON s.table_name = ?1 AND s.row_id = t.id
+                          AND s.old_key_encrypted = t.{column}
+                         WHERE t.{column} IS NOT NULL AND s.row_id IS NULL"
+                    ))
+                    .map_err(|e| format!("Failed to prepare key rotation catch-up: {}", e))?;
+                let rows = stmt
+                    .query_map(params![table], |row| Ok((row.get(0)?, row.get(1)?)))
+                    .map_err(|e| format!("Failed to read {} row keys: {}", table, e))?
+                    .filter_map(|r| r.ok())
+                    .collect();
+                rows
+            };
+            late_rows.push((
+                *table,
+                Self::rewrap_row_keys(table, rows, &keys.public_key)?,
+            ));
+        }
+
+        // Annotation postings are re-hashed from the note text.
+        let annotations: Vec<(i64, String)> = {
+            let mut stmt = conn
+                .prepare("SELECT id, text_enc, text_key_encrypted FROM annotations")
+                .map_err(|e| format!("Failed to prepare annotation read: {}", e))?;
+            let rows: Vec<(i64, Vec<u8>, Vec<u8>)> = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+                .map_err(|e| format!("Failed to read annotations: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows.into_iter()
+                .filter_map(|(id, text_enc, text_key)| {
+                    let bytes = self
+                        .decrypt_payload_with_row_key(&text_enc, &text_key)
+                        .ok()?;
+                    String::from_utf8(bytes).ok().map(|text| (id, text))
+                })
+                .collect()
+        };
+        let mut hmac_key = derive_hmac_key_from_master(&keys.master_key);
+
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to begin key rotation switch: {}", e))?;
+        for (table, staged) in &late_rows {
+            Self::insert_staged_keys(&tx, table, staged)?;
+        }
+        Self::apply_staged_keys(&tx)?;
+
+        // The lazy indexer rebuilds OCR and page metadata postings under the new HMAC key;
+        // clipboard entries and audio transcripts are re-indexed by their next search.
+        tx.execute_batch(
+            "DELETE FROM blind_bitmap_index;
+             DELETE FROM blind_bitmap_index_staging;
+             UPDATE ocr_results SET text_hash = '';
+             DELETE FROM annotation_bitmap_index;
+             DELETE FROM metadata_bitmap_index;
+             DELETE FROM clipboard_bitmap_index;
+             UPDATE clipboard_entries SET indexed = 0, content_hash = NULL;
+             DELETE FROM audio_bitmap_index;
+             UPDATE audio_transcripts SET indexed = 0;
+             UPDATE screenshots SET metadata_index_version = 0;
+             DELETE FROM key_rotation_staging;",
+        )
+        .map_err(|e| format!("Failed to reset blind indexes: {}", e))?;
+        // A half-built index rebuild used the old HMAC key.
+        tx.execute(
+            "DELETE FROM app_metadata WHERE key = ?1",
+            params![Self::BITMAP_REBUILD_CURSOR_KEY],
+        )
+        .map_err(|e| format!("Failed to reset index rebuild: {}", e))?;
+        let reindexed = annotations.iter().try_for_each(|(id, text)| {
+            Self::update_annotation_postings(&tx, &hmac_key, *id, None, Some(text))
+        });
+        Self::zeroize_bytes(&mut hmac_key);
+        reindexed?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit key rotation switch: {}", e))?;
+
+        // The SQLCipher key is derived from the public key. Rekeying needs the
+        // database out of WAL mode and no other connections open.
+        self.reset_read_pool();
+        let key_hex = hex::encode(derive_db_key_from_public_key(&keys.public_key));
+        conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE);")
+            .map_err(|e| format!("Failed to checkpoint WAL before rekey: {}", e))?;
+        conn.query_row("PRAGMA journal_mode = DELETE;", [], |row| {
+            row.get::<_, String>(0)
+        })
+        .map_err(|e| format!("Failed to leave WAL mode before rekey: {}", e))?;
+        conn.execute_batch(&format!("PRAGMA rekey = \"x'{}'\";", key_hex))
+            .map_err(|e| format!("Failed to rekey database: {}", e))?;
+        Self::enable_wal(conn)?;
+
+        self.install_pending_policy()?;
+        commit_key_rotation(&self.credential_state, Some(&keys.master_key))
+            .map_err(|e| format!("Failed to install rotated keys: {}", e))?;
+        self.reset_read_pool();
+        Ok(())
+    }
+
+    /// Open the database with the key of a rotation that was interrupted after the
+    /// rekey but before its key files were installed, then finish installing them.
+    /// Returns `None` when there is no such rotation or its key does not match.
+    pub(super) fn open_with_rotated_key(
+        &self,
+        db_path: &Path,
+        read_only: bool,
+    ) -> Result<Option<Connection>, String> {
+        if read_only {
+            return Ok(None);
+        }
+        let Some(public_key) = pending_rotation_public_key(&self.credential_state) else {
+            return Ok(None);
+        };
+
+        let conn =
+            Connection::open(db_path).map_err(|e| format!("Failed to open database: {}", e))?;
+        let key_hex = hex::encode(derive_db_key_from_public_key(&public_key));
+        conn.execute_batch(&format!("PRAGMA key = \"x'{}'\";", key_hex))
+            .map_err(|e| format!("Failed to set database key: {}", e))?;
+        if conn
+            .execute_batch("SELECT count(*) FROM sqlite_master;")
+            .is_err()
+        {
+            return Ok(None);
+        }
+
+        self.install_pending_policy()?;
+        commit_key_rotation(&self.credential_state, None)
+            .map_err(|e| format!("Failed to finish key rotation: {}", e))?;
+        tracing::info!("[KEY_ROTATION] Finished a rotation interrupted after the rekey");
+        Ok(Some(conn))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp_token::{self, TokenScope};
+    use crate::storage::remote_sync::policy_sync_data_key;
+    use crate::storage::{file_test_storage, test_storage, SyncConfig};
+
+    #[test]
+    fn staged_keys_replace_only_unchanged_rows() {
+        let (_temp, storage) = test_storage();
+        let mut guard = storage.db.lock().unwrap_or_else(|e| e.into_inner());
+        let connection = guard.as_mut().expect("test database");
+        connection
+            .execute_batch(
+                "INSERT INTO screenshots (id, image_path, image_hash, width, height, content_key_encrypted)
+                 VALUES (1, 'a.png', 'a', 1, 1, X'01'), (2, 'b.png', 'b', 1, 1, X'02');",
+            )
+            .expect("insert screenshots");
+
+        let tx = connection.transaction().expect("transaction");
+        StorageState::insert_staged_keys(
+            &tx,
+            "screenshots",
+            &[(1, vec![0x01], vec![0x11]), (2, vec![0x09], vec![0x12])],
+        )
+        .unwrap();
+        StorageState::apply_staged_keys(&tx).unwrap();
+        tx.commit().expect("commit");
+
+        let key_of = |id: i64| -> Vec<u8> {
+            connection
+                .query_row(
+                    "SELECT content_key_encrypted FROM screenshots WHERE id = ?1",
+                    params![id],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+        assert_eq!(key_of(1), vec![0x11]);
+        // Row 2 was re-keyed after it was staged, so the stale staged key is ignored.
+        assert_eq!(key_of(2), vec![0x02]);
+    }
+
+    #[test]
+    fn policy_secrets_stay_readable_across_two_rotations() {
+        let (_temp, storage) = file_test_storage();
+        let first_master = get_cached_master_key(&storage.credential_state).unwrap();
+        storage
+            .set_sync_config(
+                SyncConfig {
+                    endpoint: "https://dav.example.com/carbonpaper".to_string(),
+                    username: "me".to_string(),
+                    ..SyncConfig::default()
+                },
+                Some("dav-password"),
+            )
+            .unwrap();
+        let mut policy = storage.load_policy().unwrap();
+        policy["mcp_token_encrypted"] =
+            mcp_token::encrypt_token(&storage.credential_state, "mcp-token")
+                .unwrap()
+                .into();
+        let (record, api_token) =
+            mcp_token::create_api_token(&storage.credential_state, "ci", vec![TokenScope::Admin])
+                .unwrap();
+        mcp_token::store_api_tokens(&mut policy, &[record]).unwrap();
+        storage.save_policy(&policy).unwrap();
+        let changelog_key = policy_sync_data_key(&policy, &first_master).unwrap();
+
+        for next_master in [[2u8; 32], [3u8; 32]] {
+            let resealed = storage.resealed_policy(&next_master).unwrap();
+            storage.save_policy(&resealed).unwrap();
+            storage.credential_state.unlock_for_test(&next_master);
+        }
+
+        let policy = storage.load_policy().unwrap();
+        let state = &storage.credential_state;
+        assert_eq!(
+            mcp_token::decrypt_token(state, policy["mcp_token_encrypted"].as_str().unwrap())
+                .unwrap(),
+            "mcp-token"
+        );
+        let tokens = mcp_token::load_api_tokens(&policy);
+        assert_eq!(
+            mcp_token::decrypt_token(state, &tokens[0].token_encrypted).unwrap(),
+            api_token
+        );
+        assert_eq!(storage.sync_secret(&[3u8; 32]).unwrap(), "dav-password");
+        assert_eq!(
+            policy_sync_data_key(&policy, &[3u8; 32]).unwrap(),
+            changelog_key
+        );
+    }
+}
@@ -0,0 +1,284 @@
+//! Full database maintenance: blind index compaction, REINDEX, ANALYZE, and VACUUM.
+//!
+//! Deleting screenshots frees pages but never returns them to the file system, and
+//! posting lists in `blind_bitmap_index` can keep IDs of OCR rows that were removed
+//! without going through the delete queue. Maintenance compacts both. VACUUM holds
+//! the writer connection for its whole run, so the scheduler only starts it while the
+//! user is idle.
+
+use std::sync::atomic::Ordering;
+
+use roaring::RoaringBitmap;
+use rusqlite::{params, OptionalExtension};
+
+use super::{DatabaseMaintenanceReport, StorageState};
+
+/// Posting lists read and rewritten per transaction, so capture can interleave.
+const BITMAP_COMPACTION_BATCH: i64 = 500;
+const LAST_MAINTENANCE_KEY: &str = "last_database_maintenance_at";
+
+/// Step names reported to the progress callback, in order.
+const MAINTENANCE_STEPS: [&str; 4] = ["bitmap_index", "reindex", "analyze", "vacuum"];
+
+impl StorageState {
+    pub(super) fn database_size_bytes(&self) -> u64 {
+        let data_dir = self
+            .data_dir
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        ["screenshots.db", "screenshots.db-wal"]
+            .iter()
+            .filter_map(|name| std::fs::metadata(data_dir.join(name)).ok())
+            .map(|meta| meta.len())
+            .sum()
+    }
+
+    /// Unix seconds of the last completed maintenance run, if any.
+    pub fn last_database_maintenance_at(&self) -> Result<Option<i64>, String> {
+        let guard = self.get_connection_named("last_database_maintenance_at")?;
+        let conn = guard.as_ref().unwrap();
+        let value: Option<String> = conn
+            .query_row(
+                "SELECT value FROM app_metadata WHERE key = ?1",
+                params![LAST_MAINTENANCE_KEY],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to read last maintenance time: {}", e))?;
+        Ok(value.and_then(|v| v.parse().ok()))
+    }
+
+    /// Drop IDs of OCR rows that no longer exist from every posting list.
+    ///
+    /// IDs above the live-set snapshot belong to OCR rows inserted while compaction
+    /// runs and are always kept.
+    pub(super) fn compact_blind_bitmap_index(
+        &self,
+        report: &mut DatabaseMaintenanceReport,
+    ) -> Result<(), String> {
+        let (live, max_live_id) = {
+            let guard = self.get_connection_named("compact_bitmap_live_ids")?;
+            let conn = guard.as_ref().unwrap();
+            let mut stmt = conn
+                .prepare("SELECT id FROM ocr_results")
+                .map_err(|e| format!("Failed to prepare live OCR id query: {}", e))?;
+            let live: RoaringBitmap = stmt
+                .query_map([], |row| row.get::<_, i64>(0))
+                .map_err(|e| format!("Failed to query live OCR ids: {}", e))?
+                .filter_map(|r| r.ok())
+                .filter_map(|id| u32::try_from(id).ok())
+                .collect();
+            let max_live_id = live.max().unwrap_or(0);
+            (live, max_live_id)
+        };
+
+        let mut last_token = String::new();
+        loop {
+            let mut guard = self.get_connection_named("compact_bitmap_batch")?;
+            let conn = guard.as_mut().unwrap();
+            let tx = conn
+                .transaction()
+                .map_err(|e| format!("Failed to start bitmap compaction transaction: {}", e))?;
+            let rows: Vec<(String, Vec<u8>)> = {
+                let mut stmt = tx
+                    .prepare_cached(
+                        "SELECT token_hash, postings_blob FROM blind_bitmap_index
+                         WHERE token_hash > ?1 ORDER BY token_hash LIMIT ?2",
+                    )
+                    .map_err(|e| format!("Failed to prepare bitmap scan: {}", e))?;
+                let rows = stmt
+                    .query_map(params![&last_token, BITMAP_COMPACTION_BATCH], |row| {
+                        Ok((row.get(0)?, row.get(1)?))
+                    })
+                    .map_err(|e| format!("Failed to scan bitmap index: {}", e))?
+                    .filter_map(|r| r.ok())
+                    .collect();
+                rows
+            };
+            let Some((token, _)) = rows.last() else {
+                break;
+            };
+            last_token = token.clone();
+
+            for (token_hash, blob) in &rows {
+                report.bitmap_tokens_scanned += 1;
+                let bitmap = match RoaringBitmap::deserialize_from(&blob[..]) {
+                    Ok(bitmap) => bitmap,
+                    Err(e) => {
+                        tracing::warn!("[DB] Skipping unreadable posting list: {}", e);
+                        continue;
+                    }
+                };
+                let mut newer = bitmap.clone();
+                newer.remove_range(..=max_live_id);
+                let compacted = (&bitmap & &live) | newer;
+                if compacted.len() == bitmap.len() {
+                    continue;
+                }
+                if compacted.is_empty() {
+                    tx.execute(
+                        "DELETE FROM blind_bitmap_index WHERE token_hash = ?1",
+                        params![token_hash],
+                    )
+                    .map_err(|e| format!("Failed to delete empty bitmap row: {}", e))?;
+                    report.bitmap_tokens_removed += 1;
+                } else {
+                    let mut buf = Vec::new();
+                    compacted
+                        .serialize_into(&mut buf)
+                        .map_err(|e| format!("Failed to serialize bitmap: {}", e))?;
+                    tx.execute(
+                        "UPDATE blind_bitmap_index SET postings_blob = ?2 WHERE token_hash = ?1",
+                        params![token_hash, &buf],
+                    )
+                    .map_err(|e| format!("Failed to write bitmap row: {}", e))?;
+                    report.bitmap_tokens_compacted += 1;
+                }
+            }
+            tx.commit()
+                .map_err(|e| format!("Failed to commit bitmap compaction: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Compact the blind index, rebuild indexes, refresh planner statistics, and
+    /// VACUUM the database. `on_progress(step, index, total)` is called before each
+    /// step in `MAINTENANCE_STEPS`.
+    ///
+    /// Shares the VACUUM-in-progress flag with the startup and manual VACUUM, and
+    /// returns `ALREADY_RUNNING` when one of them is active.
+    pub fn run_database_maintenance(
+        &self,
+        on_progress: &dyn Fn(&str, usize, usize),
+    ) -> Result<DatabaseMaintenanceReport, String> {
+        if self
+            .startup_vacuum_in_progress
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Err("ALREADY_RUNNING".to_string());
+        }
+
+        let started = std::time::Instant::now();
+        let total = MAINTENANCE_STEPS.len();
+        let result = (|| {
+            let mut report = DatabaseMaintenanceReport {
+                bytes_before: self.database_size_bytes(),
+                ..Default::default()
+            };
+
+            on_progress(MAINTENANCE_STEPS[0], 0, total);
+            self.compact_blind_bitmap_index(&mut report)?;
+
+            let statements = [
+                "REINDEX;",
+                "ANALYZE;",
+                "VACUUM; PRAGMA wal_checkpoint(TRUNCATE);",
+            ];
+            for (offset, sql) in statements.iter().enumerate() {
+                let step = MAINTENANCE_STEPS[offset + 1];
+                on_progress(step, offset + 1, total);
+                let guard = self.get_connection_named("database_maintenance")?;
+                let conn = guard.as_ref().unwrap();
+                conn.execute_batch(sql)
+                    .map_err(|e| format!("Failed to run {}: {}", step, e))?;
+            }
+
+            {
+                let guard = self.get_connection_named("database_maintenance_mark")?;
+                let conn = guard.as_ref().unwrap();
+                conn.execute(
+                    "INSERT OR REPLACE INTO app_metadata (key, value) VALUES (?1, ?2)",
+                    params![
+                        LAST_MAINTENANCE_KEY,
+                        chrono::Utc::now().timestamp().to_string()
+                    ],
+                )
+                .map_err(|e| format!("Failed to record maintenance time: {}", e))?;
+            }
+
+            report.bytes_after = self.database_size_bytes();
+            report.duration_ms = started.elapsed().as_millis() as i64;
+            tracing::info!(
+                "[DB] Maintenance finished in {:?}: {} -> {} bytes, {} posting lists compacted, {} removed",
+                started.elapsed(),
+                report.bytes_before,
+                report.bytes_after,
+                report.bitmap_tokens_compacted,
+                report.bitmap_tokens_removed
+            );
+            Ok(report)
+        })();
+
+        self.startup_vacuum_in_progress
+            .store(false, Ordering::SeqCst);
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_storage;
+    use std::cell::RefCell;
+
+    fn postings(ids: &[u32]) -> Vec<u8> {
+        let bitmap: RoaringBitmap = ids.iter().copied().collect();
+        let mut buf = Vec::new();
+        bitmap.serialize_into(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn maintenance_drops_dead_postings_and_reports_steps() {
+        let (_temp, storage) = test_storage();
+        let guard = storage.db.lock().unwrap_or_else(|e| e.into_inner());
+        let connection = guard.as_ref().expect("test database");
+        connection
+            .execute_batch(
+                "INSERT INTO screenshots (id, image_path, image_hash) VALUES (1, 'a', 'h1');
+                 INSERT INTO ocr_results (id, screenshot_id, text_hash) VALUES (1, 1, 'x'), (2, 1, 'y'), (10, 1, 'z');",
+            )
+            .expect("maintenance fixture");
+        for (token, ids) in [
+            ("t_live", &[1u32, 7][..]),
+            ("t_dead", &[5, 6][..]),
+            ("t_new", &[2, 12][..]),
+        ] {
+            connection
+                .execute(
+                    "INSERT INTO blind_bitmap_index (token_hash, postings_blob) VALUES (?1, ?2)",
+                    params![token, postings(ids)],
+                )
+                .unwrap();
+        }
+        drop(guard);
+
+        let steps = RefCell::new(Vec::new());
+        let report = storage
+            .run_database_maintenance(&|step, _, _| steps.borrow_mut().push(step.to_string()))
+            .unwrap();
+        assert_eq!(steps.into_inner(), MAINTENANCE_STEPS.to_vec());
+        assert_eq!(report.bitmap_tokens_scanned, 3);
+        assert_eq!(report.bitmap_tokens_removed, 1);
+        // ID 12 is above the live snapshot's max (10), so it is kept as a concurrent insert.
+        assert_eq!(report.bitmap_tokens_compacted, 1);
+        assert!(storage.last_database_maintenance_at().unwrap().is_some());
+        assert!(!storage.is_startup_vacuum_in_progress());
+
+        let guard = storage.db.lock().unwrap();
+        let blob: Vec<u8> = guard
+            .as_ref()
+            .unwrap()
+            .query_row(
+                "SELECT postings_blob FROM blind_bitmap_index WHERE token_hash = 't_live'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        let live = RoaringBitmap::deserialize_from(&blob[..]).unwrap();
+        assert_eq!(live.iter().collect::<Vec<_>>(), vec![1]);
+    }
+}
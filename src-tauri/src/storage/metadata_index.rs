@@ -0,0 +1,545 @@
+//! Blind index over screenshot metadata: window titles and browser page data.
+//!
+//! Window titles, and the encrypted page URL and link set of browser captures, are
+//! text OCR never sees. Their bigrams, and the host of the page URL, are hashed with
+//! the blind-index HMAC key into `metadata_bitmap_index`, whose postings are
+//! screenshot IDs. Tokens are namespaced before hashing (`title:` for window title
+//! bigrams, `page:` for URL and link text bigrams, `domain:` for host suffixes) so
+//! each kind is looked up on its own.
+//!
+//! New screenshots are indexed when saved. Rows saved while the HMAC key was
+//! unavailable, or before the current `METADATA_INDEX_VERSION`, are picked up in the
+//! background by the lazy indexer. Postings of purged screenshots are left behind and
+//! dropped at query time.
+
+use std::collections::{HashMap, HashSet};
+
+use roaring::RoaringBitmap;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::search_query::{ClauseMatch, ParsedQuery};
+use super::{SaveScreenshotRequest, StorageState, VisibleLink};
+
+/// Bumped when the token set changes, so existing rows are indexed again.
+pub(super) const METADATA_INDEX_VERSION: i64 = 2;
+
+/// Screenshots indexed per lazy-indexer batch.
+const METADATA_INDEXING_BATCH: i64 = 100;
+
+const TITLE_TOKEN_PREFIX: &str = "title:";
+const PAGE_TOKEN_PREFIX: &str = "page:";
+const DOMAIN_TOKEN_PREFIX: &str = "domain:";
+
+/// Lowercased host of `url` without scheme, credentials, port or path; `None` when
+/// there is none.
+pub(super) fn url_host(url: &str) -> Option<String> {
+    let rest = url.trim();
+    let rest = rest.split_once("://").map_or(rest, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host_port = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = match host_port.strip_prefix('[') {
+        // IPv6 literal: keep it whole, without the brackets
+        Some(v6) => v6.split(']').next().unwrap_or_default(),
+        None => host_port.split(':').next().unwrap_or_default(),
+    };
+    let host = host.trim_end_matches('.').to_lowercase();
+    (!host.is_empty()).then_some(host)
+}
+
+/// `docs.github.com` -> `docs.github.com`, `github.com`. Bare top-level labels are
+/// left out so `domain:com` does not match every site.
+fn domain_suffixes(host: &str) -> Vec<&str> {
+    let mut suffixes = vec![host];
+    let mut rest = host;
+    while let Some((_, parent)) = rest.split_once('.') {
+        if !parent.contains('.') {
+            break;
+        }
+        suffixes.push(parent);
+        rest = parent;
+    }
+    suffixes
+}
+
+/// Lowercased bigrams of `text` under `prefix`. Metadata is matched case-insensitively.
+fn prefixed_bigrams(prefix: &str, text: &str) -> Vec<String> {
+    StorageState::bigram_tokenize(&text.to_lowercase())
+        .into_iter()
+        .map(|bigram| format!("{}{}", prefix, bigram))
+        .collect()
+}
+
+/// Index tokens of a screenshot's window title, page URL and visible links, before
+/// hashing.
+fn metadata_tokens(
+    window_title: Option<&str>,
+    page_url: Option<&str>,
+    visible_links: Option<&[VisibleLink]>,
+) -> HashSet<String> {
+    let mut tokens: HashSet<String> = window_title
+        .map(|title| {
+            prefixed_bigrams(TITLE_TOKEN_PREFIX, title)
+                .into_iter()
+                .collect()
+        })
+        .unwrap_or_default();
+    if let Some(url) = page_url {
+        tokens.extend(prefixed_bigrams(PAGE_TOKEN_PREFIX, url));
+    }
+    for link in visible_links.into_iter().flatten() {
+        tokens.extend(prefixed_bigrams(PAGE_TOKEN_PREFIX, &link.text));
+    }
+    if let Some(host) = page_url.and_then(url_host) {
+        for suffix in domain_suffixes(&host) {
+            tokens.insert(format!("{}{}", DOMAIN_TOKEN_PREFIX, suffix));
+        }
+    }
+    tokens
+}
+
+impl StorageState {
+    /// Index one batch of screenshots not yet at `METADATA_INDEX_VERSION`; returns how
+    /// many were processed. Rows without a window title or browser metadata are only
+    /// marked.
+    pub fn process_metadata_indexing_batch(&self) -> Result<usize, String> {
+        let hmac_key = self.credential_state.get_hmac_key()?;
+
+        let ids: Vec<i64> = {
+            let mut guard = self.get_connection_named("metadata_indexer_backlog")?;
+            let conn = guard.as_mut().unwrap();
+            let marked = conn
+                .execute(
+                    "UPDATE screenshots SET metadata_index_version = ?1
+                     WHERE metadata_index_version < ?1
+                       AND window_title_enc IS NULL AND window_title IS NULL
+                       AND page_url_enc IS NULL AND link_set_id IS NULL
+                       AND visible_links_enc IS NULL",
+                    params![METADATA_INDEX_VERSION],
+                )
+                .map_err(|e| format!("Failed to mark unindexable screenshots: {}", e))?;
+            let mut stmt = conn
+                .prepare(
+                    "SELECT id FROM screenshots
+                     WHERE metadata_index_version < ?1 AND is_deleted = 0
+                     ORDER BY id ASC LIMIT ?2",
+                )
+                .map_err(|e| format!("Failed to prepare metadata index backlog: {}", e))?;
+            let ids: Vec<i64> = stmt
+                .query_map(
+                    params![METADATA_INDEX_VERSION, METADATA_INDEXING_BATCH],
+                    |row| row.get(0),
+                )
+                .map_err(|e| format!("Failed to query metadata index backlog: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            if ids.is_empty() {
+                return Ok(marked);
+            }
+            ids
+        };
+
+        let mut batch_tokens: HashMap<String, RoaringBitmap> = HashMap::new();
+        for record in self.get_screenshots_by_ids(&ids)? {
+            let Ok(posting_id) = u32::try_from(record.id) else {
+                continue;
+            };
+            let tokens = metadata_tokens(
+                record.window_title.as_deref(),
+                record.page_url.as_deref(),
+                record.visible_links.as_deref(),
+            );
+            for token in tokens {
+                batch_tokens
+                    .entry(Self::compute_hmac_hash(&token, &hmac_key))
+                    .or_default()
+                    .insert(posting_id);
+            }
+        }
+
+        let mut guard = self.get_connection_named("metadata_indexer_write")?;
+        let conn = guard.as_mut().unwrap();
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start metadata index transaction: {}", e))?;
+        Self::merge_metadata_postings(&tx, &batch_tokens)?;
+        // Rows that failed to decrypt are marked too, so they are not retried forever
+        let placeholders = ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let mut mark_params: Vec<&dyn rusqlite::ToSql> = vec![&METADATA_INDEX_VERSION];
+        mark_params.extend(ids.iter().map(|id| id as &dyn rusqlite::ToSql));
+        tx.execute(
+            &format!(
+                "UPDATE screenshots SET metadata_index_version = ?1 WHERE id IN ({})",
+                placeholders
+            ),
+            mark_params.as_slice(),
+        )
+        .map_err(|e| format!("Failed to mark indexed screenshots: {}", e))?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit metadata index batch: {}", e))?;
+        Ok(ids.len())
+    }
+
+    /// Hashed index tokens of a screenshot about to be saved, or `None` when the
+    /// blind-index key is unavailable and the lazy indexer has to cover it. Computed
+    /// before the save takes the database lock.
+    pub(super) fn metadata_token_hashes_for_save(
+        &self,
+        request: &SaveScreenshotRequest,
+    ) -> Option<Vec<String>> {
+        let hmac_key = self.credential_state.get_hmac_key().ok()?;
+        let tokens = metadata_tokens(
+            request.window_title.as_deref(),
+            request.page_url.as_deref().filter(|url| !url.is_empty()),
+            request.visible_links.as_deref(),
+        );
+        Some(
+            tokens
+                .iter()
+                .map(|token| Self::compute_hmac_hash(token, &hmac_key))
+                .collect(),
+        )
+    }
+
+    /// Posts a just-inserted screenshot under `token_hashes` and marks it indexed.
+    /// Failures are logged and left to the lazy indexer rather than failing the save.
+    pub(super) fn index_metadata_on_save(
+        conn: &Connection,
+        screenshot_id: i64,
+        token_hashes: Option<Vec<String>>,
+    ) {
+        let (Some(token_hashes), Ok(posting_id)) = (token_hashes, u32::try_from(screenshot_id))
+        else {
+            return;
+        };
+        let single = RoaringBitmap::from_iter([posting_id]);
+        let batch_tokens: HashMap<String, RoaringBitmap> = token_hashes
+            .into_iter()
+            .map(|hash| (hash, single.clone()))
+            .collect();
+        let indexed = Self::merge_metadata_postings(conn, &batch_tokens).and_then(|_| {
+            conn.execute(
+                "UPDATE screenshots SET metadata_index_version = ?1 WHERE id = ?2",
+                params![METADATA_INDEX_VERSION, screenshot_id],
+            )
+            .map_err(|e| format!("Failed to mark indexed screenshot: {}", e))
+        });
+        if let Err(e) = indexed {
+            tracing::warn!(
+                "Failed to index metadata of screenshot {} on save: {}",
+                screenshot_id,
+                e
+            );
+        }
+    }
+
+    fn merge_metadata_postings(
+        conn: &Connection,
+        batch_tokens: &HashMap<String, RoaringBitmap>,
+    ) -> Result<(), String> {
+        let mut get_stmt = conn
+            .prepare_cached("SELECT postings_blob FROM metadata_bitmap_index WHERE token_hash = ?1")
+            .map_err(|e| format!("Failed to prepare metadata bitmap read: {}", e))?;
+        let mut put_stmt = conn
+            .prepare_cached(
+                "INSERT OR REPLACE INTO metadata_bitmap_index (token_hash, postings_blob) VALUES (?1, ?2)",
+            )
+            .map_err(|e| format!("Failed to prepare metadata bitmap write: {}", e))?;
+        for (token_hash, new_bitmap) in batch_tokens {
+            let existing_blob: Option<Vec<u8>> = get_stmt
+                .query_row(params![token_hash], |row| row.get(0))
+                .optional()
+                .map_err(|e| format!("Failed to load metadata bitmap row: {}", e))?;
+            let merged = match existing_blob {
+                Some(blob) => {
+                    RoaringBitmap::deserialize_from(&blob[..])
+                        .map_err(|e| format!("Failed to deserialize bitmap: {}", e))?
+                        | new_bitmap
+                }
+                None => new_bitmap.clone(),
+            };
+            let mut buf = Vec::new();
+            merged
+                .serialize_into(&mut buf)
+                .map_err(|e| format!("Failed to serialize bitmap: {}", e))?;
+            put_stmt
+                .execute(params![token_hash, buf])
+                .map_err(|e| format!("Failed to write metadata bitmap row: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Union of the postings of `tokens` (when `all` is false) or their intersection.
+    /// Unknown tokens contribute nothing to a union and empty an intersection.
+    fn metadata_postings(
+        conn: &Connection,
+        hmac_key: &[u8],
+        tokens: impl IntoIterator<Item = String>,
+        all: bool,
+    ) -> Result<RoaringBitmap, String> {
+        let mut stmt = conn
+            .prepare_cached("SELECT postings_blob FROM metadata_bitmap_index WHERE token_hash = ?1")
+            .map_err(|e| format!("Failed to prepare metadata bitmap read: {}", e))?;
+        let mut result: Option<RoaringBitmap> = None;
+        for token in tokens {
+            let token_hash = Self::compute_hmac_hash(&token, hmac_key);
+            let blob: Option<Vec<u8>> = stmt
+                .query_row(params![&token_hash], |row| row.get(0))
+                .optional()
+                .map_err(|e| format!("Failed to query metadata bitmap: {}", e))?;
+            let bitmap = match blob {
+                Some(blob) => RoaringBitmap::deserialize_from(&blob[..])
+                    .map_err(|e| format!("Failed to deserialize bitmap: {}", e))?,
+                None if all => return Ok(RoaringBitmap::new()),
+                None => continue,
+            };
+            result = Some(match result {
+                Some(acc) if all => acc & bitmap,
+                Some(acc) => acc | bitmap,
+                None => bitmap,
+            });
+        }
+        Ok(result.unwrap_or_default())
+    }
+
+    /// Live screenshots whose page is on any of `domains` or one of their subdomains.
+    /// `domains` may be given as full URLs.
+    pub(super) fn domain_screenshot_ids(
+        conn: &Connection,
+        hmac_key: &[u8],
+        domains: &[String],
+    ) -> Result<HashSet<i64>, String> {
+        let tokens = domains
+            .iter()
+            .filter_map(|domain| url_host(domain))
+            .map(|host| format!("{}{}", DOMAIN_TOKEN_PREFIX, host));
+        let bitmap = Self::metadata_postings(conn, hmac_key, tokens, false)?;
+        let ids: Vec<i64> = bitmap.iter().map(i64::from).collect();
+        Self::live_screenshot_ids(conn, &ids)
+    }
+
+    /// Live screenshots whose window title contains every one of `titles`. Titles too
+    /// short to yield a bigram are ignored; `None` when none is left to filter on.
+    pub(super) fn title_screenshot_ids(
+        conn: &Connection,
+        hmac_key: &[u8],
+        titles: &[String],
+    ) -> Result<Option<HashSet<i64>>, String> {
+        let tokens: HashSet<String> = titles
+            .iter()
+            .flat_map(|title| prefixed_bigrams(TITLE_TOKEN_PREFIX, title))
+            .collect();
+        if tokens.is_empty() {
+            return Ok(None);
+        }
+        let bitmap = Self::metadata_postings(conn, hmac_key, tokens, true)?;
+        let ids: Vec<i64> = bitmap.iter().map(i64::from).collect();
+        Self::live_screenshot_ids(conn, &ids).map(Some)
+    }
+
+    /// Live screenshots whose window title contains any one of `titles`.
+    fn any_title_screenshot_ids(
+        conn: &Connection,
+        hmac_key: &[u8],
+        titles: &[String],
+    ) -> Result<HashSet<i64>, String> {
+        let mut ids = HashSet::new();
+        for title in titles {
+            if let Some(matched) =
+                Self::title_screenshot_ids(conn, hmac_key, std::slice::from_ref(title))?
+            {
+                ids.extend(matched);
+            }
+        }
+        Ok(ids)
+    }
+
+    /// `allowed` narrowed by the `domain:` and `title:` filters of `parsed`, or
+    /// unchanged when it has none.
+    pub(super) fn restrict_by_metadata(
+        conn: &Connection,
+        hmac_key: &[u8],
+        allowed: Option<HashSet<i64>>,
+        parsed: &ParsedQuery,
+    ) -> Result<Option<HashSet<i64>>, String> {
+        let mut allowed = allowed;
+        let mut narrow = |ids: HashSet<i64>| {
+            allowed = Some(match allowed.take() {
+                Some(current) => current.intersection(&ids).copied().collect(),
+                None => ids,
+            });
+        };
+        if !parsed.domains.is_empty() {
+            narrow(Self::domain_screenshot_ids(
+                conn,
+                hmac_key,
+                &parsed.domains,
+            )?);
+        }
+        if let Some(ids) = Self::title_screenshot_ids(conn, hmac_key, &parsed.titles)? {
+            narrow(ids);
+        }
+        Ok(allowed)
+    }
+
+    /// Screenshots dropped by the `-domain:` and `-title:` filters of `parsed`.
+    pub(super) fn excluded_by_metadata(
+        conn: &Connection,
+        hmac_key: &[u8],
+        parsed: &ParsedQuery,
+    ) -> Result<HashSet<i64>, String> {
+        let mut ids = HashSet::new();
+        if !parsed.excluded_domains.is_empty() {
+            ids.extend(Self::domain_screenshot_ids(
+                conn,
+                hmac_key,
+                &parsed.excluded_domains,
+            )?);
+        }
+        ids.extend(Self::any_title_screenshot_ids(
+            conn,
+            hmac_key,
+            &parsed.excluded_titles,
+        )?);
+        Ok(ids)
+    }
+
+    /// `clause_match`, extended with screenshots whose page URL or visible link text
+    /// contains any term of the clause. Such a screenshot is represented by its newest
+    /// text box, since results are OCR rows; screenshots without OCR text cannot be
+    /// returned. With `include_pages` false this is just `clause_match`.
+    pub(super) fn clause_match_with_pages(
+        conn: &Connection,
+        terms: &[(&str, Vec<String>)],
+        hmac_key: &[u8],
+        fuzzy: bool,
+        include_pages: bool,
+    ) -> Result<Option<ClauseMatch>, String> {
+        let matched = Self::clause_match(conn, terms, hmac_key, fuzzy)?;
+        if !include_pages {
+            return Ok(matched);
+        }
+
+        let mut page_screenshots = RoaringBitmap::new();
+        for (term, _) in terms {
+            let tokens = prefixed_bigrams(PAGE_TOKEN_PREFIX, term);
+            if !tokens.is_empty() {
+                page_screenshots |= Self::metadata_postings(conn, hmac_key, tokens, true)?;
+            }
+        }
+        if page_screenshots.is_empty() {
+            return Ok(matched);
+        }
+        let ids: Vec<i64> = page_screenshots.iter().map(i64::from).collect();
+        let page_rows = Self::representative_ocr_ids(conn, &ids)?;
+        if page_rows.is_empty() {
+            return Ok(matched);
+        }
+
+        Ok(Some(match matched {
+            Some(mut clause) => {
+                clause.bitmap |= page_rows;
+                clause
+            }
+            None => ClauseMatch {
+                bitmap: page_rows,
+                counts: HashMap::new(),
+                term_doc_freqs: Vec::new(),
+            },
+        }))
+    }
+
+    /// Newest live text box of each live screenshot in `screenshot_ids`.
+    fn representative_ocr_ids(
+        conn: &Connection,
+        screenshot_ids: &[i64],
+    ) -> Result<RoaringBitmap, String> {
+        let mut bitmap = RoaringBitmap::new();
+        for chunk in screenshot_ids.chunks(500) {
+            let placeholders = chunk.iter().map(|_| "?").collect::<Vec<&str>>().join(",");
+            let sql = format!(
+                "SELECT MAX(r.id) FROM ocr_results r
+                 JOIN screenshots s ON s.id = r.screenshot_id
+                 WHERE r.screenshot_id IN ({}) AND r.is_deleted = 0 AND s.is_deleted = 0
+                 GROUP BY r.screenshot_id",
+                placeholders
+            );
+            let params: Vec<&dyn rusqlite::ToSql> =
+                chunk.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
+            let mut stmt = conn
+                .prepare(&sql)
+                .map_err(|e| format!("Failed to prepare page match resolve: {}", e))?;
+            let rows = stmt
+                .query_map(params.as_slice(), |row| row.get::<_, i64>(0))
+                .map_err(|e| format!("Failed to resolve page matches: {}", e))?;
+            bitmap.extend(rows.filter_map(|r| r.ok()).map(|id| id as u32));
+        }
+        Ok(bitmap)
+    }
+
+    fn live_screenshot_ids(conn: &Connection, ids: &[i64]) -> Result<HashSet<i64>, String> {
+        let mut live = HashSet::new();
+        for chunk in ids.chunks(500) {
+            let placeholders = chunk.iter().map(|_| "?").collect::<Vec<&str>>().join(",");
+            let sql = format!(
+                "SELECT id FROM screenshots WHERE id IN ({}) AND is_deleted = 0",
+                placeholders
+            );
+            let params: Vec<&dyn rusqlite::ToSql> =
+                chunk.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
+            let mut stmt = conn
+                .prepare(&sql)
+                .map_err(|e| format!("Failed to prepare screenshot lookup: {}", e))?;
+            let rows = stmt
+                .query_map(params.as_slice(), |row| row.get::<_, i64>(0))
+                .map_err(|e| format!("Failed to look up screenshots: {}", e))?;
+            live.extend(rows.filter_map(|r| r.ok()));
+        }
+        Ok(live)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_host_strips_scheme_credentials_port_and_path() {
+        assert_eq!(
+            url_host("https://user:pw@Docs.GitHub.com:443/a/b?q=1#x").as_deref(),
+            Some("docs.github.com")
+        );
+        assert_eq!(url_host("github.com").as_deref(), Some("github.com"));
+        assert_eq!(url_host("http://[::1]:8080/").as_deref(), Some("::1"));
+        assert_eq!(url_host("file:///C:/notes.txt"), None);
+        assert_eq!(url_host("  "), None);
+    }
+
+    #[test]
+    fn domain_suffixes_stop_before_the_top_level_label() {
+        assert_eq!(
+            domain_suffixes("a.docs.github.com"),
+            vec!["a.docs.github.com", "docs.github.com", "github.com"]
+        );
+        assert_eq!(domain_suffixes("localhost"), vec!["localhost"]);
+    }
+
+    #[test]
+    fn metadata_tokens_are_namespaced_by_source() {
+        let links = [VisibleLink {
+            text: "Docs".to_string(),
+            url: "https://example.com/docs".to_string(),
+        }];
+        let tokens = metadata_tokens(
+            Some("Q3 Report"),
+            Some("https://www.Example.com/a"),
+            Some(&links),
+        );
+        assert!(tokens.contains("title:q3") && tokens.contains("title:re"));
+        assert!(tokens.contains("page:ex") && tokens.contains("page:do"));
+        assert!(tokens.contains("domain:www.example.com"));
+        assert!(tokens.contains("domain:example.com"));
+        assert!(!tokens.contains("title:do") && !tokens.contains("page:q3"));
+    }
+}
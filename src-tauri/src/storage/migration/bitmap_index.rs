@@ -1,12 +1,18 @@
-//! Bitmap index lazy indexing and maintenance.
+//! Bitmap index lazy indexing, full rebuild and maintenance.
 
 use super::super::StorageState;
-use rusqlite::{params, Connection, OptionalExtension};
+use roaring::RoaringBitmap;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use std::collections::HashMap;
 use std::sync::atomic::Ordering;
 
 impl StorageState {
     /// Number of OCR rows to process per batch for lazy indexing.
     const LAZY_INDEXING_BATCH: i64 = 100;
+    /// Number of OCR rows decrypted per batch by a full index rebuild.
+    const INDEX_REBUILD_BATCH: i64 = 500;
+    /// Cursor key (last OCR id staged) of an index rebuild that has not been swapped in.
+    pub(crate) const BITMAP_REBUILD_CURSOR_KEY: &'static str = "bitmap_rebuild_cursor";
 
     /// Attempt to start the lazy indexer (and check migration status).
     /// Called after user authentication succeeds.
@@ -44,7 +50,11 @@ impl StorageState {
 
             // Process unindexed rows (text_hash = '') even if a full migration (old hashes -> HMAC) is pending.
             // This ensures new snapshots are searchable immediately during the migration process.
-            match self.process_lazy_indexing_batch() {
+            // Page URLs and link text of browser captures are indexed alongside.
+            let batch = self
+                .process_lazy_indexing_batch()
+                .and_then(|ocr_rows| Ok(ocr_rows + self.process_metadata_indexing_batch()?));
+            match batch {
                 Ok(processed) => {
                     if processed == 0 {
                         std::thread::sleep(std::time::Duration::from_secs(5));
@@ -96,8 +106,7 @@ impl StorageState {
         rows: Vec<(i64, Vec<u8>, Vec<u8>)>,
         hmac_key: &[u8],
     ) -> Result<usize, String> {
-        let mut batch_tokens: std::collections::HashMap<String, roaring::RoaringBitmap> =
-            std::collections::HashMap::new();
+        let mut batch_tokens: HashMap<String, RoaringBitmap> = HashMap::new();
         let mut row_hashes: Vec<(i64, String)> = Vec::new();
 
         for (ocr_id, text_enc, text_key_enc) in &rows {
@@ -117,7 +126,7 @@ impl StorageState {
                 let token_hash = Self::compute_hmac_hash(&token, hmac_key);
                 batch_tokens
                     .entry(token_hash)
-                    .or_insert_with(roaring::RoaringBitmap::new)
+                    .or_insert_with(RoaringBitmap::new)
                     .insert(*ocr_id as u32);
             }
         }
@@ -139,42 +148,7 @@ impl StorageState {
             }
 
             // Update blind_bitmap_index
-            {
-                let mut get_stmt = tx
-                    .prepare_cached(
-                        "SELECT postings_blob FROM blind_bitmap_index WHERE token_hash = ?1",
-                    )
-                    .map_err(|e| format!("lazy get prep: {}", e))?;
-                let mut put_stmt = tx
-                    .prepare_cached(
-                        "INSERT OR REPLACE INTO blind_bitmap_index (token_hash, postings_blob) VALUES (?1, ?2)",
-                    )
-                    .map_err(|e| format!("lazy put prep: {}", e))?;
-
-                for (hash, new_bitmap) in &batch_tokens {
-                    let existing_blob: Option<Vec<u8>> = get_stmt
-                        .query_row(params![hash], |row| row.get(0))
-                        .optional()
-                        .map_err(|e| format!("lazy get: {}", e))?;
-
-                    let merged = if let Some(blob) = existing_blob {
-                        let mut existing = roaring::RoaringBitmap::deserialize_from(&blob[..])
-                            .map_err(|e| format!("lazy deser: {}", e))?;
-                        existing |= new_bitmap;
-                        existing
-                    } else {
-                        new_bitmap.clone()
-                    };
-
-                    let mut buf = Vec::new();
-                    merged
-                        .serialize_into(&mut buf)
-                        .map_err(|e| format!("lazy ser: {}", e))?;
-                    put_stmt
-                        .execute(params![hash, buf])
-                        .map_err(|e| format!("lazy put: {}", e))?;
-                }
-            }
+            Self::merge_ocr_postings(&tx, &batch_tokens)?;
 
             tx.commit().map_err(|e| format!("lazy commit: {}", e))?;
         }
@@ -182,6 +156,117 @@ impl StorageState {
         Ok(rows.len())
     }
 
+    /// Merges `postings` into `blind_bitmap_index`, reading and rewriting each token's
+    /// blob once however many rows it gains.
+    pub(crate) fn merge_ocr_postings(
+        conn: &Connection,
+        postings: &HashMap<String, RoaringBitmap>,
+    ) -> Result<(), String> {
+        let mut get_stmt = conn
+            .prepare_cached("SELECT postings_blob FROM blind_bitmap_index WHERE token_hash = ?1")
+            .map_err(|e| format!("Failed to prepare bitmap read: {}", e))?;
+        let mut put_stmt = conn
+            .prepare_cached(
+                "INSERT OR REPLACE INTO blind_bitmap_index (token_hash, postings_blob) VALUES (?1, ?2)",
+            )
+            .map_err(|e| format!("Failed to prepare bitmap write: {}", e))?;
+
+        for (hash, new_bitmap) in postings {
+            let existing_blob: Option<Vec<u8>> = get_stmt
+                .query_row(params![hash], |row| row.get(0))
+                .optional()
+                .map_err(|e| format!("Failed to load bitmap row: {}", e))?;
+
+            let merged = match existing_blob {
+                Some(blob) => {
+                    RoaringBitmap::deserialize_from(&blob[..])
+                        .map_err(|e| format!("Failed to deserialize bitmap: {}", e))?
+                        | new_bitmap
+                }
+                None => new_bitmap.clone(),
+            };
+
+            let mut buf = Vec::new();
+            merged
+                .serialize_into(&mut buf)
+                .map_err(|e| format!("Failed to serialize bitmap: {}", e))?;
+            put_stmt
+                .execute(params![hash, buf])
+                .map_err(|e| format!("Failed to write bitmap row: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// HMAC key for indexing OCR rows as they are saved. `None` while the master key
+    /// is locked or being rotated, in which case rows are left to the lazy indexer.
+    pub(crate) fn ocr_hmac_key_for_save(&self) -> Option<Vec<u8>> {
+        if self.is_key_rotation_in_progress() {
+            return None;
+        }
+        self.credential_state.get_hmac_key().ok()
+    }
+
+    /// Text hash and bigram token hashes of an OCR row, computed from its plaintext
+    /// before the writer lock is taken so the row can be indexed as it is inserted.
+    pub(crate) fn ocr_index_hashes(text: &str, hmac_key: &[u8]) -> (String, Vec<String>) {
+        let text_hash = Self::compute_hmac_hash(text, hmac_key);
+        let token_hashes = Self::bigram_tokenize(text)
+            .iter()
+            .map(|token| Self::compute_hmac_hash(token, hmac_key))
+            .collect();
+        (text_hash, token_hashes)
+    }
+
+    /// Indexes OCR rows just inserted in `tx`, given as `(ocr_id, text_hash,
+    /// token_hashes)`: postings of the whole screenshot are accumulated and merged
+    /// once. Runs in a savepoint; on failure the rows keep an empty text hash and are
+    /// left to the lazy indexer rather than failing the save.
+    pub(crate) fn index_ocr_rows_on_commit(
+        tx: &mut Transaction<'_>,
+        screenshot_id: i64,
+        rows: &[(i64, String, Vec<String>)],
+    ) {
+        if rows.is_empty() {
+            return;
+        }
+        let mut postings: HashMap<String, RoaringBitmap> = HashMap::new();
+        for (ocr_id, _, token_hashes) in rows {
+            for token_hash in token_hashes {
+                postings
+                    .entry(token_hash.clone())
+                    .or_default()
+                    .insert(*ocr_id as u32);
+            }
+        }
+
+        let indexed = tx
+            .savepoint()
+            .map_err(|e| format!("Failed to start index savepoint: {}", e))
+            .and_then(|savepoint| {
+                {
+                    let mut upd_stmt = savepoint
+                        .prepare_cached("UPDATE ocr_results SET text_hash = ?1 WHERE id = ?2")
+                        .map_err(|e| format!("Failed to prepare text hash update: {}", e))?;
+                    for (ocr_id, text_hash, _) in rows {
+                        upd_stmt
+                            .execute(params![text_hash, ocr_id])
+                            .map_err(|e| format!("Failed to store text hash: {}", e))?;
+                    }
+                }
+                Self::merge_ocr_postings(&savepoint, &postings)?;
+                savepoint
+                    .commit()
+                    .map_err(|e| format!("Failed to release index savepoint: {}", e))
+            });
+        if let Err(e) = indexed {
+            tracing::warn!(
+                "Failed to index OCR rows of screenshot {} on commit: {}",
+                screenshot_id,
+                e
+            );
+        }
+    }
+
     /// Internal helper to re-index a batch using a provided connection.
     pub(crate) fn index_batch_internal_on_conn(
         &self,
@@ -189,8 +274,18 @@ impl StorageState {
         rows: Vec<(i64, Vec<u8>, Vec<u8>)>,
         hmac_key: &[u8],
     ) -> Result<(), String> {
-        let mut batch_tokens: std::collections::HashMap<String, roaring::RoaringBitmap> =
-            std::collections::HashMap::new();
+        self.index_batch_into_on_conn(conn, rows, hmac_key, "blind_bitmap_index")
+    }
+
+    /// Same as `index_batch_internal_on_conn`, merging postings into `index_table`.
+    fn index_batch_into_on_conn(
+        &self,
+        conn: &Connection,
+        rows: Vec<(i64, Vec<u8>, Vec<u8>)>,
+        hmac_key: &[u8],
+        index_table: &str,
+    ) -> Result<(), String> {
+        let mut batch_tokens: HashMap<String, RoaringBitmap> = HashMap::new();
         let mut row_hashes: Vec<(i64, String)> = Vec::new();
 
         for (ocr_id, text_enc, text_key_enc) in &rows {
@@ -210,7 +305,7 @@ impl StorageState {
                 let token_hash = Self::compute_hmac_hash(&token, hmac_key);
                 batch_tokens
                     .entry(token_hash)
-                    .or_insert_with(roaring::RoaringBitmap::new)
+                    .or_insert_with(RoaringBitmap::new)
                     .insert(*ocr_id as u32);
             }
         }
@@ -226,14 +321,16 @@ impl StorageState {
             }
 
             let mut get_stmt = tx
-                .prepare_cached(
-                    "SELECT postings_blob FROM blind_bitmap_index WHERE token_hash = ?1",
-                )
+                .prepare_cached(&format!(
+                    "SELECT postings_blob FROM {} WHERE token_hash = ?1",
+                    index_table
+                ))
                 .map_err(|e| e.to_string())?;
             let mut put_stmt = tx
-                .prepare_cached(
-                    "INSERT OR REPLACE INTO blind_bitmap_index (token_hash, postings_blob) VALUES (?1, ?2)",
-                )
+                .prepare_cached(&format!(
+                    "INSERT OR REPLACE INTO {} (token_hash, postings_blob) VALUES (?1, ?2)",
+                    index_table
+                ))
                 .map_err(|e| e.to_string())?;
 
             for (hash, new_bitmap) in &batch_tokens {
@@ -242,7 +339,7 @@ impl StorageState {
                     .optional()
                     .unwrap_or(None);
                 let merged = if let Some(blob) = existing_blob {
-                    if let Ok(mut existing) = roaring::RoaringBitmap::deserialize_from(&blob[..]) {
+                    if let Ok(mut existing) = RoaringBitmap::deserialize_from(&blob[..]) {
                         existing |= new_bitmap;
                         existing
                     } else {
@@ -261,4 +358,173 @@ impl StorageState {
         tx.commit().map_err(|e| e.to_string())?;
         Ok(())
     }
+
+    pub fn is_index_rebuild_in_progress(&self) -> bool {
+        self.index_rebuild_in_progress.load(Ordering::SeqCst)
+    }
+
+    /// Rebuild `blind_bitmap_index` from the decrypted text of every OCR row.
+    ///
+    /// Postings are built in `blind_bitmap_index_staging` while searches keep using the
+    /// current index, then swapped in with one transaction. An interrupted rebuild
+    /// resumes from its cursor. `progress_callback` receives `(phase, processed, total)`.
+    pub fn run_index_rebuild<F>(&self, mut progress_callback: F) -> Result<(), String>
+    where
+        F: FnMut(&str, usize, usize),
+    {
+        if self
+            .index_rebuild_in_progress
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Err("ALREADY_RUNNING".to_string());
+        }
+
+        let result = self.run_index_rebuild_internal(&mut progress_callback);
+
+        self.index_rebuild_in_progress
+            .store(false, Ordering::SeqCst);
+        result
+    }
+
+    fn run_index_rebuild_internal<F>(&self, progress_callback: &mut F) -> Result<(), String>
+    where
+        F: FnMut(&str, usize, usize),
+    {
+        if self.is_key_rotation_in_progress() {
+            return Err("KEY_ROTATION_IN_PROGRESS".to_string());
+        }
+        let hmac_key = self.credential_state.get_hmac_key()?;
+
+        let (mut cursor, mut processed, total) = {
+            let guard = self.get_connection_named("index_rebuild_start")?;
+            let conn = guard.as_ref().unwrap();
+            let cursor: i64 = conn
+                .query_row(
+                    "SELECT value FROM app_metadata WHERE key = ?1",
+                    params![Self::BITMAP_REBUILD_CURSOR_KEY],
+                    |r| r.get::<_, String>(0),
+                )
+                .optional()
+                .map_err(|e| format!("Failed to read index rebuild cursor: {}", e))?
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0);
+            if cursor == 0 {
+                conn.execute("DELETE FROM blind_bitmap_index_staging", [])
+                    .map_err(|e| format!("Failed to clear index staging: {}", e))?;
+            }
+            let (processed, total): (i64, i64) = conn
+                .query_row(
+                    "SELECT COALESCE(SUM(id <= ?1), 0), COUNT(*) FROM ocr_results",
+                    params![cursor],
+                    |r| Ok((r.get(0)?, r.get(1)?)),
+                )
+                .map_err(|e| format!("Failed to count OCR rows: {}", e))?;
+            (cursor, processed as usize, total as usize)
+        };
+        tracing::info!(
+            "[INDEX_REBUILD] Rebuilding blind index from OCR id {} ({} rows)",
+            cursor,
+            total
+        );
+        progress_callback("indexing", processed, total);
+
+        loop {
+            let batch = {
+                let guard = self.get_connection_named("index_rebuild_batch")?;
+                let conn = guard.as_ref().unwrap();
+                let rows = Self::read_ocr_rows_after(conn, cursor, Self::INDEX_REBUILD_BATCH)?;
+                match rows.last() {
+                    Some(&(last_id, _, _)) => {
+                        let count = rows.len();
+                        self.index_batch_into_on_conn(
+                            conn,
+                            rows,
+                            &hmac_key,
+                            "blind_bitmap_index_staging",
+                        )?;
+                        conn.execute(
+                            "INSERT OR REPLACE INTO app_metadata (key, value) VALUES (?1, ?2)",
+                            params![Self::BITMAP_REBUILD_CURSOR_KEY, last_id.to_string()],
+                        )
+                        .map_err(|e| format!("Failed to save index rebuild cursor: {}", e))?;
+                        Some((last_id, count))
+                    }
+                    None => None,
+                }
+            };
+            let Some((last_id, count)) = batch else {
+                break;
+            };
+            cursor = last_id;
+            processed += count;
+            progress_callback("indexing", processed.min(total), total);
+
+            // Let capture and UI queries take the writer lock between batches.
+            std::thread::sleep(std::time::Duration::from_millis(200));
+        }
+
+        progress_callback("swapping", processed.min(total), total);
+        {
+            let mut guard = self.get_connection_named("index_rebuild_swap")?;
+            let conn = guard.as_mut().unwrap();
+            // OCR rows committed after the last batch.
+            let late_rows = Self::read_ocr_rows_after(conn, cursor, i64::MAX)?;
+            if !late_rows.is_empty() {
+                self.index_batch_into_on_conn(
+                    conn,
+                    late_rows,
+                    &hmac_key,
+                    "blind_bitmap_index_staging",
+                )?;
+            }
+
+            let tx = conn
+                .transaction()
+                .map_err(|e| format!("Failed to begin index swap: {}", e))?;
+            tx.execute_batch(
+                "DELETE FROM blind_bitmap_index;
+                 INSERT INTO blind_bitmap_index (token_hash, postings_blob)
+                     SELECT token_hash, postings_blob FROM blind_bitmap_index_staging;
+                 DELETE FROM blind_bitmap_index_staging;",
+            )
+            .map_err(|e| format!("Failed to swap rebuilt index: {}", e))?;
+            tx.execute(
+                "DELETE FROM app_metadata WHERE key = ?1",
+                params![Self::BITMAP_REBUILD_CURSOR_KEY],
+            )
+            .map_err(|e| format!("Failed to clear index rebuild cursor: {}", e))?;
+            tx.commit()
+                .map_err(|e| format!("Failed to commit index swap: {}", e))?;
+        }
+
+        tracing::info!(
+            "[INDEX_REBUILD] Rebuilt blind index from {} OCR rows",
+            processed
+        );
+        Ok(())
+    }
+
+    /// Encrypted OCR rows with an id above `after_id`, oldest first.
+    fn read_ocr_rows_after(
+        conn: &Connection,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<(i64, Vec<u8>, Vec<u8>)>, String> {
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT id, text_enc, text_key_encrypted FROM ocr_results
+                 WHERE id > ?1 AND text_enc IS NOT NULL AND text_key_encrypted IS NOT NULL
+                 ORDER BY id ASC LIMIT ?2",
+            )
+            .map_err(|e| format!("Failed to prepare OCR row read: {}", e))?;
+        let rows = stmt
+            .query_map(params![after_id, limit], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .map_err(|e| format!("Failed to read OCR rows: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(rows)
+    }
 }
@@ -4,7 +4,9 @@ pub mod bitmap_index;
 pub mod data_dir;
 pub mod dedup;
 pub mod hmac;
+pub mod paths;
 pub mod plaintext;
+pub mod restore;
 
 use std::sync::atomic::{AtomicBool, Ordering};
 
@@ -0,0 +1,202 @@
+//! One-shot rewrite of legacy absolute `image_path` values to data-dir-relative form.
+//!
+//! Databases created before image paths were stored relative to the data directory
+//! still hold absolute paths, which point at the old location after
+//! `storage_migrate_data_dir`. Each absolute path is cut at the data directory, or,
+//! for paths from a previous data directory, at its `screenshots/` or `cold/`
+//! component. The row is only rewritten if the file exists at the relative location;
+//! every other row is left alone and reported as unresolved.
+
+use rusqlite::params;
+use std::path::Path;
+
+use super::super::cold_storage::{BundleEntry, COLD_STORAGE_DIR};
+use super::super::{PathNormalizationReport, StorageState, UnresolvedImagePath};
+
+/// Tables holding image paths, each with an integer `id` and an `image_path` column.
+const IMAGE_PATH_TABLES: &[&str] = &["screenshots", "delta_keyframes"];
+
+/// Data-dir subdirectories that hold image files.
+const IMAGE_DIRS: &[&str] = &["screenshots", COLD_STORAGE_DIR];
+
+/// Unresolved rows listed in full; the rest are only counted.
+const MAX_UNRESOLVED_LISTED: usize = 500;
+
+/// Whether a stored path is absolute on Windows or Unix, whatever the current platform.
+fn is_absolute_stored_path(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    path.starts_with('/')
+        || path.starts_with('\\')
+        || (bytes.len() >= 3
+            && bytes[0].is_ascii_alphabetic()
+            && bytes[1] == b':'
+            && matches!(bytes[2], b'/' | b'\\'))
+}
+
+/// Data-dir-relative form of a legacy absolute path, or `None` if no image directory
+/// appears in it.
+fn relative_candidate(stored: &str, data_dir: &Path) -> Option<String> {
+    let normalized = stored.replace('\\', "/");
+    let root = data_dir.to_string_lossy().replace('\\', "/");
+    let root = root.trim_end_matches('/');
+    if !root.is_empty() {
+        let prefix = format!("{}/", root);
+        let head = normalized.get(..prefix.len());
+        let matches_root = if cfg!(windows) {
+            head.is_some_and(|head| head.eq_ignore_ascii_case(&prefix))
+        } else {
+            head == Some(prefix.as_str())
+        };
+        if matches_root {
+            return Some(normalized[prefix.len()..].to_string());
+        }
+    }
+
+    // File names never contain a separator, so the last image directory component
+    // is the one that belonged to the old data directory.
+    IMAGE_DIRS
+        .iter()
+        .filter_map(|dir| normalized.rfind(&format!("/{}/", dir)))
+        .max()
+        .map(|pos| normalized[pos + 1..].to_string())
+}
+
+impl StorageState {
+    /// Whether the file behind a stored image path exists; bundle entries check the bundle.
+    pub(in crate::storage) fn stored_image_exists(&self, image_path: &str) -> bool {
+        let abs = self.resolve_image_path(image_path);
+        match BundleEntry::parse(&abs) {
+            Some(entry) => entry.bundle.is_file(),
+            None => abs.is_file(),
+        }
+    }
+
+    /// Rewrite absolute image paths to data-dir-relative form where the file is found.
+    pub fn normalize_image_paths(&self) -> Result<PathNormalizationReport, String> {
+        let data_dir = self
+            .data_dir
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let mut report = PathNormalizationReport::default();
+
+        for table in IMAGE_PATH_TABLES {
+            let rows: Vec<(i64, String)> = {
+                let conn = self.open_read_connection_named("normalize_image_paths")?;
+                let mut stmt = conn
+                    .prepare(&format!(
+                        "SELECT id, image_path FROM {}
+                         WHERE substr(image_path, 1, 1) IN ('/', '\\')
+                            OR substr(image_path, 2, 1) = ':'
+                         ORDER BY id",
+                        table
+                    ))
+                    .map_err(|e| format!("Failed to prepare path scan: {}", e))?;
+                let rows = stmt
+                    .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                    .map_err(|e| format!("Failed to scan image paths: {}", e))?
+                    .filter_map(|r| r.ok())
+                    .filter(|(_, path): &(i64, String)| is_absolute_stored_path(path))
+                    .collect();
+                rows
+            };
+            report.scanned += rows.len();
+
+            let mut rewrites = Vec::new();
+            for (id, image_path) in rows {
+                let reason = match relative_candidate(&image_path, &data_dir) {
+                    Some(rel) if self.stored_image_exists(&rel) => {
+                        rewrites.push((id, rel));
+                        continue;
+                    }
+                    _ if self.stored_image_exists(&image_path) => "outside_data_dir",
+                    _ => "missing",
+                };
+                report.unresolved_count += 1;
+                if report.unresolved.len() < MAX_UNRESOLVED_LISTED {
+                    report.unresolved.push(UnresolvedImagePath {
+                        table: table.to_string(),
+                        id,
+                        image_path,
+                        reason: reason.to_string(),
+                    });
+                }
+            }
+            if rewrites.is_empty() {
+                continue;
+            }
+
+            let mut guard = self.get_connection_named("normalize_image_paths")?;
+            let conn = guard.as_mut().unwrap();
+            let tx = conn
+                .transaction()
+                .map_err(|e| format!("Failed to start path rewrite: {}", e))?;
+            {
+                let mut stmt = tx
+                    .prepare(&format!(
+                        "UPDATE {} SET image_path = ?1 WHERE id = ?2",
+                        table
+                    ))
+                    .map_err(|e| format!("Failed to prepare path rewrite: {}", e))?;
+                for (id, rel) in &rewrites {
+                    stmt.execute(params![rel, id])
+                        .map_err(|e| format!("Failed to rewrite image path {}: {}", id, e))?;
+                }
+            }
+            tx.commit()
+                .map_err(|e| format!("Failed to commit path rewrite: {}", e))?;
+            report.rewritten += rewrites.len();
+        }
+
+        tracing::info!(
+            "[PATHS] normalized {} of {} absolute image paths, {} unresolved",
+            report.rewritten,
+            report.scanned,
+            report.unresolved_count
+        );
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn absolute_paths_are_recognised_for_both_platforms() {
+        assert!(is_absolute_stored_path(
+            "C:\\Users\\a\\screenshots\\x.png.enc"
+        ));
+        assert!(is_absolute_stored_path("d:/data/screenshots/x.png.enc"));
+        assert!(is_absolute_stored_path("/home/a/screenshots/x.png.enc"));
+        assert!(is_absolute_stored_path(
+            "\\\\server\\share\\screenshots\\x.png.enc"
+        ));
+        assert!(!is_absolute_stored_path("screenshots/x.png.enc"));
+        assert!(!is_absolute_stored_path(
+            "cold/2026-01-02.cpbundle#8-10-x.png.enc"
+        ));
+    }
+
+    #[test]
+    fn relative_candidates_cut_at_the_data_directory() {
+        let data_dir = Path::new("/data/carbon");
+        assert_eq!(
+            relative_candidate("/data/carbon/screenshots/keyframes/k.png.enc", data_dir),
+            Some("screenshots/keyframes/k.png.enc".to_string())
+        );
+        // A previous data directory, even one that itself contains `screenshots`.
+        assert_eq!(
+            relative_candidate("E:\\screenshots\\old\\screenshots\\a.webp.enc", data_dir),
+            Some("screenshots/a.webp.enc".to_string())
+        );
+        assert_eq!(
+            relative_candidate(
+                "D:\\old\\cold\\2026-01-02.cpbundle#8-10-a.png.enc",
+                data_dir
+            ),
+            Some("cold/2026-01-02.cpbundle#8-10-a.png.enc".to_string())
+        );
+        assert_eq!(relative_candidate("/elsewhere/a.png.enc", data_dir), None);
+    }
+}
@@ -164,7 +164,7 @@ impl StorageState {
     }
 
     /// Marker key in app_metadata for backfill completion.
-    const BACKFILL_PROCESS_NAMES_DONE_KEY: &'static str = "backfill_process_names_done";
+    pub(crate) const BACKFILL_PROCESS_NAMES_DONE_KEY: &'static str = "backfill_process_names_done";
 
     /// Background migration: batch-decrypt old records and backfill plaintext process_name.
     /// Waits for user authentication before starting CNG decryption.
@@ -208,6 +208,13 @@ impl StorageState {
         loop {
             batch_num += 1;
 
+            if !storage.plaintext_metadata_allowed() {
+                tracing::info!(
+                    "[BACKFILL] paranoid or compliance mode enabled, skipping plaintext backfill"
+                );
+                return;
+            }
+
             // Re-check auth: if session expired, pause until user re-authenticates
             if !storage.credential_state.is_session_valid() {
                 tracing::info!(
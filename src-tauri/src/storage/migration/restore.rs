@@ -0,0 +1,640 @@
+//! Restore from a backup written by `storage_backup_now` (`storage/backup.rs`).
+//!
+//! The manifest is validated and the database snapshot checked against its hash and
+//! the current database key before anything is touched; a backup made with another
+//! key cannot be read here and is refused. Two modes are supported:
+//!
+//! - `replace` closes storage, moves `screenshots.db`, `screenshots/`, `cold/` and
+//!   `shards/` aside into `.restore-previous/`, copies the backup in, and reopens
+//!   storage. A failed or cancelled restore puts the previous files back.
+//! - `merge` keeps the current database and adds the backup's committed screenshots
+//!   that it does not have (matched by image hash), with their OCR and other rows in
+//!   tables that only reference `screenshots`. Image files are only added, never
+//!   overwritten; screenshots whose file exists locally with different contents are
+//!   skipped. Merged OCR rows need a blind index rebuild before they are searchable.
+//!
+//! Restores share the migration flags, so `storage_migration_cancel` cancels them.
+//! Every copied file is verified against the SHA-256 in the manifest.
+
+use rusqlite::{params, Connection, OpenFlags};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+
+use super::super::backup::{
+    copy_verified, file_sha256, BackupFileEntry, BackupManifest, BACKUP_FOLDER, DATABASE_FILE,
+    MANIFEST_FILE,
+};
+use super::super::cold_storage::{BundleEntry, COLD_STORAGE_DIR};
+use super::super::month_shards::screenshot_child_tables;
+use super::super::{RestoreReport, StorageState};
+use super::MigrationRunGuard;
+
+/// Data-dir entries a `replace` restore swaps out.
+const REPLACED_ENTRIES: &[&str] = &[
+    "screenshots.db",
+    "screenshots.db-wal",
+    "screenshots.db-shm",
+    "screenshots",
+    COLD_STORAGE_DIR,
+    "shards",
+];
+/// Backup directories restored by each mode; shards need their month rows, which only
+/// a `replace` brings along.
+const REPLACE_DIRS: &[&str] = &["screenshots", COLD_STORAGE_DIR, "shards"];
+const MERGE_DIRS: &[&str] = &["screenshots", COLD_STORAGE_DIR];
+const PREVIOUS_DIR: &str = ".restore-previous";
+const RESTORE_SCHEMA: &str = "restore_src";
+const CANCELLED: &str = "Restore cancelled by user";
+
+/// The backup folder for a path pointing at it or at the destination containing it.
+fn locate_backup_root(path: &Path) -> Result<PathBuf, String> {
+    [path.to_path_buf(), path.join(BACKUP_FOLDER)]
+        .into_iter()
+        .find(|root| root.join(MANIFEST_FILE).is_file())
+        .ok_or_else(|| format!("No backup manifest found in {}", path.display()))
+}
+
+/// Whether a manifest path lies in one of `dirs`.
+fn in_dirs(rel: &str, dirs: &[&str]) -> bool {
+    dirs.iter().any(|dir| {
+        rel.strip_prefix(dir)
+            .is_some_and(|rest| rest.starts_with('/'))
+    })
+}
+
+/// Columns of `schema.table` with whether each is the rowid alias.
+fn column_info(
+    conn: &Connection,
+    schema: &str,
+    table: &str,
+) -> Result<Vec<(String, bool)>, String> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA {}.table_info(\"{}\")", schema, table))
+        .map_err(|e| format!("Failed to read columns of {}: {}", table, e))?;
+    let columns: Vec<(String, String, i64)> = stmt
+        .query_map([], |row| Ok((row.get(1)?, row.get(2)?, row.get(5)?)))
+        .map_err(|e| format!("Failed to read columns of {}: {}", table, e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    let single_pk = columns.iter().filter(|(_, _, pk)| *pk > 0).count() == 1;
+    Ok(columns
+        .into_iter()
+        .map(|(name, kind, pk)| {
+            let rowid = single_pk && pk == 1 && kind.eq_ignore_ascii_case("INTEGER");
+            (name, rowid)
+        })
+        .collect())
+}
+
+/// Columns present in both copies of `table`, without the rowid alias unless it is
+/// the `keep` column.
+fn mergeable_columns(
+    conn: &Connection,
+    table: &str,
+    keep: Option<&str>,
+) -> Result<Vec<String>, String> {
+    let main = column_info(conn, "main", table)?;
+    let backup = column_info(conn, RESTORE_SCHEMA, table)?;
+    Ok(main
+        .into_iter()
+        .filter(|(name, rowid)| {
+            (!rowid || Some(name.as_str()) == keep) && backup.iter().any(|(other, _)| other == name)
+        })
+        .map(|(name, _)| name)
+        .collect())
+}
+
+fn table_exists(conn: &Connection, schema: &str, table: &str) -> bool {
+    conn.query_row(
+        &format!(
+            "SELECT EXISTS(SELECT 1 FROM {}.sqlite_master WHERE type = 'table' AND name = ?1)",
+            schema
+        ),
+        params![table],
+        |row| row.get(0),
+    )
+    .unwrap_or(false)
+}
+
+/// Rows added by [`merge_backup_rows`].
+#[derive(Debug, Default, PartialEq)]
+struct MergeCounts {
+    screenshots: usize,
+    skipped: usize,
+    ocr_rows: usize,
+}
+
+/// Copy backup screenshots missing from `main` out of the attached [`RESTORE_SCHEMA`],
+/// with new ids, their keyframes and their rows in screenshot child tables.
+/// `keep(image_path)` decides whether a screenshot's file is usable.
+fn merge_backup_rows(
+    conn: &mut Connection,
+    keep: &dyn Fn(&str) -> bool,
+) -> Result<MergeCounts, String> {
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Failed to start restore merge: {}", e))?;
+    let mut counts = MergeCounts::default();
+
+    let candidates: Vec<(i64, String, Option<i64>)> = {
+        let mut stmt = tx
+            .prepare(&format!(
+                "SELECT r.id, r.image_path, r.delta_keyframe_id FROM {schema}.screenshots r
+                 WHERE r.is_deleted = 0 AND (r.status IS NULL OR r.status = 'committed')
+                   AND NOT EXISTS (
+                       SELECT 1 FROM main.screenshots m WHERE m.image_hash = r.image_hash
+                   )
+                 ORDER BY r.id",
+                schema = RESTORE_SCHEMA
+            ))
+            .map_err(|e| format!("Failed to prepare restore candidates: {}", e))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .map_err(|e| format!("Failed to query restore candidates: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+        rows
+    };
+    let (candidates, skipped): (Vec<_>, Vec<_>) = candidates
+        .into_iter()
+        .partition(|(_, image_path, _)| keep(image_path));
+    counts.skipped = skipped.len();
+
+    tx.execute_batch(
+        "CREATE TEMP TABLE restore_keyframe_map (old_id INTEGER PRIMARY KEY, new_id INTEGER);
+         CREATE TEMP TABLE restore_screenshot_map (old_id INTEGER PRIMARY KEY, new_id INTEGER);",
+    )
+    .map_err(|e| format!("Failed to create restore maps: {}", e))?;
+
+    let keyframe_ids: HashSet<i64> = candidates.iter().filter_map(|(_, _, k)| *k).collect();
+    if !keyframe_ids.is_empty() && table_exists(&tx, RESTORE_SCHEMA, "delta_keyframes") {
+        let columns = mergeable_columns(&tx, "delta_keyframes", None)?;
+        let list = columns
+            .iter()
+            .map(|c| format!("\"{}\"", c))
+            .collect::<Vec<_>>()
+            .join(", ");
+        for old_id in keyframe_ids {
+            let existing: Option<i64> = tx
+                .query_row(
+                    &format!(
+                        "SELECT m.id FROM main.delta_keyframes m
+                         JOIN {}.delta_keyframes r ON r.image_path = m.image_path
+                         WHERE r.id = ?1",
+                        RESTORE_SCHEMA
+                    ),
+                    params![old_id],
+                    |row| row.get(0),
+                )
+                .ok();
+            let new_id = match existing {
+                Some(id) => id,
+                None => {
+                    tx.execute(
+                        &format!(
+                            "INSERT INTO main.delta_keyframes ({list})
+                             SELECT {list} FROM {schema}.delta_keyframes WHERE id = ?1",
+                            list = list,
+                            schema = RESTORE_SCHEMA
+                        ),
+                        params![old_id],
+                    )
+                    .map_err(|e| format!("Failed to merge keyframe {}: {}", old_id, e))?;
+                    tx.last_insert_rowid()
+                }
+            };
+            tx.execute(
+                "INSERT INTO temp.restore_keyframe_map (old_id, new_id) VALUES (?1, ?2)",
+                params![old_id, new_id],
+            )
+            .map_err(|e| format!("Failed to map keyframe {}: {}", old_id, e))?;
+        }
+    }
+
+    let columns = mergeable_columns(&tx, "screenshots", None)?;
+    let list = columns
+        .iter()
+        .map(|c| format!("\"{}\"", c))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let values = columns
+        .iter()
+        .map(|c| match c.as_str() {
+            "delta_keyframe_id" => "(SELECT new_id FROM temp.restore_keyframe_map
+                                     WHERE old_id = r.delta_keyframe_id)"
+                .to_string(),
+            _ => format!("r.\"{}\"", c),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    for (old_id, _, _) in &candidates {
+        tx.execute(
+            &format!(
+                "INSERT INTO main.screenshots ({}) SELECT {} FROM {}.screenshots r WHERE r.id = ?1",
+                list, values, RESTORE_SCHEMA
+            ),
+            params![old_id],
+        )
+        .map_err(|e| format!("Failed to merge screenshot {}: {}", old_id, e))?;
+        tx.execute(
+            "INSERT INTO temp.restore_screenshot_map (old_id, new_id) VALUES (?1, ?2)",
+            params![old_id, tx.last_insert_rowid()],
+        )
+        .map_err(|e| format!("Failed to map screenshot {}: {}", old_id, e))?;
+        counts.screenshots += 1;
+    }
+
+    for (table, fk) in screenshot_child_tables(&tx)? {
+        if !table_exists(&tx, RESTORE_SCHEMA, &table) {
+            continue;
+        }
+        let columns = mergeable_columns(&tx, &table, Some(&fk))?;
+        if !columns.contains(&fk) {
+            continue;
+        }
+        let list = columns
+            .iter()
+            .map(|c| format!("\"{}\"", c))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let values = columns
+            .iter()
+            .map(|c| {
+                if *c == fk {
+                    "m.new_id".to_string()
+                } else {
+                    format!("c.\"{}\"", c)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let inserted = tx
+            .execute(
+                &format!(
+                    "INSERT INTO main.\"{table}\" ({list})
+                     SELECT {values} FROM {schema}.\"{table}\" c
+                     JOIN temp.restore_screenshot_map m ON m.old_id = c.\"{fk}\"",
+                    table = table,
+                    list = list,
+                    values = values,
+                    schema = RESTORE_SCHEMA,
+                    fk = fk
+                ),
+                [],
+            )
+            .map_err(|e| format!("Failed to merge {}: {}", table, e))?;
+        if table == "ocr_results" {
+            counts.ocr_rows += inserted;
+        }
+    }
+
+    tx.execute_batch(
+        "DROP TABLE temp.restore_keyframe_map; DROP TABLE temp.restore_screenshot_map;",
+    )
+    .map_err(|e| format!("Failed to drop restore maps: {}", e))?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit restore merge: {}", e))?;
+    Ok(counts)
+}
+
+impl StorageState {
+    /// Restore the backup at `path`, replacing or merging into the current data.
+    /// `on_progress(phase, done, total)` is called with phase `verify`, `files` or
+    /// `database`.
+    pub fn restore_from_backup(
+        &self,
+        path: &Path,
+        merge: bool,
+        on_progress: &dyn Fn(&str, usize, usize),
+    ) -> Result<RestoreReport, String> {
+        if self.migration_in_progress.swap(true, Ordering::SeqCst) {
+            return Err("A storage migration is already in progress".to_string());
+        }
+        self.migration_cancel_requested
+            .store(false, Ordering::SeqCst);
+        let _migration_guard = MigrationRunGuard::new(
+            &self.migration_in_progress,
+            &self.migration_cancel_requested,
+        );
+        let _derived_publish_guard = self
+            .derived_generation_publish_lock
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+
+        let backup_root = locate_backup_root(path)?;
+        let manifest = BackupManifest::load(&backup_root)?;
+        let data_dir = self
+            .data_dir
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        if backup_root.starts_with(&data_dir) {
+            return Err("Cannot restore from a backup inside the data directory".to_string());
+        }
+
+        // Validate before anything is changed.
+        let dirs = if merge { MERGE_DIRS } else { REPLACE_DIRS };
+        let files: Vec<(&String, &BackupFileEntry)> = manifest
+            .files
+            .iter()
+            .filter(|(rel, _)| in_dirs(rel, dirs))
+            .collect();
+        on_progress("verify", 0, files.len() + 1);
+        let snapshot = backup_root.join(DATABASE_FILE);
+        if file_sha256(&snapshot)? != manifest.database.sha256 {
+            return Err("The backup database does not match its manifest".to_string());
+        }
+        self.check_snapshot_key(&snapshot)?;
+        for (index, (rel, entry)) in files.iter().enumerate() {
+            let size = std::fs::metadata(backup_root.join(rel)).map(|m| m.len());
+            if size.ok() != Some(entry.bytes) {
+                return Err(format!("Backup file {} is missing or incomplete", rel));
+            }
+            if index % 200 == 0 {
+                on_progress("verify", index + 1, files.len() + 1);
+            }
+        }
+        on_progress("verify", files.len() + 1, files.len() + 1);
+
+        let mut report = RestoreReport {
+            mode: if merge { "merge" } else { "replace" }.to_string(),
+            backup_created_at: manifest.created_at.clone(),
+            ..Default::default()
+        };
+        if merge {
+            self.merge_from_backup(&backup_root, &data_dir, &files, &mut report, on_progress)?;
+        } else {
+            self.replace_from_backup(
+                &backup_root,
+                &data_dir,
+                &manifest.database.sha256,
+                &files,
+                &mut report,
+                on_progress,
+            )?;
+        }
+        tracing::info!(
+            "[RESTORE] {} from {}: files={}, skipped={}, conflicts={}, merged={}",
+            report.mode,
+            backup_root.display(),
+            report.files_restored,
+            report.files_skipped,
+            report.file_conflicts,
+            report.screenshots_merged
+        );
+        Ok(report)
+    }
+
+    /// Refuse a snapshot that the current database key cannot open.
+    fn check_snapshot_key(&self, snapshot: &Path) -> Result<(), String> {
+        let key_hex = self.database_key_hex()?;
+        let conn = Connection::open_with_flags(snapshot, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(|e| format!("Failed to open backup database: {}", e))?;
+        conn.execute_batch(&format!("PRAGMA key = \"x'{}'\";", key_hex))
+            .map_err(|e| format!("Failed to set backup database key: {}", e))?;
+        conn.execute_batch("SELECT count(*) FROM sqlite_master;")
+            .map_err(|_| {
+                "The backup was made with a different encryption key and cannot be restored here"
+                    .to_string()
+            })
+    }
+
+    /// Copy one manifest file into the data directory and check it against the manifest.
+    fn restore_backup_file(
+        backup_root: &Path,
+        data_dir: &Path,
+        rel: &str,
+        sha256: &str,
+    ) -> Result<PathBuf, String> {
+        let target = data_dir.join(rel);
+        let copied_hash = copy_verified(&backup_root.join(rel), &target)?;
+        if copied_hash != sha256 {
+            let _ = std::fs::remove_file(&target);
+            return Err(format!("Backup file {} does not match its manifest", rel));
+        }
+        Ok(target)
+    }
+
+    fn replace_from_backup(
+        &self,
+        backup_root: &Path,
+        data_dir: &Path,
+        database_sha256: &str,
+        files: &[(&String, &BackupFileEntry)],
+        report: &mut RestoreReport,
+        on_progress: &dyn Fn(&str, usize, usize),
+    ) -> Result<(), String> {
+        self.shutdown()?;
+        let previous = data_dir.join(PREVIOUS_DIR);
+        let _ = std::fs::remove_dir_all(&previous);
+        let mut moved = Vec::new();
+        let result = (|| -> Result<(), String> {
+            std::fs::create_dir_all(&previous)
+                .map_err(|e| format!("Failed to create {}: {}", previous.display(), e))?;
+            for name in REPLACED_ENTRIES {
+                let current = data_dir.join(name);
+                if current.exists() {
+                    std::fs::rename(&current, previous.join(name)).map_err(|e| {
+                        format!("Failed to move {} aside: {}", current.display(), e)
+                    })?;
+                    moved.push(*name);
+                }
+            }
+
+            on_progress("database", 0, 1);
+            Self::restore_backup_file(backup_root, data_dir, DATABASE_FILE, database_sha256)?;
+            on_progress("database", 1, 1);
+            for (index, (rel, entry)) in files.iter().enumerate() {
+                if self.is_migration_cancel_requested() {
+                    return Err(CANCELLED.to_string());
+                }
+                Self::restore_backup_file(backup_root, data_dir, rel, &entry.sha256)?;
+                report.files_restored += 1;
+                on_progress("files", index + 1, files.len());
+            }
+            Ok(())
+        })();
+
+        if let Err(e) = result {
+            for name in REPLACED_ENTRIES {
+                let restored = data_dir.join(name);
+                let _ = std::fs::remove_dir_all(&restored);
+                let _ = std::fs::remove_file(&restored);
+            }
+            for name in &moved {
+                if let Err(move_err) = std::fs::rename(previous.join(name), data_dir.join(name)) {
+                    tracing::error!("[RESTORE] failed to put {} back: {}", name, move_err);
+                }
+            }
+            let _ = std::fs::remove_dir_all(&previous);
+            if let Err(init_err) = self.initialize() {
+                return Err(format!("{}; failed to reopen storage: {}", e, init_err));
+            }
+            return Err(e);
+        }
+
+        let _ = std::fs::remove_dir_all(&previous);
+        self.initialize()
+            .map_err(|e| format!("Failed to reopen storage after restore: {}", e))
+    }
+
+    fn merge_from_backup(
+        &self,
+        backup_root: &Path,
+        data_dir: &Path,
+        files: &[(&String, &BackupFileEntry)],
+        report: &mut RestoreReport,
+        on_progress: &dyn Fn(&str, usize, usize),
+    ) -> Result<(), String> {
+        let mut copied = Vec::new();
+        let mut conflicts = HashSet::new();
+        let result = (|| -> Result<MergeCounts, String> {
+            for (index, (rel, entry)) in files.iter().enumerate() {
+                if self.is_migration_cancel_requested() {
+                    return Err(CANCELLED.to_string());
+                }
+                match std::fs::metadata(data_dir.join(rel)) {
+                    Ok(local) if local.len() == entry.bytes => report.files_skipped += 1,
+                    Ok(_) => {
+                        conflicts.insert(rel.to_string());
+                    }
+                    Err(_) => {
+                        copied.push(Self::restore_backup_file(
+                            backup_root,
+                            data_dir,
+                            rel,
+                            &entry.sha256,
+                        )?);
+                        report.files_restored += 1;
+                    }
+                }
+                on_progress("files", index + 1, files.len());
+            }
+            if self.is_migration_cancel_requested() {
+                return Err(CANCELLED.to_string());
+            }
+
+            on_progress("database", 0, 1);
+            let key_hex = self.database_key_hex()?;
+            let mut guard = self.get_connection_named("merge_from_backup")?;
+            let conn = guard.as_mut().unwrap();
+            conn.execute(
+                &format!("ATTACH DATABASE ?1 AS {} KEY ?2", RESTORE_SCHEMA),
+                params![
+                    backup_root.join(DATABASE_FILE).to_string_lossy(),
+                    format!("x'{}'", key_hex)
+                ],
+            )
+            .map_err(|e| format!("Failed to attach backup database: {}", e))?;
+            let keep = |image_path: &str| {
+                let abs = self.resolve_image_path(image_path);
+                let file = BundleEntry::parse(&abs).map_or(abs, |entry| entry.bundle);
+                !conflicts.contains(&self.to_relative_image_path(&file)) && file.is_file()
+            };
+            let merged = merge_backup_rows(conn, &keep);
+            if let Err(e) = conn.execute_batch(&format!("DETACH DATABASE {};", RESTORE_SCHEMA)) {
+                tracing::warn!("[RESTORE] failed to detach backup database: {}", e);
+            }
+            on_progress("database", 1, 1);
+            merged
+        })();
+
+        let counts = match result {
+            Ok(counts) => counts,
+            Err(e) => {
+                for file in &copied {
+                    let _ = std::fs::remove_file(file);
+                }
+                return Err(e);
+            }
+        };
+        report.file_conflicts = conflicts.len();
+        report.screenshots_merged = counts.screenshots;
+        report.screenshots_skipped = counts.skipped;
+        report.index_rebuild_required = counts.ocr_rows > 0;
+
+        // Reopen so counters, caches and pooled readers see the merged rows.
+        self.shutdown()?;
+        self.initialize()
+            .map_err(|e| format!("Failed to reopen storage after restore: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credential_manager::CredentialManagerState;
+    use std::sync::Arc;
+
+    #[test]
+    fn manifest_paths_are_matched_by_directory() {
+        assert!(in_dirs("screenshots/a.png.enc", MERGE_DIRS));
+        assert!(in_dirs("cold/2026-01-02.cpbundle", MERGE_DIRS));
+        assert!(!in_dirs("shards/screenshots-2026-01.db", MERGE_DIRS));
+        assert!(in_dirs("shards/screenshots-2026-01.db", REPLACE_DIRS));
+        assert!(!in_dirs("credential_public_key.bin", REPLACE_DIRS));
+        assert!(!in_dirs("screenshots.db", REPLACE_DIRS));
+    }
+
+    #[test]
+    fn merge_adds_missing_screenshots_with_new_ids() {
+        let temp = tempfile::tempdir().expect("temp storage directory");
+        let credential_state = Arc::new(CredentialManagerState::new(temp.path().to_path_buf()));
+        let storage = StorageState::new(temp.path().to_path_buf(), credential_state);
+
+        let snapshot_path = temp.path().join("snapshot.db");
+        let snapshot = Connection::open(&snapshot_path).expect("snapshot database");
+        storage.init_tables(&snapshot).expect("snapshot schema");
+        snapshot
+            .execute_batch(
+                "INSERT INTO screenshots (id, image_path, image_hash) VALUES
+                    (1, 'screenshots/shared.png.enc', 'shared'),
+                    (2, 'screenshots/only-backup.png.enc', 'backup'),
+                    (3, 'screenshots/conflict.png.enc', 'conflict');
+                 INSERT INTO ocr_results (screenshot_id, text_hash) VALUES (2, 't2'), (3, 't3');",
+            )
+            .expect("snapshot rows");
+        drop(snapshot);
+
+        let mut conn = Connection::open_in_memory().expect("in-memory database");
+        storage.init_tables(&conn).expect("initialize schema");
+        conn.execute_batch(
+            "INSERT INTO screenshots (id, image_path, image_hash) VALUES
+                (1, 'screenshots/shared.png.enc', 'shared'),
+                (2, 'screenshots/local.png.enc', 'local');",
+        )
+        .expect("local rows");
+        conn.execute(
+            &format!("ATTACH DATABASE ?1 AS {}", RESTORE_SCHEMA),
+            params![snapshot_path.to_string_lossy()],
+        )
+        .expect("attach snapshot");
+
+        let counts =
+            merge_backup_rows(&mut conn, &|path| !path.contains("conflict")).expect("merge");
+        assert_eq!(
+            counts,
+            MergeCounts {
+                screenshots: 1,
+                skipped: 1,
+                ocr_rows: 1
+            }
+        );
+        let (id, ocr_rows): (i64, i64) = conn
+            .query_row(
+                "SELECT s.id, (SELECT COUNT(*) FROM ocr_results o WHERE o.screenshot_id = s.id)
+                 FROM main.screenshots s WHERE s.image_hash = 'backup'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .expect("merged row");
+        assert_eq!((id, ocr_rows), (3, 1));
+        let total: i64 = conn
+            .query_row("SELECT COUNT(*) FROM main.screenshots", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(total, 3);
+    }
+}
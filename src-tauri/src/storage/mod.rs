@@ -5,24 +5,84 @@
 //! 2. Screenshot metadata and OCR results
 //! 3. OCR data storage and search
 
+mod annotations;
+mod archive;
+mod audio_transcripts;
+mod audit;
+mod backup;
+mod batch;
+mod capture_day;
+mod clipboard;
+mod cold_storage;
+mod compliance;
+mod context;
+mod daily_note;
+mod data_location;
+mod decrypt_cache;
+mod delta_frame;
 mod derived_index;
+mod digest;
 mod encryption;
+mod gc;
+mod image_codec;
 mod image_io;
+mod integrity;
+mod key_rotation;
 mod link_scoring;
+mod maintenance;
+mod metadata_index;
 pub mod migration;
+mod month_shards;
+mod near_duplicate;
+mod ocr_batch;
+mod ocr_correction;
+mod ocr_reprocess;
+mod ocr_text;
+mod paranoid;
+mod pdf_export;
+mod pending_recovery;
+mod pins;
 mod policy;
+mod preview;
 mod process;
+mod protected_ranges;
+mod relevance;
+mod remote_sync;
+mod row_postings;
+mod saved_searches;
+mod scheduled_jobs;
 mod schema;
 mod screenshot;
 mod search;
+mod search_count;
+mod search_export;
+mod search_history;
+mod search_query;
+mod search_regex;
+mod sessions;
 pub mod smart_cluster;
+mod tags;
 pub mod task;
+mod title_search;
+mod trash;
 mod types;
+mod usage;
 
+pub(crate) use audit::AUDIT_ACTOR_MCP;
+pub use backup::{backup_dir, set_backup_dir};
+pub(crate) use compliance::compliance_session_timeout;
+pub use daily_note::{daily_note_vault_dir, set_daily_note_vault_dir};
+pub use data_location::{estimate_monthly_usage, list_data_drives, validate_data_root};
+pub(crate) use delta_frame::DELTA_FRAMES_REGISTRY_KEY;
 #[allow(unused_imports)]
 pub use derived_index::*;
+pub(crate) use image_codec::{
+    StoredImageCodec, IMAGE_CODEC_REGISTRY_KEY, IMAGE_QUALITY_REGISTRY_KEY,
+};
 #[allow(unused_imports)]
-pub use image_io::{read_encrypted_image_as_base64, read_image_as_base64};
+pub use image_io::{read_encrypted_image_as_base64, read_image_as_base64, ImageIntegrityError};
+pub(crate) use near_duplicate::NEAR_DUPLICATE_THRESHOLD_REGISTRY_KEY;
+pub(crate) use remote_sync::{SYNC_KEY_POLICY_KEY, SYNC_SECRET_POLICY_KEY};
 pub use types::*;
 
 use crate::credential_manager::{
@@ -32,8 +92,8 @@ use crate::credential_manager::{
 use rusqlite::{Connection, OpenFlags};
 use std::ops::{Deref, DerefMut};
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 
 /// Error returned by background-only reads of encrypted screenshot content.
 /// `AuthRequired` is intentionally distinct so callers can defer work without
@@ -56,8 +116,13 @@ impl From<String> for BackgroundReadError {
 /// It provides methods for initializing storage, saving/loading screenshots and OCR results,
 /// and migrating the data directory.
 pub struct StorageState {
-    /// Database connection
+    /// Database connection (the single writer; WAL lets readers run alongside it)
     db: Mutex<Option<Connection>>,
+    /// Idle read-only connections reused by `open_read_connection_named`
+    read_pool: Mutex<Vec<Connection>>,
+    /// Bumped on `initialize()`/`shutdown()` so connections to a previous data
+    /// directory are closed instead of returned to the pool
+    read_pool_generation: AtomicU64,
     /// Data directory (contains database, screenshots, logs, etc.)
     pub data_dir: Mutex<PathBuf>,
     pub screenshot_dir: Mutex<PathBuf>,
@@ -68,6 +133,8 @@ pub struct StorageState {
     migration_in_progress: AtomicBool,
     hmac_migration_cancel_requested: AtomicBool,
     hmac_migration_in_progress: AtomicBool,
+    key_rotation_in_progress: AtomicBool,
+    index_rebuild_in_progress: AtomicBool,
     lazy_indexer_shutdown: AtomicBool,
     /// Diagnostic: tracks which operation currently holds the DB mutex
     lock_holder: Mutex<&'static str>,
@@ -81,9 +148,37 @@ pub struct StorageState {
     pub(crate) thumbnail_warmup_done: AtomicBool,
     /// Whether startup VACUUM is currently running
     startup_vacuum_in_progress: AtomicBool,
+    /// Whether a backup to the backup folder is running
+    backup_in_progress: AtomicBool,
+    /// Whether a sync to the remote target is running
+    sync_in_progress: AtomicBool,
     /// Serializes derived-index sidecar publication without participating in
     /// the data-directory/database lock ordering.
     derived_generation_publish_lock: Mutex<()>,
+    /// OCR text post-processing applied before OCR results are stored and indexed
+    ocr_text_pipeline: RwLock<Arc<ocr_text::OcrTextPipeline>>,
+    /// Data-at-rest compliance mode (no plaintext metadata, audited reads)
+    compliance_mode: AtomicBool,
+    /// Paranoid mode (no plaintext metadata, nothing else restricted)
+    paranoid_mode: AtomicBool,
+    /// Codec (`StoredImageCodec`) and quality applied to new screenshots before encryption
+    image_codec: AtomicU8,
+    image_quality: AtomicU8,
+    /// Max dHash distance for `save_screenshot_temp` to skip a frame as a near-duplicate (0 = off)
+    near_duplicate_threshold: AtomicU32,
+    /// Store mostly unchanged frames as patches against a keyframe (`storage/delta_frame.rs`)
+    delta_frames_enabled: AtomicBool,
+    delta_chain: Mutex<Option<delta_frame::DeltaChain>>,
+    /// Guest/demo mode: the database is opened read-only and mutating commands are rejected
+    read_only: AtomicBool,
+    /// Decrypted notifying saved searches; `None` until loaded or after a change
+    saved_search_alerts: Mutex<Option<Vec<saved_searches::SavedSearchAlert>>>,
+    /// Decrypted search history, newest first; `None` until loaded
+    search_history_cache: Mutex<Option<Vec<SearchHistoryEntry>>>,
+    /// Recently decrypted images and row keys, flushed when the session locks
+    decrypt_cache: Mutex<decrypt_cache::DecryptCache>,
+    /// Outcome of the stale pending capture recovery run by `initialize()`
+    pending_recovery_summary: Mutex<Option<PendingRecoverySummary>>,
 }
 
 struct NamedConnectionGuard<'a> {
@@ -113,12 +208,54 @@ impl Drop for NamedConnectionGuard<'_> {
     }
 }
 
+/// Read-only connection borrowed from `StorageState::read_pool`; returned on drop.
+pub(crate) struct PooledReadConnection<'a> {
+    conn: Option<Connection>,
+    generation: u64,
+    storage: &'a StorageState,
+}
+
+impl Deref for PooledReadConnection<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Self::Target {
+        self.conn.as_ref().unwrap()
+    }
+}
+
+impl Drop for PooledReadConnection<'_> {
+    fn drop(&mut self) {
+        let Some(conn) = self.conn.take() else {
+            return;
+        };
+        if self.generation != self.storage.read_pool_generation.load(Ordering::SeqCst) {
+            return;
+        }
+        let mut pool = self
+            .storage
+            .read_pool
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if pool.len() < READ_POOL_MAX_IDLE {
+            pool.push(conn);
+        }
+    }
+}
+
+/// Idle read connections kept open; extra concurrent readers open and close their own.
+const READ_POOL_MAX_IDLE: usize = 4;
+/// How long a connection waits on a locked database (WAL checkpoint, writer
+/// recovery) before returning `SQLITE_BUSY`.
+pub(super) const DB_BUSY_TIMEOUT_MS: u32 = 5_000;
+
 impl StorageState {
     pub fn new(data_dir: PathBuf, credential_state: Arc<CredentialManagerState>) -> Self {
         let screenshot_dir = data_dir.join("screenshots");
 
         Self {
             db: Mutex::new(None),
+            read_pool: Mutex::new(Vec::new()),
+            read_pool_generation: AtomicU64::new(0),
             data_dir: Mutex::new(data_dir),
             screenshot_dir: Mutex::new(screenshot_dir),
             credential_state,
@@ -127,6 +264,8 @@ impl StorageState {
             migration_in_progress: AtomicBool::new(false),
             hmac_migration_cancel_requested: AtomicBool::new(false),
             hmac_migration_in_progress: AtomicBool::new(false),
+            key_rotation_in_progress: AtomicBool::new(false),
+            index_rebuild_in_progress: AtomicBool::new(false),
             lazy_indexer_shutdown: AtomicBool::new(false),
             lock_holder: Mutex::new(""),
             ocr_row_count: AtomicU64::new(0),
@@ -134,7 +273,22 @@ impl StorageState {
             bitmap_index_migrated: AtomicBool::new(false),
             thumbnail_warmup_done: AtomicBool::new(false),
             startup_vacuum_in_progress: AtomicBool::new(false),
+            backup_in_progress: AtomicBool::new(false),
+            sync_in_progress: AtomicBool::new(false),
             derived_generation_publish_lock: Mutex::new(()),
+            ocr_text_pipeline: RwLock::new(Arc::new(ocr_text::OcrTextPipeline::default())),
+            compliance_mode: AtomicBool::new(false),
+            paranoid_mode: AtomicBool::new(false),
+            image_codec: AtomicU8::new(0),
+            image_quality: AtomicU8::new(image_codec::DEFAULT_IMAGE_QUALITY),
+            near_duplicate_threshold: AtomicU32::new(0),
+            delta_frames_enabled: AtomicBool::new(false),
+            delta_chain: Mutex::new(None),
+            read_only: AtomicBool::new(false),
+            saved_search_alerts: Mutex::new(None),
+            search_history_cache: Mutex::new(None),
+            decrypt_cache: Mutex::new(decrypt_cache::DecryptCache::default()),
+            pending_recovery_summary: Mutex::new(None),
         }
     }
 
@@ -200,6 +354,16 @@ impl StorageState {
         self.startup_vacuum_in_progress.load(Ordering::SeqCst)
     }
 
+    /// Whether storage runs in read-only guest mode (`--guest`).
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::SeqCst)
+    }
+
+    /// Must be set before `initialize()`, which decides how the database is opened.
+    pub fn set_read_only(&self, read_only: bool) {
+        self.read_only.store(read_only, Ordering::SeqCst);
+    }
+
     /// Acquire DB connection with caller identification for diagnostic logging.
     fn get_connection_named(
         &self,
@@ -230,14 +394,34 @@ impl StorageState {
         })
     }
 
-    /// Open an independent SQLCipher read-only connection for read-heavy paths.
+    /// Borrow a SQLCipher read-only connection for read-heavy paths.
     ///
-    /// The database key is derived from the public key, matching initialize().
-    /// This does not require the private key or an unlocked credential session.
+    /// Connections come from a small pool and never touch the writer mutex; with the
+    /// database in WAL mode they read the last committed snapshot while a write is
+    /// in progress. The database key is derived from the public key, matching
+    /// initialize(). This does not require the private key or an unlocked credential session.
     pub(crate) fn open_read_connection_named(
         &self,
         caller: &'static str,
-    ) -> Result<Connection, String> {
+    ) -> Result<PooledReadConnection<'_>, String> {
+        let generation = self.read_pool_generation.load(Ordering::SeqCst);
+        let pooled = self
+            .read_pool
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .pop();
+        let conn = match pooled {
+            Some(conn) => conn,
+            None => self.open_new_read_connection(caller)?,
+        };
+        Ok(PooledReadConnection {
+            conn: Some(conn),
+            generation,
+            storage: self,
+        })
+    }
+
+    fn open_new_read_connection(&self, caller: &'static str) -> Result<Connection, String> {
         let started = std::time::Instant::now();
         let data_dir = self
             .data_dir
@@ -259,6 +443,8 @@ impl StorageState {
             .map_err(|e| format!("Failed to set read database key: {}", e))?;
         conn.execute_batch("SELECT count(*) FROM sqlite_master;")
             .map_err(|e| format!("Read database key verification failed: {}", e))?;
+        conn.busy_timeout(std::time::Duration::from_millis(DB_BUSY_TIMEOUT_MS as u64))
+            .map_err(|e| format!("Failed to set read database busy timeout: {}", e))?;
 
         let elapsed = started.elapsed();
         if elapsed.as_millis() >= 250 {
@@ -271,8 +457,73 @@ impl StorageState {
         Ok(conn)
     }
 
+    /// Close idle pooled read connections and make checked-out ones close on return.
+    pub(super) fn reset_read_pool(&self) {
+        self.read_pool_generation.fetch_add(1, Ordering::SeqCst);
+        self.read_pool
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
     /// Returns whether the current credential session is unlocked/valid.
     pub fn is_session_valid(&self) -> bool {
         self.credential_state.is_session_valid()
     }
+
+    /// Returns whether the main DB connection can be acquired within `timeout`.
+    ///
+    /// Used by the monitor heartbeat to tell a hung backend (e.g. a stuck write
+    /// holding the connection) apart from a merely slow pipe.
+    pub(crate) fn probe_db_responsive(&self, timeout: std::time::Duration) -> bool {
+        let deadline = std::time::Instant::now() + timeout;
+        loop {
+            match self.db.try_lock() {
+                Ok(_) | Err(std::sync::TryLockError::Poisoned(_)) => return true,
+                Err(std::sync::TryLockError::WouldBlock) => {
+                    if std::time::Instant::now() >= deadline {
+                        return false;
+                    }
+                    std::thread::sleep(std::time::Duration::from_millis(20));
+                }
+            }
+        }
+    }
+}
+
+/// Storage rooted in a fresh temp directory with an initialized in-memory database.
+/// Keep the returned `TempDir` alive for the duration of the test.
+#[cfg(test)]
+pub(super) fn test_storage() -> (tempfile::TempDir, StorageState) {
+    let temp = tempfile::tempdir().expect("temp storage directory");
+    let credential_state = Arc::new(CredentialManagerState::new(temp.path().to_path_buf()));
+    let storage = StorageState::new(temp.path().to_path_buf(), credential_state);
+    let connection = Connection::open_in_memory().expect("in-memory database");
+    storage.init_tables(&connection).expect("initialize schema");
+    *storage.db.lock().unwrap_or_else(|e| e.into_inner()) = Some(connection);
+    (temp, storage)
+}
+
+/// Like [`test_storage`], but the database is a keyed file so pooled read connections
+/// see the same rows, and the session is unlocked with a fixed master key.
+#[cfg(test)]
+pub(super) fn file_test_storage() -> (tempfile::TempDir, StorageState) {
+    let temp = tempfile::tempdir().expect("temp storage directory");
+    // The policy file lives next to the data directory, so keep both inside `temp`.
+    let data_dir = temp.path().join("data");
+    std::fs::create_dir_all(&data_dir).expect("data directory");
+    let public_key = [7u8; 32];
+    std::fs::write(data_dir.join("credential_public_key.bin"), public_key).expect("public key");
+
+    let credential_state = Arc::new(CredentialManagerState::new(data_dir.clone()));
+    credential_state.unlock_for_test(&[9u8; 32]);
+    let storage = StorageState::new(data_dir.clone(), credential_state);
+    let connection = Connection::open(data_dir.join("screenshots.db")).expect("database file");
+    let key_hex = hex::encode(derive_db_key_from_public_key(&public_key));
+    connection
+        .execute_batch(&format!("PRAGMA key = \"x'{}'\";", key_hex))
+        .expect("database key");
+    storage.init_tables(&connection).expect("initialize schema");
+    *storage.db.lock().unwrap_or_else(|e| e.into_inner()) = Some(connection);
+    (temp, storage)
 }
@@ -0,0 +1,593 @@
+//! Per-month database shards for closed months.
+//!
+//! Detaching a month moves its screenshot rows, and every row in a table whose only
+//! foreign key is `screenshots(id)` (OCR results, OCR status, tags, annotations,
+//! ...), out of `screenshots.db` into `shards/screenshots-YYYY-MM.db`, a SQLCipher
+//! file keyed like the main database. The live database, and with it every insert
+//! and search, then only carries the months still in use. Image files stay where
+//! they are, and the `month_shards` table routes each detached month to its shard
+//! so it can be archived, moved or attached again on its own. Timeline pages and
+//! search counts name the detached months in their range so the UI can say that
+//! history is offline rather than show it as empty.
+//!
+//! Pinned screenshots and screenshots inside a protected range stay in the live
+//! database. Cluster and task assignments of detached rows are dropped; they are
+//! derived and are recomputed after the month is attached. Attaching a month copies
+//! its rows back and clears their `text_hash` and metadata index version so the lazy
+//! indexer rebuilds their search postings. Keys cannot be rotated while a month is
+//! detached, because the shard's wrapped row keys would not be re-wrapped.
+
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+
+use chrono::{DateTime, Datelike, Local, NaiveDate};
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::credential_manager::{
+    derive_db_key_from_public_key, get_cached_public_key, load_public_key_from_file,
+};
+
+use super::protected_ranges::PROTECTED_RANGE_MATCH;
+use super::{MonthShard, StorageState};
+
+const SHARD_DIR: &str = "shards";
+const SHARD_SCHEMA: &str = "month_shard";
+
+/// Validate a `YYYY-MM` month and return it normalised.
+fn parse_month(month: &str) -> Result<String, String> {
+    let first_day = NaiveDate::parse_from_str(&format!("{}-01", month.trim()), "%Y-%m-%d")
+        .map_err(|_| format!("Invalid month {:?}; expected YYYY-MM", month))?;
+    Ok(first_day.format("%Y-%m").to_string())
+}
+
+/// Local `YYYY-MM` of a Unix timestamp in seconds.
+fn local_month(ts: f64) -> Option<String> {
+    DateTime::from_timestamp(ts as i64, 0)
+        .map(|dt| dt.with_timezone(&Local).format("%Y-%m").to_string())
+}
+
+/// Months from `months` between `first` and `last` inclusive; a missing bound is open.
+fn months_overlapping(
+    months: Vec<String>,
+    first: Option<String>,
+    last: Option<String>,
+) -> Vec<String> {
+    months
+        .into_iter()
+        .filter(|month| {
+            first.as_ref().is_none_or(|first| month >= first)
+                && last.as_ref().is_none_or(|last| month <= last)
+        })
+        .collect()
+}
+
+/// Tables whose foreign keys all point at `screenshots(id)`, with the referencing column.
+pub(super) fn screenshot_child_tables(conn: &Connection) -> Result<Vec<(String, String)>, String> {
+    let tables: Vec<String> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT name FROM main.sqlite_master
+                 WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+            )
+            .map_err(|e| format!("Failed to list tables: {}", e))?;
+        let names = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(|e| format!("Failed to list tables: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+        names
+    };
+
+    let mut children = Vec::new();
+    for table in tables {
+        let mut stmt = conn
+            .prepare("SELECT \"table\", \"from\" FROM pragma_foreign_key_list(?1)")
+            .map_err(|e| format!("Failed to read foreign keys of {}: {}", table, e))?;
+        let keys: Vec<(String, String)> = stmt
+            .query_map(params![table], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| format!("Failed to read foreign keys of {}: {}", table, e))?
+            .filter_map(|r| r.ok())
+            .collect();
+        if let [(parent, column)] = keys.as_slice() {
+            if parent == "screenshots" {
+                children.push((table, column.clone()));
+            }
+        }
+    }
+    Ok(children)
+}
+
+/// Columns of `schema.table`, in declaration order.
+fn table_columns(conn: &Connection, schema: &str, table: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA {}.table_info(\"{}\")", schema, table))
+        .map_err(|e| format!("Failed to read columns of {}: {}", table, e))?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(1))
+        .map_err(|e| format!("Failed to read columns of {}: {}", table, e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(columns)
+}
+
+/// Quoted column list shared by both copies of a table.
+fn shared_columns(conn: &Connection, table: &str) -> Result<String, String> {
+    let main = table_columns(conn, "main", table)?;
+    let shard = table_columns(conn, SHARD_SCHEMA, table)?;
+    Ok(shard
+        .iter()
+        .filter(|column| main.contains(column))
+        .map(|column| format!("\"{}\"", column))
+        .collect::<Vec<_>>()
+        .join(", "))
+}
+
+impl StorageState {
+    fn month_shard_path(&self, month: &str) -> PathBuf {
+        self.data_dir
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .join(SHARD_DIR)
+            .join(format!("screenshots-{}.db", month))
+    }
+
+    /// Attach a shard file to the writer connection under [`SHARD_SCHEMA`].
+    fn attach_shard(&self, conn: &Connection, month: &str) -> Result<(), String> {
+        let public_key = get_cached_public_key(&self.credential_state)
+            .or_else(|| load_public_key_from_file(&self.credential_state).ok())
+            .ok_or_else(|| "Public key not initialized".to_string())?;
+        let key_hex = hex::encode(derive_db_key_from_public_key(&public_key));
+        let path = self.month_shard_path(month);
+        conn.execute(
+            &format!("ATTACH DATABASE ?1 AS {} KEY ?2", SHARD_SCHEMA),
+            params![path.to_string_lossy(), format!("x'{}'", key_hex)],
+        )
+        .map_err(|e| format!("Failed to attach shard {}: {}", path.display(), e))?;
+        conn.execute_batch(&format!(
+            "SELECT count(*) FROM {}.sqlite_master;",
+            SHARD_SCHEMA
+        ))
+        .map_err(|e| format!("Shard key verification failed: {}", e))
+    }
+
+    fn detach_shard(conn: &Connection) {
+        if let Err(e) = conn.execute_batch(&format!("DETACH DATABASE {};", SHARD_SCHEMA)) {
+            tracing::warn!("[SHARDS] failed to detach shard: {}", e);
+        }
+    }
+
+    /// Detached months, oldest first.
+    pub fn list_month_shards(&self) -> Result<Vec<MonthShard>, String> {
+        let conn = self.open_read_connection_named("list_month_shards")?;
+        let mut stmt = conn
+            .prepare("SELECT month, screenshot_count, detached_at FROM month_shards ORDER BY month")
+            .map_err(|e| format!("Failed to prepare shard query: {}", e))?;
+        let rows: Vec<(String, i64, String)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .map_err(|e| format!("Failed to query shards: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(rows
+            .into_iter()
+            .map(|(month, screenshot_count, detached_at)| {
+                let file_bytes = std::fs::metadata(self.month_shard_path(&month))
+                    .map(|m| m.len())
+                    .ok();
+                MonthShard {
+                    month,
+                    screenshot_count,
+                    detached_at,
+                    file_bytes,
+                }
+            })
+            .collect())
+    }
+
+    /// Months recorded as detached, read through the writer connection.
+    pub(super) fn detached_months(&self) -> Result<Vec<String>, String> {
+        let guard = self.get_connection_named("detached_months")?;
+        let conn = guard.as_ref().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT month FROM month_shards ORDER BY month")
+            .map_err(|e| format!("Failed to prepare shard query: {}", e))?;
+        let months = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(|e| format!("Failed to query shards: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(months)
+    }
+
+    /// Detached months overlapping `[start_ts, end_ts]` (Unix seconds, either end
+    /// open), oldest first. Timeline and search read only the live database, so
+    /// callers report these months as offline instead of empty.
+    pub fn offline_months_between(
+        &self,
+        start_ts: Option<f64>,
+        end_ts: Option<f64>,
+    ) -> Result<Vec<String>, String> {
+        let conn = self.open_read_connection_named("offline_months_between")?;
+        let mut stmt = conn
+            .prepare("SELECT month FROM month_shards ORDER BY month")
+            .map_err(|e| format!("Failed to prepare shard query: {}", e))?;
+        let months = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(|e| format!("Failed to query shards: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(months_overlapping(
+            months,
+            start_ts.and_then(local_month),
+            end_ts.and_then(local_month),
+        ))
+    }
+
+    /// `image_path` values of every row kept in a shard, so file scans leave them alone.
+    pub(super) fn month_shard_image_paths(&self) -> Result<Vec<String>, String> {
+        let mut paths = Vec::new();
+        for month in self.detached_months()? {
+            if !self.month_shard_path(&month).is_file() {
+                continue;
+            }
+            let guard = self.get_connection_named("month_shard_image_paths")?;
+            let conn = guard.as_ref().unwrap();
+            self.attach_shard(conn, &month)?;
+            let result = (|| -> Result<Vec<String>, String> {
+                let mut stmt = conn
+                    .prepare(&format!(
+                        "SELECT image_path FROM {schema}.screenshots
+                         UNION ALL SELECT image_path FROM {schema}.delta_keyframes",
+                        schema = SHARD_SCHEMA
+                    ))
+                    .map_err(|e| format!("Failed to read shard {}: {}", month, e))?;
+                let rows = stmt
+                    .query_map([], |row| row.get(0))
+                    .map_err(|e| format!("Failed to read shard {}: {}", month, e))?
+                    .filter_map(|r| r.ok())
+                    .collect();
+                Ok(rows)
+            })();
+            Self::detach_shard(conn);
+            paths.extend(result?);
+        }
+        Ok(paths)
+    }
+
+    /// Whether any month currently lives in a shard.
+    pub(crate) fn has_month_shards(&self) -> Result<bool, String> {
+        let conn = self.open_read_connection_named("has_month_shards")?;
+        conn.query_row("SELECT EXISTS(SELECT 1 FROM month_shards)", [], |row| {
+            row.get(0)
+        })
+        .map_err(|e| format!("Failed to query shards: {}", e))
+    }
+
+    /// Move a closed month out of the live database into its own shard.
+    pub fn detach_month(&self, month: &str) -> Result<MonthShard, String> {
+        let month = parse_month(month)?;
+        let today = Local::now().date_naive();
+        if month >= format!("{:04}-{:02}", today.year(), today.month()) {
+            return Err("Only months before the current one can be detached".to_string());
+        }
+        let shard_path = self.month_shard_path(&month);
+        if shard_path.exists() {
+            return Err(format!("Month {} is already detached", month));
+        }
+        if let Some(parent) = shard_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create shard directory: {}", e))?;
+        }
+
+        let (moved, ocr_moved) = {
+            let mut guard = self.get_connection_named("detach_month")?;
+            let conn = guard.as_mut().unwrap();
+            self.attach_shard(conn, &month)?;
+            let result = Self::move_month_into_shard(conn, &month);
+            Self::detach_shard(conn);
+            if result.is_err() {
+                let _ = std::fs::remove_file(&shard_path);
+            }
+            result?
+        };
+        if moved == 0 {
+            let _ = std::fs::remove_file(&shard_path);
+            return Err(format!("No screenshots to detach in {}", month));
+        }
+        self.release_ocr_row_count(ocr_moved);
+        tracing::info!("[SHARDS] detached {} ({} screenshots)", month, moved);
+        self.list_month_shards()?
+            .into_iter()
+            .find(|shard| shard.month == month)
+            .ok_or_else(|| "Detached month was not recorded".to_string())
+    }
+
+    /// Copy the month's rows into the attached shard, then delete them from `main`.
+    /// Returns `(screenshots, live OCR rows)` moved.
+    fn move_month_into_shard(conn: &mut Connection, month: &str) -> Result<(usize, i64), String> {
+        let children = screenshot_child_tables(conn)?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start detach transaction: {}", e))?;
+
+        tx.execute(
+            &format!(
+                "CREATE TEMP TABLE month_shard_ids AS
+                 SELECT id FROM main.screenshots
+                 WHERE is_deleted = 0 AND pinned = 0
+                   AND strftime('%Y-%m', created_at, 'localtime') = ?1
+                   AND NOT {}",
+                PROTECTED_RANGE_MATCH
+            ),
+            params![month],
+        )
+        .map_err(|e| format!("Failed to select month rows: {}", e))?;
+
+        let mut tables = vec![("screenshots".to_string(), "id".to_string())];
+        tables.extend(children);
+        tables.push(("delta_keyframes".to_string(), String::new()));
+        for (table, _) in &tables {
+            let create_sql: String = tx
+                .query_row(
+                    "SELECT sql FROM main.sqlite_master WHERE type = 'table' AND name = ?1",
+                    params![table],
+                    |row| row.get(0),
+                )
+                .map_err(|e| format!("Failed to read schema of {}: {}", table, e))?;
+            // SQLite stores the statement as `CREATE TABLE <name> ...`.
+            let definition = create_sql
+                .strip_prefix("CREATE TABLE ")
+                .ok_or_else(|| format!("Unexpected schema for {}", table))?;
+            let shard_sql = format!("CREATE TABLE {}.{}", SHARD_SCHEMA, definition);
+            tx.execute_batch(&shard_sql)
+                .map_err(|e| format!("Failed to create shard table {}: {}", table, e))?;
+        }
+
+        // Keyframes go along so patches can be rebuilt once the month is attached;
+        // the live copy stays while other screenshots still use it.
+        tx.execute(
+            &format!(
+                "INSERT INTO {schema}.delta_keyframes SELECT * FROM main.delta_keyframes
+                 WHERE id IN (SELECT delta_keyframe_id FROM main.screenshots
+                              WHERE id IN (SELECT id FROM temp.month_shard_ids))",
+                schema = SHARD_SCHEMA
+            ),
+            [],
+        )
+        .map_err(|e| format!("Failed to copy keyframes: {}", e))?;
+        for (table, column) in &tables {
+            if column.is_empty() {
+                continue;
+            }
+            tx.execute(
+                &format!(
+                    "INSERT INTO {schema}.\"{table}\" SELECT * FROM main.\"{table}\"
+                     WHERE \"{column}\" IN (SELECT id FROM temp.month_shard_ids)",
+                    schema = SHARD_SCHEMA
+                ),
+                [],
+            )
+            .map_err(|e| format!("Failed to copy {} rows: {}", table, e))?;
+        }
+
+        let ocr_moved: i64 = tx
+            .query_row(
+                "SELECT COUNT(*) FROM main.ocr_results
+                 WHERE is_deleted = 0 AND screenshot_id IN (SELECT id FROM temp.month_shard_ids)",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to count OCR rows: {}", e))?;
+        // Child rows go with the screenshot rows through ON DELETE CASCADE.
+        let moved = tx
+            .execute(
+                "DELETE FROM main.screenshots WHERE id IN (SELECT id FROM temp.month_shard_ids)",
+                [],
+            )
+            .map_err(|e| format!("Failed to remove detached rows: {}", e))?;
+        tx.execute_batch("DROP TABLE temp.month_shard_ids;")
+            .map_err(|e| format!("Failed to drop detach selection: {}", e))?;
+        if moved > 0 {
+            tx.execute(
+                "INSERT INTO main.month_shards (month, screenshot_count) VALUES (?1, ?2)",
+                params![month, moved as i64],
+            )
+            .map_err(|e| format!("Failed to record shard: {}", e))?;
+            // Keeps keyframe pruning away from files the shard still needs.
+            tx.execute(
+                &format!(
+                    "INSERT INTO main.month_shard_keyframes (month, keyframe_id)
+                     SELECT ?1, id FROM {}.delta_keyframes",
+                    SHARD_SCHEMA
+                ),
+                params![month],
+            )
+            .map_err(|e| format!("Failed to record shard keyframes: {}", e))?;
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed to commit detach: {}", e))?;
+        Ok((moved, ocr_moved))
+    }
+
+    /// Move a detached month back into the live database and delete its shard.
+    /// Returns the number of screenshots restored.
+    pub fn attach_month(&self, month: &str) -> Result<usize, String> {
+        let month = parse_month(month)?;
+        let shard_path = self.month_shard_path(&month);
+        let recorded: Option<i64> = {
+            let conn = self.open_read_connection_named("attach_month")?;
+            conn.query_row(
+                "SELECT screenshot_count FROM month_shards WHERE month = ?1",
+                params![month],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to query shards: {}", e))?
+        };
+        if recorded.is_none() {
+            return Err(format!("Month {} is not detached", month));
+        }
+        if !shard_path.exists() {
+            return Err(format!("Shard file {} is missing", shard_path.display()));
+        }
+
+        let (restored, ocr_restored) = {
+            let mut guard = self.get_connection_named("attach_month")?;
+            let conn = guard.as_mut().unwrap();
+            self.attach_shard(conn, &month)?;
+            let result = Self::move_shard_into_main(conn, &month);
+            Self::detach_shard(conn);
+            result?
+        };
+        self.ocr_row_count
+            .fetch_add(ocr_restored as u64, Ordering::Relaxed);
+        if let Err(e) = std::fs::remove_file(&shard_path) {
+            tracing::warn!("[SHARDS] failed to remove {}: {}", shard_path.display(), e);
+        }
+        tracing::info!("[SHARDS] attached {} ({} screenshots)", month, restored);
+        Ok(restored)
+    }
+
+    fn move_shard_into_main(conn: &mut Connection, month: &str) -> Result<(usize, i64), String> {
+        let children = screenshot_child_tables(conn)?;
+        let shard_tables: Vec<String> = {
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT name FROM {}.sqlite_master WHERE type = 'table'",
+                    SHARD_SCHEMA
+                ))
+                .map_err(|e| format!("Failed to list shard tables: {}", e))?;
+            let names = stmt
+                .query_map([], |row| row.get(0))
+                .map_err(|e| format!("Failed to list shard tables: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            names
+        };
+
+        // Parents first so foreign keys hold.
+        let mut tables = vec!["delta_keyframes".to_string(), "screenshots".to_string()];
+        tables.extend(children.into_iter().map(|(table, _)| table));
+        let mut column_lists = Vec::new();
+        for table in &tables {
+            if shard_tables.contains(table) {
+                column_lists.push((table.clone(), shared_columns(conn, table)?));
+            }
+        }
+
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start attach transaction: {}", e))?;
+        let mut restored = 0usize;
+        for (table, columns) in &column_lists {
+            let inserted = tx
+                .execute(
+                    &format!(
+                        "INSERT OR IGNORE INTO main.\"{table}\" ({columns})
+                         SELECT {columns} FROM {schema}.\"{table}\"",
+                        schema = SHARD_SCHEMA
+                    ),
+                    [],
+                )
+                .map_err(|e| format!("Failed to restore {} rows: {}", table, e))?;
+            if table == "screenshots" {
+                restored = inserted;
+            }
+        }
+
+        // Postings of detached rows may have been compacted away; rebuild them.
+        let ocr_restored = tx
+            .execute(
+                &format!(
+                    "UPDATE main.ocr_results SET text_hash = ''
+                     WHERE is_deleted = 0 AND id IN (SELECT id FROM {}.ocr_results)",
+                    SHARD_SCHEMA
+                ),
+                [],
+            )
+            .map_err(|e| format!("Failed to queue OCR reindexing: {}", e))?;
+        tx.execute(
+            &format!(
+                "UPDATE main.screenshots SET metadata_index_version = 0
+                 WHERE id IN (SELECT id FROM {}.screenshots)",
+                SHARD_SCHEMA
+            ),
+            [],
+        )
+        .map_err(|e| format!("Failed to queue metadata reindexing: {}", e))?;
+        tx.execute(
+            "DELETE FROM main.month_shards WHERE month = ?1",
+            params![month],
+        )
+        .map_err(|e| format!("Failed to clear shard record: {}", e))?;
+        tx.execute(
+            "DELETE FROM main.month_shard_keyframes WHERE month = ?1",
+            params![month],
+        )
+        .map_err(|e| format!("Failed to clear shard keyframes: {}", e))?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit attach: {}", e))?;
+        Ok((restored, ocr_restored as i64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credential_manager::CredentialManagerState;
+    use std::sync::Arc;
+
+    #[test]
+    fn months_are_validated_and_normalised() {
+        assert_eq!(parse_month("2024-3").unwrap(), "2024-03");
+        assert_eq!(parse_month(" 2024-12 ").unwrap(), "2024-12");
+        assert!(parse_month("2024-13").is_err());
+        assert!(parse_month("March").is_err());
+    }
+
+    #[test]
+    fn offline_months_are_limited_to_the_queried_range() {
+        let months = vec![
+            "2024-01".to_string(),
+            "2024-03".to_string(),
+            "2024-06".to_string(),
+        ];
+        assert_eq!(
+            months_overlapping(
+                months.clone(),
+                Some("2024-02".to_string()),
+                Some("2024-03".to_string())
+            ),
+            vec!["2024-03".to_string()]
+        );
+        assert_eq!(
+            months_overlapping(months.clone(), None, Some("2024-03".to_string())),
+            vec!["2024-01".to_string(), "2024-03".to_string()]
+        );
+        assert_eq!(months_overlapping(months, None, None).len(), 3);
+
+        let mid_june = NaiveDate::from_ymd_opt(2024, 6, 15)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_utc()
+            .timestamp() as f64;
+        assert_eq!(local_month(mid_june).as_deref(), Some("2024-06"));
+    }
+
+    #[test]
+    fn child_tables_are_found_through_foreign_keys() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let credential_state = Arc::new(CredentialManagerState::new(temp.path().to_path_buf()));
+        let storage = StorageState::new(temp.path().to_path_buf(), credential_state);
+        let conn = Connection::open_in_memory().expect("in-memory db");
+        storage.init_tables(&conn).expect("schema");
+
+        let children = screenshot_child_tables(&conn).expect("child tables");
+        let names: Vec<&str> = children.iter().map(|(table, _)| table.as_str()).collect();
+        assert!(names.contains(&"ocr_results"));
+        assert!(names.contains(&"annotations"));
+        // Assignments also reference clusters or tasks and are left behind.
+        assert!(!names.contains(&"task_assignments"));
+        assert!(children.iter().all(|(_, column)| column == "screenshot_id"));
+    }
+}
@@ -0,0 +1,153 @@
+//! Near-duplicate suppression for consecutive captures.
+//!
+//! The exact `image_hash` check misses frames that differ only by a blinking cursor
+//! or a ticking clock. When a threshold is configured, each saved screenshot stores a
+//! 256-bit difference hash (dHash) in `screenshots.perceptual_hash`, and
+//! `save_screenshot_temp` skips a frame whose hash is within the threshold of the
+//! most recently saved one.
+
+use std::sync::atomic::Ordering;
+
+use rusqlite::OptionalExtension;
+
+use super::StorageState;
+
+/// Registry value name used by `get_advanced_config` / `set_advanced_config`.
+pub(crate) const NEAR_DUPLICATE_THRESHOLD_REGISTRY_KEY: &str = "near_duplicate_threshold";
+
+/// Side of the dHash grid; the hash has `HASH_SIZE * HASH_SIZE` bits.
+const HASH_SIZE: u32 = 16;
+const PERCEPTUAL_HASH_BITS: u32 = HASH_SIZE * HASH_SIZE;
+const PERCEPTUAL_HASH_BYTES: usize = (PERCEPTUAL_HASH_BITS / 8) as usize;
+
+/// 256-bit difference hash of encoded image bytes (PNG, JPEG, WebP, ...).
+fn compute_perceptual_hash(image_bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let gray = image::load_from_memory(image_bytes)
+        .map_err(|e| format!("Failed to decode image for perceptual hash: {}", e))?
+        .to_luma8();
+    let resized = image::imageops::resize(
+        &gray,
+        HASH_SIZE + 1,
+        HASH_SIZE,
+        image::imageops::FilterType::Triangle,
+    );
+
+    let mut hash = vec![0u8; PERCEPTUAL_HASH_BYTES];
+    let mut bit_index = 0usize;
+    for row in 0..HASH_SIZE {
+        for col in 0..HASH_SIZE {
+            if resized.get_pixel(col, row)[0] > resized.get_pixel(col + 1, row)[0] {
+                hash[bit_index / 8] |= 1 << (bit_index % 8);
+            }
+            bit_index += 1;
+        }
+    }
+    Ok(hash)
+}
+
+/// Number of differing bits; hashes of different lengths never match.
+fn hamming_distance(a: &[u8], b: &[u8]) -> Option<u32> {
+    if a.len() != b.len() {
+        return None;
+    }
+    Some(a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones()).sum())
+}
+
+impl StorageState {
+    /// Maximum differing bits (out of 256) for a frame to count as a near-duplicate
+    /// of the previous one; 0 disables the check.
+    pub fn near_duplicate_threshold(&self) -> u32 {
+        self.near_duplicate_threshold.load(Ordering::Relaxed)
+    }
+
+    pub fn set_near_duplicate_threshold(&self, threshold: u32) {
+        self.near_duplicate_threshold
+            .store(threshold.min(PERCEPTUAL_HASH_BITS), Ordering::Relaxed);
+    }
+
+    /// Restore the threshold saved by `set_advanced_config`.
+    pub(crate) fn load_near_duplicate_threshold_from_registry(&self) {
+        let threshold =
+            crate::registry_config::get_u32(NEAR_DUPLICATE_THRESHOLD_REGISTRY_KEY).unwrap_or(0);
+        self.set_near_duplicate_threshold(threshold);
+    }
+
+    /// Hash `image_bytes` when near-duplicate suppression is enabled.
+    ///
+    /// Returns `(hash, is_near_duplicate)`; the hash is `None` when the check is off or
+    /// the image cannot be decoded, in which case the frame is always kept.
+    pub(super) fn check_near_duplicate(
+        &self,
+        image_bytes: &[u8],
+    ) -> Result<(Option<Vec<u8>>, bool), String> {
+        let threshold = self.near_duplicate_threshold();
+        if threshold == 0 {
+            return Ok((None, false));
+        }
+        let hash = match compute_perceptual_hash(image_bytes) {
+            Ok(hash) => hash,
+            Err(e) => {
+                tracing::warn!("{}", e);
+                return Ok((None, false));
+            }
+        };
+
+        let previous: Option<Vec<u8>> = {
+            let guard = self.get_connection_named("check_near_duplicate")?;
+            let conn = guard.as_ref().unwrap();
+            conn.query_row(
+                "SELECT perceptual_hash FROM screenshots
+                 WHERE is_deleted = 0 AND perceptual_hash IS NOT NULL
+                 ORDER BY id DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to query previous perceptual hash: {}", e))?
+        };
+
+        let is_near_duplicate = previous
+            .and_then(|prev| hamming_distance(&hash, &prev))
+            .is_some_and(|distance| distance <= threshold);
+        Ok((Some(hash), is_near_duplicate))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_png(img: &image::RgbImage) -> Vec<u8> {
+        let mut buf = std::io::Cursor::new(Vec::new());
+        img.write_to(&mut buf, image::ImageFormat::Png)
+            .expect("encode fixture png");
+        buf.into_inner()
+    }
+
+    /// Horizontal gradient, optionally with a small text cursor in the middle.
+    fn frame(descending: bool, cursor_visible: bool) -> image::RgbImage {
+        image::RgbImage::from_fn(320, 200, |x, y| {
+            if cursor_visible && (150..152).contains(&x) && (90..102).contains(&y) {
+                return image::Rgb([0, 0, 0]);
+            }
+            let v = (if descending {
+                255 - x * 3 / 4
+            } else {
+                15 + x * 3 / 4
+            }) as u8;
+            image::Rgb([v, v, v])
+        })
+    }
+
+    #[test]
+    fn blinking_cursor_is_near_duplicate_but_new_content_is_not() {
+        let base = compute_perceptual_hash(&encode_png(&frame(true, false))).unwrap();
+        let cursor = compute_perceptual_hash(&encode_png(&frame(true, true))).unwrap();
+        let reversed = compute_perceptual_hash(&encode_png(&frame(false, false))).unwrap();
+
+        assert_eq!(base.len(), PERCEPTUAL_HASH_BYTES);
+        assert!(hamming_distance(&base, &cursor).unwrap() <= 4);
+        assert!(hamming_distance(&base, &reversed).unwrap() > 200);
+        assert_eq!(hamming_distance(&base, &base[..8]), None);
+    }
+}
@@ -0,0 +1,168 @@
+//! Batch retrieval of decrypted OCR text for many screenshots in one call.
+//!
+//! Every OCR row carries its own wrapped key, so unwrapping through CNG dominates the
+//! cost of reading text back. Rows are fetched in one query and then decrypted on a
+//! few worker threads, grouped by screenshot so each worker owns whole screenshots.
+
+use std::collections::HashMap;
+
+use super::screenshot::EncryptedOcrRow;
+use super::{OcrTextEntry, StorageState};
+
+/// Most screenshot ids accepted by one `get_ocr_texts` call.
+const MAX_OCR_TEXT_BATCH: usize = 500;
+/// Per-screenshot character cap used when the caller does not pass one.
+const DEFAULT_OCR_TEXT_MAX_CHARS: usize = 8_000;
+/// Upper bound for a caller-supplied per-screenshot character cap.
+const MAX_OCR_TEXT_MAX_CHARS: usize = 100_000;
+/// Upper bound on decryption worker threads.
+const MAX_DECRYPT_WORKERS: usize = 8;
+
+/// Cut `text` to at most `max_chars` characters on a char boundary.
+fn truncate_chars(text: &mut String, max_chars: usize) -> bool {
+    match text.char_indices().nth(max_chars) {
+        Some((byte_idx, _)) => {
+            text.truncate(byte_idx);
+            true
+        }
+        None => false,
+    }
+}
+
+impl StorageState {
+    /// Decrypted OCR text for each of `screenshot_ids`, in request order.
+    ///
+    /// Boxes are joined with a space in reading order and each text is capped at
+    /// `max_chars` characters (default [`DEFAULT_OCR_TEXT_MAX_CHARS`]). Duplicate ids are
+    /// returned once; ids without OCR rows yield an empty text. Rows that fail to
+    /// decrypt are skipped, matching `get_ocr_results_by_screenshot_ids`.
+    pub fn get_ocr_texts(
+        &self,
+        screenshot_ids: &[i64],
+        max_chars: Option<usize>,
+    ) -> Result<Vec<OcrTextEntry>, String> {
+        if screenshot_ids.len() > MAX_OCR_TEXT_BATCH {
+            return Err(format!(
+                "Too many screenshot ids: {} (max {})",
+                screenshot_ids.len(),
+                MAX_OCR_TEXT_BATCH
+            ));
+        }
+        let max_chars = max_chars
+            .unwrap_or(DEFAULT_OCR_TEXT_MAX_CHARS)
+            .clamp(1, MAX_OCR_TEXT_MAX_CHARS);
+
+        let mut ids = Vec::with_capacity(screenshot_ids.len());
+        for &id in screenshot_ids {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let raw_rows = self.fetch_encrypted_ocr_rows(&ids, "get_ocr_texts")?;
+        let mut texts = self.decrypt_ocr_rows_parallel(raw_rows, max_chars);
+
+        Ok(ids
+            .into_iter()
+            .map(|screenshot_id| {
+                let (text, truncated) = texts.remove(&screenshot_id).unwrap_or_default();
+                OcrTextEntry {
+                    screenshot_id,
+                    text,
+                    truncated,
+                }
+            })
+            .collect())
+    }
+
+    fn decrypt_ocr_rows_parallel(
+        &self,
+        raw_rows: Vec<EncryptedOcrRow>,
+        max_chars: usize,
+    ) -> HashMap<i64, (String, bool)> {
+        // Rows arrive ordered by screenshot, so consecutive runs form one group each.
+        let mut groups: Vec<(i64, Vec<(Vec<u8>, Vec<u8>)>)> = Vec::new();
+        for (screenshot_id, text_enc, text_key_enc) in raw_rows {
+            let (Some(data), Some(key)) = (text_enc, text_key_enc) else {
+                continue;
+            };
+            match groups.last_mut() {
+                Some((id, rows)) if *id == screenshot_id => rows.push((data, key)),
+                _ => groups.push((screenshot_id, vec![(data, key)])),
+            }
+        }
+        if groups.is_empty() {
+            return HashMap::new();
+        }
+
+        let workers = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .clamp(1, MAX_DECRYPT_WORKERS)
+            .min(groups.len());
+        let per_worker = groups.len().div_ceil(workers);
+
+        let decrypt_group = |rows: &[(Vec<u8>, Vec<u8>)]| {
+            let mut text = String::new();
+            for (data, key) in rows {
+                let Some(part) = self
+                    .decrypt_payload_with_row_key(data, key)
+                    .ok()
+                    .and_then(|v| String::from_utf8(v).ok())
+                else {
+                    continue;
+                };
+                if part.is_empty() {
+                    continue;
+                }
+                if !text.is_empty() {
+                    text.push(' ');
+                }
+                text.push_str(&part);
+                // Stop unwrapping keys once the cap is reached.
+                if truncate_chars(&mut text, max_chars) {
+                    return (text, true);
+                }
+            }
+            (text, false)
+        };
+
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = groups
+                .chunks(per_worker)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|(id, rows)| (*id, decrypt_group(rows)))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .filter_map(|handle| handle.join().ok())
+                .flatten()
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_chars_respects_multibyte_boundaries() {
+        let mut text = "截图 OCR 文本".to_string();
+        assert!(truncate_chars(&mut text, 4));
+        assert_eq!(text, "截图 O");
+
+        let mut short = "abc".to_string();
+        assert!(!truncate_chars(&mut short, 3));
+        assert_eq!(short, "abc");
+    }
+}
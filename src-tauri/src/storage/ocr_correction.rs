@@ -0,0 +1,257 @@
+//! Manual corrections of recognized OCR text.
+//!
+//! A corrected row is re-encrypted under a fresh row key and its postings in
+//! `blind_bitmap_index` are moved from the bigrams of the old text to those of the new
+//! one, so the correction is searchable at once. Rows the lazy indexer has not reached
+//! yet (empty `text_hash`) only get the new text; the indexer covers them later.
+//! Corrected rows get confidence 1.0 so `min_confidence` filters keep them.
+
+use std::collections::{HashMap, HashSet};
+
+use roaring::RoaringBitmap;
+use rusqlite::{params, OptionalExtension};
+
+use super::{OcrResult, StorageState};
+
+/// Longest accepted correction, in characters.
+const MAX_OCR_TEXT_CHARS: usize = 10_000;
+
+/// Confidence recorded for text the user typed in.
+const CORRECTED_CONFIDENCE: f64 = 1.0;
+
+/// Trim `text`; rejects empty or overlong corrections.
+fn normalize_ocr_text(text: &str) -> Result<String, String> {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return Err("OCR text must not be empty".to_string());
+    }
+    if trimmed.chars().count() > MAX_OCR_TEXT_CHARS {
+        return Err(format!(
+            "OCR text is longer than {} characters",
+            MAX_OCR_TEXT_CHARS
+        ));
+    }
+    Ok(trimmed.to_string())
+}
+
+impl StorageState {
+    /// Replace the text of one OCR box and update its search postings.
+    pub fn update_ocr_text(&self, ocr_id: i64, new_text: &str) -> Result<OcrResult, String> {
+        let new_text = normalize_ocr_text(new_text)?;
+        let hmac_key = self.credential_state.get_hmac_key()?;
+        let (text_enc, text_key_enc) = self.encrypt_payload_with_row_key(new_text.as_bytes())?;
+
+        let mut guard = self.get_connection_named("update_ocr_text")?;
+        let conn = guard.as_mut().unwrap();
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start OCR correction transaction: {}", e))?;
+        let (screenshot_id, old_enc, old_key_enc, text_hash, box_values, created_at): (
+            i64,
+            Option<Vec<u8>>,
+            Option<Vec<u8>>,
+            String,
+            [f64; 8],
+            String,
+        ) = tx
+            .query_row(
+                "SELECT screenshot_id, text_enc, text_key_encrypted, text_hash,
+                        box_x1, box_y1, box_x2, box_y2,
+                        box_x3, box_y3, box_x4, box_y4, created_at
+                 FROM ocr_results WHERE id = ?1 AND is_deleted = 0",
+                params![ocr_id],
+                |row| {
+                    let mut box_values = [0.0; 8];
+                    for (i, value) in box_values.iter_mut().enumerate() {
+                        *value = row.get(4 + i)?;
+                    }
+                    Ok((
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        box_values,
+                        row.get(12)?,
+                    ))
+                },
+            )
+            .optional()
+            .map_err(|e| format!("Failed to query OCR result: {}", e))?
+            .ok_or_else(|| format!("OCR result {} not found", ocr_id))?;
+
+        let indexed = !text_hash.is_empty();
+        let new_text_hash = if indexed {
+            let old_text = match (old_enc.as_deref(), old_key_enc.as_deref()) {
+                (Some(enc), Some(key_enc)) => self
+                    .decrypt_payload_with_row_key(enc, key_enc)
+                    .ok()
+                    .and_then(|bytes| String::from_utf8(bytes).ok()),
+                _ => None,
+            };
+            Self::update_ocr_postings(
+                &tx,
+                &hmac_key,
+                ocr_id,
+                old_text.as_deref(),
+                Some(&new_text),
+            )?;
+            Self::compute_hmac_hash(&new_text, &hmac_key)
+        } else {
+            String::new()
+        };
+
+        tx.execute(
+            "UPDATE ocr_results
+             SET text = NULL, text_enc = ?2, text_key_encrypted = ?3, text_hash = ?4,
+                 confidence = ?5
+             WHERE id = ?1",
+            params![
+                ocr_id,
+                text_enc,
+                text_key_enc,
+                new_text_hash,
+                CORRECTED_CONFIDENCE
+            ],
+        )
+        .map_err(|e| format!("Failed to update OCR result: {}", e))?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit OCR correction: {}", e))?;
+
+        Ok(OcrResult {
+            id: ocr_id,
+            screenshot_id,
+            text: new_text,
+            confidence: CORRECTED_CONFIDENCE,
+            box_coords: box_values.chunks(2).map(|point| point.to_vec()).collect(),
+            created_at,
+        })
+    }
+
+    /// Move `ocr_id` from the `blind_bitmap_index` postings of `old_text` to those of
+    /// `new_text` inside the caller's transaction.
+    pub(super) fn update_ocr_postings(
+        tx: &rusqlite::Transaction<'_>,
+        hmac_key: &[u8],
+        ocr_id: i64,
+        old_text: Option<&str>,
+        new_text: Option<&str>,
+    ) -> Result<(), String> {
+        let posting_id = u32::try_from(ocr_id)
+            .map_err(|_| format!("OCR result id {} exceeds index range", ocr_id))?;
+        let old_tokens: HashSet<String> = old_text.map(Self::bigram_tokenize).unwrap_or_default();
+        let new_tokens: HashSet<String> = new_text.map(Self::bigram_tokenize).unwrap_or_default();
+
+        let mut changes: HashMap<String, bool> = HashMap::new();
+        for token in old_tokens.difference(&new_tokens) {
+            changes.insert(Self::compute_hmac_hash(token, hmac_key), false);
+        }
+        for token in new_tokens.difference(&old_tokens) {
+            changes.insert(Self::compute_hmac_hash(token, hmac_key), true);
+        }
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let mut get_stmt = tx
+            .prepare_cached("SELECT postings_blob FROM blind_bitmap_index WHERE token_hash = ?1")
+            .map_err(|e| format!("Failed to prepare bitmap read: {}", e))?;
+        let mut put_stmt = tx
+            .prepare_cached(
+                "INSERT OR REPLACE INTO blind_bitmap_index (token_hash, postings_blob) VALUES (?1, ?2)",
+            )
+            .map_err(|e| format!("Failed to prepare bitmap write: {}", e))?;
+        let mut del_stmt = tx
+            .prepare_cached("DELETE FROM blind_bitmap_index WHERE token_hash = ?1")
+            .map_err(|e| format!("Failed to prepare bitmap delete: {}", e))?;
+
+        for (token_hash, insert) in changes {
+            let existing_blob: Option<Vec<u8>> = get_stmt
+                .query_row(params![&token_hash], |row| row.get(0))
+                .optional()
+                .map_err(|e| format!("Failed to load bitmap row: {}", e))?;
+            let mut bitmap = match existing_blob {
+                Some(blob) => RoaringBitmap::deserialize_from(&blob[..])
+                    .map_err(|e| format!("Failed to deserialize bitmap: {}", e))?,
+                None if insert => RoaringBitmap::new(),
+                None => continue,
+            };
+            if insert {
+                bitmap.insert(posting_id);
+            } else {
+                bitmap.remove(posting_id);
+            }
+
+            if bitmap.is_empty() {
+                del_stmt
+                    .execute(params![&token_hash])
+                    .map_err(|e| format!("Failed to delete empty bitmap row: {}", e))?;
+            } else {
+                let mut buf = Vec::new();
+                bitmap
+                    .serialize_into(&mut buf)
+                    .map_err(|e| format!("Failed to serialize bitmap: {}", e))?;
+                put_stmt
+                    .execute(params![&token_hash, &buf])
+                    .map_err(|e| format!("Failed to write bitmap row: {}", e))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+
+    const TEST_HMAC_KEY: &[u8] = b"ocr-correction-test-hmac-key-32b";
+
+    #[test]
+    fn corrected_text_replaces_only_changed_postings() {
+        let mut conn = Connection::open_in_memory().expect("in-memory database");
+        conn.execute_batch(
+            "CREATE TABLE blind_bitmap_index (
+                token_hash TEXT PRIMARY KEY,
+                postings_blob BLOB NOT NULL
+            );",
+        )
+        .expect("index table");
+
+        let tx = conn.transaction().unwrap();
+        StorageState::update_ocr_postings(&tx, TEST_HMAC_KEY, 7, None, Some("Inv0ice")).unwrap();
+        StorageState::update_ocr_postings(&tx, TEST_HMAC_KEY, 8, None, Some("voice")).unwrap();
+        StorageState::update_ocr_postings(&tx, TEST_HMAC_KEY, 7, Some("Inv0ice"), Some("Invoice"))
+            .unwrap();
+        tx.commit().unwrap();
+
+        let postings = |token: &str| -> Vec<u32> {
+            let hash = StorageState::compute_hmac_hash(token, TEST_HMAC_KEY);
+            conn.query_row(
+                "SELECT postings_blob FROM blind_bitmap_index WHERE token_hash = ?1",
+                params![hash],
+                |row| row.get::<_, Vec<u8>>(0),
+            )
+            .optional()
+            .unwrap()
+            .map(|blob| {
+                RoaringBitmap::deserialize_from(&blob[..])
+                    .unwrap()
+                    .iter()
+                    .collect()
+            })
+            .unwrap_or_default()
+        };
+        assert_eq!(postings("nv"), vec![7]);
+        assert_eq!(postings("vo"), vec![7, 8]);
+        assert_eq!(postings("ce"), vec![7, 8]);
+        assert!(postings("v0").is_empty());
+        assert!(postings("0i").is_empty());
+    }
+
+    #[test]
+    fn normalize_ocr_text_trims_and_rejects_empty() {
+        assert_eq!(normalize_ocr_text("  Invoice ").unwrap(), "Invoice");
+        assert!(normalize_ocr_text("   ").is_err());
+        assert!(normalize_ocr_text(&"x".repeat(MAX_OCR_TEXT_CHARS + 1)).is_err());
+    }
+}
@@ -0,0 +1,156 @@
+//! Storage side of re-running OCR on screenshots that already have results.
+//!
+//! The OCR itself runs in the ML worker (see `ocr_reprocess` at the crate root). This
+//! module lists the screenshots to reprocess and swaps their OCR rows in one
+//! transaction: the postings of the old rows are removed and the old rows deleted,
+//! and the new rows are inserted already indexed so they are searchable at once.
+
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+use std::sync::atomic::Ordering;
+
+use super::screenshot::validate_ocr_result;
+use super::{OcrResultInput, StorageState};
+
+/// A screenshot queued for reprocessing: id and stored image path.
+pub type OcrReprocessTarget = (i64, String);
+
+impl StorageState {
+    /// Committed screenshots to reprocess, oldest first: `screenshot_id` alone when
+    /// given, otherwise those between `start_ts` and `end_ts` (Unix seconds).
+    pub fn list_ocr_reprocess_targets(
+        &self,
+        screenshot_id: Option<i64>,
+        start_ts: f64,
+        end_ts: f64,
+        limit: i64,
+    ) -> Result<Vec<OcrReprocessTarget>, String> {
+        let conn = self.open_read_connection_named("list_ocr_reprocess_targets")?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, image_path FROM screenshots
+                 WHERE is_deleted = 0 AND status = 'committed'
+                   AND (?1 IS NULL OR id = ?1)
+                   AND (?1 IS NOT NULL OR created_at BETWEEN ?2 AND ?3)
+                 ORDER BY created_at ASC, id ASC
+                 LIMIT ?4",
+            )
+            .map_err(|e| format!("Failed to prepare reprocess query: {}", e))?;
+        let format_ts = |ts: f64| {
+            DateTime::<Utc>::from_timestamp(ts as i64, 0)
+                .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default()
+        };
+        let rows = stmt
+            .query_map(
+                params![screenshot_id, format_ts(start_ts), format_ts(end_ts), limit],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|e| format!("Failed to query reprocess targets: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(rows)
+    }
+
+    /// Replace every OCR row of a screenshot with `results`, moving the search
+    /// postings along. Returns the number of rows removed and added.
+    pub fn replace_ocr_results(
+        &self,
+        screenshot_id: i64,
+        results: &[OcrResultInput],
+    ) -> Result<(usize, usize), String> {
+        let hmac_key = self.credential_state.get_hmac_key()?;
+
+        // Encrypt outside the DB lock, as commit_screenshot does.
+        let mut new_rows = Vec::new();
+        for result in self.postprocess_ocr_results(results) {
+            if let Err(error) = validate_ocr_result(&result) {
+                tracing::warn!(
+                    "Skipping invalid OCR result while reprocessing screenshot {}: {}",
+                    screenshot_id,
+                    error
+                );
+                continue;
+            }
+            let (text_enc, text_key_enc) =
+                self.encrypt_payload_with_row_key(result.text.as_bytes())?;
+            let text_hash = Self::compute_hmac_hash(&result.text, &hmac_key);
+            new_rows.push((result, text_enc, text_key_enc, text_hash));
+        }
+
+        let mut guard = self.get_connection_named("replace_ocr_results")?;
+        let conn = guard.as_mut().unwrap();
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start OCR replace transaction: {}", e))?;
+
+        let old_rows: Vec<(i64, Option<Vec<u8>>, Option<Vec<u8>>, String)> = {
+            let mut stmt = tx
+                .prepare(
+                    "SELECT id, text_enc, text_key_encrypted, text_hash
+                     FROM ocr_results WHERE screenshot_id = ?1 AND is_deleted = 0",
+                )
+                .map_err(|e| format!("Failed to prepare OCR row query: {}", e))?;
+            let rows = stmt
+                .query_map(params![screenshot_id], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+                })
+                .map_err(|e| format!("Failed to query OCR rows: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows
+        };
+        for (ocr_id, text_enc, text_key_enc, text_hash) in &old_rows {
+            // Unindexed rows have no postings yet.
+            if !text_hash.is_empty() {
+                let old_text = match (text_enc.as_deref(), text_key_enc.as_deref()) {
+                    (Some(enc), Some(key_enc)) => self
+                        .decrypt_payload_with_row_key(enc, key_enc)
+                        .ok()
+                        .and_then(|bytes| String::from_utf8(bytes).ok()),
+                    _ => None,
+                };
+                Self::update_ocr_postings(&tx, &hmac_key, *ocr_id, old_text.as_deref(), None)?;
+            }
+            tx.execute("DELETE FROM ocr_results WHERE id = ?1", params![ocr_id])
+                .map_err(|e| format!("Failed to delete OCR row: {}", e))?;
+        }
+
+        for (result, text_enc, text_key_enc, text_hash) in &new_rows {
+            tx.execute(
+                "INSERT INTO ocr_results (
+                    screenshot_id, text, text_hash, text_enc, text_key_encrypted, confidence,
+                    box_x1, box_y1, box_x2, box_y2,
+                    box_x3, box_y3, box_x4, box_y4
+                 ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                params![
+                    screenshot_id,
+                    Option::<String>::None,
+                    text_hash,
+                    text_enc,
+                    text_key_enc,
+                    result.confidence,
+                    result.box_coords[0][0],
+                    result.box_coords[0][1],
+                    result.box_coords[1][0],
+                    result.box_coords[1][1],
+                    result.box_coords[2][0],
+                    result.box_coords[2][1],
+                    result.box_coords[3][0],
+                    result.box_coords[3][1],
+                ],
+            )
+            .map_err(|e| format!("Failed to insert OCR result: {}", e))?;
+            let ocr_id = tx.last_insert_rowid();
+            Self::update_ocr_postings(&tx, &hmac_key, ocr_id, None, Some(&result.text))?;
+        }
+
+        tx.commit()
+            .map_err(|e| format!("Failed to commit OCR replace: {}", e))?;
+
+        self.release_ocr_row_count(old_rows.len() as i64);
+        self.ocr_row_count
+            .fetch_add(new_rows.len() as u64, Ordering::Relaxed);
+        Ok((old_rows.len(), new_rows.len()))
+    }
+}
@@ -0,0 +1,321 @@
+//! OCR text post-processing applied before OCR results are indexed and stored.
+//!
+//! Each rule is an [`OcrTextStage`]; [`OcrTextPipeline::from_rules`] assembles the
+//! enabled stages in a fixed order (width normalization, hyphen merging, whitespace
+//! cleanup) so CJK/Latin mixed text produces stable search tokens.
+
+use super::{OcrResultInput, OcrTextRules, StorageState};
+use serde_json::Value as JsonValue;
+use std::sync::Arc;
+
+/// Policy key holding the persisted [`OcrTextRules`].
+pub(crate) const OCR_TEXT_RULES_POLICY_KEY: &str = "ocr_text_rules";
+
+/// A single text transformation in the OCR post-processing pipeline.
+pub trait OcrTextStage: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn apply(&self, text: &str) -> String;
+}
+
+/// Maps full-width ASCII and half-width katakana/punctuation to their canonical forms
+/// (the NFKC subset that matters for OCR output).
+struct NormalizeWidth;
+
+/// Half-width katakana U+FF65..=U+FF9D in code point order.
+const HALFWIDTH_KATAKANA: &str =
+    "・ヲァィゥェォャュョッーアイウエオカキクケコサシスセソタチツテトナニヌネノハヒフヘホマミムメモヤユヨラリルレロワン";
+
+fn voiced_katakana(base: char, mark: char) -> Option<char> {
+    let code = base as u32;
+    match mark {
+        // Dakuten: カ..ト and ハ..ホ gain +1, ウ becomes ヴ.
+        '\u{FF9E}' => match base {
+            'ウ' => Some('ヴ'),
+            'カ' | 'キ' | 'ク' | 'ケ' | 'コ' | 'サ' | 'シ' | 'ス' | 'セ' | 'ソ' | 'タ' | 'チ'
+            | 'ツ' | 'テ' | 'ト' | 'ハ' | 'ヒ' | 'フ' | 'ヘ' | 'ホ' => {
+                char::from_u32(code + 1)
+            }
+            _ => None,
+        },
+        // Handakuten: ハ..ホ gain +2.
+        '\u{FF9F}' => match base {
+            'ハ' | 'ヒ' | 'フ' | 'ヘ' | 'ホ' => char::from_u32(code + 2),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn normalize_width_char(c: char) -> char {
+    match c {
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+        '\u{3000}' => ' ',
+        '\u{FF5F}' => '⦅',
+        '\u{FF60}' => '⦆',
+        '\u{FF61}' => '。',
+        '\u{FF62}' => '「',
+        '\u{FF63}' => '」',
+        '\u{FF64}' => '、',
+        '\u{FF65}'..='\u{FF9D}' => HALFWIDTH_KATAKANA
+            .chars()
+            .nth((c as u32 - 0xFF65) as usize)
+            .unwrap_or(c),
+        '\u{FF9E}' => '゛',
+        '\u{FF9F}' => '゜',
+        '\u{FFE0}' => '¢',
+        '\u{FFE1}' => '£',
+        '\u{FFE2}' => '¬',
+        '\u{FFE3}' => '¯',
+        '\u{FFE4}' => '¦',
+        '\u{FFE5}' => '¥',
+        '\u{FFE6}' => '₩',
+        _ => c,
+    }
+}
+
+impl OcrTextStage for NormalizeWidth {
+    fn name(&self) -> &'static str {
+        "normalize_width"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            let normalized = normalize_width_char(c);
+            if ('\u{FF65}'..='\u{FF9D}').contains(&c) {
+                if let Some(&mark) = chars.peek() {
+                    if let Some(voiced) = voiced_katakana(normalized, mark) {
+                        chars.next();
+                        out.push(voiced);
+                        continue;
+                    }
+                }
+            }
+            out.push(normalized);
+        }
+        out
+    }
+}
+
+/// Joins words split across lines with a trailing hyphen ("recog-\nnition").
+///
+/// Only merges when a letter precedes the hyphen and a lowercase letter starts the
+/// next line, so list markers and ranges such as "2020-\n2021" are left alone.
+struct MergeHyphenatedLineBreaks;
+
+fn is_line_break_hyphen(c: char) -> bool {
+    matches!(c, '-' | '\u{2010}' | '\u{00AD}')
+}
+
+impl OcrTextStage for MergeHyphenatedLineBreaks {
+    fn name(&self) -> &'static str {
+        "merge_hyphenated_line_breaks"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let mut out = String::with_capacity(text.len());
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let follows_letter = out.chars().last().is_some_and(|p| p.is_alphabetic());
+            if is_line_break_hyphen(c) && follows_letter {
+                let mut j = i + 1;
+                while j < chars.len() && matches!(chars[j], ' ' | '\t') {
+                    j += 1;
+                }
+                if j < chars.len() && chars[j] == '\r' {
+                    j += 1;
+                }
+                if j < chars.len() && chars[j] == '\n' {
+                    j += 1;
+                    while j < chars.len() && matches!(chars[j], ' ' | '\t') {
+                        j += 1;
+                    }
+                    if j < chars.len() && chars[j].is_lowercase() {
+                        i = j;
+                        continue;
+                    }
+                }
+            }
+            out.push(c);
+            i += 1;
+        }
+        out
+    }
+}
+
+/// Collapses runs of whitespace to a single space (or a single newline when the run
+/// spans lines) and trims the result.
+struct CollapseWhitespace;
+
+impl OcrTextStage for CollapseWhitespace {
+    fn name(&self) -> &'static str {
+        "collapse_whitespace"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut pending: Option<char> = None;
+        for c in text.chars() {
+            if c.is_whitespace() {
+                if c == '\n' || c == '\r' {
+                    pending = Some('\n');
+                } else if pending.is_none() {
+                    pending = Some(' ');
+                }
+                continue;
+            }
+            if let Some(separator) = pending.take() {
+                if !out.is_empty() {
+                    out.push(separator);
+                }
+            }
+            out.push(c);
+        }
+        out
+    }
+}
+
+/// Ordered set of enabled OCR text stages.
+pub struct OcrTextPipeline {
+    rules: OcrTextRules,
+    stages: Vec<Box<dyn OcrTextStage>>,
+}
+
+impl OcrTextPipeline {
+    pub fn from_rules(rules: OcrTextRules) -> Self {
+        let mut stages: Vec<Box<dyn OcrTextStage>> = Vec::new();
+        if rules.enabled {
+            if rules.normalize_width {
+                stages.push(Box::new(NormalizeWidth));
+            }
+            if rules.merge_hyphenated_line_breaks {
+                stages.push(Box::new(MergeHyphenatedLineBreaks));
+            }
+            if rules.collapse_whitespace {
+                stages.push(Box::new(CollapseWhitespace));
+            }
+        }
+        Self { rules, stages }
+    }
+
+    pub fn rules(&self) -> OcrTextRules {
+        self.rules
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        let mut current = text.to_string();
+        for stage in &self.stages {
+            let next = stage.apply(&current);
+            if next != current {
+                tracing::trace!("[OCR_TEXT] stage {} rewrote text", stage.name());
+            }
+            current = next;
+        }
+        current
+    }
+}
+
+impl Default for OcrTextPipeline {
+    fn default() -> Self {
+        Self::from_rules(OcrTextRules::default())
+    }
+}
+
+impl StorageState {
+    /// Returns the active OCR text post-processing rules.
+    pub fn ocr_text_rules(&self) -> OcrTextRules {
+        self.ocr_text_pipeline
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .rules()
+    }
+
+    /// Replaces the OCR text post-processing rules used for new OCR results.
+    pub fn set_ocr_text_rules(&self, rules: OcrTextRules) {
+        *self
+            .ocr_text_pipeline
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = Arc::new(OcrTextPipeline::from_rules(rules));
+    }
+
+    /// Applies `ocr_text_rules` from a storage policy object, if present.
+    pub(crate) fn apply_ocr_text_rules_from_policy(&self, policy: &JsonValue) {
+        let Some(value) = policy.get(OCR_TEXT_RULES_POLICY_KEY) else {
+            return;
+        };
+        match serde_json::from_value::<OcrTextRules>(value.clone()) {
+            Ok(rules) => self.set_ocr_text_rules(rules),
+            Err(e) => tracing::warn!("[OCR_TEXT] ignoring invalid ocr_text_rules: {}", e),
+        }
+    }
+
+    /// Runs the post-processing pipeline over OCR results before they are stored.
+    pub(crate) fn postprocess_ocr_results(
+        &self,
+        results: &[OcrResultInput],
+    ) -> Vec<OcrResultInput> {
+        let pipeline = self
+            .ocr_text_pipeline
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        results
+            .iter()
+            .map(|result| OcrResultInput {
+                text: pipeline.apply(&result.text),
+                confidence: result.confidence,
+                box_coords: result.box_coords.clone(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_pipeline_normalizes_mixed_cjk_latin_text() {
+        let pipeline = OcrTextPipeline::default();
+
+        assert_eq!(pipeline.apply("ＡＢＣ１２３　测试"), "ABC123 测试");
+        assert_eq!(pipeline.apply("ｶﾞｲﾄﾞ ﾊﾟﾝ"), "ガイド パン");
+        assert_eq!(
+            pipeline.apply("recog-\n  nition  of   text"),
+            "recognition of text"
+        );
+        assert_eq!(
+            pipeline.apply("  line one \n\n line two  "),
+            "line one\nline two"
+        );
+    }
+
+    #[test]
+    fn hyphen_merge_keeps_ranges_and_capitalized_lines() {
+        let stage = MergeHyphenatedLineBreaks;
+
+        assert_eq!(stage.apply("2020-\n2021"), "2020-\n2021");
+        assert_eq!(stage.apply("Intro-\nChapter"), "Intro-\nChapter");
+        assert_eq!(stage.apply("well-known"), "well-known");
+        assert_eq!(stage.apply("post-\r\nprocessing"), "postprocessing");
+    }
+
+    #[test]
+    fn disabled_rules_leave_text_untouched() {
+        let pipeline = OcrTextPipeline::from_rules(OcrTextRules {
+            enabled: false,
+            ..OcrTextRules::default()
+        });
+        assert_eq!(pipeline.apply("ＡＢ  c-\nd"), "ＡＢ  c-\nd");
+
+        let width_only = OcrTextPipeline::from_rules(OcrTextRules {
+            merge_hyphenated_line_breaks: false,
+            collapse_whitespace: false,
+            ..OcrTextRules::default()
+        });
+        assert_eq!(width_only.apply("ＡＢ  c-\nd"), "AB  c-\nd");
+    }
+}
@@ -0,0 +1,297 @@
+//! Paranoid mode: persist no plaintext metadata at all.
+//!
+//! Like compliance mode (which implies it), new rows keep the process name only in
+//! `process_name_enc` and get no pixelated preview, and the startup plaintext backfill
+//! stays off. Unlike compliance mode it leaves retention and the session timeout
+//! alone. Every row carries `process_name_hash`, an HMAC blind index of the process
+//! name, so process lists and per-process browsing group rows without decrypting
+//! each one: one row per distinct hash is decrypted to recover the name.
+
+use std::sync::atomic::Ordering;
+
+use rusqlite::params;
+use serde_json::Value as JsonValue;
+
+use super::{ParanoidModeStatus, StorageState};
+
+/// Policy key holding `{ "enabled": bool }`.
+pub(crate) const PARANOID_POLICY_KEY: &str = "paranoid_mode";
+/// Rows hashed per backfill batch.
+const PROCESS_HASH_BACKFILL_BATCH: i64 = 500;
+
+fn policy_paranoid_enabled(policy: &JsonValue) -> bool {
+    policy
+        .get(PARANOID_POLICY_KEY)
+        .and_then(|v| v.get("enabled"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+impl StorageState {
+    /// Returns whether paranoid mode was switched on (compliance mode aside).
+    pub fn paranoid_mode_enabled(&self) -> bool {
+        self.paranoid_mode.load(Ordering::SeqCst)
+    }
+
+    /// Whether new rows may carry plaintext metadata: off in paranoid and
+    /// compliance mode.
+    pub fn plaintext_metadata_allowed(&self) -> bool {
+        !self.paranoid_mode_enabled() && !self.compliance_mode_enabled()
+    }
+
+    /// Applies `paranoid_mode` from a storage policy object.
+    pub(crate) fn apply_paranoid_mode_from_policy(&self, policy: &JsonValue) {
+        self.paranoid_mode
+            .store(policy_paranoid_enabled(policy), Ordering::SeqCst);
+    }
+
+    /// Blind index of a process name; `None` for empty names or without an HMAC key.
+    pub(super) fn process_name_hash(&self, process_name: Option<&str>) -> Option<String> {
+        let name = process_name.filter(|name| !name.trim().is_empty())?;
+        let hmac_key = self.credential_state.get_hmac_key().ok()?;
+        Some(Self::compute_hmac_hash(
+            &format!("process:{}", name),
+            &hmac_key,
+        ))
+    }
+
+    /// Reports paranoid mode and the plaintext and unindexed rows that remain.
+    pub fn paranoid_mode_status(&self) -> Result<ParanoidModeStatus, String> {
+        let conn = self.open_read_connection_named("paranoid_mode_status")?;
+        let (plaintext_process_rows, unhashed_process_rows): (i64, i64) = conn
+            .query_row(
+                "SELECT COUNT(process_name),
+                        COUNT(CASE WHEN process_name_hash IS NULL
+                                    AND (process_name IS NOT NULL OR process_name_enc IS NOT NULL)
+                                   THEN 1 END)
+                 FROM screenshots",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|e| format!("Failed to count plaintext process names: {}", e))?;
+        Ok(ParanoidModeStatus {
+            enabled: self.paranoid_mode_enabled(),
+            compliance_mode: self.compliance_mode_enabled(),
+            plaintext_process_rows,
+            unhashed_process_rows,
+        })
+    }
+
+    /// Switches paranoid mode on or off.
+    ///
+    /// Turning it on hashes every process name, then encrypts and clears the
+    /// remaining plaintext names and drops stored previews; it needs an authenticated session for rows that
+    /// only have an encrypted name. Turning it off lets the startup backfill restore
+    /// plaintext names on the next launch unless compliance mode is on.
+    pub fn set_paranoid_mode(&self, enabled: bool) -> Result<ParanoidModeStatus, String> {
+        let mut policy = self.load_policy()?;
+        let obj = policy
+            .as_object_mut()
+            .ok_or_else(|| "Policy is not a valid JSON object".to_string())?;
+        if enabled {
+            obj.insert(
+                PARANOID_POLICY_KEY.into(),
+                serde_json::json!({ "enabled": true }),
+            );
+        } else {
+            obj.remove(PARANOID_POLICY_KEY);
+        }
+        self.save_policy(&policy)?;
+        self.apply_paranoid_mode_from_policy(&policy);
+
+        if enabled {
+            let hashed = self.backfill_process_name_hashes()?;
+            let cleared = self.drop_plaintext_process_names()?;
+            tracing::info!(
+                "[PARANOID] enabled: hashed {} process names, cleared {} plaintext values",
+                hashed,
+                cleared
+            );
+        } else if !self.compliance_mode_enabled() {
+            let guard = self.get_connection_named("set_paranoid_mode")?;
+            let conn = guard.as_ref().unwrap();
+            conn.execute(
+                "DELETE FROM app_metadata WHERE key = ?1",
+                params![Self::BACKFILL_PROCESS_NAMES_DONE_KEY],
+            )
+            .map_err(|e| format!("Failed to reset process name backfill: {}", e))?;
+            tracing::info!("[PARANOID] disabled");
+        }
+        self.paranoid_mode_status()
+    }
+
+    /// Fill `process_name_hash` for rows written before the blind index existed.
+    /// Rows with only an encrypted name are skipped without a valid session.
+    /// Returns the number of rows hashed.
+    pub(crate) fn backfill_process_name_hashes(&self) -> Result<usize, String> {
+        let can_decrypt = self.is_session_valid();
+        let mut hashed = 0usize;
+        let mut after_id = 0i64;
+        loop {
+            let rows: Vec<(i64, Option<String>, Option<Vec<u8>>, Option<Vec<u8>>)> = {
+                let conn = self.open_read_connection_named("backfill_process_name_hashes")?;
+                let mut stmt = conn
+                    .prepare(
+                        "SELECT id, process_name, process_name_enc, content_key_encrypted
+                         FROM screenshots
+                         WHERE id > ?1 AND process_name_hash IS NULL
+                           AND (process_name IS NOT NULL OR process_name_enc IS NOT NULL)
+                         ORDER BY id LIMIT ?2",
+                    )
+                    .map_err(|e| format!("Failed to prepare process hash backfill: {}", e))?;
+                let rows = stmt
+                    .query_map(params![after_id, PROCESS_HASH_BACKFILL_BATCH], |row| {
+                        Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+                    })
+                    .map_err(|e| format!("Failed to query process hash backfill: {}", e))?
+                    .filter_map(|r| r.ok())
+                    .collect();
+                rows
+            };
+            let Some(last) = rows.last() else {
+                break;
+            };
+            after_id = last.0;
+
+            let mut updates = Vec::with_capacity(rows.len());
+            for (id, plaintext, process_enc, key_enc) in rows {
+                let name = match (plaintext, process_enc, key_enc) {
+                    (Some(name), _, _) => Some(name),
+                    (None, Some(data), Some(key)) if can_decrypt => self
+                        .decrypt_payload_with_row_key(&data, &key)
+                        .ok()
+                        .and_then(|bytes| String::from_utf8(bytes).ok()),
+                    _ => None,
+                };
+                if let Some(hash) = self.process_name_hash(name.as_deref()) {
+                    updates.push((id, hash));
+                }
+            }
+            if updates.is_empty() {
+                continue;
+            }
+            let mut guard = self.get_connection_named("backfill_process_name_hashes")?;
+            let conn = guard.as_mut().unwrap();
+            let tx = conn
+                .transaction()
+                .map_err(|e| format!("Failed to start process hash backfill: {}", e))?;
+            // Hashing changes the rows' session grouping key.
+            Self::resegment_activity_sessions_from(&tx, updates[0].0)?;
+            for (id, hash) in &updates {
+                tx.execute(
+                    "UPDATE screenshots SET process_name_hash = ?1 WHERE id = ?2",
+                    params![hash, id],
+                )
+                .map_err(|e| format!("Failed to store process name hash: {}", e))?;
+            }
+            tx.commit()
+                .map_err(|e| format!("Failed to commit process hash backfill: {}", e))?;
+            hashed += updates.len();
+        }
+        Ok(hashed)
+    }
+
+    /// Encrypt plaintext-only process names and clear every plaintext value and
+    /// stored preview.
+    fn drop_plaintext_process_names(&self) -> Result<usize, String> {
+        let guard = self.get_connection_named("drop_plaintext_process_names")?;
+        let conn = guard.as_ref().unwrap();
+        let (encrypted, dropped) = self.encrypt_plaintext_only_process_names(conn)?;
+        let cleared = conn
+            .execute(
+                "UPDATE screenshots SET process_name = NULL
+                 WHERE process_name IS NOT NULL AND process_name_enc IS NOT NULL",
+                [],
+            )
+            .map_err(|e| format!("Failed to clear plaintext process names: {}", e))?;
+        super::preview::clear_stored_previews(conn)?;
+        // Rows that were never hashed lose their plaintext grouping key.
+        Self::resegment_activity_sessions_from(conn, 0)?;
+        Ok(encrypted + dropped + cleared)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{file_test_storage, test_storage};
+
+    #[test]
+    fn plaintext_is_disallowed_in_either_mode() {
+        let (_temp, storage) = test_storage();
+        assert!(storage.plaintext_metadata_allowed());
+
+        storage.apply_paranoid_mode_from_policy(&serde_json::json!({
+            PARANOID_POLICY_KEY: { "enabled": true }
+        }));
+        assert!(storage.paranoid_mode_enabled());
+        assert!(!storage.plaintext_metadata_allowed());
+
+        storage.apply_paranoid_mode_from_policy(&serde_json::json!({}));
+        assert!(storage.plaintext_metadata_allowed());
+    }
+
+    #[test]
+    fn enabling_clears_plaintext_and_previews_but_keeps_process_grouping() {
+        let (_temp, storage) = file_test_storage();
+        let code_hash = storage.process_name_hash(Some("Code.exe")).unwrap();
+        let chrome_hash = storage.process_name_hash(Some("chrome.exe")).unwrap();
+        {
+            let guard = storage.get_connection_named("test").unwrap();
+            let conn = guard.as_ref().unwrap();
+            conn.execute(
+                "INSERT INTO screenshots (id, image_path, image_hash, process_name,
+                    process_name_enc, process_name_hash, preview_image, created_at)
+                 VALUES (1, 'a', 'h1', 'Code.exe', X'01', ?1, X'89', '2026-01-01 10:00:00'),
+                        (2, 'b', 'h2', 'Code.exe', X'01', NULL, X'89', '2026-01-01 10:01:00'),
+                        (3, 'c', 'h3', 'chrome.exe', X'02', ?2, NULL, '2026-01-01 10:02:00')",
+                params![code_hash, chrome_hash],
+            )
+            .unwrap();
+        }
+
+        let status = storage.set_paranoid_mode(true).unwrap();
+        assert!(status.enabled);
+        assert_eq!(status.plaintext_process_rows, 0);
+        assert_eq!(status.unhashed_process_rows, 0);
+        {
+            let guard = storage.get_connection_named("test").unwrap();
+            let conn = guard.as_ref().unwrap();
+            let (names, previews, hashed): (i64, i64, i64) = conn
+                .query_row(
+                    "SELECT COUNT(process_name), COUNT(preview_image), COUNT(process_name_hash)
+                     FROM screenshots",
+                    [],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                )
+                .unwrap();
+            assert_eq!((names, previews, hashed), (0, 0, 3));
+        }
+
+        // Per-process browsing matches through the blind index.
+        let page = storage
+            .get_process_monthly_thumbnails("Code.exe", 0, 50)
+            .unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(
+            page.items
+                .iter()
+                .map(|item| item.screenshot_id)
+                .collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+
+        // Sessions still split per process without plaintext names.
+        let day_start = 1_767_225_600.0; // 2026-01-01T00:00:00Z
+        let sessions = storage
+            .get_activity_sessions(day_start, day_start + 86_400.0)
+            .unwrap();
+        assert_eq!(
+            sessions
+                .iter()
+                .map(|session| (session.first_screenshot_id, session.screenshot_count))
+                .collect::<Vec<_>>(),
+            vec![(1, 2), (3, 1)]
+        );
+    }
+}
@@ -0,0 +1,424 @@
+//! Searchable PDF export of screenshots.
+//!
+//! Each screenshot becomes one page: the image fills the page and every OCR box is
+//! laid over it as invisible text (render mode 3), stretched to the box with
+//! horizontal scaling, so viewers can select, copy and search it. Text uses a
+//! non-embedded CID font with Identity-H encoding and a `ToUnicode` map, which keeps
+//! the file small and covers any BMP character; glyphs are never drawn.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use chrono::Utc;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+
+use super::{OcrResult, PdfExportSummary, ScreenshotRecord, StorageState};
+
+/// Points per image pixel (a 96 DPI screen on a 72 DPI page).
+const PT_PER_PX: f64 = 0.75;
+const JPEG_QUALITY: u8 = 85;
+
+const CATALOG_ID: u32 = 1;
+const PAGES_ID: u32 = 2;
+const FONT_ID: u32 = 3;
+const CID_FONT_ID: u32 = 4;
+const FONT_DESCRIPTOR_ID: u32 = 5;
+const TO_UNICODE_ID: u32 = 6;
+const INFO_ID: u32 = 7;
+const FIRST_PAGE_OBJECT_ID: u32 = 8;
+
+/// Minimal PDF object writer that tracks offsets for the cross-reference table.
+struct PdfWriter<W: Write> {
+    out: W,
+    offset: usize,
+    xref: Vec<(u32, usize)>,
+}
+
+impl<W: Write> PdfWriter<W> {
+    fn new(out: W) -> Result<Self, String> {
+        let mut writer = Self {
+            out,
+            offset: 0,
+            xref: Vec::new(),
+        };
+        writer.write(b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n")?;
+        Ok(writer)
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), String> {
+        self.out
+            .write_all(bytes)
+            .map_err(|e| format!("Failed to write PDF: {}", e))?;
+        self.offset += bytes.len();
+        Ok(())
+    }
+
+    fn object(&mut self, id: u32, body: &str) -> Result<(), String> {
+        self.xref.push((id, self.offset));
+        self.write(format!("{} 0 obj\n{}\nendobj\n", id, body).as_bytes())
+    }
+
+    fn stream_object(&mut self, id: u32, dict: &str, data: &[u8]) -> Result<(), String> {
+        self.xref.push((id, self.offset));
+        self.write(
+            format!(
+                "{} 0 obj\n<< {} /Length {} >>\nstream\n",
+                id,
+                dict,
+                data.len()
+            )
+            .as_bytes(),
+        )?;
+        self.write(data)?;
+        self.write(b"\nendstream\nendobj\n")
+    }
+
+    /// Write the cross-reference table and trailer. Object ids must be contiguous
+    /// from 1.
+    fn finish(mut self) -> Result<W, String> {
+        self.xref.sort_unstable();
+        let size = self.xref.len() + 1;
+        let xref_offset = self.offset;
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", size);
+        for (_, offset) in &self.xref {
+            table.push_str(&format!("{:010} 00000 n \n", offset));
+        }
+        table.push_str(&format!(
+            "trailer\n<< /Size {} /Root {} 0 R /Info {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+            size, CATALOG_ID, INFO_ID, xref_offset
+        ));
+        self.write(table.as_bytes())?;
+        self.out
+            .flush()
+            .map_err(|e| format!("Failed to write PDF: {}", e))?;
+        Ok(self.out)
+    }
+}
+
+/// Identity `ToUnicode` map: each 2-byte code is the UTF-16 unit of the same value.
+fn to_unicode_cmap() -> String {
+    let ranges: Vec<String> = (0u32..=0xFF)
+        .filter(|hi| !(0xD8..=0xDF).contains(hi))
+        .map(|hi| format!("<{hi:02X}00> <{hi:02X}FF> <{hi:02X}00>", hi = hi))
+        .collect();
+    let mut cmap = String::from(
+        "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+         /CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+         1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n",
+    );
+    for chunk in ranges.chunks(100) {
+        cmap.push_str(&format!("{} beginbfrange\n", chunk.len()));
+        for range in chunk {
+            cmap.push_str(range);
+            cmap.push('\n');
+        }
+        cmap.push_str("endbfrange\n");
+    }
+    cmap.push_str("endcmap\nCMapName currentdict /CMapResource defineresource pop\nend\nend\n");
+    cmap
+}
+
+/// Invisible text operators for the OCR boxes of one page. `scale_x`/`scale_y` map
+/// OCR coordinates to pixels of the embedded image; `page_height` is in points.
+/// Returns the operators and the number of boxes written.
+fn text_layer(ocr: &[OcrResult], scale_x: f64, scale_y: f64, page_height: f64) -> (String, usize) {
+    let mut ops = String::new();
+    let mut written = 0;
+    for result in ocr {
+        if result.box_coords.len() < 4 || result.box_coords.iter().any(|p| p.len() < 2) {
+            continue;
+        }
+        let xs = result.box_coords.iter().map(|p| p[0] * scale_x * PT_PER_PX);
+        let ys = result.box_coords.iter().map(|p| p[1] * scale_y * PT_PER_PX);
+        let (x0, x1) = xs.fold((f64::MAX, f64::MIN), |(lo, hi), x| (lo.min(x), hi.max(x)));
+        let (y0, y1) = ys.fold((f64::MAX, f64::MIN), |(lo, hi), y| (lo.min(y), hi.max(y)));
+        let (width, font_size) = (x1 - x0, y1 - y0);
+        // Identity-H codes are UTF-16 units; characters outside the BMP are dropped.
+        let hex: String = result
+            .text
+            .chars()
+            .filter(|c| c.len_utf16() == 1 && !c.is_control())
+            .map(|c| format!("{:04X}", c as u32))
+            .collect();
+        let glyphs = hex.len() / 4;
+        if glyphs == 0 || width <= 0.0 || font_size <= 0.0 {
+            continue;
+        }
+        // Every glyph is 1 em wide (DW 1000); scale the run to the box width.
+        let horizontal_scale = 100.0 * width / (glyphs as f64 * font_size);
+        ops.push_str(&format!(
+            "BT\n3 Tr\n/F1 {:.2} Tf\n{:.2} Tz\n1 0 0 1 {:.2} {:.2} Tm\n<{}> Tj\nET\n",
+            font_size,
+            horizontal_scale,
+            x0,
+            page_height - y1,
+            hex
+        ));
+        written += 1;
+    }
+    (ops, written)
+}
+
+impl StorageState {
+    /// Write `screenshot_id`, or up to `limit` screenshots captured between `start_ts`
+    /// and `end_ts` (Unix seconds), to a searchable PDF at `output_path`, one page each.
+    pub fn export_screenshots_pdf(
+        &self,
+        screenshot_id: Option<i64>,
+        start_ts: f64,
+        end_ts: f64,
+        limit: i64,
+        output_path: &Path,
+    ) -> Result<PdfExportSummary, String> {
+        let records: Vec<ScreenshotRecord> = match screenshot_id {
+            Some(id) => self
+                .get_screenshot_by_id(id)?
+                .map(|record| vec![record])
+                .ok_or_else(|| format!("Screenshot {} not found", id))?,
+            None => self.get_screenshots_by_time_range_limited(start_ts, end_ts, Some(limit))?,
+        };
+        if records.is_empty() {
+            return Err("No screenshots to export".to_string());
+        }
+
+        if let Some(parent) = output_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create export directory: {}", e))?;
+        }
+        let file =
+            File::create(output_path).map_err(|e| format!("Failed to create PDF file: {}", e))?;
+        let mut pdf = PdfWriter::new(BufWriter::new(file))?;
+
+        let mut page_ids = Vec::new();
+        let mut exported_ids = Vec::new();
+        let mut text_boxes = 0usize;
+        let mut skipped = 0usize;
+        let mut next_id = FIRST_PAGE_OBJECT_ID;
+        for record in &records {
+            let page = match self.render_pdf_page(record) {
+                Ok(page) => page,
+                Err(error) => {
+                    tracing::warn!("Skipping screenshot {} in PDF export: {}", record.id, error);
+                    skipped += 1;
+                    continue;
+                }
+            };
+            let (page_id, content_id, image_id) = (next_id, next_id + 1, next_id + 2);
+            next_id += 3;
+            pdf.stream_object(
+                image_id,
+                &format!(
+                    "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB \
+                     /BitsPerComponent 8 /Filter /DCTDecode",
+                    page.width_px, page.height_px
+                ),
+                &page.jpeg,
+            )?;
+            pdf.stream_object(content_id, "/Filter /FlateDecode", &page.content)?;
+            pdf.object(
+                page_id,
+                &format!(
+                    "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {:.2} {:.2}] \
+                     /Resources << /Font << /F1 {} 0 R >> /XObject << /Im0 {} 0 R >> >> \
+                     /Contents {} 0 R >>",
+                    PAGES_ID, page.width_pt, page.height_pt, FONT_ID, image_id, content_id
+                ),
+            )?;
+            page_ids.push(page_id);
+            exported_ids.push(record.id);
+            text_boxes += page.text_boxes;
+        }
+        if page_ids.is_empty() {
+            drop(pdf);
+            let _ = std::fs::remove_file(output_path);
+            return Err("None of the screenshots could be exported".to_string());
+        }
+
+        let kids: Vec<String> = page_ids.iter().map(|id| format!("{} 0 R", id)).collect();
+        pdf.object(
+            PAGES_ID,
+            &format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                page_ids.len()
+            ),
+        )?;
+        pdf.object(
+            CATALOG_ID,
+            &format!("<< /Type /Catalog /Pages {} 0 R >>", PAGES_ID),
+        )?;
+        pdf.object(
+            FONT_ID,
+            &format!(
+                "<< /Type /Font /Subtype /Type0 /BaseFont /GlyphLessFont /Encoding /Identity-H \
+                 /DescendantFonts [{} 0 R] /ToUnicode {} 0 R >>",
+                CID_FONT_ID, TO_UNICODE_ID
+            ),
+        )?;
+        pdf.object(
+            CID_FONT_ID,
+            &format!(
+                "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /GlyphLessFont \
+                 /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> \
+                 /FontDescriptor {} 0 R /DW 1000 /CIDToGIDMap /Identity >>",
+                FONT_DESCRIPTOR_ID
+            ),
+        )?;
+        pdf.object(
+            FONT_DESCRIPTOR_ID,
+            "<< /Type /FontDescriptor /FontName /GlyphLessFont /Flags 5 \
+             /FontBBox [0 0 1000 1000] /ItalicAngle 0 /Ascent 1000 /Descent 0 \
+             /CapHeight 1000 /StemV 80 >>",
+        )?;
+        pdf.stream_object(TO_UNICODE_ID, "", to_unicode_cmap().as_bytes())?;
+        pdf.object(
+            INFO_ID,
+            &format!(
+                "<< /Producer (CarbonPaper) /CreationDate (D:{}Z) >>",
+                Utc::now().format("%Y%m%d%H%M%S")
+            ),
+        )?;
+        pdf.finish()?;
+
+        self.record_access_audit(
+            "export_pdf",
+            &serde_json::json!({
+                "path": output_path.display().to_string(),
+                "screenshot_ids": exported_ids,
+            }),
+        )?;
+        tracing::info!(
+            "Exported {} screenshots to PDF {}",
+            page_ids.len(),
+            output_path.display()
+        );
+        Ok(PdfExportSummary {
+            path: output_path.display().to_string(),
+            pages: page_ids.len(),
+            text_boxes,
+            skipped,
+        })
+    }
+
+    /// Decode, re-encode as JPEG and build the content stream of one page.
+    fn render_pdf_page(&self, record: &ScreenshotRecord) -> Result<PdfPage, String> {
+        let (bytes, _) = self.read_image_bytes(&record.image_path)?;
+        let rgb = image::load_from_memory(&bytes)
+            .map_err(|e| format!("Failed to decode screenshot image: {}", e))?
+            .to_rgb8();
+        let (width_px, height_px) = rgb.dimensions();
+        let mut jpeg = Vec::new();
+        image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, JPEG_QUALITY)
+            .encode_image(&rgb)
+            .map_err(|e| format!("Failed to encode page image: {}", e))?;
+
+        // OCR boxes are in capture coordinates; the stored image may be downscaled.
+        let scale = |stored: u32, captured: Option<i32>| match captured {
+            Some(captured) if captured > 0 => stored as f64 / captured as f64,
+            _ => 1.0,
+        };
+        let (width_pt, height_pt) = (width_px as f64 * PT_PER_PX, height_px as f64 * PT_PER_PX);
+        let ocr = self.get_screenshot_ocr_results(record.id, None)?;
+        let (text, text_boxes) = text_layer(
+            &ocr,
+            scale(width_px, record.width),
+            scale(height_px, record.height),
+            height_pt,
+        );
+        let mut content = format!("q {:.2} 0 0 {:.2} 0 0 cm /Im0 Do Q\n", width_pt, height_pt);
+        content.push_str(&text);
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(content.as_bytes())
+            .map_err(|e| format!("Failed to compress page content: {}", e))?;
+        let content = encoder
+            .finish()
+            .map_err(|e| format!("Failed to compress page content: {}", e))?;
+
+        Ok(PdfPage {
+            jpeg,
+            content,
+            width_px,
+            height_px,
+            width_pt,
+            height_pt,
+            text_boxes,
+        })
+    }
+}
+
+struct PdfPage {
+    jpeg: Vec<u8>,
+    content: Vec<u8>,
+    width_px: u32,
+    height_px: u32,
+    width_pt: f64,
+    height_pt: f64,
+    text_boxes: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ocr(text: &str, box_coords: Vec<Vec<f64>>) -> OcrResult {
+        OcrResult {
+            id: 1,
+            screenshot_id: 1,
+            text: text.to_string(),
+            confidence: 0.9,
+            box_coords,
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn text_layer_fits_boxes_and_skips_degenerate_ones() {
+        let boxes = vec![
+            ocr(
+                "Hi 发票",
+                vec![
+                    vec![100.0, 40.0],
+                    vec![300.0, 40.0],
+                    vec![300.0, 80.0],
+                    vec![100.0, 80.0],
+                ],
+            ),
+            ocr("flat", vec![vec![0.0, 10.0]; 4]),
+            ocr(
+                "\u{1F600}",
+                vec![
+                    vec![0.0, 0.0],
+                    vec![10.0, 10.0],
+                    vec![0.0, 10.0],
+                    vec![10.0, 0.0],
+                ],
+            ),
+        ];
+        let (ops, written) = text_layer(&boxes, 1.0, 1.0, 600.0);
+        assert_eq!(written, 1);
+        // 40px tall box -> 30pt font; 5 glyphs over 150pt -> 100% scale
+        assert!(ops.contains("/F1 30.00 Tf\n100.00 Tz\n1 0 0 1 75.00 540.00 Tm"));
+        assert!(ops.contains("<00480069002053D17968> Tj"));
+    }
+
+    #[test]
+    fn xref_offsets_point_at_objects() {
+        let mut pdf = PdfWriter::new(Vec::new()).unwrap();
+        for id in 1..=7 {
+            pdf.object(id, "<< >>").unwrap();
+        }
+        let bytes = pdf.finish().unwrap();
+        let xref_at = bytes.windows(5).position(|w| w == b"xref\n").unwrap();
+        let table = std::str::from_utf8(&bytes[xref_at..]).unwrap();
+        assert!(table.contains("/Size 8 /Root 1 0 R"));
+        for (id, line) in table.lines().skip(3).take(7).enumerate() {
+            let offset: usize = line[..10].parse().unwrap();
+            let expected = format!("{} 0 obj", id + 1);
+            assert!(bytes[offset..].starts_with(expected.as_bytes()));
+        }
+    }
+}
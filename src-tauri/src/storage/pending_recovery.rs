@@ -0,0 +1,250 @@
+//! Startup recovery of captures left half-finished by a crash.
+//!
+//! A capture is written as `*.enc.pending` with a `status = 'pending'` row and only
+//! renamed and marked `committed` once OCR returns. A crash in between leaves both
+//! behind, and aborted captures keep their row forever. `initialize()` runs this pass
+//! before capture starts:
+//!
+//! - a pending row whose image file survived is committed without OCR text and its OCR
+//!   status set to `failed`, which puts it in the failed-OCR backlog for re-running;
+//! - a pending row without its file, and every `aborted` row, is deleted;
+//! - a `.pending` file that no row points at is deleted.
+//!
+//! The summary of the last pass is kept for `storage_get_pending_recovery` and emitted
+//! as `storage-pending-recovery` once the app is set up.
+
+use rusqlite::params;
+use std::path::PathBuf;
+
+use super::{PendingRecoverySummary, StorageState};
+
+const PENDING_SUFFIX: &str = ".pending";
+/// Recovered ids listed in the summary; the rest are only counted.
+const MAX_LISTED_IDS: usize = 200;
+
+/// The committed file name for a `.pending` path.
+fn final_image_path(path: &std::path::Path) -> PathBuf {
+    match path.file_name().and_then(|s| s.to_str()) {
+        Some(name) if name.ends_with(PENDING_SUFFIX) => {
+            path.with_file_name(name.trim_end_matches(PENDING_SUFFIX))
+        }
+        _ => path.to_path_buf(),
+    }
+}
+
+impl StorageState {
+    /// Commit or delete stale pending captures and remove stray `.pending` files.
+    pub(super) fn recover_orphaned_pending(&self) -> Result<PendingRecoverySummary, String> {
+        let mut summary = PendingRecoverySummary::default();
+        let rows: Vec<(i64, String, String)> = {
+            let guard = self.get_connection_named("recover_orphaned_pending")?;
+            let conn = guard.as_ref().unwrap();
+            let mut stmt = conn
+                .prepare(
+                    "SELECT id, image_path, status FROM screenshots
+                     WHERE status IN ('pending', 'aborted') AND is_deleted = 0
+                     ORDER BY id",
+                )
+                .map_err(|e| format!("Failed to prepare pending recovery query: {}", e))?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+                .map_err(|e| format!("Failed to query pending screenshots: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows
+        };
+
+        for (id, image_path, status) in rows {
+            let abs_path = self.resolve_image_path(&image_path);
+            let final_path = final_image_path(&abs_path);
+            let file = [&abs_path, &final_path]
+                .into_iter()
+                .find(|path| path.is_file())
+                .cloned();
+            match file {
+                Some(file) if status == "pending" => {
+                    self.commit_recovered_pending(id, &file, &final_path)?;
+                    summary.committed += 1;
+                    if summary.requeued_ids.len() < MAX_LISTED_IDS {
+                        summary.requeued_ids.push(id);
+                    }
+                }
+                _ => {
+                    for path in [&abs_path, &final_path] {
+                        let _ = std::fs::remove_file(path);
+                    }
+                    let _ = std::fs::remove_file(Self::thumbnail_path_for(&final_path));
+                    let guard = self.get_connection_named("recover_orphaned_pending.delete")?;
+                    let conn = guard.as_ref().unwrap();
+                    conn.execute("DELETE FROM screenshots WHERE id = ?1", params![id])
+                        .map_err(|e| format!("Failed to delete pending screenshot: {}", e))?;
+                    summary.aborted += 1;
+                }
+            }
+        }
+
+        summary.orphan_files_removed = self.remove_unreferenced_pending_files()?;
+        if summary.committed + summary.aborted + summary.orphan_files_removed > 0 {
+            tracing::info!(
+                "[DIAG:STARTUP] pending recovery: committed={}, aborted={}, orphan_files_removed={}",
+                summary.committed,
+                summary.aborted,
+                summary.orphan_files_removed
+            );
+        }
+        Ok(summary)
+    }
+
+    /// Move a surviving pending file to its final name and mark the row committed
+    /// with a failed OCR status so it is picked up for another OCR run.
+    fn commit_recovered_pending(
+        &self,
+        id: i64,
+        file: &std::path::Path,
+        final_path: &std::path::Path,
+    ) -> Result<(), String> {
+        if file != final_path {
+            std::fs::rename(file, final_path)
+                .map_err(|e| format!("Failed to rename pending image file: {}", e))?;
+        }
+        let final_rel = self.to_relative_image_path(final_path);
+        let mut guard = self.get_connection_named("recover_orphaned_pending.commit")?;
+        let conn = guard.as_mut().unwrap();
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start pending recovery: {}", e))?;
+        tx.execute(
+            "UPDATE screenshots SET image_path = ?1, status = 'committed',
+                    committed_at = CURRENT_TIMESTAMP
+             WHERE id = ?2",
+            params![final_rel, id],
+        )
+        .map_err(|e| format!("Failed to commit recovered screenshot: {}", e))?;
+        tx.execute(
+            "INSERT INTO screenshot_ocr_status (screenshot_id, status, error, updated_at)
+             VALUES (?1, 'failed', 'Interrupted before OCR completed', CURRENT_TIMESTAMP)
+             ON CONFLICT(screenshot_id) DO UPDATE SET
+                status = 'failed',
+                error = excluded.error,
+                updated_at = CURRENT_TIMESTAMP",
+            params![id],
+        )
+        .map_err(|e| format!("Failed to queue recovered screenshot for OCR: {}", e))?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit pending recovery: {}", e))
+    }
+
+    /// Delete `.pending` files in the screenshot directory that no row refers to.
+    fn remove_unreferenced_pending_files(&self) -> Result<usize, String> {
+        let screenshot_dir = self
+            .screenshot_dir
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let Ok(entries) = std::fs::read_dir(&screenshot_dir) else {
+            return Ok(0);
+        };
+        let pending_files: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.is_file()
+                    && path
+                        .file_name()
+                        .and_then(|s| s.to_str())
+                        .is_some_and(|name| name.ends_with(PENDING_SUFFIX))
+            })
+            .collect();
+        if pending_files.is_empty() {
+            return Ok(0);
+        }
+
+        let guard = self.get_connection_named("remove_unreferenced_pending_files")?;
+        let conn = guard.as_ref().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT EXISTS(SELECT 1 FROM screenshots WHERE image_path = ?1)")
+            .map_err(|e| format!("Failed to prepare pending file query: {}", e))?;
+        let mut removed = 0;
+        for path in pending_files {
+            let rel = self.to_relative_image_path(&path);
+            let referenced: bool = stmt
+                .query_row(params![rel], |row| row.get(0))
+                .map_err(|e| format!("Failed to check pending file: {}", e))?;
+            if !referenced && std::fs::remove_file(&path).is_ok() {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Summary of the recovery pass run by the last `initialize()`.
+    pub fn pending_recovery_summary(&self) -> Option<PendingRecoverySummary> {
+        self.pending_recovery_summary
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_storage;
+
+    #[test]
+    fn stale_pending_rows_are_committed_or_deleted() {
+        let (temp, storage) = test_storage();
+        let screenshots = temp.path().join("screenshots");
+        std::fs::create_dir_all(&screenshots).unwrap();
+        std::fs::write(screenshots.join("a.png.enc.pending"), [1u8; 8]).unwrap();
+        std::fs::write(screenshots.join("stray.png.enc.pending"), [1u8; 8]).unwrap();
+
+        let guard = storage.db.lock().unwrap_or_else(|e| e.into_inner());
+        let connection = guard.as_ref().expect("test database");
+        connection
+            .execute_batch(
+                "INSERT INTO screenshots (id, image_path, image_hash, status, is_deleted) VALUES
+                    (1, 'screenshots/a.png.enc.pending', 'h1', 'pending', 0),
+                    (2, 'screenshots/b.png.enc.pending', 'h2', 'pending', 0),
+                    (3, 'screenshots/c.png.enc', 'h3', 'aborted', 0),
+                    (4, 'screenshots/d.png.enc', 'h4', 'committed', 0);",
+            )
+            .expect("pending fixture");
+        drop(guard);
+
+        let summary = storage.recover_orphaned_pending().expect("recovery");
+        assert_eq!(summary.committed, 1);
+        assert_eq!(summary.aborted, 2);
+        assert_eq!(summary.requeued_ids, vec![1]);
+        assert_eq!(summary.orphan_files_removed, 1);
+        assert!(!screenshots.join("stray.png.enc.pending").exists());
+        assert!(screenshots.join("a.png.enc").is_file());
+        assert!(!screenshots.join("a.png.enc.pending").exists());
+
+        let guard = storage.db.lock().unwrap();
+        let conn = guard.as_ref().unwrap();
+        let (path, status): (String, String) = conn
+            .query_row(
+                "SELECT image_path, status FROM screenshots WHERE id = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(
+            (path.as_str(), status.as_str()),
+            ("screenshots/a.png.enc", "committed")
+        );
+        let ocr_status: String = conn
+            .query_row(
+                "SELECT status FROM screenshot_ocr_status WHERE screenshot_id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(ocr_status, "failed");
+        let remaining: i64 = conn
+            .query_row("SELECT COUNT(*) FROM screenshots", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(remaining, 2);
+    }
+}
@@ -0,0 +1,159 @@
+//! Pinned (favorite) screenshots.
+//!
+//! Pinning sets `screenshots.pinned`. Retention pruning, quota and disk-pressure
+//! reclaim never select pinned rows, and time-range deletion keeps them unless the
+//! caller forces it. Deleting a single screenshot or a batch selection is an explicit
+//! choice and still removes pinned rows.
+
+use std::collections::HashMap;
+
+use rusqlite::{params, OptionalExtension};
+
+use super::{ScreenshotRecord, StorageState};
+
+impl StorageState {
+    /// Set the pinned flag of one screenshot. Returns `false` when it already had
+    /// that state.
+    pub fn set_screenshot_pinned(&self, screenshot_id: i64, pinned: bool) -> Result<bool, String> {
+        let guard = self.get_connection_named("set_screenshot_pinned")?;
+        let conn = guard.as_ref().unwrap();
+        let current: Option<bool> = conn
+            .query_row(
+                "SELECT pinned FROM screenshots WHERE id = ?1 AND is_deleted = 0",
+                params![screenshot_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to query screenshot: {}", e))?;
+        match current {
+            None => Err(format!("Screenshot {} not found", screenshot_id)),
+            Some(current) if current == pinned => Ok(false),
+            Some(_) => {
+                conn.execute(
+                    "UPDATE screenshots SET pinned = ?1 WHERE id = ?2",
+                    params![pinned, screenshot_id],
+                )
+                .map_err(|e| format!("Failed to update pinned flag: {}", e))?;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Whether a live screenshot is pinned; `false` when it does not exist.
+    pub fn is_screenshot_pinned(&self, screenshot_id: i64) -> Result<bool, String> {
+        let guard = self.get_connection_named("is_screenshot_pinned")?;
+        let conn = guard.as_ref().unwrap();
+        let pinned: Option<bool> = conn
+            .query_row(
+                "SELECT pinned FROM screenshots WHERE id = ?1 AND is_deleted = 0",
+                params![screenshot_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to query pinned flag: {}", e))?;
+        Ok(pinned.unwrap_or(false))
+    }
+
+    /// Pinned screenshots, newest first.
+    pub fn list_pinned(&self, limit: i64, offset: i64) -> Result<Vec<ScreenshotRecord>, String> {
+        let ids: Vec<i64> = {
+            let guard = self.get_connection_named("list_pinned")?;
+            let conn = guard.as_ref().unwrap();
+            let mut stmt = conn
+                .prepare(
+                    "SELECT id FROM screenshots
+                     WHERE pinned = 1 AND is_deleted = 0
+                     ORDER BY created_at DESC, id DESC
+                     LIMIT ?1 OFFSET ?2",
+                )
+                .map_err(|e| format!("Failed to prepare pinned query: {}", e))?;
+            let ids = stmt
+                .query_map(params![limit.clamp(1, 500), offset.max(0)], |row| {
+                    row.get(0)
+                })
+                .map_err(|e| format!("Failed to list pinned screenshots: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            ids
+        };
+
+        let order: HashMap<i64, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+        let mut records = self.get_screenshots_by_ids(&ids)?;
+        records.sort_by_key(|record| order.get(&record.id).copied().unwrap_or(usize::MAX));
+        Ok(records)
+    }
+
+    /// Pin or unpin `normalized_ids` inside the caller's transaction; returns the
+    /// number of screenshots changed.
+    pub(super) fn set_pinned_in_tx(
+        tx: &rusqlite::Transaction<'_>,
+        normalized_ids: &[i64],
+        pinned: bool,
+    ) -> Result<i64, String> {
+        let mut changed = 0i64;
+        for chunk in normalized_ids.chunks(500) {
+            let placeholders = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+            let sql = format!(
+                "UPDATE screenshots SET pinned = ? WHERE is_deleted = 0 AND pinned != ? AND id IN ({})",
+                placeholders
+            );
+            let mut params_ref: Vec<&dyn rusqlite::ToSql> = vec![&pinned, &pinned];
+            params_ref.extend(chunk.iter().map(|id| id as &dyn rusqlite::ToSql));
+            changed += tx
+                .execute(&sql, params_ref.as_slice())
+                .map_err(|e| format!("Failed to update pinned flags: {}", e))?
+                as i64;
+        }
+        Ok(changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_storage;
+
+    #[test]
+    fn pinned_screenshots_survive_retention_and_range_deletion() {
+        let (_temp, storage) = test_storage();
+        let guard = storage.db.lock().unwrap_or_else(|e| e.into_inner());
+        let connection = guard.as_ref().expect("test database");
+        connection
+            .execute_batch(
+                "INSERT INTO screenshots (id, image_path, image_hash, created_at, is_deleted) VALUES
+                    (1, 'a', 'h1', '2026-01-01 10:00:00', 0),
+                    (2, 'b', 'h2', '2026-01-01 11:00:00', 0),
+                    (3, 'c', 'h3', '2026-01-01 12:00:00', 0);",
+            )
+            .expect("pin fixture");
+        drop(guard);
+
+        assert!(storage.set_screenshot_pinned(2, true).unwrap());
+        assert!(!storage.set_screenshot_pinned(2, true).unwrap());
+        assert!(storage.is_screenshot_pinned(2).unwrap());
+        assert!(storage.set_screenshot_pinned(99, true).is_err());
+
+        let (retention_ids, _) = storage
+            .select_screenshots_created_before("2026-02-01 00:00:00", 100)
+            .unwrap();
+        assert_eq!(retention_ids, vec![1, 3]);
+
+        let start_ms = 1_767_225_600_000.0; // 2026-01-01T00:00:00Z
+        let end_ms = start_ms + 86_400_000.0;
+        let result = storage
+            .delete_screenshots_by_time_range(start_ms, end_ms, false, false)
+            .unwrap();
+        assert_eq!(result.deleted, 2);
+        assert_eq!(result.pinned_kept, 1);
+        let mut hashes = result.image_hashes;
+        hashes.sort();
+        assert_eq!(hashes, vec!["h1".to_string(), "h3".to_string()]);
+        assert!(storage.is_screenshot_pinned(2).unwrap());
+
+        let forced = storage
+            .delete_screenshots_by_time_range(start_ms, end_ms, true, false)
+            .unwrap();
+        assert_eq!(forced.deleted, 1);
+        assert!(!storage.is_screenshot_pinned(2).unwrap());
+    }
+}
@@ -2,18 +2,18 @@
 
 use chrono::{Duration, Utc};
 use serde_json::Value as JsonValue;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use sysinfo::Disks;
 use walkdir::WalkDir;
 
-use super::StorageState;
+use super::{QuotaEviction, RetentionPolicy, StorageQuota, StoragePolicyRun, StorageState};
 
 const GIB: u64 = 1024 * 1024 * 1024;
 const DISK_PRESSURE_TRIGGER_FREE_BYTES: u64 = 2 * GIB;
 const DISK_PRESSURE_SAFE_FREE_BYTES: u64 = 5 * GIB;
 const MAX_POLICY_DELETE_CANDIDATES_PER_RUN: i64 = 2_000;
 
-fn directory_size(path: &Path) -> u64 {
+pub(super) fn directory_size(path: &Path) -> u64 {
     WalkDir::new(path)
         .into_iter()
         .filter_map(Result::ok)
@@ -22,6 +22,14 @@ fn directory_size(path: &Path) -> u64 {
         .sum()
 }
 
+fn write_storage_limit(obj: &mut serde_json::Map<String, JsonValue>, gb: Option<u64>) {
+    let limit = match gb.filter(|gb| *gb > 0) {
+        Some(gb) => gb.to_string(),
+        None => "unlimited".to_string(),
+    };
+    obj.insert("storage_limit".into(), JsonValue::from(limit));
+}
+
 fn parse_storage_limit_bytes(policy: &JsonValue) -> Option<u64> {
     let raw = policy.get("storage_limit")?;
 
@@ -45,27 +53,45 @@ fn parse_storage_limit_bytes(policy: &JsonValue) -> Option<u64> {
     Some(gb.saturating_mul(GIB))
 }
 
-/// Resolve `retention_period` to a UTC cutoff datetime string
-/// (`%Y-%m-%d %H:%M:%S`, matching the `created_at` column). Snapshots created
-/// strictly before this value are considered expired. Returns `None` for
-/// `permanent`, empty, or unrecognized values (retention disabled).
-fn parse_retention_cutoff(policy: &JsonValue) -> Option<String> {
+/// Preset `retention_period` values and their day counts. Fixed-length
+/// approximations; a retention policy does not need calendar-exact months.
+const RETENTION_PRESETS: &[(&str, u32)] = &[
+    ("1month", 30),
+    ("6months", 180),
+    ("1year", 365),
+    ("2years", 730),
+];
+
+/// Resolve the maximum snapshot age in days. `retention_period` is either a
+/// preset, `custom` (read `retention_days`), or `permanent`/unrecognized
+/// (retention disabled).
+pub(super) fn parse_retention_days(policy: &JsonValue) -> Option<u32> {
     let key = match policy.get("retention_period")? {
         JsonValue::String(v) => v.trim().to_ascii_lowercase(),
         _ => return None,
     };
 
-    // Fixed-length day approximations; a retention policy does not need
-    // calendar-exact month boundaries.
-    let days = match key.as_str() {
-        "1month" => 30,
-        "6months" => 180,
-        "1year" => 365,
-        "2years" => 730,
-        _ => return None,
-    };
+    if key == "custom" {
+        return policy
+            .get("retention_days")
+            .and_then(|v| v.as_u64())
+            .and_then(|days| u32::try_from(days).ok())
+            .filter(|days| *days > 0);
+    }
 
-    let cutoff = Utc::now() - Duration::days(days);
+    RETENTION_PRESETS
+        .iter()
+        .find(|(preset, _)| *preset == key)
+        .map(|(_, days)| *days)
+}
+
+/// Resolve the retention policy to a UTC cutoff datetime string
+/// (`%Y-%m-%d %H:%M:%S`, matching the `created_at` column). Snapshots created
+/// strictly before this value are considered expired. Returns `None` when
+/// retention is disabled.
+fn parse_retention_cutoff(policy: &JsonValue) -> Option<String> {
+    let days = parse_retention_days(policy)?;
+    let cutoff = Utc::now() - Duration::days(i64::from(days));
     Some(cutoff.format("%Y-%m-%d %H:%M:%S").to_string())
 }
 
@@ -98,8 +124,8 @@ fn disk_totals_for_path(path: &Path) -> Option<(u64, u64)> {
 }
 
 impl StorageState {
-    /// Save storage policy to storage_policy.json in the app config directory.
-    pub fn save_policy(&self, policy: &JsonValue) -> Result<(), String> {
+    /// storage_policy.json in the app config directory, the parent of the data directory.
+    pub(super) fn policy_path(&self) -> PathBuf {
         let mut cfg_dir = self
             .data_dir
             .lock()
@@ -108,7 +134,12 @@ impl StorageState {
         if let Some(parent) = cfg_dir.parent() {
             cfg_dir = parent.to_path_buf();
         }
-        let policy_path = cfg_dir.join("storage_policy.json");
+        cfg_dir.join("storage_policy.json")
+    }
+
+    /// Save storage policy to storage_policy.json in the app config directory.
+    pub fn save_policy(&self, policy: &JsonValue) -> Result<(), String> {
+        let policy_path = self.policy_path();
 
         let s =
             serde_json::to_string_pretty(policy).map_err(|e| format!("serde json error: {}", e))?;
@@ -117,15 +148,7 @@ impl StorageState {
 
     /// Load storage policy from storage_policy.json. Returns empty object if file doesn't exist.
     pub fn load_policy(&self) -> Result<JsonValue, String> {
-        let mut cfg_dir = self
-            .data_dir
-            .lock()
-            .unwrap_or_else(|e| e.into_inner())
-            .clone();
-        if let Some(parent) = cfg_dir.parent() {
-            cfg_dir = parent.to_path_buf();
-        }
-        let policy_path = cfg_dir.join("storage_policy.json");
+        let policy_path = self.policy_path();
 
         if !policy_path.exists() {
             return Ok(serde_json::json!({}));
@@ -138,16 +161,95 @@ impl StorageState {
         Ok(v)
     }
 
+    /// Read the retention limits from the stored policy.
+    pub fn retention_policy(&self) -> Result<RetentionPolicy, String> {
+        let policy = self.load_policy()?;
+        Ok(RetentionPolicy {
+            max_age_days: parse_retention_days(&policy),
+            max_total_gb: parse_storage_limit_bytes(&policy).map(|bytes| bytes / GIB),
+        })
+    }
+
+    /// Persist retention limits. Preset ages keep their `retention_period`
+    /// name so the settings page still recognises them; other values are
+    /// stored as `custom` with an explicit `retention_days`.
+    pub fn set_retention_policy(&self, retention: RetentionPolicy) -> Result<(), String> {
+        let mut policy = self.load_policy()?;
+        let obj = policy
+            .as_object_mut()
+            .ok_or_else(|| "Policy is not a valid JSON object".to_string())?;
+
+        match retention.max_age_days.filter(|days| *days > 0) {
+            None => {
+                obj.insert("retention_period".into(), JsonValue::from("permanent"));
+                obj.remove("retention_days");
+            }
+            Some(days) => {
+                match RETENTION_PRESETS.iter().find(|(_, preset)| *preset == days) {
+                    Some((name, _)) => {
+                        obj.insert("retention_period".into(), JsonValue::from(*name));
+                        obj.remove("retention_days");
+                    }
+                    None => {
+                        obj.insert("retention_period".into(), JsonValue::from("custom"));
+                        obj.insert("retention_days".into(), JsonValue::from(days));
+                    }
+                }
+            }
+        }
+
+        write_storage_limit(obj, retention.max_total_gb);
+
+        self.check_policy_compliant(&policy)?;
+        self.save_policy(&policy)
+    }
+
+    /// Read the screenshot storage quota and the current size of the screenshots directory.
+    pub fn storage_quota(&self) -> Result<StorageQuota, String> {
+        let policy = self.load_policy()?;
+        let screenshot_dir = self
+            .screenshot_dir
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        Ok(StorageQuota {
+            quota_gb: parse_storage_limit_bytes(&policy).map(|bytes| bytes / GIB),
+            usage_bytes: if screenshot_dir.exists() {
+                directory_size(&screenshot_dir)
+            } else {
+                0
+            },
+        })
+    }
+
+    /// Persist the screenshot storage quota in GB; `None` or `0` removes it.
+    /// Eviction happens on the next `storage_policy` scheduled job.
+    pub fn set_storage_quota(&self, quota_gb: Option<u64>) -> Result<StorageQuota, String> {
+        let mut policy = self.load_policy()?;
+        let obj = policy
+            .as_object_mut()
+            .ok_or_else(|| "Policy is not a valid JSON object".to_string())?;
+        write_storage_limit(obj, quota_gb);
+        self.save_policy(&policy)?;
+        self.storage_quota()
+    }
+
     /// Enforce snapshot storage policy once.
     ///
     /// Policy includes:
-    /// 1) Retention (`retention_period`): prune snapshots older than the
-    ///    configured age (1 month / 6 months / 1 year / 2 years).
+    /// 1) Retention (`retention_period` / `retention_days`): prune snapshots older
+    ///    than the configured age.
     /// 2) User snapshot cap (`storage_limit` in GB): prune oldest snapshots beyond cap.
     /// 3) Disk pressure fallback: if free space gets too low, prune to a safe free-space value.
     ///
     /// Returns a summary string when pruning is enqueued, otherwise `None`.
     pub fn enforce_snapshot_storage_policy_once(&self) -> Result<Option<String>, String> {
+        self.enforce_snapshot_storage_policy().map(|run| run.summary)
+    }
+
+    /// Like [`Self::enforce_snapshot_storage_policy_once`], but also reports quota
+    /// evictions so the caller can notify the frontend.
+    pub fn enforce_snapshot_storage_policy(&self) -> Result<StoragePolicyRun, String> {
         let policy = self.load_policy()?;
 
         let screenshot_dir = self
@@ -164,6 +266,19 @@ impl StorageState {
         let mut reasons: Vec<String> = Vec::new();
         let mut total_marked = 0i64;
 
+        // 0) Screenshots left in the trash past `trash_retention_days` are queued
+        //    for permanent deletion.
+        let trash_purged = self.purge_expired_trash()?;
+        if trash_purged > 0 {
+            reasons.push(format!("trash_expired(queued={})", trash_purged));
+        }
+
+        // Delta-frame keyframes whose patches are all gone are removed.
+        let keyframes_pruned = self.prune_unused_keyframes()?;
+        if keyframes_pruned > 0 {
+            reasons.push(format!("keyframes_pruned(removed={})", keyframes_pruned));
+        }
+
         // 1) Age-based retention. Snapshots older than the cutoff are pruned
         //    regardless of how much space they occupy.
         let mut retention_freed_bytes = 0u64;
@@ -195,9 +310,11 @@ impl StorageState {
         let effective_images_bytes = current_images_bytes.saturating_sub(retention_freed_bytes);
 
         let mut required_reclaim_bytes = 0u64;
+        let mut quota_exceeded: Option<u64> = None;
 
         if let Some(limit_bytes) = parse_storage_limit_bytes(&policy) {
             if effective_images_bytes > limit_bytes {
+                quota_exceeded = Some(limit_bytes);
                 let exceed = effective_images_bytes.saturating_sub(limit_bytes);
                 required_reclaim_bytes = required_reclaim_bytes.max(exceed);
                 reasons.push(format!(
@@ -231,6 +348,7 @@ impl StorageState {
             }
         }
 
+        let mut quota_eviction = None;
         if required_reclaim_bytes > 0 {
             let (candidate_ids, estimated_reclaim_bytes) = self
                 .select_oldest_screenshots_for_reclaim(
@@ -245,25 +363,37 @@ impl StorageState {
                         "cap_reclaim(queued={}, estimated_reclaim={} bytes, target_reclaim={} bytes)",
                         result.screenshots_marked, estimated_reclaim_bytes, required_reclaim_bytes
                     ));
+                    quota_eviction = quota_exceeded.map(|quota_bytes| QuotaEviction {
+                        evicted: result.screenshots_marked,
+                        estimated_freed_bytes: estimated_reclaim_bytes,
+                        usage_bytes: effective_images_bytes,
+                        quota_bytes,
+                    });
                 }
             }
         }
 
         if total_marked == 0 {
-            return Ok(None);
+            return Ok(StoragePolicyRun::default());
         }
 
-        Ok(Some(format!(
-            "queued {} screenshots ({})",
-            total_marked,
-            reasons.join("; ")
-        )))
+        Ok(StoragePolicyRun {
+            summary: Some(format!(
+                "queued {} screenshots ({})",
+                total_marked,
+                reasons.join("; ")
+            )),
+            quota_eviction,
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::parse_retention_cutoff;
+    use super::{
+        parse_retention_cutoff, parse_retention_days, parse_storage_limit_bytes,
+        write_storage_limit, GIB,
+    };
     use chrono::{Duration, NaiveDateTime, Utc};
     use serde_json::json;
 
@@ -322,4 +452,34 @@ mod tests {
             assert!(skew <= 5, "{period}: cutoff off by {skew}s");
         }
     }
+
+    #[test]
+    fn storage_limit_writer_round_trips_quota() {
+        let mut policy = json!({});
+        let obj = policy.as_object_mut().unwrap();
+        write_storage_limit(obj, Some(20));
+        assert_eq!(policy["storage_limit"], "20");
+        assert_eq!(parse_storage_limit_bytes(&policy), Some(20 * GIB));
+
+        for cleared in [None, Some(0)] {
+            write_storage_limit(policy.as_object_mut().unwrap(), cleared);
+            assert_eq!(policy["storage_limit"], "unlimited");
+            assert_eq!(parse_storage_limit_bytes(&policy), None);
+        }
+    }
+
+    #[test]
+    fn custom_period_reads_retention_days() {
+        let policy = json!({ "retention_period": "custom", "retention_days": 14 });
+        assert_eq!(parse_retention_days(&policy), Some(14));
+
+        // Stale `retention_days` is ignored once a preset or `permanent` is chosen.
+        let preset = json!({ "retention_period": "1year", "retention_days": 14 });
+        assert_eq!(parse_retention_days(&preset), Some(365));
+        let permanent = json!({ "retention_period": "permanent", "retention_days": 14 });
+        assert_eq!(parse_retention_days(&permanent), None);
+
+        let invalid = json!({ "retention_period": "custom", "retention_days": 0 });
+        assert_eq!(parse_retention_days(&invalid), None);
+    }
 }
@@ -0,0 +1,251 @@
+//! Pixelated privacy previews that can be shown before the user unlocks.
+//!
+//! Full images and thumbnails are encrypted with per-row keys that need an
+//! authenticated session. At save time we additionally keep a tiny downscaled copy
+//! (at most `PREVIEW_MAX_SIDE` pixels on its longest side) in `screenshots.preview_image`,
+//! protected only by the database key like other non-sensitive metadata. At that
+//! size text is unreadable, but layout and colours stay recognizable. Paranoid and
+//! compliance mode neither store new previews nor serve previews kept from before.
+
+use std::io::Cursor;
+
+use image::imageops::FilterType;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::{InlineThumbnail, StorageState};
+
+/// Longest side of the stored preview, in pixels.
+const PREVIEW_MAX_SIDE: u32 = 64;
+/// Longest side of the pixelated image returned to the frontend.
+const PREVIEW_OUTPUT_SIDE: u32 = 256;
+/// Highest accepted `blur_level`; each level halves the number of blocks.
+const MAX_PREVIEW_BLUR_LEVEL: u8 = 3;
+
+fn encode_png(img: &image::DynamicImage) -> Result<Vec<u8>, String> {
+    let mut buf = Cursor::new(Vec::new());
+    img.write_to(&mut buf, image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode preview: {}", e))?;
+    Ok(buf.into_inner())
+}
+
+/// Downscale captured image bytes to the stored preview size.
+pub(super) fn build_privacy_preview(image_bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let img = image::load_from_memory(image_bytes)
+        .map_err(|e| format!("Failed to decode image for preview: {}", e))?;
+    let small = img.resize(PREVIEW_MAX_SIDE, PREVIEW_MAX_SIDE, FilterType::Triangle);
+    encode_png(&image::DynamicImage::ImageRgb8(small.to_rgb8()))
+}
+
+/// Pixelate a stored preview: level 0 keeps its full block grid, each further level
+/// halves it. The result is upscaled with nearest-neighbour so blocks stay crisp.
+fn pixelate_preview(preview_png: &[u8], blur_level: u8) -> Result<Vec<u8>, String> {
+    let img = image::load_from_memory(preview_png)
+        .map_err(|e| format!("Failed to decode stored preview: {}", e))?;
+    let divisor = 1u32 << blur_level.min(MAX_PREVIEW_BLUR_LEVEL);
+    let blocks_w = (img.width() / divisor).max(1);
+    let blocks_h = (img.height() / divisor).max(1);
+    let blocks = img.resize_exact(blocks_w, blocks_h, FilterType::Triangle);
+
+    let scale = PREVIEW_OUTPUT_SIDE as f64 / img.width().max(img.height()).max(1) as f64;
+    let out_w = ((img.width() as f64 * scale).round() as u32).max(1);
+    let out_h = ((img.height() as f64 * scale).round() as u32).max(1);
+    encode_png(&blocks.resize_exact(out_w, out_h, FilterType::Nearest))
+}
+
+/// Drops every stored preview. Run when paranoid or compliance mode is turned on so
+/// no image-derived data stays readable without a session.
+pub(super) fn clear_stored_previews(conn: &Connection) -> Result<usize, String> {
+    conn.execute(
+        "UPDATE screenshots SET preview_image = NULL WHERE preview_image IS NOT NULL",
+        [],
+    )
+    .map_err(|e| format!("Failed to clear image previews: {}", e))
+}
+
+impl StorageState {
+    /// Rejects preview reads without an unlocked session while compliance mode is on.
+    pub fn check_preview_access(&self) -> Result<(), String> {
+        if self.compliance_mode_enabled() && !self.is_session_valid() {
+            return Err("AUTH_REQUIRED".to_string());
+        }
+        Ok(())
+    }
+
+    /// Stored preview for a new capture, or `None` in paranoid or compliance mode or
+    /// when the image cannot be decoded.
+    pub(super) fn privacy_preview_for_save(&self, image_bytes: &[u8]) -> Option<Vec<u8>> {
+        if !self.plaintext_metadata_allowed() {
+            return None;
+        }
+        match build_privacy_preview(image_bytes) {
+            Ok(preview) => Some(preview),
+            Err(e) => {
+                tracing::warn!("{}", e);
+                None
+            }
+        }
+    }
+
+    /// Pixelated PNG preview of screenshot `id`, readable without an unlocked session.
+    ///
+    /// Screenshots saved before previews existed have none until they are backfilled
+    /// from the full image, which only happens while the session is unlocked; until
+    /// then this returns `None`. Also `None` in paranoid or compliance mode.
+    pub fn get_image_preview(
+        &self,
+        id: i64,
+        blur_level: u8,
+    ) -> Result<Option<InlineThumbnail>, String> {
+        if !self.plaintext_metadata_allowed() {
+            return Ok(None);
+        }
+        let (stored, image_path): (Option<Vec<u8>>, String) = {
+            let guard = self.get_connection_named("get_image_preview")?;
+            let conn = guard.as_ref().unwrap();
+            match conn
+                .query_row(
+                    "SELECT preview_image, image_path FROM screenshots
+                     WHERE id = ?1 AND is_deleted = 0",
+                    params![id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()
+                .map_err(|e| format!("Failed to query image preview: {}", e))?
+            {
+                Some(row) => row,
+                None => return Ok(None),
+            }
+        };
+
+        let preview = match stored {
+            Some(preview) => preview,
+            None => match self.backfill_image_preview(id, &image_path)? {
+                Some(preview) => preview,
+                None => return Ok(None),
+            },
+        };
+
+        let pixelated = pixelate_preview(&preview, blur_level)?;
+        Ok(Some(InlineThumbnail {
+            data: base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &pixelated),
+            mime_type: "image/png".to_string(),
+        }))
+    }
+
+    fn backfill_image_preview(&self, id: i64, image_path: &str) -> Result<Option<Vec<u8>>, String> {
+        if !self.is_session_valid() || !self.plaintext_metadata_allowed() {
+            return Ok(None);
+        }
+        let image_bytes = match self.read_image_bytes_silent(image_path) {
+            Ok((bytes, _)) => bytes,
+            Err(e) => {
+                tracing::debug!("Cannot backfill preview for screenshot {}: {}", id, e);
+                return Ok(None);
+            }
+        };
+        let preview = build_privacy_preview(&image_bytes)?;
+        if !self.is_read_only() {
+            let guard = self.get_connection_named("backfill_image_preview")?;
+            let conn = guard.as_ref().unwrap();
+            conn.execute(
+                "UPDATE screenshots SET preview_image = ?1 WHERE id = ?2",
+                params![&preview, id],
+            )
+            .map_err(|e| format!("Failed to store image preview: {}", e))?;
+        }
+        Ok(Some(preview))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screenshot_png() -> Vec<u8> {
+        let img = image::RgbImage::from_fn(1920, 1080, |x, y| {
+            if (y / 12) % 2 == 0 && (x / 7) % 2 == 0 {
+                image::Rgb([10, 10, 10])
+            } else {
+                image::Rgb([240, (x % 256) as u8, (y % 256) as u8])
+            }
+        });
+        let mut buf = Cursor::new(Vec::new());
+        img.write_to(&mut buf, image::ImageFormat::Png)
+            .expect("encode fixture png");
+        buf.into_inner()
+    }
+
+    #[test]
+    fn preview_is_tiny_and_pixelation_coarsens_with_level() {
+        let preview = build_privacy_preview(&screenshot_png()).unwrap();
+        let stored = image::load_from_memory(&preview).unwrap();
+        assert_eq!((stored.width(), stored.height()), (64, 36));
+
+        let count_colors = |png: &[u8]| {
+            let img = image::load_from_memory(png).unwrap().to_rgb8();
+            img.pixels()
+                .map(|p| p.0)
+                .collect::<std::collections::HashSet<_>>()
+                .len()
+        };
+        let fine = pixelate_preview(&preview, 0).unwrap();
+        let coarse = pixelate_preview(&preview, MAX_PREVIEW_BLUR_LEVEL + 5).unwrap();
+        let out = image::load_from_memory(&coarse).unwrap();
+        assert_eq!((out.width(), out.height()), (256, 144));
+        assert!(count_colors(&coarse) <= 8 * 4);
+        assert!(count_colors(&coarse) < count_colors(&fine));
+    }
+
+    #[test]
+    fn stored_preview_is_withheld_once_compliance_mode_is_on() {
+        let (_temp, storage) = crate::storage::test_storage();
+        let preview = build_privacy_preview(&screenshot_png()).unwrap();
+        {
+            let guard = storage.get_connection_named("test").unwrap();
+            let conn = guard.as_ref().unwrap();
+            conn.execute(
+                "INSERT INTO screenshots (id, image_path, image_hash, preview_image)
+                 VALUES (1, 'a.enc', 'h1', ?1)",
+                params![&preview],
+            )
+            .unwrap();
+        }
+        assert!(storage.get_image_preview(1, 0).unwrap().is_some());
+
+        storage.apply_compliance_mode_from_policy(&serde_json::json!({
+            "compliance_mode": { "enabled": true },
+        }));
+        assert!(storage.get_image_preview(1, 0).unwrap().is_none());
+    }
+
+    #[test]
+    fn compliance_mode_clears_previews_and_rejects_locked_reads() {
+        let (_temp, storage) = crate::storage::test_storage();
+        let preview = build_privacy_preview(&screenshot_png()).unwrap();
+        {
+            let guard = storage.get_connection_named("test").unwrap();
+            let conn = guard.as_ref().unwrap();
+            conn.execute(
+                "INSERT INTO screenshots (id, image_path, image_hash, preview_image)
+                 VALUES (1, 'a.enc', 'h1', ?1), (2, 'b.enc', 'h2', NULL)",
+                params![&preview],
+            )
+            .unwrap();
+            assert_eq!(clear_stored_previews(conn).unwrap(), 1);
+            let remaining: i64 = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM screenshots WHERE preview_image IS NOT NULL",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(remaining, 0);
+        }
+
+        assert!(storage.check_preview_access().is_ok());
+        storage.apply_compliance_mode_from_policy(&serde_json::json!({
+            "compliance_mode": { "enabled": true },
+        }));
+        assert_eq!(storage.check_preview_access().unwrap_err(), "AUTH_REQUIRED");
+    }
+}
@@ -1,4 +1,8 @@
 //! List distinct processes (two-phase: SQL aggregation + offline decryption of old records).
+//!
+//! Rows stored without a plaintext `process_name` (paranoid or compliance mode) are
+//! grouped by their `process_name_hash` blind index, so only one row per process has
+//! to be decrypted to label the group.
 
 use crate::credential_manager::{decrypt_row_key_with_cng, decrypt_with_master_key};
 
@@ -14,7 +18,7 @@ impl StorageState {
         // Phase 1: SQL aggregation + extract encrypted-only rows (hold mutex)
         let (mut counts, encrypted_rows): (
             std::collections::HashMap<String, i64>,
-            Vec<(Option<Vec<u8>>, Option<Vec<u8>>)>,
+            Vec<(Option<Vec<u8>>, Option<Vec<u8>>, i64)>,
         ) = {
             let guard = self.get_connection_named("list_distinct_processes")?;
             let conn = guard.as_ref().unwrap();
@@ -38,26 +42,53 @@ impl StorageState {
                 counts.insert(row.0, row.1);
             }
 
-            // Slow path: collect encrypted-only rows for offline decryption
-            let mut enc_stmt = conn
+            // Hashed path: one representative encrypted row per blind-index group
+            let mut hash_stmt = conn
                 .prepare(
-                    "SELECT process_name_enc, content_key_encrypted FROM screenshots
-                                 WHERE is_deleted = 0
-                                     AND process_name IS NULL
-                                     AND process_name_enc IS NOT NULL",
+                    "SELECT process_name_enc, content_key_encrypted, COUNT(*), MAX(id)
+                     FROM screenshots
+                     WHERE is_deleted = 0
+                         AND process_name IS NULL
+                         AND process_name_hash IS NOT NULL
+                         AND process_name_enc IS NOT NULL
+                     GROUP BY process_name_hash",
                 )
-                .map_err(|e| format!("Failed to prepare enc query: {}", e))?;
-            let enc_rows: Vec<_> = enc_stmt
+                .map_err(|e| format!("Failed to prepare hashed query: {}", e))?;
+            let mut enc_rows: Vec<_> = hash_stmt
                 .query_map([], |row| {
                     Ok((
                         row.get::<_, Option<Vec<u8>>>(0)?,
                         row.get::<_, Option<Vec<u8>>>(1)?,
+                        row.get::<_, i64>(2)?,
                     ))
                 })
-                .map_err(|e| format!("Failed to execute enc query: {}", e))?
+                .map_err(|e| format!("Failed to execute hashed query: {}", e))?
                 .filter_map(|r| r.ok())
                 .collect();
 
+            // Slow path: collect unhashed encrypted-only rows for offline decryption
+            let mut enc_stmt = conn
+                .prepare(
+                    "SELECT process_name_enc, content_key_encrypted FROM screenshots
+                                 WHERE is_deleted = 0
+                                     AND process_name IS NULL
+                                     AND process_name_hash IS NULL
+                                     AND process_name_enc IS NOT NULL",
+                )
+                .map_err(|e| format!("Failed to prepare enc query: {}", e))?;
+            enc_rows.extend(
+                enc_stmt
+                    .query_map([], |row| {
+                        Ok((
+                            row.get::<_, Option<Vec<u8>>>(0)?,
+                            row.get::<_, Option<Vec<u8>>>(1)?,
+                            1,
+                        ))
+                    })
+                    .map_err(|e| format!("Failed to execute enc query: {}", e))?
+                    .filter_map(|r| r.ok()),
+            );
+
             (counts, enc_rows)
             // guard dropped — mutex released
         };
@@ -67,7 +98,7 @@ impl StorageState {
         let session_valid = self.credential_state.is_session_valid();
         let skipped_encrypted = !session_valid && !encrypted_rows.is_empty();
         if session_valid {
-            for (process_enc, key_enc) in &encrypted_rows {
+            for (process_enc, key_enc, row_count) in &encrypted_rows {
                 let mut row_key = key_enc
                     .as_ref()
                     .and_then(|enc| decrypt_row_key_with_cng(enc).ok());
@@ -79,7 +110,7 @@ impl StorageState {
                 };
                 if let Some(name) = process_name {
                     if !name.trim().is_empty() {
-                        *counts.entry(name).or_insert(0) += 1;
+                        *counts.entry(name).or_insert(0) += row_count;
                     }
                 }
                 if let Some(ref mut key) = row_key {
@@ -150,13 +181,17 @@ impl StorageState {
         let safe_page_size = page_size.clamp(1, 200);
         let offset = safe_page * safe_page_size;
 
+        let process_hash = self.process_name_hash(Some(process_name));
+
         let guard = self.get_connection_named("get_process_monthly_thumbnails")?;
         let conn = guard.as_ref().unwrap();
 
         let total: i64 = conn
             .query_row(
-                "SELECT COUNT(*) FROM screenshots WHERE is_deleted = 0 AND process_name = ?1",
-                [process_name],
+                "SELECT COUNT(*) FROM screenshots
+                 WHERE is_deleted = 0
+                   AND (process_name = ?1 OR (process_name IS NULL AND process_name_hash = ?2))",
+                rusqlite::params![process_name, process_hash],
                 |row| row.get(0),
             )
             .map_err(|e| format!("Failed to count process screenshots: {}", e))?;
@@ -165,7 +200,8 @@ impl StorageState {
             .prepare(
                 "SELECT id, image_path, created_at, strftime('%s', created_at) AS ts, strftime('%Y-%m', created_at) AS month_key
                  FROM screenshots
-                 WHERE is_deleted = 0 AND process_name = ?1
+                 WHERE is_deleted = 0
+                   AND (process_name = ?1 OR (process_name IS NULL AND process_name_hash = ?4))
                  ORDER BY created_at DESC
                  LIMIT ?2 OFFSET ?3",
             )
@@ -177,6 +213,7 @@ impl StorageState {
                     &process_name as &dyn rusqlite::ToSql,
                     &safe_page_size,
                     &offset,
+                    &process_hash,
                 ],
                 |row| {
                     let ts_raw: Option<String> = row.get(3)?;
@@ -0,0 +1,159 @@
+//! Protected time ranges.
+//!
+//! A protected range keeps every screenshot captured inside it, e.g. the evidence of a
+//! transaction. Age-based retention, quota and disk-pressure reclaim never select
+//! those screenshots, and time-range deletion keeps them unless the caller forces it.
+//! Deleting a single screenshot or a batch selection is an explicit choice and still
+//! removes them, as for pinned screenshots.
+
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+
+use super::{ProtectedRange, StorageState};
+
+/// SQL condition that holds for a `screenshots` row inside a protected range.
+pub(super) const PROTECTED_RANGE_MATCH: &str = "EXISTS (
+    SELECT 1 FROM protected_ranges p
+    WHERE screenshots.created_at BETWEEN p.start_at AND p.end_at
+)";
+
+/// Longest accepted label, in characters.
+const MAX_LABEL_CHARS: usize = 200;
+
+/// Millisecond timestamp as the UTC `created_at` format.
+fn format_ms(ts_ms: f64) -> Result<String, String> {
+    DateTime::<Utc>::from_timestamp((ts_ms / 1000.0) as i64, 0)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+        .ok_or_else(|| format!("Invalid timestamp: {}", ts_ms))
+}
+
+impl StorageState {
+    /// Protect the screenshots captured within `[start_ms, end_ms]` (milliseconds).
+    pub fn protect_range(
+        &self,
+        start_ms: f64,
+        end_ms: f64,
+        label: Option<&str>,
+    ) -> Result<ProtectedRange, String> {
+        if end_ms < start_ms {
+            return Err("end_time must not be before start_time".to_string());
+        }
+        let label = label
+            .map(str::trim)
+            .filter(|label| !label.is_empty())
+            .map(|label| label.chars().take(MAX_LABEL_CHARS).collect::<String>());
+        let (start_at, end_at) = (format_ms(start_ms)?, format_ms(end_ms)?);
+
+        let id = {
+            let guard = self.get_connection_named("protect_range")?;
+            let conn = guard.as_ref().unwrap();
+            conn.execute(
+                "INSERT INTO protected_ranges (start_at, end_at, label) VALUES (?1, ?2, ?3)",
+                params![start_at, end_at, label],
+            )
+            .map_err(|e| format!("Failed to protect range: {}", e))?;
+            conn.last_insert_rowid()
+        };
+        self.list_protected_ranges()?
+            .into_iter()
+            .find(|range| range.id == id)
+            .ok_or_else(|| format!("Protected range {} not found", id))
+    }
+
+    /// Every protected range, earliest first.
+    pub fn list_protected_ranges(&self) -> Result<Vec<ProtectedRange>, String> {
+        let guard = self.get_connection_named("list_protected_ranges")?;
+        let conn = guard.as_ref().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT p.id, p.start_at, p.end_at, p.label, p.created_at,
+                        (SELECT COUNT(*) FROM screenshots s
+                         WHERE s.is_deleted = 0
+                           AND s.created_at BETWEEN p.start_at AND p.end_at)
+                 FROM protected_ranges p
+                 ORDER BY p.start_at ASC, p.id ASC",
+            )
+            .map_err(|e| format!("Failed to prepare protected range query: {}", e))?;
+        let ranges = stmt
+            .query_map([], |row| {
+                Ok(ProtectedRange {
+                    id: row.get(0)?,
+                    start_at: row.get(1)?,
+                    end_at: row.get(2)?,
+                    label: row.get(3)?,
+                    created_at: row.get(4)?,
+                    screenshot_count: row.get(5)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query protected ranges: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(ranges)
+    }
+
+    /// Lift a protection. Returns `false` when the range did not exist.
+    pub fn remove_protected_range(&self, id: i64) -> Result<bool, String> {
+        let guard = self.get_connection_named("remove_protected_range")?;
+        let conn = guard.as_ref().unwrap();
+        let removed = conn
+            .execute("DELETE FROM protected_ranges WHERE id = ?1", params![id])
+            .map_err(|e| format!("Failed to remove protected range: {}", e))?;
+        Ok(removed > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_storage;
+
+    #[test]
+    fn protected_screenshots_survive_retention_and_range_deletion() {
+        let (_temp, storage) = test_storage();
+        let guard = storage.db.lock().unwrap_or_else(|e| e.into_inner());
+        let connection = guard.as_ref().expect("test database");
+        connection
+            .execute_batch(
+                "INSERT INTO screenshots (id, image_path, image_hash, created_at, is_deleted) VALUES
+                    (1, 'a', 'h1', '2026-01-01 10:00:00', 0),
+                    (2, 'b', 'h2', '2026-01-01 11:00:00', 0),
+                    (3, 'c', 'h3', '2026-01-01 12:00:00', 0);",
+            )
+            .expect("screenshot fixture");
+        drop(guard);
+
+        let start_ms = 1_767_225_600_000.0; // 2026-01-01T00:00:00Z
+        let hour_ms = 3_600_000.0;
+        let range = storage
+            .protect_range(
+                start_ms + 10.5 * hour_ms,
+                start_ms + 11.5 * hour_ms,
+                Some(" invoice "),
+            )
+            .unwrap();
+        assert_eq!(range.label.as_deref(), Some("invoice"));
+        assert_eq!(range.screenshot_count, 1);
+        assert!(storage
+            .protect_range(start_ms, start_ms - 1.0, None)
+            .is_err());
+
+        let (retention_ids, _) = storage
+            .select_screenshots_created_before("2026-02-01 00:00:00", 100)
+            .unwrap();
+        assert_eq!(retention_ids, vec![1, 3]);
+
+        let end_ms = start_ms + 24.0 * hour_ms;
+        let result = storage
+            .delete_screenshots_by_time_range(start_ms, end_ms, false, false)
+            .unwrap();
+        assert_eq!(result.deleted, 2);
+        assert_eq!(result.protected_kept, 1);
+
+        let forced = storage
+            .delete_screenshots_by_time_range(start_ms, end_ms, false, true)
+            .unwrap();
+        assert_eq!(forced.deleted, 1);
+        assert!(storage.remove_protected_range(range.id).unwrap());
+        assert!(!storage.remove_protected_range(range.id).unwrap());
+    }
+}
@@ -0,0 +1,234 @@
+//! BM25 relevance ranking for blind-index search results.
+//!
+//! The blind index only records which OCR rows contain a token, so document frequency
+//! comes from postings cardinality and the collection size from `ocr_row_count`. Term
+//! frequency and text length need the plaintext: only the first `RELEVANCE_WINDOW`
+//! candidates (newest first, or by bigram coverage in fuzzy mode) are decrypted and
+//! scored, and the remaining candidates follow in their original order.
+//!
+//! Multi-keyword queries match at screenshot level; there a keyword's term frequency
+//! is the number of the screenshot's text boxes containing it, which the postings
+//! already provide, so every candidate is scored without decrypting anything.
+
+use rusqlite::Connection;
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+
+use super::{SearchResult, StorageState};
+
+/// Number of leading OCR-level candidates decrypted for scoring.
+pub(super) const RELEVANCE_WINDOW: usize = 200;
+
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+
+/// BM25 inverse document frequency. The `+ 1` keeps terms present in more than half
+/// of the collection from scoring negative.
+pub(super) fn bm25_idf(doc_freq: u64, total_docs: u64) -> f64 {
+    let n = total_docs.max(doc_freq) as f64;
+    let df = doc_freq as f64;
+    ((n - df + 0.5) / (df + 0.5) + 1.0).ln()
+}
+
+/// BM25 contribution of a single term occurring `tf` times in a document of
+/// `doc_len` against a collection average of `avg_doc_len`.
+pub(super) fn bm25_term_score(tf: f64, idf: f64, doc_len: f64, avg_doc_len: f64) -> f64 {
+    if tf <= 0.0 {
+        return 0.0;
+    }
+    let norm = if avg_doc_len > 0.0 {
+        1.0 - BM25_B + BM25_B * doc_len / avg_doc_len
+    } else {
+        1.0
+    };
+    idf * tf * (BM25_K1 + 1.0) / (tf + BM25_K1 * norm)
+}
+
+/// Stable sort of `ids` by descending score; ties keep their incoming order.
+fn sort_by_score(ids: &mut [i64], scores: &HashMap<i64, f64>) {
+    ids.sort_by(|a, b| {
+        let sa = scores.get(a).copied().unwrap_or(0.0);
+        let sb = scores.get(b).copied().unwrap_or(0.0);
+        sb.total_cmp(&sa)
+    });
+}
+
+/// Reorders fetched page rows to follow `page_ids`, which the page SQL's
+/// `ORDER BY` discards. `by_screenshot` selects whether the ids are screenshot ids.
+pub(super) fn restore_page_order(
+    results: &mut [SearchResult],
+    page_ids: &[i64],
+    by_screenshot: bool,
+) {
+    let order: HashMap<i64, usize> = page_ids
+        .iter()
+        .enumerate()
+        .map(|(i, &id)| (id, i))
+        .collect();
+    results.sort_by_key(|r| {
+        let id = if by_screenshot { r.screenshot_id } else { r.id };
+        order.get(&id).copied().unwrap_or(usize::MAX)
+    });
+}
+
+impl StorageState {
+    /// Ranks OCR-level candidates by BM25 over `terms` (keyword, postings cardinality).
+    /// Occurrences are counted case-insensitively in the decrypted text, and length is
+    /// measured in characters against the average of the scored window.
+    pub(super) fn rank_ocr_ids_by_relevance(
+        &self,
+        conn: &Connection,
+        mut ids: Vec<i64>,
+        terms: &[(&str, u64)],
+    ) -> Result<Vec<i64>, String> {
+        let window = ids.len().min(RELEVANCE_WINDOW);
+        if window < 2 || terms.is_empty() {
+            return Ok(ids);
+        }
+
+        let texts = self.decrypt_ocr_texts(conn, &ids[..window])?;
+        if texts.is_empty() {
+            return Ok(ids);
+        }
+
+        let total_docs = self.ocr_row_count.load(Ordering::Relaxed);
+        let terms: Vec<(String, f64)> = terms
+            .iter()
+            .map(|(term, df)| (term.to_lowercase(), bm25_idf(*df, total_docs)))
+            .collect();
+        let avg_len = texts
+            .values()
+            .map(|t| t.chars().count() as f64)
+            .sum::<f64>()
+            / texts.len() as f64;
+
+        let scores: HashMap<i64, f64> = texts
+            .iter()
+            .map(|(&id, text)| {
+                let text = text.to_lowercase();
+                let len = text.chars().count() as f64;
+                let score = terms
+                    .iter()
+                    .map(|(term, idf)| {
+                        bm25_term_score(
+                            text.matches(term.as_str()).count() as f64,
+                            *idf,
+                            len,
+                            avg_len,
+                        )
+                    })
+                    .sum();
+                (id, score)
+            })
+            .collect();
+
+        sort_by_score(&mut ids[..window], &scores);
+        Ok(ids)
+    }
+
+    /// Ranks screenshot-level candidates by BM25 where `per_keyword_counts[k]` maps each
+    /// screenshot to the number of its text boxes matching keyword `k`. Screenshots are
+    /// not length-normalised: the box count says little about how much text they hold.
+    pub(super) fn rank_screenshot_ids_by_relevance(
+        conn: &Connection,
+        mut ids: Vec<i64>,
+        per_keyword_counts: &[HashMap<i64, u32>],
+    ) -> Result<Vec<i64>, String> {
+        if ids.len() < 2 || per_keyword_counts.is_empty() {
+            return Ok(ids);
+        }
+
+        // Approximate live screenshot count, like `ocr_row_count` for OCR rows.
+        let total_docs: i64 = conn
+            .query_row("SELECT COALESCE(MAX(id), 0) FROM screenshots", [], |row| {
+                row.get(0)
+            })
+            .map_err(|e| format!("Failed to count screenshots: {}", e))?;
+        let idfs: Vec<f64> = per_keyword_counts
+            .iter()
+            .map(|counts| bm25_idf(counts.len() as u64, total_docs.max(0) as u64))
+            .collect();
+
+        let scores: HashMap<i64, f64> = ids
+            .iter()
+            .map(|&id| {
+                let score = per_keyword_counts
+                    .iter()
+                    .zip(&idfs)
+                    .map(|(counts, idf)| {
+                        let tf = counts.get(&id).copied().unwrap_or(0) as f64;
+                        bm25_term_score(tf, *idf, 1.0, 1.0)
+                    })
+                    .sum();
+                (id, score)
+            })
+            .collect();
+
+        sort_by_score(&mut ids, &scores);
+        Ok(ids)
+    }
+
+    /// Decrypted text of the given OCR rows; rows that fail to decrypt are omitted.
+    fn decrypt_ocr_texts(
+        &self,
+        conn: &Connection,
+        ids: &[i64],
+    ) -> Result<HashMap<i64, String>, String> {
+        let mut texts = HashMap::with_capacity(ids.len());
+        for chunk in ids.chunks(500) {
+            let placeholders = chunk.iter().map(|_| "?").collect::<Vec<&str>>().join(",");
+            let sql = format!(
+                "SELECT id, text_enc, text_key_encrypted FROM ocr_results WHERE id IN ({}) AND is_deleted = 0",
+                placeholders
+            );
+            let params: Vec<&dyn rusqlite::ToSql> =
+                chunk.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
+            let mut stmt = conn
+                .prepare(&sql)
+                .map_err(|e| format!("Failed to prepare relevance query: {}", e))?;
+            let rows: Vec<(i64, Option<Vec<u8>>, Option<Vec<u8>>)> = stmt
+                .query_map(params.as_slice(), |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+                })
+                .map_err(|e| format!("Failed to query relevance texts: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            for (id, text_enc, text_key_enc) in rows {
+                if let (Some(data), Some(key)) = (text_enc, text_key_enc) {
+                    if let Some(text) = self
+                        .decrypt_payload_with_row_key(&data, &key)
+                        .ok()
+                        .and_then(|v| String::from_utf8(v).ok())
+                    {
+                        texts.insert(id, text);
+                    }
+                }
+            }
+        }
+        Ok(texts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rarer_terms_and_denser_matches_score_higher() {
+        assert!(bm25_idf(10, 10_000) > bm25_idf(5_000, 10_000));
+        assert!(bm25_idf(10_000, 10_000) > 0.0);
+
+        let idf = bm25_idf(10, 10_000);
+        assert_eq!(bm25_term_score(0.0, idf, 20.0, 20.0), 0.0);
+        assert!(bm25_term_score(3.0, idf, 20.0, 20.0) > bm25_term_score(1.0, idf, 20.0, 20.0));
+        assert!(bm25_term_score(1.0, idf, 10.0, 20.0) > bm25_term_score(1.0, idf, 80.0, 20.0));
+    }
+
+    #[test]
+    fn ties_keep_incoming_order() {
+        let mut ids = vec![9, 7, 5, 3];
+        let scores = HashMap::from([(5, 2.0), (3, 2.0), (9, 1.0)]);
+        sort_by_score(&mut ids, &scores);
+        assert_eq!(ids, vec![5, 3, 9, 7]);
+    }
+}
@@ -0,0 +1,781 @@
+//! End-to-end encrypted sync to a WebDAV or S3-compatible target.
+//!
+//! The target receives two kinds of objects under the configured prefix:
+//!
+//! - `files/<path>`: image, thumbnail, keyframe and bundle files, uploaded byte for
+//!   byte. They are already encrypted with per-row keys that only this machine's
+//!   credential can unwrap.
+//! - `changelog/<seq>.cpsync`: one object per batch, holding the database rows of the
+//!   screenshots it adds (with their keyframes and rows in screenshot child tables,
+//!   OCR included) and the image hashes of synced screenshots deleted since. The JSON
+//!   is zstd-compressed and sealed with AES-256-GCM under a key derived from the
+//!   master key, so the server never sees plaintext rows or text. A key rotation
+//!   stores that key sealed under the new master key, so the changelog key stays the
+//!   same across rotations.
+//!
+//! A screenshot is synced once, keyed by its image hash, and recorded in
+//! `sync_uploads` only after its changelog object is stored, so an interrupted run
+//! re-sends the batch instead of losing it. When a synced screenshot leaves the
+//! database (deleted or moved to the trash), its hash goes into the next changelog
+//! and the files no other synced screenshot uses are removed from the target. Rows
+//! moved to a detached month shard are not deletions. Settings live in the storage
+//! policy, with the password or secret key encrypted under the master key; syncing
+//! needs the app to be unlocked. The `sync` scheduled job runs [`StorageState::sync_now`].
+
+mod s3;
+mod webdav;
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use base64::Engine;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use rusqlite::types::ValueRef;
+use rusqlite::{params, Connection};
+use serde_json::{json, Map, Value as JsonValue};
+use sha2::{Digest, Sha256};
+
+use super::cold_storage::BundleEntry;
+use super::month_shards::screenshot_child_tables;
+use super::{StorageState, SyncConfig, SyncProvider, SyncReport};
+use crate::credential_manager::{
+    decrypt_with_master_key, encrypt_with_master_key, get_cached_master_key,
+};
+
+/// Policy key holding the [`SyncConfig`].
+const SYNC_CONFIG_POLICY_KEY: &str = "sync_target";
+/// Policy key holding the encrypted password or secret key; redacted for the frontend.
+pub(crate) const SYNC_SECRET_POLICY_KEY: &str = "sync_secret_encrypted";
+/// Policy key holding the changelog key once a key rotation has sealed it; redacted
+/// for the frontend.
+pub(crate) const SYNC_KEY_POLICY_KEY: &str = "sync_key_encrypted";
+const CHANGELOG_FORMAT: &str = "carbonpaper-sync";
+const CHANGELOG_VERSION: u32 = 1;
+/// Leading bytes of every changelog object, before the AES-GCM nonce.
+const CHANGELOG_MAGIC: &[u8] = b"CPS1";
+const CHANGELOG_ZSTD_LEVEL: i32 = 3;
+/// Screenshots per changelog object.
+const BATCH_SIZE: usize = 100;
+const FILES_FOLDER: &str = "files";
+const CHANGELOG_FOLDER: &str = "changelog";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// Characters left as-is in remote path segments (RFC 3986 unreserved).
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// Object store the sync engine writes to. Paths are relative to the configured
+/// prefix and use forward slashes.
+trait SyncRemote {
+    /// Store `body` at `path`, replacing any existing object.
+    fn put(&self, path: &str, body: Vec<u8>) -> Result<(), String>;
+    /// Store a local file at `path` and return its size.
+    fn put_file(&self, path: &str, file: &Path) -> Result<u64, String>;
+    /// Remove `path`; a missing object is not an error.
+    fn delete(&self, path: &str) -> Result<(), String>;
+}
+
+/// Percent-encode each segment of a slash-separated path.
+fn encode_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| utf8_percent_encode(segment, PATH_SEGMENT).to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn derive_key(master_key: &[u8], label: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(master_key);
+    hasher.update(label);
+    hasher.finalize().into()
+}
+
+fn sync_secret_key(master_key: &[u8]) -> [u8; 32] {
+    derive_key(master_key, b"CarbonPaper-Sync-Secret-v1")
+}
+
+/// Decrypt a policy value sealed under `master_key` by [`seal_policy_value`].
+fn open_policy_value(master_key: &[u8], encoded: &str) -> Result<Vec<u8>, String> {
+    let sealed = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| format!("Invalid saved sync secret: {}", e))?;
+    decrypt_with_master_key(&sync_secret_key(master_key), &sealed)
+        .map_err(|e| format!("Failed to decrypt sync secret: {}", e))
+}
+
+fn seal_policy_value(master_key: &[u8], value: &[u8]) -> Result<String, String> {
+    let sealed = encrypt_with_master_key(&sync_secret_key(master_key), value)
+        .map_err(|e| format!("Failed to encrypt sync secret: {}", e))?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(sealed))
+}
+
+/// The changelog key: the one sealed in `policy` by a key rotation, or else the key
+/// derived from `master_key`.
+pub(super) fn policy_sync_data_key(
+    policy: &JsonValue,
+    master_key: &[u8],
+) -> Result<[u8; 32], String> {
+    match policy.get(SYNC_KEY_POLICY_KEY).and_then(|v| v.as_str()) {
+        Some(encoded) => open_policy_value(master_key, encoded)?
+            .try_into()
+            .map_err(|_| "Invalid saved sync key".to_string()),
+        None => Ok(derive_key(master_key, b"CarbonPaper-Sync-Key-v1")),
+    }
+}
+
+/// Re-seal the saved sync secret and the changelog key in `policy` from
+/// `old_master_key` to `new_master_key` for a key rotation.
+pub(super) fn reseal_sync_secrets(
+    policy: &mut JsonValue,
+    old_master_key: &[u8],
+    new_master_key: &[u8],
+) -> Result<(), String> {
+    let data_key = policy_sync_data_key(policy, old_master_key)?;
+    let secret = match policy
+        .get(SYNC_SECRET_POLICY_KEY)
+        .and_then(|v| v.as_str())
+        .filter(|secret| !secret.is_empty())
+    {
+        Some(encoded) => Some(open_policy_value(old_master_key, encoded)?),
+        None => None,
+    };
+    let obj = policy
+        .as_object_mut()
+        .ok_or_else(|| "Policy is not a valid JSON object".to_string())?;
+    if let Some(secret) = secret {
+        obj.insert(
+            SYNC_SECRET_POLICY_KEY.into(),
+            seal_policy_value(new_master_key, &secret)?.into(),
+        );
+    }
+    // Changelogs already on the target stay sealed under the same key.
+    if obj.contains_key(SYNC_CONFIG_POLICY_KEY) || obj.contains_key(SYNC_KEY_POLICY_KEY) {
+        obj.insert(
+            SYNC_KEY_POLICY_KEY.into(),
+            seal_policy_value(new_master_key, &data_key)?.into(),
+        );
+    }
+    Ok(())
+}
+
+fn changelog_path(seq: i64) -> String {
+    format!("{}/{:010}.cpsync", CHANGELOG_FOLDER, seq)
+}
+
+/// Compress and seal a changelog for upload.
+fn seal_changelog(key: &[u8; 32], changelog: &JsonValue) -> Result<Vec<u8>, String> {
+    let json = serde_json::to_vec(changelog)
+        .map_err(|e| format!("Failed to serialize sync changelog: {}", e))?;
+    let compressed = zstd::bulk::compress(&json, CHANGELOG_ZSTD_LEVEL)
+        .map_err(|e| format!("Failed to compress sync changelog: {}", e))?;
+    let sealed = encrypt_with_master_key(key, &compressed)
+        .map_err(|e| format!("Failed to encrypt sync changelog: {}", e))?;
+    Ok([CHANGELOG_MAGIC, sealed.as_slice()].concat())
+}
+
+/// Read a changelog written by [`seal_changelog`].
+#[cfg(test)]
+fn open_changelog(key: &[u8; 32], sealed: &[u8]) -> Result<JsonValue, String> {
+    let sealed = sealed
+        .strip_prefix(CHANGELOG_MAGIC)
+        .ok_or_else(|| "Not a sync changelog".to_string())?;
+    let compressed = decrypt_with_master_key(key, sealed)
+        .map_err(|e| format!("Failed to decrypt sync changelog: {}", e))?;
+    let json = zstd::stream::decode_all(compressed.as_slice())
+        .map_err(|e| format!("Failed to decompress sync changelog: {}", e))?;
+    serde_json::from_slice(&json).map_err(|e| format!("Failed to parse sync changelog: {}", e))
+}
+
+/// Every column of every row returned by `sql`, as JSON objects; blobs become
+/// `{ "b64": ... }`.
+fn query_rows_as_json(conn: &Connection, sql: &str) -> Result<Vec<JsonValue>, String> {
+    let mut stmt = conn
+        .prepare(sql)
+        .map_err(|e| format!("Failed to prepare sync row query: {}", e))?;
+    let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+    let rows = stmt
+        .query_map([], |row| {
+            let mut object = Map::new();
+            for (index, name) in columns.iter().enumerate() {
+                let value = match row.get_ref(index)? {
+                    ValueRef::Null => JsonValue::Null,
+                    ValueRef::Integer(v) => json!(v),
+                    ValueRef::Real(v) => json!(v),
+                    ValueRef::Text(v) => json!(String::from_utf8_lossy(v)),
+                    ValueRef::Blob(v) => {
+                        json!({ "b64": base64::engine::general_purpose::STANDARD.encode(v) })
+                    }
+                };
+                object.insert(name.clone(), value);
+            }
+            Ok(JsonValue::Object(object))
+        })
+        .map_err(|e| format!("Failed to query sync rows: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(rows)
+}
+
+/// A live screenshot that is not on the target yet.
+struct PendingScreenshot {
+    id: i64,
+    image_hash: String,
+    image_path: String,
+    keyframe_path: Option<String>,
+}
+
+/// A screenshot recorded in `sync_uploads`.
+struct SyncedScreenshot {
+    image_hash: String,
+    files: Vec<String>,
+}
+
+impl StorageState {
+    /// The sync target settings.
+    pub fn sync_config(&self) -> Result<SyncConfig, String> {
+        let policy = self.load_policy()?;
+        let mut config: SyncConfig = policy
+            .get(SYNC_CONFIG_POLICY_KEY)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .unwrap_or_default();
+        config.has_secret = policy
+            .get(SYNC_SECRET_POLICY_KEY)
+            .and_then(|value| value.as_str())
+            .is_some_and(|secret| !secret.is_empty());
+        Ok(config)
+    }
+
+    /// Save the sync target. `secret` replaces the saved password or secret key,
+    /// `Some("")` clears it and `None` keeps it.
+    pub fn set_sync_config(
+        &self,
+        mut config: SyncConfig,
+        secret: Option<&str>,
+    ) -> Result<SyncConfig, String> {
+        config.endpoint = config.endpoint.trim().trim_end_matches('/').to_string();
+        config.prefix = config.prefix.trim().trim_matches('/').to_string();
+        config.bucket = config.bucket.trim().to_string();
+        config.region = config.region.trim().to_string();
+        config.username = config.username.trim().to_string();
+        config.has_secret = false;
+        if !config.endpoint.is_empty() {
+            let url = reqwest::Url::parse(&config.endpoint)
+                .map_err(|e| format!("Invalid sync endpoint {}: {}", config.endpoint, e))?;
+            if !matches!(url.scheme(), "http" | "https") {
+                return Err(format!(
+                    "Sync endpoint must use http or https: {}",
+                    config.endpoint
+                ));
+            }
+        } else if config.enabled {
+            return Err("Sync endpoint is required".to_string());
+        }
+        if config.enabled && config.provider == SyncProvider::S3 && config.bucket.is_empty() {
+            return Err("S3 bucket is required".to_string());
+        }
+
+        let encrypted_secret = match secret {
+            Some(secret) if !secret.is_empty() => {
+                let master_key = get_cached_master_key(&self.credential_state)
+                    .ok_or_else(|| "AUTH_REQUIRED".to_string())?;
+                Some(seal_policy_value(&master_key, secret.as_bytes())?)
+            }
+            _ => None,
+        };
+
+        let mut policy = self.load_policy()?;
+        let obj = policy
+            .as_object_mut()
+            .ok_or_else(|| "Policy is not a valid JSON object".to_string())?;
+        let value = serde_json::to_value(&config)
+            .map_err(|e| format!("Failed to serialize sync settings: {}", e))?;
+        obj.insert(SYNC_CONFIG_POLICY_KEY.into(), value);
+        match (secret, encrypted_secret) {
+            (_, Some(sealed)) => {
+                obj.insert(SYNC_SECRET_POLICY_KEY.into(), JsonValue::from(sealed));
+            }
+            (Some(_), None) => {
+                obj.remove(SYNC_SECRET_POLICY_KEY);
+            }
+            (None, None) => {}
+        }
+        self.check_policy_compliant(&policy)?;
+        self.save_policy(&policy)?;
+        self.sync_config()
+    }
+
+    pub(super) fn sync_secret(&self, master_key: &[u8]) -> Result<String, String> {
+        let policy = self.load_policy()?;
+        let Some(encoded) = policy
+            .get(SYNC_SECRET_POLICY_KEY)
+            .and_then(|value| value.as_str())
+            .filter(|secret| !secret.is_empty())
+        else {
+            return Ok(String::new());
+        };
+        let secret = open_policy_value(master_key, encoded)?;
+        String::from_utf8(secret).map_err(|e| format!("Invalid saved sync secret: {}", e))
+    }
+
+    /// Upload screenshots that are not on the sync target yet and record deletions.
+    /// `on_progress(phase, done, total)` is called with phase `files`.
+    pub fn sync_now(&self, on_progress: &dyn Fn(&str, usize, usize)) -> Result<SyncReport, String> {
+        if self.migration_in_progress.load(Ordering::SeqCst) {
+            return Err("A storage migration is in progress".to_string());
+        }
+        if self
+            .sync_in_progress
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Err("ALREADY_RUNNING".to_string());
+        }
+        let result = self.run_sync(on_progress);
+        self.sync_in_progress.store(false, Ordering::SeqCst);
+        result
+    }
+
+    fn run_sync(&self, on_progress: &dyn Fn(&str, usize, usize)) -> Result<SyncReport, String> {
+        let config = self.sync_config()?;
+        if config.endpoint.is_empty() {
+            return Err("Sync target is not configured".to_string());
+        }
+        let master_key = get_cached_master_key(&self.credential_state)
+            .ok_or_else(|| "AUTH_REQUIRED".to_string())?;
+        let secret = self.sync_secret(&master_key)?;
+        let remote: Box<dyn SyncRemote> = match config.provider {
+            SyncProvider::Webdav => Box::new(webdav::WebDavRemote::new(
+                &config.endpoint,
+                &config.prefix,
+                &config.username,
+                &secret,
+            )?),
+            SyncProvider::S3 => Box::new(s3::S3Remote::new(
+                &config.endpoint,
+                &config.bucket,
+                &config.region,
+                &config.prefix,
+                &config.username,
+                &secret,
+            )?),
+        };
+        let data_key = policy_sync_data_key(&self.load_policy()?, &master_key)?;
+        let report = self.sync_with_remote(remote.as_ref(), &data_key, on_progress)?;
+        tracing::info!(
+            "[SYNC] {}: screenshots={}, files={} ({} bytes), deleted={}, batches={}, skipped={}",
+            config.endpoint,
+            report.screenshots_uploaded,
+            report.files_uploaded,
+            report.bytes_uploaded,
+            report.screenshots_deleted,
+            report.changelog_batches,
+            report.screenshots_skipped
+        );
+        Ok(report)
+    }
+
+    fn sync_with_remote(
+        &self,
+        remote: &dyn SyncRemote,
+        key: &[u8; 32],
+        on_progress: &dyn Fn(&str, usize, usize),
+    ) -> Result<SyncReport, String> {
+        let started = std::time::Instant::now();
+        let mut report = SyncReport::default();
+
+        // Files of screenshots still on the target; nothing in here is uploaded again
+        // or removed.
+        let mut remote_files: HashSet<String> = HashSet::new();
+        let mut deleted = Vec::new();
+        let shard_files = self.month_shard_files()?;
+        for synced in self.synced_screenshots()? {
+            let (live, in_shard) = {
+                let guard = self.get_connection_named("sync_with_remote")?;
+                let conn = guard.as_ref().unwrap();
+                let live: bool = conn
+                    .query_row(
+                        "SELECT EXISTS(SELECT 1 FROM screenshots
+                                       WHERE image_hash = ?1 AND is_deleted = 0)",
+                        params![synced.image_hash],
+                        |row| row.get(0),
+                    )
+                    .map_err(|e| format!("Failed to look up synced screenshot: {}", e))?;
+                let in_shard = synced
+                    .files
+                    .first()
+                    .is_some_and(|file| shard_files.contains(file));
+                (live, in_shard)
+            };
+            if live || in_shard {
+                remote_files.extend(synced.files);
+            } else {
+                deleted.push(synced);
+            }
+        }
+
+        let pending = self.pending_sync_screenshots()?;
+        let total = pending.len();
+        let mut batches: Vec<&[PendingScreenshot]> = pending.chunks(BATCH_SIZE).collect();
+        if batches.is_empty() && !deleted.is_empty() {
+            batches.push(&[]);
+        }
+        let mut seq = self.next_sync_changelog_seq()?;
+        let mut done = 0;
+        for batch in batches {
+            let mut entries: Vec<(i64, String, Vec<String>)> = Vec::with_capacity(batch.len());
+            for item in batch {
+                done += 1;
+                let Some(files) = self.sync_files_for(item) else {
+                    report.screenshots_skipped += 1;
+                    continue;
+                };
+                for (rel, abs) in &files {
+                    if remote_files.contains(rel) {
+                        continue;
+                    }
+                    let bytes = remote.put_file(&format!("{}/{}", FILES_FOLDER, rel), abs)?;
+                    remote_files.insert(rel.clone());
+                    report.files_uploaded += 1;
+                    report.bytes_uploaded += bytes;
+                }
+                entries.push((
+                    item.id,
+                    item.image_hash.clone(),
+                    files.into_iter().map(|(rel, _)| rel).collect(),
+                ));
+                on_progress("files", done, total);
+            }
+            let batch_deleted = std::mem::take(&mut deleted);
+            if entries.is_empty() && batch_deleted.is_empty() {
+                continue;
+            }
+
+            let changelog = self.build_sync_changelog(seq, &entries, &batch_deleted)?;
+            remote.put(&changelog_path(seq), seal_changelog(key, &changelog)?)?;
+            self.record_sync_batch(seq, &entries, &batch_deleted)?;
+            for file in batch_deleted.iter().flat_map(|synced| &synced.files) {
+                if !remote_files.contains(file) {
+                    remote.delete(&format!("{}/{}", FILES_FOLDER, file))?;
+                    // Shared files are listed by several deleted screenshots.
+                    remote_files.insert(file.clone());
+                }
+            }
+            report.screenshots_uploaded += entries.len();
+            report.screenshots_deleted += batch_deleted.len();
+            report.changelog_batches += 1;
+            seq += 1;
+        }
+
+        report.duration_ms = started.elapsed().as_millis() as i64;
+        Ok(report)
+    }
+
+    fn synced_screenshots(&self) -> Result<Vec<SyncedScreenshot>, String> {
+        let guard = self.get_connection_named("synced_screenshots")?;
+        let conn = guard.as_ref().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT image_hash, files FROM sync_uploads ORDER BY changelog_seq")
+            .map_err(|e| format!("Failed to prepare sync upload query: {}", e))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(|e| format!("Failed to query sync uploads: {}", e))?
+            .filter_map(|r| r.ok())
+            .map(|(image_hash, files)| SyncedScreenshot {
+                image_hash,
+                files: serde_json::from_str(&files).unwrap_or_default(),
+            })
+            .collect();
+        Ok(rows)
+    }
+
+    fn pending_sync_screenshots(&self) -> Result<Vec<PendingScreenshot>, String> {
+        let guard = self.get_connection_named("pending_sync_screenshots")?;
+        let conn = guard.as_ref().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT s.id, s.image_hash, s.image_path, k.image_path
+                 FROM screenshots s
+                 LEFT JOIN delta_keyframes k ON k.id = s.delta_keyframe_id
+                 WHERE s.is_deleted = 0 AND (s.status IS NULL OR s.status = 'committed')
+                   AND NOT EXISTS (SELECT 1 FROM sync_uploads u WHERE u.image_hash = s.image_hash)
+                 ORDER BY s.id",
+            )
+            .map_err(|e| format!("Failed to prepare pending sync query: {}", e))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(PendingScreenshot {
+                    id: row.get(0)?,
+                    image_hash: row.get(1)?,
+                    image_path: row.get(2)?,
+                    keyframe_path: row.get(3)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query pending sync screenshots: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(rows)
+    }
+
+    /// Data-dir-relative path of the file holding an image: the bundle for archived
+    /// images, `None` for paths outside the data directory.
+    fn sync_file_for_image(&self, image_path: &str) -> Option<(String, PathBuf)> {
+        let abs = self.resolve_image_path(image_path);
+        let file = BundleEntry::parse(&abs).map_or(abs, |entry| entry.bundle);
+        let rel = self.to_relative_image_path(&file);
+        if Path::new(&rel).is_absolute() || rel.starts_with('/') || rel.contains("..") {
+            return None;
+        }
+        Some((rel, file))
+    }
+
+    /// Files a screenshot needs on the target, image file first; `None` when the image
+    /// file is missing.
+    fn sync_files_for(&self, item: &PendingScreenshot) -> Option<Vec<(String, PathBuf)>> {
+        let image = self.sync_file_for_image(&item.image_path)?;
+        if !image.1.is_file() {
+            return None;
+        }
+        let mut files = vec![image];
+        let thumbnail = Self::thumbnail_path_for(&self.resolve_image_path(&item.image_path));
+        if thumbnail.is_file() {
+            if let Some(thumbnail) = self.sync_file_for_image(&thumbnail.to_string_lossy()) {
+                files.push(thumbnail);
+            }
+        }
+        if let Some(keyframe) = item
+            .keyframe_path
+            .as_deref()
+            .and_then(|path| self.sync_file_for_image(path))
+            .filter(|(_, abs)| abs.is_file())
+        {
+            files.push(keyframe);
+        }
+        files.dedup_by(|a, b| a.0 == b.0);
+        Some(files)
+    }
+
+    /// Image files of rows kept in detached month shards.
+    fn month_shard_files(&self) -> Result<HashSet<String>, String> {
+        Ok(self
+            .month_shard_image_paths()?
+            .iter()
+            .filter_map(|path| self.sync_file_for_image(path))
+            .map(|(rel, _)| rel)
+            .collect())
+    }
+
+    fn next_sync_changelog_seq(&self) -> Result<i64, String> {
+        let guard = self.get_connection_named("next_sync_changelog_seq")?;
+        let conn = guard.as_ref().unwrap();
+        conn.query_row(
+            "SELECT COALESCE(MAX(seq), 0) + 1 FROM sync_changelog",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to read sync changelog sequence: {}", e))
+    }
+
+    fn build_sync_changelog(
+        &self,
+        seq: i64,
+        entries: &[(i64, String, Vec<String>)],
+        deleted: &[SyncedScreenshot],
+    ) -> Result<JsonValue, String> {
+        let ids = entries
+            .iter()
+            .map(|(id, _, _)| id.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut screenshots = Vec::new();
+        let mut keyframes = Vec::new();
+        let mut children = Map::new();
+        if !entries.is_empty() {
+            let guard = self.get_connection_named("build_sync_changelog")?;
+            let conn = guard.as_ref().unwrap();
+            screenshots = query_rows_as_json(
+                conn,
+                &format!("SELECT * FROM screenshots WHERE id IN ({})", ids),
+            )?;
+            keyframes = query_rows_as_json(
+                conn,
+                &format!(
+                    "SELECT * FROM delta_keyframes WHERE id IN (
+                         SELECT delta_keyframe_id FROM screenshots WHERE id IN ({})
+                     )",
+                    ids
+                ),
+            )?;
+            for (table, fk) in screenshot_child_tables(conn)? {
+                let rows = query_rows_as_json(
+                    conn,
+                    &format!("SELECT * FROM \"{}\" WHERE \"{}\" IN ({})", table, fk, ids),
+                )?;
+                if !rows.is_empty() {
+                    children.insert(table, JsonValue::Array(rows));
+                }
+            }
+        }
+
+        Ok(json!({
+            "format": CHANGELOG_FORMAT,
+            "version": CHANGELOG_VERSION,
+            "seq": seq,
+            "created_at": chrono::Utc::now().to_rfc3339(),
+            "app_version": env!("CARGO_PKG_VERSION"),
+            "screenshots": screenshots,
+            "delta_keyframes": keyframes,
+            "children": children,
+            "files": entries
+                .iter()
+                .map(|(_, image_hash, files)| (image_hash.clone(), json!(files)))
+                .collect::<Map<_, _>>(),
+            "deleted": deleted.iter().map(|synced| &synced.image_hash).collect::<Vec<_>>(),
+        }))
+    }
+
+    fn record_sync_batch(
+        &self,
+        seq: i64,
+        entries: &[(i64, String, Vec<String>)],
+        deleted: &[SyncedScreenshot],
+    ) -> Result<(), String> {
+        let mut guard = self.get_connection_named("record_sync_batch")?;
+        let conn = guard.as_mut().unwrap();
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start sync transaction: {}", e))?;
+        for (_, image_hash, files) in entries {
+            tx.execute(
+                "INSERT OR REPLACE INTO sync_uploads (image_hash, files, changelog_seq)
+                 VALUES (?1, ?2, ?3)",
+                params![image_hash, json!(files).to_string(), seq],
+            )
+            .map_err(|e| format!("Failed to record synced screenshot: {}", e))?;
+        }
+        for synced in deleted {
+            tx.execute(
+                "DELETE FROM sync_uploads WHERE image_hash = ?1",
+                params![synced.image_hash],
+            )
+            .map_err(|e| format!("Failed to record synced deletion: {}", e))?;
+        }
+        tx.execute(
+            "INSERT INTO sync_changelog (seq, screenshots, deleted) VALUES (?1, ?2, ?3)",
+            params![seq, entries.len() as i64, deleted.len() as i64],
+        )
+        .map_err(|e| format!("Failed to record sync changelog: {}", e))?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit sync batch: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_storage;
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemoryRemote {
+        objects: Mutex<BTreeMap<String, Vec<u8>>>,
+    }
+
+    impl SyncRemote for MemoryRemote {
+        fn put(&self, path: &str, body: Vec<u8>) -> Result<(), String> {
+            self.objects.lock().unwrap().insert(path.to_string(), body);
+            Ok(())
+        }
+
+        fn put_file(&self, path: &str, file: &Path) -> Result<u64, String> {
+            let body = std::fs::read(file).map_err(|e| e.to_string())?;
+            let bytes = body.len() as u64;
+            self.put(path, body)?;
+            Ok(bytes)
+        }
+
+        fn delete(&self, path: &str) -> Result<(), String> {
+            self.objects.lock().unwrap().remove(path);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn remote_paths_are_encoded_per_segment() {
+        assert_eq!(
+            encode_path("files/screenshots/a b+c.png.enc"),
+            "files/screenshots/a%20b%2Bc.png.enc"
+        );
+        assert_eq!(changelog_path(7), "changelog/0000000007.cpsync");
+    }
+
+    #[test]
+    fn sync_uploads_new_screenshots_once_and_records_deletions() {
+        let (temp, storage) = test_storage();
+        let screenshots = temp.path().join("screenshots");
+        std::fs::create_dir_all(&screenshots).unwrap();
+        std::fs::write(screenshots.join("a.png.enc"), [1u8; 8]).unwrap();
+        std::fs::write(screenshots.join("b.png.enc"), [2u8; 4]).unwrap();
+
+        let guard = storage.db.lock().unwrap_or_else(|e| e.into_inner());
+        let connection = guard.as_ref().expect("test database");
+        connection
+            .execute_batch(
+                "INSERT INTO screenshots (id, image_path, image_hash) VALUES
+                    (1, 'screenshots/a.png.enc', 'ha'),
+                    (2, 'screenshots/b.png.enc', 'hb'),
+                    (3, 'screenshots/missing.png.enc', 'hc');
+                 INSERT INTO ocr_results (screenshot_id, text_hash) VALUES (1, 't1');",
+            )
+            .expect("sync fixture");
+        drop(guard);
+
+        let key = [7u8; 32];
+        let remote = MemoryRemote::default();
+        let report = storage
+            .sync_with_remote(&remote, &key, &|_, _, _| {})
+            .expect("first sync");
+        assert_eq!(report.screenshots_uploaded, 2);
+        assert_eq!(report.files_uploaded, 2);
+        assert_eq!(report.bytes_uploaded, 12);
+        assert_eq!(report.screenshots_skipped, 1);
+        assert_eq!(report.changelog_batches, 1);
+
+        let changelog = {
+            let objects = remote.objects.lock().unwrap();
+            assert!(objects.contains_key("files/screenshots/a.png.enc"));
+            open_changelog(&key, &objects[&changelog_path(1)]).expect("open changelog")
+        };
+        assert_eq!(changelog["screenshots"].as_array().unwrap().len(), 2);
+        assert_eq!(changelog["children"]["ocr_results"][0]["text_hash"], "t1");
+        assert!(open_changelog(&[8u8; 32], &seal_changelog(&key, &changelog).unwrap()).is_err());
+
+        // Nothing new: no upload and no changelog.
+        let report = storage
+            .sync_with_remote(&remote, &key, &|_, _, _| {})
+            .expect("idle sync");
+        assert_eq!(report.changelog_batches, 0);
+
+        storage
+            .get_connection_named("test")
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .execute("UPDATE screenshots SET is_deleted = 1 WHERE id = 2", [])
+            .unwrap();
+        let report = storage
+            .sync_with_remote(&remote, &key, &|_, _, _| {})
+            .expect("deletion sync");
+        assert_eq!(report.screenshots_deleted, 1);
+        let objects = remote.objects.lock().unwrap();
+        assert!(!objects.contains_key("files/screenshots/b.png.enc"));
+        let changelog = open_changelog(&key, &objects[&changelog_path(2)]).unwrap();
+        assert_eq!(changelog["deleted"], json!(["hb"]));
+    }
+}
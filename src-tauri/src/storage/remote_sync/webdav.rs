@@ -0,0 +1,132 @@
+//! WebDAV sync target: `PUT` uploads, `MKCOL` for folders that do not exist yet and
+//! `DELETE` removals, authenticated with HTTP basic auth.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Mutex;
+
+use reqwest::blocking::{Body, Client, RequestBuilder};
+use reqwest::{Method, StatusCode};
+
+use super::{encode_path, SyncRemote, REQUEST_TIMEOUT};
+
+pub(super) struct WebDavRemote {
+    client: Client,
+    endpoint: String,
+    prefix: String,
+    username: String,
+    password: String,
+    /// Folders known to exist, so each is created at most once per run.
+    folders: Mutex<HashSet<String>>,
+}
+
+impl WebDavRemote {
+    pub(super) fn new(
+        endpoint: &str,
+        prefix: &str,
+        username: &str,
+        password: &str,
+    ) -> Result<Self, String> {
+        let client = Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| format!("Failed to build WebDAV client: {}", e))?;
+        Ok(Self {
+            client,
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            prefix: prefix.to_string(),
+            username: username.to_string(),
+            password: password.to_string(),
+            folders: Mutex::new(HashSet::new()),
+        })
+    }
+
+    fn full_path(&self, path: &str) -> String {
+        if self.prefix.is_empty() {
+            path.to_string()
+        } else {
+            format!("{}/{}", self.prefix, path)
+        }
+    }
+
+    fn request(&self, method: Method, full_path: &str) -> RequestBuilder {
+        let url = format!("{}/{}", self.endpoint, encode_path(full_path));
+        let request = self.client.request(method, url);
+        if self.username.is_empty() {
+            request
+        } else {
+            request.basic_auth(&self.username, Some(&self.password))
+        }
+    }
+
+    /// Create the folders above `full_path` that are not known to exist.
+    fn ensure_parents(&self, full_path: &str) -> Result<(), String> {
+        let mkcol = Method::from_bytes(b"MKCOL").expect("MKCOL is a valid method");
+        let mut folders = self.folders.lock().unwrap_or_else(|e| e.into_inner());
+        let mut folder = String::new();
+        let segments: Vec<&str> = full_path.split('/').collect();
+        for segment in &segments[..segments.len().saturating_sub(1)] {
+            if !folder.is_empty() {
+                folder.push('/');
+            }
+            folder.push_str(segment);
+            if folders.contains(&folder) {
+                continue;
+            }
+            let response = self
+                .request(mkcol.clone(), &format!("{}/", folder))
+                .send()
+                .map_err(|e| format!("WebDAV MKCOL {} failed: {}", folder, e))?;
+            // 405 means the folder already exists.
+            let status = response.status();
+            if !status.is_success() && status != StatusCode::METHOD_NOT_ALLOWED {
+                return Err(format!("WebDAV MKCOL {} failed: {}", folder, status));
+            }
+            folders.insert(folder.clone());
+        }
+        Ok(())
+    }
+
+    fn put_body(&self, path: &str, body: Body) -> Result<(), String> {
+        let full_path = self.full_path(path);
+        self.ensure_parents(&full_path)?;
+        let response = self
+            .request(Method::PUT, &full_path)
+            .body(body)
+            .send()
+            .map_err(|e| format!("WebDAV PUT {} failed: {}", path, e))?;
+        if !response.status().is_success() {
+            return Err(format!("WebDAV PUT {} failed: {}", path, response.status()));
+        }
+        Ok(())
+    }
+}
+
+impl SyncRemote for WebDavRemote {
+    fn put(&self, path: &str, body: Vec<u8>) -> Result<(), String> {
+        self.put_body(path, Body::from(body))
+    }
+
+    fn put_file(&self, path: &str, file: &Path) -> Result<u64, String> {
+        let handle = std::fs::File::open(file)
+            .map_err(|e| format!("Failed to open {}: {}", file.display(), e))?;
+        let bytes = handle
+            .metadata()
+            .map_err(|e| format!("Failed to stat {}: {}", file.display(), e))?
+            .len();
+        self.put_body(path, Body::sized(handle, bytes))?;
+        Ok(bytes)
+    }
+
+    fn delete(&self, path: &str) -> Result<(), String> {
+        let response = self
+            .request(Method::DELETE, &self.full_path(path))
+            .send()
+            .map_err(|e| format!("WebDAV DELETE {} failed: {}", path, e))?;
+        let status = response.status();
+        if !status.is_success() && status != StatusCode::NOT_FOUND {
+            return Err(format!("WebDAV DELETE {} failed: {}", path, status));
+        }
+        Ok(())
+    }
+}
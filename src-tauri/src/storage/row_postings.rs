@@ -0,0 +1,184 @@
+//! Blind-index postings keyed by row ID for append-only encrypted tables.
+//!
+//! Clipboard entries and audio transcripts each keep a `(token_hash, postings_blob)`
+//! table whose bitmaps hold row IDs. Rows are never edited, so postings are only
+//! added on insert and subtracted on delete; the subtraction walks the postings
+//! instead of re-tokenizing the deleted text, so it needs no key.
+
+use roaring::RoaringBitmap;
+use rusqlite::{params, OptionalExtension, Transaction};
+
+use super::StorageState;
+
+impl StorageState {
+    /// Row ID as stored in a posting bitmap.
+    pub(super) fn posting_id(row_id: i64) -> Result<u32, String> {
+        u32::try_from(row_id).map_err(|_| format!("Row id {} exceeds index range", row_id))
+    }
+
+    /// Add `row_id` to the postings of `token_hashes` in `table` inside the caller's
+    /// transaction.
+    pub(super) fn add_row_postings(
+        tx: &Transaction<'_>,
+        table: &str,
+        row_id: i64,
+        token_hashes: &[String],
+    ) -> Result<(), String> {
+        let posting_id = Self::posting_id(row_id)?;
+        let mut get_stmt = tx
+            .prepare_cached(&format!(
+                "SELECT postings_blob FROM {} WHERE token_hash = ?1",
+                table
+            ))
+            .map_err(|e| format!("Failed to prepare bitmap read: {}", e))?;
+        let mut put_stmt = tx
+            .prepare_cached(&format!(
+                "INSERT OR REPLACE INTO {} (token_hash, postings_blob) VALUES (?1, ?2)",
+                table
+            ))
+            .map_err(|e| format!("Failed to prepare bitmap write: {}", e))?;
+
+        for token_hash in token_hashes {
+            let existing_blob: Option<Vec<u8>> = get_stmt
+                .query_row(params![token_hash], |row| row.get(0))
+                .optional()
+                .map_err(|e| format!("Failed to load bitmap row: {}", e))?;
+            let mut bitmap = match existing_blob {
+                Some(blob) => RoaringBitmap::deserialize_from(&blob[..])
+                    .map_err(|e| format!("Failed to deserialize bitmap: {}", e))?,
+                None => RoaringBitmap::new(),
+            };
+            if !bitmap.insert(posting_id) {
+                continue;
+            }
+            let mut buf = Vec::new();
+            bitmap
+                .serialize_into(&mut buf)
+                .map_err(|e| format!("Failed to serialize bitmap: {}", e))?;
+            put_stmt
+                .execute(params![token_hash, &buf])
+                .map_err(|e| format!("Failed to write bitmap row: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Subtract `removed` from every posting in `table` inside the caller's
+    /// transaction, dropping postings that become empty.
+    pub(super) fn remove_row_postings(
+        tx: &Transaction<'_>,
+        table: &str,
+        removed: &RoaringBitmap,
+    ) -> Result<(), String> {
+        if removed.is_empty() {
+            return Ok(());
+        }
+        let rows: Vec<(String, Vec<u8>)> = {
+            let mut stmt = tx
+                .prepare(&format!("SELECT token_hash, postings_blob FROM {}", table))
+                .map_err(|e| format!("Failed to prepare bitmap scan: {}", e))?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(|e| format!("Failed to scan bitmap: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows
+        };
+
+        let mut put_stmt = tx
+            .prepare_cached(&format!(
+                "UPDATE {} SET postings_blob = ?2 WHERE token_hash = ?1",
+                table
+            ))
+            .map_err(|e| format!("Failed to prepare bitmap write: {}", e))?;
+        let mut del_stmt = tx
+            .prepare_cached(&format!("DELETE FROM {} WHERE token_hash = ?1", table))
+            .map_err(|e| format!("Failed to prepare bitmap delete: {}", e))?;
+        for (token_hash, blob) in rows {
+            let bitmap = RoaringBitmap::deserialize_from(&blob[..])
+                .map_err(|e| format!("Failed to deserialize bitmap: {}", e))?;
+            if bitmap.is_disjoint(removed) {
+                continue;
+            }
+            let remaining = bitmap - removed;
+            if remaining.is_empty() {
+                del_stmt
+                    .execute(params![&token_hash])
+                    .map_err(|e| format!("Failed to delete empty bitmap row: {}", e))?;
+            } else {
+                let mut buf = Vec::new();
+                remaining
+                    .serialize_into(&mut buf)
+                    .map_err(|e| format!("Failed to serialize bitmap: {}", e))?;
+                put_stmt
+                    .execute(params![&token_hash, &buf])
+                    .map_err(|e| format!("Failed to write bitmap row: {}", e))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+    use std::collections::HashSet;
+
+    const TEST_HMAC_KEY: &[u8] = b"row-postings-test-hmac-key-32-by";
+    const TABLE: &str = "clipboard_bitmap_index";
+
+    fn tokens(text: &str) -> HashSet<String> {
+        StorageState::bigram_tokenize(&text.to_lowercase())
+    }
+
+    fn token_hashes(text: &str) -> Vec<String> {
+        tokens(text)
+            .iter()
+            .map(|token| StorageState::compute_hmac_hash(token, TEST_HMAC_KEY))
+            .collect()
+    }
+
+    #[test]
+    fn postings_are_added_and_subtracted_without_plaintext() {
+        let mut conn = Connection::open_in_memory().expect("in-memory database");
+        conn.execute_batch(
+            "CREATE TABLE clipboard_bitmap_index (
+                token_hash TEXT PRIMARY KEY,
+                postings_blob BLOB NOT NULL
+            );",
+        )
+        .expect("index table");
+
+        let tx = conn.transaction().unwrap();
+        StorageState::add_row_postings(&tx, TABLE, 1, &token_hashes("Tracking 1Z999")).unwrap();
+        StorageState::add_row_postings(&tx, TABLE, 2, &token_hashes("tracking 发票")).unwrap();
+        tx.commit().unwrap();
+
+        let ids = |conn: &Connection, query: &str| -> Vec<u32> {
+            let query_tokens = query.split_whitespace().flat_map(tokens).collect();
+            StorageState::blind_postings_matching_all(conn, TABLE, TEST_HMAC_KEY, query_tokens)
+                .unwrap()
+                .iter()
+                .collect()
+        };
+        assert_eq!(ids(&conn, "TRACKING"), vec![1, 2]);
+        assert_eq!(ids(&conn, "发票"), vec![2]);
+
+        let tx = conn.transaction().unwrap();
+        StorageState::remove_row_postings(&tx, TABLE, &RoaringBitmap::from_iter([2u32])).unwrap();
+        tx.commit().unwrap();
+
+        assert_eq!(ids(&conn, "tracking"), vec![1]);
+        assert!(ids(&conn, "发票").is_empty());
+        let rows: i64 = conn
+            .query_row("SELECT COUNT(*) FROM clipboard_bitmap_index", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(
+            rows,
+            token_hashes("Tracking 1Z999").len() as i64,
+            "only the bigrams of row 1 remain"
+        );
+    }
+}
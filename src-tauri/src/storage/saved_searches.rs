@@ -0,0 +1,439 @@
+//! Saved searches and on-commit match alerts.
+//!
+//! A saved search keeps a display name and query, sealed together with a per-row key
+//! like annotation text, plus the match mode used to run it. Searches with `notify`
+//! set are checked against the OCR text of every newly committed screenshot; callers
+//! raise the notification (see `saved_search_alerts`).
+//!
+//! Alert checks run on the commit path, so decrypted queries are cached in memory
+//! until a saved search changes. While the CNG key is locked the cache cannot be
+//! filled and commits go unchecked. A search that just alerted stays quiet for
+//! `ALERT_COOLDOWN` so text that lingers on screen does not alert on every capture.
+
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use super::search::normalize_phrase;
+use super::search_query::{parse_search_query, ParsedQuery};
+use super::search_regex::compile_search_regex;
+use super::{
+    BackgroundReadError, OcrResultInput, SavedSearch, SavedSearchMatch, SearchMatchMode,
+    StorageState,
+};
+
+const MAX_SAVED_SEARCHES: i64 = 200;
+const MAX_NAME_CHARS: usize = 200;
+const MAX_QUERY_CHARS: usize = 1_000;
+
+/// Minimum time between two alerts of the same saved search.
+const ALERT_COOLDOWN: Duration = Duration::from_secs(10 * 60);
+
+/// Encrypted part of a saved search row.
+#[derive(Serialize, Deserialize)]
+struct SavedSearchPayload {
+    name: String,
+    query: String,
+}
+
+/// A notifying saved search, ready to check against committed text.
+pub(super) struct SavedSearchAlert {
+    id: i64,
+    name: String,
+    matcher: AlertMatcher,
+    last_alert: Option<Instant>,
+}
+
+enum AlertMatcher {
+    /// Parsed query with every term passed through `normalize_phrase`.
+    Terms(ParsedQuery),
+    Phrase(String),
+    Regex(Regex),
+}
+
+impl AlertMatcher {
+    fn new(query: &str, match_mode: SearchMatchMode) -> Result<Self, String> {
+        Ok(match match_mode {
+            SearchMatchMode::Terms => {
+                let mut parsed = parse_search_query(query);
+                for term in parsed.clauses.iter_mut().flatten() {
+                    *term = normalize_phrase(term);
+                }
+                for term in parsed.excluded.iter_mut() {
+                    *term = normalize_phrase(term);
+                }
+                Self::Terms(parsed)
+            }
+            SearchMatchMode::Phrase => Self::Phrase(normalize_phrase(query)),
+            SearchMatchMode::Regex => Self::Regex(compile_search_regex(query)?),
+        })
+    }
+
+    /// Whether a screenshot whose text boxes are `texts` (raw) and `normalized`
+    /// (through `normalize_phrase`) matches. Terms follow `search_text` at screenshot
+    /// level but compare case-insensitively; phrases and regexes must match one box.
+    fn matches(&self, texts: &[&str], normalized: &[String]) -> bool {
+        let contains = |term: &String| normalized.iter().any(|text| text.contains(term.as_str()));
+        match self {
+            Self::Terms(parsed) => {
+                !parsed.clauses.is_empty()
+                    && parsed
+                        .clauses
+                        .iter()
+                        .all(|clause| clause.iter().any(contains))
+                    && !parsed.excluded.iter().any(contains)
+            }
+            Self::Phrase(phrase) => !phrase.is_empty() && contains(phrase),
+            Self::Regex(regex) => texts.iter().any(|text| regex.is_match(text)),
+        }
+    }
+}
+
+fn match_mode_to_sql(match_mode: SearchMatchMode) -> &'static str {
+    match match_mode {
+        SearchMatchMode::Terms => "terms",
+        SearchMatchMode::Phrase => "phrase",
+        SearchMatchMode::Regex => "regex",
+    }
+}
+
+fn match_mode_from_sql(value: &str) -> SearchMatchMode {
+    match value {
+        "phrase" => SearchMatchMode::Phrase,
+        "regex" => SearchMatchMode::Regex,
+        _ => SearchMatchMode::Terms,
+    }
+}
+
+type SavedSearchRow = (
+    i64,
+    Vec<u8>,
+    Vec<u8>,
+    String,
+    bool,
+    String,
+    Option<String>,
+    Option<i64>,
+);
+
+impl StorageState {
+    /// Store a query for later use. An empty `name` defaults to the query; regex
+    /// queries must compile.
+    pub fn create_saved_search(
+        &self,
+        name: &str,
+        query: &str,
+        match_mode: SearchMatchMode,
+        notify: bool,
+    ) -> Result<SavedSearch, String> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Err("Saved search query must not be empty".to_string());
+        }
+        if query.chars().count() > MAX_QUERY_CHARS {
+            return Err(format!(
+                "Saved search query is longer than {} characters",
+                MAX_QUERY_CHARS
+            ));
+        }
+        let name = match name.trim() {
+            "" => query,
+            name => name,
+        };
+        if name.chars().count() > MAX_NAME_CHARS {
+            return Err(format!(
+                "Saved search name is longer than {} characters",
+                MAX_NAME_CHARS
+            ));
+        }
+        AlertMatcher::new(query, match_mode)?;
+
+        let payload = serde_json::to_vec(&SavedSearchPayload {
+            name: name.to_string(),
+            query: query.to_string(),
+        })
+        .map_err(|e| format!("Failed to serialize saved search: {}", e))?;
+        let (payload_enc, payload_key_enc) = self.encrypt_payload_with_row_key(&payload)?;
+
+        let guard = self.get_connection_named("create_saved_search")?;
+        let conn = guard.as_ref().unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM saved_searches", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to count saved searches: {}", e))?;
+        if count >= MAX_SAVED_SEARCHES {
+            return Err(format!(
+                "At most {} saved searches are allowed",
+                MAX_SAVED_SEARCHES
+            ));
+        }
+        conn.execute(
+            "INSERT INTO saved_searches (payload_enc, payload_key_encrypted, match_mode, notify)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                payload_enc,
+                payload_key_enc,
+                match_mode_to_sql(match_mode),
+                notify
+            ],
+        )
+        .map_err(|e| format!("Failed to insert saved search: {}", e))?;
+        let id = conn.last_insert_rowid();
+        let created_at: String = conn
+            .query_row(
+                "SELECT created_at FROM saved_searches WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to read saved search: {}", e))?;
+        drop(guard);
+
+        self.invalidate_saved_search_alerts();
+        Ok(SavedSearch {
+            id,
+            name: name.to_string(),
+            query: query.to_string(),
+            match_mode,
+            notify,
+            created_at,
+            last_matched_at: None,
+            last_matched_screenshot_id: None,
+        })
+    }
+
+    /// All saved searches, oldest first. Rows that fail to decrypt are skipped.
+    pub fn list_saved_searches(&self) -> Result<Vec<SavedSearch>, String> {
+        let rows = self.saved_search_rows(false)?;
+        Ok(rows
+            .into_iter()
+            .filter_map(
+                |(
+                    id,
+                    payload_enc,
+                    payload_key_enc,
+                    match_mode,
+                    notify,
+                    created_at,
+                    last_matched_at,
+                    last_matched_screenshot_id,
+                )| {
+                    let payload = self
+                        .decrypt_payload_with_row_key(&payload_enc, &payload_key_enc)
+                        .map_err(|e| tracing::warn!("Failed to decrypt saved search {}: {}", id, e))
+                        .ok()?;
+                    let payload: SavedSearchPayload = serde_json::from_slice(&payload).ok()?;
+                    Some(SavedSearch {
+                        id,
+                        name: payload.name,
+                        query: payload.query,
+                        match_mode: match_mode_from_sql(&match_mode),
+                        notify,
+                        created_at,
+                        last_matched_at,
+                        last_matched_screenshot_id,
+                    })
+                },
+            )
+            .collect())
+    }
+
+    /// Remove a saved search. Returns `false` when it did not exist.
+    pub fn delete_saved_search(&self, saved_search_id: i64) -> Result<bool, String> {
+        let deleted = {
+            let guard = self.get_connection_named("delete_saved_search")?;
+            let conn = guard.as_ref().unwrap();
+            conn.execute(
+                "DELETE FROM saved_searches WHERE id = ?1",
+                params![saved_search_id],
+            )
+            .map_err(|e| format!("Failed to delete saved search: {}", e))?
+        };
+        self.invalidate_saved_search_alerts();
+        Ok(deleted > 0)
+    }
+
+    /// Notifying saved searches matched by the OCR text just committed for
+    /// `screenshot_id`, excluding those still in their alert cooldown. Text goes
+    /// through the same post-processing as stored OCR text. Failures are logged and
+    /// yield no matches so they never affect the commit.
+    pub(crate) fn match_saved_searches(
+        &self,
+        screenshot_id: i64,
+        ocr_results: &[OcrResultInput],
+    ) -> Vec<SavedSearchMatch> {
+        let mut cache = self
+            .saved_search_alerts
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if cache.is_none() {
+            match self.load_saved_search_alerts() {
+                Ok(Some(alerts)) => *cache = Some(alerts),
+                Ok(None) => return Vec::new(),
+                Err(e) => {
+                    tracing::warn!("Failed to load saved search alerts: {}", e);
+                    return Vec::new();
+                }
+            }
+        }
+        let Some(alerts) = cache.as_mut().filter(|alerts| !alerts.is_empty()) else {
+            return Vec::new();
+        };
+
+        let processed = self.postprocess_ocr_results(ocr_results);
+        let texts: Vec<&str> = processed.iter().map(|r| r.text.as_str()).collect();
+        if texts.is_empty() {
+            return Vec::new();
+        }
+        let normalized: Vec<String> = texts.iter().map(|t| normalize_phrase(t)).collect();
+
+        let now = Instant::now();
+        let mut matched_ids = Vec::new();
+        let mut matches = Vec::new();
+        for alert in alerts.iter_mut() {
+            if !alert.matcher.matches(&texts, &normalized) {
+                continue;
+            }
+            matched_ids.push(alert.id);
+            if alert
+                .last_alert
+                .is_some_and(|last| now.duration_since(last) < ALERT_COOLDOWN)
+            {
+                continue;
+            }
+            alert.last_alert = Some(now);
+            matches.push(SavedSearchMatch {
+                saved_search_id: alert.id,
+                name: alert.name.clone(),
+                screenshot_id,
+            });
+        }
+        drop(cache);
+
+        if !matched_ids.is_empty() {
+            if let Err(e) = self.record_saved_search_matches(&matched_ids, screenshot_id) {
+                tracing::warn!("Failed to record saved search matches: {}", e);
+            }
+        }
+        matches
+    }
+
+    /// Decrypted notifying searches; `None` while the CNG key is locked.
+    fn load_saved_search_alerts(&self) -> Result<Option<Vec<SavedSearchAlert>>, String> {
+        let mut alerts = Vec::new();
+        for (id, payload_enc, payload_key_enc, match_mode, ..) in self.saved_search_rows(true)? {
+            let payload =
+                match self.decrypt_payload_with_row_key_silent(&payload_enc, &payload_key_enc) {
+                    Ok(payload) => payload,
+                    Err(BackgroundReadError::AuthRequired) => return Ok(None),
+                    Err(BackgroundReadError::Other(e)) => {
+                        tracing::warn!("Failed to decrypt saved search {}: {}", id, e);
+                        continue;
+                    }
+                };
+            let Ok(payload) = serde_json::from_slice::<SavedSearchPayload>(&payload) else {
+                continue;
+            };
+            match AlertMatcher::new(&payload.query, match_mode_from_sql(&match_mode)) {
+                Ok(matcher) => alerts.push(SavedSearchAlert {
+                    id,
+                    name: payload.name,
+                    matcher,
+                    last_alert: None,
+                }),
+                Err(e) => tracing::warn!("Skipping saved search {}: {}", id, e),
+            }
+        }
+        Ok(Some(alerts))
+    }
+
+    fn saved_search_rows(&self, notify_only: bool) -> Result<Vec<SavedSearchRow>, String> {
+        let conn = self.open_read_connection_named("saved_search_rows")?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, payload_enc, payload_key_encrypted, match_mode, notify,
+                        created_at, last_matched_at, last_matched_screenshot_id
+                 FROM saved_searches
+                 WHERE notify = 1 OR ?1 = 0
+                 ORDER BY id",
+            )
+            .map_err(|e| format!("Failed to prepare saved search query: {}", e))?;
+        let rows = stmt
+            .query_map(params![notify_only], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                    row.get(6)?,
+                    row.get(7)?,
+                ))
+            })
+            .map_err(|e| format!("Failed to query saved searches: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(rows)
+    }
+
+    fn record_saved_search_matches(
+        &self,
+        saved_search_ids: &[i64],
+        screenshot_id: i64,
+    ) -> Result<(), String> {
+        let guard = self.get_connection_named("record_saved_search_matches")?;
+        let conn = guard.as_ref().unwrap();
+        let mut stmt = conn
+            .prepare_cached(
+                "UPDATE saved_searches
+                 SET last_matched_at = CURRENT_TIMESTAMP, last_matched_screenshot_id = ?2
+                 WHERE id = ?1",
+            )
+            .map_err(|e| format!("Failed to prepare saved search update: {}", e))?;
+        for id in saved_search_ids {
+            stmt.execute(params![id, screenshot_id])
+                .map_err(|e| format!("Failed to update saved search: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Drop cached alert matchers so the next commit reloads them.
+    pub(super) fn invalidate_saved_search_alerts(&self) {
+        *self
+            .saved_search_alerts
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(query: &str, mode: SearchMatchMode, texts: &[&str]) -> bool {
+        let normalized: Vec<String> = texts.iter().map(|t| normalize_phrase(t)).collect();
+        AlertMatcher::new(query, mode)
+            .expect("valid query")
+            .matches(texts, &normalized)
+    }
+
+    #[test]
+    fn terms_match_across_boxes_and_honor_exclusions() {
+        let texts = ["Build FAILED", "exit code 1"];
+        assert!(check("build failed", SearchMatchMode::Terms, &texts));
+        assert!(check("failed OR passed", SearchMatchMode::Terms, &texts));
+        assert!(!check("failed -exit", SearchMatchMode::Terms, &texts));
+        assert!(!check("-exit", SearchMatchMode::Terms, &texts));
+    }
+
+    #[test]
+    fn phrases_and_regexes_match_within_one_box() {
+        let texts = ["Build  failed", "code 1"];
+        assert!(check("build failed", SearchMatchMode::Phrase, &texts));
+        assert!(!check("failed code", SearchMatchMode::Phrase, &texts));
+        assert!(check(r"code \d", SearchMatchMode::Regex, &texts));
+        assert!(!check(r"build", SearchMatchMode::Regex, &texts));
+        assert!(AlertMatcher::new("[", SearchMatchMode::Regex).is_err());
+    }
+}
@@ -0,0 +1,183 @@
+//! Persistence for the background job scheduler (`crate::scheduler`).
+
+use rusqlite::params;
+
+use super::{ScheduledJob, StorageState};
+
+/// Shortest interval a job may be configured with.
+pub const MIN_JOB_INTERVAL_SECS: i64 = 30;
+
+impl StorageState {
+    /// Insert a job with its defaults unless it already exists; user changes are kept.
+    pub fn register_scheduled_job(
+        &self,
+        name: &str,
+        default_enabled: bool,
+        default_interval_secs: i64,
+    ) -> Result<(), String> {
+        let guard = self.get_connection_named("register_scheduled_job")?;
+        let conn = guard.as_ref().unwrap();
+        conn.execute(
+            "INSERT OR IGNORE INTO scheduled_jobs (name, enabled, interval_secs) VALUES (?, ?, ?)",
+            params![name, default_enabled as i32, default_interval_secs],
+        )
+        .map_err(|e| format!("Failed to register scheduled job: {}", e))?;
+        Ok(())
+    }
+
+    /// List all registered jobs ordered by name.
+    pub fn list_scheduled_jobs(&self) -> Result<Vec<ScheduledJob>, String> {
+        let guard = self.get_connection_named("list_scheduled_jobs")?;
+        let conn = guard.as_ref().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT name, enabled, interval_secs, last_run_at, last_status, last_result,
+                        last_duration_ms, run_count
+                 FROM scheduled_jobs ORDER BY name",
+            )
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+        let jobs = stmt
+            .query_map([], |row| {
+                Ok(ScheduledJob {
+                    name: row.get(0)?,
+                    enabled: row.get::<_, i32>(1)? != 0,
+                    interval_secs: row.get(2)?,
+                    last_run_at: row.get(3)?,
+                    last_status: row.get(4)?,
+                    last_result: row.get(5)?,
+                    last_duration_ms: row.get(6)?,
+                    run_count: row.get(7)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query scheduled jobs: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(jobs)
+    }
+
+    /// Get a single job by name.
+    pub fn get_scheduled_job(&self, name: &str) -> Result<Option<ScheduledJob>, String> {
+        Ok(self
+            .list_scheduled_jobs()?
+            .into_iter()
+            .find(|job| job.name == name))
+    }
+
+    /// Enable or disable a job. Returns `false` if no such job exists.
+    pub fn set_scheduled_job_enabled(&self, name: &str, enabled: bool) -> Result<bool, String> {
+        let guard = self.get_connection_named("set_scheduled_job_enabled")?;
+        let conn = guard.as_ref().unwrap();
+        let changed = conn
+            .execute(
+                "UPDATE scheduled_jobs SET enabled = ? WHERE name = ?",
+                params![enabled as i32, name],
+            )
+            .map_err(|e| format!("Failed to update scheduled job: {}", e))?;
+        Ok(changed > 0)
+    }
+
+    /// Change a job's interval. Returns `false` if no such job exists.
+    pub fn set_scheduled_job_interval(
+        &self,
+        name: &str,
+        interval_secs: i64,
+    ) -> Result<bool, String> {
+        if interval_secs < MIN_JOB_INTERVAL_SECS {
+            return Err(format!(
+                "Interval must be at least {} seconds",
+                MIN_JOB_INTERVAL_SECS
+            ));
+        }
+        let guard = self.get_connection_named("set_scheduled_job_interval")?;
+        let conn = guard.as_ref().unwrap();
+        let changed = conn
+            .execute(
+                "UPDATE scheduled_jobs SET interval_secs = ? WHERE name = ?",
+                params![interval_secs, name],
+            )
+            .map_err(|e| format!("Failed to update scheduled job: {}", e))?;
+        Ok(changed > 0)
+    }
+
+    /// Record the outcome of a run that started at `started_at` (Unix seconds).
+    pub fn record_scheduled_job_run(
+        &self,
+        name: &str,
+        started_at: i64,
+        status: &str,
+        result: Option<&str>,
+        duration_ms: i64,
+    ) -> Result<(), String> {
+        let guard = self.get_connection_named("record_scheduled_job_run")?;
+        let conn = guard.as_ref().unwrap();
+        conn.execute(
+            "UPDATE scheduled_jobs
+             SET last_run_at = ?, last_status = ?, last_result = ?, last_duration_ms = ?,
+                 run_count = run_count + 1
+             WHERE name = ?",
+            params![started_at, status, result, duration_ms, name],
+        )
+        .map_err(|e| format!("Failed to record scheduled job run: {}", e))?;
+        Ok(())
+    }
+
+    /// Names of enabled jobs whose interval has elapsed at `now` (Unix seconds).
+    pub fn due_scheduled_jobs(&self, now: i64) -> Result<Vec<String>, String> {
+        let guard = self.get_connection_named("due_scheduled_jobs")?;
+        let conn = guard.as_ref().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT name FROM scheduled_jobs
+                 WHERE enabled = 1 AND (last_run_at IS NULL OR last_run_at + interval_secs <= ?)
+                 ORDER BY COALESCE(last_run_at, 0), name",
+            )
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+        let names = stmt
+            .query_map([now], |row| row.get(0))
+            .map_err(|e| format!("Failed to query due jobs: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(names)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_storage;
+
+    #[test]
+    fn registration_keeps_user_overrides() {
+        let (_temp, storage) = test_storage();
+        storage
+            .register_scheduled_job("digest", false, 3600)
+            .unwrap();
+        assert!(storage.set_scheduled_job_enabled("digest", true).unwrap());
+        storage
+            .register_scheduled_job("digest", false, 7200)
+            .unwrap();
+
+        let job = storage.get_scheduled_job("digest").unwrap().unwrap();
+        assert!(job.enabled);
+        assert_eq!(job.interval_secs, 3600);
+        assert!(!storage.set_scheduled_job_enabled("missing", true).unwrap());
+    }
+
+    #[test]
+    fn due_jobs_follow_interval_and_enabled_flag() {
+        let (_temp, storage) = test_storage();
+        storage.register_scheduled_job("a", true, 60).unwrap();
+        storage.register_scheduled_job("b", false, 60).unwrap();
+        assert_eq!(storage.due_scheduled_jobs(1_000).unwrap(), vec!["a"]);
+
+        storage
+            .record_scheduled_job_run("a", 1_000, "ok", Some("done"), 5)
+            .unwrap();
+        assert!(storage.due_scheduled_jobs(1_059).unwrap().is_empty());
+        assert_eq!(storage.due_scheduled_jobs(1_060).unwrap(), vec!["a"]);
+
+        let job = storage.get_scheduled_job("a").unwrap().unwrap();
+        assert_eq!(job.run_count, 1);
+        assert_eq!(job.last_result.as_deref(), Some("done"));
+    }
+}
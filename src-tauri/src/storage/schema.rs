@@ -3,7 +3,7 @@
 use crate::credential_manager::{
     derive_db_key_from_public_key, get_cached_public_key, load_public_key_from_file,
 };
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OpenFlags};
 use std::sync::atomic::Ordering;
 
 use super::StorageState;
@@ -31,10 +31,13 @@ impl StorageState {
             .unwrap_or_else(|e| e.into_inner())
             .clone();
 
-        std::fs::create_dir_all(&data_dir)
-            .map_err(|e| format!("Failed to create data directory: {}", e))?;
-        std::fs::create_dir_all(&screenshot_dir)
-            .map_err(|e| format!("Failed to create screenshot directory: {}", e))?;
+        let read_only = self.is_read_only();
+        if !read_only {
+            std::fs::create_dir_all(&data_dir)
+                .map_err(|e| format!("Failed to create data directory: {}", e))?;
+            std::fs::create_dir_all(&screenshot_dir)
+                .map_err(|e| format!("Failed to create screenshot directory: {}", e))?;
+        }
 
         let t0 = std::time::Instant::now();
         // Derive weak database key from public key (no user authentication required)
@@ -47,8 +50,13 @@ impl StorageState {
         // Open SQLCipher encrypted database
         let t1 = std::time::Instant::now();
         let db_path = data_dir.join("screenshots.db");
-        let conn =
-            Connection::open(&db_path).map_err(|e| format!("Failed to open database: {}", e))?;
+        let mut conn = if read_only {
+            // Guest mode: SQLite itself rejects every write on this connection.
+            Connection::open_with_flags(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        } else {
+            Connection::open(&db_path)
+        }
+        .map_err(|e| format!("Failed to open database: {}", e))?;
         let open_dur = t1.elapsed();
 
         // Set SQLCipher key (hex format)
@@ -58,18 +66,36 @@ impl StorageState {
             .map_err(|e| format!("Failed to set database key: {}", e))?;
 
         // Verify that the key is correct
-        conn.execute_batch("SELECT count(*) FROM sqlite_master;")
-            .map_err(|e| format!("Database key verification failed: {}", e))?;
+        if let Err(e) = conn.execute_batch("SELECT count(*) FROM sqlite_master;") {
+            conn = self
+                .open_with_rotated_key(&db_path, read_only)?
+                .ok_or_else(|| format!("Database key verification failed: {}", e))?;
+        }
         let pragma_dur = t2.elapsed();
 
+        // WAL lets pooled read connections (timeline, search) proceed while the
+        // writer commits, instead of queueing behind it.
+        conn.busy_timeout(std::time::Duration::from_millis(
+            super::DB_BUSY_TIMEOUT_MS as u64,
+        ))
+        .map_err(|e| format!("Failed to set database busy timeout: {}", e))?;
+        if !read_only {
+            Self::enable_wal(&conn)?;
+        }
+
         // Initialize table schema
         let t3 = std::time::Instant::now();
-        self.init_tables(&conn)?;
-        self.cleanup_derived_index_sidecars_at_startup(&conn, &data_dir)?;
-        Self::set_auto_vacuum_incremental(&conn)?;
+        if read_only {
+            tracing::info!("Guest mode: opened database read-only, skipping schema upgrades");
+        } else {
+            self.init_tables(&conn)?;
+            self.cleanup_derived_index_sidecars_at_startup(&conn, &data_dir)?;
+            Self::set_auto_vacuum_incremental(&conn)?;
+        }
         let tables_dur = t3.elapsed();
 
         *self.db.lock().unwrap_or_else(|e| e.into_inner()) = Some(conn);
+        self.reset_read_pool();
 
         // Initialize approximate OCR row count using MAX(id) — O(log N) via primary key index.
         // AUTOINCREMENT ids only increase, so MAX(id) >= actual row count; acceptable for IDF.
@@ -88,6 +114,19 @@ impl StorageState {
 
         *initialized = true;
 
+        // Captures interrupted by a crash; see `storage/pending_recovery.rs`.
+        if !read_only {
+            match self.recover_orphaned_pending() {
+                Ok(summary) => {
+                    *self
+                        .pending_recovery_summary
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner()) = Some(summary);
+                }
+                Err(e) => tracing::warn!("[DIAG:INIT] pending recovery failed: {}", e),
+            }
+        }
+
         tracing::info!(
             "[DIAG:INIT] SQLCipher initialized in {:?} (key_derive={:?}, db_open={:?}, pragma={:?}, init_tables={:?})",
             init_start.elapsed(),
@@ -103,7 +142,17 @@ impl StorageState {
     /// Shut down storage: close database connection.
     pub fn shutdown(&self) -> Result<(), String> {
         self.lazy_indexer_shutdown.store(true, Ordering::SeqCst);
+        self.reset_read_pool();
+        self.invalidate_saved_search_alerts();
+        self.invalidate_search_history();
+        self.flush_decrypt_cache();
         let mut db_guard = self.db.lock().map_err(|e| format!("lock error: {}", e))?;
+        if let Some(conn) = db_guard.as_ref() {
+            // Fold the WAL back into screenshots.db so export/migration copy one file.
+            if let Err(e) = conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE);") {
+                tracing::warn!("Failed to checkpoint WAL on shutdown: {}", e);
+            }
+        }
         if db_guard.is_some() {
             *db_guard = None;
         }
@@ -524,6 +573,20 @@ impl StorageState {
         )
     }
 
+    /// Switch to WAL journaling. The mode is persistent in the database file.
+    pub(super) fn enable_wal(conn: &Connection) -> Result<(), String> {
+        let mode: String = conn
+            .query_row("PRAGMA journal_mode = WAL;", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to set PRAGMA journal_mode=WAL: {}", e))?;
+        if !mode.eq_ignore_ascii_case("wal") {
+            tracing::warn!("SQLite journal_mode is {} instead of wal", mode);
+        }
+        // NORMAL is durable across application crashes in WAL mode; only an OS
+        // crash can lose the last commits.
+        conn.execute_batch("PRAGMA synchronous = NORMAL;")
+            .map_err(|e| format!("Failed to set PRAGMA synchronous=NORMAL: {}", e))
+    }
+
     fn set_auto_vacuum_incremental(conn: &Connection) -> Result<(), String> {
         conn.execute_batch("PRAGMA auto_vacuum = INCREMENTAL;")
             .map_err(|e| format!("Failed to set PRAGMA auto_vacuum=INCREMENTAL: {}", e))
@@ -703,6 +766,22 @@ impl StorageState {
         Self::add_column_if_missing(conn, "screenshots", "category", "TEXT")?;
         Self::add_column_if_missing(conn, "screenshots", "category_confidence", "REAL")?;
 
+        // UTC offset active at capture time (NULL for rows captured before it was recorded)
+        Self::add_column_if_missing(conn, "screenshots", "utc_offset_minutes", "INTEGER")?;
+
+        // dHash for near-duplicate suppression (NULL when the check was off at save time)
+        Self::add_column_if_missing(conn, "screenshots", "perceptual_hash", "BLOB")?;
+
+        // Tiny downscaled copy shown pixelated before unlock (NULL in compliance mode)
+        Self::add_column_if_missing(conn, "screenshots", "preview_image", "BLOB")?;
+
+        // Pinned screenshots are skipped by retention, quota reclaim and range deletion
+        Self::add_column_if_missing(conn, "screenshots", "pinned", "INTEGER NOT NULL DEFAULT 0")?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_screenshots_pinned ON screenshots(created_at) WHERE pinned = 1;",
+        )
+        .map_err(|e| format!("Failed to create pinned index: {}", e))?;
+
         Self::add_column_if_missing(conn, "derived_index_generations", "model_id", "TEXT")?;
         Self::add_column_if_missing(
             conn,
@@ -925,6 +1004,388 @@ impl StorageState {
             "#,
         )?;
 
+        Self::create_table_if_missing(
+            conn,
+            "scheduled_jobs",
+            r#"
+            CREATE TABLE IF NOT EXISTS scheduled_jobs (
+                name TEXT PRIMARY KEY,
+                enabled INTEGER NOT NULL DEFAULT 1,
+                interval_secs INTEGER NOT NULL,
+                last_run_at INTEGER,
+                last_status TEXT,
+                last_result TEXT,
+                last_duration_ms INTEGER,
+                run_count INTEGER NOT NULL DEFAULT 0
+            )
+            "#,
+        )?;
+
+        Self::create_table_if_missing(
+            conn,
+            "access_audit_log",
+            r#"
+            CREATE TABLE IF NOT EXISTS access_audit_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                action TEXT NOT NULL,
+                target_enc BLOB,
+                target_key_encrypted BLOB
+            )
+            "#,
+        )?;
+        // Who performed the read ("app" or "mcp"); older entries have NULL.
+        Self::add_column_if_missing(conn, "access_audit_log", "actor", "TEXT")?;
+        // The audit log is append-only.
+        conn.execute_batch(
+            r#"
+            CREATE TRIGGER IF NOT EXISTS access_audit_log_no_update
+            BEFORE UPDATE ON access_audit_log
+            BEGIN
+                SELECT RAISE(ABORT, 'access_audit_log is append-only');
+            END;
+            CREATE TRIGGER IF NOT EXISTS access_audit_log_no_delete
+            BEFORE DELETE ON access_audit_log
+            BEGIN
+                SELECT RAISE(ABORT, 'access_audit_log is append-only');
+            END;
+            "#,
+        )
+        .map_err(|e| format!("Failed to create audit log triggers: {}", e))?;
+
+        // User labels; matching is case-insensitive so "Receipt" and "receipt" are one tag.
+        Self::create_table_if_missing(
+            conn,
+            "tags",
+            r#"
+            CREATE TABLE IF NOT EXISTS tags (
+                screenshot_id INTEGER NOT NULL,
+                tag TEXT NOT NULL COLLATE NOCASE,
+                created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (screenshot_id, tag),
+                FOREIGN KEY (screenshot_id) REFERENCES screenshots(id) ON DELETE CASCADE
+            )
+            "#,
+        )?;
+        conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_tags_tag ON tags(tag);")
+            .map_err(|e| format!("Failed to create tag index: {}", e))?;
+
+        // User notes, encrypted with a per-row key like OCR text.
+        Self::create_table_if_missing(
+            conn,
+            "annotations",
+            r#"
+            CREATE TABLE IF NOT EXISTS annotations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                screenshot_id INTEGER NOT NULL,
+                text_enc BLOB NOT NULL,
+                text_key_encrypted BLOB NOT NULL,
+                created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (screenshot_id) REFERENCES screenshots(id) ON DELETE CASCADE
+            )
+            "#,
+        )?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_annotations_screenshot ON annotations(screenshot_id);",
+        )
+        .map_err(|e| format!("Failed to create annotation index: {}", e))?;
+
+        // Blind bigram postings of annotation IDs. Kept apart from
+        // `blind_bitmap_index`, whose postings are OCR row IDs and are compacted
+        // against `ocr_results`.
+        Self::create_table_if_missing(
+            conn,
+            "annotation_bitmap_index",
+            r#"
+            CREATE TABLE IF NOT EXISTS annotation_bitmap_index (
+                token_hash TEXT PRIMARY KEY,
+                postings_blob BLOB NOT NULL
+            )
+            "#,
+        )?;
+
+        // Timeline activity sessions; see `storage/sessions.rs`.
+        Self::create_table_if_missing(
+            conn,
+            "activity_sessions",
+            r#"
+            CREATE TABLE IF NOT EXISTS activity_sessions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                process_name TEXT,
+                start_ts INTEGER NOT NULL,
+                end_ts INTEGER NOT NULL,
+                first_screenshot_id INTEGER NOT NULL,
+                last_screenshot_id INTEGER NOT NULL,
+                screenshot_count INTEGER NOT NULL
+            )
+            "#,
+        )?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_activity_sessions_range ON activity_sessions(start_ts, end_ts);
+             CREATE INDEX IF NOT EXISTS idx_activity_sessions_last ON activity_sessions(last_screenshot_id);",
+        )
+        .map_err(|e| format!("Failed to create session indexes: {}", e))?;
+
+        // Saved queries, sealed with a row key; see `storage/saved_searches.rs`.
+        Self::create_table_if_missing(
+            conn,
+            "saved_searches",
+            r#"
+            CREATE TABLE IF NOT EXISTS saved_searches (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                payload_enc BLOB NOT NULL,
+                payload_key_encrypted BLOB NOT NULL,
+                match_mode TEXT NOT NULL DEFAULT 'terms',
+                notify INTEGER NOT NULL DEFAULT 1,
+                created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                last_matched_at TIMESTAMP,
+                last_matched_screenshot_id INTEGER
+            )
+            "#,
+        )?;
+
+        // Recent search queries, sealed with a row key; see `storage/search_history.rs`.
+        Self::create_table_if_missing(
+            conn,
+            "search_history",
+            r#"
+            CREATE TABLE IF NOT EXISTS search_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                query_enc BLOB NOT NULL,
+                query_key_encrypted BLOB NOT NULL,
+                use_count INTEGER NOT NULL DEFAULT 1,
+                last_used_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )?;
+
+        // Blind postings of screenshot IDs for page URLs, link text and domains; see
+        // `storage/metadata_index.rs`.
+        Self::create_table_if_missing(
+            conn,
+            "metadata_bitmap_index",
+            r#"
+            CREATE TABLE IF NOT EXISTS metadata_bitmap_index (
+                token_hash TEXT PRIMARY KEY,
+                postings_blob BLOB NOT NULL
+            )
+            "#,
+        )?;
+        Self::add_column_if_missing(
+            conn,
+            "screenshots",
+            "metadata_index_version",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_screenshots_metadata_index_version
+             ON screenshots(metadata_index_version);",
+        )
+        .map_err(|e| format!("Failed to create metadata index version index: {}", e))?;
+
+        // Trashed screenshots are hidden like soft-deleted ones but keep their files
+        // until restored or purged; see `storage/trash.rs`.
+        Self::add_column_if_missing(conn, "screenshots", "deleted_at", "TIMESTAMP")?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_screenshots_deleted_at
+             ON screenshots(deleted_at) WHERE deleted_at IS NOT NULL;",
+        )
+        .map_err(|e| format!("Failed to create trash index: {}", e))?;
+
+        // Periods retention and time-range deletion must keep; see
+        // `storage/protected_ranges.rs`.
+        Self::create_table_if_missing(
+            conn,
+            "protected_ranges",
+            r#"
+            CREATE TABLE IF NOT EXISTS protected_ranges (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                start_at TIMESTAMP NOT NULL,
+                end_at TIMESTAMP NOT NULL,
+                label TEXT,
+                created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )?;
+
+        // Keyed hash of the process name so process filters still work when the
+        // plaintext column is left empty; see `storage/paranoid.rs`.
+        Self::add_column_if_missing(conn, "screenshots", "process_name_hash", "TEXT")?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_screenshots_process_name_hash
+             ON screenshots(process_name_hash) WHERE process_name_hash IS NOT NULL;",
+        )
+        .map_err(|e| format!("Failed to create process name hash index: {}", e))?;
+
+        // Sessions group on the blind index instead of the plaintext name; sessions
+        // segmented on the plaintext name are dropped and rebuilt.
+        Self::add_column_if_missing(conn, "activity_sessions", "process_key", "TEXT")?;
+        conn.execute_batch(
+            "DELETE FROM activity_sessions
+             WHERE EXISTS (SELECT 1 FROM activity_sessions WHERE process_name IS NOT NULL);",
+        )
+        .map_err(|e| format!("Failed to reset legacy activity sessions: {}", e))?;
+
+        // Screenshots stored as a patch against a keyframe; see `storage/delta_frame.rs`.
+        Self::create_table_if_missing(
+            conn,
+            "delta_keyframes",
+            r#"
+            CREATE TABLE IF NOT EXISTS delta_keyframes (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                image_path TEXT NOT NULL,
+                key_encrypted BLOB NOT NULL,
+                created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )?;
+        Self::add_column_if_missing(conn, "screenshots", "delta_keyframe_id", "INTEGER")?;
+        Self::add_column_if_missing(conn, "screenshots", "delta_x", "INTEGER")?;
+        Self::add_column_if_missing(conn, "screenshots", "delta_y", "INTEGER")?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_screenshots_delta_keyframe
+             ON screenshots(delta_keyframe_id) WHERE delta_keyframe_id IS NOT NULL;",
+        )
+        .map_err(|e| format!("Failed to create delta keyframe index: {}", e))?;
+
+        // Closed months moved into their own database file; see `storage/month_shards.rs`.
+        Self::create_table_if_missing(
+            conn,
+            "month_shards",
+            r#"
+            CREATE TABLE IF NOT EXISTS month_shards (
+                month TEXT PRIMARY KEY,
+                screenshot_count INTEGER NOT NULL,
+                detached_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )?;
+        Self::create_table_if_missing(
+            conn,
+            "month_shard_keyframes",
+            r#"
+            CREATE TABLE IF NOT EXISTS month_shard_keyframes (
+                month TEXT NOT NULL,
+                keyframe_id INTEGER NOT NULL,
+                PRIMARY KEY (month, keyframe_id)
+            )
+            "#,
+        )?;
+
+        // Row keys re-wrapped for a key rotation that has not switched over yet;
+        // see `storage/key_rotation.rs`.
+        Self::create_table_if_missing(
+            conn,
+            "key_rotation_staging",
+            r#"
+            CREATE TABLE IF NOT EXISTS key_rotation_staging (
+                table_name TEXT NOT NULL,
+                row_id INTEGER NOT NULL,
+                old_key_encrypted BLOB NOT NULL,
+                key_encrypted BLOB NOT NULL,
+                PRIMARY KEY (table_name, row_id)
+            )
+            "#,
+        )?;
+
+        // Screenshots and changelog batches already sent to the sync target;
+        // see `storage/remote_sync/mod.rs`.
+        Self::create_table_if_missing(
+            conn,
+            "sync_uploads",
+            r#"
+            CREATE TABLE IF NOT EXISTS sync_uploads (
+                image_hash TEXT PRIMARY KEY,
+                files TEXT NOT NULL,
+                changelog_seq INTEGER NOT NULL,
+                uploaded_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )?;
+        Self::create_table_if_missing(
+            conn,
+            "sync_changelog",
+            r#"
+            CREATE TABLE IF NOT EXISTS sync_changelog (
+                seq INTEGER PRIMARY KEY,
+                screenshots INTEGER NOT NULL,
+                deleted INTEGER NOT NULL,
+                created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )?;
+
+        // Copied text, sealed with a row key together with the window it was copied
+        // from; see `storage/clipboard.rs`.
+        Self::create_table_if_missing(
+            conn,
+            "clipboard_entries",
+            r#"
+            CREATE TABLE IF NOT EXISTS clipboard_entries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                captured_at INTEGER NOT NULL,
+                payload_enc BLOB NOT NULL,
+                payload_key_encrypted BLOB NOT NULL,
+                content_hash TEXT,
+                indexed INTEGER NOT NULL DEFAULT 0
+            )
+            "#,
+        )?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_clipboard_entries_captured_at
+             ON clipboard_entries(captured_at);
+             CREATE INDEX IF NOT EXISTS idx_clipboard_entries_unindexed
+             ON clipboard_entries(id) WHERE indexed = 0;",
+        )
+        .map_err(|e| format!("Failed to create clipboard indexes: {}", e))?;
+        // Blind bigram postings of clipboard entry IDs.
+        Self::create_table_if_missing(
+            conn,
+            "clipboard_bitmap_index",
+            r#"
+            CREATE TABLE IF NOT EXISTS clipboard_bitmap_index (
+                token_hash TEXT PRIMARY KEY,
+                postings_blob BLOB NOT NULL
+            )
+            "#,
+        )?;
+        // Audio transcripts: one row per recorded chunk and source. Text, language and
+        // segment timings are sealed with a per-row key; the unique key makes a
+        // replayed save from the monitor a no-op.
+        Self::create_table_if_missing(
+            conn,
+            "audio_transcripts",
+            r#"
+            CREATE TABLE IF NOT EXISTS audio_transcripts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                started_at INTEGER NOT NULL,
+                ended_at INTEGER NOT NULL,
+                source TEXT NOT NULL,
+                payload_enc BLOB NOT NULL,
+                payload_key_encrypted BLOB NOT NULL,
+                indexed INTEGER NOT NULL DEFAULT 0,
+                UNIQUE(started_at, source)
+            )
+            "#,
+        )?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_audio_transcripts_unindexed
+             ON audio_transcripts(id) WHERE indexed = 0;",
+        )
+        .map_err(|e| format!("Failed to create audio transcript indexes: {}", e))?;
+        // Blind bigram postings of audio transcript IDs.
+        Self::create_table_if_missing(
+            conn,
+            "audio_bitmap_index",
+            r#"
+            CREATE TABLE IF NOT EXISTS audio_bitmap_index (
+                token_hash TEXT PRIMARY KEY,
+                postings_blob BLOB NOT NULL
+            )
+            "#,
+        )?;
+
         conn.execute_batch(
             r#"
             CREATE INDEX IF NOT EXISTS idx_screenshots_deleted_created_at ON screenshots(is_deleted, created_at);
@@ -973,3 +1434,36 @@ impl StorageState {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wal_readers_do_not_block_behind_an_open_write() {
+        let temp = tempfile::tempdir().expect("temp storage directory");
+        let db_path = temp.path().join("screenshots.db");
+        let mut writer = Connection::open(&db_path).expect("writer connection");
+        StorageState::enable_wal(&writer).expect("enable WAL");
+        writer
+            .execute_batch("CREATE TABLE t (v INTEGER); INSERT INTO t VALUES (1);")
+            .expect("fixture");
+
+        let tx = writer.transaction().expect("write transaction");
+        tx.execute("INSERT INTO t VALUES (2)", [])
+            .expect("uncommitted write");
+
+        let reader = Connection::open_with_flags(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .expect("reader connection");
+        let visible: i64 = reader
+            .query_row("SELECT COUNT(*) FROM t", [], |row| row.get(0))
+            .expect("read during write");
+        assert_eq!(visible, 1);
+
+        tx.commit().expect("commit");
+        let visible: i64 = reader
+            .query_row("SELECT COUNT(*) FROM t", [], |row| row.get(0))
+            .expect("read after commit");
+        assert_eq!(visible, 2);
+    }
+}
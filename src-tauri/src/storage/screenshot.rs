@@ -10,16 +10,23 @@ use roaring::RoaringBitmap;
 use rusqlite::{params, Connection, OptionalExtension};
 use std::sync::atomic::Ordering;
 
+use super::capture_day::current_utc_offset_minutes;
+use super::cold_storage::stored_image_size;
+use super::delta_frame::DELTA_EXTENSION;
+use super::protected_ranges::PROTECTED_RANGE_MATCH;
 use super::types::RawScreenshotRow;
 use super::{
     BackgroundReadError, BackgroundScreenshotSummary, DeleteQueueStatus, DensityBucket,
     IndexStorageStats, OcrResultInput, QueueScreenshotCandidate, SaveScreenshotRequest,
     SaveScreenshotResponse, ScreenshotRecord, SoftDeleteResult, SoftDeleteScreenshotsResult,
-    StorageState,
+    StorageState, TimeRangeDeleteResult, TimelineCursor, TimelinePage,
 };
 
 const MAX_OCR_POSTPROCESS_ATTEMPTS: i64 = 5;
 
+/// `(screenshot_id, text_enc, text_key_encrypted)` as read from `ocr_results`.
+pub(super) type EncryptedOcrRow = (i64, Option<Vec<u8>>, Option<Vec<u8>>);
+
 struct EncryptedOcrResultRow {
     id: i64,
     screenshot_id: i64,
@@ -74,7 +81,7 @@ fn ocr_postprocess_retry_decision(current_attempts: i64) -> (&'static str, Optio
     }
 }
 
-fn validate_ocr_result(result: &OcrResultInput) -> Result<(), String> {
+pub(super) fn validate_ocr_result(result: &OcrResultInput) -> Result<(), String> {
     if result.box_coords.len() != 4 || result.box_coords.iter().any(|point| point.len() != 2) {
         return Err("OCR box must contain exactly four 2D points".to_string());
     }
@@ -104,7 +111,7 @@ impl StorageState {
         error: Option<&str>,
         elapsed_ms: Option<f64>,
     ) -> Result<(), String> {
-        const VALID_STATUSES: &[&str] = &["pending", "running", "completed", "failed"];
+        const VALID_STATUSES: &[&str] = &["pending", "running", "completed", "failed", "skipped"];
         if !VALID_STATUSES.contains(&status) {
             return Err(format!("Invalid OCR status: {status}"));
         }
@@ -390,8 +397,9 @@ impl StorageState {
         let mut row_key = vec![0u8; 32];
         rand::thread_rng().fill_bytes(&mut row_key);
 
-        // Encrypt image data
-        let encrypted_image = encrypt_with_master_key(&row_key, &image_data)
+        // Re-encode with the configured codec, then encrypt
+        let (stored_image, image_ext) = self.encode_image_for_storage(&image_data);
+        let encrypted_image = encrypt_with_master_key(&row_key, &stored_image)
             .map_err(|e| format!("Failed to encrypt image: {}", e))?;
         let encrypted_row_key = self
             .wrap_row_key_for_storage(&row_key)
@@ -399,7 +407,7 @@ impl StorageState {
 
         // Generate filename (use .enc extension to indicate encrypted file)
         let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S_%3f");
-        let filename = format!("screenshot_{}.png.enc", timestamp);
+        let filename = format!("screenshot_{}.{}.enc", timestamp, image_ext);
         let screenshot_dir = self
             .screenshot_dir
             .lock()
@@ -417,6 +425,9 @@ impl StorageState {
         if let Err(e) = self.generate_thumbnail_from_data(&image_data, &image_path, &row_key) {
             tracing::warn!("Failed to generate thumbnail during save: {}", e);
         }
+        let preview_image = self.privacy_preview_for_save(&image_data);
+        let metadata_token_hashes = self.metadata_token_hashes_for_save(request);
+        let ocr_hmac_key = self.ocr_hmac_key_for_save();
 
         // Save to database (SQLCipher whole-database encryption)
         let mut guard = self.get_connection_named("save_screenshot")?;
@@ -468,21 +479,34 @@ impl StorageState {
 
         Self::zeroize_bytes(&mut row_key);
 
-        conn.execute(
+        // Paranoid and compliance mode keep the process name encrypted-only.
+        let plaintext_process_name = if self.plaintext_metadata_allowed() {
+            request.process_name.clone()
+        } else {
+            None
+        };
+        let process_name_hash = self.process_name_hash(request.process_name.as_deref());
+
+        // One transaction for the screenshot and all its OCR rows.
+        let mut tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+        tx.execute(
             "INSERT INTO screenshots (
                 image_path, image_hash, width, height,
                 window_title, process_name, metadata,
                 window_title_enc, process_name_enc, metadata_enc,
                 content_key_encrypted,
-                source, page_url_enc, page_icon_id, link_set_id
-             ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                source, page_url_enc, page_icon_id, link_set_id, utc_offset_minutes,
+                preview_image, process_name_hash
+             ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
             params![
                 &image_path_str,
                 &request.image_hash,
                 request.width,
                 request.height,
                 Option::<String>::None,
-                request.process_name.clone(), // plaintext for fast aggregation
+                plaintext_process_name, // plaintext for fast aggregation
                 Option::<String>::None,
                 window_title_enc,
                 process_name_enc,
@@ -492,17 +516,26 @@ impl StorageState {
                 page_url_enc_save,
                 page_icon_id,
                 link_set_id,
+                current_utc_offset_minutes(),
+                preview_image,
+                process_name_hash,
             ],
         )
         .map_err(|e| format!("Failed to insert screenshot: {}", e))?;
 
-        let screenshot_id = conn.last_insert_rowid();
+        let screenshot_id = tx.last_insert_rowid();
+        Self::index_metadata_on_save(&tx, screenshot_id, metadata_token_hashes);
 
         // Save OCR results
         let mut added = 0;
         let mut skipped = 0;
+        let mut indexed_rows = Vec::new();
 
-        if let Some(ocr_results) = &request.ocr_results {
+        let ocr_results = request
+            .ocr_results
+            .as_deref()
+            .map(|results| self.postprocess_ocr_results(results));
+        if let Some(ocr_results) = &ocr_results {
             for result in ocr_results {
                 if let Err(error) = validate_ocr_result(result) {
                     tracing::warn!("Skipping invalid OCR result during save: {}", error);
@@ -515,7 +548,7 @@ impl StorageState {
                 // Check for duplicates
                 let box_coords = &result.box_coords;
                 if box_coords.len() >= 4 {
-                    let existing: i64 = conn
+                    let existing: i64 = tx
                         .query_row(
                             "SELECT COUNT(*) FROM ocr_results
                              WHERE screenshot_id = ?
@@ -531,8 +564,9 @@ impl StorageState {
                         continue;
                     }
 
-                    // Insert new OCR result with encrypted text (lazy indexing will process this later)
-                    conn.execute(
+                    // Insert new OCR result with encrypted text; it is indexed below with the
+                    // rest of the screenshot, or by the lazy indexer without an HMAC key.
+                    tx.execute(
                         "INSERT INTO ocr_results (
                             screenshot_id, text, text_hash, text_enc, text_key_encrypted, confidence,
                             box_x1, box_y1, box_x2, box_y2,
@@ -553,12 +587,23 @@ impl StorageState {
                     )
                     .map_err(|e| format!("Failed to insert OCR result: {}", e))?;
 
+                    if let Some(key) = &ocr_hmac_key {
+                        let (text_hash, token_hashes) = Self::ocr_index_hashes(&result.text, key);
+                        indexed_rows.push((tx.last_insert_rowid(), text_hash, token_hashes));
+                    }
                     added += 1;
-                    self.ocr_row_count.fetch_add(1, Ordering::Relaxed);
                 }
             }
         }
 
+        Self::index_ocr_rows_on_commit(&mut tx, screenshot_id, &indexed_rows);
+        tx.commit()
+            .map_err(|e| format!("Failed to commit screenshot: {}", e))?;
+        if added > 0 {
+            self.ocr_row_count
+                .fetch_add(added as u64, Ordering::Relaxed);
+        }
+
         Ok(SaveScreenshotResponse {
             status: "success".to_string(),
             screenshot_id: Some(screenshot_id),
@@ -619,14 +664,33 @@ impl StorageState {
         let image_data = image_data_bytes
             .or_else(|| decoded_image.as_deref())
             .ok_or_else(|| "Missing screenshot image bytes".to_string())?;
+
+        // Skip frames that only differ from the previous one by a cursor blink or clock tick
+        let (perceptual_hash, is_near_duplicate) = self.check_near_duplicate(image_data)?;
+        if is_near_duplicate {
+            return Ok(SaveScreenshotResponse {
+                status: "duplicate".to_string(),
+                screenshot_id: None,
+                image_path: None,
+                added: 0,
+                skipped: 0,
+            });
+        }
         let decode_dur = t0.elapsed();
 
-        // Generate row key and encrypt image
+        // Mostly unchanged frames of the same window keep only the changed region
+        let delta_plan = self.plan_delta_frame(request, image_data);
+
+        // Generate row key, re-encode with the configured codec, and encrypt image
         let t1 = std::time::Instant::now();
         let mut row_key = vec![0u8; 32];
         rand::thread_rng().fill_bytes(&mut row_key);
 
-        let encrypted_image = encrypt_with_master_key(&row_key, image_data)
+        let (stored_image, image_ext) = match &delta_plan.patch {
+            Some(patch) => (patch.png.clone(), DELTA_EXTENSION),
+            None => self.encode_image_for_storage(image_data),
+        };
+        let encrypted_image = encrypt_with_master_key(&row_key, &stored_image)
             .map_err(|e| format!("Failed to encrypt image: {}", e))?;
         let encrypted_row_key = self
             .wrap_row_key_for_storage(&row_key)
@@ -636,7 +700,7 @@ impl StorageState {
         // Use .pending suffix to mark temporary file
         let t2 = std::time::Instant::now();
         let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S_%3f");
-        let filename = format!("screenshot_{}.png.enc.pending", timestamp);
+        let filename = format!("screenshot_{}.{}.enc.pending", timestamp, image_ext);
         let screenshot_dir = self
             .screenshot_dir
             .lock()
@@ -666,6 +730,8 @@ impl StorageState {
         if let Err(e) = self.generate_thumbnail_from_data(image_data, &final_image_path, &row_key) {
             tracing::warn!("Failed to generate thumbnail during temp save: {}", e);
         }
+        let preview_image = self.privacy_preview_for_save(image_data);
+        let metadata_token_hashes = self.metadata_token_hashes_for_save(request);
 
         let mut guard = self.get_connection_named("save_screenshot_temp")?;
         let conn = guard.as_mut().unwrap();
@@ -720,21 +786,31 @@ impl StorageState {
 
         Self::zeroize_bytes(&mut row_key);
 
+        // Paranoid and compliance mode keep the process name encrypted-only.
+        let plaintext_process_name = if self.plaintext_metadata_allowed() {
+            request.process_name.clone()
+        } else {
+            None
+        };
+        let process_name_hash = self.process_name_hash(request.process_name.as_deref());
+
         conn.execute(
             "INSERT INTO screenshots (
                 image_path, image_hash, width, height,
                 window_title, process_name, metadata,
                 window_title_enc, process_name_enc, metadata_enc,
                 content_key_encrypted, status,
-                source, page_url_enc, page_icon_id, link_set_id
-             ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                source, page_url_enc, page_icon_id, link_set_id, utc_offset_minutes,
+                perceptual_hash, preview_image, process_name_hash,
+                delta_keyframe_id, delta_x, delta_y
+             ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
             params![
                 &image_path_str,
                 &request.image_hash,
                 request.width,
                 request.height,
                 Option::<String>::None,
-                request.process_name.clone(), // plaintext for fast aggregation
+                plaintext_process_name, // plaintext for fast aggregation
                 Option::<String>::None,
                 window_title_enc,
                 process_name_enc,
@@ -745,14 +821,25 @@ impl StorageState {
                 page_url_enc,
                 page_icon_id,
                 link_set_id,
+                current_utc_offset_minutes(),
+                perceptual_hash,
+                preview_image,
+                process_name_hash,
+                delta_plan.patch.as_ref().map(|patch| patch.keyframe_id),
+                delta_plan.patch.as_ref().map(|patch| patch.x),
+                delta_plan.patch.as_ref().map(|patch| patch.y),
             ],
         )
         .map_err(|e| format!("Failed to insert screenshot: {}", e))?;
 
         let screenshot_id = conn.last_insert_rowid();
+        Self::index_metadata_on_save(conn, screenshot_id, metadata_token_hashes);
         let in_lock_dur = t3.elapsed();
 
         drop(guard);
+        if let Some((window, frame)) = delta_plan.next_keyframe {
+            self.start_delta_chain(window, screenshot_id, frame);
+        }
 
         let total_dur = fn_start.elapsed();
         if total_dur.as_millis() >= 500 {
@@ -822,12 +909,14 @@ impl StorageState {
         // Timing accumulators for OCR processing
         let mut total_encrypt_dur = std::time::Duration::ZERO;
         let total_db_insert_dur;
-        let total_bitmap_dur = std::time::Duration::ZERO;
+        let total_bitmap_dur;
 
-        // Encrypt OCR results outside the global DB mutex. This avoids holding the
+        // Encrypt and hash OCR results outside the global DB mutex. This avoids holding the
         // storage lock while generating row keys and wrapping them with CNG/public-key APIs.
+        let ocr_hmac_key = self.ocr_hmac_key_for_save();
+        let processed_results = ocr_results.map(|results| self.postprocess_ocr_results(results));
         let mut encrypted_results = Vec::new();
-        if let Some(results) = ocr_results {
+        if let Some(results) = &processed_results {
             for result in results {
                 if let Err(error) = validate_ocr_result(result) {
                     skipped += 1;
@@ -842,7 +931,10 @@ impl StorageState {
                 let (text_enc, text_key_encrypted) =
                     self.encrypt_payload_with_row_key(result.text.as_bytes())?;
                 total_encrypt_dur += te0.elapsed();
-                encrypted_results.push((result, text_enc, text_key_encrypted));
+                let index_hashes = ocr_hmac_key
+                    .as_ref()
+                    .map(|key| Self::ocr_index_hashes(&result.text, key));
+                encrypted_results.push((result, text_enc, text_key_encrypted, index_hashes));
             }
         }
 
@@ -850,11 +942,12 @@ impl StorageState {
         {
             let mut guard = self.get_connection_named("commit_screenshot.write")?;
             let conn = guard.as_mut().unwrap();
-            let tx = conn
+            let mut tx = conn
                 .transaction()
                 .map_err(|e| format!("Failed to start commit transaction: {}", e))?;
+            let mut indexed_rows = Vec::new();
 
-            for (result, text_enc, text_key_encrypted) in encrypted_results {
+            for (result, text_enc, text_key_encrypted, index_hashes) in encrypted_results {
                 tx.execute(
                     "INSERT INTO ocr_results (
                         screenshot_id, text, text_hash, text_enc, text_key_encrypted, confidence,
@@ -880,9 +973,18 @@ impl StorageState {
                 )
                 .map_err(|e| format!("Failed to insert OCR result: {}", e))?;
 
+                if let Some((text_hash, token_hashes)) = index_hashes {
+                    indexed_rows.push((tx.last_insert_rowid(), text_hash, token_hashes));
+                }
                 added += 1;
             }
 
+            // Postings of every row are merged together, so each bigram blob is rewritten
+            // once per screenshot instead of once per row.
+            let tb0 = std::time::Instant::now();
+            Self::index_ocr_rows_on_commit(&mut tx, screenshot_id, &indexed_rows);
+            total_bitmap_dur = tb0.elapsed();
+
             // Mark committed and set committed_at. If classification has already
             // arrived asynchronously, do not overwrite it with NULL.
             let updated = if category.is_some() {
@@ -1140,13 +1242,54 @@ impl StorageState {
         start_ts: f64,
         end_ts: f64,
         max_records: Option<i64>,
+    ) -> Result<Vec<ScreenshotRecord>, String> {
+        self.get_screenshots_by_time_range_after(start_ts, end_ts, None, max_records)
+    }
+
+    /// One page of the timeline, oldest first. Pass the returned cursor back to load
+    /// the records after it; it is `None` once the range is exhausted.
+    pub fn get_timeline_page(
+        &self,
+        start_ts: f64,
+        end_ts: f64,
+        after: Option<TimelineCursor>,
+        limit: i64,
+    ) -> Result<TimelinePage, String> {
+        let limit = limit.max(1);
+        let mut records =
+            self.get_screenshots_by_time_range_after(start_ts, end_ts, after, Some(limit + 1))?;
+        let has_more = records.len() as i64 > limit;
+        records.truncate(limit as usize);
+        let next_cursor = if has_more {
+            records.last().map(|record| TimelineCursor {
+                after_id: record.id,
+                after_timestamp: record.timestamp.unwrap_or_default(),
+            })
+        } else {
+            None
+        };
+        Ok(TimelinePage {
+            records,
+            next_cursor,
+            offline_months: self.offline_months_between(Some(start_ts), Some(end_ts))?,
+        })
+    }
+
+    /// Records in the range ordered by `(created_at, id)`, strictly after `after` when
+    /// given.
+    fn get_screenshots_by_time_range_after(
+        &self,
+        start_ts: f64,
+        end_ts: f64,
+        after: Option<TimelineCursor>,
+        max_records: Option<i64>,
     ) -> Result<Vec<ScreenshotRecord>, String> {
         let diag_start = std::time::Instant::now();
 
-        // Phase 1: Hold mutex only for SQL query, extract raw data without decryption
+        // Phase 1: Query on a pooled read connection so the timeline never waits on
+        // the writer mutex; extract raw data without decryption
         let raw_rows = {
-            let mut guard = self.get_connection_named("get_screenshots_by_time_range")?;
-            let conn = guard.as_mut().unwrap();
+            let conn = self.open_read_connection_named("get_screenshots_by_time_range")?;
 
             let start_dt = DateTime::<Utc>::from_timestamp(start_ts as i64, 0)
                 .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
@@ -1159,6 +1302,19 @@ impl StorageState {
                 Some(n) => format!(" LIMIT {}", n),
                 None => String::new(),
             };
+            let after_clause = match &after {
+                Some(cursor) => {
+                    let after_dt = DateTime::<Utc>::from_timestamp(cursor.after_timestamp, 0)
+                        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+                        .unwrap_or_default();
+                    format!(
+                        " AND (s.created_at > '{dt}' OR (s.created_at = '{dt}' AND s.id > {id}))",
+                        dt = after_dt,
+                        id = cursor.after_id
+                    )
+                }
+                None => String::new(),
+            };
 
             let sql = format!(
                 "SELECT s.id, s.image_path, s.image_hash, s.width, s.height,
@@ -1173,9 +1329,9 @@ impl StorageState {
                  FROM screenshots s
                  LEFT JOIN page_icons pi ON s.page_icon_id = pi.id
                  LEFT JOIN link_sets ls ON s.link_set_id = ls.id
-                 WHERE s.is_deleted = 0 AND s.created_at BETWEEN '{}' AND '{}'
-                 ORDER BY s.created_at ASC{}",
-                start_dt, end_dt, limit_clause
+                 WHERE s.is_deleted = 0 AND s.created_at BETWEEN '{}' AND '{}'{}
+                 ORDER BY s.created_at ASC, s.id ASC{}",
+                start_dt, end_dt, after_clause, limit_clause
             );
 
             let mut stmt = conn
@@ -1189,7 +1345,7 @@ impl StorageState {
                 .collect();
 
             rows
-            // guard is dropped here, mutex released
+            // connection is returned to the read pool here
         };
 
         let query_elapsed = diag_start.elapsed();
@@ -1297,16 +1453,17 @@ impl StorageState {
         })
     }
 
-    /// Get screenshot density (counts per time bucket) within a time range.
-    /// No decryption or joins - extremely fast index-only scan.
+    /// Get screenshot density (counts per time bucket) within a time range, with the
+    /// process captured most in each bucket.
+    /// No decryption or joins - reads only the plaintext `process_name` column, which is
+    /// empty in compliance mode.
     pub fn get_screenshot_density(
         &self,
         start_ts: f64,
         end_ts: f64,
         bucket_seconds: i64,
     ) -> Result<Vec<DensityBucket>, String> {
-        let guard = self.get_connection_named("get_screenshot_density")?;
-        let conn = guard.as_ref().unwrap();
+        let conn = self.open_read_connection_named("get_screenshot_density")?;
 
         let start_dt = DateTime::<Utc>::from_timestamp(start_ts as i64, 0)
             .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
@@ -1315,34 +1472,26 @@ impl StorageState {
             .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
             .unwrap_or_default();
 
-        let sql = format!(
-            "SELECT (CAST(strftime('%s', created_at) AS INTEGER) / {bs}) * {bs} AS bucket, \
-                    COUNT(*) AS cnt \
-             FROM screenshots \
-             WHERE is_deleted = 0 AND created_at BETWEEN '{start}' AND '{end}' \
-             GROUP BY bucket \
-             ORDER BY bucket",
-            bs = bucket_seconds,
-            start = start_dt,
-            end = end_dt
-        );
-
         let mut stmt = conn
-            .prepare(&sql)
+            .prepare(
+                "SELECT (CAST(strftime('%s', created_at) AS INTEGER) / ?1) * ?1 AS bucket,
+                        process_name, COUNT(*) AS cnt
+                 FROM screenshots
+                 WHERE is_deleted = 0 AND created_at BETWEEN ?2 AND ?3
+                 GROUP BY bucket, process_name
+                 ORDER BY bucket",
+            )
             .map_err(|e| format!("Failed to prepare density query: {}", e))?;
 
-        let rows: Vec<DensityBucket> = stmt
-            .query_map([], |row| {
-                Ok(DensityBucket {
-                    timestamp: row.get(0)?,
-                    count: row.get(1)?,
-                })
+        let rows: Vec<(i64, Option<String>, i64)> = stmt
+            .query_map(params![bucket_seconds, start_dt, end_dt], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
             })
             .map_err(|e| format!("Failed to execute density query: {}", e))?
             .filter_map(|r| r.ok())
             .collect();
 
-        Ok(rows)
+        Ok(fold_density_rows(rows))
     }
 
     /// Get screenshots within a time range with SQL-level LIMIT/OFFSET.
@@ -1800,11 +1949,14 @@ impl StorageState {
         }
     }
 
-    /// Get OCR results for a screenshot.
+    /// Get OCR results for a screenshot, leaving out text boxes scored below
+    /// `min_confidence` when given.
     pub fn get_screenshot_ocr_results(
         &self,
         screenshot_id: i64,
+        min_confidence: Option<f64>,
     ) -> Result<Vec<super::OcrResult>, String> {
+        let min_confidence = super::search::normalize_min_confidence(min_confidence);
         let guard = self.get_connection_named("get_screenshot_ocr_results")?;
         let conn = guard.as_ref().unwrap();
 
@@ -1813,13 +1965,15 @@ impl StorageState {
                 "SELECT id, screenshot_id, text_enc, text_key_encrypted, confidence,
                         box_x1, box_y1, box_x2, box_y2,
                         box_x3, box_y3, box_x4, box_y4, created_at
-                 FROM ocr_results WHERE screenshot_id = ? AND is_deleted = 0
+                 FROM ocr_results
+                 WHERE screenshot_id = ?1 AND is_deleted = 0
+                   AND (?2 IS NULL OR confidence >= ?2)
                  ORDER BY box_y1, box_x1",
             )
             .map_err(|e| format!("Failed to prepare query: {}", e))?;
 
         let results = stmt
-            .query_map([screenshot_id], |row| {
+            .query_map(params![screenshot_id, min_confidence], |row| {
                 let text_enc: Option<Vec<u8>> = row.get(2)?;
                 let text_key_enc: Option<Vec<u8>> = row.get(3)?;
                 let text = match (text_enc.as_ref(), text_key_enc.as_ref()) {
@@ -1959,54 +2113,16 @@ impl StorageState {
         }
 
         // Phase 1: Use an independent read-only connection to retrieve the raw encrypted rows.
-        let raw_rows = {
-            let conn = self
-                .open_read_connection_named(if silent {
+        let raw_rows = self
+            .fetch_encrypted_ocr_rows(
+                screenshot_ids,
+                if silent {
                     "get_ocr_results_by_screenshot_ids_silent"
                 } else {
                     "get_ocr_results_by_screenshot_ids"
-                })
-                .map_err(BackgroundReadError::Other)?;
-
-            let mut raw_rows = Vec::new();
-
-            // Process in chunks to avoid SQLite parameter limit (usually 999)
-            for chunk in screenshot_ids.chunks(500) {
-                let placeholders: Vec<&str> = chunk.iter().map(|_| "?").collect();
-                let sql = format!(
-                    "SELECT screenshot_id, text_enc, text_key_encrypted
-                     FROM ocr_results
-                     WHERE is_deleted = 0 AND screenshot_id IN ({})
-                     ORDER BY screenshot_id, box_y1, box_x1",
-                    placeholders.join(",")
-                );
-                let params: Vec<&dyn rusqlite::ToSql> =
-                    chunk.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
-
-                let mut stmt = conn.prepare(&sql).map_err(|e| {
-                    BackgroundReadError::Other(format!("Failed to prepare batch OCR query: {}", e))
-                })?;
-
-                let rows = stmt
-                    .query_map(params.as_slice(), |row| {
-                        let screenshot_id: i64 = row.get(0)?;
-                        let text_enc: Option<Vec<u8>> = row.get(1)?;
-                        let text_key_enc: Option<Vec<u8>> = row.get(2)?;
-                        Ok((screenshot_id, text_enc, text_key_enc))
-                    })
-                    .map_err(|e| {
-                        BackgroundReadError::Other(format!(
-                            "Failed to execute batch OCR query: {}",
-                            e
-                        ))
-                    })?;
-
-                for row in rows.filter_map(|r| r.ok()) {
-                    raw_rows.push(row);
-                }
-            }
-            raw_rows
-        };
+                },
+            )
+            .map_err(BackgroundReadError::Other)?;
 
         // Phase 2: Decrypt payloads and build the final map outside the DB lock.
         let mut result_map = std::collections::HashMap::new();
@@ -2051,6 +2167,49 @@ impl StorageState {
         Ok(final_map)
     }
 
+    /// Raw `(screenshot_id, text_enc, text_key_encrypted)` OCR rows for `screenshot_ids`,
+    /// ordered by screenshot and reading position. Nothing is decrypted here.
+    pub(super) fn fetch_encrypted_ocr_rows(
+        &self,
+        screenshot_ids: &[i64],
+        caller: &str,
+    ) -> Result<Vec<EncryptedOcrRow>, String> {
+        let conn = self.open_read_connection_named(caller)?;
+        let mut raw_rows = Vec::new();
+
+        // Process in chunks to avoid SQLite parameter limit (usually 999)
+        for chunk in screenshot_ids.chunks(500) {
+            let placeholders: Vec<&str> = chunk.iter().map(|_| "?").collect();
+            let sql = format!(
+                "SELECT screenshot_id, text_enc, text_key_encrypted
+                 FROM ocr_results
+                 WHERE is_deleted = 0 AND screenshot_id IN ({})
+                 ORDER BY screenshot_id, box_y1, box_x1",
+                placeholders.join(",")
+            );
+            let params: Vec<&dyn rusqlite::ToSql> =
+                chunk.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
+
+            let mut stmt = conn
+                .prepare(&sql)
+                .map_err(|e| format!("Failed to prepare batch OCR query: {}", e))?;
+
+            let rows = stmt
+                .query_map(params.as_slice(), |row| {
+                    let screenshot_id: i64 = row.get(0)?;
+                    let text_enc: Option<Vec<u8>> = row.get(1)?;
+                    let text_key_enc: Option<Vec<u8>> = row.get(2)?;
+                    Ok((screenshot_id, text_enc, text_key_enc))
+                })
+                .map_err(|e| format!("Failed to execute batch OCR query: {}", e))?;
+
+            for row in rows.filter_map(|r| r.ok()) {
+                raw_rows.push(row);
+            }
+        }
+        Ok(raw_rows)
+    }
+
     /// Delete a screenshot by ID.
     pub fn delete_screenshot(&self, id: i64) -> Result<bool, String> {
         let (deleted, image_path, ocr_count) = {
@@ -2109,12 +2268,18 @@ impl StorageState {
         Ok(deleted > 0)
     }
 
-    /// Delete screenshots within a time range.
+    /// Permanently delete screenshots created within `[start_ts, end_ts]` (milliseconds).
+    ///
+    /// Pinned screenshots are kept unless `include_pinned` is set, and screenshots in
+    /// a protected range unless `include_protected` is set. The result carries the
+    /// image hashes of deleted rows so their vectors can be removed too.
     pub fn delete_screenshots_by_time_range(
         &self,
         start_ts: f64,
         end_ts: f64,
-    ) -> Result<i32, String> {
+        include_pinned: bool,
+        include_protected: bool,
+    ) -> Result<TimeRangeDeleteResult, String> {
         // Convert timestamps (milliseconds) to SQLite datetime
         let start_dt = DateTime::<Utc>::from_timestamp((start_ts / 1000.0) as i64, 0)
             .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
@@ -2122,26 +2287,67 @@ impl StorageState {
         let end_dt = DateTime::<Utc>::from_timestamp((end_ts / 1000.0) as i64, 0)
             .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
             .unwrap_or_default();
+        let mut range_filter = String::from("is_deleted = 0 AND created_at BETWEEN ?1 AND ?2");
+        if !include_pinned {
+            range_filter.push_str(" AND pinned = 0");
+        }
+        if !include_protected {
+            range_filter.push_str(" AND NOT ");
+            range_filter.push_str(PROTECTED_RANGE_MATCH);
+        }
 
-        let (paths, deleted, ocr_count) = {
+        let (rows, deleted, ocr_count, pinned_kept, protected_kept) = {
             let guard = self.get_connection_named("delete_screenshots_by_time_range")?;
             let conn = guard.as_ref().unwrap();
 
-            // Get all image paths to delete
+            // Get all image paths and hashes to delete
             let mut stmt = conn
-                .prepare("SELECT image_path FROM screenshots WHERE is_deleted = 0 AND created_at BETWEEN ? AND ?")
+                .prepare(&format!(
+                    "SELECT image_path, image_hash FROM screenshots WHERE {}",
+                    range_filter
+                ))
                 .map_err(|e| format!("Failed to prepare query: {}", e))?;
 
-            let paths: Vec<String> = stmt
-                .query_map([&start_dt, &end_dt], |row| row.get(0))
+            let rows: Vec<(String, String)> = stmt
+                .query_map([&start_dt, &end_dt], |row| Ok((row.get(0)?, row.get(1)?)))
                 .map_err(|e| format!("Failed to execute query: {}", e))?
                 .filter_map(|r| r.ok())
                 .collect();
 
+            let pinned_kept: i64 = if include_pinned {
+                0
+            } else {
+                conn.query_row(
+                    "SELECT COUNT(*) FROM screenshots
+                     WHERE is_deleted = 0 AND pinned = 1 AND created_at BETWEEN ?1 AND ?2",
+                    [&start_dt, &end_dt],
+                    |row| row.get(0),
+                )
+                .map_err(|e| format!("Failed to count pinned screenshots: {}", e))?
+            };
+
+            let protected_kept: i64 = if include_protected {
+                0
+            } else {
+                conn.query_row(
+                    &format!(
+                        "SELECT COUNT(*) FROM screenshots
+                         WHERE is_deleted = 0 AND created_at BETWEEN ?1 AND ?2 AND {}",
+                        PROTECTED_RANGE_MATCH
+                    ),
+                    [&start_dt, &end_dt],
+                    |row| row.get(0),
+                )
+                .map_err(|e| format!("Failed to count protected screenshots: {}", e))?
+            };
+
             // Count OCR rows that will be cascade-deleted
             let ocr_count: i64 = conn
                 .query_row(
-                    "SELECT COUNT(*) FROM ocr_results WHERE is_deleted = 0 AND screenshot_id IN (SELECT id FROM screenshots WHERE is_deleted = 0 AND created_at BETWEEN ? AND ?)",
+                    &format!(
+                        "SELECT COUNT(*) FROM ocr_results WHERE is_deleted = 0 AND screenshot_id IN (SELECT id FROM screenshots WHERE {})",
+                        range_filter
+                    ),
                     [&start_dt, &end_dt],
                     |row| row.get(0),
                 )
@@ -2150,7 +2356,7 @@ impl StorageState {
             // Delete database records
             let deleted = conn
                 .execute(
-                    "DELETE FROM screenshots WHERE is_deleted = 0 AND created_at BETWEEN ? AND ?",
+                    &format!("DELETE FROM screenshots WHERE {}", range_filter),
                     [&start_dt, &end_dt],
                 )
                 .map_err(|e| format!("Failed to delete screenshots: {}", e))?;
@@ -2161,7 +2367,7 @@ impl StorageState {
             }
             drop(stmt);
             drop(guard);
-            (paths, deleted, ocr_count)
+            (rows, deleted, ocr_count, pinned_kept, protected_kept)
         };
 
         if deleted > 0 {
@@ -2173,14 +2379,21 @@ impl StorageState {
         }
 
         // Try to delete image files
-        for path in paths {
+        let mut image_hashes = Vec::with_capacity(rows.len());
+        for (path, image_hash) in rows {
             let abs_path = self.resolve_image_path(&path);
             let _ = std::fs::remove_file(&abs_path);
             let thumb = Self::thumbnail_path_for(&abs_path);
             let _ = std::fs::remove_file(&thumb);
+            image_hashes.push(image_hash);
         }
 
-        Ok(deleted as i32)
+        Ok(TimeRangeDeleteResult {
+            deleted: deleted as i64,
+            pinned_kept,
+            protected_kept,
+            image_hashes,
+        })
     }
 
     /// Soft-delete screenshots by process (and optional month key `YYYY-MM`) and enqueue IDs.
@@ -2212,14 +2425,23 @@ impl StorageState {
             }
         }
 
-        let mut filter_plain = String::from("process_name = ?1 AND is_deleted = 0");
-        let mut filter_alias = String::from("s.process_name = ?1 AND s.is_deleted = 0");
-        let mut param_values: Vec<Box<dyn rusqlite::ToSql>> =
-            vec![Box::new(normalized_process.to_string())];
+        // Rows without a plaintext name match on the process name blind index.
+        let mut filter_plain = String::from(
+            "(process_name = ?1 OR (process_name IS NULL AND process_name_hash = ?2))
+             AND is_deleted = 0",
+        );
+        let mut filter_alias = String::from(
+            "(s.process_name = ?1 OR (s.process_name IS NULL AND s.process_name_hash = ?2))
+             AND s.is_deleted = 0",
+        );
+        let mut param_values: Vec<Box<dyn rusqlite::ToSql>> = vec![
+            Box::new(normalized_process.to_string()),
+            Box::new(self.process_name_hash(Some(normalized_process))),
+        ];
 
         if let Some(ref month_key) = normalized_month {
-            filter_plain.push_str(" AND strftime('%Y-%m', created_at) = ?2");
-            filter_alias.push_str(" AND strftime('%Y-%m', s.created_at) = ?2");
+            filter_plain.push_str(" AND strftime('%Y-%m', created_at) = ?3");
+            filter_alias.push_str(" AND strftime('%Y-%m', s.created_at) = ?3");
             param_values.push(Box::new(month_key.clone()));
         }
 
@@ -2298,14 +2520,7 @@ impl StorageState {
         &self,
         screenshot_ids: &[i64],
     ) -> Result<SoftDeleteScreenshotsResult, String> {
-        let mut normalized_ids: Vec<i64> = screenshot_ids
-            .iter()
-            .copied()
-            .filter(|id| *id > 0)
-            .collect();
-        normalized_ids.sort_unstable();
-        normalized_ids.dedup();
-
+        let normalized_ids = Self::normalize_screenshot_ids(screenshot_ids);
         if normalized_ids.is_empty() {
             return Ok(SoftDeleteScreenshotsResult {
                 requested: 0,
@@ -2316,12 +2531,40 @@ impl StorageState {
             });
         }
 
-        let mut guard = self.get_connection_named("soft_delete_screenshots")?;
-        let conn = guard.as_mut().unwrap();
-        let tx = conn
-            .transaction()
-            .map_err(|e| format!("Failed to start screenshot soft-delete transaction: {}", e))?;
+        let result = {
+            let mut guard = self.get_connection_named("soft_delete_screenshots")?;
+            let conn = guard.as_mut().unwrap();
+            let tx = conn.transaction().map_err(|e| {
+                format!("Failed to start screenshot soft-delete transaction: {}", e)
+            })?;
+            let result = Self::soft_delete_screenshots_in_tx(&tx, &normalized_ids)?;
+            tx.commit()
+                .map_err(|e| format!("Failed to commit selected screenshot soft-delete: {}", e))?;
+            result
+        };
+
+        self.release_ocr_row_count(result.ocr_marked);
+        Ok(result)
+    }
+
+    /// Positive, sorted, de-duplicated screenshot IDs.
+    pub(super) fn normalize_screenshot_ids(screenshot_ids: &[i64]) -> Vec<i64> {
+        let mut normalized_ids: Vec<i64> = screenshot_ids
+            .iter()
+            .copied()
+            .filter(|id| *id > 0)
+            .collect();
+        normalized_ids.sort_unstable();
+        normalized_ids.dedup();
+        normalized_ids
+    }
 
+    /// Mark `normalized_ids` and their OCR rows deleted and enqueue them for physical
+    /// cleanup inside the caller's transaction.
+    pub(super) fn soft_delete_screenshots_in_tx(
+        tx: &rusqlite::Transaction<'_>,
+        normalized_ids: &[i64],
+    ) -> Result<SoftDeleteScreenshotsResult, String> {
         let mut queued_screenshots = 0i64;
         let mut queued_ocr = 0i64;
         let mut ocr_marked = 0i64;
@@ -2380,9 +2623,17 @@ impl StorageState {
                 as i64;
         }
 
-        tx.commit()
-            .map_err(|e| format!("Failed to commit selected screenshot soft-delete: {}", e))?;
+        Ok(SoftDeleteScreenshotsResult {
+            requested: normalized_ids.len() as i64,
+            screenshots_marked,
+            ocr_marked,
+            queued_screenshots,
+            queued_ocr,
+        })
+    }
 
+    /// Keep the approximate OCR row count in step after rows are marked deleted.
+    pub(super) fn release_ocr_row_count(&self, ocr_marked: i64) {
         if ocr_marked > 0 {
             let _ = self
                 .ocr_row_count
@@ -2390,17 +2641,11 @@ impl StorageState {
                     Some(v.saturating_sub(ocr_marked as u64))
                 });
         }
-
-        Ok(SoftDeleteScreenshotsResult {
-            requested: normalized_ids.len() as i64,
-            screenshots_marked,
-            ocr_marked,
-            queued_screenshots,
-            queued_ocr,
-        })
     }
 
     /// Select oldest screenshots until the estimated reclaim size reaches target bytes.
+    /// Pinned screenshots, screenshots in a protected range and pending captures that
+    /// have not been committed yet are never selected.
     ///
     /// Returns `(ids, estimated_reclaim_bytes)`.
     pub fn select_oldest_screenshots_for_reclaim(
@@ -2419,13 +2664,16 @@ impl StorageState {
             let conn = guard.as_ref().unwrap();
 
             let mut stmt = conn
-                .prepare(
+                .prepare(&format!(
                     "SELECT id, image_path
                      FROM screenshots
-                     WHERE is_deleted = 0
+                     WHERE is_deleted = 0 AND pinned = 0
+                       AND (status IS NULL OR status != 'pending')
+                       AND NOT {}
                      ORDER BY created_at ASC
                      LIMIT ?1",
-                )
+                    PROTECTED_RANGE_MATCH
+                ))
                 .map_err(|e| format!("Failed to prepare reclaim candidate query: {}", e))?;
 
             let rows: Vec<(i64, String)> = stmt
@@ -2443,7 +2691,7 @@ impl StorageState {
 
         for (id, image_path) in rows {
             let abs_path = self.resolve_image_path(&image_path);
-            let image_bytes = std::fs::metadata(&abs_path).map(|m| m.len()).unwrap_or(0);
+            let image_bytes = stored_image_size(&abs_path);
             let thumb_path = Self::thumbnail_path_for(&abs_path);
             let thumb_bytes = std::fs::metadata(&thumb_path).map(|m| m.len()).unwrap_or(0);
             let row_bytes = image_bytes.saturating_add(thumb_bytes);
@@ -2479,8 +2727,8 @@ impl StorageState {
         Ok((selected_ids, estimated_reclaim_bytes))
     }
 
-    /// Select up to `max_candidates` non-deleted screenshots created strictly
-    /// before `cutoff_dt` (UTC `%Y-%m-%d %H:%M:%S`, matching `created_at`),
+    /// Select up to `max_candidates` non-deleted, unpinned and unprotected screenshots
+    /// created strictly before `cutoff_dt` (UTC `%Y-%m-%d %H:%M:%S`, matching `created_at`),
     /// oldest first, for age-based retention pruning.
     ///
     /// Returns `(ids, estimated_freed_bytes)`. Unlike the reclaim selector this
@@ -2503,13 +2751,15 @@ impl StorageState {
             let conn = guard.as_ref().unwrap();
 
             let mut stmt = conn
-                .prepare(
+                .prepare(&format!(
                     "SELECT id, image_path
                      FROM screenshots
-                     WHERE is_deleted = 0 AND created_at < ?1
+                     WHERE is_deleted = 0 AND pinned = 0 AND created_at < ?1
+                       AND NOT {}
                      ORDER BY created_at ASC
                      LIMIT ?2",
-                )
+                    PROTECTED_RANGE_MATCH
+                ))
                 .map_err(|e| format!("Failed to prepare retention candidate query: {}", e))?;
 
             let rows: Vec<(i64, String)> = stmt
@@ -2528,7 +2778,7 @@ impl StorageState {
 
         for (id, image_path) in rows {
             let abs_path = self.resolve_image_path(&image_path);
-            let image_bytes = std::fs::metadata(&abs_path).map(|m| m.len()).unwrap_or(0);
+            let image_bytes = stored_image_size(&abs_path);
             let thumb_path = Self::thumbnail_path_for(&abs_path);
             let thumb_bytes = std::fs::metadata(&thumb_path).map(|m| m.len()).unwrap_or(0);
             estimated_freed_bytes = estimated_freed_bytes
@@ -2970,12 +3220,67 @@ impl StorageState {
     }
 }
 
+/// Fold per-(bucket, process) counts, ordered by bucket, into one `DensityBucket` per
+/// bucket. Ties for the dominant process go to the name that sorts first; screenshots
+/// without a process name are counted but never dominant.
+fn fold_density_rows(rows: Vec<(i64, Option<String>, i64)>) -> Vec<DensityBucket> {
+    let mut buckets: Vec<DensityBucket> = Vec::new();
+    for (timestamp, process_name, count) in rows {
+        let bucket = match buckets.last_mut() {
+            Some(bucket) if bucket.timestamp == timestamp => bucket,
+            _ => {
+                buckets.push(DensityBucket {
+                    timestamp,
+                    count: 0,
+                    dominant_process: None,
+                    dominant_process_count: 0,
+                });
+                buckets.last_mut().unwrap()
+            }
+        };
+        bucket.count += count;
+        let Some(process_name) = process_name.filter(|name| !name.is_empty()) else {
+            continue;
+        };
+        let better = count > bucket.dominant_process_count
+            || (count == bucket.dominant_process_count
+                && bucket
+                    .dominant_process
+                    .as_ref()
+                    .is_some_and(|current| process_name < *current));
+        if better {
+            bucket.dominant_process = Some(process_name);
+            bucket.dominant_process_count = count;
+        }
+    }
+    buckets
+}
+
 #[cfg(test)]
 mod ocr_lifecycle_tests {
     use super::*;
     use crate::credential_manager::CredentialManagerState;
     use std::sync::Arc;
 
+    #[test]
+    fn density_buckets_sum_processes_and_pick_the_dominant_one() {
+        let buckets = fold_density_rows(vec![
+            (0, None, 7),
+            (0, Some("code.exe".to_string()), 3),
+            (0, Some("chrome.exe".to_string()), 5),
+            (600, Some("slack.exe".to_string()), 2),
+            (600, Some("code.exe".to_string()), 2),
+            (1200, Some(String::new()), 4),
+        ]);
+        assert_eq!(buckets.len(), 3);
+        assert_eq!(buckets[0].count, 15);
+        assert_eq!(buckets[0].dominant_process.as_deref(), Some("chrome.exe"));
+        assert_eq!(buckets[0].dominant_process_count, 5);
+        assert_eq!(buckets[1].dominant_process.as_deref(), Some("code.exe"));
+        assert_eq!(buckets[2].count, 4);
+        assert_eq!(buckets[2].dominant_process, None);
+    }
+
     #[test]
     fn postprocess_retry_uses_bounded_exponential_backoff() {
         assert_eq!(ocr_postprocess_retry_decision(0), ("pending", Some(30), 1));
@@ -3022,6 +3327,124 @@ mod ocr_lifecycle_tests {
         assert_eq!(storage.count_expected_clip_image_rows().unwrap(), 3);
     }
 
+    #[test]
+    fn commit_merges_ocr_postings_once_and_falls_back_to_lazy_indexing() {
+        let mut connection = Connection::open_in_memory().expect("in-memory database");
+        connection
+            .execute_batch(
+                "CREATE TABLE ocr_results (
+                    id INTEGER PRIMARY KEY,
+                    text_hash TEXT NOT NULL DEFAULT ''
+                 );
+                 CREATE TABLE blind_bitmap_index (
+                    token_hash TEXT PRIMARY KEY,
+                    postings_blob BLOB NOT NULL
+                 );
+                 INSERT INTO ocr_results (id) VALUES (1), (2), (3);",
+            )
+            .expect("bitmap fixture");
+        let mut existing = Vec::new();
+        roaring::RoaringBitmap::from_iter([7u32])
+            .serialize_into(&mut existing)
+            .unwrap();
+        connection
+            .execute(
+                "INSERT INTO blind_bitmap_index (token_hash, postings_blob) VALUES ('ab', ?)",
+                params![existing],
+            )
+            .unwrap();
+
+        let rows = vec![
+            (
+                1,
+                "h1".to_string(),
+                vec!["ab".to_string(), "bc".to_string()],
+            ),
+            (2, "h2".to_string(), vec!["ab".to_string()]),
+        ];
+        let mut tx = connection.transaction().unwrap();
+        StorageState::index_ocr_rows_on_commit(&mut tx, 10, &rows);
+        tx.commit().unwrap();
+
+        let postings = |conn: &Connection, token: &str| -> Vec<u32> {
+            let blob: Vec<u8> = conn
+                .query_row(
+                    "SELECT postings_blob FROM blind_bitmap_index WHERE token_hash = ?",
+                    params![token],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            roaring::RoaringBitmap::deserialize_from(&blob[..])
+                .unwrap()
+                .iter()
+                .collect()
+        };
+        assert_eq!(postings(&connection, "ab"), vec![1, 2, 7]);
+        assert_eq!(postings(&connection, "bc"), vec![1]);
+        let hash: String = connection
+            .query_row(
+                "SELECT text_hash FROM ocr_results WHERE id = 2",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(hash, "h2");
+
+        // Without an index table the savepoint is rolled back and the row stays unindexed.
+        connection
+            .execute_batch("DROP TABLE blind_bitmap_index")
+            .unwrap();
+        let mut tx = connection.transaction().unwrap();
+        StorageState::index_ocr_rows_on_commit(
+            &mut tx,
+            11,
+            &[(3, "h3".to_string(), vec!["cd".to_string()])],
+        );
+        tx.commit().unwrap();
+        let hash: String = connection
+            .query_row(
+                "SELECT text_hash FROM ocr_results WHERE id = 3",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(hash, "");
+    }
+
+    #[test]
+    fn reclaim_evicts_oldest_committed_screenshots_only() {
+        let temp = tempfile::tempdir().expect("temp storage directory");
+        let credential_state = Arc::new(CredentialManagerState::new(temp.path().to_path_buf()));
+        let storage = StorageState::new(temp.path().to_path_buf(), credential_state);
+        for name in ["a.enc", "b.enc", "c.enc", "d.enc"] {
+            std::fs::write(temp.path().join(name), [0u8; 100]).expect("fixture image");
+        }
+        let connection = Connection::open_in_memory().expect("in-memory database");
+        connection
+            .execute_batch(
+                "CREATE TABLE screenshots (
+                    id INTEGER PRIMARY KEY,
+                    image_path TEXT NOT NULL,
+                    status TEXT,
+                    created_at TEXT NOT NULL,
+                    is_deleted INTEGER NOT NULL DEFAULT 0
+                 );
+                 INSERT INTO screenshots (id, image_path, status, created_at) VALUES
+                    (1, 'a.enc', 'pending', '2024-01-01 00:00:00'),
+                    (2, 'b.enc', 'committed', '2024-01-02 00:00:00'),
+                    (3, 'c.enc', NULL, '2024-01-03 00:00:00'),
+                    (4, 'd.enc', 'committed', '2024-01-04 00:00:00');",
+            )
+            .expect("reclaim fixture");
+        *storage.db.lock().unwrap_or_else(|e| e.into_inner()) = Some(connection);
+
+        let (ids, bytes) = storage
+            .select_oldest_screenshots_for_reclaim(150, 10)
+            .expect("select reclaim candidates");
+        assert_eq!(ids, vec![2, 3]);
+        assert_eq!(bytes, 200);
+    }
+
     #[test]
     fn silent_clustering_reads_fail_fast_while_session_is_locked() {
         let temp = tempfile::tempdir().expect("temp storage directory");
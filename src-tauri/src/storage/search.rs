@@ -5,10 +5,13 @@ use chrono::{DateTime, Utc};
 use hmac::{Hmac, Mac};
 use jieba_rs::Jieba;
 use once_cell::sync::Lazy;
-use rusqlite::{params, OptionalExtension};
+use regex::Regex;
 use std::collections::{HashMap, HashSet};
 
-use super::{SearchResult, StorageState};
+use super::relevance::restore_page_order;
+use super::search_query::{parse_search_query, ParsedQuery};
+use super::search_regex::compile_search_regex;
+use super::{SearchMatchMode, SearchResult, SearchSortBy, StorageState};
 
 impl StorageState {
     /// Compute HMAC hash for blind index.
@@ -99,6 +102,19 @@ impl StorageState {
     }
 
     /// Search text using blind bigram bitmap index.
+    ///
+    /// `query` accepts the boolean syntax described in `search_query`. A single clause
+    /// matches individual text boxes; several clauses must all match the same screenshot.
+    /// Plain terms also match the page URL and visible link text of browser captures
+    /// (see `metadata_index`); `domain:` and `title:` narrow results by page site and
+    /// window title.
+    /// `SearchMatchMode::Phrase` instead takes the whole query literally and keeps only
+    /// text boxes whose decrypted text contains it, and `SearchMatchMode::Regex` keeps
+    /// those matching the query as a regular expression (see `search_regex`).
+    /// `categories` and `tags` each keep screenshots matching any listed value; when
+    /// both are given a screenshot must match both filters. `sort_by` chooses between
+    /// recency and BM25 relevance ordering (see `relevance`). With `min_confidence`,
+    /// text boxes the OCR engine scored below it are ignored, so noise cannot match.
     pub fn search_text(
         &self,
         query: &str,
@@ -109,141 +125,120 @@ impl StorageState {
         start_time: Option<f64>,
         end_time: Option<f64>,
         categories: Option<Vec<String>>,
+        tags: Option<Vec<String>>,
+        sort_by: SearchSortBy,
+        match_mode: SearchMatchMode,
+        min_confidence: Option<f64>,
     ) -> Result<Vec<SearchResult>, String> {
+        let by_relevance = sort_by == SearchSortBy::Relevance;
+        let min_confidence = normalize_min_confidence(min_confidence);
+        // Phrase matches are verified against the text, so the bigrams must all be present
+        let phrase = (match_mode == SearchMatchMode::Phrase)
+            .then(|| normalize_phrase(query))
+            .filter(|p| !p.is_empty());
+        let regex = (match_mode == SearchMatchMode::Regex && !query.trim().is_empty())
+            .then(|| compile_search_regex(query))
+            .transpose()?;
+        let fuzzy = fuzzy && phrase.is_none() && regex.is_none();
+        let matcher = match (&phrase, &regex) {
+            (Some(phrase), _) => Some(TextMatcher::Phrase(phrase)),
+            (_, Some(regex)) => Some(TextMatcher::Regex(regex)),
+            _ => None,
+        };
         let hmac_key = self.credential_state.get_hmac_key()?;
         let conn = self.open_read_connection_named("search_text")?;
 
-        // Pre-compute set of screenshot IDs matching the category filter.
+        // Pre-compute set of screenshot IDs matching the category and tag filters.
         // This allows us to filter bitmap candidates BEFORE pagination,
         // avoiding the expensive fetch-decrypt-then-discard pattern.
-        let category_screenshot_ids: Option<std::collections::HashSet<i64>> = match &categories {
-            Some(cats) if !cats.is_empty() => {
-                let placeholders = cats.iter().map(|_| "?").collect::<Vec<&str>>().join(",");
-                let sql = format!(
-                    "SELECT id FROM screenshots WHERE is_deleted = 0 AND category IN ({})",
-                    placeholders
-                );
-                let cat_params: Vec<&dyn rusqlite::ToSql> =
-                    cats.iter().map(|c| c as &dyn rusqlite::ToSql).collect();
-                let mut stmt = conn
-                    .prepare(&sql)
-                    .map_err(|e| format!("Failed to query category filter: {}", e))?;
-                let ids: std::collections::HashSet<i64> = stmt
-                    .query_map(cat_params.as_slice(), |row| row.get::<_, i64>(0))
-                    .map_err(|e| format!("Failed to fetch category ids: {}", e))?
-                    .filter_map(|r| r.ok())
-                    .collect();
-                Some(ids)
-            }
-            _ => None,
+        let tags = Self::normalize_search_tags(tags);
+        let category_screenshot_ids =
+            Self::search_filter_screenshot_ids(&conn, categories.as_deref(), &tags)?;
+
+        // Parse the boolean query; each term is tokenized independently to avoid
+        // generating invalid cross-keyword bigrams containing spaces
+        let parsed = match phrase {
+            Some(_) => ParsedQuery::phrase(query),
+            None if regex.is_some() => ParsedQuery::default(),
+            None => parse_search_query(query),
+        };
+        if parsed.has_only_exclusions() {
+            // Exclusions only narrow a match set; on their own they match nothing
+            return Ok(vec![]);
+        }
+        let category_screenshot_ids =
+            Self::restrict_by_metadata(&conn, &hmac_key, category_screenshot_ids, &parsed)?;
+        let mut excluded_screenshot_ids =
+            Self::excluded_screenshot_ids(&conn, &parsed.excluded, &hmac_key)?;
+        excluded_screenshot_ids.extend(Self::excluded_by_metadata(&conn, &hmac_key, &parsed)?);
+        // Page URLs and link text can only satisfy plain terms; phrases and regexes
+        // are verified against the OCR text itself
+        let include_pages = matcher.is_none();
+        let has_screenshot_filter =
+            category_screenshot_ids.is_some() || !excluded_screenshot_ids.is_empty();
+        let keep_screenshot = |sid: &i64| {
+            category_screenshot_ids
+                .as_ref()
+                .is_none_or(|ids| ids.contains(sid))
+                && !excluded_screenshot_ids.contains(sid)
         };
 
-        // Split keywords by whitespace, compute bigrams for each keyword independently
-        // to avoid generating invalid cross-keyword bigrams containing spaces
-        let keywords: Vec<&str> = query.split_whitespace().collect();
-        let per_keyword_bigrams: Vec<HashSet<String>> = keywords
-            .iter()
-            .map(|kw| Self::bigram_tokenize(kw))
-            .filter(|set| !set.is_empty())
-            .collect();
+        let bigram_clauses = Self::bigram_clauses(&parsed);
 
         // If no bigram tokens, try token-based bitmap index for short queries
         // If tokens are also empty, fall back to simple SQL query (ordered by time)
-        if per_keyword_bigrams.is_empty() {
-            if !query.is_empty() {
-                // Use word segmentation (short query strategy), tokenize each keyword separately
-                let per_keyword_tokens: Vec<Vec<String>> = keywords
-                    .iter()
-                    .map(|kw| Self::tokenize_text(kw))
-                    .filter(|tokens| !tokens.is_empty())
-                    .collect();
-
-                if !per_keyword_tokens.is_empty() {
-                    // Each keyword's token set -> corresponding OCR ID bitmap
-                    let mut keyword_bitmaps: Vec<roaring::RoaringBitmap> = Vec::new();
+        if bigram_clauses.is_empty() && regex.is_none() {
+            if !parsed.clauses.is_empty() {
+                // Use word segmentation (short query strategy), tokenize each term separately
+                let token_clauses = Self::token_clauses(&parsed);
 
-                    for kw_tokens in &per_keyword_tokens {
-                        let mut bitmaps: Vec<roaring::RoaringBitmap> = Vec::new();
-                        for token in kw_tokens {
-                            let token_hash = Self::compute_hmac_hash(token, &hmac_key);
-                            let blob: Option<Vec<u8>> = conn
-                                .query_row(
-                                    "SELECT postings_blob FROM blind_bitmap_index WHERE token_hash = ?",
-                                    params![&token_hash],
-                                    |row| row.get(0),
-                                )
-                                .optional()
-                                .map_err(|e| format!("Failed to query bitmap: {}", e))?;
-
-                            if let Some(b) = blob {
-                                let rb = roaring::RoaringBitmap::deserialize_from(&b[..])
-                                    .map_err(|e| format!("Failed to deserialize bitmap: {}", e))?;
-                                bitmaps.push(rb);
-                            } else {
-                                bitmaps.clear();
-                                break;
+                if !token_clauses.is_empty() {
+                    // Each clause's terms -> union of their OCR ID bitmaps
+                    let mut keyword_bitmaps: Vec<roaring::RoaringBitmap> = Vec::new();
+                    let mut term_doc_freqs: Vec<(String, u64)> = Vec::new();
+
+                    for clause in &token_clauses {
+                        match Self::clause_match_with_pages(
+                            &conn,
+                            clause,
+                            &hmac_key,
+                            false,
+                            include_pages,
+                        )? {
+                            Some(matched) => {
+                                keyword_bitmaps.push(matched.bitmap);
+                                term_doc_freqs = matched.term_doc_freqs;
                             }
+                            None => return Ok(vec![]),
                         }
-
-                        if bitmaps.is_empty() {
-                            return Ok(vec![]);
-                        }
-
-                        let mut iter = bitmaps.into_iter();
-                        let mut kw_intersection = iter.next().unwrap();
-                        for bm in iter {
-                            kw_intersection &= &bm;
-                        }
-                        keyword_bitmaps.push(kw_intersection);
+                    }
+                    if !Self::drop_low_confidence(&conn, &mut keyword_bitmaps, min_confidence)? {
+                        return Ok(vec![]);
                     }
 
                     // Multi-keyword: intersect at screenshot level
                     let is_multi_keyword = keyword_bitmaps.len() > 1;
+                    // Matching text boxes per screenshot, per keyword (for relevance ranking)
+                    let mut per_kw_screenshot_counts: Vec<HashMap<i64, u32>> = Vec::new();
                     let intersection = if is_multi_keyword {
-                        let mut per_kw_screenshot_ids: Vec<std::collections::HashSet<i64>> =
-                            Vec::new();
-
                         for kw_bitmap in &keyword_bitmaps {
                             let ocr_ids: Vec<i64> = kw_bitmap.iter().map(|v| v as i64).collect();
                             if ocr_ids.is_empty() {
                                 return Ok(vec![]);
                             }
 
-                            let mut screenshot_ids = std::collections::HashSet::new();
-                            for chunk in ocr_ids.chunks(500) {
-                                let placeholders =
-                                    chunk.iter().map(|_| "?").collect::<Vec<&str>>().join(",");
-                                let sql = format!(
-                                    "SELECT DISTINCT screenshot_id FROM ocr_results WHERE id IN ({}) AND is_deleted = 0",
-                                    placeholders
-                                );
-                                let params: Vec<&dyn rusqlite::ToSql> =
-                                    chunk.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
-                                let mut stmt = conn.prepare(&sql).map_err(|e| {
-                                    format!("Failed to prepare screenshot resolve: {}", e)
-                                })?;
-                                let rows = stmt
-                                    .query_map(params.as_slice(), |row| row.get::<_, i64>(0))
-                                    .map_err(|e| {
-                                        format!("Failed to resolve screenshot ids: {}", e)
-                                    })?;
-                                for row in rows.filter_map(|r| r.ok()) {
-                                    screenshot_ids.insert(row);
-                                }
-                            }
-                            per_kw_screenshot_ids.push(screenshot_ids);
+                            per_kw_screenshot_counts
+                                .push(Self::screenshot_match_counts(&conn, &ocr_ids)?);
                         }
 
-                        let mut iter = per_kw_screenshot_ids.into_iter();
-                        let mut matching = iter.next().unwrap();
-                        for s in iter {
-                            matching.retain(|id| s.contains(id));
+                        let mut matching: HashSet<i64> =
+                            per_kw_screenshot_counts[0].keys().copied().collect();
+                        for counts in &per_kw_screenshot_counts[1..] {
+                            matching.retain(|id| counts.contains_key(id));
                         }
 
-                        // Pre-filter by category before pagination
-                        if let Some(ref cat_ids) = category_screenshot_ids {
-                            matching.retain(|id| cat_ids.contains(id));
-                        }
+                        // Pre-filter by category and exclusions before pagination
+                        matching.retain(&keep_screenshot);
 
                         if matching.is_empty() {
                             return Ok(vec![]);
@@ -267,45 +262,33 @@ impl StorageState {
                     let mut ids: Vec<i64> = intersection.into_iter().map(|v| v as i64).collect();
                     ids.sort_unstable_by(|a, b| b.cmp(a));
 
-                    // For single-keyword path (OCR-level IDs), pre-filter by category
+                    if by_relevance {
+                        ids = if is_multi_keyword {
+                            Self::rank_screenshot_ids_by_relevance(
+                                &conn,
+                                ids,
+                                &per_kw_screenshot_counts,
+                            )?
+                        } else {
+                            let terms: Vec<(&str, u64)> = term_doc_freqs
+                                .iter()
+                                .map(|(term, df)| (term.as_str(), *df))
+                                .collect();
+                            self.rank_ocr_ids_by_relevance(&conn, ids, &terms)?
+                        };
+                    }
+
+                    // For single-keyword path (OCR-level IDs), pre-filter by category, exclusions and text match
                     if !is_multi_keyword {
-                        if let Some(ref cat_ids) = category_screenshot_ids {
+                        if has_screenshot_filter || matcher.is_some() {
                             let needed = (offset + limit) as usize;
-                            let mut filtered_ids = Vec::with_capacity(needed);
-                            for chunk in ids.chunks(500) {
-                                if filtered_ids.len() >= needed {
-                                    break;
-                                }
-                                let placeholders =
-                                    chunk.iter().map(|_| "?").collect::<Vec<&str>>().join(",");
-                                let sql = format!(
-                                    "SELECT id, screenshot_id FROM ocr_results WHERE id IN ({}) AND is_deleted = 0",
-                                    placeholders
-                                );
-                                let params: Vec<&dyn rusqlite::ToSql> =
-                                    chunk.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
-                                let mut stmt = conn
-                                    .prepare(&sql)
-                                    .map_err(|e| format!("Failed to filter by category: {}", e))?;
-                                let mut chunk_map: std::collections::HashMap<i64, i64> =
-                                    std::collections::HashMap::new();
-                                let rows = stmt
-                                    .query_map(params.as_slice(), |row| {
-                                        Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?))
-                                    })
-                                    .map_err(|e| format!("Failed to filter by category: {}", e))?;
-                                for row in rows.filter_map(|r| r.ok()) {
-                                    chunk_map.insert(row.0, row.1);
-                                }
-                                for &id in chunk {
-                                    if let Some(&sid) = chunk_map.get(&id) {
-                                        if cat_ids.contains(&sid) {
-                                            filtered_ids.push(id);
-                                        }
-                                    }
-                                }
-                            }
-                            ids = filtered_ids;
+                            ids = self.filter_ocr_candidates(
+                                &conn,
+                                &ids,
+                                needed,
+                                &keep_screenshot,
+                                matcher.as_ref(),
+                            )?;
                         }
                     }
 
@@ -338,9 +321,10 @@ impl StorageState {
                                                          WHERE s.id IN ({})
                                                              AND s.is_deleted = 0
                                                              AND r.is_deleted = 0
-                                                             AND r.id = (SELECT MAX(r2.id) FROM ocr_results r2 WHERE r2.screenshot_id = s.id AND r2.is_deleted = 0)
+                                                             AND r.id = (SELECT MAX(r2.id) FROM ocr_results r2 WHERE r2.screenshot_id = s.id AND r2.is_deleted = 0{})
                              ORDER BY s.created_at DESC",
-                            placeholders.join(",")
+                            placeholders.join(","),
+                            representative_confidence_sql(min_confidence)
                         )
                     } else {
                         // Single keyword: page_ids are ocr_result ids
@@ -475,11 +459,17 @@ impl StorageState {
                                     category,
                                     created_at,
                                     screenshot_created_at,
+                                    thumbnail: None,
                                 }
                             },
                         )
                         .collect();
 
+                    let mut results = results;
+                    if by_relevance {
+                        restore_page_order(&mut results, &page_ids, is_multi_keyword);
+                    }
+
                     for (_, mut key) in screenshot_key_cache.into_iter() {
                         Self::zeroize_bytes(&mut key);
                     }
@@ -547,6 +537,24 @@ impl StorageState {
             ];
             let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
 
+            if parsed.has_metadata_filter() {
+                let ids = category_screenshot_ids
+                    .as_ref()
+                    .map_or_else(Vec::new, |ids| {
+                        ids.iter().map(i64::to_string).collect::<Vec<_>>()
+                    });
+                if ids.is_empty() {
+                    return Ok(vec![]);
+                }
+                // Integer IDs read from the database; inlined to avoid the bound parameter limit
+                where_clauses.push(format!("s.id IN ({})", ids.join(",")));
+            }
+
+            if let Some(min_confidence) = min_confidence {
+                where_clauses.push("r.confidence >= ?".to_string());
+                params.push(Box::new(min_confidence));
+            }
+
             if let Some(start) = start_time {
                 let start_dt = DateTime::<Utc>::from_timestamp(start as i64, 0)
                     .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
@@ -574,6 +582,17 @@ impl StorageState {
                 }
             }
 
+            if !tags.is_empty() {
+                let tag_placeholders = tags.iter().map(|_| "?").collect::<Vec<&str>>().join(",");
+                where_clauses.push(format!(
+                    "s.id IN (SELECT screenshot_id FROM tags WHERE tag IN ({}))",
+                    tag_placeholders
+                ));
+                for tag in &tags {
+                    params.push(Box::new(tag.clone()));
+                }
+            }
+
             if !where_clauses.is_empty() {
                 sql.push_str(" WHERE ");
                 sql.push_str(&where_clauses.join(" AND "));
@@ -688,6 +707,7 @@ impl StorageState {
                             category,
                             created_at,
                             screenshot_created_at,
+                            thumbnail: None,
                         }
                     },
                 )
@@ -697,11 +717,14 @@ impl StorageState {
                 Self::zeroize_bytes(&mut key);
             }
 
-            // Post-processing: filter by process name only
+            // Post-processing: filter by process name and exclusions
             // (category and time range already handled in SQL)
             let filtered: Vec<SearchResult> = results
                 .into_iter()
                 .filter(|r| {
+                    if excluded_screenshot_ids.contains(&r.screenshot_id) {
+                        return false;
+                    }
                     if let Some(ref names) = process_names {
                         if !names.is_empty() {
                             if let Some(p) = &r.process_name {
@@ -720,70 +743,41 @@ impl StorageState {
             return Ok(filtered);
         }
 
-        // Has bigram tokens: load bitmaps per keyword
+        // Has bigram tokens: load bitmaps per clause
         // In fuzzy mode, union bigram bitmaps and count matches per OCR ID.
         // In strict mode, intersect bigram bitmaps (original behavior).
         let mut keyword_bitmaps: Vec<roaring::RoaringBitmap> = Vec::new();
         let mut keyword_count_maps: Vec<HashMap<u32, u32>> = Vec::new();
-        for kw_bigrams in &per_keyword_bigrams {
-            let mut bitmaps: Vec<roaring::RoaringBitmap> = Vec::new();
-            for token in kw_bigrams {
-                let token_hash = Self::compute_hmac_hash(token, &hmac_key);
-                let blob: Option<Vec<u8>> = conn
-                    .query_row(
-                        "SELECT postings_blob FROM blind_bitmap_index WHERE token_hash = ?",
-                        params![&token_hash],
-                        |row| row.get(0),
-                    )
-                    .optional()
-                    .map_err(|e| format!("Failed to query bitmap: {}", e))?;
-
-                if let Some(b) = blob {
-                    let rb = roaring::RoaringBitmap::deserialize_from(&b[..])
-                        .map_err(|e| format!("Failed to deserialize bitmap: {}", e))?;
-                    bitmaps.push(rb);
-                } else if !fuzzy {
-                    // Strict mode: a missing bigram means no matches for this keyword
-                    bitmaps.clear();
-                    break;
-                }
-                // Fuzzy mode: skip missing bigrams
+        let mut term_doc_freqs: Vec<(String, u64)> = Vec::new();
+        if regex.is_some() {
+            // Regex: one OCR-level candidate set, verified against the text below
+            match Self::regex_candidates(&conn, query, &hmac_key)? {
+                Some(bitmap) => keyword_bitmaps.push(bitmap),
+                None => return Ok(vec![]),
             }
-
-            if bitmaps.is_empty() {
-                // This keyword has no matches => entire query has no matches
-                return Ok(vec![]);
-            }
-
-            if fuzzy {
-                // Union all bigram bitmaps and count how many bigrams each OCR ID matches
-                let mut count_map: HashMap<u32, u32> = HashMap::new();
-                let mut union = roaring::RoaringBitmap::new();
-                for bm in &bitmaps {
-                    union |= bm;
-                    for id in bm.iter() {
-                        *count_map.entry(id).or_insert(0) += 1;
-                    }
-                }
-                keyword_bitmaps.push(union);
-                keyword_count_maps.push(count_map);
-            } else {
-                // Strict mode: intra-keyword bigram intersection
-                let mut iter = bitmaps.into_iter();
-                let mut kw_intersection = iter.next().unwrap();
-                for bm in iter {
-                    kw_intersection &= &bm;
+        }
+        for clause in &bigram_clauses {
+            match Self::clause_match_with_pages(&conn, clause, &hmac_key, fuzzy, include_pages)? {
+                Some(matched) => {
+                    keyword_bitmaps.push(matched.bitmap);
+                    keyword_count_maps.push(matched.counts);
+                    term_doc_freqs = matched.term_doc_freqs;
                 }
-                keyword_bitmaps.push(kw_intersection);
+                // This clause has no matches => entire query has no matches
+                None => return Ok(vec![]),
             }
         }
+        if !Self::drop_low_confidence(&conn, &mut keyword_bitmaps, min_confidence)? {
+            return Ok(vec![]);
+        }
 
         // Cross-keyword intersection
         let is_multi_keyword = keyword_bitmaps.len() > 1;
 
         if is_multi_keyword {
             // Multi-keyword: intersect at screenshot level (different keywords may appear in different text boxes of the same screenshot)
-            let mut per_kw_screenshot_ids: Vec<std::collections::HashSet<i64>> = Vec::new();
+            // Matching text boxes per screenshot, per keyword (for relevance ranking)
+            let mut per_kw_screenshot_counts: Vec<HashMap<i64, u32>> = Vec::new();
 
             for kw_bitmap in &keyword_bitmaps {
                 let ocr_ids: Vec<i64> = kw_bitmap.iter().map(|v| v as i64).collect();
@@ -791,39 +785,18 @@ impl StorageState {
                     return Ok(vec![]);
                 }
 
-                let mut screenshot_ids = std::collections::HashSet::new();
-                for chunk in ocr_ids.chunks(500) {
-                    let placeholders = chunk.iter().map(|_| "?").collect::<Vec<&str>>().join(",");
-                    let sql = format!(
-                        "SELECT DISTINCT screenshot_id FROM ocr_results WHERE id IN ({}) AND is_deleted = 0",
-                        placeholders
-                    );
-                    let params: Vec<&dyn rusqlite::ToSql> =
-                        chunk.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
-                    let mut stmt = conn
-                        .prepare(&sql)
-                        .map_err(|e| format!("Failed to prepare screenshot resolve: {}", e))?;
-                    let rows = stmt
-                        .query_map(params.as_slice(), |row| row.get::<_, i64>(0))
-                        .map_err(|e| format!("Failed to resolve screenshot ids: {}", e))?;
-                    for row in rows.filter_map(|r| r.ok()) {
-                        screenshot_ids.insert(row);
-                    }
-                }
-                per_kw_screenshot_ids.push(screenshot_ids);
+                per_kw_screenshot_counts.push(Self::screenshot_match_counts(&conn, &ocr_ids)?);
             }
 
             // Intersect screenshot_ids across keywords
-            let mut iter = per_kw_screenshot_ids.into_iter();
-            let mut matching_screenshots: std::collections::HashSet<i64> = iter.next().unwrap();
-            for s in iter {
-                matching_screenshots.retain(|id| s.contains(id));
+            let mut matching_screenshots: HashSet<i64> =
+                per_kw_screenshot_counts[0].keys().copied().collect();
+            for counts in &per_kw_screenshot_counts[1..] {
+                matching_screenshots.retain(|id| counts.contains_key(id));
             }
 
-            // Pre-filter by category before pagination
-            if let Some(ref cat_ids) = category_screenshot_ids {
-                matching_screenshots.retain(|id| cat_ids.contains(id));
-            }
+            // Pre-filter by category and exclusions before pagination
+            matching_screenshots.retain(&keep_screenshot);
 
             if matching_screenshots.is_empty() {
                 return Ok(vec![]);
@@ -831,6 +804,13 @@ impl StorageState {
 
             let mut screenshot_ids_vec: Vec<i64> = matching_screenshots.into_iter().collect();
             screenshot_ids_vec.sort_unstable_by(|a, b| b.cmp(a));
+            if by_relevance {
+                screenshot_ids_vec = Self::rank_screenshot_ids_by_relevance(
+                    &conn,
+                    screenshot_ids_vec,
+                    &per_kw_screenshot_counts,
+                )?;
+            }
 
             // Pagination (by screenshot)
             let start = offset as usize;
@@ -863,9 +843,10 @@ impl StorageState {
                                  WHERE s.id IN ({})
                                      AND s.is_deleted = 0
                                      AND r.is_deleted = 0
-                                     AND r.id = (SELECT MAX(r2.id) FROM ocr_results r2 WHERE r2.screenshot_id = s.id AND r2.is_deleted = 0)
+                                     AND r.id = (SELECT MAX(r2.id) FROM ocr_results r2 WHERE r2.screenshot_id = s.id AND r2.is_deleted = 0{})
                  ORDER BY s.created_at DESC",
-                placeholders
+                placeholders,
+                representative_confidence_sql(min_confidence)
             );
 
             let param_refs: Vec<&dyn rusqlite::ToSql> = page_screenshot_ids
@@ -977,11 +958,17 @@ impl StorageState {
                             category,
                             created_at,
                             screenshot_created_at,
+                            thumbnail: None,
                         }
                     },
                 )
                 .collect();
 
+            let mut results = results;
+            if by_relevance {
+                restore_page_order(&mut results, &page_screenshot_ids, true);
+            }
+
             for (_, mut key) in screenshot_key_cache.into_iter() {
                 Self::zeroize_bytes(&mut key);
             }
@@ -1056,43 +1043,24 @@ impl StorageState {
             ids.sort_unstable_by(|a, b| b.cmp(a));
         }
 
-        // Pre-filter OCR IDs by category before pagination
-        if let Some(ref cat_ids) = category_screenshot_ids {
+        if by_relevance {
+            let terms: Vec<(&str, u64)> = term_doc_freqs
+                .iter()
+                .map(|(term, df)| (term.as_str(), *df))
+                .collect();
+            ids = self.rank_ocr_ids_by_relevance(&conn, ids, &terms)?;
+        }
+
+        // Pre-filter OCR IDs by category, exclusions and phrase or regex before pagination
+        if has_screenshot_filter || matcher.is_some() {
             let needed = (offset + limit) as usize;
-            let mut filtered_ids = Vec::with_capacity(needed);
-            for chunk in ids.chunks(500) {
-                if filtered_ids.len() >= needed {
-                    break;
-                }
-                let placeholders = chunk.iter().map(|_| "?").collect::<Vec<&str>>().join(",");
-                let sql = format!(
-                    "SELECT id, screenshot_id FROM ocr_results WHERE id IN ({}) AND is_deleted = 0",
-                    placeholders
-                );
-                let params: Vec<&dyn rusqlite::ToSql> =
-                    chunk.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
-                let mut stmt = conn
-                    .prepare(&sql)
-                    .map_err(|e| format!("Failed to filter by category: {}", e))?;
-                let mut chunk_map: std::collections::HashMap<i64, i64> =
-                    std::collections::HashMap::new();
-                let rows = stmt
-                    .query_map(params.as_slice(), |row| {
-                        Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?))
-                    })
-                    .map_err(|e| format!("Failed to filter by category: {}", e))?;
-                for row in rows.filter_map(|r| r.ok()) {
-                    chunk_map.insert(row.0, row.1);
-                }
-                for &id in chunk {
-                    if let Some(&sid) = chunk_map.get(&id) {
-                        if cat_ids.contains(&sid) {
-                            filtered_ids.push(id);
-                        }
-                    }
-                }
-            }
-            ids = filtered_ids;
+            ids = self.filter_ocr_candidates(
+                &conn,
+                &ids,
+                needed,
+                &keep_screenshot,
+                matcher.as_ref(),
+            )?;
         }
 
         // Pagination
@@ -1233,21 +1201,17 @@ impl StorageState {
                         category,
                         created_at,
                         screenshot_created_at,
+                        thumbnail: None,
                     })
                 },
             )
             .collect();
 
-        // In fuzzy mode, re-sort results to match the relevance order of page_ids
+        // In fuzzy or relevance mode, re-sort results to match the order of page_ids
         // (SQL ORDER BY destroys our score-based ordering)
         let mut results = results;
-        if fuzzy {
-            let id_order: HashMap<i64, usize> = page_ids
-                .iter()
-                .enumerate()
-                .map(|(i, &id)| (id, i))
-                .collect();
-            results.sort_by_key(|r| id_order.get(&r.id).copied().unwrap_or(usize::MAX));
+        if fuzzy || by_relevance {
+            restore_page_order(&mut results, &page_ids, false);
         }
 
         for (_, mut key) in screenshot_key_cache.into_iter() {
@@ -1298,4 +1262,137 @@ impl StorageState {
 
         Ok(filtered)
     }
+
+    /// Keeps OCR candidates, in order, whose screenshot passes `keep_screenshot` and,
+    /// for a phrase or regex search, whose decrypted text satisfies `matcher`. Stops
+    /// once `needed` candidates are kept.
+    pub(super) fn filter_ocr_candidates(
+        &self,
+        conn: &rusqlite::Connection,
+        ids: &[i64],
+        needed: usize,
+        keep_screenshot: &dyn Fn(&i64) -> bool,
+        matcher: Option<&TextMatcher>,
+    ) -> Result<Vec<i64>, String> {
+        let mut filtered_ids = Vec::with_capacity(needed);
+        for chunk in ids.chunks(500) {
+            if filtered_ids.len() >= needed {
+                break;
+            }
+            let placeholders = chunk.iter().map(|_| "?").collect::<Vec<&str>>().join(",");
+            let sql = format!(
+                "SELECT id, screenshot_id, text_enc, text_key_encrypted
+                 FROM ocr_results WHERE id IN ({}) AND is_deleted = 0",
+                placeholders
+            );
+            let params: Vec<&dyn rusqlite::ToSql> =
+                chunk.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
+            let mut stmt = conn
+                .prepare(&sql)
+                .map_err(|e| format!("Failed to filter search candidates: {}", e))?;
+            type CandidateRow = (i64, Option<Vec<u8>>, Option<Vec<u8>>);
+            let chunk_map: HashMap<i64, CandidateRow> = stmt
+                .query_map(params.as_slice(), |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        (row.get(1)?, row.get(2)?, row.get(3)?),
+                    ))
+                })
+                .map_err(|e| format!("Failed to filter search candidates: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            for &id in chunk {
+                if filtered_ids.len() >= needed {
+                    break;
+                }
+                let Some((sid, text_enc, text_key_enc)) = chunk_map.get(&id) else {
+                    continue;
+                };
+                if !keep_screenshot(sid) {
+                    continue;
+                }
+                if let Some(matcher) = matcher {
+                    let text = match (text_enc, text_key_enc) {
+                        (Some(data), Some(key)) => self
+                            .decrypt_payload_with_row_key(data, key)
+                            .ok()
+                            .and_then(|v| String::from_utf8(v).ok()),
+                        _ => None,
+                    };
+                    if !text.is_some_and(|t| matcher.is_match(&t)) {
+                        continue;
+                    }
+                }
+                filtered_ids.push(id);
+            }
+        }
+        Ok(filtered_ids)
+    }
+
+    /// Removes text boxes scored below `min_confidence` from each clause's OCR
+    /// candidates. Returns `false` once a clause has no candidates left.
+    pub(super) fn drop_low_confidence(
+        conn: &rusqlite::Connection,
+        bitmaps: &mut [roaring::RoaringBitmap],
+        min_confidence: Option<f64>,
+    ) -> Result<bool, String> {
+        let Some(min_confidence) = min_confidence else {
+            return Ok(true);
+        };
+        if bitmaps.is_empty() {
+            return Ok(true);
+        }
+        let mut stmt = conn
+            .prepare("SELECT id FROM ocr_results WHERE confidence < ?1 AND is_deleted = 0")
+            .map_err(|e| format!("Failed to prepare confidence filter: {}", e))?;
+        let low_confidence: roaring::RoaringBitmap = stmt
+            .query_map([min_confidence], |row| row.get::<_, i64>(0))
+            .map_err(|e| format!("Failed to query confidence filter: {}", e))?
+            .filter_map(|r| r.ok())
+            .map(|id| id as u32)
+            .collect();
+        for bitmap in bitmaps.iter_mut() {
+            *bitmap -= &low_confidence;
+        }
+        Ok(bitmaps.iter().all(|bitmap| !bitmap.is_empty()))
+    }
+}
+
+/// A usable confidence threshold: finite and above zero, since OCR confidences are
+/// clamped to `0.0..=1.0` and a zero threshold keeps everything.
+pub(super) fn normalize_min_confidence(min_confidence: Option<f64>) -> Option<f64> {
+    min_confidence.filter(|c| c.is_finite() && *c > 0.0)
+}
+
+/// Extra condition picking a screenshot's representative text box at or above the
+/// confidence threshold. The threshold is a normalized `f64`, so it is safe to inline.
+fn representative_confidence_sql(min_confidence: Option<f64>) -> String {
+    min_confidence
+        .map(|c| format!(" AND r2.confidence >= {}", c))
+        .unwrap_or_default()
+}
+
+/// Text check applied to decrypted OCR candidates.
+pub(super) enum TextMatcher<'a> {
+    /// Contains this normalized phrase (see `normalize_phrase`).
+    Phrase(&'a str),
+    Regex(&'a Regex),
+}
+
+impl TextMatcher<'_> {
+    pub(super) fn is_match(&self, text: &str) -> bool {
+        match self {
+            Self::Phrase(phrase) => normalize_phrase(text).contains(phrase),
+            Self::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
+/// Lowercases `text` and collapses whitespace runs, so phrase matching ignores case
+/// and OCR spacing differences.
+pub(super) fn normalize_phrase(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
 }
@@ -0,0 +1,242 @@
+//! Total match counts and facets for text search.
+//!
+//! Counts follow the matching rules of `search_text` without paging through results:
+//! candidates come from the blind index, and only screenshot metadata is read to
+//! bucket them by process and capture-local day. Phrase and regex searches still
+//! decrypt every candidate text box to verify it.
+
+use crate::credential_manager::{decrypt_row_key_with_cng, decrypt_with_master_key};
+use std::collections::{HashMap, HashSet};
+
+use super::capture_day::{current_utc_offset_minutes, CAPTURE_DAY_SQL};
+use super::search::{normalize_min_confidence, normalize_phrase, TextMatcher};
+use super::search_query::{parse_search_query, ParsedQuery};
+use super::search_regex::compile_search_regex;
+use super::{SearchCount, SearchFacet, SearchMatchMode, StorageState};
+
+impl StorageState {
+    /// Counts the results `search_text` would return for the same query and filters,
+    /// with per-process and per-day breakdowns.
+    ///
+    /// The process facet ignores `process_names` so the other processes stay
+    /// selectable. Queries without searchable terms count nothing.
+    pub fn count_search_matches(
+        &self,
+        query: &str,
+        fuzzy: bool,
+        process_names: Option<Vec<String>>,
+        start_time: Option<f64>,
+        end_time: Option<f64>,
+        categories: Option<Vec<String>>,
+        tags: Option<Vec<String>>,
+        match_mode: SearchMatchMode,
+        min_confidence: Option<f64>,
+    ) -> Result<SearchCount, String> {
+        let min_confidence = normalize_min_confidence(min_confidence);
+        let phrase = (match_mode == SearchMatchMode::Phrase)
+            .then(|| normalize_phrase(query))
+            .filter(|p| !p.is_empty());
+        let regex = (match_mode == SearchMatchMode::Regex && !query.trim().is_empty())
+            .then(|| compile_search_regex(query))
+            .transpose()?;
+        let fuzzy = fuzzy && phrase.is_none() && regex.is_none();
+        let matcher = match (&phrase, &regex) {
+            (Some(phrase), _) => Some(TextMatcher::Phrase(phrase)),
+            (_, Some(regex)) => Some(TextMatcher::Regex(regex)),
+            _ => None,
+        };
+        let hmac_key = self.credential_state.get_hmac_key()?;
+        let conn = self.open_read_connection_named("count_search_matches")?;
+
+        let tags = Self::normalize_search_tags(tags);
+        let category_screenshot_ids =
+            Self::search_filter_screenshot_ids(&conn, categories.as_deref(), &tags)?;
+        let parsed = match phrase {
+            Some(_) => ParsedQuery::phrase(query),
+            None if regex.is_some() => ParsedQuery::default(),
+            None => parse_search_query(query),
+        };
+        let category_screenshot_ids =
+            Self::restrict_by_metadata(&conn, &hmac_key, category_screenshot_ids, &parsed)?;
+        let mut excluded_screenshot_ids =
+            Self::excluded_screenshot_ids(&conn, &parsed.excluded, &hmac_key)?;
+        excluded_screenshot_ids.extend(Self::excluded_by_metadata(&conn, &hmac_key, &parsed)?);
+        let keep_screenshot = |sid: &i64| {
+            category_screenshot_ids
+                .as_ref()
+                .is_none_or(|ids| ids.contains(sid))
+                && !excluded_screenshot_ids.contains(sid)
+        };
+
+        let mut clause_bitmaps = Vec::new();
+        if regex.is_some() {
+            match Self::regex_candidates(&conn, query, &hmac_key)? {
+                Some(bitmap) => clause_bitmaps.push(bitmap),
+                None => return Ok(SearchCount::default()),
+            }
+        } else {
+            let mut term_clauses = Self::bigram_clauses(&parsed);
+            let mut fuzzy_terms = fuzzy;
+            if term_clauses.is_empty() {
+                term_clauses = Self::token_clauses(&parsed);
+                fuzzy_terms = false;
+            }
+            if term_clauses.is_empty() {
+                return Ok(SearchCount::default());
+            }
+
+            for clause in &term_clauses {
+                match Self::clause_match_with_pages(
+                    &conn,
+                    clause,
+                    &hmac_key,
+                    fuzzy_terms,
+                    matcher.is_none(),
+                )? {
+                    Some(matched) => clause_bitmaps.push(matched.bitmap),
+                    None => return Ok(SearchCount::default()),
+                }
+            }
+        }
+        if !Self::drop_low_confidence(&conn, &mut clause_bitmaps, min_confidence)? {
+            return Ok(SearchCount::default());
+        }
+
+        // Results per screenshot: matching text boxes for a single clause, or one per
+        // screenshot when several clauses must match it
+        let mut per_screenshot: HashMap<i64, u32> = if clause_bitmaps.len() == 1 {
+            let mut ids: Vec<i64> = clause_bitmaps[0].iter().map(|v| v as i64).collect();
+            if let Some(ref matcher) = matcher {
+                ids = self.filter_ocr_candidates(
+                    &conn,
+                    &ids,
+                    usize::MAX,
+                    &keep_screenshot,
+                    Some(matcher),
+                )?;
+            }
+            Self::screenshot_match_counts(&conn, &ids)?
+        } else {
+            let mut matching: Option<HashSet<i64>> = None;
+            for bitmap in &clause_bitmaps {
+                let ids: Vec<i64> = bitmap.iter().map(|v| v as i64).collect();
+                let screenshots: HashSet<i64> = Self::screenshot_match_counts(&conn, &ids)?
+                    .into_keys()
+                    .collect();
+                matching = Some(match matching {
+                    Some(mut acc) => {
+                        acc.retain(|id| screenshots.contains(id));
+                        acc
+                    }
+                    None => screenshots,
+                });
+            }
+            matching
+                .unwrap_or_default()
+                .into_iter()
+                .map(|sid| (sid, 1))
+                .collect()
+        };
+        per_screenshot.retain(|sid, _| keep_screenshot(sid));
+
+        let process_filter: Option<HashSet<&str>> = process_names
+            .as_ref()
+            .filter(|names| !names.is_empty())
+            .map(|names| names.iter().map(String::as_str).collect());
+
+        let mut total = 0i64;
+        let mut by_process: HashMap<String, i64> = HashMap::new();
+        let mut by_day: HashMap<String, i64> = HashMap::new();
+        let screenshot_ids: Vec<i64> = per_screenshot.keys().copied().collect();
+        let fallback_offset = current_utc_offset_minutes();
+        for chunk in screenshot_ids.chunks(500) {
+            // `?1` in the day expression binds the fallback offset; the id list follows
+            let placeholders = chunk.iter().map(|_| "?").collect::<Vec<&str>>().join(",");
+            let sql = format!(
+                "SELECT id, process_name, process_name_enc, content_key_encrypted,
+                        CAST(strftime('%s', created_at) AS INTEGER), {day}
+                 FROM screenshots WHERE id IN ({ids}) AND is_deleted = 0",
+                day = CAPTURE_DAY_SQL,
+                ids = placeholders
+            );
+            let mut params: Vec<&dyn rusqlite::ToSql> = vec![&fallback_offset];
+            params.extend(chunk.iter().map(|id| id as &dyn rusqlite::ToSql));
+            let mut stmt = conn
+                .prepare(&sql)
+                .map_err(|e| format!("Failed to prepare search count query: {}", e))?;
+            type FacetRow = (
+                i64,
+                Option<String>,
+                Option<Vec<u8>>,
+                Option<Vec<u8>>,
+                i64,
+                String,
+            );
+            let rows: Vec<FacetRow> = stmt
+                .query_map(params.as_slice(), |row| {
+                    Ok((
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                        row.get(5)?,
+                    ))
+                })
+                .map_err(|e| format!("Failed to execute search count query: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            for (sid, process_name, process_name_enc, key_enc, created_secs, day) in rows {
+                let created = created_secs as f64;
+                if start_time.is_some_and(|start| created < start)
+                    || end_time.is_some_and(|end| created > end)
+                {
+                    continue;
+                }
+                let process_name = process_name.filter(|p| !p.is_empty()).or_else(|| {
+                    let mut row_key = key_enc
+                        .as_ref()
+                        .and_then(|enc| decrypt_row_key_with_cng(enc).ok())?;
+                    let name = process_name_enc
+                        .as_ref()
+                        .and_then(|data| decrypt_with_master_key(&row_key, data).ok())
+                        .and_then(|v| String::from_utf8(v).ok());
+                    Self::zeroize_bytes(&mut row_key);
+                    name
+                });
+
+                let count = per_screenshot.get(&sid).copied().unwrap_or(0) as i64;
+                if let Some(ref name) = process_name {
+                    *by_process.entry(name.clone()).or_insert(0) += count;
+                }
+                let passes_process = match (&process_filter, &process_name) {
+                    (None, _) => true,
+                    (Some(names), Some(name)) => names.contains(name.as_str()),
+                    (Some(_), None) => false,
+                };
+                if passes_process {
+                    total += count;
+                    *by_day.entry(day).or_insert(0) += count;
+                }
+            }
+        }
+
+        let mut by_process: Vec<SearchFacet> = by_process
+            .into_iter()
+            .map(|(value, count)| SearchFacet { value, count })
+            .collect();
+        by_process.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+        let mut by_day: Vec<SearchFacet> = by_day
+            .into_iter()
+            .map(|(value, count)| SearchFacet { value, count })
+            .collect();
+        by_day.sort_by(|a, b| a.value.cmp(&b.value));
+
+        Ok(SearchCount {
+            total,
+            by_process,
+            by_day,
+        })
+    }
+}
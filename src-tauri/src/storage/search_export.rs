@@ -0,0 +1,185 @@
+//! Export of search results to CSV or JSON files.
+//!
+//! Results are fetched from `search_text` page by page and written as they arrive,
+//! so a large history never has to fit in memory. Each row is one OCR text box with
+//! its screenshot's capture time, process name, window title and category.
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use serde_json::json;
+
+use super::{
+    SearchExportFormat, SearchExportSummary, SearchMatchMode, SearchResult, SearchSortBy,
+    StorageState,
+};
+
+/// Results fetched from the index per page.
+const EXPORT_PAGE_SIZE: i32 = 500;
+
+const CSV_HEADER: &str =
+    "ocr_id,screenshot_id,captured_at,process_name,window_title,category,confidence,text";
+
+/// Quote a CSV field when needed. Values starting with a formula character are
+/// prefixed with `'` so spreadsheet apps do not evaluate OCR text as a formula.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+fn csv_row(result: &SearchResult) -> String {
+    [
+        result.id.to_string(),
+        result.screenshot_id.to_string(),
+        csv_field(&result.screenshot_created_at),
+        csv_field(result.process_name.as_deref().unwrap_or("")),
+        csv_field(result.window_title.as_deref().unwrap_or("")),
+        csv_field(result.category.as_deref().unwrap_or("")),
+        format!("{:.3}", result.confidence),
+        csv_field(&result.text),
+    ]
+    .join(",")
+}
+
+fn json_row(result: &SearchResult) -> serde_json::Value {
+    json!({
+        "ocr_id": result.id,
+        "screenshot_id": result.screenshot_id,
+        "captured_at": result.screenshot_created_at,
+        "process_name": result.process_name,
+        "window_title": result.window_title,
+        "category": result.category,
+        "confidence": result.confidence,
+        "text": result.text,
+    })
+}
+
+impl StorageState {
+    /// Write the search results for `query` to `output_path`, newest first, stopping
+    /// after `max_rows` rows. Fuzzy matching is off so the export holds exact matches.
+    pub fn export_search_results(
+        &self,
+        query: &str,
+        format: SearchExportFormat,
+        output_path: &Path,
+        process_names: Option<Vec<String>>,
+        start_time: Option<f64>,
+        end_time: Option<f64>,
+        match_mode: SearchMatchMode,
+        max_rows: usize,
+    ) -> Result<SearchExportSummary, String> {
+        if let Some(parent) = output_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create export directory: {}", e))?;
+        }
+        let file = File::create(output_path)
+            .map_err(|e| format!("Failed to create export file: {}", e))?;
+        let mut writer = BufWriter::new(file);
+        let write_err = |e: std::io::Error| format!("Failed to write export: {}", e);
+
+        match format {
+            SearchExportFormat::Csv => {
+                writer.write_all("\u{feff}".as_bytes()).map_err(write_err)?;
+                writeln!(writer, "{}", CSV_HEADER).map_err(write_err)?;
+            }
+            SearchExportFormat::Json => writer.write_all(b"[").map_err(write_err)?,
+        }
+
+        let mut rows = 0usize;
+        let mut screenshots: HashSet<i64> = HashSet::new();
+        let mut truncated = false;
+        let mut offset = 0i32;
+        loop {
+            let page = self.search_text(
+                query,
+                EXPORT_PAGE_SIZE,
+                offset,
+                false,
+                process_names.clone(),
+                start_time,
+                end_time,
+                None,
+                None,
+                SearchSortBy::Recent,
+                match_mode,
+                None,
+            )?;
+            let page_len = page.len();
+            for result in page {
+                if rows >= max_rows {
+                    truncated = true;
+                    break;
+                }
+                match format {
+                    SearchExportFormat::Csv => {
+                        writeln!(writer, "{}", csv_row(&result)).map_err(write_err)?;
+                    }
+                    SearchExportFormat::Json => {
+                        if rows > 0 {
+                            writer.write_all(b",").map_err(write_err)?;
+                        }
+                        writer.write_all(b"\n  ").map_err(write_err)?;
+                        serde_json::to_writer(&mut writer, &json_row(&result))
+                            .map_err(|e| format!("Failed to write export: {}", e))?;
+                    }
+                }
+                rows += 1;
+                screenshots.insert(result.screenshot_id);
+            }
+            if truncated || page_len < EXPORT_PAGE_SIZE as usize {
+                break;
+            }
+            offset += EXPORT_PAGE_SIZE;
+        }
+
+        if format == SearchExportFormat::Json {
+            writer.write_all(b"\n]\n").map_err(write_err)?;
+        }
+        writer.flush().map_err(write_err)?;
+
+        let mut screenshot_ids: Vec<i64> = screenshots.iter().copied().collect();
+        screenshot_ids.sort_unstable();
+        self.record_access_audit(
+            "export_search",
+            &json!({ "path": output_path.display().to_string(), "screenshot_ids": screenshot_ids }),
+        )?;
+        tracing::info!(
+            "Exported {} search results to {}",
+            rows,
+            output_path.display()
+        );
+        Ok(SearchExportSummary {
+            path: output_path.display().to_string(),
+            format,
+            rows,
+            screenshots: screenshots.len(),
+            truncated,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_fields_are_quoted_and_formula_safe() {
+        assert_eq!(csv_field("plain text"), "plain text");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("line\nbreak"), "\"line\nbreak\"");
+        assert_eq!(csv_field("=SUM(A1:A2)"), "'=SUM(A1:A2)");
+        assert_eq!(csv_field("-1,5"), "\"'-1,5\"");
+        assert_eq!(csv_field("发票 总计"), "发票 总计");
+    }
+}
@@ -0,0 +1,240 @@
+//! Recent search queries and prefix suggestions.
+//!
+//! Each distinct query is one row sealed with a per-row key, with a use count and the
+//! time it was last run. Queries that differ only in case and spacing share a row,
+//! which keeps the spelling it was first recorded with. Only the newest
+//! `MAX_SEARCH_HISTORY` queries are kept.
+//!
+//! Suggestions are looked up as the user types, so the decrypted history is cached in
+//! memory and updated alongside each write instead of being decrypted per lookup.
+
+use rusqlite::params;
+
+use super::search::normalize_phrase;
+use super::{SearchHistoryEntry, StorageState};
+
+const MAX_SEARCH_HISTORY: usize = 200;
+const MAX_HISTORY_QUERY_CHARS: usize = 500;
+
+impl StorageState {
+    /// Record a run of `query`. Blank and overlong queries are ignored.
+    pub fn record_search_query(&self, query: &str) -> Result<(), String> {
+        let query = query.split_whitespace().collect::<Vec<_>>().join(" ");
+        if query.is_empty() || query.chars().count() > MAX_HISTORY_QUERY_CHARS {
+            return Ok(());
+        }
+        let key = normalize_phrase(&query);
+
+        let mut cache = self
+            .search_history_cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if cache.is_none() {
+            *cache = Some(self.load_search_history()?);
+        }
+        let entries = cache.as_mut().unwrap();
+        let existing = entries
+            .iter()
+            .position(|entry| normalize_phrase(&entry.query) == key);
+
+        let guard = self.get_connection_named("record_search_query")?;
+        let conn = guard.as_ref().unwrap();
+        let id = match existing {
+            Some(index) => {
+                let id = entries[index].id;
+                conn.execute(
+                    "UPDATE search_history
+                     SET use_count = use_count + 1, last_used_at = CURRENT_TIMESTAMP
+                     WHERE id = ?1",
+                    params![id],
+                )
+                .map_err(|e| format!("Failed to update search history: {}", e))?;
+                id
+            }
+            None => {
+                let (query_enc, query_key_enc) =
+                    self.encrypt_payload_with_row_key(query.as_bytes())?;
+                conn.execute(
+                    "INSERT INTO search_history (query_enc, query_key_encrypted) VALUES (?1, ?2)",
+                    params![query_enc, query_key_enc],
+                )
+                .map_err(|e| format!("Failed to insert search history: {}", e))?;
+                conn.last_insert_rowid()
+            }
+        };
+        let (use_count, last_used_at): (i64, String) = conn
+            .query_row(
+                "SELECT use_count, last_used_at FROM search_history WHERE id = ?1",
+                params![id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|e| format!("Failed to read search history: {}", e))?;
+        conn.execute(
+            "DELETE FROM search_history WHERE id NOT IN (
+                 SELECT id FROM search_history ORDER BY last_used_at DESC, id DESC LIMIT ?1
+             )",
+            params![MAX_SEARCH_HISTORY as i64],
+        )
+        .map_err(|e| format!("Failed to prune search history: {}", e))?;
+        drop(guard);
+
+        let query = match existing {
+            Some(index) => entries.remove(index).query,
+            None => query,
+        };
+        entries.insert(
+            0,
+            SearchHistoryEntry {
+                id,
+                query,
+                use_count,
+                last_used_at,
+            },
+        );
+        entries.truncate(MAX_SEARCH_HISTORY);
+        Ok(())
+    }
+
+    /// Recent queries, newest first.
+    pub fn get_search_history(&self, limit: usize) -> Result<Vec<SearchHistoryEntry>, String> {
+        let mut cache = self
+            .search_history_cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if cache.is_none() {
+            *cache = Some(self.load_search_history()?);
+        }
+        Ok(cache
+            .as_ref()
+            .unwrap()
+            .iter()
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    /// Past queries starting with `prefix`, ignoring case and spacing; the prefix
+    /// itself is left out. Most used first, then most recent.
+    pub fn suggest_search_queries(
+        &self,
+        prefix: &str,
+        limit: usize,
+    ) -> Result<Vec<String>, String> {
+        let prefix = normalize_phrase(prefix);
+        if prefix.is_empty() {
+            return Ok(Vec::new());
+        }
+        let history = self.get_search_history(MAX_SEARCH_HISTORY)?;
+        Ok(suggest_from_history(history, &prefix, limit))
+    }
+
+    /// Delete all recorded queries; returns how many were removed.
+    pub fn clear_search_history(&self) -> Result<usize, String> {
+        let mut cache = self
+            .search_history_cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let guard = self.get_connection_named("clear_search_history")?;
+        let conn = guard.as_ref().unwrap();
+        let removed = conn
+            .execute("DELETE FROM search_history", [])
+            .map_err(|e| format!("Failed to clear search history: {}", e))?;
+        *cache = Some(Vec::new());
+        Ok(removed)
+    }
+
+    /// Drop the cached history so the next lookup decrypts it again.
+    pub(super) fn invalidate_search_history(&self) {
+        *self
+            .search_history_cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// Decrypted history, newest first. Rows that fail to decrypt are skipped.
+    fn load_search_history(&self) -> Result<Vec<SearchHistoryEntry>, String> {
+        let conn = self.open_read_connection_named("load_search_history")?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, query_enc, query_key_encrypted, use_count, last_used_at
+                 FROM search_history ORDER BY last_used_at DESC, id DESC",
+            )
+            .map_err(|e| format!("Failed to prepare search history query: {}", e))?;
+        let rows: Vec<(i64, Vec<u8>, Vec<u8>, i64, String)> = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                ))
+            })
+            .map_err(|e| format!("Failed to query search history: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(id, query_enc, query_key_enc, use_count, last_used_at)| {
+                let query = self
+                    .decrypt_payload_with_row_key(&query_enc, &query_key_enc)
+                    .ok()
+                    .and_then(|v| String::from_utf8(v).ok())?;
+                Some(SearchHistoryEntry {
+                    id,
+                    query,
+                    use_count,
+                    last_used_at,
+                })
+            })
+            .collect())
+    }
+}
+
+/// Entries of `history` (newest first) extending the normalized `prefix`.
+fn suggest_from_history(
+    mut history: Vec<SearchHistoryEntry>,
+    prefix: &str,
+    limit: usize,
+) -> Vec<String> {
+    history.retain(|entry| {
+        let query = normalize_phrase(&entry.query);
+        query.len() > prefix.len() && query.starts_with(prefix)
+    });
+    // Stable sort keeps newest first among equal counts
+    history.sort_by(|a, b| b.use_count.cmp(&a.use_count));
+    history
+        .into_iter()
+        .take(limit)
+        .map(|entry| entry.query)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: i64, query: &str, use_count: i64) -> SearchHistoryEntry {
+        SearchHistoryEntry {
+            id,
+            query: query.to_string(),
+            use_count,
+            last_used_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn suggestions_extend_the_prefix_by_use_then_recency() {
+        let history = vec![
+            entry(4, "Invoice  March", 1),
+            entry(3, "invoice", 9),
+            entry(2, "invoice april", 3),
+            entry(1, "build failed", 5),
+        ];
+        assert_eq!(
+            suggest_from_history(history, "invoice", 5),
+            vec!["invoice april", "Invoice  March"]
+        );
+    }
+}
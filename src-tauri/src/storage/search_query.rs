@@ -0,0 +1,497 @@
+//! Boolean query syntax for text search.
+//!
+//! Whitespace-separated terms must all match (`foo bar`). `a OR b` lets either term
+//! satisfy the same clause, `-term` or `NOT term` drops screenshots containing the
+//! term, and `"exact phrase"` keeps its words together as one term so its bigrams are
+//! matched within a single text box. `OR`, `NOT` and `AND` are operators only in upper
+//! case and outside quotes; `AND` is accepted and ignored. `domain:example.com` keeps
+//! browser captures of that site or its subdomains, and `title:report` (or
+//! `title:"q3 report"`) screenshots whose window title contains the text; prefixed
+//! with `-`, both drop matching screenshots instead.
+
+use roaring::RoaringBitmap;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::{HashMap, HashSet};
+
+use super::StorageState;
+
+/// A search query split into positive clauses and exclusions.
+#[derive(Debug, Default, PartialEq)]
+pub(super) struct ParsedQuery {
+    /// Every clause must match; a clause matches when any of its terms does.
+    pub clauses: Vec<Vec<String>>,
+    /// Screenshots containing any of these terms are dropped.
+    pub excluded: Vec<String>,
+    /// When set, only screenshots of pages on one of these domains are kept.
+    pub domains: Vec<String>,
+    /// Screenshots of pages on any of these domains are dropped.
+    pub excluded_domains: Vec<String>,
+    /// When set, only screenshots whose window title contains all of these are kept.
+    pub titles: Vec<String>,
+    /// Screenshots whose window title contains any of these are dropped.
+    pub excluded_titles: Vec<String>,
+}
+
+impl ParsedQuery {
+    /// Whether the query narrows results by `domain:` or `title:`.
+    pub(super) fn has_metadata_filter(&self) -> bool {
+        !self.domains.is_empty() || !self.titles.is_empty()
+    }
+
+    /// Whether the query only excludes, by terms or metadata.
+    pub(super) fn has_only_exclusions(&self) -> bool {
+        self.clauses.is_empty()
+            && !self.has_metadata_filter()
+            && !(self.excluded.is_empty()
+                && self.excluded_domains.is_empty()
+                && self.excluded_titles.is_empty())
+    }
+
+    /// A single clause holding `phrase` with whitespace collapsed, with no operators
+    /// interpreted. Case is kept: the blind index is case-sensitive.
+    pub(super) fn phrase(phrase: &str) -> Self {
+        Self {
+            clauses: vec![vec![phrase
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")]],
+            ..Self::default()
+        }
+    }
+}
+
+enum Lexeme {
+    Term {
+        text: String,
+        negated: bool,
+    },
+    Field {
+        field: Field,
+        value: String,
+        negated: bool,
+    },
+    Or,
+    Not,
+}
+
+/// `name:value` operators, which filter on screenshot metadata instead of matching
+/// OCR text.
+#[derive(Clone, Copy)]
+enum Field {
+    Domain,
+    Title,
+}
+
+const FIELD_OPERATORS: [(&str, Field); 2] = [("domain:", Field::Domain), ("title:", Field::Title)];
+
+fn lex(query: &str) -> Vec<Lexeme> {
+    let mut lexemes = Vec::new();
+    let mut rest = query;
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+        let negated = rest.starts_with('-');
+        if negated {
+            rest = &rest[1..];
+        }
+        let field = FIELD_OPERATORS.iter().find(|(name, _)| {
+            rest.get(..name.len())
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(name))
+        });
+        if let Some((name, _)) = field {
+            rest = &rest[name.len()..];
+        }
+        let quoted = rest.starts_with('"');
+        let text = if quoted {
+            let body = &rest[1..];
+            let end = body.find('"').unwrap_or(body.len());
+            let phrase = body[..end].split_whitespace().collect::<Vec<_>>().join(" ");
+            rest = body.get(end + 1..).unwrap_or_default();
+            phrase
+        } else {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let word = rest[..end].to_string();
+            rest = &rest[end..];
+            word
+        };
+        if text.is_empty() {
+            continue;
+        }
+        if let Some(&(_, field)) = field {
+            lexemes.push(Lexeme::Field {
+                field,
+                value: text,
+                negated,
+            });
+            continue;
+        }
+        match text.as_str() {
+            "OR" if !quoted && !negated => lexemes.push(Lexeme::Or),
+            "NOT" if !quoted && !negated => lexemes.push(Lexeme::Not),
+            "AND" if !quoted && !negated => {}
+            _ => lexemes.push(Lexeme::Term { text, negated }),
+        }
+    }
+    lexemes
+}
+
+/// Parses `query`. Operators without an operand (a leading `OR`, a trailing `NOT`)
+/// are ignored rather than rejected.
+pub(super) fn parse_search_query(query: &str) -> ParsedQuery {
+    let mut parsed = ParsedQuery::default();
+    let mut last_was_positive = false;
+    let mut join_next = false;
+    let mut negate_next = false;
+    for lexeme in lex(query) {
+        match lexeme {
+            Lexeme::Or => join_next = last_was_positive,
+            Lexeme::Not => negate_next = true,
+            Lexeme::Field {
+                field,
+                value,
+                negated,
+            } => {
+                let target = match (field, negated || negate_next) {
+                    (Field::Domain, false) => &mut parsed.domains,
+                    (Field::Domain, true) => &mut parsed.excluded_domains,
+                    (Field::Title, false) => &mut parsed.titles,
+                    (Field::Title, true) => &mut parsed.excluded_titles,
+                };
+                target.push(value);
+                last_was_positive = false;
+                join_next = false;
+                negate_next = false;
+            }
+            Lexeme::Term { text, negated } => {
+                if negated || negate_next {
+                    parsed.excluded.push(text);
+                    last_was_positive = false;
+                } else {
+                    match parsed.clauses.last_mut() {
+                        Some(clause) if join_next => clause.push(text),
+                        _ => parsed.clauses.push(vec![text]),
+                    }
+                    last_was_positive = true;
+                }
+                join_next = false;
+                negate_next = false;
+            }
+        }
+    }
+    parsed
+}
+
+/// OCR rows matching one clause of a parsed query.
+pub(super) struct ClauseMatch {
+    pub bitmap: RoaringBitmap,
+    /// Fuzzy mode: the most tokens any single term of the clause matched, per OCR row.
+    pub counts: HashMap<u32, u32>,
+    /// Matching OCR rows per term, for relevance ranking.
+    pub term_doc_freqs: Vec<(String, u64)>,
+}
+
+/// Tokens of each term of each clause. Terms without tokens are dropped, as are
+/// clauses left without terms.
+type TermClauses<'a> = Vec<Vec<(&'a str, Vec<String>)>>;
+
+impl StorageState {
+    /// Bigrams of each term of each clause.
+    pub(super) fn bigram_clauses(parsed: &ParsedQuery) -> TermClauses<'_> {
+        Self::tokenize_clauses(parsed, |term| {
+            Self::bigram_tokenize(term).into_iter().collect()
+        })
+    }
+
+    /// Word-segmentation tokens of each term of each clause, used when no term is
+    /// long enough for bigrams.
+    pub(super) fn token_clauses(parsed: &ParsedQuery) -> TermClauses<'_> {
+        Self::tokenize_clauses(parsed, Self::tokenize_text)
+    }
+
+    fn tokenize_clauses(
+        parsed: &ParsedQuery,
+        tokenize: impl Fn(&str) -> Vec<String>,
+    ) -> TermClauses<'_> {
+        parsed
+            .clauses
+            .iter()
+            .map(|clause| {
+                clause
+                    .iter()
+                    .map(|term| (term.as_str(), tokenize(term)))
+                    .filter(|(_, tokens)| !tokens.is_empty())
+                    .collect::<Vec<_>>()
+            })
+            .filter(|clause| !clause.is_empty())
+            .collect()
+    }
+
+    /// Normalized search tags; invalid tags are dropped.
+    pub(super) fn normalize_search_tags(tags: Option<Vec<String>>) -> Vec<String> {
+        tags.unwrap_or_default()
+            .iter()
+            .filter_map(|tag| super::tags::normalize_tag(tag).ok())
+            .collect()
+    }
+
+    /// Screenshots allowed by the category and tag filters, or `None` when neither is
+    /// set. Each filter keeps screenshots matching any listed value; when both are given
+    /// a screenshot must match both.
+    pub(super) fn search_filter_screenshot_ids(
+        conn: &Connection,
+        categories: Option<&[String]>,
+        tags: &[String],
+    ) -> Result<Option<HashSet<i64>>, String> {
+        let category_ids: Option<HashSet<i64>> = match categories {
+            Some(cats) if !cats.is_empty() => {
+                let placeholders = cats.iter().map(|_| "?").collect::<Vec<&str>>().join(",");
+                let sql = format!(
+                    "SELECT id FROM screenshots WHERE is_deleted = 0 AND category IN ({})",
+                    placeholders
+                );
+                let cat_params: Vec<&dyn rusqlite::ToSql> =
+                    cats.iter().map(|c| c as &dyn rusqlite::ToSql).collect();
+                let mut stmt = conn
+                    .prepare(&sql)
+                    .map_err(|e| format!("Failed to query category filter: {}", e))?;
+                let ids: HashSet<i64> = stmt
+                    .query_map(cat_params.as_slice(), |row| row.get::<_, i64>(0))
+                    .map_err(|e| format!("Failed to fetch category ids: {}", e))?
+                    .filter_map(|r| r.ok())
+                    .collect();
+                Some(ids)
+            }
+            _ => None,
+        };
+
+        if tags.is_empty() {
+            return Ok(category_ids);
+        }
+        let tag_ids = Self::screenshot_ids_with_tags(conn, tags)?;
+        Ok(Some(match category_ids {
+            Some(mut ids) => {
+                ids.retain(|id| tag_ids.contains(id));
+                ids
+            }
+            None => tag_ids,
+        }))
+    }
+
+    /// Number of the given live OCR rows in each screenshot.
+    pub(super) fn screenshot_match_counts(
+        conn: &Connection,
+        ocr_ids: &[i64],
+    ) -> Result<HashMap<i64, u32>, String> {
+        let mut screenshot_counts: HashMap<i64, u32> = HashMap::new();
+        for chunk in ocr_ids.chunks(500) {
+            let placeholders = chunk.iter().map(|_| "?").collect::<Vec<&str>>().join(",");
+            let sql = format!(
+                "SELECT screenshot_id, COUNT(*) FROM ocr_results WHERE id IN ({}) AND is_deleted = 0 GROUP BY screenshot_id",
+                placeholders
+            );
+            let params: Vec<&dyn rusqlite::ToSql> =
+                chunk.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
+            let mut stmt = conn
+                .prepare(&sql)
+                .map_err(|e| format!("Failed to prepare screenshot resolve: {}", e))?;
+            let rows = stmt
+                .query_map(params.as_slice(), |row| {
+                    Ok((row.get::<_, i64>(0)?, row.get::<_, u32>(1)?))
+                })
+                .map_err(|e| format!("Failed to resolve screenshot ids: {}", e))?;
+            for (sid, count) in rows.filter_map(|r| r.ok()) {
+                *screenshot_counts.entry(sid).or_insert(0) += count;
+            }
+        }
+        Ok(screenshot_counts)
+    }
+
+    /// Posting list of one term: the intersection of its tokens' bitmaps, or in fuzzy
+    /// mode their union along with how many tokens each OCR row matched. `None` when
+    /// the term cannot match.
+    pub(super) fn term_bitmap(
+        conn: &Connection,
+        tokens: &[String],
+        hmac_key: &[u8],
+        fuzzy: bool,
+    ) -> Result<Option<(RoaringBitmap, HashMap<u32, u32>)>, String> {
+        let mut bitmaps: Vec<RoaringBitmap> = Vec::new();
+        for token in tokens {
+            let token_hash = Self::compute_hmac_hash(token, hmac_key);
+            let blob: Option<Vec<u8>> = conn
+                .query_row(
+                    "SELECT postings_blob FROM blind_bitmap_index WHERE token_hash = ?",
+                    params![&token_hash],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|e| format!("Failed to query bitmap: {}", e))?;
+
+            if let Some(b) = blob {
+                let rb = RoaringBitmap::deserialize_from(&b[..])
+                    .map_err(|e| format!("Failed to deserialize bitmap: {}", e))?;
+                bitmaps.push(rb);
+            } else if !fuzzy {
+                // Strict mode: a missing token means no matches for this term
+                return Ok(None);
+            }
+            // Fuzzy mode: skip missing tokens
+        }
+
+        if bitmaps.is_empty() {
+            return Ok(None);
+        }
+
+        let mut counts: HashMap<u32, u32> = HashMap::new();
+        let bitmap = if fuzzy {
+            // Union all token bitmaps and count how many tokens each OCR ID matches
+            let mut union = RoaringBitmap::new();
+            for bm in &bitmaps {
+                union |= bm;
+                for id in bm.iter() {
+                    *counts.entry(id).or_insert(0) += 1;
+                }
+            }
+            union
+        } else {
+            let mut iter = bitmaps.into_iter();
+            let mut intersection = iter.next().unwrap();
+            for bm in iter {
+                intersection &= &bm;
+            }
+            intersection
+        };
+
+        Ok(if bitmap.is_empty() {
+            None
+        } else {
+            Some((bitmap, counts))
+        })
+    }
+
+    /// Union of the posting lists of a clause's alternatives, each given as the term
+    /// and its tokens. `None` when no alternative matches.
+    pub(super) fn clause_match(
+        conn: &Connection,
+        terms: &[(&str, Vec<String>)],
+        hmac_key: &[u8],
+        fuzzy: bool,
+    ) -> Result<Option<ClauseMatch>, String> {
+        let mut clause = ClauseMatch {
+            bitmap: RoaringBitmap::new(),
+            counts: HashMap::new(),
+            term_doc_freqs: Vec::new(),
+        };
+        for (term, tokens) in terms {
+            if let Some((bitmap, counts)) = Self::term_bitmap(conn, tokens, hmac_key, fuzzy)? {
+                clause.term_doc_freqs.push((term.to_string(), bitmap.len()));
+                clause.bitmap |= &bitmap;
+                for (id, count) in counts {
+                    let best = clause.counts.entry(id).or_insert(0);
+                    *best = (*best).max(count);
+                }
+            }
+        }
+        Ok(if clause.bitmap.is_empty() {
+            None
+        } else {
+            Some(clause)
+        })
+    }
+
+    /// Screenshots containing any excluded term. Terms match as in strict search, so
+    /// an exclusion may also drop screenshots where its bigrams occur apart.
+    pub(super) fn excluded_screenshot_ids(
+        conn: &Connection,
+        excluded: &[String],
+        hmac_key: &[u8],
+    ) -> Result<HashSet<i64>, String> {
+        let mut screenshot_ids = HashSet::new();
+        for term in excluded {
+            let bigrams: Vec<String> = Self::bigram_tokenize(term).into_iter().collect();
+            let tokens = if bigrams.is_empty() {
+                Self::tokenize_text(term)
+            } else {
+                bigrams
+            };
+            if tokens.is_empty() {
+                continue;
+            }
+            let Some((bitmap, _)) = Self::term_bitmap(conn, &tokens, hmac_key, false)? else {
+                continue;
+            };
+
+            let ocr_ids: Vec<i64> = bitmap.iter().map(|v| v as i64).collect();
+            for chunk in ocr_ids.chunks(500) {
+                let placeholders = chunk.iter().map(|_| "?").collect::<Vec<&str>>().join(",");
+                let sql = format!(
+                    "SELECT DISTINCT screenshot_id FROM ocr_results WHERE id IN ({})",
+                    placeholders
+                );
+                let params: Vec<&dyn rusqlite::ToSql> =
+                    chunk.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
+                let mut stmt = conn
+                    .prepare(&sql)
+                    .map_err(|e| format!("Failed to prepare exclusion resolve: {}", e))?;
+                let rows = stmt
+                    .query_map(params.as_slice(), |row| row.get::<_, i64>(0))
+                    .map_err(|e| format!("Failed to resolve excluded screenshots: {}", e))?;
+                screenshot_ids.extend(rows.filter_map(|r| r.ok()));
+            }
+        }
+        Ok(screenshot_ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(words: &[&str]) -> Vec<String> {
+        words.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_and_or_and_exclusions() {
+        let parsed = parse_search_query("invoice a OR b -draft NOT spam AND total");
+        assert_eq!(
+            parsed.clauses,
+            vec![terms(&["invoice"]), terms(&["a", "b"]), terms(&["total"])]
+        );
+        assert_eq!(parsed.excluded, terms(&["draft", "spam"]));
+    }
+
+    #[test]
+    fn quotes_group_phrases_and_disable_operators() {
+        let parsed = parse_search_query(r#""exact   phrase" OR "OR" -"not this" or"#);
+        assert_eq!(
+            parsed.clauses,
+            vec![terms(&["exact phrase", "OR"]), terms(&["or"])]
+        );
+        assert_eq!(parsed.excluded, terms(&["not this"]));
+    }
+
+    #[test]
+    fn dangling_operators_are_ignored() {
+        let parsed = parse_search_query("OR foo - -bar OR baz NOT");
+        assert_eq!(parsed.clauses, vec![terms(&["foo"]), terms(&["baz"])]);
+        assert_eq!(parsed.excluded, terms(&["bar"]));
+        assert_eq!(parse_search_query("   "), ParsedQuery::default());
+    }
+
+    #[test]
+    fn field_operators_filter_instead_of_matching_text() {
+        let parsed = parse_search_query(
+            r#"Domain:github.com pull OR -domain:gist.github.com "domain:x" title:"Q3  report" NOT title:draft domain:"#,
+        );
+        assert_eq!(parsed.clauses, vec![terms(&["pull"]), terms(&["domain:x"])]);
+        assert_eq!(parsed.domains, terms(&["github.com"]));
+        assert_eq!(parsed.excluded_domains, terms(&["gist.github.com"]));
+        assert_eq!(parsed.titles, terms(&["Q3 report"]));
+        assert_eq!(parsed.excluded_titles, terms(&["draft"]));
+        assert!(parsed.has_metadata_filter());
+        assert!(parse_search_query("-title:draft").has_only_exclusions());
+    }
+}
@@ -0,0 +1,139 @@
+//! Regular-expression search over OCR text.
+//!
+//! The blind index cannot evaluate a regex, so candidates are narrowed first: literal
+//! runs that every match must contain are split into bigrams and looked up like strict
+//! terms, and their posting lists are intersected. The surviving text boxes are then
+//! decrypted and checked against the compiled regex. Patterns without such a run
+//! (`\d+\.\d+\.\d+\.\d+`, or anything under `(?i)`, whose letters become classes) scan
+//! the newest `REGEX_SCAN_LIMIT` text boxes instead.
+
+use regex::{Regex, RegexBuilder};
+use regex_syntax::hir::{Hir, HirKind};
+use roaring::RoaringBitmap;
+use rusqlite::Connection;
+
+use super::StorageState;
+
+/// Compiled program size cap, so a pathological pattern cannot exhaust memory.
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+/// Text boxes scanned, newest first, when a pattern has no literal run to pre-filter on.
+const REGEX_SCAN_LIMIT: u32 = 20_000;
+
+/// Compiles a search pattern. Matching is case-sensitive unless the pattern opts out
+/// with `(?i)`, like the blind index itself.
+pub(super) fn compile_search_regex(pattern: &str) -> Result<Regex, String> {
+    RegexBuilder::new(pattern)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .build()
+        .map_err(|e| format!("Invalid regex: {}", e))
+}
+
+/// Literal runs, in pattern order, that every match of `pattern` contains. Runs too
+/// short to yield a bigram are dropped.
+pub(super) fn required_literals(pattern: &str) -> Vec<String> {
+    let Ok(hir) = regex_syntax::parse(pattern) else {
+        return Vec::new();
+    };
+    let mut literals = Vec::new();
+    let mut run = String::new();
+    collect_required_literals(&hir, &mut run, &mut literals);
+    flush_literal_run(&mut run, &mut literals);
+    literals
+}
+
+fn collect_required_literals(hir: &Hir, run: &mut String, out: &mut Vec<String>) {
+    match hir.kind() {
+        HirKind::Literal(literal) => run.push_str(&String::from_utf8_lossy(&literal.0)),
+        // Assertions consume no text, so the run continues across them
+        HirKind::Empty | HirKind::Look(_) => {}
+        HirKind::Capture(capture) => collect_required_literals(&capture.sub, run, out),
+        HirKind::Concat(parts) => {
+            for part in parts {
+                collect_required_literals(part, run, out);
+            }
+        }
+        HirKind::Repetition(repetition) if repetition.min > 0 => {
+            // The body occurs at least once, but only its own runs are contiguous
+            flush_literal_run(run, out);
+            let mut inner = String::new();
+            collect_required_literals(&repetition.sub, &mut inner, out);
+            flush_literal_run(&mut inner, out);
+        }
+        // Classes, alternations and optional parts break the run
+        _ => flush_literal_run(run, out),
+    }
+}
+
+fn flush_literal_run(run: &mut String, out: &mut Vec<String>) {
+    if StorageState::bigram_tokenize(run).is_empty() {
+        run.clear();
+    } else {
+        out.push(std::mem::take(run));
+    }
+}
+
+impl StorageState {
+    /// OCR rows that may match `pattern`: the intersected strict postings of its
+    /// required literals, or the newest `REGEX_SCAN_LIMIT` live rows when it has none.
+    /// `None` when some literal does not occur at all.
+    pub(super) fn regex_candidates(
+        conn: &Connection,
+        pattern: &str,
+        hmac_key: &[u8],
+    ) -> Result<Option<RoaringBitmap>, String> {
+        let literals = required_literals(pattern);
+        if literals.is_empty() {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT id FROM ocr_results WHERE is_deleted = 0 ORDER BY id DESC LIMIT ?1",
+                )
+                .map_err(|e| format!("Failed to prepare regex scan: {}", e))?;
+            let bitmap: RoaringBitmap = stmt
+                .query_map([REGEX_SCAN_LIMIT], |row| row.get::<_, i64>(0))
+                .map_err(|e| format!("Failed to query regex scan: {}", e))?
+                .filter_map(|r| r.ok())
+                .map(|id| id as u32)
+                .collect();
+            return Ok((!bitmap.is_empty()).then_some(bitmap));
+        }
+
+        let mut candidates: Option<RoaringBitmap> = None;
+        for literal in &literals {
+            let tokens: Vec<String> = Self::bigram_tokenize(literal).into_iter().collect();
+            let Some((bitmap, _)) = Self::term_bitmap(conn, &tokens, hmac_key, false)? else {
+                return Ok(None);
+            };
+            candidates = Some(match candidates {
+                Some(mut acc) => {
+                    acc &= &bitmap;
+                    acc
+                }
+                None => bitmap,
+            });
+        }
+        Ok(candidates.filter(|bitmap| !bitmap.is_empty()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn literal_runs_stop_at_classes_and_optional_parts() {
+        assert_eq!(required_literals(r"INV-\d{6}"), vec!["INV-"]);
+        assert_eq!(required_literals(r"ab(cd)?ef"), vec!["ab", "ef"]);
+        assert_eq!(required_literals(r"(foo|bar)baz"), vec!["baz"]);
+        assert_eq!(required_literals(r"x(abc)+y"), vec!["abc"]);
+        assert_eq!(required_literals(r"\bport (\d+)\b"), vec!["port "]);
+    }
+
+    #[test]
+    fn patterns_without_bigram_literals_need_a_scan() {
+        assert!(required_literals(r"\d+\.\d+\.\d+\.\d+").is_empty());
+        assert!(required_literals(r"(?i)invoice").is_empty());
+        assert!(required_literals(r"[").is_empty());
+        assert!(compile_search_regex(r"[").is_err());
+    }
+}
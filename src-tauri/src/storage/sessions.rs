@@ -0,0 +1,392 @@
+//! Activity sessions: runs of consecutive screenshots of the same process.
+//!
+//! The timeline renders a day as a few dozen collapsed blocks ("Code, 45 min")
+//! instead of thousands of frames. Sessions are built incrementally from screenshot
+//! IDs above the last segmented one, so each call only looks at new captures. A
+//! session ends when the process changes or capture pauses for longer than
+//! [`SESSION_IDLE_GAP_SECS`]. Counts and bounds are recomputed from live rows when
+//! read, so deleting screenshots shrinks or hides sessions without re-segmenting.
+//! Segmentation stops at the oldest capture still pending commit and resumes there.
+//!
+//! Grouping uses the `process_name_hash` blind index (the plaintext name for rows not
+//! hashed yet), so sessions still split per process in paranoid and compliance mode.
+//! Sessions store no process name; it is read from the screenshots, or decrypted from
+//! one of them when only an encrypted name is left and the session is unlocked.
+
+use std::collections::HashMap;
+
+use rusqlite::{params, OptionalExtension};
+
+use super::{ActivitySession, StorageState};
+
+/// A capture pause longer than this starts a new session.
+const SESSION_IDLE_GAP_SECS: i64 = 300;
+/// Screenshots segmented per transaction.
+const SESSION_REFRESH_BATCH: i64 = 5000;
+
+/// A session being extended or created during segmentation.
+#[derive(Debug, Clone, PartialEq)]
+struct SessionSpan {
+    /// Row ID when the span continues a stored session.
+    id: Option<i64>,
+    /// `process_name_hash`, or the plaintext name of rows not hashed yet.
+    process_key: Option<String>,
+    start_ts: i64,
+    end_ts: i64,
+    first_screenshot_id: i64,
+    last_screenshot_id: i64,
+    screenshot_count: i64,
+}
+
+/// Append `frames` (id, process key, Unix seconds; ascending by id) to `open`, starting
+/// a new span on process changes and idle gaps. The first returned span is `open`
+/// when it was extended or left untouched.
+fn segment_frames(
+    open: Option<SessionSpan>,
+    frames: &[(i64, Option<String>, i64)],
+) -> Vec<SessionSpan> {
+    let mut spans: Vec<SessionSpan> = open.into_iter().collect();
+    for (id, process_key, ts) in frames {
+        match spans.last_mut() {
+            Some(span)
+                if span.process_key == *process_key
+                    && (ts - span.end_ts).abs() <= SESSION_IDLE_GAP_SECS =>
+            {
+                span.start_ts = span.start_ts.min(*ts);
+                span.end_ts = span.end_ts.max(*ts);
+                span.last_screenshot_id = *id;
+                span.screenshot_count += 1;
+            }
+            _ => spans.push(SessionSpan {
+                id: None,
+                process_key: process_key.clone(),
+                start_ts: *ts,
+                end_ts: *ts,
+                first_screenshot_id: *id,
+                last_screenshot_id: *id,
+                screenshot_count: 1,
+            }),
+        }
+    }
+    spans
+}
+
+impl StorageState {
+    /// Segment screenshots captured since the last refresh. Returns the number of
+    /// screenshots added to sessions.
+    pub fn refresh_activity_sessions(&self) -> Result<usize, String> {
+        let mut segmented = 0usize;
+        loop {
+            let mut guard = self.get_connection_named("refresh_activity_sessions")?;
+            let conn = guard.as_mut().unwrap();
+            let tx = conn
+                .transaction()
+                .map_err(|e| format!("Failed to start session transaction: {}", e))?;
+
+            let open: Option<SessionSpan> = tx
+                .query_row(
+                    "SELECT id, process_key, start_ts, end_ts, first_screenshot_id,
+                            last_screenshot_id, screenshot_count
+                     FROM activity_sessions
+                     ORDER BY last_screenshot_id DESC LIMIT 1",
+                    [],
+                    |row| {
+                        Ok(SessionSpan {
+                            id: Some(row.get(0)?),
+                            process_key: row.get(1)?,
+                            start_ts: row.get(2)?,
+                            end_ts: row.get(3)?,
+                            first_screenshot_id: row.get(4)?,
+                            last_screenshot_id: row.get(5)?,
+                            screenshot_count: row.get(6)?,
+                        })
+                    },
+                )
+                .optional()
+                .map_err(|e| format!("Failed to load last session: {}", e))?;
+            let watermark = open.as_ref().map(|s| s.last_screenshot_id).unwrap_or(0);
+
+            let frames: Vec<(i64, Option<String>, i64)> = {
+                let mut stmt = tx
+                    .prepare(
+                        "SELECT id, COALESCE(process_name_hash, NULLIF(process_name, '')),
+                                CAST(strftime('%s', created_at) AS INTEGER)
+                         FROM screenshots
+                         WHERE id > ?1 AND is_deleted = 0
+                           AND (status IS NULL OR status = 'committed')
+                           AND id < COALESCE(
+                               (SELECT MIN(id) FROM screenshots
+                                WHERE id > ?1 AND is_deleted = 0 AND status = 'pending'),
+                               9223372036854775807)
+                         ORDER BY id
+                         LIMIT ?2",
+                    )
+                    .map_err(|e| format!("Failed to prepare session scan: {}", e))?;
+                let frames = stmt
+                    .query_map(params![watermark, SESSION_REFRESH_BATCH], |row| {
+                        Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+                    })
+                    .map_err(|e| format!("Failed to scan screenshots for sessions: {}", e))?
+                    .filter_map(|r| r.ok())
+                    .collect();
+                frames
+            };
+            if frames.is_empty() {
+                return Ok(segmented);
+            }
+
+            for span in segment_frames(open, &frames) {
+                match span.id {
+                    Some(id) => {
+                        tx.execute(
+                            "UPDATE activity_sessions
+                             SET start_ts = ?2, end_ts = ?3, last_screenshot_id = ?4,
+                                 screenshot_count = ?5
+                             WHERE id = ?1",
+                            params![
+                                id,
+                                span.start_ts,
+                                span.end_ts,
+                                span.last_screenshot_id,
+                                span.screenshot_count
+                            ],
+                        )
+                        .map_err(|e| format!("Failed to extend session: {}", e))?;
+                    }
+                    None => {
+                        tx.execute(
+                            "INSERT INTO activity_sessions (
+                                process_key, start_ts, end_ts, first_screenshot_id,
+                                last_screenshot_id, screenshot_count
+                             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                            params![
+                                span.process_key,
+                                span.start_ts,
+                                span.end_ts,
+                                span.first_screenshot_id,
+                                span.last_screenshot_id,
+                                span.screenshot_count
+                            ],
+                        )
+                        .map_err(|e| format!("Failed to insert session: {}", e))?;
+                    }
+                }
+            }
+            tx.commit()
+                .map_err(|e| format!("Failed to commit sessions: {}", e))?;
+            segmented += frames.len();
+            if (frames.len() as i64) < SESSION_REFRESH_BATCH {
+                return Ok(segmented);
+            }
+        }
+    }
+
+    /// Activity sessions overlapping `[start_ts, end_ts]` (Unix seconds), oldest first.
+    /// New screenshots are segmented first.
+    pub fn get_activity_sessions(
+        &self,
+        start_ts: f64,
+        end_ts: f64,
+    ) -> Result<Vec<ActivitySession>, String> {
+        if !self.is_read_only() {
+            self.refresh_activity_sessions()?;
+        }
+
+        let (mut sessions, process_keys): (Vec<ActivitySession>, Vec<Option<String>>) = {
+            let guard = self.get_connection_named("get_activity_sessions")?;
+            let conn = guard.as_ref().unwrap();
+            let mut stmt = conn
+                .prepare(
+                    "SELECT a.id, MAX(NULLIF(s.process_name, '')), COUNT(s.id),
+                            MIN(CAST(strftime('%s', s.created_at) AS INTEGER)),
+                            MAX(CAST(strftime('%s', s.created_at) AS INTEGER)),
+                            MIN(s.id), MAX(s.id), a.process_key
+                     FROM activity_sessions a
+                     JOIN screenshots s
+                       ON s.id BETWEEN a.first_screenshot_id AND a.last_screenshot_id
+                      AND s.is_deleted = 0
+                      AND (s.status IS NULL OR s.status = 'committed')
+                      AND COALESCE(s.process_name_hash, NULLIF(s.process_name, '')) IS a.process_key
+                     WHERE a.end_ts >= ?1 AND a.start_ts <= ?2
+                     GROUP BY a.id
+                     ORDER BY a.start_ts, a.id",
+                )
+                .map_err(|e| format!("Failed to prepare session query: {}", e))?;
+            let rows: Vec<(ActivitySession, Option<String>)> = stmt
+                .query_map(params![start_ts as i64, end_ts as i64], |row| {
+                    let start_time: i64 = row.get(3)?;
+                    let end_time: i64 = row.get(4)?;
+                    Ok((
+                        ActivitySession {
+                            id: row.get(0)?,
+                            process_name: row.get(1)?,
+                            screenshot_count: row.get(2)?,
+                            start_time,
+                            end_time,
+                            duration_secs: end_time - start_time,
+                            first_screenshot_id: row.get(5)?,
+                            last_screenshot_id: row.get(6)?,
+                        },
+                        row.get(7)?,
+                    ))
+                })
+                .map_err(|e| format!("Failed to query sessions: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows.into_iter().unzip()
+        };
+
+        if self.is_session_valid() {
+            let mut names: HashMap<String, Option<String>> = HashMap::new();
+            for (session, key) in sessions.iter_mut().zip(process_keys) {
+                let Some(key) = key else { continue };
+                if session.process_name.is_some() {
+                    continue;
+                }
+                let name = match names.get(&key) {
+                    Some(name) => name.clone(),
+                    None => {
+                        let name = self.decrypt_process_name_for_hash(&key)?;
+                        names.insert(key, name.clone());
+                        name
+                    }
+                };
+                session.process_name = name;
+            }
+        }
+        Ok(sessions)
+    }
+
+    /// Decrypts the process name of one screenshot carrying blind index `hash`.
+    fn decrypt_process_name_for_hash(&self, hash: &str) -> Result<Option<String>, String> {
+        let row: Option<(Vec<u8>, Vec<u8>)> = {
+            let guard = self.get_connection_named("decrypt_process_name_for_hash")?;
+            let conn = guard.as_ref().unwrap();
+            conn.query_row(
+                "SELECT process_name_enc, content_key_encrypted FROM screenshots
+                 WHERE process_name_hash = ?1
+                   AND process_name_enc IS NOT NULL AND content_key_encrypted IS NOT NULL
+                 LIMIT 1",
+                params![hash],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to query encrypted process name: {}", e))?
+        };
+        let Some((process_enc, key_enc)) = row else {
+            return Ok(None);
+        };
+        Ok(self
+            .decrypt_payload_with_row_key(&process_enc, &key_enc)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .filter(|name| !name.trim().is_empty()))
+    }
+
+    /// Drops the sessions that end at or after screenshot `first_id` so they are
+    /// segmented again, e.g. after those rows changed their process key.
+    pub(super) fn resegment_activity_sessions_from(
+        conn: &rusqlite::Connection,
+        first_id: i64,
+    ) -> Result<usize, String> {
+        conn.execute(
+            "DELETE FROM activity_sessions WHERE last_screenshot_id >= ?1",
+            params![first_id],
+        )
+        .map_err(|e| format!("Failed to reset activity sessions: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_storage;
+
+    #[test]
+    fn segmentation_splits_on_process_change_and_idle_gap() {
+        let code = Some("Code.exe".to_string());
+        let chrome = Some("chrome.exe".to_string());
+        let spans = segment_frames(
+            None,
+            &[
+                (1, code.clone(), 0),
+                (2, code.clone(), 60),
+                (3, chrome.clone(), 120),
+                (4, chrome.clone(), 120 + SESSION_IDLE_GAP_SECS + 1),
+            ],
+        );
+        assert_eq!(spans.len(), 3);
+        assert_eq!(
+            (spans[0].first_screenshot_id, spans[0].last_screenshot_id),
+            (1, 2)
+        );
+        assert_eq!(spans[0].end_ts, 60);
+        assert_eq!(spans[1].screenshot_count, 1);
+        assert_eq!(spans[2].first_screenshot_id, 4);
+
+        // A stored open session is extended in place.
+        let mut open = spans[2].clone();
+        open.id = Some(7);
+        let extended = segment_frames(Some(open), &[(5, chrome, 500)]);
+        assert_eq!(extended.len(), 1);
+        assert_eq!(extended[0].id, Some(7));
+        assert_eq!(extended[0].screenshot_count, 2);
+    }
+
+    #[test]
+    fn sessions_are_incremental_and_reflect_deletions() {
+        let (_temp, storage) = test_storage();
+        let guard = storage.db.lock().unwrap_or_else(|e| e.into_inner());
+        let connection = guard.as_ref().expect("test database");
+        connection
+            .execute_batch(
+                "INSERT INTO screenshots (id, image_path, image_hash, process_name, created_at) VALUES
+                    (1, 'a', 'h1', 'Code.exe', '2026-01-01 10:00:00'),
+                    (2, 'b', 'h2', 'Code.exe', '2026-01-01 10:01:00'),
+                    (3, 'c', 'h3', 'chrome.exe', '2026-01-01 10:02:00');",
+            )
+            .expect("session fixture");
+        drop(guard);
+
+        let day_start = 1_767_225_600.0; // 2026-01-01T00:00:00Z
+        let day_end = day_start + 86_400.0;
+        let sessions = storage.get_activity_sessions(day_start, day_end).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].process_name.as_deref(), Some("Code.exe"));
+        assert_eq!(sessions[0].duration_secs, 60);
+
+        {
+            let guard = storage.db.lock().unwrap();
+            let conn = guard.as_ref().unwrap();
+            conn.execute_batch(
+                "INSERT INTO screenshots (id, image_path, image_hash, process_name, created_at, status)
+                    VALUES (4, 'd', 'h4', 'chrome.exe', '2026-01-01 10:03:00', 'committed'),
+                           (5, 'e', 'h5', 'chrome.exe', '2026-01-01 10:04:00', 'pending'),
+                           (6, 'f', 'h6', 'chrome.exe', '2026-01-01 10:05:00', 'committed');
+                 UPDATE screenshots SET is_deleted = 1 WHERE id = 1;",
+            )
+            .unwrap();
+        }
+        assert_eq!(storage.refresh_activity_sessions().unwrap(), 1);
+        let sessions = storage.get_activity_sessions(day_start, day_end).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].screenshot_count, 1);
+        assert_eq!(sessions[0].duration_secs, 0);
+        assert_eq!(sessions[1].screenshot_count, 2);
+        assert_eq!(sessions[1].last_screenshot_id, 4);
+
+        // Once the pending capture commits, segmentation resumes at it.
+        {
+            let guard = storage.db.lock().unwrap();
+            let conn = guard.as_ref().unwrap();
+            conn.execute(
+                "UPDATE screenshots SET status = 'committed' WHERE id = 5",
+                [],
+            )
+            .unwrap();
+        }
+        assert_eq!(storage.refresh_activity_sessions().unwrap(), 2);
+        let sessions = storage.get_activity_sessions(day_start, day_end).unwrap();
+        assert_eq!(sessions[1].screenshot_count, 4);
+    }
+}
@@ -0,0 +1,242 @@
+//! User-defined tags ("receipt", "meeting", ...) attached to screenshots.
+//!
+//! Tags are short labels chosen by the user and, like categories, are stored in
+//! plaintext inside the encrypted database so they can filter the timeline and
+//! search results without decrypting every row. Matching is case-insensitive.
+
+use std::collections::{HashMap, HashSet};
+
+use rusqlite::{params, Connection};
+
+use super::{ScreenshotRecord, StorageState, TagCount};
+
+/// Longest accepted tag, in characters.
+const MAX_TAG_CHARS: usize = 64;
+
+/// Trim `tag` and collapse inner whitespace; rejects empty, overlong or control
+/// characters.
+pub(super) fn normalize_tag(tag: &str) -> Result<String, String> {
+    let normalized = tag.split_whitespace().collect::<Vec<_>>().join(" ");
+    if normalized.is_empty() {
+        return Err("Tag must not be empty".to_string());
+    }
+    if normalized.chars().count() > MAX_TAG_CHARS {
+        return Err(format!("Tag is longer than {} characters", MAX_TAG_CHARS));
+    }
+    if normalized.chars().any(char::is_control) {
+        return Err("Tag must not contain control characters".to_string());
+    }
+    Ok(normalized)
+}
+
+impl StorageState {
+    /// Attach `tag` to a screenshot. Returns `false` when it was already tagged.
+    pub fn add_tag(&self, screenshot_id: i64, tag: &str) -> Result<bool, String> {
+        let tag = normalize_tag(tag)?;
+        let guard = self.get_connection_named("add_tag")?;
+        let conn = guard.as_ref().unwrap();
+        let inserted = conn
+            .execute(
+                "INSERT OR IGNORE INTO tags (screenshot_id, tag)
+                 SELECT id, ?2 FROM screenshots WHERE id = ?1 AND is_deleted = 0",
+                params![screenshot_id, tag],
+            )
+            .map_err(|e| format!("Failed to add tag: {}", e))?;
+        if inserted == 0 {
+            let exists: bool = conn
+                .query_row(
+                    "SELECT EXISTS(SELECT 1 FROM screenshots WHERE id = ?1 AND is_deleted = 0)",
+                    params![screenshot_id],
+                    |row| row.get(0),
+                )
+                .map_err(|e| format!("Failed to query screenshot: {}", e))?;
+            if !exists {
+                return Err(format!("Screenshot {} not found", screenshot_id));
+            }
+        }
+        Ok(inserted > 0)
+    }
+
+    /// Detach `tag` from a screenshot. Returns `false` when it was not tagged.
+    pub fn remove_tag(&self, screenshot_id: i64, tag: &str) -> Result<bool, String> {
+        let tag = normalize_tag(tag)?;
+        let guard = self.get_connection_named("remove_tag")?;
+        let conn = guard.as_ref().unwrap();
+        let removed = conn
+            .execute(
+                "DELETE FROM tags WHERE screenshot_id = ?1 AND tag = ?2",
+                params![screenshot_id, tag],
+            )
+            .map_err(|e| format!("Failed to remove tag: {}", e))?;
+        Ok(removed > 0)
+    }
+
+    /// Tags with the number of live screenshots carrying them, most used first.
+    /// With `screenshot_id`, only that screenshot's tags are listed.
+    pub fn list_tags(&self, screenshot_id: Option<i64>) -> Result<Vec<TagCount>, String> {
+        let guard = self.get_connection_named("list_tags")?;
+        let conn = guard.as_ref().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT t.tag, COUNT(*) FROM tags t
+                 JOIN screenshots s ON s.id = t.screenshot_id
+                 WHERE s.is_deleted = 0 AND (?1 IS NULL OR t.screenshot_id = ?1)
+                 GROUP BY t.tag
+                 ORDER BY COUNT(*) DESC, t.tag",
+            )
+            .map_err(|e| format!("Failed to prepare tag query: {}", e))?;
+        let tags = stmt
+            .query_map(params![screenshot_id], |row| {
+                Ok(TagCount {
+                    tag: row.get(0)?,
+                    screenshot_count: row.get(1)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query tags: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(tags)
+    }
+
+    /// Screenshots carrying `tag`, newest first.
+    pub fn search_by_tag(
+        &self,
+        tag: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<ScreenshotRecord>, String> {
+        let tag = normalize_tag(tag)?;
+        let ids: Vec<i64> = {
+            let guard = self.get_connection_named("search_by_tag")?;
+            let conn = guard.as_ref().unwrap();
+            let mut stmt = conn
+                .prepare(
+                    "SELECT s.id FROM tags t
+                     JOIN screenshots s ON s.id = t.screenshot_id
+                     WHERE t.tag = ?1 AND s.is_deleted = 0
+                     ORDER BY s.created_at DESC, s.id DESC
+                     LIMIT ?2 OFFSET ?3",
+                )
+                .map_err(|e| format!("Failed to prepare tag search: {}", e))?;
+            let ids = stmt
+                .query_map(params![tag, limit.clamp(1, 500), offset.max(0)], |row| {
+                    row.get(0)
+                })
+                .map_err(|e| format!("Failed to search by tag: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            ids
+        };
+
+        let order: HashMap<i64, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+        let mut records = self.get_screenshots_by_ids(&ids)?;
+        records.sort_by_key(|record| order.get(&record.id).copied().unwrap_or(usize::MAX));
+        Ok(records)
+    }
+
+    /// Live screenshot IDs carrying any of `tags`, for pre-filtering search candidates.
+    pub(super) fn screenshot_ids_with_tags(
+        conn: &Connection,
+        tags: &[String],
+    ) -> Result<HashSet<i64>, String> {
+        let placeholders = tags.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let sql = format!(
+            "SELECT DISTINCT t.screenshot_id FROM tags t
+             JOIN screenshots s ON s.id = t.screenshot_id
+             WHERE s.is_deleted = 0 AND t.tag IN ({})",
+            placeholders
+        );
+        let tag_params: Vec<&dyn rusqlite::ToSql> =
+            tags.iter().map(|t| t as &dyn rusqlite::ToSql).collect();
+        let mut stmt = conn
+            .prepare(&sql)
+            .map_err(|e| format!("Failed to query tag filter: {}", e))?;
+        let ids = stmt
+            .query_map(tag_params.as_slice(), |row| row.get::<_, i64>(0))
+            .map_err(|e| format!("Failed to fetch tag ids: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(ids)
+    }
+
+    /// Tag or untag `normalized_ids` inside the caller's transaction; returns the
+    /// number of screenshots changed.
+    pub(super) fn set_tag_in_tx(
+        tx: &rusqlite::Transaction<'_>,
+        normalized_ids: &[i64],
+        tag: &str,
+        tagged: bool,
+    ) -> Result<i64, String> {
+        let tag = normalize_tag(tag)?;
+        let mut changed = 0i64;
+        for chunk in normalized_ids.chunks(500) {
+            let placeholders = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+            let sql = if tagged {
+                format!(
+                    "INSERT OR IGNORE INTO tags (screenshot_id, tag)
+                     SELECT id, ? FROM screenshots WHERE is_deleted = 0 AND id IN ({})",
+                    placeholders
+                )
+            } else {
+                format!(
+                    "DELETE FROM tags WHERE tag = ? AND screenshot_id IN ({})",
+                    placeholders
+                )
+            };
+            let mut params_ref: Vec<&dyn rusqlite::ToSql> = vec![&tag];
+            params_ref.extend(chunk.iter().map(|id| id as &dyn rusqlite::ToSql));
+            changed +=
+                tx.execute(&sql, params_ref.as_slice())
+                    .map_err(|e| format!("Failed to update tags: {}", e))? as i64;
+        }
+        Ok(changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_storage;
+
+    #[test]
+    fn normalize_tag_trims_and_rejects_empty() {
+        assert_eq!(normalize_tag("  team   meeting ").unwrap(), "team meeting");
+        assert!(normalize_tag("   ").is_err());
+        assert!(normalize_tag(&"x".repeat(MAX_TAG_CHARS + 1)).is_err());
+    }
+
+    #[test]
+    fn tags_are_case_insensitive_and_skip_deleted_screenshots() {
+        let (_temp, storage) = test_storage();
+        let guard = storage.db.lock().unwrap_or_else(|e| e.into_inner());
+        let connection = guard.as_ref().expect("test database");
+        connection
+            .execute_batch(
+                "INSERT INTO screenshots (id, image_path, image_hash, is_deleted) VALUES
+                    (1, 'a', 'h1', 0), (2, 'b', 'h2', 0), (3, 'c', 'h3', 1);",
+            )
+            .expect("tag fixture");
+        drop(guard);
+
+        assert!(storage.add_tag(1, "Receipt").unwrap());
+        assert!(!storage.add_tag(1, "receipt").unwrap());
+        assert!(storage.add_tag(2, "receipt").unwrap());
+        assert!(storage.add_tag(3, "receipt").is_err());
+
+        let tags = storage.list_tags(None).unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].screenshot_count, 2);
+
+        {
+            let guard = storage.db.lock().unwrap();
+            let conn = guard.as_ref().unwrap();
+            let ids =
+                StorageState::screenshot_ids_with_tags(conn, &["RECEIPT".to_string()]).unwrap();
+            assert_eq!(ids, HashSet::from([1, 2]));
+        }
+
+        assert!(storage.remove_tag(1, "RECEIPT").unwrap());
+        assert!(!storage.remove_tag(1, "receipt").unwrap());
+        assert_eq!(storage.list_tags(Some(1)).unwrap().len(), 0);
+    }
+}
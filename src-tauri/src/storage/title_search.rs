@@ -0,0 +1,133 @@
+//! Window-title lookup.
+//!
+//! Candidates come from the `title:` postings of `metadata_index`. The newest
+//! `TITLE_SEARCH_SCAN_LIMIT` are decrypted, checked to really contain the query (the
+//! bigrams may occur apart) and grouped by title, since one window usually spans many
+//! screenshots.
+
+use std::collections::HashMap;
+
+use super::search::normalize_phrase;
+use super::{StorageState, TitleSearchResult};
+
+/// Matching screenshots decrypted per lookup, newest first.
+const TITLE_SEARCH_SCAN_LIMIT: usize = 2_000;
+
+/// A matching screenshot: id, window title, process name, created_at.
+type TitleRow = (i64, String, Option<String>, String);
+
+impl StorageState {
+    /// Distinct window titles containing `query`, ignoring case and spacing, most
+    /// recently seen first. Queries too short for a bigram match nothing.
+    pub fn search_window_titles(
+        &self,
+        query: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<TitleSearchResult>, String> {
+        let needle = normalize_phrase(query);
+        if needle.is_empty() {
+            return Ok(Vec::new());
+        }
+        let hmac_key = self.credential_state.get_hmac_key()?;
+        let candidates = {
+            let conn = self.open_read_connection_named("search_window_titles")?;
+            Self::title_screenshot_ids(&conn, &hmac_key, &[needle.clone()])?
+        };
+        let Some(candidates) = candidates else {
+            return Ok(Vec::new());
+        };
+        let mut ids: Vec<i64> = candidates.into_iter().collect();
+        ids.sort_unstable_by(|a, b| b.cmp(a));
+        ids.truncate(TITLE_SEARCH_SCAN_LIMIT);
+
+        let mut rows: Vec<TitleRow> = Vec::with_capacity(ids.len());
+        for chunk in ids.chunks(500) {
+            rows.extend(
+                self.get_screenshots_by_ids(chunk)?
+                    .into_iter()
+                    .filter_map(|record| {
+                        let title = record.window_title?;
+                        Some((record.id, title, record.process_name, record.created_at))
+                    }),
+            );
+        }
+        Ok(group_title_matches(rows, &needle)
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .collect())
+    }
+}
+
+/// Groups screenshots whose title contains the normalized `needle` by title and
+/// process, most recently seen first.
+fn group_title_matches(rows: Vec<TitleRow>, needle: &str) -> Vec<TitleSearchResult> {
+    let mut groups: HashMap<(String, Option<String>), TitleSearchResult> = HashMap::new();
+    for (id, title, process_name, created_at) in rows {
+        if !normalize_phrase(&title).contains(needle) {
+            continue;
+        }
+        let group = groups
+            .entry((title.clone(), process_name.clone()))
+            .or_insert_with(|| TitleSearchResult {
+                window_title: title,
+                process_name,
+                screenshot_count: 0,
+                first_seen: created_at.clone(),
+                last_seen: created_at.clone(),
+                latest_screenshot_id: id,
+            });
+        group.screenshot_count += 1;
+        if created_at < group.first_seen {
+            group.first_seen = created_at.clone();
+        }
+        if (&created_at, id) > (&group.last_seen, group.latest_screenshot_id) {
+            group.last_seen = created_at;
+            group.latest_screenshot_id = id;
+        }
+    }
+    let mut results: Vec<TitleSearchResult> = groups.into_values().collect();
+    results.sort_by(|a, b| {
+        b.last_seen
+            .cmp(&a.last_seen)
+            .then(b.latest_screenshot_id.cmp(&a.latest_screenshot_id))
+    });
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(id: i64, title: &str, process: &str, created_at: &str) -> TitleRow {
+        (
+            id,
+            title.to_string(),
+            Some(process.to_string()),
+            created_at.to_string(),
+        )
+    }
+
+    #[test]
+    fn groups_titles_and_drops_scattered_bigram_hits() {
+        let rows = vec![
+            row(1, "Q3 Report.xlsx", "excel.exe", "2026-01-01 09:00:00"),
+            row(5, "Q3 Report.xlsx", "excel.exe", "2026-01-02 10:00:00"),
+            row(
+                3,
+                "Q3  report - draft",
+                "winword.exe",
+                "2026-01-01 12:00:00",
+            ),
+            row(4, "Report for Q3", "winword.exe", "2026-01-03 08:00:00"),
+        ];
+        let results = group_title_matches(rows, "q3 report");
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].window_title, "Q3 Report.xlsx");
+        assert_eq!(results[0].screenshot_count, 2);
+        assert_eq!(results[0].first_seen, "2026-01-01 09:00:00");
+        assert_eq!(results[0].latest_screenshot_id, 5);
+        assert_eq!(results[1].window_title, "Q3  report - draft");
+    }
+}
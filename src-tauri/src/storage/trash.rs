@@ -0,0 +1,383 @@
+//! Trash for deleted screenshots.
+//!
+//! A trashed screenshot is marked `is_deleted` like a soft-deleted one, so every
+//! query already hides it, and `deleted_at` records when it was trashed. Unlike a
+//! soft delete it is not queued for physical cleanup: the image file, OCR rows and
+//! search postings stay until the screenshot is restored or purged. Purging queues
+//! it on the regular delete queues and clears `deleted_at`.
+//!
+//! Trashed screenshots are kept for `trash_retention_days` from the storage policy
+//! (default [`DEFAULT_TRASH_RETENTION_DAYS`]); the storage policy job purges older
+//! ones. `0` turns the trash off and deletes immediately.
+
+use chrono::{Duration, Utc};
+use rusqlite::params;
+use serde_json::Value as JsonValue;
+use std::sync::atomic::Ordering;
+
+use super::{StorageState, TrashEntry};
+
+pub(super) const DEFAULT_TRASH_RETENTION_DAYS: u32 = 30;
+const MAX_TRASH_RETENTION_DAYS: u32 = 365;
+
+/// Days trashed screenshots are kept, from the storage policy.
+pub(super) fn parse_trash_retention_days(policy: &JsonValue) -> u32 {
+    policy
+        .get("trash_retention_days")
+        .and_then(|v| v.as_u64())
+        .map(|days| days.min(MAX_TRASH_RETENTION_DAYS as u64) as u32)
+        .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS)
+}
+
+impl StorageState {
+    /// Days trashed screenshots are kept; `0` when the trash is turned off.
+    pub fn trash_retention_days(&self) -> u32 {
+        self.load_policy()
+            .map(|policy| parse_trash_retention_days(&policy))
+            .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS)
+    }
+
+    /// Move a live screenshot to the trash. Returns `false` when it was not found.
+    pub fn move_screenshot_to_trash(&self, screenshot_id: i64) -> Result<bool, String> {
+        let ocr_marked = {
+            let mut guard = self.get_connection_named("move_screenshot_to_trash")?;
+            let conn = guard.as_mut().unwrap();
+            let tx = conn
+                .transaction()
+                .map_err(|e| format!("Failed to start trash transaction: {}", e))?;
+            let trashed = tx
+                .execute(
+                    "UPDATE screenshots SET is_deleted = 1, deleted_at = CURRENT_TIMESTAMP
+                     WHERE id = ?1 AND is_deleted = 0",
+                    params![screenshot_id],
+                )
+                .map_err(|e| format!("Failed to move screenshot to trash: {}", e))?;
+            if trashed == 0 {
+                return Ok(false);
+            }
+            let ocr_marked = tx
+                .execute(
+                    "UPDATE ocr_results SET is_deleted = 1
+                     WHERE screenshot_id = ?1 AND is_deleted = 0",
+                    params![screenshot_id],
+                )
+                .map_err(|e| format!("Failed to hide OCR rows: {}", e))?;
+            tx.commit()
+                .map_err(|e| format!("Failed to commit trash transaction: {}", e))?;
+            ocr_marked
+        };
+        self.release_ocr_row_count(ocr_marked as i64);
+        Ok(true)
+    }
+
+    /// Bring a trashed screenshot back. Returns `false` when it is not in the trash.
+    ///
+    /// Its vector embedding was removed when it was trashed, so OCR postprocessing
+    /// is queued again to rebuild it.
+    pub fn restore_screenshot(&self, screenshot_id: i64) -> Result<bool, String> {
+        let ocr_restored = {
+            let mut guard = self.get_connection_named("restore_screenshot")?;
+            let conn = guard.as_mut().unwrap();
+            let tx = conn
+                .transaction()
+                .map_err(|e| format!("Failed to start restore transaction: {}", e))?;
+            let restored = tx
+                .execute(
+                    "UPDATE screenshots SET is_deleted = 0, deleted_at = NULL
+                     WHERE id = ?1 AND deleted_at IS NOT NULL
+                       AND id NOT IN (SELECT id FROM delete_queue_screenshots)",
+                    params![screenshot_id],
+                )
+                .map_err(|e| format!("Failed to restore screenshot: {}", e))?;
+            if restored == 0 {
+                return Ok(false);
+            }
+            let ocr_restored = tx
+                .execute(
+                    "UPDATE ocr_results SET is_deleted = 0
+                     WHERE screenshot_id = ?1 AND is_deleted = 1
+                       AND id NOT IN (SELECT id FROM delete_queue_ocr)",
+                    params![screenshot_id],
+                )
+                .map_err(|e| format!("Failed to restore OCR rows: {}", e))?;
+            tx.commit()
+                .map_err(|e| format!("Failed to commit restore transaction: {}", e))?;
+            ocr_restored
+        };
+        self.ocr_row_count
+            .fetch_add(ocr_restored as u64, Ordering::Relaxed);
+        if let Err(error) = self.set_ocr_postprocess_status(screenshot_id, "pending", None) {
+            tracing::warn!(
+                "Restored screenshot {} but could not queue postprocessing: {}",
+                screenshot_id,
+                error
+            );
+        }
+        Ok(true)
+    }
+
+    /// Trashed screenshots, most recently trashed first.
+    pub fn list_trash(&self, limit: i64, offset: i64) -> Result<Vec<TrashEntry>, String> {
+        let retention_days = self.trash_retention_days();
+        let conn = self.open_read_connection_named("list_trash")?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, created_at, deleted_at, process_name,
+                        datetime(deleted_at, '+' || ?3 || ' days')
+                 FROM screenshots
+                 WHERE deleted_at IS NOT NULL
+                 ORDER BY deleted_at DESC, id DESC
+                 LIMIT ?1 OFFSET ?2",
+            )
+            .map_err(|e| format!("Failed to prepare trash query: {}", e))?;
+        let entries = stmt
+            .query_map(params![limit, offset, retention_days], |row| {
+                Ok(TrashEntry {
+                    screenshot_id: row.get(0)?,
+                    created_at: row.get(1)?,
+                    deleted_at: row.get(2)?,
+                    process_name: row.get(3)?,
+                    purge_after: row.get(4)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query trash: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(entries)
+    }
+
+    /// Queue every trashed screenshot for permanent deletion; returns how many.
+    pub fn empty_trash(&self) -> Result<usize, String> {
+        self.purge_trash_before(None)
+    }
+
+    /// Queue screenshots trashed longer than the retention period for permanent
+    /// deletion; returns how many.
+    pub fn purge_expired_trash(&self) -> Result<usize, String> {
+        let cutoff = Utc::now() - Duration::days(self.trash_retention_days() as i64);
+        self.purge_trash_before(Some(cutoff.format("%Y-%m-%d %H:%M:%S").to_string()))
+    }
+
+    /// Move trashed screenshots (all, or those trashed before `cutoff`) onto the
+    /// delete queues.
+    fn purge_trash_before(&self, cutoff: Option<String>) -> Result<usize, String> {
+        const TRASHED: &str = "SELECT id FROM screenshots
+             WHERE deleted_at IS NOT NULL AND (?1 IS NULL OR deleted_at < ?1)";
+        let mut guard = self.get_connection_named("purge_trash")?;
+        let conn = guard.as_mut().unwrap();
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start trash purge transaction: {}", e))?;
+        tx.execute(
+            &format!(
+                "INSERT OR IGNORE INTO delete_queue_ocr (id)
+                 SELECT id FROM ocr_results WHERE screenshot_id IN ({})",
+                TRASHED
+            ),
+            params![cutoff],
+        )
+        .map_err(|e| format!("Failed to queue trashed OCR rows: {}", e))?;
+        tx.execute(
+            &format!(
+                "INSERT OR IGNORE INTO delete_queue_screenshots (id) {}",
+                TRASHED
+            ),
+            params![cutoff],
+        )
+        .map_err(|e| format!("Failed to queue trashed screenshots: {}", e))?;
+        let purged = tx
+            .execute(
+                &format!(
+                    "UPDATE screenshots SET deleted_at = NULL WHERE id IN ({})",
+                    TRASHED
+                ),
+                params![cutoff],
+            )
+            .map_err(|e| format!("Failed to clear trash state: {}", e))?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit trash purge: {}", e))?;
+        Ok(purged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_storage;
+    use rusqlite::Connection;
+    use serde_json::json;
+
+    fn ids(conn: &Connection, sql: &str) -> Vec<i64> {
+        let mut stmt = conn.prepare(sql).unwrap();
+        let rows = stmt
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        rows
+    }
+
+    #[test]
+    fn trash_retention_defaults_and_caps() {
+        assert_eq!(
+            parse_trash_retention_days(&json!({})),
+            DEFAULT_TRASH_RETENTION_DAYS
+        );
+        assert_eq!(
+            parse_trash_retention_days(&json!({ "trash_retention_days": 0 })),
+            0
+        );
+        assert_eq!(
+            parse_trash_retention_days(&json!({ "trash_retention_days": 7 })),
+            7
+        );
+        assert_eq!(
+            parse_trash_retention_days(&json!({ "trash_retention_days": 10_000 })),
+            MAX_TRASH_RETENTION_DAYS
+        );
+        assert_eq!(
+            parse_trash_retention_days(&json!({ "trash_retention_days": "7" })),
+            DEFAULT_TRASH_RETENTION_DAYS
+        );
+    }
+
+    #[test]
+    fn trashed_screenshots_are_hidden_and_restored_without_queued_rows() {
+        let (_temp, storage) = test_storage();
+        {
+            let guard = storage.db.lock().unwrap();
+            guard
+                .as_ref()
+                .unwrap()
+                .execute_batch(
+                    "INSERT INTO screenshots (id, image_path, image_hash) VALUES
+                        (1, 'a', 'h1'), (2, 'b', 'h2');
+                     INSERT INTO ocr_results (id, screenshot_id, text_hash) VALUES
+                        (1, 1, 'x'), (2, 1, 'y'), (3, 2, 'z');",
+                )
+                .expect("trash fixture");
+        }
+
+        assert!(storage.move_screenshot_to_trash(1).unwrap());
+        assert!(!storage.move_screenshot_to_trash(1).unwrap());
+        {
+            let guard = storage.db.lock().unwrap();
+            let conn = guard.as_ref().unwrap();
+            assert_eq!(
+                ids(conn, "SELECT id FROM screenshots WHERE is_deleted = 0"),
+                vec![2]
+            );
+            assert_eq!(
+                ids(
+                    conn,
+                    "SELECT id FROM screenshots WHERE deleted_at IS NOT NULL"
+                ),
+                vec![1]
+            );
+            assert_eq!(
+                ids(
+                    conn,
+                    "SELECT id FROM ocr_results WHERE is_deleted = 1 ORDER BY id"
+                ),
+                vec![1, 2]
+            );
+            // Nothing is queued for cleanup while the screenshot sits in the trash.
+            assert!(ids(conn, "SELECT id FROM delete_queue_ocr").is_empty());
+            // An OCR row queued on its own stays deleted after the restore.
+            conn.execute("INSERT INTO delete_queue_ocr (id) VALUES (2)", [])
+                .unwrap();
+        }
+
+        assert!(storage.restore_screenshot(1).unwrap());
+        assert!(!storage.restore_screenshot(1).unwrap());
+        assert!(!storage.restore_screenshot(2).unwrap());
+        let guard = storage.db.lock().unwrap();
+        let conn = guard.as_ref().unwrap();
+        assert_eq!(
+            ids(
+                conn,
+                "SELECT id FROM screenshots WHERE is_deleted = 0 AND deleted_at IS NULL ORDER BY id"
+            ),
+            vec![1, 2]
+        );
+        assert_eq!(
+            ids(
+                conn,
+                "SELECT id FROM ocr_results WHERE is_deleted = 0 ORDER BY id"
+            ),
+            vec![1, 3]
+        );
+    }
+
+    #[test]
+    fn purging_queues_trashed_rows_and_clears_the_trash_state() {
+        let (_temp, storage) = test_storage();
+        {
+            let guard = storage.db.lock().unwrap();
+            guard
+                .as_ref()
+                .unwrap()
+                .execute_batch(
+                    "INSERT INTO screenshots (id, image_path, image_hash, is_deleted, deleted_at) VALUES
+                        (1, 'a', 'h1', 1, '2020-01-01 00:00:00'),
+                        (2, 'b', 'h2', 1, '2030-01-01 00:00:00'),
+                        (3, 'c', 'h3', 0, NULL);
+                     INSERT INTO ocr_results (id, screenshot_id, text_hash, is_deleted) VALUES
+                        (1, 1, 'x', 1), (2, 1, 'y', 1), (3, 2, 'z', 1), (4, 3, 'w', 0);",
+                )
+                .expect("purge fixture");
+        }
+
+        assert_eq!(
+            storage
+                .purge_trash_before(Some("2021-01-01 00:00:00".to_string()))
+                .unwrap(),
+            1
+        );
+        {
+            let guard = storage.db.lock().unwrap();
+            let conn = guard.as_ref().unwrap();
+            assert_eq!(
+                ids(conn, "SELECT id FROM delete_queue_screenshots"),
+                vec![1]
+            );
+            assert_eq!(
+                ids(conn, "SELECT id FROM delete_queue_ocr ORDER BY id"),
+                vec![1, 2]
+            );
+            assert_eq!(
+                ids(
+                    conn,
+                    "SELECT id FROM screenshots WHERE deleted_at IS NOT NULL"
+                ),
+                vec![2]
+            );
+        }
+        // A purged screenshot has left the trash and cannot be restored.
+        assert!(!storage.restore_screenshot(1).unwrap());
+
+        assert_eq!(storage.empty_trash().unwrap(), 1);
+        let guard = storage.db.lock().unwrap();
+        let conn = guard.as_ref().unwrap();
+        assert_eq!(
+            ids(conn, "SELECT id FROM delete_queue_screenshots ORDER BY id"),
+            vec![1, 2]
+        );
+        assert_eq!(
+            ids(conn, "SELECT id FROM delete_queue_ocr ORDER BY id"),
+            vec![1, 2, 3]
+        );
+        assert!(ids(
+            conn,
+            "SELECT id FROM screenshots WHERE deleted_at IS NOT NULL"
+        )
+        .is_empty());
+        assert_eq!(
+            ids(
+                conn,
+                "SELECT id FROM screenshots WHERE is_deleted = 1 ORDER BY id"
+            ),
+            vec![1, 2]
+        );
+    }
+}
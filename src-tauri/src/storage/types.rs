@@ -88,6 +88,163 @@ pub struct SearchResult {
     pub category: Option<String>,
     pub created_at: String,
     pub screenshot_created_at: String,
+    /// Inline thumbnail, populated only when the caller asks for thumbnails.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<InlineThumbnail>,
+}
+
+/// Result order for text search.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchSortBy {
+    /// Newest first (by bigram coverage first in fuzzy mode).
+    #[default]
+    Recent,
+    /// BM25 score first, newest first among equal scores.
+    Relevance,
+}
+
+/// How text search interprets its query.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchMatchMode {
+    /// Boolean query syntax; each term matches on its bigrams.
+    #[default]
+    Terms,
+    /// The whole query as a literal phrase, verified against the decrypted text.
+    Phrase,
+    /// The whole query as a regular expression, verified against the decrypted text.
+    Regex,
+}
+
+/// File format of a search export.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchExportFormat {
+    /// UTF-8 CSV with a byte-order mark so spreadsheet apps detect the encoding.
+    #[default]
+    Csv,
+    /// A JSON array of objects.
+    Json,
+}
+
+/// Outcome of `storage_export_search`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchExportSummary {
+    pub path: String,
+    pub format: SearchExportFormat,
+    /// OCR text boxes written
+    pub rows: usize,
+    /// Distinct screenshots among them
+    pub screenshots: usize,
+    /// `true` when `max_rows` cut the export short
+    pub truncated: bool,
+}
+
+/// Outcome of `storage_export_pdf`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PdfExportSummary {
+    pub path: String,
+    pub pages: usize,
+    /// OCR boxes written as selectable text
+    pub text_boxes: usize,
+    /// Screenshots whose image could not be read or decoded
+    pub skipped: usize,
+}
+
+/// Outcome of writing a Markdown daily note.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyNoteSummary {
+    pub path: String,
+    /// Local date, `YYYY-MM-DD`
+    pub date: String,
+    pub sessions: usize,
+    pub highlights: usize,
+    /// Images exported next to the note
+    pub images: usize,
+}
+
+/// Match totals for a text search, returned by `storage_search_count`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchCount {
+    /// Results `storage_search` would page through: text boxes for a single-clause
+    /// query, screenshots when several clauses must match.
+    pub total: i64,
+    /// Per process, most results first; ignores the process filter.
+    pub by_process: Vec<SearchFacet>,
+    /// Per capture-local day (`YYYY-MM-DD`), ascending.
+    pub by_day: Vec<SearchFacet>,
+    /// Detached months (`YYYY-MM`) in the searched range; their screenshots are not
+    /// searched until the month is attached again.
+    #[serde(default)]
+    pub offline_months: Vec<String>,
+}
+
+/// One facet value with its result count.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SearchFacet {
+    pub value: String,
+    pub count: i64,
+}
+
+/// A decrypted saved search, returned by `storage_list_saved_searches`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub id: i64,
+    pub name: String,
+    pub query: String,
+    pub match_mode: SearchMatchMode,
+    /// Alert when a newly committed screenshot matches.
+    pub notify: bool,
+    pub created_at: String,
+    /// Last commit that matched; only tracked while `notify` is set.
+    pub last_matched_at: Option<String>,
+    pub last_matched_screenshot_id: Option<i64>,
+}
+
+/// A recorded search query, returned by `storage_get_search_history`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHistoryEntry {
+    pub id: i64,
+    pub query: String,
+    pub use_count: i64,
+    pub last_used_at: String,
+}
+
+/// A distinct window title returned by `storage_search_titles`, with the screenshots
+/// captured under it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TitleSearchResult {
+    pub window_title: String,
+    pub process_name: Option<String>,
+    pub screenshot_count: i64,
+    pub first_seen: String,
+    pub last_seen: String,
+    pub latest_screenshot_id: i64,
+}
+
+/// Payload of the `saved-search-matched` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSearchMatch {
+    pub saved_search_id: i64,
+    pub name: String,
+    pub screenshot_id: i64,
+}
+
+/// A Base64 thumbnail returned inline with another payload.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InlineThumbnail {
+    pub data: String,
+    pub mime_type: String,
+}
+
+/// Decrypted OCR text of one screenshot, joined in reading order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OcrTextEntry {
+    pub screenshot_id: i64,
+    pub text: String,
+    /// Whether `text` was cut at the requested character limit.
+    pub truncated: bool,
 }
 
 /// The input for saving a screenshot, containing all necessary data and metadata.
@@ -123,6 +280,155 @@ pub struct OcrResultInput {
     pub box_coords: Vec<Vec<f64>>,
 }
 
+/// Automatic cleanup limits applied by the `storage_policy` scheduled job.
+/// `None` disables the corresponding limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// Screenshots older than this many days are deleted.
+    pub max_age_days: Option<u32>,
+    /// Oldest screenshots are deleted while screenshot storage exceeds this size.
+    pub max_total_gb: Option<u64>,
+}
+
+/// Data-at-rest compliance state returned by `storage_get_compliance_status`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceStatus {
+    pub enabled: bool,
+    /// RFC 3339 timestamp of when the mode was last enabled.
+    pub enabled_at: Option<String>,
+    /// Screenshot rows that still carry a plaintext `process_name`.
+    pub plaintext_process_rows: i64,
+    pub retention_days: Option<u32>,
+    pub session_timeout_secs: i64,
+    pub audit_log_entries: i64,
+}
+
+/// Paranoid mode state returned by `storage_get_paranoid_mode`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParanoidModeStatus {
+    pub enabled: bool,
+    /// Compliance mode is on, which implies paranoid mode's storage rules.
+    pub compliance_mode: bool,
+    /// Screenshot rows that still carry a plaintext `process_name`.
+    pub plaintext_process_rows: i64,
+    /// Rows with a process name but no `process_name_hash` yet.
+    pub unhashed_process_rows: i64,
+}
+
+/// One entry of the access audit log returned by `storage_get_audit_log`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessAuditEntry {
+    pub id: i64,
+    pub created_at: String,
+    /// `app` for the desktop UI, `mcp` for MCP clients.
+    pub actor: String,
+    pub action: String,
+    /// What was read, e.g. `{ "screenshot_id": 1 }`; `None` when it cannot be decrypted.
+    pub target: Option<serde_json::Value>,
+}
+
+/// Screenshot storage quota returned by `storage_get_quota` / `storage_set_quota`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageQuota {
+    /// Quota on the screenshots directory; `None` means unlimited.
+    pub quota_gb: Option<u64>,
+    /// Current size of the screenshots directory.
+    pub usage_bytes: u64,
+}
+
+/// Payload of the `storage-quota-evicted` event emitted when the quota forced the
+/// oldest committed screenshots into the delete queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaEviction {
+    pub evicted: i64,
+    pub estimated_freed_bytes: u64,
+    pub usage_bytes: u64,
+    pub quota_bytes: u64,
+}
+
+/// Outcome of one storage policy pass.
+#[derive(Debug, Clone, Default)]
+pub struct StoragePolicyRun {
+    /// Human-readable summary when anything was queued for deletion.
+    pub summary: Option<String>,
+    pub quota_eviction: Option<QuotaEviction>,
+}
+
+/// Result of exporting a time range to a password-protected portable archive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RangeArchiveSummary {
+    pub path: String,
+    pub screenshots: usize,
+    pub images: usize,
+    pub ocr_results: usize,
+    /// Screenshots whose image file could not be read; their metadata is still exported.
+    pub missing_images: usize,
+    pub archive_bytes: u64,
+}
+
+/// Result of importing a portable range archive back into the timeline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RangeArchiveImportSummary {
+    pub imported: usize,
+    /// Screenshots skipped because the same image hash already exists locally.
+    pub duplicates: usize,
+    /// Archived records without a readable image entry; they are not imported.
+    pub missing_images: usize,
+    /// Archived records whose image does not match its recorded hash; not imported.
+    pub rejected_images: usize,
+    pub ocr_results: usize,
+    /// OCR rows whose bitmap postings were built during import; the rest are picked
+    /// up by the lazy indexer once the session is unlocked.
+    pub indexed_ocr_results: usize,
+}
+
+/// The capture session surrounding a moment: consecutive screenshots at most five
+/// minutes apart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextSession {
+    /// Unix seconds of the first and last capture in the session.
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub screenshot_count: i64,
+    /// Most captured processes; rows with only an encrypted name count while unlocked.
+    pub apps: Vec<DigestBucket>,
+    pub categories: Vec<DigestBucket>,
+}
+
+/// Answer to "what was I doing at this moment".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextAt {
+    /// Requested moment, Unix seconds.
+    pub requested_ts: i64,
+    /// Nearest screenshot (active app, window title, page URL); `None` if nothing was
+    /// captured close enough.
+    pub screenshot: Option<ScreenshotRecord>,
+    pub distance_secs: Option<i64>,
+    pub session: Option<ContextSession>,
+}
+
+/// Toggles for the OCR text post-processing pipeline, persisted as
+/// `ocr_text_rules` in the storage policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OcrTextRules {
+    pub enabled: bool,
+    pub merge_hyphenated_line_breaks: bool,
+    pub normalize_width: bool,
+    pub collapse_whitespace: bool,
+}
+
+impl Default for OcrTextRules {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            merge_hyphenated_line_breaks: true,
+            normalize_width: true,
+            collapse_whitespace: true,
+        }
+    }
+}
+
 /// Response after saving a screenshot, contains the assigned ID and dedup counts.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SaveScreenshotResponse {
@@ -304,6 +610,41 @@ impl RawScreenshotRow {
     }
 }
 
+/// A run of consecutive screenshots of one process, rendered as a collapsed timeline block.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivitySession {
+    pub id: i64,
+    /// `None` when the name is stored encrypted-only (paranoid or compliance mode)
+    /// and the session is locked.
+    pub process_name: Option<String>,
+    /// First and last capture time as Unix seconds.
+    pub start_time: i64,
+    pub end_time: i64,
+    pub duration_secs: i64,
+    pub screenshot_count: i64,
+    pub first_screenshot_id: i64,
+    pub last_screenshot_id: i64,
+}
+
+/// Position after the last record of a timeline page.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TimelineCursor {
+    pub after_id: i64,
+    /// Unix seconds of the record with `after_id`
+    pub after_timestamp: i64,
+}
+
+/// A page of timeline records, oldest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelinePage {
+    pub records: Vec<ScreenshotRecord>,
+    /// Cursor for the next page; `None` when no records remain in the range
+    pub next_cursor: Option<TimelineCursor>,
+    /// Detached months (`YYYY-MM`) overlapping the range; their records are missing
+    /// from every page until the month is attached again
+    pub offline_months: Vec<String>,
+}
+
 /// A time bucket with its screenshot count, used for density visualization.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DensityBucket {
@@ -311,6 +652,10 @@ pub struct DensityBucket {
     pub timestamp: i64,
     /// Number of screenshots in this bucket
     pub count: i64,
+    /// Process with the most screenshots in this bucket, if any were named
+    pub dominant_process: Option<String>,
+    /// Number of screenshots of `dominant_process` in this bucket
+    pub dominant_process_count: i64,
 }
 
 /// Storage statistics grouped by process.
@@ -321,6 +666,94 @@ pub struct ProcessStorageStat {
     pub percentage: f64,
 }
 
+/// Disk usage of one application on one day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppStorageUsage {
+    pub process_name: String,
+    pub screenshot_count: i64,
+    /// Size of the encrypted image files on disk (thumbnails excluded).
+    pub image_bytes: u64,
+    pub ocr_rows: i64,
+}
+
+/// Disk usage of one capture-local calendar day, broken down by application.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyStorageUsage {
+    /// Capture-local `YYYY-MM-DD`.
+    pub day: String,
+    pub screenshot_count: i64,
+    pub image_bytes: u64,
+    pub ocr_rows: i64,
+    pub apps: Vec<AppStorageUsage>,
+}
+
+/// Storage dashboard summary returned by `storage_get_stats`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageStats {
+    pub screenshot_count: i64,
+    pub ocr_row_count: i64,
+    /// Encrypted image bytes of the screenshots in `by_day`.
+    pub image_bytes: u64,
+    /// `screenshots.db` plus its WAL file.
+    pub database_bytes: u64,
+    /// Blind keyword index postings (stored inside the database).
+    pub keyword_index_bytes: u64,
+    /// Embedding rows in the database plus vector index files on disk.
+    pub vector_index_bytes: u64,
+    pub index_bytes: u64,
+    pub by_day: Vec<DailyStorageUsage>,
+    /// Same range as `by_day`, merged per process, by descending image bytes.
+    pub by_process: Vec<AppStorageUsage>,
+}
+
+/// A mounted drive offered by the first-run data directory chooser.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataDrive {
+    pub mount_point: String,
+    pub name: String,
+    pub file_system: String,
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+    pub is_removable: bool,
+}
+
+/// Estimated disk growth for a set of capture settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageUsageEstimate {
+    pub bytes_per_frame: u64,
+    /// Captures kept per day after near-duplicate frames are skipped.
+    pub frames_per_day: u64,
+    pub bytes_per_month: u64,
+}
+
+/// Result of checking a candidate storage root for the data directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataDirValidation {
+    pub ok: bool,
+    /// Resolved data directory (`<root>/data`).
+    pub data_dir: String,
+    pub writable: bool,
+    /// Sync client owning the path, e.g. `"OneDrive"`.
+    pub cloud_provider: Option<String>,
+    pub available_bytes: Option<u64>,
+    /// The data directory already contains a database that will be reused.
+    pub has_existing_data: bool,
+    /// `NOT_ABSOLUTE`, `CLOUD_SYNCED` or `NOT_WRITABLE`.
+    pub problems: Vec<String>,
+}
+
+/// Screenshot count for one local day, using the UTC offset active at capture time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureLocalDay {
+    /// `YYYY-MM-DD` in the time zone the screenshots were captured in.
+    pub day: String,
+    pub screenshot_count: i64,
+    pub first_ts: i64,
+    pub last_ts: i64,
+    /// Distinct UTC offsets (minutes east of UTC) seen that day, ascending.
+    pub utc_offsets: Vec<i32>,
+}
+
 /// A lightweight screenshot record for process/month archive views.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessMonthlyThumbnailItem {
@@ -344,6 +777,18 @@ pub struct ProcessMonthlyThumbnailPage {
     pub next_page: Option<i64>,
 }
 
+/// Outcome of a permanent time-range deletion.
+#[derive(Debug, Clone)]
+pub struct TimeRangeDeleteResult {
+    pub deleted: i64,
+    /// Pinned screenshots in the range that were kept.
+    pub pinned_kept: i64,
+    /// Screenshots in a protected range that were kept.
+    pub protected_kept: i64,
+    /// Image hashes of the deleted screenshots, for vector cleanup.
+    pub image_hashes: Vec<String>,
+}
+
 /// Soft delete enqueue result.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SoftDeleteResult {
@@ -366,6 +811,275 @@ pub struct SoftDeleteScreenshotsResult {
     pub queued_ocr: i64,
 }
 
+/// A screenshot in the trash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub screenshot_id: i64,
+    pub created_at: String,
+    pub deleted_at: String,
+    pub process_name: Option<String>,
+    /// When the retention task purges it for good
+    pub purge_after: Option<String>,
+}
+
+/// A period that retention cleanup and time-range deletion keep.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtectedRange {
+    pub id: i64,
+    /// UTC `%Y-%m-%d %H:%M:%S`, inclusive
+    pub start_at: String,
+    /// UTC `%Y-%m-%d %H:%M:%S`, inclusive
+    pub end_at: String,
+    pub label: Option<String>,
+    pub created_at: String,
+    /// Live screenshots currently inside the range
+    pub screenshot_count: i64,
+}
+
+/// Outcome of one cold-storage run (`archive_cold_screenshots`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ColdStorageSummary {
+    /// Screenshots moved into bundles.
+    pub archived: usize,
+    /// Bundles created by this run; existing day bundles are appended to.
+    pub bundles_written: usize,
+    /// Size of the archived image files before and after bundling.
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    /// Bundles deleted because no screenshot points into them any more.
+    pub removed_bundles: usize,
+    /// The run stopped because a row key needed Windows Hello.
+    pub auth_required: bool,
+}
+
+/// A closed month moved out of the live database into its own shard file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonthShard {
+    /// `YYYY-MM`, in local time.
+    pub month: String,
+    pub screenshot_count: i64,
+    pub detached_at: String,
+    /// Size of the shard file; `None` if it is missing.
+    pub file_bytes: Option<u64>,
+}
+
+/// Result of the startup recovery of stale pending captures.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PendingRecoverySummary {
+    /// Pending rows whose file survived, committed without OCR text.
+    pub committed: usize,
+    /// Pending rows without a file and aborted rows, deleted.
+    pub aborted: usize,
+    /// `.pending` files no row referred to, deleted.
+    pub orphan_files_removed: usize,
+    /// Committed rows now waiting for OCR; at most 200 are listed.
+    pub requeued_ids: Vec<i64>,
+}
+
+/// An image file on disk that no database row refers to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrphanFile {
+    /// Path relative to the data directory.
+    pub path: String,
+    pub bytes: u64,
+}
+
+/// What `storage_gc` found, and removed when asked to clean.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GcReport {
+    /// Image, thumbnail and bundle files looked at.
+    pub scanned_files: usize,
+    pub orphan_files: usize,
+    pub orphan_bytes: u64,
+    /// The first 200 orphaned files.
+    pub orphan_samples: Vec<OrphanFile>,
+    /// Live screenshots whose image file is gone.
+    pub missing_rows: usize,
+    /// The first 200 of them.
+    pub missing_ids: Vec<i64>,
+    /// Missing-file rows left alone because they are pinned or protected.
+    pub missing_kept: usize,
+    pub files_removed: usize,
+    pub bytes_removed: u64,
+    /// Missing-file rows moved to the trash.
+    pub rows_removed: i64,
+}
+
+/// Outcome of an incremental backup run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackupReport {
+    /// Backup folder that was written.
+    pub destination: String,
+    /// Size of the database snapshot, in bytes.
+    pub database_bytes: u64,
+    /// Files new or changed since the previous backup, copied and verified.
+    pub files_copied: usize,
+    pub bytes_copied: u64,
+    pub files_unchanged: usize,
+    /// Files dropped from the backup because they left the data directory.
+    pub files_removed: usize,
+    pub duration_ms: i64,
+}
+
+/// Outcome of `storage_restore_from_backup`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RestoreReport {
+    /// `replace` or `merge`.
+    pub mode: String,
+    /// `created_at` of the backup manifest.
+    pub backup_created_at: String,
+    pub files_restored: usize,
+    /// Files already present in the data directory, left alone by a merge.
+    pub files_skipped: usize,
+    /// Files present in both with different contents; a merge keeps the local file
+    /// and skips the screenshots stored in the backup's copy.
+    pub file_conflicts: usize,
+    /// Screenshots added by a merge.
+    pub screenshots_merged: usize,
+    /// Backup screenshots a merge left out because their file conflicted or was missing.
+    pub screenshots_skipped: usize,
+    /// Merged OCR rows are not in the blind index until `storage_rebuild_index` runs.
+    pub index_rebuild_required: bool,
+}
+
+/// Remote storage kind used by the sync engine.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncProvider {
+    #[default]
+    Webdav,
+    S3,
+}
+
+/// Sync target settings. The password or secret access key is stored separately,
+/// encrypted with the master key, and never returned.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncConfig {
+    pub enabled: bool,
+    pub provider: SyncProvider,
+    /// WebDAV collection URL, or S3 endpoint such as `https://s3.eu-west-1.amazonaws.com`.
+    pub endpoint: String,
+    /// S3 only.
+    pub bucket: String,
+    /// S3 only; `us-east-1` when empty.
+    pub region: String,
+    /// Folder under the endpoint or bucket that holds this device's data.
+    pub prefix: String,
+    /// WebDAV user name or S3 access key id.
+    pub username: String,
+    /// Whether a password or secret key is saved. Ignored when saving.
+    pub has_secret: bool,
+}
+
+/// Outcome of a sync run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncReport {
+    /// Screenshots uploaded with their changelog entries.
+    pub screenshots_uploaded: usize,
+    pub files_uploaded: usize,
+    pub bytes_uploaded: u64,
+    /// Screenshots whose deletion was recorded and whose remote files were removed.
+    pub screenshots_deleted: usize,
+    /// Encrypted changelog batches written.
+    pub changelog_batches: usize,
+    /// Screenshots left for the next run because their file is missing.
+    pub screenshots_skipped: usize,
+    pub duration_ms: i64,
+}
+
+/// A user tag and the number of live screenshots carrying it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub screenshot_count: i64,
+}
+
+/// A decrypted user note attached to a screenshot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Annotation {
+    pub id: i64,
+    pub screenshot_id: i64,
+    pub text: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// A decrypted clipboard history entry.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ClipboardEntry {
+    pub id: i64,
+    /// Unix time in milliseconds.
+    pub captured_at: i64,
+    pub text: String,
+    /// Foreground window the text was copied in.
+    pub process_name: Option<String>,
+    pub window_title: Option<String>,
+}
+
+/// A timed piece of an audio transcript. Offsets are milliseconds from the start
+/// of the chunk.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TranscriptSegment {
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub text: String,
+}
+
+/// A decrypted transcript of one recorded audio chunk.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AudioTranscript {
+    pub id: i64,
+    /// Unix time in milliseconds.
+    pub started_at: i64,
+    pub ended_at: i64,
+    /// `"system"`, `"microphone"` or `"both"`.
+    pub source: String,
+    /// Language detected or forced for the chunk, e.g. `"en"`.
+    pub language: Option<String>,
+    pub text: String,
+    pub segments: Vec<TranscriptSegment>,
+}
+
+/// The input for saving an audio transcript over reverse IPC.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveAudioTranscriptRequest {
+    /// Unix time in milliseconds.
+    pub started_at: i64,
+    pub ended_at: i64,
+    pub source: String,
+    #[serde(default)]
+    pub language: Option<String>,
+    pub segments: Vec<TranscriptSegment>,
+}
+
+/// Operation applied to every screenshot of a batch selection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BatchScreenshotOperation {
+    /// Soft-delete the screenshots and queue file and OCR cleanup.
+    Delete,
+    /// Attach `tag` to every screenshot.
+    AddTag { tag: String },
+    /// Detach `tag` from every screenshot.
+    RemoveTag { tag: String },
+    /// Exempt the screenshots from retention and range deletion.
+    Pin,
+    /// Clear the pinned flag.
+    Unpin,
+}
+
+/// Outcome of a batch operation over a screenshot selection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchOperationResult {
+    /// Distinct valid IDs in the request.
+    pub requested: i64,
+    /// Screenshots actually changed by the operation.
+    pub affected: i64,
+    /// Vectors removed by the Python backend; `None` when not applicable or unreachable.
+    pub vector_deleted: Option<i64>,
+}
+
 /// Background delete queue status for UI polling.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeleteQueueStatus {
@@ -403,3 +1117,139 @@ pub struct MigrationResult {
     pub skipped: usize,
     pub errors: Vec<String>,
 }
+
+/// Result of rewriting legacy absolute image paths, returned by `storage_normalize_paths`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PathNormalizationReport {
+    /// Rows that held an absolute path.
+    pub scanned: usize,
+    pub rewritten: usize,
+    pub unresolved_count: usize,
+    /// The first unresolved rows; at most 500 are listed.
+    pub unresolved: Vec<UnresolvedImagePath>,
+}
+
+/// An absolute image path that was left unchanged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnresolvedImagePath {
+    /// `screenshots` or `delta_keyframes`.
+    pub table: String,
+    pub id: i64,
+    pub image_path: String,
+    /// `outside_data_dir` when the file only exists at the absolute path, else `missing`.
+    pub reason: String,
+}
+
+/// A stored screenshot whose encrypted file failed verification.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorruptImageEntry {
+    pub screenshot_id: i64,
+    pub image_path: String,
+    /// Stable failure code from `ImageIntegrityError::code`.
+    pub code: String,
+    pub error: String,
+}
+
+/// Result of a sampled `.enc` image spot-check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageVerificationReport {
+    pub sample_rate: f64,
+    pub total: i64,
+    pub checked: i64,
+    pub ok: i64,
+    pub corrupt: Vec<CorruptImageEntry>,
+}
+
+/// Outcome of `storage_check_integrity`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DatabaseHealthReport {
+    /// Whether every check passed; after a repair, whether nothing is left to fix.
+    pub healthy: bool,
+    /// Messages from `PRAGMA integrity_check`, empty when it reported `ok`.
+    pub integrity_errors: Vec<String>,
+    /// Messages from `PRAGMA cipher_integrity_check`; `None` when the build does not
+    /// support it.
+    pub cipher_errors: Option<Vec<String>>,
+    /// Rows violating a foreign key, per `PRAGMA foreign_key_check`.
+    pub foreign_key_violations: i64,
+    pub row_keys_checked: i64,
+    /// Screenshots whose row key could not be unwrapped.
+    pub row_key_failures: Vec<i64>,
+    pub bitmap_tokens_scanned: i64,
+    /// Posting lists holding IDs of OCR rows that no longer exist.
+    pub bitmap_tokens_dangling: i64,
+    /// Dangling OCR row IDs across all posting lists.
+    pub bitmap_dangling_postings: i64,
+    /// Posting lists that could not be deserialized; the index needs a rebuild.
+    pub bitmap_tokens_unreadable: i64,
+    /// Repairs made when `repair` was requested, e.g. `reindex` or `compact_bitmap_index`.
+    pub repairs: Vec<String>,
+}
+
+/// How much of the captured content a weekly digest may reveal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestPrivacyLevel {
+    /// Capture counts per day and category only.
+    Summary,
+    /// Adds per-application capture counts.
+    #[default]
+    Apps,
+    /// Adds the most frequent window titles (decrypted on demand).
+    Detailed,
+}
+
+/// A labelled count in a digest section (day, category, process, or title).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DigestBucket {
+    pub label: String,
+    pub count: i64,
+}
+
+/// One week of capture activity, aggregated for the HTML digest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklyDigest {
+    /// Unix seconds, inclusive.
+    pub week_start: i64,
+    /// Unix seconds, exclusive.
+    pub week_end: i64,
+    pub privacy_level: DigestPrivacyLevel,
+    pub total_screenshots: i64,
+    /// Per local calendar day; days without captures are omitted.
+    pub daily: Vec<DigestBucket>,
+    pub categories: Vec<DigestBucket>,
+    /// Empty below `DigestPrivacyLevel::Apps`.
+    pub processes: Vec<DigestBucket>,
+    /// Empty below `DigestPrivacyLevel::Detailed`.
+    pub top_titles: Vec<DigestBucket>,
+}
+
+/// Persisted schedule and last-run state of a background job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledJob {
+    pub name: String,
+    pub enabled: bool,
+    pub interval_secs: i64,
+    /// Unix seconds when the last run started.
+    pub last_run_at: Option<i64>,
+    /// `ok`, `skipped`, or `error`.
+    pub last_status: Option<String>,
+    /// Summary or error message from the last run.
+    pub last_result: Option<String>,
+    pub last_duration_ms: Option<i64>,
+    pub run_count: i64,
+}
+
+/// Outcome of `run_database_maintenance`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DatabaseMaintenanceReport {
+    /// Size of `screenshots.db` plus its WAL before and after, in bytes.
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    pub bitmap_tokens_scanned: i64,
+    /// Posting lists rewritten without IDs of OCR rows that no longer exist.
+    pub bitmap_tokens_compacted: i64,
+    /// Posting lists deleted because none of their OCR rows exist any more.
+    pub bitmap_tokens_removed: i64,
+    pub duration_ms: i64,
+}
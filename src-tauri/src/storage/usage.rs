@@ -0,0 +1,338 @@
+//! Per-day, per-app disk usage: encrypted image bytes and OCR row counts, so users can
+//! see which days and applications grew the data directory and delete precisely.
+//! [`StorageState::get_storage_stats`] adds totals plus database and index sizes for
+//! the storage dashboard.
+
+use std::collections::{BTreeMap, HashMap};
+
+use rusqlite::params;
+
+use crate::credential_manager::{decrypt_row_key_with_cng, decrypt_with_master_key};
+
+use super::capture_day::{current_utc_offset_minutes, format_sql_timestamp, CAPTURE_DAY_SQL};
+use super::cold_storage::stored_image_size;
+use super::policy::directory_size;
+use super::{AppStorageUsage, DailyStorageUsage, StorageState, StorageStats};
+
+/// Label for rows whose process name is only stored encrypted while the session is locked.
+const ENCRYPTED_PROCESS_LABEL: &str = "(encrypted)";
+const UNKNOWN_PROCESS_LABEL: &str = "(unknown)";
+
+struct UsageRow {
+    day: String,
+    image_path: String,
+    process_name: Option<String>,
+    process_name_enc: Option<Vec<u8>>,
+    key_enc: Option<Vec<u8>>,
+    ocr_rows: i64,
+}
+
+fn add_usage(app: &mut AppStorageUsage, image_bytes: u64, ocr_rows: i64) {
+    app.screenshot_count += 1;
+    app.image_bytes += image_bytes;
+    app.ocr_rows += ocr_rows;
+}
+
+impl StorageState {
+    /// Break down storage for screenshots captured in `[start_ts, end_ts]` (Unix seconds)
+    /// by capture-local calendar day and process.
+    ///
+    /// Image bytes are the on-disk size of each encrypted file (thumbnails excluded);
+    /// missing files count as zero. Days are returned in ascending order and apps within
+    /// a day by descending image bytes.
+    pub fn get_storage_usage_by_day(
+        &self,
+        start_ts: f64,
+        end_ts: f64,
+    ) -> Result<Vec<DailyStorageUsage>, String> {
+        let start_dt = format_sql_timestamp(start_ts as i64)?;
+        let end_dt = format_sql_timestamp(end_ts as i64)?;
+        let fallback_offset = current_utc_offset_minutes();
+
+        let rows: Vec<UsageRow> = {
+            let guard = self.get_connection_named("get_storage_usage_by_day")?;
+            let conn = guard.as_ref().unwrap();
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT {day}, s.image_path, s.process_name,
+                            s.process_name_enc, s.content_key_encrypted,
+                            (SELECT COUNT(*) FROM ocr_results o
+                             WHERE o.is_deleted = 0 AND o.screenshot_id = s.id)
+                     FROM screenshots s
+                     WHERE s.is_deleted = 0 AND s.created_at BETWEEN ?2 AND ?3",
+                    day = CAPTURE_DAY_SQL
+                ))
+                .map_err(|e| format!("Failed to prepare usage query: {}", e))?;
+            let rows = stmt
+                .query_map(params![fallback_offset, start_dt, end_dt], |row| {
+                    Ok(UsageRow {
+                        day: row.get(0)?,
+                        image_path: row.get(1)?,
+                        process_name: row.get(2)?,
+                        process_name_enc: row.get(3)?,
+                        key_enc: row.get(4)?,
+                        ocr_rows: row.get(5)?,
+                    })
+                })
+                .map_err(|e| format!("Failed to execute usage query: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows
+            // guard dropped — mutex released before file stats and decryption
+        };
+
+        let can_decrypt = self.credential_state.is_session_valid();
+        let mut days: BTreeMap<String, HashMap<String, AppStorageUsage>> = BTreeMap::new();
+        for row in rows {
+            let image_bytes = stored_image_size(&self.resolve_image_path(&row.image_path));
+            let process_name = match row.process_name.filter(|p| !p.is_empty()) {
+                Some(name) => name,
+                None => match (row.process_name_enc, row.key_enc) {
+                    (Some(data), Some(key_enc)) if can_decrypt => {
+                        let mut row_key = decrypt_row_key_with_cng(&key_enc).ok();
+                        let name = row_key
+                            .as_ref()
+                            .and_then(|key| decrypt_with_master_key(key, &data).ok())
+                            .and_then(|v| String::from_utf8(v).ok());
+                        if let Some(key) = row_key.as_mut() {
+                            Self::zeroize_bytes(key);
+                        }
+                        name.unwrap_or_else(|| ENCRYPTED_PROCESS_LABEL.to_string())
+                    }
+                    (Some(_), _) => ENCRYPTED_PROCESS_LABEL.to_string(),
+                    _ => UNKNOWN_PROCESS_LABEL.to_string(),
+                },
+            };
+
+            let app = days
+                .entry(row.day)
+                .or_default()
+                .entry(process_name.clone())
+                .or_insert_with(|| AppStorageUsage {
+                    process_name,
+                    screenshot_count: 0,
+                    image_bytes: 0,
+                    ocr_rows: 0,
+                });
+            add_usage(app, image_bytes, row.ocr_rows);
+        }
+
+        Ok(days
+            .into_iter()
+            .map(|(day, apps)| {
+                let mut apps: Vec<AppStorageUsage> = apps.into_values().collect();
+                apps.sort_by(|a, b| {
+                    b.image_bytes
+                        .cmp(&a.image_bytes)
+                        .then_with(|| a.process_name.cmp(&b.process_name))
+                });
+                DailyStorageUsage {
+                    day,
+                    screenshot_count: apps.iter().map(|a| a.screenshot_count).sum(),
+                    image_bytes: apps.iter().map(|a| a.image_bytes).sum(),
+                    ocr_rows: apps.iter().map(|a| a.ocr_rows).sum(),
+                    apps,
+                }
+            })
+            .collect())
+    }
+}
+
+impl StorageState {
+    /// Bytes of keyword and embedding index payloads stored inside the database.
+    fn in_database_index_bytes(&self) -> Result<(u64, u64), String> {
+        let guard = self.get_connection_named("in_database_index_bytes")?;
+        let conn = guard.as_ref().unwrap();
+        let keyword: i64 = conn
+            .query_row(
+                "SELECT COALESCE(SUM(LENGTH(token_hash) + LENGTH(postings_blob)), 0)
+                 FROM blind_bitmap_index",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to measure keyword index: {}", e))?;
+        let vectors: i64 = conn
+            .query_row(
+                "SELECT COALESCE(SUM(LENGTH(vector_f32)), 0) FROM derived_embeddings",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to measure embedding rows: {}", e))?;
+        Ok((keyword.max(0) as u64, vectors.max(0) as u64))
+    }
+
+    /// Storage dashboard summary for screenshots captured in `[start_ts, end_ts]`.
+    ///
+    /// `by_day`, `by_process` and `image_bytes` cover the range; counts, database and
+    /// index sizes cover all stored data. Index payloads kept inside the database are
+    /// also part of `database_bytes`.
+    pub fn get_storage_stats(&self, start_ts: f64, end_ts: f64) -> Result<StorageStats, String> {
+        let by_day = self.get_storage_usage_by_day(start_ts, end_ts)?;
+
+        let mut processes: HashMap<String, AppStorageUsage> = HashMap::new();
+        for app in by_day.iter().flat_map(|day| day.apps.iter()) {
+            let entry = processes
+                .entry(app.process_name.clone())
+                .or_insert_with(|| AppStorageUsage {
+                    process_name: app.process_name.clone(),
+                    screenshot_count: 0,
+                    image_bytes: 0,
+                    ocr_rows: 0,
+                });
+            entry.screenshot_count += app.screenshot_count;
+            entry.image_bytes += app.image_bytes;
+            entry.ocr_rows += app.ocr_rows;
+        }
+        let mut by_process: Vec<AppStorageUsage> = processes.into_values().collect();
+        by_process.sort_by(|a, b| {
+            b.image_bytes
+                .cmp(&a.image_bytes)
+                .then_with(|| a.process_name.cmp(&b.process_name))
+        });
+
+        let (keyword_index_bytes, embedding_bytes) = self.in_database_index_bytes()?;
+        let data_dir = self
+            .data_dir
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let vector_index_bytes = embedding_bytes
+            + directory_size(&data_dir.join("derived-indexes"))
+            + directory_size(&data_dir.join("chroma_db"));
+
+        Ok(StorageStats {
+            screenshot_count: self.count_active_screenshots()?,
+            ocr_row_count: self.count_active_ocr_rows()?,
+            image_bytes: by_day.iter().map(|day| day.image_bytes).sum(),
+            database_bytes: self.database_size_bytes(),
+            keyword_index_bytes,
+            vector_index_bytes,
+            index_bytes: keyword_index_bytes + vector_index_bytes,
+            by_day,
+            by_process,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_storage;
+    use chrono::DateTime;
+
+    #[test]
+    fn usage_groups_bytes_and_ocr_rows_by_day_and_app() {
+        let (temp, storage) = test_storage();
+        std::fs::create_dir_all(temp.path().join("screenshots")).unwrap();
+        std::fs::write(temp.path().join("screenshots/a.enc"), vec![0u8; 100]).unwrap();
+        std::fs::write(temp.path().join("screenshots/b.enc"), vec![0u8; 300]).unwrap();
+        std::fs::write(temp.path().join("screenshots/c.enc"), vec![0u8; 50]).unwrap();
+
+        let guard = storage.db.lock().unwrap_or_else(|e| e.into_inner());
+        let connection = guard.as_ref().expect("test database");
+        connection
+            .execute_batch(
+                "INSERT INTO screenshots (id, image_path, image_hash, process_name, created_at, is_deleted) VALUES
+                    (1, 'screenshots/a.enc', 'h1', 'code.exe', '2026-03-02 12:00:00', 0),
+                    (2, 'screenshots/b.enc', 'h2', 'chrome.exe', '2026-03-02 12:30:00', 0),
+                    (3, 'screenshots/c.enc', 'h3', 'code.exe', '2026-03-02 13:00:00', 0),
+                    (4, 'screenshots/missing.enc', 'h4', 'code.exe', '2026-03-02 13:30:00', 1);
+                 INSERT INTO ocr_results (screenshot_id, text_hash, is_deleted) VALUES
+                    (1, 'x', 0), (1, 'y', 0), (2, 'z', 0), (3, 'w', 1);",
+            )
+            .expect("usage fixture");
+        drop(guard);
+
+        let start = DateTime::parse_from_rfc3339("2026-03-02T00:00:00Z")
+            .unwrap()
+            .timestamp() as f64;
+        let usage = storage
+            .get_storage_usage_by_day(start, start + 86_399.0)
+            .unwrap();
+
+        let screenshots: i64 = usage.iter().map(|d| d.screenshot_count).sum();
+        let bytes: u64 = usage.iter().map(|d| d.image_bytes).sum();
+        let ocr_rows: i64 = usage.iter().map(|d| d.ocr_rows).sum();
+        assert_eq!(screenshots, 3);
+        assert_eq!(bytes, 450);
+        assert_eq!(ocr_rows, 3);
+
+        let code: Vec<&AppStorageUsage> = usage
+            .iter()
+            .flat_map(|d| d.apps.iter())
+            .filter(|a| a.process_name == "code.exe")
+            .collect();
+        assert_eq!(code.iter().map(|a| a.image_bytes).sum::<u64>(), 150);
+        assert_eq!(code.iter().map(|a| a.ocr_rows).sum::<i64>(), 2);
+    }
+
+    #[test]
+    fn usage_days_follow_the_offset_recorded_at_capture() {
+        let (_temp, storage) = test_storage();
+        let guard = storage.db.lock().unwrap_or_else(|e| e.into_inner());
+        let connection = guard.as_ref().expect("test database");
+        connection
+            .execute_batch(
+                "INSERT INTO screenshots (id, image_path, image_hash, process_name, created_at, utc_offset_minutes) VALUES
+                    (1, 'screenshots/a.enc', 'h1', 'code.exe', '2026-03-02 23:30:00', 60),
+                    (2, 'screenshots/b.enc', 'h2', 'code.exe', '2026-03-03 10:00:00', 0);",
+            )
+            .expect("offset fixture");
+        drop(guard);
+
+        let start = DateTime::parse_from_rfc3339("2026-03-02T00:00:00Z")
+            .unwrap()
+            .timestamp() as f64;
+        let usage = storage
+            .get_storage_usage_by_day(start, start + 2.0 * 86_400.0)
+            .unwrap();
+
+        let days: Vec<(&str, i64)> = usage
+            .iter()
+            .map(|d| (d.day.as_str(), d.screenshot_count))
+            .collect();
+        // 23:30 UTC at +01:00 is already the next day where it was captured.
+        assert_eq!(days, vec![("2026-03-03", 2)]);
+    }
+
+    #[test]
+    fn stats_merge_apps_across_days_and_measure_indexes() {
+        let (temp, storage) = test_storage();
+        std::fs::create_dir_all(temp.path().join("screenshots")).unwrap();
+        std::fs::write(temp.path().join("screenshots/a.enc"), vec![0u8; 100]).unwrap();
+        std::fs::write(temp.path().join("screenshots/b.enc"), vec![0u8; 40]).unwrap();
+        std::fs::create_dir_all(temp.path().join("derived-indexes")).unwrap();
+        std::fs::write(temp.path().join("derived-indexes/ocr.idx"), vec![0u8; 64]).unwrap();
+
+        let guard = storage.db.lock().unwrap_or_else(|e| e.into_inner());
+        let connection = guard.as_ref().expect("test database");
+        connection
+            .execute_batch(
+                "INSERT INTO screenshots (id, image_path, image_hash, process_name, created_at, is_deleted) VALUES
+                    (1, 'screenshots/a.enc', 'h1', 'code.exe', '2026-03-02 12:00:00', 0),
+                    (2, 'screenshots/b.enc', 'h2', 'code.exe', '2026-03-05 12:00:00', 0);
+                 INSERT INTO ocr_results (screenshot_id, text_hash, is_deleted) VALUES
+                    (1, 'x', 0), (2, 'y', 0), (2, 'z', 0);
+                 INSERT INTO blind_bitmap_index (token_hash, postings_blob) VALUES ('abcd', x'00112233');",
+            )
+            .expect("stats fixture");
+        drop(guard);
+
+        let start = DateTime::parse_from_rfc3339("2026-03-01T00:00:00Z")
+            .unwrap()
+            .timestamp() as f64;
+        let stats = storage
+            .get_storage_stats(start, start + 10.0 * 86_400.0)
+            .unwrap();
+
+        assert_eq!(stats.screenshot_count, 2);
+        assert_eq!(stats.ocr_row_count, 3);
+        assert_eq!(stats.image_bytes, 140);
+        assert_eq!(stats.by_process.len(), 1);
+        assert_eq!(stats.by_process[0].screenshot_count, 2);
+        assert_eq!(stats.by_process[0].ocr_rows, 3);
+        assert_eq!(stats.keyword_index_bytes, 8);
+        assert_eq!(stats.vector_index_bytes, 64);
+        assert_eq!(stats.index_bytes, 72);
+    }
+}
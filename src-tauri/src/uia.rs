@@ -0,0 +1,51 @@
+//! Shared UI Automation plumbing.
+//!
+//! UI Automation calls are answered by the target app's process, so a hung app blocks
+//! the caller. Every read therefore runs on a blocking thread with its own COM
+//! apartment and is abandoned after a timeout; the thread finishes on its own.
+
+use std::time::Duration;
+
+use windows::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_INPROC_SERVER, COINIT_MULTITHREADED,
+};
+use windows::Win32::UI::Accessibility::{CUIAutomation, IUIAutomation};
+
+/// Balances `CoInitializeEx` on the blocking thread.
+struct ComGuard;
+
+impl Drop for ComGuard {
+    fn drop(&mut self) {
+        // SAFETY: created only after `CoInitializeEx` succeeded on this thread.
+        unsafe { CoUninitialize() };
+    }
+}
+
+fn with_automation<T>(read: impl FnOnce(&IUIAutomation) -> Result<T, String>) -> Result<T, String> {
+    // SAFETY: COM is initialized for this thread before the automation object is
+    // created, and `ComGuard` is declared first so it is dropped after it.
+    unsafe {
+        CoInitializeEx(None, COINIT_MULTITHREADED)
+            .ok()
+            .map_err(|e| format!("Failed to initialize COM: {}", e))?;
+        let _com = ComGuard;
+        let automation: IUIAutomation =
+            CoCreateInstance(&CUIAutomation, None, CLSCTX_INPROC_SERVER)
+                .map_err(|e| format!("Failed to create UI Automation: {}", e))?;
+        read(&automation)
+    }
+}
+
+/// Runs `read` with a UI Automation client on a blocking thread, giving up after
+/// `timeout`.
+pub(crate) async fn read_with_timeout<T, F>(timeout: Duration, read: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&IUIAutomation) -> Result<T, String> + Send + 'static,
+{
+    let task = tokio::task::spawn_blocking(move || with_automation(read));
+    match tokio::time::timeout(timeout, task).await {
+        Ok(result) => result.map_err(|e| format!("Task join error: {:?}", e))?,
+        Err(_) => Err(format!("UI Automation timed out after {:?}", timeout)),
+    }
+}
@@ -0,0 +1,435 @@
+//! Text extraction through UI Automation as an alternative to OCR.
+//!
+//! Editors, terminals and Office apps expose their visible text through the
+//! accessibility tree. For processes on the UIA list the capture loop reads that text
+//! instead of running OCR on the frame: it is exact, costs a few milliseconds instead
+//! of a model run, and has no recognition errors. The text is stored through the same
+//! `OcrResultInput` path as OCR output, one block per visible line, with boxes
+//! synthesized from the line rectangles UI Automation reports, mapped from screen
+//! coordinates into the captured frame.
+//!
+//! Elements with the Text pattern (documents, edit controls, terminal buffers) are
+//! read line by line from their visible ranges; plain Text controls contribute their
+//! name. When the tree yields less than `MIN_TEXT_CHARS` characters, the app probably
+//! draws its own UI, so the frame falls back to OCR.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use windows::core::VARIANT;
+use windows::Win32::Foundation::{HWND, RECT};
+use windows::Win32::System::Com::SAFEARRAY;
+use windows::Win32::System::Ole::{
+    SafeArrayAccessData, SafeArrayDestroy, SafeArrayGetLBound, SafeArrayGetUBound,
+    SafeArrayUnaccessData,
+};
+use windows::Win32::UI::Accessibility::{
+    IUIAutomation, IUIAutomationElement, IUIAutomationTextPattern, TreeScope_Descendants,
+    UIA_ControlTypePropertyId, UIA_DocumentControlTypeId, UIA_EditControlTypeId,
+    UIA_IsOffscreenPropertyId, UIA_TextControlTypeId, UIA_TextPatternId,
+};
+
+use crate::storage::OcrResultInput;
+
+pub const UIA_TEXT_ENABLED_KEY: &str = "uia_text_enabled";
+/// JSON array of lower-case process names whose text is read through UI Automation.
+pub const UIA_TEXT_PROCESSES_KEY: &str = "uia_text_processes";
+pub const DEFAULT_UIA_TEXT_PROCESSES: &[&str] = &[
+    "notepad.exe",
+    "wordpad.exe",
+    "windowsterminal.exe",
+    "winword.exe",
+    "excel.exe",
+    "powerpnt.exe",
+    "outlook.exe",
+    "onenote.exe",
+];
+
+/// Longest time a capture waits for the app's accessibility tree.
+const EXTRACT_TIMEOUT: Duration = Duration::from_millis(1500);
+/// Elements inspected per frame; very large trees fall back to OCR.
+const MAX_ELEMENTS: i32 = 4000;
+const MAX_BLOCKS: usize = 10_000;
+const MAX_TEXT_CHARS: usize = 16_384;
+/// Below this much text the tree is considered unusable and OCR runs instead.
+const MIN_TEXT_CHARS: usize = 20;
+
+/// UI Automation text extraction options.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiaTextSettings {
+    pub enabled: bool,
+    pub processes: Vec<String>,
+}
+
+pub fn load_settings() -> UiaTextSettings {
+    let processes = crate::registry_config::get_string(UIA_TEXT_PROCESSES_KEY)
+        .and_then(|raw| serde_json::from_str::<Vec<String>>(&raw).ok())
+        .unwrap_or_else(|| {
+            DEFAULT_UIA_TEXT_PROCESSES
+                .iter()
+                .map(|name| name.to_string())
+                .collect()
+        });
+    UiaTextSettings {
+        enabled: crate::registry_config::get_bool(UIA_TEXT_ENABLED_KEY).unwrap_or(false),
+        processes: normalize_process_names(&processes),
+    }
+}
+
+pub fn save_enabled(enabled: bool) -> Result<(), String> {
+    crate::registry_config::set_bool(UIA_TEXT_ENABLED_KEY, enabled)
+}
+
+pub fn save_processes(processes: &[String]) -> Result<Vec<String>, String> {
+    let processes = normalize_process_names(processes);
+    let raw = serde_json::to_string(&processes)
+        .map_err(|e| format!("Failed to serialize UIA process list: {}", e))?;
+    crate::registry_config::set_string(UIA_TEXT_PROCESSES_KEY, &raw)?;
+    Ok(processes)
+}
+
+fn normalize_process_names(names: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for name in names {
+        let name = name.trim().to_lowercase();
+        if !name.is_empty() && !normalized.contains(&name) {
+            normalized.push(name);
+        }
+    }
+    normalized
+}
+
+/// Maps screen coordinates into the captured frame of a window.
+#[derive(Debug, Clone, Copy)]
+struct FrameGeometry {
+    left: f64,
+    top: f64,
+    scale_x: f64,
+    scale_y: f64,
+    width: f64,
+    height: f64,
+}
+
+impl FrameGeometry {
+    /// The frame is the window scaled to `width`×`height`.
+    fn new(window_rect: &RECT, width: u32, height: u32) -> Option<Self> {
+        let window_width = f64::from(window_rect.right - window_rect.left);
+        let window_height = f64::from(window_rect.bottom - window_rect.top);
+        if window_width <= 0.0 || window_height <= 0.0 || width == 0 || height == 0 {
+            return None;
+        }
+        Some(Self {
+            left: f64::from(window_rect.left),
+            top: f64::from(window_rect.top),
+            scale_x: f64::from(width) / window_width,
+            scale_y: f64::from(height) / window_height,
+            width: f64::from(width),
+            height: f64::from(height),
+        })
+    }
+
+    /// OCR-style quad (clockwise from top-left) for a screen rectangle
+    /// `[x, y, width, height]`, clipped to the frame. `None` when nothing is visible.
+    fn quad(&self, rect: [f64; 4]) -> Option<Vec<Vec<f64>>> {
+        let [x, y, w, h] = rect;
+        if !(w > 0.0 && h > 0.0) {
+            return None;
+        }
+        let x1 = ((x - self.left) * self.scale_x).clamp(0.0, self.width);
+        let y1 = ((y - self.top) * self.scale_y).clamp(0.0, self.height);
+        let x2 = ((x + w - self.left) * self.scale_x).clamp(0.0, self.width);
+        let y2 = ((y + h - self.top) * self.scale_y).clamp(0.0, self.height);
+        if x2 - x1 < 1.0 || y2 - y1 < 1.0 {
+            return None;
+        }
+        Some(vec![vec![x1, y1], vec![x2, y1], vec![x2, y2], vec![x1, y2]])
+    }
+}
+
+fn contains_center(outer: &[f64; 4], inner: &[f64; 4]) -> bool {
+    let cx = inner[0] + inner[2] / 2.0;
+    let cy = inner[1] + inner[3] / 2.0;
+    cx >= outer[0] && cx <= outer[0] + outer[2] && cy >= outer[1] && cy <= outer[1] + outer[3]
+}
+
+fn rect_to_xywh(rect: &RECT) -> [f64; 4] {
+    [
+        f64::from(rect.left),
+        f64::from(rect.top),
+        f64::from(rect.right - rect.left),
+        f64::from(rect.bottom - rect.top),
+    ]
+}
+
+/// Pairs the lines of a text range with the per-line rectangles UI Automation
+/// returned for it. When the counts differ (wrapped lines, blank lines without a
+/// rectangle) the range becomes one block spanning all rectangles.
+fn lines_with_rects(text: &str, rects: &[[f64; 4]]) -> Vec<(String, [f64; 4])> {
+    let lines: Vec<&str> = text
+        .split(['\r', '\n'])
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    if lines.is_empty() || rects.is_empty() {
+        return Vec::new();
+    }
+    if lines.len() == rects.len() {
+        return lines
+            .into_iter()
+            .zip(rects.iter().copied())
+            .map(|(line, rect)| (line.to_string(), rect))
+            .collect();
+    }
+    let x1 = rects.iter().map(|r| r[0]).fold(f64::INFINITY, f64::min);
+    let y1 = rects.iter().map(|r| r[1]).fold(f64::INFINITY, f64::min);
+    let x2 = rects
+        .iter()
+        .map(|r| r[0] + r[2])
+        .fold(f64::NEG_INFINITY, f64::max);
+    let y2 = rects
+        .iter()
+        .map(|r| r[1] + r[3])
+        .fold(f64::NEG_INFINITY, f64::max);
+    vec![(lines.join(" "), [x1, y1, x2 - x1, y2 - y1])]
+}
+
+/// Copies and frees a SAFEARRAY of doubles holding `[x, y, width, height]` groups.
+///
+/// # Safety
+/// `array` must be null or a one-dimensional SAFEARRAY of `f64` owned by the caller.
+unsafe fn take_rect_array(array: *mut SAFEARRAY) -> Vec<[f64; 4]> {
+    if array.is_null() {
+        return Vec::new();
+    }
+    let mut rects = Vec::new();
+    if let (Ok(lower), Ok(upper)) = (SafeArrayGetLBound(array, 1), SafeArrayGetUBound(array, 1)) {
+        let len = (upper - lower + 1).max(0) as usize;
+        let mut data: *mut std::ffi::c_void = std::ptr::null_mut();
+        if SafeArrayAccessData(array, &mut data).is_ok() {
+            if !data.is_null() {
+                let values = std::slice::from_raw_parts(data as *const f64, len);
+                rects.extend(
+                    values
+                        .chunks_exact(4)
+                        .map(|chunk| [chunk[0], chunk[1], chunk[2], chunk[3]]),
+                );
+            }
+            let _ = SafeArrayUnaccessData(array);
+        }
+    }
+    let _ = SafeArrayDestroy(array);
+    rects
+}
+
+/// Visible lines of an element that supports the Text pattern.
+///
+/// # Safety
+/// Calls into UI Automation; `element` must belong to `automation`'s thread.
+unsafe fn text_pattern_lines(element: &IUIAutomationElement) -> Option<Vec<(String, [f64; 4])>> {
+    let pattern = element
+        .GetCurrentPatternAs::<IUIAutomationTextPattern>(UIA_TextPatternId)
+        .ok()?;
+    let ranges = pattern.GetVisibleRanges().ok()?;
+    let mut lines = Vec::new();
+    for index in 0..ranges.Length().unwrap_or(0) {
+        let Ok(range) = ranges.GetElement(index) else {
+            continue;
+        };
+        let Ok(text) = range.GetText(MAX_TEXT_CHARS as i32) else {
+            continue;
+        };
+        let rects = range
+            .GetBoundingRectangles()
+            .map(|array| take_rect_array(array))
+            .unwrap_or_default();
+        lines.extend(lines_with_rects(&text.to_string(), &rects));
+    }
+    Some(lines)
+}
+
+fn read_window_text(
+    automation: &IUIAutomation,
+    hwnd_raw: isize,
+    geometry: FrameGeometry,
+) -> Result<Vec<OcrResultInput>, String> {
+    // SAFETY: every interface is reference counted by its wrapper, `hwnd_raw` is only
+    // passed to UI Automation, which validates it, and SAFEARRAYs are released by
+    // `take_rect_array`.
+    unsafe {
+        let root = automation
+            .ElementFromHandle(HWND(hwnd_raw as *mut _))
+            .map_err(|e| format!("Failed to get window element: {}", e))?;
+        let control_type = |id: i32| {
+            automation.CreatePropertyCondition(UIA_ControlTypePropertyId, &VARIANT::from(id))
+        };
+        let text_like = automation
+            .CreateOrCondition(
+                &automation
+                    .CreateOrCondition(
+                        &control_type(UIA_DocumentControlTypeId.0)
+                            .map_err(|e| format!("Failed to create UIA condition: {}", e))?,
+                        &control_type(UIA_EditControlTypeId.0)
+                            .map_err(|e| format!("Failed to create UIA condition: {}", e))?,
+                    )
+                    .map_err(|e| format!("Failed to create UIA condition: {}", e))?,
+                &control_type(UIA_TextControlTypeId.0)
+                    .map_err(|e| format!("Failed to create UIA condition: {}", e))?,
+            )
+            .map_err(|e| format!("Failed to create UIA condition: {}", e))?;
+        let on_screen = automation
+            .CreatePropertyCondition(UIA_IsOffscreenPropertyId, &VARIANT::from(false))
+            .map_err(|e| format!("Failed to create UIA condition: {}", e))?;
+        let condition = automation
+            .CreateAndCondition(&text_like, &on_screen)
+            .map_err(|e| format!("Failed to create UIA condition: {}", e))?;
+
+        let elements = root
+            .FindAll(TreeScope_Descendants, &condition)
+            .map_err(|e| format!("Failed to enumerate UIA elements: {}", e))?;
+        let count = elements.Length().unwrap_or(0);
+        if count > MAX_ELEMENTS {
+            return Err(format!("UIA tree too large ({} elements)", count));
+        }
+
+        // Elements are returned parent first; text inside an element already read
+        // through its Text pattern would otherwise be stored twice.
+        let mut read_areas: Vec<[f64; 4]> = Vec::new();
+        let mut results = Vec::new();
+        for index in 0..count {
+            let Ok(element) = elements.GetElement(index) else {
+                continue;
+            };
+            let Ok(bounds) = element.CurrentBoundingRectangle() else {
+                continue;
+            };
+            let bounds = rect_to_xywh(&bounds);
+            if read_areas.iter().any(|area| contains_center(area, &bounds)) {
+                continue;
+            }
+            let lines = match text_pattern_lines(&element) {
+                Some(lines) => {
+                    read_areas.push(bounds);
+                    lines
+                }
+                None => match element.CurrentName() {
+                    Ok(name) => lines_with_rects(&name.to_string(), &[bounds]),
+                    Err(_) => continue,
+                },
+            };
+            for (text, rect) in lines {
+                let Some(box_coords) = geometry.quad(rect) else {
+                    continue;
+                };
+                results.push(OcrResultInput {
+                    text: text.chars().take(MAX_TEXT_CHARS).collect(),
+                    confidence: 1.0,
+                    box_coords,
+                });
+                if results.len() >= MAX_BLOCKS {
+                    return Ok(results);
+                }
+            }
+        }
+        Ok(results)
+    }
+}
+
+/// Visible text of the captured window read through UI Automation, as OCR results
+/// in frame coordinates. `None` means OCR should run: the feature is off, the process
+/// is not on the list, or its accessibility tree has too little text.
+pub async fn extract_window_text(
+    hwnd_raw: isize,
+    window_rect: &RECT,
+    frame_width: u32,
+    frame_height: u32,
+    process_name: &str,
+) -> Option<Vec<OcrResultInput>> {
+    let settings = load_settings();
+    if !settings.enabled
+        || !settings
+            .processes
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(process_name))
+    {
+        return None;
+    }
+    let geometry = FrameGeometry::new(window_rect, frame_width, frame_height)?;
+    let read = crate::uia::read_with_timeout(EXTRACT_TIMEOUT, move |automation| {
+        read_window_text(automation, hwnd_raw, geometry)
+    });
+    match read.await {
+        Ok(results) => {
+            let chars: usize = results.iter().map(|r| r.text.chars().count()).sum();
+            if chars < MIN_TEXT_CHARS {
+                tracing::debug!(
+                    "UIA text for {} too short ({} chars); using OCR",
+                    process_name,
+                    chars
+                );
+                return None;
+            }
+            Some(results)
+        }
+        Err(e) => {
+            tracing::debug!(
+                "UIA text for {} unavailable, using OCR: {}",
+                process_name,
+                e
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn screen_rects_are_mapped_into_the_scaled_frame() {
+        let window = RECT {
+            left: 100,
+            top: 50,
+            right: 1100,
+            bottom: 850,
+        };
+        // Frame downscaled to half size.
+        let geometry = FrameGeometry::new(&window, 500, 400).unwrap();
+        assert_eq!(
+            geometry.quad([200.0, 150.0, 300.0, 40.0]),
+            Some(vec![
+                vec![50.0, 50.0],
+                vec![200.0, 50.0],
+                vec![200.0, 70.0],
+                vec![50.0, 70.0],
+            ])
+        );
+        // Partly outside the window: clipped to the frame edge.
+        assert_eq!(
+            geometry.quad([1000.0, 800.0, 400.0, 400.0]).unwrap()[2],
+            vec![500.0, 400.0]
+        );
+        assert_eq!(geometry.quad([0.0, 0.0, 50.0, 20.0]), None);
+        assert_eq!(geometry.quad([200.0, 150.0, 0.0, 40.0]), None);
+    }
+
+    #[test]
+    fn range_lines_pair_with_rects_or_merge_when_counts_differ() {
+        let rects = [[0.0, 0.0, 80.0, 10.0], [0.0, 12.0, 40.0, 10.0]];
+        assert_eq!(
+            lines_with_rects("fn main() {\r\n}\r\n", &rects),
+            vec![
+                ("fn main() {".to_string(), rects[0]),
+                ("}".to_string(), rects[1]),
+            ]
+        );
+        assert_eq!(
+            lines_with_rects("a long paragraph that wraps", &rects),
+            vec![(
+                "a long paragraph that wraps".to_string(),
+                [0.0, 0.0, 80.0, 22.0]
+            )]
+        );
+        assert!(lines_with_rects("  \n ", &rects).is_empty());
+    }
+}
@@ -116,6 +116,7 @@ function App() {
     handleManualStopMonitor,
     backendStatus,
     monitorPaused,
+    pauseRemainingSecs,
     backendError,
     handleStartBackend,
     handlePauseMonitor,
@@ -238,6 +239,7 @@ function App() {
         onSearchModeChange={setSearchMode}
         backendStatus={backendStatus}
         monitorPaused={monitorPaused}
+        pauseRemainingSecs={pauseRemainingSecs}
         handleStartBackend={handleStartBackend}
         handlePauseMonitor={handlePauseMonitor}
         handleResumeMonitor={handleResumeMonitor}
@@ -339,6 +339,7 @@ export function AdvancedSearch({ active, searchParams, onSelectResult, onOpenSna
     loadingMore,
     hasMore,
     error,
+    offlineMonths,
     selectedProcesses,
     setSelectedProcesses,
     processOptions,
@@ -467,6 +468,11 @@ export function AdvancedSearch({ active, searchParams, onSelectResult, onOpenSna
             {t('advancedSearch.search.error', { message: error })}
           </div>
         )}
+        {offlineMonths.length > 0 && (
+          <div className="p-3 mx-4 mt-3 bg-amber-500/10 border border-amber-500/20 rounded text-amber-300 text-xs shrink-0">
+            {t('advancedSearch.search.offline_months', { months: offlineMonths.join(', ') })}
+          </div>
+        )}
         {loading && (
           <div className="flex items-center justify-center py-8 text-ide-muted gap-2">
             <Loader2 className="w-4 h-4 animate-spin" />
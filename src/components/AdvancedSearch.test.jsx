@@ -27,6 +27,7 @@ vi.mock('./ThumbnailCard', () => ({
 
 vi.mock('../lib/monitor_api', () => ({
   searchScreenshots: vi.fn(async () => []),
+  countSearchResults: vi.fn(async () => ({ total: 0, by_process: [], by_day: [], offline_months: [] })),
   fetchImage: vi.fn(async () => null),
   fetchThumbnailBatch: vi.fn(async () => ({})),
   listProcesses: vi.fn(async () => [{ process_name: 'code.exe', count: 2 }]),
@@ -35,7 +36,7 @@ vi.mock('../lib/monitor_api', () => ({
 }));
 
 import { AdvancedSearch } from './AdvancedSearch';
-import { searchScreenshots, listProcesses, getCategoriesFromDb } from '../lib/monitor_api';
+import { searchScreenshots, countSearchResults, listProcesses, getCategoriesFromDb } from '../lib/monitor_api';
 
 const makeOcrResult = (id) => ({
   screenshot_id: id,
@@ -105,6 +106,23 @@ describe('AdvancedSearch', () => {
     });
   });
 
+  it('names detached months that the OCR search could not cover', async () => {
+    countSearchResults.mockResolvedValueOnce({ total: 0, by_process: [], by_day: [], offline_months: ['2024-01'] });
+
+    render(
+      <AdvancedSearch
+        active
+        searchParams={{ query: 'hello', mode: 'ocr' }}
+        onSelectResult={vi.fn()}
+        backendOnline
+      />
+    );
+
+    await waitFor(() => {
+      expect(screen.getByText('advancedSearch.search.offline_months')).toBeInTheDocument();
+    });
+  });
+
   it('auto-switches to OCR when backend is offline in NL mode', async () => {
     const onSearchModeChange = vi.fn();
 
@@ -18,6 +18,7 @@ import {
   SNAPSHOT_PREVIEW_WINDOW_LABEL,
   SNAPSHOT_PREVIEW_WINDOW_STATE_KEY,
 } from '../lib/snapshot_preview';
+import { takePendingSearchOverlayTarget } from '../lib/search_overlay';
 
 function upsertSnapshotPreviewTab(prevTabs, normalized, key) {
   const existingIndex = prevTabs.findIndex((tab) => getSnapshotPreviewKey(tab) === key);
@@ -295,6 +296,25 @@ export default function MainArea({
     };
   }, [onAdvancedSelect, setActiveTab]);
 
+  useEffect(() => {
+    // Results picked in the search overlay; a freshly created main window picks up
+    // the pending one on mount, an existing one when the overlay signals it.
+    const openPendingOverlayResult = () => {
+      const target = takePendingSearchOverlayTarget();
+      if (!target) return;
+      onAdvancedSelect?.(target);
+      setActiveTab('preview');
+    };
+    openPendingOverlayResult();
+    let unlisten;
+    listen('search-overlay-open-main', openPendingOverlayResult).then((fn) => {
+      unlisten = fn;
+    });
+    return () => {
+      if (unlisten) unlisten();
+    };
+  }, [onAdvancedSelect, setActiveTab]);
+
   const openCurrentPreviewInDock = useCallback(() => {
     if (!selectedEvent) return;
     openSnapshotPreview({
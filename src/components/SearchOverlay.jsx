@@ -0,0 +1,210 @@
+import React, { useCallback, useEffect, useRef, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { Loader2, Lock, Search } from 'lucide-react';
+import { useTranslation } from 'react-i18next';
+import { requestAuth, searchScreenshots } from '../lib/monitor_api';
+import { setPendingSearchOverlayTarget } from '../lib/search_overlay';
+
+const RESULT_LIMIT = 12;
+
+function applyThemeFromStorage() {
+  const saved = localStorage.getItem('theme');
+  const prefersDark = window.matchMedia?.('(prefers-color-scheme: dark)').matches;
+  document.documentElement.classList.toggle('dark', saved ? saved === 'dark' : !!prefersDark);
+}
+
+function hideOverlay() {
+  invoke('search_overlay_hide').catch(() => {});
+}
+
+export default function SearchOverlay() {
+  const { t } = useTranslation();
+  const [query, setQuery] = useState('');
+  const [results, setResults] = useState([]);
+  const [selectedIndex, setSelectedIndex] = useState(0);
+  const [loading, setLoading] = useState(false);
+  const [locked, setLocked] = useState(false);
+  const [error, setError] = useState(null);
+  const [searchNonce, setSearchNonce] = useState(0);
+  const inputRef = useRef(null);
+  // The Windows Hello prompt takes focus; the overlay must not hide itself meanwhile.
+  const authPendingRef = useRef(false);
+
+  const reset = useCallback(() => {
+    setQuery('');
+    setResults([]);
+    setSelectedIndex(0);
+    setLocked(false);
+    setError(null);
+  }, []);
+
+  useEffect(() => {
+    applyThemeFromStorage();
+  }, []);
+
+  useEffect(() => {
+    const unlisteners = [
+      listen('search-overlay-shown', () => {
+        applyThemeFromStorage();
+        reset();
+        inputRef.current?.focus();
+      }),
+      listen('search-overlay-hidden', reset),
+    ];
+    const handleBlur = () => {
+      if (!authPendingRef.current) hideOverlay();
+    };
+    window.addEventListener('blur', handleBlur);
+    inputRef.current?.focus();
+    return () => {
+      window.removeEventListener('blur', handleBlur);
+      unlisteners.forEach((promise) => promise.then((fn) => fn()));
+    };
+  }, [reset]);
+
+  useEffect(() => {
+    const trimmed = query.trim();
+    if (!trimmed) {
+      setResults([]);
+      setError(null);
+      return undefined;
+    }
+    let active = true;
+    const timer = setTimeout(async () => {
+      setLoading(true);
+      try {
+        const res = await searchScreenshots(trimmed, 'ocr', { limit: RESULT_LIMIT });
+        if (!active) return;
+        setResults(res);
+        setSelectedIndex(0);
+        setLocked(false);
+        setError(null);
+      } catch (e) {
+        if (!active) return;
+        const message = e?.message || String(e);
+        setResults([]);
+        if (message.includes('AUTH_REQUIRED')) {
+          setLocked(true);
+        } else {
+          setError(message);
+        }
+      } finally {
+        if (active) setLoading(false);
+      }
+    }, 250);
+    return () => {
+      active = false;
+      clearTimeout(timer);
+    };
+  }, [query, searchNonce]);
+
+  const unlock = async () => {
+    authPendingRef.current = true;
+    try {
+      if (await requestAuth()) {
+        setLocked(false);
+        setSearchNonce((n) => n + 1);
+      }
+    } finally {
+      authPendingRef.current = false;
+      inputRef.current?.focus();
+    }
+  };
+
+  const openResult = (item) => {
+    if (!item) return;
+    try {
+      setPendingSearchOverlayTarget(item);
+    } catch (e) {
+      console.error('Failed to hand off search result:', e);
+      return;
+    }
+    invoke('search_overlay_open_main').catch((e) => {
+      console.error('Failed to open main window from search overlay:', e);
+    });
+  };
+
+  const handleKeyDown = (event) => {
+    if (event.key === 'Escape') {
+      event.preventDefault();
+      hideOverlay();
+    } else if (event.key === 'ArrowDown') {
+      event.preventDefault();
+      setSelectedIndex((i) => Math.min(i + 1, Math.max(results.length - 1, 0)));
+    } else if (event.key === 'ArrowUp') {
+      event.preventDefault();
+      setSelectedIndex((i) => Math.max(i - 1, 0));
+    } else if (event.key === 'Enter') {
+      event.preventDefault();
+      if (locked) unlock();
+      else openResult(results[selectedIndex]);
+    }
+  };
+
+  return (
+    <div className="flex h-screen flex-col overflow-hidden rounded-xl border border-ide-border bg-ide-bg text-ide-text shadow-2xl">
+      <div className="flex items-center gap-3 border-b border-ide-border px-4 py-3" data-tauri-drag-region>
+        {loading
+          ? <Loader2 className="h-5 w-5 shrink-0 animate-spin text-ide-muted" />
+          : <Search className="h-5 w-5 shrink-0 text-ide-muted" />}
+        <input
+          ref={inputRef}
+          value={query}
+          onChange={(e) => setQuery(e.target.value)}
+          onKeyDown={handleKeyDown}
+          placeholder={t('searchOverlay.placeholder')}
+          className="min-w-0 flex-1 bg-transparent text-base text-ide-text placeholder:text-ide-muted/60 focus:outline-none"
+          spellCheck={false}
+        />
+      </div>
+
+      <div className="min-h-0 flex-1 overflow-y-auto p-2">
+        {locked ? (
+          <div className="flex h-full flex-col items-center justify-center gap-3 text-sm text-ide-muted">
+            <Lock className="h-6 w-6" />
+            <p>{t('searchOverlay.locked')}</p>
+            <button
+              type="button"
+              onClick={unlock}
+              className="rounded border border-ide-accent/50 bg-ide-accent/15 px-3 py-1.5 text-xs font-medium text-ide-text hover:bg-ide-accent/25"
+            >
+              {t('searchOverlay.unlock')}
+            </button>
+          </div>
+        ) : error ? (
+          <p className="px-3 py-6 text-center text-sm text-red-400">{t('search.searchError', { message: error })}</p>
+        ) : query.trim() && !loading && results.length === 0 ? (
+          <p className="px-3 py-6 text-center text-sm text-ide-muted">{t('search.noContents')}</p>
+        ) : (
+          results.map((item, index) => (
+            <button
+              key={item.id ?? index}
+              type="button"
+              onMouseEnter={() => setSelectedIndex(index)}
+              onClick={() => openResult(item)}
+              className={`block w-full rounded-lg px-3 py-2 text-left ${
+                index === selectedIndex ? 'bg-ide-accent/15' : 'hover:bg-ide-hover'
+              }`}
+            >
+              <div className="flex items-center justify-between gap-3 text-xs text-ide-muted">
+                <span className="truncate">
+                  {item.process_name || t('search.unknownProcess')}
+                  {item.window_title ? ` · ${item.window_title}` : ''}
+                </span>
+                <span className="shrink-0">{item.screenshot_created_at || item.created_at}</span>
+              </div>
+              <p className="mt-0.5 truncate text-sm text-ide-text">
+                {item.text || t('search.noTextContent')}
+              </p>
+            </button>
+          ))
+        )}
+      </div>
+
+      <div className="border-t border-ide-border px-4 py-2 text-[11px] text-ide-muted">
+        {t('searchOverlay.hint')}
+      </div>
+    </div>
+  );
+}
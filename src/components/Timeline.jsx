@@ -219,6 +219,7 @@ const Timeline = ({ onSelectEvent, onClearHighlight, jumpTimestamp, highlightedE
     const fetchEpochRef = useRef(0);
     const [toolbarExpanded, setToolbarExpanded] = useState(false);
     const [densityBuckets, setDensityBuckets] = useState([]);
+    const [offlineMonths, setOfflineMonths] = useState([]);
     const densityEpochRef = useRef(0);
 
     // Initial width detection
@@ -300,47 +301,56 @@ const Timeline = ({ onSelectEvent, onClearHighlight, jumpTimestamp, highlightedE
         const endTime = center + (timeSpan / 2) + (timeSpan * 0.5);
 
         try {
-            const records = await getTimeline(startTime, endTime);
-            if (fetchEpochRef.current !== epoch) return; // stale response, discard
-            const mapped = (records || [])
-                .filter(r => r.timestamp != null) // Filter out records without timestamp
-                .map(r => {
-                    let meta = null;
-                    if (r?.metadata) {
-                        try {
-                            meta = typeof r.metadata === 'string' ? JSON.parse(r.metadata) : r.metadata;
-                        } catch (e) {
-                            meta = null;
+            // Dense periods arrive in pages; merge each one as it loads and stop once
+            // the view has moved on.
+            let cursor = null;
+            do {
+                const { records, nextCursor, offlineMonths: offline } = await getTimeline(startTime, endTime, { cursor });
+                if (fetchEpochRef.current !== epoch) return; // stale response, discard
+                if (!cursor) {
+                    setOfflineMonths(offline || []);
+                }
+                const mapped = records
+                    .filter(r => r.timestamp != null) // Filter out records without timestamp
+                    .map(r => {
+                        let meta = null;
+                        if (r?.metadata) {
+                            try {
+                                meta = typeof r.metadata === 'string' ? JSON.parse(r.metadata) : r.metadata;
+                            } catch (e) {
+                                meta = null;
+                            }
                         }
-                    }
 
-                    return {
-                        id: r.id,
-                        timestamp: r.timestamp ? r.timestamp * 1000 : (r.created_at ? new Date(r.created_at).getTime() : 0),
-                        imagePath: r.image_path,
-                        appName: r.process_name,
-                        windowTitle: r.window_title,
-                        processIcon: r.process_icon || meta?.process_icon || r.page_icon || null,
-                        processPath: r.process_path || meta?.process_path || null,
-                        category: r.category || null,
-                    };
-                })
-                .filter(e => !isNaN(e.timestamp)); // Filter out invalid timestamps
+                        return {
+                            id: r.id,
+                            timestamp: r.timestamp ? r.timestamp * 1000 : (r.created_at ? new Date(r.created_at).getTime() : 0),
+                            imagePath: r.image_path,
+                            appName: r.process_name,
+                            windowTitle: r.window_title,
+                            processIcon: r.process_icon || meta?.process_icon || r.page_icon || null,
+                            processPath: r.process_path || meta?.process_path || null,
+                            category: r.category || null,
+                        };
+                    })
+                    .filter(e => !isNaN(e.timestamp)); // Filter out invalid timestamps
             
-            setEvents(prev => {
-                const combined = [...mapped, ...prev];
-                const seen = new Set();
-                const unique = [];
-                for (const e of combined) {
-                    const key = getEventKey(e);
-                    if (!seen.has(key)) {
-                        seen.add(key);
-                        unique.push(e);
+                setEvents(prev => {
+                    const combined = [...mapped, ...prev];
+                    const seen = new Set();
+                    const unique = [];
+                    for (const e of combined) {
+                        const key = getEventKey(e);
+                        if (!seen.has(key)) {
+                            seen.add(key);
+                            unique.push(e);
+                        }
                     }
-                }
-                const sorted = unique.sort((a, b) => a.timestamp - b.timestamp);
-                return sorted;
-            });
+                    const sorted = unique.sort((a, b) => a.timestamp - b.timestamp);
+                    return sorted;
+                });
+                cursor = nextCursor;
+            } while (cursor);
         } catch (err) {
             console.error('[Timeline] Fetch error:', err);
         }
@@ -900,6 +910,15 @@ const Timeline = ({ onSelectEvent, onClearHighlight, jumpTimestamp, highlightedE
             
             <div className="absolute top-0 bottom-0 left-1/2 w-px bg-ide-accent opacity-50 pointer-events-none z-0"></div>
 
+            {offlineMonths.length > 0 && (
+                <div
+                    className="absolute top-1 left-2 px-2 py-0.5 text-xs rounded border bg-ide-panel text-ide-muted border-ide-border pointer-events-none z-50"
+                    title={t('timeline.offlineMonthsHint')}
+                >
+                    {t('timeline.offlineMonths', { months: offlineMonths.join(', ') })}
+                </div>
+            )}
+
              {/* Timeline Toolbar */}
             <div className="absolute bottom-2 right-2 flex items-center gap-1 z-50">
                 {toolbarExpanded && (
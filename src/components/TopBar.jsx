@@ -3,8 +3,9 @@ import { Moon, Sun, Settings, Bell, Terminal, Minus, Square, X, Copy, Loader2 }
 import { useTranslation } from 'react-i18next';
 import { SearchBox } from './SearchBox';
 import { APP_VERSION } from '../lib/version';
+import { formatCountdown } from '../lib/utils';
 
-function ServiceStatusBadge({ backendStatus, monitorPaused, handleStartBackend, handlePauseMonitor, handleResumeMonitor }) {
+function ServiceStatusBadge({ backendStatus, monitorPaused, pauseRemainingSecs, handleStartBackend, handlePauseMonitor, handleResumeMonitor }) {
   const { t } = useTranslation();
 
   let dotColor, label, onClick, disabled = false, showSpinner = false;
@@ -12,11 +13,13 @@ function ServiceStatusBadge({ backendStatus, monitorPaused, handleStartBackend,
   if (backendStatus === 'online' && !monitorPaused) {
     dotColor = 'bg-green-500';
     label = t('topbar.service.running');
-    onClick = handlePauseMonitor;
+    onClick = () => handlePauseMonitor();
   } else if (backendStatus === 'online' && monitorPaused) {
     dotColor = 'bg-yellow-500';
-    label = t('topbar.service.paused');
-    onClick = handleResumeMonitor;
+    label = pauseRemainingSecs != null
+      ? t('topbar.service.pausedFor', { time: formatCountdown(pauseRemainingSecs) })
+      : t('topbar.service.paused');
+    onClick = () => handleResumeMonitor();
   } else if (backendStatus === 'waiting') {
     dotColor = 'bg-orange-500';
     label = t('topbar.service.starting');
@@ -61,6 +64,7 @@ export default function TopBar({
   onSearchModeChange,
   backendStatus,
   monitorPaused,
+  pauseRemainingSecs,
   handleStartBackend,
   handlePauseMonitor,
   handleResumeMonitor,
@@ -95,6 +99,7 @@ export default function TopBar({
         <ServiceStatusBadge
           backendStatus={backendStatus}
           monitorPaused={monitorPaused}
+          pauseRemainingSecs={pauseRemainingSecs}
           handleStartBackend={handleStartBackend}
           handlePauseMonitor={handlePauseMonitor}
           handleResumeMonitor={handleResumeMonitor}
@@ -4,9 +4,11 @@ import AdvancedWarning from './advanced/AdvancedWarning';
 import ClusteringTechnicalCard from './advanced/ClusteringTechnicalCard';
 import CpuLimitCard from './advanced/CpuLimitCard';
 import DatabaseMaintenanceCard from './advanced/DatabaseMaintenanceCard';
-import { DmlAccelerationCard, OcrEngineCard, OnnxRuntimeCard } from './advanced/InferenceCards';
+import { DmlAccelerationCard, NpuAccelerationCard, OcrEngineCard, OnnxRuntimeCard } from './advanced/InferenceCards';
+import CaptureCadenceCard from './advanced/CaptureCadenceCard';
 import NetworkAccessCard from './advanced/NetworkAccessCard';
 import OcrQueueCard from './advanced/OcrQueueCard';
+import MonitorMetricsCard from './advanced/MonitorMetricsCard';
 import { useAdvancedSectionController } from './useAdvancedSectionController';
 
 export default function AdvancedSection({ monitorStatus, onRestartMonitor }) {
@@ -17,11 +19,14 @@ export default function AdvancedSection({ monitorStatus, onRestartMonitor }) {
     cpuDropdownOpen,
     gpuDropdownOpen,
     clusteringDropdownOpen,
-    cpuChanged,
     dmlChanged,
+    npuChanged,
     onnxChanged,
+    ocrQueueChanged,
     gpus,
     gpuLoading,
+    npus,
+    npuLoading,
     vacuumRunning,
     vacuumMessage,
     selectedGpu,
@@ -32,14 +37,24 @@ export default function AdvancedSection({ monitorStatus, onRestartMonitor }) {
     setCpuDropdownOpen,
     setGpuDropdownOpen,
     setClusteringDropdownOpen,
-    clearCpuChanged,
     clearDmlChanged,
+    clearNpuChanged,
     clearOnnxChanged,
+    clearOcrQueueChanged,
     handleToggle,
     handleCpuPercentChange,
+    handleMemoryLimitChange,
     handleOcrTimeoutDraftChange,
     handleOcrTimeoutChange,
+    handleOcrQueueSizeChange,
+    handleOcrQueuePolicyChange,
+    handleCaptureDraftChange,
+    handleCaptureIntervalChange,
+    handleCaptureThresholdChange,
+    handleDmlIdleThresholdChange,
+    handleUiaProcessesChange,
     handleGpuChange,
+    handleNpuProviderChange,
     handleClusteringIntervalChange,
     handleManualVacuum,
     handleRestartMlOcr,
@@ -60,22 +75,36 @@ export default function AdvancedSection({ monitorStatus, onRestartMonitor }) {
 
       <CpuLimitCard
         config={config}
-        monitorStatus={monitorStatus}
         cpuDropdownOpen={cpuDropdownOpen}
-        cpuChanged={cpuChanged}
         onToggle={handleToggle}
         onToggleDropdown={() => setCpuDropdownOpen(!cpuDropdownOpen)}
         onPercentChange={handleCpuPercentChange}
-        onRestartMonitor={onRestartMonitor}
-        onClearChanged={clearCpuChanged}
+        onDraftChange={handleCaptureDraftChange}
+        onMemoryLimitChange={handleMemoryLimitChange}
+      />
+
+      <CaptureCadenceCard
+        config={config}
+        onDraftChange={handleCaptureDraftChange}
+        onIntervalChange={handleCaptureIntervalChange}
+        onThresholdChange={handleCaptureThresholdChange}
       />
 
       <OcrQueueCard
         config={config}
+        monitorStatus={monitorStatus}
+        queueChanged={ocrQueueChanged}
         onOcrTimeoutDraftChange={handleOcrTimeoutDraftChange}
         onOcrTimeoutChange={handleOcrTimeoutChange}
+        onDraftChange={handleCaptureDraftChange}
+        onQueueSizeChange={handleOcrQueueSizeChange}
+        onQueuePolicyChange={handleOcrQueuePolicyChange}
+        onRestartMonitor={onRestartMonitor}
+        onClearChanged={clearOcrQueueChanged}
       />
 
+      <MonitorMetricsCard />
+
       <OcrEngineCard
         config={config}
         status={mlOcrStatus}
@@ -85,6 +114,7 @@ export default function AdvancedSection({ monitorStatus, onRestartMonitor }) {
         onToggle={handleToggle}
         onRestart={handleRestartMlOcr}
         onDownloadModel={handleDownloadRustOcrModel}
+        onUiaProcessesChange={handleUiaProcessesChange}
       />
 
       <DmlAccelerationCard
@@ -98,10 +128,24 @@ export default function AdvancedSection({ monitorStatus, onRestartMonitor }) {
         onToggle={handleToggle}
         onToggleGpuDropdown={() => setGpuDropdownOpen(!gpuDropdownOpen)}
         onGpuChange={handleGpuChange}
+        onDraftChange={handleCaptureDraftChange}
+        onIdleThresholdChange={handleDmlIdleThresholdChange}
         onRestartMonitor={onRestartMonitor}
         onClearChanged={clearDmlChanged}
       />
 
+      <NpuAccelerationCard
+        config={config}
+        monitorStatus={monitorStatus}
+        npuChanged={npuChanged}
+        npus={npus}
+        npuLoading={npuLoading}
+        onToggle={handleToggle}
+        onProviderChange={handleNpuProviderChange}
+        onRestartMonitor={onRestartMonitor}
+        onClearChanged={clearNpuChanged}
+      />
+
       <OnnxRuntimeCard
         config={config}
         monitorStatus={monitorStatus}
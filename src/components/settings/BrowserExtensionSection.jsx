@@ -9,6 +9,7 @@ export default function BrowserExtensionSection() {
   const { t } = useTranslation();
   const [status, setStatus] = useState({ chrome: false, edge: false });
   const [enhanceEnabled, setEnhanceEnabled] = useState(false);
+  const [urlCaptureEnabled, setUrlCaptureEnabled] = useState(false);
   const [sessions, setSessions] = useState([]);
   const [installing, setInstalling] = useState(null); // 'chrome' | 'edge' | null
   const [message, setMessage] = useState('');
@@ -32,6 +33,15 @@ export default function BrowserExtensionSection() {
     }
   };
 
+  const loadUrlCaptureConfig = async () => {
+    try {
+      const result = await invoke('get_browser_url_capture_config');
+      setUrlCaptureEnabled(Boolean(result?.enabled));
+    } catch (e) {
+      console.error('Failed to load browser URL capture config:', e);
+    }
+  };
+
   const loadSessions = async () => {
     try {
       const result = await invoke('get_nmh_sessions');
@@ -44,6 +54,7 @@ export default function BrowserExtensionSection() {
   useEffect(() => {
     checkStatus();
     loadEnhanceConfig();
+    loadUrlCaptureConfig();
     loadSessions();
     const timer = setInterval(loadSessions, 5000);
     return () => clearInterval(timer);
@@ -74,6 +85,15 @@ export default function BrowserExtensionSection() {
     }
   };
 
+  const handleUrlCaptureToggle = async (enabled) => {
+    try {
+      await withAuth(() => invoke('set_browser_url_capture', { enabled }), { autoPrompt: true });
+      setUrlCaptureEnabled(enabled);
+    } catch (e) {
+      console.error('Failed to set browser URL capture:', e);
+    }
+  };
+
   const renderBrowser = (browser) => (
     <div className="px-4 py-3 rounded-lg bg-ide-panel border border-ide-border">
       <div className="flex items-center justify-between">
@@ -133,6 +153,21 @@ export default function BrowserExtensionSection() {
         )}
       </div>
 
+      {/* Address-bar URL capture for browsers without the extension */}
+      <div className="px-4 py-3 rounded-lg bg-ide-panel border border-ide-border space-y-2">
+        <div className="flex items-center justify-between">
+          <span className="text-sm font-medium">{t('settings.extension.urlCapture.label')}</span>
+          <SettingsSwitch
+            checked={urlCaptureEnabled}
+            onChange={handleUrlCaptureToggle}
+            title={t('settings.extension.urlCapture.label')}
+          />
+        </div>
+        <p className="text-xs text-ide-text-secondary">
+          {t('settings.extension.urlCapture.description')}
+        </p>
+      </div>
+
       {/* Live connected-browser sessions */}
       <div className="px-4 py-3 rounded-lg bg-ide-panel border border-ide-border space-y-2">
         <div className="flex items-center gap-2">
@@ -14,6 +14,42 @@ export default function CaptureFiltersSection({
   onRemoveProcess,
   onRemoveTitle,
   onToggleProtected,
+  screenSharePause,
+  onToggleScreenSharePause,
+  clipboardHistory,
+  clipboardProcessInput,
+  clipboardMessage,
+  onClipboardProcessInputChange,
+  onClipboardHistoryChange,
+  onAddClipboardProcess,
+  onRemoveClipboardProcess,
+  onClearClipboardHistory,
+  audioCapture,
+  audioMessage,
+  onAudioCaptureChange,
+  captureSchedule,
+  scheduleMessage,
+  onScheduleChange,
+  regionDraft,
+  regionError,
+  onRegionDraftChange,
+  onAddRegion,
+  onRemoveRegion,
+  titleRules,
+  titleRuleInput,
+  titleRuleMessage,
+  onTitleRuleInputChange,
+  onAddTitleRule,
+  onRemoveTitleRule,
+  allowedInput,
+  onAllowedInputChange,
+  onAddAllowed,
+  onRemoveAllowed,
+  onToggleAllowlist,
+  ocrSkipInput,
+  onOcrSkipInputChange,
+  onAddOcrSkip,
+  onRemoveOcrSkip,
   onSave,
   filtersDirty,
   savingFilters,
@@ -29,6 +65,17 @@ export default function CaptureFiltersSection({
     { id: '1hour', minutes: 60, key: '1hour' },
     { id: 'today', minutes: 'today', key: 'today' },
   ].map((opt) => ({ ...opt, label: t(`settings.captureFilters.quickDelete.options.${opt.key}`) }));
+  const scheduleDays = t('settings.captureFilters.schedule.days', { returnObjects: true });
+  const scheduleWindows = captureSchedule?.windows || [];
+  const updateScheduleWindow = (index, patch) => onScheduleChange({
+    ...captureSchedule,
+    windows: scheduleWindows.map((w, i) => (i === index ? { ...w, ...patch } : w)),
+  });
+  const toggleScheduleDay = (index, day) => {
+    const days = scheduleWindows[index].days || [];
+    const next = days.includes(day) ? days.filter((d) => d !== day) : [...days, day].sort();
+    if (next.length) updateScheduleWindow(index, { days: next });
+  };
 
   return (
     <div className="space-y-8">
@@ -124,6 +171,356 @@ export default function CaptureFiltersSection({
               </div>
               <p className="text-xs text-ide-muted mt-2 ml-1">{t('settings.captureFilters.titles.hint')}</p>
             </div>
+
+            <div className="w-full h-px bg-ide-border/50" />
+
+            <div>
+              <label className="block mb-2 font-semibold text-ide-text">{t('settings.captureFilters.titleRules.label')}</label>
+              <div className="flex flex-wrap gap-2 mb-3 min-h-[1.5rem]">
+                {(titleRules || []).map((rule) => (
+                  <span
+                    key={rule}
+                    className="inline-flex items-center gap-1.5 pl-2.5 pr-1.5 py-1 bg-ide-panel border border-ide-border rounded-full text-xs font-mono text-ide-text group"
+                  >
+                    {rule}
+                    <button onClick={() => onRemoveTitleRule(rule)} className="p-0.5 rounded-full hover:bg-ide-hover text-ide-muted hover:text-red-400 transition-colors" title={t('settings.captureFilters.remove')}>
+                      <X className="w-3 h-3" />
+                    </button>
+                  </span>
+                ))}
+                {(titleRules || []).length === 0 && <span className="text-xs text-ide-muted py-1 italic">{t('settings.captureFilters.empty')}</span>}
+              </div>
+              <div className="flex gap-2">
+                <input
+                  className="flex-1 bg-ide-bg border border-ide-border rounded-lg px-3 py-2 text-xs font-mono text-ide-text focus:outline-none focus:border-ide-accent focus:ring-1 focus:ring-ide-accent placeholder:text-ide-muted/50"
+                  value={titleRuleInput}
+                  onChange={(e) => onTitleRuleInputChange(e.target.value)}
+                  onKeyDown={(e) => {
+                    if (e.key === 'Enter') {
+                      e.preventDefault();
+                      onAddTitleRule();
+                    }
+                  }}
+                  placeholder={t('settings.captureFilters.titleRules.placeholder')}
+                />
+                <button
+                  onClick={onAddTitleRule}
+                  disabled={!titleRuleInput.trim()}
+                  className="px-4 py-2 bg-ide-accent hover:bg-ide-accent/90 text-white rounded-lg text-xs font-medium transition-colors disabled:opacity-50 disabled:cursor-not-allowed"
+                >
+                  {t('settings.captureFilters.add')}
+                </button>
+              </div>
+              <p className="text-xs text-ide-muted mt-2 ml-1">{t('settings.captureFilters.titleRules.hint')}</p>
+              {titleRuleMessage && <p className="text-xs text-red-500 mt-1 ml-1">{titleRuleMessage}</p>}
+            </div>
+          </div>
+
+          <div className="w-full h-px bg-ide-border/50" />
+
+          <div>
+            <label className="block mb-2 font-semibold text-ide-text">{t('settings.captureFilters.redaction.label')}</label>
+            <div className="space-y-2 mb-3">
+              {(filterSettings.redactionRegions || []).map((region, index) => (
+                <div
+                  key={`${region.scope}-${region.x}-${region.y}-${index}`}
+                  className="flex items-center justify-between gap-2 px-3 py-1.5 bg-ide-panel border border-ide-border rounded-lg text-xs text-ide-text"
+                >
+                  <span className="font-mono">
+                    {t(`settings.captureFilters.redaction.scope.${region.scope}`)} · ({region.x}, {region.y}) {region.width}×{region.height} · {t(`settings.captureFilters.redaction.mode.${region.mode}`)} · {region.process || t('settings.captureFilters.redaction.allProcesses')}
+                  </span>
+                  <button onClick={() => onRemoveRegion(index)} className="p-0.5 rounded-full hover:bg-ide-hover text-ide-muted hover:text-red-400 transition-colors" title={t('settings.captureFilters.remove')}>
+                    <X className="w-3 h-3" />
+                  </button>
+                </div>
+              ))}
+              {(filterSettings.redactionRegions || []).length === 0 && <span className="text-xs text-ide-muted py-1 italic">{t('settings.captureFilters.empty')}</span>}
+            </div>
+            <div className="flex flex-wrap gap-2">
+              <select
+                className="bg-ide-bg border border-ide-border rounded-lg px-2 py-2 text-xs text-ide-text focus:outline-none focus:border-ide-accent focus:ring-1 focus:ring-ide-accent placeholder:text-ide-muted/50"
+                value={regionDraft.scope}
+                onChange={(e) => onRegionDraftChange({ ...regionDraft, scope: e.target.value })}
+              >
+                <option value="screen">{t('settings.captureFilters.redaction.scope.screen')}</option>
+                <option value="window">{t('settings.captureFilters.redaction.scope.window')}</option>
+              </select>
+              {['x', 'y', 'width', 'height'].map((field) => (
+                <input
+                  key={field}
+                  type="number"
+                  className="w-20 bg-ide-bg border border-ide-border rounded-lg px-2 py-2 text-xs text-ide-text focus:outline-none focus:border-ide-accent focus:ring-1 focus:ring-ide-accent placeholder:text-ide-muted/50"
+                  value={regionDraft[field]}
+                  onChange={(e) => onRegionDraftChange({ ...regionDraft, [field]: e.target.value })}
+                  placeholder={field}
+                />
+              ))}
+              <select
+                className="bg-ide-bg border border-ide-border rounded-lg px-2 py-2 text-xs text-ide-text focus:outline-none focus:border-ide-accent focus:ring-1 focus:ring-ide-accent placeholder:text-ide-muted/50"
+                value={regionDraft.mode}
+                onChange={(e) => onRegionDraftChange({ ...regionDraft, mode: e.target.value })}
+              >
+                <option value="blackout">{t('settings.captureFilters.redaction.mode.blackout')}</option>
+                <option value="blur">{t('settings.captureFilters.redaction.mode.blur')}</option>
+              </select>
+              <input
+                className="flex-1 min-w-[8rem] bg-ide-bg border border-ide-border rounded-lg px-2 py-2 text-xs text-ide-text focus:outline-none focus:border-ide-accent focus:ring-1 focus:ring-ide-accent placeholder:text-ide-muted/50"
+                value={regionDraft.process}
+                onChange={(e) => onRegionDraftChange({ ...regionDraft, process: e.target.value })}
+                placeholder={t('settings.captureFilters.redaction.process')}
+              />
+              <button
+                onClick={onAddRegion}
+                className="px-4 py-2 bg-ide-accent hover:bg-ide-accent/90 text-white rounded-lg text-xs font-medium transition-colors"
+              >
+                {t('settings.captureFilters.add')}
+              </button>
+            </div>
+            <p className="text-xs text-ide-muted mt-2 ml-1">{t('settings.captureFilters.redaction.hint')}</p>
+            {regionError && <p className="text-xs text-red-500 mt-1 ml-1">{regionError}</p>}
+          </div>
+
+          <div className="w-full h-px bg-ide-border/50" />
+
+          <div className="flex items-center justify-between gap-4">
+            <div>
+              <label className="block mb-1 font-semibold text-ide-text">{t('settings.captureFilters.screenShare.label')}</label>
+              <p className="text-xs text-ide-muted">{t('settings.captureFilters.screenShare.description')}</p>
+              {screenSharePause?.enabled && screenSharePause?.paused && (
+                <p className="text-xs mt-1 text-ide-warning">{t('settings.captureFilters.screenShare.active')}</p>
+              )}
+            </div>
+            <SettingsSwitch
+              checked={Boolean(screenSharePause?.enabled)}
+              onChange={onToggleScreenSharePause}
+            />
+          </div>
+
+          <div className="w-full h-px bg-ide-border/50" />
+
+          <div className="space-y-3">
+            <div className="flex items-center justify-between gap-4">
+              <div>
+                <label className="block mb-1 font-semibold text-ide-text">{t('settings.captureFilters.clipboard.label')}</label>
+                <p className="text-xs text-ide-muted">{t('settings.captureFilters.clipboard.description')}</p>
+              </div>
+              <SettingsSwitch
+                checked={Boolean(clipboardHistory?.enabled)}
+                onChange={(enabled) => onClipboardHistoryChange({ enabled })}
+              />
+            </div>
+            {clipboardHistory?.enabled && (
+              <div className="space-y-3">
+                <div className="flex items-center gap-2">
+                  <span className="text-xs text-ide-text">{t('settings.captureFilters.clipboard.retention')}</span>
+                  <input
+                    type="number"
+                    min={0}
+                    max={3650}
+                    className="w-20 bg-ide-bg border border-ide-border rounded-lg px-2 py-1 text-xs text-ide-text focus:outline-none focus:border-ide-accent"
+                    value={clipboardHistory.retention_days ?? 30}
+                    onChange={(e) => onClipboardHistoryChange({
+                      retention_days: Math.min(3650, Math.max(0, parseInt(e.target.value, 10) || 0)),
+                    })}
+                  />
+                  <span className="text-xs text-ide-muted">{t('settings.captureFilters.clipboard.retentionHint')}</span>
+                </div>
+                <div className="flex flex-wrap gap-2 min-h-[1.5rem]">
+                  {(clipboardHistory.excluded_processes || []).map((p) => (
+                    <span
+                      key={p}
+                      className="inline-flex items-center gap-1.5 pl-2.5 pr-1.5 py-1 bg-ide-panel border border-ide-border rounded-full text-xs text-ide-text group"
+                    >
+                      {p}
+                      <button onClick={() => onRemoveClipboardProcess(p)} className="p-0.5 rounded-full hover:bg-ide-hover text-ide-muted hover:text-red-400 transition-colors" title={t('settings.captureFilters.remove')}>
+                        <X className="w-3 h-3" />
+                      </button>
+                    </span>
+                  ))}
+                  {(clipboardHistory.excluded_processes || []).length === 0 && <span className="text-xs text-ide-muted py-1 italic">{t('settings.captureFilters.empty')}</span>}
+                </div>
+                <div className="flex gap-2">
+                  <input
+                    className="flex-1 bg-ide-bg border border-ide-border rounded-lg px-3 py-2 text-xs text-ide-text focus:outline-none focus:border-ide-accent focus:ring-1 focus:ring-ide-accent placeholder:text-ide-muted/50"
+                    value={clipboardProcessInput}
+                    onChange={(e) => onClipboardProcessInputChange(e.target.value)}
+                    onKeyDown={(e) => {
+                      if (e.key === 'Enter' || e.key === ',') {
+                        e.preventDefault();
+                        onAddClipboardProcess();
+                      }
+                    }}
+                    placeholder={t('settings.captureFilters.clipboard.processPlaceholder')}
+                  />
+                  <button
+                    onClick={onAddClipboardProcess}
+                    disabled={!clipboardProcessInput.trim()}
+                    className="px-4 py-2 bg-ide-accent hover:bg-ide-accent/90 text-white rounded-lg text-xs font-medium transition-colors disabled:opacity-50 disabled:cursor-not-allowed"
+                  >
+                    {t('settings.captureFilters.add')}
+                  </button>
+                </div>
+                <p className="text-xs text-ide-muted ml-1">{t('settings.captureFilters.clipboard.processHint')}</p>
+                <button
+                  onClick={onClearClipboardHistory}
+                  className="inline-flex items-center gap-1.5 px-3 py-1.5 border border-ide-border rounded-lg text-xs text-ide-text hover:text-red-400 hover:bg-ide-hover transition-colors"
+                >
+                  <Trash2 className="w-3 h-3" />
+                  {t('settings.captureFilters.clipboard.clear')}
+                </button>
+              </div>
+            )}
+            {clipboardMessage && <p className="text-xs text-ide-muted ml-1">{clipboardMessage}</p>}
+          </div>
+
+          <div className="w-full h-px bg-ide-border/50" />
+
+          <div className="space-y-3">
+            <div className="flex items-center justify-between gap-4">
+              <div>
+                <label className="block mb-1 font-semibold text-ide-text">{t('settings.captureFilters.audio.label')}</label>
+                <p className="text-xs text-ide-muted">{t('settings.captureFilters.audio.description')}</p>
+              </div>
+              <SettingsSwitch
+                checked={Boolean(audioCapture?.enabled)}
+                onChange={(enabled) => onAudioCaptureChange({ enabled })}
+              />
+            </div>
+            {audioCapture?.enabled && (
+              <div className="grid grid-cols-2 gap-3">
+                <label className="flex items-center gap-2 text-xs text-ide-text">
+                  {t('settings.captureFilters.audio.source')}
+                  <select
+                    className="bg-ide-bg border border-ide-border rounded-lg px-2 py-1 text-xs text-ide-text focus:outline-none focus:border-ide-accent"
+                    value={audioCapture.source}
+                    onChange={(e) => onAudioCaptureChange({ source: e.target.value })}
+                  >
+                    <option value="system">{t('settings.captureFilters.audio.sources.system')}</option>
+                    <option value="microphone">{t('settings.captureFilters.audio.sources.microphone')}</option>
+                    <option value="both">{t('settings.captureFilters.audio.sources.both')}</option>
+                  </select>
+                </label>
+                <label className="flex items-center gap-2 text-xs text-ide-text">
+                  {t('settings.captureFilters.audio.model')}
+                  <select
+                    className="bg-ide-bg border border-ide-border rounded-lg px-2 py-1 text-xs text-ide-text focus:outline-none focus:border-ide-accent"
+                    value={audioCapture.model}
+                    onChange={(e) => onAudioCaptureChange({ model: e.target.value })}
+                  >
+                    {['tiny', 'base', 'small', 'medium'].map((model) => (
+                      <option key={model} value={model}>{model}</option>
+                    ))}
+                  </select>
+                </label>
+                <label className="flex items-center gap-2 text-xs text-ide-text">
+                  {t('settings.captureFilters.audio.chunk')}
+                  <input
+                    type="number"
+                    min={10}
+                    max={300}
+                    className="w-20 bg-ide-bg border border-ide-border rounded-lg px-2 py-1 text-xs text-ide-text focus:outline-none focus:border-ide-accent"
+                    value={audioCapture.chunk_secs ?? 30}
+                    onChange={(e) => onAudioCaptureChange({
+                      chunk_secs: Math.min(300, Math.max(10, parseInt(e.target.value, 10) || 30)),
+                    })}
+                  />
+                </label>
+                <label className="flex items-center gap-2 text-xs text-ide-text">
+                  {t('settings.captureFilters.audio.language')}
+                  <input
+                    className="w-20 bg-ide-bg border border-ide-border rounded-lg px-2 py-1 text-xs text-ide-text focus:outline-none focus:border-ide-accent placeholder:text-ide-muted/50"
+                    value={audioCapture.language ?? ''}
+                    onChange={(e) => onAudioCaptureChange({ language: e.target.value })}
+                    placeholder={t('settings.captureFilters.audio.languageAuto')}
+                  />
+                </label>
+                <div className="col-span-2 flex items-center gap-2">
+                  <span className="text-xs text-ide-text">{t('settings.captureFilters.audio.retention')}</span>
+                  <input
+                    type="number"
+                    min={0}
+                    max={3650}
+                    className="w-20 bg-ide-bg border border-ide-border rounded-lg px-2 py-1 text-xs text-ide-text focus:outline-none focus:border-ide-accent"
+                    value={audioCapture.retention_days ?? 30}
+                    onChange={(e) => onAudioCaptureChange({
+                      retention_days: Math.min(3650, Math.max(0, parseInt(e.target.value, 10) || 0)),
+                    })}
+                  />
+                  <span className="text-xs text-ide-muted">{t('settings.captureFilters.clipboard.retentionHint')}</span>
+                </div>
+                <p className="col-span-2 text-xs text-ide-muted ml-1">{t('settings.captureFilters.audio.hint')}</p>
+              </div>
+            )}
+            {audioMessage && <p className="text-xs text-ide-muted ml-1">{audioMessage}</p>}
+          </div>
+
+          <div className="w-full h-px bg-ide-border/50" />
+
+          <div className="space-y-3">
+            <div className="flex items-center justify-between gap-4">
+              <div>
+                <label className="block mb-1 font-semibold text-ide-text">{t('settings.captureFilters.schedule.label')}</label>
+                <p className="text-xs text-ide-muted">{t('settings.captureFilters.schedule.description')}</p>
+                {captureSchedule?.enabled && captureSchedule?.paused && (
+                  <p className="text-xs mt-1 text-ide-warning">{t('settings.captureFilters.schedule.active')}</p>
+                )}
+              </div>
+              <SettingsSwitch
+                checked={Boolean(captureSchedule?.enabled)}
+                onChange={(enabled) => onScheduleChange({ ...captureSchedule, enabled })}
+              />
+            </div>
+            {captureSchedule?.enabled && (
+              <>
+                {scheduleWindows.map((slot, index) => (
+                  <div key={index} className="flex flex-wrap items-center gap-2">
+                    {Array.isArray(scheduleDays) && scheduleDays.map((label, day) => (
+                      <button
+                        key={day}
+                        onClick={() => toggleScheduleDay(index, day)}
+                        className={`px-2 py-1 rounded-md text-xs border transition-colors ${
+                          (slot.days || []).includes(day)
+                            ? 'bg-ide-accent/10 text-ide-accent border-ide-accent/20'
+                            : 'bg-ide-bg text-ide-muted border-ide-border'
+                        }`}
+                      >
+                        {label}
+                      </button>
+                    ))}
+                    <input
+                      type="time"
+                      className="bg-ide-bg border border-ide-border rounded-lg px-2 py-1 text-xs focus:border-ide-accent focus:outline-none"
+                      value={slot.start}
+                      onChange={(e) => e.target.value && updateScheduleWindow(index, { start: e.target.value })}
+                    />
+                    <span className="text-xs text-ide-muted">–</span>
+                    <input
+                      type="time"
+                      className="bg-ide-bg border border-ide-border rounded-lg px-2 py-1 text-xs focus:border-ide-accent focus:outline-none"
+                      value={slot.end}
+                      onChange={(e) => e.target.value && updateScheduleWindow(index, { end: e.target.value })}
+                    />
+                    <button
+                      onClick={() => onScheduleChange({ ...captureSchedule, windows: scheduleWindows.filter((_, i) => i !== index) })}
+                      className="hover:text-red-500 text-ide-muted transition-colors"
+                    >
+                      <X className="w-3 h-3" />
+                    </button>
+                  </div>
+                ))}
+                <button
+                  onClick={() => onScheduleChange({
+                    ...captureSchedule,
+                    windows: [...scheduleWindows, { days: [0, 1, 2, 3, 4], start: '09:00', end: '18:00' }],
+                  })}
+                  className="px-4 py-2 bg-ide-accent hover:bg-ide-accent/90 text-white rounded-lg text-xs font-medium transition-colors"
+                >
+                  {t('settings.captureFilters.schedule.addWindow')}
+                </button>
+                <p className="text-xs text-ide-muted ml-1">{t('settings.captureFilters.schedule.hint')}</p>
+              </>
+            )}
+            {scheduleMessage && <p className="text-xs text-red-500 ml-1">{scheduleMessage}</p>}
           </div>
 
           <div className="w-full h-px bg-ide-border/50" />
@@ -139,6 +536,102 @@ export default function CaptureFiltersSection({
             />
           </div>
 
+          <div className="w-full h-px bg-ide-border/50" />
+
+          <div className="space-y-3">
+            <div className="flex items-center justify-between gap-4">
+              <div>
+                <label className="block mb-1 font-semibold text-ide-text">{t('settings.captureFilters.allowlist.label')}</label>
+                <p className="text-xs text-ide-muted">{t('settings.captureFilters.allowlist.description')}</p>
+              </div>
+              <SettingsSwitch
+                checked={Boolean(filterSettings.allowlistEnabled)}
+                onChange={onToggleAllowlist}
+              />
+            </div>
+            {filterSettings.allowlistEnabled && (
+              <>
+                <div className="flex flex-wrap gap-2 min-h-[1.5rem]">
+                  {(filterSettings.allowedProcesses || []).map((p) => (
+                    <span
+                      key={p}
+                      className="inline-flex items-center gap-1.5 pl-2.5 pr-1.5 py-1 bg-ide-panel border border-ide-border rounded-full text-xs text-ide-text group"
+                    >
+                      {p}
+                      <button onClick={() => onRemoveAllowed(p)} className="p-0.5 rounded-full hover:bg-ide-hover text-ide-muted hover:text-red-400 transition-colors" title={t('settings.captureFilters.remove')}>
+                        <X className="w-3 h-3" />
+                      </button>
+                    </span>
+                  ))}
+                  {(filterSettings.allowedProcesses || []).length === 0 && <span className="text-xs text-ide-muted py-1 italic">{t('settings.captureFilters.empty')}</span>}
+                </div>
+                <div className="flex gap-2">
+                  <input
+                    className="flex-1 bg-ide-bg border border-ide-border rounded-lg px-3 py-2 text-xs text-ide-text focus:outline-none focus:border-ide-accent focus:ring-1 focus:ring-ide-accent placeholder:text-ide-muted/50"
+                    value={allowedInput}
+                    onChange={(e) => onAllowedInputChange(e.target.value)}
+                    onKeyDown={(e) => {
+                      if (e.key === 'Enter' || e.key === ',') {
+                        e.preventDefault();
+                        onAddAllowed();
+                      }
+                    }}
+                    placeholder={t('settings.captureFilters.allowlist.placeholder')}
+                  />
+                  <button
+                    onClick={onAddAllowed}
+                    disabled={!allowedInput.trim()}
+                    className="px-4 py-2 bg-ide-accent hover:bg-ide-accent/90 text-white rounded-lg text-xs font-medium transition-colors disabled:opacity-50 disabled:cursor-not-allowed"
+                  >
+                    {t('settings.captureFilters.add')}
+                  </button>
+                </div>
+              </>
+            )}
+          </div>
+
+          <div className="w-full h-px bg-ide-border/50" />
+
+          <div>
+            <label className="block mb-1 font-semibold text-ide-text">{t('settings.captureFilters.ocrSkip.label')}</label>
+            <p className="text-xs text-ide-muted mb-2">{t('settings.captureFilters.ocrSkip.description')}</p>
+            <div className="flex flex-wrap gap-2 mb-3 min-h-[1.5rem]">
+              {(filterSettings.ocrSkipProcesses || []).map((p) => (
+                <span
+                  key={p}
+                  className="inline-flex items-center gap-1.5 pl-2.5 pr-1.5 py-1 bg-ide-panel border border-ide-border rounded-full text-xs text-ide-text group"
+                >
+                  {p}
+                  <button onClick={() => onRemoveOcrSkip(p)} className="p-0.5 rounded-full hover:bg-ide-hover text-ide-muted hover:text-red-400 transition-colors" title={t('settings.captureFilters.remove')}>
+                    <X className="w-3 h-3" />
+                  </button>
+                </span>
+              ))}
+              {(filterSettings.ocrSkipProcesses || []).length === 0 && <span className="text-xs text-ide-muted py-1 italic">{t('settings.captureFilters.empty')}</span>}
+            </div>
+            <div className="flex gap-2">
+              <input
+                className="flex-1 bg-ide-bg border border-ide-border rounded-lg px-3 py-2 text-xs text-ide-text focus:outline-none focus:border-ide-accent focus:ring-1 focus:ring-ide-accent placeholder:text-ide-muted/50"
+                value={ocrSkipInput}
+                onChange={(e) => onOcrSkipInputChange(e.target.value)}
+                onKeyDown={(e) => {
+                  if (e.key === 'Enter' || e.key === ',') {
+                    e.preventDefault();
+                    onAddOcrSkip();
+                  }
+                }}
+                placeholder={t('settings.captureFilters.ocrSkip.placeholder')}
+              />
+              <button
+                onClick={onAddOcrSkip}
+                disabled={!ocrSkipInput.trim()}
+                className="px-4 py-2 bg-ide-accent hover:bg-ide-accent/90 text-white rounded-lg text-xs font-medium transition-colors disabled:opacity-50 disabled:cursor-not-allowed"
+              >
+                {t('settings.captureFilters.add')}
+              </button>
+            </div>
+          </div>
+
           <div className="flex items-center justify-between gap-3 pt-2">
             <div className="text-xs text-ide-muted">{saveFiltersMessage}</div>
             <button
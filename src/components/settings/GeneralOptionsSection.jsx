@@ -2,6 +2,7 @@ import React from 'react';
 import { useTranslation } from 'react-i18next';
 import { SettingsSwitch } from './SettingsControls';
 import CardClickBehaviorCard from './general/CardClickBehaviorCard';
+import HotkeysCard from './general/HotkeysCard';
 import ResourcePolicyCard from './general/ResourcePolicyCard';
 import WindowBehaviorCard from './general/WindowBehaviorCard';
 import { useGeneralOptionsController } from './useGeneralOptionsController';
@@ -21,8 +22,11 @@ export default function GeneralOptionsSection({
     fullscreenPaused,
     useDml,
     gameModeLoading,
+    gameModeConfig,
     resourcePolicyLoading,
     lightweightConfig,
+    hotkeys,
+    hotkeyError,
     cardClickBehaviorSearch,
     cardClickBehaviorClusters,
     cardClickBehaviorActivityContext,
@@ -31,9 +35,11 @@ export default function GeneralOptionsSection({
     selectedResourcePolicy,
     handleSetPowerSaving,
     handleSetGameMode,
+    handleGameModeConfigChange,
     handleResourcePolicyChange,
     handleLightweightConfigChange,
     handleSwitchToLightweight,
+    handleHotkeyChange,
     setCardClickBehavior,
   } = useGeneralOptionsController({ externalPowerSavingMode, onTogglePowerSaving, t });
 
@@ -69,9 +75,11 @@ export default function GeneralOptionsSection({
           gameModePermanent={gameModePermanent}
           fullscreenPaused={fullscreenPaused}
           useDml={useDml}
+          gameModeConfig={gameModeConfig}
           onResourcePolicyChange={handleResourcePolicyChange}
           onSetPowerSaving={handleSetPowerSaving}
           onSetGameMode={handleSetGameMode}
+          onGameModeConfigChange={handleGameModeConfigChange}
         />
 
         <div className="w-full h-px bg-ide-border/50" />
@@ -84,6 +92,14 @@ export default function GeneralOptionsSection({
 
         <div className="w-full h-px bg-ide-border/50" />
 
+        <HotkeysCard
+          hotkeys={hotkeys}
+          hotkeyError={hotkeyError}
+          onHotkeyChange={handleHotkeyChange}
+        />
+
+        <div className="w-full h-px bg-ide-border/50" />
+
         <CardClickBehaviorCard
           cardClickBehaviorSearch={cardClickBehaviorSearch}
           cardClickBehaviorClusters={cardClickBehaviorClusters}
@@ -3,12 +3,16 @@ import { useTranslation } from 'react-i18next';
 import { Play, Pause, Square as StopSquare, Loader2, RotateCw, Squirrel, Circle } from 'lucide-react';
 import SettingsHelpTooltip from './SettingsHelpTooltip';
 import { SettingsSwitch } from './SettingsControls';
+import { formatCountdown } from '../../lib/utils';
+
+const TIMED_PAUSE_MINUTES = [15, 30, 60];
 
 export default function MonitorServiceSection({
   monitorStatus,
   onStart,
   onStop,
   onPause,
+  pauseRemainingSecs,
   onResume,
   onRestart,
   autoStartMonitor,
@@ -47,6 +51,11 @@ export default function MonitorServiceSection({
               </span>
             </label>
             <p className="text-xs text-ide-muted">{t('settings.general.monitor.description')}</p>
+            {monitorStatus === 'paused' && pauseRemainingSecs != null && (
+              <p className="text-xs text-yellow-500">
+                {t('settings.general.monitor.resumesIn', { time: formatCountdown(pauseRemainingSecs) })}
+              </p>
+            )}
             {powerSavingSuppressed && monitorStatus === 'stopped' && (
               <p className="text-xs text-yellow-500">{t('settings.general.monitor.power_saving_blocked')}</p>
             )}
@@ -68,9 +77,19 @@ export default function MonitorServiceSection({
               </button>
             ) : (
               <>
+                {TIMED_PAUSE_MINUTES.map((minutes) => (
+                  <button
+                    key={minutes}
+                    onClick={() => onPause(minutes)}
+                    className="px-2 py-2 bg-ide-panel hover:bg-ide-hover border border-ide-border rounded-lg text-xs text-yellow-500 transition-colors"
+                    title={t('settings.general.monitor.pauseFor', { minutes })}
+                  >
+                    {t('settings.general.monitor.pauseForShort', { minutes })}
+                  </button>
+                ))}
                 {monitorStatus === 'paused' ? (
                   <button
-                    onClick={onResume}
+                    onClick={() => onResume()}
                     className="p-2 bg-ide-panel hover:bg-ide-hover border border-ide-border rounded-lg text-green-500 transition-colors"
                     title={t('settings.general.monitor.resume')}
                   >
@@ -78,7 +97,7 @@ export default function MonitorServiceSection({
                   </button>
                 ) : (
                   <button
-                    onClick={onPause}
+                    onClick={() => onPause()}
                     className="p-2 bg-ide-panel hover:bg-ide-hover border border-ide-border rounded-lg text-yellow-500 transition-colors"
                     title={t('settings.general.monitor.pause')}
                   >
@@ -51,11 +51,39 @@ function SettingsDialog({
     sendTelemetryDiagnostics,
     toggleTelemetryDiagnostics,
     monitorStatus,
+    pauseRemainingSecs,
     filterSettings,
     processInput,
     setProcessInput,
     titleInput,
     setTitleInput,
+    allowedInput,
+    setAllowedInput,
+    ocrSkipInput,
+    setOcrSkipInput,
+    regionDraft,
+    setRegionDraft,
+    regionError,
+    titleRules,
+    titleRuleInput,
+    setTitleRuleInput,
+    titleRuleMessage,
+    screenSharePause,
+    handleToggleScreenSharePause,
+    clipboardHistory,
+    clipboardProcessInput,
+    setClipboardProcessInput,
+    clipboardMessage,
+    handleClipboardHistoryChange,
+    addClipboardProcesses,
+    removeClipboardProcess,
+    handleClearClipboardHistory,
+    audioCapture,
+    audioMessage,
+    handleAudioCaptureChange,
+    captureSchedule,
+    scheduleMessage,
+    handleScheduleChange,
     filtersDirty,
     savingFilters,
     saveFiltersMessage,
@@ -78,6 +106,15 @@ function SettingsDialog({
     addTitleTags,
     removeProcessTag,
     removeTitleTag,
+    addTitleRule,
+    removeTitleRule,
+    addRedactionRegion,
+    removeRedactionRegion,
+    addAllowedTags,
+    removeAllowedTag,
+    addOcrSkipTags,
+    removeOcrSkipTag,
+    handleToggleAllowlist,
     handleToggleProtected,
     handleQuickDelete,
     handleSaveFilters,
@@ -156,6 +193,7 @@ function SettingsDialog({
                 onStart={handleStartMonitor}
                 onStop={handleStopMonitor}
                 onPause={handlePauseMonitor}
+                pauseRemainingSecs={pauseRemainingSecs}
                 onResume={handleResumeMonitor}
                 onRestart={handleRestartMonitor}
                 autoStartMonitor={autoStartMonitor}
@@ -191,6 +229,42 @@ function SettingsDialog({
                 onRemoveProcess={removeProcessTag}
                 onRemoveTitle={removeTitleTag}
                 onToggleProtected={handleToggleProtected}
+                regionDraft={regionDraft}
+                regionError={regionError}
+                onRegionDraftChange={setRegionDraft}
+                onAddRegion={addRedactionRegion}
+                onRemoveRegion={removeRedactionRegion}
+                screenSharePause={screenSharePause}
+                onToggleScreenSharePause={handleToggleScreenSharePause}
+                clipboardHistory={clipboardHistory}
+                clipboardProcessInput={clipboardProcessInput}
+                clipboardMessage={clipboardMessage}
+                onClipboardProcessInputChange={setClipboardProcessInput}
+                onClipboardHistoryChange={handleClipboardHistoryChange}
+                onAddClipboardProcess={addClipboardProcesses}
+                onRemoveClipboardProcess={removeClipboardProcess}
+                onClearClipboardHistory={handleClearClipboardHistory}
+                audioCapture={audioCapture}
+                audioMessage={audioMessage}
+                onAudioCaptureChange={handleAudioCaptureChange}
+                captureSchedule={captureSchedule}
+                scheduleMessage={scheduleMessage}
+                onScheduleChange={handleScheduleChange}
+                titleRules={titleRules}
+                titleRuleInput={titleRuleInput}
+                titleRuleMessage={titleRuleMessage}
+                onTitleRuleInputChange={setTitleRuleInput}
+                onAddTitleRule={addTitleRule}
+                onRemoveTitleRule={removeTitleRule}
+                allowedInput={allowedInput}
+                onAllowedInputChange={setAllowedInput}
+                onAddAllowed={addAllowedTags}
+                onRemoveAllowed={removeAllowedTag}
+                ocrSkipInput={ocrSkipInput}
+                onOcrSkipInputChange={setOcrSkipInput}
+                onAddOcrSkip={addOcrSkipTags}
+                onRemoveOcrSkip={removeOcrSkipTag}
+                onToggleAllowlist={handleToggleAllowlist}
                 onSave={handleSaveFilters}
                 filtersDirty={filtersDirty}
                 savingFilters={savingFilters}
@@ -0,0 +1,70 @@
+import React from 'react';
+import { useTranslation } from 'react-i18next';
+import { Camera, Timer, ScanEye } from 'lucide-react';
+
+export default function CaptureCadenceCard({
+  config,
+  onDraftChange,
+  onIntervalChange,
+  onThresholdChange,
+}) {
+  const { t } = useTranslation();
+
+  return (
+    <div className="space-y-3">
+      <label className="text-sm font-semibold text-ide-accent px-1 flex items-center gap-2">
+        <Camera className="w-4 h-4" />
+        {t('settings.advanced.capture.title')}
+      </label>
+
+      <div className="p-4 bg-ide-bg border border-ide-border rounded-xl space-y-4">
+        <div className="flex items-center justify-between gap-4">
+          <div className="flex-1 min-w-0">
+            <p className="text-sm text-ide-text font-medium flex items-center gap-2">
+              <Timer className="w-4 h-4 text-ide-muted" />
+              {t('settings.advanced.capture.interval_label')}
+            </p>
+            <p className="text-xs text-ide-muted mt-1">{t('settings.advanced.capture.interval_desc')}</p>
+          </div>
+          <div className="flex items-center gap-2 shrink-0">
+            <input
+              type="number"
+              min="2"
+              max="600"
+              step="1"
+              value={config.capture_interval_secs ?? 10}
+              onChange={(e) => onDraftChange('capture_interval_secs', e.target.value)}
+              onBlur={(e) => onIntervalChange(e.target.value)}
+              className="w-24 px-3 py-2 bg-ide-panel border border-ide-border rounded-lg text-sm text-ide-text text-right"
+            />
+            <span className="text-xs text-ide-muted">{t('settings.advanced.ocr.seconds')}</span>
+          </div>
+        </div>
+
+        <div className="flex items-center justify-between gap-4">
+          <div className="flex-1 min-w-0">
+            <p className="text-sm text-ide-text font-medium flex items-center gap-2">
+              <ScanEye className="w-4 h-4 text-ide-muted" />
+              {t('settings.advanced.capture.threshold_label')}
+            </p>
+            <p className="text-xs text-ide-muted mt-1">{t('settings.advanced.capture.threshold_desc')}</p>
+          </div>
+          <div className="flex items-center gap-2 shrink-0">
+            <input
+              type="number"
+              min="0"
+              max="64"
+              step="1"
+              value={config.capture_dhash_threshold ?? 10}
+              onChange={(e) => onDraftChange('capture_dhash_threshold', e.target.value)}
+              onBlur={(e) => onThresholdChange(e.target.value)}
+              className="w-24 px-3 py-2 bg-ide-panel border border-ide-border rounded-lg text-sm text-ide-text text-right"
+            />
+          </div>
+        </div>
+
+        <p className="text-xs text-ide-muted">{t('settings.advanced.capture.info')}</p>
+      </div>
+    </div>
+  );
+}
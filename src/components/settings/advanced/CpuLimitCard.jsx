@@ -1,19 +1,17 @@
 import React from 'react';
 import { useTranslation } from 'react-i18next';
-import { AlertTriangle, ChevronDown, Cpu } from 'lucide-react';
+import { ChevronDown, Cpu, MemoryStick, RotateCcw } from 'lucide-react';
 import { SettingsSwitch } from '../SettingsControls';
 import { CPU_PERCENT_OPTIONS } from './advancedOptions';
 
 export default function CpuLimitCard({
   config,
-  monitorStatus,
   cpuDropdownOpen,
-  cpuChanged,
   onToggle,
   onToggleDropdown,
   onPercentChange,
-  onRestartMonitor,
-  onClearChanged,
+  onDraftChange,
+  onMemoryLimitChange,
 }) {
   const { t } = useTranslation();
 
@@ -75,20 +73,49 @@ export default function CpuLimitCard({
           </div>
         )}
 
-        {cpuChanged && (
-          <div className="flex items-center gap-2 p-2.5 bg-ide-warning-bg border border-ide-warning-border rounded-lg">
-            <AlertTriangle className="w-4 h-4 text-ide-warning shrink-0" />
-            <p className="text-xs text-ide-warning-muted flex-1">{t('settings.advanced.cpu.changed_notice')}</p>
-            {monitorStatus === 'running' && onRestartMonitor && (
-              <button
-                onClick={() => { onRestartMonitor(); onClearChanged(); }}
-                className="text-xs text-ide-warning hover:opacity-80 underline shrink-0 transition-colors"
-              >
-                {t('settings.advanced.quick_restart')}
-              </button>
-            )}
+        <div className="flex items-center justify-between gap-4">
+          <div className="flex-1 min-w-0">
+            <p className="text-sm text-ide-text font-medium flex items-center gap-2">
+              <RotateCcw className="w-4 h-4 text-ide-muted" />
+              {t('settings.advanced.cpu.recycle_label')}
+            </p>
+            <p className="text-xs text-ide-muted mt-1">{t('settings.advanced.cpu.recycle_desc')}</p>
           </div>
-        )}
+          <div className="flex items-center gap-2 shrink-0">
+            <input
+              type="number"
+              min="0"
+              step="256"
+              value={config.sidecar_memory_limit_mb ?? 4096}
+              onChange={(e) => onDraftChange('sidecar_memory_limit_mb', e.target.value)}
+              onBlur={(e) => onMemoryLimitChange('sidecar_memory_limit_mb', e.target.value)}
+              className="w-24 px-3 py-2 bg-ide-panel border border-ide-border rounded-lg text-sm text-ide-text text-right"
+            />
+            <span className="text-xs text-ide-muted">MB</span>
+          </div>
+        </div>
+
+        <div className="flex items-center justify-between gap-4">
+          <div className="flex-1 min-w-0">
+            <p className="text-sm text-ide-text font-medium flex items-center gap-2">
+              <MemoryStick className="w-4 h-4 text-ide-muted" />
+              {t('settings.advanced.cpu.memory_label')}
+            </p>
+            <p className="text-xs text-ide-muted mt-1">{t('settings.advanced.cpu.memory_desc')}</p>
+          </div>
+          <div className="flex items-center gap-2 shrink-0">
+            <input
+              type="number"
+              min="0"
+              step="256"
+              value={config.job_memory_limit_mb ?? 0}
+              onChange={(e) => onDraftChange('job_memory_limit_mb', e.target.value)}
+              onBlur={(e) => onMemoryLimitChange('job_memory_limit_mb', e.target.value)}
+              className="w-24 px-3 py-2 bg-ide-panel border border-ide-border rounded-lg text-sm text-ide-text text-right"
+            />
+            <span className="text-xs text-ide-muted">MB</span>
+          </div>
+        </div>
       </div>
     </div>
   );
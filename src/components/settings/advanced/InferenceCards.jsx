@@ -1,8 +1,24 @@
 import React from 'react';
 import { useTranslation } from 'react-i18next';
-import { AlertTriangle, ChevronDown, Cpu, Monitor, RefreshCw, Zap } from 'lucide-react';
+import { AlertTriangle, BrainCircuit, ChevronDown, Cpu, Monitor, RefreshCw, Zap } from 'lucide-react';
 import SettingsHelpTooltip from '../SettingsHelpTooltip';
 import { SettingsSwitch } from '../SettingsControls';
+import { NPU_PROVIDER_OPTIONS } from './advancedOptions';
+
+// Integrated GPUs report a token amount of dedicated VRAM and use system memory instead.
+const INTEGRATED_GPU_VRAM_MB = 512;
+
+function formatGpuDetail(gpu, t) {
+  const parts = [];
+  if (gpu.dedicated_vram_mb >= INTEGRATED_GPU_VRAM_MB) {
+    parts.push(t('settings.advanced.dml.gpu_vram', { size: (gpu.dedicated_vram_mb / 1024).toFixed(1) }));
+  } else if (gpu.dedicated_vram_mb != null) {
+    parts.push(t('settings.advanced.dml.gpu_shared'));
+  }
+  if (gpu.driver_version) parts.push(gpu.driver_version);
+  if (gpu.directml_supported === false) parts.push(t('settings.advanced.dml.gpu_no_directml'));
+  return parts.join(' · ');
+}
 
 function ChangedNotice({
   children,
@@ -37,8 +53,11 @@ export function OcrEngineCard({
   onToggle,
   onRestart,
   onDownloadModel,
+  onUiaProcessesChange,
 }) {
   const { t } = useTranslation();
+  const [uiaProcessDraft, setUiaProcessDraft] = React.useState(null);
+  const uiaProcesses = (config.uia_text_processes || []).join(', ');
 
   return (
     <div className="space-y-3">
@@ -74,6 +93,38 @@ export function OcrEngineCard({
             />
         </div>
 
+        <div className="space-y-2">
+          <div className="flex items-center justify-between gap-4">
+            <div className="flex-1 min-w-0">
+              <p className="text-sm text-ide-text font-medium">
+                {t('settings.advanced.rust_ocr.uia_text')}
+              </p>
+              <p className="text-xs text-ide-muted mt-1">
+                {t('settings.advanced.rust_ocr.uia_text_desc')}
+              </p>
+            </div>
+            <SettingsSwitch
+              checked={Boolean(config.uia_text_enabled)}
+              onChange={() => onToggle('uia_text_enabled')}
+            />
+          </div>
+          {config.uia_text_enabled && (
+            <input
+              className="w-full bg-ide-panel border border-ide-border rounded-lg px-3 py-2 text-xs text-ide-text focus:outline-none focus:border-ide-accent placeholder:text-ide-muted/50"
+              value={uiaProcessDraft ?? uiaProcesses}
+              onChange={(e) => setUiaProcessDraft(e.target.value)}
+              onBlur={() => {
+                if (uiaProcessDraft !== null && uiaProcessDraft !== uiaProcesses) {
+                  onUiaProcessesChange(uiaProcessDraft);
+                }
+                setUiaProcessDraft(null);
+              }}
+              placeholder={t('settings.advanced.rust_ocr.uia_processes_placeholder')}
+              title={t('settings.advanced.rust_ocr.uia_processes_placeholder')}
+            />
+          )}
+        </div>
+
         <div className="flex items-center justify-between gap-4 rounded-lg border border-ide-border/60 bg-ide-panel/40 p-3">
           <div className="min-w-0 text-xs">
             <p className="text-ide-text font-medium">
@@ -141,10 +192,13 @@ export function DmlAccelerationCard({
   onToggle,
   onToggleGpuDropdown,
   onGpuChange,
+  onDraftChange,
+  onIdleThresholdChange,
   onRestartMonitor,
   onClearChanged,
 }) {
   const { t } = useTranslation();
+  const dmlInUse = config.use_dml || config.rust_ocr_dml_beta;
 
   return (
     <div className="space-y-3">
@@ -205,7 +259,12 @@ export function DmlAccelerationCard({
                           onClick={() => onGpuChange(gpu.id)}
                           className={`w-full px-4 py-2.5 text-left hover:bg-ide-hover transition-colors flex items-center justify-between gap-2 ${gpu.id === config.dml_device_id ? 'bg-ide-accent/10' : ''}`}
                         >
-                          <span className="text-sm text-ide-text truncate">{gpu.name}</span>
+                          <span className="flex flex-col min-w-0">
+                            <span className="text-sm text-ide-text truncate">{gpu.name}</span>
+                            <span className="text-xs text-ide-muted truncate">
+                              {formatGpuDetail(gpu, t)}
+                            </span>
+                          </span>
                           {gpu.id === config.dml_device_id && (
                             <div className="w-2 h-2 rounded-full bg-ide-accent shrink-0" />
                           )}
@@ -219,6 +278,41 @@ export function DmlAccelerationCard({
           </div>
         )}
 
+        {dmlInUse && (
+          <div className="flex items-center justify-between gap-4">
+            <div className="flex-1 min-w-0">
+              <p className="text-sm text-ide-text font-medium">{t('settings.advanced.dml.idle_priority_label')}</p>
+              <p className="text-xs text-ide-muted mt-1">{t('settings.advanced.dml.idle_priority_desc')}</p>
+            </div>
+            <SettingsSwitch
+              checked={!!config.dml_idle_priority}
+              onChange={() => onToggle('dml_idle_priority')}
+            />
+          </div>
+        )}
+
+        {dmlInUse && config.dml_idle_priority && (
+          <div className="flex items-center justify-between gap-4">
+            <div className="flex-1 min-w-0">
+              <p className="text-sm text-ide-text font-medium">{t('settings.advanced.dml.idle_threshold_label')}</p>
+              <p className="text-xs text-ide-muted mt-1">{t('settings.advanced.dml.idle_threshold_desc')}</p>
+            </div>
+            <div className="flex items-center gap-2 shrink-0">
+              <input
+                type="number"
+                min="10"
+                max="100"
+                step="5"
+                value={config.dml_idle_gpu_threshold ?? 60}
+                onChange={(e) => onDraftChange('dml_idle_gpu_threshold', e.target.value)}
+                onBlur={(e) => onIdleThresholdChange(e.target.value)}
+                className="w-24 px-3 py-2 bg-ide-panel border border-ide-border rounded-lg text-sm text-ide-text text-right"
+              />
+              <span className="text-xs text-ide-muted">%</span>
+            </div>
+          </div>
+        )}
+
         {dmlChanged && (
           <ChangedNotice
             monitorStatus={monitorStatus}
@@ -233,6 +327,92 @@ export function DmlAccelerationCard({
   );
 }
 
+export function NpuAccelerationCard({
+  config,
+  monitorStatus,
+  npuChanged,
+  npus,
+  npuLoading,
+  onToggle,
+  onProviderChange,
+  onRestartMonitor,
+  onClearChanged,
+}) {
+  const { t } = useTranslation();
+  const selectedProvider = config.npu_provider || 'auto';
+
+  return (
+    <div className="space-y-3">
+      <label className="text-sm font-semibold text-ide-accent px-1 flex items-center gap-2">
+        <BrainCircuit className="w-4 h-4" />
+        {t('settings.advanced.npu.title')}
+      </label>
+
+      <div className="p-4 bg-ide-bg border border-ide-border rounded-xl space-y-4">
+        <div className="flex items-center justify-between gap-4">
+          <div className="flex-1 min-w-0">
+            <p className="text-sm text-ide-text font-medium">{t('settings.advanced.npu.label')}</p>
+            <p className="text-xs text-ide-muted mt-1">{t('settings.advanced.npu.description')}</p>
+          </div>
+          <SettingsSwitch
+            checked={!!config.use_npu}
+            onChange={() => onToggle('use_npu')}
+          />
+        </div>
+
+        {config.use_npu && (
+          <>
+            <div className="space-y-1">
+              {npuLoading ? (
+                <p className="text-xs text-ide-muted">{t('settings.advanced.npu.detecting')}</p>
+              ) : npus.length === 0 ? (
+                <p className="text-xs text-ide-muted">{t('settings.advanced.npu.none')}</p>
+              ) : (
+                npus.map((npu) => (
+                  <p key={`${npu.vendor_id}-${npu.name}`} className="text-xs text-ide-muted flex items-center gap-2">
+                    <Cpu className="w-3.5 h-3.5 shrink-0" />
+                    <span className="text-ide-text truncate">{npu.name}</span>
+                    {npu.driver_version && <span className="shrink-0">{npu.driver_version}</span>}
+                  </p>
+                ))
+              )}
+            </div>
+
+            <div className="space-y-2">
+              <p className="text-sm text-ide-text font-medium">{t('settings.advanced.npu.provider_label')}</p>
+              <div className="grid grid-cols-2 gap-2">
+                {NPU_PROVIDER_OPTIONS.map((provider) => {
+                  const selected = selectedProvider === provider;
+                  return (
+                    <button
+                      key={provider}
+                      onClick={() => onProviderChange(provider)}
+                      className={`p-3 text-left rounded-lg border transition-colors ${selected ? 'border-ide-accent bg-ide-accent/10' : 'border-ide-border bg-ide-panel hover:bg-ide-hover'}`}
+                    >
+                      <p className="text-sm text-ide-text">{t(`settings.advanced.npu.provider.${provider}`)}</p>
+                      <p className="text-xs text-ide-muted mt-1">{t(`settings.advanced.npu.provider.${provider}_desc`)}</p>
+                    </button>
+                  );
+                })}
+              </div>
+            </div>
+          </>
+        )}
+
+        {npuChanged && (
+          <ChangedNotice
+            monitorStatus={monitorStatus}
+            onRestartMonitor={onRestartMonitor}
+            onClearChanged={onClearChanged}
+          >
+            {t('settings.advanced.npu.changed_notice')}
+          </ChangedNotice>
+        )}
+      </div>
+    </div>
+  );
+}
+
 export function OnnxRuntimeCard({
   config,
   monitorStatus,
@@ -0,0 +1,73 @@
+import React, { useEffect, useState } from 'react';
+import { useTranslation } from 'react-i18next';
+import { Activity } from 'lucide-react';
+import { getMonitorMetrics } from '../../../lib/monitor_api';
+
+const POLL_INTERVAL_MS = 3000;
+
+function formatMs(value) {
+  return typeof value === 'number' ? `${value.toFixed(value < 10 ? 1 : 0)} ms` : '—';
+}
+
+function formatBytes(value) {
+  return typeof value === 'number' ? `${(value / (1024 * 1024)).toFixed(0)} MB` : '—';
+}
+
+export default function MonitorMetricsCard() {
+  const { t } = useTranslation();
+  const [metrics, setMetrics] = useState(null);
+
+  useEffect(() => {
+    let cancelled = false;
+    const load = () => {
+      getMonitorMetrics()
+        .then((next) => {
+          if (!cancelled) setMetrics(next);
+        })
+        .catch((e) => console.warn('Failed to load monitor metrics', e));
+    };
+    load();
+    const interval = setInterval(load, POLL_INTERVAL_MS);
+    return () => {
+      cancelled = true;
+      clearInterval(interval);
+    };
+  }, []);
+
+  const rows = [
+    ['framesCaptured', metrics?.frames?.captured ?? '—'],
+    ['framesDuplicate', metrics?.frames?.skipped_duplicate ?? '—'],
+    ['framesOcrBusy', metrics?.frames?.skipped_ocr_busy ?? '—'],
+    ['framesExcluded', metrics?.frames?.skipped_excluded ?? '—'],
+    ['ocrQueue', metrics?.ocr?.queue_depth ?? '—'],
+    ['ocrAverage', formatMs(metrics?.ocr?.average_ms)],
+    ['ocrLast', formatMs(metrics?.ocr?.last_ms)],
+    ['ocrFailed', metrics?.ocr?.failed ?? '—'],
+    ['pipeRtt', formatMs(metrics?.pipe_rtt_ms)],
+    ['workingSet', formatBytes(metrics?.memory?.working_set_bytes)],
+    ['privateBytes', formatBytes(metrics?.memory?.private_bytes)],
+    ['jobPeak', formatBytes(metrics?.memory?.job_peak_bytes)],
+    ['memoryRecycles', metrics?.memory?.recycles ?? '—'],
+  ];
+
+  return (
+    <div className="space-y-3">
+      <label className="text-sm font-semibold text-ide-accent px-1 flex items-center gap-2">
+        <Activity className="w-4 h-4" />
+        {t('settings.advanced.metrics.title')}
+      </label>
+
+      <div className="p-4 bg-ide-bg border border-ide-border rounded-xl space-y-3">
+        <p className="text-xs text-ide-muted">{t('settings.advanced.metrics.description')}</p>
+        <div className="grid grid-cols-2 gap-x-6 gap-y-2">
+          {rows.map(([key, value]) => (
+            <div key={key} className="flex items-center justify-between text-xs">
+              <span className="text-ide-muted">{t(`settings.advanced.metrics.${key}`)}</span>
+              <span className="font-mono text-ide-text">{value}</span>
+            </div>
+          ))}
+        </div>
+      </div>
+    </div>
+  );
+}
@@ -1,11 +1,19 @@
 import React from 'react';
 import { useTranslation } from 'react-i18next';
-import { Clock, Info, ListOrdered } from 'lucide-react';
+import { AlertTriangle, Clock, Info, Layers, ListOrdered } from 'lucide-react';
+import { OCR_QUEUE_DROP_POLICY_OPTIONS } from './advancedOptions';
 
 export default function OcrQueueCard({
   config,
+  monitorStatus,
+  queueChanged,
   onOcrTimeoutDraftChange,
   onOcrTimeoutChange,
+  onDraftChange,
+  onQueueSizeChange,
+  onQueuePolicyChange,
+  onRestartMonitor,
+  onClearChanged,
 }) {
   const { t } = useTranslation();
 
@@ -56,6 +64,61 @@ export default function OcrQueueCard({
             <span className="text-xs text-ide-muted">{t('settings.advanced.ocr.seconds', '秒')}</span>
           </div>
         </div>
+
+        <div className="flex items-center justify-between gap-4">
+          <div className="flex-1 min-w-0">
+            <p className="text-sm text-ide-text font-medium flex items-center gap-2">
+              <Layers className="w-4 h-4 text-ide-muted" />
+              {t('settings.advanced.ocr.postprocess_queue_label')}
+            </p>
+            <p className="text-xs text-ide-muted mt-1">{t('settings.advanced.ocr.postprocess_queue_desc')}</p>
+          </div>
+          <input
+            type="number"
+            min="1"
+            max="1024"
+            step="1"
+            value={config.ocr_queue_max_size ?? 64}
+            onChange={(e) => onDraftChange('ocr_queue_max_size', e.target.value)}
+            onBlur={(e) => onQueueSizeChange(e.target.value)}
+            className="w-24 px-3 py-2 bg-ide-panel border border-ide-border rounded-lg text-sm text-ide-text text-right shrink-0"
+          />
+        </div>
+
+        <div className="space-y-2">
+          <p className="text-sm text-ide-text font-medium">{t('settings.advanced.ocr.drop_policy_label')}</p>
+          <div className="grid grid-cols-2 gap-2">
+            {OCR_QUEUE_DROP_POLICY_OPTIONS.map((policy) => {
+              const selected = (config.ocr_queue_drop_policy || 'reject_new') === policy;
+              return (
+                <button
+                  key={policy}
+                  onClick={() => onQueuePolicyChange(policy)}
+                  className={`p-3 text-left rounded-lg border transition-colors ${selected ? 'border-ide-accent bg-ide-accent/10' : 'border-ide-border bg-ide-panel hover:bg-ide-hover'}`}
+                >
+                  <p className="text-sm text-ide-text">{t(`settings.advanced.ocr.drop_policy.${policy}`)}</p>
+                  <p className="text-xs text-ide-muted mt-1">{t(`settings.advanced.ocr.drop_policy.${policy}_desc`)}</p>
+                </button>
+              );
+            })}
+          </div>
+          <p className="text-xs text-ide-muted">{t('settings.advanced.ocr.priority_info')}</p>
+        </div>
+
+        {queueChanged && (
+          <div className="flex items-center gap-2 p-2.5 bg-ide-warning-bg border border-ide-warning-border rounded-lg">
+            <AlertTriangle className="w-4 h-4 text-ide-warning shrink-0" />
+            <p className="text-xs text-ide-warning-muted flex-1">{t('settings.advanced.ocr.queue_changed_notice')}</p>
+            {monitorStatus === 'running' && onRestartMonitor && (
+              <button
+                onClick={() => { onRestartMonitor(); onClearChanged(); }}
+                className="text-xs text-ide-warning hover:opacity-80 underline shrink-0 transition-colors"
+              >
+                {t('settings.advanced.quick_restart')}
+              </button>
+            )}
+          </div>
+        )}
       </div>
     </div>
   );
@@ -1,2 +1,4 @@
 export const CPU_PERCENT_OPTIONS = [5, 10, 15, 20, 30, 50];
 export const CLUSTERING_INTERVAL_OPTIONS = ['1d', '1w', '1m', '6m'];
+export const OCR_QUEUE_DROP_POLICY_OPTIONS = ['reject_new', 'drop_oldest'];
+export const NPU_PROVIDER_OPTIONS = ['auto', 'openvino', 'vitisai', 'qnn'];
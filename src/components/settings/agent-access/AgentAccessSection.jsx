@@ -37,6 +37,16 @@ export default function AgentAccessSection() {
     recheckLoading,
     showPiiAdvanced,
     setShowPiiAdvanced,
+    apiTokens,
+    apiTokenName,
+    setApiTokenName,
+    apiTokenScope,
+    setApiTokenScope,
+    apiTokenLoading,
+    apiTokenCopiedId,
+    handleCreateApiToken,
+    handleCopyApiToken,
+    handleRevokeApiToken,
     CONFIRM_TEXT,
     startMcpService,
     handleToggle,
@@ -95,6 +105,11 @@ export default function AgentAccessSection() {
         recheckLoading={recheckLoading}
         showPiiAdvanced={showPiiAdvanced}
         filterLevel={filterLevel}
+        apiTokens={apiTokens}
+        apiTokenName={apiTokenName}
+        apiTokenScope={apiTokenScope}
+        apiTokenLoading={apiTokenLoading}
+        apiTokenCopiedId={apiTokenCopiedId}
         shouldShowStartButton={shouldShowStartButton}
         statusBadge={statusBadge}
         statusMessage={statusMessage}
@@ -113,6 +128,11 @@ export default function AgentAccessSection() {
         onCopyCurrentToken={handleCopyCurrentToken}
         onCopyAgentSetupPrompt={handleCopyAgentSetupPrompt}
         onCopyAgentDiagnostics={handleCopyAgentDiagnostics}
+        onApiTokenNameChange={setApiTokenName}
+        onApiTokenScopeChange={setApiTokenScope}
+        onCreateApiToken={handleCreateApiToken}
+        onCopyApiToken={handleCopyApiToken}
+        onRevokeApiToken={handleRevokeApiToken}
       />
 
       <AgentAccessDialogs
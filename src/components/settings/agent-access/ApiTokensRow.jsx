@@ -0,0 +1,91 @@
+import React from 'react';
+import { useTranslation } from 'react-i18next';
+import { Check, Copy, Plus, Trash2 } from 'lucide-react';
+import { API_TOKEN_SCOPES } from './agentAccessConstants';
+
+export default function ApiTokensRow({
+  apiTokens,
+  apiTokenName,
+  apiTokenScope,
+  apiTokenLoading,
+  apiTokenCopiedId,
+  onApiTokenNameChange,
+  onApiTokenScopeChange,
+  onCreateApiToken,
+  onCopyApiToken,
+  onRevokeApiToken,
+}) {
+  const { t } = useTranslation();
+
+  return (
+    <div>
+      <label className="block mb-1 font-semibold text-ide-text">{t('settings.ai_embedding.api_tokens.title')}</label>
+      <p className="text-xs text-ide-muted mb-2">{t('settings.ai_embedding.api_tokens.description')}</p>
+
+      {apiTokens.length === 0 ? (
+        <p className="text-xs text-ide-muted mb-2">{t('settings.ai_embedding.api_tokens.empty')}</p>
+      ) : (
+        <div className="space-y-1 mb-2">
+          {apiTokens.map((token) => (
+            <div key={token.id} className="flex items-center justify-between gap-3 px-3 py-1.5 rounded-lg border border-ide-border">
+              <div className="min-w-0">
+                <span className="text-ide-text text-xs font-semibold truncate">{token.name}</span>
+                <p className="text-ide-muted text-[11px]">
+                  {(token.scopes || []).map((scope) => t(`settings.ai_embedding.api_tokens.scopes.${scope}`)).join(', ')}
+                  {apiTokenCopiedId === token.id && (
+                    <span className="text-green-400 ml-2">{t('settings.ai_embedding.token.copied')}</span>
+                  )}
+                </p>
+              </div>
+              <div className="flex items-center gap-1 shrink-0">
+                <button
+                  onClick={() => onCopyApiToken(token.id)}
+                  disabled={apiTokenLoading}
+                  title={t('settings.ai_embedding.token.copy')}
+                  className="p-1.5 text-ide-text hover:bg-ide-hover border border-ide-border rounded-lg transition-colors disabled:opacity-50"
+                >
+                  {apiTokenCopiedId === token.id ? <Check className="w-3.5 h-3.5 text-green-400" /> : <Copy className="w-3.5 h-3.5" />}
+                </button>
+                <button
+                  onClick={() => onRevokeApiToken(token.id)}
+                  disabled={apiTokenLoading}
+                  title={t('settings.ai_embedding.api_tokens.revoke')}
+                  className="p-1.5 text-red-400 hover:text-red-300 hover:bg-red-500/10 border border-red-500/30 rounded-lg transition-colors disabled:opacity-50"
+                >
+                  <Trash2 className="w-3.5 h-3.5" />
+                </button>
+              </div>
+            </div>
+          ))}
+        </div>
+      )}
+
+      <div className="flex items-center gap-2">
+        <input
+          type="text"
+          value={apiTokenName}
+          onChange={(e) => onApiTokenNameChange(e.target.value)}
+          placeholder={t('settings.ai_embedding.api_tokens.name_placeholder')}
+          className="flex-1 bg-ide-bg border border-ide-border rounded-lg px-2 py-1.5 text-xs text-ide-text focus:outline-none focus:border-ide-accent placeholder:text-ide-muted/50"
+        />
+        <select
+          value={apiTokenScope}
+          onChange={(e) => onApiTokenScopeChange(e.target.value)}
+          className="bg-ide-bg border border-ide-border rounded-lg px-2 py-1.5 text-xs text-ide-text focus:outline-none focus:border-ide-accent"
+        >
+          {API_TOKEN_SCOPES.map((scope) => (
+            <option key={scope} value={scope}>{t(`settings.ai_embedding.api_tokens.scopes.${scope}`)}</option>
+          ))}
+        </select>
+        <button
+          onClick={onCreateApiToken}
+          disabled={apiTokenLoading || !apiTokenName.trim()}
+          className="shrink-0 px-3 py-1.5 text-xs text-ide-text hover:bg-ide-hover border border-ide-border rounded-lg transition-colors flex items-center gap-1.5 disabled:opacity-50"
+        >
+          <Plus className="w-3.5 h-3.5" />
+          {t('settings.ai_embedding.api_tokens.create')}
+        </button>
+      </div>
+    </div>
+  );
+}
@@ -4,6 +4,7 @@ import { Loader2, RefreshCw } from 'lucide-react';
 import { SettingsSwitch } from '../SettingsControls';
 import AccessTokenRow from './AccessTokenRow';
 import AgentSetupRow from './AgentSetupRow';
+import ApiTokensRow from './ApiTokensRow';
 import ConnectionInfoRow from './ConnectionInfoRow';
 import ContentFilterSection from './ContentFilterSection';
 import FilterModeSection from './FilterModeSection';
@@ -34,6 +35,11 @@ export default function McpServiceCard({
   recheckLoading,
   showPiiAdvanced,
   filterLevel,
+  apiTokens,
+  apiTokenName,
+  apiTokenScope,
+  apiTokenLoading,
+  apiTokenCopiedId,
   shouldShowStartButton,
   statusBadge,
   statusMessage,
@@ -52,6 +58,11 @@ export default function McpServiceCard({
   onCopyCurrentToken,
   onCopyAgentSetupPrompt,
   onCopyAgentDiagnostics,
+  onApiTokenNameChange,
+  onApiTokenScopeChange,
+  onCreateApiToken,
+  onCopyApiToken,
+  onRevokeApiToken,
 }) {
   const { t } = useTranslation();
 
@@ -105,6 +116,19 @@ export default function McpServiceCard({
             onCopyCurrentToken={onCopyCurrentToken}
             onRequestResetToken={onRequestResetToken}
           />
+          <RowDivider />
+          <ApiTokensRow
+            apiTokens={apiTokens}
+            apiTokenName={apiTokenName}
+            apiTokenScope={apiTokenScope}
+            apiTokenLoading={apiTokenLoading}
+            apiTokenCopiedId={apiTokenCopiedId}
+            onApiTokenNameChange={onApiTokenNameChange}
+            onApiTokenScopeChange={onApiTokenScopeChange}
+            onCreateApiToken={onCreateApiToken}
+            onCopyApiToken={onCopyApiToken}
+            onRevokeApiToken={onRevokeApiToken}
+          />
         </>
       )}
 
@@ -3,6 +3,7 @@ export const AGENT_SKILL_REPO = 'https://github.com/White-NX/carbonPaperSkill';
 export const CONTENT_FILTER_LEVELS = ['standard', 'minimal', 'off'];
 export const CONTENT_FILTER_CATEGORIES = ['cat_01', 'cat_02', 'cat_03', 'cat_04', 'cat_05'];
 export const CONTENT_FILTER_MODES = ['reject', 'remove_paragraph', 'mask'];
+export const API_TOKEN_SCOPES = ['read-search', 'read-images', 'admin'];
 export const SPACY_MODELS = [
   { key: 'zh_core_web_sm', label: 'zh_core_web_sm', lang: 'zh' },
   { key: 'en_core_web_sm', label: 'en_core_web_sm', lang: 'en' },
@@ -0,0 +1,78 @@
+import { useCallback, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { withAuth } from '../../../lib/auth_api';
+
+export function useApiTokenSettings({ onError }) {
+  const [apiTokens, setApiTokens] = useState([]);
+  const [apiTokenName, setApiTokenName] = useState('');
+  const [apiTokenScope, setApiTokenScope] = useState('read-search');
+  const [apiTokenLoading, setApiTokenLoading] = useState(false);
+  const [apiTokenCopiedId, setApiTokenCopiedId] = useState(null);
+
+  const loadApiTokens = useCallback(async () => {
+    try {
+      const tokens = await withAuth(() => invoke('mcp_list_api_tokens'));
+      setApiTokens(tokens || []);
+    } catch (e) {
+      console.error('Failed to load API tokens:', e);
+    }
+  }, []);
+
+  const handleCreateApiToken = async () => {
+    const name = apiTokenName.trim();
+    if (!name) return;
+    setApiTokenLoading(true);
+    onError('');
+    try {
+      // 明文令牌只写入剪贴板，不经过 IPC 返回
+      const result = await withAuth(() => invoke('mcp_create_api_token', {
+        name,
+        scopes: [apiTokenScope],
+      }), { autoPrompt: true });
+      setApiTokenName('');
+      setApiTokenCopiedId(result?.copied_to_clipboard ? result.id : null);
+      await loadApiTokens();
+    } catch (e) {
+      onError(String(e));
+    } finally {
+      setApiTokenLoading(false);
+    }
+  };
+
+  const handleCopyApiToken = async (id) => {
+    try {
+      await withAuth(() => invoke('mcp_copy_api_token_to_clipboard', { id }), { autoPrompt: true });
+      setApiTokenCopiedId(id);
+    } catch (e) {
+      onError(String(e));
+    }
+  };
+
+  const handleRevokeApiToken = async (id) => {
+    setApiTokenLoading(true);
+    onError('');
+    try {
+      await withAuth(() => invoke('mcp_revoke_api_token', { id }), { autoPrompt: true });
+      setApiTokenCopiedId((prev) => (prev === id ? null : prev));
+      await loadApiTokens();
+    } catch (e) {
+      onError(String(e));
+    } finally {
+      setApiTokenLoading(false);
+    }
+  };
+
+  return {
+    apiTokens,
+    apiTokenName,
+    setApiTokenName,
+    apiTokenScope,
+    setApiTokenScope,
+    apiTokenLoading,
+    apiTokenCopiedId,
+    loadApiTokens,
+    handleCreateApiToken,
+    handleCopyApiToken,
+    handleRevokeApiToken,
+  };
+}
@@ -2,6 +2,38 @@ export const defaultFilterSettings = {
   processes: ['carbonpaper.exe'],
   titles: ['carbonpaper', 'pornhub'],
   ignoreProtected: true,
+  ocrSkipProcesses: [],
+  allowlistEnabled: false,
+  allowedProcesses: [],
+  redactionRegions: [],
+};
+
+export const defaultRedactionRegionDraft = {
+  scope: 'screen',
+  mode: 'blackout',
+  process: '',
+  x: '',
+  y: '',
+  width: '',
+  height: '',
+};
+
+// Turns the add-region form into a `RedactionRegion` payload, or null if the rectangle is invalid.
+export const parseRedactionRegion = (draft) => {
+  const [x, y, width, height] = [draft.x, draft.y, draft.width, draft.height].map((v) => Number.parseInt(v, 10));
+  if ([x, y, width, height].some((v) => !Number.isFinite(v)) || width <= 0 || height <= 0) {
+    return null;
+  }
+  const process = (draft.process || '').trim().toLowerCase();
+  return {
+    scope: draft.scope === 'window' ? 'window' : 'screen',
+    mode: draft.mode === 'blur' ? 'blur' : 'blackout',
+    process: process || null,
+    x,
+    y,
+    width,
+    height,
+  };
 };
 
 export const normalizeList = (value) =>
@@ -1,5 +1,5 @@
 import { describe, expect, it } from 'vitest';
-import { formatInvokeError, normalizeList } from './filterUtils';
+import { formatInvokeError, normalizeList, parseRedactionRegion } from './filterUtils';
 
 describe('filterUtils', () => {
   it('normalizes list by commas/newlines and lowercases values', () => {
@@ -21,4 +21,16 @@ describe('filterUtils', () => {
     expect(formatInvokeError(circular)).toBe('未知错误');
     expect(formatInvokeError(null)).toBe('未知错误');
   });
+
+  it('parses redaction region drafts and rejects empty rectangles', () => {
+    expect(parseRedactionRegion({
+      scope: 'window', mode: 'blur', process: ' Slack.EXE ', x: '0', y: '10', width: '300', height: '40',
+    })).toEqual({
+      scope: 'window', mode: 'blur', process: 'slack.exe', x: 0, y: 10, width: 300, height: 40,
+    });
+    expect(parseRedactionRegion({ scope: 'screen', x: '-5', y: '0', width: '10', height: '10' }))
+      .toMatchObject({ scope: 'screen', mode: 'blackout', process: null, x: -5 });
+    expect(parseRedactionRegion({ x: '0', y: '0', width: '0', height: '10' })).toBeNull();
+    expect(parseRedactionRegion({ x: 'a', y: '0', width: '10', height: '10' })).toBeNull();
+  });
 });
@@ -0,0 +1,94 @@
+import React, { useEffect, useState } from 'react';
+import { useTranslation } from 'react-i18next';
+
+const NUMBER_FIELDS = [
+  { key: 'suppress_threshold_pct', min: 10, max: 100, unit: '%' },
+  { key: 'resume_threshold_pct', min: 0, max: 95, unit: '%' },
+  { key: 'resume_delay_secs', min: 0, max: 3600, unitKey: 'seconds' },
+];
+
+function parseProcesses(value) {
+  return Array.from(new Set(
+    value
+      .split(',')
+      .map((name) => name.trim().toLowerCase())
+      .filter(Boolean),
+  ));
+}
+
+export default function GameModeTriggersPanel({ config, disabled, onChange }) {
+  const { t } = useTranslation();
+  const [processDraft, setProcessDraft] = useState('');
+  const [numberDrafts, setNumberDrafts] = useState({});
+
+  useEffect(() => {
+    setProcessDraft((config.processes || []).join(', '));
+    setNumberDrafts({});
+  }, [config]);
+
+  const commitProcesses = () => {
+    const next = parseProcesses(processDraft);
+    if (next.join(',') !== (config.processes || []).join(',')) {
+      onChange({ processes: next });
+    }
+  };
+
+  const commitNumber = (field) => {
+    const draft = numberDrafts[field.key];
+    if (draft === undefined) return;
+    const value = parseInt(draft, 10);
+    if (isNaN(value) || value < field.min || value > field.max || value === config[field.key]) {
+      setNumberDrafts((prev) => ({ ...prev, [field.key]: undefined }));
+      return;
+    }
+    onChange({ [field.key]: value });
+  };
+
+  return (
+    <div className="space-y-3">
+      <div>
+        <label className="block text-xs font-medium text-ide-text">{t('settings.general.gameMode.triggers.label')}</label>
+        <p className="text-xs text-ide-muted">{t('settings.general.gameMode.triggers.description')}</p>
+      </div>
+
+      <div className="space-y-1">
+        <label className="block text-xs text-ide-muted">{t('settings.general.gameMode.triggers.processes')}</label>
+        <input
+          type="text"
+          value={processDraft}
+          onChange={(e) => setProcessDraft(e.target.value)}
+          onBlur={commitProcesses}
+          disabled={disabled}
+          placeholder={t('settings.general.gameMode.triggers.processesPlaceholder')}
+          className="w-full px-2 py-1 bg-ide-panel border border-ide-border rounded text-ide-text text-xs"
+          spellCheck={false}
+        />
+      </div>
+
+      <div className="grid gap-3 md:grid-cols-3">
+        {NUMBER_FIELDS.map((field) => (
+          <div key={field.key} className="space-y-1">
+            <label className="block text-xs text-ide-muted">
+              {t(`settings.general.gameMode.triggers.${field.key}`)}
+            </label>
+            <div className="flex items-center gap-2">
+              <input
+                type="number"
+                min={field.min}
+                max={field.max}
+                value={numberDrafts[field.key] ?? config[field.key]}
+                onChange={(e) => setNumberDrafts((prev) => ({ ...prev, [field.key]: e.target.value }))}
+                onBlur={() => commitNumber(field)}
+                disabled={disabled}
+                className="w-20 px-2 py-1 bg-ide-panel border border-ide-border rounded text-ide-text text-xs"
+              />
+              <span className="text-xs text-ide-muted">
+                {field.unitKey ? t(`settings.general.gameMode.triggers.${field.unitKey}`) : field.unit}
+              </span>
+            </div>
+          </div>
+        ))}
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,89 @@
+import React from 'react';
+import { useTranslation } from 'react-i18next';
+import { X } from 'lucide-react';
+import { SettingsButton } from '../SettingsControls';
+import { acceleratorFromKeyEvent } from '../../../lib/hotkey_api';
+
+function HotkeyField({ label, description, value, onChange }) {
+  const { t } = useTranslation();
+  const [recording, setRecording] = React.useState(false);
+
+  const handleKeyDown = (event) => {
+    if (event.key === 'Tab') return;
+    event.preventDefault();
+    if (event.key === 'Escape') {
+      event.currentTarget.blur();
+      return;
+    }
+    if ((event.key === 'Backspace' || event.key === 'Delete') && !event.ctrlKey && !event.altKey) {
+      onChange('');
+      event.currentTarget.blur();
+      return;
+    }
+    const accelerator = acceleratorFromKeyEvent(event);
+    if (accelerator) {
+      onChange(accelerator);
+      event.currentTarget.blur();
+    }
+  };
+
+  return (
+    <div className="flex items-center justify-between gap-4">
+      <div className="min-w-0">
+        <label className="block text-xs font-medium text-ide-text">{label}</label>
+        <p className="text-xs text-ide-muted">{description}</p>
+      </div>
+      <div className="flex items-center gap-2 shrink-0">
+        <input
+          readOnly
+          value={recording ? '' : value}
+          placeholder={recording ? t('settings.general.hotkeys.recording') : t('settings.general.hotkeys.placeholder')}
+          onFocus={() => setRecording(true)}
+          onBlur={() => setRecording(false)}
+          onKeyDown={handleKeyDown}
+          className="w-44 px-2 py-1 bg-ide-panel border border-ide-border rounded text-ide-text text-xs text-center cursor-pointer focus:outline-none focus:border-ide-accent placeholder:text-ide-muted/60"
+        />
+        <SettingsButton
+          icon={X}
+          onClick={() => onChange('')}
+          disabled={!value}
+          title={t('settings.general.hotkeys.clear')}
+          aria-label={t('settings.general.hotkeys.clear')}
+        />
+      </div>
+    </div>
+  );
+}
+
+export default function HotkeysCard({ hotkeys, hotkeyError, onHotkeyChange }) {
+  const { t } = useTranslation();
+
+  return (
+    <div className="space-y-3">
+      <div>
+        <label className="block font-semibold text-ide-text mb-1">{t('settings.general.hotkeys.label')}</label>
+        <p className="text-xs text-ide-muted">{t('settings.general.hotkeys.description')}</p>
+      </div>
+
+      <HotkeyField
+        label={t('settings.general.hotkeys.capture.label')}
+        description={t('settings.general.hotkeys.capture.description')}
+        value={hotkeys.capture}
+        onChange={(value) => onHotkeyChange('capture', value)}
+      />
+
+      <HotkeyField
+        label={t('settings.general.hotkeys.search.label')}
+        description={t('settings.general.hotkeys.search.description')}
+        value={hotkeys.search}
+        onChange={(value) => onHotkeyChange('search', value)}
+      />
+
+      {hotkeyError && (
+        <p className="text-xs text-red-400">
+          {t('settings.general.hotkeys.saveFailed', { error: hotkeyError })}
+        </p>
+      )}
+    </div>
+  );
+}
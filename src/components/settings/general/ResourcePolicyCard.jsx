@@ -1,6 +1,7 @@
 import React from 'react';
 import { useTranslation } from 'react-i18next';
 import { SettingsSegmentedControl, SettingsSwitch } from '../SettingsControls';
+import GameModeTriggersPanel from './GameModeTriggersPanel';
 
 export default function ResourcePolicyCard({
   resourcePolicy,
@@ -14,9 +15,11 @@ export default function ResourcePolicyCard({
   gameModePermanent,
   fullscreenPaused,
   useDml,
+  gameModeConfig,
   onResourcePolicyChange,
   onSetPowerSaving,
   onSetGameMode,
+  onGameModeConfigChange,
 }) {
   const { t } = useTranslation();
 
@@ -73,6 +76,16 @@ export default function ResourcePolicyCard({
         </div>
       )}
 
+      {gameModeEnabled && (
+        <div className="rounded-lg border border-ide-border/70 bg-ide-panel/40 p-3">
+          <GameModeTriggersPanel
+            config={gameModeConfig}
+            disabled={gameModeLoading || resourcePolicyLoading}
+            onChange={onGameModeConfigChange}
+          />
+        </div>
+      )}
+
       <div>
         {gameModeEnabled && useDml && (
           <p className={`text-xs mt-1 ${gameModeActive ? 'text-ide-warning' : 'text-ide-info-success'}`}>
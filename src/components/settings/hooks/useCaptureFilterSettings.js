@@ -1,6 +1,28 @@
 import { useCallback, useEffect, useRef, useState } from 'react';
-import { deleteRecordsByTimeRange, updateMonitorFilters } from '../../../lib/monitor_api';
-import { defaultFilterSettings, normalizeList } from '../filterUtils';
+import {
+  clearClipboardHistory,
+  deleteRecordsByTimeRange,
+  getAudioCaptureSettings,
+  getCaptureSchedule,
+  getClipboardHistorySettings,
+  getExclusionRules,
+  getMonitorFilters,
+  getScreenShareStatus,
+  setAudioCaptureSettings,
+  setCaptureSchedule,
+  setClipboardHistorySettings,
+  setExclusionRules,
+  setScreenSharePauseEnabled,
+  updateMonitorFilters,
+} from '../../../lib/monitor_api';
+import {
+  defaultFilterSettings,
+  defaultRedactionRegionDraft,
+  formatInvokeError,
+  normalizeList,
+  parseRedactionRegion,
+} from '../filterUtils';
+import { useTauriEventListener } from '../../../hooks/useTauriEventListener';
 
 function readInitialFilterSettings() {
   try {
@@ -12,6 +34,10 @@ function readInitialFilterSettings() {
         processes: Array.isArray(saved.processes) ? saved.processes : [],
         titles: Array.isArray(saved.titles) ? saved.titles : [],
         ignoreProtected: typeof saved.ignoreProtected === 'boolean' ? saved.ignoreProtected : true,
+        ocrSkipProcesses: Array.isArray(saved.ocrSkipProcesses) ? saved.ocrSkipProcesses : [],
+        allowlistEnabled: typeof saved.allowlistEnabled === 'boolean' ? saved.allowlistEnabled : false,
+        allowedProcesses: Array.isArray(saved.allowedProcesses) ? saved.allowedProcesses : [],
+        redactionRegions: Array.isArray(saved.redactionRegions) ? saved.redactionRegions : [],
       };
     }
   } catch (e) {
@@ -28,6 +54,23 @@ export function useCaptureFilterSettings({
   const [filterSettings, setFilterSettings] = useState(readInitialFilterSettings);
   const [processInput, setProcessInput] = useState('');
   const [titleInput, setTitleInput] = useState('');
+  const [allowedInput, setAllowedInput] = useState('');
+  const [ocrSkipInput, setOcrSkipInput] = useState('');
+  const [regionDraft, setRegionDraft] = useState(defaultRedactionRegionDraft);
+  const [regionError, setRegionError] = useState('');
+  const [screenSharePause, setScreenSharePause] = useState({ enabled: true, paused: false });
+  const [clipboardHistory, setClipboardHistory] = useState({ enabled: false, retention_days: 30, excluded_processes: [] });
+  const [clipboardProcessInput, setClipboardProcessInput] = useState('');
+  const [clipboardMessage, setClipboardMessage] = useState('');
+  const [audioCapture, setAudioCapture] = useState({
+    enabled: false, source: 'system', chunk_secs: 30, model: 'base', language: '', retention_days: 30,
+  });
+  const [audioMessage, setAudioMessage] = useState('');
+  const [captureSchedule, setCaptureScheduleState] = useState({ enabled: false, windows: [], paused: false });
+  const [scheduleMessage, setScheduleMessage] = useState('');
+  const [titleRules, setTitleRules] = useState([]);
+  const [titleRuleInput, setTitleRuleInput] = useState('');
+  const [titleRuleMessage, setTitleRuleMessage] = useState('');
   const [filtersDirty, setFiltersDirty] = useState(false);
   const [savingFilters, setSavingFilters] = useState(false);
   const [saveFiltersMessage, setSaveFiltersMessage] = useState('');
@@ -59,6 +102,127 @@ export function useCaptureFilterSettings({
     setSaveFiltersMessage('');
   };
 
+  const addAllowedTags = () => {
+    const items = normalizeList(allowedInput);
+    if (!items.length) return;
+    setFilterSettings((prev) => {
+      const merged = Array.from(new Set([...(prev.allowedProcesses || []), ...items]));
+      return { ...prev, allowedProcesses: merged };
+    });
+    setAllowedInput('');
+    setFiltersDirty(true);
+    setSaveFiltersMessage('');
+  };
+
+  const addOcrSkipTags = () => {
+    const items = normalizeList(ocrSkipInput);
+    if (!items.length) return;
+    setFilterSettings((prev) => {
+      const merged = Array.from(new Set([...(prev.ocrSkipProcesses || []), ...items]));
+      return { ...prev, ocrSkipProcesses: merged };
+    });
+    setOcrSkipInput('');
+    setFiltersDirty(true);
+    setSaveFiltersMessage('');
+  };
+
+  const saveTitleRules = async (nextRules) => {
+    setTitleRuleMessage('');
+    try {
+      const result = await setExclusionRules(nextRules);
+      setTitleRules(Array.isArray(result?.rules) ? result.rules : nextRules);
+      return true;
+    } catch (e) {
+      setTitleRuleMessage(t('settings.captureFilters.titleRules.failure', { error: formatInvokeError(e) }));
+      return false;
+    }
+  };
+
+  const addTitleRule = async () => {
+    const pattern = titleRuleInput.trim();
+    if (!pattern || titleRules.includes(pattern)) return;
+    if (await saveTitleRules([...titleRules, pattern])) {
+      setTitleRuleInput('');
+    }
+  };
+
+  const removeTitleRule = (pattern) => saveTitleRules(titleRules.filter((p) => p !== pattern));
+
+  const handleToggleScreenSharePause = async (next) => {
+    const previous = screenSharePause.enabled;
+    setScreenSharePause((prev) => ({ ...prev, enabled: next }));
+    try {
+      await setScreenSharePauseEnabled(next);
+    } catch (e) {
+      console.error('Failed to set screen share pause', e);
+      setScreenSharePause((prev) => ({ ...prev, enabled: previous }));
+    }
+  };
+
+  const handleClipboardHistoryChange = async (patch) => {
+    const previous = clipboardHistory;
+    const next = { ...clipboardHistory, ...patch };
+    setClipboardHistory(next);
+    setClipboardMessage('');
+    try {
+      setClipboardHistory(await setClipboardHistorySettings(next));
+    } catch (e) {
+      setClipboardHistory(previous);
+      setClipboardMessage(t('settings.captureFilters.clipboard.failure', { error: formatInvokeError(e) }));
+    }
+  };
+
+  const handleAudioCaptureChange = async (patch) => {
+    const previous = audioCapture;
+    const next = { ...audioCapture, ...patch };
+    setAudioCapture(next);
+    setAudioMessage('');
+    try {
+      setAudioCapture(await setAudioCaptureSettings(next));
+    } catch (e) {
+      setAudioCapture(previous);
+      setAudioMessage(t('settings.captureFilters.audio.failure', { error: formatInvokeError(e) }));
+    }
+  };
+
+  const addClipboardProcesses = () => {
+    const items = normalizeList(clipboardProcessInput);
+    if (!items.length) return;
+    handleClipboardHistoryChange({
+      excluded_processes: Array.from(new Set([...(clipboardHistory.excluded_processes || []), ...items])),
+    });
+    setClipboardProcessInput('');
+  };
+
+  const removeClipboardProcess = (name) => {
+    handleClipboardHistoryChange({
+      excluded_processes: (clipboardHistory.excluded_processes || []).filter((p) => p !== name),
+    });
+  };
+
+  const handleClearClipboardHistory = async () => {
+    setClipboardMessage('');
+    try {
+      const count = await clearClipboardHistory();
+      setClipboardMessage(t('settings.captureFilters.clipboard.cleared', { count }));
+    } catch (e) {
+      setClipboardMessage(t('settings.captureFilters.clipboard.failure', { error: formatInvokeError(e) }));
+    }
+  };
+
+  const handleScheduleChange = async (next) => {
+    const previous = captureSchedule;
+    setCaptureScheduleState((prev) => ({ ...prev, ...next }));
+    setScheduleMessage('');
+    try {
+      const result = await setCaptureSchedule({ enabled: next.enabled, windows: next.windows });
+      setCaptureScheduleState((prev) => ({ ...prev, paused: Boolean(result?.paused) }));
+    } catch (e) {
+      setCaptureScheduleState(previous);
+      setScheduleMessage(t('settings.captureFilters.schedule.failure', { error: formatInvokeError(e) }));
+    }
+  };
+
   const removeProcessTag = (tag) => {
     setFilterSettings((prev) => ({
       ...prev,
@@ -77,6 +241,55 @@ export function useCaptureFilterSettings({
     setSaveFiltersMessage('');
   };
 
+  const addRedactionRegion = () => {
+    const region = parseRedactionRegion(regionDraft);
+    if (!region) {
+      setRegionError(t('settings.captureFilters.redaction.invalid'));
+      return;
+    }
+    setFilterSettings((prev) => ({
+      ...prev,
+      redactionRegions: [...(prev.redactionRegions || []), region],
+    }));
+    setRegionDraft(defaultRedactionRegionDraft);
+    setRegionError('');
+    setFiltersDirty(true);
+    setSaveFiltersMessage('');
+  };
+
+  const removeRedactionRegion = (index) => {
+    setFilterSettings((prev) => ({
+      ...prev,
+      redactionRegions: (prev.redactionRegions || []).filter((_, i) => i !== index),
+    }));
+    setFiltersDirty(true);
+    setSaveFiltersMessage('');
+  };
+
+  const removeAllowedTag = (tag) => {
+    setFilterSettings((prev) => ({
+      ...prev,
+      allowedProcesses: (prev.allowedProcesses || []).filter((p) => p !== tag),
+    }));
+    setFiltersDirty(true);
+    setSaveFiltersMessage('');
+  };
+
+  const removeOcrSkipTag = (tag) => {
+    setFilterSettings((prev) => ({
+      ...prev,
+      ocrSkipProcesses: (prev.ocrSkipProcesses || []).filter((p) => p !== tag),
+    }));
+    setFiltersDirty(true);
+    setSaveFiltersMessage('');
+  };
+
+  const handleToggleAllowlist = () => {
+    setFilterSettings((prev) => ({ ...prev, allowlistEnabled: !prev.allowlistEnabled }));
+    setFiltersDirty(true);
+    setSaveFiltersMessage('');
+  };
+
   const handleToggleProtected = () => {
     setFilterSettings((prev) => ({ ...prev, ignoreProtected: !prev.ignoreProtected }));
     setFiltersDirty(true);
@@ -84,15 +297,20 @@ export function useCaptureFilterSettings({
   };
 
   const syncFiltersToMonitor = useCallback(async (filtersPayload = filterSettingsRef.current) => {
-    if (monitorStatus !== 'running') {
-      return { ok: false, reason: 'not_running' };
-    }
     try {
+      // Persisted on the Rust side even when stopped, so the next capture loop starts filtered.
       await updateMonitorFilters({
         processes: filtersPayload.processes,
         titles: filtersPayload.titles,
         ignore_protected: filtersPayload.ignoreProtected,
+        ocr_skip_processes: filtersPayload.ocrSkipProcesses || [],
+        allowlist_enabled: Boolean(filtersPayload.allowlistEnabled),
+        allowed_processes: filtersPayload.allowedProcesses || [],
+        redaction_regions: filtersPayload.redactionRegions || [],
       });
+      if (monitorStatus !== 'running') {
+        return { ok: false, reason: 'not_running' };
+      }
       return { ok: true };
     } catch (e) {
       if (e?.code === 'unsupported') {
@@ -143,6 +361,77 @@ export function useCaptureFilterSettings({
     }
   };
 
+  useEffect(() => {
+    getScreenShareStatus()
+      .then((status) => setScreenSharePause({ enabled: status?.enabled !== false, paused: Boolean(status?.paused) }))
+      .catch((e) => console.warn('Failed to load screen share status', e));
+  }, []);
+
+  useTauriEventListener('screen-share-status', (event) => {
+    setScreenSharePause((prev) => ({ ...prev, paused: Boolean(event.payload?.paused) }));
+  });
+
+  useEffect(() => {
+    getClipboardHistorySettings()
+      .then((settings) => settings && setClipboardHistory(settings))
+      .catch((e) => console.warn('Failed to load clipboard history settings', e));
+  }, []);
+
+  useEffect(() => {
+    getAudioCaptureSettings()
+      .then((settings) => settings && setAudioCapture(settings))
+      .catch((e) => console.warn('Failed to load audio capture settings', e));
+  }, []);
+
+  useEffect(() => {
+    getCaptureSchedule()
+      .then((result) => setCaptureScheduleState({
+        enabled: Boolean(result?.schedule?.enabled),
+        windows: Array.isArray(result?.schedule?.windows) ? result.schedule.windows : [],
+        paused: Boolean(result?.paused),
+      }))
+      .catch((e) => console.warn('Failed to load capture schedule', e));
+  }, []);
+
+  useTauriEventListener('capture-schedule-status', (event) => {
+    setCaptureScheduleState((prev) => ({ ...prev, paused: Boolean(event.payload?.paused) }));
+  });
+
+  useEffect(() => {
+    let cancelled = false;
+    getExclusionRules()
+      .then((result) => {
+        if (!cancelled && Array.isArray(result?.rules)) setTitleRules(result.rules);
+      })
+      .catch((e) => console.warn('Failed to load title rules', e));
+    return () => {
+      cancelled = true;
+    };
+  }, []);
+
+  useEffect(() => {
+    if (localStorage.getItem('monitorFilters')) return undefined;
+    let cancelled = false;
+    getMonitorFilters()
+      .then((saved) => {
+        if (cancelled || !saved?.persisted) return;
+        setFilterSettings((prev) => ({
+          ...prev,
+          processes: Array.isArray(saved.processes) ? saved.processes : prev.processes,
+          titles: Array.isArray(saved.titles) ? saved.titles : prev.titles,
+          ignoreProtected: typeof saved.ignore_protected === 'boolean' ? saved.ignore_protected : prev.ignoreProtected,
+          ocrSkipProcesses: Array.isArray(saved.ocr_skip_processes) ? saved.ocr_skip_processes : prev.ocrSkipProcesses,
+          allowlistEnabled: typeof saved.allowlist_enabled === 'boolean' ? saved.allowlist_enabled : prev.allowlistEnabled,
+          allowedProcesses: Array.isArray(saved.allowed_processes) ? saved.allowed_processes : prev.allowedProcesses,
+          redactionRegions: Array.isArray(saved.redaction_regions) ? saved.redaction_regions : prev.redactionRegions,
+        }));
+      })
+      .catch((e) => console.warn('Failed to load persisted filters', e));
+    return () => {
+      cancelled = true;
+    };
+  }, []);
+
   useEffect(() => {
     filterSettingsRef.current = filterSettings;
     localStorage.setItem('monitorFilters', JSON.stringify(filterSettings));
@@ -160,6 +449,33 @@ export function useCaptureFilterSettings({
     setProcessInput,
     titleInput,
     setTitleInput,
+    allowedInput,
+    setAllowedInput,
+    ocrSkipInput,
+    setOcrSkipInput,
+    regionDraft,
+    setRegionDraft,
+    regionError,
+    titleRules,
+    titleRuleInput,
+    setTitleRuleInput,
+    titleRuleMessage,
+    screenSharePause,
+    handleToggleScreenSharePause,
+    clipboardHistory,
+    clipboardProcessInput,
+    setClipboardProcessInput,
+    clipboardMessage,
+    handleClipboardHistoryChange,
+    addClipboardProcesses,
+    removeClipboardProcess,
+    handleClearClipboardHistory,
+    audioCapture,
+    audioMessage,
+    handleAudioCaptureChange,
+    captureSchedule,
+    scheduleMessage,
+    handleScheduleChange,
     filtersDirty,
     savingFilters,
     saveFiltersMessage,
@@ -169,6 +485,15 @@ export function useCaptureFilterSettings({
     addTitleTags,
     removeProcessTag,
     removeTitleTag,
+    addTitleRule,
+    removeTitleRule,
+    addRedactionRegion,
+    removeRedactionRegion,
+    addAllowedTags,
+    removeAllowedTag,
+    addOcrSkipTags,
+    removeOcrSkipTag,
+    handleToggleAllowlist,
     handleToggleProtected,
     handleQuickDelete,
     handleSaveFilters,
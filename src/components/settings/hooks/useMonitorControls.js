@@ -1,5 +1,6 @@
 import { useCallback, useEffect, useRef, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { useTauriEventListener } from '../../../hooks/useTauriEventListener';
 
 export function useMonitorControls({
   isOpen,
@@ -7,6 +8,7 @@ export function useMonitorControls({
   onManualStopMonitor,
 }) {
   const [monitorStatus, setMonitorStatus] = useState('stopped');
+  const [pauseRemainingSecs, setPauseRemainingSecs] = useState(null);
   const monitorStatusRef = useRef('stopped');
 
   const checkMonitorStatus = useCallback(async () => {
@@ -81,9 +83,11 @@ export function useMonitorControls({
     }
   };
 
-  const handlePauseMonitor = async () => {
+  const handlePauseMonitor = async (durationMinutes) => {
     try {
-      await invoke('pause_monitor');
+      await invoke('pause_monitor', {
+        durationMinutes: Number.isFinite(durationMinutes) ? durationMinutes : null,
+      });
       await checkMonitorStatus();
     } catch (e) {
       console.error(e);
@@ -112,8 +116,14 @@ export function useMonitorControls({
     monitorStatusRef.current = monitorStatus;
   }, [monitorStatus]);
 
+  useTauriEventListener('monitor-pause-timer', (event) => {
+    const payload = event?.payload || {};
+    setPauseRemainingSecs(payload.resume_at ? payload.remaining_secs ?? null : null);
+  });
+
   return {
     monitorStatus,
+    pauseRemainingSecs,
     handleStartMonitor,
     handleStopMonitor,
     handleRestartMonitor,
@@ -1,6 +1,7 @@
 import { useEffect, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { withAuth } from '../../lib/auth_api';
+import { useTauriEventListener } from '../../hooks/useTauriEventListener';
 
 export function useAdvancedSectionController({ monitorStatus, t }) {
   const [config, setConfig] = useState(null);
@@ -8,11 +9,14 @@ export function useAdvancedSectionController({ monitorStatus, t }) {
   const [cpuDropdownOpen, setCpuDropdownOpen] = useState(false);
   const [gpuDropdownOpen, setGpuDropdownOpen] = useState(false);
   const [clusteringDropdownOpen, setClusteringDropdownOpen] = useState(false);
-  const [cpuChanged, setCpuChanged] = useState(false);
   const [dmlChanged, setDmlChanged] = useState(false);
+  const [npuChanged, setNpuChanged] = useState(false);
   const [onnxChanged, setOnnxChanged] = useState(false);
+  const [ocrQueueChanged, setOcrQueueChanged] = useState(false);
   const [gpus, setGpus] = useState([]);
   const [gpuLoading, setGpuLoading] = useState(false);
+  const [npus, setNpus] = useState([]);
+  const [npuLoading, setNpuLoading] = useState(false);
   const [vacuumRunning, setVacuumRunning] = useState(false);
   const [vacuumMessage, setVacuumMessage] = useState('');
   const [mlOcrStatus, setMlOcrStatus] = useState(null);
@@ -73,6 +77,19 @@ export function useAdvancedSectionController({ monitorStatus, t }) {
     }
   };
 
+  const loadNpus = async () => {
+    setNpuLoading(true);
+    try {
+      const result = await invoke('enumerate_npus');
+      setNpus(result || []);
+    } catch (err) {
+      console.error('Failed to enumerate NPUs:', err);
+      setNpus([]);
+    } finally {
+      setNpuLoading(false);
+    }
+  };
+
   const refreshVacuumRunningStatus = async () => {
     try {
       const status = await invoke('storage_get_startup_vacuum_status');
@@ -86,12 +103,23 @@ export function useAdvancedSectionController({ monitorStatus, t }) {
     loadConfig();
   }, []);
 
+  // The backend moved DirectML to another GPU or CPU after a device failure.
+  useTauriEventListener('dml-fallback', () => {
+    loadConfig();
+  });
+
   useEffect(() => {
     if (config?.use_dml) {
       loadGpus();
     }
   }, [config?.use_dml]);
 
+  useEffect(() => {
+    if (config?.use_npu) {
+      loadNpus();
+    }
+  }, [config?.use_npu]);
+
   useEffect(() => {
     const handler = () => {
       setCpuDropdownOpen(false);
@@ -155,9 +183,9 @@ export function useAdvancedSectionController({ monitorStatus, t }) {
     const newConfig = { ...config, [key]: !config[key] };
     const saved = await saveConfig(newConfig);
     if (!saved) return;
-    if (key === 'cpu_limit_enabled') setCpuChanged(true);
-    if (key === 'use_dml') setDmlChanged(true);
+    if (key === 'use_dml' || key === 'dml_idle_priority') setDmlChanged(true);
     if (key === 'use_onnx') setOnnxChanged(true);
+    if (key === 'use_npu') setNpuChanged(true);
     if (key === 'clustering_allow_full_low_memory') {
       await syncOcrConfigToMonitor(newConfig);
     }
@@ -188,7 +216,13 @@ export function useAdvancedSectionController({ monitorStatus, t }) {
   const handleCpuPercentChange = async (value) => {
     setCpuDropdownOpen(false);
     await saveConfig({ ...config, cpu_limit_percent: value });
-    setCpuChanged(true);
+  };
+
+  // Both memory limits apply without a restart; 0 turns a limit off.
+  const handleMemoryLimitChange = async (key, value) => {
+    const parsed = Number.parseInt(value, 10);
+    const next = Number.isFinite(parsed) && parsed > 0 ? Math.max(1024, parsed) : 0;
+    await saveConfig({ ...config, [key]: next });
   };
 
   const handleOcrTimeoutDraftChange = (value) => {
@@ -203,6 +237,53 @@ export function useAdvancedSectionController({ monitorStatus, t }) {
     await syncOcrConfigToMonitor(newConfig);
   };
 
+  // Read by the monitor at start, so changes need a restart.
+  const handleOcrQueueSizeChange = async (value) => {
+    const parsed = Number.parseInt(value, 10);
+    const next = Number.isFinite(parsed) ? Math.min(1024, Math.max(1, parsed)) : 64;
+    if (await saveConfig({ ...config, ocr_queue_max_size: next })) setOcrQueueChanged(true);
+  };
+
+  const handleOcrQueuePolicyChange = async (policy) => {
+    if (await saveConfig({ ...config, ocr_queue_drop_policy: policy })) setOcrQueueChanged(true);
+  };
+
+  const handleCaptureDraftChange = (key, value) => {
+    setConfig({ ...config, [key]: value });
+  };
+
+  // Applied to the running capture loop by set_advanced_config; no restart needed.
+  const handleCaptureIntervalChange = async (value) => {
+    const parsed = Number.parseInt(value, 10);
+    const next = Number.isFinite(parsed) ? Math.min(600, Math.max(2, parsed)) : 10;
+    await saveConfig({ ...config, capture_interval_secs: next });
+  };
+
+  const handleCaptureThresholdChange = async (value) => {
+    const parsed = Number.parseInt(value, 10);
+    const next = Number.isFinite(parsed) ? Math.min(64, Math.max(0, parsed)) : 10;
+    await saveConfig({ ...config, capture_dhash_threshold: next });
+  };
+
+  // Read by the GPU pressure monitor on every poll; no restart needed.
+  const handleDmlIdleThresholdChange = async (value) => {
+    const parsed = Number.parseInt(value, 10);
+    const next = Number.isFinite(parsed) ? Math.min(100, Math.max(10, parsed)) : 60;
+    await saveConfig({ ...config, dml_idle_gpu_threshold: next });
+  };
+
+  // Read by the capture loop on every frame; no restart needed.
+  const handleUiaProcessesChange = async (value) => {
+    const processes = Array.from(new Set(
+      String(value || '').split(',').map((name) => name.trim().toLowerCase()).filter(Boolean),
+    ));
+    await saveConfig({ ...config, uia_text_processes: processes });
+  };
+
+  const handleNpuProviderChange = async (provider) => {
+    if (await saveConfig({ ...config, npu_provider: provider })) setNpuChanged(true);
+  };
+
   const handleGpuChange = async (deviceId) => {
     setGpuDropdownOpen(false);
     await saveConfig({ ...config, dml_device_id: deviceId });
@@ -246,11 +327,14 @@ export function useAdvancedSectionController({ monitorStatus, t }) {
     cpuDropdownOpen,
     gpuDropdownOpen,
     clusteringDropdownOpen,
-    cpuChanged,
     dmlChanged,
+    npuChanged,
     onnxChanged,
+    ocrQueueChanged,
     gpus,
     gpuLoading,
+    npus,
+    npuLoading,
     vacuumRunning,
     vacuumMessage,
     selectedGpu,
@@ -261,14 +345,24 @@ export function useAdvancedSectionController({ monitorStatus, t }) {
     setCpuDropdownOpen,
     setGpuDropdownOpen,
     setClusteringDropdownOpen,
-    clearCpuChanged: () => setCpuChanged(false),
     clearDmlChanged: () => setDmlChanged(false),
+    clearNpuChanged: () => setNpuChanged(false),
     clearOnnxChanged: () => setOnnxChanged(false),
+    clearOcrQueueChanged: () => setOcrQueueChanged(false),
     handleToggle,
     handleCpuPercentChange,
+    handleMemoryLimitChange,
     handleOcrTimeoutDraftChange,
     handleOcrTimeoutChange,
+    handleOcrQueueSizeChange,
+    handleOcrQueuePolicyChange,
+    handleCaptureDraftChange,
+    handleCaptureIntervalChange,
+    handleCaptureThresholdChange,
+    handleDmlIdleThresholdChange,
+    handleUiaProcessesChange,
     handleGpuChange,
+    handleNpuProviderChange,
     handleClusteringIntervalChange,
     handleManualVacuum,
     handleRestartMlOcr,
@@ -3,6 +3,7 @@ import { invoke } from '@tauri-apps/api/core';
 import { withAuth } from '../../lib/auth_api';
 import { useTauriEventListener } from '../../hooks/useTauriEventListener';
 import { useSensitiveFilterSettings } from './agent-access/useSensitiveFilterSettings';
+import { useApiTokenSettings } from './agent-access/useApiTokenSettings';
 
 export function useAiEmbeddingController({ t, agentSkillName, agentSkillRepo }) {
   const [enabled, setEnabled] = useState(() => localStorage.getItem('mcpEnabled') === 'true');
@@ -32,6 +33,8 @@ export function useAiEmbeddingController({ t, agentSkillName, agentSkillRepo })
   const CONFIRM_TEXT = t('settings.ai_embedding.privacy_warning.confirm_text');
   const sensitiveFilter = useSensitiveFilterSettings({ t, onError: setError });
   const { loadFilterConfig, loadSpacyModels } = sensitiveFilter;
+  const apiTokenSettings = useApiTokenSettings({ onError: setError });
+  const { loadApiTokens } = apiTokenSettings;
 
   const loadStatus = useCallback(async (retryCount = 0) => {
     try {
@@ -48,6 +51,7 @@ export function useAiEmbeddingController({ t, agentSkillName, agentSkillRepo })
 
       await loadFilterConfig();
       await loadSpacyModels();
+      await loadApiTokens();
     } catch (e) {
       console.error('Failed to load MCP status:', e);
       if (retryCount < 1) {
@@ -57,7 +61,7 @@ export function useAiEmbeddingController({ t, agentSkillName, agentSkillRepo })
     } finally {
       setLoading(false);
     }
-  }, [loadFilterConfig, loadSpacyModels]);
+  }, [loadFilterConfig, loadSpacyModels, loadApiTokens]);
 
   useEffect(() => {
     loadStatus();
@@ -267,6 +271,7 @@ export function useAiEmbeddingController({ t, agentSkillName, agentSkillRepo })
     showResetConfirm,
     setShowResetConfirm,
     ...sensitiveFilter,
+    ...apiTokenSettings,
     CONFIRM_TEXT,
     startMcpService,
     handleToggle,
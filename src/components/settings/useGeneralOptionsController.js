@@ -1,11 +1,17 @@
 import { useEffect, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { getLightweightConfig, setLightweightConfig, switchToLightweightMode } from '../../lib/lightweight_api';
+import { getHotkeys, setCaptureHotkey, setSearchHotkey } from '../../lib/hotkey_api';
 import { withAuth } from '../../lib/auth_api';
 import { useTauriEventListener } from '../../hooks/useTauriEventListener';
 
 const RESOURCE_POLICY_STORAGE_KEY = 'settings.resourcePolicy';
 
+const HOTKEY_SETTERS = {
+  capture: setCaptureHotkey,
+  search: setSearchHotkey,
+};
+
 const RESOURCE_POLICY_OPTIONS = [
   {
     value: 'complete',
@@ -58,6 +64,12 @@ export function useGeneralOptionsController({ externalPowerSavingMode, onToggleP
   const [fullscreenPaused, setFullscreenPaused] = useState(false);
   const [useDml, setUseDml] = useState(false);
   const [gameModeLoading, setGameModeLoading] = useState(true);
+  const [gameModeConfig, setGameModeConfig] = useState({
+    processes: [],
+    suppress_threshold_pct: 50,
+    resume_threshold_pct: 40,
+    resume_delay_secs: 0,
+  });
   const [resourcePolicyLoading, setResourcePolicyLoading] = useState(false);
   const [manualResourcePolicy, setManualResourcePolicy] = useState(() => {
     if (typeof window === 'undefined') return null;
@@ -68,12 +80,15 @@ export function useGeneralOptionsController({ externalPowerSavingMode, onToggleP
     auto_lightweight_enabled: false,
     auto_lightweight_delay_minutes: 5,
   });
+  const [hotkeys, setHotkeys] = useState({ capture: '', search: '' });
+  const [hotkeyError, setHotkeyError] = useState('');
   const [cardClickBehaviorSearch, setCardClickBehaviorSearch] = useState(() => localStorage.getItem('cardClickBehavior_search') || 'preview');
   const [cardClickBehaviorClusters, setCardClickBehaviorClusters] = useState(() => localStorage.getItem('cardClickBehavior_clusters') || 'standalone');
   const [cardClickBehaviorActivityContext, setCardClickBehaviorActivityContext] = useState(() => localStorage.getItem('cardClickBehavior_activityContext') || 'preview');
 
   useEffect(() => {
     getLightweightConfig().then(setLightweightConfigState).catch(console.error);
+    getHotkeys().then(setHotkeys).catch(console.error);
   }, []);
 
   useEffect(() => {
@@ -110,6 +125,8 @@ export function useGeneralOptionsController({ externalPowerSavingMode, onToggleP
         setGameModeActive(status.active || false);
         setGameModePermanent(status.permanent || false);
         setFullscreenPaused(status.fullscreen_paused || false);
+
+        setGameModeConfig(await invoke('get_game_mode_config'));
       } catch (err) {
         console.error('Failed to load config for game mode:', err);
       } finally {
@@ -138,6 +155,21 @@ export function useGeneralOptionsController({ externalPowerSavingMode, onToggleP
     }
   };
 
+  const handleGameModeConfigChange = async (patch) => {
+    const previous = gameModeConfig;
+    setGameModeConfig({ ...previous, ...patch });
+    try {
+      const saved = await withAuth(
+        () => invoke('set_game_mode_config', { config: { ...previous, ...patch } }),
+        { autoPrompt: true },
+      );
+      setGameModeConfig(saved);
+    } catch (err) {
+      console.error('Failed to save game mode config:', err);
+      setGameModeConfig(previous);
+    }
+  };
+
   const handleResourcePolicyChange = async (nextPolicy) => {
     if (nextPolicy === 'custom') {
       setManualResourcePolicy('custom');
@@ -211,6 +243,17 @@ export function useGeneralOptionsController({ externalPowerSavingMode, onToggleP
     }
   };
 
+  const handleHotkeyChange = async (action, value) => {
+    setHotkeyError('');
+    try {
+      const saved = await HOTKEY_SETTERS[action](value);
+      setHotkeys((prev) => ({ ...prev, [action]: saved }));
+    } catch (error) {
+      console.error('Failed to set hotkey:', error);
+      setHotkeyError(String(error?.message || error));
+    }
+  };
+
   const setCardClickBehavior = (scope, value) => {
     localStorage.setItem(`cardClickBehavior_${scope}`, value);
     if (scope === 'search') setCardClickBehaviorSearch(value);
@@ -237,8 +280,11 @@ export function useGeneralOptionsController({ externalPowerSavingMode, onToggleP
     fullscreenPaused,
     useDml,
     gameModeLoading,
+    gameModeConfig,
     resourcePolicyLoading,
     lightweightConfig,
+    hotkeys,
+    hotkeyError,
     cardClickBehaviorSearch,
     cardClickBehaviorClusters,
     cardClickBehaviorActivityContext,
@@ -247,9 +293,11 @@ export function useGeneralOptionsController({ externalPowerSavingMode, onToggleP
     selectedResourcePolicy,
     handleSetPowerSaving,
     handleSetGameMode,
+    handleGameModeConfigChange,
     handleResourcePolicyChange,
     handleLightweightConfigChange,
     handleSwitchToLightweight,
+    handleHotkeyChange,
     setCardClickBehavior,
   };
 }
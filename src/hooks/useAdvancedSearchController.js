@@ -1,6 +1,7 @@
 import { useCallback, useEffect, useMemo, useRef, useState } from 'react';
 import {
   batchGetCategories,
+  countSearchResults,
   fetchThumbnailBatch,
   getCategoriesFromDb,
   listProcesses,
@@ -35,6 +36,7 @@ export function useAdvancedSearchController({
   const [loadingMore, setLoadingMore] = useState(false);
   const [hasMore, setHasMore] = useState(false);
   const [error, setError] = useState(null);
+  const [offlineMonths, setOfflineMonths] = useState([]);
   const [selectedProcesses, setSelectedProcesses] = useState([]);
   const [processOptions, setProcessOptions] = useState([]);
   const [selectedCategories, setSelectedCategories] = useState([]);
@@ -91,6 +93,7 @@ export function useAdvancedSearchController({
     setLoading(false);
     setLoadingMore(false);
     setError(null);
+    setOfflineMonths([]);
     offsetRef.current = 0;
     if (searchMode === undefined) {
       setMode(nextMode);
@@ -165,6 +168,7 @@ export function useAdvancedSearchController({
       setLoading(false);
       setLoadingMore(false);
       setError(null);
+      setOfflineMonths([]);
       offsetRef.current = 0;
       return;
     }
@@ -176,14 +180,27 @@ export function useAdvancedSearchController({
     setThumbnailCache({});
     offsetRef.current = 0;
     const pageSize = mode === 'nl' ? NL_PAGE_SIZE : PAGE_SIZE;
+    const startTime = computeTimestamp(startDate);
+    const endTime = computeTimestamp(endDate);
+    setOfflineMonths([]);
+    if (mode === 'ocr') {
+      // Detached months are not searched; the count response names them
+      countSearchResults(normalizedQuery, { startTime, endTime, fuzzy: true })
+        .then((count) => {
+          if (searchIdRef.current === currentSearchId) {
+            setOfflineMonths(count?.offline_months || []);
+          }
+        })
+        .catch((e) => console.warn('Advanced search offline months failed:', e));
+    }
     try {
       const fetched = await searchScreenshots(normalizedQuery, mode, {
         limit: pageSize,
         offset: 0,
         processNames: selectedProcesses,
         categories: mode === 'ocr' ? selectedCategories : [],
-        startTime: computeTimestamp(startDate),
-        endTime: computeTimestamp(endDate),
+        startTime,
+        endTime,
         fuzzy: mode !== 'nl',
       });
       if (searchIdRef.current !== currentSearchId) return;
@@ -323,6 +340,7 @@ export function useAdvancedSearchController({
     loadingMore,
     hasMore,
     error,
+    offlineMonths,
     selectedProcesses,
     setSelectedProcesses,
     processOptions,
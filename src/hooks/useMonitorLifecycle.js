@@ -23,6 +23,7 @@ export function useMonitorLifecycle({
   const [autoStartSuppressed, setAutoStartSuppressed] = useState(false);
   const [backendStatus, setBackendStatus] = useState('unknown');
   const [monitorPaused, setMonitorPaused] = useState(false);
+  const [pauseRemainingSecs, setPauseRemainingSecs] = useState(null);
   const [backendError, setBackendError] = useState('');
   const backendStatusRef = useRef('unknown');
   const backendStartAtRef = useRef(null);
@@ -89,9 +90,11 @@ export function useMonitorLifecycle({
     }
   }, [formatErrorDetails, reportBackendError, t]);
 
-  const handlePauseMonitor = useCallback(async () => {
+  const handlePauseMonitor = useCallback(async (durationMinutes) => {
     try {
-      await invoke('pause_monitor');
+      await invoke('pause_monitor', {
+        durationMinutes: Number.isFinite(durationMinutes) ? durationMinutes : null,
+      });
       setMonitorPaused(true);
     } catch (err) {
       console.warn('Failed to pause monitor:', err);
@@ -169,9 +172,15 @@ export function useMonitorLifecycle({
     const code = payload.code || 'unknown';
     const errMsg = payload.error ? `; ${payload.error}` : '';
     const recovery = payload.recovery || {};
-    const recoveryMsg = recovery.policy === 'manual_restart'
-      ? t('settings.general.monitor.errors.manualRestartRecovery')
-      : '';
+    let recoveryMsg = '';
+    if (recovery.policy === 'manual_restart') {
+      recoveryMsg = t('settings.general.monitor.errors.manualRestartRecovery');
+    } else if (recovery.policy === 'auto_restart' && recovery.restart_attempts) {
+      recoveryMsg = t('settings.general.monitor.errors.autoRestartRecovery', {
+        attempt: recovery.restart_attempts,
+        max: recovery.max_restart_attempts,
+      });
+    }
     const message = t('settings.general.monitor.errors.exitedMessage', {
       code,
       error: errMsg,
@@ -184,6 +193,16 @@ export function useMonitorLifecycle({
     reportBackendError(t('settings.general.monitor.errors.exitedTitle'), message, details);
   }, [formatErrorDetails, reportBackendError, t]);
 
+  useTauriEventListener('monitor-pause-timer', (event) => {
+    const payload = event?.payload || {};
+    if (payload.resume_at) {
+      setMonitorPaused(true);
+      setPauseRemainingSecs(payload.remaining_secs ?? null);
+    } else {
+      setPauseRemainingSecs(null);
+    }
+  });
+
   useTauriEventListener('monitor-stopped', () => {
     setBackendStatus('offline');
     backendStatusRef.current = 'offline';
@@ -224,6 +243,7 @@ export function useMonitorLifecycle({
     handleManualStopMonitor,
     backendStatus,
     monitorPaused,
+    pauseRemainingSecs,
     backendError,
     handleStartBackend,
     handlePauseMonitor,
@@ -0,0 +1,48 @@
+import { invoke } from '@tauri-apps/api/core';
+import { withAuth } from './auth_api';
+
+/**
+ * Get the global hotkey bindings ({ capture, search }); empty strings mean unbound.
+ */
+export async function getHotkeys() {
+  return await invoke('get_hotkeys');
+}
+
+/**
+ * Bind the manual-capture hotkey, e.g. "Ctrl+Shift+S". An empty string unbinds it.
+ * Rejects when the accelerator is invalid or already taken by another app.
+ */
+export async function setCaptureHotkey(hotkey) {
+  return await withAuth(
+    () => invoke('set_capture_hotkey', { hotkey }),
+    { autoPrompt: true },
+  );
+}
+
+/**
+ * Bind the hotkey that shows the search overlay. An empty string unbinds it.
+ */
+export async function setSearchHotkey(hotkey) {
+  return await withAuth(
+    () => invoke('set_search_hotkey', { hotkey }),
+    { autoPrompt: true },
+  );
+}
+
+const MODIFIER_KEYS = new Set(['Control', 'Shift', 'Alt', 'Meta']);
+
+/**
+ * Turn a keydown event into an accelerator string such as "Ctrl+Shift+S".
+ * Returns null while only modifiers are held or when no modifier is used.
+ */
+export function acceleratorFromKeyEvent(event) {
+  if (MODIFIER_KEYS.has(event.key)) return null;
+  const modifiers = [];
+  if (event.ctrlKey) modifiers.push('Ctrl');
+  if (event.altKey) modifiers.push('Alt');
+  if (event.shiftKey) modifiers.push('Shift');
+  if (event.metaKey) modifiers.push('Super');
+  if (modifiers.length === 0) return null;
+  const key = event.code.replace(/^Key|^Digit/, '');
+  return [...modifiers, key].join('+');
+}
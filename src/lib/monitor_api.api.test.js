@@ -9,7 +9,7 @@ vi.mock('./auth_api', () => ({
   lockSession: vi.fn(),
 }));
 
-import { withAuth } from './auth_api';
+import { checkAuthSession, withAuth } from './auth_api';
 import {
   fetchImage,
   fetchThumbnail,
@@ -95,8 +95,12 @@ describe('monitor_api command wrappers', () => {
       fuzzy: true,
       processNames: null,
       categories: null,
+      tags: null,
       startTime: null,
       endTime: null,
+      includeThumbnails: false,
+      sortBy: 'recent',
+      matchMode: 'terms',
     });
     expectWithAuth(1);
   });
@@ -122,18 +126,34 @@ describe('monitor_api command wrappers', () => {
     expectWithAuth(1, { autoPrompt: true });
   });
 
-  it('dedupes concurrent full image requests by id', async () => {
+  it('serves full images by id through the image protocol', async () => {
+    checkAuthSession.mockResolvedValue(true);
+
+    await expect(fetchImage(42)).resolves.toBe('carbonpaper-img://localhost/42');
+    expect(invoke).not.toHaveBeenCalled();
+    expectWithAuth(1);
+  });
+
+  it('asks for authentication before handing out an image protocol url', async () => {
+    checkAuthSession.mockResolvedValue(false);
+
+    await expect(fetchImage(42)).rejects.toThrow('AUTH_REQUIRED');
+    expectWithAuth(1);
+  });
+
+  it('dedupes concurrent full image requests by legacy path', async () => {
     invoke.mockImplementation(async () => {
       await sleep(5);
       return { status: 'success', data: 'abc', mime_type: 'image/png' };
     });
 
-    const [first, second] = await Promise.all([fetchImage(42), fetchImage(42)]);
+    const path = 'screenshots/a.png.enc';
+    const [first, second] = await Promise.all([fetchImage(null, path), fetchImage(null, path)]);
 
     expect(first).toBe('data:image/png;base64,abc');
     expect(second).toBe(first);
     expect(invoke).toHaveBeenCalledTimes(1);
-    expect(invoke).toHaveBeenCalledWith('storage_get_image', { id: 42, path: null });
+    expect(invoke).toHaveBeenCalledWith('storage_get_image', { id: null, path });
     expectWithAuth(1);
   });
 
@@ -141,13 +161,16 @@ describe('monitor_api command wrappers', () => {
     vi.useFakeTimers();
     invoke.mockImplementation(() => new Promise(() => {}));
 
-    const request = fetchImage(9001);
+    const request = fetchImage(null, 'screenshots/b.png.enc');
     const assertion = expect(request).rejects.toMatchObject({ code: 'deadline_exceeded' });
 
     await vi.advanceTimersByTimeAsync(REQUEST_DEADLINES.imageMs + 1);
 
     await assertion;
-    expect(invoke).toHaveBeenCalledWith('storage_get_image', { id: 9001, path: null });
+    expect(invoke).toHaveBeenCalledWith('storage_get_image', {
+      id: null,
+      path: 'screenshots/b.png.enc',
+    });
     expectWithAuth(1);
   });
 
@@ -1,4 +1,4 @@
-import { invoke } from '@tauri-apps/api/core';
+import { convertFileSrc, invoke } from '@tauri-apps/api/core';
 import { withAuth, requestAuth, checkAuthSession } from './auth_api';
 
 // Re-export auth functions for convenience
@@ -190,6 +190,43 @@ export const initializeCredentials = async () => {
     }
 };
 
+/**
+ * 获取首次运行引导状态（可在中断后恢复）
+ */
+export const getSetupState = async () => {
+    return invoke('get_setup_state');
+};
+
+/**
+ * 执行引导步骤（默认执行当前步骤），返回更新后的状态
+ * @param {object} options - { step: 可选，重新执行的步骤名, pythonPath: 可选，自定义 Python 路径 }
+ */
+export const advanceSetupStep = async (options = {}) => {
+    const { step = null, pythonPath = null } = options || {};
+    return invoke('advance_setup_step', { step, pythonPath });
+};
+
+// 首次运行：列出可用磁盘及剩余空间
+export const listDataDrives = async () => {
+    return invoke('setup_list_data_drives');
+};
+
+// 首次运行：按编码、质量、截图间隔估算每月占用空间
+export const estimateStorageUsage = async (options = {}) => {
+    const { codec = null, quality = null, intervalSecs = null, activeHoursPerDay = null } = options || {};
+    return invoke('setup_estimate_storage', { codec, quality, intervalSecs, activeHoursPerDay });
+};
+
+// 首次运行：检查所选存储位置（是否可写、是否位于云同步目录）
+export const validateDataDir = async (path) => {
+    return invoke('setup_validate_data_dir', { path });
+};
+
+// 首次运行：在初始化存储前直接设置数据目录（已初始化时需改用迁移）
+export const setInitialDataDir = async (path) => {
+    return invoke('setup_set_data_dir', { path });
+};
+
 /**
  * 请求用户验证（Windows Hello PIN）
  * @deprecated 使用 auth_api.js 中的 requestAuth
@@ -202,24 +239,34 @@ export const verifyUser = async () => {
  * 获取时间线数据 - 直接从 Rust 存储层获取
  * 需要认证才能访问
  */
-export const getTimeline = async (startTime, endTime, maxRecords = null) => {
+export const getTimeline = async (startTime, endTime, options = {}) => {
+    const { limit = null, cursor = null } = options;
     return withAuth(async () => {
-        // 使用新的 Rust 存储命令
+        // 使用新的 Rust 存储命令；按游标分页，nextCursor 为 null 时表示已加载完毕
         const params = {
             startTime: startTime,
             endTime: endTime
         };
-        if (maxRecords !== null) {
-            params.maxRecords = maxRecords;
+        if (limit !== null) {
+            params.limit = limit;
         }
-        const records = await invoke('storage_get_timeline', params);
-        return records || [];
+        if (cursor) {
+            params.afterId = cursor.after_id;
+            params.afterTimestamp = cursor.after_timestamp;
+        }
+        const page = await invoke('storage_get_timeline', params);
+        return {
+            records: page?.records || [],
+            nextCursor: page?.next_cursor || null,
+            // 已分离到月度分片的月份（YYYY-MM），其记录不在时间线中
+            offlineMonths: page?.offline_months || [],
+        };
     });
 };
 
 /**
- * 获取时间线密度数据 - 返回按时间桶分组的快照计数
- * 用于大时间尺度下显示快照密集程度
+ * 获取时间线密度数据 - 返回按时间桶分组的快照计数及占比最高的进程
+ * 用于大时间尺度下显示快照密集程度，不解密任何记录
  */
 export const getTimelineDensity = async (startTime, endTime, bucketMs) => {
     return withAuth(async () => {
@@ -232,11 +279,35 @@ export const getTimelineDensity = async (startTime, endTime, bucketMs) => {
     });
 };
 
+/**
+ * 获取活动会话 - 同一进程的连续截图合并为一个时间段
+ * 用于时间线折叠显示（如 "VSCode, 45 分钟"）
+ */
+export const getActivitySessions = async (start, end) => {
+    return withAuth(async () => {
+        const sessions = await invoke('storage_get_sessions', { start, end });
+        return sessions || [];
+    });
+};
+
+/**
+ * 截图原图的 carbonpaper-img:// 地址，WebView 直接加载解密后的图片字节
+ */
+export const imageProtocolUrl = (id) => convertFileSrc(String(id), 'carbonpaper-img');
+
 /**
  * 获取图片 - 直接从 Rust 存储层获取
- * 需要认证才能访问
+ * 需要认证才能访问；按 id 获取时返回 carbonpaper-img:// 地址，不再经过 base64 JSON
  */
 export const fetchImage = async (id, path = null) => {
+    if (typeof id === 'number' && id > 0) {
+        return withAuth(async () => {
+            if (!(await checkAuthSession())) {
+                throw new Error('AUTH_REQUIRED');
+            }
+            return imageProtocolUrl(id);
+        });
+    }
     const key = imageRequestKey('image', id, path);
     // Use queue to limit concurrent image requests
     return imageQueue.enqueue(async () => {
@@ -324,7 +395,7 @@ export const cancelTimelineImageRequest = (key) => {
  * 搜索截图
  * @param {string} query - 搜索查询
  * @param {string} mode - 'ocr' 使用 Rust 存储, 'nl' 使用 Python 自然语言搜索
- * @param {object} options - 搜索选项
+ * @param {object} options - 搜索选项（sortBy: 'recent' 按时间, 'relevance' 按相关度；matchMode: 'phrase' 精确短语匹配, 'regex' 正则表达式匹配；minConfidence: 忽略置信度低于该值（0-1）的 OCR 文本框；recordHistory: 为 true 时记入搜索历史，仅用于用户提交的查询；仅 OCR 搜索）
  * 需要认证才能访问
  */
 export const searchScreenshots = async (query, mode = 'ocr', options = {}) => {
@@ -333,9 +404,15 @@ export const searchScreenshots = async (query, mode = 'ocr', options = {}) => {
         offset = 0,
         processNames = [],
         categories = [],
+        tags = [],
         startTime = null,
         endTime = null,
-        fuzzy = true
+        fuzzy = true,
+        includeThumbnails = false,
+        sortBy = 'recent',
+        matchMode = 'terms',
+        minConfidence = null,
+        recordHistory = false
     } = options || {};
     
     return withAuth(async () => {
@@ -364,13 +441,78 @@ export const searchScreenshots = async (query, mode = 'ocr', options = {}) => {
             fuzzy: fuzzy,
             processNames: processNames.length > 0 ? processNames : null,
             categories: categories.length > 0 ? categories : null,
+            tags: tags.length > 0 ? tags : null,
             startTime: startTime,
-            endTime: endTime
+            endTime: endTime,
+            includeThumbnails: includeThumbnails,
+            sortBy: sortBy,
+            matchMode: matchMode,
+            ...(minConfidence ? { minConfidence } : {}),
+            ...(recordHistory ? { recordHistory: true } : {})
         });
         return results || [];
     });
 };
 
+// 统计 OCR 搜索结果总数及按进程/按天的分面计数（选项同 searchScreenshots）
+export const countSearchResults = async (query, options = {}) => {
+    const {
+        processNames = [],
+        categories = [],
+        tags = [],
+        startTime = null,
+        endTime = null,
+        fuzzy = true,
+        matchMode = 'terms',
+        minConfidence = null
+    } = options || {};
+    return withAuth(() => invoke('storage_search_count', {
+        query,
+        fuzzy,
+        processNames: processNames.length > 0 ? processNames : null,
+        categories: categories.length > 0 ? categories : null,
+        tags: tags.length > 0 ? tags : null,
+        startTime,
+        endTime,
+        matchMode,
+        ...(minConfidence ? { minConfidence } : {})
+    }), { autoPrompt: true });
+};
+
+// 按窗口标题搜索（返回去重后的标题，最近出现的在前）
+export const searchWindowTitles = async (query, options = {}) => {
+    const { limit = 50, offset = 0 } = options || {};
+    return withAuth(() => invoke('storage_search_titles', { query, limit, offset }), { autoPrompt: true });
+};
+
+// 搜索历史（最近提交的查询，最新在前）
+export const getSearchHistory = async (limit = 50) => {
+    return withAuth(() => invoke('storage_get_search_history', { limit }), { autoPrompt: true });
+};
+
+// 根据搜索历史补全查询前缀
+export const suggestSearchQueries = async (prefix, limit = 8) => {
+    return withAuth(() => invoke('storage_suggest_search_queries', { prefix, limit }), { autoPrompt: true });
+};
+
+export const clearSearchHistory = async () => {
+    return withAuth(() => invoke('storage_clear_search_history'), { autoPrompt: true });
+};
+
+// 保存搜索（options: name, matchMode, notify；notify 为 true 时新截图命中会触发 saved-search-matched 事件与系统通知）
+export const createSavedSearch = async (query, options = {}) => {
+    const { name = null, matchMode = 'terms', notify = true } = options || {};
+    return withAuth(() => invoke('storage_create_saved_search', { name, query, matchMode, notify }), { autoPrompt: true });
+};
+
+export const listSavedSearches = async () => {
+    return withAuth(() => invoke('storage_list_saved_searches'), { autoPrompt: true });
+};
+
+export const deleteSavedSearch = async (savedSearchId) => {
+    return withAuth(() => invoke('storage_delete_saved_search', { savedSearchId }), { autoPrompt: true });
+};
+
 export const listProcesses = async () => {
     try {
         return await withAuth(async () => {
@@ -395,6 +537,35 @@ export const getProcessStorageStats = async () => {
     }
 };
 
+// 按截图时所在时区的本地日期分组统计（旅行期间的记录不会因回国后时区变化而错位）
+export const getCaptureDays = async (start, end) => {
+    return withAuth(async () => {
+        const days = await invoke('storage_get_capture_days', { start, end });
+        return days || [];
+    });
+};
+
+// 获取某个“截图时本地日期”（YYYY-MM-DD）的全部截图
+export const getScreenshotsByCaptureDay = async (day) => {
+    return withAuth(async () => {
+        const records = await invoke('storage_get_screenshots_by_capture_day', { day });
+        return records || [];
+    });
+};
+
+// 按天、按应用统计加密图片占用字节数与 OCR 行数，便于定位占用空间的日期和应用
+export const getStorageUsageByDay = async (start, end) => {
+    return withAuth(async () => {
+        const usage = await invoke('storage_get_usage_by_day', { start, end });
+        return usage || [];
+    });
+};
+
+// 存储面板汇总：截图/OCR 数量、数据库与索引大小，以及按天、按应用的占用（start/end 省略时统计全部）
+export const getStorageStats = async (start = null, end = null) => {
+    return withAuth(() => invoke('storage_get_stats', { start, end }), { autoPrompt: true });
+};
+
 export const getProcessMonthlyThumbnails = async (processName, page = 0, pageSize = 60) => {
     return withAuth(async () => {
         const response = await invoke('storage_get_process_monthly_thumbnails', {
@@ -425,6 +596,131 @@ export const softDeleteScreenshots = async (screenshotIds = []) => {
     });
 };
 
+// 对一组截图（如搜索结果）批量执行操作，单个事务完成并只发送一次向量删除请求
+export const batchApplyToScreenshots = async (screenshotIds = [], operation) => {
+    return withAuth(() => invoke('storage_batch_apply', { screenshotIds, operation }));
+};
+
+export const batchDeleteScreenshots = async (screenshotIds = []) => {
+    return batchApplyToScreenshots(screenshotIds, { op: 'delete' });
+};
+
+export const batchTagScreenshots = async (screenshotIds = [], tag) => {
+    return batchApplyToScreenshots(screenshotIds, { op: 'add_tag', tag });
+};
+
+export const batchUntagScreenshots = async (screenshotIds = [], tag) => {
+    return batchApplyToScreenshots(screenshotIds, { op: 'remove_tag', tag });
+};
+
+export const batchPinScreenshots = async (screenshotIds = []) => {
+    return batchApplyToScreenshots(screenshotIds, { op: 'pin' });
+};
+
+export const batchUnpinScreenshots = async (screenshotIds = []) => {
+    return batchApplyToScreenshots(screenshotIds, { op: 'unpin' });
+};
+
+// 置顶截图：保留策略、容量回收和按时间段删除都会跳过置顶的截图
+export const pinScreenshot = async (screenshotId) => {
+    return withAuth(() => invoke('storage_pin_screenshot', { screenshotId }), { autoPrompt: true });
+};
+
+export const unpinScreenshot = async (screenshotId) => {
+    return withAuth(() => invoke('storage_unpin_screenshot', { screenshotId }), { autoPrompt: true });
+};
+
+// 列出置顶截图（按时间倒序分页）
+export const listPinnedScreenshots = async (limit = 100, offset = 0) => {
+    return withAuth(() => invoke('storage_list_pinned', { limit, offset }), { autoPrompt: true });
+};
+
+// 为截图添加标签（不区分大小写，已存在时返回 false）
+export const addTag = async (screenshotId, tag) => {
+    return withAuth(() => invoke('storage_add_tag', { screenshotId, tag }), { autoPrompt: true });
+};
+
+// 移除截图上的标签
+export const removeTag = async (screenshotId, tag) => {
+    return withAuth(() => invoke('storage_remove_tag', { screenshotId, tag }), { autoPrompt: true });
+};
+
+// 列出所有标签及其截图数量；传入 screenshotId 时只列出该截图的标签
+export const listTags = async (screenshotId = null) => {
+    return withAuth(() => invoke('storage_list_tags', { screenshotId }), { autoPrompt: true });
+};
+
+// 按标签查询截图（按时间倒序分页）
+export const searchByTag = async (tag, limit = 100, offset = 0) => {
+    return withAuth(() => invoke('storage_search_by_tag', { tag, limit, offset }), { autoPrompt: true });
+};
+
+// 修正 OCR 识别文本（重新加密并更新搜索索引）
+export const updateOcrText = async (ocrId, newText) => {
+    return withAuth(() => invoke('storage_update_ocr_text', { ocrId, newText }), { autoPrompt: true });
+};
+
+// 重新识别截图文本（单张截图或时间范围），替换原有 OCR 结果与搜索索引
+export const reprocessOcr = async ({ screenshotId = null, startTime = null, endTime = null, limit = null } = {}) => {
+    return withAuth(() => invoke('reprocess_ocr', {
+        ...(screenshotId !== null ? { screenshotId } : {}),
+        ...(startTime !== null ? { startTime } : {}),
+        ...(endTime !== null ? { endTime } : {}),
+        ...(limit !== null ? { limit } : {}),
+    }), { autoPrompt: true });
+};
+
+// 截图批注（加密存储，可通过盲索引搜索）
+export const addAnnotation = async (screenshotId, text) => {
+    return withAuth(() => invoke('storage_add_annotation', { screenshotId, text }), { autoPrompt: true });
+};
+
+export const updateAnnotation = async (annotationId, text) => {
+    return withAuth(() => invoke('storage_update_annotation', { annotationId, text }), { autoPrompt: true });
+};
+
+export const deleteAnnotation = async (annotationId) => {
+    return withAuth(() => invoke('storage_delete_annotation', { annotationId }), { autoPrompt: true });
+};
+
+export const listAnnotations = async (screenshotId) => {
+    return withAuth(() => invoke('storage_list_annotations', { screenshotId }), { autoPrompt: true });
+};
+
+export const searchAnnotations = async (query, limit = 100, offset = 0) => {
+    return withAuth(() => invoke('storage_search_annotations', { query, limit, offset }), { autoPrompt: true });
+};
+
+// 剪贴板历史（加密存储，可通过盲索引搜索）
+export const listClipboardEntries = async (startMs = null, endMs = null, limit = 200) => {
+    return withAuth(() => invoke('storage_list_clipboard_entries', { startMs, endMs, limit }), { autoPrompt: true });
+};
+
+export const searchClipboardEntries = async (query, limit = 100, offset = 0) => {
+    return withAuth(() => invoke('storage_search_clipboard_entries', { query, limit, offset }), { autoPrompt: true });
+};
+
+export const deleteClipboardEntry = async (entryId) => {
+    return withAuth(() => invoke('storage_delete_clipboard_entry', { entryId }), { autoPrompt: true });
+};
+
+export const clearClipboardHistory = async () => {
+    return withAuth(() => invoke('storage_clear_clipboard_history'), { autoPrompt: true });
+};
+
+// 音频转写（本地 Whisper 转写后加密存储，可通过盲索引搜索）
+export const listAudioTranscripts = async (startMs = null, endMs = null, limit = 200) => {
+    return withAuth(() => invoke('storage_list_audio_transcripts', { startMs, endMs, limit }), { autoPrompt: true });
+};
+
+export const searchAudioTranscripts = async (query, limit = 100, offset = 0) => {
+    return withAuth(() => invoke('storage_search_audio_transcripts', { query, limit, offset }), { autoPrompt: true });
+};
+
+export const deleteAudioTranscript = async (transcriptId) => {
+    return withAuth(() => invoke('storage_delete_audio_transcript', { transcriptId }), { autoPrompt: true });
+};
+
 export const getSoftDeleteQueueStatus = async () => {
     try {
         return await withAuth(async () => {
@@ -437,11 +733,18 @@ export const getSoftDeleteQueueStatus = async () => {
     }
 };
 
-export const getScreenshotDetails = async (id, path = null) => {
-    const key = imageRequestKey('detail', id, path);
+// options.minConfidence: 隐藏置信度低于该值（0-1）的 OCR 文本框
+export const getScreenshotDetails = async (id, path = null, options = {}) => {
+    const { minConfidence = null } = options || {};
+    const baseKey = imageRequestKey('detail', id, path);
+    const key = baseKey && minConfidence ? `${baseKey}:conf:${minConfidence}` : baseKey;
     try {
         const response = await detailQueue.enqueue(
-            () => withAuth(() => invoke('storage_get_screenshot_details', { id, path })),
+            () => withAuth(() => invoke('storage_get_screenshot_details', {
+                id,
+                path,
+                ...(minConfidence ? { minConfidence } : {})
+            })),
             { priority: 'high', key, deadlineMs: REQUEST_DEADLINES.detailMs }
         );
         if (response?.error) {
@@ -455,6 +758,11 @@ export const getScreenshotDetails = async (id, path = null) => {
     }
 };
 
+// 批量获取多张截图的 OCR 文本（按请求顺序返回，单条文本按 maxChars 截断）
+export const getOcrTexts = async (screenshotIds, maxChars = null) => {
+    return withAuth(() => invoke('storage_get_ocr_texts', { screenshotIds, maxChars }), { autoPrompt: true });
+};
+
 export const updateMonitorFilters = async (filters) => {
     return withAuth(async () => {
         try {
@@ -474,6 +782,49 @@ export const updateMonitorFilters = async (filters) => {
     }, { autoPrompt: true });
 };
 
+// 读取 Rust 端持久化的捕获黑名单（monitor_filters.json）及内置的进程/标题规则
+export const getMonitorFilters = async () => {
+    return withAuth(() => invoke('monitor_get_filters'), { autoPrompt: true });
+};
+
+// 读取/替换窗口标题正则规则（前台窗口标题匹配时暂停捕获，存于注册表）
+export const getExclusionRules = async () => {
+    return withAuth(() => invoke('monitor_get_exclusion_rules'), { autoPrompt: true });
+};
+
+export const setExclusionRules = async (rules) => {
+    return withAuth(() => invoke('monitor_set_exclusion_rules', { rules }), { autoPrompt: true });
+};
+
+// 屏幕共享/演示时自动暂停捕获
+export const getScreenShareStatus = async () => invoke('get_screen_share_status');
+
+export const setScreenSharePauseEnabled = async (enabled) => {
+    return withAuth(() => invoke('set_screen_share_pause_enabled', { enabled }), { autoPrompt: true });
+};
+
+export const getClipboardHistorySettings = async () => invoke('get_clipboard_history_settings');
+
+export const setClipboardHistorySettings = async (settings) => {
+    return withAuth(() => invoke('set_clipboard_history_settings', { settings }), { autoPrompt: true });
+};
+
+export const getAudioCaptureSettings = async () => invoke('get_audio_capture_settings');
+
+export const setAudioCaptureSettings = async (settings) => {
+    return withAuth(() => invoke('set_audio_capture_settings', { settings }), { autoPrompt: true });
+};
+
+// 监控诊断指标（帧计数、OCR 耗时、管道往返延迟、子进程内存）
+export const getMonitorMetrics = async () => invoke('monitor_get_metrics');
+
+// 每周捕获时段（计划外自动暂停捕获，存于注册表）
+export const getCaptureSchedule = async () => invoke('get_capture_schedule');
+
+export const setCaptureSchedule = async (schedule) => {
+    return withAuth(() => invoke('set_capture_schedule', { schedule }), { autoPrompt: true });
+};
+
 export const deleteScreenshot = async (screenshotId) => {
     return withAuth(async () => {
         try {
@@ -491,7 +842,23 @@ export const deleteScreenshot = async (screenshotId) => {
     }, { autoPrompt: true });
 };
 
-export const deleteRecordsByTimeRange = async (minutes, centerTimestamp = null) => {
+// 从回收站恢复截图，返回 { restored }
+export const restoreScreenshot = async (screenshotId) => {
+    return withAuth(() => invoke('storage_restore_screenshot', { screenshotId }), { autoPrompt: true });
+};
+
+// 回收站中的截图，最近删除的在前
+export const listTrash = async (limit = 100, offset = 0) => {
+    return withAuth(() => invoke('storage_list_trash', { limit, offset }), { autoPrompt: true });
+};
+
+// 清空回收站（永久删除），返回 { purged }
+export const emptyTrash = async () => {
+    return withAuth(() => invoke('storage_empty_trash'), { autoPrompt: true });
+};
+
+// 置顶（收藏）的截图和受保护时间段内的截图默认保留，force 为 true 时一并删除
+export const deleteRecordsByTimeRange = async (minutes, centerTimestamp = null, force = false) => {
     return withAuth(async () => {
         try {
             const now = centerTimestamp || Date.now();
@@ -512,7 +879,8 @@ export const deleteRecordsByTimeRange = async (minutes, centerTimestamp = null)
             
             const response = await invoke('storage_delete_by_time_range', {
                 startTime,
-                endTime
+                endTime,
+                ...(force ? { force: true } : {})
             });
             if (response?.error) {
                 throw new Error(response.error);
@@ -525,6 +893,25 @@ export const deleteRecordsByTimeRange = async (minutes, centerTimestamp = null)
     }, { autoPrompt: true });
 };
 
+// 保护某个时间段（毫秒时间戳），其中的截图不会被保留策略或按时间段删除清理
+export const protectRange = async (startTime, endTime, label = null) => {
+    return withAuth(() => invoke('storage_protect_range', {
+        startTime,
+        endTime,
+        ...(label ? { label } : {})
+    }), { autoPrompt: true });
+};
+
+// 所有受保护的时间段
+export const listProtectedRanges = async () => {
+    return withAuth(() => invoke('storage_list_protected_ranges'), { autoPrompt: true });
+};
+
+// 取消某个时间段的保护
+export const unprotectRange = async (rangeId) => {
+    return withAuth(() => invoke('storage_unprotect_range', { rangeId }), { autoPrompt: true });
+};
+
 // ==================== 数据迁移 API ====================
 
 /**
@@ -553,6 +940,15 @@ export const migratePlaintextFiles = async () => {
     });
 };
 
+/**
+ * 将旧版数据库中的绝对图片路径改写为相对数据目录的路径
+ * 需要认证
+ * @returns {Promise<{scanned: number, rewritten: number, unresolved_count: number, unresolved: Array<{table: string, id: number, image_path: string, reason: string}>}>}
+ */
+export const normalizeImagePaths = async () => {
+    return withAuth(() => invoke('storage_normalize_paths'), { autoPrompt: true });
+};
+
 /**
  * 删除所有明文截图文件（不迁移，直接删除）
  * 需要认证
@@ -654,3 +1050,299 @@ export const retryVectorIndexing = async (limit = 32) => {
         { autoPrompt: true },
     );
 };
+
+export const verifyImages = async (sampleRate = 0.05) => {
+    return withAuth(
+        () => invoke('storage_verify_images', { sampleRate }),
+        { autoPrompt: true },
+    );
+};
+
+// 数据库健康检查：页校验、行密钥抽样解包、盲索引与 OCR 记录交叉核对；repair 为 true 时自动修复可修复的问题
+export const checkDatabaseIntegrity = async (repair = false) => {
+    return withAuth(() => invoke('storage_check_integrity', { repair }), { autoPrompt: true });
+};
+
+/**
+ * 导出每周摘要 HTML
+ * @param {string} outputPath - 输出文件路径
+ * @param {object} options - { weekStart: Unix 秒, privacyLevel: 'summary' | 'apps' | 'detailed' }
+ */
+export const exportWeeklyDigest = async (outputPath, options = {}) => {
+    const { weekStart = null, privacyLevel = null } = options || {};
+    return withAuth(
+        () => invoke('storage_export_weekly_digest', { outputPath, weekStart, privacyLevel }),
+        { autoPrompt: true },
+    );
+};
+
+/**
+ * 将搜索结果（OCR 文本、时间、窗口标题、应用名）导出为 CSV 或 JSON 文件
+ * @param {string} query - 搜索词，空字符串导出时间范围内的全部文本
+ * @param {string} outputPath - 输出文件路径
+ * @param {object} options - { format: 'csv' | 'json', processNames, startTime, endTime, matchMode, maxRows }
+ */
+export const exportSearchResults = async (query, outputPath, options = {}) => {
+    const {
+        format = 'csv',
+        processNames = null,
+        startTime = null,
+        endTime = null,
+        matchMode = null,
+        maxRows = null,
+    } = options || {};
+    return withAuth(
+        () => invoke('storage_export_search', {
+            query,
+            format,
+            outputPath,
+            processNames,
+            startTime,
+            endTime,
+            matchMode,
+            maxRows,
+        }),
+        { autoPrompt: true },
+    );
+};
+
+/**
+ * 将截图导出为带可选中文字层的 PDF（每张截图一页）
+ * @param {string} outputPath - 输出文件路径
+ * @param {object} options - { screenshotId } 或 { startTs, endTs: Unix 秒, limit }
+ */
+export const exportScreenshotsPdf = async (outputPath, options = {}) => {
+    const { screenshotId = null, startTs = null, endTs = null, limit = null } = options || {};
+    return withAuth(
+        () => invoke('storage_export_pdf', { outputPath, screenshotId, startTs, endTs, limit }),
+        { autoPrompt: true },
+    );
+};
+
+/**
+ * 将某天的活动写成 Markdown 日记（会话摘要、OCR 片段、截图），适用于 Obsidian 等笔记库
+ * @param {object} options - { date: 'YYYY-MM-DD'（默认今天）, vaultDir（默认使用已保存的笔记库目录） }
+ */
+export const exportDailyNote = async (options = {}) => {
+    const { date = null, vaultDir = null } = options || {};
+    return withAuth(
+        () => invoke('storage_export_daily_note', { date, vaultDir }),
+        { autoPrompt: true },
+    );
+};
+
+// 定时任务 daily_note 写入的笔记库目录，未设置时为 null
+export const getDailyNoteVault = async () => {
+    return withAuth(() => invoke('storage_get_daily_note_vault'), { autoPrompt: true });
+};
+
+export const setDailyNoteVault = async (vaultDir) => {
+    return withAuth(() => invoke('storage_set_daily_note_vault', { vaultDir }), { autoPrompt: true });
+};
+
+// 自动保留策略（最长保留天数、截图总大小上限，null 表示不限制）
+export const getRetentionPolicy = async () => {
+    return withAuth(() => invoke('storage_get_retention_policy'), { autoPrompt: true });
+};
+
+/**
+ * @param {object} retention - { max_age_days: number | null, max_total_gb: number | null }
+ */
+export const setRetentionPolicy = async (retention) => {
+    return withAuth(() => invoke('storage_set_retention_policy', { retention }), { autoPrompt: true });
+};
+
+// 截图目录存储配额（GB，null 表示不限）；超额时最旧的截图会被清理并触发 storage-quota-evicted 事件
+export const getStorageQuota = async () => {
+    return withAuth(() => invoke('storage_get_quota'), { autoPrompt: true });
+};
+
+export const setStorageQuota = async (quotaGb) => {
+    return withAuth(() => invoke('storage_set_quota', { quotaGb }), { autoPrompt: true });
+};
+
+// 数据合规模式：禁止明文进程名、缩短保留期、限制会话超时并记录访问审计
+export const getComplianceStatus = async () => {
+    return withAuth(() => invoke('storage_get_compliance_status'), { autoPrompt: true });
+};
+
+export const setComplianceMode = async (enabled) => {
+    return withAuth(() => invoke('storage_set_compliance_mode', { enabled }), { autoPrompt: true });
+};
+
+// 偏执模式：不保存任何明文元数据，进程筛选改用盲索引（不影响保留期与会话超时）
+export const getParanoidMode = async () => {
+    return withAuth(() => invoke('storage_get_paranoid_mode'), { autoPrompt: true });
+};
+
+export const setParanoidMode = async (enabled) => {
+    return withAuth(() => invoke('storage_set_paranoid_mode', { enabled }), { autoPrompt: true });
+};
+
+// 启动时对崩溃遗留的待提交截图的恢复结果（已补提交 / 已清理 / 孤立文件）
+export const getPendingRecovery = async () => {
+    return withAuth(() => invoke('storage_get_pending_recovery'), { autoPrompt: true });
+};
+
+// 按月分片：把已结束的月份移出主数据库，单独存放为加密分片，可随时重新挂回
+export const listMonthShards = async () => {
+    return withAuth(() => invoke('storage_list_month_shards'), { autoPrompt: true });
+};
+
+export const detachMonth = async (month) => {
+    return withAuth(() => invoke('storage_detach_month', { month }), { autoPrompt: true });
+};
+
+export const attachMonth = async (month) => {
+    return withAuth(() => invoke('storage_attach_month', { month }), { autoPrompt: true });
+};
+
+// 孤立文件清理：找出没有数据库记录的图片文件和文件已丢失的截图，clean 为 true 时一并清理
+export const runStorageGc = async (clean = false) => {
+    return withAuth(() => invoke('storage_gc', { clean }), { autoPrompt: true });
+};
+
+// 访问审计日志：每次解密图片或 OCR 文本都会追加一条（只追加，不可修改）
+export const getAuditLog = async ({ limit = 100, offset = 0, action = null } = {}) => {
+    return withAuth(() => invoke('storage_get_audit_log', { limit, offset, action }), { autoPrompt: true });
+};
+
+// 从全部 OCR 文本重建盲索引（进度见 index-rebuild-progress 事件），用于修复搜索结果
+export const rebuildSearchIndex = async () => {
+    return withAuth(() => invoke('storage_rebuild_index'), { autoPrompt: true });
+};
+
+// 轮换主密钥与 CNG 密钥对并重新包装所有行密钥（进度见 key-rotation-progress 事件）
+export const rotateKeys = async () => {
+    return withAuth(() => invoke('credential_rotate_keys'), { autoPrompt: true });
+};
+
+// 查询某一时刻正在做什么：最近的截图、活动应用、窗口标题、网页地址及所在会话摘要
+export const getContextAt = async (timestamp, maxDistanceSecs = null) => {
+    return withAuth(
+        () => invoke('storage_get_context_at', { timestamp, maxDistanceSecs }),
+        { autoPrompt: true }
+    );
+};
+
+// 将时间范围内的截图、OCR 与元数据导出为密码保护的便携归档（可在其他设备上导入）
+export const exportRange = async (startTs, endTs, password, exportPath) => {
+    return withAuth(
+        () => invoke('storage_export_range', { startTs, endTs, password, exportPath }),
+        { autoPrompt: true }
+    );
+};
+
+// 导入之前导出的便携归档：用本机密钥重新加密并写回时间线（重复截图会被跳过）
+export const importArchive = async (archivePath, password) => {
+    return withAuth(
+        () => invoke('storage_import_archive', { archivePath, password }),
+        { autoPrompt: true }
+    );
+};
+
+// 截图时排除 CarbonPaper 自身窗口（调试时可关闭）
+export const getCaptureExcludeOwnWindows = async () => {
+    return invoke('get_capture_exclude_own_windows');
+};
+
+export const setCaptureExcludeOwnWindows = async (enabled) => {
+    return withAuth(() => invoke('set_capture_exclude_own_windows', { enabled }), { autoPrompt: true });
+};
+
+// OCR 文本后处理规则
+export const getOcrTextRules = async () => {
+    return withAuth(() => invoke('storage_get_ocr_text_rules'), { autoPrompt: true });
+};
+
+/**
+ * 更新 OCR 文本后处理规则（仅影响之后保存的 OCR 结果）
+ * @param {object} rules - { enabled, merge_hyphenated_line_breaks, normalize_width, collapse_whitespace }
+ */
+export const setOcrTextRules = async (rules) => {
+    return withAuth(() => invoke('storage_set_ocr_text_rules', { rules }), { autoPrompt: true });
+};
+
+// 后台计划任务
+export const listScheduledJobs = async () => {
+    return withAuth(() => invoke('scheduler_list_jobs'), { autoPrompt: true });
+};
+
+export const setScheduledJobEnabled = async (name, enabled) => {
+    return withAuth(
+        () => invoke('scheduler_set_job_enabled', { name, enabled }),
+        { autoPrompt: true },
+    );
+};
+
+export const setScheduledJobInterval = async (name, intervalSecs) => {
+    return withAuth(
+        () => invoke('scheduler_set_job_interval', { name, intervalSecs }),
+        { autoPrompt: true },
+    );
+};
+
+export const runScheduledJob = async (name) => {
+    return withAuth(() => invoke('scheduler_run_job', { name }), { autoPrompt: true });
+};
+
+// 监控状态钩子（启动/停止/暂停/恢复/游戏模式时运行程序或调用 Webhook）
+export const getMonitorHooks = async () => {
+    return withAuth(() => invoke('get_monitor_hooks'), { autoPrompt: true });
+};
+
+export const setMonitorHooks = async (config) => {
+    return withAuth(() => invoke('set_monitor_hooks', { config }), { autoPrompt: true });
+};
+
+// 是否以只读访客模式运行（--guest），此时截图、删除与导入均被后端拒绝
+export const getReadOnlyMode = async () => {
+    try {
+        return Boolean(await invoke('get_read_only_mode'));
+    } catch {
+        return false;
+    }
+};
+
+// 数据库维护：压缩搜索索引、重建索引、更新统计并 VACUUM，进度通过 storage-maintenance-progress 事件推送
+export const runStorageMaintenance = async () => {
+    return withAuth(() => invoke('storage_maintenance'), { autoPrompt: true });
+};
+
+// 增量备份：把数据库快照和新增/变更的加密文件复制到备份文件夹，进度通过 storage-backup-progress 事件推送
+export const runBackupNow = async (destination = null) => {
+    return withAuth(() => invoke('storage_backup_now', { destination }), { autoPrompt: true });
+};
+
+export const getBackupDir = async () => {
+    return withAuth(() => invoke('storage_get_backup_dir'), { autoPrompt: true });
+};
+
+export const setBackupDir = async (backupDir) => {
+    return withAuth(() => invoke('storage_set_backup_dir', { backupDir }), { autoPrompt: true });
+};
+
+// 从备份目录恢复：merge=false 时替换当前数据，merge=true 时把备份中缺少的截图合并进当前数据库
+export const restoreFromBackup = async (path, merge = false) => {
+    return withAuth(() => invoke('storage_restore_from_backup', { path, merge }), { autoPrompt: true });
+};
+
+// 立即同步到 WebDAV / S3：截图文件原样上传（本身已加密），数据库变更以主密钥加密后上传
+export const runSyncNow = async () => {
+    return withAuth(() => invoke('storage_sync_now'), { autoPrompt: true });
+};
+
+// 读取同步目标设置（不返回密码，只返回 has_secret）
+export const getSyncConfig = async () => {
+    return withAuth(() => invoke('storage_get_sync_config'), { autoPrompt: true });
+};
+
+// 保存同步目标设置：secret 为 null 时保留已保存的密码，空字符串时清除
+export const setSyncConfig = async (config, secret = null) => {
+    return withAuth(() => invoke('storage_set_sync_config', { config, secret }), { autoPrompt: true });
+};
+
+// 未解锁时可用的马赛克预览（可辨认布局但无法阅读文字），blurLevel 0-3，越大越模糊
+export const getImagePreview = async (id, blurLevel = 1) => {
+    return invoke('storage_get_image_preview', { id, blurLevel });
+};
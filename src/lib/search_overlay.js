@@ -0,0 +1,32 @@
+export const SEARCH_OVERLAY_WINDOW_LABEL = 'search-overlay';
+// Result picked in the overlay, waiting for the main window to open it.
+export const SEARCH_OVERLAY_PENDING_KEY = 'carbonpaper:search-overlay-pending';
+
+/**
+ * Store the result the main window should open. Only identifying fields are kept.
+ */
+export function setPendingSearchOverlayTarget(item) {
+  const target = {
+    screenshot_id: item?.screenshot_id ?? null,
+    image_path: item?.image_path || null,
+    process_name: item?.process_name || null,
+    window_title: item?.window_title || null,
+    created_at: item?.screenshot_created_at || item?.created_at || null,
+  };
+  localStorage.setItem(SEARCH_OVERLAY_PENDING_KEY, JSON.stringify(target));
+}
+
+/**
+ * Read and clear the pending result, or return null when there is none.
+ */
+export function takePendingSearchOverlayTarget() {
+  const raw = localStorage.getItem(SEARCH_OVERLAY_PENDING_KEY);
+  if (!raw) return null;
+  localStorage.removeItem(SEARCH_OVERLAY_PENDING_KEY);
+  try {
+    const target = JSON.parse(raw);
+    return target && (target.screenshot_id || target.image_path) ? target : null;
+  } catch {
+    return null;
+  }
+}
@@ -0,0 +1,41 @@
+import { beforeEach, describe, expect, it } from 'vitest';
+import {
+  SEARCH_OVERLAY_PENDING_KEY,
+  setPendingSearchOverlayTarget,
+  takePendingSearchOverlayTarget,
+} from './search_overlay';
+
+describe('search overlay hand-off', () => {
+  beforeEach(() => {
+    localStorage.clear();
+  });
+
+  it('keeps identifying fields only and is consumed once', () => {
+    setPendingSearchOverlayTarget({
+      id: 91,
+      screenshot_id: 12,
+      image_path: 'D:/shots/12.jpg',
+      process_name: 'code.exe',
+      window_title: 'notes.md',
+      text: 'quarterly numbers',
+      screenshot_created_at: '2026-10-01 09:00:00',
+    });
+
+    expect(takePendingSearchOverlayTarget()).toEqual({
+      screenshot_id: 12,
+      image_path: 'D:/shots/12.jpg',
+      process_name: 'code.exe',
+      window_title: 'notes.md',
+      created_at: '2026-10-01 09:00:00',
+    });
+    expect(takePendingSearchOverlayTarget()).toBeNull();
+  });
+
+  it('ignores malformed entries', () => {
+    localStorage.setItem(SEARCH_OVERLAY_PENDING_KEY, '{not json');
+    expect(takePendingSearchOverlayTarget()).toBeNull();
+    localStorage.setItem(SEARCH_OVERLAY_PENDING_KEY, JSON.stringify({ process_name: 'x.exe' }));
+    expect(takePendingSearchOverlayTarget()).toBeNull();
+    expect(localStorage.getItem(SEARCH_OVERLAY_PENDING_KEY)).toBeNull();
+  });
+});
@@ -13,3 +13,12 @@ export function getApiBase() {
 export function cn(...inputs) {
   return twMerge(clsx(inputs))
 }
+
+// 倒计时显示：m:ss，超过一小时为 h:mm:ss
+export function formatCountdown(totalSeconds) {
+  const secs = Math.max(0, Math.floor(totalSeconds || 0));
+  const h = Math.floor(secs / 3600);
+  const m = Math.floor((secs % 3600) / 60);
+  const s = String(secs % 60).padStart(2, '0');
+  return h > 0 ? `${h}:${String(m).padStart(2, '0')}:${s}` : `${m}:${s}`;
+}
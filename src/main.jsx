@@ -2,13 +2,16 @@ import React from 'react'
 import ReactDOM from 'react-dom/client'
 import App from './App.jsx'
 import SnapshotPreviewStandalone from './components/SnapshotPreviewStandalone.jsx'
+import SearchOverlay from './components/SearchOverlay.jsx'
 import './index.css'
 import './i18n'
 
 const params = new URLSearchParams(window.location.search)
-const RootComponent = params.get('window') === 'snapshot-preview'
-  ? SnapshotPreviewStandalone
-  : App
+const WINDOW_ROOTS = {
+  'snapshot-preview': SnapshotPreviewStandalone,
+  'search-overlay': SearchOverlay,
+}
+const RootComponent = WINDOW_ROOTS[params.get('window')] || App
 
 ReactDOM.createRoot(document.getElementById('root')).render(
   <React.StrictMode>
@@ -4,6 +4,7 @@ import { cleanup } from '@testing-library/react';
 
 vi.mock('@tauri-apps/api/core', () => ({
   invoke: vi.fn(),
+  convertFileSrc: vi.fn((path, protocol = 'asset') => `${protocol}://localhost/${encodeURIComponent(path)}`),
 }));
 
 if (typeof globalThis.IntersectionObserver === 'undefined') {